@@ -55,6 +55,7 @@ fn benchmark_files_no_partitions(c: &mut Criterion) {
                         path: output.try_into().unwrap(),
                         idle_timeout_secs: None,
                         encoding: sinks::file::Encoding::Text.into(),
+                        csv: None,
                         compression: sinks::file::Compression::None,
                     },
                 );
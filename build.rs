@@ -108,6 +108,7 @@ fn main() {
 
         let mut prost_build = prost_build::Config::new();
         prost_build.btree_map(&["."]);
+        prost_build.bytes(&[".event"]);
 
         tonic_build::configure()
             .compile_with_config(
@@ -122,6 +123,84 @@ fn main() {
             .unwrap();
     }
 
+    #[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+    {
+        println!("cargo:rerun-if-changed=proto/opentelemetry");
+
+        tonic_build::configure()
+            .compile(
+                &[
+                    "proto/opentelemetry/proto/collector/logs/v1/logs_service.proto",
+                    "proto/opentelemetry/proto/collector/metrics/v1/metrics_service.proto",
+                    "proto/opentelemetry/proto/collector/trace/v1/trace_service.proto",
+                ],
+                &["proto/"],
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "sinks-datadog_traces")]
+    {
+        println!("cargo:rerun-if-changed=proto/datadog");
+
+        prost_build::compile_protos(
+            &[
+                "proto/datadog/agent_payload.proto",
+                "proto/datadog/ddsketch.proto",
+            ],
+            &["proto/"],
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "sources-gcp_pubsub")]
+    {
+        println!("cargo:rerun-if-changed=proto/google/pubsub/v1/pubsub.proto");
+
+        tonic_build::configure()
+            .build_server(false)
+            .compile(&["proto/google/pubsub/v1/pubsub.proto"], &["proto/"])
+            .unwrap();
+    }
+
+    #[cfg(feature = "sinks-gcp_bigquery")]
+    {
+        println!("cargo:rerun-if-changed=proto/google/cloud/bigquery/storage/v1");
+        println!("cargo:rerun-if-changed=proto/google/rpc/status.proto");
+
+        tonic_build::configure()
+            .build_server(false)
+            .compile(
+                &["proto/google/cloud/bigquery/storage/v1/storage.proto"],
+                &["proto/"],
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "sinks-loki")]
+    {
+        println!("cargo:rerun-if-changed=proto/loki/push.proto");
+
+        prost_build::compile_protos(&["proto/loki/push.proto"], &["proto/"]).unwrap();
+    }
+
+    #[cfg(feature = "sources-ebpf")]
+    {
+        println!("cargo:rerun-if-changed=src/sources/ebpf/probes.bpf.c");
+
+        let out_dir = env::var("OUT_DIR").expect("OUT_DIR not present in build script!");
+        let clang = env::var("CLANG").unwrap_or_else(|_| "clang".to_string());
+        println!("cargo:rerun-if-env-changed=CLANG");
+        let status = std::process::Command::new(&clang)
+            .args(&["-O2", "-g", "-target", "bpf", "-c"])
+            .arg("src/sources/ebpf/probes.bpf.c")
+            .arg("-o")
+            .arg(Path::new(&out_dir).join("probes.bpf.o"))
+            .status()
+            .unwrap_or_else(|error| panic!("Failed to run {}: {}", clang, error));
+        assert!(status.success(), "Failed to compile the eBPF probes");
+    }
+
     // We keep track of which environment variables we slurp in, and then emit stanzas at the end to
     // inform Cargo when it needs to rerun this build script.  This allows us to avoid rerunning it
     // every single time unless something _actually_ changes.
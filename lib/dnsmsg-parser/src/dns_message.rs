@@ -18,6 +18,8 @@ pub(super) const RTYPE_A6: u16 = 38;
 pub(super) const RTYPE_SINK: u16 = 40;
 pub(super) const RTYPE_APL: u16 = 42;
 pub(super) const RTYPE_DHCID: u16 = 49;
+pub(super) const RTYPE_SVCB: u16 = 64;
+pub(super) const RTYPE_HTTPS: u16 = 65;
 pub(super) const RTYPE_SPF: u16 = 99;
 
 #[derive(Clone, Debug, Default)]
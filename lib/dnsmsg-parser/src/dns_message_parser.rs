@@ -338,6 +338,24 @@ impl DnsMessageParser {
         Ok((Some(apl_rdata.trim_end().to_string()), None))
     }
 
+    fn parse_svcb_rdata(
+        &mut self,
+        raw_rdata: &[u8],
+    ) -> DnsParserResult<(Option<String>, Option<Vec<u8>>)> {
+        let mut decoder = BinDecoder::new(raw_rdata);
+        let priority = parse_u16(&mut decoder)?;
+        let target_name = parse_domain_name(&mut decoder)?;
+        let mut svcb_rdata = format!("{} {}", priority, target_name);
+        while !decoder.is_empty() {
+            let key = parse_u16(&mut decoder)?;
+            let value_len = parse_u16(&mut decoder)?;
+            let value = parse_vec_with_u16_len(&mut decoder, value_len)?;
+            svcb_rdata.push(' ');
+            svcb_rdata.push_str(&format_svc_param(key, &value)?);
+        }
+        Ok((Some(svcb_rdata), None))
+    }
+
     pub fn format_unknown_rdata(
         &mut self,
         code: u16,
@@ -648,6 +666,20 @@ impl DnsMessageParser {
                 }),
             },
 
+            dns_message::RTYPE_SVCB => match rdata.anything() {
+                Some(raw_rdata) => self.parse_svcb_rdata(raw_rdata),
+                None => Err(DnsMessageParserError::SimpleError {
+                    cause: String::from("Empty SVCB rdata"),
+                }),
+            },
+
+            dns_message::RTYPE_HTTPS => match rdata.anything() {
+                Some(raw_rdata) => self.parse_svcb_rdata(raw_rdata),
+                None => Err(DnsMessageParserError::SimpleError {
+                    cause: String::from("Empty HTTPS rdata"),
+                }),
+            },
+
             _ => match rdata.anything() {
                 Some(raw_rdata) => Ok((None, Some(raw_rdata.to_vec()))),
                 None => Err(DnsMessageParserError::SimpleError {
@@ -1001,10 +1033,156 @@ fn parse_edns_opt_dnssec_algorithms(
 }
 
 fn parse_edns_opt(opt_code: EdnsCode, opt_data: &[u8]) -> EdnsOptionEntry {
+    let code = Into::<u16>::into(opt_code);
     EdnsOptionEntry {
-        opt_code: Into::<u16>::into(opt_code),
-        opt_name: format!("{:?}", opt_code),
-        opt_data: BASE64.encode(opt_data),
+        opt_code: code,
+        opt_name: match code {
+            EDNS_OPT_EXTENDED_DNS_ERROR => String::from("ExtendedDnsError"),
+            _ => format!("{:?}", opt_code),
+        },
+        // Malformed options of known types are kept as they are
+        opt_data: format_edns_opt_data(code, opt_data).unwrap_or_else(|_| BASE64.encode(opt_data)),
+    }
+}
+
+const EDNS_OPT_NSID: u16 = 3;
+const EDNS_OPT_CLIENT_SUBNET: u16 = 8;
+const EDNS_OPT_COOKIE: u16 = 10;
+const EDNS_OPT_KEEPALIVE: u16 = 11;
+const EDNS_OPT_PADDING: u16 = 12;
+const EDNS_OPT_EXTENDED_DNS_ERROR: u16 = 15;
+
+fn format_edns_opt_data(opt_code: u16, opt_data: &[u8]) -> DnsParserResult<String> {
+    let mut decoder = BinDecoder::new(opt_data);
+    match opt_code {
+        EDNS_OPT_NSID => Ok(HEXUPPER.encode(opt_data)),
+        EDNS_OPT_CLIENT_SUBNET => {
+            let family = parse_u16(&mut decoder)?;
+            let source_prefix = parse_u8(&mut decoder)?;
+            let scope_prefix = parse_u8(&mut decoder)?;
+            let address_len = match family {
+                1 => 4,
+                2 => 16,
+                _ => {
+                    return Err(DnsMessageParserError::SimpleError {
+                        cause: format!("Unknown address family {}", family),
+                    })
+                }
+            };
+            // Only the bytes covered by the source prefix are sent
+            let mut address_vec = decoder.rest().to_vec();
+            if address_vec.len() > address_len {
+                return Err(DnsMessageParserError::SimpleError {
+                    cause: format!("Address too long for address family {}", family),
+                });
+            }
+            address_vec.resize(address_len, 0);
+            let mut address_decoder = BinDecoder::new(&address_vec);
+            let address = if family == 1 {
+                parse_ipv4_address(&mut address_decoder)?
+            } else {
+                parse_ipv6_address(&mut address_decoder)?
+            };
+            Ok(format!("{}/{}/{}", address, source_prefix, scope_prefix))
+        }
+        EDNS_OPT_COOKIE => {
+            let client_cookie = parse_vec(&mut decoder, 8)?;
+            let server_cookie = decoder.rest();
+            if server_cookie.is_empty() {
+                Ok(HEXUPPER.encode(&client_cookie))
+            } else {
+                Ok(format!(
+                    "{} {}",
+                    HEXUPPER.encode(&client_cookie),
+                    HEXUPPER.encode(server_cookie)
+                ))
+            }
+        }
+        EDNS_OPT_KEEPALIVE => {
+            if decoder.is_empty() {
+                Ok(String::new())
+            } else {
+                // in units of 100 milliseconds
+                Ok(parse_u16(&mut decoder)?.to_string())
+            }
+        }
+        EDNS_OPT_PADDING => Ok(opt_data.len().to_string()),
+        EDNS_OPT_EXTENDED_DNS_ERROR => {
+            let info_code = parse_u16(&mut decoder)?;
+            let extra_text = String::from_utf8_lossy(decoder.rest());
+            if extra_text.is_empty() {
+                Ok(info_code.to_string())
+            } else {
+                Ok(format!(
+                    "{} \"{}\"",
+                    info_code,
+                    escape_string_for_text_representation(extra_text.into_owned())
+                ))
+            }
+        }
+        _ => Ok(BASE64.encode(opt_data)),
+    }
+}
+
+fn format_svc_param(key: u16, value: &[u8]) -> DnsParserResult<String> {
+    let mut decoder = BinDecoder::new(value);
+    let value_text = match key {
+        // mandatory
+        0 => {
+            let mut keys = Vec::new();
+            while !decoder.is_empty() {
+                keys.push(format_svc_param_key(parse_u16(&mut decoder)?));
+            }
+            keys.join(",")
+        }
+        // alpn
+        1 => {
+            let mut protocols = Vec::new();
+            while !decoder.is_empty() {
+                protocols.push(parse_character_string(&mut decoder)?);
+            }
+            protocols.join(",")
+        }
+        // no-default-alpn
+        2 => return Ok(format_svc_param_key(key)),
+        // port
+        3 => parse_u16(&mut decoder)?.to_string(),
+        // ipv4hint
+        4 => {
+            let mut addresses = Vec::new();
+            while !decoder.is_empty() {
+                addresses.push(parse_ipv4_address(&mut decoder)?);
+            }
+            addresses.join(",")
+        }
+        // ech
+        5 => BASE64.encode(value),
+        // ipv6hint
+        6 => {
+            let mut addresses = Vec::new();
+            while !decoder.is_empty() {
+                addresses.push(parse_ipv6_address(&mut decoder)?);
+            }
+            addresses.join(",")
+        }
+        _ => format!(
+            "\"{}\"",
+            escape_string_for_text_representation(String::from_utf8_lossy(value).into_owned())
+        ),
+    };
+    Ok(format!("{}={}", format_svc_param_key(key), value_text))
+}
+
+fn format_svc_param_key(key: u16) -> String {
+    match key {
+        0 => String::from("mandatory"),
+        1 => String::from("alpn"),
+        2 => String::from("no-default-alpn"),
+        3 => String::from("port"),
+        4 => String::from("ipv4hint"),
+        5 => String::from("ech"),
+        6 => String::from("ipv6hint"),
+        _ => format!("key{}", key),
     }
 }
 
@@ -1205,6 +1383,8 @@ fn parse_unknown_record_type(rtype: u16) -> Option<String> {
         61 => Some(String::from("OPENPGPKEY")),
         62 => Some(String::from("CSYNC")),
         63 => Some(String::from("ZONEMD")),
+        64 => Some(String::from("SVCB")),
+        65 => Some(String::from("HTTPS")),
         99 => Some(String::from("SPF")),
         100 => Some(String::from("UINFO")),
         101 => Some(String::from("UID")),
@@ -1910,6 +2090,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_rdata_for_svcb_type() {
+        test_format_rdata(
+            "ABADZm9vB2V4YW1wbGUDb3JnAAAAAAIAAQABAAMCaDIAAgAAAAYAECABDbgAAAAAAAAAAAAAAAE=",
+            64,
+            "16 foo.example.org. mandatory=alpn alpn=h2 no-default-alpn ipv6hint=2001:db8::1",
+        );
+    }
+
+    #[test]
+    fn test_format_rdata_for_https_type() {
+        test_format_rdata(
+            "AAEAAAEABgJoMgJoMwADAAIBuwAEAATAAAIB",
+            65,
+            "1 . alpn=h2,h3 port=443 ipv4hint=192.0.2.1",
+        );
+    }
+
+    #[test]
+    fn test_parse_edns_opt() {
+        let subnet = parse_edns_opt(EdnsCode::from(8), &[0, 1, 24, 0, 192, 0, 2]);
+        assert_eq!(8, subnet.opt_code);
+        assert_eq!("192.0.2.0/24/0", subnet.opt_data);
+
+        let cookie = parse_edns_opt(EdnsCode::from(10), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!("0102030405060708", cookie.opt_data);
+
+        let error = parse_edns_opt(EdnsCode::from(15), b"\x00\x12blocked");
+        assert_eq!("ExtendedDnsError", error.opt_name);
+        assert_eq!("18 \"blocked\"", error.opt_data);
+
+        let malformed = parse_edns_opt(EdnsCode::from(8), &[0, 3]);
+        assert_eq!("AAM=", malformed.opt_data);
+    }
+
     fn test_format_rdata(raw_data: &str, code: u16, expected_output: &str) {
         let raw_rdata = BASE64
             .decode(raw_data.as_bytes())
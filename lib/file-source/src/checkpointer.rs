@@ -7,7 +7,7 @@ use dashmap::DashMap;
 use glob::glob;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeSet, HashSet},
     fs, io,
     path::{Path, PathBuf},
     sync::Arc,
@@ -116,6 +116,26 @@ impl CheckpointsView {
         }
     }
 
+    /// Drops the checkpoints of files other than `keep` which have not been
+    /// updated since `cutoff`, returning how many were dropped. Without this,
+    /// the checkpoints of files which are rotated away or deleted while Vector
+    /// is not running are kept forever.
+    pub fn remove_stale(&self, keep: &HashSet<FileFingerprint>, cutoff: DateTime<Utc>) -> usize {
+        let to_remove = self
+            .modified_times
+            .iter()
+            .filter(|entry| *entry.value() < cutoff && !keep.contains(entry.key()))
+            .map(|entry| *entry.key())
+            .collect::<Vec<FileFingerprint>>();
+
+        for fng in &to_remove {
+            self.checkpoints.remove(fng);
+            self.modified_times.remove(fng);
+            self.removed_times.remove(fng);
+        }
+        to_remove.len()
+    }
+
     fn load(&self, checkpoint: Checkpoint) {
         self.checkpoints
             .insert(checkpoint.fingerprint, checkpoint.position);
@@ -681,6 +701,36 @@ mod test {
         assert_eq!(chkptr.get_checkpoint(cases[3].0), None);
     }
 
+    #[test]
+    fn test_checkpointer_remove_stale() {
+        let cases = vec![
+            // (checkpoint, seconds since modified, kept)
+            (FileFingerprint::BytesChecksum(123), 30, true),
+            (FileFingerprint::BytesChecksum(456), 120, false),
+            (FileFingerprint::BytesChecksum(789), 120, true),
+        ];
+
+        let data_dir = tempdir().unwrap();
+        let mut chkptr = Checkpointer::new(data_dir.path());
+
+        for (fingerprint, modified, _) in cases.clone() {
+            chkptr.update_checkpoint(fingerprint, 1);
+            chkptr.checkpoints.modified_times.insert(
+                fingerprint,
+                Utc::now() - chrono::Duration::seconds(modified),
+            );
+        }
+
+        // The third one is still being watched
+        let keep = std::iter::once(cases[2].0).collect();
+        let cutoff = Utc::now() - chrono::Duration::seconds(60);
+        assert_eq!(chkptr.checkpoints.remove_stale(&keep, cutoff), 1);
+
+        for (fingerprint, _, kept) in cases {
+            assert_eq!(chkptr.get_checkpoint(fingerprint).is_some(), kept);
+        }
+    }
+
     #[test]
     fn test_checkpointer_checksum_updates() {
         let data_dir = tempdir().unwrap();
@@ -14,9 +14,9 @@ use futures::{
 use indexmap::IndexMap;
 use std::{
     cmp,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::{self, remove_file},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{self, Duration},
 };
@@ -48,6 +48,14 @@ where
     pub fingerprinter: Fingerprinter,
     pub oldest_first: bool,
     pub remove_after: Option<Duration>,
+    /// Files not modified within this window are neither opened nor
+    /// fingerprinted, and watched files idle for this long are closed until
+    /// they are modified again.
+    pub active_window: Option<Duration>,
+    /// Checkpoints of unwatched files not updated for this long are dropped.
+    pub checkpoint_retention: Option<Duration>,
+    /// The most bytes read from any one file each second.
+    pub max_read_bytes_per_sec: Option<usize>,
     pub emitter: E,
     pub handle: tokio::runtime::Handle,
 }
@@ -95,6 +103,9 @@ where
 
         let mut existing_files = Vec::new();
         for path in self.paths_provider.paths().into_iter() {
+            if !self.is_active(&path) {
+                continue;
+            }
             if let Some(file_id) = self.fingerprinter.get_fingerprint_or_log_error(
                 &path,
                 &mut fingerprint_buffer,
@@ -126,6 +137,8 @@ where
         }
         self.emitter.emit_files_open(fp_map.len());
 
+        let mut rate_limits: HashMap<FileFingerprint, ReadRateLimit> = HashMap::new();
+
         let mut stats = TimingStats::default();
 
         // Spawn the checkpoint writer task
@@ -198,7 +211,16 @@ where
 
                 let paths = trace_span!("paths_provider").in_scope(|| self.paths_provider.paths());
 
+                // Watched files are always looked for, so that they stay findable
+                let watched_paths = fp_map
+                    .values()
+                    .map(|watcher| watcher.path.clone())
+                    .collect::<HashSet<_>>();
+
                 for path in paths.into_iter() {
+                    if !watched_paths.contains(&path) && !self.is_active(&path) {
+                        continue;
+                    }
                     if let Some(file_id) = self.fingerprinter.get_fingerprint_or_log_error(
                         &path,
                         &mut fingerprint_buffer,
@@ -253,6 +275,19 @@ where
                     }
                 }
                 stats.record("discovery", start.elapsed());
+
+                let cutoff = self.checkpoint_retention.and_then(|retention| {
+                    chrono::Duration::from_std(retention)
+                        .ok()
+                        .and_then(|retention| Utc::now().checked_sub_signed(retention))
+                });
+                if let Some(cutoff) = cutoff {
+                    let watched = fp_map.keys().copied().collect();
+                    let removed = checkpoints.remove_stale(&watched, cutoff);
+                    if removed > 0 {
+                        debug!(message = "Removed stale checkpoints.", count = removed);
+                    }
+                }
             }
 
             // Collect lines by polling files.
@@ -266,6 +301,19 @@ where
                     continue;
                 }
 
+                let max_read_bytes = match self.max_read_bytes_per_sec {
+                    Some(rate) => {
+                        let limit = rate_limits
+                            .entry(file_id)
+                            .or_insert_with(|| ReadRateLimit::new(rate));
+                        cmp::min(self.max_read_bytes, limit.available())
+                    }
+                    None => self.max_read_bytes,
+                };
+                if max_read_bytes == 0 {
+                    continue;
+                }
+
                 let start = time::Instant::now();
                 let mut bytes_read: usize = 0;
                 while let Ok(Some(line)) = watcher.read_line() {
@@ -290,13 +338,17 @@ where
                         offset: watcher.get_file_position(),
                     });
 
-                    if bytes_read > self.max_read_bytes {
+                    if bytes_read > max_read_bytes {
                         maxed_out_reading_single_file = true;
                         break;
                     }
                 }
                 stats.record("reading", start.elapsed());
 
+                if let Some(limit) = rate_limits.get_mut(&file_id) {
+                    limit.consume(bytes_read);
+                }
+
                 if bytes_read > 0 {
                     global_bytes_read = global_bytes_read.saturating_add(bytes_read);
                 } else {
@@ -327,10 +379,20 @@ where
 
             // A FileWatcher is dead when the underlying file has disappeared.
             // If the FileWatcher is dead we don't retain it; it will be deallocated.
+            // Idle files are also let go of, but keep their checkpoints so they
+            // are resumed once they are modified again.
+            let active_window = self.active_window;
             fp_map.retain(|file_id, watcher| {
                 if watcher.dead() {
                     self.emitter.emit_file_unwatched(&watcher.path);
                     checkpoints.set_dead(*file_id);
+                    rate_limits.remove(file_id);
+                    false
+                } else if active_window.map_or(false, |window| {
+                    watcher.last_read_success().elapsed() > window
+                }) {
+                    self.emitter.emit_file_unwatched(&watcher.path);
+                    rate_limits.remove(file_id);
                     false
                 } else {
                     true
@@ -395,6 +457,19 @@ where
         }
     }
 
+    /// Whether the file was modified within the active window, if any. Files
+    /// whose modification time can't be read are assumed active, so that
+    /// fingerprinting them reports the problem.
+    fn is_active(&self, path: &Path) -> bool {
+        self.active_window.map_or(true, |window| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map_or(true, |modified| {
+                    modified.elapsed().map_or(true, |age| age <= window)
+                })
+        })
+    }
+
     fn watch_new_file(
         &self,
         path: PathBuf,
@@ -450,6 +525,42 @@ where
     }
 }
 
+/// A token bucket limiting the bytes read from a file, allowing bursts of up to
+/// a second's worth.
+struct ReadRateLimit {
+    rate: usize,
+    tokens: f64,
+    refilled_at: time::Instant,
+}
+
+impl ReadRateLimit {
+    fn new(rate: usize) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            refilled_at: time::Instant::now(),
+        }
+    }
+
+    fn available(&mut self) -> usize {
+        let now = time::Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.refilled_at = now;
+        if self.tokens > 0.0 {
+            self.tokens as usize
+        } else {
+            0
+        }
+    }
+
+    /// Reads finish on line boundaries, so they may overdraw the bucket, which
+    /// is then paid back before reading again.
+    fn consume(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+}
+
 /// A sentinel type to signal that file server was gracefully shut down.
 ///
 /// The purpose of this type is to clarify the semantics of the result values
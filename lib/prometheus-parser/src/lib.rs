@@ -687,6 +687,8 @@ mod test {
                     samples: vec![
                         $( proto::Sample { value: $sample as f64, timestamp: $timestamp as i64 }, )*
                     ],
+                    exemplars: vec![],
+                    histograms: vec![],
                 }, )* ],
             }
         };
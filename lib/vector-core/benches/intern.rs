@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::sync::Arc;
+use vector_core::event::intern::intern;
+
+const EVENTS: usize = 10_000;
+
+/// Keys for `EVENTS` events drawn from `cardinality` distinct keys, as
+/// they'd arrive from a source.
+fn keys(cardinality: usize) -> Vec<String> {
+    (0..EVENTS)
+        .map(|i| format!("tag_{}", i % cardinality))
+        .collect()
+}
+
+fn benchmark_intern(c: &mut Criterion) {
+    let mut group = c.benchmark_group("intern");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+
+    for cardinality in &[10, 1_000, EVENTS] {
+        let keys = keys(*cardinality);
+
+        group.bench_with_input(BenchmarkId::new("owned", cardinality), &keys, |b, keys| {
+            b.iter(|| {
+                keys.iter()
+                    .map(|key| Arc::from(key.as_str()))
+                    .collect::<Vec<Arc<str>>>()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("interned", cardinality),
+            &keys,
+            |b, keys| b.iter(|| keys.iter().map(|key| intern(key)).collect::<Vec<_>>()),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_intern);
+criterion_main!(benches);
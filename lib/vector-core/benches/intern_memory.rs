@@ -0,0 +1,161 @@
+use criterion::{
+    criterion_group, criterion_main,
+    measurement::{Measurement, ValueFormatter},
+    BenchmarkId, Criterion, Throughput,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use vector_core::event::{intern::intern_tags, metric::MetricTags};
+
+const EVENTS: usize = 10_000;
+
+/// Keeps count of the bytes allocated, so the benchmarks can measure the
+/// memory held by what they build.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The bytes allocated during an iteration that are still allocated at its
+/// end, which with `iter_with_large_drop` is the memory held by its output.
+struct RetainedBytes;
+
+impl Measurement for RetainedBytes {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> usize {
+        ALLOCATED.load(Ordering::SeqCst)
+    }
+
+    fn end(&self, start: usize) -> usize {
+        ALLOCATED.load(Ordering::SeqCst).saturating_sub(start)
+    }
+
+    fn add(&self, v1: &usize, v2: &usize) -> usize {
+        v1 + v2
+    }
+
+    fn zero(&self) -> usize {
+        0
+    }
+
+    fn to_f64(&self, value: &usize) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &BytesFormatter
+    }
+}
+
+struct BytesFormatter;
+
+impl ValueFormatter for BytesFormatter {
+    fn scale_values(&self, typical: f64, values: &mut [f64]) -> &'static str {
+        let (factor, unit) = if typical < 1024.0 {
+            (1.0, "B")
+        } else if typical < 1024.0 * 1024.0 {
+            (1024.0, "KiB")
+        } else {
+            (1024.0 * 1024.0, "MiB")
+        };
+
+        for value in values {
+            *value /= factor;
+        }
+
+        unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        let (count, unit) = match throughput {
+            Throughput::Bytes(bytes) => (*bytes, "B/B"),
+            Throughput::Elements(elements) => (*elements, "B/elem"),
+        };
+
+        for value in values {
+            *value /= count as f64;
+        }
+
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "B"
+    }
+}
+
+/// Tags for `EVENTS` metrics drawn from `cardinality` distinct series, as
+/// they'd arrive from a source.
+fn tags(cardinality: usize) -> Vec<MetricTags> {
+    (0..EVENTS)
+        .map(|i| {
+            let mut tags = MetricTags::new();
+            tags.insert("host".to_owned(), format!("host-{}", i % cardinality));
+            tags.insert("service".to_owned(), "api".to_owned());
+            tags
+        })
+        .collect()
+}
+
+fn benchmark_intern_memory(c: &mut Criterion<RetainedBytes>) {
+    let mut group = c.benchmark_group("intern_memory");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+
+    for cardinality in &[10, 1_000, EVENTS] {
+        let tags = tags(*cardinality);
+
+        group.bench_with_input(BenchmarkId::new("owned", cardinality), &tags, |b, tags| {
+            b.iter_with_large_drop(|| {
+                tags.iter()
+                    .map(|tags| Arc::new(tags.clone()))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("interned", cardinality),
+            &tags,
+            |b, tags| {
+                b.iter_with_large_drop(|| {
+                    tags.iter()
+                        .map(|tags| intern_tags(tags.clone()))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().with_measurement(RetainedBytes);
+    targets = benchmark_intern_memory
+);
+criterion_main!(benches);
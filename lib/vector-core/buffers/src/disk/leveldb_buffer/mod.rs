@@ -132,7 +132,7 @@ where
             ack_counter,
             max_uncompacted_size,
             uncompacted_size: 0,
-            unacked_sizes: VecDeque::new(),
+            unacked: VecDeque::new(),
             buffer: VecDeque::new(),
             last_compaction: Instant::now(),
             phantom: PhantomData,
@@ -9,6 +9,7 @@ use leveldb::database::{
     options::{ReadOptions, WriteOptions},
     Database,
 };
+use metrics::counter;
 use std::collections::VecDeque;
 use std::fmt::Display;
 use std::marker::PhantomData;
@@ -68,8 +69,8 @@ where
     pub(crate) ack_counter: Arc<AtomicUsize>,
     /// Size of deleted, not compacted, events in bytes.
     pub(crate) uncompacted_size: usize,
-    /// Sizes in bytes of read, not acked/deleted, events.
-    pub(crate) unacked_sizes: VecDeque<usize>,
+    /// Read, not acked/deleted, records.
+    pub(crate) unacked: VecDeque<Unacked>,
     /// Buffer for internal use.
     pub(crate) buffer: VecDeque<(Key, Vec<u8>)>,
     /// Limit on uncompacted_size after which we trigger compaction.
@@ -79,6 +80,15 @@ where
     pub(crate) phantom: PhantomData<T>,
 }
 
+/// A record that has been read but not deleted yet.
+pub(crate) struct Unacked {
+    /// Size of the record in bytes.
+    size: usize,
+    /// Whether the record was skipped because it couldn't be decoded. The
+    /// consumer never sees, and so never acks, skipped records.
+    skipped: bool,
+}
+
 // Writebatch isn't Send, but the leveldb docs explicitly say that it's okay to
 // share across threads
 unsafe impl<T> Send for Reader<T> where T: Send + Sync + Unpin {}
@@ -98,44 +108,59 @@ where
         // write.
         this.write_notifier.register(cx.waker());
 
-        let unread_size = this.delete_acked();
+        loop {
+            let unread_size = this.delete_acked();
 
-        if this.acked >= 100 {
-            this.flush(unread_size);
-        }
+            if this.acked >= 100 {
+                this.flush(unread_size);
+            }
 
-        if this.buffer.is_empty() {
-            // This will usually complete instantly, but in the case of a large
-            // queue (or a fresh launch of the app), this will have to go to
-            // disk.
-            tokio::task::block_in_place(|| {
-                this.buffer.extend(
-                    this.db
-                        .iter(ReadOptions::new())
-                        .from(&Key(this.read_offset))
-                        .take(100),
-                );
-            });
-        }
+            if this.buffer.is_empty() {
+                // This will usually complete instantly, but in the case of a large
+                // queue (or a fresh launch of the app), this will have to go to
+                // disk.
+                tokio::task::block_in_place(|| {
+                    this.buffer.extend(
+                        this.db
+                            .iter(ReadOptions::new())
+                            .from(&Key(this.read_offset))
+                            .take(100),
+                    );
+                });
+            }
 
-        if let Some((key, value)) = this.buffer.pop_front() {
-            this.unacked_sizes.push_back(value.len());
+            let (key, value) = match this.buffer.pop_front() {
+                Some(record) => record,
+                // There are no writers left
+                None if Arc::strong_count(&this.db) == 1 => return Poll::Ready(None),
+                None => return Poll::Pending,
+            };
             this.read_offset = key.0;
 
-            let buffer: Bytes = Bytes::from(value);
-            match T::decode(buffer) {
-                Ok(event) => Poll::Ready(Some(event)),
+            let size = value.len();
+            match T::decode(Bytes::from(value)) {
+                Ok(event) => {
+                    this.unacked.push_back(Unacked {
+                        size,
+                        skipped: false,
+                    });
+                    return Poll::Ready(Some(event));
+                }
                 Err(error) => {
-                    error!(message = "Error deserializing event.", %error);
-                    debug_assert!(false);
-                    Pin::new(this).poll_next(cx)
+                    // A corrupt record can't be recovered, so skip over it
+                    // rather than take the whole process down with it.
+                    error!(
+                        message = "Error deserializing event.",
+                        %error,
+                        internal_log_rate_secs = 10
+                    );
+                    counter!("buffer_decode_errors_total", 1);
+                    this.unacked.push_back(Unacked {
+                        size,
+                        skipped: true,
+                    });
                 }
             }
-        } else if Arc::strong_count(&this.db) == 1 {
-            // There are no writers left
-            Poll::Ready(None)
-        } else {
-            Poll::Pending
         }
     }
 }
@@ -156,10 +181,25 @@ where
 {
     /// Returns number of bytes to be read.
     fn delete_acked(&mut self) -> usize {
-        let num_to_delete = self.ack_counter.swap(0, Ordering::Relaxed);
+        let mut num_acked = self.ack_counter.swap(0, Ordering::Relaxed);
+
+        // Skipped records are deleted once every record read before them is,
+        // as acks only count the records handed to the consumer.
+        let mut num_to_delete = 0;
+        let mut size_deleted = 0;
+        while let Some(record) = self.unacked.front() {
+            if !record.skipped {
+                if num_acked == 0 {
+                    break;
+                }
+                num_acked -= 1;
+            }
+            num_to_delete += 1;
+            size_deleted += record.size;
+            self.unacked.pop_front();
+        }
 
         let unread_size = if num_to_delete > 0 {
-            let size_deleted = self.unacked_sizes.drain(..num_to_delete).sum();
             let unread_size =
                 self.current_size.fetch_sub(size_deleted, Ordering::Release) - size_deleted;
 
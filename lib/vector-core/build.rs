@@ -2,6 +2,9 @@ fn main() {
     println!("cargo:rerun-if-changed=proto/event.proto");
     let mut prost_build = prost_build::Config::new();
     prost_build.btree_map(&["."]);
+    // Decode `bytes` fields as `Bytes` so they're sliced out of the buffer
+    // they're decoded from rather than copied.
+    prost_build.bytes(&["."]);
     prost_build
         .compile_protos(&["proto/event.proto"], &["proto/"])
         .unwrap();
@@ -1,5 +1,6 @@
 use crate::config::proxy::ProxyConfig;
 use crate::config::LogSchema;
+use crate::event::SizeAccounting;
 use serde::{Deserialize, Serialize};
 use shared::TimeZone;
 use snafu::{ResultExt, Snafu};
@@ -38,6 +39,13 @@ pub struct GlobalOptions {
     pub timezone: TimeZone,
     #[serde(skip_serializing_if = "crate::serde::skip_serializing_if_default")]
     pub proxy: ProxyConfig,
+    /// Record the IDs of the components each event passes through in its
+    /// metadata.
+    #[serde(skip_serializing_if = "crate::serde::skip_serializing_if_default")]
+    pub lineage: bool,
+    /// How the allocated size of log events is accounted for.
+    #[serde(skip_serializing_if = "crate::serde::skip_serializing_if_default")]
+    pub size_accounting: SizeAccounting,
 }
 
 impl GlobalOptions {
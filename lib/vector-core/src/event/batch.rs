@@ -0,0 +1,305 @@
+#[cfg(feature = "arrow")]
+mod record_batch;
+
+use super::{LogEvent, Value};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// A batch of log events laid out column by column, rather than event by
+/// event.
+///
+/// Each top-level field of the events becomes a column holding that field's
+/// value for every event in the batch. Columns whose values all share a type
+/// are stored as that type, which is what columnar formats such as Arrow and
+/// Parquet expect and what lets transforms work on a whole column at once.
+///
+/// Fields that are missing and fields set to `null` are both stored as `None`,
+/// so converting back into events drops `null` fields. Event metadata is not
+/// carried by the batch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventBatch {
+    len: usize,
+    columns: BTreeMap<String, Column>,
+}
+
+/// The values of one field across all events of an [`EventBatch`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    /// Text, each value valid UTF-8.
+    String(Vec<Option<Bytes>>),
+    Bytes(Vec<Option<Bytes>>),
+    Integer(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Boolean(Vec<Option<bool>>),
+    Timestamp(Vec<Option<DateTime<Utc>>>),
+    /// Maps, arrays and fields holding values of more than one type.
+    Value(Vec<Option<Value>>),
+}
+
+impl EventBatch {
+    /// A batch of `len` events without any columns yet, for building a batch
+    /// column by column with [`EventBatch::insert_column`].
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            len,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    /// Lay out `logs` as columns, one per top-level field.
+    pub fn from_logs(logs: impl IntoIterator<Item = LogEvent>) -> Self {
+        let maps = logs
+            .into_iter()
+            .map(|log| log.into_parts().0)
+            .collect::<Vec<_>>();
+        let len = maps.len();
+
+        let mut values = BTreeMap::<String, Vec<Option<Value>>>::new();
+        for (row, map) in maps.into_iter().enumerate() {
+            for (key, value) in map {
+                let column = values.entry(key).or_insert_with(|| vec![None; len]);
+                column[row] = match value {
+                    Value::Null => None,
+                    value => Some(value),
+                };
+            }
+        }
+
+        let columns = values
+            .into_iter()
+            .map(|(key, values)| (key, Column::from_values(values)))
+            .collect();
+
+        Self { len, columns }
+    }
+
+    /// Turn the batch back into one log event per row.
+    pub fn into_logs(self) -> Vec<LogEvent> {
+        let mut maps = vec![BTreeMap::new(); self.len];
+
+        for (key, column) in self.columns {
+            for (map, value) in maps.iter_mut().zip(column.into_values()) {
+                if let Some(value) = value {
+                    map.insert(key.clone(), value);
+                }
+            }
+        }
+
+        maps.into_iter().map(LogEvent::from).collect()
+    }
+
+    /// The number of events in the batch.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.get(name)
+    }
+
+    pub fn column_mut(&mut self, name: &str) -> Option<&mut Column> {
+        self.columns.get_mut(name)
+    }
+
+    /// The columns of the batch, ordered by name.
+    pub fn columns(&self) -> impl Iterator<Item = (&String, &Column)> {
+        self.columns.iter()
+    }
+
+    /// Add a column, replacing any column of the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column doesn't hold a value for every event of the batch.
+    pub fn insert_column(&mut self, name: impl Into<String>, column: Column) -> Option<Column> {
+        assert_eq!(
+            column.len(),
+            self.len,
+            "column length must match the batch length"
+        );
+        self.columns.insert(name.into(), column)
+    }
+
+    pub fn remove_column(&mut self, name: &str) -> Option<Column> {
+        self.columns.remove(name)
+    }
+}
+
+impl From<Vec<LogEvent>> for EventBatch {
+    fn from(logs: Vec<LogEvent>) -> Self {
+        Self::from_logs(logs)
+    }
+}
+
+impl Column {
+    /// Store `values` in the narrowest column type that holds all of them.
+    pub fn from_values(values: Vec<Option<Value>>) -> Self {
+        let first = values.iter().flatten().next();
+        let uniform = |f: fn(&Value) -> bool| values.iter().flatten().all(f);
+
+        match first {
+            Some(Value::String(_)) if uniform(|v| matches!(v, Value::String(_))) => {
+                Self::String(collect(values, |v| match v {
+                    Value::String(s) => s,
+                    _ => unreachable!(),
+                }))
+            }
+            Some(Value::Bytes(_)) if uniform(|v| matches!(v, Value::Bytes(_))) => {
+                Self::Bytes(collect(values, |v| match v {
+                    Value::Bytes(b) => b,
+                    _ => unreachable!(),
+                }))
+            }
+            Some(Value::Integer(_)) if uniform(|v| matches!(v, Value::Integer(_))) => {
+                Self::Integer(collect(values, |v| match v {
+                    Value::Integer(i) => i,
+                    _ => unreachable!(),
+                }))
+            }
+            Some(Value::Float(_)) if uniform(|v| matches!(v, Value::Float(_))) => {
+                Self::Float(collect(values, |v| match v {
+                    Value::Float(f) => f,
+                    _ => unreachable!(),
+                }))
+            }
+            Some(Value::Boolean(_)) if uniform(|v| matches!(v, Value::Boolean(_))) => {
+                Self::Boolean(collect(values, |v| match v {
+                    Value::Boolean(b) => b,
+                    _ => unreachable!(),
+                }))
+            }
+            Some(Value::Timestamp(_)) if uniform(|v| matches!(v, Value::Timestamp(_))) => {
+                Self::Timestamp(collect(values, |v| match v {
+                    Value::Timestamp(ts) => ts,
+                    _ => unreachable!(),
+                }))
+            }
+            _ => Self::Value(values),
+        }
+    }
+
+    /// The number of values in the column, including missing ones.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::String(values) | Self::Bytes(values) => values.len(),
+            Self::Integer(values) => values.len(),
+            Self::Float(values) => values.len(),
+            Self::Boolean(values) => values.len(),
+            Self::Timestamp(values) => values.len(),
+            Self::Value(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value of the column for the event at `row`, if it has one.
+    pub fn get(&self, row: usize) -> Option<Value> {
+        match self {
+            Self::String(values) => values.get(row)?.clone().map(Value::String),
+            Self::Bytes(values) => values.get(row)?.clone().map(Value::Bytes),
+            Self::Integer(values) => values.get(row)?.map(Value::Integer),
+            Self::Float(values) => values.get(row)?.map(Value::Float),
+            Self::Boolean(values) => values.get(row)?.map(Value::Boolean),
+            Self::Timestamp(values) => values.get(row)?.map(Value::Timestamp),
+            Self::Value(values) => values.get(row)?.clone(),
+        }
+    }
+
+    pub fn into_values(self) -> Vec<Option<Value>> {
+        fn wrap<T>(values: Vec<Option<T>>, f: fn(T) -> Value) -> Vec<Option<Value>> {
+            values.into_iter().map(|v| v.map(f)).collect()
+        }
+
+        match self {
+            Self::String(values) => wrap(values, Value::String),
+            Self::Bytes(values) => wrap(values, Value::Bytes),
+            Self::Integer(values) => wrap(values, Value::Integer),
+            Self::Float(values) => wrap(values, Value::Float),
+            Self::Boolean(values) => wrap(values, Value::Boolean),
+            Self::Timestamp(values) => wrap(values, Value::Timestamp),
+            Self::Value(values) => values,
+        }
+    }
+}
+
+fn collect<T>(values: Vec<Option<Value>>, f: fn(Value) -> T) -> Vec<Option<T>> {
+    values.into_iter().map(|v| v.map(f)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn logs() -> Vec<LogEvent> {
+        let mut first = LogEvent::from("first");
+        first.insert("count", 1);
+        first.insert("mixed", 1);
+        first.insert("nested.field", true);
+        first.insert("timestamp", Utc.ymd(2021, 8, 1).and_hms(0, 0, 0));
+
+        let mut second = LogEvent::from("second");
+        second.insert("mixed", "one");
+        second.insert("ratio", 0.5);
+        second.insert("timestamp", Utc.ymd(2021, 8, 1).and_hms(0, 0, 1));
+
+        vec![first, second]
+    }
+
+    #[test]
+    fn columns_by_type() {
+        let batch = EventBatch::from_logs(logs());
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch.column("message"),
+            Some(&Column::String(vec![
+                Some(Bytes::from("first")),
+                Some(Bytes::from("second"))
+            ]))
+        );
+        assert_eq!(
+            batch.column("count"),
+            Some(&Column::Integer(vec![Some(1), None]))
+        );
+        assert_eq!(
+            batch.column("ratio"),
+            Some(&Column::Float(vec![None, Some(0.5)]))
+        );
+        assert!(matches!(
+            batch.column("timestamp"),
+            Some(Column::Timestamp(_))
+        ));
+        assert!(matches!(batch.column("mixed"), Some(Column::Value(_))));
+        assert!(matches!(batch.column("nested"), Some(Column::Value(_))));
+        assert_eq!(
+            batch.column("count").unwrap().get(0),
+            Some(Value::Integer(1))
+        );
+    }
+
+    #[test]
+    fn back_into_logs() {
+        let logs = logs();
+        let batch = EventBatch::from_logs(logs.clone());
+
+        assert_eq!(batch.into_logs(), logs);
+    }
+
+    #[test]
+    fn built_by_column() {
+        let mut batch = EventBatch::with_len(2);
+        batch.insert_column("count", Column::Integer(vec![Some(1), None]));
+
+        let logs = batch.into_logs();
+        assert_eq!(logs[0].get("count"), Some(&Value::Integer(1)));
+        assert!(logs[1].is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Conversion between [`EventBatch`] and Arrow record batches.
+//!
+//! Typed columns map onto the matching Arrow type, with `String` columns
+//! written as `Utf8` and `Bytes` columns as `Binary`. Columns of maps, arrays or mixed types are written as `Utf8` holding JSON, and the
+//! field is marked with [`ENCODING_KEY`] so they can be read back as values.
+
+use super::{Column, EventBatch};
+use crate::event::Value;
+use arrow::{
+    array::{
+        Array, ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, StringArray,
+        TimestampNanosecondArray,
+    },
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Field metadata key recording how a column was encoded.
+pub const ENCODING_KEY: &str = "vector.encoding";
+
+const JSON_ENCODING: &str = "json";
+
+impl EventBatch {
+    /// Convert the batch into an Arrow record batch, one nullable Arrow column
+    /// per column of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the batch has no columns, as Arrow can't tell how many rows a
+    /// record batch without columns has.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let (fields, arrays): (Vec<_>, Vec<_>) = self
+            .columns
+            .iter()
+            .map(|(name, column)| column.to_arrow(name))
+            .unzip();
+
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+    }
+
+    /// Read a batch back from an Arrow record batch.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a column has an Arrow type with no equivalent column type, or
+    /// a JSON encoded column holds invalid JSON.
+    pub fn from_record_batch(batch: &RecordBatch) -> Result<Self, ArrowError> {
+        let schema = batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, array)| Ok((field.name().clone(), Column::from_arrow(field, array)?)))
+            .collect::<Result<_, ArrowError>>()?;
+
+        Ok(Self {
+            len: batch.num_rows(),
+            columns,
+        })
+    }
+}
+
+impl Column {
+    fn to_arrow(&self, name: &str) -> (Field, ArrayRef) {
+        let mut metadata = None;
+
+        let array: ArrayRef = match self {
+            Self::String(values) => {
+                let strings = values
+                    .iter()
+                    .map(|v| v.as_deref().map(String::from_utf8_lossy))
+                    .collect::<Vec<_>>();
+                Arc::new(StringArray::from(
+                    strings.iter().map(Option::as_deref).collect::<Vec<_>>(),
+                ))
+            }
+            Self::Bytes(values) => Arc::new(BinaryArray::from(
+                values.iter().map(Option::as_deref).collect::<Vec<_>>(),
+            )),
+            Self::Integer(values) => Arc::new(Int64Array::from(values.clone())),
+            Self::Float(values) => Arc::new(Float64Array::from(values.clone())),
+            Self::Boolean(values) => Arc::new(BooleanArray::from(values.clone())),
+            Self::Timestamp(values) => Arc::new(TimestampNanosecondArray::from(
+                values
+                    .iter()
+                    .map(|v| v.map(|ts| ts.timestamp_nanos()))
+                    .collect::<Vec<_>>(),
+            )),
+            Self::Value(values) => {
+                let mut encoding = BTreeMap::new();
+                encoding.insert(ENCODING_KEY.to_owned(), JSON_ENCODING.to_owned());
+                metadata = Some(encoding);
+
+                let json = values
+                    .iter()
+                    .map(|v| {
+                        v.as_ref()
+                            .map(|v| serde_json::to_string(v).expect("values serialize to JSON"))
+                    })
+                    .collect::<Vec<_>>();
+                Arc::new(StringArray::from(
+                    json.iter().map(Option::as_deref).collect::<Vec<_>>(),
+                ))
+            }
+        };
+
+        let mut field = Field::new(name, array.data_type().clone(), true);
+        field.set_metadata(metadata);
+        (field, array)
+    }
+
+    fn from_arrow(field: &Field, array: &ArrayRef) -> Result<Self, ArrowError> {
+        let is_json = field
+            .metadata()
+            .as_ref()
+            .and_then(|metadata| metadata.get(ENCODING_KEY))
+            .map_or(false, |encoding| encoding == JSON_ENCODING);
+
+        Ok(match array.data_type() {
+            DataType::Utf8 if is_json => {
+                let array = downcast::<StringArray>(array)?;
+                Self::Value(
+                    values(array, |i| array.value(i))
+                        .into_iter()
+                        .map(|json| json.map(parse_json).transpose())
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            DataType::Utf8 => {
+                let array = downcast::<StringArray>(array)?;
+                Self::String(values(array, |i| {
+                    Bytes::copy_from_slice(array.value(i).as_bytes())
+                }))
+            }
+            DataType::Binary => {
+                let array = downcast::<BinaryArray>(array)?;
+                Self::Bytes(values(array, |i| Bytes::copy_from_slice(array.value(i))))
+            }
+            DataType::Int64 => {
+                let array = downcast::<Int64Array>(array)?;
+                Self::Integer(values(array, |i| array.value(i)))
+            }
+            DataType::Float64 => {
+                let array = downcast::<Float64Array>(array)?;
+                Self::Float(values(array, |i| array.value(i)))
+            }
+            DataType::Boolean => {
+                let array = downcast::<BooleanArray>(array)?;
+                Self::Boolean(values(array, |i| array.value(i)))
+            }
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                let array = downcast::<TimestampNanosecondArray>(array)?;
+                Self::Timestamp(values(array, |i| Utc.timestamp_nanos(array.value(i))))
+            }
+            data_type => {
+                return Err(ArrowError::SchemaError(format!(
+                    "field {:?} has unsupported type {:?}",
+                    field.name(),
+                    data_type
+                )))
+            }
+        })
+    }
+}
+
+fn downcast<T: Array + 'static>(array: &ArrayRef) -> Result<&T, ArrowError> {
+    array.as_any().downcast_ref::<T>().ok_or_else(|| {
+        ArrowError::CastError(format!(
+            "array of type {:?} doesn't match its schema",
+            array.data_type()
+        ))
+    })
+}
+
+fn parse_json(json: &str) -> Result<Value, ArrowError> {
+    serde_json::from_str::<serde_json::Value>(json)
+        .map(Value::from)
+        .map_err(|error| ArrowError::JsonError(error.to_string()))
+}
+
+fn values<'a, T>(array: &'a dyn Array, value: impl Fn(usize) -> T + 'a) -> Vec<Option<T>> {
+    (0..array.len())
+        .map(|i| {
+            if array.is_null(i) {
+                None
+            } else {
+                Some(value(i))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::LogEvent;
+
+    #[test]
+    fn through_record_batch() {
+        let mut first = LogEvent::from("first");
+        first.insert("count", 1);
+        first.insert("nested.field", true);
+        first.insert("payload", Value::Bytes(Bytes::from_static(b"\xff\x00")));
+
+        let mut second = LogEvent::from("second");
+        second.insert("ratio", 0.5);
+
+        let batch = EventBatch::from_logs(vec![first, second]);
+        let record_batch = batch.to_record_batch().unwrap();
+
+        assert_eq!(record_batch.num_rows(), 2);
+        assert_eq!(record_batch.num_columns(), 5);
+        let schema = record_batch.schema();
+        assert_eq!(
+            schema.field_with_name("message").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        assert_eq!(
+            schema.field_with_name("payload").unwrap().data_type(),
+            &DataType::Binary
+        );
+        assert_eq!(
+            schema.field_with_name("count").unwrap().data_type(),
+            &DataType::Int64
+        );
+
+        assert_eq!(EventBatch::from_record_batch(&record_batch).unwrap(), batch);
+    }
+
+    #[test]
+    fn rejects_unsupported_types() {
+        let schema = Schema::new(vec![Field::new("small", DataType::Int8, true)]);
+        let array: ArrayRef = Arc::new(arrow::array::Int8Array::from(vec![Some(1)]));
+        let record_batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+
+        assert!(EventBatch::from_record_batch(&record_batch).is_err());
+    }
+}
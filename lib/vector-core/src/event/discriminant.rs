@@ -51,7 +51,9 @@ impl Eq for Discriminant {}
 fn value_eq(this: &Value, other: &Value) -> bool {
     match (this, other) {
         // Trivial.
-        (Value::Bytes(this), Value::Bytes(other)) => this.eq(other),
+        (Value::Bytes(this), Value::Bytes(other)) | (Value::String(this), Value::String(other)) => {
+            this.eq(other)
+        }
         (Value::Boolean(this), Value::Boolean(other)) => this.eq(other),
         (Value::Integer(this), Value::Integer(other)) => this.eq(other),
         (Value::Timestamp(this), Value::Timestamp(other)) => this.eq(other),
@@ -119,7 +121,7 @@ impl Hash for Discriminant {
 fn hash_value<H: Hasher>(hasher: &mut H, value: &Value) {
     match value {
         // Trivial.
-        Value::Bytes(val) => val.hash(hasher),
+        Value::Bytes(val) | Value::String(val) => val.hash(hasher),
         Value::Boolean(val) => val.hash(hasher),
         Value::Integer(val) => val.hash(hasher),
         Value::Timestamp(val) => val.hash(hasher),
@@ -87,6 +87,11 @@ impl EventFinalizers {
         }
     }
 
+    /// Whether there are no finalizers in this set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Update the status of all finalizers in this set.
     pub fn update_status(&self, status: EventStatus) {
         for finalizer in self.0.iter() {
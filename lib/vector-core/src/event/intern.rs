@@ -0,0 +1,120 @@
+//! Interning for values that repeat across many events, such as metadata
+//! strings and the tags of metric series.
+//!
+//! Interned values are handed out behind an `Arc`, so every event holding the
+//! same value shares one allocation instead of carrying its own copy. The
+//! pools are global and only ever grow, so they are capped at
+//! [`MAX_INTERNED_STRINGS`] and [`MAX_INTERNED_TAGS`] entries; once full,
+//! values are still returned behind an `Arc` but are no longer shared. This
+//! keeps high-cardinality data, like request IDs used as tag values, from
+//! growing the pools without bound.
+
+use super::metric::MetricTags;
+use once_cell::sync::Lazy;
+use std::{collections::HashSet, sync::Arc, sync::Mutex};
+
+/// The largest number of distinct strings the pool will hold.
+pub const MAX_INTERNED_STRINGS: usize = 64 * 1024;
+
+/// Strings longer than this are never interned, as they are unlikely to be
+/// keys and the lookup would cost more than the allocation it saves.
+pub const MAX_INTERNED_LENGTH: usize = 128;
+
+/// The largest number of distinct sets of metric tags the pool will hold.
+pub const MAX_INTERNED_TAGS: usize = 16 * 1024;
+
+static STRINGS: Lazy<Mutex<HashSet<Arc<str>>>> = Lazy::new(Default::default);
+
+static TAGS: Lazy<Mutex<HashSet<Arc<MetricTags>>>> = Lazy::new(Default::default);
+
+/// Return a shared copy of `string`, allocating it only the first time it is
+/// seen.
+///
+/// # Panics
+///
+/// Panics if another thread panicked while holding the pool's lock.
+pub fn intern(string: &str) -> Arc<str> {
+    if string.len() > MAX_INTERNED_LENGTH {
+        return Arc::from(string);
+    }
+
+    let mut strings = STRINGS.lock().expect("interned strings lock poisoned");
+    if let Some(interned) = strings.get(string) {
+        return Arc::clone(interned);
+    }
+
+    let interned: Arc<str> = Arc::from(string);
+    if strings.len() < MAX_INTERNED_STRINGS {
+        strings.insert(Arc::clone(&interned));
+    }
+    interned
+}
+
+/// Return a shared copy of the tags of a metric series, so that the metrics
+/// of a series all hold one copy of its tag names and values.
+///
+/// # Panics
+///
+/// Panics if another thread panicked while holding the pool's lock.
+pub fn intern_tags(tags: MetricTags) -> Arc<MetricTags> {
+    let mut pool = TAGS.lock().expect("interned tags lock poisoned");
+    if let Some(interned) = pool.get(&tags) {
+        return Arc::clone(interned);
+    }
+
+    let interned = Arc::new(tags);
+    if pool.len() < MAX_INTERNED_TAGS {
+        pool.insert(Arc::clone(&interned));
+    }
+    interned
+}
+
+/// The number of distinct strings currently in the pool.
+///
+/// # Panics
+///
+/// Panics if another thread panicked while holding the pool's lock.
+pub fn interned_count() -> usize {
+    STRINGS
+        .lock()
+        .expect("interned strings lock poisoned")
+        .len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_allocations() {
+        let first = intern("interned_key");
+        let second = intern(&String::from("interned_key"));
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(&*first, "interned_key");
+    }
+
+    #[test]
+    fn skips_long_strings() {
+        let long = "x".repeat(MAX_INTERNED_LENGTH + 1);
+
+        assert!(!Arc::ptr_eq(&intern(&long), &intern(&long)));
+    }
+
+    #[test]
+    fn shares_tags() {
+        let tags = |value: &str| {
+            let mut tags = MetricTags::new();
+            tags.insert("interned_tag".to_owned(), value.to_owned());
+            tags
+        };
+
+        let first = intern_tags(tags("value"));
+        let second = intern_tags(tags("value"));
+        let other = intern_tags(tags("other"));
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(*first, tags("value"));
+    }
+}
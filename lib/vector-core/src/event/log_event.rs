@@ -2,63 +2,342 @@ use super::{
     finalization::{BatchNotifier, EventFinalizer},
     legacy_lookup::Segment,
     metadata::EventMetadata,
-    util, Lookup, PathComponent, Value,
+    proto, util, Lookup, PathComponent, PathIter, Value,
 };
 use crate::{config::log_schema, ByteSizeOf};
 use bytes::Bytes;
 use chrono::Utc;
 use derivative::Derivative;
 use getset::{Getters, MutGetters};
-use serde::{Deserialize, Serialize, Serializer};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use shared::EventDataEq;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
 use std::{
+    cmp::Ordering as CmpOrdering,
     collections::{btree_map::Entry, BTreeMap, HashMap},
     convert::{TryFrom, TryInto},
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display, Formatter},
     iter::FromIterator,
 };
 
-#[derive(Clone, Debug, Getters, MutGetters, PartialEq, PartialOrd, Derivative, Deserialize)]
+#[derive(Clone, Getters, MutGetters, Derivative, Deserialize)]
+#[derivative(Debug, PartialEq, PartialOrd)]
 pub struct LogEvent {
-    // **IMPORTANT:** Due to numerous legacy reasons this **must** be a Map variant.
-    #[derivative(Default(value = "Value::from(BTreeMap::default())"))]
     #[serde(flatten)]
-    fields: Value,
+    fields: Fields,
 
     #[getset(get = "pub", get_mut = "pub")]
     #[serde(skip)]
     metadata: EventMetadata,
+
+    #[derivative(Debug = "ignore", PartialEq = "ignore", PartialOrd = "ignore")]
+    #[serde(skip)]
+    size_cache: SizeCache,
 }
 
-impl Default for LogEvent {
+/// The fields of a log event.
+///
+/// Events decoded from protobuf keep the encoded entries of their fields and
+/// only decode them when they're first accessed. Until the fields are changed
+/// the entries are encoded again as they are, so events passed through
+/// untouched, as most are by the `vector` source and disk buffers, are never
+/// decoded at all.
+#[derive(Clone, Default)]
+struct Fields {
+    // **IMPORTANT:** Due to numerous legacy reasons this **must** be a Map variant.
+    decoded: OnceCell<Value>,
+    encoded: Option<Vec<Bytes>>,
+}
+
+impl Fields {
+    fn encoded(entries: Vec<Bytes>) -> Self {
+        Self {
+            decoded: OnceCell::new(),
+            encoded: Some(entries),
+        }
+    }
+
+    fn value(&self) -> &Value {
+        self.decoded.get_or_init(|| {
+            let entries = self.encoded.as_deref().unwrap_or_default();
+            Value::Map(proto::decode_fields(entries))
+        })
+    }
+
+    fn value_mut(&mut self) -> &mut Value {
+        self.value();
+        // The entries no longer match the fields once they're changed.
+        self.encoded = None;
+        self.decoded
+            .get_mut()
+            .unwrap_or_else(|| unreachable!("fields were decoded"))
+    }
+
+    fn into_value(self) -> Value {
+        match self.decoded.into_inner() {
+            Some(value) => value,
+            None => Value::Map(proto::decode_fields(
+                self.encoded.as_deref().unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+impl From<Value> for Fields {
+    fn from(value: Value) -> Self {
+        Self {
+            decoded: OnceCell::from(value),
+            encoded: None,
+        }
+    }
+}
+
+impl Debug for Fields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.value(), f)
+    }
+}
+
+impl PartialEq for Fields {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl PartialOrd for Fields {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        self.value().partial_cmp(other.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// How the allocated size of log events is accounted for as they are
+/// buffered and batched.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeAccounting {
+    /// The size is measured once and kept up to date as fields change.
+    Cached,
+    /// Every value is measured each time the size is needed.
+    Exact,
+}
+
+impl Default for SizeAccounting {
     fn default() -> Self {
+        Self::Cached
+    }
+}
+
+static EXACT_SIZE_ACCOUNTING: AtomicBool = AtomicBool::new(false);
+
+/// Sets how the size of all log events is accounted for from now on.
+pub fn set_size_accounting(accounting: SizeAccounting) {
+    EXACT_SIZE_ACCOUNTING.store(accounting == SizeAccounting::Exact, Ordering::Relaxed);
+}
+
+fn size_accounting() -> SizeAccounting {
+    if EXACT_SIZE_ACCOUNTING.load(Ordering::Relaxed) {
+        SizeAccounting::Exact
+    } else {
+        SizeAccounting::Cached
+    }
+}
+
+/// The allocated size of a log event's fields, so that accounting for the
+/// event doesn't walk all of its values every time it is buffered.
+///
+/// The size is measured on first use. Inserting or removing a field only
+/// measures the top-level field it's in again. Top-level fields handed out
+/// mutably are left out of the size, and measured when it's next read, until
+/// the next change folds them back in. Only handing out the whole map
+/// mutably forgets the size.
+#[derive(Debug, Default)]
+struct SizeCache {
+    // The size is stored off by one, so that zero means it isn't known.
+    size: AtomicUsize,
+    changed: Vec<String>,
+}
+
+impl SizeCache {
+    fn get(&self) -> Option<usize> {
+        self.size.load(Ordering::Relaxed).checked_sub(1)
+    }
+
+    fn set(&self, size: usize) {
+        self.size.store(size + 1, Ordering::Relaxed);
+    }
+
+    fn clear(&mut self) {
+        *self.size.get_mut() = 0;
+        self.changed.clear();
+    }
+
+    fn update(&mut self, added: usize, removed: usize) {
+        let cached = self.size.get_mut();
+        if *cached != 0 {
+            *cached += added;
+            *cached -= removed;
+        }
+    }
+}
+
+impl Clone for SizeCache {
+    fn clone(&self) -> Self {
         Self {
-            fields: Value::Map(BTreeMap::new()),
-            metadata: EventMetadata::default(),
+            size: AtomicUsize::new(self.size.load(Ordering::Relaxed)),
+            changed: self.changed.clone(),
         }
     }
 }
 
+/// The size of the top-level field `root`, as it counts towards the size of
+/// the fields.
+fn field_size(fields: &BTreeMap<String, Value>, root: &str) -> usize {
+    fields
+        .get(root)
+        .map_or(0, |value| root.len() + value.size_of())
+}
+
+/// The top-level field a path is in.
+fn path_root(path: &str) -> Option<String> {
+    match PathIter::new(path).next() {
+        Some(PathComponent::Key(key)) => Some(key),
+        _ => None,
+    }
+}
+
+impl Default for LogEvent {
+    fn default() -> Self {
+        Self::from_parts(BTreeMap::new(), EventMetadata::default())
+    }
+}
+
 impl ByteSizeOf for LogEvent {
     fn allocated_bytes(&self) -> usize {
-        self.fields.allocated_bytes() + self.metadata.allocated_bytes()
+        self.fields_allocated_bytes(size_accounting()) + self.metadata.allocated_bytes()
     }
 }
 
 impl LogEvent {
+    /// The allocated size of the fields. Fields that haven't been decoded
+    /// count the size of their encoded entries, and aren't decoded for it.
+    fn fields_allocated_bytes(&self, accounting: SizeAccounting) -> usize {
+        let encoded = self
+            .fields
+            .encoded
+            .as_deref()
+            .map_or(0, |entries| entries.iter().map(Bytes::len).sum());
+        let fields = match self.fields.decoded.get() {
+            Some(Value::Map(fields)) => fields,
+            Some(_) => unreachable!("fields must be a map"),
+            None => return encoded,
+        };
+
+        let decoded = match accounting {
+            SizeAccounting::Exact => self.fields.value().allocated_bytes(),
+            SizeAccounting::Cached => {
+                let size = self.size_cache.get().unwrap_or_else(|| {
+                    let size = self.fields.value().allocated_bytes();
+                    self.size_cache.set(size);
+                    size
+                });
+                let changed = self
+                    .size_cache
+                    .changed
+                    .iter()
+                    .map(|root| field_size(fields, root))
+                    .sum::<usize>();
+                size + changed
+            }
+        };
+        encoded + decoded
+    }
+
+    /// Folds the top-level fields handed out mutably back into the cached
+    /// size.
+    fn settle_size(&mut self) {
+        if self.size_cache.changed.is_empty() {
+            return;
+        }
+
+        let changed = std::mem::take(&mut self.size_cache.changed);
+        let fields = self.as_map();
+        let added = changed.iter().map(|root| field_size(fields, root)).sum();
+        self.size_cache.update(added, 0);
+    }
+
+    /// Changes the fields, which only touches the top-level field `root`, so
+    /// that only that field is measured again.
+    fn change_field<T>(
+        &mut self,
+        root: Option<&str>,
+        change: impl FnOnce(&mut BTreeMap<String, Value>) -> T,
+    ) -> T {
+        self.settle_size();
+        let root = match root {
+            Some(root) if self.size_cache.get().is_some() => root,
+            _ => {
+                self.size_cache.clear();
+                return change(self.map_mut());
+            }
+        };
+
+        let fields = self.map_mut();
+        let before = field_size(fields, root);
+        let changed = change(fields);
+        let after = field_size(fields, root);
+        self.size_cache.update(after, before);
+        changed
+    }
+
+    /// Leaves the top-level field `root` out of the cached size, as it's
+    /// about to be handed out mutably.
+    fn field_changing(&mut self, root: Option<String>) {
+        self.settle_size();
+        match root {
+            Some(root) if self.size_cache.get().is_some() => {
+                let removed = field_size(self.as_map(), &root);
+                self.size_cache.update(0, removed);
+                self.size_cache.changed.push(root);
+            }
+            _ => self.size_cache.clear(),
+        }
+    }
+
     #[must_use]
     pub fn new_with_metadata(metadata: EventMetadata) -> Self {
+        Self::from_parts(BTreeMap::new(), metadata)
+    }
+
+    ///  Create a `LogEvent` into a tuple of its components
+    pub fn from_parts(map: BTreeMap<String, Value>, metadata: EventMetadata) -> Self {
         Self {
-            fields: Value::Map(Default::default()),
+            fields: Value::Map(map).into(),
             metadata,
+            size_cache: SizeCache::default(),
         }
     }
 
-    ///  Create a `LogEvent` into a tuple of its components
-    pub fn from_parts(map: BTreeMap<String, Value>, metadata: EventMetadata) -> Self {
-        let fields = Value::Map(map);
-        Self { fields, metadata }
+    /// Create a `LogEvent` from the encoded entries of its fields, which are
+    /// decoded when they're first accessed.
+    pub(crate) fn from_encoded(entries: Vec<Bytes>, metadata: EventMetadata) -> Self {
+        Self {
+            fields: Fields::encoded(entries),
+            metadata,
+            size_cache: SizeCache::default(),
+        }
     }
 
     /// Convert a `LogEvent` into a tuple of its components
@@ -69,12 +348,29 @@ impl LogEvent {
     pub fn into_parts(self) -> (BTreeMap<String, Value>, EventMetadata) {
         (
             self.fields
+                .into_value()
                 .into_map()
                 .unwrap_or_else(|| unreachable!("fields must be a map")),
             self.metadata,
         )
     }
 
+    /// Convert a `LogEvent` into the encoded entries of its fields and its
+    /// metadata. Fields that haven't changed since they were decoded are
+    /// handed back as they were received rather than encoded again.
+    pub(crate) fn into_encoded_parts(self) -> (Vec<Bytes>, EventMetadata) {
+        let Fields { decoded, encoded } = self.fields;
+        let entries = encoded.unwrap_or_else(|| {
+            proto::encode_fields(
+                decoded
+                    .into_inner()
+                    .and_then(Value::into_map)
+                    .unwrap_or_default(),
+            )
+        });
+        (entries, self.metadata)
+    }
+
     pub fn with_batch_notifier(mut self, batch: &Arc<BatchNotifier>) -> Self {
         self.metadata = self.metadata.with_batch_notifier(batch);
         self
@@ -96,7 +392,9 @@ impl LogEvent {
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key.as_ref()))]
     pub fn get_mut(&mut self, key: impl AsRef<str>) -> Option<&mut Value> {
-        util::log::get_mut(self.as_map_mut(), key.as_ref())
+        let key = key.as_ref();
+        self.field_changing(path_root(key));
+        util::log::get_mut(self.map_mut(), key)
     }
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key.as_ref()))]
@@ -110,7 +408,11 @@ impl LogEvent {
         key: impl AsRef<str>,
         value: impl Into<Value> + Debug,
     ) -> Option<Value> {
-        util::log::insert(self.as_map_mut(), key.as_ref(), value.into())
+        let key = key.as_ref();
+        let value = value.into();
+        self.change_field(path_root(key).as_deref(), |fields| {
+            util::log::insert(fields, key, value)
+        })
     }
 
     #[instrument(level = "trace", skip(self, key), fields(key = ?key))]
@@ -118,7 +420,14 @@ impl LogEvent {
     where
         V: Into<Value> + Debug,
     {
-        util::log::insert_path(self.as_map_mut(), key, value.into())
+        let root = match key.first() {
+            Some(PathComponent::Key(root)) => Some(root.clone()),
+            _ => None,
+        };
+        let value = value.into();
+        self.change_field(root.as_deref(), |fields| {
+            util::log::insert_path(fields, key, value)
+        })
     }
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key))]
@@ -127,7 +436,16 @@ impl LogEvent {
         K: Into<String> + Display,
         V: Into<Value> + Debug,
     {
-        self.as_map_mut().insert(key.into(), value.into());
+        self.settle_size();
+        let key = key.into();
+        let value = value.into();
+        let key_len = key.len();
+        let added = key_len + value.size_of();
+        let removed = self
+            .map_mut()
+            .insert(key, value)
+            .map_or(0, |old| key_len + old.size_of());
+        self.size_cache.update(added, removed);
     }
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key.as_ref()))]
@@ -140,20 +458,20 @@ impl LogEvent {
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key.as_ref()))]
     pub fn remove(&mut self, key: impl AsRef<str>) -> Option<Value> {
-        util::log::remove(self.as_map_mut(), key.as_ref(), false)
+        self.remove_prune(key, false)
     }
 
     #[instrument(level = "trace", skip(self, key), fields(key = %key.as_ref()))]
     pub fn remove_prune(&mut self, key: impl AsRef<str>, prune: bool) -> Option<Value> {
-        util::log::remove(self.as_map_mut(), key.as_ref(), prune)
+        let key = key.as_ref();
+        self.change_field(path_root(key).as_deref(), |fields| {
+            util::log::remove(fields, key, prune)
+        })
     }
 
     #[instrument(level = "trace", skip(self))]
     pub fn keys<'a>(&'a self) -> impl Iterator<Item = String> + 'a {
-        match &self.fields {
-            Value::Map(map) => util::log::keys(map),
-            _ => unreachable!(),
-        }
+        util::log::keys(self.as_map())
     }
 
     #[instrument(level = "trace", skip(self))]
@@ -168,7 +486,7 @@ impl LogEvent {
 
     #[instrument(level = "trace", skip(self))]
     pub fn as_map(&self) -> &BTreeMap<String, Value> {
-        match &self.fields {
+        match self.fields.value() {
             Value::Map(map) => map,
             _ => unreachable!(),
         }
@@ -176,8 +494,15 @@ impl LogEvent {
 
     #[instrument(level = "trace", skip(self))]
     pub fn as_map_mut(&mut self) -> &mut BTreeMap<String, Value> {
-        match self.fields {
-            Value::Map(ref mut map) => map,
+        // The caller may change any of the fields, so the cached size can't
+        // be trusted afterwards.
+        self.size_cache.clear();
+        self.map_mut()
+    }
+
+    fn map_mut(&mut self) -> &mut BTreeMap<String, Value> {
+        match self.fields.value_mut() {
+            Value::Map(map) => map,
             _ => unreachable!(),
         }
     }
@@ -187,7 +512,8 @@ impl LogEvent {
         let mut walker = lookup.into_iter().enumerate();
 
         let mut current_pointer = if let Some((_index, Segment::Field(segment))) = walker.next() {
-            self.as_map_mut().entry(segment)
+            self.field_changing(Some(segment.clone()));
+            self.map_mut().entry(segment)
         } else {
             // It should be noted that Remap can create a lookup without a contained segment.
             // This is the root `.` path. That is handled explicitly by the Target implementation
@@ -269,16 +595,13 @@ impl From<String> for LogEvent {
 
 impl From<BTreeMap<String, Value>> for LogEvent {
     fn from(map: BTreeMap<String, Value>) -> Self {
-        LogEvent {
-            fields: Value::Map(map),
-            metadata: EventMetadata::default(),
-        }
+        Self::from_parts(map, EventMetadata::default())
     }
 }
 
 impl From<LogEvent> for BTreeMap<String, Value> {
     fn from(event: LogEvent) -> BTreeMap<String, Value> {
-        match event.fields {
+        match event.fields.into_value() {
             Value::Map(map) => map,
             _ => unreachable!(),
         }
@@ -287,10 +610,7 @@ impl From<LogEvent> for BTreeMap<String, Value> {
 
 impl From<HashMap<String, Value>> for LogEvent {
     fn from(map: HashMap<String, Value>) -> Self {
-        LogEvent {
-            fields: map.into_iter().collect(),
-            metadata: EventMetadata::default(),
-        }
+        Self::from_parts(map.into_iter().collect(), EventMetadata::default())
     }
 }
 
@@ -326,7 +646,7 @@ impl TryInto<serde_json::Value> for LogEvent {
     type Error = crate::Error;
 
     fn try_into(self) -> Result<serde_json::Value, Self::Error> {
-        Ok(serde_json::to_value(self.fields)?)
+        Ok(serde_json::to_value(self.fields.into_value())?)
     }
 }
 
@@ -385,9 +705,9 @@ impl From<&tracing::Event<'_>> for LogEvent {
         log.insert(
             "metadata.kind",
             if meta.is_event() {
-                Value::Bytes("event".to_string().into())
+                Value::String("event".to_string().into())
             } else if meta.is_span() {
-                Value::Bytes("span".to_string().into())
+                Value::String("span".to_string().into())
             } else {
                 Value::Null
             },
@@ -396,7 +716,7 @@ impl From<&tracing::Event<'_>> for LogEvent {
         log.insert(
             "metadata.module_path",
             meta.module_path()
-                .map_or(Value::Null, |mp| Value::Bytes(mp.to_string().into())),
+                .map_or(Value::Null, |mp| Value::String(mp.to_string().into())),
         );
         log.insert("metadata.target", meta.target().to_string());
 
@@ -613,4 +933,65 @@ mod test {
 
         shared::assert_event_data_eq!(merged, expected);
     }
+
+    #[test]
+    fn cached_size_tracks_changes() {
+        let recomputed = |log: &LogEvent| log.fields.value().allocated_bytes();
+
+        let mut log = LogEvent::from("message");
+        log.insert("nested.field", vec![1, 2, 3]);
+        assert_eq!(log.allocated_bytes(), recomputed(&log));
+        assert!(log.size_cache.get().is_some());
+
+        log.insert_flat("flat", "value");
+        assert!(log.size_cache.get().is_some());
+        assert_eq!(log.allocated_bytes(), recomputed(&log));
+
+        log.insert_flat("flat", "a longer value");
+        assert_eq!(log.size_cache.get(), Some(recomputed(&log)));
+
+        log.insert("nested.other", "value");
+        assert_eq!(log.size_cache.get(), Some(recomputed(&log)));
+
+        *log.get_mut("flat").unwrap() = Value::from("short");
+        assert!(log.size_cache.get().is_some());
+        assert_eq!(log.allocated_bytes(), recomputed(&log));
+
+        log.insert("nested.field[5]", 6);
+        assert_eq!(log.size_cache.get(), Some(recomputed(&log)));
+        assert!(log.size_cache.changed.is_empty());
+
+        log.remove("nested.other");
+        assert_eq!(log.size_cache.get(), Some(recomputed(&log)));
+        log.remove("nested");
+        assert_eq!(log.size_cache.get(), Some(recomputed(&log)));
+        assert_eq!(log.clone().allocated_bytes(), recomputed(&log));
+
+        log.as_map_mut();
+        assert!(log.size_cache.get().is_none());
+        assert_eq!(log.allocated_bytes(), recomputed(&log));
+    }
+
+    #[test]
+    fn accounts_exact_size() {
+        let mut log = LogEvent::from("message");
+        log.insert("nested.field", "value");
+        let exact = log.fields_allocated_bytes(SizeAccounting::Exact);
+
+        assert_eq!(exact, log.fields.value().allocated_bytes());
+        assert!(log.size_cache.get().is_none());
+        assert_eq!(log.fields_allocated_bytes(SizeAccounting::Cached), exact);
+    }
+
+    #[test]
+    fn sizes_encoded_fields_without_decoding() {
+        let mut fields = BTreeMap::new();
+        fields.insert("message".to_owned(), Value::from("hello"));
+        let entries = proto::encode_fields(fields);
+        let encoded = entries.iter().map(Bytes::len).sum::<usize>();
+
+        let log = LogEvent::from_encoded(entries, EventMetadata::default());
+        assert_eq!(log.fields_allocated_bytes(SizeAccounting::Cached), encoded);
+        assert!(log.fields.decoded.get().is_none());
+    }
 }
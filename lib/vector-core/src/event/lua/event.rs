@@ -8,6 +8,13 @@ impl<'a> ToLua<'a> for Event {
         match self {
             Event::Log(log) => table.raw_set("log", log.to_lua(lua)?)?,
             Event::Metric(metric) => table.raw_set("metric", metric.to_lua(lua)?)?,
+            Event::Trace(_) => {
+                return Err(LuaError::ToLuaConversionError {
+                    from: "Event",
+                    to: "table",
+                    message: Some("Trace events are not supported".to_string()),
+                })
+            }
         }
         Ok(LuaValue::Table(table))
     }
@@ -115,8 +122,8 @@ mod test {
 
         let event = Lua::new().load(lua_event).eval::<Event>().unwrap();
         let log = event.as_log();
-        assert_eq!(log["field"], Value::Bytes("example".into()));
-        assert_eq!(log["nested.field"], Value::Bytes("another example".into()));
+        assert_eq!(log["field"], Value::String("example".into()));
+        assert_eq!(log["nested.field"], Value::String("another example".into()));
     }
 
     #[test]
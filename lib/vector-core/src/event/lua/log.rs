@@ -81,15 +81,15 @@ mod test {
         let event: LogEvent = Lua::new().load(lua_event).eval().unwrap();
 
         assert_eq!(event["a"], Value::Integer(1));
-        assert_eq!(event["nested.field"], Value::Bytes("2".into()));
+        assert_eq!(event["nested.field"], Value::String("2".into()));
         assert_eq!(
             event["nested.array[0]"],
-            Value::Bytes("example value".into())
+            Value::String("example value".into())
         );
-        assert_eq!(event["nested.array[1]"], Value::Bytes("".into()));
+        assert_eq!(event["nested.array[1]"], Value::String("".into()));
         assert_eq!(
             event["nested.array[2]"],
-            Value::Bytes("another value".into())
+            Value::String("another value".into())
         );
     }
 }
@@ -68,8 +68,8 @@ impl<'a> ToLua<'a> for Metric {
         if let Some(ts) = self.data.timestamp {
             tbl.raw_set("timestamp", timestamp_to_table(lua, ts)?)?;
         }
-        if let Some(tags) = self.series.tags {
-            tbl.raw_set("tags", tags)?;
+        if let Some(tags) = self.tags() {
+            tbl.raw_set("tags", tags.clone())?;
         }
         tbl.raw_set("kind", self.data.kind)?;
 
@@ -126,6 +126,18 @@ impl<'a> ToLua<'a> for Metric {
                 aggregated_summary.raw_set("sum", sum)?;
                 tbl.raw_set("aggregated_summary", aggregated_summary)?;
             }
+            MetricValue::Sketch { sketch: value } => {
+                let sketch = lua.create_table()?;
+                let samples = value.samples();
+                let sample_rates: Vec<_> = samples.iter().map(|s| s.rate).collect();
+                let values: Vec<_> = samples.into_iter().map(|s| s.value).collect();
+                sketch.raw_set("relative_accuracy", value.relative_accuracy())?;
+                sketch.raw_set("values", values)?;
+                sketch.raw_set("sample_rates", sample_rates)?;
+                sketch.raw_set("count", value.count())?;
+                sketch.raw_set("sum", value.sum())?;
+                tbl.raw_set("sketch", sketch)?;
+            }
         }
 
         Ok(LuaValue::Table(tbl))
@@ -196,11 +208,22 @@ impl<'a> FromLua<'a> for Metric {
                 count: aggregated_summary.raw_get("count")?,
                 sum: aggregated_summary.raw_get("sum")?,
             }
+        } else if let Some(sketch) = table.raw_get::<_, Option<LuaTable>>("sketch")? {
+            let values: Vec<f64> = sketch.raw_get("values")?;
+            let rates: Vec<u32> = sketch.raw_get("sample_rates")?;
+            MetricValue::Sketch {
+                sketch: metric::DDSketch::from_samples(
+                    sketch
+                        .raw_get::<_, Option<f64>>("relative_accuracy")?
+                        .unwrap_or(metric::DEFAULT_RELATIVE_ACCURACY),
+                    metric::zip_samples(values, rates),
+                ),
+            }
         } else {
             return Err(LuaError::FromLuaConversionError {
                 from: value.type_name(),
                 to: "Metric",
-                message: Some("Cannot find metric value, expected presence one of \"counter\", \"gauge\", \"set\", \"distribution\", \"aggregated_histogram\", \"aggregated_summary\"".to_string()),
+                message: Some("Cannot find metric value, expected presence one of \"counter\", \"gauge\", \"set\", \"distribution\", \"aggregated_histogram\", \"aggregated_summary\", \"sketch\"".to_string()),
             });
         };
 
@@ -385,6 +408,31 @@ mod test {
         assert_metric(metric, assertions);
     }
 
+    #[test]
+    fn to_lua_sketch() {
+        let metric = Metric::new(
+            "example sketch",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: metric::DDSketch::from_samples(
+                    0.01,
+                    crate::samples![1.0 => 10, 100.0 => 20],
+                ),
+            },
+        );
+        let assertions = vec![
+            "type(metric.sketch) == 'table'",
+            "metric.sketch.relative_accuracy == 0.01",
+            "#metric.sketch.values == 2",
+            "#metric.sketch.sample_rates == 2",
+            "metric.sketch.sample_rates[1] == 10",
+            "metric.sketch.sample_rates[2] == 20",
+            "metric.sketch.count == 30",
+            "metric.sketch.sum == 2010",
+        ];
+        assert_metric(metric, assertions);
+    }
+
     #[test]
     fn from_lua_counter_minimal() {
         let value = r#"{
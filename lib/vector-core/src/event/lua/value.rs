@@ -1,12 +1,15 @@
 use super::util::{table_is_timestamp, table_to_timestamp, timestamp_to_table};
 use crate::event::Value;
+use bytes::Bytes;
 use mlua::prelude::*;
 
 impl<'a> ToLua<'a> for Value {
     #![allow(clippy::wrong_self_convention)] // this trait is defined by mlua
     fn to_lua(self, lua: &'a Lua) -> LuaResult<LuaValue> {
         match self {
-            Value::Bytes(b) => lua.create_string(b.as_ref()).map(LuaValue::String),
+            Value::Bytes(b) | Value::String(b) => {
+                lua.create_string(b.as_ref()).map(LuaValue::String)
+            }
             Value::Integer(i) => Ok(LuaValue::Integer(i)),
             Value::Float(f) => Ok(LuaValue::Number(f)),
             Value::Boolean(b) => Ok(LuaValue::Boolean(b)),
@@ -21,7 +24,7 @@ impl<'a> ToLua<'a> for Value {
 impl<'a> FromLua<'a> for Value {
     fn from_lua(value: LuaValue<'a>, lua: &'a Lua) -> LuaResult<Self> {
         match value {
-            LuaValue::String(s) => Ok(Value::Bytes(Vec::from(s.as_bytes()).into())),
+            LuaValue::String(s) => Ok(Value::from(Bytes::copy_from_slice(s.as_bytes()))),
             LuaValue::Integer(i) => Ok(Value::Integer(i)),
             LuaValue::Number(f) => Ok(Value::Float(f)),
             LuaValue::Boolean(b) => Ok(Value::Boolean(b)),
@@ -53,7 +56,7 @@ mod test {
         let pairs = vec![
             (
                 "'\u{237a}\u{3b2}\u{3b3}'",
-                Value::Bytes("\u{237a}\u{3b2}\u{3b3}".into()),
+                Value::String("\u{237a}\u{3b2}\u{3b3}".into()),
             ),
             ("123", Value::Integer(123)),
             ("4.333", Value::Float(4.333)),
@@ -104,7 +107,7 @@ mod test {
     fn to_lua() {
         let pairs = vec![
             (
-                Value::Bytes("\u{237a}\u{3b2}\u{3b3}".into()),
+                Value::String("\u{237a}\u{3b2}\u{3b3}".into()),
                 r#"
                 function (value)
                     return value == '\u{237a}\u{3b2}\u{3b3}'
@@ -1,26 +1,120 @@
 #![deny(missing_docs)]
 
-use super::{BatchNotifier, EventFinalizer, EventFinalizers, EventStatus};
+use super::{BatchNotifier, EventFinalizer, EventFinalizers, EventStatus, Secrets};
 use crate::ByteSizeOf;
-use getset::{Getters, Setters};
+use chrono::{DateTime, Utc};
+use getset::{CopyGetters, Getters, MutGetters, Setters};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use shared::EventDataEq;
-use std::sync::Arc;
+use std::{cell::Cell, sync::Arc, time::Instant};
 
 /// The top-level metadata structure contained by both `struct Metric`
 /// and `struct LogEvent` types.
 #[derive(
-    Clone, Debug, Default, Deserialize, Getters, PartialEq, PartialOrd, Serialize, Setters,
+    Clone,
+    CopyGetters,
+    Debug,
+    Default,
+    Deserialize,
+    Getters,
+    MutGetters,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    Setters,
 )]
 pub struct EventMetadata {
     /// Used to store the datadog API from sources to sinks
     #[getset(get = "pub", set = "pub")]
     #[serde(default, skip)]
     datadog_api_key: Option<Arc<str>>,
+    /// Why a component dropped, or would have dropped, the event
+    #[getset(get_copy = "pub", set = "pub")]
+    #[serde(default, skip)]
+    drop_reason: Option<DropReason>,
+    /// When the event was ingested, as a reading of [`monotonic_now`]
+    #[getset(get_copy = "pub", set = "pub")]
+    #[serde(default, skip)]
+    ingest_monotonic: Option<u64>,
+    /// When the event was first ingested, by the wall clock
+    #[getset(get_copy = "pub", set = "pub")]
+    #[serde(default, skip)]
+    ingest_timestamp: Option<DateTime<Utc>>,
+    /// Credentials attached by the source, never encoded along with the event
+    #[getset(get = "pub", get_mut = "pub")]
+    #[serde(default, skip)]
+    secrets: Secrets,
+    /// IDs of the components that sent the event on, from its source onward
+    #[getset(get = "pub")]
+    #[serde(default, skip)]
+    lineage: Vec<Arc<str>>,
+    /// Whether the Vector the event was received from waits on its delivery
+    #[getset(get_copy = "pub", set = "pub")]
+    #[serde(default, skip)]
+    upstream_awaits_delivery: bool,
     #[serde(default, skip)]
     finalizers: EventFinalizers,
 }
 
+static MONOTONIC_EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+thread_local! {
+    static MONOTONIC_LAST: Cell<u64> = Cell::new(0);
+}
+
+/// Read the monotonic ingest clock, in nanoseconds since the clock was first
+/// read.
+///
+/// Unlike wall clock timestamps, readings never go backwards, and no two
+/// readings taken on the same thread are the same, so they order events
+/// ingested in quick succession by a source even when their timestamps tie.
+/// Readings taken on different threads are ordered by the monotonic clock of
+/// the system, and may be equal. They are only comparable within one process,
+/// which is why they aren't carried across encoding.
+#[allow(clippy::cast_possible_truncation)] // would take centuries of uptime
+pub fn monotonic_now() -> u64 {
+    let now = MONOTONIC_EPOCH.elapsed().as_nanos() as u64;
+    MONOTONIC_LAST.with(|last| {
+        let next = now.max(last.get() + 1);
+        last.set(next);
+        next
+    })
+}
+
+/// The reasons a component may drop an event for, used to account for
+/// discarded events by cause.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    /// The event didn't match a filter condition.
+    Filtered,
+    /// The event was a duplicate of one seen before.
+    Deduplicated,
+    /// A remap program aborted while processing the event.
+    MappingAborted,
+    /// A remap program failed while processing the event.
+    MappingFailed,
+}
+
+impl DropReason {
+    /// The name of the reason, as used in the `reason` tag of internal metrics.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Filtered => "filtered",
+            Self::Deduplicated => "deduplicated",
+            Self::MappingAborted => "mapping_aborted",
+            Self::MappingFailed => "mapping_failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DropReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl ByteSizeOf for EventMetadata {
     fn allocated_bytes(&self) -> usize {
         // NOTE we don't count the `str` here because it's allocated somewhere
@@ -42,13 +136,61 @@ impl EventMetadata {
         self.with_finalizer(EventFinalizer::new(Arc::clone(batch)))
     }
 
+    /// Record the current reading of the monotonic ingest clock, along with
+    /// the current time unless the event was ingested earlier, by another
+    /// Vector.
+    pub fn record_ingest(&mut self) {
+        self.ingest_monotonic = Some(monotonic_now());
+        self.ingest_timestamp.get_or_insert_with(Utc::now);
+    }
+
+    /// Record that the component `id` sent the event on, appending it to the
+    /// event's lineage.
+    pub fn record_lineage(&mut self, id: Arc<str>) {
+        self.lineage.push(id);
+    }
+
+    /// Replace the lineage of the event, as decoded from another Vector.
+    pub fn set_lineage(&mut self, lineage: Vec<Arc<str>>) {
+        self.lineage = lineage;
+    }
+
+    /// The ID of the source that ingested the event, if lineage is recorded.
+    pub fn source_id(&self) -> Option<&Arc<str>> {
+        self.lineage.first()
+    }
+
+    /// Whether the event's delivery is waited on, here or by the Vector it
+    /// was received from.
+    pub fn awaits_delivery(&self) -> bool {
+        !self.finalizers.is_empty() || self.upstream_awaits_delivery
+    }
+
     /// Merge the other `EventMetadata` into this.
-    /// If a Datadog API key is not set in `self`, the one from `other` will be used.
+    /// If a Datadog API key, drop reason or lineage is not set in `self`, the
+    /// one from `other` will be used, and likewise for each secret. The
+    /// earliest ingest reading and timestamp of the two are kept.
     pub fn merge(&mut self, other: Self) {
         self.finalizers.merge(other.finalizers);
         if self.datadog_api_key.is_none() {
             self.datadog_api_key = other.datadog_api_key;
         }
+        if self.drop_reason.is_none() {
+            self.drop_reason = other.drop_reason;
+        }
+        self.secrets.merge(other.secrets);
+        if self.lineage.is_empty() {
+            self.lineage = other.lineage;
+        }
+        self.ingest_monotonic = match (self.ingest_monotonic, other.ingest_monotonic) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.ingest_timestamp = match (self.ingest_timestamp, other.ingest_timestamp) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.upstream_awaits_delivery |= other.upstream_awaits_delivery;
     }
 
     /// Update the finalizer(s) status.
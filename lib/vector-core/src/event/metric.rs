@@ -1,9 +1,9 @@
-use super::{BatchNotifier, EventFinalizer, EventMetadata};
+use super::{intern::intern_tags, BatchNotifier, EventFinalizer, EventMetadata};
 use crate::metrics::Handle;
 use crate::ByteSizeOf;
 use chrono::{DateTime, Utc};
 use getset::{Getters, MutGetters};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use shared::EventDataEq;
 #[cfg(feature = "vrl")]
 use std::convert::TryFrom;
@@ -14,6 +14,11 @@ use std::{
     sync::Arc,
 };
 
+mod sketch;
+mod temporality;
+pub use sketch::{DDSketch, DEFAULT_RELATIVE_ACCURACY};
+pub use temporality::TemporalityConverter;
+
 #[derive(Clone, Debug, Deserialize, Getters, MutGetters, PartialEq, PartialOrd, Serialize)]
 pub struct Metric {
     #[getset(get = "pub")]
@@ -53,15 +58,30 @@ impl AsRef<MetricValue> for Metric {
 pub struct MetricSeries {
     #[serde(flatten)]
     pub name: MetricName,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tags: Option<MetricTags>,
+    /// The tags of the series, shared with every other series holding the
+    /// same tags. Changing them copies them first.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_tags",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tags: Option<Arc<MetricTags>>,
 }
 
 pub type MetricTags = BTreeMap<String, String>;
 
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Option<Arc<MetricTags>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<MetricTags>::deserialize(deserializer).map(|tags| tags.map(intern_tags))
+}
+
 impl ByteSizeOf for MetricSeries {
     fn allocated_bytes(&self) -> usize {
-        self.name.allocated_bytes() + self.tags.allocated_bytes()
+        // Shared tags are counted in full by every series holding them, so
+        // that dropping any one event never frees more than it accounted for.
+        self.name.allocated_bytes() + self.tags.as_ref().map_or(0, |tags| tags.size_of())
     }
 }
 
@@ -166,6 +186,10 @@ pub enum MetricValue {
         count: u32,
         sum: f64,
     },
+    /// A Sketch contains a DDSketch of a distribution, which gives quantiles
+    /// within a known relative accuracy without keeping every sample, and
+    /// can be merged with other sketches.
+    Sketch { sketch: DDSketch },
 }
 
 impl ByteSizeOf for MetricValue {
@@ -176,6 +200,7 @@ impl ByteSizeOf for MetricValue {
             Self::Distribution { samples, .. } => samples.allocated_bytes(),
             Self::AggregatedHistogram { buckets, .. } => buckets.allocated_bytes(),
             Self::AggregatedSummary { quantiles, .. } => quantiles.allocated_bytes(),
+            Self::Sketch { sketch } => sketch.allocated_bytes(),
         }
     }
 }
@@ -294,6 +319,7 @@ impl From<MetricValue> for vrl_core::Value {
             MetricValue::Distribution { .. } => "distribution",
             MetricValue::AggregatedHistogram { .. } => "aggregated histogram",
             MetricValue::AggregatedSummary { .. } => "aggregated summary",
+            MetricValue::Sketch { .. } => "sketch",
         }
         .into()
     }
@@ -365,7 +391,7 @@ impl Metric {
 
     #[inline]
     pub fn with_tags(mut self, tags: Option<MetricTags>) -> Self {
-        self.series.tags = tags;
+        self.series.tags = tags.map(intern_tags);
         self
     }
 
@@ -466,7 +492,7 @@ impl Metric {
 
     #[inline]
     pub fn tags(&self) -> Option<&MetricTags> {
-        self.series.tags.as_ref()
+        self.series.tags.as_deref()
     }
 
     #[inline]
@@ -540,6 +566,76 @@ impl Metric {
     pub fn subtract(&mut self, other: impl AsRef<MetricData>) -> bool {
         self.data.subtract(other.as_ref())
     }
+
+    /// Merge the data from the other metric into this one, like `update`,
+    /// but also merging histograms with different buckets and summaries.
+    /// See `MetricValue::merge`.
+    #[must_use]
+    pub fn merge(&mut self, other: impl AsRef<MetricData>) -> bool {
+        self.data.merge(other.as_ref())
+    }
+
+    /// The incremental metric covering the change since the `previous`
+    /// reading of this absolute metric, or `None` if either one isn't
+    /// absolute or their values can't be subtracted.
+    pub fn delta_since(&self, previous: &Self) -> Option<Self> {
+        if self.kind() != MetricKind::Absolute || previous.kind() != MetricKind::Absolute {
+            return None;
+        }
+
+        let value = self.value().delta(previous.value())?;
+        Some(Self {
+            series: self.series.clone(),
+            data: MetricData {
+                timestamp: self.data.timestamp,
+                kind: MetricKind::Incremental,
+                value,
+            },
+            metadata: self.metadata.clone(),
+        })
+    }
+
+    /// The per-second rate at which this absolute counter grew since the
+    /// `previous` reading, or `None` if either one has no timestamp or
+    /// `previous` isn't older than this one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rate_since(&self, previous: &Self) -> Option<f64> {
+        let elapsed = (self.timestamp()? - previous.timestamp()?).num_nanoseconds()?;
+        if elapsed <= 0 {
+            return None;
+        }
+
+        match self.delta_since(previous)?.value() {
+            MetricValue::Counter { value } => Some(value * 1e9 / elapsed as f64),
+            _ => None,
+        }
+    }
+
+    /// Rewrite this metric into one of the given `kind`, where `previous` is
+    /// the last absolute reading of the same series, if there was one.
+    ///
+    /// Absolute metrics become incremental ones by taking the change since
+    /// `previous`, so the first reading of a series, which has nothing to
+    /// compare to, gives `None`. Incremental metrics become absolute ones by
+    /// adding them to `previous`.
+    pub fn normalize(self, kind: MetricKind, previous: Option<&Self>) -> Option<Self> {
+        match (self.kind(), kind) {
+            (from, to) if from == to => Some(self),
+            (MetricKind::Absolute, _) => self.delta_since(previous?),
+            (MetricKind::Incremental, _) => match previous {
+                Some(previous) => {
+                    let mut absolute = previous.clone();
+                    absolute.data.timestamp = self.data.timestamp;
+                    if absolute.merge(&self) {
+                        Some(absolute)
+                    } else {
+                        None
+                    }
+                }
+                None => Some(self.into_absolute()),
+            },
+        }
+    }
 }
 
 impl EventDataEq for Metric {
@@ -554,7 +650,7 @@ impl MetricSeries {
     /// Set or updates the string value of a tag. *Note:* This will
     /// create the tags map if it is not present.
     pub fn insert_tag(&mut self, key: String, value: String) -> Option<String> {
-        (self.tags.get_or_insert_with(Default::default)).insert(key, value)
+        self.tags_mut().insert(key, value)
     }
 
     /// Remove the tag entry for the named key, if it exists, and return
@@ -562,14 +658,14 @@ impl MetricSeries {
     /// was the last entry in it.
     pub fn remove_tag(&mut self, key: &str) -> Option<String> {
         match &mut self.tags {
-            None => None,
-            Some(tags) => {
-                let result = tags.remove(key);
+            Some(tags) if tags.contains_key(key) => {
+                let result = Arc::make_mut(tags).remove(key);
                 if tags.is_empty() {
                     self.tags = None;
                 }
                 result
             }
+            _ => None,
         }
     }
 
@@ -577,7 +673,21 @@ impl MetricSeries {
     /// the tags map if it is not present, even if nothing is later
     /// inserted.
     pub fn tag_entry(&mut self, key: String) -> btree_map::Entry<String, String> {
-        self.tags.get_or_insert_with(Default::default).entry(key)
+        self.tags_mut().entry(key)
+    }
+
+    /// The tags of the series, copied first if they're shared, so they can
+    /// be changed. *Note:* This will create the tags map if it is not
+    /// present.
+    pub fn tags_mut(&mut self) -> &mut MetricTags {
+        Arc::make_mut(self.tags.get_or_insert_with(Default::default))
+    }
+
+    /// Take the tags out of the series, copying them if they're shared.
+    pub fn take_tags(&mut self) -> Option<MetricTags> {
+        self.tags
+            .take()
+            .map(|tags| Arc::try_unwrap(tags).unwrap_or_else(|tags| (*tags).clone()))
     }
 }
 
@@ -604,16 +714,30 @@ impl MetricData {
     #[must_use]
     pub fn update(&mut self, other: &Self) -> bool {
         self.value.add(&other.value) && {
-            // Update the timestamp to the latest one
-            self.timestamp = match (self.timestamp, other.timestamp) {
-                (None, None) => None,
-                (Some(t), None) | (None, Some(t)) => Some(t),
-                (Some(t1), Some(t2)) => Some(t1.max(t2)),
-            };
+            self.update_timestamp(other.timestamp);
             true
         }
     }
 
+    /// Update this `MetricData` by merging the value from another. See
+    /// `MetricValue::merge`.
+    #[must_use]
+    pub fn merge(&mut self, other: &Self) -> bool {
+        self.value.merge(&other.value) && {
+            self.update_timestamp(other.timestamp);
+            true
+        }
+    }
+
+    /// Update the timestamp to the latest one
+    fn update_timestamp(&mut self, other: Option<DateTime<Utc>>) {
+        self.timestamp = match (self.timestamp, other) {
+            (None, None) => None,
+            (Some(t), None) | (None, Some(t)) => Some(t),
+            (Some(t1), Some(t2)) => Some(t1.max(t2)),
+        };
+    }
+
     /// Add the data from the other metric to this one. The `other` must
     /// be incremental and contain the same value type as this one.
     #[must_use]
@@ -672,6 +796,7 @@ impl MetricValue {
                 *count = 0;
                 *sum = 0.0;
             }
+            Self::Sketch { sketch } => sketch.clear(),
         }
     }
 
@@ -725,6 +850,9 @@ impl MetricValue {
                 *sum += sum2;
                 true
             }
+            (Self::Sketch { ref mut sketch }, Self::Sketch { sketch: sketch2 }) => {
+                sketch.merge(sketch2)
+            }
 
             _ => false,
         }
@@ -789,9 +917,211 @@ impl MetricValue {
                 *sum -= sum2;
                 true
             }
+            (Self::Sketch { ref mut sketch }, Self::Sketch { sketch: sketch2 }) => {
+                sketch.subtract(sketch2)
+            }
             _ => false,
         }
     }
+
+    /// Merge another value into this, like `add`, but also merging
+    /// histograms with different buckets, and summaries.
+    ///
+    /// Histograms are re-bucketed onto the bucket limits both have in
+    /// common, plus the largest limit of either. Each bucket then lands in
+    /// the first new bucket whose limit is at least its own, which covers
+    /// exactly its range, so counts stay exact at the cost of resolution.
+    ///
+    /// Summaries keep the quantiles both have in common, averaging their
+    /// values weighted by the counts. This approximates the quantiles of the
+    /// merged observations, which can't be recovered exactly.
+    #[must_use]
+    pub fn merge(&mut self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::AggregatedHistogram {
+                    ref mut buckets,
+                    ref mut count,
+                    ref mut sum,
+                },
+                Self::AggregatedHistogram {
+                    buckets: buckets2,
+                    count: count2,
+                    sum: sum2,
+                },
+            ) => {
+                let limits = common_limits(buckets, buckets2);
+                let mut merged = rebucket(buckets, &limits);
+                for (b1, b2) in merged.iter_mut().zip(rebucket(buckets2, &limits)) {
+                    b1.count += b2.count;
+                }
+                *buckets = merged;
+                *count += count2;
+                *sum += sum2;
+                true
+            }
+            (
+                Self::AggregatedSummary {
+                    ref mut quantiles,
+                    ref mut count,
+                    ref mut sum,
+                },
+                Self::AggregatedSummary {
+                    quantiles: quantiles2,
+                    count: count2,
+                    sum: sum2,
+                },
+            ) => {
+                if quantiles.is_empty() {
+                    *quantiles = quantiles2.clone();
+                } else if !quantiles2.is_empty() {
+                    let total = f64::from(*count) + f64::from(*count2);
+                    let weight = if total > 0.0 {
+                        f64::from(*count) / total
+                    } else {
+                        0.5
+                    };
+                    *quantiles = quantiles
+                        .iter()
+                        .filter_map(|q1| {
+                            let q2 = quantiles2
+                                .iter()
+                                .find(|q2| q2.upper_limit == q1.upper_limit)?;
+                            Some(Quantile {
+                                upper_limit: q1.upper_limit,
+                                value: q1.value * weight + q2.value * (1.0 - weight),
+                            })
+                        })
+                        .collect();
+                }
+                *count += count2;
+                *sum += sum2;
+                true
+            }
+            (this, other) => this.add(other),
+        }
+    }
+
+    /// The change in this cumulative value since the `previous` one, or
+    /// `None` if the value isn't cumulative or the two don't line up.
+    ///
+    /// Counters, histograms and sketches can only grow, so one that's lower
+    /// than `previous` was reset in between and the change is the whole
+    /// value. Gauges may move either way, so their change is the difference.
+    pub fn delta(&self, previous: &Self) -> Option<Self> {
+        match (self, previous) {
+            (Self::Counter { value }, Self::Counter { value: previous }) => Some(Self::Counter {
+                value: if value >= previous {
+                    value - previous
+                } else {
+                    *value
+                },
+            }),
+            (Self::Gauge { value }, Self::Gauge { value: previous }) => Some(Self::Gauge {
+                value: value - previous,
+            }),
+            (Self::Set { values }, Self::Set { values: previous }) => Some(Self::Set {
+                values: values.difference(previous).cloned().collect(),
+            }),
+            (
+                Self::AggregatedHistogram {
+                    buckets,
+                    count,
+                    sum,
+                },
+                Self::AggregatedHistogram {
+                    buckets: buckets2,
+                    count: count2,
+                    sum: sum2,
+                },
+            ) if buckets.len() == buckets2.len()
+                && buckets
+                    .iter()
+                    .zip(buckets2.iter())
+                    .all(|(b1, b2)| b1.upper_limit == b2.upper_limit) =>
+            {
+                let reset = count < count2
+                    || buckets
+                        .iter()
+                        .zip(buckets2.iter())
+                        .any(|(b1, b2)| b1.count < b2.count);
+                Some(if reset {
+                    self.clone()
+                } else {
+                    Self::AggregatedHistogram {
+                        buckets: buckets
+                            .iter()
+                            .zip(buckets2.iter())
+                            .map(|(b1, b2)| Bucket {
+                                upper_limit: b1.upper_limit,
+                                count: b1.count - b2.count,
+                            })
+                            .collect(),
+                        count: count - count2,
+                        sum: sum - sum2,
+                    }
+                })
+            }
+            (Self::Sketch { sketch }, Self::Sketch { sketch: sketch2 }) => {
+                let mut delta = sketch.clone();
+                Some(if delta.subtract(sketch2) {
+                    Self::Sketch { sketch: delta }
+                } else {
+                    self.clone()
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The bucket limits two histograms have in common, plus the largest limit
+/// of either so every bucket of both is covered. If either histogram has no
+/// buckets, the other's limits are kept as they are.
+fn common_limits(buckets: &[Bucket], buckets2: &[Bucket]) -> Vec<f64> {
+    if buckets.is_empty() || buckets2.is_empty() {
+        return buckets
+            .iter()
+            .chain(buckets2)
+            .map(|bucket| bucket.upper_limit)
+            .collect();
+    }
+
+    let mut limits = buckets
+        .iter()
+        .map(|bucket| bucket.upper_limit)
+        .filter(|limit| buckets2.iter().any(|bucket| bucket.upper_limit == *limit))
+        .collect::<Vec<_>>();
+    let largest = buckets
+        .iter()
+        .chain(buckets2)
+        .map(|bucket| bucket.upper_limit)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if limits.last() != Some(&largest) {
+        limits.push(largest);
+    }
+    limits
+}
+
+/// Count `buckets` into new buckets with the given `limits`, each landing in
+/// the first one whose limit is at least its own.
+fn rebucket(buckets: &[Bucket], limits: &[f64]) -> Vec<Bucket> {
+    let mut rebucketed = limits
+        .iter()
+        .map(|&upper_limit| Bucket {
+            upper_limit,
+            count: 0,
+        })
+        .collect::<Vec<_>>();
+    for bucket in buckets {
+        if let Some(target) = rebucketed
+            .iter_mut()
+            .find(|target| target.upper_limit >= bucket.upper_limit)
+        {
+            target.count += bucket.count;
+        }
+    }
+    rebucketed
 }
 
 impl Display for Metric {
@@ -865,6 +1195,12 @@ impl Display for Metric {
                     write!(fmt, "{}@{}", quantile.upper_limit, quantile.value)
                 })
             }
+            MetricValue::Sketch { sketch } => {
+                write!(fmt, "count={} sum={} ", sketch.count(), sketch.sum())?;
+                write_list(fmt, " ", sketch.samples(), |fmt, sample| {
+                    write!(fmt, "{}@{}", sample.rate, sample.value)
+                })
+            }
         }
     }
 }
@@ -1057,6 +1393,199 @@ mod test {
         assert_eq!(dist, expected);
     }
 
+    #[test]
+    fn merge_sketches() {
+        let mut sketch = Metric::new(
+            "sketch",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, samples![1.0 => 10]),
+            },
+        );
+
+        let delta = Metric::new(
+            "sketch",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, samples![2.0 => 20]),
+            },
+        )
+        .with_namespace(Some("vector"))
+        .with_tags(Some(tags()))
+        .with_timestamp(Some(ts()));
+
+        let expected = sketch
+            .clone()
+            .with_value(MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, samples![1.0 => 10, 2.0 => 20]),
+            })
+            .with_timestamp(Some(ts()));
+
+        assert!(sketch.data.add(&delta.data));
+        assert_eq!(sketch, expected);
+
+        let other_accuracy = Metric::new(
+            "sketch",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: DDSketch::new(0.05),
+            },
+        );
+        assert!(!sketch.data.add(&other_accuracy.data));
+    }
+
+    #[test]
+    fn merge_histograms_with_different_buckets() {
+        let mut histogram = MetricValue::AggregatedHistogram {
+            buckets: buckets![1.0 => 1, 2.0 => 2, 5.0 => 3, f64::INFINITY => 4],
+            count: 10,
+            sum: 30.0,
+        };
+        let other = MetricValue::AggregatedHistogram {
+            buckets: buckets![1.0 => 5, 5.0 => 6, 10.0 => 7],
+            count: 18,
+            sum: 60.0,
+        };
+
+        assert!(histogram.merge(&other));
+        assert_eq!(
+            histogram,
+            MetricValue::AggregatedHistogram {
+                buckets: buckets![1.0 => 6, 5.0 => 11, f64::INFINITY => 11],
+                count: 28,
+                sum: 90.0,
+            }
+        );
+
+        let mut empty = MetricValue::AggregatedHistogram {
+            buckets: vec![],
+            count: 0,
+            sum: 0.0,
+        };
+        assert!(empty.merge(&other));
+        assert_eq!(empty, other);
+    }
+
+    #[test]
+    fn merge_summaries() {
+        let mut summary = MetricValue::AggregatedSummary {
+            quantiles: quantiles![0.5 => 10.0, 0.9 => 20.0, 0.99 => 30.0],
+            count: 30,
+            sum: 300.0,
+        };
+        let other = MetricValue::AggregatedSummary {
+            quantiles: quantiles![0.5 => 20.0, 0.99 => 50.0],
+            count: 10,
+            sum: 200.0,
+        };
+
+        assert!(!summary.clone().add(&other));
+        assert!(summary.merge(&other));
+        assert_eq!(
+            summary,
+            MetricValue::AggregatedSummary {
+                quantiles: quantiles![0.5 => 12.5, 0.99 => 35.0],
+                count: 40,
+                sum: 500.0,
+            }
+        );
+    }
+
+    #[test]
+    fn delta_since() {
+        let previous = Metric::new(
+            "counter",
+            MetricKind::Absolute,
+            MetricValue::Counter { value: 10.0 },
+        )
+        .with_timestamp(Some(ts()));
+        let current = previous
+            .clone()
+            .with_value(MetricValue::Counter { value: 25.0 })
+            .with_timestamp(Some(ts() + chrono::Duration::seconds(5)));
+
+        let delta = current.delta_since(&previous).unwrap();
+        assert_eq!(delta.kind(), MetricKind::Incremental);
+        assert_eq!(delta.value(), &MetricValue::Counter { value: 15.0 });
+        assert_eq!(current.rate_since(&previous), Some(3.0));
+        assert_eq!(previous.rate_since(&current), None);
+
+        let reset = current
+            .clone()
+            .with_value(MetricValue::Counter { value: 4.0 });
+        assert_eq!(
+            reset.delta_since(&previous).unwrap().value(),
+            &MetricValue::Counter { value: 4.0 }
+        );
+
+        assert!(delta.delta_since(&previous).is_none());
+    }
+
+    #[test]
+    fn delta_histograms() {
+        let previous = MetricValue::AggregatedHistogram {
+            buckets: buckets![1.0 => 1, 2.0 => 2],
+            count: 3,
+            sum: 4.0,
+        };
+        let current = MetricValue::AggregatedHistogram {
+            buckets: buckets![1.0 => 2, 2.0 => 5],
+            count: 7,
+            sum: 10.0,
+        };
+
+        assert_eq!(
+            current.delta(&previous),
+            Some(MetricValue::AggregatedHistogram {
+                buckets: buckets![1.0 => 1, 2.0 => 3],
+                count: 4,
+                sum: 6.0,
+            })
+        );
+        assert_eq!(previous.delta(&current), Some(previous.clone()));
+    }
+
+    #[test]
+    fn normalize_kinds() {
+        let absolute = Metric::new(
+            "gauge",
+            MetricKind::Absolute,
+            MetricValue::Gauge { value: 10.0 },
+        );
+        let incremental = Metric::new(
+            "gauge",
+            MetricKind::Incremental,
+            MetricValue::Gauge { value: -3.0 },
+        );
+
+        assert_eq!(
+            absolute.clone().normalize(MetricKind::Absolute, None),
+            Some(absolute.clone())
+        );
+        assert_eq!(
+            absolute.clone().normalize(MetricKind::Incremental, None),
+            None
+        );
+        assert_eq!(
+            incremental
+                .clone()
+                .normalize(MetricKind::Absolute, Some(&absolute)),
+            Some(
+                absolute
+                    .clone()
+                    .with_value(MetricValue::Gauge { value: 7.0 })
+            )
+        );
+
+        let next = absolute
+            .clone()
+            .with_value(MetricValue::Gauge { value: 7.0 });
+        assert_eq!(
+            next.normalize(MetricKind::Incremental, Some(&absolute)),
+            Some(incremental)
+        );
+    }
+
     #[test]
     // `too_many_lines` is mostly just useful for production code but we're not
     // able to flag the lint on only for non-test.
@@ -0,0 +1,441 @@
+use super::Sample;
+use crate::ByteSizeOf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The relative accuracy sketches are created with unless told otherwise,
+/// matching the default of the Datadog Agent.
+pub const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// A `DDSketch`, a quantile sketch with relative error guarantees.
+///
+/// Values are counted into logarithmically sized bins, so any quantile read
+/// back from the sketch is within `relative_accuracy` of the real one, no
+/// matter how many values went in. Sketches with the same relative accuracy
+/// can be merged without losing any of that accuracy.
+///
+/// See <https://arxiv.org/abs/1908.10693> for the details.
+#[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
+pub struct DDSketch {
+    relative_accuracy: f64,
+    /// Counts of positive values, keyed by bin index.
+    positive_bins: BTreeMap<i32, u32>,
+    /// Counts of negative values, keyed by the bin index of their magnitude.
+    negative_bins: BTreeMap<i32, u32>,
+    /// Count of values too close to zero to be put in a bin.
+    zero_count: u32,
+    count: u32,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl DDSketch {
+    /// Create an empty sketch. The relative accuracy must be between zero
+    /// and one, exclusive, otherwise the default accuracy is used.
+    pub fn new(relative_accuracy: f64) -> Self {
+        let relative_accuracy = if relative_accuracy > 0.0 && relative_accuracy < 1.0 {
+            relative_accuracy
+        } else {
+            DEFAULT_RELATIVE_ACCURACY
+        };
+
+        Self {
+            relative_accuracy,
+            positive_bins: BTreeMap::new(),
+            negative_bins: BTreeMap::new(),
+            zero_count: 0,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Create a sketch from its components, as produced by the accessors
+    /// below. The count, sum, min and max are recomputed from the bins if the
+    /// sketch is empty or the count doesn't add up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        relative_accuracy: f64,
+        positive_bins: BTreeMap<i32, u32>,
+        negative_bins: BTreeMap<i32, u32>,
+        zero_count: u32,
+        count: u32,
+        sum: f64,
+        min: f64,
+        max: f64,
+    ) -> Self {
+        let mut sketch = Self {
+            positive_bins,
+            negative_bins,
+            zero_count,
+            count,
+            sum,
+            min,
+            max,
+            ..Self::new(relative_accuracy)
+        };
+
+        if count == 0 || sketch.bin_count() != count {
+            sketch.rebuild_stats();
+        }
+
+        sketch
+    }
+
+    /// Build a sketch out of the samples of a distribution.
+    pub fn from_samples(relative_accuracy: f64, samples: impl IntoIterator<Item = Sample>) -> Self {
+        let mut sketch = Self::new(relative_accuracy);
+        for sample in samples {
+            sketch.insert_n(sample.value, sample.rate);
+        }
+        sketch
+    }
+
+    pub fn relative_accuracy(&self) -> f64 {
+        self.relative_accuracy
+    }
+
+    pub fn positive_bins(&self) -> &BTreeMap<i32, u32> {
+        &self.positive_bins
+    }
+
+    pub fn negative_bins(&self) -> &BTreeMap<i32, u32> {
+        &self.negative_bins
+    }
+
+    pub fn zero_count(&self) -> u32 {
+        self.zero_count
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// The smallest value inserted, if any.
+    pub fn min(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.min)
+        }
+    }
+
+    /// The largest value inserted, if any.
+    pub fn max(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.max)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Count a value into the sketch. `NaN` is ignored.
+    pub fn insert(&mut self, value: f64) {
+        self.insert_n(value, 1);
+    }
+
+    /// Count a value into the sketch `n` times. `NaN` is ignored.
+    pub fn insert_n(&mut self, value: f64, n: u32) {
+        if value.is_nan() || n == 0 {
+            return;
+        }
+
+        if value.abs() < f64::MIN_POSITIVE {
+            self.zero_count += n;
+        } else if value > 0.0 {
+            *self.positive_bins.entry(self.index(value)).or_default() += n;
+        } else {
+            *self.negative_bins.entry(self.index(-value)).or_default() += n;
+        }
+
+        self.count += n;
+        self.sum += value * f64::from(n);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Merge another sketch into this one. Both sketches must have the same
+    /// relative accuracy, otherwise this one is left as it is and `false` is
+    /// returned.
+    #[must_use]
+    pub fn merge(&mut self, other: &Self) -> bool {
+        if self.relative_accuracy != other.relative_accuracy {
+            return false;
+        }
+
+        for (index, count) in &other.positive_bins {
+            *self.positive_bins.entry(*index).or_default() += count;
+        }
+        for (index, count) in &other.negative_bins {
+            *self.negative_bins.entry(*index).or_default() += count;
+        }
+        self.zero_count += other.zero_count;
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+
+        true
+    }
+
+    /// Take the values counted in another sketch back out of this one. Both
+    /// sketches must have the same relative accuracy, otherwise this one is
+    /// left as it is and `false` is returned.
+    ///
+    /// The sum, min and max can't be subtracted exactly, so they're estimated
+    /// from the bins that are left.
+    #[must_use]
+    pub fn subtract(&mut self, other: &Self) -> bool {
+        if self.relative_accuracy != other.relative_accuracy {
+            return false;
+        }
+
+        subtract_bins(&mut self.positive_bins, &other.positive_bins);
+        subtract_bins(&mut self.negative_bins, &other.negative_bins);
+        self.zero_count = self.zero_count.saturating_sub(other.zero_count);
+        self.rebuild_stats();
+
+        true
+    }
+
+    /// Empty the sketch, keeping its relative accuracy.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.relative_accuracy);
+    }
+
+    /// The value at quantile `q` (0 <= q <= 1), or `None` if the sketch is
+    /// empty or `q` is out of range.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+
+        // The rank of the value we're after, counting from zero.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let rank = (q * f64::from(self.count - 1)).round() as u32;
+
+        // The ends are known exactly, so there's no need to estimate them.
+        if rank == 0 {
+            return Some(self.min);
+        } else if rank == self.count - 1 {
+            return Some(self.max);
+        }
+
+        let value = self
+            .iter()
+            .scan(0, |seen, (value, count)| {
+                *seen += count;
+                Some((value, *seen))
+            })
+            .find(|(_, seen)| *seen > rank)
+            .map_or(self.max, |(value, _)| value);
+
+        Some(value.max(self.min).min(self.max))
+    }
+
+    /// The sketch as distribution samples, with one sample per bin holding
+    /// the bin's value at the rate of its count.
+    pub fn samples(&self) -> Vec<Sample> {
+        self.iter()
+            .map(|(value, rate)| Sample { value, rate })
+            .collect()
+    }
+
+    /// Iterate over the bins in order of their values, as pairs of the value
+    /// each stands for and its count.
+    fn iter(&self) -> impl Iterator<Item = (f64, u32)> + '_ {
+        let negative = self
+            .negative_bins
+            .iter()
+            .rev()
+            .map(move |(index, count)| (-self.value(*index), *count));
+        let zero = Some((0.0, self.zero_count)).filter(|(_, count)| *count > 0);
+        let positive = self
+            .positive_bins
+            .iter()
+            .map(move |(index, count)| (self.value(*index), *count));
+
+        negative.chain(zero).chain(positive)
+    }
+
+    fn gamma(&self) -> f64 {
+        (1.0 + self.relative_accuracy) / (1.0 - self.relative_accuracy)
+    }
+
+    /// The index of the bin a positive value goes in.
+    #[allow(clippy::cast_possible_truncation)]
+    fn index(&self, value: f64) -> i32 {
+        (value.ln() / self.gamma().ln()).ceil() as i32
+    }
+
+    /// The value a bin stands for, which is within the relative accuracy of
+    /// every value counted into it.
+    fn value(&self, index: i32) -> f64 {
+        let gamma = self.gamma();
+        2.0 * gamma.powi(index) / (gamma + 1.0)
+    }
+
+    /// Recompute the count, sum, min and max from the bins.
+    fn rebuild_stats(&mut self) {
+        let (count, sum, min, max) = self.iter().fold(
+            (0, 0.0, f64::INFINITY, f64::NEG_INFINITY),
+            |(count, sum, min, max), (value, n)| {
+                (
+                    count + n,
+                    sum + value * f64::from(n),
+                    min.min(value),
+                    max.max(value),
+                )
+            },
+        );
+
+        self.count = count;
+        self.sum = sum;
+        self.min = min;
+        self.max = max;
+    }
+
+    fn bin_count(&self) -> u32 {
+        self.positive_bins.values().sum::<u32>()
+            + self.negative_bins.values().sum::<u32>()
+            + self.zero_count
+    }
+}
+
+impl Default for DDSketch {
+    fn default() -> Self {
+        Self::new(DEFAULT_RELATIVE_ACCURACY)
+    }
+}
+
+impl ByteSizeOf for DDSketch {
+    fn allocated_bytes(&self) -> usize {
+        self.positive_bins.allocated_bytes() + self.negative_bins.allocated_bytes()
+    }
+}
+
+fn subtract_bins(bins: &mut BTreeMap<i32, u32>, other: &BTreeMap<i32, u32>) {
+    for (index, count) in other {
+        if let Some(existing) = bins.get_mut(index) {
+            *existing = existing.saturating_sub(*count);
+            if *existing == 0 {
+                bins.remove(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_within(actual: f64, expected: f64, relative_accuracy: f64) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * relative_accuracy,
+            "{} is not within {} of {}",
+            actual,
+            relative_accuracy,
+            expected
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let sketch = DDSketch::default();
+        assert!(sketch.is_empty());
+        assert_eq!(sketch.quantile(0.5), None);
+        assert_eq!(sketch.min(), None);
+        assert_eq!(sketch.max(), None);
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut sketch = DDSketch::new(0.01);
+        for value in 1..=1000 {
+            sketch.insert(f64::from(value));
+        }
+
+        assert_eq!(sketch.count(), 1000);
+        assert_eq!(sketch.sum(), 500_500.0);
+        assert_eq!(sketch.min(), Some(1.0));
+        assert_eq!(sketch.max(), Some(1000.0));
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(1.0), Some(1000.0));
+        assert_within(sketch.quantile(0.5).unwrap(), 500.0, 0.01);
+        assert_within(sketch.quantile(0.99).unwrap(), 990.0, 0.01);
+        assert_eq!(sketch.quantile(1.5), None);
+    }
+
+    #[test]
+    fn negative_and_zero_values() {
+        let mut sketch = DDSketch::new(0.01);
+        for value in &[-100.0, -10.0, 0.0, 0.0, 10.0, 100.0, f64::NAN] {
+            sketch.insert(*value);
+        }
+
+        assert_eq!(sketch.count(), 6);
+        assert_eq!(sketch.zero_count(), 2);
+        assert_eq!(sketch.quantile(0.0), Some(-100.0));
+        assert_within(sketch.quantile(0.2).unwrap(), -10.0, 0.01);
+        assert_eq!(sketch.quantile(0.5), Some(0.0));
+        assert_within(sketch.quantile(0.8).unwrap(), 10.0, 0.01);
+        assert_eq!(sketch.quantile(1.0), Some(100.0));
+    }
+
+    #[test]
+    fn merge() {
+        let mut low = DDSketch::new(0.02);
+        let mut high = DDSketch::new(0.02);
+        let mut all = DDSketch::new(0.02);
+        for value in 1..=100 {
+            low.insert(f64::from(value));
+            high.insert(f64::from(value + 100));
+            all.insert(f64::from(value));
+            all.insert(f64::from(value + 100));
+        }
+
+        assert!(low.merge(&high));
+        assert_eq!(low, all);
+        assert!(!low.merge(&DDSketch::new(0.01)));
+    }
+
+    #[test]
+    fn subtract() {
+        let mut sketch = DDSketch::new(0.01);
+        sketch.insert_n(1.0, 3);
+        sketch.insert_n(100.0, 2);
+
+        let mut other = DDSketch::new(0.01);
+        other.insert_n(100.0, 2);
+
+        assert!(sketch.subtract(&other));
+        assert_eq!(sketch.count(), 3);
+        assert_within(sketch.max().unwrap(), 1.0, 0.01);
+        assert!(sketch.negative_bins().is_empty());
+        assert_eq!(sketch.positive_bins().len(), 1);
+    }
+
+    #[test]
+    fn from_samples() {
+        let sketch = DDSketch::from_samples(0.01, crate::samples![2.0 => 3, 4.0 => 1]);
+
+        assert_eq!(sketch.count(), 4);
+        assert_eq!(sketch.sum(), 10.0);
+        assert_within(sketch.quantile(0.5).unwrap(), 2.0, 0.01);
+
+        let samples = sketch.samples();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].rate, 3);
+        assert_within(samples[1].value, 4.0, 0.01);
+    }
+}
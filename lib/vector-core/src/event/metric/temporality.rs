@@ -0,0 +1,169 @@
+use super::{Metric, MetricKind, MetricSeries, MetricValue};
+use std::collections::HashMap;
+
+/// Converts metrics to a single temporality: absolute, also known as
+/// cumulative, or incremental, also known as delta.
+///
+/// Converting needs the previous reading of each series, so the converter
+/// keeps the last absolute value of every series it has seen. Incremental
+/// metrics are added onto that value to make them absolute, and absolute
+/// metrics are compared against it to get the change since. Components that
+/// only see a series for a while should `remove` it once done, as the state
+/// otherwise lives as long as the converter.
+#[derive(Clone, Debug)]
+pub struct TemporalityConverter {
+    kind: MetricKind,
+    state: HashMap<MetricSeries, Metric>,
+}
+
+impl TemporalityConverter {
+    /// Create a converter producing metrics of the given `kind`.
+    pub fn new(kind: MetricKind) -> Self {
+        Self {
+            kind,
+            state: HashMap::new(),
+        }
+    }
+
+    /// The kind of the metrics the converter produces.
+    pub fn kind(&self) -> MetricKind {
+        self.kind
+    }
+
+    /// Convert `metric` to the kind of the converter.
+    ///
+    /// Gives `None` for the first absolute reading of a series converted to
+    /// incremental, as there is nothing to take the change against, and
+    /// likewise for a reading whose value type differs from the previous one.
+    /// Either way the reading is kept to convert the next one against.
+    pub fn convert(&mut self, metric: Metric) -> Option<Metric> {
+        match (metric.kind(), self.kind) {
+            (MetricKind::Incremental, MetricKind::Incremental) => Some(metric),
+            (_, MetricKind::Absolute) => Some(self.record(metric).1),
+            (MetricKind::Absolute, MetricKind::Incremental) => {
+                let (previous, current) = self.record(metric);
+                current.delta_since(&previous?)
+            }
+        }
+    }
+
+    /// Turn a reading of a counter into an absolute gauge of the per-second
+    /// rate the counter grew at since the previous reading of its series.
+    /// Incremental counters are added up first.
+    ///
+    /// Gives `None` for the first reading of a series, for readings without a
+    /// timestamp or no newer than the previous one, and for metrics other than
+    /// counters.
+    pub fn rate(&mut self, metric: Metric) -> Option<Metric> {
+        let (previous, current) = self.record(metric);
+        let rate = current.rate_since(&previous?)?;
+        Some(current.with_value(MetricValue::Gauge { value: rate }))
+    }
+
+    /// Forget the state kept for `series`, returning its last absolute value.
+    pub fn remove(&mut self, series: &MetricSeries) -> Option<Metric> {
+        self.state.remove(series)
+    }
+
+    /// The number of series the converter keeps state for.
+    pub fn len(&self) -> usize {
+        self.state.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.is_empty()
+    }
+
+    /// Store the absolute value of `metric` as the latest reading of its
+    /// series, returning the reading it replaced along with the new one.
+    fn record(&mut self, metric: Metric) -> (Option<Metric>, Metric) {
+        let previous = self.state.remove(metric.series());
+        let current = match metric.kind() {
+            MetricKind::Absolute => metric,
+            MetricKind::Incremental => previous
+                .as_ref()
+                .and_then(|previous| {
+                    metric
+                        .clone()
+                        .normalize(MetricKind::Absolute, Some(previous))
+                })
+                // The series changed type, so start over from this metric.
+                .unwrap_or_else(|| metric.into_absolute()),
+        };
+        self.state.insert(current.series().clone(), current.clone());
+        (previous, current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn counter(kind: MetricKind, value: f64, seconds: i64) -> Metric {
+        Metric::new("requests", kind, MetricValue::Counter { value })
+            .with_timestamp(Some(Utc.timestamp(seconds, 0)))
+    }
+
+    #[test]
+    fn absolute_to_incremental() {
+        let mut converter = TemporalityConverter::new(MetricKind::Incremental);
+
+        assert_eq!(
+            converter.convert(counter(MetricKind::Absolute, 10.0, 0)),
+            None
+        );
+        assert_eq!(
+            converter.convert(counter(MetricKind::Absolute, 15.0, 10)),
+            Some(counter(MetricKind::Incremental, 5.0, 10))
+        );
+        // A counter lower than before was reset.
+        assert_eq!(
+            converter.convert(counter(MetricKind::Absolute, 3.0, 20)),
+            Some(counter(MetricKind::Incremental, 3.0, 20))
+        );
+        assert_eq!(converter.len(), 1);
+    }
+
+    #[test]
+    fn incremental_to_absolute() {
+        let mut converter = TemporalityConverter::new(MetricKind::Absolute);
+        let gauge = |kind, value| Metric::new("load", kind, MetricValue::Gauge { value });
+
+        assert_eq!(
+            converter.convert(gauge(MetricKind::Incremental, 2.0)),
+            Some(gauge(MetricKind::Absolute, 2.0))
+        );
+        assert_eq!(
+            converter.convert(gauge(MetricKind::Incremental, -0.5)),
+            Some(gauge(MetricKind::Absolute, 1.5))
+        );
+        assert_eq!(
+            converter.convert(gauge(MetricKind::Absolute, 4.0)),
+            Some(gauge(MetricKind::Absolute, 4.0))
+        );
+        assert_eq!(
+            converter.convert(gauge(MetricKind::Incremental, 1.0)),
+            Some(gauge(MetricKind::Absolute, 5.0))
+        );
+    }
+
+    #[test]
+    fn counter_rate() {
+        let mut converter = TemporalityConverter::new(MetricKind::Absolute);
+
+        assert_eq!(converter.rate(counter(MetricKind::Absolute, 10.0, 0)), None);
+        assert_eq!(
+            converter
+                .rate(counter(MetricKind::Absolute, 30.0, 10))
+                .map(|metric| metric.value().clone()),
+            Some(MetricValue::Gauge { value: 2.0 })
+        );
+        assert_eq!(
+            converter
+                .rate(counter(MetricKind::Incremental, 5.0, 15))
+                .map(|metric| metric.value().clone()),
+            Some(MetricValue::Gauge { value: 1.0 })
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::ByteSizeOf;
+pub use batch::EventBatch;
 use buffers::bytes::{DecodeBytes, EncodeBytes};
 use bytes::{Buf, BufMut, Bytes};
 use chrono::{DateTime, SecondsFormat, Utc};
@@ -6,24 +7,28 @@ pub use finalization::{
     BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizer, EventFinalizers, EventStatus,
 };
 pub use legacy_lookup::Lookup;
-pub use log_event::LogEvent;
-pub use metadata::{EventMetadata, WithMetadata};
+pub use log_event::{set_size_accounting, LogEvent, SizeAccounting};
+pub use metadata::{monotonic_now, DropReason, EventMetadata, WithMetadata};
 pub use metric::{Metric, MetricKind, MetricValue, StatisticKind};
-use prost::{DecodeError, EncodeError, Message};
+use prost::{EncodeError, Message};
+pub use secrets::Secrets;
 use shared::EventDataEq;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::sync::Arc;
+pub use trace::TraceEvent;
 pub use util::log::PathComponent;
 pub use util::log::PathIter;
 pub use value::Value;
 #[cfg(feature = "vrl")]
 pub use vrl_target::VrlTarget;
 
+pub mod batch;
 pub mod discriminant;
 pub mod error;
 mod finalization;
+pub mod intern;
 mod legacy_lookup;
 mod log_event;
 #[cfg(feature = "lua")]
@@ -31,9 +36,12 @@ pub mod lua;
 pub mod merge_state;
 mod metadata;
 pub mod metric;
+pub mod otlp;
 pub mod proto;
+mod secrets;
 #[cfg(test)]
 mod test;
+pub mod trace;
 pub mod util;
 mod value;
 #[cfg(feature = "vrl")]
@@ -45,6 +53,7 @@ pub const PARTIAL: &str = "_partial";
 pub enum Event {
     Log(LogEvent),
     Metric(Metric),
+    Trace(TraceEvent),
 }
 
 impl ByteSizeOf for Event {
@@ -52,6 +61,7 @@ impl ByteSizeOf for Event {
         match self {
             Event::Log(log_event) => log_event.allocated_bytes(),
             Event::Metric(metric_event) => metric_event.allocated_bytes(),
+            Event::Trace(trace_event) => trace_event.allocated_bytes(),
         }
     }
 }
@@ -134,10 +144,47 @@ impl Event {
         }
     }
 
+    /// Return self as a `TraceEvent`
+    ///
+    /// # Panics
+    ///
+    /// This function panics if self is anything other than an `Event::Trace`.
+    pub fn as_trace(&self) -> &TraceEvent {
+        match self {
+            Event::Trace(trace) => trace,
+            _ => panic!("Failed type coercion, {:?} is not a trace event", self),
+        }
+    }
+
+    /// Return self as a mutable `TraceEvent`
+    ///
+    /// # Panics
+    ///
+    /// This function panics if self is anything other than an `Event::Trace`.
+    pub fn as_mut_trace(&mut self) -> &mut TraceEvent {
+        match self {
+            Event::Trace(trace) => trace,
+            _ => panic!("Failed type coercion, {:?} is not a trace event", self),
+        }
+    }
+
+    /// Coerces self into a `TraceEvent`
+    ///
+    /// # Panics
+    ///
+    /// This function panics if self is anything other than an `Event::Trace`.
+    pub fn into_trace(self) -> TraceEvent {
+        match self {
+            Event::Trace(trace) => trace,
+            _ => panic!("Failed type coercion, {:?} is not a trace event", self),
+        }
+    }
+
     pub fn metadata(&self) -> &EventMetadata {
         match self {
             Self::Log(log) => log.metadata(),
             Self::Metric(metric) => metric.metadata(),
+            Self::Trace(trace) => trace.metadata(),
         }
     }
 
@@ -145,6 +192,7 @@ impl Event {
         match self {
             Self::Log(log) => log.metadata_mut(),
             Self::Metric(metric) => metric.metadata_mut(),
+            Self::Trace(trace) => trace.metadata_mut(),
         }
     }
 
@@ -153,6 +201,7 @@ impl Event {
         match self {
             Self::Log(log) => log.into_parts().1,
             Self::Metric(metric) => metric.into_parts().2,
+            Self::Trace(trace) => trace.into_parts().1,
         }
     }
 
@@ -161,6 +210,7 @@ impl Event {
         match self {
             Self::Log(log) => log.add_finalizer(finalizer),
             Self::Metric(metric) => metric.add_finalizer(finalizer),
+            Self::Trace(trace) => trace.add_finalizer(finalizer),
         }
     }
 
@@ -168,6 +218,7 @@ impl Event {
         match self {
             Self::Log(log) => log.with_batch_notifier(batch).into(),
             Self::Metric(metric) => metric.with_batch_notifier(batch).into(),
+            Self::Trace(trace) => trace.with_batch_notifier(batch).into(),
         }
     }
 }
@@ -177,6 +228,7 @@ impl EventDataEq for Event {
         match (self, other) {
             (Self::Log(a), Self::Log(b)) => a.event_data_eq(b),
             (Self::Metric(a), Self::Metric(b)) => a.event_data_eq(b),
+            (Self::Trace(a), Self::Trace(b)) => a.event_data_eq(b),
             _ => false,
         }
     }
@@ -223,6 +275,7 @@ impl TryInto<serde_json::Value> for Event {
         match self {
             Event::Log(fields) => serde_json::to_value(fields),
             Event::Metric(metric) => serde_json::to_value(metric),
+            Event::Trace(trace) => serde_json::to_value(trace),
         }
     }
 }
@@ -320,12 +373,19 @@ impl From<Metric> for Event {
     }
 }
 
+impl From<TraceEvent> for Event {
+    fn from(trace: TraceEvent) -> Self {
+        Event::Trace(trace)
+    }
+}
+
 /// A wrapper for references to inner event types, where reconstituting
 /// a full `Event` from a `LogEvent` or `Metric` might be inconvenient.
 #[derive(Clone, Copy, Debug)]
 pub enum EventRef<'a> {
     Log(&'a LogEvent),
     Metric(&'a Metric),
+    Trace(&'a TraceEvent),
 }
 
 impl<'a> From<&'a Event> for EventRef<'a> {
@@ -333,6 +393,7 @@ impl<'a> From<&'a Event> for EventRef<'a> {
         match event {
             Event::Log(log) => log.into(),
             Event::Metric(metric) => metric.into(),
+            Event::Trace(trace) => trace.into(),
         }
     }
 }
@@ -349,6 +410,12 @@ impl<'a> From<&'a Metric> for EventRef<'a> {
     }
 }
 
+impl<'a> From<&'a TraceEvent> for EventRef<'a> {
+    fn from(trace: &'a TraceEvent) -> Self {
+        Self::Trace(trace)
+    }
+}
+
 impl EncodeBytes<Event> for Event {
     type Error = EncodeError;
 
@@ -356,17 +423,17 @@ impl EncodeBytes<Event> for Event {
     where
         B: BufMut,
     {
-        proto::EventWrapper::from(self).encode(buffer)
+        proto::EncodedEventWrapper::from(self).encode(buffer)
     }
 }
 
 impl DecodeBytes<Event> for Event {
-    type Error = DecodeError;
+    type Error = proto::DecodeError;
 
     fn decode<B>(buffer: B) -> Result<Event, Self::Error>
     where
         B: Buf,
     {
-        proto::EventWrapper::decode(buffer).map(|wrp| wrp.into())
+        proto::EncodedEventWrapper::decode(buffer)?.try_into()
     }
 }
@@ -0,0 +1,263 @@
+//! Mapping between log events and the OTLP log data model.
+//!
+//! [`LogRecord`] mirrors the OTLP `LogRecord` message, with `AnyValue`s held as
+//! [`Value`]s, so that components speaking OTLP only have to convert between
+//! the protobuf types and this model, and all of them lay the record out in a
+//! log event the same way:
+//!
+//! * the body becomes the message field and the time the timestamp field, as
+//!   named by the log schema,
+//! * attributes are kept apart from the rest of the event, under
+//!   [`ATTRIBUTES`],
+//! * the remaining record fields are stored under the field of the same name,
+//!   with trace and span IDs as lowercase hex strings.
+//!
+//! Converting a record to an event and back gives the same record. Converting
+//! an event that didn't come from a record moves its other top-level fields
+//! into the attributes, as OTLP has nowhere else to put them.
+
+use super::{LogEvent, Value};
+use crate::config::log_schema;
+use chrono::{DateTime, TimeZone, Utc};
+use std::{collections::BTreeMap, convert::TryFrom};
+
+pub const ATTRIBUTES: &str = "attributes";
+pub const SEVERITY_NUMBER: &str = "severity_number";
+pub const SEVERITY_TEXT: &str = "severity_text";
+pub const NAME: &str = "name";
+pub const TRACE_ID: &str = "trace_id";
+pub const SPAN_ID: &str = "span_id";
+pub const FLAGS: &str = "flags";
+pub const DROPPED_ATTRIBUTES_COUNT: &str = "dropped_attributes_count";
+
+/// An OTLP log record. Zero and empty values mean the field is unset, as in
+/// protobuf.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogRecord {
+    pub time_unix_nano: u64,
+    pub severity_number: i32,
+    pub severity_text: String,
+    pub name: String,
+    pub body: Option<Value>,
+    pub attributes: BTreeMap<String, Value>,
+    pub dropped_attributes_count: u32,
+    pub flags: u32,
+    /// 16 bytes when set.
+    pub trace_id: Vec<u8>,
+    /// 8 bytes when set.
+    pub span_id: Vec<u8>,
+}
+
+/// The name of the range an OTLP severity number falls in, such as `WARN`
+/// for 13 to 16, or `None` if the severity is unspecified or out of range.
+pub const fn severity_name(severity_number: i32) -> Option<&'static str> {
+    match severity_number {
+        1..=4 => Some("TRACE"),
+        5..=8 => Some("DEBUG"),
+        9..=12 => Some("INFO"),
+        13..=16 => Some("WARN"),
+        17..=20 => Some("ERROR"),
+        21..=24 => Some("FATAL"),
+        _ => None,
+    }
+}
+
+/// The OTLP severity number for a textual log level, such as `warning`, or
+/// `None` if the level isn't recognized. Levels map onto the lowest number of
+/// their range.
+pub fn severity_number(level: &str) -> Option<i32> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" => Some(1),
+        "debug" => Some(5),
+        "info" | "informational" | "notice" => Some(9),
+        "warn" | "warning" => Some(13),
+        "error" | "err" => Some(17),
+        "fatal" | "critical" | "crit" | "alert" | "emergency" | "emerg" => Some(21),
+        _ => None,
+    }
+}
+
+impl From<LogRecord> for LogEvent {
+    fn from(record: LogRecord) -> Self {
+        let mut log = LogEvent::default();
+
+        if let Some(body) = record.body {
+            log.insert(log_schema().message_key(), body);
+        }
+        if let Some(timestamp) = timestamp_from_nanos(record.time_unix_nano) {
+            log.insert(log_schema().timestamp_key(), timestamp);
+        }
+        if !record.attributes.is_empty() {
+            log.insert_flat(ATTRIBUTES, record.attributes);
+        }
+        if record.severity_number != 0 {
+            log.insert_flat(SEVERITY_NUMBER, record.severity_number);
+        }
+        if !record.severity_text.is_empty() {
+            log.insert_flat(SEVERITY_TEXT, record.severity_text);
+        }
+        if !record.name.is_empty() {
+            log.insert_flat(NAME, record.name);
+        }
+        if !record.trace_id.is_empty() {
+            log.insert_flat(TRACE_ID, encode_hex(&record.trace_id));
+        }
+        if !record.span_id.is_empty() {
+            log.insert_flat(SPAN_ID, encode_hex(&record.span_id));
+        }
+        if record.flags != 0 {
+            log.insert_flat(FLAGS, record.flags);
+        }
+        if record.dropped_attributes_count != 0 {
+            log.insert_flat(DROPPED_ATTRIBUTES_COUNT, record.dropped_attributes_count);
+        }
+
+        log
+    }
+}
+
+impl From<LogEvent> for LogRecord {
+    fn from(mut log: LogEvent) -> Self {
+        let body = log.remove(log_schema().message_key());
+        let time_unix_nano = match log.remove(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => timestamp_to_nanos(timestamp),
+            _ => 0,
+        };
+
+        let mut fields = log.into_parts().0;
+        let mut attributes = match fields.remove(ATTRIBUTES) {
+            Some(Value::Map(attributes)) => attributes,
+            _ => BTreeMap::new(),
+        };
+        let record = Self {
+            time_unix_nano,
+            severity_number: take_integer(&mut fields, SEVERITY_NUMBER),
+            severity_text: take_string(&mut fields, SEVERITY_TEXT),
+            name: take_string(&mut fields, NAME),
+            body,
+            attributes: BTreeMap::new(),
+            dropped_attributes_count: take_integer(&mut fields, DROPPED_ATTRIBUTES_COUNT),
+            flags: take_integer(&mut fields, FLAGS),
+            trace_id: decode_hex(&take_string(&mut fields, TRACE_ID)).unwrap_or_default(),
+            span_id: decode_hex(&take_string(&mut fields, SPAN_ID)).unwrap_or_default(),
+        };
+
+        // Anything left over has no place of its own in the record.
+        for (key, value) in fields {
+            attributes.entry(key).or_insert(value);
+        }
+
+        Self {
+            attributes,
+            ..record
+        }
+    }
+}
+
+fn take_integer<T: TryFrom<i64> + Default>(fields: &mut BTreeMap<String, Value>, key: &str) -> T {
+    match fields.remove(key) {
+        Some(Value::Integer(i)) => T::try_from(i).unwrap_or_default(),
+        _ => T::default(),
+    }
+}
+
+fn take_string(fields: &mut BTreeMap<String, Value>, key: &str) -> String {
+    match fields.remove(key) {
+        Some(Value::Bytes(bytes) | Value::String(bytes)) => {
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+/// The time `nanos` nanoseconds after the Unix epoch, or `None` for zero,
+/// which OTLP uses for an unset time.
+pub fn timestamp_from_nanos(nanos: u64) -> Option<DateTime<Utc>> {
+    if nanos == 0 {
+        return None;
+    }
+    let seconds = i64::try_from(nanos / 1_000_000_000).ok()?;
+    #[allow(clippy::cast_possible_truncation)] // less than a billion
+    let nanos = (nanos % 1_000_000_000) as u32;
+    Utc.timestamp_opt(seconds, nanos).single()
+}
+
+/// The nanoseconds from the Unix epoch to `timestamp`, or zero, for unset, if
+/// it is before the epoch or too far after it.
+pub fn timestamp_to_nanos(timestamp: DateTime<Utc>) -> u64 {
+    u64::try_from(timestamp.timestamp_nanos()).unwrap_or(0)
+}
+
+/// Format an ID, such as a trace or span ID, as lowercase hex.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Parse an ID formatted by [`encode_hex`], or `None` if it isn't valid hex.
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record() -> LogRecord {
+        let mut attributes = BTreeMap::new();
+        attributes.insert("http.method".to_owned(), Value::from("GET"));
+        attributes.insert("retries".to_owned(), Value::from(2));
+
+        LogRecord {
+            time_unix_nano: 1_628_000_000_123_456_789,
+            severity_number: 13,
+            severity_text: "Warning".to_owned(),
+            name: "request".to_owned(),
+            body: Some(Value::from("slow request")),
+            attributes,
+            dropped_attributes_count: 1,
+            flags: 1,
+            trace_id: (0..16).collect(),
+            span_id: (0..8).collect(),
+        }
+    }
+
+    #[test]
+    fn record_through_event() {
+        let log = LogEvent::from(record());
+
+        assert_eq!(log[log_schema().message_key()], "slow request".into());
+        assert_eq!(log["attributes.retries"], 2.into());
+        assert_eq!(log[SEVERITY_NUMBER], 13.into());
+        assert_eq!(log[SPAN_ID], "0001020304050607".into());
+        assert_eq!(LogRecord::from(log), record());
+    }
+
+    #[test]
+    fn event_fields_become_attributes() {
+        let mut log = LogEvent::from("message");
+        log.insert("host", "example.com");
+        log.insert(SEVERITY_NUMBER, 9);
+
+        let record = LogRecord::from(log);
+
+        assert_eq!(record.body, Some(Value::from("message")));
+        assert_ne!(record.time_unix_nano, 0);
+        assert_eq!(record.severity_number, 9);
+        assert_eq!(record.attributes["host"], Value::from("example.com"));
+        assert_eq!(record.attributes.len(), 1);
+    }
+
+    #[test]
+    fn severities() {
+        assert_eq!(severity_number("WARNING"), Some(13));
+        assert_eq!(severity_name(14), Some("WARN"));
+        assert_eq!(severity_name(0), None);
+        assert_eq!(severity_number("verbose"), None);
+    }
+}
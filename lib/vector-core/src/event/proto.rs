@@ -1,14 +1,201 @@
 use crate::event::{self, BTreeMap, WithMetadata};
+use bytes::{Bytes, BytesMut};
 use chrono::TimeZone;
+use prost::Message;
+use snafu::Snafu;
+use std::convert::TryFrom;
 
 include!(concat!(env!("OUT_DIR"), "/event.rs"));
 pub use event_wrapper::Event;
 pub use metric::Value as MetricValue;
 
+/// The version of the event schema this build encodes events with.
+///
+/// Bump this whenever a kind of event or metric value is added, and teach
+/// [`downgrade`] how to turn the new kind into something older versions
+/// understand, so mixed-version deployments can keep talking to each other.
+///
+/// * `0`: logs and metrics.
+/// * `1`: traces, sketches and event metadata.
+/// * `2`: binary values, told apart from text.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Errors raised while turning decoded protobuf messages back into events.
+#[derive(Debug, Snafu)]
+pub enum DecodeError {
+    #[snafu(display("invalid protobuf message: {}", source))]
+    Protobuf { source: prost::DecodeError },
+    #[snafu(display("event wrapper holds no event"))]
+    MissingEvent,
+    #[snafu(display("metric {:?} has no value", name))]
+    MissingMetricValue { name: String },
+}
+
+impl From<prost::DecodeError> for DecodeError {
+    fn from(source: prost::DecodeError) -> Self {
+        Self::Protobuf { source }
+    }
+}
+
 impl From<Event> for EventWrapper {
     fn from(event: Event) -> Self {
-        Self { event: Some(event) }
+        Self {
+            event: Some(event),
+            version: SCHEMA_VERSION,
+        }
+    }
+}
+
+/// Rewrite an event into something a peer on schema `version` can decode,
+/// or `None` if it has no equivalent there.
+pub fn downgrade(event: event::Event, version: u32) -> Option<event::Event> {
+    if version >= 2 {
+        return Some(event);
+    }
+
+    // Older versions keep all bytes, text or not, as `raw_bytes`.
+    let event = match event {
+        event::Event::Log(log) => {
+            let (fields, metadata) = log.into_parts();
+            event::Event::Log(event::LogEvent::from_parts(
+                binary_as_text(fields),
+                metadata,
+            ))
+        }
+        event::Event::Trace(trace) => {
+            let (fields, metadata) = trace.into_parts();
+            event::Event::Trace(event::TraceEvent::from_parts(
+                binary_as_text(fields),
+                metadata,
+            ))
+        }
+        event => event,
+    };
+
+    if version >= 1 {
+        return Some(event);
     }
+
+    match event {
+        event::Event::Trace(_) => None,
+        event::Event::Metric(metric) => Some(event::Event::Metric(match metric.value() {
+            event::MetricValue::Sketch { sketch } => {
+                let samples = sketch.samples();
+                metric.with_value(event::MetricValue::Distribution {
+                    samples,
+                    statistic: event::StatisticKind::Summary,
+                })
+            }
+            _ => metric,
+        })),
+        event => Some(event),
+    }
+}
+
+fn binary_as_text(fields: BTreeMap<String, event::Value>) -> BTreeMap<String, event::Value> {
+    fn convert(value: event::Value) -> event::Value {
+        match value {
+            event::Value::Bytes(bytes) => event::Value::String(bytes),
+            event::Value::Map(fields) => event::Value::Map(binary_as_text(fields)),
+            event::Value::Array(items) => {
+                event::Value::Array(items.into_iter().map(convert).collect())
+            }
+            value => value,
+        }
+    }
+
+    fields
+        .into_iter()
+        .map(|(key, value)| (key, convert(value)))
+        .collect()
+}
+
+impl From<event::Event> for EncodedEventWrapper {
+    fn from(event: event::Event) -> Self {
+        let event = match event {
+            event::Event::Log(log) => encoded_event_wrapper::Event::Log(log.into()),
+            event::Event::Metric(metric) => encoded_event_wrapper::Event::Metric(metric.into()),
+            event::Event::Trace(trace) => {
+                encoded_event_wrapper::Event::Trace(event::LogEvent::from(trace).into())
+            }
+        };
+        Self {
+            event: Some(event),
+            version: SCHEMA_VERSION,
+        }
+    }
+}
+
+impl TryFrom<EncodedEventWrapper> for event::Event {
+    type Error = DecodeError;
+
+    fn try_from(proto: EncodedEventWrapper) -> Result<Self, Self::Error> {
+        Ok(match proto.event.ok_or(DecodeError::MissingEvent)? {
+            encoded_event_wrapper::Event::Log(proto) => Self::Log(proto.into()),
+            encoded_event_wrapper::Event::Metric(proto) => {
+                Self::Metric(event::Metric::try_from(proto)?)
+            }
+            encoded_event_wrapper::Event::Trace(proto) => {
+                Self::Trace(event::LogEvent::from(proto).into())
+            }
+        })
+    }
+}
+
+impl From<EncodedLog> for event::LogEvent {
+    fn from(log: EncodedLog) -> Self {
+        let metadata = log.metadata.map(Into::into).unwrap_or_default();
+
+        Self::from_encoded(log.fields, metadata)
+    }
+}
+
+impl From<event::LogEvent> for EncodedLog {
+    fn from(log_event: event::LogEvent) -> Self {
+        let (fields, metadata) = log_event.into_encoded_parts();
+
+        Self {
+            fields,
+            metadata: Some((&metadata).into()),
+        }
+    }
+}
+
+/// Decode the encoded entries of a map of fields, leaving out the entries
+/// that can't be decoded.
+pub(crate) fn decode_fields(entries: &[Bytes]) -> BTreeMap<String, event::Value> {
+    entries
+        .iter()
+        .filter_map(|entry| match FieldEntry::decode(entry.clone()) {
+            Ok(entry) => {
+                decode_value(entry.value.unwrap_or_default()).map(|value| (entry.key, value))
+            }
+            Err(error) => {
+                error!(message = "Encoded event contains an invalid field.", %error);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Encode each of the fields as an entry of a map, all sharing one buffer.
+pub(crate) fn encode_fields(fields: BTreeMap<String, event::Value>) -> Vec<Bytes> {
+    let entries = fields
+        .into_iter()
+        .map(|(key, value)| FieldEntry {
+            key,
+            value: Some(encode_value(value)),
+        })
+        .collect::<Vec<_>>();
+
+    let mut buffer = BytesMut::with_capacity(entries.iter().map(Message::encoded_len).sum());
+    entries
+        .iter()
+        .map(|entry| {
+            entry.encode(&mut buffer).expect("Out of memory");
+            buffer.split().freeze()
+        })
+        .collect()
 }
 
 impl From<Log> for Event {
@@ -23,6 +210,12 @@ impl From<Metric> for Event {
     }
 }
 
+impl From<Trace> for Event {
+    fn from(trace: Trace) -> Self {
+        Self::Trace(trace)
+    }
+}
+
 impl From<Log> for event::LogEvent {
     fn from(log: Log) -> Self {
         let fields = log
@@ -31,12 +224,72 @@ impl From<Log> for event::LogEvent {
             .filter_map(|(k, v)| decode_value(v).map(|value| (k, value)))
             .collect::<BTreeMap<_, _>>();
 
-        Self::from(fields)
+        let metadata = log.metadata.map(Into::into).unwrap_or_default();
+
+        Self::from_parts(fields, metadata)
     }
 }
 
-impl From<Metric> for event::Metric {
-    fn from(metric: Metric) -> Self {
+impl From<Trace> for event::TraceEvent {
+    fn from(trace: Trace) -> Self {
+        let fields = trace
+            .fields
+            .into_iter()
+            .filter_map(|(k, v)| decode_value(v).map(|value| (k, value)))
+            .collect::<BTreeMap<_, _>>();
+
+        let metadata = trace.metadata.map(Into::into).unwrap_or_default();
+
+        Self::from_parts(fields, metadata)
+    }
+}
+
+impl From<Metadata> for event::EventMetadata {
+    fn from(metadata: Metadata) -> Self {
+        let mut event_metadata = Self::default();
+
+        if !metadata.datadog_api_key.is_empty() {
+            event_metadata
+                .set_datadog_api_key(Some(event::intern::intern(&metadata.datadog_api_key)));
+        }
+        event_metadata.set_ingest_timestamp(metadata.ingest_timestamp.and_then(decode_timestamp));
+        event_metadata.set_lineage(
+            metadata
+                .lineage
+                .iter()
+                .map(String::as_str)
+                .map(event::intern::intern)
+                .collect(),
+        );
+        event_metadata.set_upstream_awaits_delivery(metadata.awaits_delivery);
+
+        event_metadata
+    }
+}
+
+// Finalizers are left out, as they only mean something to the process the
+// event was received in, and only whether there were any is kept. Secrets are
+// left out so credentials never leave the process through the `vector` sink
+// or a disk buffer.
+impl From<&event::EventMetadata> for Metadata {
+    fn from(metadata: &event::EventMetadata) -> Self {
+        Self {
+            datadog_api_key: metadata
+                .datadog_api_key()
+                .as_deref()
+                .map(ToOwned::to_owned)
+                .unwrap_or_default(),
+            ingest_timestamp: metadata.ingest_timestamp().map(encode_timestamp),
+            lineage: metadata.lineage().iter().map(ToString::to_string).collect(),
+            awaits_delivery: metadata.awaits_delivery(),
+        }
+    }
+}
+
+impl TryFrom<Metric> for event::Metric {
+    type Error = DecodeError;
+
+    fn try_from(metric: Metric) -> Result<Self, Self::Error> {
         let kind = match metric.kind() {
             metric::Kind::Incremental => event::MetricKind::Incremental,
             metric::Kind::Absolute => event::MetricKind::Absolute,
@@ -50,9 +303,7 @@ impl From<Metric> for event::Metric {
             Some(metric.namespace)
         };
 
-        let timestamp = metric
-            .timestamp
-            .map(|ts| chrono::Utc.timestamp(ts.seconds, ts.nanos as u32));
+        let timestamp = metric.timestamp.and_then(decode_timestamp);
 
         let tags = if metric.tags.is_empty() {
             None
@@ -60,7 +311,10 @@ impl From<Metric> for event::Metric {
             Some(metric.tags)
         };
 
-        let value = match metric.value.unwrap() {
+        let value = match metric
+            .value
+            .ok_or_else(|| DecodeError::MissingMetricValue { name: name.clone() })?
+        {
             MetricValue::Counter(counter) => event::MetricValue::Counter {
                 value: counter.value,
             },
@@ -96,23 +350,38 @@ impl From<Metric> for event::Metric {
                 count: summary.count,
                 sum: summary.sum,
             },
+            MetricValue::Sketch(sketch) => event::MetricValue::Sketch {
+                sketch: event::metric::DDSketch::from_parts(
+                    sketch.relative_accuracy,
+                    sketch.positive_bins,
+                    sketch.negative_bins,
+                    sketch.zero_count,
+                    sketch.count,
+                    sketch.sum,
+                    sketch.min,
+                    sketch.max,
+                ),
+            },
         };
 
-        Self::new(name, kind, value)
+        let metadata = metric.metadata.map(Into::into).unwrap_or_default();
+
+        Ok(Self::new_with_metadata(name, kind, value, metadata)
             .with_namespace(namespace)
             .with_tags(tags)
-            .with_timestamp(timestamp)
+            .with_timestamp(timestamp))
     }
 }
 
-impl From<EventWrapper> for event::Event {
-    fn from(proto: EventWrapper) -> Self {
-        let event = proto.event.unwrap();
+impl TryFrom<EventWrapper> for event::Event {
+    type Error = DecodeError;
 
-        match event {
+    fn try_from(proto: EventWrapper) -> Result<Self, Self::Error> {
+        Ok(match proto.event.ok_or(DecodeError::MissingEvent)? {
             Event::Log(proto) => Self::Log(proto.into()),
-            Event::Metric(proto) => Self::Metric(proto.into()),
-        }
+            Event::Metric(proto) => Self::Metric(event::Metric::try_from(proto)?),
+            Event::Trace(proto) => Self::Trace(proto.into()),
+        })
     }
 }
 
@@ -130,7 +399,32 @@ impl From<event::LogEvent> for WithMetadata<Log> {
             .map(|(k, v)| (k, encode_value(v)))
             .collect::<BTreeMap<_, _>>();
 
-        let data = Log { fields };
+        let data = Log {
+            fields,
+            metadata: Some((&metadata).into()),
+        };
+        Self { data, metadata }
+    }
+}
+
+impl From<event::TraceEvent> for Trace {
+    fn from(trace: event::TraceEvent) -> Self {
+        WithMetadata::<Self>::from(trace).data
+    }
+}
+
+impl From<event::TraceEvent> for WithMetadata<Trace> {
+    fn from(trace: event::TraceEvent) -> Self {
+        let (fields, metadata) = trace.into_parts();
+        let fields = fields
+            .into_iter()
+            .map(|(k, v)| (k, encode_value(v)))
+            .collect::<BTreeMap<_, _>>();
+
+        let data = Trace {
+            fields,
+            metadata: Some((&metadata).into()),
+        };
         Self { data, metadata }
     }
 }
@@ -143,16 +437,12 @@ impl From<event::Metric> for Metric {
 
 impl From<event::Metric> for WithMetadata<Metric> {
     fn from(metric: event::Metric) -> Self {
-        let (series, data, metadata) = metric.into_parts();
+        let (mut series, data, metadata) = metric.into_parts();
+        let tags = series.take_tags().unwrap_or_default();
         let name = series.name.name;
         let namespace = series.name.namespace.unwrap_or_default();
 
-        let timestamp = data.timestamp.map(|ts| prost_types::Timestamp {
-            seconds: ts.timestamp(),
-            nanos: ts.timestamp_subsec_nanos() as i32,
-        });
-
-        let tags = series.tags.unwrap_or_default();
+        let timestamp = data.timestamp.map(encode_timestamp);
 
         let kind = match data.kind {
             event::MetricKind::Incremental => metric::Kind::Incremental,
@@ -194,6 +484,16 @@ impl From<event::Metric> for WithMetadata<Metric> {
                 count,
                 sum,
             }),
+            event::MetricValue::Sketch { sketch } => MetricValue::Sketch(Sketch {
+                relative_accuracy: sketch.relative_accuracy(),
+                positive_bins: sketch.positive_bins().clone(),
+                negative_bins: sketch.negative_bins().clone(),
+                zero_count: sketch.zero_count(),
+                count: sketch.count(),
+                sum: sketch.sum(),
+                min: sketch.min().unwrap_or_default(),
+                max: sketch.max().unwrap_or_default(),
+            }),
         };
 
         let data = Metric {
@@ -203,6 +503,7 @@ impl From<event::Metric> for WithMetadata<Metric> {
             tags,
             kind,
             value: Some(metric),
+            metadata: Some((&metadata).into()),
         };
         Self { data, metadata }
     }
@@ -219,6 +520,7 @@ impl From<event::Event> for WithMetadata<Event> {
         match event {
             event::Event::Log(log_event) => WithMetadata::<Log>::from(log_event).into(),
             event::Event::Metric(metric) => WithMetadata::<Metric>::from(metric).into(),
+            event::Event::Trace(trace) => WithMetadata::<Trace>::from(trace).into(),
         }
     }
 }
@@ -235,12 +537,46 @@ impl From<event::Event> for WithMetadata<EventWrapper> {
     }
 }
 
+/// Decode a timestamp down to the nanosecond, or `None` if it's out of the
+/// range `DateTime` can represent or its nanoseconds are out of bounds.
+fn decode_timestamp(ts: prost_types::Timestamp) -> Option<chrono::DateTime<chrono::Utc>> {
+    if !(0..NANOS_PER_SECOND).contains(&ts.nanos) {
+        error!(
+            message = "Encoded timestamp has invalid nanoseconds.",
+            nanos = ts.nanos
+        );
+        return None;
+    }
+
+    let timestamp = chrono::Utc
+        .timestamp_opt(ts.seconds, ts.nanos as u32)
+        .single();
+    if timestamp.is_none() {
+        error!(
+            message = "Encoded timestamp is out of range.",
+            seconds = ts.seconds
+        );
+    }
+    timestamp
+}
+
+fn encode_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        // Leap seconds are represented by `chrono` as nanoseconds past the
+        // end of the second, which protobuf timestamps can't hold.
+        nanos: (ts.timestamp_subsec_nanos() as i32).min(NANOS_PER_SECOND - 1),
+    }
+}
+
+const NANOS_PER_SECOND: i32 = 1_000_000_000;
+
 fn decode_value(input: Value) -> Option<event::Value> {
     match input.kind {
-        Some(value::Kind::RawBytes(data)) => Some(event::Value::Bytes(data.into())),
-        Some(value::Kind::Timestamp(ts)) => Some(event::Value::Timestamp(
-            chrono::Utc.timestamp(ts.seconds, ts.nanos as u32),
-        )),
+        // Older versions sent binary data as `raw_bytes` too.
+        Some(value::Kind::RawBytes(data)) => Some(event::Value::from(data)),
+        Some(value::Kind::Binary(data)) => Some(event::Value::Bytes(data)),
+        Some(value::Kind::Timestamp(ts)) => decode_timestamp(ts).map(event::Value::Timestamp),
         Some(value::Kind::Integer(value)) => Some(event::Value::Integer(value)),
         Some(value::Kind::Float(value)) => Some(event::Value::Float(value)),
         Some(value::Kind::Boolean(value)) => Some(event::Value::Boolean(value)),
@@ -281,11 +617,9 @@ fn decode_array(items: Vec<Value>) -> Option<event::Value> {
 fn encode_value(value: event::Value) -> Value {
     Value {
         kind: match value {
-            event::Value::Bytes(b) => Some(value::Kind::RawBytes(b.to_vec())),
-            event::Value::Timestamp(ts) => Some(value::Kind::Timestamp(prost_types::Timestamp {
-                seconds: ts.timestamp(),
-                nanos: ts.timestamp_subsec_nanos() as i32,
-            })),
+            event::Value::String(b) => Some(value::Kind::RawBytes(b)),
+            event::Value::Bytes(b) => Some(value::Kind::Binary(b)),
+            event::Value::Timestamp(ts) => Some(value::Kind::Timestamp(encode_timestamp(ts))),
             event::Value::Integer(value) => Some(value::Kind::Integer(value)),
             event::Value::Float(value) => Some(value::Kind::Float(value)),
             event::Value::Boolean(value) => Some(value::Kind::Boolean(value)),
@@ -0,0 +1,104 @@
+#![deny(missing_docs)]
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+const REDACTED: &str = "<redacted>";
+
+/// Credentials attached to an event by the component that received it, such
+/// as the token a client authenticated with.
+///
+/// Secrets travel with the event through the topology but are never encoded:
+/// the type implements neither `Serialize` nor a revealing `Debug`, and the
+/// protobuf encoding used by the `vector` sink and disk buffers leaves them
+/// out. A sink that needs one has to ask for it by name with [`Secrets::get`].
+#[derive(Clone, Default, PartialEq, PartialOrd)]
+pub struct Secrets(BTreeMap<String, Arc<str>>);
+
+impl Secrets {
+    /// Create an empty set of secrets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up the secret stored under `key`.
+    pub fn get(&self, key: &str) -> Option<&Arc<str>> {
+        self.0.get(key)
+    }
+
+    /// Store `secret` under `key`, returning the secret it replaced, if any.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        secret: impl Into<Arc<str>>,
+    ) -> Option<Arc<str>> {
+        self.0.insert(key.into(), secret.into())
+    }
+
+    /// Remove the secret stored under `key`, returning it.
+    pub fn remove(&mut self, key: &str) -> Option<Arc<str>> {
+        self.0.remove(key)
+    }
+
+    /// The names the secrets are stored under, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// The number of secrets stored.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no secrets are stored.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Add the secrets of `other` whose keys aren't already present.
+    pub fn merge(&mut self, other: Self) {
+        for (key, secret) in other.0 {
+            self.0.entry(key).or_insert(secret);
+        }
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.keys().map(|key| (key, REDACTED)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_values() {
+        let mut secrets = Secrets::new();
+        secrets.insert("splunk_hec_token", "hunter2");
+
+        let debug = format!("{:?}", secrets);
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("splunk_hec_token"));
+        assert_eq!(
+            secrets.get("splunk_hec_token").map(|secret| &**secret),
+            Some("hunter2")
+        );
+    }
+
+    #[test]
+    fn merge_keeps_existing() {
+        let mut secrets = Secrets::new();
+        secrets.insert("token", "first");
+
+        let mut other = Secrets::new();
+        other.insert("token", "second");
+        other.insert("other", "third");
+
+        secrets.merge(other);
+        assert_eq!(secrets.get("token").map(|secret| &**secret), Some("first"));
+        assert_eq!(secrets.get("other").map(|secret| &**secret), Some("third"));
+    }
+}
@@ -1,11 +1,15 @@
 use crate::event::{
-    metric::{Bucket, MetricData, MetricName, MetricSeries, Quantile, Sample},
-    Event, EventMetadata, LogEvent, Metric, MetricKind, MetricValue, StatisticKind, Value,
+    metric::{Bucket, DDSketch, MetricData, MetricName, MetricSeries, Quantile, Sample},
+    Event, EventMetadata, LogEvent, Metric, MetricKind, MetricValue, StatisticKind, TraceEvent,
+    Value,
 };
 use bytes::Bytes;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use quickcheck::{empty_shrinker, Arbitrary, Gen};
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
 const MAX_F64_SIZE: f64 = 1_000_000.0;
 const MAX_ARRAY_SIZE: usize = 4;
@@ -53,10 +57,10 @@ impl Arbitrary for Event {
         let choice: u8 = u8::arbitrary(g);
         // Quickcheck can't derive Arbitrary for enums, see
         // https://github.com/BurntSushi/quickcheck/issues/98
-        if choice % 2 == 0 {
-            Event::Log(LogEvent::arbitrary(g))
-        } else {
-            Event::Metric(Metric::arbitrary(g))
+        match choice % 3 {
+            0 => Event::Log(LogEvent::arbitrary(g)),
+            1 => Event::Metric(Metric::arbitrary(g)),
+            _ => Event::Trace(TraceEvent::from(LogEvent::arbitrary(g))),
         }
     }
 
@@ -64,6 +68,12 @@ impl Arbitrary for Event {
         match self {
             Event::Log(log_event) => Box::new(log_event.shrink().map(Event::Log)),
             Event::Metric(metric) => Box::new(metric.shrink().map(Event::Metric)),
+            Event::Trace(trace) => Box::new(
+                trace
+                    .as_ref()
+                    .shrink()
+                    .map(|log| Event::Trace(TraceEvent::from(log))),
+            ),
         }
     }
 }
@@ -151,7 +161,7 @@ impl Arbitrary for MetricValue {
         // constant here are the number of fields in `MetricValue`. Because the
         // field total is not a power of two we introduce a bias into choice
         // here toward `MetricValue::Counter` and `MetricValue::Gauge`.
-        match u8::arbitrary(g) % 6 {
+        match u8::arbitrary(g) % 7 {
             0 => MetricValue::Counter {
                 value: f64::arbitrary(g) % MAX_F64_SIZE,
             },
@@ -175,6 +185,13 @@ impl Arbitrary for MetricValue {
                 count: u32::arbitrary(g),
                 sum: f64::arbitrary(g) % MAX_F64_SIZE,
             },
+            6 => {
+                let mut sketch = DDSketch::default();
+                for value in Vec::<f64>::arbitrary(g) {
+                    sketch.insert(value % MAX_F64_SIZE);
+                }
+                MetricValue::Sketch { sketch }
+            }
             _ => unreachable!(),
         }
     }
@@ -302,6 +319,15 @@ impl Arbitrary for MetricValue {
                         }),
                 )
             }
+            MetricValue::Sketch { sketch } => {
+                if sketch.is_empty() {
+                    empty_shrinker()
+                } else {
+                    let mut empty = sketch.clone();
+                    empty.clear();
+                    Box::new(std::iter::once(MetricValue::Sketch { sketch: empty }))
+                }
+            }
         }
     }
 }
@@ -425,7 +451,7 @@ impl Arbitrary for MetricSeries {
             if map.is_empty() {
                 None
             } else {
-                Some(map)
+                Some(Arc::new(map))
             }
         } else {
             None
@@ -544,8 +570,12 @@ impl Arbitrary for Value {
         // field picking.
         match u8::arbitrary(g) % 8 {
             0 => {
-                let bytes: Vec<u8> = Vec::arbitrary(g);
-                Value::Bytes(Bytes::from(bytes))
+                if bool::arbitrary(g) {
+                    Value::from(String::arbitrary(g))
+                } else {
+                    let bytes: Vec<u8> = Vec::arbitrary(g);
+                    Value::Bytes(Bytes::from(bytes))
+                }
             }
             1 => Value::Integer(i64::arbitrary(g)),
             2 => Value::Float(f64::arbitrary(g) % MAX_F64_SIZE),
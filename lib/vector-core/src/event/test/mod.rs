@@ -56,3 +56,25 @@ fn event_iteration_order() {
         ]
     );
 }
+
+#[test]
+fn monotonic_ingest_order() {
+    let mut events = (0..1000)
+        .map(|_| {
+            let mut event = Event::new_empty_log();
+            event.metadata_mut().record_ingest();
+            event
+        })
+        .collect::<Vec<_>>();
+
+    let readings = events
+        .iter()
+        .map(|event| event.metadata().ingest_monotonic().unwrap())
+        .collect::<Vec<_>>();
+    assert!(readings.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let last = events.pop().unwrap();
+    let mut first = events.remove(0);
+    first.metadata_mut().merge(last.metadata().clone());
+    assert_eq!(first.metadata().ingest_monotonic(), Some(readings[0]));
+}
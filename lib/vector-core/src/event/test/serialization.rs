@@ -1,9 +1,10 @@
 use super::*;
 use crate::config::log_schema;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use pretty_assertions::assert_eq;
 use quickcheck::{QuickCheck, TestResult};
 use regex::Regex;
+use std::sync::Arc;
 
 // Ser/De the Event never loses bytes
 #[test]
@@ -64,6 +65,203 @@ fn back_and_forth_through_bytes() {
         .quickcheck(inner as fn(Event) -> TestResult);
 }
 
+// Ser/De the Event through EncodeBytes -> DecodeBytes keeps its metadata
+#[test]
+fn metadata_through_bytes() {
+    let mut log = LogEvent::from("raw log line");
+    log.metadata_mut()
+        .set_datadog_api_key(Some(Arc::from("0123456789abcdef")));
+
+    let mut metric = Metric::new(
+        "counter",
+        MetricKind::Incremental,
+        MetricValue::Counter { value: 1.0 },
+    );
+    metric
+        .metadata_mut()
+        .set_datadog_api_key(Some(Arc::from("fedcba9876543210")));
+
+    for event in vec![Event::from(log), Event::from(metric)] {
+        let expected = event.metadata().clone();
+
+        let mut buffer = BytesMut::with_capacity(64);
+        Event::encode(event, &mut buffer).unwrap();
+        let actual = Event::decode(buffer).unwrap();
+
+        assert_eq!(actual.metadata(), &expected);
+    }
+}
+
+// Ser/De the Event through EncodeBytes -> DecodeBytes keeps where and when it
+// was ingested, and whether its delivery is waited on
+#[test]
+fn ingest_metadata_through_bytes() {
+    let (batch, _receiver) = BatchNotifier::new_with_receiver();
+    let mut event = Event::from(LogEvent::from("raw log line")).with_batch_notifier(&batch);
+    event.metadata_mut().record_ingest();
+    event.metadata_mut().record_lineage(Arc::from("in"));
+    event.metadata_mut().record_lineage(Arc::from("remap"));
+    let ingest_timestamp = event.metadata().ingest_timestamp();
+    assert!(ingest_timestamp.is_some());
+
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(event, &mut buffer).unwrap();
+    let mut actual = Event::decode(buffer).unwrap();
+
+    let metadata = actual.metadata();
+    assert_eq!(metadata.ingest_timestamp(), ingest_timestamp);
+    assert_eq!(metadata.source_id().map(AsRef::as_ref), Some("in"));
+    assert_eq!(metadata.lineage().len(), 2);
+    assert!(metadata.awaits_delivery());
+    assert_eq!(metadata.ingest_monotonic(), None);
+
+    // Ingesting it again keeps when it was first ingested.
+    actual.metadata_mut().record_ingest();
+    assert_eq!(actual.metadata().ingest_timestamp(), ingest_timestamp);
+}
+
+// Corrupt or incomplete frames fail to decode instead of panicking
+#[test]
+fn decode_errors_through_bytes() {
+    let decode = |wrapper: proto::EventWrapper| {
+        let mut buffer = BytesMut::with_capacity(64);
+        wrapper.encode(&mut buffer).unwrap();
+        Event::decode(buffer)
+    };
+
+    assert!(matches!(
+        decode(proto::EventWrapper::default()),
+        Err(proto::DecodeError::MissingEvent)
+    ));
+
+    let metric = proto::Metric {
+        name: "counter".into(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        decode(proto::Event::Metric(metric).into()),
+        Err(proto::DecodeError::MissingMetricValue { name }) if name == "counter"
+    ));
+
+    let buffer = BytesMut::from(&b"\xff\xff\xff"[..]);
+    assert!(matches!(
+        Event::decode(buffer),
+        Err(proto::DecodeError::Protobuf { .. })
+    ));
+}
+
+// Timestamps keep their nanoseconds, and invalid ones are dropped rather
+// than panicking
+#[test]
+fn timestamps_through_bytes() {
+    use chrono::TimeZone;
+
+    let timestamp = Utc.ymd(2021, 8, 1).and_hms_nano(12, 30, 15, 123_456_789);
+    let mut log = LogEvent::default();
+    log.insert("timestamp", timestamp);
+
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(Event::from(log), &mut buffer).unwrap();
+    let actual = Event::decode(buffer).unwrap();
+    assert_eq!(
+        actual.as_log().get("timestamp"),
+        Some(&Value::Timestamp(timestamp))
+    );
+
+    let mut fields = BTreeMap::new();
+    fields.insert(
+        "timestamp".to_owned(),
+        proto::Value {
+            kind: Some(proto::value::Kind::Timestamp(prost_types::Timestamp {
+                seconds: 0,
+                nanos: -1,
+            })),
+        },
+    );
+    let wrapper = proto::EventWrapper::from(proto::Event::Log(proto::Log {
+        fields,
+        metadata: None,
+    }));
+    let mut buffer = BytesMut::with_capacity(64);
+    wrapper.encode(&mut buffer).unwrap();
+    let actual = Event::decode(buffer).unwrap();
+    assert_eq!(actual.as_log().get("timestamp"), None);
+}
+
+// Secrets are redacted from every encoding of the event
+#[test]
+fn secrets_never_encoded() {
+    let mut log = LogEvent::from("message");
+    log.metadata_mut()
+        .secrets_mut()
+        .insert("splunk_hec_token", "hunter2");
+
+    assert!(!format!("{:?}", log).contains("hunter2"));
+    assert!(!serde_json::to_string(&log).unwrap().contains("hunter2"));
+
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(Event::from(log), &mut buffer).unwrap();
+    assert!(!buffer.windows(7).any(|window| window == b"hunter2"));
+    let actual = Event::decode(buffer).unwrap();
+    assert!(actual.as_log().metadata().secrets().is_empty());
+}
+
+// Binary values stay apart from text through the protobuf encoding, and peers
+// on older schema versions get them as text
+#[test]
+fn binary_values_through_bytes() {
+    let mut log = LogEvent::from("message");
+    log.insert("payload", Value::Bytes(Bytes::from_static(b"\xff\x00")));
+    log.insert("nested.text", Value::from(Bytes::from_static(b"text")));
+    assert_eq!(
+        log["nested.text"],
+        Value::String(Bytes::from_static(b"text"))
+    );
+
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(Event::from(log.clone()), &mut buffer).unwrap();
+    let actual = Event::decode(buffer).unwrap();
+    assert_eq!(actual.as_log(), &log);
+
+    let downgraded = proto::downgrade(Event::from(log), 1).unwrap();
+    assert_eq!(
+        downgraded.as_log()["payload"],
+        Value::String(Bytes::from_static(b"\xff\x00"))
+    );
+    assert_eq!(downgraded.as_log()["message"], Value::from("message"));
+}
+
+// Fields decoded from protobuf are encoded again as they were received until
+// they're changed, and decode the same as the eagerly decoded event
+#[test]
+fn encoded_fields_through_bytes() {
+    let mut log = LogEvent::from("message");
+    log.insert("nested.value", 1);
+
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(Event::from(log.clone()), &mut buffer).unwrap();
+    let received = buffer.freeze();
+
+    let eager: Event = proto::EventWrapper::decode(received.clone())
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(eager.as_log(), &log);
+
+    let decoded = Event::decode(received.clone()).unwrap();
+    assert_eq!(decoded.as_log(), &log);
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(decoded, &mut buffer).unwrap();
+    assert_eq!(buffer.freeze(), received);
+
+    let mut changed = Event::decode(received).unwrap();
+    changed.as_mut_log().insert("nested.value", 2);
+    log.insert("nested.value", 2);
+    let mut buffer = BytesMut::with_capacity(64);
+    Event::encode(changed, &mut buffer).unwrap();
+    assert_eq!(Event::decode(buffer).unwrap().as_log(), &log);
+}
+
 #[test]
 fn serialization() {
     let mut event = Event::from("raw log line");
@@ -99,4 +297,10 @@ fn type_serialization() {
     assert_eq!(map["int"], json!(4));
     assert_eq!(map["bool"], json!(true));
     assert_eq!(map["string"], json!("thisisastring"));
+
+    event
+        .as_mut_log()
+        .insert("binary", Value::Bytes(Bytes::from_static(b"\xff\x00")));
+    let map = serde_json::to_value(event.as_log().all_fields()).unwrap();
+    assert_eq!(map["binary"], json!("/wA="));
 }
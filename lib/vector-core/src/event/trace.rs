@@ -0,0 +1,141 @@
+use super::{BatchNotifier, EventFinalizer, EventMetadata, LogEvent, Value};
+use crate::ByteSizeOf;
+use serde::{Deserialize, Serialize};
+use shared::EventDataEq;
+use std::{collections::BTreeMap, fmt::Debug, sync::Arc};
+
+/// Span field names, following the OpenTelemetry trace data model.
+pub mod fields {
+    pub const TRACE_ID: &str = "trace_id";
+    pub const SPAN_ID: &str = "span_id";
+    pub const PARENT_SPAN_ID: &str = "parent_span_id";
+    pub const NAME: &str = "name";
+    pub const KIND: &str = "kind";
+    pub const START_TIME: &str = "start_time";
+    pub const END_TIME: &str = "end_time";
+    pub const ATTRIBUTES: &str = "attributes";
+    pub const EVENTS: &str = "events";
+    pub const LINKS: &str = "links";
+    pub const STATUS: &str = "status";
+}
+
+/// A span of a trace.
+///
+/// Spans are made up of fields, like logs, so they share the representation
+/// of a `LogEvent`, but flow through the topology as an event type of their
+/// own. The well known fields of a span are named in [`fields`].
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct TraceEvent(LogEvent);
+
+impl TraceEvent {
+    /// Create a `TraceEvent` from its components.
+    pub fn from_parts(map: BTreeMap<String, Value>, metadata: EventMetadata) -> Self {
+        Self(LogEvent::from_parts(map, metadata))
+    }
+
+    /// Convert a `TraceEvent` into a tuple of its components.
+    pub fn into_parts(self) -> (BTreeMap<String, Value>, EventMetadata) {
+        self.0.into_parts()
+    }
+
+    pub fn metadata(&self) -> &EventMetadata {
+        self.0.metadata()
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut EventMetadata {
+        self.0.metadata_mut()
+    }
+
+    #[must_use]
+    pub fn with_batch_notifier(self, batch: &Arc<BatchNotifier>) -> Self {
+        Self(self.0.with_batch_notifier(batch))
+    }
+
+    pub fn add_finalizer(&mut self, finalizer: EventFinalizer) {
+        self.0.add_finalizer(finalizer);
+    }
+
+    pub fn get(&self, key: impl AsRef<str>) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: impl AsRef<str>) -> Option<&mut Value> {
+        self.0.get_mut(key)
+    }
+
+    pub fn contains(&self, key: impl AsRef<str>) -> bool {
+        self.0.contains(key)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: impl AsRef<str>,
+        value: impl Into<Value> + Debug,
+    ) -> Option<Value> {
+        self.0.insert(key, value)
+    }
+
+    pub fn remove(&mut self, key: impl AsRef<str>) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    pub fn as_map(&self) -> &BTreeMap<String, Value> {
+        self.0.as_map()
+    }
+
+    pub fn as_map_mut(&mut self) -> &mut BTreeMap<String, Value> {
+        self.0.as_map_mut()
+    }
+
+    /// The ID of the trace this span is part of, if set.
+    pub fn trace_id(&self) -> Option<&Value> {
+        self.get(fields::TRACE_ID)
+    }
+
+    /// The ID of this span, if set.
+    pub fn span_id(&self) -> Option<&Value> {
+        self.get(fields::SPAN_ID)
+    }
+}
+
+impl ByteSizeOf for TraceEvent {
+    fn allocated_bytes(&self) -> usize {
+        self.0.allocated_bytes()
+    }
+}
+
+impl EventDataEq for TraceEvent {
+    fn event_data_eq(&self, other: &Self) -> bool {
+        self.0.event_data_eq(&other.0)
+    }
+}
+
+impl From<LogEvent> for TraceEvent {
+    fn from(log: LogEvent) -> Self {
+        Self(log)
+    }
+}
+
+impl From<TraceEvent> for LogEvent {
+    fn from(trace: TraceEvent) -> Self {
+        trace.0
+    }
+}
+
+impl From<BTreeMap<String, Value>> for TraceEvent {
+    fn from(map: BTreeMap<String, Value>) -> Self {
+        Self(LogEvent::from(map))
+    }
+}
+
+impl AsRef<LogEvent> for TraceEvent {
+    fn as_ref(&self) -> &LogEvent {
+        &self.0
+    }
+}
+
+impl AsMut<LogEvent> for TraceEvent {
+    fn as_mut(&mut self) -> &mut LogEvent {
+        &mut self.0
+    }
+}
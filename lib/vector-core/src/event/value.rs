@@ -8,11 +8,15 @@ use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::iter::FromIterator;
+use std::str::FromStr;
 use toml::value::Value as TomlValue;
 
 #[derive(PartialEq, PartialOrd, Debug, Clone, Deserialize)]
 pub enum Value {
+    /// Binary data, which isn't assumed to be text.
     Bytes(Bytes),
+    /// Text, kept as its UTF-8 encoding so it can be shared without copying.
+    String(Bytes),
     Integer(i64),
     Float(f64),
     Boolean(bool),
@@ -25,7 +29,7 @@ pub enum Value {
 impl ByteSizeOf for Value {
     fn allocated_bytes(&self) -> usize {
         match self {
-            Value::Bytes(bytes) => bytes.len(),
+            Value::Bytes(bytes) | Value::String(bytes) => bytes.len(),
             Value::Map(map) => map
                 .iter()
                 .fold(0, |acc, (k, v)| acc + k.len() + v.size_of()),
@@ -44,9 +48,12 @@ impl Serialize for Value {
             Value::Integer(i) => serializer.serialize_i64(*i),
             Value::Float(f) => serializer.serialize_f64(*f),
             Value::Boolean(b) => serializer.serialize_bool(*b),
-            Value::Bytes(_) | Value::Timestamp(_) => {
+            Value::String(_) | Value::Timestamp(_) => {
                 serializer.serialize_str(&self.to_string_lossy())
             }
+            // Text formats have no place for raw bytes, so binary data is
+            // base64 encoded rather than mangled into UTF-8.
+            Value::Bytes(bytes) => serializer.serialize_str(&base64::encode(bytes)),
             Value::Map(m) => serializer.collect_map(m),
             Value::Array(a) => serializer.collect_seq(a),
             Value::Null => serializer.serialize_none(),
@@ -54,9 +61,15 @@ impl Serialize for Value {
     }
 }
 
+/// Bytes whose content isn't known are taken to be text when they are valid
+/// UTF-8. Build a `Value::Bytes` directly to keep text as binary data.
 impl From<Bytes> for Value {
     fn from(bytes: Bytes) -> Self {
-        Value::Bytes(bytes)
+        if std::str::from_utf8(&bytes).is_ok() {
+            Value::String(bytes)
+        } else {
+            Value::Bytes(bytes)
+        }
     }
 }
 
@@ -70,7 +83,7 @@ impl<T: Into<Value>> From<Vec<T>> for Value {
 
 impl From<String> for Value {
     fn from(string: String) -> Self {
-        Value::Bytes(string.into())
+        Value::String(string.into())
     }
 }
 
@@ -100,7 +113,7 @@ impl TryFrom<TomlValue> for Value {
 
 impl From<&str> for Value {
     fn from(s: &str) -> Self {
-        Value::Bytes(Vec::from(s.as_bytes()).into())
+        Value::String(Vec::from(s.as_bytes()).into())
     }
 }
 
@@ -181,11 +194,11 @@ impl From<serde_json::Value> for Value {
             serde_json::Value::Number(n) => {
                 let float_or_byte = || {
                     n.as_f64()
-                        .map_or_else(|| Value::Bytes(n.to_string().into()), Value::Float)
+                        .map_or_else(|| Value::String(n.to_string().into()), Value::Float)
                 };
                 n.as_i64().map_or_else(float_or_byte, Value::Integer)
             }
-            serde_json::Value::String(s) => Value::Bytes(Bytes::from(s)),
+            serde_json::Value::String(s) => Value::String(Bytes::from(s)),
             serde_json::Value::Object(obj) => Value::Map(
                 obj.into_iter()
                     .map(|(key, value)| (key, Value::from(value)))
@@ -207,7 +220,8 @@ impl TryInto<serde_json::Value> for Value {
             Value::Boolean(v) => Ok(serde_json::Value::from(v)),
             Value::Integer(v) => Ok(serde_json::Value::from(v)),
             Value::Float(v) => Ok(serde_json::Value::from(v)),
-            Value::Bytes(v) => Ok(serde_json::Value::from(String::from_utf8(v.to_vec())?)),
+            Value::String(v) => Ok(serde_json::Value::from(String::from_utf8(v.to_vec())?)),
+            Value::Bytes(v) => Ok(serde_json::Value::from(base64::encode(v))),
             Value::Map(v) => Ok(serde_json::to_value(v)?),
             Value::Array(v) => Ok(serde_json::to_value(v)?),
             Value::Null => Ok(serde_json::Value::Null),
@@ -224,14 +238,16 @@ impl From<vrl_core::Value> for Value {
         };
 
         match v {
-            Bytes(v) => Value::Bytes(v),
+            // VRL doesn't tell text from binary data, so it is told apart the
+            // same way as bytes read by sources.
+            Bytes(v) => Value::from(v),
             Integer(v) => Value::Integer(v),
             Float(v) => Value::Float(*v),
             Boolean(v) => Value::Boolean(v),
             Object(v) => Value::Map(v.into_iter().map(|(k, v)| (k, v.into())).collect()),
             Array(v) => Value::Array(v.into_iter().map(Into::into).collect()),
             Timestamp(v) => Value::Timestamp(v),
-            Regex(v) => Value::Bytes(bytes::Bytes::copy_from_slice(v.to_string().as_bytes())),
+            Regex(v) => Value::String(bytes::Bytes::copy_from_slice(v.to_string().as_bytes())),
             Null => Value::Null,
         }
     }
@@ -243,7 +259,7 @@ impl From<Value> for vrl_core::Value {
         use vrl_core::Value::{Array, Object};
 
         match v {
-            Value::Bytes(v) => v.into(),
+            Value::Bytes(v) | Value::String(v) => v.into(),
             Value::Integer(v) => v.into(),
             Value::Float(v) => v.into(),
             Value::Boolean(v) => v.into(),
@@ -259,7 +275,9 @@ impl Value {
     // TODO: return Cow
     pub fn to_string_lossy(&self) -> String {
         match self {
-            Value::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            Value::Bytes(bytes) | Value::String(bytes) => {
+                String::from_utf8_lossy(bytes).into_owned()
+            }
             Value::Timestamp(timestamp) => timestamp_to_string(timestamp),
             Value::Integer(num) => format!("{}", num),
             Value::Float(num) => format!("{}", num),
@@ -272,7 +290,7 @@ impl Value {
 
     pub fn as_bytes(&self) -> Bytes {
         match self {
-            Value::Bytes(bytes) => bytes.clone(), // cloning a Bytes is cheap
+            Value::Bytes(bytes) | Value::String(bytes) => bytes.clone(), // cloning a Bytes is cheap
             Value::Timestamp(timestamp) => Bytes::from(timestamp_to_string(timestamp)),
             Value::Integer(num) => Bytes::from(format!("{}", num)),
             Value::Float(num) => Bytes::from(format!("{}", num)),
@@ -289,6 +307,31 @@ impl Value {
         self.as_bytes()
     }
 
+    /// Returns the text held by the value, if it is a string or bytes that
+    /// are valid UTF-8.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Bytes(bytes) | Value::String(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+
+    /// Parses the text held by the value, ignoring surrounding whitespace.
+    ///
+    /// This is how sinks writing typed columns coerce strings to the type of
+    /// the column.
+    pub fn parse_text<T: FromStr>(&self) -> Option<T> {
+        self.as_text()?.trim().parse().ok()
+    }
+
+    /// Parses the text held by the value as an RFC 3339 timestamp, see
+    /// [`Value::parse_text`].
+    pub fn parse_timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(self.as_text()?.trim())
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    }
+
     pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
         match &self {
             Value::Map(map) => Some(map),
@@ -348,7 +391,8 @@ impl Value {
 
     pub fn kind(&self) -> &str {
         match self {
-            Value::Bytes(_) => "string",
+            Value::Bytes(_) => "bytes",
+            Value::String(_) => "string",
             Value::Timestamp(_) => "timestamp",
             Value::Integer(_) => "integer",
             Value::Float(_) => "float",
@@ -361,17 +405,27 @@ impl Value {
 
     /// Merges `incoming` value into self.
     ///
-    /// Will concatenate `Bytes` and overwrite the rest value kinds.
+    /// Will concatenate `String` and `Bytes` and overwrite the rest value
+    /// kinds. Concatenating text with binary data gives binary data.
     pub fn merge(&mut self, incoming: Value) {
-        match (self, incoming) {
-            (Value::Bytes(self_bytes), Value::Bytes(ref incoming)) => {
-                let mut bytes = BytesMut::with_capacity(self_bytes.len() + incoming.len());
-                bytes.extend_from_slice(&self_bytes[..]);
-                bytes.extend_from_slice(&incoming[..]);
-                *self_bytes = bytes.freeze();
-            }
-            (current, incoming) => *current = incoming,
+        fn concat(current: &Bytes, incoming: &Bytes) -> Bytes {
+            let mut bytes = BytesMut::with_capacity(current.len() + incoming.len());
+            bytes.extend_from_slice(&current[..]);
+            bytes.extend_from_slice(&incoming[..]);
+            bytes.freeze()
         }
+
+        let merged = match (&*self, &incoming) {
+            (Value::String(current), Value::String(incoming)) => {
+                Some(Value::String(concat(current, incoming)))
+            }
+            (
+                Value::Bytes(current) | Value::String(current),
+                Value::Bytes(incoming) | Value::String(incoming),
+            ) => Some(Value::Bytes(concat(current, incoming))),
+            _ => None,
+        };
+        *self = merged.unwrap_or(incoming);
     }
 
     /// Return if the node is empty, that is, it is an array or map with no items.
@@ -401,6 +455,7 @@ impl Value {
         match &self {
             Value::Boolean(_)
             | Value::Bytes(_)
+            | Value::String(_)
             | Value::Timestamp(_)
             | Value::Float(_)
             | Value::Integer(_) => false,
@@ -668,6 +723,7 @@ impl Value {
             // if the type is one of the following, the field is modified to be a map.
             (Some(segment), Value::Boolean(_))
             | (Some(segment), Value::Bytes(_))
+            | (Some(segment), Value::String(_))
             | (Some(segment), Value::Timestamp(_))
             | (Some(segment), Value::Float(_))
             | (Some(segment), Value::Integer(_))
@@ -754,6 +810,7 @@ impl Value {
             // This is just not allowed!
             (Some(segment), Value::Boolean(_))
             | (Some(segment), Value::Bytes(_))
+            | (Some(segment), Value::String(_))
             | (Some(segment), Value::Timestamp(_))
             | (Some(segment), Value::Float(_))
             | (Some(segment), Value::Integer(_))
@@ -940,6 +997,7 @@ impl Value {
             // This is just not allowed!
             (Some(_s), Value::Boolean(_))
             | (Some(_s), Value::Bytes(_))
+            | (Some(_s), Value::String(_))
             | (Some(_s), Value::Timestamp(_))
             | (Some(_s), Value::Float(_))
             | (Some(_s), Value::Integer(_))
@@ -989,6 +1047,7 @@ impl Value {
             // This is just not allowed!
             (_, Value::Boolean(_))
             | (_, Value::Bytes(_))
+            | (_, Value::String(_))
             | (_, Value::Timestamp(_))
             | (_, Value::Float(_))
             | (_, Value::Integer(_))
@@ -1100,6 +1159,7 @@ impl Value {
         match &self {
             Value::Boolean(_)
             | Value::Bytes(_)
+            | Value::String(_)
             | Value::Timestamp(_)
             | Value::Float(_)
             | Value::Integer(_)
@@ -1206,6 +1266,7 @@ impl Value {
         match &self {
             Value::Boolean(_)
             | Value::Bytes(_)
+            | Value::String(_)
             | Value::Timestamp(_)
             | Value::Float(_)
             | Value::Integer(_)
@@ -1263,6 +1324,7 @@ impl Value {
 #[cfg(test)]
 mod test {
     use super::*;
+    use chrono::TimeZone;
     use quickcheck::{QuickCheck, TestResult};
     use std::{fs, io::Read, path::Path};
 
@@ -1564,6 +1626,25 @@ mod test {
         }
     }
 
+    #[test]
+    fn as_text() {
+        assert_eq!(Value::from("42").as_text(), Some("42"));
+        assert_eq!(Value::Bytes(Bytes::from("42")).as_text(), Some("42"));
+        assert_eq!(Value::Bytes(Bytes::from_static(&[0xff])).as_text(), None);
+        assert_eq!(Value::Integer(42).as_text(), None);
+    }
+
+    #[test]
+    fn parse_text() {
+        assert_eq!(Value::from(" 42\n").parse_text(), Some(42));
+        assert_eq!(Value::from("4.5").parse_text::<i64>(), None);
+        assert_eq!(Value::Integer(42).parse_text::<i64>(), None);
+        assert_eq!(
+            Value::from("1970-01-01T00:00:01+01:00").parse_timestamp(),
+            Some(Utc.timestamp(-3599, 0))
+        );
+    }
+
     #[test]
     fn quickcheck_value() {
         fn inner(mut path: LookupBuf) -> TestResult {
@@ -1637,7 +1718,7 @@ mod test {
                                 let is_match = match vector_value {
                                     Value::Boolean(_) => expected_type.eq("boolean"),
                                     Value::Integer(_) => expected_type.eq("integer"),
-                                    Value::Bytes(_) => expected_type.eq("bytes"),
+                                    Value::Bytes(_) | Value::String(_) => expected_type.eq("bytes"),
                                     Value::Array { .. } => expected_type.eq("array"),
                                     Value::Map(_) => expected_type.eq("map"),
                                     Value::Null => expected_type.eq("null"),
@@ -1,4 +1,4 @@
-use super::{Event, EventMetadata, LogEvent, Metric, MetricKind, Value};
+use super::{Event, EventMetadata, LogEvent, Metric, MetricKind, TraceEvent, Value};
 use crate::config::log_schema;
 use lookup::LookupBuf;
 use snafu::Snafu;
@@ -22,6 +22,8 @@ pub enum VrlTarget {
     // that `fields` must always be a `Map` variant.
     LogEvent(Value, EventMetadata),
     Metric(Metric),
+    // `Trace` is a destructured `event::TraceEvent`, handled just like `LogEvent`.
+    Trace(Value, EventMetadata),
 }
 
 impl VrlTarget {
@@ -32,6 +34,10 @@ impl VrlTarget {
                 VrlTarget::LogEvent(Value::Map(fields), metadata)
             }
             Event::Metric(event) => VrlTarget::Metric(event),
+            Event::Trace(event) => {
+                let (fields, metadata) = event.into_parts();
+                VrlTarget::Trace(Value::Map(fields), metadata)
+            }
         }
     }
 
@@ -47,6 +53,10 @@ impl VrlTarget {
             VrlTarget::Metric(metric) => {
                 Box::new(std::iter::once(Event::Metric(metric))) as Box<dyn Iterator<Item = Event>>
             }
+            VrlTarget::Trace(value, metadata) => Box::new(
+                value_into_log_events(value, metadata)
+                    .map(|event| Event::Trace(TraceEvent::from(event.into_log()))),
+            ) as Box<dyn Iterator<Item = Event>>,
         }
     }
 }
@@ -54,7 +64,7 @@ impl VrlTarget {
 impl vrl_core::Target for VrlTarget {
     fn insert(&mut self, path: &LookupBuf, value: vrl_core::Value) -> Result<(), String> {
         match self {
-            VrlTarget::LogEvent(ref mut log, _) => log
+            VrlTarget::LogEvent(ref mut log, _) | VrlTarget::Trace(ref mut log, _) => log
                 .insert(path.clone(), value)
                 .map(|_| ())
                 .map_err(|err| err.to_string()),
@@ -127,7 +137,7 @@ impl vrl_core::Target for VrlTarget {
 
     fn get(&self, path: &LookupBuf) -> std::result::Result<Option<vrl_core::Value>, String> {
         match self {
-            VrlTarget::LogEvent(log, _) => log
+            VrlTarget::LogEvent(log, _) | VrlTarget::Trace(log, _) => log
                 .get(path)
                 .map(|val| val.map(|val| val.clone().into()))
                 .map_err(|err| err.to_string()),
@@ -202,7 +212,7 @@ impl vrl_core::Target for VrlTarget {
         compact: bool,
     ) -> Result<Option<vrl_core::Value>, String> {
         match self {
-            VrlTarget::LogEvent(ref mut log, _) => {
+            VrlTarget::LogEvent(ref mut log, _) | VrlTarget::Trace(ref mut log, _) => {
                 if path.is_root() {
                     Ok(Some({
                         let mut map = Value::Map(BTreeMap::new());
@@ -227,7 +237,7 @@ impl vrl_core::Target for VrlTarget {
                         }
                         ["timestamp"] => return Ok(metric.data.timestamp.take().map(Into::into)),
                         ["tags"] => {
-                            return Ok(metric.series.tags.take().map(|map| {
+                            return Ok(metric.series.take_tags().map(|map| {
                                 map.into_iter()
                                     .map(|(k, v)| (k, v.into()))
                                     .collect::<vrl_core::Value>()
@@ -4,7 +4,7 @@ use crate::{
     event::{Event, Value},
     mapping::Result,
 };
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 
 #[derive(Debug, Clone)]
 pub(in crate::mapping) enum Operator {
@@ -73,6 +73,13 @@ fn compare_number_types(
     }
 }
 
+fn concat(left: &[u8], right: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(left.len() + right.len());
+    buf.extend_from_slice(left);
+    buf.extend_from_slice(right);
+    buf.freeze()
+}
+
 impl Function for Arithmetic {
     // This long function could potentially be shortened but when clippy lints
     // were made more strict in #7341 there was no desire to add functional
@@ -167,13 +174,13 @@ impl Function for Arithmetic {
                         Value::Integer(ir) => Value::Integer(il + ir),
                         vr => return Err(format!("unable to add right-hand field type {:?}", vr)),
                     },
+                    Value::String(sl) => match right {
+                        Value::String(sr) => Value::String(concat(&sl, &sr)),
+                        Value::Bytes(sr) => Value::Bytes(concat(&sl, &sr)),
+                        vr => return Err(format!("unable to add right-hand field type {:?}", vr)),
+                    },
                     Value::Bytes(sl) => match right {
-                        Value::Bytes(sr) => {
-                            let mut buf = BytesMut::with_capacity(sl.len() + sr.len());
-                            buf.extend_from_slice(&sl);
-                            buf.extend_from_slice(&sr);
-                            Value::Bytes(buf.into())
-                        }
+                        Value::Bytes(sr) | Value::String(sr) => Value::Bytes(concat(&sl, &sr)),
                         vr => return Err(format!("unable to add right-hand field type {:?}", vr)),
                     },
                     vl => return Err(format!("unable to add left-hand field type {:?}", vl)),
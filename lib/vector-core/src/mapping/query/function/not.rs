@@ -46,7 +46,7 @@ mod tests {
             ),
             (
                 Event::from(""),
-                Err("unable to perform NOT on Bytes(b\"not a bool\") value".to_string()),
+                Err("unable to perform NOT on String(b\"not a bool\") value".to_string()),
                 NotFn::new(Box::new(Literal::from(Value::from("not a bool")))),
             ),
         ];
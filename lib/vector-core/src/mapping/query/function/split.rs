@@ -27,7 +27,8 @@ impl Function for SplitFn {
     #[allow(clippy::cast_possible_truncation)] // `limit as usize` might misbehave on 32bit platforms
     fn execute(&self, ctx: &Event) -> Result<QueryValue> {
         let string = {
-            let bytes = required_value!(ctx, self.path, Value::Bytes(v) => v);
+            let bytes =
+                required_value!(ctx, self.path, Value::Bytes(v) => v, Value::String(v) => v);
             String::from_utf8_lossy(&bytes).into_owned()
         };
         let limit = optional_value!(ctx, self.limit, Value::Integer(i) => i)
@@ -42,14 +43,14 @@ impl Function for SplitFn {
 
         let to_value = |iter: Box<dyn Iterator<Item = &str>>| {
             Value::Array(
-                iter.map(|sub| Value::Bytes(sub.to_string().into()))
+                iter.map(|sub| Value::String(sub.to_string().into()))
                     .collect(),
             )
             .into()
         };
 
         match self.pattern.execute(ctx)? {
-            QueryValue::Value(Value::Bytes(path)) => {
+            QueryValue::Value(Value::Bytes(path) | Value::String(path)) => {
                 let pattern = String::from_utf8_lossy(&path).into_owned();
                 Ok(match limit {
                     Some(limit) => to_value(Box::new(string.splitn(limit, &pattern))),
@@ -68,12 +69,18 @@ impl Function for SplitFn {
         &[
             Parameter {
                 keyword: "value",
-                accepts: |v| matches!(v, QueryValue::Value(Value::Bytes(_))),
+                accepts: |v| matches!(v, QueryValue::Value(Value::Bytes(_) | Value::String(_))),
                 required: true,
             },
             Parameter {
                 keyword: "pattern",
-                accepts: |v| matches!(v, QueryValue::Value(Value::Bytes(_)) | QueryValue::Regex(_)),
+                accepts: |v| {
+                    matches!(
+                        v,
+                        QueryValue::Value(Value::Bytes(_) | Value::String(_))
+                            | QueryValue::Regex(_)
+                    )
+                },
                 required: true,
             },
             Parameter {
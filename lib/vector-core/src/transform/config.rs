@@ -7,6 +7,7 @@ pub enum DataType {
     Any,
     Log,
     Metric,
+    Trace,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Debug)]
@@ -1,4 +1,6 @@
 use crate::expression::*;
+use crate::function::closure::{self, Closure};
+use crate::value::Kind;
 use crate::{Function, Program, State, Value};
 use chrono::{TimeZone, Utc};
 use diagnostic::DiagnosticError;
@@ -329,6 +331,7 @@ impl<'a> Compiler<'a> {
             ident,
             abort_on_error,
             arguments,
+            closure,
         } = node.into_inner();
 
         let arguments = arguments
@@ -336,6 +339,19 @@ impl<'a> Compiler<'a> {
             .map(|node| Node::new(node.span(), self.compile_function_argument(node)))
             .collect();
 
+        let definition = self
+            .fns
+            .iter()
+            .find(|f| f.identifier() == (*ident).as_ref())
+            .and_then(|f| f.closure());
+
+        let closure = closure.map(|node| {
+            Node::new(
+                node.span(),
+                self.compile_function_closure(node, definition.as_ref()),
+            )
+        });
+
         if abort_on_error {
             self.fallible = true;
         }
@@ -345,6 +361,7 @@ impl<'a> Compiler<'a> {
             ident,
             abort_on_error,
             arguments,
+            closure,
             self.fns,
             self.state,
         )
@@ -360,6 +377,54 @@ impl<'a> Compiler<'a> {
         FunctionArgument::new(ident, expr)
     }
 
+    /// Compile the closure of a function call.
+    ///
+    /// The closure variables are only in scope while compiling the closure
+    /// block, any program variables they shadow are restored afterwards.
+    fn compile_function_closure(
+        &mut self,
+        node: Node<ast::FunctionClosure>,
+        definition: Option<&closure::Definition>,
+    ) -> Closure {
+        let ast::FunctionClosure { variables, block } = node.into_inner();
+
+        let variables = variables
+            .into_iter()
+            .map(Node::into_inner)
+            .collect::<Vec<_>>();
+
+        let shadowed = variables
+            .iter()
+            .enumerate()
+            .map(|(index, ident)| {
+                let kind = definition.map_or_else(Kind::all, |def| def.input(index));
+                let details = assignment::Details {
+                    type_def: kind.into(),
+                    value: None,
+                };
+
+                let shadowed = self.state.remove_variable(ident);
+                self.state.insert_variable(ident.clone(), details);
+
+                (ident.clone(), shadowed)
+            })
+            .collect::<Vec<_>>();
+
+        let block = self.compile_block(block);
+        let type_def = block.type_def(self.state);
+
+        for (ident, shadowed) in shadowed {
+            match shadowed {
+                Some(details) => self.state.insert_variable(ident, details),
+                None => {
+                    self.state.remove_variable(&ident);
+                }
+            }
+        }
+
+        Closure::new(variables, block, type_def)
+    }
+
     fn compile_variable(&mut self, node: Node<ast::Ident>) -> Variable {
         Variable::new(node.into_inner(), self.state)
     }
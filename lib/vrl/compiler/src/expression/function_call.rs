@@ -1,5 +1,5 @@
 use crate::expression::{levenstein, ExpressionError, FunctionArgument, Noop};
-use crate::function::{ArgumentList, Parameter};
+use crate::function::{closure::Closure, ArgumentList, Parameter};
 use crate::parser::{Ident, Node};
 use crate::{value::Kind, Context, Expression, Function, Resolved, Span, State, TypeDef};
 use diagnostic::{DiagnosticError, Label, Note, Urls};
@@ -32,6 +32,7 @@ impl FunctionCall {
         ident: Node<Ident>,
         abort_on_error: bool,
         arguments: Vec<Node<FunctionArgument>>,
+        closure: Option<Node<Closure>>,
         funcs: &[Box<dyn Function>],
         state: &mut State,
     ) -> Result<Self, Error> {
@@ -161,6 +162,43 @@ impl FunctionCall {
                 })
             })?;
 
+        // Check the closure matches the one the function expects, if any.
+        match (function.closure(), closure) {
+            (None, None) => {}
+            (None, Some(closure)) => {
+                return Err(Error::UnexpectedClosure {
+                    closure_span: closure.span(),
+                })
+            }
+            (Some(_), None) => return Err(Error::MissingClosure { call_span }),
+            (Some(definition), Some(closure)) => {
+                let (closure_span, closure) = closure.take();
+
+                if closure.variables().len() != definition.inputs.len() {
+                    return Err(Error::ClosureArityMismatch {
+                        closure_span,
+                        expected: definition.inputs.len(),
+                        supplied: closure.variables().len(),
+                    });
+                }
+
+                if closure.type_def().is_fallible() {
+                    return Err(Error::FallibleClosure { closure_span });
+                }
+
+                let got = closure.type_def().kind();
+                if !definition.output().contains(got) {
+                    return Err(Error::InvalidClosureReturnKind {
+                        closure_span,
+                        expected: definition.output(),
+                        got,
+                    });
+                }
+
+                list.set_closure(closure);
+            }
+        }
+
         let expr = function
             .compile(list)
             .map_err(|error| Error::Compilation { call_span, error })?;
@@ -398,6 +436,29 @@ pub enum Error {
 
     #[error("fallible argument")]
     FallibleArgument { expr_span: Span },
+
+    #[error("unexpected closure")]
+    UnexpectedClosure { closure_span: Span },
+
+    #[error("missing closure")]
+    MissingClosure { call_span: Span },
+
+    #[error("closure arity mismatch")]
+    ClosureArityMismatch {
+        closure_span: Span,
+        expected: usize,
+        supplied: usize,
+    },
+
+    #[error("invalid closure return type")]
+    InvalidClosureReturnKind {
+        closure_span: Span,
+        expected: Kind,
+        got: Kind,
+    },
+
+    #[error("fallible closure")]
+    FallibleClosure { closure_span: Span },
 }
 
 impl DiagnosticError for Error {
@@ -413,6 +474,11 @@ impl DiagnosticError for Error {
             AbortInfallible { .. } => 620,
             InvalidArgumentKind { .. } => 110,
             FallibleArgument { .. } => 630,
+            ClosureArityMismatch { .. } => 109,
+            UnexpectedClosure { .. } => 111,
+            MissingClosure { .. } => 112,
+            InvalidClosureReturnKind { .. } => 113,
+            FallibleClosure { .. } => 631,
         }
     }
 
@@ -561,6 +627,66 @@ impl DiagnosticError for Error {
                     expr_span,
                 ),
             ],
+
+            UnexpectedClosure { closure_span } => vec![
+                Label::primary("unexpected closure", closure_span),
+                Label::context("this function does not accept a closure", closure_span),
+            ],
+
+            MissingClosure { call_span } => vec![
+                Label::primary("this function expects a closure", call_span),
+                Label::context(
+                    r#"add a closure after the function call, e.g. "-> |value| { value }""#,
+                    call_span,
+                ),
+            ],
+
+            ClosureArityMismatch {
+                closure_span,
+                expected,
+                supplied,
+            } => {
+                let variables = |n: usize| if n == 1 { "variable" } else { "variables" };
+
+                vec![
+                    Label::primary(
+                        format!(
+                            "this closure declares {} {}",
+                            supplied,
+                            variables(*supplied)
+                        ),
+                        closure_span,
+                    ),
+                    Label::context(
+                        format!(
+                            "but the function expects {} {}",
+                            expected,
+                            variables(*expected)
+                        ),
+                        closure_span,
+                    ),
+                ]
+            }
+
+            InvalidClosureReturnKind {
+                closure_span,
+                expected,
+                got,
+            } => vec![
+                Label::primary(format!("this closure resolves to {}", got), closure_span),
+                Label::context(
+                    format!("but the function expects it to resolve to {}", expected),
+                    closure_span,
+                ),
+            ],
+
+            FallibleClosure { closure_span } => vec![
+                Label::primary("this closure can fail", closure_span),
+                Label::context(
+                    "handle the error inside the closure before returning its value",
+                    closure_span,
+                ),
+            ],
         }
     }
 
@@ -572,7 +698,9 @@ impl DiagnosticError for Error {
                 "function arguments".to_owned(),
                 Urls::expression_docs_url("#arguments"),
             )],
-            AbortInfallible { .. } | FallibleArgument { .. } => vec![Note::SeeErrorDocs],
+            AbortInfallible { .. } | FallibleArgument { .. } | FallibleClosure { .. } => {
+                vec![Note::SeeErrorDocs]
+            }
             InvalidArgumentKind {
                 function_ident,
                 abort_on_error,
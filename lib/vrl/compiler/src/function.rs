@@ -1,9 +1,12 @@
+pub mod closure;
+
 use crate::expression::{
     container::Variant, Container, Expr, Expression, FunctionArgument, Literal, Query,
 };
 use crate::parser::Node;
 use crate::value::Kind;
 use crate::{Span, Value};
+use closure::Closure;
 use diagnostic::{DiagnosticError, Label, Note};
 use std::collections::HashMap;
 use std::fmt;
@@ -48,6 +51,14 @@ pub trait Function: Sync + fmt::Debug {
     fn parameters(&self) -> &'static [Parameter] {
         &[]
     }
+
+    /// The closure this function accepts, if any.
+    ///
+    /// Functions that define a closure must be called with one, and vice
+    /// versa.
+    fn closure(&self) -> Option<closure::Definition> {
+        None
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -91,7 +102,10 @@ impl Parameter {
 // -----------------------------------------------------------------------------
 
 #[derive(Debug, Default)]
-pub struct ArgumentList(HashMap<&'static str, Expr>);
+pub struct ArgumentList {
+    arguments: HashMap<&'static str, Expr>,
+    closure: Option<Closure>,
+}
 
 impl ArgumentList {
     pub fn optional(&mut self, keyword: &'static str) -> Option<Box<dyn Expression>> {
@@ -211,16 +225,28 @@ impl ArgumentList {
         Ok(required(self.optional_array(keyword)?))
     }
 
+    pub fn optional_closure(&mut self) -> Option<Closure> {
+        self.closure.take()
+    }
+
+    pub fn required_closure(&mut self) -> Closure {
+        required(self.optional_closure())
+    }
+
     pub(crate) fn keywords(&self) -> Vec<&'static str> {
-        self.0.keys().copied().collect::<Vec<_>>()
+        self.arguments.keys().copied().collect::<Vec<_>>()
     }
 
     pub(crate) fn insert(&mut self, k: &'static str, v: Expr) {
-        self.0.insert(k, v);
+        self.arguments.insert(k, v);
+    }
+
+    pub(crate) fn set_closure(&mut self, closure: Closure) {
+        self.closure = Some(closure);
     }
 
     fn optional_expr(&mut self, keyword: &'static str) -> Option<Expr> {
-        self.0.remove(keyword)
+        self.arguments.remove(keyword)
     }
 
     fn required_expr(&mut self, keyword: &'static str) -> Expr {
@@ -234,11 +260,13 @@ fn required<T>(argument: Option<T>) -> T {
 
 impl From<HashMap<&'static str, Value>> for ArgumentList {
     fn from(map: HashMap<&'static str, Value>) -> Self {
-        Self(
-            map.into_iter()
+        Self {
+            arguments: map
+                .into_iter()
                 .map(|(k, v)| (k, v.into_expr()))
                 .collect::<HashMap<_, _>>(),
-        )
+            closure: None,
+        }
     }
 }
 
@@ -256,7 +284,10 @@ impl From<Vec<Node<FunctionArgument>>> for ArgumentList {
             })
             .collect::<HashMap<_, _>>();
 
-        Self(arguments)
+        Self {
+            arguments,
+            closure: None,
+        }
     }
 }
 
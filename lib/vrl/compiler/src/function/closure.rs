@@ -0,0 +1,93 @@
+use crate::expression::{Block, Resolved};
+use crate::parser::Ident;
+use crate::value::Kind;
+use crate::{Context, Expression, TypeDef, Value};
+
+/// The closure a function accepts.
+///
+/// This is used at compile-time to type the variables bound by the closure,
+/// and to check the closure resolves to a value the function can use.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Definition {
+    /// The type kind(s) of each of the variables the closure binds, in the
+    /// order in which they are declared.
+    pub inputs: &'static [u16],
+
+    /// The type kind(s) the closure block is expected to resolve to.
+    pub output: u16,
+}
+
+impl Definition {
+    pub fn input(&self, index: usize) -> Kind {
+        self.inputs
+            .get(index)
+            .map(|kind| Kind::new(*kind))
+            .unwrap_or_else(Kind::all)
+    }
+
+    pub fn output(&self) -> Kind {
+        Kind::new(self.output)
+    }
+}
+
+/// A compiled closure, passed to the function it is attached to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    variables: Vec<Ident>,
+    block: Block,
+    type_def: TypeDef,
+}
+
+impl Closure {
+    pub(crate) fn new(variables: Vec<Ident>, block: Block, type_def: TypeDef) -> Self {
+        Self {
+            variables,
+            block,
+            type_def,
+        }
+    }
+
+    pub(crate) fn variables(&self) -> &[Ident] {
+        &self.variables
+    }
+
+    /// The type definition of the closure block, as computed with its
+    /// variables in scope.
+    pub fn type_def(&self) -> &TypeDef {
+        &self.type_def
+    }
+
+    /// Resolve the closure block, with its variables bound to `values`.
+    ///
+    /// Any variable of the program shadowed by the closure is restored once
+    /// the block is resolved.
+    pub fn resolve<I>(&self, ctx: &mut Context, values: I) -> Resolved
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        let shadowed = self
+            .variables
+            .iter()
+            .zip(values)
+            .map(|(ident, value)| {
+                let shadowed = ctx.state_mut().remove_variable(ident);
+                ctx.state_mut().insert_variable(ident.clone(), value);
+
+                (ident, shadowed)
+            })
+            .collect::<Vec<_>>();
+
+        let result = self.block.resolve(ctx);
+
+        for (ident, shadowed) in shadowed {
+            match shadowed {
+                Some(value) => ctx.state_mut().insert_variable(ident.clone(), value),
+                None => {
+                    ctx.state_mut().remove_variable(ident);
+                }
+            }
+        }
+
+        result
+    }
+}
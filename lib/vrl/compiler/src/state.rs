@@ -48,6 +48,10 @@ impl Compiler {
         self.variables.insert(ident, details);
     }
 
+    pub(crate) fn remove_variable(&mut self, ident: &Ident) -> Option<assignment::Details> {
+        self.variables.remove(ident)
+    }
+
     pub(crate) fn target(&self) -> Option<&assignment::Details> {
         self.target.as_ref()
     }
@@ -104,4 +108,8 @@ impl Runtime {
     pub(crate) fn insert_variable(&mut self, ident: Ident, value: Value) {
         self.variables.insert(ident, value);
     }
+
+    pub(crate) fn remove_variable(&mut self, ident: &Ident) -> Option<Value> {
+        self.variables.remove(ident)
+    }
 }
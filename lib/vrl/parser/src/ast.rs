@@ -929,13 +929,14 @@ impl fmt::Debug for QueryTarget {
 
 /// A function call expression.
 ///
-/// It contains the identifier of the function, and any arguments passed into
-/// the function call.
+/// It contains the identifier of the function, any arguments passed into
+/// the function call, and an optional closure.
 #[derive(Clone, PartialEq)]
 pub struct FunctionCall {
     pub ident: Node<Ident>,
     pub abort_on_error: bool,
     pub arguments: Vec<Node<FunctionArgument>>,
+    pub closure: Option<Node<FunctionClosure>>,
 }
 
 impl fmt::Display for FunctionCall {
@@ -952,7 +953,13 @@ impl fmt::Display for FunctionCall {
             }
         }
 
-        f.write_str(")")
+        f.write_str(")")?;
+
+        if let Some(closure) = &self.closure {
+            closure.fmt(f)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -972,7 +979,13 @@ impl fmt::Debug for FunctionCall {
             }
         }
 
-        f.write_str("))")
+        f.write_str(")")?;
+
+        if let Some(closure) = &self.closure {
+            write!(f, " {:?}", closure)?;
+        }
+
+        f.write_str(")")
     }
 }
 
@@ -1008,6 +1021,51 @@ impl fmt::Debug for FunctionArgument {
     }
 }
 
+/// A closure attached to a function call.
+///
+/// The function defines how many variables the closure takes, and what each
+/// of them is bound to when the function runs the block.
+#[derive(Clone, PartialEq)]
+pub struct FunctionClosure {
+    pub variables: Vec<Node<Ident>>,
+    pub block: Node<Block>,
+}
+
+impl fmt::Display for FunctionClosure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(" -> |")?;
+
+        let mut iter = self.variables.iter().peekable();
+        while let Some(var) = iter.next() {
+            var.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        f.write_str("| ")?;
+        self.block.fmt(f)
+    }
+}
+
+impl fmt::Debug for FunctionClosure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Closure(")?;
+
+        let mut iter = self.variables.iter().peekable();
+        while let Some(var) = iter.next() {
+            var.fmt(f)?;
+
+            if iter.peek().is_some() {
+                f.write_str(", ")?;
+            }
+        }
+
+        write!(f, ": {:?})", self.block)
+    }
+}
+
 // -----------------------------------------------------------------------------
 // unary
 // -----------------------------------------------------------------------------
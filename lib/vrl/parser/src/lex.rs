@@ -266,6 +266,7 @@ pub enum Token<S> {
     MergeEquals,
     Bang,
     Question,
+    Arrow,
 
     /// The {L,R}Query token is an "instruction" token. It does not represent
     /// any character in the source, instead it represents the start or end of a
@@ -351,6 +352,7 @@ impl<S> Token<S> {
             MergeEquals => MergeEquals,
             Bang => Bang,
             Question => Question,
+            Arrow => Arrow,
 
             LQuery => LQuery,
             RQuery => RQuery,
@@ -405,6 +407,7 @@ where
             MergeEquals => "MergeEquals",
             Bang => "Bang",
             Question => "Question",
+            Arrow => "Arrow",
 
             LQuery => "LQuery",
             RQuery => "RQuery",
@@ -525,6 +528,11 @@ impl<'input> Iterator for Lexer<'input> {
                         Some(Ok(self.token(start, Bang)))
                     }
 
+                    '-' if self.test_peek(|ch| ch == '>') => {
+                        self.bump();
+                        Some(Ok(self.token(start, Arrow)))
+                    }
+
                     '#' => {
                         self.take_until(start, |ch| ch == '\n');
                         continue;
@@ -1843,6 +1851,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn closure() {
+        test(
+            data("f(a) -> |b| { b }"),
+            vec![
+                ("~                ", FunctionCall("f")),
+                (" ~               ", LParen),
+                ("  ~              ", Identifier("a")),
+                ("   ~             ", RParen),
+                ("     ~~          ", Arrow),
+                ("        ~        ", Operator("|")),
+                ("         ~       ", Identifier("b")),
+                ("          ~      ", Operator("|")),
+                ("            ~    ", LBrace),
+                ("              ~  ", Identifier("b")),
+                ("                ~", RBrace),
+            ],
+        );
+    }
+
     #[test]
     fn multi_byte_character_1() {
         use StringLiteral as S;
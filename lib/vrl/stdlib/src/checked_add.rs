@@ -0,0 +1,140 @@
+use std::convert::TryFrom;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CheckedAdd;
+
+impl Function for CheckedAdd {
+    fn identifier(&self) -> &'static str {
+        "checked_add"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+            Parameter {
+                keyword: "other",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "integers",
+                source: r#"checked_add!(1, 2)"#,
+                result: Ok("3"),
+            },
+            Example {
+                title: "float",
+                source: r#"checked_add!(1, 2.5)"#,
+                result: Ok("3.5"),
+            },
+            Example {
+                title: "overflow",
+                source: r#"checked_add!(9223372036854775807, 1)"#,
+                result: Err(
+                    r#"function call error for "checked_add" at (0:36): integer overflow: 9223372036854775807 + 1"#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let other = arguments.required("other");
+
+        Ok(Box::new(CheckedAddFn { value, other }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CheckedAddFn {
+    value: Box<dyn Expression>,
+    other: Box<dyn Expression>,
+}
+
+impl Expression for CheckedAddFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let other = self.other.resolve(ctx)?;
+
+        match (value, other) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => lhs
+                .checked_add(rhs)
+                .map(Into::into)
+                .ok_or_else(|| format!("integer overflow: {} + {}", lhs, rhs).into()),
+            (lhs, rhs) => {
+                let sum = f64::try_from(&lhs)? + f64::try_from(&rhs)?;
+
+                if sum.is_finite() {
+                    Ok(sum.into())
+                } else {
+                    Err(format!("float overflow: {} + {}", lhs, rhs).into())
+                }
+            }
+        }
+    }
+
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        let integers =
+            self.value.type_def(state).is_integer() && self.other.type_def(state).is_integer();
+
+        if integers {
+            TypeDef::new().fallible().integer()
+        } else {
+            TypeDef::new().fallible().integer().add_float()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        checked_add => CheckedAdd;
+
+        integers {
+            args: func_args![value: 1, other: 2],
+            want: Ok(value!(3)),
+            tdef: TypeDef::new().fallible().integer(),
+        }
+
+        negative {
+            args: func_args![value: -5, other: 2],
+            want: Ok(value!(-3)),
+            tdef: TypeDef::new().fallible().integer(),
+        }
+
+        float {
+            args: func_args![value: 1, other: 2.5],
+            want: Ok(value!(3.5)),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+
+        integer_overflow {
+            args: func_args![value: i64::MAX, other: 1],
+            want: Err("integer overflow: 9223372036854775807 + 1"),
+            tdef: TypeDef::new().fallible().integer(),
+        }
+
+        integer_underflow {
+            args: func_args![value: i64::MIN, other: -1],
+            want: Err("integer overflow: -9223372036854775808 + -1"),
+            tdef: TypeDef::new().fallible().integer(),
+        }
+
+        float_overflow {
+            args: func_args![value: f64::MAX, other: f64::MAX],
+            want: Err(format!("float overflow: {} + {}", Value::from(f64::MAX), Value::from(f64::MAX))),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+    ];
+}
@@ -0,0 +1,144 @@
+use crate::util::{sample_hash, sample_seed, sample_threshold};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ConsistentSample;
+
+impl Function for ConsistentSample {
+    fn identifier(&self) -> &'static str {
+        "consistent_sample"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "key",
+                kind: kind::ANY,
+                required: true,
+            },
+            Parameter {
+                keyword: "rate",
+                kind: kind::FLOAT | kind::INTEGER,
+                required: true,
+            },
+            Parameter {
+                keyword: "seed",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "not sampled",
+                source: r#"consistent_sample("4bf92f3577b34da6", 0.5)"#,
+                result: Ok("false"),
+            },
+            Example {
+                title: "sampled with seed",
+                source: r#"consistent_sample("4bf92f3577b34da6", 0.5, seed: 1)"#,
+                result: Ok("true"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let key = arguments.required("key");
+        let threshold = sample_threshold(&mut arguments)?;
+        let seed = sample_seed(&mut arguments)?.unwrap_or(0);
+
+        Ok(Box::new(ConsistentSampleFn {
+            key,
+            threshold,
+            seed,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ConsistentSampleFn {
+    key: Box<dyn Expression>,
+    threshold: u64,
+    seed: u64,
+}
+
+impl Expression for ConsistentSampleFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let key = self.key.resolve(ctx)?;
+        let sampled = match self.threshold {
+            0 => false,
+            u64::MAX => true,
+            threshold => sample_hash(&key, self.seed) < threshold,
+        };
+
+        Ok(sampled.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().infallible().boolean()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::rate_threshold;
+
+    test_function![
+        consistent_sample => ConsistentSample;
+
+        not_sampled {
+            args: func_args![key: "4bf92f3577b34da6", rate: 0.5],
+            want: Ok(false),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        seeded {
+            args: func_args![key: "4bf92f3577b34da6", rate: 0.5, seed: 1],
+            want: Ok(true),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        non_string_key {
+            args: func_args![key: 42, rate: 0.5],
+            want: Ok(true),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        rate_one {
+            args: func_args![key: "4bf92f3577b34da6", rate: 1],
+            want: Ok(true),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        rate_zero {
+            args: func_args![key: "4bf92f3577b34da6", rate: 0.0],
+            want: Ok(false),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+    ];
+
+    #[test]
+    fn seed_zero_matches_seahash() {
+        assert_eq!(sample_hash(&value!("foo"), 0), seahash::hash(b"foo"));
+    }
+
+    #[test]
+    fn invalid_rate() {
+        assert_eq!(rate_threshold(1.5), None);
+        assert_eq!(rate_threshold(-0.1), None);
+        assert_eq!(rate_threshold(f64::NAN), None);
+    }
+
+    #[test]
+    fn samples_at_roughly_the_configured_rate() {
+        let threshold = rate_threshold(0.25).unwrap();
+        let sampled = (0..10_000)
+            .filter(|i| sample_hash(&value!(format!("trace-{}", i)), 42) < threshold)
+            .count();
+
+        assert!((2_250..2_750).contains(&sampled), "{}", sampled);
+    }
+}
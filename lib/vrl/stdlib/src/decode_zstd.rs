@@ -0,0 +1,72 @@
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeZstd;
+
+impl Function for DecodeZstd {
+    fn identifier(&self) -> &'static str {
+        "decode_zstd"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "demo string",
+            source: r#"decode_zstd!(decode_base64!("KLUv/QRYgQAAcGxlYXNlIGRlY29kZSBtZeLOD6U="))"#,
+            result: Ok("please decode me"),
+        }]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(DecodeZstdFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DecodeZstdFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for DecodeZstdFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?.try_bytes()?;
+
+        zstd::stream::decode_all(&value[..])
+            .map(Into::into)
+            .map_err(|err| format!("unable to decode value with zstd: {}", err).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        decode_zstd => DecodeZstd;
+
+        valid {
+            args: func_args![value: Bytes::from_static(b"\x28\xb5\x2f\xfd\x04\x58\x81\x00\x00\x70\x6c\x65\x61\x73\x65\x20\x64\x65\x63\x6f\x64\x65\x20\x6d\x65\xe2\xce\x0f\xa5")],
+            want: Ok(value!("please decode me")),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+
+        invalid {
+            args: func_args![value: "please decode me"],
+            want: Err("unable to decode value with zstd: Unknown frame descriptor"),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+    ];
+}
@@ -0,0 +1,96 @@
+use crate::dns_util::{parse_timeout, resolve_host};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DnsLookup;
+
+impl Function for DnsLookup {
+    fn identifier(&self) -> &'static str {
+        "dns_lookup"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "timeout",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "localhost",
+            source: r#"length(dns_lookup!("localhost")) > 0"#,
+            result: Ok("true"),
+        }]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let timeout = arguments.optional("timeout");
+
+        Ok(Box::new(DnsLookupFn { value, timeout }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DnsLookupFn {
+    value: Box<dyn Expression>,
+    timeout: Option<Box<dyn Expression>>,
+}
+
+impl Expression for DnsLookupFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let host = value.try_bytes_utf8_lossy()?;
+
+        let timeout = self
+            .timeout
+            .as_ref()
+            .map(|expr| expr.resolve(ctx))
+            .transpose()?;
+        let timeout = parse_timeout(timeout)?;
+
+        let addrs = resolve_host(&host, timeout)?;
+
+        Ok(addrs
+            .into_iter()
+            .map(|addr| Value::from(addr.to_string()))
+            .collect::<Vec<_>>()
+            .into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new()
+            .fallible()
+            .array_mapped::<(), Kind>(map! { (): Kind::Bytes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        dns_lookup => DnsLookup;
+
+        ipv4_literal {
+            args: func_args![value: "127.0.0.1"],
+            want: Ok(value!(["127.0.0.1"])),
+            tdef: TypeDef::new().fallible().array_mapped::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        invalid_timeout {
+            args: func_args![value: "127.0.0.1", timeout: 0],
+            want: Err("timeout must be a positive number of seconds"),
+            tdef: TypeDef::new().fallible().array_mapped::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+    ];
+}
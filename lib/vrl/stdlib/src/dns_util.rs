@@ -0,0 +1,204 @@
+use cached::{Cached, TimedSizedCache};
+use dns_lookup_rs::{lookup_addr, lookup_host};
+use lazy_static::lazy_static;
+use std::{
+    net::IpAddr,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// The maximum number of entries held in each of the forward and reverse caches.
+const CACHE_SIZE: usize = 1024;
+
+/// How long, in seconds, a successful resolution is reused before being looked up again.
+const CACHE_TTL_SECS: u64 = 60;
+
+/// The default timeout applied to a single resolution when none is given.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The longest timeout a resolution can be given.
+const MAX_TIMEOUT_SECS: f64 = 60.0;
+
+/// The number of threads running the system resolver.
+const RESOLVER_THREADS: usize = 4;
+
+/// How many lookups can wait for a resolver thread before new ones are
+/// rejected.
+const RESOLVER_QUEUE_SIZE: usize = 64;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    static ref RESOLVER: Result<SyncSender<Job>, String> = spawn_resolver();
+    static ref FORWARD_CACHE: Mutex<TimedSizedCache<String, Vec<IpAddr>>> = Mutex::new(
+        TimedSizedCache::with_size_and_lifespan(CACHE_SIZE, CACHE_TTL_SECS)
+    );
+    static ref REVERSE_CACHE: Mutex<TimedSizedCache<IpAddr, String>> = Mutex::new(
+        TimedSizedCache::with_size_and_lifespan(CACHE_SIZE, CACHE_TTL_SECS)
+    );
+}
+
+/// Resolves `host` into the list of addresses it points to.
+///
+/// Successful results are cached, failures are not, so a transient resolver
+/// outage doesn't pin an error for the lifetime of the cache entry.
+pub(crate) fn resolve_host(host: &str, timeout: Duration) -> Result<Vec<IpAddr>, String> {
+    if let Some(addrs) = FORWARD_CACHE
+        .lock()
+        .expect("dns cache poisoned")
+        .cache_get(&host.to_owned())
+    {
+        return Ok(addrs.clone());
+    }
+
+    let owned = host.to_owned();
+    let mut addrs = with_timeout(timeout, move || lookup_host(&owned))?
+        .map_err(|error| format!("unable to resolve {}: {}", host, error))?;
+
+    // `getaddrinfo` returns one entry per socket type, collapse those.
+    addrs.sort();
+    addrs.dedup();
+
+    FORWARD_CACHE
+        .lock()
+        .expect("dns cache poisoned")
+        .cache_set(host.to_owned(), addrs.clone());
+
+    Ok(addrs)
+}
+
+/// Resolves `addr` into the hostname it is registered under.
+pub(crate) fn resolve_addr(addr: IpAddr, timeout: Duration) -> Result<String, String> {
+    if let Some(host) = REVERSE_CACHE
+        .lock()
+        .expect("dns cache poisoned")
+        .cache_get(&addr)
+    {
+        return Ok(host.clone());
+    }
+
+    let host = with_timeout(timeout, move || lookup_addr(&addr))?
+        .map_err(|error| format!("unable to resolve {}: {}", addr, error))?;
+
+    REVERSE_CACHE
+        .lock()
+        .expect("dns cache poisoned")
+        .cache_set(addr, host.clone());
+
+    Ok(host)
+}
+
+/// Parses the optional `timeout` argument, given in (fractional) seconds.
+pub(crate) fn parse_timeout(value: Option<vrl::Value>) -> Result<Duration, String> {
+    let secs = match value {
+        None => return Ok(DEFAULT_TIMEOUT),
+        Some(vrl::Value::Integer(secs)) => secs as f64,
+        Some(vrl::Value::Float(secs)) => secs.into_inner(),
+        Some(value) => return Err(format!("invalid timeout: {}", value)),
+    };
+
+    if !(secs.is_finite() && secs > 0.0) {
+        Err("timeout must be a positive number of seconds".to_owned())
+    } else if secs > MAX_TIMEOUT_SECS {
+        Err(format!(
+            "timeout must be at most {} seconds",
+            MAX_TIMEOUT_SECS
+        ))
+    } else {
+        Ok(Duration::from_secs_f64(secs))
+    }
+}
+
+/// Starts the threads running the lookups queued on the returned sender.
+fn spawn_resolver() -> Result<SyncSender<Job>, String> {
+    let (tx, rx) = mpsc::sync_channel::<Job>(RESOLVER_QUEUE_SIZE);
+    let rx = Arc::new(Mutex::new(rx));
+
+    for _ in 0..RESOLVER_THREADS {
+        let rx = Arc::clone(&rx);
+        thread::Builder::new()
+            .name("vrl-dns-resolver".to_owned())
+            .spawn(move || run_resolver(&rx))
+            .map_err(|error| format!("unable to spawn resolver: {}", error))?;
+    }
+
+    Ok(tx)
+}
+
+fn run_resolver(rx: &Mutex<Receiver<Job>>) {
+    loop {
+        // Release the lock before running the job, so the other threads can
+        // take the next ones.
+        let job = match rx.lock().expect("dns resolver poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        job();
+    }
+}
+
+/// The system resolver is blocking and has no timeout of its own, so run it on
+/// one of a fixed set of threads and stop waiting for it once `timeout` has
+/// elapsed. A lookup that timed out keeps its thread until it returns, and
+/// lookups are rejected rather than queued without bound while every thread
+/// is busy.
+fn with_timeout<T, F>(timeout: Duration, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let resolver = RESOLVER.as_ref().map_err(Clone::clone)?;
+    let (tx, rx) = mpsc::sync_channel(1);
+
+    resolver
+        .try_send(Box::new(move || {
+            // The receiver is gone if we timed out, nothing left to do then.
+            let _ = tx.send(f());
+        }))
+        .map_err(|error| match error {
+            TrySendError::Full(_) => "too many pending dns resolutions".to_owned(),
+            TrySendError::Disconnected(_) => "dns resolver stopped".to_owned(),
+        })?;
+
+    rx.recv_timeout(timeout)
+        .map_err(|_| format!("dns resolution timed out after {:?}", timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_default() {
+        assert_eq!(parse_timeout(None), Ok(DEFAULT_TIMEOUT));
+    }
+
+    #[test]
+    fn timeout_fractional() {
+        assert_eq!(
+            parse_timeout(Some(vrl::Value::from(0.5))),
+            Ok(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn timeout_invalid() {
+        assert!(parse_timeout(Some(vrl::Value::Integer(0))).is_err());
+        assert!(parse_timeout(Some(vrl::Value::Integer(-1))).is_err());
+        assert!(parse_timeout(Some(vrl::Value::Integer(61))).is_err());
+        assert!(parse_timeout(Some(vrl::Value::from(1e20))).is_err());
+    }
+
+    #[test]
+    fn with_timeout_elapsed() {
+        let result = with_timeout(Duration::from_millis(10), || {
+            thread::sleep(Duration::from_millis(200));
+        });
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+use vrl::prelude::*;
+
+const MAX_COMPRESSION_LEVEL: i64 = 9;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeGzip;
+
+impl Function for EncodeGzip {
+    fn identifier(&self) -> &'static str {
+        "encode_gzip"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "compression_level",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "round trip",
+            source: r#"decode_gzip!(encode_gzip!("please encode me", compression_level: 9))"#,
+            result: Ok("please encode me"),
+        }]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let compression_level = arguments.optional("compression_level");
+
+        Ok(Box::new(EncodeGzipFn {
+            value,
+            compression_level,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EncodeGzipFn {
+    value: Box<dyn Expression>,
+    compression_level: Option<Box<dyn Expression>>,
+}
+
+impl Expression for EncodeGzipFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?.try_bytes()?;
+
+        let level = match &self.compression_level {
+            None => Compression::default(),
+            Some(level) => {
+                let level = level.resolve(ctx)?.try_integer()?;
+                if !(0..=MAX_COMPRESSION_LEVEL).contains(&level) {
+                    return Err(format!(
+                        "compression level must be between 0 and {}, got {}",
+                        MAX_COMPRESSION_LEVEL, level
+                    )
+                    .into());
+                }
+
+                Compression::new(level as u32)
+            }
+        };
+
+        let mut encoder = GzEncoder::new(Vec::new(), level);
+        encoder
+            .write_all(&value)
+            .and_then(|_| encoder.finish())
+            .map(Into::into)
+            .map_err(|err| format!("unable to encode value with gzip: {}", err).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new()
+            .bytes()
+            .with_fallibility(self.compression_level.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::MultiGzDecoder;
+    use shared::TimeZone;
+    use std::io::Read;
+
+    test_function![
+        encode_gzip => EncodeGzip;
+
+        invalid_compression_level {
+            args: func_args![value: "please encode me", compression_level: 10],
+            want: Err("compression level must be between 0 and 9, got 10"),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+    ];
+
+    test_type_def![
+        default {
+            expr: |_| EncodeGzipFn { value: expr!("foo"), compression_level: None },
+            want: TypeDef::new().infallible().bytes(),
+        }
+
+        compression_level {
+            expr: |_| EncodeGzipFn { value: expr!("foo"), compression_level: Some(expr!(1)) },
+            want: TypeDef::new().fallible().bytes(),
+        }
+    ];
+
+    #[test]
+    fn round_trip() {
+        for compression_level in vec![None, Some(expr!(0)), Some(expr!(9))] {
+            let mut state = vrl::state::Runtime::default();
+            let mut object: Value = map![].into();
+            let tz = TimeZone::default();
+            let mut ctx = Context::new(&mut object, &mut state, &tz);
+
+            let encoded = EncodeGzipFn {
+                value: expr!("please encode me"),
+                compression_level,
+            }
+            .resolve(&mut ctx)
+            .unwrap()
+            .try_bytes()
+            .unwrap();
+
+            let mut decoded = String::new();
+            MultiGzDecoder::new(&encoded[..])
+                .read_to_string(&mut decoded)
+                .unwrap();
+
+            assert_eq!(decoded, "please encode me");
+        }
+    }
+}
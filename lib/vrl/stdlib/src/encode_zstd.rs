@@ -0,0 +1,135 @@
+use vrl::prelude::*;
+
+const MAX_COMPRESSION_LEVEL: i64 = 21;
+
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeZstd;
+
+impl Function for EncodeZstd {
+    fn identifier(&self) -> &'static str {
+        "encode_zstd"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "compression_level",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "round trip",
+            source: r#"decode_zstd!(encode_zstd!("please encode me", compression_level: 19))"#,
+            result: Ok("please encode me"),
+        }]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let compression_level = arguments.optional("compression_level");
+
+        Ok(Box::new(EncodeZstdFn {
+            value,
+            compression_level,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EncodeZstdFn {
+    value: Box<dyn Expression>,
+    compression_level: Option<Box<dyn Expression>>,
+}
+
+impl Expression for EncodeZstdFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?.try_bytes()?;
+
+        // Level 0 picks zstd's default level.
+        let level = match &self.compression_level {
+            None => 0,
+            Some(level) => {
+                let level = level.resolve(ctx)?.try_integer()?;
+                if !(1..=MAX_COMPRESSION_LEVEL).contains(&level) {
+                    return Err(format!(
+                        "compression level must be between 1 and {}, got {}",
+                        MAX_COMPRESSION_LEVEL, level
+                    )
+                    .into());
+                }
+
+                level as i32
+            }
+        };
+
+        zstd::stream::encode_all(&value[..], level)
+            .map(Into::into)
+            .map_err(|err| format!("unable to encode value with zstd: {}", err).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new()
+            .bytes()
+            .with_fallibility(self.compression_level.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::TimeZone;
+
+    test_function![
+        encode_zstd => EncodeZstd;
+
+        invalid_compression_level {
+            args: func_args![value: "please encode me", compression_level: 0],
+            want: Err("compression level must be between 1 and 21, got 0"),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+    ];
+
+    test_type_def![
+        default {
+            expr: |_| EncodeZstdFn { value: expr!("foo"), compression_level: None },
+            want: TypeDef::new().infallible().bytes(),
+        }
+
+        compression_level {
+            expr: |_| EncodeZstdFn { value: expr!("foo"), compression_level: Some(expr!(1)) },
+            want: TypeDef::new().fallible().bytes(),
+        }
+    ];
+
+    #[test]
+    fn round_trip() {
+        for compression_level in vec![None, Some(expr!(1)), Some(expr!(21))] {
+            let mut state = vrl::state::Runtime::default();
+            let mut object: Value = map![].into();
+            let tz = TimeZone::default();
+            let mut ctx = Context::new(&mut object, &mut state, &tz);
+
+            let encoded = EncodeZstdFn {
+                value: expr!("please encode me"),
+                compression_level,
+            }
+            .resolve(&mut ctx)
+            .unwrap()
+            .try_bytes()
+            .unwrap();
+
+            let decoded = zstd::stream::decode_all(&encoded[..]).unwrap();
+
+            assert_eq!(decoded, b"please encode me");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Filter;
+
+impl Function for Filter {
+    fn identifier(&self) -> &'static str {
+        "filter"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::OBJECT | kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        Some(closure::Definition {
+            inputs: &[kind::BYTES | kind::INTEGER, kind::ANY],
+            output: kind::BOOLEAN,
+        })
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "object",
+                source: r#"filter({ "a": 1, "b": null }) -> |_key, value| { value != null }"#,
+                result: Ok(r#"{ "a": 1 }"#),
+            },
+            Example {
+                title: "array",
+                source: r#"filter([10, 20, 30]) -> |index, _value| { index != 1 }"#,
+                result: Ok("[10, 30]"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let closure = arguments.required_closure();
+
+        Ok(Box::new(FilterFn { value, closure }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FilterFn {
+    value: Box<dyn Expression>,
+    closure: closure::Closure,
+}
+
+impl FilterFn {
+    fn keep(&self, ctx: &mut Context, key: Value, value: &Value) -> Result<bool> {
+        self.closure
+            .resolve(ctx, vec![key, value.clone()])?
+            .try_boolean()
+            .map_err(Into::into)
+    }
+}
+
+impl Expression for FilterFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        match self.value.resolve(ctx)? {
+            Value::Object(map) => {
+                let mut filtered = BTreeMap::new();
+                for (key, value) in map {
+                    if self.keep(ctx, key.clone().into(), &value)? {
+                        filtered.insert(key, value);
+                    }
+                }
+
+                Ok(filtered.into())
+            }
+            Value::Array(array) => {
+                let mut filtered = Vec::with_capacity(array.len());
+                for (index, value) in array.into_iter().enumerate() {
+                    if self.keep(ctx, (index as i64).into(), &value)? {
+                        filtered.push(value);
+                    }
+                }
+
+                Ok(filtered.into())
+            }
+            value => Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::Object | Kind::Array,
+            }
+            .into()),
+        }
+    }
+
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        if self.value.type_def(state).is_array() {
+            TypeDef::new().array_mapped::<(), Kind>(map! { (): Kind::all() })
+        } else {
+            TypeDef::new().object::<(), Kind>(map! { (): Kind::all() })
+        }
+    }
+}
@@ -10,11 +10,23 @@ impl Function for Flatten {
     }
 
     fn parameters(&self) -> &'static [Parameter] {
-        &[Parameter {
-            keyword: "value",
-            kind: kind::OBJECT | kind::ARRAY,
-            required: true,
-        }]
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT | kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "separator",
+                kind: kind::BYTES,
+                required: false,
+            },
+            Parameter {
+                keyword: "max_depth",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
     }
 
     fn examples(&self) -> &'static [Example] {
@@ -29,28 +41,60 @@ impl Function for Flatten {
                 source: r#"flatten([[true]])"#,
                 result: Ok(r#"[true]"#),
             },
+            Example {
+                title: "separator",
+                source: r#"flatten({ "foo": { "bar": true }}, separator: "_")"#,
+                result: Ok(r#"{ "foo_bar": true }"#),
+            },
+            Example {
+                title: "max depth",
+                source: r#"flatten({ "foo": { "bar": { "baz": true }}}, max_depth: 1)"#,
+                result: Ok(r#"{ "foo.bar": { "baz": true } }"#),
+            },
         ]
     }
 
     fn compile(&self, mut arguments: ArgumentList) -> Compiled {
         let value = arguments.required("value");
-        Ok(Box::new(FlattenFn { value }))
+        let separator = arguments.optional("separator").unwrap_or(expr!("."));
+        let max_depth = arguments.optional("max_depth");
+
+        Ok(Box::new(FlattenFn {
+            value,
+            separator,
+            max_depth,
+        }))
     }
 }
 
 #[derive(Debug, Clone)]
 struct FlattenFn {
     value: Box<dyn Expression>,
+    separator: Box<dyn Expression>,
+    max_depth: Option<Box<dyn Expression>>,
 }
 
 impl Expression for FlattenFn {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let separator = self
+            .separator
+            .resolve(ctx)?
+            .try_bytes_utf8_lossy()?
+            .into_owned();
+        let depth = match &self.max_depth {
+            Some(max_depth) => {
+                let max_depth = max_depth.resolve(ctx)?.try_integer()?;
+                Some(if max_depth < 0 { 0 } else { max_depth as usize })
+            }
+            None => None,
+        };
+
         match self.value.resolve(ctx)? {
             Value::Array(arr) => Ok(Value::Array(
-                ArrayFlatten::new(arr.iter()).cloned().collect(),
+                ArrayFlatten::new(arr.iter(), depth).cloned().collect(),
             )),
             Value::Object(map) => Ok(Value::Object(
-                MapFlatten::new(map.iter())
+                MapFlatten::new(map.iter(), &separator, depth)
                     .map(|(k, v)| (k, v.clone()))
                     .collect(),
             )),
@@ -74,26 +118,44 @@ impl Expression for FlattenFn {
 }
 
 /// An iterator to walk over maps allowing us to flatten nested maps to a single level.
+///
+/// Once `depth` levels of nesting are flattened, any deeper map is returned
+/// as-is.
 struct MapFlatten<'a> {
     values: btree_map::Iter<'a, String, Value>,
     inner: Option<Box<MapFlatten<'a>>>,
     parent: Option<String>,
+    separator: &'a str,
+    depth: Option<usize>,
 }
 
 impl<'a> MapFlatten<'a> {
-    fn new(values: btree_map::Iter<'a, String, Value>) -> Self {
+    fn new(
+        values: btree_map::Iter<'a, String, Value>,
+        separator: &'a str,
+        depth: Option<usize>,
+    ) -> Self {
         Self {
             values,
             inner: None,
             parent: None,
+            separator,
+            depth,
         }
     }
 
-    fn new_from_parent(parent: String, values: btree_map::Iter<'a, String, Value>) -> Self {
+    fn new_from_parent(
+        parent: String,
+        values: btree_map::Iter<'a, String, Value>,
+        separator: &'a str,
+        depth: Option<usize>,
+    ) -> Self {
         Self {
             values,
             inner: None,
             parent: Some(parent),
+            separator,
+            depth,
         }
     }
 
@@ -101,7 +163,7 @@ impl<'a> MapFlatten<'a> {
     fn new_key(&self, key: &str) -> String {
         match self.parent {
             None => key.to_string(),
-            Some(ref parent) => format!("{}.{}", parent, key),
+            Some(ref parent) => format!("{}{}{}", parent, self.separator, key),
         }
     }
 }
@@ -120,10 +182,12 @@ impl<'a> std::iter::Iterator for MapFlatten<'a> {
 
         let next = self.values.next();
         match next {
-            Some((key, Value::Object(value))) => {
+            Some((key, Value::Object(value))) if self.depth != Some(0) => {
                 self.inner = Some(Box::new(MapFlatten::new_from_parent(
                     self.new_key(key),
                     value.iter(),
+                    self.separator,
+                    self.depth.map(|depth| depth - 1),
                 )));
                 self.next()
             }
@@ -134,17 +198,19 @@ impl<'a> std::iter::Iterator for MapFlatten<'a> {
 }
 
 /// Create an iterator that can walk a tree of Array values.
-/// This can be used to flatten the array.
+/// This can be used to flatten the array, up to `depth` levels of nesting.
 struct ArrayFlatten<'a> {
     values: std::slice::Iter<'a, Value>,
     inner: Option<Box<ArrayFlatten<'a>>>,
+    depth: Option<usize>,
 }
 
 impl<'a> ArrayFlatten<'a> {
-    fn new(values: std::slice::Iter<'a, Value>) -> Self {
+    fn new(values: std::slice::Iter<'a, Value>, depth: Option<usize>) -> Self {
         ArrayFlatten {
             values,
             inner: None,
+            depth,
         }
     }
 }
@@ -168,9 +234,12 @@ impl<'a> std::iter::Iterator for ArrayFlatten<'a> {
         // Then iterate over our values.
         let next = self.values.next();
         match next {
-            Some(Value::Array(next)) => {
+            Some(Value::Array(next)) if self.depth != Some(0) => {
                 // Create a new iterator for this child list.
-                self.inner = Some(Box::new(ArrayFlatten::new(next.iter())));
+                self.inner = Some(Box::new(ArrayFlatten::new(
+                    next.iter(),
+                    self.depth.map(|depth| depth - 1),
+                )));
                 self.next()
             }
             _ => next,
@@ -294,5 +363,53 @@ mod test {
             })),
             tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
         }
+
+        separator {
+            args: func_args![value: value!({parent: {child1: 1, child2: {grandchild: 2}}}),
+                             separator: "_"],
+            want: Ok(value!({"parent_child1": 1, "parent_child2_grandchild": 2})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        empty_separator {
+            args: func_args![value: value!({parent: {child: 1}}), separator: ""],
+            want: Ok(value!({"parentchild": 1})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        map_max_depth {
+            args: func_args![value: value!({
+                parent1: {
+                    child1: { grandchild1: { leaf: 1 } },
+                    child2: 2,
+                },
+                parent2: 3,
+            }),
+            max_depth: 2],
+            want: Ok(value!({
+                "parent1.child1.grandchild1": { leaf: 1 },
+                "parent1.child2": 2,
+                parent2: 3,
+            })),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        map_zero_max_depth {
+            args: func_args![value: value!({parent: {child: 1}}), max_depth: 0],
+            want: Ok(value!({parent: {child: 1}})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        map_negative_max_depth {
+            args: func_args![value: value!({parent: {child: 1}}), max_depth: -1],
+            want: Ok(value!({parent: {child: 1}})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        array_max_depth {
+            args: func_args![value: value!([42, [43, [44, [45]]]]), max_depth: 1],
+            want: Ok(value!([42, 43, [44, [45]]])),
+            tdef: TypeDef::new().array_mapped::<(), Kind>(map! { (): Kind::all() }),
+        }
     ];
 }
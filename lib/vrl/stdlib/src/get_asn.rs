@@ -0,0 +1,147 @@
+use crate::util::{geoip_address, open_geoip_database};
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use std::{collections::BTreeMap, sync::Arc};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GetAsn;
+
+impl Function for GetAsn {
+    fn identifier(&self) -> &'static str {
+        "get_asn"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "database",
+                kind: kind::BYTES,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "asn",
+                source: r#"get_asn!("1.128.0.0", database: "../../../tests/data/GeoLite2-ASN-Test.mmdb")"#,
+                result: Ok(
+                    r#"{ "autonomous_system_number": 1221, "autonomous_system_organization": "Telstra Pty Ltd", "isp": null, "organization": null }"#,
+                ),
+            },
+            Example {
+                title: "not found",
+                source: r#"get_asn!("10.1.12.1", database: "../../../tests/data/GeoLite2-ASN-Test.mmdb")"#,
+                result: Ok("null"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let database = open_geoip_database(
+            &mut arguments,
+            &["GeoLite2-ASN", "GeoIP2-ISP"],
+            "database isn't an ASN or ISP database",
+        )?;
+
+        Ok(Box::new(GetAsnFn { value, database }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GetAsnFn {
+    value: Box<dyn Expression>,
+    database: Arc<Reader<Vec<u8>>>,
+}
+
+impl Expression for GetAsnFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let ip = geoip_address(self.value.resolve(ctx)?)?;
+
+        let data = match self.database.lookup::<geoip2::Isp>(ip) {
+            Ok(data) => data,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => return Ok(Value::Null),
+            Err(err) => return Err(format!("unable to look up IP address: {}", err).into()),
+        };
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            "autonomous_system_number".to_owned(),
+            data.autonomous_system_number.map(i64::from).into(),
+        );
+        map.insert(
+            "autonomous_system_organization".to_owned(),
+            data.autonomous_system_organization.into(),
+        );
+        map.insert("isp".to_owned(), data.isp.into());
+        map.insert("organization".to_owned(), data.organization.into());
+
+        Ok(map.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().object(type_def()).add_null()
+    }
+}
+
+fn type_def() -> BTreeMap<&'static str, TypeDef> {
+    map! {
+        "autonomous_system_number": Kind::Integer | Kind::Null,
+        "autonomous_system_organization": Kind::Bytes | Kind::Null,
+        "isp": Kind::Bytes | Kind::Null,
+        "organization": Kind::Bytes | Kind::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASN_DATABASE: &str = "../../../tests/data/GeoLite2-ASN-Test.mmdb";
+    const ISP_DATABASE: &str = "../../../tests/data/GeoIP2-ISP-Test.mmdb";
+
+    test_function![
+        get_asn => GetAsn;
+
+        asn {
+            args: func_args![value: "2600:7000::1", database: ASN_DATABASE],
+            want: Ok(value!({
+                autonomous_system_number: 6939,
+                autonomous_system_organization: "Hurricane Electric, Inc.",
+                isp: null,
+                organization: null,
+            })),
+            tdef: TypeDef::new().fallible().object(type_def()).add_null(),
+        }
+
+        isp {
+            args: func_args![value: "208.192.1.2", database: ISP_DATABASE],
+            want: Ok(value!({
+                autonomous_system_number: 701,
+                autonomous_system_organization: "MCI Communications Services, Inc. d/b/a Verizon Business",
+                isp: "Verizon Business",
+                organization: "Verizon Business",
+            })),
+            tdef: TypeDef::new().fallible().object(type_def()).add_null(),
+        }
+
+        not_found {
+            args: func_args![value: "10.1.12.1", database: ASN_DATABASE],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().object(type_def()).add_null(),
+        }
+
+        invalid_address {
+            args: func_args![value: "foo", database: ASN_DATABASE],
+            want: Err("unable to parse IP address: invalid IP address syntax"),
+            tdef: TypeDef::new().fallible().object(type_def()).add_null(),
+        }
+    ];
+}
@@ -0,0 +1,111 @@
+use crate::util::{geoip_address, open_geoip_database};
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use std::sync::Arc;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GetConnectionType;
+
+impl Function for GetConnectionType {
+    fn identifier(&self) -> &'static str {
+        "get_connection_type"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "database",
+                kind: kind::BYTES,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "connection type",
+                source: r#"get_connection_type!("1.0.1.200", database: "../../../tests/data/GeoIP2-Connection-Type-Test.mmdb")"#,
+                result: Ok(r#""Cellular""#),
+            },
+            Example {
+                title: "not found",
+                source: r#"get_connection_type!("10.1.12.1", database: "../../../tests/data/GeoIP2-Connection-Type-Test.mmdb")"#,
+                result: Ok("null"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let database = open_geoip_database(
+            &mut arguments,
+            &["GeoIP2-Connection-Type"],
+            "database isn't a connection type database",
+        )?;
+
+        Ok(Box::new(GetConnectionTypeFn { value, database }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GetConnectionTypeFn {
+    value: Box<dyn Expression>,
+    database: Arc<Reader<Vec<u8>>>,
+}
+
+impl Expression for GetConnectionTypeFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let ip = geoip_address(self.value.resolve(ctx)?)?;
+
+        match self.database.lookup::<geoip2::ConnectionType>(ip) {
+            Ok(data) => Ok(data.connection_type.into()),
+            Err(MaxMindDBError::AddressNotFoundError(_)) => Ok(Value::Null),
+            Err(err) => Err(format!("unable to look up IP address: {}", err).into()),
+        }
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().bytes().add_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATABASE: &str = "../../../tests/data/GeoIP2-Connection-Type-Test.mmdb";
+
+    test_function![
+        get_connection_type => GetConnectionType;
+
+        ipv4 {
+            args: func_args![value: "1.0.4.1", database: DATABASE],
+            want: Ok(value!("Dialup")),
+            tdef: TypeDef::new().fallible().bytes().add_null(),
+        }
+
+        ipv6 {
+            args: func_args![value: "2003::1", database: DATABASE],
+            want: Ok(value!("Cable/DSL")),
+            tdef: TypeDef::new().fallible().bytes().add_null(),
+        }
+
+        not_found {
+            args: func_args![value: "10.1.12.1", database: DATABASE],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().bytes().add_null(),
+        }
+
+        invalid_address {
+            args: func_args![value: "foo", database: DATABASE],
+            want: Err("unable to parse IP address: invalid IP address syntax"),
+            tdef: TypeDef::new().fallible().bytes().add_null(),
+        }
+    ];
+}
@@ -0,0 +1,171 @@
+use hmac_rs::{Mac, NewMac};
+use sha_2::{Sha256, Sha512};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Hmac;
+
+impl Function for Hmac {
+    fn identifier(&self) -> &'static str {
+        "hmac"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "key",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "algorithm",
+                kind: kind::BYTES,
+                required: false,
+            },
+            Parameter {
+                keyword: "encoding",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "default algorithm",
+                source: r#"hmac("The quick brown fox jumps over the lazy dog", "key")"#,
+                result: Ok("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"),
+            },
+            Example {
+                title: "custom algorithm and encoding",
+                source: r#"hmac("The quick brown fox jumps over the lazy dog", "key", algorithm: "SHA-256", encoding: "base64")"#,
+                result: Ok("97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let algorithms = vec![value!("SHA-256"), value!("SHA-512"), value!("BLAKE3")];
+        let encodings = vec![value!("hex"), value!("base64")];
+
+        let value = arguments.required("value");
+        let key = arguments.required("key");
+        let algorithm = arguments
+            .optional_enum("algorithm", &algorithms)?
+            .unwrap_or_else(|| value!("SHA-256"))
+            .try_bytes()
+            .expect("algorithm not bytes");
+        let encoding = arguments
+            .optional_enum("encoding", &encodings)?
+            .unwrap_or_else(|| value!("hex"))
+            .try_bytes()
+            .expect("encoding not bytes");
+
+        Ok(Box::new(HmacFn {
+            value,
+            key,
+            algorithm,
+            encoding,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HmacFn {
+    value: Box<dyn Expression>,
+    key: Box<dyn Expression>,
+    algorithm: Bytes,
+    encoding: Bytes,
+}
+
+impl Expression for HmacFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?.try_bytes()?;
+        let key = self.key.resolve(ctx)?.try_bytes()?;
+
+        let code = match self.algorithm.as_ref() {
+            b"SHA-256" => sign::<hmac_rs::Hmac<Sha256>>(&key, &value),
+            b"SHA-512" => sign::<hmac_rs::Hmac<Sha512>>(&key, &value),
+            b"BLAKE3" => sign::<hmac_rs::Hmac<blake3::Hasher>>(&key, &value),
+            _ => unreachable!("enum invariant"),
+        };
+
+        let encoded = match self.encoding.as_ref() {
+            b"hex" => hex::encode(code),
+            b"base64" => base64::encode(code),
+            _ => unreachable!("enum invariant"),
+        };
+
+        Ok(encoded.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().infallible().bytes()
+    }
+}
+
+#[inline]
+fn sign<M: Mac + NewMac>(key: &[u8], value: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, longer keys are hashed first.
+    let mut mac = M::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(value);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        hmac => Hmac;
+
+        sha_256 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog",
+                             key: "key"
+            ],
+            want: Ok("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        sha_256_base64 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog",
+                             key: "key",
+                             encoding: "base64"
+            ],
+            want: Ok("97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        sha_512 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog",
+                             key: "key",
+                             algorithm: "SHA-512"
+            ],
+            want: Ok("b42af09057bac1e2d41708e48a902e09b5ff7f12ab428a4fe86653c73dd248fb82f948a549f7b791a5b41915ee4d1ec3935357e4e2317250d0372afa2ebeeb3a"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        blake3 {
+            args: func_args![value: "The quick brown fox jumps over the lazy dog",
+                             key: "key",
+                             algorithm: "BLAKE3"
+            ],
+            want: Ok("3742da5c89b7c0c376c0af2f211bd59f97aeaa282f21dccb0c0308b7703ac959"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        empty_key {
+            args: func_args![value: "foo",
+                             key: ""
+            ],
+            want: Ok("0c0d98f7e3d9d45e72e8877bc1b104327efb9c07b18f2ffeced76d81307f1fff"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+    ];
+}
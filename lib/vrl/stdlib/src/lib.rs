@@ -12,20 +12,34 @@ mod assert_eq;
 mod boolean;
 #[cfg(feature = "ceil")]
 mod ceil;
+#[cfg(feature = "checked_add")]
+mod checked_add;
 #[cfg(feature = "compact")]
 mod compact;
+#[cfg(feature = "consistent_sample")]
+mod consistent_sample;
 #[cfg(feature = "contains")]
 mod contains;
 #[cfg(feature = "decode_base64")]
 mod decode_base64;
+#[cfg(feature = "decode_gzip")]
+mod decode_gzip;
 #[cfg(feature = "decode_percent")]
 mod decode_percent;
+#[cfg(feature = "decode_zstd")]
+mod decode_zstd;
 #[cfg(feature = "del")]
 mod del;
+#[cfg(feature = "dns_lookup")]
+mod dns_lookup;
+#[cfg(any(feature = "dns_lookup", feature = "reverse_dns"))]
+mod dns_util;
 #[cfg(feature = "downcase")]
 mod downcase;
 #[cfg(feature = "encode_base64")]
 mod encode_base64;
+#[cfg(feature = "encode_gzip")]
+mod encode_gzip;
 #[cfg(feature = "encode_json")]
 mod encode_json;
 #[cfg(feature = "encode_key_value")]
@@ -34,10 +48,14 @@ mod encode_key_value;
 mod encode_logfmt;
 #[cfg(feature = "encode_percent")]
 mod encode_percent;
+#[cfg(feature = "encode_zstd")]
+mod encode_zstd;
 #[cfg(feature = "ends_with")]
 mod ends_with;
 #[cfg(feature = "exists")]
 mod exists;
+#[cfg(feature = "filter")]
+mod filter;
 #[cfg(feature = "flatten")]
 mod flatten;
 #[cfg(feature = "float")]
@@ -50,10 +68,16 @@ mod format_int;
 mod format_number;
 #[cfg(feature = "format_timestamp")]
 mod format_timestamp;
+#[cfg(feature = "get_asn")]
+mod get_asn;
+#[cfg(feature = "get_connection_type")]
+mod get_connection_type;
 #[cfg(feature = "get_env_var")]
 mod get_env_var;
 #[cfg(feature = "get_hostname")]
 mod get_hostname;
+#[cfg(feature = "hmac")]
+mod hmac;
 #[cfg(feature = "includes")]
 mod includes;
 #[cfg(feature = "integer")]
@@ -102,6 +126,10 @@ mod log;
     feature = "parse_nginx_log"
 ))]
 mod log_util;
+#[cfg(feature = "map_keys")]
+mod map_keys;
+#[cfg(feature = "map_values")]
+mod map_values;
 #[cfg(feature = "match")]
 mod r#match;
 #[cfg(feature = "match_any")]
@@ -110,10 +138,18 @@ mod match_any;
 mod match_array;
 #[cfg(feature = "match_datadog_query")]
 mod match_datadog_query;
+#[cfg(feature = "max")]
+mod max;
 #[cfg(feature = "md5")]
 mod md5;
+#[cfg(feature = "mean")]
+mod mean;
+#[cfg(feature = "median")]
+mod median;
 #[cfg(feature = "merge")]
 mod merge;
+#[cfg(feature = "min")]
+mod min;
 #[cfg(feature = "now")]
 mod now;
 #[cfg(feature = "object")]
@@ -160,6 +196,8 @@ mod parse_regex;
 mod parse_regex_all;
 #[cfg(feature = "parse_ruby_hash")]
 mod parse_ruby_hash;
+#[cfg(feature = "parse_snowflake_id")]
+mod parse_snowflake_id;
 #[cfg(feature = "parse_syslog")]
 mod parse_syslog;
 #[cfg(feature = "parse_timestamp")]
@@ -172,14 +210,24 @@ mod parse_url;
 mod parse_user_agent;
 #[cfg(feature = "parse_xml")]
 mod parse_xml;
+#[cfg(feature = "percentile")]
+mod percentile;
 #[cfg(feature = "push")]
 mod push;
+#[cfg(feature = "query")]
+mod query;
 #[cfg(feature = "redact")]
 mod redact;
 #[cfg(feature = "replace")]
 mod replace;
+#[cfg(feature = "reverse_dns")]
+mod reverse_dns;
 #[cfg(feature = "round")]
 mod round;
+#[cfg(feature = "sample")]
+mod sample;
+#[cfg(feature = "saturating_mul")]
+mod saturating_mul;
 #[cfg(feature = "sha1")]
 mod sha1;
 #[cfg(feature = "sha2")]
@@ -198,10 +246,14 @@ mod string;
 mod strip_ansi_escape_codes;
 #[cfg(feature = "strip_whitespace")]
 mod strip_whitespace;
+#[cfg(feature = "sum")]
+mod sum;
 #[cfg(feature = "tag_types_externally")]
 mod tag_types_externally;
 #[cfg(feature = "timestamp")]
 mod timestamp;
+#[cfg(feature = "to_bigint")]
+mod to_bigint;
 #[cfg(feature = "to_bool")]
 mod to_bool;
 #[cfg(feature = "to_float")]
@@ -224,6 +276,10 @@ mod to_timestamp;
 mod to_unix_timestamp;
 #[cfg(feature = "truncate")]
 mod truncate;
+#[cfg(feature = "ulid")]
+mod ulid;
+#[cfg(feature = "unflatten")]
+mod unflatten;
 #[cfg(feature = "unnest")]
 mod unnest;
 #[cfg(feature = "upcase")]
@@ -231,6 +287,8 @@ mod upcase;
 #[cfg(feature = "uuid_v4")]
 mod uuid_v4;
 
+#[cfg(feature = "uuid_v7")]
+mod uuid_v7;
 // -----------------------------------------------------------------------------
 
 #[cfg(feature = "array")]
@@ -249,20 +307,32 @@ pub use assert_eq::AssertEq;
 pub use boolean::Boolean;
 #[cfg(feature = "ceil")]
 pub use ceil::Ceil;
+#[cfg(feature = "checked_add")]
+pub use checked_add::CheckedAdd;
 #[cfg(feature = "compact")]
 pub use compact::Compact;
+#[cfg(feature = "consistent_sample")]
+pub use consistent_sample::ConsistentSample;
 #[cfg(feature = "contains")]
 pub use contains::Contains;
 #[cfg(feature = "decode_base64")]
 pub use decode_base64::DecodeBase64;
+#[cfg(feature = "decode_gzip")]
+pub use decode_gzip::DecodeGzip;
 #[cfg(feature = "decode_percent")]
 pub use decode_percent::DecodePercent;
+#[cfg(feature = "decode_zstd")]
+pub use decode_zstd::DecodeZstd;
 #[cfg(feature = "del")]
 pub use del::Del;
+#[cfg(feature = "dns_lookup")]
+pub use dns_lookup::DnsLookup;
 #[cfg(feature = "downcase")]
 pub use downcase::Downcase;
 #[cfg(feature = "encode_base64")]
 pub use encode_base64::EncodeBase64;
+#[cfg(feature = "encode_gzip")]
+pub use encode_gzip::EncodeGzip;
 #[cfg(feature = "encode_json")]
 pub use encode_json::EncodeJson;
 #[cfg(feature = "encode_key_value")]
@@ -271,10 +341,14 @@ pub use encode_key_value::EncodeKeyValue;
 pub use encode_logfmt::EncodeLogfmt;
 #[cfg(feature = "encode_percent")]
 pub use encode_percent::EncodePercent;
+#[cfg(feature = "encode_zstd")]
+pub use encode_zstd::EncodeZstd;
 #[cfg(feature = "ends_with")]
 pub use ends_with::EndsWith;
 #[cfg(feature = "exists")]
 pub use exists::Exists;
+#[cfg(feature = "filter")]
+pub use filter::Filter;
 #[cfg(feature = "flatten")]
 pub use flatten::Flatten;
 #[cfg(feature = "float")]
@@ -287,10 +361,16 @@ pub use format_int::FormatInt;
 pub use format_number::FormatNumber;
 #[cfg(feature = "format_timestamp")]
 pub use format_timestamp::FormatTimestamp;
+#[cfg(feature = "get_asn")]
+pub use get_asn::GetAsn;
+#[cfg(feature = "get_connection_type")]
+pub use get_connection_type::GetConnectionType;
 #[cfg(feature = "get_env_var")]
 pub use get_env_var::GetEnvVar;
 #[cfg(feature = "get_hostname")]
 pub use get_hostname::GetHostname;
+#[cfg(feature = "hmac")]
+pub use hmac::Hmac;
 #[cfg(feature = "includes")]
 pub use includes::Includes;
 #[cfg(feature = "integer")]
@@ -333,14 +413,26 @@ pub use join::Join;
 pub use length::Length;
 #[cfg(feature = "log")]
 pub use log::Log;
+#[cfg(feature = "map_keys")]
+pub use map_keys::MapKeys;
+#[cfg(feature = "map_values")]
+pub use map_values::MapValues;
 #[cfg(feature = "match_any")]
 pub use match_any::MatchAny;
 #[cfg(feature = "match_array")]
 pub use match_array::MatchArray;
 #[cfg(feature = "match_datadog_query")]
 pub use match_datadog_query::MatchDatadogQuery;
+#[cfg(feature = "max")]
+pub use max::Max;
+#[cfg(feature = "mean")]
+pub use mean::Mean;
+#[cfg(feature = "median")]
+pub use median::Median;
 #[cfg(feature = "merge")]
 pub use merge::Merge;
+#[cfg(feature = "min")]
+pub use min::Min;
 #[cfg(feature = "now")]
 pub use now::Now;
 #[cfg(feature = "object")]
@@ -387,6 +479,8 @@ pub use parse_regex::ParseRegex;
 pub use parse_regex_all::ParseRegexAll;
 #[cfg(feature = "parse_ruby_hash")]
 pub use parse_ruby_hash::ParseRubyHash;
+#[cfg(feature = "parse_snowflake_id")]
+pub use parse_snowflake_id::ParseSnowflakeId;
 #[cfg(feature = "parse_syslog")]
 pub use parse_syslog::ParseSyslog;
 #[cfg(feature = "parse_timestamp")]
@@ -399,16 +493,26 @@ pub use parse_url::ParseUrl;
 pub use parse_user_agent::ParseUserAgent;
 #[cfg(feature = "parse_xml")]
 pub use parse_xml::ParseXml;
+#[cfg(feature = "percentile")]
+pub use percentile::Percentile;
 #[cfg(feature = "push")]
 pub use push::Push;
 #[cfg(feature = "match")]
 pub use r#match::Match;
+#[cfg(feature = "query")]
+pub use query::Query;
 #[cfg(feature = "redact")]
 pub use redact::Redact;
 #[cfg(feature = "replace")]
 pub use replace::Replace;
+#[cfg(feature = "reverse_dns")]
+pub use reverse_dns::ReverseDns;
 #[cfg(feature = "round")]
 pub use round::Round;
+#[cfg(feature = "sample")]
+pub use sample::Sample;
+#[cfg(feature = "saturating_mul")]
+pub use saturating_mul::SaturatingMul;
 #[cfg(feature = "sha2")]
 pub use sha2::Sha2;
 #[cfg(feature = "sha3")]
@@ -425,10 +529,14 @@ pub use string::String;
 pub use strip_ansi_escape_codes::StripAnsiEscapeCodes;
 #[cfg(feature = "strip_whitespace")]
 pub use strip_whitespace::StripWhitespace;
+#[cfg(feature = "sum")]
+pub use sum::Sum;
 #[cfg(feature = "tag_types_externally")]
 pub use tag_types_externally::TagTypesExternally;
 #[cfg(feature = "timestamp")]
 pub use timestamp::Timestamp;
+#[cfg(feature = "to_bigint")]
+pub use to_bigint::ToBigint;
 #[cfg(feature = "to_bool")]
 pub use to_bool::ToBool;
 #[cfg(feature = "to_float")]
@@ -451,6 +559,10 @@ pub use to_timestamp::ToTimestamp;
 pub use to_unix_timestamp::ToUnixTimestamp;
 #[cfg(feature = "truncate")]
 pub use truncate::Truncate;
+#[cfg(feature = "ulid")]
+pub use ulid::Ulid;
+#[cfg(feature = "unflatten")]
+pub use unflatten::Unflatten;
 #[cfg(feature = "unnest")]
 pub use unnest::Unnest;
 #[cfg(feature = "upcase")]
@@ -458,6 +570,8 @@ pub use upcase::Upcase;
 #[cfg(feature = "uuid_v4")]
 pub use uuid_v4::UuidV4;
 
+#[cfg(feature = "uuid_v7")]
+pub use uuid_v7::UuidV7;
 pub fn all() -> Vec<Box<dyn vrl::Function>> {
     vec![
         #[cfg(feature = "array")]
@@ -472,20 +586,32 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Boolean),
         #[cfg(feature = "ceil")]
         Box::new(Ceil),
+        #[cfg(feature = "checked_add")]
+        Box::new(CheckedAdd),
         #[cfg(feature = "compact")]
         Box::new(Compact),
+        #[cfg(feature = "consistent_sample")]
+        Box::new(ConsistentSample),
         #[cfg(feature = "contains")]
         Box::new(Contains),
         #[cfg(feature = "decode_base64")]
         Box::new(DecodeBase64),
+        #[cfg(feature = "decode_gzip")]
+        Box::new(DecodeGzip),
         #[cfg(feature = "decode_percent")]
         Box::new(DecodePercent),
+        #[cfg(feature = "decode_zstd")]
+        Box::new(DecodeZstd),
         #[cfg(feature = "del")]
         Box::new(Del),
+        #[cfg(feature = "dns_lookup")]
+        Box::new(DnsLookup),
         #[cfg(feature = "downcase")]
         Box::new(Downcase),
         #[cfg(feature = "encode_base64")]
         Box::new(EncodeBase64),
+        #[cfg(feature = "encode_gzip")]
+        Box::new(EncodeGzip),
         #[cfg(feature = "encode_json")]
         Box::new(EncodeJson),
         #[cfg(feature = "encode_key_value")]
@@ -494,10 +620,14 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(EncodeLogfmt),
         #[cfg(feature = "encode_percent")]
         Box::new(EncodePercent),
+        #[cfg(feature = "encode_zstd")]
+        Box::new(EncodeZstd),
         #[cfg(feature = "ends_with")]
         Box::new(EndsWith),
         #[cfg(feature = "exists")]
         Box::new(Exists),
+        #[cfg(feature = "filter")]
+        Box::new(Filter),
         #[cfg(feature = "flatten")]
         Box::new(Flatten),
         #[cfg(feature = "float")]
@@ -510,10 +640,16 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(FormatNumber),
         #[cfg(feature = "format_timestamp")]
         Box::new(FormatTimestamp),
+        #[cfg(feature = "get_asn")]
+        Box::new(GetAsn),
+        #[cfg(feature = "get_connection_type")]
+        Box::new(GetConnectionType),
         #[cfg(feature = "get_env_var")]
         Box::new(GetEnvVar),
         #[cfg(feature = "get_hostname")]
         Box::new(GetHostname),
+        #[cfg(feature = "hmac")]
+        Box::new(Hmac),
         #[cfg(feature = "includes")]
         Box::new(Includes),
         #[cfg(feature = "integer")]
@@ -556,6 +692,10 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(Length),
         #[cfg(feature = "log")]
         Box::new(Log),
+        #[cfg(feature = "map_keys")]
+        Box::new(MapKeys),
+        #[cfg(feature = "map_values")]
+        Box::new(MapValues),
         #[cfg(feature = "match")]
         Box::new(Match),
         #[cfg(feature = "match_any")]
@@ -564,10 +704,18 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(MatchArray),
         #[cfg(feature = "match_datadog_query")]
         Box::new(MatchDatadogQuery),
+        #[cfg(feature = "max")]
+        Box::new(Max),
         #[cfg(feature = "md5")]
         Box::new(Md5),
+        #[cfg(feature = "mean")]
+        Box::new(Mean),
+        #[cfg(feature = "median")]
+        Box::new(Median),
         #[cfg(feature = "merge")]
         Box::new(Merge),
+        #[cfg(feature = "min")]
+        Box::new(Min),
         #[cfg(feature = "now")]
         Box::new(Now),
         // We are not sure if this is the way we want to expose this functionality yet
@@ -616,6 +764,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseRegexAll),
         #[cfg(feature = "parse_ruby_hash")]
         Box::new(ParseRubyHash),
+        #[cfg(feature = "parse_snowflake_id")]
+        Box::new(ParseSnowflakeId),
         #[cfg(feature = "parse_syslog")]
         Box::new(ParseSyslog),
         #[cfg(feature = "parse_timestamp")]
@@ -628,6 +778,8 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ParseUserAgent),
         #[cfg(feature = "parse_xml")]
         Box::new(ParseXml),
+        #[cfg(feature = "percentile")]
+        Box::new(Percentile),
         #[cfg(feature = "push")]
         Box::new(Push),
         #[cfg(feature = "match")]
@@ -636,12 +788,20 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(MatchAny),
         #[cfg(feature = "match_array")]
         Box::new(MatchArray),
+        #[cfg(feature = "query")]
+        Box::new(Query),
         #[cfg(feature = "redact")]
         Box::new(Redact),
         #[cfg(feature = "replace")]
         Box::new(Replace),
+        #[cfg(feature = "reverse_dns")]
+        Box::new(ReverseDns),
         #[cfg(feature = "round")]
         Box::new(Round),
+        #[cfg(feature = "sample")]
+        Box::new(Sample),
+        #[cfg(feature = "saturating_mul")]
+        Box::new(SaturatingMul),
         #[cfg(feature = "sha1")]
         Box::new(Sha1),
         #[cfg(feature = "sha2")]
@@ -660,10 +820,14 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(StripAnsiEscapeCodes),
         #[cfg(feature = "strip_whitespace")]
         Box::new(StripWhitespace),
+        #[cfg(feature = "sum")]
+        Box::new(Sum),
         #[cfg(feature = "tag_types_externally")]
         Box::new(TagTypesExternally),
         #[cfg(feature = "timestamp")]
         Box::new(Timestamp),
+        #[cfg(feature = "to_bigint")]
+        Box::new(ToBigint),
         #[cfg(feature = "to_bool")]
         Box::new(ToBool),
         #[cfg(feature = "to_float")]
@@ -686,11 +850,17 @@ pub fn all() -> Vec<Box<dyn vrl::Function>> {
         Box::new(ToUnixTimestamp),
         #[cfg(feature = "truncate")]
         Box::new(Truncate),
+        #[cfg(feature = "ulid")]
+        Box::new(Ulid),
+        #[cfg(feature = "unflatten")]
+        Box::new(Unflatten),
         #[cfg(feature = "unnest")]
         Box::new(Unnest),
         #[cfg(feature = "upcase")]
         Box::new(Upcase),
         #[cfg(feature = "uuid_v4")]
         Box::new(UuidV4),
+        #[cfg(feature = "uuid_v7")]
+        Box::new(UuidV7),
     ]
 }
@@ -57,18 +57,20 @@ lazy_static! {
     "#)
     .expect("failed compiling regex for combined log");
 
-    // It is possible to customise the format output by apache. This function just handles the default defined here.
+    // It is possible to customise the format output by apache. This function handles the default defined here
     // https://github.com/mingrammer/flog/blob/9bc83b14408ca446e934c32e4a88a81a46e78d83/log.go#L16
+    // as well as the default of Apache 2.4, where the module and client are optional:
+    // https://httpd.apache.org/docs/2.4/mod/core.html#errorlogformat
     pub static ref REGEX_APACHE_ERROR_LOG: Regex = Regex::new(
         r#"(?x)                                     # Ignore whitespace and comments in the regex expression.
         ^\s*                                        # Start with any number of whitespaces.
         (-|\[(-|(?P<timestamp>[^\[]*))\])\s+        # Match `-` or `[` followed by `-` or any character except `]`, `]` and at least one whitespace.
-        (-|\[(-|(?P<module>[^:]*):                  # Match `-` or `[` followed by `-` or any character except `:`.
-        (?P<severity>[^\[]*))\])\s+                 # Match ary character except `]`, `]` and at least one whitespace.
-        (-|\[\s*pid\s*(-|(?P<pid>[^:]*)             # Match `-` or `[` followed by `pid`, `-` or any character except `:`.
-        (:\s*tid\s*(?P<thread>[^\[]*))?)\])\s       # Match `tid` followed by any character except `]`, `]` and at least one whitespace.
-        (-|\[\s*client\s*(-|(?P<client>.*:?):       # Match `-` or `[` followed by `client`, `-` or any character until the first or last `:` for the port.
-        (?P<port>[^\[]*))\])\s                      # Match `-` or `[` followed by `-` or any character except `]`, `]` and at least one whitespace.
+        (-|\[(-|((?P<module>[^:\]]*):)?             # Match `-` or `[` followed by `-` or optionally any character except `:` and `:`.
+        (?P<severity>[^\[\]]*))\])\s+               # Match ary character except `]`, `]` and at least one whitespace.
+        (-|\[\s*pid\s*(-|(?P<pid>[^:\]]*)            # Match `-` or `[` followed by `pid`, `-` or any character except `:`.
+        (:\s*tid\s*(?P<thread>[^\[\]]*))?)\])\s+     # Match `tid` followed by any character except `]`, `]` and at least one whitespace.
+        (-\s+|\[\s*client\s*(-|(?P<client>.*?)      # Optionally match `-` or `[` followed by `client`, `-` or any character...
+        (:(?P<port>\d+))?)\]\s+)?                    # ...until the last `:` for the optional port, `]` and at least one whitespace.
         (-|(?P<message>.*))                         # Match `-` or any character.
         \s*$                                        # Match any number of whitespaces (to be discarded).
     "#)
@@ -174,3 +176,421 @@ pub fn log_fields(
         .collect::<std::result::Result<BTreeMap<String, Value>, String>>()?
         .into())
 }
+
+/// Compiles the `log_format` argument with `compile` if `format` is
+/// `custom`, which requires it.
+pub fn custom_log_format(
+    arguments: &mut ArgumentList,
+    format: &[u8],
+    compile: fn(&str) -> std::result::Result<CustomLogFormat, &'static str>,
+) -> std::result::Result<Option<CustomLogFormat>, vrl::function::Error> {
+    let log_format = match arguments.optional_literal("log_format")? {
+        Some(log_format) => log_format.to_value(),
+        None if format == b"custom" => {
+            return Err(vrl::function::Error::InvalidArgument {
+                keyword: "format",
+                value: value!("custom"),
+                error: "custom format requires a `log_format`",
+            })
+        }
+        None => return Ok(None),
+    };
+
+    if format != b"custom" {
+        return Err(vrl::function::Error::InvalidArgument {
+            keyword: "log_format",
+            value: log_format,
+            error: "`log_format` can only be used with the custom format",
+        });
+    }
+
+    let custom = log_format
+        .try_bytes_utf8_lossy()
+        .map_err(|_| "log format must be a string")
+        .and_then(|format| compile(&format))
+        .map_err(|error| vrl::function::Error::InvalidArgument {
+            keyword: "log_format",
+            value: log_format.clone(),
+            error,
+        })?;
+
+    Ok(Some(custom))
+}
+
+/// The type a field of a custom log format is converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Bytes,
+    Integer,
+    Float,
+    Timestamp,
+}
+
+impl FieldKind {
+    /// The pattern matching a value of this kind, `quoted` if the value is
+    /// enclosed in double quotes in the log format.
+    fn pattern(self, quoted: bool) -> &'static str {
+        match self {
+            FieldKind::Integer => r"\d+",
+            FieldKind::Float => r"\d+(?:\.\d+)?",
+            FieldKind::Bytes | FieldKind::Timestamp if quoted => r#"(?:[^"\\]|\\.)*?"#,
+            FieldKind::Bytes | FieldKind::Timestamp => r"\S+?",
+        }
+    }
+
+    fn kind(self) -> Kind {
+        match self {
+            FieldKind::Bytes => Kind::Bytes,
+            FieldKind::Integer => Kind::Integer,
+            FieldKind::Float => Kind::Float,
+            FieldKind::Timestamp => Kind::Timestamp,
+        }
+    }
+}
+
+/// A field produced by a directive of a custom log format.
+struct Directive {
+    name: String,
+    kind: FieldKind,
+    timestamp_format: Option<String>,
+}
+
+impl Directive {
+    fn new(name: impl Into<String>, kind: FieldKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            timestamp_format: None,
+        }
+    }
+
+    fn timestamp(format: impl Into<String>) -> Self {
+        Self {
+            name: "timestamp".to_owned(),
+            kind: FieldKind::Timestamp,
+            timestamp_format: Some(format.into()),
+        }
+    }
+}
+
+/// A log format as configured on the server, with Apache's `LogFormat` or
+/// Nginx's `log_format` directives, compiled to a regex.
+///
+/// Every field is typed according to the directive it is produced by, and
+/// fields logged as `-` are left out, same as for the predefined formats.
+#[derive(Debug, Clone)]
+pub struct CustomLogFormat {
+    regex: Regex,
+    fields: BTreeMap<String, FieldKind>,
+    timestamp_format: Option<String>,
+}
+
+impl CustomLogFormat {
+    /// Compiles an Apache `LogFormat`, such as
+    /// `%h %l %u %t "%r" %>s %b "%{Referer}i" "%{User-agent}i"`.
+    pub fn apache(format: &str) -> std::result::Result<Self, &'static str> {
+        let mut builder = CustomLogFormatBuilder::default();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                builder.literal(c);
+                continue;
+            }
+
+            // Skip the modifiers of the directive, i.e. conditions on the
+            // status code and whether to log the original or final request.
+            while matches!(chars.peek(), Some(c) if "<>!,0123456789".contains(*c)) {
+                chars.next();
+            }
+
+            let argument = if chars.peek() == Some(&'{') {
+                chars.next();
+                let argument = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                Some(argument)
+            } else {
+                None
+            };
+
+            let directive = match (chars.next(), argument) {
+                (Some('%'), None) => {
+                    builder.literal('%');
+                    continue;
+                }
+                (Some('t'), None) => {
+                    builder.literal('[');
+                    builder.directive(Directive::timestamp("%d/%b/%Y:%T %z"))?;
+                    builder.literal(']');
+                    continue;
+                }
+                (Some('t'), Some(argument)) => {
+                    let argument = argument
+                        .trim_start_matches("begin:")
+                        .trim_start_matches("end:");
+
+                    match argument {
+                        "sec" | "msec" | "usec" | "msec_frac" | "usec_frac" => {
+                            Directive::new("timestamp", FieldKind::Integer)
+                        }
+                        format => Directive::timestamp(format),
+                    }
+                }
+                (Some('r'), None) => {
+                    builder.request("message")?;
+                    continue;
+                }
+                (Some(c @ 'i'), Some(header))
+                | (Some(c @ 'o'), Some(header))
+                | (Some(c @ 'e'), Some(header))
+                | (Some(c @ 'n'), Some(header))
+                | (Some(c @ 'C'), Some(header)) => {
+                    let name = match (c, header.to_lowercase().as_str()) {
+                        ('i', "referer") => "referrer".to_owned(),
+                        ('i', "user-agent") => "agent".to_owned(),
+                        (_, name) => name.replace('-', "_"),
+                    };
+                    Directive::new(name, FieldKind::Bytes)
+                }
+                (Some(c), _) => match c {
+                    'a' => Directive::new("client", FieldKind::Bytes),
+                    'A' => Directive::new("local_address", FieldKind::Bytes),
+                    'b' | 'B' => Directive::new("size", FieldKind::Integer),
+                    'D' => Directive::new("duration_us", FieldKind::Integer),
+                    'f' => Directive::new("filename", FieldKind::Bytes),
+                    'h' => Directive::new("host", FieldKind::Bytes),
+                    'H' => Directive::new("protocol", FieldKind::Bytes),
+                    'I' => Directive::new("bytes_received", FieldKind::Integer),
+                    'k' => Directive::new("keepalive_requests", FieldKind::Integer),
+                    'l' => Directive::new("identity", FieldKind::Bytes),
+                    'L' => Directive::new("log_id", FieldKind::Bytes),
+                    'm' => Directive::new("method", FieldKind::Bytes),
+                    'O' => Directive::new("bytes_sent", FieldKind::Integer),
+                    'p' => Directive::new("port", FieldKind::Integer),
+                    'P' => Directive::new("pid", FieldKind::Integer),
+                    'q' => Directive::new("query", FieldKind::Bytes),
+                    'R' => Directive::new("handler", FieldKind::Bytes),
+                    's' => Directive::new("status", FieldKind::Integer),
+                    'S' => Directive::new("bytes_transferred", FieldKind::Integer),
+                    'T' => Directive::new("duration_s", FieldKind::Integer),
+                    'u' => Directive::new("user", FieldKind::Bytes),
+                    'U' => Directive::new("path", FieldKind::Bytes),
+                    'v' | 'V' => Directive::new("server_name", FieldKind::Bytes),
+                    'X' => Directive::new("connection_status", FieldKind::Bytes),
+                    _ => return Err("unsupported directive in log format"),
+                },
+                (None, _) => return Err("log format ends with an incomplete directive"),
+            };
+
+            builder.directive(directive)?;
+        }
+
+        builder.build()
+    }
+
+    /// Compiles an Nginx `log_format`, such as
+    /// `$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent`.
+    ///
+    /// The variables of the combined format produce the same fields as it,
+    /// all other variables produce a field of their own name.
+    pub fn nginx(format: &str) -> std::result::Result<Self, &'static str> {
+        let mut builder = CustomLogFormatBuilder::default();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                builder.literal(c);
+                continue;
+            }
+
+            let variable = if chars.peek() == Some(&'{') {
+                chars.next();
+                chars.by_ref().take_while(|c| *c != '}').collect::<String>()
+            } else {
+                let mut variable = String::new();
+                while let Some(c) = chars
+                    .peek()
+                    .filter(|c| c.is_ascii_alphanumeric() || **c == '_')
+                {
+                    variable.push(*c);
+                    chars.next();
+                }
+                variable
+            };
+
+            let directive = match variable.as_str() {
+                "" => return Err("log format contains a variable without a name"),
+                "request" => {
+                    builder.request("request")?;
+                    continue;
+                }
+                "remote_addr" => Directive::new("client", FieldKind::Bytes),
+                "remote_user" => Directive::new("user", FieldKind::Bytes),
+                "time_local" => Directive::timestamp("%d/%b/%Y:%T %z"),
+                "time_iso8601" => Directive::timestamp("%+"),
+                "body_bytes_sent" => Directive::new("size", FieldKind::Integer),
+                "http_referer" => Directive::new("referer", FieldKind::Bytes),
+                "http_user_agent" => Directive::new("agent", FieldKind::Bytes),
+                "status"
+                | "bytes_sent"
+                | "request_length"
+                | "connection"
+                | "connection_requests"
+                | "content_length"
+                | "pid"
+                | "remote_port"
+                | "server_port" => Directive::new(variable, FieldKind::Integer),
+                "request_time" | "msec" | "gzip_ratio" => {
+                    Directive::new(variable, FieldKind::Float)
+                }
+                _ => Directive::new(variable, FieldKind::Bytes),
+            };
+
+            builder.directive(directive)?;
+        }
+
+        builder.build()
+    }
+
+    /// Extracts the typed fields of `message`.
+    pub fn log_fields(
+        &self,
+        message: &str,
+        timestamp_format: Option<&str>,
+        timezone: &TimeZone,
+    ) -> std::result::Result<Value, String> {
+        let captures = self
+            .regex
+            .captures(message)
+            .ok_or("failed parsing log line")?;
+
+        let timestamp_format = timestamp_format
+            .or(self.timestamp_format.as_deref())
+            .unwrap_or("%d/%b/%Y:%T %z");
+
+        let mut fields = BTreeMap::new();
+        for (name, value) in self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| captures.name(name).map(|value| (name, value.as_str())))
+        {
+            let value = match self.fields.get(name) {
+                Some(FieldKind::Timestamp) => {
+                    Value::Timestamp(parse_time(value, timestamp_format, timezone)?)
+                }
+                Some(FieldKind::Integer) => Value::Integer(
+                    value
+                        .parse()
+                        .map_err(|_| format!("failed parsing {}", name))?,
+                ),
+                Some(FieldKind::Float) => Value::from(
+                    value
+                        .parse::<f64>()
+                        .map_err(|_| format!("failed parsing {}", name))?,
+                ),
+                _ => Value::Bytes(value.to_owned().into()),
+            };
+
+            fields.insert(name.to_owned(), value);
+        }
+
+        Ok(fields.into())
+    }
+
+    /// The fields this format produces, any of which are missing if logged
+    /// as `-`.
+    pub fn type_def(&self) -> BTreeMap<String, TypeDef> {
+        self.fields
+            .iter()
+            .map(|(name, kind)| (name.clone(), (kind.kind() | Kind::Null).into()))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct CustomLogFormatBuilder {
+    pattern: String,
+    fields: BTreeMap<String, FieldKind>,
+    timestamp_format: Option<String>,
+    quoted: bool,
+}
+
+impl CustomLogFormatBuilder {
+    fn literal(&mut self, c: char) {
+        if c == '"' {
+            self.quoted = !self.quoted;
+        }
+
+        if c.is_whitespace() {
+            self.pattern.push_str(r"\s+");
+        } else {
+            self.pattern.push_str(&regex::escape(&c.to_string()));
+        }
+    }
+
+    fn field(&mut self, name: &str, kind: FieldKind) -> std::result::Result<(), &'static str> {
+        if self.fields.insert(name.to_owned(), kind).is_some() {
+            return Err("log format contains the same field more than once");
+        }
+
+        Ok(())
+    }
+
+    fn directive(&mut self, directive: Directive) -> std::result::Result<(), &'static str> {
+        let name = directive
+            .name
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+
+        self.field(&name, directive.kind)?;
+        if directive.timestamp_format.is_some() {
+            self.timestamp_format = directive.timestamp_format;
+        }
+
+        // Timestamps may contain spaces, in which case the literal following
+        // them tells where they end.
+        let pattern = match directive.kind {
+            FieldKind::Timestamp if !self.quoted => ".+?",
+            kind => kind.pattern(self.quoted),
+        };
+
+        self.pattern
+            .push_str(&format!("(-|(?P<{}>{}))", name, pattern));
+
+        Ok(())
+    }
+
+    /// A request line, additionally split into its method, path and protocol.
+    fn request(&mut self, name: &str) -> std::result::Result<(), &'static str> {
+        for (name, kind) in &[
+            (name, FieldKind::Bytes),
+            ("method", FieldKind::Bytes),
+            ("path", FieldKind::Bytes),
+            ("protocol", FieldKind::Bytes),
+        ] {
+            self.field(name, *kind)?;
+        }
+
+        self.pattern.push_str(&format!(
+            r#"(-|(?P<{}>(?P<method>\w+)\s+(?P<path>\S+)\s+(?P<protocol>[^"\s]+)|{}))"#,
+            name,
+            FieldKind::Bytes.pattern(self.quoted),
+        ));
+
+        Ok(())
+    }
+
+    fn build(self) -> std::result::Result<CustomLogFormat, &'static str> {
+        if self.fields.is_empty() {
+            return Err("log format doesn't contain any fields");
+        }
+
+        let regex = Regex::new(&format!(r"^\s*{}\s*$", self.pattern))
+            .map_err(|_| "unable to compile log format")?;
+
+        Ok(CustomLogFormat {
+            regex,
+            fields: self.fields,
+            timestamp_format: self.timestamp_format,
+        })
+    }
+}
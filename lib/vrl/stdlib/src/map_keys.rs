@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MapKeys;
+
+impl Function for MapKeys {
+    fn identifier(&self) -> &'static str {
+        "map_keys"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT,
+                required: true,
+            },
+            Parameter {
+                keyword: "recursive",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
+        ]
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        Some(closure::Definition {
+            inputs: &[kind::BYTES],
+            output: kind::BYTES,
+        })
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "upcase keys",
+                source: r#"map_keys({ "a": 1, "b": { "c": 2 } }) -> |key| { upcase(key) }"#,
+                result: Ok(r#"{ "A": 1, "B": { "c": 2 } }"#),
+            },
+            Example {
+                title: "recursive",
+                source: r#"map_keys({ "a": 1, "b": [{ "c": 2 }] }, recursive: true) -> |key| { "_" + key }"#,
+                result: Ok(r#"{ "_a": 1, "_b": [{ "_c": 2 }] }"#),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let recursive = arguments.optional("recursive").unwrap_or(expr!(false));
+        let closure = arguments.required_closure();
+
+        Ok(Box::new(MapKeysFn {
+            value,
+            recursive,
+            closure,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MapKeysFn {
+    value: Box<dyn Expression>,
+    recursive: Box<dyn Expression>,
+    closure: closure::Closure,
+}
+
+impl MapKeysFn {
+    fn map_object(
+        &self,
+        ctx: &mut Context,
+        map: BTreeMap<String, Value>,
+        recursive: bool,
+    ) -> Resolved {
+        map.into_iter()
+            .map(|(key, value)| {
+                let key = self.closure.resolve(ctx, vec![key.into()])?;
+                let key = String::from_utf8_lossy(&key.try_bytes()?).into_owned();
+                let value = if recursive {
+                    self.map_nested(ctx, value)?
+                } else {
+                    value
+                };
+
+                Ok((key, value))
+            })
+            .collect::<Result<_>>()
+            .map(Value::Object)
+    }
+
+    /// Objects nested in arrays have their keys mapped as well.
+    fn map_nested(&self, ctx: &mut Context, value: Value) -> Resolved {
+        match value {
+            Value::Object(map) => self.map_object(ctx, map, true),
+            Value::Array(array) => array
+                .into_iter()
+                .map(|value| self.map_nested(ctx, value))
+                .collect::<Result<_>>()
+                .map(Value::Array),
+            value => Ok(value),
+        }
+    }
+}
+
+impl Expression for MapKeysFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let recursive = self.recursive.resolve(ctx)?.try_boolean()?;
+        let map = self.value.resolve(ctx)?.try_object()?;
+
+        self.map_object(ctx, map, recursive)
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().object::<(), Kind>(map! { (): Kind::all() })
+    }
+}
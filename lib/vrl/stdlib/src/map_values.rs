@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MapValues;
+
+impl Function for MapValues {
+    fn identifier(&self) -> &'static str {
+        "map_values"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT | kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "recursive",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
+        ]
+    }
+
+    fn closure(&self) -> Option<closure::Definition> {
+        Some(closure::Definition {
+            inputs: &[kind::ANY],
+            output: kind::ANY,
+        })
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "object",
+                source: r#"map_values({ "a": 1, "b": 2 }) -> |value| { int!(value) * 10 }"#,
+                result: Ok(r#"{ "a": 10, "b": 20 }"#),
+            },
+            Example {
+                title: "recursive",
+                source: r#"map_values({ "a": "foo", "b": ["bar"] }, recursive: true) -> |value| { upcase(to_string(value) ?? "") }"#,
+                result: Ok(r#"{ "a": "FOO", "b": ["BAR"] }"#),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let recursive = arguments.optional("recursive").unwrap_or(expr!(false));
+        let closure = arguments.required_closure();
+
+        Ok(Box::new(MapValuesFn {
+            value,
+            recursive,
+            closure,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MapValuesFn {
+    value: Box<dyn Expression>,
+    recursive: Box<dyn Expression>,
+    closure: closure::Closure,
+}
+
+impl MapValuesFn {
+    fn map_object(
+        &self,
+        ctx: &mut Context,
+        map: BTreeMap<String, Value>,
+        recursive: bool,
+    ) -> Resolved {
+        map.into_iter()
+            .map(|(key, value)| Ok((key, self.map_value(ctx, value, recursive)?)))
+            .collect::<Result<_>>()
+            .map(Value::Object)
+    }
+
+    fn map_array(&self, ctx: &mut Context, array: Vec<Value>, recursive: bool) -> Resolved {
+        array
+            .into_iter()
+            .map(|value| self.map_value(ctx, value, recursive))
+            .collect::<Result<_>>()
+            .map(Value::Array)
+    }
+
+    /// When recursing, nested containers are walked into rather than passed
+    /// to the closure.
+    fn map_value(&self, ctx: &mut Context, value: Value, recursive: bool) -> Resolved {
+        match value {
+            Value::Object(map) if recursive => self.map_object(ctx, map, recursive),
+            Value::Array(array) if recursive => self.map_array(ctx, array, recursive),
+            value => self.closure.resolve(ctx, vec![value]),
+        }
+    }
+}
+
+impl Expression for MapValuesFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let recursive = self.recursive.resolve(ctx)?.try_boolean()?;
+
+        match self.value.resolve(ctx)? {
+            Value::Object(map) => self.map_object(ctx, map, recursive),
+            Value::Array(array) => self.map_array(ctx, array, recursive),
+            value => Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::Object | Kind::Array,
+            }
+            .into()),
+        }
+    }
+
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        if self.value.type_def(state).is_array() {
+            TypeDef::new().array_mapped::<(), Kind>(map! { (): Kind::all() })
+        } else {
+            TypeDef::new().object::<(), Kind>(map! { (): Kind::all() })
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::util;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Mean;
+
+impl Function for Mean {
+    fn identifier(&self) -> &'static str {
+        "mean"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "numbers",
+                source: r#"mean!([1, 2, 3, 4])"#,
+                result: Ok("2.5"),
+            },
+            Example {
+                title: "empty",
+                source: r#"mean!([])"#,
+                result: Ok("null"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(MeanFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MeanFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for MeanFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let array = self.value.resolve(ctx)?.try_array()?;
+        let numbers = util::numbers(&array)?;
+
+        if numbers.is_empty() {
+            return Ok(Value::Null);
+        }
+
+        Ok((numbers.iter().sum::<f64>() / numbers.len() as f64).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().float().add_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        mean => Mean;
+
+        integers {
+            args: func_args![value: value!([1, 2, 3, 4])],
+            want: Ok(value!(2.5)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        mixed {
+            args: func_args![value: value!(vec![value!(1), value!(2.5), value!(-0.5)])],
+            want: Ok(value!(1.0)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        empty {
+            args: func_args![value: value!([])],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        not_numeric {
+            args: func_args![value: value!([true])],
+            want: Err(r#"expected "integer" or "float", got "boolean""#),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+    ];
+}
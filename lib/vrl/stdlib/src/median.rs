@@ -0,0 +1,97 @@
+use crate::util;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Median;
+
+impl Function for Median {
+    fn identifier(&self) -> &'static str {
+        "median"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "odd length",
+                source: r#"median!([3, 1, 2])"#,
+                result: Ok("2.0"),
+            },
+            Example {
+                title: "even length",
+                source: r#"median!([4, 1, 3, 2])"#,
+                result: Ok("2.5"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(MedianFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MedianFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for MedianFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let array = self.value.resolve(ctx)?.try_array()?;
+        let numbers = util::numbers(&array)?;
+
+        Ok(util::percentile(numbers, 50.0).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().float().add_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        median => Median;
+
+        odd {
+            args: func_args![value: value!([3, 1, 2])],
+            want: Ok(value!(2.0)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        even {
+            args: func_args![value: value!(vec![value!(4), value!(1.5), value!(3), value!(2)])],
+            want: Ok(value!(2.5)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        single {
+            args: func_args![value: value!([7])],
+            want: Ok(value!(7.0)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        empty {
+            args: func_args![value: value!([])],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        not_numeric {
+            args: func_args![value: value!([null])],
+            want: Err(r#"expected "integer" or "float", got "null""#),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+    ];
+}
@@ -0,0 +1,102 @@
+use crate::util;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Min;
+
+impl Function for Min {
+    fn identifier(&self) -> &'static str {
+        "min"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "integers",
+                source: r#"min!([3, 1, 2])"#,
+                result: Ok("1"),
+            },
+            Example {
+                title: "mixed",
+                source: r#"min!([3, 1.5, 2])"#,
+                result: Ok("1.5"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(MinFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MinFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for MinFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let array = self.value.resolve(ctx)?.try_array()?;
+        let numbers = util::numbers(&array)?;
+
+        // The smallest element is returned as is, keeping it an integer or a float.
+        let index = (0..numbers.len()).reduce(|min, index| {
+            if numbers[index] < numbers[min] {
+                index
+            } else {
+                min
+            }
+        });
+
+        Ok(index
+            .map(|index| array[index].clone())
+            .unwrap_or(Value::Null))
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().integer().add_float().add_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        min => Min;
+
+        integers {
+            args: func_args![value: value!([2, 3, 1])],
+            want: Ok(value!(1)),
+            tdef: TypeDef::new().fallible().integer().add_float().add_null(),
+        }
+
+        mixed {
+            args: func_args![value: value!(vec![value!(2), value!(-1.5), value!(1)])],
+            want: Ok(value!(-1.5)),
+            tdef: TypeDef::new().fallible().integer().add_float().add_null(),
+        }
+
+        empty {
+            args: func_args![value: value!([])],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().integer().add_float().add_null(),
+        }
+
+        not_numeric {
+            args: func_args![value: value!(vec![value!(1), value!("2")])],
+            want: Err(r#"expected "integer" or "float", got "string""#),
+            tdef: TypeDef::new().fallible().integer().add_float().add_null(),
+        }
+    ];
+}
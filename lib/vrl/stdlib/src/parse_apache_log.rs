@@ -1,4 +1,4 @@
-use crate::log_util;
+use crate::log_util::{self, CustomLogFormat};
 use std::collections::BTreeMap;
 use vrl::prelude::*;
 
@@ -27,11 +27,21 @@ impl Function for ParseApacheLog {
                 kind: kind::BYTES,
                 required: false,
             },
+            Parameter {
+                keyword: "log_format",
+                kind: kind::BYTES,
+                required: false,
+            },
         ]
     }
 
     fn compile(&self, mut arguments: ArgumentList) -> Compiled {
-        let variants = vec![value!("common"), value!("combined"), value!("error")];
+        let variants = vec![
+            value!("common"),
+            value!("combined"),
+            value!("error"),
+            value!("custom"),
+        ];
 
         let value = arguments.required("value");
         let format = arguments
@@ -40,11 +50,13 @@ impl Function for ParseApacheLog {
             .expect("format not bytes");
 
         let timestamp_format = arguments.optional("timestamp_format");
+        let custom = log_util::custom_log_format(&mut arguments, &format, CustomLogFormat::apache)?;
 
         Ok(Box::new(ParseApacheLogFn {
             value,
             format,
             timestamp_format,
+            custom,
         }))
     }
 
@@ -71,6 +83,20 @@ impl Function for ParseApacheLog {
                     r#"s'{"client":"147.159.108.175","message":"I will bypass the haptic COM bandwidth, that should matrix the CSS driver!","module":"ab","pid":4803,"port":24259,"severity":"alert","thread":"3814","timestamp":"2021-03-01T12:00:19Z"}'"#,
                 ),
             },
+            Example {
+                title: "parse apache 2.4 error log",
+                source: r#"encode_json(parse_apache_log!(s'[Wed Oct 11 14:32:52.123456 2000] [core:error] [pid 35708:tid 4328636416] [client 72.15.99.187] AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico', "error"))"#,
+                result: Ok(
+                    r#"s'{"client":"72.15.99.187","message":"AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico","module":"core","pid":35708,"severity":"error","thread":"4328636416","timestamp":"2000-10-11T14:32:52.123456Z"}'"#,
+                ),
+            },
+            Example {
+                title: "parse apache custom log",
+                source: r#"encode_json(parse_apache_log!(s'127.0.0.1 [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326 1500', "custom", log_format: s'%a %t "%r" %>s %b %D'))"#,
+                result: Ok(
+                    r#"s'{"client":"127.0.0.1","duration_us":1500,"message":"GET /apache_pb.gif HTTP/1.0","method":"GET","path":"/apache_pb.gif","protocol":"HTTP/1.0","size":2326,"status":200,"timestamp":"2000-10-10T20:55:36Z"}'"#,
+                ),
+            },
         ]
     }
 }
@@ -80,6 +106,7 @@ struct ParseApacheLogFn {
     value: Box<dyn Expression>,
     format: Bytes,
     timestamp_format: Option<Box<dyn Expression>>,
+    custom: Option<CustomLogFormat>,
 }
 
 impl Expression for ParseApacheLogFn {
@@ -87,11 +114,30 @@ impl Expression for ParseApacheLogFn {
         let bytes = self.value.resolve(ctx)?;
         let message = bytes.try_bytes_utf8_lossy()?;
         let timestamp_format = match &self.timestamp_format {
-            None => "%d/%b/%Y:%T %z".to_owned(),
-            Some(timestamp_format) => timestamp_format
-                .resolve(ctx)?
-                .try_bytes_utf8_lossy()?
-                .to_string(),
+            None => None,
+            Some(timestamp_format) => Some(
+                timestamp_format
+                    .resolve(ctx)?
+                    .try_bytes_utf8_lossy()?
+                    .to_string(),
+            ),
+        };
+
+        if let Some(custom) = &self.custom {
+            return custom
+                .log_fields(&message, timestamp_format.as_deref(), ctx.timezone())
+                .map_err(Into::into);
+        }
+
+        // Error logs default to the timestamps of Apache 2.4 if they aren't
+        // in the format of the access logs.
+        let timestamp_formats = match (timestamp_format, self.format.as_ref()) {
+            (Some(timestamp_format), _) => vec![timestamp_format],
+            (None, b"error") => vec![
+                "%d/%b/%Y:%T %z".to_owned(),
+                "%a %b %d %H:%M:%S%.f %Y".to_owned(),
+            ],
+            (None, _) => vec!["%d/%b/%Y:%T %z".to_owned()],
         };
 
         let regex = match self.format.as_ref() {
@@ -105,19 +151,27 @@ impl Expression for ParseApacheLogFn {
             .captures(&message)
             .ok_or("failed parsing common log line")?;
 
-        log_util::log_fields(regex, &captures, &timestamp_format, ctx.timezone())
-            .map_err(Into::into)
+        let mut result = Err(String::new());
+        for timestamp_format in &timestamp_formats {
+            result = log_util::log_fields(regex, &captures, timestamp_format, ctx.timezone());
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        result.map_err(Into::into)
     }
 
     fn type_def(&self, _: &state::Compiler) -> TypeDef {
-        TypeDef::new()
-            .fallible()
-            .object(match self.format.as_ref() {
-                b"common" => type_def_common(),
-                b"combined" => type_def_combined(),
-                b"error" => type_def_error(),
-                _ => unreachable!(),
-            })
+        let type_def = TypeDef::new().fallible();
+
+        match (&self.custom, self.format.as_ref()) {
+            (Some(custom), _) => type_def.object(custom.type_def()),
+            (None, b"common") => type_def.object(type_def_common()),
+            (None, b"combined") => type_def.object(type_def_combined()),
+            (None, b"error") => type_def.object(type_def_error()),
+            _ => unreachable!(),
+        }
     }
 }
 
@@ -158,8 +212,10 @@ fn type_def_error() -> BTreeMap<&'static str, TypeDef> {
          "timestamp": Kind::Timestamp | Kind::Null,
          "module": Kind::Bytes | Kind::Null,
          "severity": Kind::Bytes | Kind::Null,
+         "pid": Kind::Integer | Kind::Null,
          "thread": Kind::Bytes | Kind::Null,
-         "port": Kind::Bytes | Kind::Null,
+         "client": Kind::Bytes | Kind::Null,
+         "port": Kind::Integer | Kind::Null,
          "message": Kind::Bytes | Kind::Null,
     }
 }
@@ -291,6 +347,112 @@ mod tests {
             tz: shared::TimeZone::Named(chrono_tz::Tz::UTC),
         }
 
+        error_line_apache_2_4 {
+            args: func_args![
+                value: r#"[Wed Oct 11 14:32:52.123456 2000] [core:error] [pid 35708:tid 4328636416] [client 72.15.99.187] AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico"#,
+                format: "error",
+            ],
+            want: Ok(btreemap! {
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2000-10-11T14:32:52.123456Z").unwrap().into()),
+                "message" => "AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico",
+                "module" => "core",
+                "severity" => "error",
+                "pid" => 35708,
+                "thread" => "4328636416",
+                "client" => "72.15.99.187",
+            }),
+            tdef: TypeDef::new().fallible().object(type_def_error()),
+            tz: shared::TimeZone::Named(chrono_tz::Tz::UTC),
+        }
+
+        error_line_without_client {
+            args: func_args![
+                value: r#"[Wed Oct 11 14:32:52.123456 2000] [mpm_event:notice] [pid 1:tid 140131380622464] AH00489: Apache/2.4.48 (Unix) configured -- resuming normal operations"#,
+                format: "error",
+            ],
+            want: Ok(btreemap! {
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2000-10-11T14:32:52.123456Z").unwrap().into()),
+                "message" => "AH00489: Apache/2.4.48 (Unix) configured -- resuming normal operations",
+                "module" => "mpm_event",
+                "severity" => "notice",
+                "pid" => 1,
+                "thread" => "140131380622464",
+            }),
+            tdef: TypeDef::new().fallible().object(type_def_error()),
+            tz: shared::TimeZone::Named(chrono_tz::Tz::UTC),
+        }
+
+        custom_line_valid {
+            args: func_args![
+                value: r#"127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif?a=b HTTP/1.0" 200 - "-" "curl/7.75.0" 4213 example.com"#,
+                format: "custom",
+                log_format: r#"%h %l %u %t "%r" %>s %b "%{Referer}i" "%{User-agent}i" %D %v"#,
+            ],
+            want: Ok(btreemap! {
+                "host" => "127.0.0.1",
+                "user" => "frank",
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2000-10-10T20:55:36Z").unwrap().into()),
+                "message" => "GET /apache_pb.gif?a=b HTTP/1.0",
+                "method" => "GET",
+                "path" => "/apache_pb.gif?a=b",
+                "protocol" => "HTTP/1.0",
+                "status" => 200,
+                "agent" => "curl/7.75.0",
+                "duration_us" => 4213,
+                "server_name" => "example.com",
+            }),
+            tdef: TypeDef::new().fallible().object(CustomLogFormat::apache(r#"%h %l %u %t "%r" %>s %b "%{Referer}i" "%{User-agent}i" %D %v"#).unwrap().type_def()),
+            tz: shared::TimeZone::default(),
+        }
+
+        custom_line_timestamp_format {
+            args: func_args![
+                value: r#"2000-10-10 20:55:36 GET 200 "10.1.1.1, 10.0.0.1""#,
+                format: "custom",
+                log_format: r#"%{%Y-%m-%d %H:%M:%S}t %m %s "%{X-Forwarded-For}i""#,
+            ],
+            want: Ok(btreemap! {
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2000-10-10T20:55:36Z").unwrap().into()),
+                "method" => "GET",
+                "status" => 200,
+                "x_forwarded_for" => "10.1.1.1, 10.0.0.1",
+            }),
+            tdef: TypeDef::new().fallible().object(CustomLogFormat::apache(r#"%{%Y-%m-%d %H:%M:%S}t %m %s "%{X-Forwarded-For}i""#).unwrap().type_def()),
+            tz: shared::TimeZone::Named(chrono_tz::Tz::UTC),
+        }
+
+        custom_line_invalid {
+            args: func_args![
+                value: r#"127.0.0.1 frank"#,
+                format: "custom",
+                log_format: "%h %u %s",
+            ],
+            want: Err("failed parsing log line"),
+            tdef: TypeDef::new().fallible().object(CustomLogFormat::apache("%h %u %s").unwrap().type_def()),
+            tz: shared::TimeZone::default(),
+        }
+
+        custom_without_log_format {
+            args: func_args![value: "127.0.0.1", format: "custom"],
+            want: Err("invalid argument"),
+            tdef: TypeDef::new().fallible(),
+            tz: shared::TimeZone::default(),
+        }
+
+        custom_unsupported_directive {
+            args: func_args![value: "127.0.0.1", format: "custom", log_format: "%h %J"],
+            want: Err("invalid argument"),
+            tdef: TypeDef::new().fallible(),
+            tz: shared::TimeZone::default(),
+        }
+
+        log_format_without_custom {
+            args: func_args![value: "127.0.0.1", format: "common", log_format: "%h"],
+            want: Err("invalid argument"),
+            tdef: TypeDef::new().fallible(),
+            tz: shared::TimeZone::default(),
+        }
+
         log_line_valid_empty {
             args: func_args![value: "- - - - - - -",
                              format: "common",
@@ -1,4 +1,5 @@
 use csv::ReaderBuilder;
+use std::collections::BTreeMap;
 use vrl::prelude::*;
 
 #[derive(Clone, Copy, Debug)]
@@ -10,17 +11,39 @@ impl Function for ParseCsv {
     }
 
     fn examples(&self) -> &'static [Example] {
-        &[Example {
-            title: "parse a single CSV formatted row",
-            source: r#"parse_csv!(s'foo,bar,"foo "", bar"')"#,
-            result: Ok(r#"["foo", "bar", "foo \", bar"]"#),
-        }]
+        &[
+            Example {
+                title: "parse a single CSV formatted row",
+                source: r#"parse_csv!(s'foo,bar,"foo "", bar"')"#,
+                result: Ok(r#"["foo", "bar", "foo \", bar"]"#),
+            },
+            Example {
+                title: "supplied headers",
+                source: r#"parse_csv!("foo,1", headers: ["name", "count"], coerce_numbers: true)"#,
+                result: Ok(r#"{ "name": "foo", "count": 1 }"#),
+            },
+            Example {
+                title: "header row",
+                source: r#"parse_csv!("name|count\nfoo|1", delimiter: "|", headers: true)"#,
+                result: Ok(r#"{ "name": "foo", "count": "1" }"#),
+            },
+        ]
     }
 
     fn compile(&self, mut arguments: ArgumentList) -> Compiled {
         let value = arguments.required("value");
         let delimiter = arguments.optional("delimiter").unwrap_or(expr!(","));
-        Ok(Box::new(ParseCsvFn { value, delimiter }))
+        let quote = arguments.optional("quote").unwrap_or(expr!("\""));
+        let headers = arguments.optional("headers");
+        let coerce_numbers = arguments.optional("coerce_numbers").unwrap_or(expr!(false));
+
+        Ok(Box::new(ParseCsvFn {
+            value,
+            delimiter,
+            quote,
+            headers,
+            coerce_numbers,
+        }))
     }
 
     fn parameters(&self) -> &'static [Parameter] {
@@ -35,6 +58,21 @@ impl Function for ParseCsv {
                 kind: kind::BYTES,
                 required: false,
             },
+            Parameter {
+                keyword: "quote",
+                kind: kind::BYTES,
+                required: false,
+            },
+            Parameter {
+                keyword: "headers",
+                kind: kind::ARRAY | kind::BOOLEAN,
+                required: false,
+            },
+            Parameter {
+                keyword: "coerce_numbers",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
         ]
     }
 }
@@ -43,6 +81,47 @@ impl Function for ParseCsv {
 struct ParseCsvFn {
     value: Box<dyn Expression>,
     delimiter: Box<dyn Expression>,
+    quote: Box<dyn Expression>,
+    headers: Option<Box<dyn Expression>>,
+    coerce_numbers: Box<dyn Expression>,
+}
+
+/// Where the names of the fields of a record come from.
+enum Headers {
+    /// Fields are returned as an array.
+    None,
+
+    /// The first row holds the field names.
+    FirstRow,
+
+    Supplied(Vec<String>),
+}
+
+impl ParseCsvFn {
+    fn headers(&self, ctx: &mut Context) -> Result<Headers> {
+        let headers = match &self.headers {
+            Some(headers) => headers.resolve(ctx)?,
+            None => return Ok(Headers::None),
+        };
+
+        match headers {
+            Value::Boolean(true) => Ok(Headers::FirstRow),
+            Value::Boolean(false) => Ok(Headers::None),
+            Value::Array(headers) => headers
+                .into_iter()
+                .map(|header| match header {
+                    Value::Bytes(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+                    _ => Err("headers must be strings".into()),
+                })
+                .collect::<Result<_>>()
+                .map(Headers::Supplied),
+            value => Err(value::Error::Expected {
+                got: value.kind(),
+                expected: Kind::Array | Kind::Boolean,
+            }
+            .into()),
+        }
+    }
 }
 
 impl Expression for ParseCsvFn {
@@ -54,32 +133,114 @@ impl Expression for ParseCsvFn {
         }
         let delimiter = delimiter[0];
 
+        let quote = self.quote.resolve(ctx)?.try_bytes()?;
+        if quote.len() > 1 {
+            return Err("quote must be a single character or empty".into());
+        }
+
+        let headers = self.headers(ctx)?;
+        let coerce_numbers = self.coerce_numbers.resolve(ctx)?.try_boolean()?;
+
+        // An empty quote disables quoting altogether.
         let reader = ReaderBuilder::new()
             .has_headers(false)
+            .flexible(true)
             .delimiter(delimiter)
+            .quoting(!quote.is_empty())
+            .quote(quote.first().copied().unwrap_or(b'"'))
             .from_reader(&*csv_string);
 
-        reader
-            .into_byte_records()
-            .next()
-            .transpose()
-            .map_err(|err| format!("invalid csv record: {}", err).into()) // shouldn't really happen
-            .map(|record| {
-                record
-                    .map(|record| record.iter().map(Into::into).collect::<Vec<Value>>())
+        let mut records = reader.into_byte_records();
+        let mut next_record = || {
+            records
+                .next()
+                .transpose()
+                .map_err(|err| format!("invalid csv record: {}", err)) // shouldn't really happen
+        };
+
+        let headers = match headers {
+            Headers::None => {
+                return Ok(next_record()?
+                    .map(|record| {
+                        record
+                            .iter()
+                            .map(|field| field_value(field, coerce_numbers))
+                            .collect::<Vec<_>>()
+                    })
                     .unwrap_or_default()
-                    .into()
-            })
+                    .into())
+            }
+            Headers::FirstRow => match next_record()? {
+                Some(record) => record
+                    .iter()
+                    .map(|header| String::from_utf8_lossy(header).into_owned())
+                    .collect(),
+                None => Vec::new(),
+            },
+            Headers::Supplied(headers) => headers,
+        };
+
+        let record = match next_record()? {
+            Some(record) => record,
+            None => return Ok(Value::Object(BTreeMap::new())),
+        };
+
+        if record.len() != headers.len() {
+            return Err(format!(
+                "record has {} fields, but {} headers",
+                record.len(),
+                headers.len()
+            )
+            .into());
+        }
+
+        Ok(headers
+            .into_iter()
+            .zip(record.iter())
+            .map(|(header, field)| (header, field_value(field, coerce_numbers)))
+            .collect::<BTreeMap<_, _>>()
+            .into())
     }
 
-    fn type_def(&self, _: &state::Compiler) -> TypeDef {
-        TypeDef::new().fallible().array::<Kind>(type_def())
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        let coerce_numbers = !matches!(self.coerce_numbers.as_value(), Some(Value::Boolean(false)));
+
+        let inner = if coerce_numbers {
+            Kind::Bytes | Kind::Integer | Kind::Float
+        } else {
+            Kind::Bytes
+        };
+
+        let td = TypeDef::new().fallible();
+        match &self.headers {
+            None => td.array::<Kind>(vec![inner]),
+            Some(headers) if headers.type_def(state).is_array() => {
+                td.object::<(), Kind>(map! { (): inner })
+            }
+            Some(_) => td
+                .array::<Kind>(vec![inner])
+                .add_object::<(), Kind>(map! { (): inner }),
+        }
     }
 }
 
-#[inline]
-fn type_def() -> Vec<Kind> {
-    vec![Kind::Bytes]
+/// Returns the field as a string, or as a number if it is one and numbers
+/// are to be coerced.
+fn field_value(field: &[u8], coerce_numbers: bool) -> Value {
+    if coerce_numbers {
+        if let Ok(field) = std::str::from_utf8(field) {
+            if let Ok(integer) = field.parse::<i64>() {
+                return integer.into();
+            }
+
+            match field.parse::<f64>() {
+                Ok(float) if float.is_finite() => return float.into(),
+                _ => {}
+            }
+        }
+    }
+
+    field.into()
 }
 
 #[cfg(test)]
@@ -92,43 +253,111 @@ mod tests {
         valid {
             args: func_args![value: value!("foo,bar,\"foo \"\", bar\"")],
             want: Ok(value!(["foo", "bar", "foo \", bar"])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         invalid_utf8 {
             args: func_args![value: value!(&b"foo,b\xFFar"[..])],
             want: Ok(value!(vec!["foo".into(), value!(&b"b\xFFar"[..])])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         custom_delimiter {
             args: func_args![value: value!("foo bar"), delimiter: value!(" ")],
             want: Ok(value!(["foo", "bar"])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         invalid_delimiter {
             args: func_args![value: value!("foo bar"), delimiter: value!(",,")],
             want: Err("delimiter must be a single character"),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         single_value {
             args: func_args![value: value!("foo")],
             want: Ok(value!(["foo"])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         empty_string {
             args: func_args![value: value!("")],
             want: Ok(value!([])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
         }
 
         multiple_lines {
             args: func_args![value: value!("first,line\nsecond,line,with,more,fields")],
             want: Ok(value!(["first", "line"])),
-            tdef: TypeDef::new().fallible().array::<Kind>(type_def()),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
+        }
+
+        custom_quote {
+            args: func_args![value: value!("'foo,bar',baz"), quote: "'"],
+            want: Ok(value!(["foo,bar", "baz"])),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
+        }
+
+        no_quoting {
+            args: func_args![value: value!("\"foo,bar\""), quote: ""],
+            want: Ok(value!(["\"foo", "bar\""])),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
+        }
+
+        invalid_quote {
+            args: func_args![value: value!("foo"), quote: "''"],
+            want: Err("quote must be a single character or empty"),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]),
+        }
+
+        coerce_numbers {
+            args: func_args![value: value!("foo,1,-2,1.5,1e3,inf,"), coerce_numbers: true],
+            want: Ok(value!(vec![value!("foo"), value!(1), value!(-2), value!(1.5), value!(1000.0), value!("inf"), value!("")])),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes | Kind::Integer | Kind::Float]),
+        }
+
+        supplied_headers {
+            args: func_args![value: value!("foo,1"), headers: value!(["name", "count"])],
+            want: Ok(value!({name: "foo", count: "1"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        supplied_headers_coerce_numbers {
+            args: func_args![value: value!("foo,1"),
+                             headers: value!(["name", "count"]),
+                             coerce_numbers: true],
+            want: Ok(value!({name: "foo", count: 1})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::Bytes | Kind::Integer | Kind::Float }),
+        }
+
+        supplied_headers_mismatch {
+            args: func_args![value: value!("foo,1,bar"), headers: value!(["name", "count"])],
+            want: Err("record has 3 fields, but 2 headers"),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        invalid_headers {
+            args: func_args![value: value!("foo"), headers: value!([1])],
+            want: Err("headers must be strings"),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        header_row {
+            args: func_args![value: value!("name,count\nfoo,1\nbar,2"), headers: true],
+            want: Ok(value!({name: "foo", count: "1"})),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]).add_object::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        header_row_only {
+            args: func_args![value: value!("name,count"), headers: true],
+            want: Ok(value!({})),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]).add_object::<(), Kind>(map! { (): Kind::Bytes }),
+        }
+
+        no_header_row {
+            args: func_args![value: value!("foo,1"), headers: false],
+            want: Ok(value!(["foo", "1"])),
+            tdef: TypeDef::new().fallible().array::<Kind>(vec![Kind::Bytes]).add_object::<(), Kind>(map! { (): Kind::Bytes }),
         }
     ];
 }
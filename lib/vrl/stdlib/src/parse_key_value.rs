@@ -4,12 +4,15 @@ use nom::{
     bytes::complete::{escaped, tag, take_until, take_while1},
     character::complete::{char, satisfy, space0},
     combinator::{eof, map, opt, peek, recognize, rest, verify},
-    error::{ContextError, ParseError, VerboseError},
+    error::{ContextError, ErrorKind, ParseError, VerboseError},
     multi::{many1, many_m_n, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
     IResult,
 };
-use std::{iter::FromIterator, str::FromStr};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    str::FromStr,
+};
 use vrl::prelude::*;
 
 #[derive(Clone, Copy, Debug)]
@@ -47,6 +50,16 @@ impl Function for ParseKeyValue {
                 kind: kind::BOOLEAN,
                 required: false,
             },
+            Parameter {
+                keyword: "accept_bracketed_values",
+                kind: kind::BOOLEAN,
+                required: false,
+            },
+            Parameter {
+                keyword: "duplicate_keys",
+                kind: kind::BYTES,
+                required: false,
+            },
         ]
     }
 
@@ -74,6 +87,16 @@ impl Function for ParseKeyValue {
                 source: r#"parse_key_value!(s'foo=bar foobar', whitespace: "strict")"#,
                 result: Ok(r#"{"foo": "bar", "foobar": true}"#),
             },
+            Example {
+                title: "bracketed values",
+                source: r#"parse_key_value!(s'time=[10/Oct/2000:13:55:36 -0700] user=frank', accept_bracketed_values: true)"#,
+                result: Ok(r#"{"time": "10/Oct/2000:13:55:36 -0700", "user": "frank"}"#),
+            },
+            Example {
+                title: "duplicate keys",
+                source: r#"parse_key_value!("tag=a tag=b host=c", duplicate_keys: "array")"#,
+                result: Ok(r#"{"tag": ["a", "b"], "host": "c"}"#),
+            },
         ]
     }
 
@@ -100,12 +123,29 @@ impl Function for ParseKeyValue {
             .optional("accept_standalone_key")
             .unwrap_or_else(|| expr!(true));
 
+        let bracketed_values = arguments
+            .optional("accept_bracketed_values")
+            .unwrap_or_else(|| expr!(false));
+
+        let duplicate_keys = arguments
+            .optional_enum("duplicate_keys", &DuplicateKeys::all_value())?
+            .map(|s| {
+                DuplicateKeys::from_str(
+                    &s.try_bytes_utf8_lossy().expect("duplicate_keys not bytes"),
+                )
+                .expect("validated enum")
+            })
+            .unwrap_or_default();
+
         Ok(Box::new(ParseKeyValueFn {
             value,
             key_value_delimiter,
             field_delimiter,
             whitespace,
             standalone_key,
+            bracketed_values,
+            duplicate_keys,
+            logfmt: false,
         }))
     }
 }
@@ -156,6 +196,87 @@ impl FromStr for Whitespace {
     }
 }
 
+/// How to resolve a key that appears more than once in the same line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the first value seen for the key.
+    First,
+    /// Keep the last value seen for the key.
+    Last,
+    /// Collect all values seen for the key into an array.
+    Array,
+}
+
+impl DuplicateKeys {
+    pub(crate) fn all_value() -> Vec<Value> {
+        use DuplicateKeys::*;
+
+        vec![First, Last, Array]
+            .into_iter()
+            .map(|u| u.as_str().into())
+            .collect::<Vec<_>>()
+    }
+
+    const fn as_str(self) -> &'static str {
+        use DuplicateKeys::*;
+
+        match self {
+            First => "first",
+            Last => "last",
+            Array => "array",
+        }
+    }
+
+    /// Folds the parsed pairs into an object according to the strategy.
+    fn collect(self, values: Vec<(String, Value)>) -> BTreeMap<String, Value> {
+        let mut map = BTreeMap::new();
+
+        for (key, value) in values {
+            match (map.entry(key), self) {
+                (Entry::Vacant(entry), _) => {
+                    entry.insert(value);
+                }
+                (Entry::Occupied(_), DuplicateKeys::First) => {}
+                (Entry::Occupied(mut entry), DuplicateKeys::Last) => {
+                    entry.insert(value);
+                }
+                // Parsed values are never arrays themselves, so an existing
+                // array can only be the result of a previous duplicate.
+                (Entry::Occupied(mut entry), DuplicateKeys::Array) => match entry.get_mut() {
+                    Value::Array(values) => values.push(value),
+                    existing => {
+                        let first = std::mem::replace(existing, Value::Null);
+                        *existing = Value::Array(vec![first, value]);
+                    }
+                },
+            }
+        }
+
+        map
+    }
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        Self::Last
+    }
+}
+
+impl FromStr for DuplicateKeys {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        use DuplicateKeys::*;
+
+        match s {
+            "first" => Ok(First),
+            "last" => Ok(Last),
+            "array" => Ok(Array),
+            _ => Err("unknown duplicate_keys variant"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct ParseKeyValueFn {
     pub(crate) value: Box<dyn Expression>,
@@ -163,6 +284,12 @@ pub(crate) struct ParseKeyValueFn {
     pub(crate) field_delimiter: Box<dyn Expression>,
     pub(crate) whitespace: Whitespace,
     pub(crate) standalone_key: Box<dyn Expression>,
+    pub(crate) bracketed_values: Box<dyn Expression>,
+    pub(crate) duplicate_keys: DuplicateKeys,
+
+    /// Unescape quoted values and parse unquoted `true` and `false` values as
+    /// booleans, as logfmt does.
+    pub(crate) logfmt: bool,
 }
 
 impl Expression for ParseKeyValueFn {
@@ -177,6 +304,7 @@ impl Expression for ParseKeyValueFn {
         let field_delimiter = value.try_bytes_utf8_lossy()?;
 
         let standalone_key = self.standalone_key.resolve(ctx)?.try_boolean()?;
+        let bracketed_values = self.bracketed_values.resolve(ctx)?.try_boolean()?;
 
         let values = parse(
             &bytes,
@@ -184,9 +312,11 @@ impl Expression for ParseKeyValueFn {
             &field_delimiter,
             self.whitespace,
             standalone_key,
+            bracketed_values,
+            self.logfmt,
         )?;
 
-        Ok(Value::from_iter(values))
+        Ok(self.duplicate_keys.collect(values).into())
     }
 
     fn type_def(&self, _: &state::Compiler) -> TypeDef {
@@ -202,6 +332,8 @@ fn parse<'a>(
     field_delimiter: &'a str,
     whitespace: Whitespace,
     standalone_key: bool,
+    bracketed_values: bool,
+    logfmt: bool,
 ) -> Result<Vec<(String, Value)>> {
     let (rest, result) = parse_line(
         input,
@@ -209,6 +341,8 @@ fn parse<'a>(
         field_delimiter,
         whitespace,
         standalone_key,
+        bracketed_values,
+        logfmt,
     )
     .map_err(|e| match e {
         nom::Err::Error(e) | nom::Err::Failure(e) => {
@@ -232,6 +366,8 @@ fn parse_line<'a>(
     field_delimiter: &'a str,
     whitespace: Whitespace,
     standalone_key: bool,
+    bracketed_values: bool,
+    logfmt: bool,
 ) -> IResult<&'a str, Vec<(String, Value)>, VerboseError<&'a str>> {
     separated_list1(
         parse_field_delimiter(field_delimiter),
@@ -240,6 +376,8 @@ fn parse_line<'a>(
             field_delimiter,
             whitespace,
             standalone_key,
+            bracketed_values,
+            logfmt,
         ),
     )(input)
 }
@@ -262,11 +400,15 @@ fn parse_field_delimiter<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
 /// Parse a single `key=value` tuple.
 /// Always accepts `key=`
 /// Accept standalone `key` if `standalone_key` is `true`
+/// Accept `key=[value]` if `bracketed_values` is `true`
+/// Unescape `key="value"` and accept `key=true` as a boolean if `logfmt` is `true`
 fn parse_key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     key_value_delimiter: &'a str,
     field_delimiter: &'a str,
     whitespace: Whitespace,
     standalone_key: bool,
+    bracketed_values: bool,
+    logfmt: bool,
 ) -> impl Fn(&'a str) -> IResult<&'a str, (String, Value), E> {
     move |input| {
         map(
@@ -277,7 +419,7 @@ fn parse_key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
                         parse_key(key_value_delimiter, field_delimiter, standalone_key),
                     ),
                     many_m_n(!standalone_key as usize, 1, tag(key_value_delimiter)),
-                    parse_value(field_delimiter),
+                    parse_value(field_delimiter, bracketed_values, logfmt),
                 ))(input),
                 Whitespace::Lenient => tuple((
                     preceded(
@@ -289,7 +431,7 @@ fn parse_key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
                         1,
                         delimited(space0, tag(key_value_delimiter), space0),
                     ),
-                    parse_value(field_delimiter),
+                    parse_value(field_delimiter, bracketed_values, logfmt),
                 ))(input),
             },
             |(field, sep, value): (&str, Vec<&str>, Value)| {
@@ -344,6 +486,60 @@ fn parse_delimited<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     }
 }
 
+/// Parses a value wrapped in square brackets, such as `[10/Oct/2000:13:55:36 -0700]`.
+/// Brackets can be nested, and brackets inside a `"` quoted section are ignored, so
+/// `[a [b] "c]"]` is a single value. Only the outermost pair of brackets is stripped.
+fn parse_bracketed<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    field_terminator: &'a str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |input| {
+        terminated(
+            bracketed,
+            peek(alt((
+                parse_field_delimiter(field_terminator),
+                preceded(space0, eof),
+            ))),
+        )(input)
+    }
+}
+
+fn bracketed<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    let mut chars = input.char_indices();
+
+    if !matches!(chars.next(), Some((_, '['))) {
+        return Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Char)));
+    }
+
+    let mut depth = 1;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (idx, c) in chars {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match c {
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&input[idx + 1..], &input[1..idx]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(nom::Err::Error(E::from_error_kind(
+        input,
+        ErrorKind::TakeUntil,
+    )))
+}
+
 /// An undelimited value is all the text until our field_delimiter, or if it is the last value in the line,
 /// just take the rest of the string.
 fn parse_undelimited<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
@@ -353,16 +549,40 @@ fn parse_undelimited<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
 }
 
 /// Parses the value.
-/// The value has two parsing strategies.
+/// The value has three parsing strategies.
 ///
-/// 1. Parse as a delimited field - currently the delimiter is hardcoded to a `"`.
-/// 2. If it does not start with one of the trim values, it is not a delimited field and we parse up to
+/// 1. If `bracketed_values` is set, parse as a field wrapped in `[` and `]`.
+/// 2. Parse as a delimited field - currently the delimiter is hardcoded to a `"`.
+/// 3. If it does not start with one of the trim values, it is not a delimited field and we parse up to
 ///    the next field_delimiter or the eof.
 ///
+/// If `logfmt` is set, delimited values are unescaped and undelimited `true` and `false` values are
+/// parsed as booleans.
 fn parse_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     field_delimiter: &'a str,
+    bracketed_values: bool,
+    logfmt: bool,
 ) -> impl Fn(&'a str) -> IResult<&'a str, Value, E> {
     move |input| {
+        if bracketed_values {
+            if let Ok((rest, value)) = parse_bracketed::<E>(field_delimiter)(input) {
+                return Ok((rest, value.into()));
+            }
+        }
+
+        if logfmt {
+            return alt((
+                map(parse_delimited('"', field_delimiter), |value| {
+                    unescape(value).into()
+                }),
+                map(parse_undelimited(field_delimiter), |value| match value {
+                    "true" => true.into(),
+                    "false" => false.into(),
+                    value => value.into(),
+                }),
+            ))(input);
+        }
+
         map(
             alt((
                 parse_delimited('"', field_delimiter),
@@ -373,6 +593,35 @@ fn parse_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     }
 }
 
+/// Replaces the escape sequences of a delimited value with the characters they stand for. Unknown
+/// escape sequences are kept as they are.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => unescaped.push('"'),
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
 /// Parses the key.
 /// Overall parsing strategies are the same as parse_value, but we don't need to convert the result to a `Value`.
 /// Standalone key are handled here so a quoted standalone key that contains a delimiter will be dealt with correctly.
@@ -410,12 +659,28 @@ mod test {
     fn test_quote_and_escape_char() {
         assert_eq!(
             Ok(vec![("key".to_string(), r#"a\a"#.into()),]),
-            parse(r#"key="a\a""#, "=", " ", Whitespace::Strict, true,)
+            parse(
+                r#"key="a\a""#,
+                "=",
+                " ",
+                Whitespace::Strict,
+                true,
+                false,
+                false
+            )
         );
 
         assert_eq!(
             Ok(vec![(r#"a\ a"#.to_string(), r#"val"#.into()),]),
-            parse(r#""a\ a"=val"#, "=", " ", Whitespace::Strict, true,)
+            parse(
+                r#""a\ a"=val"#,
+                "=",
+                " ",
+                Whitespace::Strict,
+                true,
+                false,
+                false
+            )
         );
     }
 
@@ -438,6 +703,8 @@ mod test {
                 " ",
                 Whitespace::Lenient,
                 false,
+                false,
+                false
             )
         );
     }
@@ -446,12 +713,26 @@ mod test {
     fn test_parse_key_value() {
         assert_eq!(
             Ok(("", ("ook".to_string(), "pook".into()))),
-            parse_key_value::<VerboseError<&str>>("=", " ", Whitespace::Lenient, false)("ook=pook")
+            parse_key_value::<VerboseError<&str>>(
+                "=",
+                " ",
+                Whitespace::Lenient,
+                false,
+                false,
+                false
+            )("ook=pook")
         );
 
         assert_eq!(
             Ok(("", ("key".to_string(), "".into()))),
-            parse_key_value::<VerboseError<&str>>("=", " ", Whitespace::Strict, false)("key=")
+            parse_key_value::<VerboseError<&str>>(
+                "=",
+                " ",
+                Whitespace::Strict,
+                false,
+                false,
+                false
+            )("key=")
         );
     }
 
@@ -462,7 +743,15 @@ mod test {
                 ("ook".to_string(), "pook".into()),
                 ("onk".to_string(), "ponk".into())
             ]),
-            parse("ook=pook onk=ponk", "=", " ", Whitespace::Lenient, false)
+            parse(
+                "ook=pook onk=ponk",
+                "=",
+                " ",
+                Whitespace::Lenient,
+                false,
+                false,
+                false
+            )
         );
     }
 
@@ -473,7 +762,15 @@ mod test {
                 ("ook".to_string(), "".into()),
                 ("onk".to_string(), "ponk".into())
             ]),
-            parse("ook= onk=ponk", "=", " ", Whitespace::Strict, false)
+            parse(
+                "ook= onk=ponk",
+                "=",
+                " ",
+                Whitespace::Strict,
+                false,
+                false,
+                false
+            )
         );
     }
 
@@ -484,7 +781,15 @@ mod test {
                 ("foo".to_string(), "bar".into()),
                 ("foobar".to_string(), value!(true))
             ]),
-            parse("foo:bar ,   foobar   ", ":", ",", Whitespace::Lenient, true)
+            parse(
+                "foo:bar ,   foobar   ",
+                ":",
+                ",",
+                Whitespace::Lenient,
+                true,
+                false,
+                false
+            )
         );
     }
 
@@ -502,7 +807,9 @@ mod test {
                 "=",
                 " ",
                 Whitespace::Lenient,
-                true
+                true,
+                false,
+                false
             )
         );
     }
@@ -522,7 +829,9 @@ mod test {
                 "=",
                 " ",
                 Whitespace::Lenient,
-                true
+                true,
+                false,
+                false
             )
         );
     }
@@ -531,7 +840,7 @@ mod test {
     fn test_parse_single_standalone_key() {
         assert_eq!(
             Ok(vec![("foobar".to_string(), value!(true))]),
-            parse("foobar", ":", ",", Whitespace::Lenient, true)
+            parse("foobar", ":", ",", Whitespace::Lenient, true, false, false)
         );
     }
 
@@ -542,7 +851,15 @@ mod test {
                 ("foo".to_string(), "bar".into()),
                 ("foobar".to_string(), value!(true))
             ]),
-            parse("foo:bar ,   foobar   ", ":", ",", Whitespace::Strict, true)
+            parse(
+                "foo:bar ,   foobar   ",
+                ":",
+                ",",
+                Whitespace::Strict,
+                true,
+                false,
+                false
+            )
         );
     }
 
@@ -590,25 +907,103 @@ mod test {
         // delimited
         assert_eq!(
             Ok(("", "noog".into())),
-            parse_value::<VerboseError<&str>>(" ")(r#""noog""#)
+            parse_value::<VerboseError<&str>>(" ", false, false)(r#""noog""#)
         );
 
         // undelimited
         assert_eq!(
             Ok(("", "noog".into())),
-            parse_value::<VerboseError<&str>>(" ")("noog")
+            parse_value::<VerboseError<&str>>(" ", false, false)("noog")
         );
 
         // empty delimited
         assert_eq!(
             Ok(("", "".into())),
-            parse_value::<VerboseError<&str>>(" ")(r#""""#)
+            parse_value::<VerboseError<&str>>(" ", false, false)(r#""""#)
         );
 
         // empty undelimited
         assert_eq!(
             Ok(("", "".into())),
-            parse_value::<VerboseError<&str>>(" ")("")
+            parse_value::<VerboseError<&str>>(" ", false, false)("")
+        );
+    }
+
+    #[test]
+    fn test_parse_value_logfmt() {
+        // escaped quotes
+        assert_eq!(
+            Ok(("", r#"say "hi" \ now"#.into())),
+            parse_value::<VerboseError<&str>>(" ", false, true)(r#""say \"hi\" \\ now""#)
+        );
+
+        // bare boolean
+        assert_eq!(
+            Ok((" foo=bar", true.into())),
+            parse_value::<VerboseError<&str>>(" ", false, true)("true foo=bar")
+        );
+
+        // quoted boolean
+        assert_eq!(
+            Ok(("", "false".into())),
+            parse_value::<VerboseError<&str>>(" ", false, true)(r#""false""#)
+        );
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r#"a\"b\\c\nd\te"#), "a\"b\\c\nd\te");
+        assert_eq!(unescape(r#"\d+\"#), r#"\d+\"#);
+    }
+
+    #[test]
+    fn test_parse_bracketed() {
+        assert_eq!(
+            Ok(("", "10/Oct/2000:13:55:36 -0700")),
+            parse_bracketed::<VerboseError<&str>>(" ")("[10/Oct/2000:13:55:36 -0700]")
+        );
+
+        // nested
+        assert_eq!(
+            Ok((" foo=bar", "a [b] c")),
+            parse_bracketed::<VerboseError<&str>>(" ")("[a [b] c] foo=bar")
+        );
+
+        // quoted closing bracket
+        assert_eq!(
+            Ok(("", r#"a "]" b"#)),
+            parse_bracketed::<VerboseError<&str>>(" ")(r#"[a "]" b]"#)
+        );
+
+        // unterminated
+        assert!(parse_bracketed::<VerboseError<&str>>(" ")("[a b").is_err());
+
+        // trailing text after the closing bracket
+        assert!(parse_bracketed::<VerboseError<&str>>(" ")("[a]b").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let values = || {
+            vec![
+                ("foo".to_string(), "a".into()),
+                ("bar".to_string(), "b".into()),
+                ("foo".to_string(), "c".into()),
+                ("foo".to_string(), "d".into()),
+            ]
+        };
+
+        assert_eq!(
+            Value::from(DuplicateKeys::First.collect(values())),
+            value!({foo: "a", bar: "b"})
+        );
+        assert_eq!(
+            Value::from(DuplicateKeys::Last.collect(values())),
+            value!({foo: "d", bar: "b"})
+        );
+        assert_eq!(
+            Value::from(DuplicateKeys::Array.collect(values())),
+            value!({foo: ["a", "c", "d"], bar: "b"})
         );
     }
 
@@ -814,6 +1209,53 @@ mod test {
             }),
         }
 
+        bracketed {
+            args: func_args! [
+                value: r#"time=[10/Oct/2000:13:55:36 -0700] msg="hello world" tags=[a [b] c]"#,
+                accept_bracketed_values: true,
+            ],
+            want: Ok(value!({time: "10/Oct/2000:13:55:36 -0700",
+                             msg: "hello world",
+                             tags: "a [b] c"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! {
+                (): Kind::all()
+            }),
+        }
+
+        bracketed_disabled {
+            args: func_args! [
+                value: r#"time=[10/Oct/2000:13:55:36] user=frank"#,
+            ],
+            want: Ok(value!({time: "[10/Oct/2000:13:55:36]",
+                             user: "frank"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! {
+                (): Kind::all()
+            }),
+        }
+
+        duplicate_keys_first {
+            args: func_args! [
+                value: r#"srcip=1.1.1.1 srcip=2.2.2.2"#,
+                duplicate_keys: "first",
+            ],
+            want: Ok(value!({srcip: "1.1.1.1"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! {
+                (): Kind::all()
+            }),
+        }
+
+        duplicate_keys_array {
+            args: func_args! [
+                value: r#"srcip=1.1.1.1 proto=tcp srcip=2.2.2.2"#,
+                duplicate_keys: "array",
+            ],
+            want: Ok(value!({srcip: ["1.1.1.1", "2.2.2.2"],
+                             proto: "tcp"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! {
+                (): Kind::all()
+            }),
+        }
+
         multi_line_with_quotes_spaces {
             args: func_args! [
                 value: "To: tom\ntest: \"tom test\"  ",
@@ -1,4 +1,5 @@
-use crate::parse_key_value::{ParseKeyValueFn, Whitespace};
+use crate::parse_key_value::{DuplicateKeys, ParseKeyValueFn, Whitespace};
+use std::str::FromStr;
 use vrl::prelude::*;
 
 #[derive(Clone, Copy, Debug)]
@@ -10,11 +11,18 @@ impl Function for ParseLogFmt {
     }
 
     fn parameters(&self) -> &'static [Parameter] {
-        &[Parameter {
-            keyword: "value",
-            kind: kind::BYTES,
-            required: true,
-        }]
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "duplicate_keys",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
     }
 
     fn examples(&self) -> &'static [Example] {
@@ -29,6 +37,16 @@ impl Function for ParseLogFmt {
                 source: r#"parse_logfmt!("zork=zook plonk zonk=nork")"#,
                 result: Ok(r#"{"plonk": true, "zork": "zook", "zonk": "nork"}"#),
             },
+            Example {
+                title: "escaped quotes and booleans",
+                source: r#"parse_logfmt!(s'msg="say \"hi\"" ok=true')"#,
+                result: Ok(r#"{"msg": "say \"hi\"", "ok": true}"#),
+            },
+            Example {
+                title: "duplicate keys",
+                source: r#"parse_logfmt!("tag=a tag=b", duplicate_keys: "array")"#,
+                result: Ok(r#"{"tag": ["a", "b"]}"#),
+            },
         ]
     }
 
@@ -41,6 +59,16 @@ impl Function for ParseLogFmt {
         let field_delimiter = expr!(" ");
         let whitespace = Whitespace::Lenient;
         let standalone_key = expr!(true);
+        let bracketed_values = expr!(false);
+        let duplicate_keys = arguments
+            .optional_enum("duplicate_keys", &DuplicateKeys::all_value())?
+            .map(|s| {
+                DuplicateKeys::from_str(
+                    &s.try_bytes_utf8_lossy().expect("duplicate_keys not bytes"),
+                )
+                .expect("validated enum")
+            })
+            .unwrap_or_default();
 
         Ok(Box::new(ParseKeyValueFn {
             value,
@@ -48,6 +76,42 @@ impl Function for ParseLogFmt {
             field_delimiter,
             whitespace,
             standalone_key,
+            bracketed_values,
+            duplicate_keys,
+            logfmt: true,
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_logfmt => ParseLogFmt;
+
+        escaped_quotes {
+            args: func_args![value: r#"level=info msg="say \"hi\" to \\\\server\"" id=1"#],
+            want: Ok(value!({level: "info", msg: r#"say "hi" to \\server""#, id: "1"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        booleans {
+            args: func_args![value: r#"debug verbose=true cached=false quoted="true""#],
+            want: Ok(value!({debug: true, verbose: true, cached: false, quoted: "true"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        duplicate_keys_last {
+            args: func_args![value: "tag=a tag=b"],
+            want: Ok(value!({tag: "b"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        duplicate_keys_array {
+            args: func_args![value: "tag=a host=b tag=c", duplicate_keys: "array"],
+            want: Ok(value!({tag: ["a", "c"], host: "b"})),
+            tdef: TypeDef::new().fallible().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+    ];
+}
@@ -1,4 +1,4 @@
-use crate::log_util;
+use crate::log_util::{self, CustomLogFormat};
 use regex::Regex;
 use std::collections::BTreeMap;
 use vrl::prelude::*;
@@ -28,11 +28,16 @@ impl Function for ParseNginxLog {
                 kind: kind::BYTES,
                 required: false,
             },
+            Parameter {
+                keyword: "log_format",
+                kind: kind::BYTES,
+                required: false,
+            },
         ]
     }
 
     fn compile(&self, mut arguments: ArgumentList) -> Compiled {
-        let variants = vec![value!("combined"), value!("error")];
+        let variants = vec![value!("combined"), value!("error"), value!("custom")];
 
         let value = arguments.required("value");
         let format = arguments
@@ -41,11 +46,13 @@ impl Function for ParseNginxLog {
             .expect("format not bytes");
 
         let timestamp_format = arguments.optional("timestamp_format");
+        let custom = log_util::custom_log_format(&mut arguments, &format, CustomLogFormat::nginx)?;
 
         Ok(Box::new(ParseNginxLogFn {
             value,
             format,
             timestamp_format,
+            custom,
         }))
     }
 
@@ -65,6 +72,13 @@ impl Function for ParseNginxLog {
                     r#"s'{"cid":1,"client":"172.17.0.1","host":"localhost:8081","message":"open() \"/usr/share/nginx/html/not-found\" failed (2: No such file or directory)","pid":31,"request":"POST /not-found HTTP/1.1","server":"localhost","severity":"error","tid":31,"timestamp":"2021-04-01T13:02:31Z"}'"#,
                 ),
             },
+            Example {
+                title: "parse nginx custom log",
+                source: r#"encode_json(parse_nginx_log!(s'172.17.0.1 [31/Mar/2021:12:04:07 +0000] "GET / HTTP/1.1" 200 612 0.005 "10.0.0.1"', "custom", log_format: s'$remote_addr [$time_local] "$request" $status $body_bytes_sent $request_time "$http_x_forwarded_for"'))"#,
+                result: Ok(
+                    r#"s'{"client":"172.17.0.1","http_x_forwarded_for":"10.0.0.1","method":"GET","path":"/","protocol":"HTTP/1.1","request":"GET / HTTP/1.1","request_time":0.005,"size":612,"status":200,"timestamp":"2021-03-31T12:04:07Z"}'"#,
+                ),
+            },
         ]
     }
 }
@@ -99,6 +113,7 @@ struct ParseNginxLogFn {
     value: Box<dyn Expression>,
     format: Bytes,
     timestamp_format: Option<Box<dyn Expression>>,
+    custom: Option<CustomLogFormat>,
 }
 
 impl Expression for ParseNginxLogFn {
@@ -106,13 +121,24 @@ impl Expression for ParseNginxLogFn {
         let bytes = self.value.resolve(ctx)?;
         let message = bytes.try_bytes_utf8_lossy()?;
         let timestamp_format = match &self.timestamp_format {
-            None => time_format_for_format(self.format.as_ref()),
-            Some(timestamp_format) => timestamp_format
-                .resolve(ctx)?
-                .try_bytes_utf8_lossy()?
-                .to_string(),
+            None => None,
+            Some(timestamp_format) => Some(
+                timestamp_format
+                    .resolve(ctx)?
+                    .try_bytes_utf8_lossy()?
+                    .to_string(),
+            ),
         };
 
+        if let Some(custom) = &self.custom {
+            return custom
+                .log_fields(&message, timestamp_format.as_deref(), ctx.timezone())
+                .map_err(Into::into);
+        }
+
+        let timestamp_format =
+            timestamp_format.unwrap_or_else(|| time_format_for_format(self.format.as_ref()));
+
         let regex = regex_for_format(self.format.as_ref());
 
         let captures = regex.captures(&message).ok_or("failed parsing log line")?;
@@ -123,13 +149,14 @@ impl Expression for ParseNginxLogFn {
     }
 
     fn type_def(&self, _: &state::Compiler) -> TypeDef {
-        TypeDef::new()
-            .fallible()
-            .object(match self.format.as_ref() {
-                b"combined" => type_def_combined(),
-                b"error" => type_def_error(),
-                _ => unreachable!(),
-            })
+        let type_def = TypeDef::new().fallible();
+
+        match (&self.custom, self.format.as_ref()) {
+            (Some(custom), _) => type_def.object(custom.type_def()),
+            (None, b"combined") => type_def.object(type_def_combined()),
+            (None, b"error") => type_def.object(type_def_error()),
+            _ => unreachable!(),
+        }
     }
 }
 
@@ -291,5 +318,54 @@ mod tests {
             }),
             tdef: TypeDef::new().fallible().object(type_def_error()),
         }
+
+        custom_line_valid {
+            args: func_args![
+                value: r#"172.17.0.1 - alice [31/Mar/2021:12:04:07 +0000] "GET / HTTP/1.1" 200 612 "-" "curl/7.75.0" 0.005 "upstream-1:8080" 1.50"#,
+                format: "custom",
+                log_format: r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent" $request_time "$upstream_addr" $gzip_ratio"#,
+            ],
+            want: Ok(btreemap! {
+                "client" => "172.17.0.1",
+                "user" => "alice",
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2021-03-31T12:04:07Z").unwrap().into()),
+                "request" => "GET / HTTP/1.1",
+                "method" => "GET",
+                "path" => "/",
+                "protocol" => "HTTP/1.1",
+                "status" => 200,
+                "size" => 612,
+                "agent" => "curl/7.75.0",
+                "request_time" => 0.005,
+                "upstream_addr" => "upstream-1:8080",
+                "gzip_ratio" => 1.5,
+            }),
+            tdef: TypeDef::new().fallible().object(CustomLogFormat::nginx(r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent" $request_time "$upstream_addr" $gzip_ratio"#).unwrap().type_def()),
+        }
+
+        custom_line_braced_variables {
+            args: func_args![
+                value: "2021-03-31T12:04:07+00:00 example.com:443 42",
+                format: "custom",
+                log_format: "$time_iso8601 ${host}:$server_port $connection",
+            ],
+            want: Ok(btreemap! {
+                "timestamp" => Value::Timestamp(DateTime::parse_from_rfc3339("2021-03-31T12:04:07Z").unwrap().into()),
+                "host" => "example.com",
+                "server_port" => 443,
+                "connection" => 42,
+            }),
+            tdef: TypeDef::new().fallible().object(CustomLogFormat::nginx("$time_iso8601 ${host}:$server_port $connection").unwrap().type_def()),
+        }
+
+        custom_duplicate_field {
+            args: func_args![
+                value: "172.17.0.1 172.17.0.1",
+                format: "custom",
+                log_format: "$remote_addr $remote_addr",
+            ],
+            want: Err("invalid argument"),
+            tdef: TypeDef::new().fallible(),
+        }
     ];
 }
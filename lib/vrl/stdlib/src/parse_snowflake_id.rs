@@ -0,0 +1,157 @@
+use chrono::{TimeZone, Utc};
+use std::collections::BTreeMap;
+use vrl::prelude::*;
+
+/// Twitter's epoch, in Unix milliseconds.
+const TWITTER_EPOCH: i64 = 1_288_834_974_657;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParseSnowflakeId;
+
+impl Function for ParseSnowflakeId {
+    fn identifier(&self) -> &'static str {
+        "parse_snowflake_id"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES | kind::INTEGER,
+                required: true,
+            },
+            Parameter {
+                keyword: "epoch",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "twitter",
+                source: r#"parse_snowflake_id!("1382350606417817604")"#,
+                result: Ok(
+                    r#"{ "timestamp": "2021-04-14T15:10:42.059Z", "machine_id": 327, "sequence": 4 }"#,
+                ),
+            },
+            Example {
+                title: "discord",
+                source: r#"parse_snowflake_id!(175928847299117063, epoch: 1420070400000)"#,
+                result: Ok(
+                    r#"{ "timestamp": "2016-04-30T11:18:25.796Z", "machine_id": 32, "sequence": 7 }"#,
+                ),
+            },
+            Example {
+                title: "invalid",
+                source: r#"parse_snowflake_id!("foo")"#,
+                result: Err(
+                    r#"function call error for "parse_snowflake_id" at (0:26): unable to parse snowflake ID: invalid digit found in string"#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let epoch = arguments.optional("epoch").unwrap_or(expr!(TWITTER_EPOCH));
+
+        Ok(Box::new(ParseSnowflakeIdFn { value, epoch }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParseSnowflakeIdFn {
+    value: Box<dyn Expression>,
+    epoch: Box<dyn Expression>,
+}
+
+impl Expression for ParseSnowflakeIdFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let id = match self.value.resolve(ctx)? {
+            Value::Bytes(bytes) => String::from_utf8_lossy(&bytes)
+                .parse::<u64>()
+                .map_err(|err| format!("unable to parse snowflake ID: {}", err))?,
+            Value::Integer(id) if id >= 0 => id as u64,
+            Value::Integer(id) => return Err(format!("invalid snowflake ID: {}", id).into()),
+            value => {
+                return Err(value::Error::Expected {
+                    got: value.kind(),
+                    expected: Kind::Bytes | Kind::Integer,
+                }
+                .into())
+            }
+        };
+        let epoch = self.epoch.resolve(ctx)?.try_integer()?;
+
+        let millis = epoch
+            .checked_add((id >> 22) as i64)
+            .ok_or("snowflake ID timestamp out of range")?;
+        let timestamp = Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .ok_or("snowflake ID timestamp out of range")?;
+
+        let mut map = BTreeMap::new();
+        map.insert("timestamp".to_owned(), timestamp.into());
+        map.insert("machine_id".to_owned(), ((id >> 12) & 0x3ff).into());
+        map.insert("sequence".to_owned(), (id & 0xfff).into());
+
+        Ok(map.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().object(type_def())
+    }
+}
+
+fn type_def() -> BTreeMap<&'static str, TypeDef> {
+    map! {
+        "timestamp": Kind::Timestamp,
+        "machine_id": Kind::Integer,
+        "sequence": Kind::Integer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        parse_snowflake_id => ParseSnowflakeId;
+
+        twitter {
+            args: func_args![value: "1382350606417817604"],
+            want: Ok(value!({
+                timestamp: (Utc.ymd(2021, 4, 14).and_hms_milli(15, 10, 42, 59)),
+                machine_id: 327,
+                sequence: 4,
+            })),
+            tdef: TypeDef::new().fallible().object(type_def()),
+        }
+
+        discord {
+            args: func_args![value: 175928847299117063_i64, epoch: 1420070400000_i64],
+            want: Ok(value!({
+                timestamp: (Utc.ymd(2016, 4, 30).and_hms_milli(11, 18, 25, 796)),
+                machine_id: 32,
+                sequence: 7,
+            })),
+            tdef: TypeDef::new().fallible().object(type_def()),
+        }
+
+        invalid_string {
+            args: func_args![value: "12ab"],
+            want: Err("unable to parse snowflake ID: invalid digit found in string"),
+            tdef: TypeDef::new().fallible().object(type_def()),
+        }
+
+        negative {
+            args: func_args![value: -1],
+            want: Err("invalid snowflake ID: -1"),
+            tdef: TypeDef::new().fallible().object(type_def()),
+        }
+    ];
+}
@@ -1,4 +1,5 @@
 use lazy_static::lazy_static;
+use regex::Regex;
 use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
@@ -12,6 +13,14 @@ lazy_static! {
         let regexes = include_bytes!("./../data/user_agent_regexes.yaml");
         UAParser::from_bytes(regexes).expect("Regex file is not valid.")
     };
+
+    // Device families and models reported by the uap project that are known to be tablets.
+    // The uap database doesn't classify devices itself, and Woothee reports tablets as
+    // smartphones, so this is used to refine the device category.
+    static ref TABLET_REGEX: Regex = Regex::new(
+        r"(?i)\b(ipad|tablet|kindle|playbook|slate|xoom|galaxy tab|nexus (?:7|9|10)|sm-t\d+|gt-p\d+|mediapad|matepad)\b",
+    )
+    .expect("failed compiling tablet regex");
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -77,7 +86,7 @@ impl Function for ParseUserAgent {
                 title: "device",
                 source: r#"parse_user_agent("Mozilla/5.0 (Linux; Android 4.4.4; HP Slate 17 Build/KTU84P) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/33.0.0.0 Safari/537.36ESPN APP", mode: "enriched")"#,
                 result: Ok(
-                    r#"{ "browser": { "family": "ESPN", "major": null, "minor": null, "patch": null, "version": "33.0.0.0" }, "device": { "brand": "HP", "category": "smartphone", "family": "HP Slate 17", "model": "Slate 17" }, "os": { "family": "Android", "major": "4", "minor": "4", "patch": "4", "patch_minor": null, "version": "4.4.4" } }"#,
+                    r#"{ "browser": { "family": "ESPN", "major": null, "minor": null, "patch": null, "version": "33.0.0.0" }, "device": { "brand": "HP", "category": "tablet", "family": "HP Slate 17", "model": "Slate 17" }, "os": { "family": "Android", "major": "4", "minor": "4", "patch": "4", "patch_minor": null, "version": "4.4.4" } }"#,
                 ),
             },
         ]
@@ -521,16 +530,40 @@ impl Parser for UAParser {
                 patch_minor: unknown_to_none(ua.os.patch_minor),
                 ..Default::default()
             },
-            device: Device {
-                family: unknown_to_none(ua.device.family),
-                brand: unknown_to_none(ua.device.brand),
-                model: unknown_to_none(ua.device.model),
-                ..Default::default()
+            device: {
+                let family = unknown_to_none(ua.device.family);
+                let brand = unknown_to_none(ua.device.brand);
+                let model = unknown_to_none(ua.device.model);
+
+                Device {
+                    category: device_category(family.as_deref(), model.as_deref()),
+                    family,
+                    brand,
+                    model,
+                }
             },
         }
     }
 }
 
+/// Classifies a device from the family and model reported by the uap project.
+///
+/// Returns `None` if the device can't be classified, in which case the category
+/// detected by Woothee is used.
+fn device_category(family: Option<&str>, model: Option<&str>) -> Option<String> {
+    match family {
+        Some("Spider") => Some("crawler".to_owned()),
+        _ if family
+            .into_iter()
+            .chain(model)
+            .any(|s| TABLET_REGEX.is_match(s)) =>
+        {
+            Some("tablet".to_owned())
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -549,5 +582,33 @@ mod tests {
             want: Ok(value!({ browser: { family: null, major: null, minor: null, patch: null, version: null }, device: { brand: null, category: null, family: null, model: null }, os: { family: null, major: null, minor: null, patch: null, patch_minor: null, version: null } })),
             tdef: Mode::Enriched.type_def(),
         }
+
+        tablet {
+            args: func_args![ value: r#"Mozilla/5.0 (Linux; Android 4.4.4; HP Slate 17 Build/KTU84P) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/33.0.0.0 Safari/537.36ESPN APP"#, mode: "enriched"],
+            want: Ok(value!({ browser: { family: "ESPN", major: null, minor: null, patch: null, version: "33.0.0.0" }, device: { brand: "HP", category: "tablet", family: "HP Slate 17", model: "Slate 17" }, os: { family: "Android", major: "4", minor: "4", patch: "4", patch_minor: null, version: "4.4.4" } })),
+            tdef: Mode::Enriched.type_def(),
+        }
     ];
+
+    #[test]
+    fn classify_device() {
+        assert_eq!(
+            device_category(Some("Spider"), None),
+            Some("crawler".to_owned())
+        );
+        assert_eq!(
+            device_category(Some("iPad"), Some("iPad")),
+            Some("tablet".to_owned())
+        );
+        assert_eq!(
+            device_category(Some("Samsung SM-T800"), Some("SM-T800")),
+            Some("tablet".to_owned())
+        );
+        assert_eq!(
+            device_category(Some("Kindle"), None),
+            Some("tablet".to_owned())
+        );
+        assert_eq!(device_category(Some("iPhone"), Some("iPhone")), None);
+        assert_eq!(device_category(None, None), None);
+    }
 }
@@ -0,0 +1,122 @@
+use crate::util;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Percentile;
+
+impl Function for Percentile {
+    fn identifier(&self) -> &'static str {
+        "percentile"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::ARRAY,
+                required: true,
+            },
+            Parameter {
+                keyword: "percentile",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "95th percentile",
+                source: r#"percentile!([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], 95)"#,
+                result: Ok("10.5"),
+            },
+            Example {
+                title: "minimum",
+                source: r#"percentile!([5, 1.5, 3], 0)"#,
+                result: Ok("1.5"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let percentile = arguments.required("percentile");
+
+        Ok(Box::new(PercentileFn { value, percentile }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PercentileFn {
+    value: Box<dyn Expression>,
+    percentile: Box<dyn Expression>,
+}
+
+impl Expression for PercentileFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let percentile = match self.percentile.resolve(ctx)? {
+            Value::Integer(percentile) => percentile as f64,
+            Value::Float(percentile) => percentile.into_inner(),
+            value => {
+                return Err(value::Error::Expected {
+                    got: value.kind(),
+                    expected: Kind::Integer | Kind::Float,
+                }
+                .into())
+            }
+        };
+
+        if !(0.0..=100.0).contains(&percentile) {
+            return Err(format!("percentile must be between 0 and 100, got {}", percentile).into());
+        }
+
+        let array = self.value.resolve(ctx)?.try_array()?;
+        let numbers = util::numbers(&array)?;
+
+        Ok(util::percentile(numbers, percentile).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().float().add_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        percentile => Percentile;
+
+        interpolated {
+            args: func_args![value: value!([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]), percentile: 95],
+            want: Ok(value!(10.5)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        unsorted {
+            args: func_args![value: value!([40, 10, 30, 20]), percentile: 25.0],
+            want: Ok(value!(17.5)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        bounds {
+            args: func_args![value: value!(vec![value!(5), value!(1.5), value!(3)]), percentile: 100],
+            want: Ok(value!(5.0)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        empty {
+            args: func_args![value: value!([]), percentile: 50],
+            want: Ok(value!(null)),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+
+        out_of_range {
+            args: func_args![value: value!([1, 2]), percentile: 101],
+            want: Err("percentile must be between 0 and 100, got 101"),
+            tdef: TypeDef::new().fallible().float().add_null(),
+        }
+    ];
+}
@@ -0,0 +1,413 @@
+use std::iter::Peekable;
+use std::str::Chars;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Query;
+
+impl Function for Query {
+    fn identifier(&self) -> &'static str {
+        "query"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::ANY,
+                required: true,
+            },
+            Parameter {
+                keyword: "expression",
+                kind: kind::BYTES,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "wildcard",
+                source: r#"query({ "users": [{ "name": "foo" }, { "name": "bar" }] }, ".users[].name")"#,
+                result: Ok(r#"["foo", "bar"]"#),
+            },
+            Example {
+                title: "recursive descent",
+                source: r#"query({ "a": { "id": 1, "b": { "id": 2 } } }, "..id")"#,
+                result: Ok("[1, 2]"),
+            },
+            Example {
+                title: "slice",
+                source: r#"query({ "items": [1, 2, 3, 4] }, ".items[1:3]")"#,
+                result: Ok("[[2, 3]]"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let expression = arguments.required_literal("expression")?.to_value();
+
+        let segments = match &expression {
+            Value::Bytes(bytes) => parse(&String::from_utf8_lossy(bytes)),
+            _ => Err("query must be a string"),
+        }
+        .map_err(|error| vrl::function::Error::InvalidArgument {
+            keyword: "expression",
+            value: expression.clone(),
+            error,
+        })?;
+
+        Ok(Box::new(QueryFn { value, segments }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct QueryFn {
+    value: Box<dyn Expression>,
+    segments: Vec<Segment>,
+}
+
+impl Expression for QueryFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+
+        let matches = self
+            .segments
+            .iter()
+            .fold(vec![value], |values, segment| segment.apply(values));
+
+        Ok(matches.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new()
+            .infallible()
+            .array_mapped::<(), Kind>(map! { (): Kind::all() })
+    }
+}
+
+/// A step of a query, each of which maps every value matched so far to any
+/// number of values.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// `.foo`, `."foo bar"` or `["foo"]`: the field of an object.
+    Field(String),
+
+    /// `[1]` or `[-1]`: the element of an array, counting from the end if
+    /// negative.
+    Index(i64),
+
+    /// `[1:3]`: the elements of an array from the start up to, but not
+    /// including, the end, either of which may be left out or negative.
+    Slice(Option<i64>, Option<i64>),
+
+    /// `[]`: every element of an array or value of an object.
+    Iterate,
+
+    /// `..`: the value itself and every value nested in it.
+    Recurse,
+}
+
+impl Segment {
+    fn apply(&self, values: Vec<Value>) -> Vec<Value> {
+        let mut matches = Vec::new();
+
+        for value in values {
+            match (self, value) {
+                (Segment::Field(field), Value::Object(mut map)) => {
+                    matches.extend(map.remove(field));
+                }
+                (Segment::Index(index), Value::Array(mut array)) => {
+                    if let Some(index) = resolve_index(*index, array.len()) {
+                        if index < array.len() {
+                            matches.push(array.swap_remove(index));
+                        }
+                    }
+                }
+                (Segment::Slice(start, end), Value::Array(array)) => {
+                    let len = array.len();
+                    let start = start.and_then(|i| resolve_index(i, len)).unwrap_or(0);
+                    let end = end
+                        .map(|i| resolve_index(i, len).unwrap_or(0))
+                        .unwrap_or(len)
+                        .min(len);
+
+                    let slice = array
+                        .into_iter()
+                        .skip(start)
+                        .take(end.saturating_sub(start))
+                        .collect::<Vec<_>>();
+                    matches.push(slice.into());
+                }
+                (Segment::Iterate, Value::Array(array)) => matches.extend(array),
+                (Segment::Iterate, Value::Object(map)) => matches.extend(map.into_values()),
+                (Segment::Recurse, value) => recurse(value, &mut matches),
+                _ => {}
+            }
+        }
+
+        matches
+    }
+}
+
+/// The position `index` refers to in an array of `len` elements, or `None`
+/// if it's negative and counts back past the start of the array.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    }
+}
+
+fn recurse(value: Value, matches: &mut Vec<Value>) {
+    matches.push(value.clone());
+
+    match value {
+        Value::Array(array) => array.into_iter().for_each(|value| recurse(value, matches)),
+        Value::Object(map) => map.into_values().for_each(|value| recurse(value, matches)),
+        _ => {}
+    }
+}
+
+/// Parses a query such as `.users[].name`, `..id` or `.items[1:3]`.
+fn parse(query: &str) -> std::result::Result<Vec<Segment>, &'static str> {
+    let query = query.trim();
+    if query == "." {
+        return Ok(Vec::new());
+    }
+
+    if !query.starts_with('.') {
+        return Err("query must start with `.`");
+    }
+
+    let mut chars = query.chars().peekable();
+    let mut segments = Vec::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' if chars.peek() == Some(&'.') => {
+                chars.next();
+                segments.push(Segment::Recurse);
+
+                // `..foo` is short for `..` followed by `.foo`.
+                if let Some(field) = parse_field(&mut chars)? {
+                    segments.push(Segment::Field(field));
+                }
+            }
+            '.' => match parse_field(&mut chars)? {
+                Some(field) => segments.push(Segment::Field(field)),
+                None if chars.peek() == Some(&'[') => {}
+                None => return Err("expected a field name after `.`"),
+            },
+            '[' => segments.push(parse_brackets(&mut chars)?),
+            _ => return Err("unexpected character in query"),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parses a bare or quoted field name, if one follows.
+fn parse_field(chars: &mut Peekable<Chars>) -> std::result::Result<Option<String>, &'static str> {
+    match chars.peek() {
+        Some('"') => {
+            chars.next();
+            parse_string(chars).map(Some)
+        }
+        Some(c) if c.is_alphanumeric() || *c == '_' => {
+            let mut field = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                field.push(*c);
+                chars.next();
+            }
+            Ok(Some(field))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parses the rest of a double quoted string, after the opening quote.
+fn parse_string(chars: &mut Peekable<Chars>) -> std::result::Result<String, &'static str> {
+    let mut string = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some(c) => string.push(c),
+                None => return Err("unterminated string in query"),
+            },
+            Some(c) => string.push(c),
+            None => return Err("unterminated string in query"),
+        }
+    }
+}
+
+/// Parses the rest of an index, slice, iterator or quoted field in brackets,
+/// after the opening bracket.
+fn parse_brackets(chars: &mut Peekable<Chars>) -> std::result::Result<Segment, &'static str> {
+    skip_whitespace(chars);
+
+    let segment = match chars.peek() {
+        Some(']') => Segment::Iterate,
+        Some('"') => {
+            chars.next();
+            Segment::Field(parse_string(chars)?)
+        }
+        _ => {
+            let start = parse_integer(chars)?;
+            skip_whitespace(chars);
+
+            if chars.peek() == Some(&':') {
+                chars.next();
+                skip_whitespace(chars);
+                Segment::Slice(start, parse_integer(chars)?)
+            } else {
+                Segment::Index(start.ok_or("expected an index, slice or `]`")?)
+            }
+        }
+    };
+
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(']') => Ok(segment),
+        _ => Err("expected `]`"),
+    }
+}
+
+fn parse_integer(chars: &mut Peekable<Chars>) -> std::result::Result<Option<i64>, &'static str> {
+    let mut integer = String::new();
+    while let Some(c) = chars
+        .peek()
+        .filter(|c| c.is_ascii_digit() || (integer.is_empty() && **c == '-'))
+    {
+        integer.push(*c);
+        chars.next();
+    }
+
+    if integer.is_empty() {
+        return Ok(None);
+    }
+
+    integer.parse().map(Some).map_err(|_| "invalid index")
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tdef() -> TypeDef {
+        TypeDef::new()
+            .infallible()
+            .array_mapped::<(), Kind>(map! { (): Kind::all() })
+    }
+
+    test_function![
+        query => Query;
+
+        identity {
+            args: func_args![value: value!({foo: 1}), expression: "."],
+            want: Ok(value!([{foo: 1}])),
+            tdef: tdef(),
+        }
+
+        field {
+            args: func_args![value: value!({foo: {bar: true}}), expression: ".foo.bar"],
+            want: Ok(value!([true])),
+            tdef: tdef(),
+        }
+
+        quoted_field {
+            args: func_args![value: value!({"foo bar": {"baz.qux": 1}}), expression: r#"."foo bar"["baz.qux"]"#],
+            want: Ok(value!([1])),
+            tdef: tdef(),
+        }
+
+        missing_field {
+            args: func_args![value: value!({foo: [1, 2]}), expression: ".bar"],
+            want: Ok(value!([])),
+            tdef: tdef(),
+        }
+
+        index {
+            args: func_args![value: value!({foo: [1, 2, 3]}), expression: ".foo[0]"],
+            want: Ok(value!([1])),
+            tdef: tdef(),
+        }
+
+        negative_index {
+            args: func_args![value: value!([1, 2, 3]), expression: ".[-1]"],
+            want: Ok(value!([3])),
+            tdef: tdef(),
+        }
+
+        out_of_range_index {
+            args: func_args![value: value!([1, 2, 3]), expression: ".[3]"],
+            want: Ok(value!([])),
+            tdef: tdef(),
+        }
+
+        slice {
+            args: func_args![value: value!([1, 2, 3, 4]), expression: ".[1:3]"],
+            want: Ok(value!([[2, 3]])),
+            tdef: tdef(),
+        }
+
+        open_slice {
+            args: func_args![value: value!([1, 2, 3, 4]), expression: ".[-2:]"],
+            want: Ok(value!([[3, 4]])),
+            tdef: tdef(),
+        }
+
+        iterate_array {
+            args: func_args![value: value!({users: [{name: "foo"}, {id: 1}, {name: "bar"}]}), expression: ".users[].name"],
+            want: Ok(value!(["foo", "bar"])),
+            tdef: tdef(),
+        }
+
+        iterate_object {
+            args: func_args![value: value!({a: {id: 1}, b: {id: 2}}), expression: ".[].id"],
+            want: Ok(value!([1, 2])),
+            tdef: tdef(),
+        }
+
+        recurse {
+            args: func_args![value: value!({a: {id: 1, b: [{id: 2}, {c: {id: 3}}]}}), expression: "..id"],
+            want: Ok(value!([1, 2, 3])),
+            tdef: tdef(),
+        }
+
+        recurse_all {
+            args: func_args![value: value!({a: [1]}), expression: ".."],
+            want: Ok(value!([{a: [1]}, [1], 1])),
+            tdef: tdef(),
+        }
+
+        invalid_start {
+            args: func_args![value: value!({}), expression: "foo"],
+            want: Err("invalid argument"),
+            tdef: tdef(),
+        }
+
+        invalid_brackets {
+            args: func_args![value: value!({}), expression: ".foo[1"],
+            want: Err("invalid argument"),
+            tdef: tdef(),
+        }
+
+        trailing_dot {
+            args: func_args![value: value!({}), expression: ".foo."],
+            want: Err("invalid argument"),
+            tdef: tdef(),
+        }
+    ];
+}
@@ -0,0 +1,87 @@
+use crate::dns_util::{parse_timeout, resolve_addr};
+use std::net::IpAddr;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReverseDns;
+
+impl Function for ReverseDns {
+    fn identifier(&self) -> &'static str {
+        "reverse_dns"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::BYTES,
+                required: true,
+            },
+            Parameter {
+                keyword: "timeout",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "loopback",
+            source: r#"reverse_dns!("127.0.0.1") != """#,
+            result: Ok("true"),
+        }]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let timeout = arguments.optional("timeout");
+
+        Ok(Box::new(ReverseDnsFn { value, timeout }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReverseDnsFn {
+    value: Box<dyn Expression>,
+    timeout: Option<Box<dyn Expression>>,
+}
+
+impl Expression for ReverseDnsFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let addr: IpAddr = self
+            .value
+            .resolve(ctx)?
+            .try_bytes_utf8_lossy()?
+            .parse()
+            .map_err(|err| format!("unable to parse IP address: {}", err))?;
+
+        let timeout = self
+            .timeout
+            .as_ref()
+            .map(|expr| expr.resolve(ctx))
+            .transpose()?;
+        let timeout = parse_timeout(timeout)?;
+
+        Ok(resolve_addr(addr, timeout)?.into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        reverse_dns => ReverseDns;
+
+        invalid_ip {
+            args: func_args![value: "not an ip"],
+            want: Err("unable to parse IP address: invalid IP address syntax"),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+    ];
+}
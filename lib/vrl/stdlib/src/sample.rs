@@ -0,0 +1,153 @@
+use crate::util::{sample_hash, sample_seed, sample_threshold};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sample;
+
+impl Function for Sample {
+    fn identifier(&self) -> &'static str {
+        "sample"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "rate",
+                kind: kind::FLOAT | kind::INTEGER,
+                required: true,
+            },
+            Parameter {
+                keyword: "key",
+                kind: kind::ANY,
+                required: false,
+            },
+            Parameter {
+                keyword: "seed",
+                kind: kind::INTEGER,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "everything",
+                source: r#"sample(1)"#,
+                result: Ok("true"),
+            },
+            Example {
+                title: "nothing",
+                source: r#"sample(0)"#,
+                result: Ok("false"),
+            },
+            Example {
+                title: "not sampled",
+                source: r#"sample(0.5, "4bf92f3577b34da6")"#,
+                result: Ok("false"),
+            },
+            Example {
+                title: "sampled with seed",
+                source: r#"sample(0.5, "4bf92f3577b34da6", seed: 1)"#,
+                result: Ok("true"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let threshold = sample_threshold(&mut arguments)?;
+        let key = arguments.optional("key");
+        let seed = sample_seed(&mut arguments)?;
+
+        if let (None, Some(seed)) = (&key, seed) {
+            return Err(Box::new(vrl::function::Error::InvalidArgument {
+                keyword: "seed",
+                value: Value::Integer(seed as i64),
+                error: "seed requires a key",
+            }));
+        }
+
+        Ok(Box::new(SampleFn {
+            key,
+            threshold,
+            seed: seed.unwrap_or(0),
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SampleFn {
+    key: Option<Box<dyn Expression>>,
+    threshold: u64,
+    seed: u64,
+}
+
+impl Expression for SampleFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let draw = match &self.key {
+            Some(key) => sample_hash(&key.resolve(ctx)?, self.seed),
+            None => rand::random(),
+        };
+
+        Ok(sampled(self.threshold, draw).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().infallible().boolean()
+    }
+}
+
+/// Whether a draw from the hash space falls in the sample.
+fn sampled(threshold: u64, draw: u64) -> bool {
+    match threshold {
+        0 => false,
+        u64::MAX => true,
+        threshold => draw < threshold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::rate_threshold;
+
+    test_function![
+        sample => Sample;
+
+        rate_one {
+            args: func_args![rate: 1],
+            want: Ok(true),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        rate_zero {
+            args: func_args![rate: 0.0],
+            want: Ok(false),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        keyed {
+            args: func_args![rate: 0.5, key: "4bf92f3577b34da6"],
+            want: Ok(false),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+
+        keyed_seeded {
+            args: func_args![rate: 0.5, key: "4bf92f3577b34da6", seed: 1],
+            want: Ok(true),
+            tdef: TypeDef::new().infallible().boolean(),
+        }
+    ];
+
+    #[test]
+    fn samples_draws_below_the_rate() {
+        let threshold = rate_threshold(0.25).unwrap();
+
+        assert!(sampled(threshold, 0));
+        assert!(sampled(threshold, threshold - 1));
+        assert!(!sampled(threshold, threshold));
+        assert!(!sampled(threshold, u64::MAX));
+        assert!(!sampled(0, 0));
+        assert!(sampled(u64::MAX, u64::MAX));
+    }
+}
@@ -0,0 +1,125 @@
+use std::convert::TryFrom;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SaturatingMul;
+
+impl Function for SaturatingMul {
+    fn identifier(&self) -> &'static str {
+        "saturating_mul"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+            Parameter {
+                keyword: "other",
+                kind: kind::INTEGER | kind::FLOAT,
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "integers",
+                source: r#"saturating_mul(3, 4)"#,
+                result: Ok("12"),
+            },
+            Example {
+                title: "overflow",
+                source: r#"saturating_mul(9223372036854775807, 2)"#,
+                result: Ok("9223372036854775807"),
+            },
+            Example {
+                title: "underflow",
+                source: r#"saturating_mul(-9223372036854775807, 2)"#,
+                result: Ok("-9223372036854775808"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let other = arguments.required("other");
+
+        Ok(Box::new(SaturatingMulFn { value, other }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SaturatingMulFn {
+    value: Box<dyn Expression>,
+    other: Box<dyn Expression>,
+}
+
+impl Expression for SaturatingMulFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let value = self.value.resolve(ctx)?;
+        let other = self.other.resolve(ctx)?;
+
+        match (value, other) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => Ok(lhs.saturating_mul(rhs).into()),
+            (lhs, rhs) => {
+                let product = f64::try_from(&lhs)? * f64::try_from(&rhs)?;
+
+                Ok(product.clamp(f64::MIN, f64::MAX).into())
+            }
+        }
+    }
+
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        let integers =
+            self.value.type_def(state).is_integer() && self.other.type_def(state).is_integer();
+
+        if integers {
+            TypeDef::new().infallible().integer()
+        } else {
+            TypeDef::new().infallible().integer().add_float()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        saturating_mul => SaturatingMul;
+
+        integers {
+            args: func_args![value: 3, other: -4],
+            want: Ok(value!(-12)),
+            tdef: TypeDef::new().infallible().integer(),
+        }
+
+        float {
+            args: func_args![value: 3, other: 0.5],
+            want: Ok(value!(1.5)),
+            tdef: TypeDef::new().infallible().integer().add_float(),
+        }
+
+        integer_overflow {
+            args: func_args![value: i64::MAX, other: 2],
+            want: Ok(value!(i64::MAX)),
+            tdef: TypeDef::new().infallible().integer(),
+        }
+
+        integer_underflow {
+            args: func_args![value: i64::MIN, other: 2],
+            want: Ok(value!(i64::MIN)),
+            tdef: TypeDef::new().infallible().integer(),
+        }
+
+        float_overflow {
+            args: func_args![value: f64::MAX, other: -2.0],
+            want: Ok(value!(f64::MIN)),
+            tdef: TypeDef::new().infallible().integer().add_float(),
+        }
+    ];
+}
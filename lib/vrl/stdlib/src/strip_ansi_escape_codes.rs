@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use vrl::prelude::*;
+use vte::{Parser, Perform};
 
 #[derive(Clone, Copy, Debug)]
 pub struct StripAnsiEscapeCodes;
@@ -37,21 +38,66 @@ impl Expression for StripAnsiEscapeCodesFn {
     fn resolve(&self, ctx: &mut Context) -> Resolved {
         let bytes = self.value.resolve(ctx)?.try_bytes()?;
 
-        strip_ansi_escapes::strip(&bytes)
-            .map(Bytes::from)
-            .map(Value::from)
-            .map(Into::into)
-            .map_err(|e| e.to_string().into())
+        Ok(Bytes::from(strip(&bytes)).into())
     }
 
     fn type_def(&self, _: &state::Compiler) -> TypeDef {
-        // We're marking this as infallible, because `strip_ansi_escapes` only
-        // fails if it can't write to the buffer, which is highly unlikely to
-        // occur.
         TypeDef::new().infallible().bytes()
     }
 }
 
+/// Strips escape sequences, keeping the text around them along with tabs,
+/// carriage returns and newlines. The C1 control codes, such as the single
+/// character CSI `\u{9b}`, start the same sequences as their two character
+/// escapes do.
+fn strip(bytes: &[u8]) -> Vec<u8> {
+    let mut parser = Parser::new();
+    let mut text = Text(Vec::with_capacity(bytes.len()));
+
+    let mut bytes = bytes.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        match (byte, bytes.peek()) {
+            // C1 control codes are encoded as `0xc2 0x80..=0x9f` in UTF-8.
+            (0xc2, Some(&c1 @ 0x80..=0x9f)) => {
+                bytes.next();
+                parser.advance(&mut text, 0x1b);
+                parser.advance(&mut text, c1 - 0x40);
+            }
+            _ => parser.advance(&mut text, byte),
+        }
+    }
+
+    text.0
+}
+
+/// Collects what the terminal would print.
+struct Text(Vec<u8>);
+
+impl Perform for Text {
+    fn print(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.0.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    }
+
+    fn execute(&mut self, byte: u8) {
+        if matches!(byte, b'\t' | b'\n' | b'\r') {
+            self.0.push(byte);
+        }
+    }
+
+    fn hook(&mut self, _: &[i64], _: &[u8], _: bool) {}
+
+    fn put(&mut self, _: u8) {}
+
+    fn unhook(&mut self) {}
+
+    fn osc_dispatch(&mut self, _: &[&[u8]]) {}
+
+    fn csi_dispatch(&mut self, _: &[i64], _: &[u8], _: bool, _: char) {}
+
+    fn esc_dispatch(&mut self, _: &[i64], _: &[u8], _: bool, _: u8) {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,5 +128,41 @@ mod tests {
             want: Ok("foo bar"),
             tdef: TypeDef::new().infallible().bytes(),
         }
+
+        keeps_whitespace {
+            args: func_args![value: "\x1b[1mfoo\tbar\r\n"],
+            want: Ok("foo\tbar\r\n"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        hyperlink {
+            args: func_args![value: "\x1b]8;;https://vector.dev\x1b\\foo\x1b]8;;\x1b\\ bar"],
+            want: Ok("foo bar"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        window_title {
+            args: func_args![value: "\x1b]0;title\x07foo bar"],
+            want: Ok("foo bar"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        device_control_string {
+            args: func_args![value: "\x1bPq#0;2;0;0;0\x1b\\foo bar"],
+            want: Ok("foo bar"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        c1_control_codes {
+            args: func_args![value: "\u{9b}31mfoo\u{9b}0m \u{9d}0;title\u{9c}bar"],
+            want: Ok("foo bar"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        keeps_other_characters {
+            args: func_args![value: "\x1b[32mcaf\u{e9}\u{a0}bar"],
+            want: Ok("caf\u{e9}\u{a0}bar"),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
     ];
 }
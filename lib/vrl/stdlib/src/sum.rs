@@ -0,0 +1,108 @@
+use crate::util;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sum;
+
+impl Function for Sum {
+    fn identifier(&self) -> &'static str {
+        "sum"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::ARRAY,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "integers",
+                source: r#"sum!([1, 2, 3])"#,
+                result: Ok("6"),
+            },
+            Example {
+                title: "floats",
+                source: r#"sum!([1, 2.5])"#,
+                result: Ok("3.5"),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(SumFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SumFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for SumFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let array = self.value.resolve(ctx)?.try_array()?;
+        let numbers = util::numbers(&array)?;
+
+        // The sum of integers stays an integer, unless it overflows.
+        if array.iter().all(Value::is_integer) {
+            let sum = array
+                .iter()
+                .filter_map(Value::as_integer)
+                .try_fold(0_i64, i64::checked_add)
+                .ok_or("sum overflows an integer")?;
+
+            return Ok(sum.into());
+        }
+
+        Ok(numbers.iter().sum::<f64>().into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().fallible().integer().add_float()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        sum => Sum;
+
+        integers {
+            args: func_args![value: value!([1, 2, 3])],
+            want: Ok(value!(6)),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+
+        floats {
+            args: func_args![value: value!(vec![value!(1), value!(2.5)])],
+            want: Ok(value!(3.5)),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+
+        empty {
+            args: func_args![value: value!([])],
+            want: Ok(value!(0)),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+
+        overflow {
+            args: func_args![value: value!(vec![value!(i64::MAX), value!(1)])],
+            want: Err("sum overflows an integer"),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+
+        not_numeric {
+            args: func_args![value: value!(vec![value!(1), value!("2")])],
+            want: Err(r#"expected "integer" or "float", got "string""#),
+            tdef: TypeDef::new().fallible().integer().add_float(),
+        }
+    ];
+}
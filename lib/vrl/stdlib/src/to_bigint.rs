@@ -0,0 +1,136 @@
+use num_bigint::BigInt;
+use std::str::FromStr;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ToBigint;
+
+impl Function for ToBigint {
+    fn identifier(&self) -> &'static str {
+        "to_bigint"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[Parameter {
+            keyword: "value",
+            kind: kind::INTEGER | kind::FLOAT | kind::BYTES,
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "integer",
+                source: r#"to_bigint(5)"#,
+                result: Ok(r#""5""#),
+            },
+            Example {
+                title: "float",
+                source: r#"to_bigint!(-3.7)"#,
+                result: Ok(r#""-3""#),
+            },
+            Example {
+                title: "string",
+                source: r#"to_bigint!("+0018446744073709551616")"#,
+                result: Ok(r#""18446744073709551616""#),
+            },
+            Example {
+                title: "invalid string",
+                source: r#"to_bigint!("foo")"#,
+                result: Err(
+                    r#"function call error for "to_bigint" at (0:17): invalid big integer "foo""#,
+                ),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+
+        Ok(Box::new(ToBigintFn { value }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ToBigintFn {
+    value: Box<dyn Expression>,
+}
+
+impl Expression for ToBigintFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let bigint = match self.value.resolve(ctx)? {
+            Value::Integer(integer) => BigInt::from(integer),
+            Value::Float(float) => {
+                // Like `to_int`, floats are truncated to their integer part.
+                let float = float.into_inner().trunc();
+                if !float.is_finite() {
+                    return Err(format!("invalid big integer {}", float).into());
+                }
+
+                format!("{:.0}", float)
+                    .parse()
+                    .expect("finite floats format as integers")
+            }
+            Value::Bytes(bytes) => {
+                let string = String::from_utf8_lossy(&bytes);
+                BigInt::from_str(&string)
+                    .map_err(|_| format!(r#"invalid big integer "{}""#, string))?
+            }
+            value => {
+                return Err(value::Error::Expected {
+                    got: value.kind(),
+                    expected: Kind::Integer | Kind::Float | Kind::Bytes,
+                }
+                .into())
+            }
+        };
+
+        Ok(bigint.to_string().into())
+    }
+
+    fn type_def(&self, state: &state::Compiler) -> TypeDef {
+        TypeDef::new()
+            .with_fallibility(!self.value.type_def(state).is_integer())
+            .bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        to_bigint => ToBigint;
+
+        integer {
+            args: func_args![value: i64::MIN],
+            want: Ok(value!("-9223372036854775808")),
+            tdef: TypeDef::new().infallible().bytes(),
+        }
+
+        float {
+            args: func_args![value: -12345678901234567890.5],
+            want: Ok(value!("-12345678901234567168")),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+
+        infinite_float {
+            args: func_args![value: f64::INFINITY],
+            want: Err("invalid big integer inf"),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+
+        string {
+            args: func_args![value: "-000123456789012345678901234567890"],
+            want: Ok(value!("-123456789012345678901234567890")),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+
+        invalid_string {
+            args: func_args![value: "1.5"],
+            want: Err(r#"invalid big integer "1.5""#),
+            tdef: TypeDef::new().fallible().bytes(),
+        }
+    ];
+}
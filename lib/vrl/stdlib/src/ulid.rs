@@ -0,0 +1,101 @@
+use bytes::Bytes;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vrl::prelude::*;
+
+/// Crockford's base32 alphabet, which leaves out `I`, `L`, `O` and `U`.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ulid;
+
+impl Function for Ulid {
+    fn identifier(&self) -> &'static str {
+        "ulid"
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "generate ULID",
+            source: r#"length(ulid())"#,
+            result: Ok("26"),
+        }]
+    }
+
+    fn compile(&self, _: ArgumentList) -> Compiled {
+        Ok(Box::new(UlidFn))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UlidFn;
+
+impl Expression for UlidFn {
+    fn resolve(&self, _: &mut Context) -> Resolved {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+
+        Ok(Bytes::copy_from_slice(&encode(millis, rand::random())).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().infallible().bytes()
+    }
+}
+
+/// Encodes 48 bits of Unix milliseconds followed by 80 random bits as 26
+/// base32 characters, most significant first.
+fn encode(millis: u64, random: u128) -> [u8; 26] {
+    let value = (u128::from(millis & 0xffff_ffff_ffff) << 80) | (random & ((1 << 80) - 1));
+
+    let mut encoded = [0; 26];
+    for (i, byte) in encoded.iter_mut().enumerate() {
+        *byte = ALPHABET[((value >> (125 - 5 * i)) & 0x1f) as usize];
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::TimeZone;
+
+    test_type_def![default {
+        expr: |_| { UlidFn },
+        want: TypeDef::new().infallible().bytes(),
+    }];
+
+    #[test]
+    fn ulid() {
+        let mut state = vrl::state::Runtime::default();
+        let mut object: Value = map![].into();
+        let tz = TimeZone::default();
+        let mut ctx = Context::new(&mut object, &mut state, &tz);
+        let value = UlidFn.resolve(&mut ctx).unwrap();
+
+        match value {
+            Value::Bytes(val) => {
+                assert_eq!(val.len(), 26);
+                assert!(val.iter().all(|byte| ALPHABET.contains(byte)));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(&encode(0, 0), b"00000000000000000000000000");
+        assert_eq!(
+            &encode(0xffff_ffff_ffff, u128::MAX),
+            b"7ZZZZZZZZZZZZZZZZZZZZZZZZZ"
+        );
+        assert_eq!(&encode(1_469_918_176_385, 0), b"01ARYZ6S410000000000000000");
+    }
+
+    #[test]
+    fn sortable() {
+        assert!(encode(1_000, u128::MAX) < encode(1_001, 0));
+    }
+}
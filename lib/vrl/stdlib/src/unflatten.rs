@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Unflatten;
+
+impl Function for Unflatten {
+    fn identifier(&self) -> &'static str {
+        "unflatten"
+    }
+
+    fn parameters(&self) -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "value",
+                kind: kind::OBJECT,
+                required: true,
+            },
+            Parameter {
+                keyword: "separator",
+                kind: kind::BYTES,
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[
+            Example {
+                title: "object",
+                source: r#"unflatten({ "foo.bar": true, "foo.baz": false })"#,
+                result: Ok(r#"{ "foo": { "bar": true, "baz": false } }"#),
+            },
+            Example {
+                title: "separator",
+                source: r#"unflatten({ "foo_bar": true }, separator: "_")"#,
+                result: Ok(r#"{ "foo": { "bar": true } }"#),
+            },
+        ]
+    }
+
+    fn compile(&self, mut arguments: ArgumentList) -> Compiled {
+        let value = arguments.required("value");
+        let separator = arguments.optional("separator").unwrap_or(expr!("."));
+
+        Ok(Box::new(UnflattenFn { value, separator }))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct UnflattenFn {
+    value: Box<dyn Expression>,
+    separator: Box<dyn Expression>,
+}
+
+impl Expression for UnflattenFn {
+    fn resolve(&self, ctx: &mut Context) -> Resolved {
+        let separator = self
+            .separator
+            .resolve(ctx)?
+            .try_bytes_utf8_lossy()?
+            .into_owned();
+        let map = self.value.resolve(ctx)?.try_object()?;
+
+        Ok(Value::Object(unflatten(map, &separator)))
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().object::<(), Kind>(map! { (): Kind::all() })
+    }
+}
+
+/// Splits every key on `separator` and nests its value under the resulting
+/// path. Nested objects are unflattened as well.
+///
+/// Keys are handled in order, so when a key is both a value of its own and
+/// the parent of other keys (e.g. `a` and `a.b`), the nested keys win. An
+/// empty separator leaves the keys as they are.
+fn unflatten(map: BTreeMap<String, Value>, separator: &str) -> BTreeMap<String, Value> {
+    let mut unflattened = BTreeMap::new();
+
+    for (key, value) in map {
+        let value = match value {
+            Value::Object(map) => Value::Object(unflatten(map, separator)),
+            value => value,
+        };
+
+        let mut path = if separator.is_empty() {
+            vec![key.as_str()]
+        } else {
+            key.split(separator).collect::<Vec<_>>()
+        };
+        let last = path.pop().expect("split returns at least one segment");
+
+        let mut target = &mut unflattened;
+        for segment in path {
+            let entry = target
+                .entry(segment.to_owned())
+                .or_insert_with(|| Value::Object(BTreeMap::new()));
+
+            if !entry.is_object() {
+                *entry = Value::Object(BTreeMap::new());
+            }
+
+            target = entry.as_object_mut().expect("just made an object");
+        }
+
+        match (target.get_mut(last), value) {
+            (Some(Value::Object(existing)), Value::Object(map)) => existing.extend(map),
+            (_, value) => {
+                target.insert(last.to_owned(), value);
+            }
+        }
+    }
+
+    unflattened
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_function![
+        unflatten => Unflatten;
+
+        flat {
+            args: func_args![value: value!({parent: "child"})],
+            want: Ok(value!({parent: "child"})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        nested {
+            args: func_args![value: value!({"parent.child1": 1, "parent.child2": 2, key: "val"})],
+            want: Ok(value!({parent: {child1: 1, child2: 2}, key: "val"})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        double_nested {
+            args: func_args![value: value!({
+                "parent.child1": 1,
+                "parent.child2.grandchild1": 1,
+                "parent.child2.grandchild2": [1, 2],
+            })],
+            want: Ok(value!({
+                parent: {
+                    child1: 1,
+                    child2: { grandchild1: 1, grandchild2: [1, 2] },
+                },
+            })),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        nested_objects {
+            args: func_args![value: value!({parent: {"child.grandchild": 1}, "parent.child.other": 2})],
+            want: Ok(value!({parent: {child: {grandchild: 1, other: 2}}})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        nested_keys_win {
+            args: func_args![value: value!({parent: 1, "parent.child": 2})],
+            want: Ok(value!({parent: {child: 2}})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        separator {
+            args: func_args![value: value!({"parent__child": 1, "other.key": 2}), separator: "__"],
+            want: Ok(value!({parent: {child: 1}, "other.key": 2})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+
+        empty_separator {
+            args: func_args![value: value!({"parent.child": 1}), separator: ""],
+            want: Ok(value!({"parent.child": 1})),
+            tdef: TypeDef::new().object::<(), Kind>(map! { (): Kind::all() }),
+        }
+    ];
+}
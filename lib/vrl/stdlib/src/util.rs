@@ -122,3 +122,168 @@ impl std::str::FromStr for Base64Charset {
         }
     }
 }
+
+/// The keys `seahash::hash` uses, the seed is mixed into each of them so that
+/// a seed of `0` yields the same hash as the `sample` transform's `key_field`.
+#[cfg(any(feature = "sample", feature = "consistent_sample"))]
+const SEAHASH_KEYS: [u64; 4] = [
+    0x16f1_1fe8_9b0d_677c,
+    0xb480_a793_d8e6_c86c,
+    0x6fe2_e5aa_f078_ebc9,
+    0x14f9_94a4_c525_9381,
+];
+
+/// Reads the literal `rate` argument of the sampling functions, and maps it
+/// onto the hash space.
+#[cfg(any(feature = "sample", feature = "consistent_sample"))]
+pub(crate) fn sample_threshold(
+    arguments: &mut vrl::function::ArgumentList,
+) -> Result<u64, Box<dyn vrl::diagnostic::DiagnosticError>> {
+    let rate = arguments.required_literal("rate")?.to_value();
+    let threshold = match rate {
+        vrl::Value::Integer(rate) => rate_threshold(rate as f64),
+        vrl::Value::Float(rate) => rate_threshold(rate.into_inner()),
+        _ => None,
+    };
+
+    threshold.ok_or_else(|| {
+        Box::new(vrl::function::Error::InvalidArgument {
+            keyword: "rate",
+            value: rate,
+            error: "rate must be a number between 0 and 1",
+        }) as _
+    })
+}
+
+/// Reads the optional literal `seed` argument of the sampling functions.
+#[cfg(any(feature = "sample", feature = "consistent_sample"))]
+pub(crate) fn sample_seed(
+    arguments: &mut vrl::function::ArgumentList,
+) -> Result<Option<u64>, Box<dyn vrl::diagnostic::DiagnosticError>> {
+    match arguments.optional_literal("seed")? {
+        Some(seed) => match seed.to_value() {
+            vrl::Value::Integer(seed) => Ok(Some(seed as u64)),
+            value => Err(Box::new(vrl::function::Error::InvalidArgument {
+                keyword: "seed",
+                value,
+                error: "seed must be an integer",
+            })),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Maps a rate in `[0, 1]` onto the hash space, `None` signals a rate outside
+/// that range.
+#[cfg(any(feature = "sample", feature = "consistent_sample"))]
+pub(crate) fn rate_threshold(rate: f64) -> Option<u64> {
+    if !(0.0..=1.0).contains(&rate) {
+        return None;
+    }
+
+    // `u64::MAX as f64` rounds up to 2^64, which saturates back to
+    // `u64::MAX` when cast, so a rate of 1 keeps every hash.
+    Some((rate * u64::MAX as f64) as u64)
+}
+
+/// Hashes `key` with the given `seed`, this is what makes the decision stable
+/// across restarts and across instances sharing the same seed.
+///
+/// Non-string keys are hashed by their string representation.
+#[cfg(any(feature = "sample", feature = "consistent_sample"))]
+pub(crate) fn sample_hash(key: &vrl::Value, seed: u64) -> u64 {
+    let [k1, k2, k3, k4] = SEAHASH_KEYS;
+    let hash =
+        |bytes: &[u8]| seahash::hash_seeded(bytes, k1 ^ seed, k2 ^ seed, k3 ^ seed, k4 ^ seed);
+
+    match key {
+        vrl::Value::Bytes(bytes) => hash(bytes),
+        value => hash(value.to_string().as_bytes()),
+    }
+}
+
+/// Opens the MaxMind database at the literal `database` argument, which has
+/// to be of one of the given database types.
+#[cfg(any(feature = "get_asn", feature = "get_connection_type"))]
+pub(crate) fn open_geoip_database(
+    arguments: &mut vrl::function::ArgumentList,
+    database_types: &[&str],
+    invalid_type: &'static str,
+) -> Result<std::sync::Arc<maxminddb::Reader<Vec<u8>>>, Box<dyn vrl::diagnostic::DiagnosticError>> {
+    let database = arguments.required_literal("database")?.to_value();
+    let invalid = |error| {
+        Box::new(vrl::function::Error::InvalidArgument {
+            keyword: "database",
+            value: database.clone(),
+            error,
+        }) as Box<dyn vrl::diagnostic::DiagnosticError>
+    };
+
+    let path = match &database {
+        vrl::Value::Bytes(path) => String::from_utf8_lossy(path).into_owned(),
+        _ => return Err(invalid("database must be a path")),
+    };
+
+    let reader =
+        maxminddb::Reader::open_readfile(path).map_err(|_| invalid("unable to open database"))?;
+
+    if !database_types.contains(&reader.metadata.database_type.as_str()) {
+        return Err(invalid(invalid_type));
+    }
+
+    Ok(std::sync::Arc::new(reader))
+}
+
+/// Parses `value` as the IP address to look up in a MaxMind database.
+#[cfg(any(feature = "get_asn", feature = "get_connection_type"))]
+pub(crate) fn geoip_address(
+    value: vrl::Value,
+) -> Result<std::net::IpAddr, vrl::prelude::ExpressionError> {
+    let value = value.try_bytes_utf8_lossy()?;
+
+    value
+        .parse()
+        .map_err(|err| format!("unable to parse IP address: {}", err).into())
+}
+
+/// The elements of the array as floats, all of which have to be integers or
+/// floats.
+#[cfg(any(
+    feature = "max",
+    feature = "mean",
+    feature = "median",
+    feature = "min",
+    feature = "percentile",
+    feature = "sum"
+))]
+pub(crate) fn numbers(array: &[vrl::Value]) -> Result<Vec<f64>, vrl::prelude::ExpressionError> {
+    array
+        .iter()
+        .map(|value| match value {
+            vrl::Value::Integer(number) => Ok(*number as f64),
+            vrl::Value::Float(number) => Ok(number.into_inner()),
+            value => Err(vrl::value::Error::Expected {
+                got: value.kind(),
+                expected: vrl::value::Kind::Integer | vrl::value::Kind::Float,
+            }
+            .into()),
+        })
+        .collect()
+}
+
+/// The `percentile` (from 0 to 100) of the numbers, interpolated linearly
+/// between the two closest ranks. `None` if there are no numbers.
+#[cfg(any(feature = "median", feature = "percentile"))]
+pub(crate) fn percentile(mut numbers: Vec<f64>, percentile: f64) -> Option<f64> {
+    if numbers.is_empty() {
+        return None;
+    }
+
+    numbers.sort_by(|a, b| a.partial_cmp(b).expect("numbers aren't NaN"));
+
+    let rank = percentile / 100.0 * (numbers.len() - 1) as f64;
+    let lower = numbers[rank.floor() as usize];
+    let upper = numbers[rank.ceil() as usize];
+
+    Some(lower + (upper - lower) * rank.fract())
+}
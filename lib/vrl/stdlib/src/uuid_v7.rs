@@ -0,0 +1,106 @@
+use bytes::Bytes;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vrl::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct UuidV7;
+
+impl Function for UuidV7 {
+    fn identifier(&self) -> &'static str {
+        "uuid_v7"
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &[Example {
+            title: "generate UUID v7",
+            source: r#"uuid_v7() != """#,
+            result: Ok("true"),
+        }]
+    }
+
+    fn compile(&self, _: ArgumentList) -> Compiled {
+        Ok(Box::new(UuidV7Fn))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UuidV7Fn;
+
+impl Expression for UuidV7Fn {
+    fn resolve(&self, _: &mut Context) -> Resolved {
+        let mut buf = [0; 36];
+        let uuid = new_v7(unix_millis(), rand::random())
+            .to_hyphenated()
+            .encode_lower(&mut buf);
+
+        Ok(Bytes::copy_from_slice(uuid.as_bytes()).into())
+    }
+
+    fn type_def(&self, _: &state::Compiler) -> TypeDef {
+        TypeDef::new().infallible().bytes()
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Lays out a version 7 UUID: 48 bits of Unix milliseconds, followed by the
+/// version, random bits, the variant and more random bits.
+fn new_v7(millis: u64, random: [u8; 10]) -> uuid::Uuid {
+    let mut bytes = [0; 16];
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    bytes[6..].copy_from_slice(&random);
+    bytes[6] = 0x70 | (bytes[6] & 0x0f);
+    bytes[8] = 0x80 | (bytes[8] & 0x3f);
+
+    uuid::Uuid::from_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::TimeZone;
+
+    test_type_def![default {
+        expr: |_| { UuidV7Fn },
+        want: TypeDef::new().infallible().bytes(),
+    }];
+
+    #[test]
+    fn uuid_v7() {
+        let mut state = vrl::state::Runtime::default();
+        let mut object: Value = map![].into();
+        let tz = TimeZone::default();
+        let mut ctx = Context::new(&mut object, &mut state, &tz);
+        let value = UuidV7Fn.resolve(&mut ctx).unwrap();
+
+        match value {
+            Value::Bytes(val) => {
+                let val = String::from_utf8_lossy(&val);
+                let uuid = uuid::Uuid::parse_str(&val).expect("valid UUID V7");
+                assert_eq!(uuid.get_variant(), Some(uuid::Variant::RFC4122));
+                assert_eq!(&val[14..15], "7");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let uuid = new_v7(0x0186_1f8a_6ba8, [0xff; 10]);
+
+        assert_eq!(uuid.to_string(), "01861f8a-6ba8-7fff-bfff-ffffffffffff");
+    }
+
+    #[test]
+    fn sortable() {
+        let earlier = new_v7(1_000, [0xff; 10]);
+        let later = new_v7(1_001, [0; 10]);
+
+        assert!(earlier.to_string() < later.to_string());
+    }
+}
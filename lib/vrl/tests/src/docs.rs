@@ -11,10 +11,14 @@ use crate::Test;
 /// This mostly consists of functions that have a non-deterministic result.
 const SKIP_FUNCTION_EXAMPLES: &[&str] = &[
     "uuid_v4",
+    "uuid_v7",
+    "ulid",
     "strip_ansi_escape_codes",
     "get_hostname",
     "now",
     "get_env_var",
+    "dns_lookup",
+    "reverse_dns",
 ];
 
 #[derive(Debug, Deserialize)]
@@ -16,6 +16,7 @@ pub enum SourceOutputType {
     Any,
     Log,
     Metric,
+    Trace,
 }
 
 impl From<DataType> for SourceOutputType {
@@ -23,6 +24,7 @@ impl From<DataType> for SourceOutputType {
         match data_type {
             DataType::Metric => SourceOutputType::Metric,
             DataType::Log => SourceOutputType::Log,
+            DataType::Trace => SourceOutputType::Trace,
             DataType::Any => SourceOutputType::Any,
         }
     }
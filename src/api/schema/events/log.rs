@@ -59,4 +59,15 @@ impl Log {
     async fn json(&self, field: String) -> Option<&Value> {
         self.event.get(field)
     }
+
+    /// Ids of the components the log event passed through, from its source onward. Only
+    /// recorded when the `lineage` global option is enabled
+    async fn lineage(&self) -> Vec<&str> {
+        self.event
+            .metadata()
+            .lineage()
+            .iter()
+            .map(|id| &**id)
+            .collect()
+    }
 }
@@ -0,0 +1,302 @@
+//! Avro encoding and decoding of events, with a schema configured inline, read
+//! from a file or looked up in a Confluent Schema Registry.
+//!
+//! Batches are Avro object container files, with the schema in their header.
+//! Single messages are bare datums of the configured schema, or are framed
+//! as the registry's serializers frame them: a zero magic byte, then the ID
+//! of the schema as a big endian 32 bit integer, then the datum.
+
+pub mod container;
+mod registry;
+mod value;
+
+pub use self::registry::AvroRegistryConfig;
+pub use self::value::{from_avro, to_avro};
+use crate::{config::ProxyConfig, event::Value};
+use avro_rs::{from_avro_datum, types::Value as AvroValue, Schema};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+pub const CONTENT_TYPE: &str = "application/avro";
+pub const EXTENSION: &str = "avro";
+
+#[derive(Debug, Snafu)]
+pub enum AvroError {
+    #[snafu(display("the Avro schema of events must be a record"))]
+    NotARecord,
+    #[snafu(display("field {:?} isn't a valid {}", field, expected))]
+    Mismatch {
+        field: String,
+        expected: &'static str,
+    },
+    #[snafu(display("field {:?} is missing, and has no default", field))]
+    MissingField { field: String },
+    #[snafu(display(
+        "field {:?} has a decimal or duration type, which isn't supported",
+        field
+    ))]
+    UnsupportedType { field: String },
+    #[snafu(display("invalid Avro schema: {}", source))]
+    InvalidSchema { source: avro_rs::Error },
+    #[snafu(display("couldn't encode Avro record: {}", source))]
+    Encode { source: avro_rs::Error },
+    #[snafu(display("couldn't decode Avro record: {}", source))]
+    Decode { source: avro_rs::Error },
+    #[snafu(display("not an Avro object container file"))]
+    NotAContainer,
+    #[snafu(display("Avro object container file is truncated or corrupted"))]
+    Truncated,
+    #[snafu(display("unsupported Avro codec {:?}", codec))]
+    UnsupportedCodec { codec: String },
+    #[snafu(display("couldn't compress or decompress Avro block: {}", source))]
+    Compression { source: std::io::Error },
+    #[snafu(display("message isn't framed with the ID of a registered schema"))]
+    NotFramed,
+    #[snafu(display("schema {} isn't a version of the subject", id))]
+    UnknownSchemaId { id: u32 },
+    #[snafu(display("one of `schema`, `schema_file` or `schema_registry` is required"))]
+    MissingSchema,
+    #[snafu(display("only one of `schema`, `schema_file` or `schema_registry` can be set"))]
+    ConflictingSchemas,
+    #[snafu(display("couldn't read the schema {:?}: {}", path, source))]
+    ReadSchema {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvroOptions {
+    /// The schema, as JSON.
+    pub schema: Option<String>,
+    pub schema_file: Option<PathBuf>,
+    pub schema_registry: Option<AvroRegistryConfig>,
+    /// The codec of the blocks of container files.
+    #[serde(default)]
+    pub compression: AvroCompression,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Eq, PartialEq, Serialize)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AvroCompression {
+    None,
+    Deflate,
+    #[derivative(Default)]
+    Snappy,
+}
+
+impl AvroCompression {
+    /// The name of the codec in the header of container files.
+    const fn codec(self) -> &'static str {
+        match self {
+            Self::None => "null",
+            Self::Deflate => "deflate",
+            Self::Snappy => "snappy",
+        }
+    }
+}
+
+impl AvroOptions {
+    /// Resolves the schema events are encoded with.
+    pub async fn encoder(&self, proxy: &ProxyConfig) -> crate::Result<AvroEncoder> {
+        let raw = match self.local_schema()? {
+            Some(raw) => raw,
+            None => match &self.schema_registry {
+                Some(registry) => {
+                    let (id, raw) = registry.schema(proxy).await?;
+                    info!(message = "Resolved schema.", subject = %registry.subject, id);
+                    raw
+                }
+                None => return Err(AvroError::MissingSchema.into()),
+            },
+        };
+        let schema = Schema::parse_str(&raw).context(InvalidSchema)?;
+        if !matches!(schema, Schema::Record { .. }) {
+            return Err(AvroError::NotARecord.into());
+        }
+
+        Ok(AvroEncoder {
+            schema,
+            raw,
+            compression: self.compression,
+        })
+    }
+
+    /// Resolves the schemas messages are decoded with. Messages framed with
+    /// the ID of a schema are read with any version of the subject, and
+    /// resolved to the configured version if there is one.
+    pub async fn decoder(&self, proxy: &ProxyConfig) -> crate::Result<AvroDecoder> {
+        if let Some(raw) = self.local_schema()? {
+            return Ok(AvroDecoder {
+                schema: Some(Schema::parse_str(&raw).context(InvalidSchema)?),
+                registered: HashMap::new(),
+            });
+        }
+        let registry = self
+            .schema_registry
+            .as_ref()
+            .ok_or(AvroError::MissingSchema)?;
+
+        let mut registered = HashMap::new();
+        for (id, raw) in registry.schemas(proxy).await? {
+            registered.insert(id, Schema::parse_str(&raw).context(InvalidSchema)?);
+        }
+        let schema = match registry.version {
+            Some(_) => {
+                Some(Schema::parse_str(&registry.schema(proxy).await?.1).context(InvalidSchema)?)
+            }
+            None => None,
+        };
+        info!(
+            message = "Resolved schemas.",
+            subject = %registry.subject,
+            versions = registered.len()
+        );
+
+        Ok(AvroDecoder { schema, registered })
+    }
+
+    /// The schema configured inline or in a file.
+    fn local_schema(&self) -> Result<Option<String>, AvroError> {
+        match (&self.schema, &self.schema_file, &self.schema_registry) {
+            (Some(schema), None, None) => Ok(Some(schema.clone())),
+            (None, Some(path), None) => std::fs::read_to_string(path)
+                .context(ReadSchema { path })
+                .map(Some),
+            (None, None, _) => Ok(None),
+            _ => Err(AvroError::ConflictingSchemas),
+        }
+    }
+}
+
+/// Encodes events with a record schema.
+#[derive(Debug)]
+pub struct AvroEncoder {
+    schema: Schema,
+    /// The schema as configured, which is written to the header of container
+    /// files with the attributes `Schema` doesn't keep.
+    raw: String,
+    compression: AvroCompression,
+}
+
+impl AvroEncoder {
+    /// Converts the fields of an event to a record of the schema.
+    pub fn convert(&self, fields: &BTreeMap<String, Value>) -> Result<AvroValue, AvroError> {
+        to_avro(fields, &self.schema)
+    }
+
+    /// Writes the records as a container file.
+    pub fn encode_container(&self, records: Vec<AvroValue>) -> Result<Vec<u8>, AvroError> {
+        container::write(&self.raw, &[], records, self.compression)
+    }
+}
+
+/// Decodes records to the values of events. Without a schema, only container
+/// files, which hold the schema they were written with, are decoded.
+#[derive(Debug, Default)]
+pub struct AvroDecoder {
+    /// The schema records are resolved to.
+    schema: Option<Schema>,
+    /// The versions of the subject in the registry, by their IDs.
+    registered: HashMap<u32, Schema>,
+}
+
+impl AvroDecoder {
+    pub fn decode_container(&self, data: &[u8]) -> Result<Vec<Value>, AvroError> {
+        Ok(container::read(data, self.schema.as_ref())?
+            .into_iter()
+            .map(from_avro)
+            .collect())
+    }
+
+    pub fn decode_message(&self, data: &[u8]) -> Result<Value, AvroError> {
+        let value = if self.registered.is_empty() {
+            let schema = self.schema.as_ref().ok_or(AvroError::MissingSchema)?;
+            from_avro_datum(schema, &mut &data[..], None).context(Decode)?
+        } else {
+            let (id, mut datum) = match data {
+                [0, a, b, c, d, datum @ ..] => (u32::from_be_bytes([*a, *b, *c, *d]), datum),
+                _ => return Err(AvroError::NotFramed),
+            };
+            let writer_schema = self
+                .registered
+                .get(&id)
+                .ok_or(AvroError::UnknownSchemaId { id })?;
+            from_avro_datum(writer_schema, &mut datum, self.schema.as_ref()).context(Decode)?
+        };
+        Ok(from_avro(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Log",
+        "fields": [
+            {"name": "message", "type": "string"},
+            {"name": "level", "type": "string", "default": "info"}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn encoded_events_are_decoded() {
+        let options = AvroOptions {
+            schema: Some(SCHEMA.into()),
+            ..Default::default()
+        };
+        let encoder = options.encoder(&ProxyConfig::default()).await.unwrap();
+        let decoder = options.decoder(&ProxyConfig::default()).await.unwrap();
+
+        let log = LogEvent::from("hello");
+        let record = encoder.convert(log.as_map()).unwrap();
+        let data = encoder.encode_container(vec![record.clone()]).unwrap();
+        let decoded = decoder.decode_container(&data).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_map().unwrap()["level"], Value::from("info"));
+
+        let datum = avro_rs::to_avro_datum(&encoder.schema, record).unwrap();
+        assert_eq!(decoder.decode_message(&datum).unwrap(), decoded[0]);
+    }
+
+    #[test]
+    fn framed_messages_are_read_with_their_schema() {
+        let mut decoder = AvroDecoder::default();
+        decoder
+            .registered
+            .insert(7, Schema::parse_str(SCHEMA).unwrap());
+
+        let mut message = vec![0, 0, 0, 0, 7];
+        message.extend(
+            avro_rs::to_avro_datum(
+                &decoder.registered[&7],
+                AvroValue::Record(vec![
+                    ("message".into(), AvroValue::String("hello".into())),
+                    ("level".into(), AvroValue::String("warn".into())),
+                ]),
+            )
+            .unwrap(),
+        );
+        let value = decoder.decode_message(&message).unwrap();
+        assert_eq!(value.as_map().unwrap()["level"], Value::from("warn"));
+
+        message[4] = 8;
+        assert!(matches!(
+            decoder.decode_message(&message),
+            Err(AvroError::UnknownSchemaId { id: 8 })
+        ));
+        assert!(matches!(
+            decoder.decode_message(&message[1..]),
+            Err(AvroError::NotFramed)
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+//! Lookups of the Avro schemas registered under a subject of a Confluent
+//! Schema Registry.
+
+use crate::{
+    config::ProxyConfig,
+    http::{Auth, HttpClient},
+    tls::{TlsOptions, TlsSettings},
+};
+use http::{Request, StatusCode};
+use hyper::Body;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::collections::HashMap;
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+#[derive(Debug, Snafu)]
+enum RegistryError {
+    #[snafu(display("schema registry responded with {}: {}", status, body))]
+    Registry { status: StatusCode, body: String },
+    #[snafu(display("subject {:?} has a {} schema rather than an Avro one", subject, found))]
+    NotAvro { subject: String, found: String },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvroRegistryConfig {
+    /// The URL of the registry, such as `http://localhost:8081`.
+    pub url: String,
+    pub subject: String,
+    /// The version of the subject events are encoded with, the latest one by
+    /// default. Decoding reads messages of any version of the subject.
+    pub version: Option<u32>,
+    pub auth: Option<Auth>,
+    pub tls: Option<TlsOptions>,
+}
+
+/// A schema as the registry returns it.
+#[derive(Debug, Deserialize)]
+struct RegisteredSchema {
+    id: u32,
+    schema: String,
+    #[serde(rename = "schemaType")]
+    schema_type: Option<String>,
+}
+
+impl AvroRegistryConfig {
+    /// The ID and the schema of the configured version of the subject.
+    pub(super) async fn schema(&self, proxy: &ProxyConfig) -> crate::Result<(u32, String)> {
+        let version = self
+            .version
+            .map_or_else(|| "latest".to_owned(), |version| version.to_string());
+        let registered = self.lookup(&self.client(proxy)?, &version).await?;
+        Ok((registered.id, registered.schema))
+    }
+
+    /// The schemas of all versions of the subject, by their IDs.
+    pub(super) async fn schemas(&self, proxy: &ProxyConfig) -> crate::Result<HashMap<u32, String>> {
+        let client = self.client(proxy)?;
+        let path = format!("/subjects/{}/versions", self.encoded_subject());
+        let versions: Vec<u32> = serde_json::from_slice(&self.request(&client, &path).await?)?;
+
+        let mut schemas = HashMap::new();
+        for version in versions {
+            let registered = self.lookup(&client, &version.to_string()).await?;
+            schemas.insert(registered.id, registered.schema);
+        }
+        Ok(schemas)
+    }
+
+    async fn lookup(&self, client: &HttpClient, version: &str) -> crate::Result<RegisteredSchema> {
+        let path = format!("/subjects/{}/versions/{}", self.encoded_subject(), version);
+        let registered: RegisteredSchema =
+            serde_json::from_slice(&self.request(client, &path).await?)?;
+
+        // Schemas without a type are Avro schemas, which older registries
+        // only support.
+        match registered.schema_type.as_deref() {
+            None | Some("AVRO") => Ok(registered),
+            Some(found) => Err(RegistryError::NotAvro {
+                subject: self.subject.clone(),
+                found: found.to_owned(),
+            }
+            .into()),
+        }
+    }
+
+    async fn request(&self, client: &HttpClient, path: &str) -> crate::Result<bytes::Bytes> {
+        let mut builder = Request::get(format!("{}{}", self.url.trim_end_matches('/'), path))
+            .header("Accept", CONTENT_TYPE);
+        if let Some(auth) = &self.auth {
+            builder = auth.apply_builder(builder);
+        }
+
+        let response = client.send(builder.body(Body::empty())?).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        if !status.is_success() {
+            return Err(RegistryError::Registry {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            }
+            .into());
+        }
+        Ok(body)
+    }
+
+    fn client(&self, proxy: &ProxyConfig) -> crate::Result<HttpClient> {
+        Ok(HttpClient::new(
+            TlsSettings::from_options(&self.tls)?,
+            proxy,
+        )?)
+    }
+
+    fn encoded_subject(&self) -> String {
+        utf8_percent_encode(&self.subject, NON_ALPHANUMERIC).to_string()
+    }
+}
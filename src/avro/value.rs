@@ -0,0 +1,305 @@
+//! Conversions between Avro values and the values of events.
+
+use super::AvroError;
+use crate::event::Value;
+use avro_rs::{
+    schema::{RecordField, Schema},
+    types::Value as AvroValue,
+};
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+};
+use uuid::Uuid;
+
+/// Converts an Avro value to the value of an event. Records and maps are
+/// maps and unions are the value of their branch. Logical types are their
+/// closest counterpart: timestamps are timestamps, dates are timestamps at
+/// midnight UTC, times of day are integers of their unit and UUIDs are
+/// strings. Decimals and durations aren't supported, and are null.
+pub fn from_avro(value: AvroValue) -> Value {
+    match value {
+        AvroValue::Null | AvroValue::Decimal(_) | AvroValue::Duration(_) => Value::Null,
+        AvroValue::Boolean(value) => Value::Boolean(value),
+        AvroValue::Int(value) | AvroValue::TimeMillis(value) => Value::Integer(value.into()),
+        AvroValue::Long(value) | AvroValue::TimeMicros(value) => Value::Integer(value),
+        AvroValue::Float(value) => Value::Float(value.into()),
+        AvroValue::Double(value) => Value::Float(value),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Value::Bytes(bytes.into()),
+        AvroValue::String(string) | AvroValue::Enum(_, string) => {
+            Value::String(Bytes::from(string))
+        }
+        AvroValue::Uuid(uuid) => Value::String(Bytes::from(uuid.to_hyphenated().to_string())),
+        AvroValue::Union(value) => from_avro(*value),
+        AvroValue::Array(values) => Value::Array(values.into_iter().map(from_avro).collect()),
+        AvroValue::Map(values) => Value::Map(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, from_avro(value)))
+                .collect(),
+        ),
+        AvroValue::Record(fields) => Value::Map(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, from_avro(value)))
+                .collect(),
+        ),
+        AvroValue::Date(days) => Value::Timestamp(epoch() + chrono::Duration::days(days.into())),
+        AvroValue::TimestampMillis(millis) => Value::Timestamp(Utc.timestamp_millis(millis)),
+        AvroValue::TimestampMicros(micros) => Value::Timestamp(Utc.timestamp(
+            micros.div_euclid(1_000_000),
+            (micros.rem_euclid(1_000_000) * 1000) as u32,
+        )),
+    }
+}
+
+/// Converts the fields of an event to a value of the schema. Fields missing
+/// from the event are set to their default, or to null if the schema allows
+/// it. Strings are parsed as the timestamps and UUIDs of logical types, so
+/// that events read back from JSON convert as they did before.
+pub fn to_avro(fields: &BTreeMap<String, Value>, schema: &Schema) -> Result<AvroValue, AvroError> {
+    match schema {
+        Schema::Record { fields: schema, .. } => record(fields, schema, ""),
+        _ => Err(AvroError::NotARecord),
+    }
+}
+
+fn record(
+    fields: &BTreeMap<String, Value>,
+    schema: &[RecordField],
+    path: &str,
+) -> Result<AvroValue, AvroError> {
+    schema
+        .iter()
+        .map(|field| {
+            let path = match path {
+                "" => field.name.clone(),
+                path => format!("{}.{}", path, field.name),
+            };
+            let value = match (fields.get(&field.name), &field.default) {
+                (None, Some(default)) | (Some(Value::Null), Some(default)) => {
+                    convert(Some(&Value::from(default.clone())), &field.schema, &path)?
+                }
+                (value, _) => convert(value, &field.schema, &path)?,
+            };
+            Ok((field.name.clone(), value))
+        })
+        .collect::<Result<_, _>>()
+        .map(AvroValue::Record)
+}
+
+fn convert(value: Option<&Value>, schema: &Schema, path: &str) -> Result<AvroValue, AvroError> {
+    let mismatch = |expected| AvroError::Mismatch {
+        field: path.to_owned(),
+        expected,
+    };
+    let value = match value {
+        None | Some(Value::Null) => {
+            return match schema {
+                Schema::Null => Ok(AvroValue::Null),
+                Schema::Union(union) if union.variants().contains(&Schema::Null) => {
+                    Ok(AvroValue::Union(Box::new(AvroValue::Null)))
+                }
+                _ => Err(AvroError::MissingField {
+                    field: path.to_owned(),
+                }),
+            };
+        }
+        Some(value) => value,
+    };
+
+    Ok(match (schema, value) {
+        (Schema::Union(union), value) => {
+            return union
+                .variants()
+                .iter()
+                .filter(|variant| **variant != Schema::Null)
+                .find_map(|variant| convert(Some(value), variant, path).ok())
+                .map(|value| AvroValue::Union(Box::new(value)))
+                .ok_or_else(|| mismatch("a branch of its union"));
+        }
+        (Schema::Boolean, Value::Boolean(value)) => AvroValue::Boolean(*value),
+        (Schema::Int, Value::Integer(value)) => {
+            AvroValue::Int(i32::try_from(*value).map_err(|_| mismatch("int"))?)
+        }
+        (Schema::Long, Value::Integer(value)) => AvroValue::Long(*value),
+        (Schema::Float, Value::Float(value)) => AvroValue::Float(*value as f32),
+        (Schema::Float, Value::Integer(value)) => AvroValue::Float(*value as f32),
+        (Schema::Double, Value::Float(value)) => AvroValue::Double(*value),
+        (Schema::Double, Value::Integer(value)) => AvroValue::Double(*value as f64),
+        (Schema::Bytes, Value::Bytes(bytes)) | (Schema::Bytes, Value::String(bytes)) => {
+            AvroValue::Bytes(bytes.to_vec())
+        }
+        (Schema::String, Value::Map(_)) | (Schema::String, Value::Array(_)) => {
+            return Err(mismatch("string"))
+        }
+        (Schema::String, value) => AvroValue::String(value.to_string_lossy()),
+        (Schema::Fixed { size, .. }, Value::Bytes(bytes))
+        | (Schema::Fixed { size, .. }, Value::String(bytes))
+            if bytes.len() == *size =>
+        {
+            AvroValue::Fixed(*size, bytes.to_vec())
+        }
+        (Schema::Enum { symbols, .. }, Value::String(_))
+        | (Schema::Enum { symbols, .. }, Value::Bytes(_)) => {
+            let symbol = value.to_string_lossy();
+            let index = symbols
+                .iter()
+                .position(|candidate| *candidate == symbol)
+                .ok_or_else(|| mismatch("a symbol of its enum"))?;
+            AvroValue::Enum(index as i32, symbol)
+        }
+        (Schema::Array(items), Value::Array(values)) => AvroValue::Array(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| convert(Some(value), items, &format!("{}[{}]", path, index)))
+                .collect::<Result<_, _>>()?,
+        ),
+        (Schema::Map(values_schema), Value::Map(values)) => AvroValue::Map(
+            values
+                .iter()
+                .map(|(key, value)| {
+                    let path = format!("{}.{}", path, key);
+                    Ok((key.clone(), convert(Some(value), values_schema, &path)?))
+                })
+                .collect::<Result<HashMap<_, _>, _>>()?,
+        ),
+        (Schema::Record { fields, .. }, Value::Map(values)) => record(values, fields, path)?,
+        (Schema::Uuid, Value::String(_)) | (Schema::Uuid, Value::Bytes(_)) => {
+            let uuid = Uuid::parse_str(&value.to_string_lossy()).map_err(|_| mismatch("uuid"))?;
+            AvroValue::Uuid(uuid)
+        }
+        (Schema::Date, Value::Integer(days)) => {
+            AvroValue::Date(i32::try_from(*days).map_err(|_| mismatch("date"))?)
+        }
+        (Schema::Date, value) => {
+            let timestamp = timestamp(value).ok_or_else(|| mismatch("date"))?;
+            let days = (timestamp - epoch()).num_days();
+            AvroValue::Date(i32::try_from(days).map_err(|_| mismatch("date"))?)
+        }
+        (Schema::TimeMillis, Value::Integer(millis)) => {
+            AvroValue::TimeMillis(i32::try_from(*millis).map_err(|_| mismatch("time-millis"))?)
+        }
+        (Schema::TimeMicros, Value::Integer(micros)) => AvroValue::TimeMicros(*micros),
+        (Schema::TimestampMillis, Value::Integer(millis)) => AvroValue::TimestampMillis(*millis),
+        (Schema::TimestampMillis, value) => AvroValue::TimestampMillis(
+            timestamp(value)
+                .ok_or_else(|| mismatch("timestamp-millis"))?
+                .timestamp_millis(),
+        ),
+        (Schema::TimestampMicros, Value::Integer(micros)) => AvroValue::TimestampMicros(*micros),
+        (Schema::TimestampMicros, value) => {
+            let timestamp = timestamp(value).ok_or_else(|| mismatch("timestamp-micros"))?;
+            AvroValue::TimestampMicros(
+                timestamp.timestamp() * 1_000_000 + i64::from(timestamp.timestamp_subsec_micros()),
+            )
+        }
+        (Schema::Null, _) => return Err(mismatch("null")),
+        (Schema::Boolean, _) => return Err(mismatch("boolean")),
+        (Schema::Int, _) => return Err(mismatch("int")),
+        (Schema::Long, _) => return Err(mismatch("long")),
+        (Schema::Float, _) => return Err(mismatch("float")),
+        (Schema::Double, _) => return Err(mismatch("double")),
+        (Schema::Bytes, _) => return Err(mismatch("bytes")),
+        (Schema::Fixed { .. }, _) => return Err(mismatch("fixed")),
+        (Schema::Enum { .. }, _) => return Err(mismatch("enum")),
+        (Schema::Array(_), _) => return Err(mismatch("array")),
+        (Schema::Map(_), _) => return Err(mismatch("map")),
+        (Schema::Record { .. }, _) => return Err(mismatch("record")),
+        (Schema::Uuid, _) => return Err(mismatch("uuid")),
+        (Schema::TimeMillis, _) => return Err(mismatch("time-millis")),
+        (Schema::TimeMicros, _) => return Err(mismatch("time-micros")),
+        (Schema::Decimal { .. }, _) | (Schema::Duration, _) => {
+            return Err(AvroError::UnsupportedType {
+                field: path.to_owned(),
+            })
+        }
+    })
+}
+
+fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Timestamp(timestamp) => Some(*timestamp),
+        Value::String(bytes) | Value::Bytes(bytes) => std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|string| DateTime::parse_from_rfc3339(string).ok())
+            .map(|timestamp| timestamp.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+fn epoch() -> DateTime<Utc> {
+    DateTime::from_utc(NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0), Utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Log",
+        "fields": [
+            {"name": "message", "type": "string"},
+            {"name": "timestamp", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {
+                "name": "level",
+                "type": {"type": "enum", "name": "Level", "symbols": ["INFO", "ERROR"]}
+            },
+            {"name": "count", "type": ["null", "int"], "default": null},
+            {"name": "tags", "type": {"type": "map", "values": "string"}, "default": {}},
+            {"name": "source", "type": "string", "default": "vector"}
+        ]
+    }"#;
+
+    fn log(fields: Vec<(&str, Value)>) -> BTreeMap<String, Value> {
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn converts_events_with_the_schema() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let timestamp = Utc.timestamp_millis(1_600_000_000_123);
+        let fields = log(vec![
+            ("message", "hello".into()),
+            ("timestamp", timestamp.into()),
+            ("level", "ERROR".into()),
+            ("count", 3.into()),
+        ]);
+
+        let value = to_avro(&fields, &schema).unwrap();
+        let datum = avro_rs::to_avro_datum(&schema, value.clone()).unwrap();
+        let read = avro_rs::from_avro_datum(&schema, &mut &datum[..], None).unwrap();
+        assert_eq!(read, value);
+
+        let event = from_avro(read);
+        let event = event.as_map().unwrap();
+        assert_eq!(event["message"], "hello".into());
+        assert_eq!(event["timestamp"], Value::Timestamp(timestamp));
+        assert_eq!(event["level"], "ERROR".into());
+        assert_eq!(event["count"], 3.into());
+        assert_eq!(event["tags"], Value::Map(BTreeMap::new()));
+        assert_eq!(event["source"], "vector".into());
+    }
+
+    #[test]
+    fn rejects_events_not_fitting_the_schema() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let error = to_avro(&log(vec![("message", "hello".into())]), &schema).unwrap_err();
+        assert!(matches!(error, AvroError::MissingField { field } if field == "timestamp"));
+
+        let fields = log(vec![
+            ("message", "hello".into()),
+            ("timestamp", "2020-09-13T12:26:40.123Z".into()),
+            ("level", "DEBUG".into()),
+        ]);
+        let error = to_avro(&fields, &schema).unwrap_err();
+        assert!(matches!(error, AvroError::Mismatch { field, .. } if field == "level"));
+    }
+}
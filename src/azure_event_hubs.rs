@@ -0,0 +1,141 @@
+//! Connection settings shared by the `azure_event_hubs` source and sink,
+//! which talk to Event Hubs over its Kafka compatible endpoint.
+use crate::kafka::{KafkaAuthConfig, KafkaSaslConfig, KafkaTlsConfig};
+use snafu::Snafu;
+
+/// The port of the Kafka endpoint of an Event Hubs namespace.
+const KAFKA_PORT: u16 = 9093;
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum ConnectionStringError {
+    #[snafu(display("Connection string has no `Endpoint`"))]
+    MissingEndpoint,
+    #[snafu(display("Invalid `Endpoint` in connection string: {:?}", endpoint))]
+    InvalidEndpoint { endpoint: String },
+    #[snafu(display("No event hub configured and no `EntityPath` in connection string"))]
+    MissingEventHub,
+}
+
+/// The parts of an Event Hubs connection string, such as
+/// `Endpoint=sb://<namespace>.servicebus.windows.net/;SharedAccessKeyName=<name>;SharedAccessKey=<key>`.
+#[derive(Clone, Debug)]
+pub(crate) struct ConnectionString {
+    raw: String,
+    host: String,
+    entity_path: Option<String>,
+}
+
+impl ConnectionString {
+    pub(crate) fn parse(raw: &str) -> Result<Self, ConnectionStringError> {
+        let mut endpoint = None;
+        let mut entity_path = None;
+        for (key, value) in raw
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+        {
+            if key.eq_ignore_ascii_case("Endpoint") {
+                endpoint = Some(value);
+            } else if key.eq_ignore_ascii_case("EntityPath") && !value.is_empty() {
+                entity_path = Some(value.to_owned());
+            }
+        }
+
+        let endpoint = endpoint.ok_or(ConnectionStringError::MissingEndpoint)?;
+        let host = endpoint
+            .strip_prefix("sb://")
+            .map(|host| host.trim_end_matches('/'))
+            .filter(|host| !host.is_empty() && !host.contains('/'))
+            .ok_or_else(|| ConnectionStringError::InvalidEndpoint {
+                endpoint: endpoint.to_owned(),
+            })?;
+
+        Ok(Self {
+            raw: raw.to_owned(),
+            host: host.to_owned(),
+            entity_path,
+        })
+    }
+
+    pub(crate) fn bootstrap_servers(&self) -> String {
+        format!("{}:{}", self.host, KAFKA_PORT)
+    }
+
+    /// The configured event hub, or else the one the connection string is
+    /// scoped to.
+    pub(crate) fn event_hub(
+        &self,
+        event_hub: Option<&String>,
+    ) -> Result<String, ConnectionStringError> {
+        event_hub
+            .or_else(|| self.entity_path.as_ref())
+            .cloned()
+            .ok_or(ConnectionStringError::MissingEventHub)
+    }
+
+    /// Event Hubs authenticates Kafka clients with SASL PLAIN over TLS,
+    /// taking the whole connection string as the password.
+    pub(crate) fn auth(&self) -> KafkaAuthConfig {
+        KafkaAuthConfig {
+            sasl: Some(KafkaSaslConfig {
+                enabled: Some(true),
+                username: Some("$ConnectionString".to_owned()),
+                password: Some(self.raw.clone()),
+                mechanism: Some("PLAIN".to_owned()),
+                aws_msk_iam: None,
+            }),
+            tls: Some(KafkaTlsConfig {
+                enabled: Some(true),
+                options: Default::default(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONNECTION_STRING: &str = "Endpoint=sb://vector.servicebus.windows.net/;SharedAccessKeyName=RootManageSharedAccessKey;SharedAccessKey=c2VjcmV0";
+
+    #[test]
+    fn parses_connection_string() {
+        let connection_string = ConnectionString::parse(CONNECTION_STRING).unwrap();
+
+        assert_eq!(
+            connection_string.bootstrap_servers(),
+            "vector.servicebus.windows.net:9093"
+        );
+        assert_eq!(
+            connection_string.event_hub(None),
+            Err(ConnectionStringError::MissingEventHub)
+        );
+        assert_eq!(
+            connection_string.event_hub(Some(&"logs".to_owned())),
+            Ok("logs".to_owned())
+        );
+
+        let sasl = connection_string.auth().sasl.unwrap();
+        assert_eq!(sasl.password.as_deref(), Some(CONNECTION_STRING));
+    }
+
+    #[test]
+    fn parses_entity_path() {
+        let connection_string =
+            ConnectionString::parse(&format!("{};EntityPath=logs", CONNECTION_STRING)).unwrap();
+
+        assert_eq!(connection_string.event_hub(None), Ok("logs".to_owned()));
+    }
+
+    #[test]
+    fn rejects_invalid_endpoint() {
+        assert_eq!(
+            ConnectionString::parse("SharedAccessKey=c2VjcmV0").unwrap_err(),
+            ConnectionStringError::MissingEndpoint
+        );
+        assert!(matches!(
+            ConnectionString::parse("Endpoint=https://vector.servicebus.windows.net/"),
+            Err(ConnectionStringError::InvalidEndpoint { .. })
+        ));
+    }
+}
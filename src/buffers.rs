@@ -71,6 +71,15 @@ impl BufferConfig {
         build(variant)
     }
 
+    /// The number of events the buffer can hold, if it is bounded by events.
+    pub fn max_events(&self) -> Option<usize> {
+        match self {
+            BufferConfig::Memory { max_events, .. } => Some(*max_events),
+            #[cfg(feature = "disk-buffer")]
+            BufferConfig::Disk { .. } => None,
+        }
+    }
+
     /// Resources that the sink is using.
     #[cfg_attr(not(feature = "disk-buffer"), allow(unused))]
     pub fn resources(&self, sink_id: &str) -> Vec<Resource> {
@@ -81,6 +81,7 @@ impl CheckFieldsPredicate for EqualsPredicate {
                     CheckFieldsPredicateArg::String(s) => s.as_bytes() == v.as_bytes(),
                     _ => false,
                 }),
+            Event::Trace(_) => false,
         }
     }
 }
@@ -246,6 +247,7 @@ impl CheckFieldsPredicate for NotEqualsPredicate {
                 .map_or(false, |v| {
                     !self.arg.iter().any(|s| v.as_bytes() == s.as_bytes())
                 }),
+            Event::Trace(_) => false,
         }
     }
 }
@@ -284,6 +286,7 @@ impl CheckFieldsPredicate for RegexPredicate {
                 .tags()
                 .and_then(|tags| tags.get(&self.target))
                 .map_or(false, |field| self.regex.is_match(field)),
+            Event::Trace(_) => false,
         }
     }
 }
@@ -313,6 +316,7 @@ impl CheckFieldsPredicate for ExistsPredicate {
         (match event {
             Event::Log(l) => l.get(&self.target).is_some(),
             Event::Metric(m) => m.tags().map_or(false, |t| t.contains_key(&self.target)),
+            Event::Trace(t) => t.get(&self.target).is_some(),
         }) == self.arg
     }
 }
@@ -417,7 +421,7 @@ impl CheckFieldsPredicate for LengthEqualsPredicate {
         match event {
             Event::Log(l) => l.get(&self.target).map_or(false, |v| {
                 let len = match v {
-                    Value::Bytes(value) => value.len(),
+                    Value::Bytes(value) | Value::String(value) => value.len(),
                     Value::Array(value) => value.len(),
                     Value::Map(value) => value.len(),
                     Value::Null => 0,
@@ -8,6 +8,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use vector_core::config::GlobalOptions;
 use vector_core::default_data_dir;
+use vector_core::event::SizeAccounting;
 use vector_core::transform::TransformConfig;
 
 #[derive(Deserialize, Serialize, Debug, Default)]
@@ -97,10 +98,7 @@ impl ConfigBuilder {
         transform: T,
     ) {
         let inputs = inputs.iter().map(|&s| s.to_owned()).collect::<Vec<_>>();
-        let transform = TransformOuter {
-            inner: Box::new(transform),
-            inputs,
-        };
+        let transform = TransformOuter::new(inputs, Box::new(transform));
 
         self.transforms.insert(id.into(), transform);
     }
@@ -125,6 +123,14 @@ impl ConfigBuilder {
             errors.push("conflicting values for 'data_dir' found".to_owned());
         }
 
+        // Lineage is recorded if any of the config files enables it.
+        self.global.lineage |= with.global.lineage;
+
+        // Sizes are measured exactly if any of the config files asks for it.
+        if with.global.size_accounting == SizeAccounting::Exact {
+            self.global.size_accounting = SizeAccounting::Exact;
+        }
+
         // If the user has multiple config files, we must *merge* log schemas
         // until we meet a conflict, then we are allowed to error.
         if let Err(merge_errors) = self.global.log_schema.merge(&with.global.log_schema) {
@@ -1,4 +1,6 @@
-use super::{builder::ConfigBuilder, validation, Config, ExpandType, TransformOuter};
+use super::{
+    builder::ConfigBuilder, dead_letter_output, validation, Config, ExpandType, TransformOuter,
+};
 use indexmap::IndexMap;
 
 pub fn compile(mut builder: ConfigBuilder) -> Result<(Config, Vec<String>), Vec<String>> {
@@ -64,11 +66,20 @@ pub(super) fn expand_macros(
 
             for (name, child) in expanded {
                 let full_name = format!("{}.{}", k, name);
+                // Only the children reading from the inputs of the transform
+                // have edges from them.
+                let input_capacities = if inputs == t.inputs {
+                    t.input_capacities.clone()
+                } else {
+                    IndexMap::new()
+                };
 
                 expanded_transforms.insert(
                     full_name.clone(),
                     TransformOuter {
                         inputs,
+                        input_capacity: t.input_capacity,
+                        input_capacities,
                         inner: child,
                     },
                 );
@@ -100,13 +111,19 @@ fn expand_globs(config: &mut ConfigBuilder) {
         .chain(config.transforms.keys())
         .cloned()
         .collect::<Vec<String>>();
+    // Dead letter outputs are only consumed when named, never through globs.
+    let dead_letters = config
+        .sinks
+        .keys()
+        .map(|sink| dead_letter_output(sink))
+        .collect::<Vec<String>>();
 
     for (name, transform) in config.transforms.iter_mut() {
-        expand_globs_inner(&mut transform.inputs, name, &candidates);
+        expand_globs_inner(&mut transform.inputs, name, &candidates, &dead_letters);
     }
 
     for (name, sink) in config.sinks.iter_mut() {
-        expand_globs_inner(&mut sink.inputs, name, &candidates);
+        expand_globs_inner(&mut sink.inputs, name, &candidates, &dead_letters);
     }
 }
 
@@ -126,9 +143,18 @@ impl InputMatcher {
     }
 }
 
-fn expand_globs_inner(inputs: &mut Vec<String>, name: &str, candidates: &[String]) {
+fn expand_globs_inner(
+    inputs: &mut Vec<String>,
+    name: &str,
+    candidates: &[String],
+    dead_letters: &[String],
+) {
     let raw_inputs = std::mem::take(inputs);
     for raw_input in raw_inputs {
+        if dead_letters.contains(&raw_input) {
+            inputs.push(raw_input);
+            continue;
+        }
         let matcher = glob::Pattern::new(&raw_input)
             .map(InputMatcher::Pattern)
             .unwrap_or_else(|error| {
@@ -240,4 +266,30 @@ mod test {
         );
         assert_eq!(config.sinks["quix"].inputs, vec!["foo1", "foo2", "foos"]);
     }
+
+    #[test]
+    fn dead_letter_inputs() {
+        let mut builder = ConfigBuilder::default();
+        builder.add_source("foo", MockSourceConfig);
+        builder.add_sink("bar", &["foo"], MockSinkConfig);
+        builder.add_sink("baz", &["bar.dead_letter"], MockSinkConfig);
+        builder.add_sink("quux", &["*"], MockSinkConfig);
+
+        let config = builder.build().expect("build should succeed");
+
+        assert_eq!(config.sinks["baz"].inputs, vec!["bar.dead_letter"]);
+        assert_eq!(config.sinks["quux"].inputs, vec!["foo"]);
+        assert!(config.dead_letter_consumed("bar"));
+        assert!(!config.dead_letter_consumed("baz"));
+
+        let mut builder = ConfigBuilder::default();
+        builder.add_source("foo", MockSourceConfig);
+        builder.add_sink("bar", &["foo", "bar.dead_letter"], MockSinkConfig);
+
+        let errors = builder.build().expect_err("build should fail");
+        assert_eq!(
+            errors,
+            vec!["Cyclic dependency detected in the chain [ bar -> bar.dead_letter -> bar ]"]
+        );
+    }
 }
@@ -14,10 +14,22 @@ impl ConfigDiff {
     }
 
     pub fn new(old: &Config, new: &Config) -> Self {
+        let mut sinks = Difference::new(&old.sinks, &new.sinks);
+        // Sinks only track the events they reject while their dead letter
+        // output is consumed.
+        let dead_letter_changed = old
+            .sinks
+            .keys()
+            .filter(|id| new.sinks.contains_key(*id))
+            .filter(|id| old.dead_letter_consumed(id) != new.dead_letter_consumed(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        sinks.to_change.extend(dead_letter_changed);
+
         ConfigDiff {
             sources: Difference::new(&old.sources, &new.sources),
             transforms: Difference::new(&old.transforms, &new.transforms),
-            sinks: Difference::new(&old.sinks, &new.sinks),
+            sinks,
         }
     }
 
@@ -282,6 +282,12 @@ impl SinkOuter {
     }
 }
 
+/// The name of the output carrying the events a sink rejected permanently,
+/// which components can take as input like any other component.
+pub fn dead_letter_output(sink: &str) -> String {
+    format!("{}.dead_letter", sink)
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(default)]
 pub struct SinkHealthcheckOptions {
@@ -370,10 +376,32 @@ inventory::collect!(SinkDescription);
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TransformOuter {
     pub inputs: Vec<String>,
+    #[serde(default = "default_input_capacity")]
+    pub input_capacity: usize,
+    /// The capacity of the edges from some of the inputs, overriding
+    /// `input_capacity`.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub input_capacities: IndexMap<String, usize>,
     #[serde(flatten)]
     pub inner: Box<dyn TransformConfig>,
 }
 
+/// The number of events buffered on each edge into a transform.
+fn default_input_capacity() -> usize {
+    100
+}
+
+impl TransformOuter {
+    pub(crate) fn new(inputs: Vec<String>, inner: Box<dyn TransformConfig>) -> Self {
+        Self {
+            inputs,
+            input_capacity: default_input_capacity(),
+            input_capacities: IndexMap::new(),
+            inner,
+        }
+    }
+}
+
 pub type TransformDescription = ComponentDescription<Box<dyn TransformConfig>>;
 
 inventory::collect!(TransformDescription);
@@ -522,6 +550,19 @@ impl Config {
             .cloned()
             .unwrap_or_else(|| vec![String::from(identifier)])
     }
+
+    /// Whether any component consumes the events rejected by the sink, which
+    /// are otherwise not tracked.
+    pub fn dead_letter_consumed(&self, sink: &str) -> bool {
+        let output = dead_letter_output(sink);
+        self.transforms
+            .values()
+            .any(|transform| transform.inputs.contains(&output))
+            || self
+                .sinks
+                .values()
+                .any(|sink| sink.inputs.contains(&output))
+    }
 }
 
 #[cfg(all(
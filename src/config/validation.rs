@@ -1,4 +1,4 @@
-use super::{builder::ConfigBuilder, DataType, Resource};
+use super::{builder::ConfigBuilder, dead_letter_output, DataType, Resource};
 use std::collections::HashMap;
 
 /// Check that provide + topology config aren't present in the same builder, which is an error.
@@ -70,7 +70,13 @@ pub fn check_shape(config: &ConfigBuilder) -> Result<(), Vec<String>> {
         }
 
         for input in inputs {
-            if !config.sources.contains_key(&input) && !config.transforms.contains_key(&input) {
+            if !config.sources.contains_key(&input)
+                && !config.transforms.contains_key(&input)
+                && !config
+                    .sinks
+                    .keys()
+                    .any(|sink| dead_letter_output(sink) == input)
+            {
                 errors.push(format!(
                     "Input {:?} for {} {:?} doesn't exist.",
                     input, output_type, name
@@ -79,6 +85,17 @@ pub fn check_shape(config: &ConfigBuilder) -> Result<(), Vec<String>> {
         }
     }
 
+    for (name, transform) in &config.transforms {
+        for input in transform.input_capacities.keys() {
+            if !transform.inputs.contains(input) {
+                errors.push(format!(
+                    "Transform {:?} has a capacity for {:?}, which isn't one of its inputs.",
+                    name, input
+                ));
+            }
+        }
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -240,7 +257,8 @@ impl Graph {
                     (Node::Source { ty: ty1 }, Node::Sink { ty: ty2, .. })
                     | (Node::Source { ty: ty1 }, Node::Transform { in_ty: ty2, .. })
                     | (Node::Transform { out_ty: ty1, .. }, Node::Transform { in_ty: ty2, .. })
-                    | (Node::Transform { out_ty: ty1, .. }, Node::Sink { ty: ty2, .. }) => {
+                    | (Node::Transform { out_ty: ty1, .. }, Node::Sink { ty: ty2, .. })
+                    | (Node::Sink { ty: ty1, .. }, Node::Transform { in_ty: ty2, .. }) => {
                         if ty1 != ty2 && ty1 != DataType::Any && ty2 != DataType::Any {
                             errors.push(format!(
                                 "Data type mismatch between {} ({:?}) and {} ({:?})",
@@ -248,7 +266,9 @@ impl Graph {
                             ));
                         }
                     }
-                    (Node::Sink { .. }, _) | (_, Node::Source { .. }) => unreachable!(),
+                    (Node::Sink { .. }, Node::Sink { .. }) | (_, Node::Source { .. }) => {
+                        unreachable!()
+                    }
                 }
             }
         }
@@ -282,7 +302,10 @@ impl From<&ConfigBuilder> for Graph {
         }
 
         for (name, config) in config.sinks.iter() {
-            graph.add_sink(name, config.inner.input_type(), config.inputs.clone());
+            let ty = config.inner.input_type();
+            graph.add_sink(name, ty, config.inputs.clone());
+            // Rejected events flow from the sink into its dead letter output.
+            graph.add_transform(&dead_letter_output(name), ty, ty, vec![name.clone()]);
         }
 
         graph
@@ -0,0 +1,42 @@
+use super::InternalEvent;
+use crate::avro::AvroError;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct AvroEncodingFailed<'a> {
+    pub error: &'a AvroError,
+}
+
+impl InternalEvent for AvroEncodingFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Event doesn't fit the Avro schema; discarding event.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("encode_errors_total", 1, "codec" => "avro");
+        counter!("events_discarded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct AvroDecodingFailed<'a> {
+    pub error: &'a AvroError,
+}
+
+impl InternalEvent for AvroDecodingFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to decode Avro message; discarding it.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("decode_errors_total", 1, "codec" => "avro");
+    }
+}
@@ -1,13 +1,103 @@
 use super::InternalEvent;
+#[cfg(feature = "sinks-aws_kinesis_streams")]
 use metrics::counter;
 
+#[cfg(feature = "sinks-aws_kinesis_streams")]
 #[derive(Debug)]
 pub struct AwsKinesisStreamsEventSent {
     pub byte_size: usize,
 }
 
+#[cfg(feature = "sinks-aws_kinesis_streams")]
 impl InternalEvent for AwsKinesisStreamsEventSent {
     fn emit_metrics(&self) {
         counter!("processed_bytes_total", self.byte_size as u64);
     }
 }
+
+#[cfg(feature = "sources-aws_kinesis_streams")]
+mod source {
+    use super::InternalEvent;
+    use metrics::counter;
+    use std::fmt::Display;
+
+    #[derive(Debug)]
+    pub struct AwsKinesisStreamsEventReceived {
+        pub byte_size: usize,
+    }
+
+    impl InternalEvent for AwsKinesisStreamsEventReceived {
+        fn emit_logs(&self) {
+            trace!(message = "Received one event.", internal_log_rate_secs = 10);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("events_in_total", 1);
+            counter!("processed_bytes_total", self.byte_size as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsKinesisStreamsListShardsFailed<E> {
+        pub error: E,
+    }
+
+    impl<E: Display> InternalEvent for AwsKinesisStreamsListShardsFailed<E> {
+        fn emit_logs(&self) {
+            warn!(
+                message = "Failed to list shards.",
+                error = %self.error,
+                internal_log_rate_secs = 10,
+            );
+        }
+
+        fn emit_metrics(&self) {
+            counter!("request_errors_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsKinesisStreamsReadFailed<'a, E> {
+        pub error: E,
+        pub shard_id: &'a str,
+    }
+
+    impl<'a, E: Display> InternalEvent for AwsKinesisStreamsReadFailed<'a, E> {
+        fn emit_logs(&self) {
+            warn!(
+                message = "Failed to read shard, reading again from its last checkpoint.",
+                error = %self.error,
+                shard_id = %self.shard_id,
+                internal_log_rate_secs = 10,
+            );
+        }
+
+        fn emit_metrics(&self) {
+            counter!("request_errors_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsKinesisStreamsCheckpointFailed<'a> {
+        pub error: crate::Error,
+        pub shard_id: &'a str,
+    }
+
+    impl<'a> InternalEvent for AwsKinesisStreamsCheckpointFailed<'a> {
+        fn emit_logs(&self) {
+            error!(
+                message = "Failed to write checkpoint.",
+                error = %self.error,
+                shard_id = %self.shard_id,
+                internal_log_rate_secs = 10,
+            );
+        }
+
+        fn emit_metrics(&self) {
+            counter!("checkpoint_write_errors_total", 1);
+        }
+    }
+}
+
+#[cfg(feature = "sources-aws_kinesis_streams")]
+pub use self::source::*;
@@ -4,6 +4,7 @@ pub mod source {
     use crate::sources::aws_s3::sqs::ProcessingError;
     use metrics::counter;
     use rusoto_core::RusotoError;
+    use rusoto_s3::ListObjectsV2Error;
     use rusoto_sqs::{
         BatchResultErrorEntry, DeleteMessageBatchError, DeleteMessageBatchRequestEntry,
         DeleteMessageBatchResultEntry, ReceiveMessageError,
@@ -164,6 +165,54 @@ pub mod source {
             counter!("sqs_s3_event_record_ignored_total", 1, "ignore_type" => "invalid_event_kind");
         }
     }
+
+    #[derive(Debug)]
+    pub(crate) struct S3ObjectsListFailed<'a> {
+        pub bucket: &'a str,
+        pub error: &'a RusotoError<ListObjectsV2Error>,
+    }
+
+    impl<'a> InternalEvent for S3ObjectsListFailed<'a> {
+        fn emit_logs(&self) {
+            warn!(message = "Failed to list S3 objects.", bucket = %self.bucket, error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("s3_objects_list_failed_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct S3ObjectProcessingFailed<'a> {
+        pub bucket: &'a str,
+        pub key: &'a str,
+        pub error: &'a ProcessingError,
+    }
+
+    impl<'a> InternalEvent for S3ObjectProcessingFailed<'a> {
+        fn emit_logs(&self) {
+            error!(message = "Failed to process S3 object.", bucket = %self.bucket, key = %self.key, error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("s3_object_processing_failed_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct S3PollProgressWriteFailed {
+        pub error: std::io::Error,
+    }
+
+    impl InternalEvent for S3PollProgressWriteFailed {
+        fn emit_logs(&self) {
+            error!(message = "Failed to write S3 polling progress.", error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("s3_poll_progress_write_failed_total", 1);
+        }
+    }
 }
 
 #[cfg(feature = "sinks-aws_s3")]
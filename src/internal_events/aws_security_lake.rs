@@ -0,0 +1,24 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct OcsfInvalidValue<'a> {
+    pub attribute: &'static str,
+    pub value: &'a str,
+}
+
+impl<'a> InternalEvent for OcsfInvalidValue<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid value for OCSF attribute; using `Other`.",
+            attribute = %self.attribute,
+            value = %self.value,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "invalid_value");
+    }
+}
@@ -1,12 +1,15 @@
 use super::InternalEvent;
+#[cfg(feature = "sinks-aws_sqs")]
 use metrics::counter;
 
+#[cfg(feature = "sinks-aws_sqs")]
 #[derive(Debug)]
 pub struct AwsSqsEventSent<'a> {
     pub byte_size: usize,
     pub message_id: Option<&'a String>,
 }
 
+#[cfg(feature = "sinks-aws_sqs")]
 impl InternalEvent for AwsSqsEventSent<'_> {
     fn emit_logs(&self) {
         trace!(message = "Event sent.", message_id = ?self.message_id);
@@ -16,3 +19,114 @@ impl InternalEvent for AwsSqsEventSent<'_> {
         counter!("processed_bytes_total", self.byte_size as u64);
     }
 }
+
+#[cfg(feature = "sources-aws_sqs")]
+mod source {
+    use super::InternalEvent;
+    use metrics::counter;
+    use rusoto_core::RusotoError;
+    use rusoto_sqs::{BatchResultErrorEntry, ReceiveMessageError};
+
+    #[derive(Debug)]
+    pub struct AwsSqsEventReceived {
+        pub byte_size: usize,
+    }
+
+    impl InternalEvent for AwsSqsEventReceived {
+        fn emit_logs(&self) {
+            trace!(message = "Received one event.", internal_log_rate_secs = 10);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("events_in_total", 1);
+            counter!("processed_bytes_total", self.byte_size as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsSqsMessageReceiveFailed<'a> {
+        pub error: &'a RusotoError<ReceiveMessageError>,
+    }
+
+    impl<'a> InternalEvent for AwsSqsMessageReceiveFailed<'a> {
+        fn emit_logs(&self) {
+            warn!(message = "Failed to fetch SQS messages.", error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("sqs_message_receive_failed_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsSqsMessageDeleteSucceeded {
+        pub count: usize,
+    }
+
+    impl InternalEvent for AwsSqsMessageDeleteSucceeded {
+        fn emit_logs(&self) {
+            trace!(message = "Deleted SQS messages.", count = %self.count);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("sqs_message_delete_succeeded_total", self.count as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsSqsMessageDeleteFailed<'a> {
+        pub count: usize,
+        pub error: &'a dyn std::error::Error,
+    }
+
+    impl<'a> InternalEvent for AwsSqsMessageDeleteFailed<'a> {
+        fn emit_logs(&self) {
+            warn!(message = "Deletion of SQS messages failed.", count = %self.count, error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!("sqs_message_delete_failed_total", self.count as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsSqsMessageDeletePartialFailure<'a> {
+        pub entries: &'a [BatchResultErrorEntry],
+    }
+
+    impl<'a> InternalEvent for AwsSqsMessageDeletePartialFailure<'a> {
+        fn emit_logs(&self) {
+            warn!(message = "Deletion of SQS messages failed.",
+                codes = %self.entries.iter()
+                    .map(|entry| entry.code.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "));
+        }
+
+        fn emit_metrics(&self) {
+            counter!("sqs_message_delete_failed_total", self.entries.len() as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct AwsSqsMessageVisibilityChangeFailed<'a> {
+        pub count: usize,
+        pub error: &'a dyn std::error::Error,
+    }
+
+    impl<'a> InternalEvent for AwsSqsMessageVisibilityChangeFailed<'a> {
+        fn emit_logs(&self) {
+            warn!(message = "Changing the visibility of SQS messages failed.", count = %self.count, error = %self.error);
+        }
+
+        fn emit_metrics(&self) {
+            counter!(
+                "sqs_message_visibility_change_failed_total",
+                self.count as u64
+            );
+        }
+    }
+}
+
+#[cfg(feature = "sources-aws_sqs")]
+pub use self::source::*;
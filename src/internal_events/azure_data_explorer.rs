@@ -0,0 +1,40 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct AzureDataExplorerBatchIngested<'a> {
+    pub ingestion: &'static str,
+    pub table: &'a str,
+    pub byte_size: usize,
+}
+
+impl<'a> InternalEvent for AzureDataExplorerBatchIngested<'a> {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Batch ingested.",
+            ingestion = %self.ingestion,
+            table = %self.table,
+            byte_size = %self.byte_size,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct AzureDataExplorerResourcesFetched {
+    pub queues: usize,
+    pub containers: usize,
+}
+
+impl InternalEvent for AzureDataExplorerResourcesFetched {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Fetched ingestion resources.",
+            queues = %self.queues,
+            containers = %self.containers,
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct CassandraRowsWritten {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for CassandraRowsWritten {
+    fn emit_logs(&self) {
+        debug!(message = "Wrote rows to the table.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
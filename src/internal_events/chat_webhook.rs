@@ -0,0 +1,22 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct ChatWebhookMessageSent {
+    pub byte_size: usize,
+    pub event_count: usize,
+}
+
+impl InternalEvent for ChatWebhookMessageSent {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Message sent.",
+            byte_size = %self.byte_size,
+            event_count = %self.event_count,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
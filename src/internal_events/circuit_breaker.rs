@@ -0,0 +1,53 @@
+use super::InternalEvent;
+use metrics::{counter, gauge};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct CircuitBreakerOpened {
+    pub failures: usize,
+    pub open_duration: Duration,
+}
+
+impl InternalEvent for CircuitBreakerOpened {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Circuit breaker opened, holding requests back.",
+            failures = %self.failures,
+            open_duration = ?self.open_duration,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("circuit_breaker_opened_total", 1);
+        gauge!("circuit_breaker_open", 1.0);
+    }
+}
+
+#[derive(Debug)]
+pub struct CircuitBreakerClosed;
+
+impl InternalEvent for CircuitBreakerClosed {
+    fn emit_logs(&self) {
+        info!(message = "Circuit breaker closed.");
+    }
+
+    fn emit_metrics(&self) {
+        gauge!("circuit_breaker_open", 0.0);
+    }
+}
+
+#[derive(Debug)]
+pub struct CircuitBreakerRequestShed;
+
+impl InternalEvent for CircuitBreakerRequestShed {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Circuit breaker is open, shedding the request.",
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("circuit_breaker_requests_shed_total", 1);
+    }
+}
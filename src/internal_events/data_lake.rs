@@ -0,0 +1,61 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct DataLakeEventsCommitted {
+    pub count: usize,
+    pub byte_size: usize,
+    pub files: usize,
+}
+
+impl InternalEvent for DataLakeEventsCommitted {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Committed events to the table.",
+            count = %self.count,
+            files = %self.files,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_events_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DataLakeRequestFailed<'a> {
+    pub stage: &'static str,
+    pub error: &'a crate::Error,
+    pub retry_secs: u64,
+}
+
+impl<'a> InternalEvent for DataLakeRequestFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to update the table, retrying.",
+            stage = %self.stage,
+            error = %self.error,
+            retry_secs = %self.retry_secs,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("request_errors_total", 1, "stage" => self.stage);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DataLakeBatchAlreadyCommitted {
+    pub sequence: i64,
+}
+
+impl InternalEvent for DataLakeBatchAlreadyCommitted {
+    fn emit_logs(&self) {
+        info!(
+            message = "Batch was committed by an earlier attempt, not committing it again.",
+            sequence = %self.sequence,
+        );
+    }
+}
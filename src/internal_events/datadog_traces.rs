@@ -0,0 +1,66 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct DatadogTracesSent {
+    pub byte_size: usize,
+    pub span_count: usize,
+}
+
+impl InternalEvent for DatadogTracesSent {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Spans sent.",
+            byte_size = %self.byte_size,
+            span_count = %self.span_count,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct DatadogTracesSpanInvalid {
+    pub field: &'static str,
+}
+
+impl InternalEvent for DatadogTracesSpanInvalid {
+    fn emit_logs(&self) {
+        error!(
+            message = "Required field is missing, dropping span.",
+            field = %self.field,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "field_missing",
+            "field" => self.field);
+        counter!("events_discarded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct DatadogTracesStatsSendFailed<E> {
+    pub error: E,
+}
+
+impl<E: std::fmt::Display> InternalEvent for DatadogTracesStatsSendFailed<E> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to send APM stats, dropping them.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "stats_send_failed");
+    }
+}
@@ -1,4 +1,5 @@
 use super::InternalEvent;
+use crate::event::DropReason;
 use metrics::counter;
 
 #[derive(Debug)]
@@ -12,6 +13,7 @@ impl InternalEvent for DedupeEventDiscarded {
     }
 
     fn emit_metrics(&self) {
-        counter!("events_discarded_total", 1);
+        counter!("events_discarded_total", 1,
+                 "reason" => DropReason::Deduplicated.as_str());
     }
 }
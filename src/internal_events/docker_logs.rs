@@ -172,3 +172,40 @@ impl<'a> InternalEvent for DockerLogsLoggingDriverUnsupported<'a> {
         counter!("logging_driver_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct DockerLogsEventStreamEnded {
+    pub backoff_secs: u64,
+}
+
+impl InternalEvent for DockerLogsEventStreamEnded {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Docker event stream has ended, reconnecting.",
+            backoff_secs = %self.backoff_secs
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct DockerLogsCheckpointWriteFailed {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for DockerLogsCheckpointWriteFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed writing checkpoints.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("checkpoint_write_errors_total", 1);
+    }
+}
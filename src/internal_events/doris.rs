@@ -0,0 +1,52 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct DorisEventsSent {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for DorisEventsSent {
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DorisRowsFiltered<'a> {
+    pub label: &'a str,
+    pub count: u64,
+    pub error_url: Option<&'a str>,
+}
+
+impl<'a> InternalEvent for DorisRowsFiltered<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Rows were filtered out of the load by the table.",
+            label = %self.label,
+            count = %self.count,
+            error_url = ?self.error_url,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("doris_filtered_rows_total", self.count);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DorisLabelAlreadyLoaded<'a> {
+    pub label: &'a str,
+    pub status: Option<&'a str>,
+}
+
+impl<'a> InternalEvent for DorisLabelAlreadyLoaded<'a> {
+    fn emit_logs(&self) {
+        info!(
+            message = "Batch was loaded by an earlier attempt, not loading it again.",
+            label = %self.label,
+            status = ?self.status,
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct EbpfEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for EbpfEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct EbpfEventsLost {
+    pub count: usize,
+}
+
+impl InternalEvent for EbpfEventsLost {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Kernel events were lost, as they were read too slowly.",
+            count = %self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", self.count as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct EbpfReadFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for EbpfReadFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to read kernel events.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("read_errors_total", 1);
+    }
+}
@@ -16,3 +16,23 @@ impl InternalEvent for ElasticSearchEventEncoded {
         counter!("processed_bytes_total", self.byte_size as u64);
     }
 }
+
+#[derive(Debug)]
+pub struct ElasticSearchTemplateBootstrapFailed<'a> {
+    pub error: crate::Error,
+    pub template: &'a str,
+}
+
+impl<'a> InternalEvent for ElasticSearchTemplateBootstrapFailed<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to bootstrap index template.",
+            error = %self.error,
+            template = %self.template,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("template_bootstrap_errors_total", 1);
+    }
+}
@@ -116,3 +116,27 @@ impl InternalEvent for ExecCommandExecuted<'_> {
         );
     }
 }
+
+#[derive(Debug)]
+pub struct ExecRespawnsExhausted<'a> {
+    pub command: &'a str,
+    pub respawns: u32,
+}
+
+impl InternalEvent for ExecRespawnsExhausted<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Command kept exiting, giving up on respawning it.",
+            command = %self.command,
+            respawns = %self.respawns,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "command" => self.command.to_owned(),
+            "error_type" => "respawns_exhausted",
+        );
+    }
+}
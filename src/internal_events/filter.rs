@@ -1,4 +1,5 @@
 use super::InternalEvent;
+use crate::event::DropReason;
 use metrics::counter;
 
 #[derive(Debug)]
@@ -6,6 +7,7 @@ pub struct FilterEventDiscarded;
 
 impl InternalEvent for FilterEventDiscarded {
     fn emit_metrics(&self) {
-        counter!("events_discarded_total", 1);
+        counter!("events_discarded_total", 1,
+                 "reason" => DropReason::Filtered.as_str());
     }
 }
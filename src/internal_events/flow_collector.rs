@@ -0,0 +1,109 @@
+use super::InternalEvent;
+use crate::sources::flow_collector::DecodeError;
+use metrics::counter;
+use std::net::SocketAddr;
+
+#[derive(Debug)]
+pub struct FlowCollectorEventsReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for FlowCollectorEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            count = %self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct FlowCollectorInvalidDatagram {
+    pub error: DecodeError,
+    pub exporter: SocketAddr,
+}
+
+impl InternalEvent for FlowCollectorInvalidDatagram {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Discarding invalid datagram.",
+            error = %self.error,
+            peer_addr = %self.exporter,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct FlowCollectorTemplateMissing {
+    pub exporter: SocketAddr,
+    pub template_id: u16,
+}
+
+impl InternalEvent for FlowCollectorTemplateMissing {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Discarding records of unknown template.",
+            template_id = %self.template_id,
+            peer_addr = %self.exporter,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+enum FlowCollectorSocketErrorType {
+    Bind,
+    Read,
+}
+
+#[derive(Debug)]
+pub struct FlowCollectorSocketError {
+    r#type: FlowCollectorSocketErrorType,
+    pub error: std::io::Error,
+}
+
+impl FlowCollectorSocketError {
+    pub const fn bind(error: std::io::Error) -> Self {
+        Self {
+            r#type: FlowCollectorSocketErrorType::Bind,
+            error,
+        }
+    }
+
+    pub const fn read(error: std::io::Error) -> Self {
+        Self {
+            r#type: FlowCollectorSocketErrorType::Read,
+            error,
+        }
+    }
+}
+
+impl InternalEvent for FlowCollectorSocketError {
+    fn emit_logs(&self) {
+        let message = match self.r#type {
+            FlowCollectorSocketErrorType::Bind => "Failed to bind to UDP listener socket.",
+            FlowCollectorSocketErrorType::Read => "Failed to read UDP datagram.",
+        };
+        error!(message, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
@@ -0,0 +1,55 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct GcpBigqueryRowsWritten {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GcpBigqueryRowsWritten {
+    fn emit_logs(&self) {
+        debug!(message = "Appended rows to the write stream.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GcpBigqueryRowsRejected<'a> {
+    pub count: usize,
+    /// Why the first of the rows was rejected.
+    pub error: &'a str,
+    /// Whether the rows were written to the dead-letter table rather than
+    /// dropped.
+    pub dead_lettered: bool,
+}
+
+impl<'a> InternalEvent for GcpBigqueryRowsRejected<'a> {
+    fn emit_logs(&self) {
+        if self.dead_lettered {
+            warn!(
+                message = "Rows don't match the table schema; wrote them to the dead-letter table.",
+                count = %self.count,
+                error = %self.error,
+                internal_log_rate_secs = 10,
+            );
+        } else {
+            warn!(
+                message = "Rows don't match the table schema; dropping them.",
+                count = %self.count,
+                error = %self.error,
+                internal_log_rate_secs = 10,
+            );
+        }
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "bigquery_rows_rejected_total", self.count as u64,
+            "dead_lettered" => self.dead_lettered.to_string(),
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct GcsPartitionCompleted<'a> {
+    pub partition: &'a str,
+    pub objects: usize,
+}
+
+impl<'a> InternalEvent for GcsPartitionCompleted<'a> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Marked partition complete.",
+            partition = %self.partition,
+            objects = %self.objects,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("partitions_completed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GcsPartitionMarkersFailed<'a> {
+    pub partition: &'a str,
+    pub error: crate::Error,
+}
+
+impl<'a> InternalEvent for GcsPartitionMarkersFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to mark partition complete; retrying.",
+            partition = %self.partition,
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "partition_markers_failed");
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GcsPartitionFailed<'a> {
+    pub partition: &'a str,
+}
+
+impl<'a> InternalEvent for GcsPartitionFailed<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to write an object of partition; it won't be marked complete.",
+            partition = %self.partition,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("partitions_failed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GcsLatePartitionEvent<'a> {
+    pub partition: &'a str,
+}
+
+impl<'a> InternalEvent for GcsLatePartitionEvent<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Event arrived after its partition was forgotten.",
+            partition = %self.partition,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("late_events_total", 1);
+    }
+}
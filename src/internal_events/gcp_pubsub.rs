@@ -0,0 +1,33 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct GcpPubsubEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GcpPubsubEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct GcpPubsubStreamingPullFailed {
+    pub error: crate::Error,
+}
+
+impl InternalEvent for GcpPubsubStreamingPullFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to pull messages; retrying.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("streaming_pull_errors_total", 1);
+    }
+}
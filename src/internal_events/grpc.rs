@@ -0,0 +1,39 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct GrpcMessagesSent {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for GrpcMessagesSent {
+    fn emit_logs(&self) {
+        trace!(message = "Messages sent.", count = %self.count, byte_size = %self.byte_size);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GrpcEventEncodingFailed<'a> {
+    pub error: &'a str,
+}
+
+impl<'a> InternalEvent for GrpcEventEncodingFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Event doesn't fit the request message; discarding event.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "value_invalid");
+        counter!("events_discarded_total", 1);
+    }
+}
@@ -95,3 +95,28 @@ impl<'a> InternalEvent for HttpDecompressError<'a> {
         counter!("parse_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct HttpRequestTemplateInvalid {
+    pub error: String,
+    pub drop_event: bool,
+}
+
+impl InternalEvent for HttpRequestTemplateInvalid {
+    fn emit_logs(&self) {
+        let message = if self.drop_event {
+            "Rendered request is invalid; discarding event."
+        } else {
+            "Rendered request is invalid; sending event to the dead-letter uri."
+        };
+        warn!(message = %message, error = %self.error, internal_log_rate_secs = 30);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "invalid_request");
+        if self.drop_event {
+            counter!("events_discarded_total", 1);
+        }
+    }
+}
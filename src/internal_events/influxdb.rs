@@ -0,0 +1,32 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct InfluxDbTcpLateEvent<'a> {
+    pub measurement: &'a str,
+    pub lateness_ns: i64,
+    pub dropped: bool,
+}
+
+impl<'a> InternalEvent for InfluxDbTcpLateEvent<'a> {
+    fn emit_logs(&self) {
+        let action = match self.dropped {
+            true => "dropping event",
+            false => "leaving its timestamp to the server",
+        };
+        debug!(
+            message = "Event arrived late.",
+            measurement = %self.measurement,
+            lateness_ms = %(self.lateness_ns / 1_000_000),
+            action,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("late_events_total", 1);
+        if self.dropped {
+            counter!("events_discarded_total", 1, "reason" => "late");
+        }
+    }
+}
@@ -99,3 +99,58 @@ impl InternalEvent for KafkaStatisticsReceived<'_> {
         );
     }
 }
+
+#[derive(Debug)]
+pub struct KafkaTransactionCommitted {
+    pub count: usize,
+}
+
+impl InternalEvent for KafkaTransactionCommitted {
+    fn emit_logs(&self) {
+        trace!(message = "Committed transaction.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("kafka_transactions_committed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct KafkaTransactionAborted<'a> {
+    pub error: &'a rdkafka::error::KafkaError,
+    pub retry_secs: u64,
+}
+
+impl InternalEvent for KafkaTransactionAborted<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Aborting transaction, retrying.",
+            error = %self.error,
+            retry_secs = %self.retry_secs,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("kafka_transactions_aborted_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct KafkaSchemaSerializationFailed<'a> {
+    pub error: &'a str,
+}
+
+impl InternalEvent for KafkaSchemaSerializationFailed<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Event doesn't fit the schema, dropping it.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "schema_mismatch");
+    }
+}
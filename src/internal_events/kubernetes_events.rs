@@ -0,0 +1,37 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct KubernetesEventsReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for KubernetesEventsReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct KubernetesEventsCheckpointFailed {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for KubernetesEventsCheckpointFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Unable to access the resource version checkpoint.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("checkpoint_write_errors_total", 1);
+    }
+}
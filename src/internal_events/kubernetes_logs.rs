@@ -105,3 +105,24 @@ impl InternalEvent for KubernetesLogsDockerFormatParseFailed<'_> {
         counter!("k8s_docker_format_parse_failures_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct KubernetesLogsPodLookupFailed<'a> {
+    pub pod_name: &'a str,
+    pub error: crate::Error,
+}
+
+impl InternalEvent for KubernetesLogsPodLookupFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to look up the pod of a log file missing from the cache.",
+            pod_name = %self.pod_name,
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("k8s_pod_lookup_failures_total", 1);
+    }
+}
@@ -42,3 +42,22 @@ impl InternalEvent for HerokuLogplexRequestReadError {
         counter!("request_read_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct HerokuLogplexDrainRejected<'a> {
+    pub drain_token: &'a str,
+}
+
+impl<'a> InternalEvent for HerokuLogplexDrainRejected<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Rejected request from unknown drain.",
+            drain_token = %self.drain_token,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("requests_rejected_total", 1);
+    }
+}
@@ -9,6 +9,8 @@ mod ansi_stripper;
 mod apache_metrics;
 #[cfg(feature = "api")]
 mod api;
+#[cfg(feature = "codecs-avro")]
+mod avro;
 #[cfg(feature = "transforms-aws_cloudwatch_logs_subscription_parser")]
 mod aws_cloudwatch_logs_subscription_parser;
 #[cfg(feature = "transforms-aws_ec2_metadata")]
@@ -17,15 +19,27 @@ mod aws_ec2_metadata;
 mod aws_ecs_metrics;
 #[cfg(feature = "sources-aws_kinesis_firehose")]
 mod aws_kinesis_firehose;
-#[cfg(feature = "sinks-aws_kinesis_streams")]
+#[cfg(any(
+    feature = "sources-aws_kinesis_streams",
+    feature = "sinks-aws_kinesis_streams"
+))]
 mod aws_kinesis_streams;
 #[cfg(any(feature = "sources-aws_s3", feature = "sinks-aws_s3"))]
 pub(crate) mod aws_s3;
-#[cfg(feature = "sinks-aws_sqs")]
+#[cfg(feature = "sinks-aws_security_lake")]
+mod aws_security_lake;
+#[cfg(any(feature = "sources-aws_sqs", feature = "sinks-aws_sqs"))]
 mod aws_sqs;
 #[cfg(feature = "sinks-azure_blob")]
 pub(crate) mod azure_blob;
+#[cfg(feature = "sinks-azure_data_explorer")]
+mod azure_data_explorer;
 mod blackhole;
+#[cfg(feature = "sinks-cassandra")]
+mod cassandra;
+#[cfg(feature = "sinks-chat_webhook")]
+mod chat_webhook;
+mod circuit_breaker;
 #[cfg(feature = "transforms-coercer")]
 mod coercer;
 #[cfg(feature = "transforms-concat")]
@@ -33,16 +47,24 @@ mod concat;
 mod conditions;
 #[cfg(feature = "sinks-console")]
 mod console;
+#[cfg(feature = "sinks-data_lake")]
+mod data_lake;
 #[cfg(feature = "sinks-datadog")]
 mod datadog_events;
 #[cfg(feature = "sinks-datadog")]
 mod datadog_logs;
+#[cfg(feature = "sinks-datadog_traces")]
+mod datadog_traces;
 #[cfg(feature = "transforms-dedupe")]
 mod dedupe;
 #[cfg(feature = "sources-dnstap")]
 mod dnstap;
 #[cfg(feature = "sources-docker_logs")]
 mod docker_logs;
+#[cfg(feature = "sinks-doris")]
+mod doris;
+#[cfg(all(target_os = "linux", feature = "sources-ebpf"))]
+mod ebpf;
 mod elasticsearch;
 mod encoding_transcode;
 #[cfg(feature = "sources-eventstoredb_metrics")]
@@ -51,19 +73,31 @@ mod eventstoredb_metrics;
 mod exec;
 #[cfg(feature = "transforms-filter")]
 mod filter;
+#[cfg(feature = "sources-flow_collector")]
+mod flow_collector;
 #[cfg(feature = "sources-fluent")]
 mod fluent;
+#[cfg(feature = "sinks-gcp_bigquery")]
+mod gcp_bigquery;
+#[cfg(feature = "sinks-gcp")]
+mod gcp_cloud_storage;
+#[cfg(feature = "sources-gcp_pubsub")]
+mod gcp_pubsub;
 #[cfg(feature = "sources-generator")]
 mod generator;
 #[cfg(feature = "transforms-geoip")]
 mod geoip;
 #[cfg(feature = "transforms-grok_parser")]
 mod grok_parser;
+#[cfg(feature = "sinks-grpc")]
+mod grpc;
 mod heartbeat;
 #[cfg(feature = "sources-host_metrics")]
 mod host_metrics;
 mod http;
 pub mod http_client;
+#[cfg(any(feature = "sinks-influxdb", feature = "prometheus-integration-tests"))]
+mod influxdb;
 #[cfg(all(unix, feature = "sources-journald"))]
 mod journald;
 #[cfg(feature = "transforms-json_parser")]
@@ -72,6 +106,8 @@ mod json_parser;
 mod kafka;
 #[cfg(feature = "transforms-key_value_parser")]
 mod key_value_parser;
+#[cfg(feature = "sources-kubernetes_events")]
+mod kubernetes_events;
 #[cfg(feature = "sources-kubernetes_logs")]
 mod kubernetes_logs;
 #[cfg(feature = "transforms-log_to_metric")]
@@ -85,18 +121,33 @@ mod lua;
 mod metric_to_log;
 #[cfg(feature = "sources-mongodb_metrics")]
 mod mongodb_metrics;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+mod mqtt;
 #[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
 mod nats;
 #[cfg(feature = "sources-nginx_metrics")]
 mod nginx_metrics;
 mod open;
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+mod opentelemetry;
+#[cfg(feature = "sinks-opsgenie")]
+mod opsgenie;
+#[cfg(feature = "sinks-pagerduty")]
+mod pagerduty;
+#[cfg(feature = "sinks-postgres")]
+mod postgres;
+#[cfg(feature = "sources-postgres_cdc")]
+mod postgres_cdc;
 #[cfg(feature = "sources-postgresql_metrics")]
 mod postgresql_metrics;
 mod process;
 #[cfg(any(feature = "sources-prometheus", feature = "sinks-prometheus"))]
 mod prometheus;
+#[cfg(feature = "codecs-protobuf")]
+mod protobuf;
+#[cfg(any(feature = "sources-pulsar", feature = "sinks-pulsar"))]
 mod pulsar;
-#[cfg(feature = "sinks-redis")]
+#[cfg(any(feature = "sinks-redis", feature = "sources-redis"))]
 mod redis;
 #[cfg(feature = "transforms-reduce")]
 mod reduce;
@@ -112,6 +163,10 @@ mod route;
 mod sample;
 #[cfg(feature = "sinks-sematext")]
 mod sematext_metrics;
+#[cfg(feature = "sinks-smtp")]
+mod smtp;
+#[cfg(feature = "sources-snmp_trap")]
+mod snmp_trap;
 mod socket;
 mod split;
 #[cfg(any(feature = "sources-splunk_hec", feature = "sinks-splunk_hec"))]
@@ -134,6 +189,10 @@ mod unix;
 mod vector;
 #[cfg(feature = "wasm")]
 mod wasm;
+#[cfg(feature = "sinks-websocket_server")]
+mod websocket_server;
+#[cfg(all(windows, feature = "sources-windows_eventlog"))]
+mod windows_eventlog;
 
 pub mod kubernetes;
 
@@ -146,6 +205,8 @@ pub use self::ansi_stripper::*;
 pub use self::apache_metrics::*;
 #[cfg(feature = "api")]
 pub use self::api::*;
+#[cfg(feature = "codecs-avro")]
+pub use self::avro::*;
 #[cfg(feature = "transforms-aws_cloudwatch_logs_subscription_parser")]
 pub(crate) use self::aws_cloudwatch_logs_subscription_parser::*;
 #[cfg(feature = "transforms-aws_ec2_metadata")]
@@ -154,11 +215,23 @@ pub use self::aws_ec2_metadata::*;
 pub use self::aws_ecs_metrics::*;
 #[cfg(feature = "sources-aws_kinesis_firehose")]
 pub use self::aws_kinesis_firehose::*;
-#[cfg(feature = "sinks-aws_kinesis_streams")]
+#[cfg(any(
+    feature = "sources-aws_kinesis_streams",
+    feature = "sinks-aws_kinesis_streams"
+))]
 pub use self::aws_kinesis_streams::*;
-#[cfg(feature = "sinks-aws_sqs")]
+#[cfg(feature = "sinks-aws_security_lake")]
+pub(crate) use self::aws_security_lake::*;
+#[cfg(any(feature = "sources-aws_sqs", feature = "sinks-aws_sqs"))]
 pub use self::aws_sqs::*;
+#[cfg(feature = "sinks-azure_data_explorer")]
+pub(crate) use self::azure_data_explorer::*;
 pub use self::blackhole::*;
+#[cfg(feature = "sinks-cassandra")]
+pub(crate) use self::cassandra::*;
+#[cfg(feature = "sinks-chat_webhook")]
+pub(crate) use self::chat_webhook::*;
+pub use self::circuit_breaker::*;
 #[cfg(feature = "transforms-coercer")]
 pub(crate) use self::coercer::*;
 #[cfg(feature = "transforms-concat")]
@@ -166,16 +239,24 @@ pub use self::concat::*;
 pub use self::conditions::*;
 #[cfg(feature = "sinks-console")]
 pub use self::console::*;
+#[cfg(feature = "sinks-data_lake")]
+pub(crate) use self::data_lake::*;
 #[cfg(feature = "sinks-datadog")]
 pub use self::datadog_events::*;
 #[cfg(feature = "sinks-datadog")]
 pub use self::datadog_logs::*;
+#[cfg(feature = "sinks-datadog_traces")]
+pub use self::datadog_traces::*;
 #[cfg(feature = "transforms-dedupe")]
 pub(crate) use self::dedupe::*;
 #[cfg(feature = "sources-dnstap")]
 pub(crate) use self::dnstap::*;
 #[cfg(feature = "sources-docker_logs")]
 pub use self::docker_logs::*;
+#[cfg(feature = "sinks-doris")]
+pub(crate) use self::doris::*;
+#[cfg(all(target_os = "linux", feature = "sources-ebpf"))]
+pub use self::ebpf::*;
 pub use self::elasticsearch::*;
 pub use self::encoding_transcode::*;
 #[cfg(feature = "sources-eventstoredb_metrics")]
@@ -190,19 +271,31 @@ pub use self::exec::*;
 pub use self::file::*;
 #[cfg(feature = "transforms-filter")]
 pub use self::filter::*;
+#[cfg(feature = "sources-flow_collector")]
+pub use self::flow_collector::*;
 #[cfg(feature = "sources-fluent")]
 pub use self::fluent::*;
+#[cfg(feature = "sinks-gcp_bigquery")]
+pub(crate) use self::gcp_bigquery::*;
+#[cfg(feature = "sinks-gcp")]
+pub(crate) use self::gcp_cloud_storage::*;
+#[cfg(feature = "sources-gcp_pubsub")]
+pub use self::gcp_pubsub::*;
 #[cfg(feature = "sources-generator")]
 pub use self::generator::*;
 #[cfg(feature = "transforms-geoip")]
 pub(crate) use self::geoip::*;
 #[cfg(feature = "transforms-grok_parser")]
 pub(crate) use self::grok_parser::*;
+#[cfg(feature = "sinks-grpc")]
+pub(crate) use self::grpc::*;
 pub use self::heartbeat::*;
 #[cfg(feature = "sources-host_metrics")]
 pub(crate) use self::host_metrics::*;
 #[cfg(any(feature = "sources-utils-http", feature = "sinks-http"))]
 pub(crate) use self::http::*;
+#[cfg(any(feature = "sinks-influxdb", feature = "prometheus-integration-tests"))]
+pub(crate) use self::influxdb::*;
 #[cfg(all(unix, feature = "sources-journald"))]
 pub(crate) use self::journald::*;
 #[cfg(feature = "transforms-json_parser")]
@@ -211,6 +304,8 @@ pub(crate) use self::json_parser::*;
 pub use self::kafka::*;
 #[cfg(feature = "transforms-key_value_parser")]
 pub(crate) use self::key_value_parser::*;
+#[cfg(feature = "sources-kubernetes_events")]
+pub use self::kubernetes_events::*;
 #[cfg(feature = "sources-kubernetes_logs")]
 pub use self::kubernetes_logs::*;
 #[cfg(feature = "transforms-log_to_metric")]
@@ -222,18 +317,33 @@ pub use self::logplex::*;
 pub use self::lua::*;
 #[cfg(feature = "transforms-metric_to_log")]
 pub(crate) use self::metric_to_log::*;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub use self::mqtt::*;
 #[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
 pub use self::nats::*;
 #[cfg(feature = "sources-nginx_metrics")]
 pub(crate) use self::nginx_metrics::*;
 pub use self::open::*;
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+pub(crate) use self::opentelemetry::*;
+#[cfg(feature = "sinks-opsgenie")]
+pub(crate) use self::opsgenie::*;
+#[cfg(feature = "sinks-pagerduty")]
+pub(crate) use self::pagerduty::*;
+#[cfg(feature = "sinks-postgres")]
+pub(crate) use self::postgres::*;
+#[cfg(feature = "sources-postgres_cdc")]
+pub(crate) use self::postgres_cdc::*;
 #[cfg(feature = "sources-postgresql_metrics")]
 pub(crate) use self::postgresql_metrics::*;
 pub use self::process::*;
 #[cfg(any(feature = "sources-prometheus", feature = "sinks-prometheus"))]
 pub(crate) use self::prometheus::*;
+#[cfg(feature = "codecs-protobuf")]
+pub use self::protobuf::*;
+#[cfg(any(feature = "sources-pulsar", feature = "sinks-pulsar"))]
 pub use self::pulsar::*;
-#[cfg(feature = "sinks-redis")]
+#[cfg(any(feature = "sinks-redis", feature = "sources-redis"))]
 pub use self::redis::*;
 #[cfg(feature = "transforms-reduce")]
 pub(crate) use self::reduce::*;
@@ -249,6 +359,10 @@ pub use self::route::*;
 pub use self::sample::*;
 #[cfg(feature = "sinks-sematext")]
 pub use self::sematext_metrics::*;
+#[cfg(feature = "sinks-smtp")]
+pub(crate) use self::smtp::*;
+#[cfg(feature = "sources-snmp_trap")]
+pub use self::snmp_trap::*;
 pub(crate) use self::socket::*;
 pub use self::split::*;
 #[cfg(any(feature = "sources-splunk_hec", feature = "sinks-splunk_hec"))]
@@ -271,8 +385,12 @@ pub use self::unix::*;
 pub use self::vector::*;
 #[cfg(feature = "wasm")]
 pub use self::wasm::*;
+#[cfg(feature = "sinks-websocket_server")]
+pub(crate) use self::websocket_server::*;
 #[cfg(windows)]
 pub use self::windows::*;
+#[cfg(all(windows, feature = "sources-windows_eventlog"))]
+pub use self::windows_eventlog::*;
 #[cfg(feature = "sources-mongodb_metrics")]
 pub use mongodb_metrics::*;
 
@@ -281,8 +399,8 @@ pub trait InternalEvent {
     fn emit_metrics(&self) {}
 }
 
-pub fn emit(event: impl InternalEvent) {
-    event.emit_logs();
+pub fn emit<E: InternalEvent>(event: E) {
+    crate::trace::with_internal_event::<E>(|| event.emit_logs());
     event.emit_metrics();
 }
 
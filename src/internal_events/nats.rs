@@ -35,7 +35,7 @@ impl InternalEvent for NatsEventSendSuccess {
 
 #[derive(Debug)]
 pub struct NatsEventSendFail {
-    pub error: Error,
+    pub error: crate::Error,
 }
 
 impl InternalEvent for NatsEventSendFail {
@@ -47,3 +47,18 @@ impl InternalEvent for NatsEventSendFail {
         counter!("send_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct NatsAcknowledgementFailed {
+    pub error: Error,
+}
+
+impl InternalEvent for NatsAcknowledgementFailed {
+    fn emit_logs(&self) {
+        error!(message = "Unable to acknowledge message.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("consumer_acknowledgements_failed_total", 1);
+    }
+}
@@ -0,0 +1,42 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct OpentelemetryEventsReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for OpentelemetryEventsReceived {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received events.",
+            count = %self.count,
+            byte_size = %self.byte_size,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct OpentelemetryEventUnsupported {
+    pub kind: &'static str,
+}
+
+impl InternalEvent for OpentelemetryEventUnsupported {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Metric has no OTLP equivalent; dropping event.",
+            kind = self.kind,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", 1);
+    }
+}
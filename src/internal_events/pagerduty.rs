@@ -0,0 +1,35 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct PagerDutyEventsProcessed {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for PagerDutyEventsProcessed {
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct PagerDutyEventInvalid {
+    pub error: &'static str,
+}
+
+impl InternalEvent for PagerDutyEventInvalid {
+    fn emit_logs(&self) {
+        error!(
+            message = "Event can't be sent to PagerDuty, dropping it.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors_total", 1,
+            "error_type" => "invalid_event");
+        counter!("events_discarded_total", 1);
+    }
+}
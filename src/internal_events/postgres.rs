@@ -0,0 +1,29 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub(crate) struct PostgresRowsWritten {
+    pub count: usize,
+    pub byte_size: usize,
+    /// The rows inserted or updated, fewer than the rows copied when
+    /// conflicting rows are skipped.
+    pub written: u64,
+}
+
+impl InternalEvent for PostgresRowsWritten {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Wrote rows to the table.",
+            count = %self.count,
+            written = %self.written,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processed_bytes_total", self.byte_size as u64);
+        let skipped = (self.count as u64).saturating_sub(self.written);
+        if skipped > 0 {
+            counter!("postgres_conflicting_rows_skipped_total", skipped);
+        }
+    }
+}
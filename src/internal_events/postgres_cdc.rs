@@ -0,0 +1,78 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct PostgresCdcEventsReceived {
+    pub count: usize,
+}
+
+impl InternalEvent for PostgresCdcEventsReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received events.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresCdcPollFailed {
+    pub error: String,
+}
+
+impl InternalEvent for PostgresCdcPollFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to read changes from replication slot.",
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresCdcDecodeFailed {
+    pub error: String,
+    pub lsn: Option<String>,
+}
+
+impl InternalEvent for PostgresCdcDecodeFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to decode change, skipping it.",
+            error = %self.error,
+            lsn = ?self.lsn,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("parse_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct PostgresCdcCheckpointFailed {
+    pub error: tokio_postgres::Error,
+    pub lsn: String,
+}
+
+impl InternalEvent for PostgresCdcCheckpointFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to advance replication slot, changes will be read again.",
+            error = %self.error,
+            lsn = %self.lsn,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("checkpoint_write_errors_total", 1);
+    }
+}
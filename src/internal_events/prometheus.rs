@@ -1,5 +1,7 @@
 use super::InternalEvent;
 use hyper::StatusCode;
+#[cfg(feature = "sources-prometheus")]
+use metrics::gauge;
 use metrics::{counter, histogram};
 #[cfg(feature = "sources-prometheus")]
 use prometheus_parser::ParserError;
@@ -165,3 +167,44 @@ impl InternalEvent for PrometheusServerRequestComplete {
         counter!("requests_received_total", 1);
     }
 }
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusTargetsDiscovered {
+    pub mechanism: &'static str,
+    pub count: usize,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl InternalEvent for PrometheusTargetsDiscovered {
+    fn emit_logs(&self) {
+        debug!(message = "Discovered targets.", mechanism = %self.mechanism, count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        gauge!("discovered_targets", self.count as f64, "mechanism" => self.mechanism);
+    }
+}
+
+#[cfg(feature = "sources-prometheus")]
+#[derive(Debug)]
+pub struct PrometheusDiscoveryFailed {
+    pub error: crate::Error,
+    pub mechanism: &'static str,
+}
+
+#[cfg(feature = "sources-prometheus")]
+impl InternalEvent for PrometheusDiscoveryFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to discover targets, keeping the previous ones.",
+            mechanism = %self.mechanism,
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("discovery_errors_total", 1, "mechanism" => self.mechanism);
+    }
+}
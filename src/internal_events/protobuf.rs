@@ -0,0 +1,22 @@
+use super::InternalEvent;
+use crate::protobuf::DecodeError;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct ProtobufDecodingFailed<'a> {
+    pub error: &'a DecodeError,
+}
+
+impl InternalEvent for ProtobufDecodingFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed to decode protobuf message; discarding it.",
+            error = %self.error,
+            internal_log_rate_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("decode_errors_total", 1, "codec" => "protobuf");
+    }
+}
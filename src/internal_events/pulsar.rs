@@ -19,3 +19,49 @@ impl<'a> InternalEvent for PulsarEncodeEventFailed<'a> {
         counter!("encode_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct PulsarEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for PulsarEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct PulsarEventFailed {
+    pub error: pulsar::Error,
+}
+
+impl InternalEvent for PulsarEventFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to read message.", error = ?self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_failed_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct PulsarAcknowledgementFailed {
+    pub error: pulsar::error::ConsumerError,
+}
+
+impl InternalEvent for PulsarAcknowledgementFailed {
+    fn emit_logs(&self) {
+        error!(message = "Unable to acknowledge message.", error = ?self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("consumer_acknowledgements_failed_total", 1);
+    }
+}
@@ -55,3 +55,38 @@ impl InternalEvent for RedisSendEventFailed {
         counter!("send_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct RedisEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for RedisEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", rate_limit_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct RedisAcknowledgementFailed {
+    pub error: redis::RedisError,
+}
+
+impl InternalEvent for RedisAcknowledgementFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to acknowledge stream entries.",
+            error = %self.error,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_send_ack_errors_total", 1);
+    }
+}
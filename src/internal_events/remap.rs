@@ -1,4 +1,5 @@
 use super::InternalEvent;
+use crate::event::DropReason;
 use metrics::counter;
 
 #[derive(Debug)]
@@ -27,6 +28,10 @@ impl InternalEvent for RemapMappingError {
     fn emit_metrics(&self) {
         counter!("processing_errors_total", 1,
                  "error_type" => "failed_mapping");
+        if self.event_dropped {
+            counter!("events_discarded_total", 1,
+                     "reason" => DropReason::MappingFailed.as_str());
+        }
     }
 }
 
@@ -47,4 +52,11 @@ impl InternalEvent for RemapMappingAbort {
 
         debug!(message, internal_log_rate_secs = 30)
     }
+
+    fn emit_metrics(&self) {
+        if self.event_dropped {
+            counter!("events_discarded_total", 1,
+                     "reason" => DropReason::MappingAborted.as_str());
+        }
+    }
 }
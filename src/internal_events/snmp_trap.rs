@@ -0,0 +1,104 @@
+use super::InternalEvent;
+use crate::sources::snmp_trap::DecodeError;
+use metrics::counter;
+use std::net::SocketAddr;
+
+#[derive(Debug)]
+pub struct SnmpTrapEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for SnmpTrapEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapInvalidMessage {
+    pub error: DecodeError,
+    pub peer: SocketAddr,
+}
+
+impl InternalEvent for SnmpTrapInvalidMessage {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Discarding invalid message.",
+            error = %self.error,
+            peer_addr = %self.peer,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("invalid_record_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapResponseFailed {
+    pub error: std::io::Error,
+    pub peer: SocketAddr,
+}
+
+impl InternalEvent for SnmpTrapResponseFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to acknowledge inform.",
+            error = %self.error,
+            peer_addr = %self.peer,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("send_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+enum SnmpTrapSocketErrorType {
+    Bind,
+    Read,
+}
+
+#[derive(Debug)]
+pub struct SnmpTrapSocketError {
+    r#type: SnmpTrapSocketErrorType,
+    pub error: std::io::Error,
+}
+
+impl SnmpTrapSocketError {
+    pub const fn bind(error: std::io::Error) -> Self {
+        Self {
+            r#type: SnmpTrapSocketErrorType::Bind,
+            error,
+        }
+    }
+
+    pub const fn read(error: std::io::Error) -> Self {
+        Self {
+            r#type: SnmpTrapSocketErrorType::Read,
+            error,
+        }
+    }
+}
+
+impl InternalEvent for SnmpTrapSocketError {
+    fn emit_logs(&self) {
+        let message = match self.r#type {
+            SnmpTrapSocketErrorType::Bind => "Failed to bind to UDP listener socket.",
+            SnmpTrapSocketErrorType::Read => "Failed to read UDP datagram.",
+        };
+        error!(message, error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_errors_total", 1);
+    }
+}
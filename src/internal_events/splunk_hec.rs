@@ -2,6 +2,8 @@ use super::InternalEvent;
 use metrics::counter;
 use serde_json::Error;
 
+#[cfg(feature = "sinks-splunk_hec")]
+pub(crate) use self::sink::*;
 #[cfg(feature = "sources-splunk_hec")]
 pub(crate) use self::source::*;
 
@@ -35,6 +37,79 @@ impl InternalEvent for SplunkEventEncodeError {
     }
 }
 
+#[cfg(feature = "sinks-splunk_hec")]
+mod sink {
+    use super::InternalEvent;
+    use metrics::counter;
+
+    #[derive(Debug)]
+    pub(crate) struct SplunkIndexerAcknowledgementUnavailable;
+
+    impl InternalEvent for SplunkIndexerAcknowledgementUnavailable {
+        fn emit_logs(&self) {
+            warn!(
+                message =
+                    "Response has no acknowledgement ID, indexer acknowledgements may be disabled.",
+                internal_log_rate_secs = 30,
+            );
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct SplunkAckQueryFailed<'a> {
+        pub error: &'a crate::Error,
+    }
+
+    impl<'a> InternalEvent for SplunkAckQueryFailed<'a> {
+        fn emit_logs(&self) {
+            warn!(
+                message = "Failed to query indexer acknowledgements.",
+                error = %self.error,
+                internal_log_rate_secs = 10,
+            );
+        }
+
+        fn emit_metrics(&self) {
+            counter!("http_request_errors_total", 1);
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct SplunkAcksExpired {
+        pub count: usize,
+    }
+
+    impl InternalEvent for SplunkAcksExpired {
+        fn emit_logs(&self) {
+            error!(
+                message = "Events were not reported indexed, marking them errored.",
+                count = %self.count,
+                internal_log_rate_secs = 10,
+            );
+        }
+
+        fn emit_metrics(&self) {
+            counter!("splunk_pending_acks_expired_total", self.count as u64);
+        }
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct SplunkChannelRotated<'a> {
+        pub previous: &'a str,
+        pub channel: &'a str,
+    }
+
+    impl<'a> InternalEvent for SplunkChannelRotated<'a> {
+        fn emit_logs(&self) {
+            info!(
+                message = "Sending events on a new channel.",
+                previous = %self.previous,
+                channel = %self.channel,
+            );
+        }
+    }
+}
+
 #[cfg(feature = "sources-splunk_hec")]
 mod source {
     use super::InternalEvent;
@@ -46,3 +46,25 @@ impl InternalEvent for SyslogUdpUtf8Error {
         counter!("utf8_convert_errors_total", 1, "mode" => "udp");
     }
 }
+
+#[derive(Debug)]
+pub struct SyslogInvalidPriority<'a> {
+    pub field: &'static str,
+    pub value: &'a str,
+}
+
+impl<'a> InternalEvent for SyslogInvalidPriority<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Invalid priority; using the default.",
+            field = %self.field,
+            value = %self.value,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "invalid_priority");
+    }
+}
@@ -1,5 +1,6 @@
 use super::InternalEvent;
-use metrics::counter;
+use metrics::{counter, gauge, histogram, Label};
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct EventIn;
@@ -27,3 +28,94 @@ impl InternalEvent for EventOut {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct ChannelSendBlocked<'a> {
+    pub downstream_component_id: &'a str,
+    pub duration: Duration,
+}
+
+impl<'a> InternalEvent for ChannelSendBlocked<'a> {
+    fn emit_metrics(&self) {
+        histogram!(
+            "channel_send_blocked_seconds", self.duration,
+            "downstream_component_id" => self.downstream_component_id.to_owned(),
+        );
+    }
+}
+
+/// The labels of the gauges of the channel into component
+/// `downstream_component_id`, built once for every report of its occupancy.
+/// Channels of their own for a single upstream component are labelled with
+/// its id as well.
+pub fn channel_labels(
+    downstream_component_id: &str,
+    upstream_component_id: Option<&str>,
+) -> Vec<Label> {
+    let mut labels = vec![Label::new(
+        "downstream_component_id",
+        downstream_component_id.to_owned(),
+    )];
+    if let Some(upstream_component_id) = upstream_component_id {
+        labels.push(Label::new(
+            "upstream_component_id",
+            upstream_component_id.to_owned(),
+        ));
+    }
+    labels
+}
+
+#[derive(Debug)]
+pub struct ChannelEventsQueued<'a> {
+    /// See [`channel_labels`].
+    pub labels: &'a [Label],
+    pub count: usize,
+    pub capacity: Option<usize>,
+}
+
+impl<'a> InternalEvent for ChannelEventsQueued<'a> {
+    fn emit_metrics(&self) {
+        gauge!("channel_queued_events", self.count as f64, self.labels);
+        if let Some(capacity) = self.capacity {
+            gauge!("channel_capacity_events", capacity as f64, self.labels);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeadLetterEventsSent {
+    pub count: usize,
+}
+
+impl InternalEvent for DeadLetterEventsSent {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Sending rejected events to the dead letter output.",
+            count = %self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("dead_letter_events_total", self.count as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct DeadLetterEventsDropped {
+    pub count: usize,
+}
+
+impl InternalEvent for DeadLetterEventsDropped {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Too many events pending, rejected events won't be sent to the dead letter output.",
+            count = %self.count,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("dead_letter_events_dropped_total", self.count as u64);
+    }
+}
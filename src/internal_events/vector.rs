@@ -1,6 +1,6 @@
 use super::InternalEvent;
 use metrics::counter;
-use prost::DecodeError;
+use vector_core::event::proto::{DecodeError, SCHEMA_VERSION};
 
 #[derive(Debug)]
 pub struct VectorEventReceived {
@@ -32,3 +32,44 @@ impl InternalEvent for VectorProtoDecodeError {
         counter!("protobuf_decode_errors_total", 1);
     }
 }
+
+#[derive(Debug)]
+pub struct VectorEventUnsupported {
+    pub version: u32,
+}
+
+impl InternalEvent for VectorEventUnsupported {
+    fn emit_logs(&self) {
+        warn!(
+            message =
+                "Event uses a newer schema than this version of Vector supports; dropping event.",
+            version = self.version,
+            supported_version = SCHEMA_VERSION,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorEventUnrepresentable {
+    pub version: u32,
+}
+
+impl InternalEvent for VectorEventUnrepresentable {
+    fn emit_logs(&self) {
+        warn!(
+            message =
+                "Event has no equivalent in the schema the Vector source supports; dropping event.",
+            peer_version = self.version,
+            internal_log_rate_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", 1);
+    }
+}
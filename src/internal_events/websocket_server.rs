@@ -0,0 +1,53 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct WebSocketServerClientConnected;
+
+impl InternalEvent for WebSocketServerClientConnected {
+    fn emit_logs(&self) {
+        debug!(message = "Client connected.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_established_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketServerClientDisconnected;
+
+impl InternalEvent for WebSocketServerClientDisconnected {
+    fn emit_logs(&self) {
+        debug!(message = "Client disconnected.");
+    }
+
+    fn emit_metrics(&self) {
+        counter!("connection_shutdown_total", 1, "mode" => "websocket");
+    }
+}
+
+#[derive(Debug)]
+pub struct WebSocketServerMessageDropped {
+    pub disconnect: bool,
+}
+
+impl InternalEvent for WebSocketServerMessageDropped {
+    fn emit_logs(&self) {
+        if self.disconnect {
+            warn!(
+                message = "Client buffer is full; disconnecting the client.",
+                internal_log_rate_secs = 10,
+            );
+        } else {
+            warn!(
+                message = "Client buffer is full; dropping messages for the client.",
+                internal_log_rate_secs = 10,
+            );
+        }
+    }
+
+    fn emit_metrics(&self) {
+        counter!("client_messages_dropped_total", 1);
+    }
+}
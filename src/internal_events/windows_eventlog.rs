@@ -0,0 +1,67 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct WindowsEventLogEventReceived {
+    pub byte_size: usize,
+}
+
+impl InternalEvent for WindowsEventLogEventReceived {
+    fn emit_logs(&self) {
+        trace!(message = "Received one event.", internal_log_rate_secs = 10);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_in_total", 1);
+        counter!("processed_bytes_total", self.byte_size as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowsEventLogRenderFailed {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for WindowsEventLogRenderFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to render event, skipping.",
+            error = %self.error,
+            internal_log_rate_secs = 10
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("render_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowsEventLogReadFailed {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for WindowsEventLogReadFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to read events, stopping.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("read_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowsEventLogBookmarkFailed {
+    pub error: std::io::Error,
+}
+
+impl InternalEvent for WindowsEventLogBookmarkFailed {
+    fn emit_logs(&self) {
+        error!(message = "Failed to save bookmark.", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("checkpoint_write_errors_total", 1);
+    }
+}
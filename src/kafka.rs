@@ -1,14 +1,34 @@
 use crate::internal_events::KafkaStatisticsReceived;
+use crate::rusoto::{AwsAuthentication, AwsCredentialsProvider};
 use crate::tls::TlsOptions;
-use rdkafka::{consumer::ConsumerContext, ClientConfig, ClientContext, Statistics};
+use chrono::Utc;
+use rdkafka::{
+    client::OAuthToken, consumer::ConsumerContext, ClientConfig, ClientContext, Statistics,
+};
+use rusoto_core::Region;
+use rusoto_credential::{AwsCredentials, ProvideAwsCredentials};
+use rusoto_signature::SignedRequest;
 use serde::{Deserialize, Serialize};
-use snafu::Snafu;
-use std::path::{Path, PathBuf};
+use snafu::{ResultExt, Snafu};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::runtime::Handle;
 
 #[derive(Debug, Snafu)]
 enum KafkaError {
     #[snafu(display("invalid path: {:?}", path))]
     InvalidPath { path: PathBuf },
+    #[snafu(display("invalid AWS region {:?}: {}", region, source))]
+    InvalidRegion {
+        region: String,
+        source: rusoto_core::region::ParseRegionError,
+    },
+    #[snafu(display("AWS MSK IAM authentication requires a Tokio runtime"))]
+    NoRuntime,
 }
 
 #[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize)]
@@ -35,6 +55,15 @@ pub(crate) struct KafkaSaslConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub mechanism: Option<String>,
+    /// Authenticate to AWS MSK with IAM, through generated OAUTHBEARER tokens.
+    pub aws_msk_iam: Option<KafkaMskIamConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct KafkaMskIamConfig {
+    pub region: String,
+    #[serde(default)]
+    pub auth: AwsAuthentication,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -68,6 +97,10 @@ impl KafkaAuthConfig {
             if let Some(mechanism) = &sasl.mechanism {
                 client.set("sasl.mechanism", mechanism);
             }
+            if sasl.aws_msk_iam.is_some() {
+                // The tokens themselves come from `KafkaStatisticsContext`.
+                client.set("sasl.mechanism", "OAUTHBEARER");
+            }
         }
 
         if tls_enabled {
@@ -95,14 +128,122 @@ fn pathbuf_to_string(path: &Path) -> crate::Result<&str> {
         .ok_or_else(|| KafkaError::InvalidPath { path: path.into() }.into())
 }
 
-pub(crate) struct KafkaStatisticsContext;
+/// The context of Kafka clients, which reports their statistics and hands
+/// librdkafka the OAUTHBEARER tokens of AWS MSK IAM authentication.
+pub(crate) struct KafkaStatisticsContext {
+    msk_iam: Option<MskIamTokenProvider>,
+}
+
+impl KafkaStatisticsContext {
+    /// Must be called from within the Tokio runtime if `auth` configures AWS
+    /// MSK IAM authentication, as credentials are fetched on it.
+    pub(crate) fn new(auth: &KafkaAuthConfig) -> crate::Result<Self> {
+        let msk_iam = match auth
+            .sasl
+            .as_ref()
+            .and_then(|sasl| sasl.aws_msk_iam.as_ref())
+        {
+            Some(config) => Some(MskIamTokenProvider::new(config)?),
+            None => None,
+        };
+        Ok(Self { msk_iam })
+    }
+}
 
 impl ClientContext for KafkaStatisticsContext {
+    // librdkafka only asks for tokens when the mechanism is OAUTHBEARER.
+    const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
+
     fn stats(&self, statistics: Statistics) {
         emit!(KafkaStatisticsReceived {
             statistics: &statistics
         });
     }
+
+    fn generate_oauth_token(
+        &self,
+        _oauthbearer_config: Option<&str>,
+    ) -> Result<OAuthToken, Box<dyn Error>> {
+        match &self.msk_iam {
+            Some(provider) => provider
+                .token()
+                .map_err(|error| -> Box<dyn Error> { error }),
+            None => Err("OAUTHBEARER tokens are only generated for `sasl.aws_msk_iam`".into()),
+        }
+    }
 }
 
 impl ConsumerContext for KafkaStatisticsContext {}
+
+/// How long MSK accepts a token for, after which librdkafka asks for a new one.
+const MSK_IAM_TOKEN_LIFETIME: Duration = Duration::from_secs(900);
+
+/// Generates the tokens of AWS MSK IAM authentication, which are presigned
+/// requests for the `kafka-cluster:Connect` action, base64 encoded.
+///
+/// See <https://github.com/aws/aws-msk-iam-auth>
+struct MskIamTokenProvider {
+    region: Region,
+    credentials: Arc<AwsCredentialsProvider>,
+    runtime: Handle,
+}
+
+impl MskIamTokenProvider {
+    fn new(config: &KafkaMskIamConfig) -> crate::Result<Self> {
+        let region = config.region.parse::<Region>().context(InvalidRegion {
+            region: config.region.clone(),
+        })?;
+        let credentials = config.auth.build(&region, None)?;
+        let runtime = Handle::try_current().map_err(|_| KafkaError::NoRuntime)?;
+        Ok(Self {
+            region,
+            credentials: Arc::new(credentials),
+            runtime,
+        })
+    }
+
+    fn token(&self) -> crate::Result<OAuthToken> {
+        // librdkafka asks for tokens while being polled, possibly on a thread
+        // of the runtime, which can't block on the credentials itself.
+        let credentials = Arc::clone(&self.credentials);
+        let runtime = self.runtime.clone();
+        let credentials = std::thread::spawn(move || runtime.block_on(credentials.credentials()))
+            .join()
+            .map_err(|_| "Fetching AWS credentials panicked")??;
+
+        let expires = Utc::now() + chrono::Duration::from_std(MSK_IAM_TOKEN_LIFETIME)?;
+        Ok(OAuthToken {
+            token: msk_iam_token(&self.region, &credentials),
+            principal_name: credentials.aws_access_key_id().to_owned(),
+            lifetime_ms: expires.timestamp_millis(),
+        })
+    }
+}
+
+fn msk_iam_token(region: &Region, credentials: &AwsCredentials) -> String {
+    let mut request = SignedRequest::new("GET", "kafka-cluster", region, "/");
+    request.set_hostname(Some(format!("kafka.{}.amazonaws.com", region.name())));
+    request.add_param("Action", "kafka-cluster:Connect");
+    let url = request.generate_presigned_url(credentials, &MSK_IAM_TOKEN_LIFETIME, false);
+    base64::encode_config(url, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn msk_iam_token_is_presigned_url() {
+        let credentials = AwsCredentials::new("AKIDEXAMPLE", "secret", None, None);
+
+        let token = msk_iam_token(&Region::UsEast1, &credentials);
+
+        let url = String::from_utf8(base64::decode_config(token, base64::URL_SAFE_NO_PAD).unwrap())
+            .unwrap();
+        assert!(url.starts_with("https://kafka.us-east-1.amazonaws.com/?"));
+        assert!(url.contains("Action=kafka-cluster%3AConnect"));
+        assert!(url.contains("X-Amz-Credential=AKIDEXAMPLE%2F"));
+        assert!(url.contains("X-Amz-Expires=900"));
+        assert!(url.contains("X-Amz-Signature="));
+    }
+}
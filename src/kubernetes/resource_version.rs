@@ -54,6 +54,12 @@ impl Candidate {
         Self::from_object(object)
     }
 
+    /// Obtain a resource version [`Candidate`] from a value persisted earlier,
+    /// to resume watching from.
+    pub fn from_persisted(resource_version: String) -> Self {
+        Self(resource_version)
+    }
+
     /// Obtain a resource version [`Candidate`] from a object of type `T`.
     pub fn from_object<T>(object: &T) -> Option<Self>
     where
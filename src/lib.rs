@@ -35,8 +35,17 @@ pub mod internal_events;
 pub mod api;
 pub mod app;
 pub mod async_read;
+#[cfg(feature = "codecs-avro")]
+pub mod avro;
+#[cfg(any(
+    feature = "sources-azure_event_hubs",
+    feature = "sinks-azure_event_hubs"
+))]
+pub mod azure_event_hubs;
 pub mod buffers;
 pub mod encoding_transcode;
+#[cfg(any(feature = "sources-gcp_pubsub", feature = "sinks-gcp"))]
+pub mod gcp;
 pub mod graph;
 pub mod heartbeat;
 pub mod http;
@@ -45,9 +54,17 @@ pub mod kafka;
 pub mod kubernetes;
 pub mod line_agg;
 pub mod list;
+#[cfg(any(feature = "sources-mqtt", feature = "sinks-mqtt"))]
+pub mod mqtt;
+#[cfg(any(feature = "sources-nats", feature = "sinks-nats"))]
+pub mod nats;
 pub(crate) mod pipeline;
 pub(crate) mod proto;
+#[cfg(feature = "codecs-protobuf")]
+pub mod protobuf;
 pub mod providers;
+#[cfg(any(feature = "sources-pulsar", feature = "sinks-pulsar"))]
+pub mod pulsar;
 #[cfg(feature = "rusoto_core")]
 pub mod rusoto;
 pub mod serde;
@@ -0,0 +1,217 @@
+//! JetStream support on top of the core NATS client.
+//!
+//! The async client in use has no JetStream API of its own, so this speaks
+//! the JetStream protocol over core NATS: publishes are acknowledged by the
+//! server replying to their reply subject, and consumers are managed through
+//! requests to the `$JS.API` subjects.
+
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+
+/// Connect to the NATS server at `url`.
+pub async fn connect(url: &str, connection_name: &str) -> crate::Result<async_nats::Connection> {
+    // Set reconnect_buffer_size on the nats client to 0 bytes so that the
+    // client doesn't buffer internally (to avoid message loss).
+    let connection = async_nats::Options::new()
+        .with_name(connection_name)
+        .reconnect_buffer_size(0)
+        .connect(url)
+        .await?;
+    Ok(connection)
+}
+
+/// The messages of a subscription, until it is closed.
+pub fn subscription_stream(
+    subscription: async_nats::Subscription,
+) -> impl Stream<Item = async_nats::Message> {
+    stream::unfold(subscription, |subscription| async move {
+        subscription.next().await.map(|msg| (msg, subscription))
+    })
+}
+
+#[derive(Debug, Snafu)]
+pub enum JetStreamError {
+    #[snafu(display("JetStream request failed: {}", source))]
+    Request { source: std::io::Error },
+    #[snafu(display("Invalid JetStream response: {}", source))]
+    InvalidResponse { source: serde_json::Error },
+    #[snafu(display("JetStream error {}: {}", code, description))]
+    Api { code: u16, description: String },
+    #[snafu(display("No JetStream stream holds subject {:?}", subject))]
+    NoStream { subject: String },
+    #[snafu(display("JetStream consumer {:?} is not a push consumer", durable_name))]
+    NotPushConsumer { durable_name: String },
+    #[snafu(display("Timed out waiting for JetStream to acknowledge the message"))]
+    AckTimeout,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    code: u16,
+    #[serde(default)]
+    description: String,
+}
+
+/// The response to a JetStream API request or to a publish, which is either
+/// the expected value or an error.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Response<T> {
+    Err { error: ApiError },
+    Ok(T),
+}
+
+fn parse_response<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, JetStreamError> {
+    match serde_json::from_slice(data)
+        .map_err(|source| JetStreamError::InvalidResponse { source })?
+    {
+        Response::Ok(value) => Ok(value),
+        Response::Err { error } => Err(JetStreamError::Api {
+            code: error.code,
+            description: error.description,
+        }),
+    }
+}
+
+async fn request<T: serde::de::DeserializeOwned>(
+    connection: &async_nats::Connection,
+    subject: &str,
+    body: &impl Serialize,
+) -> Result<T, JetStreamError> {
+    let body = serde_json::to_vec(body).expect("JetStream requests serialize");
+    let response = connection
+        .request(subject, body)
+        .await
+        .map_err(|source| JetStreamError::Request { source })?;
+    parse_response(&response.data)
+}
+
+#[derive(Debug, Deserialize)]
+struct PublishAck {
+    #[allow(dead_code)]
+    stream: String,
+}
+
+/// Check the server's reply to a message published to a stream, which
+/// acknowledges that the stream has stored it.
+pub fn parse_publish_ack(data: &[u8]) -> Result<(), JetStreamError> {
+    parse_response::<PublishAck>(data).map(|_| ())
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamNames {
+    #[serde(default)]
+    streams: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ConsumerConfig {
+    durable_name: String,
+    #[serde(default)]
+    deliver_subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deliver_group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filter_subject: Option<String>,
+    ack_policy: String,
+    deliver_policy: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConsumerInfo {
+    config: ConsumerConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateConsumer<'a> {
+    stream_name: &'a str,
+    config: &'a ConsumerConfig,
+}
+
+/// Look up the durable push consumer of the stream holding `subject`,
+/// creating it if it doesn't exist yet, and return the subject it delivers
+/// messages to. Messages have to be acknowledged explicitly.
+pub async fn push_consumer(
+    connection: &async_nats::Connection,
+    subject: &str,
+    durable_name: &str,
+    queue: Option<&str>,
+) -> Result<String, JetStreamError> {
+    let names: StreamNames = request(
+        connection,
+        "$JS.API.STREAM.NAMES",
+        &serde_json::json!({ "subject": subject }),
+    )
+    .await?;
+    let stream = names
+        .streams
+        .and_then(|streams| streams.into_iter().next())
+        .ok_or_else(|| JetStreamError::NoStream {
+            subject: subject.to_owned(),
+        })?;
+
+    let info = format!("$JS.API.CONSUMER.INFO.{}.{}", stream, durable_name);
+    match request::<ConsumerInfo>(connection, &info, &serde_json::json!({})).await {
+        Ok(ConsumerInfo {
+            config:
+                ConsumerConfig {
+                    deliver_subject: Some(deliver_subject),
+                    ..
+                },
+        }) => return Ok(deliver_subject),
+        // The consumer doesn't exist yet.
+        Err(JetStreamError::Api { code: 404, .. }) => (),
+        Ok(_) => {
+            return Err(JetStreamError::NotPushConsumer {
+                durable_name: durable_name.to_owned(),
+            })
+        }
+        Err(error) => return Err(error),
+    }
+
+    let config = ConsumerConfig {
+        durable_name: durable_name.to_owned(),
+        deliver_subject: Some(connection.new_inbox()),
+        deliver_group: queue.map(Into::into),
+        filter_subject: Some(subject.to_owned()),
+        ack_policy: "explicit".to_owned(),
+        deliver_policy: "all".to_owned(),
+    };
+    let create = format!(
+        "$JS.API.CONSUMER.DURABLE.CREATE.{}.{}",
+        stream, durable_name
+    );
+    let info: ConsumerInfo = request(
+        connection,
+        &create,
+        &CreateConsumer {
+            stream_name: &stream,
+            config: &config,
+        },
+    )
+    .await?;
+    info.config
+        .deliver_subject
+        .ok_or_else(|| JetStreamError::NotPushConsumer {
+            durable_name: durable_name.to_owned(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_publish_acks() {
+        assert!(parse_publish_ack(br#"{"stream":"events","seq":2}"#).is_ok());
+        assert!(matches!(
+            parse_publish_ack(br#"{"error":{"code":503,"description":"no responders"}}"#),
+            Err(JetStreamError::Api { code: 503, .. })
+        ));
+        assert!(matches!(
+            parse_publish_ack(b""),
+            Err(JetStreamError::InvalidResponse { .. })
+        ));
+    }
+}
@@ -3,3 +3,15 @@ use crate::event::proto as event;
 
 #[cfg(any(feature = "sources-vector", feature = "sinks-vector"))]
 pub(crate) mod vector;
+
+#[cfg(feature = "sinks-datadog_traces")]
+pub(crate) mod datadog;
+
+#[cfg(any(feature = "sources-gcp_pubsub", feature = "sinks-gcp_bigquery"))]
+pub(crate) mod google;
+
+#[cfg(feature = "sinks-loki")]
+pub(crate) mod loki;
+
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+pub(crate) mod opentelemetry;
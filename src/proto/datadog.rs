@@ -0,0 +1,9 @@
+//! The messages of the Datadog trace intake.
+
+pub mod trace {
+    include!(concat!(env!("OUT_DIR"), "/datadog.trace.rs"));
+}
+
+pub mod sketches {
+    include!(concat!(env!("OUT_DIR"), "/datadog.sketches.rs"));
+}
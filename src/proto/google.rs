@@ -0,0 +1,27 @@
+//! The parts of the Google Cloud APIs used over gRPC, with modules laid out
+//! like the protobuf packages.
+
+#![allow(clippy::clone_on_ref_ptr)]
+
+#[cfg(feature = "sinks-gcp_bigquery")]
+pub mod cloud {
+    pub mod bigquery {
+        pub mod storage {
+            pub mod v1 {
+                tonic::include_proto!("google.cloud.bigquery.storage.v1");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sources-gcp_pubsub")]
+pub mod pubsub {
+    pub mod v1 {
+        tonic::include_proto!("google.pubsub.v1");
+    }
+}
+
+#[cfg(feature = "sinks-gcp_bigquery")]
+pub mod rpc {
+    tonic::include_proto!("google.rpc");
+}
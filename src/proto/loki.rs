@@ -0,0 +1,5 @@
+//! The messages of the Loki push API.
+
+pub mod logproto {
+    include!(concat!(env!("OUT_DIR"), "/logproto.rs"));
+}
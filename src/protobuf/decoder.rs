@@ -0,0 +1,357 @@
+//! Deserializes messages of the types described by a file descriptor set as
+//! events. Fields of messages set the fields of events with the same name,
+//! nested messages set objects, and repeated fields set arrays.
+
+use super::message::{find_field, read_descriptor_set, DescriptorError, Types, TIMESTAMP};
+use crate::event::{LogEvent, Value};
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use prost::encoding::{self, DecodeContext, WireType};
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, FieldDescriptorProto,
+};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashSet},
+    convert::TryFrom,
+    path::Path,
+};
+
+/// Why a message couldn't be decoded.
+#[derive(Debug, Snafu)]
+pub enum DecodeError {
+    #[snafu(display("invalid message: {}", source))]
+    Wire { source: prost::DecodeError },
+    #[snafu(display("field {:?} is truncated", field))]
+    Truncated { field: String },
+    #[snafu(display("field {:?} is a group, which isn't supported", field))]
+    Group { field: String },
+}
+
+/// Decodes messages of a type as events.
+#[derive(Debug)]
+pub struct MessageDecoder {
+    types: Types,
+    message: String,
+    /// Fields of the message mapped to the fields of events they set.
+    fields: BTreeMap<String, String>,
+}
+
+impl MessageDecoder {
+    pub(crate) fn for_message(
+        descriptor_set_file: &Path,
+        message_type: &str,
+        fields: BTreeMap<String, String>,
+    ) -> Result<Self, DescriptorError> {
+        let types = Types::new(&read_descriptor_set(descriptor_set_file)?);
+        Self::with_types(types, message_type, fields)
+    }
+
+    fn with_types(
+        types: Types,
+        message_type: &str,
+        fields: BTreeMap<String, String>,
+    ) -> Result<Self, DescriptorError> {
+        let message = format!(".{}", message_type.trim_start_matches('.'));
+        types.check(&message, &mut HashSet::new())?;
+        let descriptor = types.message(&message)?;
+        for field in fields.keys() {
+            find_field(descriptor, &message, field)?;
+        }
+
+        Ok(Self {
+            types,
+            message,
+            fields,
+        })
+    }
+
+    /// Decodes a message as an event. Fields the message doesn't set are
+    /// left out, rather than set to their defaults, and fields unknown to
+    /// the descriptor set are skipped.
+    pub fn decode(&self, mut data: &[u8]) -> Result<LogEvent, DecodeError> {
+        let mut log = LogEvent::default();
+        for (name, value) in self.decode_message(&self.message, &mut data)? {
+            match self.fields.get(&name) {
+                Some(target) => {
+                    log.insert(target.as_str(), value);
+                }
+                None => log.insert_flat(name, value),
+            }
+        }
+        Ok(log)
+    }
+
+    fn decode_message(
+        &self,
+        name: &str,
+        buf: &mut &[u8],
+    ) -> Result<BTreeMap<String, Value>, DecodeError> {
+        // Types were all checked when the descriptor set was read.
+        let descriptor = &self.types.messages[name];
+        let mut values = BTreeMap::new();
+        while !buf.is_empty() {
+            let (tag, wire_type) = encoding::decode_key(buf).context(Wire)?;
+            let field = match descriptor
+                .field
+                .iter()
+                .find(|field| field.number() as u32 == tag)
+            {
+                Some(field) => field,
+                None => {
+                    encoding::skip_field(wire_type, tag, buf, DecodeContext::default())
+                        .context(Wire)?;
+                    continue;
+                }
+            };
+
+            if field.label() != Label::Repeated {
+                let value = self.decode_value(field, wire_type, buf)?;
+                values.insert(field.name().to_owned(), value);
+            } else if let Some(entry) = self.map_entry(field) {
+                let (key, value) = self.decode_map_entry(field, entry, wire_type, buf)?;
+                if let Value::Map(map) = values
+                    .entry(field.name().to_owned())
+                    .or_insert_with(|| Value::Map(BTreeMap::new()))
+                {
+                    map.insert(key, value);
+                }
+            } else {
+                let mut decoded = self.decode_repeated(field, wire_type, buf)?;
+                if let Value::Array(array) = values
+                    .entry(field.name().to_owned())
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                {
+                    array.append(&mut decoded);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// The entry type of map fields, which are repeated messages of keys and
+    /// values.
+    fn map_entry(&self, field: &FieldDescriptorProto) -> Option<&DescriptorProto> {
+        (field.r#type() == Type::Message)
+            .then(|| self.types.messages.get(field.type_name()))
+            .flatten()
+            .filter(|entry| {
+                entry.field.len() == 2
+                    && entry.options.as_ref().and_then(|o| o.map_entry) == Some(true)
+            })
+    }
+
+    fn decode_map_entry(
+        &self,
+        field: &FieldDescriptorProto,
+        entry: &DescriptorProto,
+        wire_type: WireType,
+        buf: &mut &[u8],
+    ) -> Result<(String, Value), DecodeError> {
+        let mut message = length_delimited(field, wire_type, buf)?;
+        let mut values = self.decode_message(field.type_name(), &mut message)?;
+        let key = values
+            .remove(entry.field[0].name())
+            .map(|key| key.to_string_lossy())
+            .unwrap_or_default();
+        let value = values.remove(entry.field[1].name()).unwrap_or(Value::Null);
+        Ok((key, value))
+    }
+
+    /// Decodes the values of a repeated field, which scalars may write packed
+    /// together.
+    fn decode_repeated(
+        &self,
+        field: &FieldDescriptorProto,
+        wire_type: WireType,
+        buf: &mut &[u8],
+    ) -> Result<Vec<Value>, DecodeError> {
+        let packed_wire_type = match field.r#type() {
+            Type::Double | Type::Fixed64 | Type::Sfixed64 => Some(WireType::SixtyFourBit),
+            Type::Float | Type::Fixed32 | Type::Sfixed32 => Some(WireType::ThirtyTwoBit),
+            Type::String | Type::Bytes | Type::Message | Type::Group => None,
+            _ => Some(WireType::Varint),
+        };
+        match packed_wire_type {
+            Some(packed_wire_type) if wire_type == WireType::LengthDelimited => {
+                let mut packed = length_delimited(field, wire_type, buf)?;
+                let mut values = Vec::new();
+                while !packed.is_empty() {
+                    values.push(self.decode_value(field, packed_wire_type, &mut packed)?);
+                }
+                Ok(values)
+            }
+            _ => Ok(vec![self.decode_value(field, wire_type, buf)?]),
+        }
+    }
+
+    fn decode_value(
+        &self,
+        field: &FieldDescriptorProto,
+        wire_type: WireType,
+        buf: &mut &[u8],
+    ) -> Result<Value, DecodeError> {
+        macro_rules! merge {
+            ($type:ident, $initial:expr) => {{
+                let mut value = $initial;
+                encoding::$type::merge(wire_type, &mut value, buf, DecodeContext::default())
+                    .context(Wire)?;
+                value
+            }};
+        }
+
+        Ok(match field.r#type() {
+            Type::Double => Value::Float(merge!(double, 0f64)),
+            Type::Float => Value::Float(merge!(float, 0f32).into()),
+            Type::Int64 => Value::Integer(merge!(int64, 0i64)),
+            Type::Sint64 => Value::Integer(merge!(sint64, 0i64)),
+            Type::Sfixed64 => Value::Integer(merge!(sfixed64, 0i64)),
+            Type::Uint64 => from_u64(merge!(uint64, 0u64)),
+            Type::Fixed64 => from_u64(merge!(fixed64, 0u64)),
+            Type::Int32 => Value::Integer(merge!(int32, 0i32).into()),
+            Type::Sint32 => Value::Integer(merge!(sint32, 0i32).into()),
+            Type::Sfixed32 => Value::Integer(merge!(sfixed32, 0i32).into()),
+            Type::Uint32 => Value::Integer(merge!(uint32, 0u32).into()),
+            Type::Fixed32 => Value::Integer(merge!(fixed32, 0u32).into()),
+            Type::Bool => Value::Boolean(merge!(bool, false)),
+            Type::String => Value::from(merge!(string, String::new())),
+            Type::Bytes => Value::Bytes(Bytes::from(merge!(bytes, Vec::new()))),
+            // Enums are the names of their values, or their numbers when the
+            // value isn't known.
+            Type::Enum => {
+                let number = merge!(int32, 0i32);
+                self.types
+                    .enums
+                    .get(field.type_name())
+                    .and_then(|descriptor| {
+                        descriptor
+                            .value
+                            .iter()
+                            .find(|value| value.number() == number)
+                    })
+                    .map(|value| Value::from(value.name()))
+                    .unwrap_or_else(|| Value::Integer(number.into()))
+            }
+            Type::Message if field.type_name() == TIMESTAMP => {
+                let message = length_delimited(field, wire_type, buf)?;
+                decode_timestamp(message)?
+            }
+            Type::Message => {
+                let mut message = length_delimited(field, wire_type, buf)?;
+                Value::Map(self.decode_message(field.type_name(), &mut message)?)
+            }
+            Type::Group => {
+                return Group {
+                    field: field.name(),
+                }
+                .fail()
+            }
+        })
+    }
+}
+
+/// Decodes a `google.protobuf.Timestamp`, which descriptor sets don't need
+/// to describe.
+fn decode_timestamp(mut buf: &[u8]) -> Result<Value, DecodeError> {
+    let (mut seconds, mut nanos) = (0i64, 0i32);
+    while !buf.is_empty() {
+        let (tag, wire_type) = encoding::decode_key(&mut buf).context(Wire)?;
+        let ctx = DecodeContext::default();
+        match tag {
+            1 => encoding::int64::merge(wire_type, &mut seconds, &mut buf, ctx),
+            2 => encoding::int32::merge(wire_type, &mut nanos, &mut buf, ctx),
+            _ => encoding::skip_field(wire_type, tag, &mut buf, ctx),
+        }
+        .context(Wire)?;
+    }
+    Ok(
+        NaiveDateTime::from_timestamp_opt(seconds, u32::try_from(nanos).unwrap_or(0))
+            .map(|timestamp| Value::Timestamp(DateTime::from_utc(timestamp, Utc)))
+            .unwrap_or(Value::Null),
+    )
+}
+
+/// Integers too large for events are floats.
+fn from_u64(value: u64) -> Value {
+    i64::try_from(value)
+        .map(Value::Integer)
+        .unwrap_or(Value::Float(value as f64))
+}
+
+/// Splits the length delimited value of the field off the buffer.
+fn length_delimited<'a>(
+    field: &FieldDescriptorProto,
+    wire_type: WireType,
+    buf: &mut &'a [u8],
+) -> Result<&'a [u8], DecodeError> {
+    encoding::check_wire_type(WireType::LengthDelimited, wire_type).context(Wire)?;
+    let length = encoding::decode_varint(buf).context(Wire)?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= buf.len())
+        .ok_or_else(|| DecodeError::Truncated {
+            field: field.name().to_owned(),
+        })?;
+    let (value, rest) = buf.split_at(length);
+    *buf = rest;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protobuf::message::{tests::descriptor_set, MessageEncoder};
+    use chrono::TimeZone;
+
+    #[test]
+    fn decodes_encoded_events() {
+        let fields: BTreeMap<_, _> = vec![("status".to_owned(), "response.code".to_owned())]
+            .into_iter()
+            .collect();
+        let (encoder, _) =
+            MessageEncoder::from_message_type(&descriptor_set(), "logs.Record", fields.clone())
+                .unwrap();
+        let decoder =
+            MessageDecoder::with_types(Types::new(&descriptor_set()), "logs.Record", fields)
+                .unwrap();
+
+        let mut log = LogEvent::default();
+        log.insert("message", "hi");
+        log.insert("response.code", 404);
+        log.insert("tags", vec!["a", "b"]);
+        log.insert("labels.app", "web");
+        log.insert("timestamp", Utc.timestamp(1, 5));
+        log.insert("level", "ERROR");
+
+        let decoded = decoder.decode(&encoder.encode(&log).unwrap()).unwrap();
+        assert_eq!(decoded.as_map(), log.as_map());
+    }
+
+    #[test]
+    fn skips_unknown_fields() {
+        let decoder = MessageDecoder::with_types(
+            Types::new(&descriptor_set()),
+            "logs.Record",
+            BTreeMap::new(),
+        )
+        .unwrap();
+        let message: &[u8] = &[
+            10, 2, b'h', b'i', // message
+            // An unknown field, 15.
+            120, 1, //
+            48, 7, // level, with an unknown value
+        ];
+
+        let log = decoder.decode(message).unwrap();
+        assert_eq!(log["message"], Value::from("hi"));
+        assert_eq!(log["level"], Value::Integer(7));
+        assert!(log.get("tags").is_none());
+
+        assert!(decoder.decode(&[10, 5, b'h']).is_err());
+        assert!(matches!(
+            decoder.decode(&[34, 10, 10]),
+            Err(DecodeError::Truncated { .. })
+        ));
+    }
+}
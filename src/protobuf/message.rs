@@ -0,0 +1,869 @@
+//! Serializes events as messages of the types described by a file descriptor
+//! set, which is how the `grpc` sink knows the request message of any method
+//! and how the `protobuf` codec encodes events of any message type.
+//! The fields of messages are read from the fields of events with the same
+//! name, nested messages from objects and repeated fields from arrays.
+
+use crate::event::{LogEvent, Value};
+use bytes::{BufMut, Bytes};
+use chrono::{DateTime, Utc};
+use http::uri::PathAndQuery;
+use prost::{
+    encoding::{self, WireType},
+    Message,
+};
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+    FileDescriptorSet,
+};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
+
+pub(super) const TIMESTAMP: &str = ".google.protobuf.Timestamp";
+
+#[derive(Debug, Snafu)]
+pub(crate) enum DescriptorError {
+    #[snafu(display("Couldn't read the descriptor set {:?}: {}", path, source))]
+    ReadDescriptorSet {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Couldn't decode the descriptor set: {}", source))]
+    DecodeDescriptorSet { source: prost::DecodeError },
+    #[snafu(display("method {:?} must be written as `package.Service/Method`", method))]
+    InvalidMethod { method: String },
+    #[snafu(display("method {:?} isn't in the descriptor set", method))]
+    MethodNotFound { method: String },
+    #[snafu(display("server streaming method {:?} isn't supported", method))]
+    ServerStreaming { method: String },
+    #[snafu(display(
+        "type {:?} isn't in the descriptor set; was it built with `--include_imports`?",
+        name
+    ))]
+    TypeNotFound { name: String },
+    #[snafu(display("{:?} isn't a field of {:?}", field, message))]
+    UnknownField { field: String, message: String },
+    #[snafu(display("batch field {:?} must be a repeated message field", field))]
+    InvalidBatchField { field: String },
+    #[snafu(display("`batch_field` is only supported with unary methods"))]
+    StreamingBatchField,
+}
+
+/// Why an event doesn't fit the message.
+#[derive(Debug, Snafu, PartialEq)]
+pub(crate) enum EncodeError {
+    #[snafu(display("field {:?} isn't a valid {}", field, field_type))]
+    InvalidValue {
+        field: String,
+        field_type: &'static str,
+    },
+    #[snafu(display("message field {:?} isn't an object", field))]
+    NotAnObject { field: String },
+    #[snafu(display("repeated field {:?} isn't an array", field))]
+    NotAnArray { field: String },
+}
+
+/// The method called by the `grpc` sink.
+#[derive(Clone, Debug)]
+pub(crate) struct Method {
+    pub(crate) path: PathAndQuery,
+    pub(crate) client_streaming: bool,
+}
+
+/// The message and enum types of a descriptor set, by their full names.
+#[derive(Debug, Default)]
+pub(super) struct Types {
+    pub(super) messages: HashMap<String, DescriptorProto>,
+    pub(super) enums: HashMap<String, EnumDescriptorProto>,
+}
+
+impl Types {
+    pub(super) fn new(set: &FileDescriptorSet) -> Self {
+        let mut types = Self::default();
+        for file in &set.file {
+            let scope = scope(file);
+            for message in &file.message_type {
+                types.insert_message(&scope, message);
+            }
+            for value in &file.enum_type {
+                types.insert_enum(&scope, value);
+            }
+        }
+        types
+    }
+
+    fn insert_message(&mut self, scope: &str, message: &DescriptorProto) {
+        let name = format!("{}.{}", scope, message.name());
+        for nested in &message.nested_type {
+            self.insert_message(&name, nested);
+        }
+        for nested in &message.enum_type {
+            self.insert_enum(&name, nested);
+        }
+        self.messages.insert(name, message.clone());
+    }
+
+    fn insert_enum(&mut self, scope: &str, value: &EnumDescriptorProto) {
+        self.enums
+            .insert(format!("{}.{}", scope, value.name()), value.clone());
+    }
+
+    pub(super) fn message(&self, name: &str) -> Result<&DescriptorProto, DescriptorError> {
+        self.messages
+            .get(name)
+            .ok_or_else(|| DescriptorError::TypeNotFound {
+                name: name.trim_start_matches('.').to_owned(),
+            })
+    }
+
+    /// Checks that the types of the fields of the message, and of their
+    /// fields in turn, are all in the descriptor set.
+    pub(super) fn check(
+        &self,
+        name: &str,
+        checked: &mut HashSet<String>,
+    ) -> Result<(), DescriptorError> {
+        if !checked.insert(name.to_owned()) {
+            return Ok(());
+        }
+        for field in &self.message(name)?.field {
+            match field.r#type() {
+                Type::Message if field.type_name() != TIMESTAMP => {
+                    self.check(field.type_name(), checked)?
+                }
+                Type::Enum if !self.enums.contains_key(field.type_name()) => {
+                    return TypeNotFound {
+                        name: field.type_name().trim_start_matches('.'),
+                    }
+                    .fail()
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encodes events as the request message of the method, or as the messages
+/// of its batch field.
+#[derive(Debug)]
+pub(crate) struct MessageEncoder {
+    types: Types,
+    message: String,
+    /// Fields of the message mapped to the fields of events setting them.
+    fields: BTreeMap<String, String>,
+    /// The number of the batch field of the request.
+    batch_tag: Option<u32>,
+}
+
+impl MessageEncoder {
+    pub(crate) fn new(
+        descriptor_set_file: &Path,
+        method: &str,
+        fields: BTreeMap<String, String>,
+        batch_field: Option<&str>,
+    ) -> Result<(Method, Self), DescriptorError> {
+        let set = read_descriptor_set(descriptor_set_file)?;
+        Self::from_descriptor_set(&set, method, fields, batch_field)
+    }
+
+    /// Encodes events as messages of the type, as they are produced to Kafka.
+    /// Along with the encoder are the indexes identifying the type in the
+    /// wire format of Confluent's Schema Registry: its index among the
+    /// messages of its file, then among the nested messages of each message
+    /// it is nested in.
+    pub(crate) fn for_message(
+        descriptor_set_file: &Path,
+        message_type: &str,
+        fields: BTreeMap<String, String>,
+    ) -> Result<(Self, Vec<i32>), DescriptorError> {
+        let set = read_descriptor_set(descriptor_set_file)?;
+        Self::from_message_type(&set, message_type, fields)
+    }
+
+    pub(super) fn from_message_type(
+        set: &FileDescriptorSet,
+        message_type: &str,
+        fields: BTreeMap<String, String>,
+    ) -> Result<(Self, Vec<i32>), DescriptorError> {
+        let message = format!(".{}", message_type.trim_start_matches('.'));
+        let indexes =
+            message_indexes(set, &message).ok_or_else(|| DescriptorError::TypeNotFound {
+                name: message_type.to_owned(),
+            })?;
+        let encoder = Self::with_types(Types::new(set), message, fields, None)?;
+        Ok((encoder, indexes))
+    }
+
+    fn from_descriptor_set(
+        set: &FileDescriptorSet,
+        method: &str,
+        fields: BTreeMap<String, String>,
+        batch_field: Option<&str>,
+    ) -> Result<(Method, Self), DescriptorError> {
+        let (service_name, method_name) = match method.trim_start_matches('/').split_once('/') {
+            Some((service, method)) if !service.is_empty() && !method.is_empty() => {
+                (service, method)
+            }
+            _ => return InvalidMethod { method }.fail(),
+        };
+        let path =
+            PathAndQuery::try_from(format!("/{}/{}", service_name, method_name)).map_err(|_| {
+                DescriptorError::InvalidMethod {
+                    method: method.to_owned(),
+                }
+            })?;
+
+        let types = Types::new(set);
+        let mut descriptor = None;
+        for file in &set.file {
+            let scope = scope(file);
+            for service in &file.service {
+                if format!("{}.{}", scope, service.name()) == format!(".{}", service_name) {
+                    descriptor = service
+                        .method
+                        .iter()
+                        .find(|method| method.name() == method_name)
+                        .or(descriptor);
+                }
+            }
+        }
+
+        let descriptor = descriptor.ok_or_else(|| DescriptorError::MethodNotFound {
+            method: method.to_owned(),
+        })?;
+        if descriptor.server_streaming() {
+            return ServerStreaming { method }.fail();
+        }
+        let method = Method {
+            path,
+            client_streaming: descriptor.client_streaming(),
+        };
+
+        let mut message = descriptor.input_type().to_owned();
+        let mut batch_tag = None;
+        if let Some(batch_field) = batch_field {
+            if method.client_streaming {
+                return StreamingBatchField.fail();
+            }
+            let field = find_field(types.message(&message)?, &message, batch_field)?;
+            if field.label() != Label::Repeated || field.r#type() != Type::Message {
+                return InvalidBatchField { field: batch_field }.fail();
+            }
+            batch_tag = Some(field.number() as u32);
+            message = field.type_name().to_owned();
+        }
+
+        let encoder = Self::with_types(types, message, fields, batch_tag)?;
+        Ok((method, encoder))
+    }
+
+    fn with_types(
+        types: Types,
+        message: String,
+        fields: BTreeMap<String, String>,
+        batch_tag: Option<u32>,
+    ) -> Result<Self, DescriptorError> {
+        types.check(&message, &mut HashSet::new())?;
+        let descriptor = types.message(&message)?;
+        for field in fields.keys() {
+            find_field(descriptor, &message, field)?;
+        }
+
+        Ok(Self {
+            types,
+            message,
+            fields,
+            batch_tag,
+        })
+    }
+
+    pub(crate) fn batch_tag(&self) -> Option<u32> {
+        self.batch_tag
+    }
+
+    pub(crate) fn encode(&self, log: &LogEvent) -> Result<Bytes, EncodeError> {
+        let descriptor = &self.types.messages[&self.message];
+        let mut buf = Vec::new();
+        for field in &descriptor.field {
+            let source = self
+                .fields
+                .get(field.name())
+                .map(String::as_str)
+                .unwrap_or_else(|| field.name());
+            if let Some(value) = log.get(source) {
+                self.encode_field(field, value, &mut buf)?;
+            }
+        }
+        Ok(buf.into())
+    }
+
+    fn encode_message(
+        &self,
+        name: &str,
+        values: &BTreeMap<String, Value>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        // Types were all checked when the descriptor set was read.
+        let descriptor = &self.types.messages[name];
+        for field in &descriptor.field {
+            if let Some(value) = values.get(field.name()) {
+                self.encode_field(field, value, buf)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the value of the field, leaving out null values.
+    fn encode_field(
+        &self,
+        field: &FieldDescriptorProto,
+        value: &Value,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        if field.label() != Label::Repeated {
+            return match value {
+                Value::Null => Ok(()),
+                value => self.encode_value(field, value, buf),
+            };
+        }
+
+        let map_entry = (field.r#type() == Type::Message)
+            .then(|| self.types.messages.get(field.type_name()))
+            .flatten()
+            .filter(|entry| {
+                entry.field.len() == 2
+                    && entry.options.as_ref().and_then(|o| o.map_entry) == Some(true)
+            });
+        match (map_entry, value) {
+            (Some(entry), Value::Map(values)) => {
+                let (key, value) = (&entry.field[0], &entry.field[1]);
+                for (k, v) in values.iter().filter(|(_, v)| **v != Value::Null) {
+                    let mut message = Vec::new();
+                    self.encode_value(key, &Value::from(k.as_str()), &mut message)?;
+                    self.encode_value(value, v, &mut message)?;
+                    encode_length_delimited(field.number() as u32, &message, buf);
+                }
+                Ok(())
+            }
+            (Some(_), _) => NotAnObject {
+                field: field.name(),
+            }
+            .fail(),
+            (None, Value::Array(values)) => values
+                .iter()
+                .filter(|value| **value != Value::Null)
+                .try_for_each(|value| self.encode_value(field, value, buf)),
+            (None, Value::Null) => Ok(()),
+            (None, _) => NotAnArray {
+                field: field.name(),
+            }
+            .fail(),
+        }
+    }
+
+    /// Encodes a single value of the field. Repeated scalars are written
+    /// unpacked, which parsers accept for packed fields too.
+    fn encode_value(
+        &self,
+        field: &FieldDescriptorProto,
+        value: &Value,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeError> {
+        let tag = field.number() as u32;
+        let field_type = field.r#type();
+        let invalid = || EncodeError::InvalidValue {
+            field: field.name().to_owned(),
+            field_type: type_name(field_type),
+        };
+
+        match field_type {
+            Type::Double => encoding::double::encode(tag, &to_f64(value).ok_or_else(invalid)?, buf),
+            Type::Float => {
+                encoding::float::encode(tag, &(to_f64(value).ok_or_else(invalid)? as f32), buf)
+            }
+            Type::Int64 => encoding::int64::encode(tag, &to_i64(value).ok_or_else(invalid)?, buf),
+            Type::Sint64 => encoding::sint64::encode(tag, &to_i64(value).ok_or_else(invalid)?, buf),
+            Type::Sfixed64 => {
+                encoding::sfixed64::encode(tag, &to_i64(value).ok_or_else(invalid)?, buf)
+            }
+            Type::Uint64 => encoding::uint64::encode(tag, &to_u64(value).ok_or_else(invalid)?, buf),
+            Type::Fixed64 => {
+                encoding::fixed64::encode(tag, &to_u64(value).ok_or_else(invalid)?, buf)
+            }
+            Type::Int32 => encoding::int32::encode(tag, &to_i32(value).ok_or_else(invalid)?, buf),
+            Type::Sint32 => encoding::sint32::encode(tag, &to_i32(value).ok_or_else(invalid)?, buf),
+            Type::Sfixed32 => {
+                encoding::sfixed32::encode(tag, &to_i32(value).ok_or_else(invalid)?, buf)
+            }
+            Type::Uint32 => encoding::uint32::encode(tag, &to_u32(value).ok_or_else(invalid)?, buf),
+            Type::Fixed32 => {
+                encoding::fixed32::encode(tag, &to_u32(value).ok_or_else(invalid)?, buf)
+            }
+            Type::Bool => encoding::bool::encode(tag, &to_bool(value).ok_or_else(invalid)?, buf),
+            Type::String => encoding::string::encode(tag, &to_string(value), buf),
+            Type::Bytes => {
+                let bytes = match value {
+                    Value::Bytes(bytes) => bytes.to_vec(),
+                    value => to_string(value).into_bytes(),
+                };
+                encoding::bytes::encode(tag, &bytes, buf)
+            }
+            Type::Enum => {
+                let number = self
+                    .types
+                    .enums
+                    .get(field.type_name())
+                    .and_then(|descriptor| enum_number(descriptor, value))
+                    .ok_or_else(invalid)?;
+                encoding::int32::encode(tag, &number, buf)
+            }
+            Type::Message if field.type_name() == TIMESTAMP => {
+                let timestamp = to_timestamp(value).ok_or_else(invalid)?;
+                let mut message = Vec::new();
+                encoding::int64::encode(1, &timestamp.timestamp(), &mut message);
+                encoding::int32::encode(
+                    2,
+                    &(timestamp.timestamp_subsec_nanos() as i32),
+                    &mut message,
+                );
+                encode_length_delimited(tag, &message, buf);
+            }
+            Type::Message => {
+                let values = match value {
+                    Value::Map(values) => values,
+                    _ => {
+                        return NotAnObject {
+                            field: field.name(),
+                        }
+                        .fail()
+                    }
+                };
+                let mut message = Vec::new();
+                self.encode_message(field.type_name(), values, &mut message)?;
+                encode_length_delimited(tag, &message, buf);
+            }
+            Type::Group => return Err(invalid()),
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn read_descriptor_set(path: &Path) -> Result<FileDescriptorSet, DescriptorError> {
+    let bytes = std::fs::read(path).context(ReadDescriptorSet { path })?;
+    FileDescriptorSet::decode(bytes.as_slice()).context(DecodeDescriptorSet)
+}
+
+/// The prefix of the full names of the types of the file.
+fn scope(file: &FileDescriptorProto) -> String {
+    match file.package() {
+        "" => String::new(),
+        package => format!(".{}", package),
+    }
+}
+
+/// The indexes of the message among the messages of its file, then among
+/// the nested messages of each message it is nested in.
+fn message_indexes(set: &FileDescriptorSet, name: &str) -> Option<Vec<i32>> {
+    fn find(scope: &str, messages: &[DescriptorProto], name: &str, indexes: &mut Vec<i32>) -> bool {
+        for (index, message) in messages.iter().enumerate() {
+            let full_name = format!("{}.{}", scope, message.name());
+            indexes.push(index as i32);
+            if full_name == name || find(&full_name, &message.nested_type, name, indexes) {
+                return true;
+            }
+            indexes.pop();
+        }
+        false
+    }
+
+    set.file.iter().find_map(|file| {
+        let mut indexes = Vec::new();
+        find(&scope(file), &file.message_type, name, &mut indexes).then(|| indexes)
+    })
+}
+
+/// Wraps messages as the batch field of a request.
+pub(crate) fn encode_batch(tag: u32, messages: &[Bytes]) -> Bytes {
+    let mut buf = Vec::new();
+    for message in messages {
+        encode_length_delimited(tag, message, &mut buf);
+    }
+    buf.into()
+}
+
+fn encode_length_delimited(tag: u32, message: &[u8], buf: &mut impl BufMut) {
+    encoding::encode_key(tag, WireType::LengthDelimited, buf);
+    encoding::encode_varint(message.len() as u64, buf);
+    buf.put_slice(message);
+}
+
+pub(super) fn find_field<'a>(
+    descriptor: &'a DescriptorProto,
+    message: &str,
+    name: &str,
+) -> Result<&'a FieldDescriptorProto, DescriptorError> {
+    descriptor
+        .field
+        .iter()
+        .find(|field| field.name() == name)
+        .ok_or_else(|| DescriptorError::UnknownField {
+            field: name.to_owned(),
+            message: message.trim_start_matches('.').to_owned(),
+        })
+}
+
+fn type_name(field_type: Type) -> &'static str {
+    match field_type {
+        Type::Double => "double",
+        Type::Float => "float",
+        Type::Int64 => "int64",
+        Type::Uint64 => "uint64",
+        Type::Int32 => "int32",
+        Type::Fixed64 => "fixed64",
+        Type::Fixed32 => "fixed32",
+        Type::Bool => "bool",
+        Type::String => "string",
+        Type::Group => "group",
+        Type::Message => "message",
+        Type::Bytes => "bytes",
+        Type::Uint32 => "uint32",
+        Type::Enum => "enum",
+        Type::Sfixed32 => "sfixed32",
+        Type::Sfixed64 => "sfixed64",
+        Type::Sint32 => "sint32",
+        Type::Sint64 => "sint64",
+    }
+}
+
+fn as_str(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(bytes) | Value::Bytes(bytes) => {
+            std::str::from_utf8(bytes).ok().map(str::trim)
+        }
+        _ => None,
+    }
+}
+
+fn to_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(value) => Some(*value),
+        // Floats are only integers when they have no fraction and fit.
+        Value::Float(value) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => {
+            Some(*value as i64)
+        }
+        value => as_str(value)?.parse().ok(),
+    }
+}
+
+fn to_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(_) | Value::Bytes(_) => as_str(value)?.parse().ok(),
+        value => to_i64(value).and_then(|value| u64::try_from(value).ok()),
+    }
+}
+
+fn to_i32(value: &Value) -> Option<i32> {
+    to_i64(value).and_then(|value| i32::try_from(value).ok())
+}
+
+fn to_u32(value: &Value) -> Option<u32> {
+    to_i64(value).and_then(|value| u32::try_from(value).ok())
+}
+
+fn to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(value) => Some(*value),
+        Value::Integer(value) => Some(*value as f64),
+        value => as_str(value)?.parse().ok(),
+    }
+}
+
+fn to_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Boolean(value) => Some(*value),
+        value => as_str(value)?.parse().ok(),
+    }
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::Map(_) | Value::Array(_) => serde_json::to_string(value).unwrap_or_default(),
+        value => value.to_string_lossy(),
+    }
+}
+
+/// Timestamps are also read from seconds since the Unix epoch, and from
+/// RFC 3339 strings.
+fn to_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Timestamp(timestamp) => Some(*timestamp),
+        Value::Integer(seconds) => Some(DateTime::from_utc(
+            chrono::NaiveDateTime::from_timestamp_opt(*seconds, 0)?,
+            Utc,
+        )),
+        value => DateTime::parse_from_rfc3339(as_str(value)?)
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc)),
+    }
+}
+
+/// Enums are read from the names of their values, or from their numbers.
+fn enum_number(descriptor: &EnumDescriptorProto, value: &Value) -> Option<i32> {
+    match value {
+        Value::String(_) | Value::Bytes(_) => {
+            let name = as_str(value)?;
+            descriptor
+                .value
+                .iter()
+                .find(|value| value.name() == name)
+                .map(|value| value.number())
+                .or_else(|| name.parse().ok())
+        }
+        value => to_i32(value),
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use prost_types::{
+        EnumValueDescriptorProto, FileDescriptorProto, MessageOptions, MethodDescriptorProto,
+        ServiceDescriptorProto,
+    };
+
+    fn field(name: &str, number: i32, label: Label, field_type: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(field_type as i32),
+            ..Default::default()
+        }
+    }
+
+    fn typed(mut field: FieldDescriptorProto, type_name: &str) -> FieldDescriptorProto {
+        field.type_name = Some(type_name.to_owned());
+        field
+    }
+
+    /// ```protobuf
+    /// package logs;
+    /// enum Level { INFO = 0; ERROR = 1; }
+    /// message Record {
+    ///   string message = 1;
+    ///   int32 status = 2;
+    ///   repeated string tags = 3;
+    ///   map<string, string> labels = 4;
+    ///   google.protobuf.Timestamp timestamp = 5;
+    ///   Level level = 6;
+    /// }
+    /// message PushRequest { repeated Record records = 1; }
+    /// service Logs { rpc Push(PushRequest) returns (PushRequest); }
+    /// ```
+    pub(in crate::protobuf) fn descriptor_set() -> FileDescriptorSet {
+        let optional = Label::Optional;
+        let labels = DescriptorProto {
+            name: Some("LabelsEntry".to_owned()),
+            field: vec![
+                field("key", 1, optional, Type::String),
+                field("value", 2, optional, Type::String),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let record = DescriptorProto {
+            name: Some("Record".to_owned()),
+            field: vec![
+                field("message", 1, optional, Type::String),
+                field("status", 2, optional, Type::Int32),
+                field("tags", 3, Label::Repeated, Type::String),
+                typed(
+                    field("labels", 4, Label::Repeated, Type::Message),
+                    ".logs.Record.LabelsEntry",
+                ),
+                typed(field("timestamp", 5, optional, Type::Message), TIMESTAMP),
+                typed(field("level", 6, optional, Type::Enum), ".logs.Level"),
+            ],
+            nested_type: vec![labels],
+            ..Default::default()
+        };
+        let request = DescriptorProto {
+            name: Some("PushRequest".to_owned()),
+            field: vec![typed(
+                field("records", 1, Label::Repeated, Type::Message),
+                ".logs.Record",
+            )],
+            ..Default::default()
+        };
+        let level = EnumDescriptorProto {
+            name: Some("Level".to_owned()),
+            value: vec![
+                EnumValueDescriptorProto {
+                    name: Some("INFO".to_owned()),
+                    number: Some(0),
+                    ..Default::default()
+                },
+                EnumValueDescriptorProto {
+                    name: Some("ERROR".to_owned()),
+                    number: Some(1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let service = ServiceDescriptorProto {
+            name: Some("Logs".to_owned()),
+            method: vec![MethodDescriptorProto {
+                name: Some("Push".to_owned()),
+                input_type: Some(".logs.PushRequest".to_owned()),
+                output_type: Some(".logs.PushRequest".to_owned()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let timestamp = FileDescriptorProto {
+            name: Some("google/protobuf/timestamp.proto".to_owned()),
+            package: Some("google.protobuf".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Timestamp".to_owned()),
+                field: vec![
+                    field("seconds", 1, optional, Type::Int64),
+                    field("nanos", 2, optional, Type::Int32),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        FileDescriptorSet {
+            file: vec![
+                timestamp,
+                FileDescriptorProto {
+                    name: Some("logs.proto".to_owned()),
+                    package: Some("logs".to_owned()),
+                    message_type: vec![record, request],
+                    enum_type: vec![level],
+                    service: vec![service],
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    fn encoder(fields: &[(&str, &str)]) -> (Method, MessageEncoder) {
+        let fields = fields
+            .iter()
+            .map(|(field, source)| (field.to_string(), source.to_string()))
+            .collect();
+        MessageEncoder::from_descriptor_set(
+            &descriptor_set(),
+            "logs.Logs/Push",
+            fields,
+            Some("records"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn resolves_method() {
+        let (method, encoder) = encoder(&[]);
+        assert_eq!(method.path.as_str(), "/logs.Logs/Push");
+        assert!(!method.client_streaming);
+        assert_eq!(encoder.batch_tag(), Some(1));
+
+        let error = MessageEncoder::from_descriptor_set(
+            &descriptor_set(),
+            "logs.Logs/Pull",
+            BTreeMap::new(),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(error, DescriptorError::MethodNotFound { .. }));
+
+        let fields = vec![("unknown".to_owned(), "message".to_owned())];
+        let error = MessageEncoder::from_descriptor_set(
+            &descriptor_set(),
+            "logs.Logs/Push",
+            fields.into_iter().collect(),
+            Some("records"),
+        )
+        .unwrap_err();
+        assert!(matches!(error, DescriptorError::UnknownField { .. }));
+    }
+
+    #[test]
+    fn resolves_message_types() {
+        let (_, indexes) = MessageEncoder::from_message_type(
+            &descriptor_set(),
+            "logs.PushRequest",
+            BTreeMap::new(),
+        )
+        .unwrap();
+        assert_eq!(indexes, vec![1]);
+
+        let (_, indexes) = MessageEncoder::from_message_type(
+            &descriptor_set(),
+            ".logs.Record.LabelsEntry",
+            BTreeMap::new(),
+        )
+        .unwrap();
+        assert_eq!(indexes, vec![0, 0]);
+
+        let error =
+            MessageEncoder::from_message_type(&descriptor_set(), "logs.Missing", BTreeMap::new())
+                .unwrap_err();
+        assert!(matches!(error, DescriptorError::TypeNotFound { .. }));
+    }
+
+    #[test]
+    fn encodes_events() {
+        let (_, encoder) = encoder(&[("status", "response.code")]);
+        let mut log = LogEvent::from("hi");
+        log.insert("response.code", 404);
+        log.insert("tags", vec!["a"]);
+        log.insert("labels.app", "web");
+        log.insert("timestamp", Utc.timestamp(1, 5));
+        log.insert("level", "ERROR");
+        log.insert("unknown", "ignored");
+
+        let encoded = encoder.encode(&log).unwrap();
+        let expected: &[u8] = &[
+            10, 2, b'h', b'i', // message
+            16, 148, 3, // status
+            26, 1, b'a', // tags
+            34, 10, 10, 3, b'a', b'p', b'p', 18, 3, b'w', b'e', b'b', // labels
+            42, 4, 8, 1, 16, 5, // timestamp
+            48, 1, // level
+        ];
+        assert_eq!(encoded.as_ref(), expected);
+
+        assert_eq!(
+            encode_batch(1, &[encoded.slice(..4), encoded.slice(..4)]).as_ref(),
+            &[10, 4, 10, 2, b'h', b'i', 10, 4, 10, 2, b'h', b'i'][..]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let (_, encoder) = encoder(&[]);
+        let mut log = LogEvent::from("hi");
+        log.insert("status", "not a number");
+
+        assert_eq!(
+            encoder.encode(&log),
+            Err(EncodeError::InvalidValue {
+                field: "status".to_owned(),
+                field_type: "int32",
+            })
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Protobuf encoding and decoding of events, as messages of a type described
+//! by a compiled file descriptor set, such as `protoc` writes with
+//! `--descriptor_set_out` and `--include_imports`. Events are converted to
+//! and from messages of any type without building its code into Vector.
+
+mod decoder;
+mod message;
+
+pub use self::decoder::{DecodeError, MessageDecoder};
+pub(crate) use self::message::{
+    encode_batch, DescriptorError, EncodeError, MessageEncoder, Method,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtobufOptions {
+    pub descriptor_set_file: PathBuf,
+    /// The full name of the message type, such as `package.Message`.
+    pub message_type: String,
+    /// Fields of the message mapped to the fields of events, for those that
+    /// don't have the same name.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl ProtobufOptions {
+    pub(crate) fn encoder(&self) -> Result<MessageEncoder, DescriptorError> {
+        MessageEncoder::for_message(
+            &self.descriptor_set_file,
+            &self.message_type,
+            self.fields.clone(),
+        )
+        .map(|(encoder, _)| encoder)
+    }
+
+    pub(crate) fn decoder(&self) -> Result<MessageDecoder, DescriptorError> {
+        MessageDecoder::for_message(
+            &self.descriptor_set_file,
+            &self.message_type,
+            self.fields.clone(),
+        )
+    }
+}
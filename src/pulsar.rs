@@ -0,0 +1,38 @@
+use crate::tls::TlsOptions;
+use pulsar::{Authentication, Pulsar, PulsarBuilder, TokioExecutor};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuthConfig {
+    name: String,  // "token"
+    token: String, // <jwt token>
+}
+
+/// Start building a client for the Pulsar cluster at `endpoint`.
+pub(crate) fn builder(endpoint: &str, auth: &Option<AuthConfig>) -> PulsarBuilder<TokioExecutor> {
+    let builder = Pulsar::builder(endpoint, TokioExecutor);
+    match auth {
+        Some(auth) => builder.with_auth(Authentication {
+            name: auth.name.clone(),
+            data: auth.token.as_bytes().to_vec(),
+        }),
+        None => builder,
+    }
+}
+
+/// Apply the CA and certificate verification settings of `tls`, which are
+/// used when connecting to a `pulsar+ssl://` endpoint.
+pub(crate) fn with_tls(
+    mut builder: PulsarBuilder<TokioExecutor>,
+    tls: &Option<TlsOptions>,
+) -> std::io::Result<PulsarBuilder<TokioExecutor>> {
+    if let Some(tls) = tls {
+        if let Some(ca_file) = &tls.ca_file {
+            builder = builder.with_certificate_chain_file(ca_file)?;
+        }
+        if tls.verify_certificate == Some(false) {
+            builder = builder.with_allow_insecure_connection(true);
+        }
+    }
+    Ok(builder)
+}
@@ -1,24 +1,33 @@
+pub(super) mod parquet;
+
+use self::parquet::ParquetOptions;
 use crate::{
+    avro::{self, AvroEncoder, AvroError, AvroOptions},
     config::{
         log_schema, DataType, GenerateConfig, ProxyConfig, SinkConfig, SinkContext, SinkDescription,
     },
-    event::Event,
-    internal_events::{aws_s3::sink::S3EventsSent, TemplateRenderingFailed},
+    event::{Event, EventStatus, LogEvent},
+    internal_events::{aws_s3::sink::S3EventsSent, AvroEncodingFailed, TemplateRenderingFailed},
     rusoto::{self, AwsAuthentication, RegionOrEndpoint},
     serde::to_string,
     sinks::util::{
         batch::{BatchConfig, BatchSettings},
-        encoding::{EncodingConfig, EncodingConfiguration},
+        buffer::GZIP_FAST,
+        encoding::{CsvOptions, EncodingConfig, EncodingConfiguration},
         retries::RetryLogic,
-        sink::Response,
-        Buffer, Compression, Concurrency, EncodedEvent, PartitionBatchSink, PartitionBuffer,
-        PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig,
+        sink::{Response, ServiceLogic, StdServiceLogic},
+        Buffer, Compression, Concurrency, EncodedEvent, EncodedLength, PartitionBatchSink,
+        PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig, VecBuffer,
     },
     template::Template,
 };
 use bytes::Bytes;
 use chrono::Utc;
-use futures::{future::BoxFuture, stream, FutureExt, SinkExt, StreamExt};
+use flate2::write::GzEncoder;
+use futures::{
+    future::{self, BoxFuture},
+    stream, FutureExt, Sink, SinkExt, StreamExt,
+};
 use http::StatusCode;
 use md5::Digest;
 use rusoto_core::RusotoError;
@@ -30,11 +39,14 @@ use snafu::Snafu;
 use std::{
     collections::BTreeMap,
     convert::{TryFrom, TryInto},
+    io::Write,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tower::{Service, ServiceBuilder};
 use tracing_futures::Instrument;
 use uuid::Uuid;
+use vector_core::ByteSizeOf;
 
 #[derive(Clone)]
 pub struct S3Sink {
@@ -60,6 +72,11 @@ pub struct S3SinkConfig {
     pub batch: BatchConfig,
     #[serde(default)]
     pub request: TowerRequestConfig,
+    #[serde(default)]
+    pub parquet: ParquetOptions,
+    #[serde(default)]
+    pub avro: AvroOptions,
+    pub csv: Option<CsvOptions>,
     // Deprecated name. Moved to auth.
     assume_role: Option<String>,
     #[serde(default)]
@@ -123,6 +140,9 @@ enum S3StorageClass {
 pub enum Encoding {
     Text,
     Ndjson,
+    Parquet,
+    Avro,
+    Csv,
 }
 
 inventory::submit! {
@@ -143,6 +163,9 @@ impl GenerateConfig for S3SinkConfig {
             compression: Compression::gzip_default(),
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
+            parquet: ParquetOptions::default(),
+            avro: AvroOptions::default(),
+            csv: None,
             assume_role: None,
             auth: AwsAuthentication::default(),
         })
@@ -157,9 +180,16 @@ impl SinkConfig for S3SinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.encoding.codec() == &Encoding::Csv && self.csv.is_none() {
+            return Err("`encoding.codec = \"csv\"` requires `csv`".into());
+        }
         let client = self.create_client(&cx.proxy)?;
         let healthcheck = self.clone().healthcheck(client.clone()).boxed();
-        let sink = self.new(client, cx)?;
+        let avro = match self.encoding.codec() {
+            Encoding::Avro => Some(Arc::new(self.avro.encoder(&cx.proxy).await?)),
+            _ => None,
+        };
+        let sink = self.new(client, cx, avro)?;
         Ok((sink, healthcheck))
     }
 
@@ -183,8 +213,13 @@ enum HealthcheckError {
 }
 
 impl S3SinkConfig {
-    pub fn new(&self, client: S3Client, cx: SinkContext) -> crate::Result<super::VectorSink> {
-        let request = self.request.unwrap_with(&TowerRequestConfig {
+    pub fn new(
+        &self,
+        client: S3Client,
+        cx: SinkContext,
+        avro: Option<Arc<AvroEncoder>>,
+    ) -> crate::Result<super::VectorSink> {
+        let request_settings = self.request.unwrap_with(&TowerRequestConfig {
             concurrency: Concurrency::Fixed(50),
             rate_limit_num: Some(250),
             ..Default::default()
@@ -192,48 +227,144 @@ impl S3SinkConfig {
 
         let encoding = self.encoding.clone();
 
-        let compression = self.compression;
+        // Parquet files are compressed by column, with `parquet.compression`,
+        // and Avro files by block, with `avro.compression`.
+        let parquet_options = match encoding.codec() {
+            Encoding::Parquet => Some(self.parquet.clone()),
+            _ => None,
+        };
+        let compression = match encoding.codec() {
+            Encoding::Parquet | Encoding::Avro => Compression::None,
+            _ => self.compression,
+        };
+        // CSV objects start with the header, so they're compressed once the
+        // header is prepended rather than while buffering.
+        let csv = match encoding.codec() {
+            Encoding::Csv => self.csv.clone(),
+            _ => None,
+        };
+        let buffer_compression = match encoding.codec() {
+            Encoding::Csv => Compression::None,
+            _ => compression,
+        };
         let filename_time_format = self
             .filename_time_format
             .clone()
             .unwrap_or_else(|| "%s".into());
         let filename_append_uuid = self.filename_append_uuid.unwrap_or(true);
-        let batch = BatchSettings::default()
-            .bytes(10_000_000)
-            .timeout(300)
-            .parse_config(self.batch)?;
 
         let key_prefix = self.key_prefix.as_deref().unwrap_or("date=%F/");
         let key_prefix = Template::try_from(key_prefix)?;
 
         let s3 = S3Sink { client };
 
-        let filename_extension = self.filename_extension.clone();
+        let mut filename_extension = self.filename_extension.clone();
         let bucket = self.bucket.clone();
-        let options = self.options.clone();
-
-        let svc = ServiceBuilder::new()
-            .map(move |req| {
-                build_request(
-                    req,
-                    filename_time_format.clone(),
-                    filename_extension.clone(),
-                    filename_append_uuid,
-                    compression,
-                    bucket.clone(),
-                    options.clone(),
-                )
-            })
-            .settings(request, S3RetryLogic)
-            .service(s3);
-
-        let buffer = PartitionBuffer::new(Buffer::new(batch.size, self.compression));
+        let mut options = self.options.clone();
+        if parquet_options.is_some() {
+            filename_extension = filename_extension.or_else(|| Some(parquet::EXTENSION.into()));
+            options.content_type = options
+                .content_type
+                .or_else(|| Some(parquet::CONTENT_TYPE.into()));
+        }
+        if avro.is_some() {
+            filename_extension = filename_extension.or_else(|| Some(avro::EXTENSION.into()));
+            options.content_type = options
+                .content_type
+                .or_else(|| Some(avro::CONTENT_TYPE.into()));
+        }
+        if csv.is_some() {
+            filename_extension = filename_extension.or_else(|| {
+                Some(match compression {
+                    Compression::None => "csv".into(),
+                    Compression::Gzip(_) => "csv.gz".into(),
+                })
+            });
+            options.content_type = options.content_type.or_else(|| Some("text/csv".into()));
+        }
+        let build = move |body: Result<Vec<u8>, String>, key: Bytes| {
+            build_request(
+                body,
+                key,
+                filename_time_format.clone(),
+                filename_extension.clone(),
+                filename_append_uuid,
+                compression,
+                bucket.clone(),
+                options.clone(),
+            )
+        };
 
-        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
-            .with_flat_map(move |e| stream::iter(encode_event(e, &key_prefix, &encoding)).map(Ok))
-            .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+        // Parquet and Avro files are encoded from the whole batch, so their
+        // events are buffered as they are.
+        let batch_encoder = match (parquet_options, &avro) {
+            (Some(options), _) => Some(BatchEncoder::Parquet(options)),
+            (None, Some(avro)) => Some(BatchEncoder::Avro(Arc::clone(avro))),
+            (None, None) => None,
+        };
+        let sink: Box<dyn Sink<Event, Error = ()> + Send + Unpin> = match batch_encoder {
+            Some(batch_encoder) => {
+                let batch = BatchSettings::default()
+                    .bytes(10_000_000)
+                    .timeout(300)
+                    .parse_config(self.batch)?;
+                let svc = ServiceBuilder::new()
+                    .map(move |req: PartitionInnerBuffer<Vec<LogEvent>, Bytes>| {
+                        let (logs, key) = req.into_parts();
+                        let body = batch_encoder
+                            .encode(logs)
+                            .map_err(|error| error.to_string());
+                        build(body, key)
+                    })
+                    .settings(request_settings, S3RetryLogic)
+                    .service(s3);
+                let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+                let sink = PartitionBatchSink::new_with_logic(
+                    svc,
+                    buffer,
+                    batch.timeout,
+                    cx.acker(),
+                    S3ServiceLogic,
+                )
+                .with_flat_map(move |e| {
+                    stream::iter(encode_log(e, &key_prefix, &encoding, avro.as_deref())).map(Ok)
+                })
+                .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+                Box::new(sink)
+            }
+            None => {
+                let batch = BatchSettings::default()
+                    .bytes(10_000_000)
+                    .timeout(300)
+                    .parse_config(self.batch)?;
+                let request_csv = csv.clone();
+                let svc = ServiceBuilder::new()
+                    .map(move |req: PartitionInnerBuffer<Vec<u8>, Bytes>| {
+                        let (mut body, key) = req.into_parts();
+                        if let Some(csv) = &request_csv {
+                            body = encode_csv(body, csv, compression);
+                        }
+                        build(Ok(body), key)
+                    })
+                    .settings(request_settings, S3RetryLogic)
+                    .service(s3);
+                let buffer = PartitionBuffer::new(Buffer::new(batch.size, buffer_compression));
+                let sink = PartitionBatchSink::new_with_logic(
+                    svc,
+                    buffer,
+                    batch.timeout,
+                    cx.acker(),
+                    S3ServiceLogic,
+                )
+                .with_flat_map(move |e| {
+                    stream::iter(encode_event(e, &key_prefix, &encoding, csv.as_ref())).map(Ok)
+                })
+                .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+                Box::new(sink)
+            }
+        };
 
-        Ok(super::VectorSink::Sink(Box::new(sink)))
+        Ok(super::VectorSink::Sink(sink))
     }
 
     pub async fn healthcheck(self, client: S3Client) -> crate::Result<()> {
@@ -270,7 +401,7 @@ impl S3SinkConfig {
 
 impl Service<Request> for S3Sink {
     type Response = PutObjectOutput;
-    type Error = RusotoError<PutObjectError>;
+    type Error = S3Error;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
@@ -278,6 +409,10 @@ impl Service<Request> for S3Sink {
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
+        let body = match request.body {
+            Ok(body) => body,
+            Err(message) => return future::err(S3Error::EncodeBatch { message }).boxed(),
+        };
         let options = request.options;
 
         let content_encoding = request.content_encoding;
@@ -288,7 +423,7 @@ impl Service<Request> for S3Sink {
             .content_type
             .or_else(|| Some("text/x-log".to_owned()));
 
-        let content_md5 = base64::encode(md5::Md5::digest(&request.body));
+        let content_md5 = base64::encode(md5::Md5::digest(&body));
 
         let mut tagging = url::form_urlencoded::Serializer::new(String::new());
         if let Some(tags) = options.tags {
@@ -299,12 +434,12 @@ impl Service<Request> for S3Sink {
         let tagging = tagging.finish();
 
         emit!(S3EventsSent {
-            byte_size: request.body.len(),
+            byte_size: body.len(),
         });
 
         let client = self.client.clone();
         let request = PutObjectRequest {
-            body: Some(request.body.into()),
+            body: Some(body.into()),
             bucket: request.bucket,
             key: request.key,
             content_encoding,
@@ -327,12 +462,14 @@ impl Service<Request> for S3Sink {
                 .put_object(request)
                 .instrument(info_span!("request"))
                 .await
+                .map_err(|source| S3Error::PutObject { source })
         })
     }
 }
 
 fn build_request(
-    req: PartitionInnerBuffer<Vec<u8>, Bytes>,
+    body: Result<Vec<u8>, String>,
+    key: Bytes,
     time_format: String,
     extension: Option<String>,
     uuid: bool,
@@ -340,8 +477,6 @@ fn build_request(
     bucket: String,
     options: S3Options,
 ) -> Request {
-    let (inner, key) = req.into_parts();
-
     // TODO: pull the seconds from the last event
     let filename = {
         let seconds = Utc::now().format(&time_format);
@@ -360,13 +495,13 @@ fn build_request(
 
     debug!(
         message = "Sending events.",
-        bytes = ?inner.len(),
+        bytes = ?body.as_ref().map(Vec::len).ok(),
         bucket = ?bucket,
         key = ?key
     );
 
     Request {
-        body: inner,
+        body,
         bucket,
         key,
         content_encoding: compression.content_encoding(),
@@ -376,7 +511,8 @@ fn build_request(
 
 #[derive(Debug, Clone)]
 struct Request {
-    body: Vec<u8>,
+    /// The encoded batch, or why it couldn't be encoded.
+    body: Result<Vec<u8>, String>,
     bucket: String,
     key: String,
     content_encoding: Option<&'static str>,
@@ -385,25 +521,56 @@ struct Request {
 
 impl Response for PutObjectOutput {}
 
+#[derive(Debug, Snafu)]
+enum S3Error {
+    #[snafu(display("Failed to encode batch: {}", message))]
+    EncodeBatch { message: String },
+    #[snafu(display("{}", source))]
+    PutObject { source: RusotoError<PutObjectError> },
+}
+
 #[derive(Debug, Clone)]
 struct S3RetryLogic;
 
 impl RetryLogic for S3RetryLogic {
-    type Error = RusotoError<PutObjectError>;
+    type Error = S3Error;
     type Response = PutObjectOutput;
 
     fn is_retriable_error(&self, error: &Self::Error) -> bool {
-        rusoto::is_retriable_error(error)
+        match error {
+            S3Error::EncodeBatch { .. } => false,
+            S3Error::PutObject { source } => rusoto::is_retriable_error(source),
+        }
     }
 }
 
-fn encode_event(
-    mut event: Event,
-    key_prefix: &Template,
-    encoding: &EncodingConfig<Encoding>,
-) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, Bytes>>> {
-    let key = key_prefix
-        .render_string(&event)
+/// Fails the events of batches that couldn't be encoded, which would fail
+/// the same way if they were sent again.
+#[derive(Clone, Debug)]
+struct S3ServiceLogic;
+
+impl ServiceLogic for S3ServiceLogic {
+    type Response = PutObjectOutput;
+
+    fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus {
+        match result {
+            Err(error)
+                if matches!(
+                    error.downcast_ref::<S3Error>(),
+                    Some(S3Error::EncodeBatch { .. })
+                ) =>
+            {
+                error!(message = "Request failed.", %error);
+                EventStatus::Failed
+            }
+            result => StdServiceLogic::default().result_status(result),
+        }
+    }
+}
+
+fn render_key(event: &Event, key_prefix: &Template) -> Option<Bytes> {
+    key_prefix
+        .render_string(event)
         .map_err(|error| {
             emit!(TemplateRenderingFailed {
                 error,
@@ -411,7 +578,17 @@ fn encode_event(
                 drop_event: true,
             });
         })
-        .ok()?;
+        .ok()
+        .map(Into::into)
+}
+
+fn encode_event(
+    mut event: Event,
+    key_prefix: &Template,
+    encoding: &EncodingConfig<Encoding>,
+    csv: Option<&CsvOptions>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, Bytes>>> {
+    let key = render_key(&event, key_prefix)?;
 
     encoding.apply_rules(&mut event);
 
@@ -431,17 +608,115 @@ fn encode_event(
             bytes.push(b'\n');
             bytes
         }
+        Encoding::Csv => csv
+            .expect("Missing `csv` options, this is a bug!")
+            .encode(&log),
+        Encoding::Parquet | Encoding::Avro => {
+            unreachable!("Parquet and Avro batches are encoded as a whole")
+        }
     };
 
     Some(EncodedEvent {
-        item: PartitionInnerBuffer::new(bytes, key.into()),
+        item: PartitionInnerBuffer::new(bytes, key),
         finalizers: log.metadata_mut().take_finalizers(),
     })
 }
 
+/// Buffers an event for the codecs encoding the whole batch at once.
+fn encode_log(
+    mut event: Event,
+    key_prefix: &Template,
+    encoding: &EncodingConfig<Encoding>,
+    avro: Option<&AvroEncoder>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<LogEvent, Bytes>>> {
+    let key = render_key(&event, key_prefix)?;
+
+    encoding.apply_rules(&mut event);
+
+    let mut log = event.into_log();
+    let finalizers = log.metadata_mut().take_finalizers();
+    // Events that don't fit the schema are failed here, rather than failing
+    // the whole batch when it's encoded.
+    if let Some(avro) = avro {
+        if let Err(error) = avro.convert(log.as_map()) {
+            emit!(AvroEncodingFailed { error: &error });
+            finalizers.update_status(EventStatus::Failed);
+            return None;
+        }
+    }
+
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(log, key),
+        finalizers,
+    })
+}
+
+impl EncodedLength for LogEvent {
+    fn encoded_length(&self) -> usize {
+        self.size_of()
+    }
+}
+
+/// The codecs encoding the events of a batch as one file.
+enum BatchEncoder {
+    Parquet(ParquetOptions),
+    Avro(Arc<AvroEncoder>),
+}
+
+impl BatchEncoder {
+    fn encode(&self, logs: Vec<LogEvent>) -> crate::Result<Vec<u8>> {
+        match self {
+            Self::Parquet(options) => parquet::encode(logs, options),
+            Self::Avro(avro) => Ok(encode_avro(&logs, avro)?),
+        }
+    }
+}
+
+/// Converts a batch of events to an Avro container file. The events were
+/// checked against the schema when they were buffered.
+fn encode_avro(logs: &[LogEvent], avro: &AvroEncoder) -> Result<Vec<u8>, AvroError> {
+    let records = logs
+        .iter()
+        .filter_map(|log| {
+            avro.convert(log.as_map())
+                .map_err(|error| emit!(AvroEncodingFailed { error: &error }))
+                .ok()
+        })
+        .collect();
+    avro.encode_container(records)
+}
+
+/// Prepends the header to a batch of CSV rows, then compresses it.
+fn encode_csv(rows: Vec<u8>, csv: &CsvOptions, compression: Compression) -> Vec<u8> {
+    let body = match csv.header() {
+        Some(mut header) => {
+            header.extend_from_slice(&rows);
+            header
+        }
+        None => rows,
+    };
+    match compression {
+        Compression::None => body,
+        Compression::Gzip(level) => {
+            let level = level.unwrap_or(GZIP_FAST);
+            let mut encoder = GzEncoder::new(
+                Vec::with_capacity(body.len()),
+                flate2::Compression::new(level as u32),
+            );
+            encoder
+                .write_all(&body)
+                .expect("Failed to compress batch, this is a bug!");
+            encoder
+                .finish()
+                .expect("Failed to compress batch, this is a bug!")
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::Value;
 
     #[test]
     fn generate_config() {
@@ -456,6 +731,7 @@ mod tests {
             message.clone().into(),
             &batch_time_format,
             &Encoding::Text.into(),
+            None,
         )
         .unwrap();
 
@@ -471,7 +747,8 @@ mod tests {
         event.as_mut_log().insert("key", "value");
 
         let batch_time_format = Template::try_from("date=%F").unwrap();
-        let encoded = encode_event(event, &batch_time_format, &Encoding::Ndjson.into()).unwrap();
+        let encoded =
+            encode_event(event, &batch_time_format, &Encoding::Ndjson.into(), None).unwrap();
 
         let (bytes, _) = encoded.item.into_parts();
         let map: BTreeMap<String, String> = serde_json::from_slice(&bytes[..]).unwrap();
@@ -496,7 +773,7 @@ mod tests {
             timestamp_format: None,
         };
 
-        let encoded = encode_event(event, &key_prefix, &encoding_config).unwrap();
+        let encoded = encode_event(event, &key_prefix, &encoding_config, None).unwrap();
 
         let (bytes, _) = encoded.item.into_parts();
         let map: BTreeMap<String, String> = serde_json::from_slice(&bytes[..]).unwrap();
@@ -505,53 +782,121 @@ mod tests {
         // assert_eq!(map["key"], "value".to_string());
     }
 
+    #[tokio::test]
+    async fn s3_encode_events_avro() {
+        let options = AvroOptions {
+            schema: Some(
+                r#"{"type": "record", "name": "Log", "fields": [
+                    {"name": "message", "type": "string"},
+                    {"name": "key", "type": "string"}
+                ]}"#
+                .into(),
+            ),
+            ..Default::default()
+        };
+        let avro = options.encoder(&ProxyConfig::default()).await.unwrap();
+        let key_prefix = Template::try_from("date=%F").unwrap();
+        let encoding = Encoding::Avro.into();
+
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("key", "value");
+        let encoded = encode_log(event, &key_prefix, &encoding, Some(&avro)).unwrap();
+        let missing_key = encode_log("hello".into(), &key_prefix, &encoding, Some(&avro));
+        assert!(missing_key.is_none());
+
+        let (log, _) = encoded.item.into_parts();
+        let data = encode_avro(&[log], &avro).unwrap();
+        let records = crate::avro::AvroDecoder::default()
+            .decode_container(&data)
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].as_map().unwrap()["key"], Value::from("value"));
+    }
+
     #[test]
-    fn s3_build_request() {
-        let buf = PartitionInnerBuffer::new(vec![0u8; 10], Bytes::from("key/"));
+    fn s3_encode_events_csv() {
+        use std::io::Read;
+
+        let csv: CsvOptions = toml::from_str(r#"fields = ["message", "key"]"#).unwrap();
+        let key_prefix = Template::try_from("date=%F").unwrap();
+        let encoding = Encoding::Csv.into();
+
+        let mut rows = Vec::new();
+        for message in &["hello", "hello, world"] {
+            let mut event = Event::from(*message);
+            event.as_mut_log().insert("key", "value");
+            let encoded = encode_event(event, &key_prefix, &encoding, Some(&csv)).unwrap();
+            rows.extend(encoded.item.into_parts().0);
+        }
 
-        let req = build_request(
-            buf.clone(),
-            "date".into(),
-            Some("ext".into()),
-            false,
-            Compression::None,
-            "bucket".into(),
-            S3Options::default(),
+        let body = encode_csv(rows.clone(), &csv, Compression::None);
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "message,key\nhello,value\n\"hello, world\",value\n"
         );
+
+        let body = encode_csv(rows, &csv, Compression::gzip_default());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert!(decoded.starts_with("message,key\nhello,value\n"));
+    }
+
+    #[test]
+    fn s3_build_request() {
+        let build = |extension: Option<&str>, uuid, compression| {
+            build_request(
+                Ok(vec![0u8; 10]),
+                Bytes::from("key/"),
+                "date".into(),
+                extension.map(Into::into),
+                uuid,
+                compression,
+                "bucket".into(),
+                S3Options::default(),
+            )
+        };
+
+        let req = build(Some("ext"), false, Compression::None);
         assert_eq!(req.key, "key/date.ext".to_string());
 
-        let req = build_request(
-            buf.clone(),
-            "date".into(),
-            None,
-            false,
-            Compression::None,
-            "bucket".into(),
-            S3Options::default(),
-        );
+        let req = build(None, false, Compression::None);
         assert_eq!(req.key, "key/date.log".to_string());
 
-        let req = build_request(
-            buf.clone(),
+        let req = build(None, false, Compression::gzip_default());
+        assert_eq!(req.key, "key/date.log.gz".to_string());
+
+        let req = build(None, true, Compression::gzip_default());
+        assert_ne!(req.key, "key/date.log.gz".to_string());
+    }
+
+    #[tokio::test]
+    async fn s3_fails_batches_that_fail_to_encode() {
+        let mut s3 = S3Sink {
+            client: S3Client::new(rusoto_core::Region::UsEast1),
+        };
+        let request = build_request(
+            Err("invalid batch".into()),
+            Bytes::from("key/"),
             "date".into(),
             None,
             false,
-            Compression::gzip_default(),
+            Compression::None,
             "bucket".into(),
             S3Options::default(),
         );
-        assert_eq!(req.key, "key/date.log.gz".to_string());
 
-        let req = build_request(
-            buf,
-            "date".into(),
-            None,
-            true,
-            Compression::gzip_default(),
-            "bucket".into(),
-            S3Options::default(),
+        let error = s3.call(request).await.unwrap_err();
+        assert!(!S3RetryLogic.is_retriable_error(&error));
+        assert_eq!(
+            S3ServiceLogic.result_status(Err(error.into())),
+            EventStatus::Failed
+        );
+        assert_eq!(
+            S3ServiceLogic.result_status(Err("connection reset".into())),
+            EventStatus::Errored
         );
-        assert_ne!(req.key, "key/date.log.gz".to_string());
     }
 
     #[test]
@@ -602,7 +947,7 @@ mod integration_tests {
         let config = config(&bucket, 1000000);
         let prefix = config.key_prefix.clone();
         let client = config.create_client(&cx.globals.proxy).unwrap();
-        let sink = config.new(client, cx).unwrap();
+        let sink = config.new(client, cx, None).unwrap();
 
         let (lines, events, mut receiver) = make_events_batch(100, 10);
         sink.run(events).await.unwrap();
@@ -637,7 +982,7 @@ mod integration_tests {
         };
         let prefix = config.key_prefix.clone();
         let client = config.create_client(&cx.globals.proxy).unwrap();
-        let sink = config.new(client, cx).unwrap();
+        let sink = config.new(client, cx, None).unwrap();
 
         let (lines, _events) = random_lines_with_stream(100, 30, None);
 
@@ -687,7 +1032,7 @@ mod integration_tests {
 
         let prefix = config.key_prefix.clone();
         let client = config.create_client(&cx.globals.proxy).unwrap();
-        let sink = config.new(client, cx).unwrap();
+        let sink = config.new(client, cx, None).unwrap();
 
         let (lines, events, mut receiver) = make_events_batch(100, 500);
         sink.run(events).await.unwrap();
@@ -741,7 +1086,7 @@ mod integration_tests {
         let config = config(&bucket, 1000000);
         let prefix = config.key_prefix.clone();
         let client = config.create_client(&cx.globals.proxy).unwrap();
-        let sink = config.new(client, cx).unwrap();
+        let sink = config.new(client, cx, None).unwrap();
 
         let (lines, events, mut receiver) = make_events_batch(100, 10);
         sink.run(events).await.unwrap();
@@ -773,7 +1118,7 @@ mod integration_tests {
         config.bucket = format!("BREAK{}IT", config.bucket);
         let prefix = config.key_prefix.clone();
         let client = config.create_client(&cx.globals.proxy).unwrap();
-        let sink = config.new(client, cx).unwrap();
+        let sink = config.new(client, cx, None).unwrap();
 
         let (_lines, events, mut receiver) = make_events_batch(1, 1);
         sink.run(events).await.unwrap();
@@ -838,6 +1183,9 @@ mod integration_tests {
                 ..Default::default()
             },
             request: TowerRequestConfig::default(),
+            parquet: ParquetOptions::default(),
+            avro: AvroOptions::default(),
+            csv: None,
             assume_role: None,
             auth: Default::default(),
         }
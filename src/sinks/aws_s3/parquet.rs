@@ -0,0 +1,228 @@
+//! Parquet encoding of batches. Events are buffered as they are, and the
+//! batch is laid out as columns when it is sent.
+
+pub use crate::sinks::util::parquet::{ParquetCompression, CONTENT_TYPE, EXTENSION};
+use crate::{
+    event::{EventBatch, LogEvent, Value},
+    sinks::util::parquet::{ParquetColumn, ParquetType, ParquetWriter},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetOptions {
+    #[serde(default)]
+    pub compression: ParquetCompression,
+    #[serde(default = "default_row_group_size")]
+    pub row_group_size: NonZeroUsize,
+    /// The columns of the files. Inferred from each batch if empty.
+    #[serde(default)]
+    pub schema: BTreeMap<String, ColumnType>,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self {
+            compression: ParquetCompression::default(),
+            row_group_size: default_row_group_size(),
+            schema: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_row_group_size() -> NonZeroUsize {
+    NonZeroUsize::new(100_000).unwrap()
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    String,
+    Long,
+    Double,
+    Boolean,
+    Timestamp,
+}
+
+impl ColumnType {
+    /// The type of a value, for schema inference. Strings are timestamps if
+    /// they are RFC 3339 timestamps.
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Boolean(_) => Some(Self::Boolean),
+            Value::Integer(_) => Some(Self::Long),
+            Value::Float(_) => Some(Self::Double),
+            Value::Timestamp(_) => Some(Self::Timestamp),
+            value if value.parse_timestamp().is_some() => Some(Self::Timestamp),
+            _ => Some(Self::String),
+        }
+    }
+
+    /// The type of a column holding values of both types.
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Long, Self::Double) | (Self::Double, Self::Long) => Self::Double,
+            _ => Self::String,
+        }
+    }
+}
+
+impl From<ColumnType> for ParquetType {
+    fn from(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::String => Self::String,
+            ColumnType::Long => Self::Int64,
+            ColumnType::Double => Self::Double,
+            ColumnType::Boolean => Self::Boolean,
+            ColumnType::Timestamp => Self::TimestampMicros,
+        }
+    }
+}
+
+/// Converts a batch of events to a Parquet file.
+pub fn encode(logs: Vec<LogEvent>, options: &ParquetOptions) -> crate::Result<Vec<u8>> {
+    encode_batch(&EventBatch::from_logs(logs), options)
+}
+
+/// The events of a batch of JSON lines.
+#[cfg(test)]
+pub(crate) fn decode_lines(lines: &[u8]) -> serde_json::Result<Vec<LogEvent>> {
+    lines
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            serde_json::from_slice::<BTreeMap<String, serde_json::Value>>(line).map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect::<BTreeMap<_, _>>()
+                    .into()
+            })
+        })
+        .collect()
+}
+
+/// Writes a batch as a Parquet file, with row groups of at most
+/// `row_group_size` rows. All the columns are optional, with nulls for
+/// missing values and values that can't be converted to their type.
+fn encode_batch(batch: &EventBatch, options: &ParquetOptions) -> crate::Result<Vec<u8>> {
+    let schema = if options.schema.is_empty() {
+        infer_schema(batch)
+    } else {
+        options.schema.clone()
+    };
+    // Columns are either top-level fields, or paths of nested fields
+    // separated by dots.
+    let columns = schema
+        .into_iter()
+        .map(|(name, column_type)| {
+            let mut column = ParquetColumn::new(name.as_str(), column_type.into());
+            if batch.column(&name).is_none() {
+                column.field = name.split('.').map(Into::into).collect();
+            }
+            column
+        })
+        .collect();
+    let writer = ParquetWriter::new(columns, options.compression, options.row_group_size)?;
+    Ok(writer.write(batch)?)
+}
+
+/// The top-level fields of the batch, with the type of their values.
+/// Fields that are null in every event are left out.
+fn infer_schema(batch: &EventBatch) -> BTreeMap<String, ColumnType> {
+    batch
+        .columns()
+        .filter_map(|(name, column)| {
+            (0..batch.len())
+                .filter_map(|row| column.get(row))
+                .filter_map(|value| ColumnType::of(&value))
+                .reduce(ColumnType::merge)
+                .map(|column_type| (name.clone(), column_type))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+        util::cursor::SliceableCursor,
+    };
+
+    const LINES: &[u8] = br#"{"message":"first","status":200,"took":0.5,"timestamp":"2021-09-01T12:00:00.123456Z","tags":["a"]}
+{"message":"second","status":404,"took":2,"timestamp":"2021-09-01T12:00:01Z","tags":null}
+{"message":"third","ok":true}
+"#;
+
+    fn read(data: Vec<u8>) -> SerializedFileReader<SliceableCursor> {
+        SerializedFileReader::new(SliceableCursor::new(data)).unwrap()
+    }
+
+    #[test]
+    fn infers_schema_from_batches() {
+        let batch = EventBatch::from_logs(decode_lines(LINES).unwrap());
+
+        let schema = infer_schema(&batch);
+        assert_eq!(
+            schema.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("message".into(), ColumnType::String),
+                ("ok".into(), ColumnType::Boolean),
+                ("status".into(), ColumnType::Long),
+                ("tags".into(), ColumnType::String),
+                ("timestamp".into(), ColumnType::Timestamp),
+                ("took".into(), ColumnType::Double),
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_inferred_columns() {
+        let reader =
+            read(encode(decode_lines(LINES).unwrap(), &ParquetOptions::default()).unwrap());
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows.len(), 3);
+
+        // Columns are in the order of their names.
+        assert_eq!(rows[0].get_string(0).unwrap(), "first");
+        assert!(rows[0].get_bool(1).is_err());
+        assert_eq!(rows[0].get_long(2).unwrap(), 200);
+        assert_eq!(rows[0].get_string(3).unwrap(), r#"["a"]"#);
+        assert_eq!(rows[0].get_timestamp_micros(4).unwrap(), 1630497600123456);
+        assert_eq!(rows[1].get_double(5).unwrap(), 2.0);
+        assert!(rows[1].get_string(3).is_err());
+        assert!(rows[2].get_bool(1).unwrap());
+    }
+
+    #[test]
+    fn encodes_configured_columns_in_row_groups() {
+        let lines = br#"{"status":"200","kubernetes":{"pod_name":"vector-0"}}
+{"status":"unknown","kubernetes":{"pod_name":"vector-1"}}
+{"status":503}
+"#;
+        let options = ParquetOptions {
+            compression: ParquetCompression::Gzip,
+            row_group_size: NonZeroUsize::new(2).unwrap(),
+            schema: vec![
+                ("kubernetes.pod_name".into(), ColumnType::String),
+                ("status".into(), ColumnType::Long),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let reader = read(encode(decode_lines(lines).unwrap(), &options).unwrap());
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows[0].get_string(0).unwrap(), "vector-0");
+        assert_eq!(rows[0].get_long(1).unwrap(), 200);
+        assert!(rows[1].get_long(1).is_err());
+        assert!(rows[2].get_string(0).is_err());
+        assert_eq!(rows[2].get_long(1).unwrap(), 503);
+    }
+}
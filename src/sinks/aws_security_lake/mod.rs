@@ -0,0 +1,425 @@
+//! Writes events to a custom source of Amazon Security Lake, normalized to
+//! a class of OCSF and encoded as Parquet.
+//!
+//! Objects are written to the layout Security Lake expects of custom
+//! sources, partitioned by the region and the account of the source and by
+//! the day of the events, as in
+//! `ext/{source_name}/region={region}/accountId={account_id}/eventDay={YYYYMMDD}/`.
+//! Sources of Security Lake 2 are versioned, with the version following
+//! their name.
+
+mod ocsf;
+mod parquet;
+
+use self::ocsf::Normalizer;
+pub use self::ocsf::{AttributeConfig, AttributeType, OcsfConfig, ProductConfig};
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, ProxyConfig, SinkConfig, SinkContext, SinkDescription,
+    },
+    event::{Event, EventStatus, LogEvent, Value},
+    internal_events::{aws_s3::sink::S3EventsSent, TemplateRenderingFailed},
+    rusoto::{self, AwsAuthentication, RegionOrEndpoint},
+    sinks::util::{
+        batch::{BatchConfig, BatchSettings},
+        parquet::{ParquetCompression, ParquetWriter, CONTENT_TYPE, EXTENSION},
+        retries::RetryLogic,
+        sink::{ServiceLogic, StdServiceLogic},
+        Concurrency, EncodedEvent, PartitionBatchSink, PartitionBuffer, PartitionInnerBuffer,
+        ServiceBuilderExt, TowerRequestConfig, VecBuffer,
+    },
+    template::Template,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{
+    future::{self, BoxFuture},
+    stream, FutureExt, SinkExt, StreamExt,
+};
+use md5::Digest;
+use rusoto_core::{Region, RusotoError};
+use rusoto_s3::{
+    HeadBucketRequest, PutObjectError, PutObjectOutput, PutObjectRequest, S3Client, S3,
+};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceBuilder};
+use uuid::Uuid;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AwsSecurityLakeSinkConfig {
+    /// The bucket of the data lake, `aws-security-data-lake-{region}-...`.
+    pub bucket: String,
+    /// The name the custom source was registered with.
+    pub source_name: String,
+    /// The version of the custom source, in the layout of Security Lake 2.
+    pub source_version: Option<String>,
+    /// Renders to the ID of the AWS account the events are from.
+    pub account_id: Template,
+    pub ocsf: OcsfConfig,
+    #[serde(default)]
+    pub compression: ParquetCompression,
+    #[serde(flatten)]
+    pub region: RegionOrEndpoint,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    #[serde(default)]
+    pub auth: AwsAuthentication,
+}
+
+inventory::submit! {
+    SinkDescription::new::<AwsSecurityLakeSinkConfig>("aws_security_lake")
+}
+
+impl GenerateConfig for AwsSecurityLakeSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"bucket = "aws-security-data-lake-us-east-1-abcdefghijklmnop"
+            source_name = "vector"
+            account_id = "123456789012"
+            region = "us-east-1"
+            ocsf.class_uid = 1001
+            ocsf.product.name = "Vector"
+            ocsf.product.vendor_name = "Vector""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "aws_security_lake")]
+impl SinkConfig for AwsSecurityLakeSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let client = self.create_client(&cx.proxy)?;
+        let healthcheck = healthcheck(client.clone(), self.bucket.clone()).boxed();
+        let sink = self.new(client, cx)?;
+        Ok((sink, healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "aws_security_lake"
+    }
+}
+
+impl AwsSecurityLakeSinkConfig {
+    fn new(&self, client: S3Client, cx: SinkContext) -> crate::Result<super::VectorSink> {
+        let normalizer = Normalizer::new(&self.ocsf)?;
+        let writer = Arc::new(parquet::writer(&normalizer.schema()?, self.compression)?);
+        let region = Region::try_from(&self.region)?;
+        let partitioner = Partitioner {
+            source_prefix: source_prefix(&self.source_name, self.source_version.as_deref()),
+            region: region.name().to_owned(),
+            account_id: self.account_id.clone(),
+        };
+
+        let request = self.request.unwrap_with(&TowerRequestConfig {
+            concurrency: Concurrency::Fixed(10),
+            rate_limit_num: Some(10),
+            ..Default::default()
+        });
+        // Security Lake recommends objects of at least 256 MB, written at
+        // most every five minutes for each partition. Batches are sized by
+        // the memory of their events, which is several times the files'.
+        let batch = BatchSettings::default()
+            .bytes(500_000_000)
+            .timeout(300)
+            .parse_config(self.batch)?;
+
+        let bucket = self.bucket.clone();
+        let svc = ServiceBuilder::new()
+            .map(move |req| build_request(req, bucket.clone(), &writer))
+            .settings(request, SecurityLakeRetryLogic)
+            .service(SecurityLakeService { client });
+
+        let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+        let sink = PartitionBatchSink::new_with_logic(
+            svc,
+            buffer,
+            batch.timeout,
+            cx.acker(),
+            SecurityLakeServiceLogic,
+        )
+        .with_flat_map(move |event| {
+            stream::iter(encode_event(event, &normalizer, &partitioner)).map(Ok)
+        })
+        .sink_map_err(|error| error!(message = "Sink failed to flush.", %error));
+
+        Ok(super::VectorSink::Sink(Box::new(sink)))
+    }
+
+    fn create_client(&self, proxy: &ProxyConfig) -> crate::Result<S3Client> {
+        let region = Region::try_from(&self.region)?;
+        let client = rusoto::client(proxy)?;
+        let creds = self.auth.build(&region, None)?;
+        Ok(S3Client::new_with(client, creds, region))
+    }
+}
+
+async fn healthcheck(client: S3Client, bucket: String) -> crate::Result<()> {
+    client
+        .head_bucket(HeadBucketRequest {
+            bucket,
+            expected_bucket_owner: None,
+        })
+        .await?;
+    Ok(())
+}
+
+fn source_prefix(source_name: &str, source_version: Option<&str>) -> String {
+    match source_version {
+        Some(version) => format!("ext/{}/{}/", source_name, version),
+        None => format!("ext/{}/", source_name),
+    }
+}
+
+/// Renders the partition of the objects the event is written to.
+struct Partitioner {
+    source_prefix: String,
+    region: String,
+    account_id: Template,
+}
+
+impl Partitioner {
+    fn partition(&self, event: &Event) -> Option<String> {
+        let account_id = self
+            .account_id
+            .render_string(event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some("account_id"),
+                    drop_event: true,
+                });
+            })
+            .ok()?;
+        let timestamp = match event.as_log().get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => Utc::now(),
+        };
+        Some(format!(
+            "{}region={}/accountId={}/eventDay={}/",
+            self.source_prefix,
+            self.region,
+            account_id,
+            timestamp.format("%Y%m%d")
+        ))
+    }
+}
+
+fn encode_event(
+    mut event: Event,
+    normalizer: &Normalizer,
+    partitioner: &Partitioner,
+) -> Option<EncodedEvent<PartitionInnerBuffer<LogEvent, Bytes>>> {
+    let finalizers = event.metadata_mut().take_finalizers();
+    let partition = match partitioner.partition(&event) {
+        Some(partition) => partition,
+        None => {
+            finalizers.update_status(EventStatus::Failed);
+            return None;
+        }
+    };
+
+    let object = normalizer
+        .normalize(&event)
+        .into_iter()
+        .map(|(key, value)| (key, Value::from(value)))
+        .collect::<BTreeMap<_, _>>();
+
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(LogEvent::from(object), partition.into()),
+        finalizers,
+    })
+}
+
+fn build_request(
+    req: PartitionInnerBuffer<Vec<LogEvent>, Bytes>,
+    bucket: String,
+    writer: &ParquetWriter,
+) -> Request {
+    let (logs, partition) = req.into_parts();
+    let key = format!(
+        "{}{}-{}.{}",
+        String::from_utf8_lossy(&partition),
+        Utc::now().timestamp(),
+        Uuid::new_v4().to_hyphenated(),
+        EXTENSION
+    );
+    let body = parquet::encode(logs, writer).map_err(|error| error.to_string());
+
+    debug!(
+        message = "Sending events.",
+        bytes = ?body.as_ref().map(Vec::len).ok(),
+        bucket = ?bucket,
+        key = ?key
+    );
+
+    Request { body, bucket, key }
+}
+
+#[derive(Debug, Clone)]
+struct Request {
+    /// The Parquet file of the batch, or why it couldn't be encoded.
+    body: Result<Vec<u8>, String>,
+    bucket: String,
+    key: String,
+}
+
+#[derive(Clone)]
+struct SecurityLakeService {
+    client: S3Client,
+}
+
+impl Service<Request> for SecurityLakeService {
+    type Response = PutObjectOutput;
+    type Error = SecurityLakeError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let body = match request.body {
+            Ok(body) => body,
+            Err(message) => return future::err(SecurityLakeError::EncodeBatch { message }).boxed(),
+        };
+        let content_md5 = base64::encode(md5::Md5::digest(&body));
+        emit!(S3EventsSent {
+            byte_size: body.len(),
+        });
+
+        let client = self.client.clone();
+        let request = PutObjectRequest {
+            body: Some(body.into()),
+            bucket: request.bucket,
+            key: request.key,
+            content_type: Some(CONTENT_TYPE.to_owned()),
+            content_md5: Some(content_md5),
+            // Security Lake reads the objects of custom sources with the
+            // permissions of the bucket owner.
+            acl: Some("bucket-owner-full-control".to_owned()),
+            ..Default::default()
+        };
+
+        Box::pin(async move {
+            client
+                .put_object(request)
+                .await
+                .map_err(|source| SecurityLakeError::PutObject { source })
+        })
+    }
+}
+
+#[derive(Debug, Snafu)]
+enum SecurityLakeError {
+    #[snafu(display("Failed to encode batch: {}", message))]
+    EncodeBatch { message: String },
+    #[snafu(display("{}", source))]
+    PutObject { source: RusotoError<PutObjectError> },
+}
+
+#[derive(Debug, Clone)]
+struct SecurityLakeRetryLogic;
+
+impl RetryLogic for SecurityLakeRetryLogic {
+    type Error = SecurityLakeError;
+    type Response = PutObjectOutput;
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            SecurityLakeError::EncodeBatch { .. } => false,
+            SecurityLakeError::PutObject { source } => rusoto::is_retriable_error(source),
+        }
+    }
+}
+
+/// Fails the events of batches that couldn't be encoded, rather than
+/// reporting them as errored.
+#[derive(Clone, Debug)]
+struct SecurityLakeServiceLogic;
+
+impl ServiceLogic for SecurityLakeServiceLogic {
+    type Response = PutObjectOutput;
+
+    fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus {
+        match result {
+            Err(error)
+                if matches!(
+                    error.downcast_ref::<SecurityLakeError>(),
+                    Some(SecurityLakeError::EncodeBatch { .. })
+                ) =>
+            {
+                error!(message = "Request failed.", %error);
+                EventStatus::Failed
+            }
+            result => StdServiceLogic::default().result_status(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AwsSecurityLakeSinkConfig>();
+    }
+
+    #[test]
+    fn partitions_by_account_and_day() {
+        let partitioner = Partitioner {
+            source_prefix: source_prefix("vector", Some("2.0")),
+            region: "eu-west-1".into(),
+            account_id: Template::try_from("{{ account }}").unwrap(),
+        };
+        let mut event = Event::from("message");
+        event.as_mut_log().insert(
+            log_schema().timestamp_key(),
+            Utc.ymd(2023, 5, 30).and_hms(23, 59, 59),
+        );
+        assert!(partitioner.partition(&event).is_none());
+
+        event.as_mut_log().insert("account", "123456789012");
+        assert_eq!(
+            partitioner.partition(&event).unwrap(),
+            "ext/vector/2.0/region=eu-west-1/accountId=123456789012/eventDay=20230530/"
+        );
+    }
+    #[tokio::test]
+    async fn fails_batches_that_fail_to_encode() {
+        let mut service = SecurityLakeService {
+            client: S3Client::new(Region::UsEast1),
+        };
+        let request = Request {
+            body: Err("invalid batch".into()),
+            bucket: "bucket".into(),
+            key: "key".into(),
+        };
+
+        let error = service.call(request).await.unwrap_err();
+        assert!(!SecurityLakeRetryLogic.is_retriable_error(&error));
+        assert_eq!(
+            SecurityLakeServiceLogic.result_status(Err(error.into())),
+            EventStatus::Failed
+        );
+    }
+}
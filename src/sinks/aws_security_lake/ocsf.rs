@@ -0,0 +1,509 @@
+//! Normalizes events to a class of the Open Cybersecurity Schema Framework,
+//! as JSON objects nested as the attributes of the class are.
+
+use crate::{
+    config::log_schema,
+    event::{Event, Value},
+    internal_events::{OcsfInvalidValue, TemplateRenderingFailed},
+    template::{Template, TemplateParseError},
+};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use snafu::{ResultExt, Snafu};
+use std::convert::TryFrom;
+
+/// The attribute OCSF uses for activities and severities it has no ID for.
+const OTHER_ID: i64 = 99;
+
+#[derive(Debug, Snafu)]
+pub(super) enum SchemaError {
+    #[snafu(display("invalid template for attribute {:?}: {}", attribute, source))]
+    AttributeTemplate {
+        attribute: String,
+        source: TemplateParseError,
+    },
+    #[snafu(display("attribute {:?} is set by the sink", attribute))]
+    ReservedAttribute { attribute: String },
+    #[snafu(display("attribute {:?} is configured more than once", attribute))]
+    DuplicateAttribute { attribute: String },
+    #[snafu(display(
+        "attribute {:?} can't be both a value and an object of {:?}",
+        attribute,
+        nested
+    ))]
+    ConflictingAttribute { attribute: String, nested: String },
+    #[snafu(display("{} is not the UID of an OCSF class", class_uid))]
+    InvalidClass { class_uid: u32 },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OcsfConfig {
+    /// The class of the events, such as 3002 for authentication.
+    pub class_uid: u32,
+    /// The version of the schema the events conform to.
+    #[serde(default = "default_version")]
+    pub version: String,
+    pub product: ProductConfig,
+    /// Renders to the ID of the activity of the class.
+    pub activity_id: Option<Template>,
+    /// Renders to the name or the ID of the severity.
+    pub severity: Option<Template>,
+    /// Sets `raw_data` to the event as JSON.
+    #[serde(default)]
+    pub raw_data: bool,
+    #[serde(default)]
+    pub attributes: Vec<AttributeConfig>,
+}
+
+/// The product that reported the events, recorded in their metadata.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProductConfig {
+    pub name: String,
+    pub vendor_name: String,
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttributeConfig {
+    /// The path of the attribute, with nested objects separated by dots.
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub attribute_type: AttributeType,
+    /// The field the value is read from, the name of the attribute by default.
+    pub field: Option<String>,
+    /// Renders the value instead of reading it from a field.
+    pub template: Option<String>,
+}
+
+/// The scalar data types of OCSF.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributeType {
+    #[derivative(Default)]
+    String,
+    Integer,
+    Long,
+    Float,
+    Boolean,
+    /// Milliseconds since the Unix epoch.
+    Timestamp,
+}
+
+fn default_version() -> String {
+    "1.1.0".to_owned()
+}
+
+/// The attributes of the class written for every event, by their paths.
+pub(super) type Schema = Vec<(String, AttributeType)>;
+
+/// The attributes the sink sets itself, with their types.
+const BASE_ATTRIBUTES: &[(&str, AttributeType)] = &[
+    ("activity_id", AttributeType::Integer),
+    ("category_uid", AttributeType::Integer),
+    ("class_uid", AttributeType::Integer),
+    ("message", AttributeType::String),
+    ("metadata.product.name", AttributeType::String),
+    ("metadata.product.vendor_name", AttributeType::String),
+    ("metadata.product.version", AttributeType::String),
+    ("metadata.version", AttributeType::String),
+    ("raw_data", AttributeType::String),
+    ("severity_id", AttributeType::Integer),
+    ("time", AttributeType::Timestamp),
+    ("type_uid", AttributeType::Long),
+];
+
+#[derive(Clone, Debug)]
+pub(super) struct Normalizer {
+    config: OcsfConfig,
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Clone, Debug)]
+struct Attribute {
+    path: Vec<String>,
+    attribute_type: AttributeType,
+    source: AttributeSource,
+}
+
+#[derive(Clone, Debug)]
+enum AttributeSource {
+    Field(String),
+    Template(Template),
+}
+
+impl Normalizer {
+    pub(super) fn new(config: &OcsfConfig) -> Result<Self, SchemaError> {
+        // Classes are numbered within their category, from 1000 on.
+        if config.class_uid < 1000 {
+            return Err(SchemaError::InvalidClass {
+                class_uid: config.class_uid,
+            });
+        }
+        let attributes = config
+            .attributes
+            .iter()
+            .map(|attribute| {
+                if BASE_ATTRIBUTES
+                    .iter()
+                    .any(|(name, _)| *name == attribute.name)
+                {
+                    return Err(SchemaError::ReservedAttribute {
+                        attribute: attribute.name.clone(),
+                    });
+                }
+                let source = match &attribute.template {
+                    Some(template) => AttributeSource::Template(
+                        Template::try_from(template.as_str()).context(AttributeTemplate {
+                            attribute: attribute.name.clone(),
+                        })?,
+                    ),
+                    None => AttributeSource::Field(
+                        attribute
+                            .field
+                            .clone()
+                            .unwrap_or_else(|| attribute.name.clone()),
+                    ),
+                };
+                Ok(Attribute {
+                    path: attribute.name.split('.').map(Into::into).collect(),
+                    attribute_type: attribute.attribute_type,
+                    source,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let normalizer = Self {
+            config: config.clone(),
+            attributes,
+        };
+        normalizer.schema()?;
+        Ok(normalizer)
+    }
+
+    /// The attributes written for every event, sorted by their paths, which
+    /// is the order of the columns of the Parquet files.
+    pub(super) fn schema(&self) -> Result<Schema, SchemaError> {
+        let mut schema = BASE_ATTRIBUTES
+            .iter()
+            .map(|(name, attribute_type)| ((*name).to_owned(), *attribute_type))
+            .chain(
+                self.attributes
+                    .iter()
+                    .map(|attribute| (attribute.path.join("."), attribute.attribute_type)),
+            )
+            .collect::<Schema>();
+        schema.sort_by(|(a, _), (b, _)| a.split('.').cmp(b.split('.')));
+
+        for pair in schema.windows(2) {
+            let (previous, next) = (&pair[0].0, &pair[1].0);
+            if previous == next {
+                return Err(SchemaError::DuplicateAttribute {
+                    attribute: next.clone(),
+                });
+            }
+            // Sorted by their segments, the attributes nested in an object
+            // come right after any attribute of the same name.
+            if next
+                .strip_prefix(previous.as_str())
+                .map_or(false, |rest| rest.starts_with('.'))
+            {
+                return Err(SchemaError::ConflictingAttribute {
+                    attribute: previous.clone(),
+                    nested: next.clone(),
+                });
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The event as an object of the class. Values missing or not
+    /// convertible to the type of their attribute are left out.
+    pub(super) fn normalize(&self, event: &Event) -> Map<String, JsonValue> {
+        let log = event.as_log();
+        let time = match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => Utc::now(),
+        };
+        let activity_id = self
+            .config
+            .activity_id
+            .as_ref()
+            .and_then(|template| render(template, event, "activity_id"))
+            .map_or(0, |value| {
+                value.trim().parse::<i64>().unwrap_or_else(|_| {
+                    emit!(OcsfInvalidValue {
+                        attribute: "activity_id",
+                        value: &value,
+                    });
+                    OTHER_ID
+                })
+            });
+        let severity_id = self
+            .config
+            .severity
+            .as_ref()
+            .and_then(|template| render(template, event, "severity"))
+            .map_or(0, |value| {
+                severity_id(value.trim()).unwrap_or_else(|| {
+                    emit!(OcsfInvalidValue {
+                        attribute: "severity_id",
+                        value: &value,
+                    });
+                    OTHER_ID
+                })
+            });
+
+        let class_uid = i64::from(self.config.class_uid);
+        let mut object = Map::new();
+        object.insert("activity_id".into(), activity_id.into());
+        object.insert("category_uid".into(), (class_uid / 1000).into());
+        object.insert("class_uid".into(), class_uid.into());
+        object.insert("severity_id".into(), severity_id.into());
+        object.insert("time".into(), time.timestamp_millis().into());
+        object.insert("type_uid".into(), (class_uid * 100 + activity_id).into());
+        if let Some(message) = log.get(log_schema().message_key()) {
+            object.insert("message".into(), message.to_string_lossy().into());
+        }
+        if self.config.raw_data {
+            if let Ok(raw_data) = serde_json::to_string(log) {
+                object.insert("raw_data".into(), raw_data.into());
+            }
+        }
+
+        let product = &self.config.product;
+        insert(
+            &mut object,
+            &["metadata", "version"],
+            self.config.version.as_str().into(),
+        );
+        insert(
+            &mut object,
+            &["metadata", "product", "name"],
+            product.name.as_str().into(),
+        );
+        insert(
+            &mut object,
+            &["metadata", "product", "vendor_name"],
+            product.vendor_name.as_str().into(),
+        );
+        if let Some(version) = &product.version {
+            insert(
+                &mut object,
+                &["metadata", "product", "version"],
+                version.as_str().into(),
+            );
+        }
+
+        for attribute in &self.attributes {
+            let value = match &attribute.source {
+                AttributeSource::Field(field) => log.get(field).cloned(),
+                AttributeSource::Template(template) => {
+                    render(template, event, &attribute.path.join(".")).map(Value::from)
+                }
+            };
+            if let Some(value) = value.and_then(|value| convert(&value, attribute.attribute_type)) {
+                insert(&mut object, &attribute.path, value);
+            }
+        }
+
+        object
+    }
+}
+
+fn render(template: &Template, event: &Event, field: &str) -> Option<String> {
+    template
+        .render_string(event)
+        .map_err(|error| {
+            emit!(TemplateRenderingFailed {
+                error,
+                field: Some(field),
+                drop_event: false,
+            });
+        })
+        .ok()
+}
+
+/// Sets the value at the path, creating the objects along it.
+fn insert<S: AsRef<str>>(object: &mut Map<String, JsonValue>, path: &[S], value: JsonValue) {
+    let (last, parents) = match path.split_last() {
+        Some(split) => split,
+        None => return,
+    };
+    let mut object = object;
+    for parent in parents {
+        let entry = object
+            .entry(parent.as_ref())
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        object = match entry {
+            JsonValue::Object(nested) => nested,
+            // Schemas are checked for attributes nested in values.
+            _ => return,
+        };
+    }
+    object.insert(last.as_ref().to_owned(), value);
+}
+
+fn convert(value: &Value, attribute_type: AttributeType) -> Option<JsonValue> {
+    match (attribute_type, value) {
+        (_, Value::Null) => None,
+        (AttributeType::String, Value::Timestamp(timestamp)) => Some(
+            timestamp
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                .into(),
+        ),
+        (AttributeType::String, value) => Some(value.to_string_lossy().into()),
+        (AttributeType::Integer, Value::Integer(value)) => {
+            i32::try_from(*value).ok().map(Into::into)
+        }
+        (AttributeType::Long, Value::Integer(value)) => Some((*value).into()),
+        (AttributeType::Float, Value::Float(value)) => Some((*value).into()),
+        (AttributeType::Float, Value::Integer(value)) => Some((*value as f64).into()),
+        (AttributeType::Boolean, Value::Boolean(value)) => Some((*value).into()),
+        (AttributeType::Timestamp, Value::Timestamp(timestamp)) => {
+            Some(timestamp.timestamp_millis().into())
+        }
+        (AttributeType::Timestamp, Value::Integer(millis)) => Some((*millis).into()),
+        (AttributeType::Integer, value) => value.parse_text::<i32>().map(Into::into),
+        (AttributeType::Long, value) => value.parse_text::<i64>().map(Into::into),
+        (AttributeType::Float, value) => value.parse_text::<f64>().map(Into::into),
+        (AttributeType::Boolean, value) => value.parse_text::<bool>().map(Into::into),
+        (AttributeType::Timestamp, value) => value
+            .parse_timestamp()
+            .map(|timestamp| timestamp.timestamp_millis().into()),
+    }
+}
+
+/// The IDs of the severities of OCSF, by name or by number.
+fn severity_id(value: &str) -> Option<i64> {
+    if let Ok(id) = value.parse::<i64>() {
+        return ((0..=6).contains(&id) || id == OTHER_ID).then(|| id);
+    }
+    let id = match value.to_ascii_lowercase().as_str() {
+        "unknown" => 0,
+        "informational" | "info" => 1,
+        "low" => 2,
+        "medium" => 3,
+        "high" => 4,
+        "critical" => 5,
+        "fatal" => 6,
+        "other" => OTHER_ID,
+        _ => return None,
+    };
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn config() -> OcsfConfig {
+        toml::from_str(
+            r#"
+            class_uid = 3002
+            activity_id = "{{ activity }}"
+            severity = "{{ level }}"
+            product.name = "sshd"
+            product.vendor_name = "OpenBSD"
+
+            [[attributes]]
+            name = "user.name"
+            field = "user"
+
+            [[attributes]]
+            name = "user.uid"
+            field = "uid"
+            type = "long"
+
+            [[attributes]]
+            name = "is_mfa"
+            field = "mfa"
+            type = "boolean"
+
+            [[attributes]]
+            name = "src_endpoint.ip"
+            template = "{{ client.ip }}"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn normalizes_events() {
+        let mut event = Event::from("Accepted publickey for root");
+        let log = event.as_mut_log();
+        log.insert(
+            log_schema().timestamp_key(),
+            Utc.ymd(2023, 5, 30).and_hms_milli(12, 0, 0, 250),
+        );
+        log.insert("activity", "1");
+        log.insert("level", "Low");
+        log.insert("user", "root");
+        log.insert("uid", "0");
+        log.insert("mfa", "maybe");
+        log.insert("client.ip", "10.0.0.1");
+
+        let object = Normalizer::new(&config()).unwrap().normalize(&event);
+        assert_eq!(
+            JsonValue::Object(object),
+            json!({
+                "activity_id": 1,
+                "category_uid": 3,
+                "class_uid": 3002,
+                "message": "Accepted publickey for root",
+                "metadata": {
+                    "product": {"name": "sshd", "vendor_name": "OpenBSD"},
+                    "version": "1.1.0",
+                },
+                "severity_id": 2,
+                "src_endpoint": {"ip": "10.0.0.1"},
+                "time": 1685448000250i64,
+                "type_uid": 300201,
+                "user": {"name": "root", "uid": 0},
+            })
+        );
+    }
+
+    #[test]
+    fn falls_back_to_other_ids() {
+        let mut event = Event::from("message");
+        event.as_mut_log().insert("activity", "login");
+        event.as_mut_log().insert("level", "bogus");
+
+        let object = Normalizer::new(&config()).unwrap().normalize(&event);
+        assert_eq!(object["activity_id"], 99);
+        assert_eq!(object["severity_id"], 99);
+        assert_eq!(object["type_uid"], 300299);
+    }
+
+    #[test]
+    fn sorts_and_checks_schema() {
+        let schema = Normalizer::new(&config()).unwrap().schema().unwrap();
+        let names = schema
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(&names[..3], &["activity_id", "category_uid", "class_uid"]);
+        assert!(names.contains(&"src_endpoint.ip"));
+
+        let mut config = config();
+        config.attributes[0].name = "user".into();
+        assert!(matches!(
+            Normalizer::new(&config),
+            Err(SchemaError::ConflictingAttribute { .. })
+        ));
+
+        let mut config = self::config();
+        config.attributes[0].name = "time".into();
+        assert!(matches!(
+            Normalizer::new(&config),
+            Err(SchemaError::ReservedAttribute { .. })
+        ));
+    }
+}
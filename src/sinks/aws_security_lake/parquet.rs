@@ -0,0 +1,93 @@
+//! Parquet encoding of batches of OCSF objects, nested as the attributes of
+//! their class are. Objects are buffered as events, and the batch is laid
+//! out as columns when it is sent.
+
+use super::ocsf::{AttributeType, Schema};
+use crate::{
+    event::{EventBatch, LogEvent},
+    sinks::util::parquet::{ParquetColumn, ParquetCompression, ParquetType, ParquetWriter},
+};
+use std::num::NonZeroUsize;
+
+const ROW_GROUP_SIZE: usize = 100_000;
+
+impl From<AttributeType> for ParquetType {
+    fn from(attribute_type: AttributeType) -> Self {
+        match attribute_type {
+            AttributeType::String => Self::String,
+            AttributeType::Integer => Self::Int32,
+            AttributeType::Long => Self::Int64,
+            AttributeType::Float => Self::Double,
+            AttributeType::Boolean => Self::Boolean,
+            AttributeType::Timestamp => Self::TimestampMillis,
+        }
+    }
+}
+
+/// The writer of the files of the schema. Objects are optional groups and
+/// attributes optional columns, with nulls for missing values.
+pub(super) fn writer(
+    schema: &Schema,
+    compression: ParquetCompression,
+) -> parquet::errors::Result<ParquetWriter> {
+    let columns = schema
+        .iter()
+        .map(|(name, attribute_type)| ParquetColumn::nested(name, (*attribute_type).into()))
+        .collect();
+    ParquetWriter::new(
+        columns,
+        compression,
+        NonZeroUsize::new(ROW_GROUP_SIZE).unwrap(),
+    )
+}
+
+/// Converts a batch of objects to a Parquet file.
+pub(super) fn encode(logs: Vec<LogEvent>, writer: &ParquetWriter) -> crate::Result<Vec<u8>> {
+    Ok(writer.write(&EventBatch::from_logs(logs))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::aws_s3::parquet::decode_lines;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+        util::cursor::SliceableCursor,
+    };
+
+    #[test]
+    fn encodes_nested_attributes() {
+        let lines = br#"{"class_uid":3002,"time":1685448000250,"user":{"name":"root","uid":0}}
+{"class_uid":3002,"time":1685448001000,"user":{"uid":1000},"is_mfa":true}
+"#;
+        let schema = vec![
+            ("class_uid".to_owned(), AttributeType::Integer),
+            ("is_mfa".to_owned(), AttributeType::Boolean),
+            ("time".to_owned(), AttributeType::Timestamp),
+            ("user.name".to_owned(), AttributeType::String),
+            ("user.uid".to_owned(), AttributeType::Long),
+        ];
+
+        let writer = writer(&schema, ParquetCompression::Gzip).unwrap();
+        let data = encode(decode_lines(lines).unwrap(), &writer).unwrap();
+        let reader = SerializedFileReader::new(SliceableCursor::new(data)).unwrap();
+        let fields = reader.metadata().file_metadata().schema().get_fields();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[3].get_fields().len(), 2);
+
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get_int(0).unwrap(), 3002);
+        assert!(rows[0].get_bool(1).is_err());
+        assert_eq!(rows[0].get_timestamp_millis(2).unwrap(), 1685448000250);
+        let user = rows[0].get_group(3).unwrap();
+        assert_eq!(user.get_string(0).unwrap(), "root");
+        assert_eq!(user.get_long(1).unwrap(), 0);
+
+        assert!(rows[1].get_bool(1).unwrap());
+        let user = rows[1].get_group(3).unwrap();
+        assert!(user.get_string(0).is_err());
+        assert_eq!(user.get_long(1).unwrap(), 1000);
+    }
+}
@@ -0,0 +1,201 @@
+use super::service::{AdxError, BuildRequest, ParseResponse, ReadResponse, SendRequest};
+use crate::http::HttpClient;
+use http::{header::CONTENT_TYPE, Request};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::time::{Duration, Instant};
+
+/// Tokens are refreshed this long before they expire, so that a request
+/// doesn't go out with a token expiring on the way.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+const IMDS_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+fn default_authority_host() -> String {
+    "https://login.microsoftonline.com".into()
+}
+
+/// How the sink gets Azure Active Directory tokens for the cluster.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields, rename_all = "snake_case", tag = "strategy")]
+pub enum AzureAdAuth {
+    /// The client credentials flow of an application registration.
+    ClientSecret {
+        tenant_id: String,
+        client_id: String,
+        client_secret: String,
+        #[serde(default = "default_authority_host")]
+        authority_host: String,
+    },
+    /// The managed identity of the Azure resource Vector runs on, system
+    /// assigned unless a client ID is set.
+    ManagedIdentity { client_id: Option<String> },
+}
+
+/// An access token and when it's due for a refresh.
+struct Token {
+    header: String,
+    refresh_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// AAD returns a number of seconds and the instance metadata service a
+    /// string of it.
+    expires_in: serde_json::Value,
+}
+
+/// Gets tokens for a resource and caches them until they're close to
+/// expiring.
+pub(super) struct TokenProvider {
+    client: HttpClient,
+    auth: AzureAdAuth,
+    resource: String,
+    token: tokio::sync::Mutex<Option<Token>>,
+}
+
+impl TokenProvider {
+    pub(super) fn new(client: HttpClient, auth: AzureAdAuth, resource: &str) -> Self {
+        Self {
+            client,
+            auth,
+            resource: resource.trim_end_matches('/').to_owned(),
+            token: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The value of the `Authorization` header.
+    pub(super) async fn authorization(&self) -> Result<String, AdxError> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token
+            .as_ref()
+            .filter(|token| token.refresh_at > Instant::now())
+        {
+            return Ok(token.header.clone());
+        }
+
+        let fetched = self.fetch().await?;
+        let header = fetched.header.clone();
+        *token = Some(fetched);
+        Ok(header)
+    }
+
+    async fn fetch(&self) -> Result<Token, AdxError> {
+        let request = match &self.auth {
+            AzureAdAuth::ClientSecret {
+                tenant_id,
+                client_id,
+                client_secret,
+                authority_host,
+            } => {
+                let body = url::form_urlencoded::Serializer::new(String::new())
+                    .append_pair("grant_type", "client_credentials")
+                    .append_pair("client_id", client_id)
+                    .append_pair("client_secret", client_secret)
+                    .append_pair("scope", &format!("{}/.default", self.resource))
+                    .finish();
+                Request::post(format!(
+                    "{}/{}/oauth2/v2.0/token",
+                    authority_host.trim_end_matches('/'),
+                    tenant_id
+                ))
+                .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(body))
+            }
+            AzureAdAuth::ManagedIdentity { client_id } => {
+                let mut query = url::form_urlencoded::Serializer::new(String::new());
+                query
+                    .append_pair("api-version", "2018-02-01")
+                    .append_pair("resource", &self.resource);
+                if let Some(client_id) = client_id {
+                    query.append_pair("client_id", client_id);
+                }
+                Request::get(format!("{}?{}", IMDS_ENDPOINT, query.finish()))
+                    .header("Metadata", "true")
+                    .body(Body::empty())
+            }
+        }
+        .context(BuildRequest)?;
+
+        let response = self.client.send(request).await.context(SendRequest)?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .context(ReadResponse)?;
+        if !status.is_success() {
+            return Err(AdxError::Token {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            });
+        }
+
+        let response: TokenResponse = serde_json::from_slice(&body).context(ParseResponse)?;
+        let expires_in = match &response.expires_in {
+            serde_json::Value::Number(number) => number.as_u64(),
+            serde_json::Value::String(string) => string.parse().ok(),
+            _ => None,
+        }
+        .unwrap_or(0);
+        Ok(Token {
+            header: format!("Bearer {}", response.access_token),
+            refresh_at: Instant::now() + refresh_after(Duration::from_secs(expires_in)),
+        })
+    }
+}
+
+/// How long a token is used before it's refreshed, which is half of its
+/// lifetime for the tokens living less than twice the margin.
+fn refresh_after(expires_in: Duration) -> Duration {
+    if expires_in > REFRESH_MARGIN * 2 {
+        expires_in - REFRESH_MARGIN
+    } else {
+        expires_in / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_before_expiry() {
+        assert_eq!(
+            refresh_after(Duration::from_secs(3600)),
+            Duration::from_secs(3300)
+        );
+        assert_eq!(
+            refresh_after(Duration::from_secs(300)),
+            Duration::from_secs(150)
+        );
+        assert_eq!(
+            refresh_after(Duration::from_secs(0)),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn parses_auth_strategies() {
+        let auth: AzureAdAuth = toml::from_str(
+            r#"
+            strategy = "client_secret"
+            tenant_id = "tenant"
+            client_id = "client"
+            client_secret = "secret"
+            "#,
+        )
+        .unwrap();
+        match auth {
+            AzureAdAuth::ClientSecret { authority_host, .. } => {
+                assert_eq!(authority_host, "https://login.microsoftonline.com")
+            }
+            _ => panic!("unexpected strategy"),
+        }
+
+        let auth: AzureAdAuth = toml::from_str(r#"strategy = "managed_identity""#).unwrap();
+        assert!(matches!(
+            auth,
+            AzureAdAuth::ManagedIdentity { client_id: None }
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+//! Ingests log events into Azure Data Explorer (Kusto) tables, as batches of
+//! newline-delimited JSON. Batches are either queued, uploaded as blobs to
+//! the ingestion service which ingests them later on, or streamed to the
+//! cluster, which ingests them before answering.
+
+mod auth;
+mod service;
+
+pub use self::auth::AzureAdAuth;
+use self::{
+    auth::TokenProvider,
+    service::{AdxRetryLogic, AdxService, IngestRequest},
+};
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    http::HttpClient,
+    internal_events::TemplateRenderingFailed,
+    sinks::{
+        util::{
+            encoding::{EncodingConfigWithDefault, EncodingConfiguration},
+            BatchConfig, BatchSettings, Buffer, Compression, EncodedEvent, PartitionBatchSink,
+            PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig,
+        },
+        Healthcheck, VectorSink,
+    },
+    template::Template,
+    tls::{TlsOptions, TlsSettings},
+};
+use bytesize::ByteSize;
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::convert::TryFrom;
+use tower::ServiceBuilder;
+
+/// Streaming ingestion takes up to 4MB of uncompressed data per request.
+const MAX_STREAMING_BATCH_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AzureDataExplorerConfig {
+    /// The URI of the cluster, such as
+    /// `https://mycluster.westeurope.kusto.windows.net`.
+    pub endpoint: String,
+    /// The URI of the ingestion service of the cluster, used for queued
+    /// ingestion. Defaults to the cluster's with `ingest-` prefixed to its
+    /// host.
+    pub ingest_endpoint: Option<String>,
+    pub database: String,
+    pub table: String,
+    /// The name of a JSON ingestion mapping of the table.
+    pub mapping: Option<String>,
+    #[serde(default)]
+    pub ingestion: Ingestion,
+    pub auth: AzureAdAuth,
+    #[serde(default = "Compression::gzip_default")]
+    pub compression: Compression,
+    #[serde(
+        skip_serializing_if = "crate::serde::skip_serializing_if_default",
+        default
+    )]
+    pub encoding: EncodingConfigWithDefault<Encoding>,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<TlsOptions>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Ingestion {
+    #[derivative(Default)]
+    Queued,
+    Streaming,
+}
+
+impl Ingestion {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Streaming => "streaming",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Default,
+}
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display(
+        "Batch size {} is too big for streaming ingestion, max is {}",
+        size,
+        max
+    ))]
+    BatchTooLarge { size: ByteSize, max: ByteSize },
+    #[snafu(display(
+        "Endpoint {:?} has no host to derive the ingestion endpoint from",
+        endpoint
+    ))]
+    NoIngestHost { endpoint: String },
+}
+
+inventory::submit! {
+    SinkDescription::new::<AzureDataExplorerConfig>("azure_data_explorer")
+}
+
+impl GenerateConfig for AzureDataExplorerConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"endpoint = "https://mycluster.westeurope.kusto.windows.net"
+            database = "logs"
+            table = "events"
+            auth.strategy = "client_secret"
+            auth.tenant_id = "${AZURE_TENANT_ID}"
+            auth.client_id = "${AZURE_CLIENT_ID}"
+            auth.client_secret = "${AZURE_CLIENT_SECRET}""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "azure_data_explorer")]
+impl SinkConfig for AzureDataExplorerConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        // Queued batches make blobs, which the ingestion service aggregates
+        // further, so they are larger and wait longer than streamed batches.
+        let batch = match self.ingestion {
+            Ingestion::Queued => BatchSettings::default()
+                .bytes(bytesize::mib(10u64))
+                .timeout(30),
+            Ingestion::Streaming => BatchSettings::default()
+                .bytes(bytesize::mib(1u64))
+                .timeout(1),
+        }
+        .parse_config(self.batch)?;
+        let batch_bytes = batch.size.bytes as u64;
+        if self.ingestion == Ingestion::Streaming && batch_bytes > MAX_STREAMING_BATCH_SIZE {
+            return Err(BuildError::BatchTooLarge {
+                size: ByteSize::b(batch_bytes),
+                max: ByteSize::b(MAX_STREAMING_BATCH_SIZE),
+            }
+            .into());
+        }
+
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+        let tls_settings = TlsSettings::from_options(&self.tls)?;
+        let client = HttpClient::new(tls_settings, cx.proxy())?;
+
+        let endpoint = self.service_endpoint()?;
+        let token = TokenProvider::new(client.clone(), self.auth.clone(), &endpoint);
+        let service = AdxService::new(
+            client,
+            endpoint,
+            self.database.clone(),
+            self.ingestion,
+            self.compression,
+            token,
+        );
+        let healthcheck = service::healthcheck(service.clone()).boxed();
+
+        let table = Template::try_from(self.table.as_str())?;
+        let mapping = self
+            .mapping
+            .as_deref()
+            .map(Template::try_from)
+            .transpose()?;
+        let encoding = self.encoding.clone();
+
+        let svc = ServiceBuilder::new()
+            .map(build_request)
+            .settings(request, AdxRetryLogic)
+            .service(service);
+
+        let buffer = PartitionBuffer::new(Buffer::new(batch.size, self.compression));
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .with_flat_map(move |event| {
+                stream::iter(encode_event(event, &table, mapping.as_ref(), &encoding)).map(Ok)
+            })
+            .sink_map_err(
+                |error| error!(message = "Fatal azure_data_explorer sink error.", %error),
+            );
+
+        Ok((VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "azure_data_explorer"
+    }
+}
+
+impl AzureDataExplorerConfig {
+    /// The endpoint the batches are sent to, which is the cluster for
+    /// streaming ingestion and its ingestion service for queued ingestion.
+    fn service_endpoint(&self) -> crate::Result<String> {
+        let endpoint = self.endpoint.trim_end_matches('/');
+        match (self.ingestion, &self.ingest_endpoint) {
+            (Ingestion::Streaming, _) => Ok(endpoint.to_owned()),
+            (Ingestion::Queued, Some(ingest)) => Ok(ingest.trim_end_matches('/').to_owned()),
+            (Ingestion::Queued, None) => {
+                let uri: Uri = endpoint.parse()?;
+                match (uri.scheme_str(), uri.authority()) {
+                    (Some(scheme), Some(authority)) => {
+                        Ok(format!("{}://ingest-{}", scheme, authority))
+                    }
+                    _ => Err(BuildError::NoIngestHost {
+                        endpoint: endpoint.to_owned(),
+                    }
+                    .into()),
+                }
+            }
+        }
+    }
+}
+
+/// The table a batch is ingested into, and the mapping it's ingested with.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct PartitionKey {
+    table: String,
+    mapping: Option<String>,
+}
+
+fn encode_event(
+    mut event: Event,
+    table: &Template,
+    mapping: Option<&Template>,
+    encoding: &EncodingConfigWithDefault<Encoding>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, PartitionKey>>> {
+    let render = |template: &Template, field| {
+        template
+            .render_string(&event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    };
+    let key = PartitionKey {
+        table: render(table, "table")?,
+        mapping: match mapping {
+            Some(mapping) => Some(render(mapping, "mapping")?),
+            None => None,
+        },
+    };
+
+    encoding.apply_rules(&mut event);
+    let mut log = event.into_log();
+    let mut body = serde_json::to_vec(&log).expect("Events should be valid json!");
+    body.push(b'\n');
+
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(body, key),
+        finalizers: log.metadata_mut().take_finalizers(),
+    })
+}
+
+fn build_request(partition: PartitionInnerBuffer<Vec<u8>, PartitionKey>) -> IngestRequest {
+    let (body, key) = partition.into_parts();
+    IngestRequest {
+        table: key.table,
+        mapping: key.mapping,
+        body: body.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AzureDataExplorerConfig>();
+    }
+
+    fn config(extra: &str) -> AzureDataExplorerConfig {
+        toml::from_str(&format!(
+            r#"
+            endpoint = "https://mycluster.westeurope.kusto.windows.net/"
+            database = "logs"
+            table = "events"
+            auth.strategy = "managed_identity"
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn derives_ingest_endpoint() {
+        assert_eq!(
+            config("").service_endpoint().unwrap(),
+            "https://ingest-mycluster.westeurope.kusto.windows.net"
+        );
+        assert_eq!(
+            config(r#"ingest_endpoint = "http://localhost:8080/""#)
+                .service_endpoint()
+                .unwrap(),
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            config(r#"ingestion = "streaming""#)
+                .service_endpoint()
+                .unwrap(),
+            "https://mycluster.westeurope.kusto.windows.net"
+        );
+    }
+
+    #[test]
+    fn partitions_by_table_and_mapping() {
+        let table = Template::try_from("{{ kind }}_events").unwrap();
+        let mapping = Template::try_from("{{ kind }}_mapping").unwrap();
+        let encoding = EncodingConfigWithDefault::default();
+
+        let mut log = LogEvent::default();
+        log.insert("kind", "audit");
+        let (body, key) = encode_event(log.into(), &table, Some(&mapping), &encoding)
+            .unwrap()
+            .item
+            .into_parts();
+        assert_eq!(body, b"{\"kind\":\"audit\"}\n");
+        assert_eq!(
+            key,
+            PartitionKey {
+                table: "audit_events".into(),
+                mapping: Some("audit_mapping".into()),
+            }
+        );
+
+        assert!(encode_event(Event::from("hi"), &table, None, &encoding).is_none());
+    }
+}
@@ -0,0 +1,518 @@
+use super::{auth::TokenProvider, Ingestion};
+use crate::{
+    http::{HttpClient, HttpError},
+    internal_events::{AzureDataExplorerBatchIngested, AzureDataExplorerResourcesFetched},
+    sinks::util::{retries::RetryLogic, Compression},
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use http::{
+    header::{ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE},
+    Request, StatusCode,
+};
+use hyper::Body;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::Service;
+use uuid::Uuid;
+
+/// The storage of the ingestion resources rotates its SAS tokens, so the
+/// resources are fetched again after a while.
+const RESOURCES_TTL: Duration = Duration::from_secs(60 * 60);
+const STORAGE_VERSION: &str = "2019-12-12";
+const FORMAT: &str = "multijson";
+/// The characters escaped in the names of databases, tables and blobs in
+/// paths, which are the reserved characters of URIs.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(super)))]
+pub(super) enum AdxError {
+    #[snafu(display("Failed to build request: {}", source))]
+    BuildRequest { source: http::Error },
+    #[snafu(display("Failed to send request: {}", source))]
+    SendRequest { source: HttpError },
+    #[snafu(display("Failed to read response: {}", source))]
+    ReadResponse { source: hyper::Error },
+    #[snafu(display("Failed to parse response: {}", source))]
+    ParseResponse { source: serde_json::Error },
+    #[snafu(display("Failed to get a token, status {}: {}", status, body))]
+    Token { status: StatusCode, body: String },
+    #[snafu(display("Unexpected status {}: {}", status, body))]
+    UnexpectedStatus { status: StatusCode, body: String },
+    #[snafu(display("Storage of the ingestion resources answered {}: {}", status, body))]
+    Storage { status: StatusCode, body: String },
+    #[snafu(display("The cluster has no ingestion resources of type {}", kind))]
+    MissingResources { kind: &'static str },
+}
+
+/// A batch of newline-delimited JSON events, compressed with the configured
+/// compression, to ingest into a table.
+#[derive(Clone, Debug)]
+pub(super) struct IngestRequest {
+    pub(super) table: String,
+    pub(super) mapping: Option<String>,
+    pub(super) body: Bytes,
+}
+
+/// The queues and containers of the ingestion service with their SAS
+/// tokens, and the token authorizing the service to ingest on behalf of the
+/// sink.
+struct Resources {
+    queues: Vec<String>,
+    containers: Vec<String>,
+    authorization_context: String,
+    fetched_at: Instant,
+}
+
+#[derive(Clone)]
+pub(super) struct AdxService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: HttpClient,
+    /// The cluster for streaming ingestion, or its ingestion service for
+    /// queued ingestion.
+    endpoint: String,
+    database: String,
+    ingestion: Ingestion,
+    compression: Compression,
+    token: TokenProvider,
+    resources: tokio::sync::Mutex<Option<Arc<Resources>>>,
+    /// Spreads the batches over the queues and containers.
+    next: AtomicUsize,
+}
+
+impl AdxService {
+    pub(super) fn new(
+        client: HttpClient,
+        endpoint: String,
+        database: String,
+        ingestion: Ingestion,
+        compression: Compression,
+        token: TokenProvider,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                endpoint,
+                database,
+                ingestion,
+                compression,
+                token,
+                resources: tokio::sync::Mutex::new(None),
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    async fn ingest(self, request: IngestRequest) -> Result<(), AdxError> {
+        let byte_size = request.body.len();
+        match self.inner.ingestion {
+            Ingestion::Queued => self.ingest_queued(&request).await?,
+            Ingestion::Streaming => self.ingest_streaming(&request).await?,
+        }
+        emit!(AzureDataExplorerBatchIngested {
+            ingestion: self.inner.ingestion.as_str(),
+            table: &request.table,
+            byte_size,
+        });
+        Ok(())
+    }
+
+    /// Sends the batch to the streaming ingestion endpoint of the cluster,
+    /// which writes it to the table before answering.
+    async fn ingest_streaming(&self, request: &IngestRequest) -> Result<(), AdxError> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("streamFormat", FORMAT);
+        if let Some(mapping) = &request.mapping {
+            query.append_pair("mappingName", mapping);
+        }
+        let uri = format!(
+            "{}/v1/rest/ingest/{}/{}?{}",
+            self.inner.endpoint,
+            utf8_percent_encode(&self.inner.database, PATH_SEGMENT),
+            utf8_percent_encode(&request.table, PATH_SEGMENT),
+            query.finish()
+        );
+
+        let mut builder = Request::post(uri)
+            .header(AUTHORIZATION, self.inner.token.authorization().await?)
+            .header(CONTENT_TYPE, "application/json; charset=utf-8")
+            .header("x-ms-client-request-id", client_request_id());
+        if let Some(encoding) = self.inner.compression.content_encoding() {
+            builder = builder.header(CONTENT_ENCODING, encoding);
+        }
+        let request = builder
+            .body(Body::from(request.body.clone()))
+            .context(BuildRequest)?;
+        self.send(request).await.map(|_| ())
+    }
+
+    /// Uploads the batch to a blob of the ingestion service and posts a
+    /// message pointing at it to one of its queues. The service ingests the
+    /// blob later on, aggregating it with the other blobs of the table.
+    async fn ingest_queued(&self, request: &IngestRequest) -> Result<(), AdxError> {
+        let resources = self.resources().await?;
+        let index = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        let container = &resources.containers[index % resources.containers.len()];
+        let queue = &resources.queues[index % resources.queues.len()];
+
+        let id = Uuid::new_v4();
+        let extension = match self.inner.compression {
+            Compression::None => FORMAT.to_owned(),
+            Compression::Gzip(_) => format!("{}.gz", FORMAT),
+        };
+        let blob_name = format!(
+            "{}__{}__{}.{}",
+            self.inner.database,
+            request.table,
+            id.to_hyphenated(),
+            extension
+        );
+        let blob_name = utf8_percent_encode(&blob_name, PATH_SEGMENT).to_string();
+        let blob_uri = storage_uri(container, &blob_name);
+
+        let upload = Request::put(&blob_uri)
+            .header("x-ms-blob-type", "BlockBlob")
+            .header("x-ms-version", STORAGE_VERSION)
+            .body(Body::from(request.body.clone()))
+            .context(BuildRequest)?;
+        self.send_storage(upload).await?;
+
+        let message = IngestionMessage {
+            id: id.to_hyphenated().to_string(),
+            blob_path: &blob_uri,
+            database_name: &self.inner.database,
+            table_name: &request.table,
+            retain_blob_on_success: false,
+            flush_immediately: false,
+            report_level: 0,
+            report_method: 0,
+            additional_properties: AdditionalProperties {
+                authorization_context: &resources.authorization_context,
+                format: FORMAT,
+                ingestion_mapping_reference: request.mapping.as_deref(),
+                ingestion_mapping_type: request.mapping.as_ref().map(|_| "json"),
+            },
+        };
+        let message = serde_json::to_vec(&message).expect("Message should be valid json!");
+        let body = format!(
+            "<QueueMessage><MessageText>{}</MessageText></QueueMessage>",
+            base64::encode(&message)
+        );
+        let post = Request::post(storage_uri(queue, "messages"))
+            .header("x-ms-version", STORAGE_VERSION)
+            .header(CONTENT_TYPE, "application/xml")
+            .body(Body::from(body))
+            .context(BuildRequest)?;
+        self.send_storage(post).await
+    }
+
+    /// The ingestion resources, fetched again once they're old enough for
+    /// their SAS tokens to be rotated.
+    async fn resources(&self) -> Result<Arc<Resources>, AdxError> {
+        let mut resources = self.inner.resources.lock().await;
+        if let Some(resources) = resources
+            .as_ref()
+            .filter(|resources| resources.fetched_at.elapsed() < RESOURCES_TTL)
+        {
+            return Ok(Arc::clone(resources));
+        }
+
+        let fetched = Arc::new(self.fetch_resources().await?);
+        emit!(AzureDataExplorerResourcesFetched {
+            queues: fetched.queues.len(),
+            containers: fetched.containers.len(),
+        });
+        *resources = Some(Arc::clone(&fetched));
+        Ok(fetched)
+    }
+
+    async fn fetch_resources(&self) -> Result<Resources, AdxError> {
+        let mut queues = Vec::new();
+        let mut containers = Vec::new();
+        for row in self.management(".get ingestion resources").await? {
+            match (
+                row.get(0).and_then(|v| v.as_str()),
+                row.get(1).and_then(|v| v.as_str()),
+            ) {
+                (Some("SecuredReadyForAggregationQueue"), Some(uri)) => queues.push(uri.into()),
+                (Some("TempStorage"), Some(uri)) => containers.push(uri.into()),
+                _ => (),
+            }
+        }
+        if queues.is_empty() {
+            return Err(AdxError::MissingResources {
+                kind: "SecuredReadyForAggregationQueue",
+            });
+        }
+        if containers.is_empty() {
+            return Err(AdxError::MissingResources {
+                kind: "TempStorage",
+            });
+        }
+
+        let authorization_context = self
+            .management(".get kusto identity token")
+            .await?
+            .into_iter()
+            .next()
+            .and_then(|row| row.into_iter().next())
+            .and_then(|value| value.as_str().map(Into::into))
+            .ok_or(AdxError::MissingResources {
+                kind: "KustoIdentityToken",
+            })?;
+
+        Ok(Resources {
+            queues,
+            containers,
+            authorization_context,
+            fetched_at: Instant::now(),
+        })
+    }
+
+    /// Runs a management command, returning the rows of its first table.
+    async fn management(&self, command: &str) -> Result<Vec<Vec<serde_json::Value>>, AdxError> {
+        let body = serde_json::json!({ "db": self.inner.database, "csl": command });
+        let request = Request::post(format!("{}/v1/rest/mgmt", self.inner.endpoint))
+            .header(AUTHORIZATION, self.inner.token.authorization().await?)
+            .header(CONTENT_TYPE, "application/json; charset=utf-8")
+            .header(ACCEPT, "application/json")
+            .header("x-ms-client-request-id", client_request_id())
+            .body(Body::from(body.to_string()))
+            .context(BuildRequest)?;
+        let body = self.send(request).await?;
+
+        let response: ManagementResponse = serde_json::from_slice(&body).context(ParseResponse)?;
+        Ok(response
+            .tables
+            .into_iter()
+            .next()
+            .map(|table| table.rows)
+            .unwrap_or_default())
+    }
+
+    async fn send(&self, request: Request<Body>) -> Result<Bytes, AdxError> {
+        let response = self.inner.client.send(request).await.context(SendRequest)?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .context(ReadResponse)?;
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(AdxError::UnexpectedStatus {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            })
+        }
+    }
+
+    /// Sends a request to the storage of the ingestion resources, dropping
+    /// the resources if it fails, as their SAS tokens may have expired.
+    async fn send_storage(&self, request: Request<Body>) -> Result<(), AdxError> {
+        match self.send(request).await {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                *self.inner.resources.lock().await = None;
+                Err(match error {
+                    AdxError::UnexpectedStatus { status, body } => {
+                        AdxError::Storage { status, body }
+                    }
+                    error => error,
+                })
+            }
+        }
+    }
+}
+
+impl Service<IngestRequest> for AdxService {
+    type Response = ();
+    type Error = AdxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: IngestRequest) -> Self::Future {
+        Box::pin(self.clone().ingest(request))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct AdxRetryLogic;
+
+impl RetryLogic for AdxRetryLogic {
+    type Error = AdxError;
+    type Response = ();
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            AdxError::SendRequest { .. } | AdxError::ReadResponse { .. } => true,
+            AdxError::Token { status, .. } | AdxError::UnexpectedStatus { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            // The resources are fetched again with fresh SAS tokens.
+            AdxError::Storage { status, .. } => {
+                status.is_server_error()
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::FORBIDDEN
+                    || *status == StatusCode::NOT_FOUND
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Checks the sink can authenticate and reach the cluster, by fetching the
+/// ingestion resources for queued ingestion, or the version of the cluster
+/// for streaming ingestion.
+pub(super) async fn healthcheck(service: AdxService) -> crate::Result<()> {
+    match service.inner.ingestion {
+        Ingestion::Queued => service.fetch_resources().await.map(|_| ())?,
+        Ingestion::Streaming => service.management(".show version").await.map(|_| ())?,
+    }
+    Ok(())
+}
+
+/// The URI of a resource under a container or queue, whose URI holds its SAS
+/// token in the query.
+fn storage_uri(base: &str, path: &str) -> String {
+    match base.find('?') {
+        Some(index) => format!("{}/{}{}", &base[..index], path, &base[index..]),
+        None => format!("{}/{}", base, path),
+    }
+}
+
+fn client_request_id() -> String {
+    format!("Vector;{}", Uuid::new_v4().to_hyphenated())
+}
+
+/// The message telling the ingestion service about a blob to ingest.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct IngestionMessage<'a> {
+    id: String,
+    blob_path: &'a str,
+    database_name: &'a str,
+    table_name: &'a str,
+    retain_blob_on_success: bool,
+    flush_immediately: bool,
+    /// Failures only.
+    report_level: u8,
+    /// To the status queue.
+    report_method: u8,
+    additional_properties: AdditionalProperties<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AdditionalProperties<'a> {
+    authorization_context: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ingestion_mapping_reference: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ingestion_mapping_type: Option<&'a str>,
+}
+
+/// The response of a management command, of which only the rows are parsed.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ManagementResponse {
+    tables: Vec<ManagementTable>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ManagementTable {
+    rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_storage_uris() {
+        assert_eq!(
+            storage_uri(
+                "https://account.queue.core.windows.net/queue?sv=1&sig=2",
+                "messages"
+            ),
+            "https://account.queue.core.windows.net/queue/messages?sv=1&sig=2"
+        );
+        assert_eq!(
+            storage_uri("http://localhost/container", "blob"),
+            "http://localhost/container/blob"
+        );
+    }
+
+    #[test]
+    fn parses_management_responses() {
+        let response: ManagementResponse = serde_json::from_str(
+            r#"{"Tables":[{"TableName":"Table_0","Columns":[],"Rows":[
+                ["SecuredReadyForAggregationQueue","https://queue?sig=1"],
+                ["TempStorage","https://container?sig=2"]
+            ]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.tables[0].rows.len(), 2);
+        assert_eq!(response.tables[0].rows[1][0], "TempStorage");
+    }
+
+    #[test]
+    fn serializes_ingestion_messages() {
+        let message = IngestionMessage {
+            id: "id".into(),
+            blob_path: "https://blob",
+            database_name: "db",
+            table_name: "logs",
+            retain_blob_on_success: false,
+            flush_immediately: false,
+            report_level: 0,
+            report_method: 0,
+            additional_properties: AdditionalProperties {
+                authorization_context: "token",
+                format: FORMAT,
+                ingestion_mapping_reference: Some("mapping"),
+                ingestion_mapping_type: Some("json"),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"Id":"id","BlobPath":"https://blob","DatabaseName":"db","TableName":"logs","RetainBlobOnSuccess":false,"FlushImmediately":false,"ReportLevel":0,"ReportMethod":0,"AdditionalProperties":{"authorizationContext":"token","format":"multijson","ingestionMappingReference":"mapping","ingestionMappingType":"json"}}"#
+        );
+    }
+
+    #[test]
+    fn retries_storage_errors() {
+        let storage = |status| AdxError::Storage {
+            status,
+            body: String::new(),
+        };
+        assert!(AdxRetryLogic.is_retriable_error(&storage(StatusCode::FORBIDDEN)));
+        assert!(
+            !AdxRetryLogic.is_retriable_error(&AdxError::UnexpectedStatus {
+                status: StatusCode::FORBIDDEN,
+                body: String::new(),
+            })
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    azure_event_hubs::ConnectionString,
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    sinks::{
+        kafka::{Encoding, KafkaSinkConfig},
+        util::{encoding::EncodingConfig, BatchConfig},
+        Healthcheck, VectorSink,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// Publishes to an event hub through the Kafka endpoint of its namespace.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AzureEventHubsSinkConfig {
+    connection_string: String,
+    /// Defaults to the `EntityPath` of the connection string.
+    event_hub: Option<String>,
+    /// The field holding the partition key of each event.
+    partition_key_field: Option<String>,
+    encoding: EncodingConfig<Encoding>,
+    #[serde(default)]
+    batch: BatchConfig,
+}
+
+inventory::submit! {
+    SinkDescription::new::<AzureEventHubsSinkConfig>("azure_event_hubs")
+}
+
+impl GenerateConfig for AzureEventHubsSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"connection_string = "Endpoint=sb://mynamespace.servicebus.windows.net/;SharedAccessKeyName=RootManageSharedAccessKey;SharedAccessKey=<key>"
+            event_hub = "logs"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "azure_event_hubs")]
+impl SinkConfig for AzureEventHubsSinkConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        self.kafka_config()?.build(cx).await
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "azure_event_hubs"
+    }
+}
+
+impl AzureEventHubsSinkConfig {
+    fn kafka_config(&self) -> crate::Result<KafkaSinkConfig> {
+        let connection_string = ConnectionString::parse(&self.connection_string)?;
+
+        Ok(KafkaSinkConfig::new(
+            connection_string.bootstrap_servers(),
+            connection_string.event_hub(self.event_hub.as_ref())?,
+            self.partition_key_field.clone(),
+            self.encoding.clone(),
+            self.batch,
+            connection_string.auth(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AzureEventHubsSinkConfig>();
+    }
+
+    #[test]
+    fn requires_event_hub() {
+        let config: AzureEventHubsSinkConfig = toml::from_str(
+            r#"
+            connection_string = "Endpoint=sb://vector.servicebus.windows.net/;SharedAccessKeyName=send;SharedAccessKey=c2VjcmV0"
+            encoding.codec = "text"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.kafka_config().is_err());
+    }
+}
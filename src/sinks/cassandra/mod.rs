@@ -0,0 +1,525 @@
+//! Writes log events to a Cassandra or ScyllaDB table, inserting each batch of
+//! rows with an unlogged batch of a prepared statement. Batches are
+//! partitioned by the partition key of the table when it's configured, so
+//! that each batch is written to the replicas of a single partition.
+
+mod service;
+
+use self::service::{CassandraRetryLogic, CassandraService, Client};
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, Value},
+    sinks::util::{
+        encoding::{EncodingConfigWithDefault, EncodingConfiguration},
+        BatchConfig, BatchSettings, EncodedEvent, EncodedLength, PartitionBatchSink,
+        PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig, VecBuffer,
+    },
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use scylla::frame::value::{Timestamp, Value as CqlValue, ValueTooBig};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tower::ServiceBuilder;
+
+#[derive(Debug, snafu::Snafu)]
+enum BuildError {
+    #[snafu(display("at least one endpoint must be set"))]
+    NoEndpoints,
+    #[snafu(display("at least one column, or `event_column`, must be set"))]
+    NoColumns,
+    #[snafu(display(
+        "partition key column {:?} is not one of the configured columns",
+        column
+    ))]
+    UnknownPartitionKeyColumn { column: String },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CassandraSinkConfig {
+    /// The nodes first connected to, as `host:port`. The other nodes of the
+    /// cluster are discovered from them.
+    pub endpoints: Vec<String>,
+    pub keyspace: String,
+    pub table: String,
+    #[serde(default)]
+    pub columns: Vec<ColumnConfig>,
+    /// A `text` column the whole event is written to, as JSON.
+    pub event_column: Option<String>,
+    /// The columns of the partition key of the table.
+    #[serde(default)]
+    pub partition_key: Vec<String>,
+    #[serde(default)]
+    pub consistency: Consistency,
+    /// The time to live of the rows written.
+    pub ttl_secs: Option<u32>,
+    /// Prefers the nodes of this datacenter, the nodes of other datacenters
+    /// only being used when none of them are available.
+    pub local_datacenter: Option<String>,
+    pub auth: Option<CassandraAuthConfig>,
+    #[serde(
+        skip_serializing_if = "crate::serde::skip_serializing_if_default",
+        default
+    )]
+    pub encoding: EncodingConfigWithDefault<Encoding>,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<CassandraTlsConfig>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CassandraAuthConfig {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CassandraTlsConfig {
+    ca_file: PathBuf,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnConfig {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub column_type: ColumnType,
+    /// The field the value is read from, the name of the column by default.
+    pub field: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    #[derivative(Default)]
+    Text,
+    Int,
+    Bigint,
+    Double,
+    Boolean,
+    Timestamp,
+}
+
+/// The consistency level batches are written with.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Consistency {
+    Any,
+    One,
+    Two,
+    Three,
+    Quorum,
+    All,
+    #[derivative(Default)]
+    LocalQuorum,
+    EachQuorum,
+    LocalOne,
+}
+
+impl From<Consistency> for scylla::statement::Consistency {
+    fn from(consistency: Consistency) -> Self {
+        match consistency {
+            Consistency::Any => Self::Any,
+            Consistency::One => Self::One,
+            Consistency::Two => Self::Two,
+            Consistency::Three => Self::Three,
+            Consistency::Quorum => Self::Quorum,
+            Consistency::All => Self::All,
+            Consistency::LocalQuorum => Self::LocalQuorum,
+            Consistency::EachQuorum => Self::EachQuorum,
+            Consistency::LocalOne => Self::LocalOne,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Default,
+}
+
+inventory::submit! {
+    SinkDescription::new::<CassandraSinkConfig>("cassandra")
+}
+
+impl GenerateConfig for CassandraSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"endpoints = ["localhost:9042"]
+            keyspace = "vector"
+            table = "events"
+            event_column = "event""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "cassandra")]
+impl SinkConfig for CassandraSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.endpoints.is_empty() {
+            return Err(BuildError::NoEndpoints.into());
+        }
+        let columns = self.columns()?;
+        let key_columns = self.key_columns(&columns)?;
+
+        let batch = BatchSettings::default()
+            .bytes(bytesize::kib(40u64))
+            .events(100)
+            .timeout(1)
+            .parse_config(self.batch)?;
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+
+        let client = Client::new(self, insert_statement(self, &columns))?;
+        let healthcheck = service::healthcheck(client.clone()).boxed();
+
+        let svc = ServiceBuilder::new()
+            .map(|partition: PartitionInnerBuffer<Vec<Row>, Vec<u8>>| partition.into_parts().0)
+            .settings(request, CassandraRetryLogic)
+            .service(CassandraService::new(client));
+
+        let encoding = self.encoding.clone();
+        let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .sink_map_err(|error| error!(message = "Fatal cassandra sink error.", %error))
+            .with_flat_map(move |event| {
+                stream::iter(encode_event(event, &columns, &key_columns, &encoding)).map(Ok)
+            });
+
+        Ok((super::VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "cassandra"
+    }
+}
+
+impl CassandraSinkConfig {
+    /// The columns written, with the event column last.
+    fn columns(&self) -> Result<Vec<Column>, BuildError> {
+        let mut columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|config| Column {
+                name: config.name.clone(),
+                column_type: config.column_type,
+                source: ColumnSource::Field(
+                    config.field.clone().unwrap_or_else(|| config.name.clone()),
+                ),
+            })
+            .collect();
+        if let Some(name) = &self.event_column {
+            columns.push(Column {
+                name: name.clone(),
+                column_type: ColumnType::Text,
+                source: ColumnSource::Event,
+            });
+        }
+        if columns.is_empty() {
+            return Err(BuildError::NoColumns);
+        }
+        Ok(columns)
+    }
+
+    /// The indices of the columns of the partition key.
+    fn key_columns(&self, columns: &[Column]) -> Result<Vec<usize>, BuildError> {
+        self.partition_key
+            .iter()
+            .map(|name| {
+                columns
+                    .iter()
+                    .position(|column| &column.name == name)
+                    .ok_or_else(|| BuildError::UnknownPartitionKeyColumn {
+                        column: name.clone(),
+                    })
+            })
+            .collect()
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn insert_statement(config: &CassandraSinkConfig, columns: &[Column]) -> String {
+    let names = columns
+        .iter()
+        .map(|column| quote_identifier(&column.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let markers = vec!["?"; columns.len()].join(", ");
+    let ttl = match config.ttl_secs {
+        Some(ttl) => format!(" USING TTL {}", ttl),
+        None => String::new(),
+    };
+    format!(
+        "INSERT INTO {}.{} ({}) VALUES ({}){}",
+        quote_identifier(&config.keyspace),
+        quote_identifier(&config.table),
+        names,
+        markers,
+        ttl
+    )
+}
+
+/// A column written, with the source of its values.
+#[derive(Clone, Debug)]
+struct Column {
+    name: String,
+    column_type: ColumnType,
+    source: ColumnSource,
+}
+
+#[derive(Clone, Debug)]
+enum ColumnSource {
+    Field(String),
+    Event,
+}
+
+/// A value of a row, converted to the type of its column.
+#[derive(Clone, Debug, PartialEq)]
+enum Cell {
+    Text(String),
+    Int(i32),
+    Bigint(i64),
+    Double(f64),
+    Boolean(bool),
+    Timestamp(DateTime<Utc>),
+}
+
+impl Cell {
+    fn new(value: &Value, column_type: ColumnType) -> Option<Self> {
+        match (column_type, value) {
+            (_, Value::Null) => None,
+            (ColumnType::Text, Value::Timestamp(timestamp)) => Some(Self::Text(
+                timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            )),
+            (ColumnType::Text, Value::Map(_)) | (ColumnType::Text, Value::Array(_)) => {
+                serde_json::to_string(value).ok().map(Self::Text)
+            }
+            (ColumnType::Text, value) => Some(Self::Text(value.to_string_lossy())),
+            (ColumnType::Int, Value::Integer(value)) => {
+                (*value as i32 as i64 == *value).then(|| Self::Int(*value as i32))
+            }
+            (ColumnType::Bigint, Value::Integer(value)) => Some(Self::Bigint(*value)),
+            (ColumnType::Bigint, Value::Float(value)) => Some(Self::Bigint(*value as i64)),
+            (ColumnType::Double, Value::Float(value)) => Some(Self::Double(*value)),
+            (ColumnType::Double, Value::Integer(value)) => Some(Self::Double(*value as f64)),
+            (ColumnType::Boolean, Value::Boolean(value)) => Some(Self::Boolean(*value)),
+            (ColumnType::Timestamp, Value::Timestamp(timestamp)) => {
+                Some(Self::Timestamp(*timestamp))
+            }
+            (ColumnType::Int, value) => value.parse_text().map(Self::Int),
+            (ColumnType::Bigint, value) => value.parse_text().map(Self::Bigint),
+            (ColumnType::Double, value) => value.parse_text().map(Self::Double),
+            (ColumnType::Boolean, value) => value.parse_text().map(Self::Boolean),
+            (ColumnType::Timestamp, value) => value.parse_timestamp().map(Self::Timestamp),
+        }
+    }
+
+    /// Roughly the size of the value in the native protocol.
+    fn size(&self) -> usize {
+        match self {
+            Self::Text(value) => value.len(),
+            Self::Int(_) => 4,
+            Self::Bigint(_) | Self::Double(_) | Self::Timestamp(_) => 8,
+            Self::Boolean(_) => 1,
+        }
+    }
+}
+
+impl CqlValue for Cell {
+    fn serialize(&self, buf: &mut Vec<u8>) -> Result<(), ValueTooBig> {
+        match self {
+            Self::Text(value) => value.serialize(buf),
+            Self::Int(value) => value.serialize(buf),
+            Self::Bigint(value) => value.serialize(buf),
+            Self::Double(value) => value.serialize(buf),
+            Self::Boolean(value) => value.serialize(buf),
+            Self::Timestamp(value) => {
+                Timestamp(chrono::Duration::milliseconds(value.timestamp_millis())).serialize(buf)
+            }
+        }
+    }
+}
+
+/// The values of an event, in the order of the columns. Values missing or not
+/// convertible to the type of their column are null.
+#[derive(Clone, Debug, PartialEq)]
+pub(self) struct Row(Vec<Option<Cell>>);
+
+impl EncodedLength for Row {
+    fn encoded_length(&self) -> usize {
+        self.0.iter().flatten().map(Cell::size).sum::<usize>() + 4 * self.0.len()
+    }
+}
+
+/// Encodes the event into its row, keyed by the serialized values of its
+/// partition key.
+fn encode_event(
+    mut event: Event,
+    columns: &[Column],
+    key_columns: &[usize],
+    encoding: &EncodingConfigWithDefault<Encoding>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Row, Vec<u8>>>> {
+    encoding.apply_rules(&mut event);
+    let finalizers = event.metadata_mut().take_finalizers();
+    let log = event.as_log();
+
+    let row = columns
+        .iter()
+        .map(|column| match &column.source {
+            ColumnSource::Field(field) => log
+                .get(field)
+                .and_then(|value| Cell::new(value, column.column_type)),
+            ColumnSource::Event => serde_json::to_string(log).ok().map(Cell::Text),
+        })
+        .collect::<Vec<_>>();
+
+    let mut key = Vec::new();
+    for &index in key_columns {
+        // Rows with values too large for the protocol fail in their batch.
+        let _ = row[index].serialize(&mut key);
+    }
+
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(Row(row), key),
+        finalizers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+    use chrono::TimeZone;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<CassandraSinkConfig>();
+    }
+
+    fn config(extra: &str) -> CassandraSinkConfig {
+        toml::from_str(&format!(
+            r#"
+            endpoints = ["localhost:9042"]
+            keyspace = "vector"
+            table = "events"
+            columns = [
+                {{ name = "message" }},
+                {{ name = "status", type = "int", field = "http.status" }},
+                {{ name = "host" }},
+            ]
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_insert_statement() {
+        let config = config(
+            r#"
+            event_column = "event"
+            ttl_secs = 86400
+            "#,
+        );
+        let columns = config.columns().unwrap();
+        assert_eq!(
+            insert_statement(&config, &columns),
+            "INSERT INTO \"vector\".\"events\" (\"message\", \"status\", \"host\", \"event\") \
+             VALUES (?, ?, ?, ?) USING TTL 86400"
+        );
+
+        assert!(matches!(
+            config.key_columns(&columns),
+            Ok(key_columns) if key_columns.is_empty()
+        ));
+        let config = self::config(r#"partition_key = ["host", "day"]"#);
+        assert!(matches!(
+            config.key_columns(&config.columns().unwrap()),
+            Err(BuildError::UnknownPartitionKeyColumn { column }) if column == "day"
+        ));
+    }
+
+    #[test]
+    fn converts_values_to_column_types() {
+        let mut log = LogEvent::from("hello");
+        log.insert("http.status", "200");
+        log.insert("host", "a");
+        log.insert("big", 1_i64 << 40);
+        log.insert("timestamp", Utc.ymd(2021, 9, 1).and_hms(12, 30, 0));
+
+        let mut config = config(r#"event_column = "event""#);
+        config.columns.extend(vec![
+            ColumnConfig {
+                name: "big".into(),
+                column_type: ColumnType::Int,
+                field: None,
+            },
+            ColumnConfig {
+                name: "timestamp".into(),
+                column_type: ColumnType::Timestamp,
+                field: None,
+            },
+        ]);
+        let columns = config.columns().unwrap();
+        let row = encode_event(log.into(), &columns, &[], &Default::default())
+            .unwrap()
+            .item
+            .into_parts()
+            .0;
+
+        assert_eq!(
+            &row.0[..5],
+            &[
+                Some(Cell::Text("hello".into())),
+                Some(Cell::Int(200)),
+                Some(Cell::Text("a".into())),
+                None,
+                Some(Cell::Timestamp(Utc.ymd(2021, 9, 1).and_hms(12, 30, 0))),
+            ]
+        );
+        match &row.0[5] {
+            Some(Cell::Text(event)) => assert!(event.contains(r#""message":"hello""#)),
+            cell => panic!("unexpected event cell {:?}", cell),
+        }
+    }
+
+    #[test]
+    fn partitions_rows_by_partition_key() {
+        let config = config(r#"partition_key = ["host"]"#);
+        let columns = config.columns().unwrap();
+        let key_columns = config.key_columns(&columns).unwrap();
+        let key = |message: &str, host: &str| {
+            let mut log = LogEvent::from(message);
+            log.insert("host", host);
+            encode_event(log.into(), &columns, &key_columns, &Default::default())
+                .unwrap()
+                .item
+                .into_parts()
+                .1
+        };
+
+        assert_eq!(key("first", "a"), key("second", "a"));
+        assert_ne!(key("first", "a"), key("first", "b"));
+    }
+}
@@ -0,0 +1,188 @@
+use super::{CassandraSinkConfig, Consistency, Row};
+use crate::{
+    internal_events::CassandraRowsWritten,
+    sinks::util::{retries::RetryLogic, EncodedLength},
+};
+use futures::future::BoxFuture;
+use openssl::ssl::{SslContext, SslMethod};
+use scylla::{
+    batch::{Batch, BatchType},
+    prepared_statement::PreparedStatement,
+    transport::{
+        errors::{DbError, NewSessionError, QueryError},
+        load_balancing::{
+            ChildLoadBalancingPolicy, DcAwareRoundRobinPolicy, RoundRobinPolicy, TokenAwarePolicy,
+        },
+    },
+    Session, SessionBuilder,
+};
+use snafu::{ResultExt, Snafu};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Snafu)]
+pub(super) enum CassandraError {
+    #[snafu(display("Failed to connect to the cluster: {}", source))]
+    Connect { source: NewSessionError },
+    #[snafu(display("Failed to prepare the insert statement: {}", source))]
+    Prepare { source: QueryError },
+    #[snafu(display("Failed to write the batch: {}", source))]
+    Write { source: QueryError },
+}
+
+/// A session to the cluster, with the insert statement prepared on it.
+struct Prepared {
+    session: Session,
+    insert: PreparedStatement,
+}
+
+/// The session of the sink, connected by the first request and shared by all
+/// requests afterwards. The driver keeps connections to the nodes of the
+/// cluster open, reconnecting to them as needed.
+#[derive(Clone)]
+pub(super) struct Client {
+    builder: SessionBuilder,
+    statement: Arc<str>,
+    consistency: Consistency,
+    prepared: Arc<Mutex<Option<Arc<Prepared>>>>,
+}
+
+impl Client {
+    pub(super) fn new(config: &CassandraSinkConfig, statement: String) -> crate::Result<Self> {
+        let mut builder = SessionBuilder::new().known_nodes(&config.endpoints);
+        if let Some(auth) = &config.auth {
+            builder = builder.user(&auth.username, &auth.password);
+        }
+
+        // Requests are sent to the replicas of their partition, preferring
+        // those of the local datacenter when it's set.
+        let child: Box<dyn ChildLoadBalancingPolicy> = match &config.local_datacenter {
+            Some(datacenter) => Box::new(DcAwareRoundRobinPolicy::new(datacenter.clone())),
+            None => Box::new(RoundRobinPolicy::new()),
+        };
+        builder = builder.load_balancing(Arc::new(TokenAwarePolicy::new(child)));
+
+        if let Some(tls) = &config.tls {
+            let mut context = SslContext::builder(SslMethod::tls_client())?;
+            context.set_ca_file(&tls.ca_file)?;
+            builder = builder.ssl_context(Some(context.build()));
+        }
+
+        Ok(Self {
+            builder,
+            statement: statement.into(),
+            consistency: config.consistency,
+            prepared: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    async fn connect(&self) -> Result<Prepared, CassandraError> {
+        let session = self.builder.build().await.context(Connect)?;
+        let insert = session
+            .prepare(self.statement.as_ref())
+            .await
+            .context(Prepare)?;
+        Ok(Prepared { session, insert })
+    }
+
+    async fn get(&self) -> Result<Arc<Prepared>, CassandraError> {
+        let mut prepared = self.prepared.lock().await;
+        match &*prepared {
+            Some(prepared) => Ok(Arc::clone(prepared)),
+            None => {
+                let connected = Arc::new(self.connect().await?);
+                *prepared = Some(Arc::clone(&connected));
+                Ok(connected)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct CassandraService {
+    client: Client,
+}
+
+impl CassandraService {
+    pub(super) fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    async fn write(self, rows: Vec<Row>) -> crate::Result<()> {
+        let prepared = self.client.get().await?;
+
+        // The rows of a batch share their partition, so an unlogged batch is
+        // applied by its replicas at once, without the batch log.
+        let mut batch = Batch::new(BatchType::Unlogged);
+        for _ in &rows {
+            batch.append_statement(prepared.insert.clone());
+        }
+        batch.set_consistency(self.client.consistency.into());
+
+        let values = rows.iter().map(|row| &row.0).collect::<Vec<_>>();
+        prepared
+            .session
+            .batch(&batch, values)
+            .await
+            .context(Write)?;
+
+        emit!(CassandraRowsWritten {
+            count: rows.len(),
+            byte_size: rows.iter().map(EncodedLength::encoded_length).sum(),
+        });
+        Ok(())
+    }
+}
+
+impl tower::Service<Vec<Row>> for CassandraService {
+    type Response = ();
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, rows: Vec<Row>) -> Self::Future {
+        Box::pin(self.clone().write(rows))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct CassandraRetryLogic;
+
+impl RetryLogic for CassandraRetryLogic {
+    type Error = CassandraError;
+    type Response = ();
+
+    /// Retries the errors of connections, and of nodes unavailable, timing
+    /// out or overloaded, rather than the errors of the rows or of the
+    /// statement.
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            CassandraError::Connect { .. } => true,
+            CassandraError::Prepare { source } | CassandraError::Write { source } => match source {
+                QueryError::IoError(_) | QueryError::TimeoutError => true,
+                QueryError::DbError(error, _) => matches!(
+                    error,
+                    DbError::Unavailable { .. }
+                        | DbError::Overloaded
+                        | DbError::IsBootstrapping
+                        | DbError::WriteTimeout { .. }
+                        | DbError::ReadTimeout { .. }
+                        | DbError::ServerError
+                        | DbError::TruncateError
+                ),
+                _ => false,
+            },
+        }
+    }
+}
+
+pub(super) async fn healthcheck(client: Client) -> crate::Result<()> {
+    client.connect().await?;
+    Ok(())
+}
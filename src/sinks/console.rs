@@ -121,6 +121,12 @@ fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> Option
                 .ok(),
             Encoding::Text => Some(format!("{}", metric)),
         },
+        // Spans have no message to write as text, so they're always JSON.
+        Event::Trace(trace) => serde_json::to_string(&trace)
+            .map_err(|error| {
+                error!(message = "Error encoding json.", %error);
+            })
+            .ok(),
     }
 }
 
@@ -0,0 +1,327 @@
+//! Delta Lake tables, committed to by writing the next version of their
+//! transaction log.
+//!
+//! The commits carry a `txn` action with the writer ID as its application
+//! ID and the sequence number of the batch as its version, as Delta Lake
+//! streaming writers do for idempotent writes.
+
+use super::{parquet, storage::Storage, Column, ColumnType, DataFile, PartitionField};
+use crate::{internal_events::DataLakeBatchAlreadyCommitted, sinks::util::parquet::ParquetWriter};
+use chrono::Utc;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const LOG_DIR: &str = "_delta_log";
+
+/// The directory name of null partition values, as Spark writes them.
+pub(super) const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// The characters escaped in the paths of the log, which are URIs.
+const PATH: &AsciiSet = &CONTROLS.add(b' ').add(b'%');
+
+pub(super) struct DeltaTable {
+    app_id: String,
+    writer: ParquetWriter,
+    partition_fields: Vec<PartitionField>,
+    next_version: u64,
+}
+
+impl DeltaTable {
+    /// Finds the latest version of the table, creating the table from the
+    /// columns if it has no log yet.
+    pub(super) async fn open(
+        storage: &Storage,
+        columns: &[Column],
+        partition_columns: &[usize],
+        writer_id: &str,
+    ) -> crate::Result<Self> {
+        let writer = parquet::writer(
+            columns
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !partition_columns.contains(index))
+                .map(|(_, column)| (column, None)),
+        )?;
+        let partition_fields = partition_columns
+            .iter()
+            .map(|&index| PartitionField {
+                name: columns[index].name.clone(),
+                column: index,
+                field_id: 0,
+                column_type: columns[index].column_type,
+            })
+            .collect();
+        let mut table = Self {
+            app_id: writer_id.to_owned(),
+            writer,
+            partition_fields,
+            next_version: 0,
+        };
+
+        table.next_version = match latest_version(storage).await? {
+            Some(version) => version + 1,
+            None => {
+                let actions = table.create_actions(columns);
+                // Another writer creating the table at the same time is fine.
+                storage.put_if_absent(&log_path(0), actions).await?;
+                1
+            }
+        };
+        Ok(table)
+    }
+
+    pub(super) async fn healthcheck(storage: &Storage) -> crate::Result<()> {
+        storage.list(LOG_DIR).await?;
+        Ok(())
+    }
+
+    pub(super) fn writer(&self) -> &ParquetWriter {
+        &self.writer
+    }
+
+    pub(super) fn partition_fields(&self) -> &[PartitionField] {
+        &self.partition_fields
+    }
+
+    /// Commits the files as the next version of the log. A version written
+    /// in the meantime is checked for the transaction of the batch, which
+    /// is there if an earlier attempt whose outcome was lost succeeded.
+    pub(super) async fn commit(
+        &mut self,
+        storage: &Storage,
+        sequence: i64,
+        files: &[DataFile],
+    ) -> crate::Result<()> {
+        let actions = self.commit_actions(sequence, files);
+        loop {
+            let version = self.next_version;
+            let written = storage
+                .put_if_absent(&log_path(version), actions.clone())
+                .await?;
+            self.next_version = version + 1;
+            if written {
+                return Ok(());
+            }
+
+            if let Some(data) = storage.get(&log_path(version)).await? {
+                if has_transaction(&data, &self.app_id, sequence) {
+                    emit!(DataLakeBatchAlreadyCommitted { sequence });
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn create_actions(&self, columns: &[Column]) -> Vec<u8> {
+        let fields: Vec<_> = columns
+            .iter()
+            .map(|column| {
+                json!({
+                    "name": column.name,
+                    "type": match column.column_type {
+                        ColumnType::String => "string",
+                        ColumnType::Long => "long",
+                        ColumnType::Double => "double",
+                        ColumnType::Boolean => "boolean",
+                        ColumnType::Timestamp => "timestamp",
+                    },
+                    "nullable": true,
+                    "metadata": {},
+                })
+            })
+            .collect();
+        let schema = json!({ "type": "struct", "fields": fields });
+        let partition_columns: Vec<_> = self
+            .partition_fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+
+        encode_actions(&[
+            json!({ "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 } }),
+            json!({
+                "metaData": {
+                    "id": Uuid::new_v4().to_string(),
+                    "format": { "provider": "parquet", "options": {} },
+                    "schemaString": schema.to_string(),
+                    "partitionColumns": partition_columns,
+                    "configuration": {},
+                    "createdTime": Utc::now().timestamp_millis(),
+                }
+            }),
+        ])
+    }
+
+    fn commit_actions(&self, sequence: i64, files: &[DataFile]) -> Vec<u8> {
+        let now = Utc::now().timestamp_millis();
+        let mut actions = vec![
+            json!({
+                "commitInfo": {
+                    "timestamp": now,
+                    "operation": "WRITE",
+                    "operationParameters": { "mode": "Append" },
+                    "isBlindAppend": true,
+                }
+            }),
+            json!({
+                "txn": { "appId": self.app_id, "version": sequence, "lastUpdated": now }
+            }),
+        ];
+        actions.extend(files.iter().map(|file| {
+            let partition_values: serde_json::Map<_, _> = self
+                .partition_fields
+                .iter()
+                .zip(&file.partition)
+                .map(|(field, value)| {
+                    let value = value.as_ref().map(|value| value.partition_value());
+                    (field.name.clone(), json!(value))
+                })
+                .collect();
+            json!({
+                "add": {
+                    "path": utf8_percent_encode(&file.path, PATH).to_string(),
+                    "partitionValues": partition_values,
+                    "size": file.size,
+                    "modificationTime": now,
+                    "dataChange": true,
+                    "stats": json!({ "numRecords": file.record_count }).to_string(),
+                }
+            })
+        }));
+        encode_actions(&actions)
+    }
+}
+
+fn log_path(version: u64) -> String {
+    format!("{}/{:020}.json", LOG_DIR, version)
+}
+
+/// The latest version of the log. Versions compacted into checkpoints keep
+/// their commit files until they expire, which only happens to versions
+/// older than the latest.
+async fn latest_version(storage: &Storage) -> crate::Result<Option<u64>> {
+    Ok(storage
+        .list(LOG_DIR)
+        .await?
+        .iter()
+        .filter_map(|name| name.strip_suffix(".json"))
+        .filter(|version| version.len() == 20)
+        .filter_map(|version| version.parse().ok())
+        .max())
+}
+
+/// One action per line.
+fn encode_actions(actions: &[Value]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for action in actions {
+        data.extend(action.to_string().into_bytes());
+        data.push(b'\n');
+    }
+    data
+}
+
+fn has_transaction(data: &[u8], app_id: &str, sequence: i64) -> bool {
+    data.split(|byte| *byte == b'\n')
+        .filter_map(|line| serde_json::from_slice::<Value>(line).ok())
+        .any(|action| {
+            action["txn"]["appId"].as_str() == Some(app_id)
+                && action["txn"]["version"].as_i64() == Some(sequence)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::data_lake::{Cell, ColumnConfig};
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new(&ColumnConfig {
+                name: "date".into(),
+                column_type: ColumnType::String,
+                field: None,
+                template: Some("%F".into()),
+            })
+            .unwrap(),
+            Column::new(&ColumnConfig {
+                name: "message".into(),
+                column_type: ColumnType::String,
+                field: None,
+                template: None,
+            })
+            .unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn creates_and_commits_to_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::Local {
+            root: dir.path().into(),
+        };
+
+        let mut table = DeltaTable::open(&storage, &columns(), &[0], "writer")
+            .await
+            .unwrap();
+        assert_eq!(table.next_version, 1);
+        assert_eq!(table.writer().columns().len(), 1);
+
+        let created = storage.get(&log_path(0)).await.unwrap().unwrap();
+        let metadata: Value =
+            serde_json::from_slice(created.split(|b| *b == b'\n').nth(1).unwrap()).unwrap();
+        assert_eq!(metadata["metaData"]["partitionColumns"], json!(["date"]));
+
+        let files = vec![DataFile {
+            path: "date=2021-09-01/part-0.snappy.parquet".into(),
+            partition: vec![Some(Cell::String("2021-09-01".into()))],
+            record_count: 3,
+            size: 100,
+        }];
+        table.commit(&storage, 42, &files).await.unwrap();
+        let committed = storage.get(&log_path(1)).await.unwrap().unwrap();
+        assert!(has_transaction(&committed, "writer", 42));
+
+        let add: Value =
+            serde_json::from_slice(committed.split(|b| *b == b'\n').nth(2).unwrap()).unwrap();
+        assert_eq!(
+            add["add"]["partitionValues"],
+            json!({ "date": "2021-09-01" })
+        );
+        assert_eq!(add["add"]["stats"], json!(r#"{"numRecords":3}"#));
+
+        let table = DeltaTable::open(&storage, &columns(), &[0], "writer")
+            .await
+            .unwrap();
+        assert_eq!(table.next_version, 2);
+    }
+
+    #[tokio::test]
+    async fn batches_committed_by_lost_attempts_are_not_committed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::Local {
+            root: dir.path().into(),
+        };
+        let mut table = DeltaTable::open(&storage, &columns(), &[0], "writer")
+            .await
+            .unwrap();
+
+        // Another writer commits version 1, then the attempt whose outcome
+        // was lost commits version 2.
+        let other = DeltaTable::open(&storage, &columns(), &[0], "other")
+            .await
+            .unwrap();
+        storage
+            .put(&log_path(1), other.commit_actions(7, &[]))
+            .await
+            .unwrap();
+        storage
+            .put(&log_path(2), table.commit_actions(42, &[]))
+            .await
+            .unwrap();
+
+        table.commit(&storage, 42, &[]).await.unwrap();
+        assert_eq!(table.next_version, 3);
+        assert_eq!(storage.get(&log_path(3)).await.unwrap(), None);
+    }
+}
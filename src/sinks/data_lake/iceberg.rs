@@ -0,0 +1,791 @@
+//! Iceberg tables of format version 2, committed to through the REST
+//! catalog API.
+//!
+//! Each batch is appended as a snapshot with one manifest of its files,
+//! and a manifest list of the manifests of the current snapshot with it.
+//! The summary of the snapshot records the writer ID and the sequence
+//! number of the batch, and the snapshots of the table are checked for them
+//! before every attempt to commit it.
+
+use super::{avro, parquet, storage::Storage, Cell, Column, ColumnType, DataFile, PartitionField};
+use crate::{
+    http::{Auth, HttpClient},
+    internal_events::DataLakeBatchAlreadyCommitted,
+    rusoto::AwsCredentialsProvider,
+    sinks::util::parquet::ParquetWriter,
+};
+use avro_rs::{types::Value as AvroValue, Schema as AvroSchema};
+use bytes::Bytes;
+use chrono::Utc;
+use http::{
+    header::{HeaderName, HeaderValue},
+    Request, StatusCode, Uri,
+};
+use hyper::Body;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use rusoto_core::Region;
+use rusoto_credential::ProvideAwsCredentials;
+use rusoto_signature::SignedRequest;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use snafu::Snafu;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+/// The directory name of null partition values, as Iceberg writes them.
+pub(super) const NULL_PARTITION: &str = "null";
+
+/// Appends with a snapshot whose parent is no longer current are attempted
+/// again from the new current snapshot, up to this many times.
+const MAX_CONFLICT_RETRIES: usize = 10;
+
+const WRITER_ID_PROPERTY: &str = "vector.writer-id";
+const SEQUENCE_PROPERTY: &str = "vector.batch-sequence";
+
+/// The fields of the manifest lists of format version 2 tables, named as
+/// in the specification. The names of older writers are aliases.
+const MANIFEST_LIST_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "manifest_file",
+    "fields": [
+        {"name": "manifest_path", "type": "string", "field-id": 500},
+        {"name": "manifest_length", "type": "long", "field-id": 501},
+        {"name": "partition_spec_id", "type": "int", "field-id": 502},
+        {"name": "content", "type": "int", "field-id": 517},
+        {"name": "sequence_number", "type": "long", "field-id": 515},
+        {"name": "min_sequence_number", "type": "long", "field-id": 516},
+        {"name": "added_snapshot_id", "type": "long", "field-id": 503},
+        {"name": "added_files_count", "type": "int", "field-id": 504},
+        {"name": "existing_files_count", "type": "int", "field-id": 505},
+        {"name": "deleted_files_count", "type": "int", "field-id": 506},
+        {"name": "added_rows_count", "type": "long", "field-id": 512},
+        {"name": "existing_rows_count", "type": "long", "field-id": 513},
+        {"name": "deleted_rows_count", "type": "long", "field-id": 514},
+        {"name": "partitions", "type": ["null", {"type": "array", "items": {
+            "type": "record",
+            "name": "r508",
+            "fields": [
+                {"name": "contains_null", "type": "boolean", "field-id": 509},
+                {"name": "contains_nan", "type": ["null", "boolean"], "default": null, "field-id": 518},
+                {"name": "lower_bound", "type": ["null", "bytes"], "default": null, "field-id": 510},
+                {"name": "upper_bound", "type": ["null", "bytes"], "default": null, "field-id": 511}
+            ]
+        }, "element-id": 508}], "default": null, "field-id": 507},
+        {"name": "key_metadata", "type": ["null", "bytes"], "default": null, "field-id": 519}
+    ]
+}"#;
+
+const MANIFEST_LIST_ALIASES: &[(&str, &str)] = &[
+    ("added_files_count", "added_data_files_count"),
+    ("existing_files_count", "existing_data_files_count"),
+    ("deleted_files_count", "deleted_data_files_count"),
+];
+
+#[derive(Debug, Snafu)]
+enum IcebergError {
+    #[snafu(display("unexpected response from the catalog: {}: {}", status, body))]
+    UnexpectedResponse { status: StatusCode, body: String },
+    #[snafu(display("table has format version {}, only version 2 is supported", version))]
+    FormatVersion { version: u8 },
+    #[snafu(display("column {:?} is not a column of the table", column))]
+    UnknownColumn { column: String },
+    #[snafu(display("column {:?} has type {} in the table", column, table_type))]
+    ColumnType { column: String, table_type: Value },
+    #[snafu(display(
+        "table column {:?} is required, only optional columns are written",
+        column
+    ))]
+    RequiredColumn { column: String },
+    #[snafu(display(
+        "partition field {:?} must be an identity partition of a `string`, `long` or `boolean` column",
+        field
+    ))]
+    UnsupportedPartition { field: String },
+    #[snafu(display("table metadata has no {}", what))]
+    MissingMetadata { what: &'static str },
+    #[snafu(display("manifest list {:?} is not in the table location", location))]
+    ManifestListLocation { location: String },
+}
+
+pub(super) enum CatalogAuth {
+    Http(Option<Auth>),
+    Aws {
+        credentials: AwsCredentialsProvider,
+        region: Region,
+    },
+}
+
+/// A table of an Iceberg REST catalog.
+#[derive(Clone)]
+pub(super) struct RestCatalog {
+    client: HttpClient,
+    table_uri: String,
+    auth: Arc<CatalogAuth>,
+}
+
+#[derive(Debug, PartialEq)]
+enum CommitOutcome {
+    Committed,
+    Conflict,
+}
+
+impl RestCatalog {
+    pub(super) fn new(
+        client: HttpClient,
+        uri: &str,
+        prefix: Option<&str>,
+        namespace: &str,
+        name: &str,
+        auth: CatalogAuth,
+    ) -> Self {
+        Self {
+            client,
+            table_uri: table_uri(uri, prefix, namespace, name),
+            auth: Arc::new(auth),
+        }
+    }
+
+    async fn request(&self, method: &str, body: Vec<u8>) -> crate::Result<(StatusCode, Bytes)> {
+        let mut builder = Request::builder().method(method).uri(&self.table_uri);
+        match &*self.auth {
+            CatalogAuth::Http(auth) => {
+                if let Some(auth) = auth {
+                    builder = auth.apply_builder(builder);
+                }
+                if !body.is_empty() {
+                    builder = builder.header("Content-Type", "application/json");
+                }
+            }
+            CatalogAuth::Aws {
+                credentials,
+                region,
+            } => {
+                let uri: Uri = self.table_uri.parse()?;
+                let mut signer = SignedRequest::new(method, "glue", region, uri.path());
+                signer.set_hostname(uri.host().map(Into::into));
+                if !body.is_empty() {
+                    signer.add_header("Content-Type", "application/json");
+                    signer.set_payload(Some(body.clone()));
+                }
+                signer.sign(&credentials.credentials().await?);
+                for (name, values) in signer.headers() {
+                    let name = name.parse::<HeaderName>()?;
+                    for value in values {
+                        builder = builder.header(&name, HeaderValue::from_bytes(value)?);
+                    }
+                }
+            }
+        }
+
+        let response = self.client.send(builder.body(Body::from(body))?).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((status, body))
+    }
+
+    async fn load(&self) -> crate::Result<TableMetadata> {
+        match self.request("GET", Vec::new()).await? {
+            (StatusCode::OK, body) => {
+                Ok(serde_json::from_slice::<LoadTableResult>(&body)?.metadata)
+            }
+            (status, body) => Err(unexpected_response(status, &body)),
+        }
+    }
+
+    async fn commit(&self, request: &Value) -> crate::Result<CommitOutcome> {
+        match self.request("POST", serde_json::to_vec(request)?).await? {
+            (StatusCode::OK, _) => Ok(CommitOutcome::Committed),
+            (StatusCode::CONFLICT, _) => Ok(CommitOutcome::Conflict),
+            (status, body) => Err(unexpected_response(status, &body)),
+        }
+    }
+}
+
+fn unexpected_response(status: StatusCode, body: &[u8]) -> crate::Error {
+    IcebergError::UnexpectedResponse {
+        status,
+        body: String::from_utf8_lossy(body).into_owned(),
+    }
+    .into()
+}
+
+/// Multi-level namespaces are separated by the unit separator in paths.
+fn table_uri(uri: &str, prefix: Option<&str>, namespace: &str, name: &str) -> String {
+    let namespace = namespace.split('.').collect::<Vec<_>>().join("\u{1f}");
+    let prefix = prefix
+        .map(|prefix| format!("{}/", prefix.trim_matches('/')))
+        .unwrap_or_default();
+    format!(
+        "{}/v1/{}namespaces/{}/tables/{}",
+        uri.trim_end_matches('/'),
+        prefix,
+        utf8_percent_encode(&namespace, NON_ALPHANUMERIC),
+        utf8_percent_encode(name, NON_ALPHANUMERIC)
+    )
+}
+
+#[derive(Deserialize)]
+struct LoadTableResult {
+    metadata: TableMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TableMetadata {
+    format_version: u8,
+    location: String,
+    #[serde(default)]
+    last_sequence_number: i64,
+    current_schema_id: i32,
+    schemas: Vec<Value>,
+    default_spec_id: i32,
+    partition_specs: Vec<Value>,
+    current_snapshot_id: Option<i64>,
+    #[serde(default)]
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Snapshot {
+    snapshot_id: i64,
+    manifest_list: Option<String>,
+    #[serde(default)]
+    summary: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SchemaField {
+    id: i32,
+    name: String,
+    required: bool,
+    #[serde(rename = "type")]
+    field_type: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SpecField {
+    name: String,
+    transform: String,
+    source_id: i32,
+    field_id: i32,
+}
+
+impl TableMetadata {
+    fn current_schema(&self) -> crate::Result<&Value> {
+        self.schemas
+            .iter()
+            .find(|schema| schema["schema-id"] == json!(self.current_schema_id))
+            .ok_or_else(|| {
+                IcebergError::MissingMetadata {
+                    what: "current schema",
+                }
+                .into()
+            })
+    }
+
+    fn default_spec(&self) -> crate::Result<&Value> {
+        self.partition_specs
+            .iter()
+            .find(|spec| spec["spec-id"] == json!(self.default_spec_id))
+            .ok_or_else(|| {
+                IcebergError::MissingMetadata {
+                    what: "default partition spec",
+                }
+                .into()
+            })
+    }
+
+    /// Tables without snapshots have no current snapshot, or -1 with some
+    /// older writers.
+    fn current_snapshot(&self) -> Option<&Snapshot> {
+        let id = self.current_snapshot_id.filter(|id| *id >= 0)?;
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.snapshot_id == id)
+    }
+}
+
+pub(super) struct IcebergTable {
+    catalog: RestCatalog,
+    writer_id: String,
+    location: String,
+    writer: ParquetWriter,
+    partition_fields: Vec<PartitionField>,
+    schema: Value,
+    spec: Value,
+    /// The manifest written for the batch being committed, by its sequence.
+    manifest: Option<(i64, ManifestFile)>,
+}
+
+/// A manifest written for a batch, as listed in manifest lists.
+#[derive(Clone, Debug)]
+struct ManifestFile {
+    location: String,
+    length: usize,
+    files: usize,
+    rows: usize,
+}
+
+impl IcebergTable {
+    /// Loads the table, and resolves the columns to the fields of its
+    /// current schema and the partition fields of its default spec.
+    pub(super) async fn open(
+        catalog: RestCatalog,
+        columns: &[Column],
+        writer_id: &str,
+    ) -> crate::Result<Self> {
+        let metadata = catalog.load().await?;
+        if metadata.format_version != 2 {
+            return Err(IcebergError::FormatVersion {
+                version: metadata.format_version,
+            }
+            .into());
+        }
+        let schema = metadata.current_schema()?.clone();
+        let spec = metadata.default_spec()?.clone();
+        let fields: Vec<SchemaField> = serde_json::from_value(schema["fields"].clone())?;
+        let spec_fields: Vec<SpecField> = serde_json::from_value(spec["fields"].clone())?;
+
+        let mut field_ids = Vec::new();
+        for column in columns {
+            let field = fields
+                .iter()
+                .find(|field| field.name == column.name)
+                .ok_or_else(|| IcebergError::UnknownColumn {
+                    column: column.name.clone(),
+                })?;
+            if !type_matches(column.column_type, &field.field_type) {
+                return Err(IcebergError::ColumnType {
+                    column: column.name.clone(),
+                    table_type: field.field_type.clone(),
+                }
+                .into());
+            }
+            field_ids.push(field.id);
+        }
+        if let Some(field) = fields.iter().find(|field| field.required) {
+            return Err(IcebergError::RequiredColumn {
+                column: field.name.clone(),
+            }
+            .into());
+        }
+
+        let partition_fields = spec_fields
+            .iter()
+            .map(|spec_field| {
+                let column = field_ids
+                    .iter()
+                    .position(|field_id| *field_id == spec_field.source_id)
+                    .filter(|index| {
+                        spec_field.transform == "identity"
+                            && matches!(
+                                columns[*index].column_type,
+                                ColumnType::String | ColumnType::Long | ColumnType::Boolean
+                            )
+                    })
+                    .ok_or_else(|| IcebergError::UnsupportedPartition {
+                        field: spec_field.name.clone(),
+                    })?;
+                Ok(PartitionField {
+                    name: spec_field.name.clone(),
+                    column,
+                    field_id: spec_field.field_id,
+                    column_type: columns[column].column_type,
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            catalog,
+            writer_id: writer_id.to_owned(),
+            location: metadata.location,
+            writer: parquet::writer(columns.iter().zip(field_ids.into_iter().map(Some)))?,
+            partition_fields,
+            schema,
+            spec,
+            manifest: None,
+        })
+    }
+
+    pub(super) fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub(super) fn writer(&self) -> &ParquetWriter {
+        &self.writer
+    }
+
+    pub(super) fn partition_fields(&self) -> &[PartitionField] {
+        &self.partition_fields
+    }
+
+    /// Appends the files as a snapshot of the table, unless the snapshots
+    /// of the table show the batch was committed by an earlier attempt.
+    pub(super) async fn commit(
+        &mut self,
+        storage: &Storage,
+        sequence: i64,
+        files: &[DataFile],
+    ) -> crate::Result<()> {
+        let manifest = match &self.manifest {
+            Some((manifest_sequence, manifest)) if *manifest_sequence == sequence => {
+                manifest.clone()
+            }
+            _ => {
+                let manifest = self.write_manifest(storage, files).await?;
+                self.manifest = Some((sequence, manifest.clone()));
+                manifest
+            }
+        };
+
+        for _ in 0..MAX_CONFLICT_RETRIES {
+            let metadata = self.catalog.load().await?;
+            if metadata
+                .snapshots
+                .iter()
+                .any(|snapshot| self.committed(snapshot, sequence))
+            {
+                emit!(DataLakeBatchAlreadyCommitted { sequence });
+                return Ok(());
+            }
+
+            let request = self
+                .snapshot_request(storage, &metadata, sequence, &manifest)
+                .await?;
+            if self.catalog.commit(&request).await? == CommitOutcome::Committed {
+                return Ok(());
+            }
+        }
+        Err("Commit conflicted with other writers too many times.".into())
+    }
+
+    fn committed(&self, snapshot: &Snapshot, sequence: i64) -> bool {
+        snapshot.summary.get(WRITER_ID_PROPERTY) == Some(&self.writer_id)
+            && snapshot.summary.get(SEQUENCE_PROPERTY) == Some(&sequence.to_string())
+    }
+
+    fn manifest_schema(&self) -> String {
+        let partition_fields: Vec<_> = self
+            .partition_fields
+            .iter()
+            .map(|field| {
+                json!({
+                    "name": field.name,
+                    "type": ["null", avro_type(field.column_type)],
+                    "default": null,
+                    "field-id": field.field_id,
+                })
+            })
+            .collect();
+        json!({
+            "type": "record",
+            "name": "manifest_entry",
+            "fields": [
+                {"name": "status", "type": "int", "field-id": 0},
+                {"name": "snapshot_id", "type": ["null", "long"], "default": null, "field-id": 1},
+                {"name": "sequence_number", "type": ["null", "long"], "default": null, "field-id": 3},
+                {"name": "file_sequence_number", "type": ["null", "long"], "default": null, "field-id": 4},
+                {"name": "data_file", "type": {
+                    "type": "record",
+                    "name": "r2",
+                    "fields": [
+                        {"name": "content", "type": "int", "field-id": 134},
+                        {"name": "file_path", "type": "string", "field-id": 100},
+                        {"name": "file_format", "type": "string", "field-id": 101},
+                        {"name": "partition", "type": {
+                            "type": "record",
+                            "name": "r102",
+                            "fields": partition_fields,
+                        }, "field-id": 102},
+                        {"name": "record_count", "type": "long", "field-id": 103},
+                        {"name": "file_size_in_bytes", "type": "long", "field-id": 104}
+                    ]
+                }, "field-id": 2}
+            ]
+        })
+        .to_string()
+    }
+
+    /// The entries of the files are added with the snapshot ID and the
+    /// sequence numbers left null, which inherit those of the snapshot.
+    async fn write_manifest(
+        &self,
+        storage: &Storage,
+        files: &[DataFile],
+    ) -> crate::Result<ManifestFile> {
+        let entries = files
+            .iter()
+            .map(|file| {
+                let partition = self
+                    .partition_fields
+                    .iter()
+                    .zip(&file.partition)
+                    .map(|(field, value)| (field.name.clone(), partition_value(value)))
+                    .collect();
+                AvroValue::Record(vec![
+                    ("status".into(), AvroValue::Int(1)),
+                    ("snapshot_id".into(), null()),
+                    ("sequence_number".into(), null()),
+                    ("file_sequence_number".into(), null()),
+                    (
+                        "data_file".into(),
+                        AvroValue::Record(vec![
+                            ("content".into(), AvroValue::Int(0)),
+                            (
+                                "file_path".into(),
+                                AvroValue::String(storage.uri(&file.path)),
+                            ),
+                            ("file_format".into(), AvroValue::String("PARQUET".into())),
+                            ("partition".into(), AvroValue::Record(partition)),
+                            (
+                                "record_count".into(),
+                                AvroValue::Long(file.record_count as i64),
+                            ),
+                            (
+                                "file_size_in_bytes".into(),
+                                AvroValue::Long(file.size as i64),
+                            ),
+                        ]),
+                    ),
+                ])
+            })
+            .collect();
+
+        let data = avro::write(
+            &self.manifest_schema(),
+            &[
+                ("schema", self.schema.to_string()),
+                ("schema-id", self.schema["schema-id"].to_string()),
+                ("partition-spec", self.spec["fields"].to_string()),
+                ("partition-spec-id", self.spec["spec-id"].to_string()),
+                ("format-version", "2".into()),
+                ("content", "data".into()),
+            ],
+            entries,
+        )?;
+        let path = format!("metadata/{}-m0.avro", Uuid::new_v4());
+        let length = data.len();
+        storage.put(&path, data).await?;
+
+        Ok(ManifestFile {
+            location: storage.uri(&path),
+            length,
+            files: files.len(),
+            rows: files.iter().map(|file| file.record_count).sum(),
+        })
+    }
+
+    /// Writes the manifest list of a new snapshot, with the manifests of
+    /// the current snapshot and the manifest of the batch, and builds the
+    /// request committing the snapshot if the current one is still current.
+    async fn snapshot_request(
+        &self,
+        storage: &Storage,
+        metadata: &TableMetadata,
+        sequence: i64,
+        manifest: &ManifestFile,
+    ) -> crate::Result<Value> {
+        let schema = AvroSchema::parse_str(MANIFEST_LIST_SCHEMA)?;
+        let snapshot_id = (Uuid::new_v4().as_u128() as i64) & i64::MAX;
+        let sequence_number = metadata.last_sequence_number + 1;
+        let parent = metadata.current_snapshot();
+
+        let mut manifests = Vec::new();
+        if let Some(manifest_list) = parent.and_then(|parent| parent.manifest_list.as_ref()) {
+            let path = storage.relative(manifest_list).ok_or_else(|| {
+                IcebergError::ManifestListLocation {
+                    location: manifest_list.clone(),
+                }
+            })?;
+            let data = storage
+                .get(&path)
+                .await?
+                .ok_or_else(|| format!("Manifest list {:?} not found.", manifest_list))?;
+            manifests.extend(
+                avro::read(&data)?
+                    .into_iter()
+                    .map(|value| avro::conform(value, &schema, MANIFEST_LIST_ALIASES)),
+            );
+        }
+        manifests.push(AvroValue::Record(vec![
+            (
+                "manifest_path".into(),
+                AvroValue::String(manifest.location.clone()),
+            ),
+            (
+                "manifest_length".into(),
+                AvroValue::Long(manifest.length as i64),
+            ),
+            (
+                "partition_spec_id".into(),
+                AvroValue::Int(self.spec["spec-id"].as_i64().unwrap_or_default() as i32),
+            ),
+            ("content".into(), AvroValue::Int(0)),
+            ("sequence_number".into(), AvroValue::Long(sequence_number)),
+            (
+                "min_sequence_number".into(),
+                AvroValue::Long(sequence_number),
+            ),
+            ("added_snapshot_id".into(), AvroValue::Long(snapshot_id)),
+            (
+                "added_files_count".into(),
+                AvroValue::Int(manifest.files as i32),
+            ),
+            ("existing_files_count".into(), AvroValue::Int(0)),
+            ("deleted_files_count".into(), AvroValue::Int(0)),
+            (
+                "added_rows_count".into(),
+                AvroValue::Long(manifest.rows as i64),
+            ),
+            ("existing_rows_count".into(), AvroValue::Long(0)),
+            ("deleted_rows_count".into(), AvroValue::Long(0)),
+            ("partitions".into(), null()),
+            ("key_metadata".into(), null()),
+        ]));
+
+        let parent_id = parent.map(|parent| parent.snapshot_id);
+        let data = avro::write(
+            MANIFEST_LIST_SCHEMA,
+            &[
+                ("snapshot-id", snapshot_id.to_string()),
+                (
+                    "parent-snapshot-id",
+                    parent_id.map_or_else(|| "null".to_owned(), |id| id.to_string()),
+                ),
+                ("sequence-number", sequence_number.to_string()),
+                ("format-version", "2".into()),
+            ],
+            manifests,
+        )?;
+        let path = format!("metadata/snap-{}-1-{}.avro", snapshot_id, Uuid::new_v4());
+        storage.put(&path, data).await?;
+
+        let mut snapshot = json!({
+            "snapshot-id": snapshot_id,
+            "sequence-number": sequence_number,
+            "timestamp-ms": Utc::now().timestamp_millis(),
+            "manifest-list": storage.uri(&path),
+            "summary": {
+                "operation": "append",
+                "added-data-files": manifest.files.to_string(),
+                "added-records": manifest.rows.to_string(),
+                WRITER_ID_PROPERTY: self.writer_id,
+                SEQUENCE_PROPERTY: sequence.to_string(),
+            },
+            "schema-id": self.schema["schema-id"],
+        });
+        if let Some(parent_id) = parent_id {
+            snapshot["parent-snapshot-id"] = json!(parent_id);
+        }
+
+        Ok(json!({
+            "requirements": [
+                {"type": "assert-ref-snapshot-id", "ref": "main", "snapshot-id": parent_id},
+            ],
+            "updates": [
+                {"action": "add-snapshot", "snapshot": snapshot},
+                {
+                    "action": "set-snapshot-ref",
+                    "ref-name": "main",
+                    "type": "branch",
+                    "snapshot-id": snapshot_id,
+                },
+            ],
+        }))
+    }
+}
+
+fn null() -> AvroValue {
+    AvroValue::Union(Box::new(AvroValue::Null))
+}
+
+fn avro_type(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Long | ColumnType::Timestamp => "long",
+        ColumnType::Double => "double",
+        ColumnType::Boolean => "boolean",
+        ColumnType::String => "string",
+    }
+}
+
+fn partition_value(value: &Option<Cell>) -> AvroValue {
+    AvroValue::Union(Box::new(match value {
+        None => AvroValue::Null,
+        Some(Cell::String(value)) => AvroValue::String(value.clone()),
+        Some(Cell::Long(value)) | Some(Cell::Timestamp(value)) => AvroValue::Long(*value),
+        Some(Cell::Double(value)) => AvroValue::Double(*value),
+        Some(Cell::Boolean(value)) => AvroValue::Boolean(*value),
+    }))
+}
+
+fn type_matches(column_type: ColumnType, table_type: &Value) -> bool {
+    match (column_type, table_type.as_str()) {
+        (ColumnType::String, Some("string"))
+        | (ColumnType::Long, Some("long"))
+        | (ColumnType::Double, Some("double"))
+        | (ColumnType::Boolean, Some("boolean"))
+        | (ColumnType::Timestamp, Some("timestamp"))
+        | (ColumnType::Timestamp, Some("timestamptz")) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_table_uris() {
+        assert_eq!(
+            table_uri("http://localhost:8181/", None, "logs", "events"),
+            "http://localhost:8181/v1/namespaces/logs/tables/events"
+        );
+        assert_eq!(
+            table_uri(
+                "https://glue.us-east-1.amazonaws.com/iceberg",
+                Some("catalogs/123456789012"),
+                "lake.logs",
+                "events"
+            ),
+            "https://glue.us-east-1.amazonaws.com/iceberg/v1/catalogs/123456789012/namespaces/lake%1Flogs/tables/events"
+        );
+    }
+
+    #[test]
+    fn finds_current_schema_spec_and_snapshot() {
+        let metadata: TableMetadata = serde_json::from_value(json!({
+            "format-version": 2,
+            "table-uuid": "9c12d441-03fe-4693-9a96-a0705ddf69c1",
+            "location": "s3://bucket/warehouse/logs/events",
+            "last-sequence-number": 3,
+            "current-schema-id": 1,
+            "schemas": [
+                {"schema-id": 0, "type": "struct", "fields": []},
+                {"schema-id": 1, "type": "struct", "fields": [
+                    {"id": 1, "name": "message", "required": false, "type": "string"}
+                ]}
+            ],
+            "default-spec-id": 0,
+            "partition-specs": [{"spec-id": 0, "fields": []}],
+            "current-snapshot-id": 7,
+            "snapshots": [{
+                "snapshot-id": 7,
+                "manifest-list": "s3://bucket/warehouse/logs/events/metadata/snap-7.avro",
+                "summary": {"operation": "append", "vector.writer-id": "vector", "vector.batch-sequence": "42"}
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(metadata.current_schema().unwrap()["fields"][0]["id"], 1);
+        assert_eq!(metadata.default_spec().unwrap()["spec-id"], 0);
+        let snapshot = metadata.current_snapshot().unwrap();
+        assert_eq!(snapshot.summary[SEQUENCE_PROPERTY], "42");
+    }
+
+    #[test]
+    fn manifest_list_schema_parses() {
+        AvroSchema::parse_str(MANIFEST_LIST_SCHEMA).unwrap();
+    }
+}
@@ -0,0 +1,807 @@
+//! Writes events to the Parquet files of a data lake table, and commits
+//! each batch of files to the table in one transaction of its format.
+//!
+//! Every batch is committed once: the commits are tagged with the writer ID
+//! and the sequence number of the batch, and a commit whose outcome is
+//! unknown is looked up in the table before being attempted again.
+
+mod avro;
+mod delta;
+mod iceberg;
+mod parquet;
+mod storage;
+
+use self::{
+    delta::DeltaTable,
+    iceberg::{IcebergTable, RestCatalog},
+    storage::{Location, Storage},
+};
+use crate::{
+    buffers::Acker,
+    config::{DataType, GenerateConfig, ProxyConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, EventFinalizers, EventStatus, Value},
+    http::{Auth, HttpClient},
+    internal_events::{DataLakeEventsCommitted, DataLakeRequestFailed, TemplateRenderingFailed},
+    rusoto::{self, AwsAuthentication, RegionOrEndpoint},
+    sinks::util::{batch::BatchConfig, parquet::ParquetWriter, StreamSink},
+    template::{Template, TemplateParseError},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{stream::BoxStream, FutureExt, StreamExt};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use rusoto_core::Region;
+use rusoto_s3::S3Client;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{collections::BTreeMap, convert::TryFrom, time::Duration};
+use tokio::time::{sleep, Instant};
+use uuid::Uuid;
+use vector_core::ByteSizeOf;
+
+const MAX_BACKOFF_SECS: u64 = 60;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid template for column {:?}: {}", column, source))]
+    ColumnTemplate {
+        column: String,
+        source: TemplateParseError,
+    },
+    #[snafu(display("partition column {:?} is not one of the configured columns", column))]
+    UnknownPartitionColumn { column: String },
+    #[snafu(display(
+        "partition column {:?} must be of type `string`, `long` or `boolean`",
+        column
+    ))]
+    PartitionColumnType { column: String },
+    #[snafu(display("at least one column must be written to the data files"))]
+    NoDataColumns,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataLakeSinkConfig {
+    pub table: TableConfig,
+    pub columns: Vec<ColumnConfig>,
+    #[serde(default = "default_writer_id")]
+    pub writer_id: String,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(flatten)]
+    pub region: RegionOrEndpoint,
+    #[serde(default)]
+    pub auth: AwsAuthentication,
+}
+
+/// The table the files are committed to, with its format.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum TableConfig {
+    DeltaLake {
+        location: String,
+        #[serde(default)]
+        partition_by: Vec<String>,
+    },
+    Iceberg {
+        catalog: CatalogConfig,
+        namespace: String,
+        name: String,
+    },
+}
+
+/// The Iceberg catalog the table is looked up and committed through.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CatalogConfig {
+    Rest {
+        uri: String,
+        prefix: Option<String>,
+        auth: Option<Auth>,
+    },
+    /// The Iceberg REST endpoint of the Glue Data Catalog, signed with the
+    /// AWS credentials of the sink.
+    Glue { catalog_id: String },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnConfig {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub column_type: ColumnType,
+    /// The field the value is read from, the name of the column by default.
+    pub field: Option<String>,
+    /// Renders the value instead of reading it from a field.
+    pub template: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    #[derivative(Default)]
+    String,
+    Long,
+    Double,
+    Boolean,
+    Timestamp,
+}
+
+fn default_writer_id() -> String {
+    "vector".to_owned()
+}
+
+inventory::submit! {
+    SinkDescription::new::<DataLakeSinkConfig>("data_lake")
+}
+
+impl GenerateConfig for DataLakeSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            table.format = "delta_lake"
+            table.location = "s3://my-bucket/tables/logs"
+            table.partition_by = ["date"]
+            region = "us-east-1"
+
+            [[columns]]
+            name = "date"
+            template = "%F"
+
+            [[columns]]
+            name = "timestamp"
+            type = "timestamp"
+
+            [[columns]]
+            name = "message""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "data_lake")]
+impl SinkConfig for DataLakeSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let columns = self
+            .columns
+            .iter()
+            .map(Column::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = self.batch.use_size_as_events()?;
+
+        let opener = match &self.table {
+            TableConfig::DeltaLake {
+                location,
+                partition_by,
+            } => {
+                let partition_columns = partition_by
+                    .iter()
+                    .map(|name| partition_column(&columns, name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if partition_columns.len() == columns.len() {
+                    return Err(BuildError::NoDataColumns.into());
+                }
+                TableOpener::DeltaLake {
+                    storage: self.storage(location, cx.proxy())?,
+                    partition_columns,
+                }
+            }
+            TableConfig::Iceberg {
+                catalog,
+                namespace,
+                name,
+            } => TableOpener::Iceberg {
+                catalog: self.catalog(catalog, namespace, name, cx.proxy())?,
+                config: self.clone(),
+                proxy: cx.proxy().clone(),
+            },
+        };
+
+        let healthcheck = opener.clone().healthcheck(columns.clone()).boxed();
+        let sink = DataLakeSink {
+            opener,
+            columns,
+            writer_id: self.writer_id.clone(),
+            max_events: batch.max_events.unwrap_or(100_000),
+            max_bytes: batch.max_bytes.unwrap_or(100_000_000),
+            timeout: Duration::from_secs(batch.timeout_secs.unwrap_or(300)),
+            last_sequence: 0,
+            acker: cx.acker(),
+        };
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "data_lake"
+    }
+}
+
+impl DataLakeSinkConfig {
+    fn storage(&self, location: &str, proxy: &ProxyConfig) -> crate::Result<Storage> {
+        Ok(match Location::parse(location)? {
+            Location::Local(root) => Storage::Local { root },
+            Location::S3 { bucket, prefix } => Storage::S3 {
+                client: self.create_client(proxy)?,
+                bucket,
+                prefix,
+            },
+        })
+    }
+
+    fn create_client(&self, proxy: &ProxyConfig) -> crate::Result<S3Client> {
+        let region = Region::try_from(&self.region)?;
+        let client = rusoto::client(proxy)?;
+        let creds = self.auth.build(&region, None)?;
+        Ok(S3Client::new_with(client, creds, region))
+    }
+
+    fn catalog(
+        &self,
+        catalog: &CatalogConfig,
+        namespace: &str,
+        name: &str,
+        proxy: &ProxyConfig,
+    ) -> crate::Result<RestCatalog> {
+        let client = HttpClient::new(None, proxy)?;
+        Ok(match catalog {
+            CatalogConfig::Rest { uri, prefix, auth } => RestCatalog::new(
+                client,
+                uri,
+                prefix.as_deref(),
+                namespace,
+                name,
+                iceberg::CatalogAuth::Http(auth.clone()),
+            ),
+            CatalogConfig::Glue { catalog_id } => {
+                let region = Region::try_from(&self.region)?;
+                let uri = format!("https://glue.{}.amazonaws.com/iceberg", region.name());
+                let credentials = self.auth.build(&region, None)?;
+                RestCatalog::new(
+                    client,
+                    &uri,
+                    Some(&format!("catalogs/{}", catalog_id)),
+                    namespace,
+                    name,
+                    iceberg::CatalogAuth::Aws {
+                        credentials,
+                        region,
+                    },
+                )
+            }
+        })
+    }
+}
+
+fn partition_column(columns: &[Column], name: &str) -> Result<usize, BuildError> {
+    let index = columns
+        .iter()
+        .position(|column| column.name == name)
+        .ok_or_else(|| BuildError::UnknownPartitionColumn {
+            column: name.to_owned(),
+        })?;
+    match columns[index].column_type {
+        ColumnType::String | ColumnType::Long | ColumnType::Boolean => Ok(index),
+        _ => Err(BuildError::PartitionColumnType {
+            column: name.to_owned(),
+        }),
+    }
+}
+
+/// A configured column, with the source of its values.
+#[derive(Clone, Debug)]
+struct Column {
+    name: String,
+    column_type: ColumnType,
+    source: ColumnSource,
+}
+
+#[derive(Clone, Debug)]
+enum ColumnSource {
+    Field(String),
+    Template(Template),
+}
+
+impl Column {
+    fn new(config: &ColumnConfig) -> Result<Self, BuildError> {
+        let source = match &config.template {
+            Some(template) => ColumnSource::Template(
+                Template::try_from(template.as_str()).context(ColumnTemplate {
+                    column: config.name.clone(),
+                })?,
+            ),
+            None => {
+                ColumnSource::Field(config.field.clone().unwrap_or_else(|| config.name.clone()))
+            }
+        };
+        Ok(Self {
+            name: config.name.clone(),
+            column_type: config.column_type,
+            source,
+        })
+    }
+
+    fn cell(&self, event: &Event) -> Option<Cell> {
+        match &self.source {
+            ColumnSource::Field(field) => event
+                .as_log()
+                .get(field)
+                .and_then(|value| Cell::new(value, self.column_type)),
+            ColumnSource::Template(template) => match template.render_string(event) {
+                Ok(value) => Cell::new(&Value::from(value), self.column_type),
+                Err(error) => {
+                    emit!(TemplateRenderingFailed {
+                        error,
+                        field: Some(&self.name),
+                        drop_event: false,
+                    });
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// A value of a row, converted to the type of its column.
+#[derive(Clone, Debug, PartialEq)]
+enum Cell {
+    String(String),
+    Long(i64),
+    Double(f64),
+    Boolean(bool),
+    /// Microseconds since the Unix epoch.
+    Timestamp(i64),
+}
+
+/// The values of an event, in the order of the configured columns. Values
+/// missing or not convertible to the type of their column are null.
+type Row = Vec<Option<Cell>>;
+
+impl Cell {
+    fn new(value: &Value, column_type: ColumnType) -> Option<Self> {
+        match (column_type, value) {
+            (_, Value::Null) => None,
+            (ColumnType::String, Value::Timestamp(timestamp)) => Some(Self::String(
+                timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            )),
+            (ColumnType::String, value) => Some(Self::String(value.to_string_lossy())),
+            (ColumnType::Long, Value::Integer(value)) => Some(Self::Long(*value)),
+            (ColumnType::Long, Value::Float(value)) => Some(Self::Long(*value as i64)),
+            (ColumnType::Double, Value::Float(value)) => Some(Self::Double(*value)),
+            (ColumnType::Double, Value::Integer(value)) => Some(Self::Double(*value as f64)),
+            (ColumnType::Boolean, Value::Boolean(value)) => Some(Self::Boolean(*value)),
+            (ColumnType::Timestamp, Value::Timestamp(timestamp)) => {
+                Some(Self::Timestamp(timestamp_micros(timestamp)))
+            }
+            (ColumnType::Long, value) => value.parse_text().map(Self::Long),
+            (ColumnType::Double, value) => value.parse_text().map(Self::Double),
+            (ColumnType::Boolean, value) => value.parse_text().map(Self::Boolean),
+            (ColumnType::Timestamp, value) => value
+                .parse_timestamp()
+                .map(|timestamp| Self::Timestamp(timestamp_micros(&timestamp))),
+        }
+    }
+
+    /// The value as written in partition paths and Delta Lake partition
+    /// values.
+    fn partition_value(&self) -> String {
+        match self {
+            Self::String(value) => value.clone(),
+            Self::Long(value) => value.to_string(),
+            Self::Double(value) => value.to_string(),
+            Self::Boolean(value) => value.to_string(),
+            Self::Timestamp(value) => value.to_string(),
+        }
+    }
+}
+
+fn timestamp_micros<Tz: chrono::TimeZone>(timestamp: &DateTime<Tz>) -> i64 {
+    timestamp.timestamp() * 1_000_000 + i64::from(timestamp.timestamp_subsec_micros())
+}
+
+/// The characters escaped in partition directory names, as Hive does.
+const PARTITION_VALUE: &AsciiSet = &CONTROLS
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'\'')
+    .add(b'*')
+    .add(b'/')
+    .add(b':')
+    .add(b'=')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'[')
+    .add(b']')
+    .add(b'^')
+    .add(b'{');
+
+/// The directory of a partition, relative to the data directory of the table.
+fn partition_path(fields: &[PartitionField], values: &[Option<Cell>], null: &str) -> String {
+    fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| match value {
+            Some(value) => format!(
+                "{}={}/",
+                field.name,
+                utf8_percent_encode(&value.partition_value(), PARTITION_VALUE)
+            ),
+            None => format!("{}={}/", field.name, null),
+        })
+        .collect()
+}
+
+/// A field of the partitioning of the table, with values from a column.
+#[derive(Clone, Debug)]
+struct PartitionField {
+    name: String,
+    column: usize,
+    /// The ID of the partition field in Iceberg tables.
+    field_id: i32,
+    column_type: ColumnType,
+}
+
+/// A data file written for a batch, relative to the table location.
+#[derive(Clone, Debug)]
+struct DataFile {
+    path: String,
+    partition: Vec<Option<Cell>>,
+    record_count: usize,
+    size: usize,
+}
+
+/// Opens the table when the sink starts, so that tables unavailable then
+/// are waited for rather than failing the sink.
+#[derive(Clone)]
+enum TableOpener {
+    DeltaLake {
+        storage: Storage,
+        partition_columns: Vec<usize>,
+    },
+    Iceberg {
+        catalog: RestCatalog,
+        config: DataLakeSinkConfig,
+        proxy: ProxyConfig,
+    },
+}
+
+impl TableOpener {
+    async fn open(&self, columns: &[Column], writer_id: &str) -> crate::Result<(Table, Storage)> {
+        Ok(match self {
+            Self::DeltaLake {
+                storage,
+                partition_columns,
+            } => {
+                let table =
+                    DeltaTable::open(storage, columns, partition_columns, writer_id).await?;
+                (Table::DeltaLake(table), storage.clone())
+            }
+            Self::Iceberg {
+                catalog,
+                config,
+                proxy,
+            } => {
+                let table = IcebergTable::open(catalog.clone(), columns, writer_id).await?;
+                let storage = config.storage(table.location(), proxy)?;
+                (Table::Iceberg(table), storage)
+            }
+        })
+    }
+
+    async fn healthcheck(self, columns: Vec<Column>) -> crate::Result<()> {
+        match self {
+            Self::DeltaLake { storage, .. } => DeltaTable::healthcheck(&storage).await,
+            Self::Iceberg { catalog, .. } => {
+                IcebergTable::open(catalog, &columns, "healthcheck").await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+enum Table {
+    DeltaLake(DeltaTable),
+    Iceberg(IcebergTable),
+}
+
+impl Table {
+    fn writer(&self) -> &ParquetWriter {
+        match self {
+            Self::DeltaLake(table) => table.writer(),
+            Self::Iceberg(table) => table.writer(),
+        }
+    }
+
+    fn partition_fields(&self) -> &[PartitionField] {
+        match self {
+            Self::DeltaLake(table) => table.partition_fields(),
+            Self::Iceberg(table) => table.partition_fields(),
+        }
+    }
+
+    fn data_path(&self, partition: &[Option<Cell>]) -> String {
+        let fields = self.partition_fields();
+        let (prefix, null) = match self {
+            Self::DeltaLake(_) => ("", delta::NULL_PARTITION),
+            Self::Iceberg(_) => ("data/", iceberg::NULL_PARTITION),
+        };
+        format!(
+            "{}{}part-{}.snappy.parquet",
+            prefix,
+            partition_path(fields, partition, null),
+            Uuid::new_v4()
+        )
+    }
+
+    async fn commit(
+        &mut self,
+        storage: &Storage,
+        sequence: i64,
+        files: &[DataFile],
+    ) -> crate::Result<()> {
+        match self {
+            Self::DeltaLake(table) => table.commit(storage, sequence, files).await,
+            Self::Iceberg(table) => table.commit(storage, sequence, files).await,
+        }
+    }
+}
+
+struct DataLakeSink {
+    opener: TableOpener,
+    columns: Vec<Column>,
+    writer_id: String,
+    max_events: usize,
+    max_bytes: usize,
+    timeout: Duration,
+    last_sequence: i64,
+    acker: Acker,
+}
+
+#[derive(Default)]
+struct Batch {
+    rows: Vec<Row>,
+    byte_size: usize,
+    finalizers: EventFinalizers,
+}
+
+impl DataLakeSink {
+    /// The sequence numbers of the batches follow the clock, so that they
+    /// keep increasing across restarts of the writer.
+    fn next_sequence(&mut self) -> i64 {
+        self.last_sequence = (self.last_sequence + 1).max(Utc::now().timestamp_millis());
+        self.last_sequence
+    }
+
+    fn encode(&self, table: &Table, rows: Vec<Row>) -> crate::Result<Vec<(DataFile, Vec<u8>)>> {
+        let fields = table.partition_fields();
+        let mut partitions = BTreeMap::<Vec<_>, Vec<Row>>::new();
+        for row in rows {
+            let partition = fields
+                .iter()
+                .map(|field| row[field.column].as_ref().map(Cell::partition_value))
+                .collect();
+            partitions.entry(partition).or_default().push(row);
+        }
+
+        partitions
+            .into_iter()
+            .map(|(_, rows)| {
+                let partition: Vec<_> = fields
+                    .iter()
+                    .map(|field| rows[0][field.column].clone())
+                    .collect();
+                let data = table
+                    .writer()
+                    .write(&parquet::batch(&self.columns, &rows))?;
+                let file = DataFile {
+                    path: table.data_path(&partition),
+                    partition,
+                    record_count: rows.len(),
+                    size: data.len(),
+                };
+                Ok((file, data))
+            })
+            .collect()
+    }
+
+    /// Writes the files of the batch and commits them, retrying until
+    /// both succeed.
+    async fn flush(&mut self, table: &mut Table, storage: &Storage, batch: Batch) {
+        let count = batch.rows.len();
+        let files = match self.encode(table, batch.rows) {
+            Ok(files) => files,
+            Err(error) => {
+                error!(message = "Failed to encode the batch as Parquet.", %error);
+                batch.finalizers.update_status(EventStatus::Errored);
+                self.acker.ack(count);
+                return;
+            }
+        };
+        let sequence = self.next_sequence();
+
+        let mut backoff = Backoff::default();
+        for (file, data) in &files {
+            while let Err(error) = storage.put(&file.path, data.clone()).await {
+                backoff.wait("write", &error).await;
+            }
+        }
+
+        let files: Vec<_> = files.into_iter().map(|(file, _)| file).collect();
+        while let Err(error) = table.commit(storage, sequence, &files).await {
+            backoff.wait("commit", &error).await;
+        }
+
+        emit!(DataLakeEventsCommitted {
+            count,
+            byte_size: batch.byte_size,
+            files: files.len(),
+        });
+        batch.finalizers.update_status(EventStatus::Delivered);
+        self.acker.ack(count);
+    }
+
+    async fn run(&mut self, mut input: BoxStream<'_, Event>) {
+        let mut backoff = Backoff::default();
+        let (mut table, storage) = loop {
+            match self.opener.open(&self.columns, &self.writer_id).await {
+                Ok(opened) => break opened,
+                Err(error) => backoff.wait("open", &error).await,
+            }
+        };
+
+        let mut batch = Batch::default();
+        let deadline = sleep(self.timeout);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                event = input.next() => match event {
+                    Some(mut event) => {
+                        if batch.rows.is_empty() {
+                            deadline.as_mut().reset(Instant::now() + self.timeout);
+                        }
+                        batch.byte_size += event.size_of();
+                        batch.finalizers.merge(event.metadata_mut().take_finalizers());
+                        batch.rows.push(self.columns.iter().map(|column| column.cell(&event)).collect());
+                        if batch.rows.len() >= self.max_events || batch.byte_size >= self.max_bytes {
+                            self.flush(&mut table, &storage, std::mem::take(&mut batch)).await;
+                        }
+                    }
+                    None => break,
+                },
+                _ = &mut deadline, if !batch.rows.is_empty() => {
+                    self.flush(&mut table, &storage, std::mem::take(&mut batch)).await;
+                }
+            }
+        }
+        if !batch.rows.is_empty() {
+            self.flush(&mut table, &storage, batch).await;
+        }
+    }
+}
+
+#[async_trait]
+impl StreamSink for DataLakeSink {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        DataLakeSink::run(self, input).await;
+        Ok(())
+    }
+}
+
+/// Exponential backoff between the attempts of a step of a batch.
+struct Backoff {
+    secs: u64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self { secs: 1 }
+    }
+}
+
+impl Backoff {
+    async fn wait(&mut self, stage: &'static str, error: &crate::Error) {
+        emit!(DataLakeRequestFailed {
+            stage,
+            error,
+            retry_secs: self.secs,
+        });
+        sleep(Duration::from_secs(self.secs)).await;
+        self.secs = (self.secs * 2).min(MAX_BACKOFF_SECS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+    use chrono::TimeZone;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<DataLakeSinkConfig>();
+    }
+
+    fn column(name: &str, column_type: ColumnType, template: Option<&str>) -> Column {
+        Column::new(&ColumnConfig {
+            name: name.into(),
+            column_type,
+            field: None,
+            template: template.map(Into::into),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn converts_values_to_column_types() {
+        let mut log = LogEvent::from("hello");
+        log.insert("count", "42");
+        log.insert("ratio", 3_i64);
+        log.insert("ok", true);
+        log.insert(
+            "timestamp",
+            Utc.ymd(2021, 9, 1).and_hms_micro(12, 30, 0, 250),
+        );
+        let event = Event::from(log);
+
+        let cells: Vec<_> = vec![
+            column("message", ColumnType::String, None),
+            column("count", ColumnType::Long, None),
+            column("ratio", ColumnType::Double, None),
+            column("ok", ColumnType::Boolean, None),
+            column("timestamp", ColumnType::Timestamp, None),
+            column("date", ColumnType::String, Some("%F")),
+            column("missing", ColumnType::String, None),
+            column("message", ColumnType::Long, None),
+        ]
+        .iter()
+        .map(|column| column.cell(&event))
+        .collect();
+
+        assert_eq!(
+            cells,
+            vec![
+                Some(Cell::String("hello".into())),
+                Some(Cell::Long(42)),
+                Some(Cell::Double(3.0)),
+                Some(Cell::Boolean(true)),
+                Some(Cell::Timestamp(1_630_499_400_000_250)),
+                Some(Cell::String("2021-09-01".into())),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_partition_paths() {
+        let fields = vec![
+            PartitionField {
+                name: "service".into(),
+                column: 0,
+                field_id: 1000,
+                column_type: ColumnType::String,
+            },
+            PartitionField {
+                name: "hour".into(),
+                column: 1,
+                field_id: 1001,
+                column_type: ColumnType::String,
+            },
+        ];
+        let path = partition_path(
+            &fields,
+            &[None, Some(Cell::String("2021-09-01 12:00".into()))],
+            "null",
+        );
+        assert_eq!(path, "service=null/hour=2021-09-01 12%3A00/");
+    }
+}
@@ -0,0 +1,148 @@
+//! The Parquet data files of the tables, written from the rows of a batch
+//! laid out as an [`EventBatch`].
+
+use super::{Cell, Column, ColumnType, Row};
+use crate::{
+    event::{batch, EventBatch},
+    sinks::util::parquet::{ParquetColumn, ParquetCompression, ParquetType, ParquetWriter},
+};
+use bytes::Bytes;
+use parquet::errors::Result;
+use std::num::NonZeroUsize;
+
+const ROW_GROUP_SIZE: usize = 100_000;
+
+impl From<ColumnType> for ParquetType {
+    fn from(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::String => Self::String,
+            ColumnType::Long => Self::Int64,
+            ColumnType::Double => Self::Double,
+            ColumnType::Boolean => Self::Boolean,
+            ColumnType::Timestamp => Self::TimestampMicros,
+        }
+    }
+}
+
+/// The writer of the data files of a table, with a column for each of the
+/// configured columns and the ID the table resolves it by, if any. All the
+/// columns are optional and the files are compressed with Snappy.
+pub(super) fn writer<'a>(
+    columns: impl IntoIterator<Item = (&'a Column, Option<i32>)>,
+) -> Result<ParquetWriter> {
+    let columns = columns
+        .into_iter()
+        .map(|(column, field_id)| ParquetColumn {
+            field_id,
+            ..ParquetColumn::new(column.name.as_str(), column.column_type.into())
+        })
+        .collect();
+    ParquetWriter::new(
+        columns,
+        ParquetCompression::Snappy,
+        NonZeroUsize::new(ROW_GROUP_SIZE).unwrap(),
+    )
+}
+
+/// The rows as a batch, with a column named after each configured column.
+/// Timestamps are held as microseconds, which is how they are written.
+pub(super) fn batch(columns: &[Column], rows: &[Row]) -> EventBatch {
+    let mut batch = EventBatch::with_len(rows.len());
+    for (index, column) in columns.iter().enumerate() {
+        let cells = rows.iter().map(|row| row[index].as_ref());
+        let values = match column.column_type {
+            ColumnType::String => batch::Column::String(
+                cells
+                    .map(|cell| match cell {
+                        Some(Cell::String(value)) => Some(Bytes::from(value.clone())),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            ColumnType::Long | ColumnType::Timestamp => batch::Column::Integer(
+                cells
+                    .map(|cell| match cell {
+                        Some(Cell::Long(value)) | Some(Cell::Timestamp(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            ColumnType::Double => batch::Column::Float(
+                cells
+                    .map(|cell| match cell {
+                        Some(Cell::Double(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            ColumnType::Boolean => batch::Column::Boolean(
+                cells
+                    .map(|cell| match cell {
+                        Some(Cell::Boolean(value)) => Some(*value),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+        };
+        batch.insert_column(column.name.clone(), values);
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ColumnSource;
+    use super::*;
+    use crate::event::Value;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+        util::cursor::SliceableCursor,
+    };
+
+    fn column(name: &str, column_type: ColumnType) -> Column {
+        Column {
+            name: name.into(),
+            column_type,
+            source: ColumnSource::Field(name.into()),
+        }
+    }
+
+    #[test]
+    fn encodes_rows_with_nulls() {
+        let columns = vec![
+            column("date", ColumnType::String),
+            column("message", ColumnType::String),
+            column("status", ColumnType::Long),
+        ];
+        let rows = vec![
+            vec![
+                None,
+                Some(Cell::String("first".into())),
+                Some(Cell::Long(200)),
+            ],
+            vec![None, Some(Cell::String("second".into())), None],
+        ];
+
+        // The first column is a partition column, which isn't written.
+        let writer = writer(columns[1..].iter().zip(vec![Some(2), Some(3)])).unwrap();
+        let batch = batch(&columns, &rows);
+        assert_eq!(
+            batch.column("status").unwrap().get(0),
+            Some(Value::Integer(200))
+        );
+
+        let data = writer.write(&batch).unwrap();
+        let reader = SerializedFileReader::new(SliceableCursor::new(data)).unwrap();
+        let schema = reader.metadata().file_metadata().schema();
+        assert_eq!(schema.get_fields().len(), 2);
+        assert_eq!(schema.get_fields()[0].get_basic_info().id(), 2);
+
+        let read: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].get_string(0).unwrap(), "first");
+        assert_eq!(read[0].get_long(1).unwrap(), 200);
+        assert_eq!(read[1].get_string(0).unwrap(), "second");
+        assert!(read[1].get_long(1).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use bytes::Bytes;
+use http::StatusCode;
+use rusoto_core::RusotoError;
+use rusoto_s3::{
+    GetObjectError, GetObjectRequest, HeadObjectRequest, ListObjectsV2Request, PutObjectRequest,
+    S3Client, S3,
+};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+/// Where a table is stored, parsed from its location.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Location {
+    Local(PathBuf),
+    S3 { bucket: String, prefix: String },
+}
+
+impl Location {
+    /// Parses `s3://` URIs, `file:` URIs and absolute paths, without their
+    /// trailing slashes.
+    pub(super) fn parse(location: &str) -> crate::Result<Self> {
+        let location = location.trim_end_matches('/');
+        if let Some(rest) = location
+            .strip_prefix("s3://")
+            .or_else(|| location.strip_prefix("s3a://"))
+        {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            Ok(Self::S3 {
+                bucket: bucket.to_owned(),
+                prefix: prefix.to_owned(),
+            })
+        } else if let Some(path) = location.strip_prefix("file:") {
+            let path = path.strip_prefix("//").unwrap_or(path);
+            Ok(Self::Local(PathBuf::from(path)))
+        } else if location.starts_with('/') {
+            Ok(Self::Local(PathBuf::from(location)))
+        } else {
+            Err(format!(
+                "Unsupported table location {:?}, expected an `s3://` URI or an absolute path.",
+                location
+            )
+            .into())
+        }
+    }
+}
+
+/// The files of a table, by their paths relative to its location.
+#[derive(Clone)]
+pub(super) enum Storage {
+    Local {
+        root: PathBuf,
+    },
+    S3 {
+        client: S3Client,
+        bucket: String,
+        prefix: String,
+    },
+}
+
+impl Storage {
+    fn key(prefix: &str, path: &str) -> String {
+        if prefix.is_empty() {
+            path.to_owned()
+        } else {
+            format!("{}/{}", prefix, path)
+        }
+    }
+
+    /// The absolute location of a file, as recorded in table metadata.
+    pub(super) fn uri(&self, path: &str) -> String {
+        match self {
+            Self::Local { root } => format!("file://{}", root.join(path).display()),
+            Self::S3 { bucket, prefix, .. } => {
+                format!("s3://{}/{}", bucket, Self::key(prefix, path))
+            }
+        }
+    }
+
+    /// The path of a file from its absolute location, if it is stored in
+    /// the table location.
+    pub(super) fn relative(&self, uri: &str) -> Option<String> {
+        match (self, Location::parse(uri).ok()?) {
+            (Self::Local { root }, Location::Local(path)) => path
+                .strip_prefix(root)
+                .ok()
+                .and_then(Path::to_str)
+                .map(Into::into),
+            (
+                Self::S3 { bucket, prefix, .. },
+                Location::S3 {
+                    bucket: uri_bucket,
+                    prefix: key,
+                },
+            ) if *bucket == uri_bucket => {
+                if prefix.is_empty() {
+                    Some(key)
+                } else {
+                    key.strip_prefix(prefix.as_str())?
+                        .strip_prefix('/')
+                        .map(Into::into)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) async fn put(&self, path: &str, data: Vec<u8>) -> crate::Result<()> {
+        match self {
+            Self::Local { root } => {
+                let path = root.join(path);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, data).await?;
+            }
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                client
+                    .put_object(PutObjectRequest {
+                        bucket: bucket.clone(),
+                        key: Self::key(prefix, path),
+                        body: Some(data.into()),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the file unless it exists, returning whether it was written.
+    ///
+    /// Local files are linked into place, which fails if the file exists.
+    /// S3 has no such condition, so the file is looked up before it is
+    /// written, and concurrent writers could both write it.
+    pub(super) async fn put_if_absent(&self, path: &str, data: Vec<u8>) -> crate::Result<bool> {
+        match self {
+            Self::Local { root } => {
+                let target = root.join(path);
+                let tmp_path = format!("{}.{}.tmp", path, Uuid::new_v4());
+                self.put(&tmp_path, data).await?;
+                let tmp_path = root.join(tmp_path);
+                let linked = tokio::fs::hard_link(&tmp_path, &target).await;
+                tokio::fs::remove_file(&tmp_path).await?;
+                match linked {
+                    Ok(()) => Ok(true),
+                    Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+                    Err(error) => Err(error.into()),
+                }
+            }
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let request = HeadObjectRequest {
+                    bucket: bucket.clone(),
+                    key: Self::key(prefix, path),
+                    ..Default::default()
+                };
+                match client.head_object(request).await {
+                    Ok(_) => return Ok(false),
+                    Err(RusotoError::Unknown(response))
+                        if response.status == StatusCode::NOT_FOUND => {}
+                    Err(error) => return Err(error.into()),
+                }
+                self.put(path, data).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    pub(super) async fn get(&self, path: &str) -> crate::Result<Option<Bytes>> {
+        match self {
+            Self::Local { root } => match tokio::fs::read(root.join(path)).await {
+                Ok(data) => Ok(Some(data.into())),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(error.into()),
+            },
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let request = GetObjectRequest {
+                    bucket: bucket.clone(),
+                    key: Self::key(prefix, path),
+                    ..Default::default()
+                };
+                let output = match client.get_object(request).await {
+                    Ok(output) => output,
+                    Err(RusotoError::Service(GetObjectError::NoSuchKey(_))) => return Ok(None),
+                    Err(error) => return Err(error.into()),
+                };
+                let mut data = Vec::new();
+                if let Some(body) = output.body {
+                    body.into_async_read().read_to_end(&mut data).await?;
+                }
+                Ok(Some(data.into()))
+            }
+        }
+    }
+
+    /// The names of the files in a directory, empty if it doesn't exist.
+    pub(super) async fn list(&self, dir: &str) -> crate::Result<Vec<String>> {
+        match self {
+            Self::Local { root } => {
+                let mut entries = match tokio::fs::read_dir(root.join(dir)).await {
+                    Ok(entries) => entries,
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+                    Err(error) => return Err(error.into()),
+                };
+                let mut names = Vec::new();
+                while let Some(entry) = entries.next_entry().await? {
+                    if let Some(name) = entry.file_name().to_str() {
+                        names.push(name.to_owned());
+                    }
+                }
+                Ok(names)
+            }
+            Self::S3 {
+                client,
+                bucket,
+                prefix,
+            } => {
+                let dir_prefix = format!("{}/", Self::key(prefix, dir));
+                let mut names = Vec::new();
+                let mut continuation_token = None;
+                loop {
+                    let output = client
+                        .list_objects_v2(ListObjectsV2Request {
+                            bucket: bucket.clone(),
+                            prefix: Some(dir_prefix.clone()),
+                            delimiter: Some("/".to_owned()),
+                            continuation_token,
+                            ..Default::default()
+                        })
+                        .await?;
+                    names.extend(
+                        output
+                            .contents
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter_map(|object| object.key)
+                            .filter_map(|key| key.strip_prefix(&dir_prefix).map(Into::into)),
+                    );
+                    continuation_token = output.next_continuation_token;
+                    if continuation_token.is_none() {
+                        return Ok(names);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(
+            Location::parse("s3://bucket/warehouse/logs/").unwrap(),
+            Location::S3 {
+                bucket: "bucket".into(),
+                prefix: "warehouse/logs".into()
+            }
+        );
+        assert_eq!(
+            Location::parse("s3://bucket").unwrap(),
+            Location::S3 {
+                bucket: "bucket".into(),
+                prefix: "".into()
+            }
+        );
+        assert_eq!(
+            Location::parse("file:/tmp/warehouse").unwrap(),
+            Location::Local("/tmp/warehouse".into())
+        );
+        assert_eq!(
+            Location::parse("file:///tmp/warehouse").unwrap(),
+            Location::Local("/tmp/warehouse".into())
+        );
+        assert!(Location::parse("hdfs://namenode/warehouse").is_err());
+    }
+
+    #[tokio::test]
+    async fn local_files_are_only_put_if_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::Local {
+            root: dir.path().into(),
+        };
+
+        assert!(storage.list("_delta_log").await.unwrap().is_empty());
+        assert!(storage
+            .put_if_absent("_delta_log/00000000000000000000.json", b"first".to_vec())
+            .await
+            .unwrap());
+        assert!(!storage
+            .put_if_absent("_delta_log/00000000000000000000.json", b"second".to_vec())
+            .await
+            .unwrap());
+        assert_eq!(
+            storage
+                .get("_delta_log/00000000000000000000.json")
+                .await
+                .unwrap(),
+            Some(Bytes::from("first"))
+        );
+        assert_eq!(
+            storage.list("_delta_log").await.unwrap(),
+            vec!["00000000000000000000.json".to_owned()]
+        );
+        assert_eq!(
+            storage.relative(&storage.uri("metadata/snap-1.avro")),
+            Some("metadata/snap-1.avro".to_owned())
+        );
+    }
+}
@@ -158,7 +158,8 @@ impl DatadogEndpoint {
             MetricValue::Distribution {
                 statistic: StatisticKind::Summary,
                 ..
-            } => Self::Distribution,
+            }
+            | MetricValue::Sketch { .. } => Self::Distribution,
             _ => Self::Series,
         }
     }
@@ -475,32 +476,29 @@ fn encode_distribution_events(
                 encode_namespace(event.namespace().or(default_namespace), '.', event.name());
             let ts = encode_timestamp(event.timestamp());
             let tags = event.tags().map(encode_tags);
-            match event.kind() {
+            let samples = match event.kind() {
                 MetricKind::Incremental => match event.value() {
                     MetricValue::Distribution {
                         samples,
                         statistic: StatisticKind::Summary,
-                    } => {
-                        let samples = samples
-                            .iter()
-                            .map(|sample| (0..sample.rate).map(move |_| sample.value))
-                            .flatten()
-                            .collect::<Vec<_>>();
-
-                        if samples.is_empty() {
-                            None
-                        } else {
-                            Some(DatadogDistributionMetric {
-                                metric: fullname,
-                                interval: Some(interval),
-                                points: vec![DatadogPoint(ts, samples)],
-                                tags,
-                            })
-                        }
-                    }
-                    _ => None,
+                    } => expand_samples(samples),
+                    // Sketches are sent as the value of each of their bins,
+                    // which keeps the quantiles within the sketch's accuracy.
+                    MetricValue::Sketch { sketch } => expand_samples(&sketch.samples()),
+                    _ => return None,
                 },
-                _ => None,
+                _ => return None,
+            };
+
+            if samples.is_empty() {
+                None
+            } else {
+                Some(DatadogDistributionMetric {
+                    metric: fullname,
+                    interval: Some(interval),
+                    points: vec![DatadogPoint(ts, samples)],
+                    tags,
+                })
             }
         })
         .collect();
@@ -508,10 +506,21 @@ fn encode_distribution_events(
     DatadogRequest { series }
 }
 
+fn expand_samples(samples: &[Sample]) -> Vec<f64> {
+    samples
+        .iter()
+        .map(|sample| (0..sample.rate).map(move |_| sample.value))
+        .flatten()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{event::metric::Sample, sinks::util::test::load_sink};
+    use crate::{
+        event::metric::{DDSketch, Sample},
+        sinks::util::test::load_sink,
+    };
     use chrono::offset::TimeZone;
     use http::Method;
     use pretty_assertions::assert_eq;
@@ -794,4 +803,23 @@ mod tests {
             r#"{"series":[{"metric":"requests","interval":60,"points":[[1542182950,[1.0,1.0,1.0,2.0,2.0,2.0,3.0,3.0]]],"tags":null}]}"#
         );
     }
+    #[test]
+    fn encode_datadog_sketch() {
+        let events = vec![Metric::new(
+            "requests",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, vector_core::samples![1.0 => 2, 100.0 => 1]),
+            },
+        )
+        .with_timestamp(Some(ts()))];
+        let input = encode_distribution_events(events, None, 60);
+
+        assert_eq!(input.series.len(), 1);
+        let points = &input.series[0].points[0].1;
+        assert_eq!(points.len(), 3);
+        assert!((points[0] - 1.0).abs() <= 0.01);
+        assert_eq!(points[0], points[1]);
+        assert!((points[2] - 100.0).abs() <= 1.0);
+    }
 }
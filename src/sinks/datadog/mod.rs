@@ -10,6 +10,8 @@ use std::sync::Arc;
 pub mod events;
 pub mod logs;
 pub mod metrics;
+#[cfg(feature = "sinks-datadog_traces")]
+pub mod traces;
 
 type ApiKey = Arc<str>;
 
@@ -0,0 +1,267 @@
+//! Mapping of spans onto the spans of the Datadog trace intake.
+//!
+//! Spans are expected in the layout of the `opentelemetry` source, and are
+//! mapped following the semantic conventions of OpenTelemetry, as the
+//! Datadog exporter of the OpenTelemetry collector does. Attributes of the
+//! span and of its resource become the tags of the Datadog span, in `meta`
+//! for the ones holding text and in `metrics` for the numeric ones.
+
+use crate::{
+    event::{otlp, trace::fields, TraceEvent, Value},
+    proto::datadog::trace::Span,
+    sinks::util::EncodedLength,
+};
+use prost::Message;
+use std::collections::{BTreeMap, HashMap};
+
+/// The field of spans holding the attributes of their resource, which is
+/// where the `opentelemetry` source puts them.
+const RESOURCES: &str = "resources";
+const DEFAULT_SERVICE: &str = "unknown_service";
+/// The sampling priority of spans that don't set one, which keeps them.
+const AUTO_KEEP: i32 = 1;
+
+pub(super) const SAMPLING_PRIORITY_KEY: &str = "_sampling_priority_v1";
+pub(super) const TOP_LEVEL_KEY: &str = "_top_level";
+pub(super) const MEASURED_KEY: &str = "_dd.measured";
+
+/// A span along with the properties of the tracer it comes from, which are
+/// sent once per tracer rather than with each span.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct DatadogSpan {
+    pub(super) span: Span,
+    pub(super) tracer: Tracer,
+    pub(super) priority: i32,
+    pub(super) http_status_code: u32,
+}
+
+/// What the spans of a tracer payload have in common.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(super) struct Tracer {
+    pub(super) hostname: String,
+    pub(super) env: String,
+    pub(super) app_version: String,
+    pub(super) language: String,
+}
+
+impl EncodedLength for DatadogSpan {
+    fn encoded_length(&self) -> usize {
+        self.span.encoded_len()
+    }
+}
+
+/// Converts a span, or returns the field it's missing to be a valid span.
+pub(super) fn convert(trace: TraceEvent, default_env: &str) -> Result<DatadogSpan, &'static str> {
+    let mut fields = trace.into_parts().0;
+    let mut resources = take_map(&mut fields, RESOURCES);
+    let mut attributes = take_map(&mut fields, fields::ATTRIBUTES);
+
+    let trace_id = take_id(&mut fields, fields::TRACE_ID).ok_or(fields::TRACE_ID)?;
+    let span_id = take_id(&mut fields, fields::SPAN_ID).ok_or(fields::SPAN_ID)?;
+    let parent_id = take_id(&mut fields, fields::PARENT_SPAN_ID).unwrap_or(0);
+    let start = take_time(&mut fields, fields::START_TIME).ok_or(fields::START_TIME)?;
+    let end = take_time(&mut fields, fields::END_TIME).unwrap_or(start);
+    let span_name = take_string(&mut fields, fields::NAME).unwrap_or_default();
+    let kind = take_string(&mut fields, fields::KIND).unwrap_or_else(|| "internal".to_owned());
+
+    let mut meta = HashMap::new();
+    let mut metrics = HashMap::new();
+    let mut error = 0;
+    if let Some(Value::Map(mut status)) = fields.remove(fields::STATUS) {
+        if take_string(&mut status, "code").as_deref() == Some("error") {
+            error = 1;
+            if let Some(message) = take_string(&mut status, "message").filter(|m| !m.is_empty()) {
+                meta.insert("error.msg".to_owned(), message);
+            }
+        }
+    }
+
+    let service = take_string(&mut resources, "service.name")
+        .or_else(|| take_string(&mut attributes, "service.name"))
+        .unwrap_or_else(|| DEFAULT_SERVICE.to_owned());
+    let tracer = Tracer {
+        hostname: take_string(&mut resources, "host.name").unwrap_or_default(),
+        env: take_string(&mut resources, "deployment.environment")
+            .unwrap_or_else(|| default_env.to_owned()),
+        app_version: take_string(&mut resources, "service.version").unwrap_or_default(),
+        language: take_string(&mut resources, "telemetry.sdk.language").unwrap_or_default(),
+    };
+
+    let name = take_string(&mut attributes, "operation.name")
+        .unwrap_or_else(|| format!("opentelemetry.{}", kind));
+    let resource = take_string(&mut attributes, "resource.name").unwrap_or_else(|| {
+        match (
+            string_value(attributes.get("http.method")),
+            string_value(attributes.get("http.route")),
+        ) {
+            (Some(method), Some(route)) => format!("{} {}", method, route),
+            _ => span_name.clone(),
+        }
+    });
+    let r#type = take_string(&mut attributes, "span.type").unwrap_or_else(|| {
+        if attributes.contains_key("db.system") {
+            "db"
+        } else {
+            match kind.as_str() {
+                "server" => "web",
+                "client" => "http",
+                _ => "custom",
+            }
+        }
+        .to_owned()
+    });
+    let http_status_code = match attributes.get("http.status_code") {
+        Some(Value::Integer(code)) => *code as u32,
+        value => string_value(value)
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(0),
+    };
+    let priority = match attributes.remove("sampling.priority") {
+        Some(Value::Integer(priority)) => priority as i32,
+        Some(Value::Float(priority)) => priority as i32,
+        _ => AUTO_KEEP,
+    };
+
+    for (key, value) in resources.into_iter().chain(attributes) {
+        match value {
+            Value::Integer(number) => {
+                metrics.insert(key, number as f64);
+            }
+            Value::Float(number) => {
+                metrics.insert(key, number);
+            }
+            Value::Null => (),
+            value => {
+                meta.insert(key, value.to_string_lossy());
+            }
+        }
+    }
+    meta.insert("span.kind".to_owned(), kind);
+    if !span_name.is_empty() && span_name != resource {
+        meta.insert("otel.span_name".to_owned(), span_name);
+    }
+    metrics.insert(SAMPLING_PRIORITY_KEY.to_owned(), f64::from(priority));
+
+    Ok(DatadogSpan {
+        span: Span {
+            service,
+            name,
+            resource,
+            trace_id,
+            span_id,
+            parent_id,
+            start,
+            duration: end.saturating_sub(start).max(0),
+            error,
+            meta,
+            metrics,
+            r#type,
+        },
+        tracer,
+        priority,
+        http_status_code,
+    })
+}
+
+fn string_value(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::Bytes(bytes) | Value::String(bytes)) => {
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+        _ => None,
+    }
+}
+
+fn take_string(fields: &mut BTreeMap<String, Value>, key: &str) -> Option<String> {
+    let value = fields.remove(key);
+    string_value(value.as_ref())
+}
+
+/// Datadog IDs are 64 bits, so only the lower 64 bits of the 128 bits trace
+/// IDs of OpenTelemetry are kept.
+fn take_id(fields: &mut BTreeMap<String, Value>, key: &str) -> Option<u64> {
+    match fields.remove(key) {
+        Some(Value::Integer(id)) => Some(id as u64),
+        value => {
+            let bytes = otlp::decode_hex(&string_value(value.as_ref())?)?;
+            let lower = &bytes[bytes.len().saturating_sub(8)..];
+            let id = lower
+                .iter()
+                .fold(0u64, |id, byte| (id << 8) | u64::from(*byte));
+            Some(id).filter(|id| *id != 0)
+        }
+    }
+}
+
+fn take_time(fields: &mut BTreeMap<String, Value>, key: &str) -> Option<i64> {
+    match fields.remove(key) {
+        Some(Value::Timestamp(timestamp)) => Some(otlp::timestamp_to_nanos(timestamp) as i64),
+        _ => None,
+    }
+}
+
+fn take_map(fields: &mut BTreeMap<String, Value>, key: &str) -> BTreeMap<String, Value> {
+    match fields.remove(key) {
+        Some(Value::Map(map)) => map,
+        _ => BTreeMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn converts_spans() {
+        let mut trace = TraceEvent::default();
+        trace.insert(
+            fields::TRACE_ID,
+            format!("{}{}", "ff".repeat(8), "01".repeat(8)),
+        );
+        trace.insert(fields::SPAN_ID, "00000000000000ab");
+        trace.insert(fields::NAME, "HTTP GET");
+        trace.insert(fields::KIND, "server");
+        trace.insert(fields::START_TIME, Utc.timestamp(1_628_000_000, 0));
+        trace.insert(fields::END_TIME, Utc.timestamp(1_628_000_001, 0));
+        trace.insert("status.code", "error");
+        trace.insert("status.message", "timed out");
+        let attributes = trace.as_map_mut();
+        let mut map = BTreeMap::new();
+        map.insert("http.method".to_owned(), Value::from("GET"));
+        map.insert("http.route".to_owned(), Value::from("/cart"));
+        map.insert("http.status_code".to_owned(), Value::from(504));
+        attributes.insert(fields::ATTRIBUTES.to_owned(), Value::from(map));
+        let mut map = BTreeMap::new();
+        map.insert("service.name".to_owned(), Value::from("shop"));
+        map.insert("host.name".to_owned(), Value::from("web-1"));
+        attributes.insert(RESOURCES.to_owned(), Value::from(map));
+
+        let converted = convert(trace, "prod").unwrap();
+        let span = &converted.span;
+        assert_eq!(span.trace_id, 0x0101_0101_0101_0101);
+        assert_eq!(span.span_id, 0xab);
+        assert_eq!(span.parent_id, 0);
+        assert_eq!(span.service, "shop");
+        assert_eq!(span.name, "opentelemetry.server");
+        assert_eq!(span.resource, "GET /cart");
+        assert_eq!(span.r#type, "web");
+        assert_eq!(span.duration, 1_000_000_000);
+        assert_eq!(span.error, 1);
+        assert_eq!(span.meta["error.msg"], "timed out");
+        assert_eq!(span.meta["http.method"], "GET");
+        assert_eq!(span.metrics["http.status_code"], 504.0);
+        assert_eq!(span.metrics[SAMPLING_PRIORITY_KEY], 1.0);
+        assert_eq!(converted.http_status_code, 504);
+        assert_eq!(converted.tracer.hostname, "web-1");
+        assert_eq!(converted.tracer.env, "prod");
+    }
+
+    #[test]
+    fn rejects_spans_without_ids() {
+        let mut trace = TraceEvent::default();
+        trace.insert(fields::SPAN_ID, "00000000000000ab");
+        trace.insert(fields::START_TIME, Utc.timestamp(1_628_000_000, 0));
+        assert_eq!(convert(trace, ""), Err(fields::TRACE_ID));
+    }
+}
@@ -0,0 +1,198 @@
+//! Sends spans to the Datadog trace intake, along with the APM stats the
+//! Datadog Agent would compute from them.
+
+mod convert;
+mod service;
+mod stats;
+
+use self::{
+    convert::{convert, DatadogSpan},
+    service::{build_request, PayloadSettings, TracesRetryLogic, TracesService},
+};
+use super::{healthcheck, ApiKey};
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    http::HttpClient,
+    internal_events::DatadogTracesSpanInvalid,
+    sinks::{
+        util::{
+            BatchConfig, BatchSettings, EncodedEvent, PartitionBatchSink, PartitionBuffer,
+            PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig, VecBuffer,
+        },
+        Healthcheck, VectorSink,
+    },
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use indoc::indoc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tower::ServiceBuilder;
+
+/// The environment the Agent reports when none is configured.
+const DEFAULT_ENV: &str = "none";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DatadogTracesConfig {
+    endpoint: Option<String>,
+    #[serde(default = "default_site")]
+    site: String,
+    default_api_key: String,
+    /// The environment of the spans whose resource doesn't set
+    /// `deployment.environment`.
+    env: Option<String>,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    batch: BatchConfig,
+    #[serde(default)]
+    request: TowerRequestConfig,
+}
+
+fn default_site() -> String {
+    "datadoghq.com".to_owned()
+}
+
+inventory::submit! {
+    SinkDescription::new::<DatadogTracesConfig>("datadog_traces")
+}
+
+impl GenerateConfig for DatadogTracesConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(indoc! {r#"
+            default_api_key = "${DATADOG_API_KEY_ENV_VAR}"
+        "#})
+        .unwrap()
+    }
+}
+
+impl DatadogTracesConfig {
+    fn get_endpoint(&self) -> String {
+        self.endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://trace.agent.{}", &self.site))
+    }
+
+    fn get_api_endpoint(&self) -> String {
+        self.endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://api.{}", &self.site))
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "datadog_traces")]
+impl SinkConfig for DatadogTracesConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        // The intake takes payloads of up to 3.2MB.
+        let batch = BatchSettings::default()
+            .bytes(bytesize::mib(3u64))
+            .events(1000)
+            .timeout(2)
+            .parse_config(self.batch)?;
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+
+        let tls_settings = MaybeTlsSettings::from_config(
+            &Some(self.tls.clone().unwrap_or_else(TlsConfig::enabled)),
+            false,
+        )?;
+        let client = HttpClient::new(tls_settings, cx.proxy())?;
+        let healthcheck = healthcheck(
+            self.get_api_endpoint(),
+            self.default_api_key.clone(),
+            client.clone(),
+        )
+        .boxed();
+
+        let env = self.env.clone().unwrap_or_else(|| DEFAULT_ENV.to_owned());
+        let settings = PayloadSettings {
+            hostname: crate::get_hostname().unwrap_or_default(),
+            env: env.clone(),
+        };
+        let service = TracesService::new(client, &self.get_endpoint())?;
+        let svc = ServiceBuilder::new()
+            .map(move |batch| build_request(batch, &settings))
+            .settings(request, TracesRetryLogic)
+            .service(service);
+
+        let default_api_key: ApiKey = Arc::from(self.default_api_key.as_str());
+        let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .with_flat_map(move |event| {
+                stream::iter(encode_event(event, &default_api_key, &env)).map(Ok)
+            })
+            .sink_map_err(|error| error!(message = "Fatal datadog_traces sink error.", %error));
+
+        Ok((VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Trace
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "datadog_traces"
+    }
+}
+
+fn encode_event(
+    event: Event,
+    default_api_key: &ApiKey,
+    env: &str,
+) -> Option<EncodedEvent<PartitionInnerBuffer<DatadogSpan, ApiKey>>> {
+    let mut trace = event.into_trace();
+    let finalizers = trace.metadata_mut().take_finalizers();
+    let api_key = Arc::clone(
+        trace
+            .metadata()
+            .datadog_api_key()
+            .as_ref()
+            .unwrap_or(default_api_key),
+    );
+
+    match convert(trace, env) {
+        Ok(span) => Some(EncodedEvent {
+            item: PartitionInnerBuffer::new(span, api_key),
+            finalizers,
+        }),
+        Err(field) => {
+            emit!(DatadogTracesSpanInvalid { field });
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{trace::fields, TraceEvent};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<DatadogTracesConfig>();
+    }
+
+    #[test]
+    fn partitions_by_api_key() {
+        let mut trace = TraceEvent::default();
+        trace.insert(fields::TRACE_ID, "01".repeat(16));
+        trace.insert(fields::SPAN_ID, "02".repeat(8));
+        trace.insert(fields::START_TIME, Utc.timestamp(1_628_000_000, 0));
+        trace
+            .metadata_mut()
+            .set_datadog_api_key(Some(Arc::from("from_metadata")));
+
+        let default_api_key = Arc::from("default");
+        let (span, api_key) = encode_event(trace.into(), &default_api_key, "none")
+            .unwrap()
+            .item
+            .into_parts();
+        assert_eq!(&*api_key, "from_metadata");
+        assert_eq!(span.tracer.env, "none");
+
+        let invalid = TraceEvent::default();
+        assert!(encode_event(invalid.into(), &default_api_key, "none").is_none());
+    }
+}
@@ -0,0 +1,337 @@
+use super::{
+    convert::{DatadogSpan, Tracer, TOP_LEVEL_KEY},
+    stats::Aggregator,
+};
+use crate::{
+    http::{HttpClient, HttpError},
+    internal_events::{DatadogTracesSent, DatadogTracesStatsSendFailed},
+    proto::datadog::trace::{AgentPayload, TraceChunk, TracerPayload},
+    sinks::{
+        datadog::ApiKey,
+        util::{retries::RetryLogic, PartitionInnerBuffer},
+    },
+};
+use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression};
+use futures::future::BoxFuture;
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    Request, StatusCode, Uri,
+};
+use hyper::Body;
+use prost::Message;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    task::{Context, Poll},
+};
+use tower::Service;
+
+/// The rates of traces per second the Agent samples towards by default.
+const TARGET_TPS: f64 = 10.0;
+const ERROR_TPS: f64 = 10.0;
+
+#[derive(Debug, Snafu)]
+pub(super) enum TracesError {
+    #[snafu(display("Failed to build request: {}", source))]
+    BuildRequest { source: http::Error },
+    #[snafu(display("Failed to send request: {}", source))]
+    SendRequest { source: HttpError },
+    #[snafu(display("Failed to read response: {}", source))]
+    ReadResponse { source: hyper::Error },
+    #[snafu(display("Unexpected status {}: {}", status, body))]
+    UnexpectedStatus { status: StatusCode, body: String },
+}
+
+/// A batch of spans and the stats computed from them, serialized and
+/// compressed once so that retries send the same payloads.
+#[derive(Clone, Debug)]
+pub(super) struct TracesRequest {
+    api_key: ApiKey,
+    traces: Bytes,
+    stats: Option<Bytes>,
+    span_count: usize,
+}
+
+/// What the payloads of all requests have in common.
+#[derive(Clone, Debug)]
+pub(super) struct PayloadSettings {
+    pub(super) hostname: String,
+    pub(super) env: String,
+}
+
+pub(super) fn build_request(
+    batch: PartitionInnerBuffer<Vec<DatadogSpan>, ApiKey>,
+    settings: &PayloadSettings,
+) -> TracesRequest {
+    let (spans, api_key) = batch.into_parts();
+    let span_count = spans.len();
+    let top_level = top_level(&spans);
+
+    let mut aggregator = Aggregator::default();
+    let mut tracers = BTreeMap::<Tracer, BTreeMap<u64, TraceChunk>>::new();
+    for (mut span, top_level) in spans.into_iter().zip(top_level) {
+        aggregator.add(&span, top_level);
+        if top_level {
+            span.span.metrics.insert(TOP_LEVEL_KEY.to_owned(), 1.0);
+        }
+
+        let priority = span.priority;
+        let chunk = tracers
+            .entry(span.tracer)
+            .or_default()
+            .entry(span.span.trace_id)
+            .or_insert_with(|| TraceChunk {
+                priority,
+                ..Default::default()
+            });
+        chunk.priority = chunk.priority.max(priority);
+        chunk.spans.push(span.span);
+    }
+
+    let payload = AgentPayload {
+        host_name: settings.hostname.clone(),
+        env: settings.env.clone(),
+        tracer_payloads: tracers
+            .into_iter()
+            .map(|(tracer, chunks)| TracerPayload {
+                language_name: tracer.language,
+                chunks: chunks.into_iter().map(|(_, chunk)| chunk).collect(),
+                env: tracer.env,
+                hostname: tracer.hostname,
+                app_version: tracer.app_version,
+                ..Default::default()
+            })
+            .collect(),
+        tags: HashMap::new(),
+        agent_version: crate::get_version(),
+        target_tps: TARGET_TPS,
+        error_tps: ERROR_TPS,
+    };
+
+    let stats = (!aggregator.is_empty()).then(|| {
+        let payload = aggregator.into_payload(settings.hostname.clone(), settings.env.clone());
+        gzip(&rmp_serde::to_vec_named(&payload).expect("Stats should be valid msgpack!"))
+    });
+
+    TracesRequest {
+        api_key,
+        traces: gzip(&payload.encode_to_vec()),
+        stats,
+        span_count,
+    }
+}
+
+/// Whether each span is top-level, meaning the entry point of its service in
+/// the trace, which is the case of spans whose parent is of another service
+/// or isn't known. Only the spans of the batch are known, so spans whose
+/// parent is in another batch are taken as top-level.
+fn top_level(spans: &[DatadogSpan]) -> Vec<bool> {
+    let services = spans
+        .iter()
+        .map(|span| {
+            (
+                (span.span.trace_id, span.span.span_id),
+                span.span.service.as_str(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    spans
+        .iter()
+        .map(|span| {
+            span.span.parent_id == 0
+                || services
+                    .get(&(span.span.trace_id, span.span.parent_id))
+                    .map_or(true, |service| *service != span.span.service)
+        })
+        .collect()
+}
+
+fn gzip(body: &[u8]) -> Bytes {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(body)
+        .expect("Writing to a Vec can't fail");
+    encoder
+        .finish()
+        .expect("Writing to a Vec can't fail")
+        .into()
+}
+
+#[derive(Clone)]
+pub(super) struct TracesService {
+    client: HttpClient,
+    traces_uri: Uri,
+    stats_uri: Uri,
+}
+
+impl TracesService {
+    pub(super) fn new(client: HttpClient, endpoint: &str) -> crate::Result<Self> {
+        Ok(Self {
+            client,
+            traces_uri: format!("{}/api/v0.2/traces", endpoint).parse()?,
+            stats_uri: format!("{}/api/v0.2/stats", endpoint).parse()?,
+        })
+    }
+
+    /// Sends the spans, then their stats. The stats of spans that were sent
+    /// are dropped if they fail to be sent, as retrying the batch would send
+    /// the spans again.
+    async fn send(self, request: TracesRequest) -> Result<(), TracesError> {
+        let byte_size = request.traces.len();
+        self.post(
+            &self.traces_uri,
+            "application/x-protobuf",
+            &request.api_key,
+            request.traces,
+        )
+        .await?;
+        emit!(DatadogTracesSent {
+            byte_size,
+            span_count: request.span_count,
+        });
+
+        if let Some(stats) = request.stats {
+            if let Err(error) = self
+                .post(
+                    &self.stats_uri,
+                    "application/msgpack",
+                    &request.api_key,
+                    stats,
+                )
+                .await
+            {
+                emit!(DatadogTracesStatsSendFailed { error });
+            }
+        }
+        Ok(())
+    }
+
+    async fn post(
+        &self,
+        uri: &Uri,
+        content_type: &str,
+        api_key: &str,
+        body: Bytes,
+    ) -> Result<(), TracesError> {
+        let request = Request::post(uri)
+            .header(CONTENT_TYPE, content_type)
+            .header(CONTENT_ENCODING, "gzip")
+            .header("DD-API-KEY", api_key)
+            .body(Body::from(body))
+            .context(BuildRequest)?;
+
+        let response = self.client.send(request).await.context(SendRequest)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .context(ReadResponse)?;
+        Err(TracesError::UnexpectedStatus {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+}
+
+impl Service<TracesRequest> for TracesService {
+    type Response = ();
+    type Error = TracesError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: TracesRequest) -> Self::Future {
+        Box::pin(self.clone().send(request))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TracesRetryLogic;
+
+impl RetryLogic for TracesRetryLogic {
+    type Error = TracesError;
+    type Response = ();
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            TracesError::SendRequest { .. } | TracesError::ReadResponse { .. } => true,
+            TracesError::UnexpectedStatus { status, .. } => {
+                status.is_server_error()
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::REQUEST_TIMEOUT
+            }
+            TracesError::BuildRequest { .. } => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::datadog::trace::Span;
+    use flate2::read::GzDecoder;
+    use std::{io::Read, sync::Arc};
+
+    fn span(trace_id: u64, span_id: u64, parent_id: u64, service: &str) -> DatadogSpan {
+        DatadogSpan {
+            span: Span {
+                service: service.into(),
+                trace_id,
+                span_id,
+                parent_id,
+                ..Default::default()
+            },
+            tracer: Tracer::default(),
+            priority: 1,
+            http_status_code: 0,
+        }
+    }
+
+    #[test]
+    fn finds_top_level_spans() {
+        let spans = vec![
+            span(1, 1, 0, "web"),
+            span(1, 2, 1, "web"),
+            span(1, 3, 2, "db"),
+            span(1, 4, 9, "web"),
+        ];
+        assert_eq!(top_level(&spans), vec![true, false, true, true]);
+    }
+
+    #[test]
+    fn groups_spans_by_trace() {
+        let spans = vec![
+            span(1, 1, 0, "web"),
+            span(2, 2, 0, "web"),
+            span(1, 3, 1, "web"),
+        ];
+        let settings = PayloadSettings {
+            hostname: "vector".into(),
+            env: "none".into(),
+        };
+        let request = build_request(
+            PartitionInnerBuffer::new(spans, Arc::from("key")),
+            &settings,
+        );
+        assert_eq!(request.span_count, 3);
+        assert!(request.stats.is_some());
+
+        let mut body = Vec::new();
+        GzDecoder::new(&request.traces[..])
+            .read_to_end(&mut body)
+            .unwrap();
+        let payload = AgentPayload::decode(&body[..]).unwrap();
+        assert_eq!(payload.host_name, "vector");
+        let chunks = &payload.tracer_payloads[0].chunks;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].spans.len(), 2);
+        assert_eq!(chunks[0].spans[0].metrics[TOP_LEVEL_KEY], 1.0);
+        assert!(!chunks[0].spans[1].metrics.contains_key(TOP_LEVEL_KEY));
+    }
+}
@@ -0,0 +1,290 @@
+//! APM stats, computed from the spans of each batch as the Datadog Agent
+//! computes them, since the trace intake doesn't compute them itself.
+//!
+//! Spans are counted into buckets of ten seconds by the time they ended, and
+//! within a bucket by their service, name, resource, type and HTTP status
+//! code. Only top-level and measured spans are counted. The intake adds up
+//! the stats sent for the same bucket, so stats of spans of the same bucket
+//! may be sent in several batches.
+
+use super::convert::{DatadogSpan, Tracer, MEASURED_KEY};
+use crate::{
+    event::metric::DDSketch,
+    proto::datadog::sketches::{self, index_mapping::Interpolation, IndexMapping, Store},
+};
+use prost::Message;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const BUCKET_DURATION_NANOS: u64 = 10_000_000_000;
+
+/// What the spans counted together have in common.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct AggregationKey {
+    service: String,
+    name: String,
+    resource: String,
+    r#type: String,
+    http_status_code: u32,
+}
+
+#[derive(Debug, Default)]
+struct GroupedStats {
+    hits: u64,
+    errors: u64,
+    top_level_hits: u64,
+    /// The total duration of the spans, in nanoseconds.
+    duration: u64,
+    /// The durations of the spans that aren't errors.
+    ok_summary: DDSketch,
+    /// The durations of the spans that are errors.
+    error_summary: DDSketch,
+}
+
+/// Buckets of stats, by tracer and start of the bucket.
+#[derive(Debug, Default)]
+pub(super) struct Aggregator {
+    buckets: BTreeMap<(Tracer, u64), BTreeMap<AggregationKey, GroupedStats>>,
+}
+
+impl Aggregator {
+    /// Counts a span if it's top-level or measured.
+    pub(super) fn add(&mut self, span: &DatadogSpan, top_level: bool) {
+        let measured = span.span.metrics.get(MEASURED_KEY) == Some(&1.0);
+        if !top_level && !measured {
+            return;
+        }
+
+        let duration = span.span.duration.max(0) as u64;
+        let end = (span.span.start.max(0) as u64).saturating_add(duration);
+        let start = end - end % BUCKET_DURATION_NANOS;
+        let key = AggregationKey {
+            service: span.span.service.clone(),
+            name: span.span.name.clone(),
+            resource: span.span.resource.clone(),
+            r#type: span.span.r#type.clone(),
+            http_status_code: span.http_status_code,
+        };
+
+        let stats = self
+            .buckets
+            .entry((span.tracer.clone(), start))
+            .or_default()
+            .entry(key)
+            .or_default();
+        stats.hits += 1;
+        stats.duration += duration;
+        if top_level {
+            stats.top_level_hits += 1;
+        }
+        if span.span.error != 0 {
+            stats.errors += 1;
+            stats.error_summary.insert(duration as f64);
+        } else {
+            stats.ok_summary.insert(duration as f64);
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// The payload of the stats, with one client payload per tracer.
+    pub(super) fn into_payload(self, agent_hostname: String, agent_env: String) -> StatsPayload {
+        let mut clients = BTreeMap::<Tracer, Vec<ClientStatsBucket>>::new();
+        for ((tracer, start), groups) in self.buckets {
+            let stats = groups
+                .into_iter()
+                .map(|(key, stats)| ClientGroupedStats {
+                    service: key.service,
+                    name: key.name,
+                    resource: key.resource,
+                    http_status_code: key.http_status_code,
+                    r#type: key.r#type,
+                    db_type: String::new(),
+                    hits: stats.hits,
+                    errors: stats.errors,
+                    duration: stats.duration,
+                    ok_summary: encode_sketch(&stats.ok_summary),
+                    error_summary: encode_sketch(&stats.error_summary),
+                    synthetics: false,
+                    top_level_hits: stats.top_level_hits,
+                })
+                .collect();
+            clients.entry(tracer).or_default().push(ClientStatsBucket {
+                start,
+                duration: BUCKET_DURATION_NANOS,
+                stats,
+                agent_time_shift: 0,
+            });
+        }
+
+        StatsPayload {
+            agent_hostname,
+            agent_env,
+            stats: clients
+                .into_iter()
+                .map(|(tracer, stats)| ClientStatsPayload {
+                    hostname: tracer.hostname,
+                    env: tracer.env,
+                    version: tracer.app_version,
+                    stats,
+                    lang: tracer.language,
+                    ..Default::default()
+                })
+                .collect(),
+            agent_version: crate::get_version(),
+            client_computed: false,
+        }
+    }
+}
+
+/// Encodes a sketch the way the `sketches-go` library does. Its bins map to
+/// `ceil(log(value) / log(gamma))`, which is the index the library maps to
+/// with an offset of one.
+fn encode_sketch(sketch: &DDSketch) -> Vec<u8> {
+    let accuracy = sketch.relative_accuracy();
+    let store = |bins: &BTreeMap<i32, u32>| Store {
+        bin_counts: bins
+            .iter()
+            .map(|(index, count)| (*index, f64::from(*count)))
+            .collect(),
+        contiguous_bin_counts: Vec::new(),
+        contiguous_bin_index_offset: 0,
+    };
+    sketches::DdSketch {
+        mapping: Some(IndexMapping {
+            gamma: (1.0 + accuracy) / (1.0 - accuracy),
+            index_offset: 1.0,
+            interpolation: Interpolation::None as i32,
+        }),
+        positive_values: Some(store(sketch.positive_bins())),
+        negative_values: Some(store(sketch.negative_bins())),
+        zero_count: f64::from(sketch.zero_count()),
+    }
+    .encode_to_vec()
+}
+
+/// The payload of the stats endpoint of the trace intake, serialized with
+/// MessagePack with the names of the fields of the Agent.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub(super) struct StatsPayload {
+    agent_hostname: String,
+    agent_env: String,
+    stats: Vec<ClientStatsPayload>,
+    agent_version: String,
+    client_computed: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ClientStatsPayload {
+    hostname: String,
+    env: String,
+    version: String,
+    stats: Vec<ClientStatsBucket>,
+    lang: String,
+    tracer_version: String,
+    #[serde(rename = "RuntimeID")]
+    runtime_id: String,
+    sequence: u64,
+    agent_aggregation: String,
+    service: String,
+    #[serde(rename = "ContainerID")]
+    container_id: String,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ClientStatsBucket {
+    start: u64,
+    duration: u64,
+    stats: Vec<ClientGroupedStats>,
+    agent_time_shift: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ClientGroupedStats {
+    service: String,
+    name: String,
+    resource: String,
+    #[serde(rename = "HTTPStatusCode")]
+    http_status_code: u32,
+    #[serde(rename = "Type")]
+    r#type: String,
+    #[serde(rename = "DBType")]
+    db_type: String,
+    hits: u64,
+    errors: u64,
+    duration: u64,
+    #[serde(with = "serde_bytes")]
+    ok_summary: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    error_summary: Vec<u8>,
+    synthetics: bool,
+    top_level_hits: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::datadog::trace::Span;
+
+    fn span(resource: &str, start: i64, duration: i64, error: i32) -> DatadogSpan {
+        DatadogSpan {
+            span: Span {
+                service: "shop".into(),
+                name: "opentelemetry.server".into(),
+                resource: resource.into(),
+                start,
+                duration,
+                error,
+                ..Default::default()
+            },
+            tracer: Tracer::default(),
+            priority: 1,
+            http_status_code: 200,
+        }
+    }
+
+    #[test]
+    fn aggregates_top_level_spans() {
+        let mut aggregator = Aggregator::default();
+        aggregator.add(&span("GET /cart", 1_000_000_000, 2_000_000, 0), true);
+        aggregator.add(&span("GET /cart", 2_000_000_000, 4_000_000, 1), true);
+        aggregator.add(&span("GET /cart", 3_000_000_000, 4_000_000, 0), false);
+        aggregator.add(&span("GET /cart", 12_000_000_000, 1_000_000, 0), true);
+
+        let payload = aggregator.into_payload("vector".into(), "prod".into());
+        assert_eq!(payload.stats.len(), 1);
+        let buckets = &payload.stats[0].stats;
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start, 0);
+        assert_eq!(buckets[1].start, 10_000_000_000);
+
+        let stats = &buckets[0].stats[0];
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.top_level_hits, 2);
+        assert_eq!(stats.duration, 6_000_000);
+
+        let summary = sketches::DdSketch::decode(&stats.ok_summary[..]).unwrap();
+        assert_eq!(summary.positive_values.unwrap().bin_counts.len(), 1);
+    }
+
+    #[test]
+    fn serializes_with_agent_field_names() {
+        let mut aggregator = Aggregator::default();
+        aggregator.add(&span("GET /cart", 0, 1, 0), true);
+        let payload = aggregator.into_payload(String::new(), String::new());
+        let value: rmpv::Value =
+            rmp_serde::from_slice(&rmp_serde::to_vec_named(&payload).unwrap()).unwrap();
+        let grouped = &value["Stats"][0]["Stats"][0]["Stats"][0];
+        assert_eq!(grouped["HTTPStatusCode"].as_u64(), Some(200));
+        assert_eq!(grouped["TopLevelHits"].as_u64(), Some(1));
+        assert!(grouped["OkSummary"].is_bin());
+    }
+}
@@ -0,0 +1,634 @@
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    http::{Auth, HttpClient, HttpError, MaybeAuth},
+    internal_events::{
+        DorisEventsSent, DorisLabelAlreadyLoaded, DorisRowsFiltered, TemplateRenderingFailed,
+    },
+    sinks::util::{
+        encoding::{EncodingConfigWithDefault, EncodingConfiguration},
+        http::RequestConfig,
+        retries::{RetryAction, RetryLogic},
+        sink::Response,
+        BatchConfig, BatchSettings, Buffer, Compression, EncodedEvent, PartitionBatchSink,
+        PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt, TowerRequestConfig, UriSerde,
+    },
+    template::Template,
+    tls::{TlsOptions, TlsSettings},
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream, FutureExt, SinkExt, StreamExt};
+use http::{header::LOCATION, Request, StatusCode};
+use hyper::Body;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    convert::TryFrom,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceBuilder};
+use uuid::Uuid;
+
+/// The FE answers stream loads with a redirect to a BE, which should not
+/// redirect again, so a few redirects are enough.
+const MAX_REDIRECTS: usize = 3;
+const MAX_LABEL_LENGTH: usize = 128;
+const LABEL_ALREADY_EXISTS: &str = "Label Already Exists";
+/// Headers set by the sink, which would break its deduplication if they were
+/// overridden.
+const RESERVED_HEADERS: &[&str] = &["label", "two_phase_commit", "txn_id", "txn_operation"];
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DorisConfig {
+    pub endpoint: UriSerde,
+    pub database: String,
+    pub table: String,
+    #[serde(default = "default_label_prefix")]
+    pub label_prefix: String,
+    #[serde(default)]
+    pub two_phase_commit: bool,
+    #[serde(
+        skip_serializing_if = "crate::serde::skip_serializing_if_default",
+        default
+    )]
+    pub encoding: EncodingConfigWithDefault<Encoding>,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: RequestConfig,
+    pub auth: Option<Auth>,
+    pub tls: Option<TlsOptions>,
+}
+
+fn default_label_prefix() -> String {
+    "vector".into()
+}
+
+inventory::submit! {
+    SinkDescription::new::<DorisConfig>("doris")
+}
+
+impl GenerateConfig for DorisConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"endpoint = "http://localhost:8030"
+            database = "logs"
+            table = "events""#,
+        )
+        .unwrap()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Default,
+}
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display(
+        "Label prefix {:?} may only contain letters, digits, '-', '_' and ':'",
+        prefix
+    ))]
+    InvalidLabelPrefix { prefix: String },
+    #[snafu(display("Header {:?} is set by the sink and can't be configured", name))]
+    ReservedHeader { name: String },
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "doris")]
+impl SinkConfig for DorisConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.label_prefix.is_empty() || !self.label_prefix.chars().all(is_label_char) {
+            return Err(BuildError::InvalidLabelPrefix {
+                prefix: self.label_prefix.clone(),
+            }
+            .into());
+        }
+        for name in self.request.headers.keys() {
+            if RESERVED_HEADERS.contains(&name.to_lowercase().as_str()) {
+                return Err(BuildError::ReservedHeader { name: name.clone() }.into());
+            }
+        }
+
+        let batch = BatchSettings::default()
+            .bytes(bytesize::mib(10u64))
+            .timeout(1)
+            .parse_config(self.batch)?;
+        let request = self
+            .request
+            .tower
+            .unwrap_with(&TowerRequestConfig::default());
+        let tls_settings = TlsSettings::from_options(&self.tls)?;
+        let client = HttpClient::new(tls_settings, &cx.proxy)?;
+
+        let service = DorisService {
+            client,
+            endpoint: self.endpoint.uri.to_string().trim_end_matches('/').into(),
+            auth: self.auth.choose_one(&self.endpoint.auth)?,
+            headers: self.request.headers.clone(),
+            two_phase_commit: self.two_phase_commit,
+        };
+        let healthcheck = healthcheck(service.clone()).boxed();
+
+        let database = Template::try_from(self.database.as_str())?;
+        let table = Template::try_from(self.table.as_str())?;
+        let encoding = self.encoding.clone();
+        let label_prefix = self.label_prefix.clone();
+
+        let svc = ServiceBuilder::new()
+            .map(move |req| build_request(req, &label_prefix))
+            .settings(request, DorisRetryLogic)
+            .service(service);
+
+        let buffer = PartitionBuffer::new(Buffer::new(batch.size, Compression::None));
+
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .with_flat_map(move |event| {
+                stream::iter(encode_event(event, &database, &table, &encoding)).map(Ok)
+            })
+            .sink_map_err(|error| error!(message = "Fatal doris sink error.", %error));
+
+        Ok((super::VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "doris"
+    }
+}
+
+/// The table a batch is loaded into.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct TableKey {
+    database: String,
+    table: String,
+}
+
+fn encode_event(
+    mut event: Event,
+    database: &Template,
+    table: &Template,
+    encoding: &EncodingConfigWithDefault<Encoding>,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, TableKey>>> {
+    let render = |template: &Template, field| {
+        template
+            .render_string(&event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    };
+    let key = TableKey {
+        database: render(database, "database")?,
+        table: render(table, "table")?,
+    };
+
+    encoding.apply_rules(&mut event);
+    let mut log = event.into_log();
+    let mut body = serde_json::to_vec(&log).expect("Events should be valid json!");
+    body.push(b'\n');
+
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(body, key),
+        finalizers: log.metadata_mut().take_finalizers(),
+    })
+}
+
+/// A stream load of a batch. Its label is generated once per batch, so that
+/// retries of the batch are deduplicated by the label.
+#[derive(Clone, Debug)]
+struct StreamLoadRequest {
+    database: String,
+    table: String,
+    label: String,
+    body: Bytes,
+}
+
+fn build_request(
+    req: PartitionInnerBuffer<Vec<u8>, TableKey>,
+    label_prefix: &str,
+) -> StreamLoadRequest {
+    let (lines, key) = req.into_parts();
+    let label = build_label(label_prefix, &key, Uuid::new_v4());
+    StreamLoadRequest {
+        body: json_array(&lines),
+        database: key.database,
+        table: key.table,
+        label,
+    }
+}
+
+fn is_label_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == ':'
+}
+
+fn build_label(prefix: &str, key: &TableKey, uuid: Uuid) -> String {
+    let label = format!(
+        "{}_{}_{}_{}",
+        prefix,
+        key.database,
+        key.table,
+        uuid.to_simple()
+    );
+    if label.len() > MAX_LABEL_LENGTH {
+        return format!("{}_{}", prefix, uuid.to_simple());
+    }
+    label
+        .chars()
+        .map(|c| if is_label_char(c) { c } else { '_' })
+        .collect()
+}
+
+/// Joins the JSON lines of a batch into a JSON array, loaded with
+/// `strip_outer_array`, which both Doris and StarRocks support.
+fn json_array(lines: &[u8]) -> Bytes {
+    let mut body = Vec::with_capacity(lines.len() + 2);
+    body.push(b'[');
+    for line in lines.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+        if body.len() > 1 {
+            body.push(b',');
+        }
+        body.extend_from_slice(line);
+    }
+    body.push(b']');
+    body.into()
+}
+
+#[derive(Debug, Snafu)]
+enum StreamLoadError {
+    #[snafu(display("Failed to build request: {}", source))]
+    BuildRequest { source: http::Error },
+    #[snafu(display("Failed to send request: {}", source))]
+    SendRequest { source: HttpError },
+    #[snafu(display("Failed to read response: {}", source))]
+    ReadResponse { source: hyper::Error },
+    #[snafu(display("Unexpected status {}: {}", status, body))]
+    UnexpectedStatus { status: StatusCode, body: String },
+    #[snafu(display("Failed to parse response: {}", source))]
+    ParseResponse { source: serde_json::Error },
+    #[snafu(display("Redirect without a location"))]
+    MissingLocation,
+    #[snafu(display("Too many redirects"))]
+    TooManyRedirects,
+    #[snafu(display("Failed to commit transaction: {}", message))]
+    CommitFailed { message: String },
+}
+
+/// The response of a stream load, of which only the fields used by the sink
+/// are parsed.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StreamLoadResponse {
+    #[serde(default)]
+    txn_id: Option<i64>,
+    #[serde(default)]
+    label: String,
+    status: String,
+    #[serde(default)]
+    existing_job_status: Option<String>,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    number_loaded_rows: u64,
+    #[serde(default)]
+    number_filtered_rows: u64,
+    #[serde(default, rename = "ErrorURL")]
+    error_url: Option<String>,
+    /// Whether the sink committed the transaction of the load.
+    #[serde(skip)]
+    committed: bool,
+}
+
+impl StreamLoadResponse {
+    fn is_precommitted(&self) -> bool {
+        self.status == LABEL_ALREADY_EXISTS
+            && self.existing_job_status.as_deref() == Some("PRECOMMITTED")
+    }
+
+    fn retry_action(&self) -> RetryAction {
+        match self.status.as_str() {
+            "Success" | "Publish Timeout" => RetryAction::Successful,
+            LABEL_ALREADY_EXISTS => match self.existing_job_status.as_deref() {
+                Some("FINISHED") | Some("VISIBLE") | Some("COMMITTED") => RetryAction::Successful,
+                Some("PRECOMMITTED") if self.committed => RetryAction::Successful,
+                Some("RUNNING") => RetryAction::Retry("load of the label is running".into()),
+                status => RetryAction::DontRetry(format!(
+                    "label {} already exists with status {:?}",
+                    self.label, status
+                )),
+            },
+            _ => {
+                let message = self.message.to_lowercase();
+                // Rows rejected by the table fail the load again.
+                if message.contains("filtered rows") || message.contains("data quality") {
+                    RetryAction::DontRetry(self.message.clone())
+                } else {
+                    RetryAction::Retry(self.message.clone())
+                }
+            }
+        }
+    }
+}
+
+impl Response for StreamLoadResponse {
+    fn is_successful(&self) -> bool {
+        matches!(self.retry_action(), RetryAction::Successful)
+    }
+}
+
+/// The response of a transaction commit of Doris.
+#[derive(Debug, Deserialize)]
+struct CommitResponse {
+    status: String,
+    #[serde(default)]
+    msg: String,
+}
+
+#[derive(Clone)]
+struct DorisService {
+    client: HttpClient,
+    endpoint: String,
+    auth: Option<Auth>,
+    headers: IndexMap<String, String>,
+    two_phase_commit: bool,
+}
+
+impl DorisService {
+    async fn load(self, request: StreamLoadRequest) -> Result<StreamLoadResponse, StreamLoadError> {
+        let uri = format!(
+            "{}/api/{}/{}/_stream_load",
+            self.endpoint, request.database, request.table
+        );
+        let mut headers = vec![
+            ("Expect".to_owned(), "100-continue".to_owned()),
+            ("label".to_owned(), request.label.clone()),
+            ("format".to_owned(), "json".to_owned()),
+            ("strip_outer_array".to_owned(), "true".to_owned()),
+        ];
+        if self.two_phase_commit {
+            headers.push(("two_phase_commit".to_owned(), "true".to_owned()));
+        }
+        headers.extend(
+            self.headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+
+        emit!(DorisEventsSent {
+            byte_size: request.body.len(),
+        });
+        let body = self.send(uri, &headers, request.body).await?;
+        let mut response: StreamLoadResponse =
+            serde_json::from_slice(&body).context(ParseResponse)?;
+
+        if response.number_filtered_rows > 0 {
+            emit!(DorisRowsFiltered {
+                label: &request.label,
+                count: response.number_filtered_rows,
+                error_url: response.error_url.as_deref(),
+            });
+        }
+        if response.status == LABEL_ALREADY_EXISTS {
+            emit!(DorisLabelAlreadyLoaded {
+                label: &request.label,
+                status: response.existing_job_status.as_deref(),
+            });
+        }
+
+        if self.two_phase_commit && (response.status == "Success" || response.is_precommitted()) {
+            // A load retried after its transaction was precommitted is
+            // committed by its label, since its response has no `TxnId`.
+            let txn = match response.txn_id {
+                Some(txn_id) if response.status == "Success" => {
+                    ("txn_id".to_owned(), txn_id.to_string())
+                }
+                _ => ("label".to_owned(), request.label.clone()),
+            };
+            self.commit(&request.database, txn).await?;
+            response.committed = true;
+        }
+
+        debug!(
+            message = "Loaded events.",
+            label = %request.label,
+            status = %response.status,
+            loaded_rows = %response.number_loaded_rows,
+        );
+        Ok(response)
+    }
+
+    async fn commit(&self, database: &str, txn: (String, String)) -> Result<(), StreamLoadError> {
+        let uri = format!("{}/api/{}/_stream_load_2pc", self.endpoint, database);
+        let headers = vec![txn, ("txn_operation".to_owned(), "commit".to_owned())];
+        let body = self.send(uri, &headers, Bytes::new()).await?;
+        let response: CommitResponse = serde_json::from_slice(&body).context(ParseResponse)?;
+        if response.status.eq_ignore_ascii_case("success") {
+            Ok(())
+        } else {
+            Err(StreamLoadError::CommitFailed {
+                message: response.msg,
+            })
+        }
+    }
+
+    /// Sends a request, following the redirects of the FE to a BE with the
+    /// headers and credentials of the request.
+    async fn send(
+        &self,
+        mut uri: String,
+        headers: &[(String, String)],
+        body: Bytes,
+    ) -> Result<Bytes, StreamLoadError> {
+        for _ in 0..=MAX_REDIRECTS {
+            let mut builder = Request::put(&uri);
+            for (name, value) in headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            let mut request = builder
+                .body(Body::from(body.clone()))
+                .context(BuildRequest)?;
+            if let Some(auth) = &self.auth {
+                auth.apply(&mut request);
+            }
+
+            let response = self.client.send(request).await.context(SendRequest)?;
+            let status = response.status();
+            if status.is_redirection() {
+                uri = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .ok_or(StreamLoadError::MissingLocation)?
+                    .to_owned();
+                continue;
+            }
+
+            let body = hyper::body::to_bytes(response.into_body())
+                .await
+                .context(ReadResponse)?;
+            if !status.is_success() {
+                return Err(StreamLoadError::UnexpectedStatus {
+                    status,
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                });
+            }
+            return Ok(body);
+        }
+        Err(StreamLoadError::TooManyRedirects)
+    }
+}
+
+impl Service<StreamLoadRequest> for DorisService {
+    type Response = StreamLoadResponse;
+    type Error = StreamLoadError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: StreamLoadRequest) -> Self::Future {
+        Box::pin(self.clone().load(request))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DorisRetryLogic;
+
+impl RetryLogic for DorisRetryLogic {
+    type Error = StreamLoadError;
+    type Response = StreamLoadResponse;
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            StreamLoadError::SendRequest { .. }
+            | StreamLoadError::ReadResponse { .. }
+            | StreamLoadError::CommitFailed { .. } => true,
+            StreamLoadError::UnexpectedStatus { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    fn should_retry_response(&self, response: &Self::Response) -> RetryAction {
+        response.retry_action()
+    }
+}
+
+async fn healthcheck(service: DorisService) -> crate::Result<()> {
+    let uri = format!("{}/api/health", service.endpoint);
+    let mut request = Request::get(uri).body(Body::empty()).unwrap();
+
+    if let Some(auth) = &service.auth {
+        auth.apply(&mut request);
+    }
+
+    let response = service.client.send(request).await?;
+
+    match response.status() {
+        StatusCode::OK => Ok(()),
+        status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<DorisConfig>();
+    }
+
+    fn key(database: &str, table: &str) -> TableKey {
+        TableKey {
+            database: database.into(),
+            table: table.into(),
+        }
+    }
+
+    #[test]
+    fn labels_are_sanitized() {
+        let uuid = Uuid::nil();
+        assert_eq!(
+            build_label("vector", &key("logs", "app.events"), uuid),
+            "vector_logs_app_events_00000000000000000000000000000000"
+        );
+
+        let long = "t".repeat(MAX_LABEL_LENGTH);
+        assert_eq!(
+            build_label("vector", &key("logs", &long), uuid),
+            "vector_00000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn batches_are_json_arrays() {
+        assert_eq!(
+            &json_array(b"{\"a\":1}\n{\"a\":2}\n")[..],
+            b"[{\"a\":1},{\"a\":2}]"
+        );
+        assert_eq!(&json_array(b"")[..], b"[]");
+    }
+
+    fn response(json: &str) -> StreamLoadResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn retries_depend_on_the_load_status() {
+        let loaded = response(
+            r#"{"TxnId": 1003, "Label": "vector_1", "Status": "Success",
+                "Message": "OK", "NumberLoadedRows": 2, "NumberFilteredRows": 0}"#,
+        );
+        assert_eq!(loaded.txn_id, Some(1003));
+        assert!(matches!(loaded.retry_action(), RetryAction::Successful));
+
+        let visible =
+            response(r#"{"Status": "Label Already Exists", "ExistingJobStatus": "FINISHED"}"#);
+        assert!(matches!(visible.retry_action(), RetryAction::Successful));
+
+        let running =
+            response(r#"{"Status": "Label Already Exists", "ExistingJobStatus": "RUNNING"}"#);
+        assert!(matches!(running.retry_action(), RetryAction::Retry(_)));
+
+        let mut precommitted =
+            response(r#"{"Status": "Label Already Exists", "ExistingJobStatus": "PRECOMMITTED"}"#);
+        assert!(precommitted.is_precommitted());
+        assert!(matches!(
+            precommitted.retry_action(),
+            RetryAction::DontRetry(_)
+        ));
+        precommitted.committed = true;
+        assert!(matches!(
+            precommitted.retry_action(),
+            RetryAction::Successful
+        ));
+
+        let filtered = response(
+            r#"{"Status": "Fail", "Message": "too many filtered rows",
+                "NumberFilteredRows": 3, "ErrorURL": "http://be:8040/api/_load_error_log"}"#,
+        );
+        assert!(matches!(filtered.retry_action(), RetryAction::DontRetry(_)));
+
+        let failed = response(r#"{"Status": "Fail", "Message": "tablet writer write failed"}"#);
+        assert!(matches!(failed.retry_action(), RetryAction::Retry(_)));
+    }
+}
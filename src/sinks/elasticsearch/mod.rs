@@ -5,7 +5,9 @@ use crate::{
     config::{log_schema, DataType, SinkConfig, SinkContext, SinkDescription},
     emit,
     http::{Auth, HttpClient, MaybeAuth},
-    internal_events::{ElasticSearchEventEncoded, TemplateRenderingFailed},
+    internal_events::{
+        ElasticSearchEventEncoded, ElasticSearchTemplateBootstrapFailed, TemplateRenderingFailed,
+    },
     rusoto::{self, region_from_endpoint, AwsAuthentication, RegionOrEndpoint},
     sinks::util::{
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
@@ -47,9 +49,14 @@ pub struct ElasticSearchConfig {
     pub index: Option<String>,
     pub doc_type: Option<String>,
     pub id_key: Option<String>,
+    pub routing_key: Option<String>,
     pub pipeline: Option<String>,
     #[serde(default)]
     pub mode: ElasticSearchMode,
+    #[serde(default)]
+    pub api_version: ElasticSearchApiVersion,
+    #[serde(default)]
+    pub opensearch_service_type: OpenSearchServiceType,
 
     #[serde(default)]
     pub compression: Compression,
@@ -106,9 +113,20 @@ impl ElasticSearchConfig {
                 let bulk_action = self.bulk_action()?;
                 Ok(ElasticSearchCommonMode::Normal { index, bulk_action })
             }
-            ElasticSearchMode::DataStream => Ok(ElasticSearchCommonMode::DataStream(
-                self.data_stream.clone().unwrap_or_default(),
-            )),
+            ElasticSearchMode::DataStream => {
+                // Data streams only accept the `create` action.
+                if let Some(bulk_action) = self.bulk_action()? {
+                    if bulk_action.get_ref() != BulkAction::Create.as_str() {
+                        return Err(ParseError::DataStreamBulkAction {
+                            bulk_action: bulk_action.get_ref().to_owned(),
+                        }
+                        .into());
+                    }
+                }
+                Ok(ElasticSearchCommonMode::DataStream(
+                    self.data_stream.clone().unwrap_or_default(),
+                ))
+            }
         }
     }
 }
@@ -139,6 +157,44 @@ pub struct DataStreamConfig {
     auto_routing: bool,
     #[serde(default = "DataStreamConfig::default_sync_fields")]
     sync_fields: bool,
+    template: Option<DataStreamTemplateConfig>,
+}
+
+/// Index template created on startup, which matches the data streams
+/// written to and optionally applies an ILM policy to their backing indices.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct DataStreamTemplateConfig {
+    name: String,
+    index_patterns: Vec<String>,
+    #[serde(default = "DataStreamTemplateConfig::default_priority")]
+    priority: u32,
+    ilm_policy: Option<String>,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+impl DataStreamTemplateConfig {
+    /// Above the priority of the built-in `logs-*-*` and `metrics-*-*` templates.
+    fn default_priority() -> u32 {
+        200
+    }
+
+    fn body(&self) -> serde_json::Value {
+        let mut body = json!({
+            "index_patterns": self.index_patterns,
+            "data_stream": {},
+            "priority": self.priority,
+        });
+        if let Some(ilm_policy) = &self.ilm_policy {
+            body["template"] = json!({
+                "settings": {
+                    "index.lifecycle.name": ilm_policy,
+                }
+            });
+        }
+        body
+    }
 }
 
 impl Default for DataStreamConfig {
@@ -149,6 +205,7 @@ impl Default for DataStreamConfig {
             namespace: Self::default_namespace(),
             auto_routing: Self::default_auto_routing(),
             sync_fields: Self::default_sync_fields(),
+            template: None,
         }
     }
 }
@@ -313,6 +370,79 @@ impl Default for ElasticSearchMode {
     }
 }
 
+/// The version of the bulk API of the cluster, which decides whether the
+/// mapping type of the documents is sent.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum ElasticSearchApiVersion {
+    /// Determined from the version of the cluster on startup.
+    Auto,
+    V6,
+    V7,
+    /// Also for OpenSearch 2.x and later, which removed mapping types too.
+    V8,
+}
+
+impl Default for ElasticSearchApiVersion {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl ElasticSearchApiVersion {
+    /// From the response of the root endpoint of the cluster.
+    fn from_cluster_info(info: &serde_json::Value) -> Option<Self> {
+        let version = info.get("version")?;
+        let major = version
+            .get("number")?
+            .as_str()?
+            .split('.')
+            .next()?
+            .parse::<u32>()
+            .ok()?;
+        let opensearch =
+            version.get("distribution").and_then(|value| value.as_str()) == Some("opensearch");
+        Some(match (opensearch, major) {
+            (true, 0..=1) => Self::V7,
+            (true, _) => Self::V8,
+            (false, 0..=6) => Self::V6,
+            (false, 7) => Self::V7,
+            (false, _) => Self::V8,
+        })
+    }
+
+    /// Mapping types were removed in Elasticsearch 8.0 and OpenSearch 2.0.
+    /// The version is assumed to be 7.x until determined.
+    fn has_mapping_types(&self) -> bool {
+        !matches!(self, Self::V8)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum OpenSearchServiceType {
+    /// Elasticsearch, or OpenSearch clusters, including Amazon OpenSearch Service domains.
+    Managed,
+    /// Amazon OpenSearch Serverless collections.
+    Serverless,
+}
+
+impl Default for OpenSearchServiceType {
+    fn default() -> Self {
+        Self::Managed
+    }
+}
+
+impl OpenSearchServiceType {
+    /// The service name requests are signed for with AWS authentication.
+    const fn signing_service(self) -> &'static str {
+        match self {
+            Self::Managed => "es",
+            Self::Serverless => "aoss",
+        }
+    }
+}
+
 #[derive(Derivative, Deserialize, Serialize, Clone, Copy, Debug)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub enum BulkAction {
@@ -367,7 +497,28 @@ impl SinkConfig for ElasticSearchConfig {
 
         let healthcheck = common.healthcheck(client.clone()).boxed();
 
-        let common = ElasticSearchCommon::parse_config(self)?;
+        let mut common = ElasticSearchCommon::parse_config(self)?;
+        if common.api_version == ElasticSearchApiVersion::Auto {
+            match common.cluster_api_version(&client).await {
+                Ok(api_version) => common.api_version = api_version,
+                Err(error) => warn!(
+                    message = "Failed to determine the version of the cluster, assuming 7.x.",
+                    %error
+                ),
+            }
+        }
+        if let Some(template) = common
+            .mode
+            .as_data_stream_config()
+            .and_then(|ds| ds.template.as_ref())
+        {
+            if let Err(error) = common.bootstrap_template(&client, template).await {
+                emit!(ElasticSearchTemplateBootstrapFailed {
+                    error,
+                    template: &template.name,
+                });
+            }
+        }
         let compression = common.compression;
         let batch = BatchSettings::default()
             .bytes(bytesize::mib(10u64))
@@ -461,12 +612,15 @@ impl ElasticSearchCommonMode {
 pub struct ElasticSearchCommon {
     pub base_url: String,
     id_key: Option<String>,
+    routing_key: Option<String>,
     bulk_uri: Uri,
     authorization: Option<Auth>,
     credentials: Option<rusoto::AwsCredentialsProvider>,
     encoding: EncodingConfigWithDefault<Encoding>,
     mode: ElasticSearchCommonMode,
     doc_type: String,
+    api_version: ElasticSearchApiVersion,
+    service_type: OpenSearchServiceType,
     tls_settings: TlsSettings,
     compression: Compression,
     region: Region,
@@ -487,6 +641,25 @@ enum ParseError {
     IndexTemplate { source: TemplateParseError },
     #[snafu(display("Batch action template parse error: {}", source))]
     BatchActionTemplate { source: TemplateParseError },
+    #[snafu(display("OpenSearch Serverless collections require AWS authentication"))]
+    ServerlessRequiresAwsAuth,
+    #[snafu(display(
+        "Data streams only accept the \"create\" bulk action, not {:?}",
+        bulk_action
+    ))]
+    DataStreamBulkAction { bulk_action: String },
+}
+
+#[derive(Debug, Snafu)]
+enum BootstrapError {
+    #[snafu(display("ILM policy {:?} doesn't exist", policy))]
+    IlmPolicyNotFound { policy: String },
+    #[snafu(display("Unexpected status {} for {}: {}", status, path, body))]
+    UnexpectedResponse {
+        path: String,
+        status: StatusCode,
+        body: String,
+    },
 }
 
 impl ElasticSearchCommon {
@@ -504,15 +677,22 @@ impl ElasticSearchCommon {
         let mut action = json!({
             bulk_action.as_str(): {
                 "_index": index,
-                "_type": self.doc_type,
             }
         });
+        if self.api_version.has_mapping_types() {
+            action[bulk_action.as_str()]["_type"] = json!(self.doc_type);
+        }
 
         maybe_set_id(
             self.id_key.as_ref(),
             action.pointer_mut(bulk_action.as_json_pointer()).unwrap(),
             &mut event,
         );
+        maybe_set_routing(
+            self.routing_key.as_ref(),
+            action.pointer_mut(bulk_action.as_json_pointer()).unwrap(),
+            &event,
+        );
 
         let mut body = serde_json::to_vec(&action).unwrap();
         body.push(b'\n');
@@ -540,6 +720,7 @@ impl HttpSink for ElasticSearchCommon {
         let log = match event {
             Event::Log(log) => Some(log),
             Event::Metric(metric) => self.metric_to_log.transform_one(metric),
+            Event::Trace(trace) => Some(trace.into()),
         };
         log.and_then(|log| self.encode_log(log.into()))
     }
@@ -629,6 +810,18 @@ impl ElasticSearchCommon {
             Some(ElasticSearchAuth::Aws(aws)) => Some(aws.build(&region, None)?),
         };
 
+        let service_type = config.opensearch_service_type;
+        // Serverless collections have no version, and no mapping types.
+        let api_version = match service_type {
+            OpenSearchServiceType::Managed => config.api_version,
+            OpenSearchServiceType::Serverless => {
+                if credentials.is_none() {
+                    return Err(ParseError::ServerlessRequiresAwsAuth.into());
+                }
+                ElasticSearchApiVersion::V8
+            }
+        };
+
         let compression = config.compression;
         let mode = config.common_mode()?;
 
@@ -674,8 +867,11 @@ impl ElasticSearchCommon {
             compression,
             credentials,
             doc_type,
+            api_version,
+            service_type,
             encoding: config.encoding,
             id_key: config.id_key,
+            routing_key: config.routing_key,
             mode,
             query_params,
             request,
@@ -686,7 +882,12 @@ impl ElasticSearchCommon {
     }
 
     fn signed_request(&self, method: &str, uri: &Uri, use_params: bool) -> SignedRequest {
-        let mut request = SignedRequest::new(method, "es", &self.region, uri.path());
+        let mut request = SignedRequest::new(
+            method,
+            self.service_type.signing_service(),
+            &self.region,
+            uri.path(),
+        );
         request.set_hostname(uri.host().map(|host| host.into()));
         if use_params {
             for (key, value) in &self.query_params {
@@ -696,21 +897,47 @@ impl ElasticSearchCommon {
         request
     }
 
-    async fn healthcheck(self, client: HttpClient) -> crate::Result<()> {
-        let mut builder = Request::get(format!("{}/_cluster/health", self.base_url));
+    /// Request to an API other than the bulk API, with a JSON body unless empty.
+    async fn api_request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Vec<u8>,
+    ) -> crate::Result<Request<Body>> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(format!("{}/{}", self.base_url, path));
 
         match &self.credentials {
             None => {
                 if let Some(authorization) = &self.authorization {
                     builder = authorization.apply_builder(builder);
                 }
+                if !body.is_empty() {
+                    builder = builder.header("Content-Type", "application/json");
+                }
             }
             Some(credentials_provider) => {
-                let mut signer = self.signed_request("GET", builder.uri_ref().unwrap(), false);
+                let mut signer = self.signed_request(method, builder.uri_ref().unwrap(), false);
+                if !body.is_empty() {
+                    signer.add_header("Content-Type", "application/json");
+                    signer.set_payload(Some(body.clone()));
+                }
                 builder = finish_signer(&mut signer, credentials_provider, builder).await?;
             }
         }
-        let request = builder.body(Body::empty())?;
+        Ok(builder.body(Body::from(body))?)
+    }
+
+    async fn healthcheck(self, client: HttpClient) -> crate::Result<()> {
+        // Serverless collections don't have the cluster APIs.
+        if self.service_type == OpenSearchServiceType::Serverless {
+            return Ok(());
+        }
+
+        let request = self
+            .api_request("GET", "_cluster/health", Vec::new())
+            .await?;
         let response = client.send(request).await?;
 
         match response.status() {
@@ -718,6 +945,79 @@ impl ElasticSearchCommon {
             status => Err(super::HealthcheckError::UnexpectedStatus { status }.into()),
         }
     }
+
+    async fn cluster_api_version(
+        &self,
+        client: &HttpClient,
+    ) -> crate::Result<ElasticSearchApiVersion> {
+        let request = self.api_request("GET", "", Vec::new()).await?;
+        let response = client.send(request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(unexpected_response(String::from("/"), response).await);
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let info = serde_json::from_slice::<serde_json::Value>(&body)?;
+        ElasticSearchApiVersion::from_cluster_info(&info)
+            .ok_or_else(|| "Missing version in the cluster information".into())
+    }
+
+    /// Creates the index template of the data streams, unless it already
+    /// exists and isn't to be overwritten.
+    async fn bootstrap_template(
+        &self,
+        client: &HttpClient,
+        template: &DataStreamTemplateConfig,
+    ) -> crate::Result<()> {
+        if let Some(policy) = &template.ilm_policy {
+            let path = format!("_ilm/policy/{}", policy);
+            let request = self.api_request("GET", &path, Vec::new()).await?;
+            let response = client.send(request).await?;
+            match response.status() {
+                StatusCode::OK => {}
+                StatusCode::NOT_FOUND => {
+                    return Err(BootstrapError::IlmPolicyNotFound {
+                        policy: policy.clone(),
+                    }
+                    .into())
+                }
+                _ => return Err(unexpected_response(path, response).await),
+            }
+        }
+
+        let path = format!("_index_template/{}", template.name);
+        if !template.overwrite {
+            let request = self.api_request("HEAD", &path, Vec::new()).await?;
+            let response = client.send(request).await?;
+            match response.status() {
+                StatusCode::OK => {
+                    debug!(message = "Index template already exists.", template = %template.name);
+                    return Ok(());
+                }
+                StatusCode::NOT_FOUND => {}
+                _ => return Err(unexpected_response(path, response).await),
+            }
+        }
+
+        let body = serde_json::to_vec(&template.body())?;
+        let request = self.api_request("PUT", &path, body).await?;
+        let response = client.send(request).await?;
+        match response.status() {
+            StatusCode::OK => {
+                info!(message = "Created index template.", template = %template.name);
+                Ok(())
+            }
+            _ => Err(unexpected_response(path, response).await),
+        }
+    }
+}
+
+async fn unexpected_response(path: String, response: http::Response<Body>) -> crate::Error {
+    let status = response.status();
+    let body = match hyper::body::to_bytes(response.into_body()).await {
+        Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+        Err(error) => return error.into(),
+    };
+    BootstrapError::UnexpectedResponse { path, status, body }.into()
 }
 
 async fn finish_signer(
@@ -758,6 +1058,19 @@ fn maybe_set_id(key: Option<impl AsRef<str>>, doc: &mut serde_json::Value, event
     }
 }
 
+/// Routes the document with the value of the field, which is kept in the document.
+fn maybe_set_routing(key: Option<impl AsRef<str>>, doc: &mut serde_json::Value, event: &Event) {
+    if let Event::Log(log) = event {
+        if let Some(val) = key.and_then(|k| log.get(k)) {
+            let val = val.to_string_lossy();
+
+            doc.as_object_mut()
+                .unwrap()
+                .insert("routing".into(), json!(val));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -847,6 +1160,139 @@ mod tests {
         assert_eq!(json!({}), action);
     }
 
+    #[test]
+    fn sets_routing_from_custom_field() {
+        let config = ElasticSearchConfig {
+            index: Some(String::from("vector")),
+            routing_key: Some(String::from("tenant")),
+            endpoint: String::from("https://example.com"),
+            ..Default::default()
+        };
+        let es = ElasticSearchCommon::parse_config(&config).unwrap();
+
+        let mut event = Event::from("hello there");
+        event.as_mut_log().insert("tenant", "acme");
+        let encoded = es.encode_event(event).unwrap();
+        let expected = r#"{"index":{"_index":"vector","_type":"_doc","routing":"acme"}}
+{"message":"hello there","tenant":"acme"}
+"#;
+        assert_eq!(std::str::from_utf8(&encoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn omits_mapping_type_from_v8() {
+        let config = ElasticSearchConfig {
+            index: Some(String::from("vector")),
+            api_version: ElasticSearchApiVersion::V8,
+            endpoint: String::from("https://example.com"),
+            ..Default::default()
+        };
+        let es = ElasticSearchCommon::parse_config(&config).unwrap();
+
+        let encoded = es.encode_event(Event::from("hello there")).unwrap();
+        let expected = r#"{"index":{"_index":"vector"}}
+{"message":"hello there"}
+"#;
+        assert_eq!(std::str::from_utf8(&encoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn api_version_from_cluster_info() {
+        let version = |number: &str, distribution: Option<&str>| {
+            let mut info = json!({ "version": { "number": number } });
+            if let Some(distribution) = distribution {
+                info["version"]["distribution"] = json!(distribution);
+            }
+            ElasticSearchApiVersion::from_cluster_info(&info)
+        };
+
+        assert_eq!(version("6.8.0", None), Some(ElasticSearchApiVersion::V6));
+        assert_eq!(version("7.10.2", None), Some(ElasticSearchApiVersion::V7));
+        assert_eq!(version("8.9.0", None), Some(ElasticSearchApiVersion::V8));
+        assert_eq!(
+            version("1.3.2", Some("opensearch")),
+            Some(ElasticSearchApiVersion::V7)
+        );
+        assert_eq!(
+            version("2.9.0", Some("opensearch")),
+            Some(ElasticSearchApiVersion::V8)
+        );
+        assert_eq!(ElasticSearchApiVersion::from_cluster_info(&json!({})), None);
+    }
+
+    #[test]
+    fn serverless_requires_aws_auth() {
+        let config = ElasticSearchConfig {
+            endpoint: String::from("https://abc-123.us-east-1.aoss.amazonaws.com"),
+            opensearch_service_type: OpenSearchServiceType::Serverless,
+            aws: Some(RegionOrEndpoint::with_region(String::from("us-east-1"))),
+            ..Default::default()
+        };
+        assert!(ElasticSearchCommon::parse_config(&config).is_err());
+
+        let config = ElasticSearchConfig {
+            auth: Some(ElasticSearchAuth::Aws(AwsAuthentication::Default {})),
+            ..config
+        };
+        let common = ElasticSearchCommon::parse_config(&config).unwrap();
+        assert_eq!(common.api_version, ElasticSearchApiVersion::V8);
+
+        let request = common.signed_request(
+            "POST",
+            &"https://abc-123.us-east-1.aoss.amazonaws.com/_bulk"
+                .parse::<Uri>()
+                .unwrap(),
+            true,
+        );
+        assert_eq!(request.service, "aoss");
+    }
+
+    #[test]
+    fn rejects_datastream_mode_without_create_action() {
+        let config = ElasticSearchConfig {
+            bulk_action: Some(String::from("index")),
+            endpoint: String::from("https://example.com"),
+            mode: ElasticSearchMode::DataStream,
+            ..Default::default()
+        };
+        assert!(ElasticSearchCommon::parse_config(&config).is_err());
+
+        let config = ElasticSearchConfig {
+            bulk_action: Some(String::from("create")),
+            ..config
+        };
+        assert!(ElasticSearchCommon::parse_config(&config).is_ok());
+    }
+
+    #[test]
+    fn datastream_template_body() {
+        let config = toml::from_str::<ElasticSearchConfig>(
+            r#"
+            endpoint = ""
+            mode = "data_stream"
+            data_stream.template.name = "vector-logs"
+            data_stream.template.index_patterns = ["logs-vector-*"]
+            data_stream.template.ilm_policy = "logs-30d"
+        "#,
+        )
+        .unwrap();
+        let template = config.data_stream.unwrap().template.unwrap();
+
+        assert_eq!(
+            template.body(),
+            json!({
+                "index_patterns": ["logs-vector-*"],
+                "data_stream": {},
+                "priority": 200,
+                "template": {
+                    "settings": {
+                        "index.lifecycle.name": "logs-30d",
+                    }
+                }
+            })
+        );
+    }
+
     #[test]
     fn sets_create_action_when_configured() {
         use crate::config::log_schema;
@@ -6,7 +6,7 @@ use crate::{
     internal_events::FileOpen,
     internal_events::TemplateRenderingFailed,
     sinks::util::{
-        encoding::{EncodingConfig, EncodingConfiguration},
+        encoding::{CsvOptions, EncodingConfig, EncodingConfiguration},
         StreamSink,
     },
     template::Template,
@@ -36,6 +36,8 @@ pub struct FileSinkConfig {
     pub path: Template,
     pub idle_timeout_secs: Option<u64>,
     pub encoding: EncodingConfig<Encoding>,
+    /// The columns of the `csv` codec.
+    pub csv: Option<CsvOptions>,
     #[serde(
         default,
         skip_serializing_if = "crate::serde::skip_serializing_if_default"
@@ -53,6 +55,7 @@ impl GenerateConfig for FileSinkConfig {
             path: Template::try_from("/tmp/vector-%Y-%m-%d.log").unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            csv: None,
             compression: Default::default(),
         })
         .unwrap()
@@ -64,6 +67,7 @@ impl GenerateConfig for FileSinkConfig {
 pub enum Encoding {
     Text,
     Ndjson,
+    Csv,
 }
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy)]
@@ -128,6 +132,9 @@ impl SinkConfig for FileSinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        if self.encoding.codec() == &Encoding::Csv && self.csv.is_none() {
+            return Err("`encoding.codec = \"csv\"` requires `csv`".into());
+        }
         let sink = FileSink::new(self, cx.acker());
         Ok((
             super::VectorSink::Stream(Box::new(sink)),
@@ -149,6 +156,7 @@ pub struct FileSink {
     acker: Acker,
     path: Template,
     encoding: EncodingConfig<Encoding>,
+    csv: Option<CsvOptions>,
     idle_timeout: Duration,
     files: ExpiringHashMap<Bytes, OutFile>,
     compression: Compression,
@@ -160,6 +168,7 @@ impl FileSink {
             acker,
             path: config.path.clone(),
             encoding: config.encoding.clone(),
+            csv: config.csv.clone(),
             idle_timeout: Duration::from_secs(config.idle_timeout_secs.unwrap_or(30)),
             files: ExpiringHashMap::default(),
             compression: config.compression,
@@ -280,7 +289,18 @@ impl FileSink {
                 }
             };
 
-            let outfile = OutFile::new(file, self.compression);
+            // Files start with the header, which isn't written again when
+            // appending to them.
+            let is_empty = file
+                .metadata()
+                .await
+                .map_or(false, |metadata| metadata.len() == 0);
+            let mut outfile = OutFile::new(file, self.compression);
+            if let Some(header) = self.csv_header().filter(|_| is_empty) {
+                if let Err(error) = outfile.write_all(&header).await {
+                    error!(message = "Failed to write file.", path = ?path, %error);
+                }
+            }
 
             self.files.insert_at(path.clone(), outfile, next_deadline);
             emit!(FileOpen {
@@ -290,10 +310,19 @@ impl FileSink {
         };
 
         trace!(message = "Writing an event to file.", path = ?path);
-        if let Err(error) = write_event_to_file(file, event, &self.encoding).await {
+        if let Err(error) =
+            write_event_to_file(file, event, &self.encoding, self.csv.as_ref()).await
+        {
             error!(message = "Failed to write file.", path = ?path, %error);
         }
     }
+
+    fn csv_header(&self) -> Option<Vec<u8>> {
+        match self.encoding.codec() {
+            Encoding::Csv => self.csv.as_ref().and_then(CsvOptions::header),
+            _ => None,
+        }
+    }
 }
 
 async fn open_file(path: impl AsRef<std::path::Path>) -> std::io::Result<File> {
@@ -312,25 +341,35 @@ async fn open_file(path: impl AsRef<std::path::Path>) -> std::io::Result<File> {
         .await
 }
 
-pub fn encode_event(encoding: &EncodingConfig<Encoding>, mut event: Event) -> Vec<u8> {
+/// Encodes the event as a line. The columns of `csv` are required with the
+/// `csv` codec.
+pub fn encode_event(
+    encoding: &EncodingConfig<Encoding>,
+    csv: Option<&CsvOptions>,
+    mut event: Event,
+) -> Vec<u8> {
     encoding.apply_rules(&mut event);
     let log = event.into_log();
-    match encoding.codec() {
+    let mut buf = match encoding.codec() {
         Encoding::Ndjson => serde_json::to_vec(&log).expect("Unable to encode event as JSON."),
         Encoding::Text => log
             .get(log_schema().message_key())
             .map(|v| v.to_string_lossy().into_bytes())
             .unwrap_or_default(),
-    }
+        // Rows are already terminated.
+        Encoding::Csv => return csv.expect("Missing `csv` options, this is a bug!").encode(&log),
+    };
+    buf.push(b'\n');
+    buf
 }
 
 async fn write_event_to_file(
     file: &mut OutFile,
     event: Event,
     encoding: &EncodingConfig<Encoding>,
+    csv: Option<&CsvOptions>,
 ) -> Result<(), std::io::Error> {
-    let mut buf = encode_event(encoding, event);
-    buf.push(b'\n');
+    let buf = encode_event(encoding, csv, event);
     file.write_all(&buf[..]).await
 }
 
@@ -367,6 +406,7 @@ mod tests {
             path: template.clone().try_into().unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            csv: None,
             compression: Compression::None,
         };
 
@@ -392,6 +432,7 @@ mod tests {
             path: template.clone().try_into().unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            csv: None,
             compression: Compression::Gzip,
         };
 
@@ -407,6 +448,44 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn single_partition_csv() {
+        trace_init();
+
+        let template = temp_file();
+
+        let config = FileSinkConfig {
+            path: template.clone().try_into().unwrap(),
+            idle_timeout_secs: None,
+            encoding: Encoding::Csv.into(),
+            csv: Some(toml::from_str(r#"fields = ["message", "level"]"#).unwrap()),
+            compression: Compression::None,
+        };
+
+        let events = vec!["a", "b,c"].into_iter().map(|message| {
+            let mut event = Event::from(message);
+            event.as_mut_log().insert("level", "info");
+            event
+        });
+
+        // Appending to the file doesn't write the header again.
+        for events in vec![events.clone(), events] {
+            let mut sink = FileSink::new(&config, Acker::Null);
+            sink.run(Box::pin(stream::iter(events))).await.unwrap();
+        }
+
+        assert_eq!(
+            lines_from_file(template),
+            vec![
+                "message,level",
+                "a,info",
+                "\"b,c\",info",
+                "a,info",
+                "\"b,c\",info"
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn many_partitions() {
         trace_init();
@@ -422,6 +501,7 @@ mod tests {
             path: template.try_into().unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            csv: None,
             compression: Compression::None,
         };
 
@@ -503,6 +583,7 @@ mod tests {
             path: template.clone().try_into().unwrap(),
             idle_timeout_secs: Some(1),
             encoding: Encoding::Text.into(),
+            csv: None,
             compression: Compression::None,
         };
 
@@ -0,0 +1,273 @@
+//! Writes log events to a BigQuery table with the Storage Write API, as rows
+//! serialized with the configured schema. Batches are appended to a
+//! committed write stream at the offsets following each other, so that
+//! BigQuery rejects the rows of retried appends it already wrote. Rows not
+//! matching the schema are written to a dead-letter table instead.
+
+mod schema;
+mod service;
+
+pub use self::schema::{FieldConfig, FieldMode, FieldType};
+use self::{
+    schema::Schema,
+    service::{Appender, BigqueryService, Tables},
+};
+use super::{GcpAuthConfig, Scope};
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    gcp::GcpGrpcService,
+    proto::google::cloud::bigquery::storage::v1::big_query_write_client::BigQueryWriteClient,
+    sinks::{
+        util::{
+            encoding::{EncodingConfigWithDefault, EncodingConfiguration},
+            BatchConfig, BatchSettings, BatchSink, EncodedEvent, EncodedLength, TowerRequestConfig,
+            VecBuffer,
+        },
+        Healthcheck, VectorSink,
+    },
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use http::uri::Uri;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_ENDPOINT: &str = "https://bigquerystorage.googleapis.com";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BigqueryConfig {
+    pub project: String,
+    pub dataset: String,
+    pub table: String,
+    /// The fields of the rows, matched to the columns of the table by name.
+    pub schema: Vec<FieldConfig>,
+    /// The table of the same dataset the rows not matching the schema are
+    /// written to. They are dropped if it isn't set.
+    pub dead_letter_table: Option<String>,
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub skip_authentication: bool,
+    #[serde(flatten)]
+    pub auth: GcpAuthConfig,
+    #[serde(
+        skip_serializing_if = "crate::serde::skip_serializing_if_default",
+        default
+    )]
+    pub encoding: EncodingConfigWithDefault<Encoding>,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<TlsConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Default,
+}
+
+inventory::submit! {
+    SinkDescription::new::<BigqueryConfig>("gcp_bigquery")
+}
+
+impl GenerateConfig for BigqueryConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"project = "my-project"
+            dataset = "my-dataset"
+            table = "my-table"
+            schema = [{ name = "message" }]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "gcp_bigquery")]
+impl SinkConfig for BigqueryConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        let schema = Schema::new(&self.schema)?;
+
+        // We only need to load the credentials if we are not targeting an emulator.
+        let creds = if self.skip_authentication {
+            None
+        } else {
+            self.auth.make_credentials(Scope::BigQuery).await?
+        };
+        if let Some(creds) = &creds {
+            creds.spawn_regenerate_token();
+        }
+
+        let tls = MaybeTlsSettings::from_config(&self.tls, false)?;
+        let uri: Uri = self
+            .endpoint
+            .as_deref()
+            .unwrap_or(DEFAULT_ENDPOINT)
+            .parse()?;
+        let client = BigQueryWriteClient::new(GcpGrpcService::new(
+            uri,
+            &tls,
+            creds,
+            self.auth.api_key.clone(),
+        )?);
+        let tables = self.tables();
+        let healthcheck = service::healthcheck(client.clone(), tables.clone()).boxed();
+
+        // Appends are limited to 10MB.
+        let batch = BatchSettings::default()
+            .bytes(bytesize::mib(9u64))
+            .events(1000)
+            .timeout(1)
+            .parse_config(self.batch)?;
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+        let service =
+            BigqueryService::new(Appender::new(client, tables, schema.descriptor()), request);
+
+        let encoding = self.encoding.clone();
+        let dead_letter = self.dead_letter_table.is_some();
+        let sink = BatchSink::new(
+            service,
+            VecBuffer::new(batch.size),
+            batch.timeout,
+            cx.acker(),
+        )
+        .sink_map_err(|error| error!(message = "Fatal gcp_bigquery sink error.", %error))
+        .with_flat_map(move |event| {
+            stream::iter(encode_event(event, &schema, &encoding, dead_letter)).map(Ok)
+        });
+
+        Ok((VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "gcp_bigquery"
+    }
+}
+
+impl BigqueryConfig {
+    fn tables(&self) -> Tables {
+        let table = |table| {
+            format!(
+                "projects/{}/datasets/{}/tables/{}",
+                self.project, self.dataset, table
+            )
+        };
+        Tables {
+            table: table(&self.table),
+            dead_letter: self.dead_letter_table.as_ref().map(table),
+        }
+    }
+}
+
+/// An event serialized with the schema, or why it doesn't match it.
+#[derive(Clone, Debug, PartialEq)]
+struct Row {
+    encoded: Result<Vec<u8>, String>,
+    /// The event as JSON, kept to write it to the dead-letter table if the
+    /// row is rejected.
+    event: Option<String>,
+}
+
+impl EncodedLength for Row {
+    fn encoded_length(&self) -> usize {
+        let encoded = match &self.encoded {
+            Ok(encoded) => encoded.len(),
+            Err(error) => error.len(),
+        };
+        encoded + self.event.as_ref().map(String::len).unwrap_or(0)
+    }
+}
+
+fn encode_event(
+    mut event: Event,
+    schema: &Schema,
+    encoding: &EncodingConfigWithDefault<Encoding>,
+    dead_letter: bool,
+) -> Option<EncodedEvent<Row>> {
+    encoding.apply_rules(&mut event);
+    let finalizers = event.metadata_mut().take_finalizers();
+    let log = event.as_log();
+
+    let row = Row {
+        encoded: schema.encode(log).map_err(|error| error.to_string()),
+        event: dead_letter
+            .then(|| serde_json::to_string(log).ok())
+            .flatten(),
+    };
+    Some(EncodedEvent {
+        item: row,
+        finalizers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<BigqueryConfig>();
+    }
+
+    fn config(extra: &str) -> BigqueryConfig {
+        toml::from_str(&format!(
+            r#"
+            project = "project"
+            dataset = "dataset"
+            table = "events"
+            schema = [
+                {{ name = "message", mode = "required" }},
+                {{ name = "count", type = "int64", field = "stats.count" }},
+            ]
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn names_tables() {
+        let tables = config(r#"dead_letter_table = "errors""#).tables();
+        assert_eq!(
+            tables.table,
+            "projects/project/datasets/dataset/tables/events"
+        );
+        assert_eq!(
+            tables.dead_letter.as_deref(),
+            Some("projects/project/datasets/dataset/tables/errors")
+        );
+    }
+
+    #[test]
+    fn keeps_events_for_dead_letters() {
+        let config = config("");
+        let schema = Schema::new(&config.schema).unwrap();
+
+        let mut log = LogEvent::default();
+        log.insert("stats.count", 2);
+        let row = encode_event(log.into(), &schema, &config.encoding, true)
+            .unwrap()
+            .item;
+        assert_eq!(
+            row.encoded,
+            Err(r#"required field "message" is missing"#.to_owned())
+        );
+        assert_eq!(row.event.as_deref(), Some(r#"{"stats":{"count":2}}"#));
+
+        let row = encode_event(Event::from("hi"), &schema, &config.encoding, false)
+            .unwrap()
+            .item;
+        assert_eq!(row.encoded, Ok(vec![0x0a, 2, b'h', b'i']));
+        assert_eq!(row.event, None);
+    }
+}
@@ -0,0 +1,441 @@
+//! The schema rows are serialized with. Each field of the table is a field of
+//! a protobuf message numbered in the order of the fields, which BigQuery
+//! matches to the columns of the table by name.
+
+use crate::event::{LogEvent, Value};
+use bytes::BufMut;
+use chrono::{DateTime, SecondsFormat, Utc};
+use prost::encoding;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, FieldDescriptorProto,
+};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::collections::HashSet;
+
+#[derive(Debug, Snafu, PartialEq)]
+pub(super) enum SchemaError {
+    #[snafu(display("`schema` must have at least one field"))]
+    EmptySchema,
+    #[snafu(display(
+        "field name {:?} must be letters, digits and underscores, not starting with a digit",
+        name
+    ))]
+    InvalidFieldName { name: String },
+    #[snafu(display("field {:?} is in the schema more than once", name))]
+    DuplicateField { name: String },
+}
+
+/// Why an event doesn't match the schema.
+#[derive(Debug, Snafu, PartialEq)]
+pub(super) enum RowError {
+    #[snafu(display("required field {:?} is missing", name))]
+    MissingField { name: String },
+    #[snafu(display("field {:?} isn't a valid {}", name, field_type.as_str()))]
+    InvalidValue { name: String, field_type: FieldType },
+    #[snafu(display("repeated field {:?} isn't an array", name))]
+    NotAnArray { name: String },
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FieldConfig {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub mode: FieldMode,
+    /// The field the value is read from, the name of the field by default.
+    pub field: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    #[derivative(Default)]
+    String,
+    Bytes,
+    Int64,
+    Float64,
+    Bool,
+    /// Serialized as microseconds since the Unix epoch.
+    Timestamp,
+    /// Serialized as a string holding the value as JSON.
+    Json,
+}
+
+impl FieldType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::Int64 => "int64",
+            Self::Float64 => "float64",
+            Self::Bool => "bool",
+            Self::Timestamp => "timestamp",
+            Self::Json => "json",
+        }
+    }
+
+    fn proto_type(self) -> Type {
+        match self {
+            Self::String | Self::Json => Type::String,
+            Self::Bytes => Type::Bytes,
+            Self::Int64 | Self::Timestamp => Type::Int64,
+            Self::Float64 => Type::Double,
+            Self::Bool => Type::Bool,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldMode {
+    #[derivative(Default)]
+    Nullable,
+    Required,
+    Repeated,
+}
+
+/// A value of a row, converted to the type of its field.
+#[derive(Clone, Debug, PartialEq)]
+enum Scalar {
+    String(String),
+    Bytes(Vec<u8>),
+    Int64(i64),
+    Float64(f64),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn new(value: &Value, field_type: FieldType) -> Option<Self> {
+        match (field_type, value) {
+            (FieldType::Json, value) => serde_json::to_string(value).ok().map(Self::String),
+            (FieldType::String, Value::Timestamp(timestamp)) => Some(Self::String(
+                timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            )),
+            (FieldType::String, Value::Map(_)) | (FieldType::String, Value::Array(_)) => {
+                serde_json::to_string(value).ok().map(Self::String)
+            }
+            (FieldType::String, value) => Some(Self::String(value.to_string_lossy())),
+            (FieldType::Bytes, Value::Bytes(bytes)) | (FieldType::Bytes, Value::String(bytes)) => {
+                Some(Self::Bytes(bytes.to_vec()))
+            }
+            (FieldType::Int64, Value::Integer(value)) => Some(Self::Int64(*value)),
+            // Floats are only integers when they have no fraction and fit.
+            (FieldType::Int64, Value::Float(value))
+                if value.fract() == 0.0 && value.abs() < i64::MAX as f64 =>
+            {
+                Some(Self::Int64(*value as i64))
+            }
+            (FieldType::Float64, Value::Float(value)) => Some(Self::Float64(*value)),
+            (FieldType::Float64, Value::Integer(value)) => Some(Self::Float64(*value as f64)),
+            (FieldType::Bool, Value::Boolean(value)) => Some(Self::Bool(*value)),
+            (FieldType::Timestamp, Value::Timestamp(timestamp)) => {
+                Some(Self::Int64(micros(timestamp)))
+            }
+            (FieldType::Int64, value) => value.parse_text().map(Self::Int64),
+            (FieldType::Float64, value) => value.parse_text().map(Self::Float64),
+            (FieldType::Bool, value) => value.parse_text().map(Self::Bool),
+            (FieldType::Timestamp, value) => value
+                .parse_timestamp()
+                .map(|timestamp| Self::Int64(micros(&timestamp))),
+            (FieldType::Bytes, _) => None,
+        }
+    }
+
+    fn encode(&self, tag: u32, buf: &mut impl BufMut) {
+        match self {
+            Self::String(value) => encoding::string::encode(tag, value, buf),
+            Self::Bytes(value) => encoding::bytes::encode(tag, value, buf),
+            Self::Int64(value) => encoding::int64::encode(tag, value, buf),
+            Self::Float64(value) => encoding::double::encode(tag, value, buf),
+            Self::Bool(value) => encoding::bool::encode(tag, value, buf),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    field_type: FieldType,
+    mode: FieldMode,
+    source: String,
+}
+
+/// The fields of the rows, in the order of their numbers.
+#[derive(Clone, Debug)]
+pub(super) struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub(super) fn new(config: &[FieldConfig]) -> Result<Self, SchemaError> {
+        if config.is_empty() {
+            return Err(SchemaError::EmptySchema);
+        }
+        let mut names = HashSet::new();
+        let fields = config
+            .iter()
+            .map(|field| {
+                if !is_valid_name(&field.name) {
+                    return Err(SchemaError::InvalidFieldName {
+                        name: field.name.clone(),
+                    });
+                }
+                // Column names are case insensitive.
+                if !names.insert(field.name.to_lowercase()) {
+                    return Err(SchemaError::DuplicateField {
+                        name: field.name.clone(),
+                    });
+                }
+                Ok(Field {
+                    name: field.name.clone(),
+                    field_type: field.field_type,
+                    mode: field.mode,
+                    source: field.field.clone().unwrap_or_else(|| field.name.clone()),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { fields })
+    }
+
+    /// The descriptor of the message the rows are serialized as, which is
+    /// sent to BigQuery along with the rows.
+    pub(super) fn descriptor(&self) -> DescriptorProto {
+        let fields = self.fields.iter().enumerate().map(|(index, field)| {
+            let label = match field.mode {
+                FieldMode::Nullable => Label::Optional,
+                FieldMode::Required => Label::Required,
+                FieldMode::Repeated => Label::Repeated,
+            };
+            field_descriptor(&field.name, index as i32 + 1, label, field.field_type)
+        });
+        DescriptorProto {
+            name: Some("Row".to_owned()),
+            field: fields.collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Serializes the fields of the event, missing and null values being
+    /// left out of the row.
+    pub(super) fn encode(&self, log: &LogEvent) -> Result<Vec<u8>, RowError> {
+        let mut buf = Vec::new();
+        for (index, field) in self.fields.iter().enumerate() {
+            let tag = index as u32 + 1;
+            let invalid = || RowError::InvalidValue {
+                name: field.name.clone(),
+                field_type: field.field_type,
+            };
+            match (field.mode, log.get(&field.source)) {
+                (FieldMode::Required, None) | (FieldMode::Required, Some(Value::Null)) => {
+                    return Err(RowError::MissingField {
+                        name: field.name.clone(),
+                    })
+                }
+                (_, None) | (_, Some(Value::Null)) => (),
+                (FieldMode::Repeated, Some(Value::Array(values))) => {
+                    for value in values.iter().filter(|value| **value != Value::Null) {
+                        Scalar::new(value, field.field_type)
+                            .ok_or_else(invalid)?
+                            .encode(tag, &mut buf);
+                    }
+                }
+                (FieldMode::Repeated, Some(_)) => {
+                    return Err(RowError::NotAnArray {
+                        name: field.name.clone(),
+                    })
+                }
+                (_, Some(value)) => Scalar::new(value, field.field_type)
+                    .ok_or_else(invalid)?
+                    .encode(tag, &mut buf),
+            }
+        }
+        Ok(buf)
+    }
+}
+
+/// The descriptor of the rows of the dead-letter table, which hold the event
+/// as JSON, why it was rejected, and when.
+pub(super) fn dead_letter_descriptor() -> DescriptorProto {
+    DescriptorProto {
+        name: Some("DeadLetter".to_owned()),
+        field: vec![
+            field_descriptor("payload", 1, Label::Required, FieldType::Json),
+            field_descriptor("error", 2, Label::Required, FieldType::String),
+            field_descriptor("timestamp", 3, Label::Required, FieldType::Timestamp),
+        ],
+        ..Default::default()
+    }
+}
+
+pub(super) fn encode_dead_letter(payload: &str, error: &str, timestamp: DateTime<Utc>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encoding::string::encode(1, &payload.to_owned(), &mut buf);
+    encoding::string::encode(2, &error.to_owned(), &mut buf);
+    encoding::int64::encode(3, &micros(&timestamp), &mut buf);
+    buf
+}
+
+fn field_descriptor(
+    name: &str,
+    number: i32,
+    label: Label,
+    field_type: FieldType,
+) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_owned()),
+        number: Some(number),
+        label: Some(label as i32),
+        r#type: Some(field_type.proto_type() as i32),
+        ..Default::default()
+    }
+}
+
+/// The microseconds since the Unix epoch, which timestamps are written as.
+fn micros(timestamp: &DateTime<Utc>) -> i64 {
+    timestamp.timestamp() * 1_000_000 + timestamp.timestamp_subsec_micros() as i64
+}
+
+/// Whether the name is both a valid column name and a valid protobuf field
+/// name.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn field(name: &str, field_type: FieldType, mode: FieldMode) -> FieldConfig {
+        FieldConfig {
+            name: name.to_owned(),
+            field_type,
+            mode,
+            field: None,
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(&[
+            field("message", FieldType::String, FieldMode::Required),
+            field("count", FieldType::Int64, FieldMode::Nullable),
+            field("tags", FieldType::String, FieldMode::Repeated),
+            field("timestamp", FieldType::Timestamp, FieldMode::Nullable),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn validates_names() {
+        assert_eq!(Schema::new(&[]).unwrap_err(), SchemaError::EmptySchema);
+        for name in &["", "1st", "a-b", "café"] {
+            assert_eq!(
+                Schema::new(&[field(name, FieldType::String, FieldMode::Nullable)]).unwrap_err(),
+                SchemaError::InvalidFieldName {
+                    name: name.to_string()
+                }
+            );
+        }
+        assert_eq!(
+            Schema::new(&[
+                field("Message", FieldType::String, FieldMode::Nullable),
+                field("message", FieldType::String, FieldMode::Nullable),
+            ])
+            .unwrap_err(),
+            SchemaError::DuplicateField {
+                name: "message".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn describes_fields() {
+        let descriptor = schema().descriptor();
+        let fields = descriptor
+            .field
+            .iter()
+            .map(|field| (field.name(), field.number(), field.label(), field.r#type()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("message", 1, Label::Required, Type::String),
+                ("count", 2, Label::Optional, Type::Int64),
+                ("tags", 3, Label::Repeated, Type::String),
+                ("timestamp", 4, Label::Optional, Type::Int64),
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_fields() {
+        let mut log = LogEvent::from("hi");
+        log.insert("count", "5");
+        log.insert(
+            "tags",
+            vec![Value::from("a"), Value::Null, Value::from("b")],
+        );
+        log.insert("timestamp", Utc.timestamp(1, 2000));
+
+        assert_eq!(
+            schema().encode(&log).unwrap(),
+            vec![
+                0x0a, 2, b'h', b'i', // message
+                0x10, 5, // count
+                0x1a, 1, b'a', 0x1a, 1, b'b', // tags
+                0x20, 0xc2, 0x84, 0x3d, // timestamp, 1000002 microseconds
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_text_as_bytes() {
+        assert_eq!(
+            Scalar::new(&Value::from("hi"), FieldType::Bytes),
+            Some(Scalar::Bytes(b"hi".to_vec()))
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_events() {
+        let mut log = LogEvent::default();
+        log.insert("count", 1);
+        assert_eq!(
+            schema().encode(&log).unwrap_err(),
+            RowError::MissingField {
+                name: "message".to_owned()
+            }
+        );
+
+        let mut log = LogEvent::from("hi");
+        log.insert("count", 1.5);
+        assert_eq!(
+            schema().encode(&log).unwrap_err(),
+            RowError::InvalidValue {
+                name: "count".to_owned(),
+                field_type: FieldType::Int64
+            }
+        );
+
+        let mut log = LogEvent::from("hi");
+        log.insert("tags", "a");
+        assert_eq!(
+            schema().encode(&log).unwrap_err(),
+            RowError::NotAnArray {
+                name: "tags".to_owned()
+            }
+        );
+    }
+}
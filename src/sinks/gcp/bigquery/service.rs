@@ -0,0 +1,435 @@
+use super::{
+    schema::{dead_letter_descriptor, encode_dead_letter},
+    Row,
+};
+use crate::{
+    gcp::GcpGrpcService,
+    internal_events::{GcpBigqueryRowsRejected, GcpBigqueryRowsWritten},
+    proto::google::cloud::bigquery::storage::v1::{
+        append_rows_request::{ProtoData, Rows},
+        append_rows_response::Response,
+        big_query_write_client::BigQueryWriteClient,
+        write_stream::Type,
+        AppendRowsRequest, AppendRowsResponse, CreateWriteStreamRequest, GetWriteStreamRequest,
+        ProtoRows, ProtoSchema, WriteStream,
+    },
+    sinks::util::{retries::RetryLogic, EncodedLength, ServiceBuilderExt, TowerRequestSettings},
+};
+use chrono::Utc;
+use futures::{future::BoxFuture, stream};
+use prost_types::DescriptorProto;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tonic::{Code, Status};
+use tower::{util::BoxService, ServiceBuilder, ServiceExt};
+
+type Client = BigQueryWriteClient<GcpGrpcService>;
+
+#[derive(Debug, Snafu)]
+pub(super) enum BigqueryError {
+    #[snafu(display("request failed: {}", source))]
+    Request { source: Status },
+    #[snafu(display("append failed with code {:?}: {}", code, message))]
+    Append { code: Code, message: String },
+    #[snafu(display("append got no response"))]
+    NoResponse,
+}
+
+/// The names of the tables of the sink, in the form of
+/// `projects/{project}/datasets/{dataset}/tables/{table}`.
+#[derive(Clone, Debug)]
+pub(super) struct Tables {
+    pub(super) table: String,
+    pub(super) dead_letter: Option<String>,
+}
+
+/// The write stream the rows are appended to, and the offset the next rows
+/// are appended at.
+#[derive(Debug)]
+struct Stream {
+    name: String,
+    offset: i64,
+    /// Whether the last append may have been written or not, as it failed
+    /// without an answer.
+    in_doubt: bool,
+}
+
+/// Appends batches to the table, one at a time. Each batch is appended at
+/// the offset following the previous batch, and a retried append at the
+/// same offset, so that BigQuery rejects the rows it already has instead of
+/// writing them twice.
+#[derive(Clone)]
+pub(super) struct BigqueryService {
+    inner: Arc<tokio::sync::Mutex<BoxService<Vec<Row>, (), crate::Error>>>,
+    stream: Arc<Mutex<Option<Stream>>>,
+}
+
+impl BigqueryService {
+    pub(super) fn new(appender: Appender, request: TowerRequestSettings) -> Self {
+        let stream = Arc::clone(&appender.stream);
+        let inner = ServiceBuilder::new()
+            .settings(request, BigqueryRetryLogic)
+            .service(appender);
+        Self {
+            inner: Arc::new(tokio::sync::Mutex::new(inner)),
+            stream,
+        }
+    }
+
+    async fn write(self, rows: Vec<Row>) -> crate::Result<()> {
+        // The retries of a batch are made before the next batch is appended,
+        // as the offset of a batch is only known once the previous batch is
+        // written.
+        let mut inner = self.inner.lock().await;
+
+        // A batch given up on while in doubt leaves the offset unknown, so
+        // the next batch can't tell its rows from the rows of that batch and
+        // goes to a new stream.
+        {
+            let mut stream = self.stream.lock().expect("poisoned lock");
+            if stream
+                .as_ref()
+                .map(|stream| stream.in_doubt)
+                .unwrap_or(false)
+            {
+                *stream = None;
+            }
+        }
+
+        (&mut *inner).oneshot(rows).await
+    }
+}
+
+impl tower::Service<Vec<Row>> for BigqueryService {
+    type Response = ();
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, rows: Vec<Row>) -> Self::Future {
+        Box::pin(self.clone().write(rows))
+    }
+}
+
+/// Makes one attempt at appending a batch.
+#[derive(Clone)]
+pub(super) struct Appender {
+    client: Client,
+    tables: Arc<Tables>,
+    descriptor: Arc<DescriptorProto>,
+    stream: Arc<Mutex<Option<Stream>>>,
+}
+
+impl Appender {
+    pub(super) fn new(client: Client, tables: Tables, descriptor: DescriptorProto) -> Self {
+        Self {
+            client,
+            tables: Arc::new(tables),
+            descriptor: Arc::new(descriptor),
+            stream: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn append(self, rows: Vec<Row>) -> Result<(), BigqueryError> {
+        let mut valid = Vec::new();
+        let mut rejected = Vec::new();
+        for row in &rows {
+            match &row.encoded {
+                Ok(encoded) => valid.push((row, encoded)),
+                Err(error) => rejected.push((row, error.clone())),
+            }
+        }
+
+        while !valid.is_empty() {
+            // The rejected rows are written first, as the rows can only be
+            // appended again at the same offset until they are written.
+            self.reject(&mut rejected).await?;
+
+            let (name, offset) = self.stream().await?;
+            let encoded = valid.iter().map(|(_, encoded)| encoded.to_vec()).collect();
+            let response = self
+                .append_rows(&name, Some(offset), &self.descriptor, encoded)
+                .await?;
+            match response.response {
+                Some(Response::AppendResult(_)) => (),
+                // The rows were written by an attempt whose answer was lost.
+                Some(Response::Error(status)) if status.code == Code::AlreadyExists as i32 => (),
+                // None of the rows are written when some of them don't match
+                // the schema of the table, and the others are appended again.
+                Some(Response::Error(_)) if !response.row_errors.is_empty() => {
+                    self.settle(|_| ());
+                    let mut errors = response
+                        .row_errors
+                        .into_iter()
+                        .map(|error| (error.index as usize, error.message))
+                        .collect::<HashMap<_, _>>();
+                    let mut index = 0;
+                    valid.retain(|(row, _)| {
+                        let error = errors.remove(&index);
+                        index += 1;
+                        match error {
+                            Some(error) => {
+                                rejected.push((*row, error));
+                                false
+                            }
+                            None => true,
+                        }
+                    });
+                    continue;
+                }
+                Some(Response::Error(status)) => {
+                    let code = Code::from_i32(status.code);
+                    // These are errors of the stream rather than of the rows,
+                    // which are appended to a new stream.
+                    let abandon = matches!(
+                        code,
+                        Code::OutOfRange | Code::NotFound | Code::FailedPrecondition
+                    );
+                    self.settle(|stream| {
+                        if abandon {
+                            *stream = None;
+                        }
+                    });
+                    return Err(BigqueryError::Append {
+                        code,
+                        message: status.message,
+                    });
+                }
+                None => return Err(BigqueryError::NoResponse),
+            }
+
+            let count = valid.len();
+            self.settle(|stream| {
+                if let Some(stream) = stream {
+                    stream.offset += count as i64;
+                }
+            });
+            emit!(GcpBigqueryRowsWritten {
+                count,
+                byte_size: valid.iter().map(|(row, _)| row.encoded_length()).sum(),
+            });
+            break;
+        }
+
+        self.reject(&mut rejected).await
+    }
+
+    /// The name of the stream and the offset to append at, creating the
+    /// stream if there is none.
+    async fn stream(&self) -> Result<(String, i64), BigqueryError> {
+        let current = self
+            .stream
+            .lock()
+            .expect("poisoned lock")
+            .as_mut()
+            .map(|stream| {
+                stream.in_doubt = true;
+                (stream.name.clone(), stream.offset)
+            });
+        if let Some(current) = current {
+            return Ok(current);
+        }
+
+        // Rows appended to committed streams are visible once written.
+        let request = CreateWriteStreamRequest {
+            parent: self.tables.table.clone(),
+            write_stream: Some(WriteStream {
+                r#type: Type::Committed as i32,
+                ..Default::default()
+            }),
+        };
+        let created = self
+            .client
+            .clone()
+            .create_write_stream(request)
+            .await
+            .context(Request)?
+            .into_inner();
+        debug!(message = "Created write stream.", stream = %created.name);
+
+        *self.stream.lock().expect("poisoned lock") = Some(Stream {
+            name: created.name.clone(),
+            offset: 0,
+            in_doubt: true,
+        });
+        Ok((created.name, 0))
+    }
+
+    /// Updates the stream after an append got an answer, which settles
+    /// whether its rows were written.
+    fn settle(&self, update: impl FnOnce(&mut Option<Stream>)) {
+        let mut stream = self.stream.lock().expect("poisoned lock");
+        if let Some(stream) = stream.as_mut() {
+            stream.in_doubt = false;
+        }
+        update(&mut stream);
+    }
+
+    /// Writes the rows that don't match the schema to the dead-letter table,
+    /// or drops them if there is none.
+    async fn reject(&self, rejected: &mut Vec<(&Row, String)>) -> Result<(), BigqueryError> {
+        if rejected.is_empty() {
+            return Ok(());
+        }
+
+        let dead_letter = match &self.tables.dead_letter {
+            Some(table) => table,
+            None => {
+                emit!(GcpBigqueryRowsRejected {
+                    count: rejected.len(),
+                    error: &rejected[0].1,
+                    dead_lettered: false,
+                });
+                rejected.clear();
+                return Ok(());
+            }
+        };
+
+        let now = Utc::now();
+        let encoded = rejected
+            .iter()
+            .map(|(row, error)| encode_dead_letter(row.event.as_deref().unwrap_or(""), error, now))
+            .collect();
+        // The default stream writes rows at least once, without offsets.
+        let name = format!("{}/streams/_default", dead_letter);
+        let response = self
+            .append_rows(&name, None, &dead_letter_descriptor(), encoded)
+            .await?;
+        match response.response {
+            Some(Response::AppendResult(_)) => {
+                emit!(GcpBigqueryRowsRejected {
+                    count: rejected.len(),
+                    error: &rejected[0].1,
+                    dead_lettered: true,
+                });
+                rejected.clear();
+                Ok(())
+            }
+            Some(Response::Error(status)) => Err(BigqueryError::Append {
+                code: Code::from_i32(status.code),
+                message: status.message,
+            }),
+            None => Err(BigqueryError::NoResponse),
+        }
+    }
+
+    /// Appends the rows with a stream holding the single request, as the
+    /// batches are appended one at a time.
+    async fn append_rows(
+        &self,
+        name: &str,
+        offset: Option<i64>,
+        descriptor: &DescriptorProto,
+        serialized_rows: Vec<Vec<u8>>,
+    ) -> Result<AppendRowsResponse, BigqueryError> {
+        let request = AppendRowsRequest {
+            write_stream: name.to_owned(),
+            offset,
+            rows: Some(Rows::ProtoRows(ProtoData {
+                writer_schema: Some(ProtoSchema {
+                    proto_descriptor: Some(descriptor.clone()),
+                }),
+                rows: Some(ProtoRows { serialized_rows }),
+            })),
+            trace_id: "vector".to_owned(),
+        };
+        let mut responses = self
+            .client
+            .clone()
+            .append_rows(stream::iter(vec![request]))
+            .await
+            .context(Request)?
+            .into_inner();
+        responses
+            .message()
+            .await
+            .context(Request)?
+            .ok_or(BigqueryError::NoResponse)
+    }
+}
+
+impl tower::Service<Vec<Row>> for Appender {
+    type Response = ();
+    type Error = BigqueryError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, rows: Vec<Row>) -> Self::Future {
+        Box::pin(self.clone().append(rows))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct BigqueryRetryLogic;
+
+impl RetryLogic for BigqueryRetryLogic {
+    type Error = BigqueryError;
+    type Response = ();
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        let code = match error {
+            BigqueryError::Request { source } => source.code(),
+            BigqueryError::Append { code, .. } => *code,
+            BigqueryError::NoResponse => return true,
+        };
+        // Streams found out of range or finalized were replaced, so their
+        // rows are retried on the new stream.
+        matches!(
+            code,
+            Code::Unknown
+                | Code::DeadlineExceeded
+                | Code::ResourceExhausted
+                | Code::Aborted
+                | Code::OutOfRange
+                | Code::FailedPrecondition
+                | Code::Internal
+                | Code::Unavailable
+        )
+    }
+}
+
+/// Checks the tables exist and can be written to, by getting their default
+/// streams.
+pub(super) async fn healthcheck(client: Client, tables: Tables) -> crate::Result<()> {
+    for table in std::iter::once(&tables.table).chain(&tables.dead_letter) {
+        let request = GetWriteStreamRequest {
+            name: format!("{}/streams/_default", table),
+        };
+        client.clone().get_write_stream(request).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_stream_errors() {
+        let retriable = |code| {
+            BigqueryRetryLogic.is_retriable_error(&BigqueryError::Append {
+                code,
+                message: String::new(),
+            })
+        };
+        assert!(retriable(Code::OutOfRange));
+        assert!(retriable(Code::Unavailable));
+        assert!(!retriable(Code::InvalidArgument));
+        assert!(!retriable(Code::PermissionDenied));
+        assert!(
+            !BigqueryRetryLogic.is_retriable_error(&BigqueryError::Request {
+                source: Status::not_found("table")
+            })
+        );
+    }
+}
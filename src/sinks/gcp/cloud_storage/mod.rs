@@ -29,10 +29,17 @@ use hyper::{
 use indoc::indoc;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::{collections::HashMap, convert::TryFrom, task::Poll};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc, task::Poll};
 use tower::{Service, ServiceBuilder};
 use uuid::Uuid;
 
+mod partitions;
+
+use self::partitions::{
+    complete_partitions, encode_partitioned, CountedBuffer, GcsPartitioningConfig, Manifest,
+    PartitionedService, Partitions,
+};
+
 const NAME: &str = "gcp_cloud_storage";
 const BASE_URL: &str = "https://storage.googleapis.com/";
 
@@ -62,6 +69,7 @@ pub struct GcsSinkConfig {
     filename_time_format: Option<String>,
     filename_append_uuid: Option<bool>,
     filename_extension: Option<String>,
+    partitioning: Option<GcsPartitioningConfig>,
     encoding: EncodingConfig<Encoding>,
     #[serde(default)]
     compression: Compression,
@@ -85,6 +93,7 @@ fn default_config(e: Encoding) -> GcsSinkConfig {
         filename_time_format: Default::default(),
         filename_append_uuid: Default::default(),
         filename_extension: Default::default(),
+        partitioning: Default::default(),
         encoding: e.into(),
         compression: Compression::gzip_default(),
         batch: Default::default(),
@@ -212,22 +221,49 @@ impl GcsSink {
             .timeout(300)
             .parse_config(config.batch)?;
 
-        let key_prefix = config.key_prefix.as_deref().unwrap_or("date=%F/");
+        // The partitions are laid out under the key prefix, which doesn't
+        // need the date then.
+        let default_key_prefix = match config.partitioning {
+            Some(_) => "",
+            None => "date=%F/",
+        };
+        let key_prefix = config.key_prefix.as_deref().unwrap_or(default_key_prefix);
         let key_prefix = Template::try_from(key_prefix).context(KeyPrefixTemplate)?;
 
-        let settings = self.settings.clone();
+        let partitions = config
+            .partitioning
+            .as_ref()
+            .map(Partitions::new)
+            .transpose()?
+            .map(Arc::new);
+        if let Some(partitions) = &partitions {
+            tokio::spawn(complete_partitions(
+                self.clone(),
+                Arc::downgrade(partitions),
+            ));
+        }
 
+        let settings = self.settings.clone();
         let svc = ServiceBuilder::new()
-            .map(move |req| RequestWrapper::new(req, settings.clone()))
             .settings(request, GcsRetryLogic)
             .service(self);
+        let svc = PartitionedService::new(svc, settings, partitions.clone());
 
-        let buffer = PartitionBuffer::new(Buffer::new(batch.size, config.compression));
+        let buffer = PartitionBuffer::new(CountedBuffer::new(Buffer::new(
+            batch.size,
+            config.compression,
+        )));
 
         let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .sink_map_err(|error| error!(message = "Fatal gcp_cloud_storage error.", %error))
             .with_flat_map(move |event| {
-                stream::iter(encode_event(event, &key_prefix, &encoding)).map(Ok)
+                let encoded = match &partitions {
+                    Some(partitions) => {
+                        encode_partitioned(event, &key_prefix, &encoding, partitions)
+                    }
+                    None => encode_event(event, &key_prefix, &encoding),
+                };
+                stream::iter(encoded).map(Ok)
             });
 
         Ok(VectorSink::Sink(Box::new(sink)))
@@ -247,6 +283,46 @@ impl GcsSink {
         let response = self.client.send(request).await?;
         healthcheck_response(self.creds, not_found_error)(response)
     }
+
+    /// Writes the manifest and the `_SUCCESS` object of the partition, the
+    /// latter last as it's what marks the partition complete.
+    async fn write_markers(
+        &self,
+        config: &GcsPartitioningConfig,
+        manifest: Manifest,
+    ) -> crate::Result<()> {
+        if config.manifest {
+            let key = format!("{}_manifest.json", manifest.partition);
+            let body = serde_json::to_vec(&manifest)?;
+            self.put_marker(&key, body, "application/json").await?;
+        }
+        if config.success_marker {
+            let key = format!("{}_SUCCESS", manifest.partition);
+            self.put_marker(&key, Vec::new(), "text/plain").await?;
+        }
+        Ok(())
+    }
+
+    async fn put_marker(&self, key: &str, body: Vec<u8>, content_type: &str) -> crate::Result<()> {
+        let uri = format!("{}{}", self.base_url, key).parse::<Uri>()?;
+        let mut request = Request::put(uri)
+            .header("content-type", content_type)
+            .header("content-length", body.len())
+            .header("x-goog-storage-class", self.settings.storage_class.clone())
+            .body(Body::from(body))?;
+        if let Some(acl) = &self.settings.acl {
+            request.headers_mut().insert("x-goog-acl", acl.clone());
+        }
+        if let Some(creds) = &self.creds {
+            creds.apply(&mut request);
+        }
+
+        let response = self.client.send(request).await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(format!("Failed to write {:?}: {}", key, status).into()),
+        }
+    }
 }
 
 impl Service<RequestWrapper> for GcsSink {
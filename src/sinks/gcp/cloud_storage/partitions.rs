@@ -0,0 +1,543 @@
+//! Hive style partitioning of the objects of the sink. Events are grouped into
+//! windows of their timestamp, and the objects of each window are written
+//! under the path of its partition. Once a window is closed and all of its
+//! objects are written, the partition is marked complete with a `_SUCCESS`
+//! object and a manifest of its objects, so that batch jobs only read
+//! complete partitions.
+
+use super::{encode_event, Encoding, GcsSink, RequestSettings, RequestWrapper};
+use crate::{
+    config::log_schema,
+    event::{Event, Value},
+    internal_events::{
+        GcsLatePartitionEvent, GcsPartitionCompleted, GcsPartitionFailed, GcsPartitionMarkersFailed,
+    },
+    sinks::util::{
+        batch::{Batch, BatchConfig, BatchError, BatchSettings, PushResult},
+        encoding::EncodingConfig,
+        sink::Response,
+        Buffer, EncodedEvent, PartitionInnerBuffer,
+    },
+    template::Template,
+};
+use bytes::Bytes;
+use chrono::{
+    format::{strftime::StrftimeItems, Item},
+    DateTime, Duration, TimeZone, Utc,
+};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll},
+};
+use tower::Service;
+
+/// How often the partitions are checked for completion.
+const CHECK_INTERVAL_SECS: u64 = 10;
+/// How long completed partitions are remembered, so that the markers of the
+/// partitions receiving late events can be written again.
+const FORGET_AFTER_HOURS: i64 = 24;
+/// The value of the partition fields missing from events, as named by Hive.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GcsPartitioningConfig {
+    /// The length of the windows events are grouped into by their timestamp.
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// The path of the partition of a window, formatted from its start.
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    /// The fields of the events added to the path of their partition, as
+    /// `field=value/`, before the time of their window.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// How long after the end of a window its late events are waited for,
+    /// before its partition is complete.
+    #[serde(default = "default_close_delay_secs")]
+    pub close_delay_secs: u64,
+    #[serde(default = "crate::serde::default_true")]
+    pub success_marker: bool,
+    #[serde(default)]
+    pub manifest: bool,
+}
+
+const fn default_window_secs() -> u64 {
+    3600
+}
+
+fn default_time_format() -> String {
+    "dt=%Y-%m-%d/hour=%H/".into()
+}
+
+const fn default_close_delay_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Default)]
+struct PartitionState {
+    window_start: Option<DateTime<Utc>>,
+    window_end: Option<DateTime<Utc>>,
+    /// The events encoded but not sent yet.
+    buffered: usize,
+    /// The objects being written.
+    uploads: usize,
+    objects: Vec<ManifestObject>,
+    /// Whether an object failed to be written, in which case the partition
+    /// is never complete.
+    failed: bool,
+    /// Whether objects were written since the markers were.
+    dirty: bool,
+    completed: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(super) struct ManifestObject {
+    pub(super) key: String,
+    pub(super) size: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(super) struct Manifest {
+    pub(super) partition: String,
+    pub(super) window_start: DateTime<Utc>,
+    pub(super) window_end: DateTime<Utc>,
+    pub(super) objects: Vec<ManifestObject>,
+}
+
+/// The partitions of the sink, and the state of their windows.
+pub(super) struct Partitions {
+    config: GcsPartitioningConfig,
+    window: i64,
+    fields: Vec<String>,
+    state: Mutex<HashMap<String, PartitionState>>,
+}
+
+impl Partitions {
+    pub(super) fn new(config: &GcsPartitioningConfig) -> crate::Result<Self> {
+        if config.window_secs == 0 {
+            return Err("`partitioning.window_secs` must be greater than zero".into());
+        }
+        if StrftimeItems::new(&config.time_format).any(|item| item == Item::Error) {
+            return Err("`partitioning.time_format` is not a valid strftime format".into());
+        }
+        Ok(Self {
+            config: config.clone(),
+            window: config.window_secs as i64,
+            fields: config.fields.clone(),
+            state: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(super) fn config(&self) -> &GcsPartitioningConfig {
+        &self.config
+    }
+
+    fn close_delay(&self) -> Duration {
+        Duration::seconds(self.config.close_delay_secs as i64)
+    }
+
+    /// The window of the timestamp of the event.
+    fn window(&self, event: &Event) -> (DateTime<Utc>, DateTime<Utc>) {
+        let timestamp = match event.as_log().get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => Utc::now(),
+        };
+        let seconds = timestamp.timestamp();
+        let start = Utc.timestamp(seconds - seconds.rem_euclid(self.window), 0);
+        (start, start + Duration::seconds(self.window))
+    }
+
+    /// The path of the partition of the event, under the key prefix.
+    fn path(&self, event: &Event, window_start: DateTime<Utc>) -> String {
+        let log = event.as_log();
+        let mut path = String::new();
+        for field in &self.fields {
+            let value = log
+                .get(field.as_str())
+                .map(|value| escape(&value.to_string_lossy()))
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| DEFAULT_PARTITION.into());
+            path.push_str(&format!("{}={}/", field, value));
+        }
+        path.push_str(&window_start.format(&self.config.time_format).to_string());
+        path
+    }
+
+    /// Tracks the event encoded into the partition, unless the partition
+    /// was forgotten, in which case its markers are not written again.
+    fn encoded(&self, prefix: &str, window: (DateTime<Utc>, DateTime<Utc>)) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if !state.contains_key(prefix) {
+            let forgotten = window.1 + self.close_delay() + Duration::hours(FORGET_AFTER_HOURS);
+            if Utc::now() >= forgotten {
+                emit!(GcsLatePartitionEvent { partition: prefix });
+                return;
+            }
+        }
+        let partition = state.entry(prefix.to_owned()).or_default();
+        partition.window_start = Some(
+            partition
+                .window_start
+                .map_or(window.0, |start| start.min(window.0)),
+        );
+        partition.window_end = Some(
+            partition
+                .window_end
+                .map_or(window.1, |end| end.max(window.1)),
+        );
+        partition.buffered += 1;
+    }
+
+    /// Tracks the upload of a batch of `count` events of the partition.
+    pub(super) fn sending(&self, prefix: &str, count: usize) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if let Some(partition) = state.get_mut(prefix) {
+            partition.buffered = partition.buffered.saturating_sub(count);
+            partition.uploads += 1;
+        }
+    }
+
+    pub(super) fn sent(&self, prefix: &str, key: String, size: usize, success: bool) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if let Some(partition) = state.get_mut(prefix) {
+            partition.uploads -= 1;
+            if success {
+                partition.objects.push(ManifestObject { key, size });
+                partition.dirty = true;
+            } else if !partition.failed {
+                partition.failed = true;
+                emit!(GcsPartitionFailed { partition: prefix });
+            }
+        }
+    }
+
+    /// Takes the manifests of the partitions whose window is closed and
+    /// whose objects are all written, forgetting the old partitions.
+    fn complete(&self, now: DateTime<Utc>) -> Vec<Manifest> {
+        let mut state = self.state.lock().expect("poisoned lock");
+        let close_delay = self.close_delay();
+        state.retain(|_, partition| {
+            let idle = partition.buffered == 0 && partition.uploads == 0;
+            let forget = partition.window_end.map_or(true, |end| {
+                now >= end + close_delay + Duration::hours(FORGET_AFTER_HOURS)
+            });
+            // Failed partitions are never completed, so their objects are
+            // never marked.
+            !(idle && forget && (!partition.dirty || partition.failed))
+        });
+
+        state
+            .iter_mut()
+            .filter(|(_, partition)| {
+                partition.dirty
+                    && !partition.failed
+                    && partition.buffered == 0
+                    && partition.uploads == 0
+                    && partition
+                        .window_end
+                        .map_or(false, |end| now >= end + close_delay)
+            })
+            .map(|(prefix, partition)| {
+                partition.dirty = false;
+                Manifest {
+                    partition: prefix.clone(),
+                    window_start: partition.window_start.unwrap_or(now),
+                    window_end: partition.window_end.unwrap_or(now),
+                    objects: partition.objects.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn completed(&self, prefix: &str, success: bool) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if let Some(partition) = state.get_mut(prefix) {
+            match success {
+                true => partition.completed = true,
+                // Written again by the next check.
+                false => partition.dirty = true,
+            }
+        }
+    }
+}
+
+/// Escapes the characters of a value that would change the path of its
+/// partition, as Hive does.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '/' | '=' | '%' | '#' | '?' | '\\' | '\n' | '\r' => {
+                escaped.push_str(&format!("%{:02X}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Encodes the event under the path of its partition.
+pub(super) fn encode_partitioned(
+    event: Event,
+    key_prefix: &Template,
+    encoding: &EncodingConfig<Encoding>,
+    partitions: &Partitions,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, Bytes>>> {
+    let window = partitions.window(&event);
+    let path = partitions.path(&event, window.0);
+    let encoded = encode_event(event, key_prefix, encoding)?;
+
+    let (bytes, key) = encoded.item.into_parts();
+    let prefix = format!("{}{}", String::from_utf8_lossy(&key), path);
+    partitions.encoded(&prefix, window);
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(bytes, prefix.into()),
+        finalizers: encoded.finalizers,
+    })
+}
+
+/// A buffer counting the events of its batch, so that the partitions know
+/// which of their events are sent.
+pub(super) struct CountedBuffer(Buffer);
+
+impl CountedBuffer {
+    pub(super) fn new(buffer: Buffer) -> Self {
+        Self(buffer)
+    }
+}
+
+impl Batch for CountedBuffer {
+    type Input = Vec<u8>;
+    type Output = (Vec<u8>, usize);
+
+    fn get_settings_defaults(
+        config: BatchConfig,
+        defaults: BatchSettings<Self>,
+    ) -> Result<BatchSettings<Self>, BatchError> {
+        Ok(Buffer::get_settings_defaults(config, defaults.into())?.into())
+    }
+
+    fn push(&mut self, item: Self::Input) -> PushResult<Self::Input> {
+        Batch::push(&mut self.0, item)
+    }
+
+    fn is_empty(&self) -> bool {
+        Batch::is_empty(&self.0)
+    }
+
+    fn fresh(&self) -> Self {
+        Self(self.0.fresh())
+    }
+
+    fn finish(self) -> Self::Output {
+        let count = self.0.num_items();
+        (self.0.finish(), count)
+    }
+
+    fn num_items(&self) -> usize {
+        self.0.num_items()
+    }
+}
+
+/// Builds the requests of the batches, tracking the objects written by the
+/// inner service in their partitions.
+#[derive(Clone)]
+pub(super) struct PartitionedService<S> {
+    inner: S,
+    settings: RequestSettings,
+    partitions: Option<Arc<Partitions>>,
+}
+
+impl<S> PartitionedService<S> {
+    pub(super) fn new(
+        inner: S,
+        settings: RequestSettings,
+        partitions: Option<Arc<Partitions>>,
+    ) -> Self {
+        Self {
+            inner,
+            settings,
+            partitions,
+        }
+    }
+}
+
+impl<S> Service<PartitionInnerBuffer<(Vec<u8>, usize), Bytes>> for PartitionedService<S>
+where
+    S: Service<RequestWrapper>,
+    S::Response: Response + Send + 'static,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: PartitionInnerBuffer<(Vec<u8>, usize), Bytes>) -> Self::Future {
+        let ((body, count), key) = request.into_parts();
+        let prefix = String::from_utf8_lossy(&key).into_owned();
+        let request =
+            RequestWrapper::new(PartitionInnerBuffer::new(body, key), self.settings.clone());
+        let (object, size) = (request.key.clone(), request.body.len());
+
+        let partitions = self.partitions.clone();
+        if let Some(partitions) = &partitions {
+            partitions.sending(&prefix, count);
+        }
+
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            if let Some(partitions) = partitions {
+                let success = matches!(&result, Ok(response) if response.is_successful());
+                partitions.sent(&prefix, object, size, success);
+            }
+            result
+        })
+    }
+}
+
+/// Writes the markers of the partitions once they are complete, until the
+/// sink is dropped.
+pub(super) async fn complete_partitions(sink: GcsSink, partitions: Weak<Partitions>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let partitions = match partitions.upgrade() {
+            Some(partitions) => partitions,
+            None => break,
+        };
+
+        for manifest in partitions.complete(Utc::now()) {
+            let prefix = manifest.partition.clone();
+            let objects = manifest.objects.len();
+            match sink.write_markers(partitions.config(), manifest).await {
+                Ok(()) => {
+                    emit!(GcsPartitionCompleted {
+                        partition: &prefix,
+                        objects,
+                    });
+                    partitions.completed(&prefix, true);
+                }
+                Err(error) => {
+                    emit!(GcsPartitionMarkersFailed {
+                        partition: &prefix,
+                        error,
+                    });
+                    partitions.completed(&prefix, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    fn partitions(extra: &str) -> Partitions {
+        let config: GcsPartitioningConfig = toml::from_str(extra).unwrap();
+        Partitions::new(&config).unwrap()
+    }
+
+    fn event(timestamp: DateTime<Utc>, service: Option<&str>) -> Event {
+        let mut log = LogEvent::from("hello");
+        log.insert(log_schema().timestamp_key(), timestamp);
+        if let Some(service) = service {
+            log.insert("service", service);
+        }
+        log.into()
+    }
+
+    #[test]
+    fn renders_partition_paths() {
+        let partitions = partitions(r#"fields = ["service"]"#);
+        let timestamp = Utc.ymd(2021, 9, 1).and_hms(12, 30, 0);
+
+        let event = event(timestamp, Some("api/v1"));
+        let window = partitions.window(&event);
+        assert_eq!(window.0, Utc.ymd(2021, 9, 1).and_hms(12, 0, 0));
+        assert_eq!(window.1, Utc.ymd(2021, 9, 1).and_hms(13, 0, 0));
+        assert_eq!(
+            partitions.path(&event, window.0),
+            "service=api%2Fv1/dt=2021-09-01/hour=12/"
+        );
+
+        let event = self::event(timestamp, None);
+        assert_eq!(
+            partitions.path(&event, window.0),
+            "service=__HIVE_DEFAULT_PARTITION__/dt=2021-09-01/hour=12/"
+        );
+    }
+
+    #[test]
+    fn completes_written_partitions_once_closed() {
+        let partitions = partitions("close_delay_secs = 60");
+        let start = Utc::now() - Duration::hours(2);
+        let window = (start, start + Duration::hours(1));
+        let prefix = "dt=x/";
+
+        partitions.encoded(prefix, window);
+        partitions.encoded(prefix, window);
+        // Events are still buffered.
+        assert!(partitions
+            .complete(window.1 + Duration::minutes(2))
+            .is_empty());
+
+        partitions.sending(prefix, 2);
+        assert!(partitions
+            .complete(window.1 + Duration::minutes(2))
+            .is_empty());
+        partitions.sent(prefix, "dt=x/1.log".into(), 10, true);
+
+        // The window isn't closed yet.
+        assert!(partitions
+            .complete(window.1 + Duration::seconds(30))
+            .is_empty());
+        let manifests = partitions.complete(window.1 + Duration::minutes(2));
+        assert_eq!(
+            manifests,
+            vec![Manifest {
+                partition: prefix.into(),
+                window_start: window.0,
+                window_end: window.1,
+                objects: vec![ManifestObject {
+                    key: "dt=x/1.log".into(),
+                    size: 10,
+                }],
+            }]
+        );
+        partitions.completed(prefix, true);
+        assert!(partitions
+            .complete(window.1 + Duration::minutes(3))
+            .is_empty());
+
+        // Late events complete the partition again.
+        partitions.encoded(prefix, window);
+        partitions.sending(prefix, 1);
+        partitions.sent(prefix, "dt=x/2.log".into(), 5, true);
+        let manifests = partitions.complete(window.1 + Duration::minutes(4));
+        assert_eq!(manifests[0].objects.len(), 2);
+    }
+
+    #[test]
+    fn never_completes_failed_partitions() {
+        let partitions = partitions("");
+        let start = Utc::now() - Duration::hours(2);
+        let window = (start, start + Duration::hours(1));
+
+        partitions.encoded("dt=x/", window);
+        partitions.sending("dt=x/", 1);
+        partitions.sent("dt=x/", "dt=x/1.log".into(), 10, false);
+        assert!(partitions.complete(Utc::now()).is_empty());
+    }
+}
@@ -237,7 +237,7 @@ impl HttpSink for StackdriverSink {
 fn remap_severity(severity: Value) -> Value {
     let n = match severity {
         Value::Integer(n) => n - n % 100,
-        Value::Bytes(s) => {
+        Value::Bytes(s) | Value::String(s) => {
             let s = String::from_utf8_lossy(&s);
             match s.parse::<usize>() {
                 Ok(n) => (n - n % 100) as i64,
@@ -370,8 +370,8 @@ mod tests {
         };
 
         let mut log = LogEvent::default();
-        log.insert("message", Value::Bytes("hello world".into()));
-        log.insert("anumber", Value::Bytes("100".into()));
+        log.insert("message", Value::String("hello world".into()));
+        log.insert("anumber", Value::String("100".into()));
         log.insert(
             "timestamp",
             Value::Timestamp(Utc.ymd(2020, 1, 1).and_hms(12, 30, 0)),
@@ -159,6 +159,8 @@ impl HttpSink for HttpEventSink {
 
         let metric_labels = series
             .tags
+            .as_deref()
+            .cloned()
             .unwrap_or_default()
             .into_iter()
             .collect::<std::collections::HashMap<_, _>>();
@@ -0,0 +1,165 @@
+//! Sends log events to any gRPC method, as messages of its request type.
+//! The method and its messages are described by a file descriptor set, so
+//! that services can receive events without generating code for them.
+
+mod service;
+
+use self::service::{default_http, healthcheck, Call, GrpcRetryLogic, GrpcService, HyperSvc};
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, EventStatus},
+    internal_events::GrpcEventEncodingFailed,
+    protobuf::MessageEncoder,
+    sinks::util::{
+        BatchConfig, BatchSettings, BatchSink, EncodedEvent, ServiceBuilderExt, TowerRequestConfig,
+        VecBuffer,
+    },
+    sinks::{Healthcheck, VectorSink},
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+use tonic::metadata::{Ascii, MetadataKey, MetadataMap, MetadataValue};
+use tower::ServiceBuilder;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcSinkConfig {
+    address: String,
+    /// The descriptor set of the service, along with its imports, as written
+    /// by `protoc --include_imports --descriptor_set_out`.
+    descriptor_set_file: PathBuf,
+    /// The method called, as `package.Service/Method`.
+    method: String,
+    /// Fields of the message, mapped to the fields of events setting them.
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    /// A repeated message field of the request of unary methods, which
+    /// batches are sent as. Each event is a message of the field.
+    batch_field: Option<String>,
+    /// Sent along with every call, as the headers of HTTP requests are.
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    batch: BatchConfig,
+    #[serde(default)]
+    request: TowerRequestConfig,
+    tls: Option<TlsConfig>,
+}
+
+inventory::submit! {
+    SinkDescription::new::<GrpcSinkConfig>("grpc")
+}
+
+impl GenerateConfig for GrpcSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "http://127.0.0.1:50051"
+            descriptor_set_file = "/etc/vector/events.desc"
+            method = "events.v1.EventService/Push""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "grpc")]
+impl SinkConfig for GrpcSinkConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        let tls = MaybeTlsSettings::from_config(&self.tls, false)?;
+        let uri = default_http(&self.address)?;
+        let (method, encoder) = MessageEncoder::new(
+            &self.descriptor_set_file,
+            &self.method,
+            self.fields.clone(),
+            self.batch_field.as_deref(),
+        )?;
+        let metadata = metadata(&self.metadata)?;
+
+        let healthcheck_uri = cx
+            .healthcheck
+            .uri
+            .clone()
+            .map(|uri| uri.uri)
+            .unwrap_or_else(|| uri.clone());
+        let healthcheck = healthcheck(
+            HyperSvc::new(healthcheck_uri, &tls)?,
+            cx.healthcheck.clone(),
+        )
+        .boxed();
+
+        let call = Call::new(&method, encoder.batch_tag());
+        let service = GrpcService::new(HyperSvc::new(uri, &tls)?, method.path, call, metadata);
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+        let batch = BatchSettings::default()
+            .events(100)
+            .timeout(1)
+            .parse_config(self.batch)?;
+
+        let svc = ServiceBuilder::new()
+            .settings(request, GrpcRetryLogic)
+            .service(service);
+
+        let buffer = VecBuffer::new(batch.size);
+        let sink = BatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .sink_map_err(|error| error!(message = "Fatal gRPC sink error.", %error))
+            .with_flat_map(move |mut event: Event| {
+                let finalizers = event.metadata_mut().take_finalizers();
+                let encoded = match encoder.encode(event.as_log()) {
+                    Ok(item) => Some(EncodedEvent { item, finalizers }),
+                    Err(error) => {
+                        emit!(GrpcEventEncodingFailed {
+                            error: &error.to_string()
+                        });
+                        finalizers.update_status(EventStatus::Failed);
+                        None
+                    }
+                };
+                stream::iter(encoded).map(Ok)
+            });
+
+        Ok((VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "grpc"
+    }
+}
+
+fn metadata(config: &BTreeMap<String, String>) -> crate::Result<MetadataMap> {
+    let mut metadata = MetadataMap::new();
+    for (key, value) in config {
+        let key = MetadataKey::<Ascii>::from_str(key)
+            .map_err(|_| format!("Invalid metadata key {:?}", key))?;
+        let value = MetadataValue::<Ascii>::from_str(value)
+            .map_err(|_| format!("Invalid metadata value for {:?}", key.as_str()))?;
+        metadata.insert(key, value);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<GrpcSinkConfig>();
+    }
+
+    #[test]
+    fn parses_metadata() {
+        let config = vec![("authorization".to_owned(), "Bearer token".to_owned())];
+        let map = metadata(&config.into_iter().collect()).unwrap();
+        let value = map.get("authorization").unwrap();
+        assert_eq!(value.to_str().unwrap(), "Bearer token");
+
+        let config = vec![("invalid key".to_owned(), "value".to_owned())];
+        assert!(metadata(&config.into_iter().collect()).is_err());
+    }
+}
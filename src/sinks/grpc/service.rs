@@ -0,0 +1,348 @@
+use crate::{
+    config::SinkHealthcheckOptions,
+    internal_events::GrpcMessagesSent,
+    protobuf::{encode_batch, Method},
+    sinks::util::retries::RetryLogic,
+    tls::{tls_connector_builder, MaybeTlsSettings},
+};
+use bytes::{Buf, BufMut, Bytes};
+use futures::{future::BoxFuture, stream};
+use http::uri::{PathAndQuery, Uri};
+use hyper::client::HttpConnector;
+use hyper_openssl::HttpsConnector;
+use snafu::Snafu;
+use std::task::{Context, Poll};
+use tonic::{
+    body::BoxBody,
+    client::Grpc,
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    metadata::MetadataMap,
+    Code, Request, Status,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Request failed: {}", source))]
+    Request { source: Status },
+}
+
+/// How the messages of a batch are sent.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Call {
+    /// A call per message.
+    Unary,
+    /// A single call, the messages making up the repeated field of the
+    /// request numbered `tag`.
+    UnaryBatch { tag: u32 },
+    /// A single call streaming the messages.
+    ClientStreaming,
+}
+
+impl Call {
+    pub(super) fn new(method: &Method, batch_tag: Option<u32>) -> Self {
+        match (method.client_streaming, batch_tag) {
+            (true, _) => Self::ClientStreaming,
+            (false, Some(tag)) => Self::UnaryBatch { tag },
+            (false, None) => Self::Unary,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct GrpcService {
+    client: Grpc<HyperSvc>,
+    path: PathAndQuery,
+    call: Call,
+    metadata: MetadataMap,
+}
+
+impl GrpcService {
+    pub(super) fn new(
+        client: HyperSvc,
+        path: PathAndQuery,
+        call: Call,
+        metadata: MetadataMap,
+    ) -> Self {
+        Self {
+            client: Grpc::new(client),
+            path,
+            call,
+            metadata,
+        }
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        *request.metadata_mut() = self.metadata.clone();
+        request
+    }
+
+    async fn unary(mut self, message: Bytes) -> Result<(), Error> {
+        self.ready().await?;
+        let request = self.request(message);
+        self.client
+            .unary(request, self.path.clone(), RawCodec)
+            .await
+            .map(drop)
+            .map_err(|source| Error::Request { source })
+    }
+
+    async fn client_streaming(mut self, messages: Vec<Bytes>) -> Result<(), Error> {
+        self.ready().await?;
+        let request = self.request(stream::iter(messages));
+        self.client
+            .client_streaming(request, self.path.clone(), RawCodec)
+            .await
+            .map(drop)
+            .map_err(|source| Error::Request { source })
+    }
+
+    /// Waits for the connection to be ready, as the generated clients do.
+    async fn ready(&mut self) -> Result<(), Error> {
+        self.client.ready().await.map_err(|error| Error::Request {
+            source: Status::new(Code::Unknown, format!("Service was not ready: {}", error)),
+        })
+    }
+}
+
+impl tower::Service<Vec<Bytes>> for GrpcService {
+    type Response = ();
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // As in the `vector` sink, readiness of the client is awaited by the
+        // calls themselves.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, messages: Vec<Bytes>) -> Self::Future {
+        let service = self.clone();
+        let count = messages.len();
+        let byte_size = messages.iter().map(Bytes::len).sum();
+
+        Box::pin(async move {
+            let call = service.call;
+            match call {
+                // Messages are sent concurrently, all of them again when any
+                // of them fails.
+                Call::Unary => {
+                    let calls = messages
+                        .into_iter()
+                        .map(|message| service.clone().unary(message));
+                    futures::future::try_join_all(calls).await?;
+                }
+                Call::UnaryBatch { tag } => service.unary(encode_batch(tag, &messages)).await?,
+                Call::ClientStreaming => service.client_streaming(messages).await?,
+            }
+            emit!(GrpcMessagesSent { count, byte_size });
+            Ok(())
+        })
+    }
+}
+
+/// Sends messages already encoded, and ignores the responses.
+#[derive(Clone, Copy, Debug, Default)]
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Bytes;
+    type Decode = ();
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        dst.put(item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = ();
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        src.advance(src.remaining());
+        Ok(Some(()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct GrpcRetryLogic;
+
+impl RetryLogic for GrpcRetryLogic {
+    type Error = Error;
+    type Response = ();
+
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error {
+            Error::Request { source } => matches!(
+                source.code(),
+                Code::Unknown
+                    | Code::DeadlineExceeded
+                    | Code::ResourceExhausted
+                    | Code::Aborted
+                    | Code::Internal
+                    | Code::Unavailable
+            ),
+        }
+    }
+}
+
+/// Calls the standard health checking service, for the health of the
+/// server as a whole. Servers not implementing it are considered healthy,
+/// since they answered.
+pub(super) async fn healthcheck(
+    client: HyperSvc,
+    options: SinkHealthcheckOptions,
+) -> crate::Result<()> {
+    if !options.enabled {
+        return Ok(());
+    }
+
+    let mut client = Grpc::new(client);
+    client.ready().await?;
+    let path = PathAndQuery::from_static("/grpc.health.v1.Health/Check");
+    // A request for the empty service name is an empty message.
+    match client
+        .unary(Request::new(Bytes::new()), path, HealthCodec)
+        .await
+    {
+        // The status `SERVING` is 1.
+        Ok(response) if *response.get_ref() == 1 => Ok(()),
+        Ok(_) => Err("Server isn't serving".into()),
+        Err(status) if status.code() == Code::Unimplemented => Ok(()),
+        Err(status) => Err(status.into()),
+    }
+}
+
+/// Reads the status of health check responses, the first field of the
+/// message and the only one set.
+#[derive(Clone, Copy, Debug, Default)]
+struct HealthCodec;
+
+impl Codec for HealthCodec {
+    type Encode = Bytes;
+    type Decode = u64;
+    type Encoder = RawCodec;
+    type Decoder = HealthCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        HealthCodec
+    }
+}
+
+impl Decoder for HealthCodec {
+    type Item = u64;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let mut status = 0;
+        while src.has_remaining() {
+            let (tag, wire_type) = prost::encoding::decode_key(src)
+                .map_err(|error| Status::new(Code::Internal, error.to_string()))?;
+            if tag == 1 {
+                status = prost::encoding::decode_varint(src)
+                    .map_err(|error| Status::new(Code::Internal, error.to_string()))?;
+            } else {
+                prost::encoding::skip_field(wire_type, tag, src, Default::default())
+                    .map_err(|error| Status::new(Code::Internal, error.to_string()))?;
+            }
+        }
+        Ok(Some(status))
+    }
+}
+
+/// Sends the requests of the client to the configured address.
+#[derive(Clone)]
+pub(super) struct HyperSvc {
+    uri: Uri,
+    client: hyper::Client<HttpsConnector<HttpConnector>, BoxBody>,
+}
+
+impl HyperSvc {
+    pub(super) fn new(uri: Uri, tls_settings: &MaybeTlsSettings) -> crate::Result<Self> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        let tls = tls_connector_builder(tls_settings)?;
+        let mut https = HttpsConnector::with_connector(http, tls)?;
+
+        let settings = tls_settings.tls().cloned();
+        https.set_callback(move |c, _uri| {
+            if let Some(settings) = &settings {
+                settings.apply_connect_configuration(c);
+            }
+
+            Ok(())
+        });
+
+        Ok(Self {
+            uri,
+            client: hyper::Client::builder().http2_only(true).build(https),
+        })
+    }
+}
+
+impl tower::Service<hyper::Request<BoxBody>> for HyperSvc {
+    type Response = hyper::Response<hyper::Body>;
+    type Error = hyper::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: hyper::Request<BoxBody>) -> Self::Future {
+        let uri = Uri::builder()
+            .scheme(self.uri.scheme().unwrap().clone())
+            .authority(self.uri.authority().unwrap().clone())
+            .path_and_query(req.uri().path_and_query().unwrap().clone())
+            .build()
+            .unwrap();
+
+        *req.uri_mut() = uri;
+
+        Box::pin(self.client.request(req))
+    }
+}
+
+/// grpc doesn't like an address without a scheme, so we default to http if one isn't specified in
+/// the address.
+pub(super) fn default_http(address: &str) -> crate::Result<Uri> {
+    let uri: Uri = address.parse()?;
+    if uri.scheme().is_none() {
+        let mut parts = uri.into_parts();
+        parts.scheme = Some(
+            "http"
+                .parse()
+                .unwrap_or_else(|_| unreachable!("http should be valid")),
+        );
+        if parts.path_and_query.is_none() {
+            parts.path_and_query = Some(
+                "/".parse()
+                    .unwrap_or_else(|_| unreachable!("root should be valid")),
+            );
+        }
+        Ok(Uri::from_parts(parts)?)
+    } else {
+        Ok(uri)
+    }
+}
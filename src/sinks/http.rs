@@ -2,26 +2,49 @@ use crate::{
     config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     event::Event,
     http::{Auth, HttpClient, MaybeAuth},
-    internal_events::{HttpEventEncoded, HttpEventMissingMessage},
+    internal_events::{
+        HttpEventEncoded, HttpEventMissingMessage, HttpRequestTemplateInvalid,
+        TemplateRenderingFailed,
+    },
     sinks::util::{
         buffer::compression::GZIP_DEFAULT,
         encoding::{EncodingConfig, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpSink, RequestConfig},
-        BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig, UriSerde,
+        http::{BatchedHttpSink, HttpBatchService, HttpRetryLogic, HttpSink, RequestConfig},
+        service::Svc,
+        BatchConfig, BatchSettings, Buffer, Compression, EncodedEvent, Partition,
+        PartitionBatchSink, PartitionBuffer, PartitionInnerBuffer, TowerRequestConfig,
+        TowerRequestSettings, UriSerde,
     },
+    template::{Template, TemplateRenderingError},
     tls::{TlsOptions, TlsSettings},
 };
+use bytes::Bytes;
 use flate2::write::GzEncoder;
-use futures::{future, FutureExt, SinkExt};
+use futures::{future, future::BoxFuture, stream, FutureExt, SinkExt, StreamExt, TryFutureExt};
 use http::{
     header::{self, HeaderName, HeaderValue},
     Method, Request, StatusCode, Uri,
 };
 use hyper::Body;
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{self, IntoDeserializer},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use snafu::{ResultExt, Snafu};
-use std::io::Write;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    io::Write,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+/// The batches that may be in flight across all targets when the requests
+/// are templated, which bounds the batches held by targets that are slow.
+const MAX_IN_FLIGHT_BATCHES: usize = 1024;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -35,13 +58,18 @@ enum BuildError {
         value: String,
         source: header::InvalidHeaderValue,
     },
+    #[snafu(display("Invalid template for {}: {}", field, source))]
+    InvalidTemplate {
+        field: String,
+        source: crate::template::TemplateParseError,
+    },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct HttpSinkConfig {
-    pub uri: UriSerde,
-    pub method: Option<HttpMethod>,
+    pub uri: Templated<UriSerde>,
+    pub method: Option<Templated<HttpMethod>>,
     pub auth: Option<Auth>,
     // Deprecated, moved to request.
     pub headers: Option<IndexMap<String, String>>,
@@ -53,6 +81,9 @@ pub struct HttpSinkConfig {
     #[serde(default)]
     pub request: RequestConfig,
     pub tls: Option<TlsOptions>,
+    /// Where events are sent when the templates of their request fail to
+    /// render, instead of being dropped.
+    pub dead_letter_uri: Option<UriSerde>,
 }
 
 #[cfg(test)]
@@ -67,6 +98,7 @@ fn default_config(e: Encoding) -> HttpSinkConfig {
         encoding: e.into(),
         request: Default::default(),
         tls: Default::default(),
+        dead_letter_uri: Default::default(),
     }
 }
 
@@ -85,6 +117,102 @@ pub enum HttpMethod {
     Patch,
 }
 
+impl From<&HttpMethod> for Method {
+    fn from(method: &HttpMethod) -> Self {
+        match method {
+            HttpMethod::Get => Method::GET,
+            HttpMethod::Head => Method::HEAD,
+            HttpMethod::Post => Method::POST,
+            HttpMethod::Put => Method::PUT,
+            HttpMethod::Delete => Method::DELETE,
+            HttpMethod::Options => Method::OPTIONS,
+            HttpMethod::Trace => Method::TRACE,
+            HttpMethod::Patch => Method::PATCH,
+        }
+    }
+}
+
+/// An option that is either fixed, or a template rendered for each event
+/// when it holds a `{{ field }}` placeholder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Templated<T> {
+    Fixed(T),
+    Template(Template),
+}
+
+impl<T: Default> Default for Templated<T> {
+    fn default() -> Self {
+        Self::Fixed(T::default())
+    }
+}
+
+impl From<HttpMethod> for Templated<HttpMethod> {
+    fn from(method: HttpMethod) -> Self {
+        Self::Fixed(method)
+    }
+}
+
+impl From<Uri> for Templated<UriSerde> {
+    fn from(uri: Uri) -> Self {
+        Self::Fixed(uri.into())
+    }
+}
+
+impl<T> Templated<T> {
+    fn fixed(&self) -> Option<&T> {
+        match self {
+            Self::Fixed(value) => Some(value),
+            Self::Template(_) => None,
+        }
+    }
+
+    fn template(&self) -> Option<&Template> {
+        match self {
+            Self::Fixed(_) => None,
+            Self::Template(template) => Some(template),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Templated<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(value) => value.fmt(f),
+            Self::Template(template) => template.get_ref().fmt(f),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Templated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Fixed(value) => value.serialize(serializer),
+            Self::Template(template) => template.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Templated<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if is_template(&value) {
+            Template::try_from(value)
+                .map(Self::Template)
+                .map_err(de::Error::custom)
+        } else {
+            let deserializer: de::value::StringDeserializer<D::Error> = value.into_deserializer();
+            T::deserialize(deserializer).map(Self::Fixed)
+        }
+    }
+}
+
+/// Whether an option is a template. Only options with `{{ field }}`
+/// placeholders are, so that fixed URIs and headers holding `%` aren't taken
+/// for `strftime` templates.
+fn is_template(value: &str) -> bool {
+    value.contains("{{")
+}
+
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum Encoding {
@@ -112,6 +240,149 @@ impl HttpSinkConfig {
         let tls = TlsSettings::from_options(&self.tls)?;
         Ok(HttpClient::new(tls, cx.proxy())?)
     }
+
+    fn request_templates(&self) -> crate::Result<RequestTemplates> {
+        let headers = self
+            .request
+            .headers
+            .iter()
+            .filter(|(_, value)| is_template(value))
+            .map(|(name, value)| {
+                let field = format!("request.headers.{}", name);
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| InvalidHeaderName { name })?;
+                let value = Template::try_from(value.as_str())
+                    .with_context(|| InvalidTemplate { field })?;
+                Ok((name, value))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(RequestTemplates {
+            uri: self.uri.template().cloned(),
+            method: self.method.as_ref().and_then(Templated::template).cloned(),
+            headers,
+        })
+    }
+
+    /// Builds the request of a batch, from the configured options and the
+    /// parts of the request rendered for its target.
+    fn build_request_to(
+        &self,
+        mut body: Vec<u8>,
+        target: &Target,
+    ) -> crate::Result<http::Request<Vec<u8>>> {
+        let (uri, auth, method, rendered_headers) = match target {
+            Target::Rendered {
+                uri,
+                auth,
+                method,
+                headers,
+            } => (
+                uri.clone()
+                    .or_else(|| self.uri.fixed().map(|uri| uri.uri.clone())),
+                auth.as_ref().or_else(|| self.auth.as_ref()),
+                method.clone().unwrap_or_else(|| {
+                    self.method
+                        .as_ref()
+                        .and_then(Templated::fixed)
+                        .map_or(Method::POST, Method::from)
+                }),
+                headers.as_slice(),
+            ),
+            Target::DeadLetter => (
+                self.dead_letter_uri.as_ref().map(|uri| uri.uri.clone()),
+                self.dead_letter_uri
+                    .as_ref()
+                    .and_then(|uri| uri.auth.as_ref())
+                    .or_else(|| self.auth.as_ref()),
+                Method::POST,
+                &[][..],
+            ),
+        };
+        let uri = uri.ok_or("The URI of the request wasn't rendered")?;
+
+        let ct = match self.encoding.codec() {
+            Encoding::Text => "text/plain",
+            Encoding::Ndjson => "application/x-ndjson",
+            Encoding::Json => {
+                body.insert(0, b'[');
+                body.pop(); // remove trailing comma from last record
+                body.push(b']');
+                "application/json"
+            }
+        };
+
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", ct);
+
+        match self.compression {
+            Compression::Gzip(level) => {
+                builder = builder.header("Content-Encoding", "gzip");
+
+                let level = level.unwrap_or(GZIP_DEFAULT) as u32;
+                let mut w = GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+                w.write_all(&body).expect("Writing to Vec can't fail");
+                body = w.finish().expect("Writing to Vec can't fail");
+            }
+            Compression::None => {}
+        }
+
+        for (header, value) in self.request.headers.iter() {
+            if !is_template(value) {
+                builder = builder.header(header.as_str(), value.as_str());
+            }
+        }
+        for (header, value) in rendered_headers {
+            builder = builder.header(header.clone(), value.clone());
+        }
+
+        let mut request = builder.body(body).unwrap();
+
+        if let Some(auth) = auth {
+            auth.apply(&mut request);
+        }
+
+        Ok(request)
+    }
+
+    /// Encodes an event along with the target of its request, which is the
+    /// dead-letter URI if its templates fail to render and one is set.
+    fn encode_event_to_target(
+        &self,
+        mut event: Event,
+        templates: &RequestTemplates,
+    ) -> Option<EncodedEvent<PartitionInnerBuffer<Vec<u8>, Target>>> {
+        let dead_letter = self.dead_letter_uri.is_some();
+        let target = match templates.render(&event, &self.auth) {
+            Ok(target) => target,
+            Err(error) => {
+                match error {
+                    TargetError::Render { field, source } => emit!(TemplateRenderingFailed {
+                        error: source,
+                        field: Some(field.as_str()),
+                        drop_event: !dead_letter,
+                    }),
+                    error => emit!(HttpRequestTemplateInvalid {
+                        error: error.to_string(),
+                        drop_event: !dead_letter,
+                    }),
+                }
+                if !dead_letter {
+                    return None;
+                }
+                Target::DeadLetter
+            }
+        };
+
+        let finalizers = event.metadata_mut().take_finalizers();
+        let body = self.encode_event(event)?;
+        Some(EncodedEvent {
+            item: PartitionInnerBuffer::new(body, target),
+            finalizers,
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -130,14 +401,19 @@ impl SinkConfig for HttpSinkConfig {
             None => future::ok(()).boxed(),
         };
 
-        let mut config = HttpSinkConfig {
-            auth: self.auth.choose_one(&self.uri.auth)?,
-            uri: self.uri.with_default_parts(),
-            ..self.clone()
-        };
+        let mut config = self.clone();
+        if let Templated::Fixed(uri) = &self.uri {
+            config.auth = self.auth.choose_one(&uri.auth)?;
+            config.uri = Templated::Fixed(uri.with_default_parts());
+        }
+        config.dead_letter_uri = self
+            .dead_letter_uri
+            .as_ref()
+            .map(UriSerde::with_default_parts);
 
         config.request.add_old_option(config.headers.take());
         validate_headers(&config.request.headers, &config.auth)?;
+        let templates = config.request_templates()?;
 
         let batch = BatchSettings::default()
             .bytes(bytesize::mib(10u64))
@@ -147,17 +423,35 @@ impl SinkConfig for HttpSinkConfig {
             .request
             .tower
             .unwrap_with(&TowerRequestConfig::default());
-        let sink = BatchedHttpSink::new(
-            config,
-            Buffer::new(batch.size, Compression::None),
-            request,
-            batch.timeout,
-            client,
-            cx.acker(),
-        )
-        .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
 
-        let sink = super::VectorSink::Sink(Box::new(sink));
+        let sink = if templates.is_empty() {
+            let sink = BatchedHttpSink::new(
+                config,
+                Buffer::new(batch.size, Compression::None),
+                request,
+                batch.timeout,
+                client,
+                cx.acker(),
+            )
+            .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
+            super::VectorSink::Sink(Box::new(sink))
+        } else {
+            let config = Arc::new(config);
+            let svc = ServiceBuilder::new()
+                .concurrency_limit(MAX_IN_FLIGHT_BATCHES)
+                .service(TargetPartitionSvc::new(
+                    Arc::clone(&config),
+                    client,
+                    request,
+                ));
+            let buffer = PartitionBuffer::new(Buffer::new(batch.size, Compression::None));
+            let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+                .with_flat_map(move |event| {
+                    stream::iter(config.encode_event_to_target(event, &templates)).map(Ok)
+                })
+                .sink_map_err(|error| error!(message = "Fatal HTTP sink error.", %error));
+            super::VectorSink::Sink(Box::new(sink))
+        };
 
         Ok((sink, healthcheck))
     }
@@ -216,58 +510,184 @@ impl HttpSink for HttpSinkConfig {
         Some(body)
     }
 
-    async fn build_request(&self, mut body: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let method = match &self.method.clone().unwrap_or(HttpMethod::Post) {
-            HttpMethod::Get => Method::GET,
-            HttpMethod::Head => Method::HEAD,
-            HttpMethod::Post => Method::POST,
-            HttpMethod::Put => Method::PUT,
-            HttpMethod::Delete => Method::DELETE,
-            HttpMethod::Options => Method::OPTIONS,
-            HttpMethod::Trace => Method::TRACE,
-            HttpMethod::Patch => Method::PATCH,
+    async fn build_request(&self, body: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
+        self.build_request_to(body, &Target::default())
+    }
+}
+
+/// The templates of the parts of the requests rendered for each event.
+#[derive(Clone, Debug)]
+struct RequestTemplates {
+    uri: Option<Template>,
+    method: Option<Template>,
+    headers: Vec<(HeaderName, Template)>,
+}
+
+#[derive(Debug, Snafu)]
+enum TargetError {
+    #[snafu(display("Failed to render template for {}: {}", field, source))]
+    Render {
+        field: String,
+        source: TemplateRenderingError,
+    },
+    #[snafu(display("Rendered {} {:?} is invalid", field, value))]
+    Invalid { field: String, value: String },
+    #[snafu(display("Rendered uri {:?} has credentials along with `auth`", uri))]
+    ConflictingAuth { uri: String },
+}
+
+impl RequestTemplates {
+    fn is_empty(&self) -> bool {
+        self.uri.is_none() && self.method.is_none() && self.headers.is_empty()
+    }
+
+    fn render(&self, event: &Event, auth: &Option<Auth>) -> Result<Target, TargetError> {
+        let render = |template: &Template, field: &str| {
+            template.render_string(event).context(Render { field })
+        };
+        let invalid = |field: &str, value: String| TargetError::Invalid {
+            field: field.to_owned(),
+            value,
         };
-        let uri: Uri = self.uri.uri.clone();
 
-        let ct = match self.encoding.codec() {
-            Encoding::Text => "text/plain",
-            Encoding::Ndjson => "application/x-ndjson",
-            Encoding::Json => {
-                body.insert(0, b'[');
-                body.pop(); // remove trailing comma from last record
-                body.push(b']');
-                "application/json"
+        let (uri, uri_auth) = match &self.uri {
+            Some(template) => {
+                let rendered = render(template, "uri")?;
+                let uri = rendered
+                    .parse::<UriSerde>()
+                    .map_err(|_| invalid("uri", rendered.clone()))?
+                    .with_default_parts();
+                if uri.auth.is_some() && auth.is_some() {
+                    return Err(TargetError::ConflictingAuth { uri: rendered });
+                }
+                (Some(uri.uri), uri.auth)
+            }
+            None => (None, None),
+        };
+        let method = match &self.method {
+            Some(template) => {
+                let rendered = render(template, "method")?;
+                let method = Method::from_bytes(rendered.to_uppercase().as_bytes())
+                    .map_err(|_| invalid("method", rendered))?;
+                Some(method)
             }
+            None => None,
         };
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, template)| {
+                let field = format!("request.headers.{}", name);
+                let rendered = render(template, &field)?;
+                let value =
+                    HeaderValue::from_str(&rendered).map_err(|_| invalid(&field, rendered))?;
+                Ok((name.clone(), value))
+            })
+            .collect::<Result<_, _>>()?;
 
-        let mut builder = Request::builder()
-            .method(method)
-            .uri(uri)
-            .header("Content-Type", ct);
+        Ok(Target::Rendered {
+            uri,
+            auth: uri_auth,
+            method,
+            headers,
+        })
+    }
+}
 
-        match self.compression {
-            Compression::Gzip(level) => {
-                builder = builder.header("Content-Encoding", "gzip");
+/// Where the requests of a batch are sent.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Target {
+    /// The parts of the request rendered from the events of the batch, the
+    /// others being the configured ones.
+    Rendered {
+        uri: Option<Uri>,
+        auth: Option<Auth>,
+        method: Option<Method>,
+        headers: Vec<(HeaderName, HeaderValue)>,
+    },
+    /// Events whose templates failed to render, sent to `dead_letter_uri`.
+    DeadLetter,
+}
 
-                let level = level.unwrap_or(GZIP_DEFAULT) as u32;
-                let mut w = GzEncoder::new(Vec::new(), flate2::Compression::new(level));
-                w.write_all(&body).expect("Writing to Vec can't fail");
-                body = w.finish().expect("Writing to Vec can't fail");
-            }
-            Compression::None => {}
+impl Default for Target {
+    fn default() -> Self {
+        Self::Rendered {
+            uri: None,
+            auth: None,
+            method: None,
+            headers: Vec::new(),
         }
+    }
+}
 
-        for (header, value) in self.request.headers.iter() {
-            builder = builder.header(header.as_str(), value.as_str());
+type TargetBatch = PartitionInnerBuffer<Vec<u8>, Target>;
+
+type TargetSvc = tower::buffer::Buffer<
+    Svc<
+        HttpBatchService<future::Ready<crate::Result<http::Request<Vec<u8>>>>, TargetBatch>,
+        HttpRetryLogic,
+    >,
+    TargetBatch,
+>;
+
+/// Sends the batches of each target through their own copy of the request
+/// settings, so that the concurrency and rate limits apply to each target
+/// and a target that is slow doesn't hold back the others.
+struct TargetPartitionSvc {
+    config: Arc<HttpSinkConfig>,
+    client: HttpClient,
+    request_settings: TowerRequestSettings,
+    targets: HashMap<Target, TargetSvc>,
+}
+
+impl TargetPartitionSvc {
+    fn new(
+        config: Arc<HttpSinkConfig>,
+        client: HttpClient,
+        request_settings: TowerRequestSettings,
+    ) -> Self {
+        Self {
+            config,
+            client,
+            request_settings,
+            targets: HashMap::new(),
         }
+    }
+}
 
-        let mut request = builder.body(body).unwrap();
+impl Service<TargetBatch> for TargetPartitionSvc {
+    type Response = http::Response<Bytes>;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
-        if let Some(auth) = &self.auth {
-            auth.apply(&mut request);
-        }
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
 
-        Ok(request)
+    fn call(&mut self, batch: TargetBatch) -> Self::Future {
+        let target = batch.partition();
+        let svc = if let Some(svc) = self.targets.get(&target) {
+            svc.clone()
+        } else {
+            let config = Arc::clone(&self.config);
+            let http = HttpBatchService::new(self.client.clone(), move |batch: TargetBatch| {
+                let (body, target) = batch.into_parts();
+                future::ready(config.build_request_to(body, &target))
+            });
+            // Buffer size is the concurrency because this service is always ready.
+            let svc = ServiceBuilder::new()
+                .buffer(
+                    self.request_settings
+                        .concurrency
+                        .unwrap_or(MAX_IN_FLIGHT_BATCHES),
+                )
+                .service(self.request_settings.service(HttpRetryLogic, http));
+
+            self.targets.insert(target, svc.clone());
+            svc
+        };
+
+        svc.oneshot(batch).map_err(Into::into).boxed()
     }
 }
 
@@ -295,7 +715,11 @@ fn validate_headers(map: &IndexMap<String, String>, auth: &Option<Auth>) -> crat
         }
 
         HeaderName::from_bytes(name.as_bytes()).with_context(|| InvalidHeaderName { name })?;
-        HeaderValue::from_bytes(value.as_bytes()).with_context(|| InvalidHeaderValue { value })?;
+        // Templated values are validated once rendered.
+        if !is_template(value) {
+            HeaderValue::from_bytes(value.as_bytes())
+                .with_context(|| InvalidHeaderValue { value })?;
+        }
     }
 
     Ok(())
@@ -307,6 +731,7 @@ mod tests {
     use crate::{
         assert_downcast_matches,
         config::SinkContext,
+        event::LogEvent,
         sinks::{
             http::HttpSinkConfig,
             util::{
@@ -396,6 +821,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn http_parses_templated_options() {
+        let config: HttpSinkConfig = toml::from_str(
+            r#"
+        uri = "http://example.com/{{ tenant }}/logs"
+        method = "{{ method }}"
+        encoding = "ndjson"
+        [request.headers]
+        X-Tenant = "{{ tenant }}"
+        X-Static = "100%"
+        "#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.uri, Templated::Template(_)));
+        assert!(matches!(config.method, Some(Templated::Template(_))));
+        assert!(super::validate_headers(&config.request.headers, &None).is_ok());
+        let templates = config.request_templates().unwrap();
+        assert_eq!(templates.headers.len(), 1);
+        assert_eq!(templates.headers[0].0, "x-tenant");
+
+        let config: HttpSinkConfig = toml::from_str(
+            r#"
+        uri = "http://example.com/a%20b"
+        method = "put"
+        encoding = "ndjson"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.method, Some(HttpMethod::Put.into()));
+        assert!(config.request_templates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn http_renders_targets() {
+        let config: HttpSinkConfig = toml::from_str(
+            r#"
+        uri = "http://{{ host }}/logs"
+        method = "{{ method }}"
+        encoding = "ndjson"
+        [request.headers]
+        X-Tenant = "{{ tenant }}"
+        "#,
+        )
+        .unwrap();
+        let templates = config.request_templates().unwrap();
+
+        let mut event = Event::from("hello");
+        event
+            .as_mut_log()
+            .insert("host", "user:pass@example.com:8080");
+        event.as_mut_log().insert("method", "patch");
+        event.as_mut_log().insert("tenant", "acme");
+        match templates.render(&event, &None).unwrap() {
+            Target::Rendered {
+                uri,
+                auth,
+                method,
+                headers,
+            } => {
+                assert_eq!(uri.unwrap(), "http://example.com:8080/logs");
+                assert_eq!(
+                    auth,
+                    Some(Auth::Basic {
+                        user: "user".into(),
+                        password: "pass".into(),
+                    })
+                );
+                assert_eq!(method, Some(Method::PATCH));
+                assert_eq!(headers[0].1, "acme");
+            }
+            Target::DeadLetter => unreachable!(),
+        }
+
+        let auth = Some(Auth::Bearer {
+            token: "token".into(),
+        });
+        assert!(matches!(
+            templates.render(&event, &auth),
+            Err(TargetError::ConflictingAuth { .. })
+        ));
+
+        event.as_mut_log().insert("tenant", "ac\nme");
+        assert!(matches!(
+            templates.render(&event, &None),
+            Err(TargetError::Invalid { .. })
+        ));
+
+        event.as_mut_log().remove("method");
+        assert!(matches!(
+            templates.render(&event, &None),
+            Err(TargetError::Render { .. })
+        ));
+    }
+
     // TODO: Fix failure on GH Actions using macos-latest image.
     #[cfg(not(target_os = "macos"))]
     #[tokio::test]
@@ -643,6 +1163,62 @@ mod tests {
         assert_eq!(input_lines, output_lines);
     }
 
+    #[tokio::test]
+    async fn http_sends_to_templated_targets() {
+        let in_addr = next_addr();
+        let config = format!(
+            r#"
+                uri = "http://{addr}/{{{{ tenant }}}}"
+                encoding = "ndjson"
+                dead_letter_uri = "http://{addr}/dead"
+                [request.headers]
+                X-Tenant = "{{{{ tenant }}}}"
+            "#,
+            addr = in_addr,
+        );
+        let config: HttpSinkConfig = toml::from_str(&config).unwrap();
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (rx, trigger, server) = build_test_server(in_addr);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let events = (0..30)
+            .map(|i| {
+                let mut log = LogEvent::from(format!("line {}", i));
+                if i % 3 != 2 {
+                    log.insert("tenant", if i % 3 == 0 { "acme" } else { "globex" });
+                }
+                Event::from(log.with_batch_notifier(&batch))
+            })
+            .collect::<Vec<_>>();
+        drop(batch);
+
+        tokio::spawn(server);
+        sink.run(stream::iter(events)).await.unwrap();
+        drop(trigger);
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+
+        let mut lines = std::collections::HashMap::<String, usize>::new();
+        rx.for_each(|(parts, body)| {
+            let path = parts.uri.path().to_owned();
+            let tenant = parts
+                .headers
+                .get("X-Tenant")
+                .map(|value| value.to_str().unwrap().to_owned());
+            match path.as_str() {
+                "/dead" => assert_eq!(tenant, None),
+                path => assert_eq!(tenant.as_deref(), Some(&path[1..])),
+            }
+            *lines.entry(path).or_default() += BufReader::new(body.reader()).lines().count();
+            future::ready(())
+        })
+        .await;
+
+        assert_eq!(lines["/acme"], 10);
+        assert_eq!(lines["/globex"], 10);
+        assert_eq!(lines["/dead"], 10);
+    }
+
     async fn get_received(
         rx: mpsc::Receiver<(Parts, Bytes)>,
         assert_parts: impl Fn(Parts),
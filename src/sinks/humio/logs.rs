@@ -92,6 +92,7 @@ impl HumioLogsConfig {
             batch: self.batch,
             request: self.request,
             tls: self.tls.clone(),
+            acknowledgements: Default::default(),
         }
     }
 }
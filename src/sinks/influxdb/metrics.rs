@@ -198,7 +198,7 @@ fn create_build_request(
     }
 }
 
-fn merge_tags(
+pub(super) fn merge_tags(
     event: &Metric,
     tags: Option<&HashMap<String, String>>,
 ) -> Option<BTreeMap<String, String>> {
@@ -266,7 +266,7 @@ fn encode_events(
     output
 }
 
-fn get_type_and_fields(
+pub(super) fn get_type_and_fields(
     value: &MetricValue,
     quantiles: &[f64],
 ) -> (&'static str, Option<HashMap<String, Field>>) {
@@ -320,13 +320,20 @@ fn get_type_and_fields(
             let fields = encode_distribution(samples, quantiles);
             ("distribution", fields)
         }
+        MetricValue::Sketch { sketch } => {
+            let fields =
+                DistributionStatistic::from_sketch(sketch, quantiles).map(encode_statistic);
+            ("distribution", fields)
+        }
     }
 }
 
 fn encode_distribution(samples: &[Sample], quantiles: &[f64]) -> Option<HashMap<String, Field>> {
-    let statistic = DistributionStatistic::from_samples(samples, quantiles)?;
+    DistributionStatistic::from_samples(samples, quantiles).map(encode_statistic)
+}
 
-    let fields: HashMap<String, Field> = vec![
+fn encode_statistic(statistic: DistributionStatistic) -> HashMap<String, Field> {
+    vec![
         ("min".to_owned(), Field::Float(statistic.min)),
         ("max".to_owned(), Field::Float(statistic.max)),
         ("median".to_owned(), Field::Float(statistic.median)),
@@ -341,9 +348,7 @@ fn encode_distribution(samples: &[Sample], quantiles: &[f64]) -> Option<HashMap<
             .iter()
             .map(|&(p, val)| (format!("quantile_{:.2}", p), Field::Float(val))),
     )
-    .collect();
-
-    Some(fields)
+    .collect()
 }
 
 fn to_fields(value: f64) -> HashMap<String, Field> {
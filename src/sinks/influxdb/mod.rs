@@ -1,5 +1,6 @@
 pub mod logs;
 pub mod metrics;
+pub mod tcp;
 
 use crate::http::HttpClient;
 use chrono::{DateTime, Utc};
@@ -22,6 +23,8 @@ pub(in crate::sinks) enum Field {
     Int(i64),
     /// boolean
     Bool(bool),
+    /// timestamp in microseconds, only understood by QuestDB
+    Timestamp(i64),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -254,6 +257,10 @@ fn encode_fields(
             Field::Bool(b) => {
                 output.push_str(&b.to_string());
             }
+            Field::Timestamp(micros) => {
+                output.push_str(&micros.to_string());
+                output.push('t');
+            }
         };
         output.push(',');
     }
@@ -0,0 +1,473 @@
+//! Writes events in the InfluxDB line protocol over TCP, as accepted by
+//! QuestDB and by the socket listeners of InfluxDB and Telegraf. Unlike the
+//! HTTP sinks, lines are streamed over a long lived connection, which is
+//! faster but gets no response for the lines the server rejects.
+
+use crate::{
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, LogEvent, Metric, Value},
+    internal_events::{InfluxDbTcpLateEvent, TemplateRenderingFailed},
+    sinks::{
+        influxdb::{
+            encode_fields, encode_string, encode_tags, encode_timestamp,
+            metrics::{default_summary_quantiles, get_type_and_fields, merge_tags},
+            Field, ProtocolVersion,
+        },
+        util::{
+            encode_namespace,
+            encoding::{EncodingConfigWithDefault, EncodingConfiguration},
+            statistic::validate_quantiles,
+            tcp::TcpSinkConfig,
+        },
+        Healthcheck, VectorSink,
+    },
+    tcp::TcpKeepaliveConfig,
+    template::Template,
+    tls::TlsConfig,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    sync::Mutex,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InfluxDbTcpConfig {
+    pub address: String,
+    /// The measurement log events are written to, the table in QuestDB.
+    pub measurement: Option<String>,
+    #[serde(alias = "namespace")]
+    pub default_namespace: Option<String>,
+    /// The fields of log events written as tags, symbols in QuestDB.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The types the fields of log events are written as.
+    #[serde(default)]
+    pub column_types: HashMap<String, ColumnType>,
+    pub out_of_order: Option<OutOfOrderConfig>,
+    #[serde(default = "default_summary_quantiles")]
+    pub quantiles: Vec<f64>,
+    #[serde(
+        skip_serializing_if = "crate::serde::skip_serializing_if_default",
+        default
+    )]
+    pub encoding: EncodingConfigWithDefault<Encoding>,
+    pub keepalive: Option<TcpKeepaliveConfig>,
+    pub tls: Option<TlsConfig>,
+    pub send_buffer_bytes: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Symbol,
+    String,
+    Long,
+    Double,
+    Boolean,
+    Timestamp,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OutOfOrderConfig {
+    /// How far an event can be behind the latest event of its measurement
+    /// before it's late.
+    pub max_lateness_secs: u64,
+    #[serde(default)]
+    pub late_events: LateEvents,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LateEvents {
+    #[derivative(Default)]
+    Send,
+    Drop,
+    ServerTimestamp,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Default,
+}
+
+inventory::submit! {
+    SinkDescription::new::<InfluxDbTcpConfig>("influxdb_tcp")
+}
+
+impl GenerateConfig for InfluxDbTcpConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "localhost:9009"
+            measurement = "logs""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "influxdb_tcp")]
+impl SinkConfig for InfluxDbTcpConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        validate_quantiles(&self.quantiles)?;
+        let encoder = Encoder::new(self)?;
+
+        let sink_config = TcpSinkConfig::new(
+            self.address.clone(),
+            self.keepalive,
+            self.tls.clone(),
+            self.send_buffer_bytes,
+        );
+        sink_config.build(cx, move |event| encoder.encode(event))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "influxdb_tcp"
+    }
+}
+
+/// Tracks the latest timestamp of each measurement, to find the events
+/// arriving late.
+struct Lateness {
+    max_lateness: i64,
+    late_events: LateEvents,
+    latest: Mutex<HashMap<String, i64>>,
+}
+
+impl Lateness {
+    /// Returns the timestamp the line is written with, if it's written.
+    fn check(&self, measurement: &str, timestamp: i64) -> Option<Option<i64>> {
+        let mut latest = self.latest.lock().expect("poisoned lock");
+        let lateness = match latest.get_mut(measurement) {
+            Some(latest) if timestamp < *latest => *latest - timestamp,
+            Some(latest) => {
+                *latest = timestamp;
+                return Some(Some(timestamp));
+            }
+            None => {
+                latest.insert(measurement.to_owned(), timestamp);
+                return Some(Some(timestamp));
+            }
+        };
+        if lateness <= self.max_lateness || self.late_events == LateEvents::Send {
+            return Some(Some(timestamp));
+        }
+        emit!(InfluxDbTcpLateEvent {
+            measurement,
+            lateness_ns: lateness,
+            dropped: self.late_events == LateEvents::Drop,
+        });
+        match self.late_events {
+            LateEvents::Drop => None,
+            _ => Some(None),
+        }
+    }
+}
+
+struct Encoder {
+    measurement: Template,
+    default_namespace: Option<String>,
+    tags: HashSet<String>,
+    column_types: HashMap<String, ColumnType>,
+    lateness: Option<Lateness>,
+    quantiles: Vec<f64>,
+    encoding: EncodingConfigWithDefault<Encoding>,
+}
+
+impl Encoder {
+    fn new(config: &InfluxDbTcpConfig) -> crate::Result<Self> {
+        let measurement = Template::try_from(config.measurement.as_deref().unwrap_or("vector"))?;
+        let lateness = config.out_of_order.as_ref().map(|out_of_order| Lateness {
+            max_lateness: (out_of_order.max_lateness_secs as i64).saturating_mul(1_000_000_000),
+            late_events: out_of_order.late_events,
+            latest: Mutex::new(HashMap::new()),
+        });
+        Ok(Self {
+            measurement,
+            default_namespace: config.default_namespace.clone(),
+            tags: config.tags.iter().cloned().collect(),
+            column_types: config.column_types.clone(),
+            lateness,
+            quantiles: config.quantiles.clone(),
+            encoding: config.encoding.clone(),
+        })
+    }
+
+    fn encode(&self, mut event: Event) -> Option<Bytes> {
+        self.encoding.apply_rules(&mut event);
+        let (measurement, tags, fields, timestamp) = match event {
+            Event::Log(log) => self.log_line(log)?,
+            Event::Metric(metric) => self.metric_line(metric),
+            Event::Trace(_) => return None,
+        };
+
+        let timestamp = match &self.lateness {
+            Some(lateness) => lateness.check(&measurement, timestamp)?,
+            None => Some(timestamp),
+        };
+
+        let mut output = String::new();
+        if let Err(error) = encode_line(measurement, tags, fields, timestamp, &mut output) {
+            warn!(message = "Failed to encode event; dropping event.", %error, internal_log_rate_secs = 30);
+            return None;
+        }
+        Some(output.into())
+    }
+
+    fn log_line(&self, mut log: LogEvent) -> Option<Line> {
+        let measurement = self
+            .measurement
+            .render_string(&log)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some("measurement"),
+                    drop_event: true,
+                })
+            })
+            .ok()?;
+        let timestamp = encode_timestamp(match log.remove(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(ts)) => Some(ts),
+            _ => None,
+        });
+
+        let mut tags = BTreeMap::new();
+        let mut fields = HashMap::new();
+        for (key, value) in log.all_fields() {
+            let column_type = self.column_types.get(&key).copied();
+            if self.tags.contains(&key) || column_type == Some(ColumnType::Symbol) {
+                tags.insert(key, to_string(value));
+            } else if let Some(field) = to_field(value, column_type) {
+                fields.insert(key, field);
+            }
+        }
+        Some((measurement, tags, fields, timestamp))
+    }
+
+    fn metric_line(&self, metric: Metric) -> Line {
+        let measurement = encode_namespace(
+            metric
+                .namespace()
+                .or_else(|| self.default_namespace.as_deref()),
+            '.',
+            metric.name(),
+        );
+        let timestamp = encode_timestamp(metric.timestamp());
+        let mut tags = merge_tags(&metric, None).unwrap_or_default();
+        let (metric_type, fields) = get_type_and_fields(metric.value(), &self.quantiles);
+        tags.insert("metric_type".to_owned(), metric_type.to_owned());
+        (measurement, tags, fields.unwrap_or_default(), timestamp)
+    }
+}
+
+type Line = (
+    String,
+    BTreeMap<String, String>,
+    HashMap<String, Field>,
+    i64,
+);
+
+/// Encodes a line, leaving the timestamp to the server when it's missing.
+fn encode_line(
+    measurement: String,
+    tags: BTreeMap<String, String>,
+    fields: HashMap<String, Field>,
+    timestamp: Option<i64>,
+    output: &mut String,
+) -> Result<(), &'static str> {
+    if fields.is_empty() {
+        return Err("fields must not be empty");
+    }
+
+    encode_string(measurement, output);
+    // Removed along with the last tag separator when there are no tags.
+    output.push(',');
+    encode_tags(tags, output);
+    output.push(' ');
+    // Integers are written with the suffix of InfluxDB v1, which QuestDB
+    // accepts, as it has no unsigned integers.
+    encode_fields(ProtocolVersion::V1, fields, output);
+    if let Some(timestamp) = timestamp {
+        output.push(' ');
+        output.push_str(&timestamp.to_string());
+    }
+    output.push('\n');
+    Ok(())
+}
+
+fn to_string(value: &Value) -> String {
+    match value {
+        Value::Timestamp(timestamp) => timestamp.to_rfc3339(),
+        value => value.to_string_lossy(),
+    }
+}
+
+/// Converts the value to the type of its column, if it can be.
+fn to_field(value: &Value, column_type: Option<ColumnType>) -> Option<Field> {
+    match (column_type, value) {
+        (None, Value::Integer(value)) => Some(Field::Int(*value)),
+        (None, Value::Float(value)) => Some(Field::Float(*value)),
+        (None, Value::Boolean(value)) => Some(Field::Bool(*value)),
+        (None, value) | (Some(ColumnType::String), value) => Some(Field::String(to_string(value))),
+        (Some(ColumnType::Long), Value::Integer(value)) => Some(Field::Int(*value)),
+        (Some(ColumnType::Long), Value::Float(value)) => Some(Field::Int(*value as i64)),
+        (Some(ColumnType::Long), value) => value.parse_text().map(Field::Int),
+        (Some(ColumnType::Double), Value::Float(value)) => Some(Field::Float(*value)),
+        (Some(ColumnType::Double), Value::Integer(value)) => Some(Field::Float(*value as f64)),
+        (Some(ColumnType::Double), value) => value.parse_text().map(Field::Float),
+        (Some(ColumnType::Boolean), Value::Boolean(value)) => Some(Field::Bool(*value)),
+        (Some(ColumnType::Boolean), value) => value.parse_text().map(Field::Bool),
+        (Some(ColumnType::Timestamp), Value::Timestamp(timestamp)) => {
+            Some(Field::Timestamp(timestamp.timestamp_nanos() / 1000))
+        }
+        (Some(ColumnType::Timestamp), value) => value
+            .parse_timestamp()
+            .map(|timestamp| Field::Timestamp(timestamp.timestamp_nanos() / 1000)),
+        (Some(ColumnType::Symbol), _) => unreachable!("symbols are written as tags"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::metric::{MetricKind, MetricValue},
+        sinks::influxdb::test_util::{assert_fields, split_line_protocol, ts},
+    };
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<InfluxDbTcpConfig>();
+    }
+
+    fn encoder(extra: &str) -> Encoder {
+        let config: InfluxDbTcpConfig = toml::from_str(&format!(
+            r#"
+            address = "localhost:9009"
+            measurement = "logs_{{{{ app }}}}"
+            tags = ["host"]
+            {}
+            "#,
+            extra
+        ))
+        .unwrap();
+        Encoder::new(&config).unwrap()
+    }
+
+    fn line(encoder: &Encoder, event: impl Into<Event>) -> Option<String> {
+        encoder
+            .encode(event.into())
+            .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    fn log(timestamp: DateTime<Utc>) -> LogEvent {
+        let mut log = LogEvent::from("hello");
+        log.insert("app", "web");
+        log.insert("host", "a");
+        log.insert("status", "200");
+        log.insert("level", "info");
+        log.insert("started", "2021-09-01T12:00:00Z");
+        log.insert(log_schema().timestamp_key(), timestamp);
+        log
+    }
+
+    #[test]
+    fn encodes_logs_with_column_types() {
+        let encoder = encoder(
+            r#"
+            column_types.status = "long"
+            column_types.level = "symbol"
+            column_types.started = "timestamp"
+            "#,
+        );
+        let line = line(&encoder, log(ts())).unwrap();
+        let (measurement, tags, fields, timestamp) = split_line_protocol(line.trim_end());
+
+        assert_eq!(measurement, "logs_web");
+        assert_eq!(tags, "host=a,level=info");
+        assert_fields(
+            fields,
+            vec![
+                "app=\"web\"",
+                "message=\"hello\"",
+                "status=200i",
+                "started=1630497600000000t",
+            ],
+        );
+        assert_eq!(timestamp, ts().timestamp_nanos().to_string());
+    }
+
+    #[test]
+    fn encodes_metrics() {
+        let metric = Metric::new(
+            "requests",
+            MetricKind::Incremental,
+            MetricValue::Counter { value: 1.5 },
+        )
+        .with_namespace(Some("app"))
+        .with_timestamp(Some(ts()));
+        let line = line(&encoder(""), metric).unwrap();
+
+        assert_eq!(
+            line,
+            format!(
+                "app.requests,metric_type=counter value=1.5 {}\n",
+                ts().timestamp_nanos()
+            )
+        );
+    }
+
+    #[test]
+    fn handles_late_events() {
+        let late = |late_events: &str| {
+            let encoder = encoder(&format!(
+                r#"
+                out_of_order.max_lateness_secs = 60
+                out_of_order.late_events = "{}"
+                "#,
+                late_events
+            ));
+            let timestamp = |line: Option<String>| {
+                let line = line.unwrap();
+                line.trim_end().splitn(3, ' ').nth(2).map(str::to_owned)
+            };
+            let seconds_ago = |secs| log(ts() - chrono::Duration::seconds(secs));
+
+            assert!(timestamp(line(&encoder, log(ts()))).is_some());
+            // Events within the tolerance are written as they are.
+            assert_eq!(
+                timestamp(line(&encoder, seconds_ago(30))),
+                Some(
+                    (ts() - chrono::Duration::seconds(30))
+                        .timestamp_nanos()
+                        .to_string()
+                )
+            );
+            line(&encoder, seconds_ago(90)).map(|line| timestamp(Some(line)))
+        };
+
+        assert_eq!(
+            late("send"),
+            Some(Some(
+                (ts() - chrono::Duration::seconds(90))
+                    .timestamp_nanos()
+                    .to_string()
+            ))
+        );
+        assert_eq!(late("drop"), None);
+        assert_eq!(late("server_timestamp"), Some(None));
+    }
+}
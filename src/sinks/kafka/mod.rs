@@ -1,8 +1,14 @@
+mod schema_registry;
+mod transaction;
+
+pub use self::schema_registry::{SchemaRegistryConfig, SchemaType, SubjectNameStrategy};
+use self::{schema_registry::ValueSerializer, transaction::TransactionalKafkaSink};
 use crate::{
     buffers::Acker,
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    internal_events::TemplateRenderingFailed,
+    internal_events::{KafkaSchemaSerializationFailed, TemplateRenderingFailed},
     kafka::{KafkaAuthConfig, KafkaCompression, KafkaStatisticsContext},
+    protobuf::ProtobufOptions,
     serde::to_string,
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
@@ -17,6 +23,7 @@ use futures::{
 use rdkafka::{
     consumer::{BaseConsumer, Consumer},
     error::{KafkaError, RDKafkaErrorCode},
+    message::{Header, OwnedHeaders},
     producer::{DeliveryFuture, FutureProducer, FutureRecord},
     ClientConfig,
 };
@@ -30,7 +37,7 @@ use std::{
     task::{Context, Poll},
 };
 use tokio::time::{sleep, Duration};
-use vector_core::event::{Event, EventMetadata, EventStatus};
+use vector_core::event::{trace, Event, EventMetadata, EventStatus, Value};
 
 // Maximum number of futures blocked by [send_result](https://docs.rs/rdkafka/0.24.0/rdkafka/producer/future_producer/struct.FutureProducer.html#method.send_result)
 const SEND_RESULT_LIMIT: usize = 5;
@@ -41,6 +48,14 @@ enum BuildError {
     KafkaCreateFailed { source: KafkaError },
     #[snafu(display("invalid topic template: {}", source))]
     TopicTemplate { source: TemplateParseError },
+    #[snafu(display(
+        "`transaction.id` sets `librdkafka_options.transactional.id`, which the config already sets. Please delete one."
+    ))]
+    TransactionalIdSetTwice,
+    #[snafu(display("`encoding.codec = \"protobuf\"` requires `protobuf`"))]
+    MissingProtobufOptions,
+    #[snafu(display("`encoding.codec` can't be `protobuf` along with `schema_registry`"))]
+    ProtobufWithSchemaRegistry,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -48,7 +63,15 @@ pub struct KafkaSinkConfig {
     bootstrap_servers: String,
     topic: String,
     key_field: Option<String>,
+    /// A map of log events whose fields are sent as the headers of their
+    /// message, as the `kafka` source reads headers into.
+    headers_key: Option<String>,
     encoding: EncodingConfig<Encoding>,
+    /// The message type of the `protobuf` codec.
+    protobuf: Option<ProtobufOptions>,
+    /// Serializes the values of messages with a schema of a Confluent Schema
+    /// Registry, rather than with `encoding.codec`.
+    schema_registry: Option<SchemaRegistryConfig>,
     /// These batching options will **not** override librdkafka_options values.
     #[serde(default)]
     batch: BatchConfig,
@@ -62,6 +85,39 @@ pub struct KafkaSinkConfig {
     message_timeout_ms: u64,
     #[serde(default)]
     librdkafka_options: HashMap<String, String>,
+    /// Enables the idempotent producer, which retries without duplicating
+    /// or reordering messages.
+    #[serde(default)]
+    idempotence: bool,
+    /// Produces the events in transactions, committed before they are
+    /// acknowledged. Implies `idempotence`.
+    transaction: Option<KafkaTransactionConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaTransactionConfig {
+    /// The `transactional.id` of the producer, which must be the same
+    /// across restarts and unique to it.
+    id: String,
+    #[serde(default = "default_transaction_timeout_ms")]
+    timeout_ms: u64,
+    #[serde(default = "default_commit_interval_ms")]
+    commit_interval_ms: u64,
+    #[serde(default = "default_transaction_max_events")]
+    max_events: usize,
+}
+
+fn default_transaction_timeout_ms() -> u64 {
+    60000 // default in librdkafka
+}
+
+fn default_commit_interval_ms() -> u64 {
+    1000
+}
+
+fn default_transaction_max_events() -> usize {
+    10000
 }
 
 fn default_socket_timeout_ms() -> u64 {
@@ -77,13 +133,16 @@ fn default_message_timeout_ms() -> u64 {
 pub enum Encoding {
     Text,
     Json,
+    Protobuf,
 }
 
 pub struct KafkaSink {
     producer: Arc<FutureProducer<KafkaStatisticsContext>>,
     topic: Template,
     key_field: Option<String>,
+    headers_key: Option<String>,
     encoding: EncodingConfig<Encoding>,
+    value_serializer: Option<Arc<ValueSerializer>>,
     delivery_fut: FuturesUnordered<
         BoxFuture<'static, (usize, Result<DeliveryFuture, KafkaError>, EventMetadata)>,
     >,
@@ -127,9 +186,35 @@ impl SinkConfig for KafkaSinkConfig {
         &self,
         cx: SinkContext,
     ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
-        let sink = KafkaSink::new(self.clone(), cx.acker())?;
         let hc = healthcheck(self.clone()).boxed();
-        Ok((super::VectorSink::Sink(Box::new(sink)), hc))
+        let value_serializer = match (&self.schema_registry, self.encoding.codec()) {
+            (Some(_), Encoding::Protobuf) => {
+                return Err(BuildError::ProtobufWithSchemaRegistry.into())
+            }
+            (Some(schema_registry), _) => Some(Arc::new(
+                schema_registry.build(&self.topic, cx.proxy()).await?,
+            )),
+            (None, Encoding::Protobuf) => {
+                let options = self
+                    .protobuf
+                    .as_ref()
+                    .ok_or(BuildError::MissingProtobufOptions)?;
+                Some(Arc::new(ValueSerializer::protobuf(options.encoder()?)))
+            }
+            (None, _) => None,
+        };
+        let sink = match &self.transaction {
+            Some(transaction) => {
+                let sink =
+                    TransactionalKafkaSink::new(self, transaction, value_serializer, cx.acker())?;
+                super::VectorSink::Stream(Box::new(sink))
+            }
+            None => {
+                let sink = KafkaSink::new(self.clone(), value_serializer, cx.acker())?;
+                super::VectorSink::Sink(Box::new(sink))
+            }
+        };
+        Ok((sink, hc))
     }
 
     fn input_type(&self) -> DataType {
@@ -150,6 +235,36 @@ enum KafkaRole {
 }
 
 impl KafkaSinkConfig {
+    /// A producer to the given topic with the defaults of every other option,
+    /// for the sinks built on top of this one.
+    #[cfg(feature = "sinks-azure_event_hubs")]
+    pub(super) fn new(
+        bootstrap_servers: String,
+        topic: String,
+        key_field: Option<String>,
+        encoding: EncodingConfig<Encoding>,
+        batch: BatchConfig,
+        auth: KafkaAuthConfig,
+    ) -> Self {
+        Self {
+            bootstrap_servers,
+            topic,
+            key_field,
+            headers_key: None,
+            encoding,
+            protobuf: None,
+            schema_registry: None,
+            batch,
+            compression: KafkaCompression::default(),
+            auth,
+            socket_timeout_ms: default_socket_timeout_ms(),
+            message_timeout_ms: default_message_timeout_ms(),
+            librdkafka_options: HashMap::new(),
+            idempotence: false,
+            transaction: None,
+        }
+    }
+
     fn to_rdkafka(&self, kafka_role: KafkaRole) -> crate::Result<ClientConfig> {
         let mut client_config = ClientConfig::new();
         client_config
@@ -222,6 +337,25 @@ impl KafkaSinkConfig {
                 );
                 client_config.set(key, &value.to_string());
             }
+
+            if self.idempotence || self.transaction.is_some() {
+                client_config.set("enable.idempotence", "true");
+            }
+            if let Some(transaction) = &self.transaction {
+                if self.librdkafka_options.contains_key("transactional.id") {
+                    return Err(BuildError::TransactionalIdSetTwice.into());
+                }
+                // librdkafka requires messages to time out before the
+                // transactions they are produced in.
+                let message_timeout_ms = self.message_timeout_ms.min(transaction.timeout_ms);
+                client_config
+                    .set("transactional.id", &transaction.id)
+                    .set(
+                        "transaction.timeout.ms",
+                        &transaction.timeout_ms.to_string(),
+                    )
+                    .set("message.timeout.ms", &message_timeout_ms.to_string());
+            }
         }
 
         for (key, value) in self.librdkafka_options.iter() {
@@ -234,16 +368,22 @@ impl KafkaSinkConfig {
 }
 
 impl KafkaSink {
-    fn new(config: KafkaSinkConfig, acker: Acker) -> crate::Result<Self> {
+    fn new(
+        config: KafkaSinkConfig,
+        value_serializer: Option<Arc<ValueSerializer>>,
+        acker: Acker,
+    ) -> crate::Result<Self> {
         let producer_config = config.to_rdkafka(KafkaRole::Producer)?;
         let producer = producer_config
-            .create_with_context(KafkaStatisticsContext)
+            .create_with_context(KafkaStatisticsContext::new(&config.auth)?)
             .context(KafkaCreateFailed)?;
         Ok(KafkaSink {
             producer: Arc::new(producer),
             topic: Template::try_from(config.topic).context(TopicTemplate)?,
             key_field: config.key_field,
+            headers_key: config.headers_key,
             encoding: config.encoding,
+            value_serializer,
             delivery_fut: FuturesUnordered::new(),
             in_flight: FuturesUnordered::new(),
             acker,
@@ -273,6 +413,19 @@ impl KafkaSink {
 
         Poll::Ready(())
     }
+
+    /// Marks the event as done, and acknowledges the events done up to the
+    /// oldest one still in flight, as the buffer is acknowledged in order.
+    fn ack(&mut self, seqno: usize) {
+        self.pending_acks.insert(seqno);
+
+        let mut num_to_ack = 0;
+        while self.pending_acks.remove(&self.seq_tail) {
+            num_to_ack += 1;
+            self.seq_tail += 1
+        }
+        self.acker.ack(num_to_ack);
+    }
 }
 
 impl Sink<Event> for KafkaSink {
@@ -299,19 +452,26 @@ impl Sink<Event> for KafkaSink {
             });
         })?;
 
-        let timestamp_ms = match &item {
-            Event::Log(log) => log
-                .get(log_schema().timestamp_key())
-                .and_then(|v| v.as_timestamp())
-                .copied(),
-            Event::Metric(metric) => metric.timestamp(),
-        }
-        .map(|ts| ts.timestamp_millis());
-        let (key, body, metadata) = encode_event(item, &self.key_field, &self.encoding);
+        let timestamp_ms = timestamp_ms(&item);
+        let headers = headers(&item, self.headers_key.as_deref());
 
         let seqno = self.seq_head;
         self.seq_head += 1;
 
+        let encoded = encode_event(
+            item,
+            &self.key_field,
+            &self.encoding,
+            self.value_serializer.as_deref(),
+        );
+        let (key, body, metadata) = match encoded {
+            Some(encoded) => encoded,
+            None => {
+                self.ack(seqno);
+                return Ok(());
+            }
+        };
+
         let producer = Arc::clone(&self.producer);
         let kf = self.key_field.is_some();
         self.delivery_fut.push(Box::pin(async move {
@@ -323,6 +483,9 @@ impl Sink<Event> for KafkaSink {
             if let Some(timestamp) = timestamp_ms {
                 record = record.timestamp(timestamp);
             }
+            if let Some(headers) = headers {
+                record = record.headers(headers);
+            }
 
             let result = loop {
                 debug!(message = "Sending event.", count = 1);
@@ -366,14 +529,7 @@ impl Sink<Event> for KafkaSink {
                         }
                     }
 
-                    this.pending_acks.insert(seqno);
-
-                    let mut num_to_ack = 0;
-                    while this.pending_acks.remove(&this.seq_tail) {
-                        num_to_ack += 1;
-                        this.seq_tail += 1
-                    }
-                    this.acker.ack(num_to_ack);
+                    this.ack(seqno);
                 }
                 Some((_, Err(Canceled), metadata)) => {
                     error!(message = "Request canceled.");
@@ -409,8 +565,9 @@ async fn healthcheck(config: KafkaSinkConfig) -> crate::Result<()> {
         }
     };
 
+    let context = KafkaStatisticsContext::new(&config.auth)?;
     tokio::task::spawn_blocking(move || {
-        let consumer: BaseConsumer = client.create().unwrap();
+        let consumer: BaseConsumer<_> = client.create_with_context(context).unwrap();
         let topic = topic.as_ref().map(|topic| &topic[..]);
 
         consumer
@@ -422,11 +579,54 @@ async fn healthcheck(config: KafkaSinkConfig) -> crate::Result<()> {
     Ok(())
 }
 
+fn timestamp_ms(event: &Event) -> Option<i64> {
+    match event {
+        Event::Log(log) => log
+            .get(log_schema().timestamp_key())
+            .and_then(|v| v.as_timestamp())
+            .copied(),
+        Event::Metric(metric) => metric.timestamp(),
+        Event::Trace(trace) => trace
+            .get(trace::fields::START_TIME)
+            .and_then(|v| v.as_timestamp())
+            .copied(),
+    }
+    .map(|ts| ts.timestamp_millis())
+}
+
+/// The headers of the message, from the map at `headers_key` of log events.
+fn headers(event: &Event, headers_key: Option<&str>) -> Option<OwnedHeaders> {
+    let map = match event {
+        Event::Log(log) => log.get(headers_key?)?.as_map()?,
+        _ => return None,
+    };
+    let mut headers = OwnedHeaders::new();
+    for (key, value) in map {
+        headers = match value {
+            Value::Null => headers.insert(Header {
+                key,
+                value: None::<&[u8]>,
+            }),
+            value => {
+                let value = value.as_bytes();
+                headers.insert(Header {
+                    key,
+                    value: Some(&value[..]),
+                })
+            }
+        };
+    }
+    Some(headers)
+}
+
+/// Encodes the key and the value of the message. Events that don't fit the
+/// schema of `value_serializer` are dropped.
 fn encode_event(
     mut event: Event,
     key_field: &Option<String>,
     encoding: &EncodingConfig<Encoding>,
-) -> (Vec<u8>, Vec<u8>, EventMetadata) {
+    value_serializer: Option<&ValueSerializer>,
+) -> Option<(Vec<u8>, Vec<u8>, EventMetadata)> {
     let key = key_field
         .as_ref()
         .and_then(|f| match &event {
@@ -435,27 +635,45 @@ fn encode_event(
                 .tags()
                 .and_then(|tags| tags.get(f))
                 .map(|value| value.clone().into_bytes()),
+            Event::Trace(trace) => trace.get(f).map(|value| value.as_bytes().to_vec()),
         })
         .unwrap_or_default();
 
     encoding.apply_rules(&mut event);
 
+    if let Some(serializer) = value_serializer {
+        return match serializer.serialize(&event) {
+            Ok(body) => Some((key, body, event.into_metadata())),
+            Err(error) => {
+                emit!(KafkaSchemaSerializationFailed {
+                    error: &error.to_string()
+                });
+                event.metadata().update_status(EventStatus::Failed);
+                None
+            }
+        };
+    }
+
+    // Protobuf values are serialized by `value_serializer`, which is always
+    // set with the `protobuf` codec.
     let body = match &event {
         Event::Log(log) => match encoding.codec() {
-            Encoding::Json => serde_json::to_vec(&log).unwrap(),
+            Encoding::Json | Encoding::Protobuf => serde_json::to_vec(&log).unwrap(),
             Encoding::Text => log
                 .get(log_schema().message_key())
                 .map(|v| v.as_bytes().to_vec())
                 .unwrap_or_default(),
         },
         Event::Metric(metric) => match encoding.codec() {
-            Encoding::Json => serde_json::to_vec(&metric).unwrap(),
+            Encoding::Json | Encoding::Protobuf => serde_json::to_vec(&metric).unwrap(),
             Encoding::Text => metric.to_string().into_bytes(),
         },
+        // Spans have no message to write as text, so they're always JSON.
+        Event::Trace(trace) => serde_json::to_vec(&trace).unwrap(),
     };
 
     let metadata = event.into_metadata();
-    (key, body, metadata)
+    Some((key, body, metadata))
 }
 
 #[cfg(test)]
@@ -469,6 +687,33 @@ mod tests {
         crate::test_util::test_generate_config::<KafkaSinkConfig>();
     }
 
+    #[test]
+    fn transactions_set_producer_options() {
+        let config: KafkaSinkConfig = toml::from_str(
+            r#"bootstrap_servers = "localhost:9092"
+            topic = "topic-1234"
+            encoding.codec = "json"
+            transaction.id = "vector-0"
+            transaction.timeout_ms = 30000"#,
+        )
+        .unwrap();
+
+        let producer = config.to_rdkafka(KafkaRole::Producer).unwrap();
+        assert_eq!(producer.get("enable.idempotence"), Some("true"));
+        assert_eq!(producer.get("transactional.id"), Some("vector-0"));
+        assert_eq!(producer.get("transaction.timeout.ms"), Some("30000"));
+        assert_eq!(producer.get("message.timeout.ms"), Some("30000"));
+
+        let consumer = config.to_rdkafka(KafkaRole::Consumer).unwrap();
+        assert_eq!(consumer.get("transactional.id"), None);
+
+        let mut config = config;
+        config
+            .librdkafka_options
+            .insert("transactional.id".into(), "vector-1".into());
+        assert!(config.to_rdkafka(KafkaRole::Producer).is_err());
+    }
+
     #[test]
     fn kafka_encode_event_log_text() {
         crate::test_util::trace_init();
@@ -478,7 +723,9 @@ mod tests {
             message.clone().into(),
             &None,
             &EncodingConfig::from(Encoding::Text),
-        );
+            None,
+        )
+        .unwrap();
 
         assert_eq!(&key_bytes[..], key.as_bytes());
         assert_eq!(&bytes[..], message.as_bytes());
@@ -496,7 +743,9 @@ mod tests {
             event,
             &Some("key".into()),
             &EncodingConfig::from(Encoding::Json),
-        );
+            None,
+        )
+        .unwrap();
 
         let map: BTreeMap<String, String> = serde_json::from_slice(&bytes[..]).unwrap();
 
@@ -517,7 +766,9 @@ mod tests {
             metric.clone().into(),
             &None,
             &EncodingConfig::from(Encoding::Text),
-        );
+            None,
+        )
+        .unwrap();
 
         assert_eq!("", String::from_utf8_lossy(&key_bytes));
         assert_eq!(metric.to_string(), String::from_utf8_lossy(&bytes));
@@ -534,7 +785,9 @@ mod tests {
             metric.clone().into(),
             &None,
             &EncodingConfig::from(Encoding::Json),
-        );
+            None,
+        )
+        .unwrap();
 
         assert_eq!("", String::from_utf8_lossy(&key_bytes));
         assert_eq!(
@@ -559,13 +812,39 @@ mod tests {
                 except_fields: Some(vec!["key".into()]),
                 timestamp_format: None,
             },
-        );
+            None,
+        )
+        .unwrap();
 
         let map: BTreeMap<String, String> = serde_json::from_slice(&bytes[..]).unwrap();
 
         assert_eq!(&key[..], b"value");
         assert!(!map.contains_key("key"));
     }
+
+    #[test]
+    fn kafka_headers_from_log_map() {
+        use rdkafka::message::Headers;
+
+        let mut event = Event::from("hello");
+        event.as_mut_log().insert("headers.trace_id", "abc");
+        event.as_mut_log().insert("headers.empty", Value::Null);
+        assert!(headers(&event, None).is_none());
+        assert!(headers(&event, Some("message")).is_none());
+
+        let headers = headers(&event, Some("headers")).unwrap();
+        let headers: Vec<_> = headers
+            .iter()
+            .map(|header| (header.key.to_owned(), header.value.map(<[u8]>::to_vec)))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("empty".to_owned(), None),
+                ("trace_id".to_owned(), Some(b"abc".to_vec())),
+            ]
+        );
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -595,13 +874,18 @@ mod integration_test {
             bootstrap_servers: "localhost:9091".into(),
             topic: topic.clone(),
             key_field: None,
+            headers_key: None,
             encoding: EncodingConfig::from(Encoding::Text),
+            protobuf: None,
+            schema_registry: None,
             batch: BatchConfig::default(),
             compression: KafkaCompression::None,
             auth: KafkaAuthConfig::default(),
             socket_timeout_ms: 60000,
             message_timeout_ms: 300000,
             librdkafka_options: HashMap::new(),
+            idempotence: false,
+            transaction: None,
         };
 
         super::healthcheck(config).await.unwrap();
@@ -647,7 +931,10 @@ mod integration_test {
             topic: format!("{}-%Y%m%d", topic),
             compression: KafkaCompression::None,
             encoding: Encoding::Text.into(),
+            protobuf: None,
+            schema_registry: None,
             key_field: None,
+            headers_key: None,
             auth: KafkaAuthConfig {
                 sasl: None,
                 tls: None,
@@ -656,12 +943,14 @@ mod integration_test {
             message_timeout_ms: 300000,
             batch,
             librdkafka_options,
+            idempotence: false,
+            transaction: None,
         };
         let (acker, _ack_counter) = Acker::new_for_testing();
         config.clone().to_rdkafka(KafkaRole::Consumer)?;
         config.clone().to_rdkafka(KafkaRole::Producer)?;
         super::healthcheck(config.clone()).await?;
-        KafkaSink::new(config, acker)
+        KafkaSink::new(config, None, acker)
     }
 
     #[tokio::test]
@@ -800,17 +1089,22 @@ mod integration_test {
             bootstrap_servers: server.to_string(),
             topic: format!("{}-%Y%m%d", topic),
             key_field: None,
+            headers_key: None,
             encoding: EncodingConfig::from(Encoding::Text),
+            protobuf: None,
+            schema_registry: None,
             batch: BatchConfig::default(),
             compression,
             auth: kafka_auth.clone(),
             socket_timeout_ms: 60000,
             message_timeout_ms: 300000,
             librdkafka_options: HashMap::new(),
+            idempotence: false,
+            transaction: None,
         };
         let topic = format!("{}-{}", topic, chrono::Utc::now().format("%Y%m%d"));
         let (acker, ack_counter) = Acker::new_for_testing();
-        let sink = KafkaSink::new(config, acker).unwrap();
+        let sink = KafkaSink::new(config, None, acker).unwrap();
 
         let num_events = 1000;
         let (batch, mut receiver) = BatchNotifier::new_with_receiver();
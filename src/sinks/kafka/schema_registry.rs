@@ -0,0 +1,551 @@
+//! Serialization of message values with the schemas of a Confluent Schema
+//! Registry. Values are written in the wire format of the registry's
+//! serializers: a zero magic byte, the ID of the schema as a big endian
+//! 32 bit integer, then the value itself. Protobuf values are preceded by the
+//! indexes of their message type in the schema, so that consumers using the
+//! registry's deserializers can read them.
+//!
+//! Without a registry, protobuf values are bare messages of the configured
+//! type, as `encoding.codec = "protobuf"` writes them.
+
+use crate::{
+    config::ProxyConfig,
+    event::Event,
+    http::{Auth, HttpClient},
+    protobuf::{DescriptorError, EncodeError, MessageEncoder},
+    template::Template,
+    tls::{TlsOptions, TlsSettings},
+};
+use http::{Request, StatusCode};
+use hyper::Body;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use snafu::{ResultExt, Snafu};
+use std::{collections::BTreeMap, convert::TryFrom, path::PathBuf};
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+const MAGIC_BYTE: u8 = 0;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("`schema_registry.auto_register` requires `schema_registry.schema_file`"))]
+    MissingSchemaFile,
+    #[snafu(display(
+        "protobuf schemas require `descriptor_set_file` and `message_type` of `schema_registry`"
+    ))]
+    MissingMessageType,
+    #[snafu(display(
+        "the {} subject name strategy requires a `topic` without templates, or a `subject`",
+        strategy
+    ))]
+    DynamicTopic { strategy: &'static str },
+    #[snafu(display(
+        "the {} subject name strategy requires the record name of `schema_registry.schema_file`",
+        strategy
+    ))]
+    MissingRecordName { strategy: &'static str },
+    #[snafu(display("Couldn't read the schema {:?}: {}", path, source))]
+    ReadSchema {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("invalid Avro schema: {}", source))]
+    AvroSchema { source: avro_rs::Error },
+    #[snafu(display("invalid JSON schema: {}", source))]
+    JsonSchema { source: serde_json::Error },
+    #[snafu(display("{}", source))]
+    Descriptor { source: DescriptorError },
+    #[snafu(display("schema registry responded with {}: {}", status, body))]
+    Registry { status: StatusCode, body: String },
+    #[snafu(display(
+        "subject {:?} has a {} schema, but `schema_registry.schema_type` is {}",
+        subject,
+        found,
+        expected
+    ))]
+    SchemaTypeMismatch {
+        subject: String,
+        expected: &'static str,
+        found: String,
+    },
+}
+
+/// Why an event couldn't be serialized with the schema.
+#[derive(Debug, Snafu)]
+pub(super) enum SerializeError {
+    #[snafu(display("{}", source))]
+    Avro { source: avro_rs::Error },
+    #[snafu(display("{}", source))]
+    Protobuf { source: EncodeError },
+    #[snafu(display("protobuf schemas only serialize log events"))]
+    NotALog,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaRegistryConfig {
+    /// The URL of the registry, such as `http://localhost:8081`.
+    url: String,
+    schema_type: SchemaType,
+    /// The subject of the schema, rather than the one named by
+    /// `subject_name_strategy`.
+    subject: Option<String>,
+    #[serde(default)]
+    subject_name_strategy: SubjectNameStrategy,
+    /// The schema registered with `auto_register`, which also names the
+    /// record for the strategies using it.
+    schema_file: Option<PathBuf>,
+    /// Registers `schema_file` under the subject, rather than looking up a
+    /// version already registered.
+    #[serde(default)]
+    auto_register: bool,
+    /// The version of the subject looked up, the latest one by default.
+    version: Option<u32>,
+    /// The descriptor set of protobuf schemas, along with its imports, as
+    /// written by `protoc --include_imports --descriptor_set_out`.
+    descriptor_set_file: Option<PathBuf>,
+    /// The message type of protobuf schemas, as `package.Message`.
+    message_type: Option<String>,
+    /// Fields of protobuf messages, mapped to the fields of events setting
+    /// them.
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+    auth: Option<Auth>,
+    tls: Option<TlsOptions>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaType {
+    Avro,
+    Protobuf,
+    Json,
+}
+
+impl SchemaType {
+    /// The name of the type in the API of the registry.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Avro => "AVRO",
+            Self::Protobuf => "PROTOBUF",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+/// How the subject of the schema is named, as the strategies of the
+/// registry's serializers do.
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectNameStrategy {
+    /// `{topic}-value`
+    #[derivative(Default)]
+    TopicName,
+    /// The full name of the record.
+    RecordName,
+    /// `{topic}-{record}`
+    TopicRecordName,
+}
+
+impl SubjectNameStrategy {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::TopicName => "topic_name",
+            Self::RecordName => "record_name",
+            Self::TopicRecordName => "topic_record_name",
+        }
+    }
+
+    fn subject(self, topic: &str, record_name: Option<&str>) -> Result<String, BuildError> {
+        if self != Self::RecordName && Template::try_from(topic).map_or(true, |t| t.is_dynamic()) {
+            return Err(BuildError::DynamicTopic {
+                strategy: self.name(),
+            });
+        }
+        let record_name = || {
+            record_name.ok_or(BuildError::MissingRecordName {
+                strategy: self.name(),
+            })
+        };
+        Ok(match self {
+            Self::TopicName => format!("{}-value", topic),
+            Self::RecordName => record_name()?.to_owned(),
+            Self::TopicRecordName => format!("{}-{}", topic, record_name()?),
+        })
+    }
+}
+
+/// A schema as the registry returns it.
+#[derive(Debug, Deserialize)]
+struct RegisteredSchema {
+    id: u32,
+    schema: Option<String>,
+    #[serde(rename = "schemaType")]
+    schema_type: Option<String>,
+}
+
+impl SchemaRegistryConfig {
+    /// Registers or looks up the schema of the values produced to `topic`.
+    pub(super) async fn build(
+        &self,
+        topic: &str,
+        proxy: &ProxyConfig,
+    ) -> crate::Result<ValueSerializer> {
+        let schema = match &self.schema_file {
+            Some(path) => Some(std::fs::read_to_string(path).context(ReadSchema { path })?),
+            None if self.auto_register => return Err(BuildError::MissingSchemaFile.into()),
+            None => None,
+        };
+        let record_name = self.record_name(schema.as_deref())?;
+        let subject = match &self.subject {
+            Some(subject) => subject.clone(),
+            None => self
+                .subject_name_strategy
+                .subject(topic, record_name.as_deref())?,
+        };
+
+        let registry = Registry {
+            client: HttpClient::new(TlsSettings::from_options(&self.tls)?, proxy)?,
+            url: self.url.trim_end_matches('/').to_owned(),
+            auth: self.auth.clone(),
+        };
+        let registered = match &schema {
+            Some(schema) if self.auto_register => {
+                registry
+                    .register(&subject, self.schema_type, schema)
+                    .await?
+            }
+            _ => registry.lookup(&subject, self.version).await?,
+        };
+        let found = registered.schema_type.as_deref().unwrap_or("AVRO");
+        if found != self.schema_type.name() {
+            return Err(BuildError::SchemaTypeMismatch {
+                subject,
+                expected: self.schema_type.name(),
+                found: found.to_owned(),
+            }
+            .into());
+        }
+        info!(
+            message = "Resolved schema.",
+            %subject,
+            id = registered.id
+        );
+
+        let format = match self.schema_type {
+            SchemaType::Avro => {
+                // The registered schema is the one the ID refers to, which
+                // the registry doesn't return when registering.
+                let schema = registered.schema.as_deref().or_else(|| schema.as_deref());
+                let schema =
+                    avro_rs::Schema::parse_str(schema.unwrap_or_default()).context(AvroSchema)?;
+                Format::Avro(schema)
+            }
+            SchemaType::Protobuf => {
+                let (descriptor_set_file, message_type) =
+                    match (&self.descriptor_set_file, &self.message_type) {
+                        (Some(file), Some(message_type)) => (file, message_type),
+                        _ => return Err(BuildError::MissingMessageType.into()),
+                    };
+                let (encoder, indexes) = MessageEncoder::for_message(
+                    descriptor_set_file,
+                    message_type,
+                    self.fields.clone(),
+                )
+                .context(Descriptor)?;
+                Format::Protobuf { encoder, indexes }
+            }
+            SchemaType::Json => Format::Json,
+        };
+
+        Ok(ValueSerializer {
+            id: Some(registered.id),
+            format,
+        })
+    }
+
+    /// The name of the record of the schema, used by the subject name
+    /// strategies naming subjects after it.
+    fn record_name(&self, schema: Option<&str>) -> Result<Option<String>, BuildError> {
+        Ok(match self.schema_type {
+            SchemaType::Avro => match schema {
+                Some(schema) => match avro_rs::Schema::parse_str(schema).context(AvroSchema)? {
+                    avro_rs::Schema::Record { name, .. } => Some(name.fullname(None)),
+                    _ => None,
+                },
+                None => None,
+            },
+            SchemaType::Protobuf => self
+                .message_type
+                .as_ref()
+                .map(|message_type| message_type.trim_start_matches('.').to_owned()),
+            SchemaType::Json => match schema {
+                Some(schema) => serde_json::from_str::<serde_json::Value>(schema)
+                    .context(JsonSchema)?
+                    .get("title")
+                    .and_then(|title| title.as_str())
+                    .map(Into::into),
+                None => None,
+            },
+        })
+    }
+}
+
+struct Registry {
+    client: HttpClient,
+    url: String,
+    auth: Option<Auth>,
+}
+
+impl Registry {
+    async fn register(
+        &self,
+        subject: &str,
+        schema_type: SchemaType,
+        schema: &str,
+    ) -> crate::Result<RegisteredSchema> {
+        let mut body = json!({ "schema": schema });
+        // Schemas without a type are Avro schemas, which older registries
+        // only support.
+        if schema_type != SchemaType::Avro {
+            body["schemaType"] = schema_type.name().into();
+        }
+        let path = format!("/subjects/{}/versions", encode(subject));
+        let mut registered = self.request("POST", &path, body.to_string()).await?;
+        registered.schema_type = Some(schema_type.name().to_owned());
+        Ok(registered)
+    }
+
+    async fn lookup(&self, subject: &str, version: Option<u32>) -> crate::Result<RegisteredSchema> {
+        let version = version.map_or_else(|| "latest".to_owned(), |version| version.to_string());
+        let path = format!("/subjects/{}/versions/{}", encode(subject), version);
+        self.request("GET", &path, String::new()).await
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: String,
+    ) -> crate::Result<RegisteredSchema> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.url, path))
+            .header("Accept", CONTENT_TYPE);
+        if !body.is_empty() {
+            builder = builder.header("Content-Type", CONTENT_TYPE);
+        }
+        if let Some(auth) = &self.auth {
+            builder = auth.apply_builder(builder);
+        }
+
+        let response = self.client.send(builder.body(Body::from(body))?).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        if !status.is_success() {
+            return Err(BuildError::Registry {
+                status,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            }
+            .into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+fn encode(subject: &str) -> String {
+    utf8_percent_encode(subject, NON_ALPHANUMERIC).to_string()
+}
+
+#[derive(Debug)]
+enum Format {
+    Avro(avro_rs::Schema),
+    Protobuf {
+        encoder: MessageEncoder,
+        indexes: Vec<i32>,
+    },
+    /// Values are JSON objects, which the registry's serializers don't
+    /// validate either unless configured to.
+    Json,
+}
+
+/// Serializes message values with a schema of the registry, or as bare
+/// protobuf messages.
+#[derive(Debug)]
+pub(super) struct ValueSerializer {
+    /// The ID of the registered schema, which values are framed with.
+    id: Option<u32>,
+    format: Format,
+}
+
+impl ValueSerializer {
+    pub(super) fn protobuf(encoder: MessageEncoder) -> Self {
+        Self {
+            id: None,
+            format: Format::Protobuf {
+                encoder,
+                indexes: Vec::new(),
+            },
+        }
+    }
+
+    pub(super) fn serialize(&self, event: &Event) -> Result<Vec<u8>, SerializeError> {
+        let mut body = Vec::new();
+        if let Some(id) = self.id {
+            body.push(MAGIC_BYTE);
+            body.extend_from_slice(&id.to_be_bytes());
+        }
+        match &self.format {
+            Format::Avro(schema) => {
+                let value = match event {
+                    Event::Log(log) => avro_rs::to_value(log),
+                    Event::Metric(metric) => avro_rs::to_value(metric),
+                    Event::Trace(trace) => avro_rs::to_value(trace),
+                }
+                .context(Avro)?;
+                let value = value.resolve(schema).context(Avro)?;
+                body.extend(avro_rs::to_avro_datum(schema, value).context(Avro)?);
+            }
+            Format::Protobuf { encoder, indexes } => {
+                let log = match event {
+                    Event::Log(log) => log,
+                    _ => return Err(SerializeError::NotALog),
+                };
+                if self.id.is_some() {
+                    encode_indexes(indexes, &mut body);
+                }
+                body.extend_from_slice(&encoder.encode(log).context(Protobuf)?);
+            }
+            Format::Json => {
+                let value = match event {
+                    Event::Log(log) => serde_json::to_vec(log),
+                    Event::Metric(metric) => serde_json::to_vec(metric),
+                    Event::Trace(trace) => serde_json::to_vec(trace),
+                };
+                body.extend(value.expect("Failed to encode event as json, this is a bug!"));
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// Writes the indexes of the message type as zigzag encoded varints, after
+/// their count. The first message of the schema, by far the most common, is
+/// written as a single zero.
+fn encode_indexes(indexes: &[i32], buf: &mut Vec<u8>) {
+    let zigzag = |value: i32| ((value << 1) ^ (value >> 31)) as u32 as u64;
+    if indexes == [0] {
+        buf.push(0);
+        return;
+    }
+    prost::encoding::encode_varint(zigzag(indexes.len() as i32), buf);
+    for index in indexes {
+        prost::encoding::encode_varint(zigzag(*index), buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    #[test]
+    fn names_subjects() {
+        let subject = |strategy: SubjectNameStrategy, topic, record| {
+            strategy
+                .subject(topic, record)
+                .map_err(|error| error.to_string())
+        };
+        assert_eq!(
+            subject(SubjectNameStrategy::TopicName, "logs", None).unwrap(),
+            "logs-value"
+        );
+        assert_eq!(
+            subject(
+                SubjectNameStrategy::RecordName,
+                "{{ topic }}",
+                Some("io.vector.Log")
+            )
+            .unwrap(),
+            "io.vector.Log"
+        );
+        assert_eq!(
+            subject(
+                SubjectNameStrategy::TopicRecordName,
+                "logs",
+                Some("io.vector.Log")
+            )
+            .unwrap(),
+            "logs-io.vector.Log"
+        );
+        assert!(subject(SubjectNameStrategy::TopicName, "logs-%Y%m%d", None).is_err());
+        assert!(subject(SubjectNameStrategy::RecordName, "logs", None).is_err());
+    }
+
+    #[test]
+    fn reads_record_names() {
+        let config: SchemaRegistryConfig = toml::from_str(
+            r#"url = "http://localhost:8081"
+            schema_type = "avro""#,
+        )
+        .unwrap();
+        let schema = r#"{"type": "record", "name": "Log", "namespace": "io.vector",
+            "fields": [{"name": "message", "type": "string"}]}"#;
+        assert_eq!(
+            config.record_name(Some(schema)).unwrap().as_deref(),
+            Some("io.vector.Log")
+        );
+
+        let config = SchemaRegistryConfig {
+            schema_type: SchemaType::Json,
+            ..config
+        };
+        let schema = r#"{"title": "Log", "type": "object"}"#;
+        assert_eq!(
+            config.record_name(Some(schema)).unwrap().as_deref(),
+            Some("Log")
+        );
+    }
+
+    #[test]
+    fn serializes_in_wire_format() {
+        let schema = avro_rs::Schema::parse_str(
+            r#"{"type": "record", "name": "Log",
+            "fields": [{"name": "message", "type": "string"}]}"#,
+        )
+        .unwrap();
+        let serializer = ValueSerializer {
+            id: Some(258),
+            format: Format::Avro(schema),
+        };
+        let mut log = LogEvent::default();
+        log.insert("message", "hi");
+        let body = serializer.serialize(&log.into()).unwrap();
+        // The length of the string is zigzag encoded too.
+        assert_eq!(body, [0, 0, 0, 1, 2, 4, b'h', b'i']);
+
+        let serializer = ValueSerializer {
+            id: Some(1),
+            format: Format::Json,
+        };
+        let mut log = LogEvent::default();
+        log.insert("message", "hi");
+        let body = serializer.serialize(&log.into()).unwrap();
+        assert_eq!(&body[..5], [0, 0, 0, 0, 1]);
+        assert_eq!(&body[5..], br#"{"message":"hi"}"#);
+    }
+
+    #[test]
+    fn encodes_message_indexes() {
+        let encoded = |indexes: &[i32]| {
+            let mut buf = Vec::new();
+            encode_indexes(indexes, &mut buf);
+            buf
+        };
+        assert_eq!(encoded(&[0]), [0]);
+        assert_eq!(encoded(&[1]), [2, 2]);
+        assert_eq!(encoded(&[0, 2]), [4, 0, 4]);
+    }
+}
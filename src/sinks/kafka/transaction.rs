@@ -0,0 +1,332 @@
+//! The transactional mode of the sink, which produces batches of events in
+//! transactions and acknowledges them once their transaction is committed.
+//! Consumers reading committed messages only see the messages of committed
+//! transactions, so aborted and retried batches aren't seen twice.
+
+use super::{
+    encode_event, headers, schema_registry::ValueSerializer, timestamp_ms, Encoding,
+    KafkaCreateFailed, KafkaRole, KafkaSinkConfig, KafkaTransactionConfig, TopicTemplate,
+};
+use crate::{
+    buffers::Acker,
+    internal_events::{
+        KafkaTransactionAborted, KafkaTransactionCommitted, TemplateRenderingFailed,
+    },
+    kafka::KafkaStatisticsContext,
+    sinks::util::{encoding::EncodingConfig, StreamSink},
+    template::Template,
+};
+use async_trait::async_trait;
+use futures::{future::join_all, stream::BoxStream, StreamExt};
+use rdkafka::{
+    error::{KafkaError, KafkaResult, RDKafkaErrorCode},
+    message::OwnedHeaders,
+    producer::{FutureProducer, FutureRecord, Producer},
+};
+use snafu::ResultExt;
+use std::{convert::TryFrom, sync::Arc};
+use tokio::time::{sleep, Duration};
+use vector_core::event::{Event, EventMetadata, EventStatus};
+
+const MAX_BACKOFF_SECS: u64 = 60;
+
+pub(super) struct TransactionalKafkaSink {
+    producer: Arc<FutureProducer<KafkaStatisticsContext>>,
+    topic: Template,
+    key_field: Option<String>,
+    headers_key: Option<String>,
+    encoding: EncodingConfig<Encoding>,
+    value_serializer: Option<Arc<ValueSerializer>>,
+    acker: Acker,
+    timeout: Duration,
+    commit_interval: Duration,
+    max_events: usize,
+}
+
+/// A message of a transaction, kept to be produced again if the transaction
+/// is aborted.
+struct Message {
+    topic: String,
+    key: Option<Vec<u8>>,
+    body: Vec<u8>,
+    headers: Option<OwnedHeaders>,
+    timestamp_ms: Option<i64>,
+    metadata: EventMetadata,
+}
+
+impl TransactionalKafkaSink {
+    pub(super) fn new(
+        config: &KafkaSinkConfig,
+        transaction: &KafkaTransactionConfig,
+        value_serializer: Option<Arc<ValueSerializer>>,
+        acker: Acker,
+    ) -> crate::Result<Self> {
+        let producer = config
+            .to_rdkafka(KafkaRole::Producer)?
+            .create_with_context(KafkaStatisticsContext::new(&config.auth)?)
+            .context(KafkaCreateFailed)?;
+        Ok(Self {
+            producer: Arc::new(producer),
+            topic: Template::try_from(config.topic.as_str()).context(TopicTemplate)?,
+            key_field: config.key_field.clone(),
+            headers_key: config.headers_key.clone(),
+            encoding: config.encoding.clone(),
+            value_serializer,
+            acker,
+            timeout: Duration::from_millis(transaction.timeout_ms),
+            commit_interval: Duration::from_millis(transaction.commit_interval_ms),
+            max_events: transaction.max_events.max(1),
+        })
+    }
+
+    /// Runs a call of the transactions API, which blocks until the brokers
+    /// respond or the transaction timeout expires.
+    async fn call<F>(&self, call: F) -> KafkaResult<()>
+    where
+        F: FnOnce(&FutureProducer<KafkaStatisticsContext>, Duration) -> KafkaResult<()>
+            + Send
+            + 'static,
+    {
+        let producer = Arc::clone(&self.producer);
+        let timeout = self.timeout;
+        tokio::task::spawn_blocking(move || call(&producer, timeout))
+            .await
+            .expect("Kafka transaction call panicked")
+    }
+
+    fn message(&self, event: Event) -> Option<Message> {
+        let topic = match self.topic.render_string(&event) {
+            Ok(topic) => topic,
+            Err(error) => {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some("topic"),
+                    drop_event: true,
+                });
+                event.metadata().update_status(EventStatus::Errored);
+                return None;
+            }
+        };
+        let timestamp_ms = timestamp_ms(&event);
+        let headers = headers(&event, self.headers_key.as_deref());
+        let (key, body, metadata) = encode_event(
+            event,
+            &self.key_field,
+            &self.encoding,
+            self.value_serializer.as_deref(),
+        )?;
+        Some(Message {
+            topic,
+            key: self.key_field.as_ref().map(|_| key),
+            body,
+            headers,
+            timestamp_ms,
+            metadata,
+        })
+    }
+
+    /// Produces the messages in transactions until they are committed. Failed
+    /// transactions are aborted and retried, without the messages that failed
+    /// for reasons retrying doesn't fix.
+    async fn produce(&self, mut messages: Vec<Message>) -> Result<(), ()> {
+        let mut backoff = Duration::from_secs(1);
+        while !messages.is_empty() {
+            let error = match self.attempt(&mut messages).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            if is_fatal(&error) {
+                error!(message = "Fatal Kafka transaction error.", %error);
+                fail(messages);
+                return Err(());
+            }
+
+            emit!(KafkaTransactionAborted {
+                error: &error,
+                retry_secs: backoff.as_secs(),
+            });
+            if let Err(error) = self
+                .call(|producer, timeout| producer.abort_transaction(timeout))
+                .await
+            {
+                if is_fatal(&error) {
+                    error!(message = "Fatal Kafka transaction error.", %error);
+                    fail(messages);
+                    return Err(());
+                }
+            }
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(MAX_BACKOFF_SECS));
+        }
+        Ok(())
+    }
+
+    async fn attempt(&self, messages: &mut Vec<Message>) -> KafkaResult<()> {
+        self.producer.begin_transaction()?;
+
+        let results = self.send(messages).await;
+        let mut failure = None;
+        let mut retried = Vec::with_capacity(messages.len());
+        for (message, result) in messages.drain(..).zip(results) {
+            match result {
+                Ok(()) => retried.push(message),
+                Err(error) => {
+                    if is_permanent(&error) {
+                        error!(message = "Kafka error.", %error);
+                        message.metadata.update_status(EventStatus::Errored);
+                    } else {
+                        retried.push(message);
+                    }
+                    failure.get_or_insert(error);
+                }
+            }
+        }
+        *messages = retried;
+        if let Some(error) = failure {
+            return Err(error);
+        }
+
+        loop {
+            match self
+                .call(|producer, timeout| producer.commit_transaction(timeout))
+                .await
+            {
+                Ok(()) => break,
+                Err(KafkaError::Transaction(error)) if error.is_retriable() => {
+                    debug!(message = "Retrying Kafka transaction commit.", %error);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        emit!(KafkaTransactionCommitted {
+            count: messages.len()
+        });
+        for message in messages.drain(..) {
+            message.metadata.update_status(EventStatus::Delivered);
+        }
+        Ok(())
+    }
+
+    /// Sends the messages, and waits for their delivery to the brokers.
+    async fn send(&self, messages: &[Message]) -> Vec<KafkaResult<()>> {
+        let mut deliveries = Vec::with_capacity(messages.len());
+        for message in messages {
+            let mut record: FutureRecord<[u8], [u8]> =
+                FutureRecord::to(&message.topic).payload(&message.body[..]);
+            if let Some(key) = &message.key {
+                record = record.key(&key[..]);
+            }
+            if let Some(timestamp) = message.timestamp_ms {
+                record = record.timestamp(timestamp);
+            }
+            if let Some(headers) = &message.headers {
+                record = record.headers(headers.clone());
+            }
+
+            let delivery = loop {
+                match self.producer.send_result(record) {
+                    Ok(future) => break Ok(future),
+                    // Try again if queue is full.
+                    Err((error, future_record))
+                        if error == KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull) =>
+                    {
+                        record = future_record;
+                        sleep(Duration::from_millis(10)).await;
+                    }
+                    Err((error, _)) => break Err(error),
+                }
+            };
+            deliveries.push(delivery);
+        }
+
+        join_all(deliveries.into_iter().map(|delivery| async move {
+            match delivery?.await {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err((error, _owned_message))) => Err(error),
+                Err(_canceled) => Err(KafkaError::Canceled),
+            }
+        }))
+        .await
+    }
+}
+
+#[async_trait]
+impl StreamSink for TransactionalKafkaSink {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        if let Err(error) = self
+            .call(|producer, timeout| producer.init_transactions(timeout))
+            .await
+        {
+            error!(message = "Failed to initialize Kafka transactions.", %error);
+            return Err(());
+        }
+
+        let mut input = input.fuse();
+        while let Some(event) = input.next().await {
+            let mut events = vec![event];
+            let deadline = sleep(self.commit_interval);
+            tokio::pin!(deadline);
+            while events.len() < self.max_events {
+                tokio::select! {
+                    event = input.next() => match event {
+                        Some(event) => events.push(event),
+                        None => break,
+                    },
+                    _ = &mut deadline => break,
+                }
+            }
+
+            let count = events.len();
+            let messages = events
+                .into_iter()
+                .filter_map(|event| self.message(event))
+                .collect();
+            self.produce(messages).await?;
+            self.acker.ack(count);
+        }
+
+        Ok(())
+    }
+}
+
+fn fail(messages: Vec<Message>) {
+    for message in messages {
+        message.metadata.update_status(EventStatus::Errored);
+    }
+}
+
+/// Errors that leave the producer unusable, such as being fenced by another
+/// producer with the same `transactional.id`.
+fn is_fatal(error: &KafkaError) -> bool {
+    matches!(error, KafkaError::Transaction(error) if error.is_fatal())
+}
+
+/// Errors of messages that fail again if they are produced again.
+fn is_permanent(error: &KafkaError) -> bool {
+    matches!(
+        error.rdkafka_error_code(),
+        Some(RDKafkaErrorCode::MessageSizeTooLarge)
+            | Some(RDKafkaErrorCode::InvalidMessageSize)
+            | Some(RDKafkaErrorCode::InvalidMessage)
+            | Some(RDKafkaErrorCode::InvalidRecord)
+            | Some(RDKafkaErrorCode::UnknownTopic)
+            | Some(RDKafkaErrorCode::UnknownPartition)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_message_errors_are_permanent() {
+        assert!(is_permanent(&KafkaError::MessageProduction(
+            RDKafkaErrorCode::MessageSizeTooLarge
+        )));
+        assert!(!is_permanent(&KafkaError::MessageProduction(
+            RDKafkaErrorCode::MessageTimedOut
+        )));
+        assert!(!is_permanent(&KafkaError::Canceled));
+    }
+}
@@ -1,7 +1,7 @@
 //! Loki sink
 //!
 //! This sink provides downstream support for `Loki` via
-//! the v1 http json endpoint.
+//! the v1 http endpoint, with JSON or snappy-compressed protobuf payloads.
 //!
 //! <https://github.com/grafana/loki/blob/master/docs/api.md>
 //!
@@ -16,6 +16,7 @@ use crate::{
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     event::{self, Event, Value},
     http::{Auth, HttpClient, MaybeAuth},
+    proto::loki::logproto,
     sinks::util::{
         buffer::loki::{GlobalTimestamps, LokiBuffer, LokiEvent, LokiRecord, PartitionKey},
         encoding::{EncodingConfig, EncodingConfiguration},
@@ -28,6 +29,7 @@ use crate::{
     tls::{TlsOptions, TlsSettings},
 };
 use futures::{FutureExt, SinkExt};
+use prost::Message;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -39,6 +41,9 @@ pub struct LokiConfig {
 
     tenant_id: Option<Template>,
     labels: HashMap<Template, Template>,
+    /// Labels of each entry, which aren't indexed.
+    #[serde(default)]
+    structured_metadata: HashMap<Template, Template>,
 
     #[serde(default = "crate::serde::default_false")]
     remove_label_fields: bool,
@@ -46,6 +51,8 @@ pub struct LokiConfig {
     remove_timestamp: bool,
     #[serde(default)]
     out_of_order_action: OutOfOrderAction,
+    #[serde(default)]
+    compression: LokiCompression,
 
     auth: Option<Auth>,
 
@@ -65,6 +72,19 @@ pub enum OutOfOrderAction {
     #[derivative(Default)]
     Drop,
     RewriteTimestamp,
+    /// Loki accepts out-of-order entries since 2.4, within its window.
+    Accept,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, PartialEq, Eq)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LokiCompression {
+    /// JSON payloads.
+    #[derivative(Default)]
+    None,
+    /// Snappy-compressed protobuf payloads.
+    Snappy,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -100,7 +120,7 @@ impl SinkConfig for LokiConfig {
             return Err("`labels` must include at least one label.".into());
         }
 
-        for label in self.labels.keys() {
+        for label in self.labels.keys().chain(self.structured_metadata.keys()) {
             if !valid_label_name(label) {
                 return Err(format!("Invalid label name {:?}", label.get_ref()).into());
             }
@@ -161,9 +181,11 @@ struct LokiSink {
 
     tenant_id: Option<Template>,
     labels: HashMap<Template, Template>,
+    structured_metadata: HashMap<Template, Template>,
 
     remove_label_fields: bool,
     remove_timestamp: bool,
+    compression: LokiCompression,
 
     auth: Option<Auth>,
 }
@@ -175,8 +197,10 @@ impl LokiSink {
             encoding: config.encoding,
             tenant_id: config.tenant_id,
             labels: config.labels,
+            structured_metadata: config.structured_metadata,
             remove_label_fields: config.remove_label_fields,
             remove_timestamp: config.remove_timestamp,
+            compression: config.compression,
             auth: config.auth,
         }
     }
@@ -201,19 +225,15 @@ impl HttpSink for LokiSink {
         });
         let key = PartitionKey { tenant_id };
 
-        let mut labels = Vec::new();
-
-        for (key_template, value_template) in &self.labels {
-            if let (Ok(key), Ok(value)) = (
-                key_template.render_string(&event),
-                value_template.render_string(&event),
-            ) {
-                labels.push((key, value));
-            }
-        }
+        let mut labels = render_labels(&self.labels, &event);
+        let structured_metadata = render_labels(&self.structured_metadata, &event);
 
         if self.remove_label_fields {
-            for template in self.labels.values() {
+            for template in self
+                .labels
+                .values()
+                .chain(self.structured_metadata.values())
+            {
                 if let Some(fields) = template.get_fields() {
                     for field in fields {
                         event.as_mut_log().remove(&field);
@@ -249,7 +269,11 @@ impl HttpSink for LokiSink {
             labels = vec![("agent".to_string(), "vector".to_string())]
         }
 
-        let event = LokiEvent { timestamp, event };
+        let event = LokiEvent {
+            timestamp,
+            event,
+            structured_metadata,
+        };
         Some(PartitionInnerBuffer::new(
             LokiRecord {
                 labels,
@@ -264,11 +288,19 @@ impl HttpSink for LokiSink {
         let (json, key) = output.into_parts();
         let tenant_id = key.tenant_id;
 
-        let body = serde_json::to_vec(&json).unwrap();
+        let (body, content_type) = match self.compression {
+            LokiCompression::None => (serde_json::to_vec(&json).unwrap(), "application/json"),
+            LokiCompression::Snappy => {
+                let body = snap::raw::Encoder::new()
+                    .compress_vec(&push_request(&json).encode_to_vec())
+                    .expect("snappy compression should never fail");
+                (body, "application/x-protobuf")
+            }
+        };
 
         let uri = format!("{}loki/api/v1/push", self.endpoint.uri);
 
-        let mut req = http::Request::post(uri).header("Content-Type", "application/json");
+        let mut req = http::Request::post(uri).header("Content-Type", content_type);
 
         if let Some(tenant_id) = tenant_id {
             req = req.header("X-Scope-OrgID", tenant_id);
@@ -284,6 +316,83 @@ impl HttpSink for LokiSink {
     }
 }
 
+fn render_labels(templates: &HashMap<Template, Template>, event: &Event) -> Vec<(String, String)> {
+    templates
+        .iter()
+        .filter_map(|(key_template, value_template)| {
+            match (
+                key_template.render_string(event),
+                value_template.render_string(event),
+            ) {
+                (Ok(key), Ok(value)) => Some((key, value)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The protobuf message of the JSON payload built by `LokiBuffer`.
+fn push_request(json: &serde_json::Value) -> logproto::PushRequest {
+    let streams = json["streams"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|stream| {
+            let labels = stream["stream"]
+                .as_object()
+                .map(|labels| {
+                    let labels = labels
+                        .iter()
+                        .map(|(name, value)| {
+                            format!("{}={:?}", name, value.as_str().unwrap_or_default())
+                        })
+                        .collect::<Vec<_>>();
+                    format!("{{{}}}", labels.join(", "))
+                })
+                .unwrap_or_default();
+            let entries = stream["values"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .map(|value| {
+                    let timestamp = value[0]
+                        .as_str()
+                        .and_then(|timestamp| timestamp.parse::<i64>().ok())
+                        .unwrap_or_default();
+                    let structured_metadata = value[2]
+                        .as_object()
+                        .map(|metadata| {
+                            metadata
+                                .iter()
+                                .map(|(name, value)| logproto::LabelPairAdapter {
+                                    name: name.clone(),
+                                    value: value.as_str().unwrap_or_default().to_owned(),
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    logproto::EntryAdapter {
+                        timestamp: Some(prost_types::Timestamp {
+                            seconds: timestamp.div_euclid(1_000_000_000),
+                            nanos: timestamp.rem_euclid(1_000_000_000) as i32,
+                        }),
+                        line: value[1].as_str().unwrap_or_default().to_owned(),
+                        structured_metadata,
+                    }
+                })
+                .collect();
+            logproto::StreamAdapter {
+                labels,
+                entries,
+                hash: 0,
+            }
+        })
+        .collect();
+    logproto::PushRequest { streams }
+}
+
 async fn healthcheck(config: LokiConfig, client: HttpClient) -> crate::Result<()> {
     let uri = format!("{}ready", config.endpoint.uri);
 
@@ -402,6 +511,108 @@ mod tests {
         assert_eq!(record.labels[3], ("label3".to_string(), "bar".to_string()));
     }
 
+    #[test]
+    fn structured_metadata() {
+        let (config, _cx) = load_sink::<LokiConfig>(
+            r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web"}
+            structured_metadata = {trace_id = "{{ trace_id }}", missing = "{{ missing }}"}
+            encoding = "json"
+            remove_label_fields = true
+        "#,
+        )
+        .unwrap();
+        let sink = LokiSink::new(config);
+
+        let mut e1 = Event::from("hello world");
+        e1.as_mut_log().insert("trace_id", "0242ac120002");
+
+        let record = sink.encode_event(e1).unwrap().into_parts().0;
+
+        assert_eq!(record.labels, vec![("app".to_string(), "web".to_string())]);
+        assert_eq!(
+            record.event.structured_metadata,
+            vec![("trace_id".to_string(), "0242ac120002".to_string())]
+        );
+        assert_eq!(
+            record.event.event,
+            serde_json::to_string(&serde_json::json!({"message": "hello world"})).unwrap()
+        );
+    }
+
+    #[test]
+    fn encode_push_request() {
+        let json = serde_json::json!({
+            "streams": [{
+                "stream": {"app": "web", "env": "prod \"eu\""},
+                "values": [
+                    ["1628000000123456789", "first"],
+                    ["1628000000123456790", "second", {"trace_id": "0242ac120002"}],
+                ],
+            }],
+        });
+
+        let request = push_request(&json);
+
+        assert_eq!(request.streams.len(), 1);
+        let stream = &request.streams[0];
+        assert_eq!(stream.labels, r#"{app="web", env="prod \"eu\""}"#);
+        assert_eq!(stream.entries.len(), 2);
+        assert_eq!(
+            stream.entries[0].timestamp,
+            Some(prost_types::Timestamp {
+                seconds: 1628000000,
+                nanos: 123456789
+            })
+        );
+        assert_eq!(stream.entries[0].line, "first");
+        assert!(stream.entries[0].structured_metadata.is_empty());
+        assert_eq!(
+            stream.entries[1].structured_metadata,
+            vec![logproto::LabelPairAdapter {
+                name: "trace_id".into(),
+                value: "0242ac120002".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn snappy_protobuf_request() {
+        let (config, _cx) = load_sink::<LokiConfig>(
+            r#"
+            endpoint = "http://localhost:3100"
+            labels = {app = "web"}
+            encoding = "text"
+            compression = "snappy"
+        "#,
+        )
+        .unwrap();
+        let sink = LokiSink::new(config);
+
+        let json = serde_json::json!({
+            "streams": [{"stream": {"app": "web"}, "values": [["1", "hello"]]}],
+        });
+        let request = sink
+            .build_request(PartitionInnerBuffer::new(
+                json,
+                PartitionKey { tenant_id: None },
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            request.headers().get("Content-Type").unwrap(),
+            "application/x-protobuf"
+        );
+        let body = snap::raw::Decoder::new()
+            .decompress_vec(request.body())
+            .unwrap();
+        let push = logproto::PushRequest::decode(body.as_slice()).unwrap();
+        assert_eq!(push.streams[0].labels, r#"{app="web"}"#);
+        assert_eq!(push.streams[0].entries[0].line, "hello");
+    }
+
     #[test]
     fn use_label_from_dropped_fields() {
         let (config, _cx) = load_sink::<LokiConfig>(
@@ -13,26 +13,42 @@ pub mod aws_kinesis_firehose;
 pub mod aws_kinesis_streams;
 #[cfg(feature = "sinks-aws_s3")]
 pub mod aws_s3;
+#[cfg(feature = "sinks-aws_security_lake")]
+pub mod aws_security_lake;
 #[cfg(feature = "sinks-aws_sqs")]
 pub mod aws_sqs;
 #[cfg(feature = "sinks-azure_blob")]
 pub mod azure_blob;
+#[cfg(feature = "sinks-azure_data_explorer")]
+pub mod azure_data_explorer;
+#[cfg(feature = "sinks-azure_event_hubs")]
+pub mod azure_event_hubs;
 #[cfg(feature = "sinks-azure_monitor_logs")]
 pub mod azure_monitor_logs;
 #[cfg(feature = "sinks-blackhole")]
 pub mod blackhole;
+#[cfg(feature = "sinks-cassandra")]
+pub mod cassandra;
+#[cfg(feature = "sinks-chat_webhook")]
+pub mod chat_webhook;
 #[cfg(feature = "sinks-clickhouse")]
 pub mod clickhouse;
 #[cfg(feature = "sinks-console")]
 pub mod console;
+#[cfg(feature = "sinks-data_lake")]
+pub mod data_lake;
 #[cfg(feature = "sinks-datadog")]
 pub mod datadog;
+#[cfg(feature = "sinks-doris")]
+pub mod doris;
 #[cfg(feature = "sinks-elasticsearch")]
 pub mod elasticsearch;
 #[cfg(feature = "sinks-file")]
 pub mod file;
 #[cfg(feature = "sinks-gcp")]
 pub mod gcp;
+#[cfg(feature = "sinks-grpc")]
+pub mod grpc;
 #[cfg(feature = "sinks-honeycomb")]
 pub mod honeycomb;
 #[cfg(feature = "sinks-http")]
@@ -47,12 +63,22 @@ pub mod kafka;
 pub mod logdna;
 #[cfg(feature = "sinks-loki")]
 pub mod loki;
+#[cfg(feature = "sinks-mqtt")]
+pub mod mqtt;
 #[cfg(feature = "sinks-nats")]
 pub mod nats;
 #[cfg(feature = "sinks-new_relic_logs")]
 pub mod new_relic_logs;
+#[cfg(feature = "sinks-opentelemetry")]
+pub mod opentelemetry;
+#[cfg(feature = "sinks-opsgenie")]
+pub mod opsgenie;
+#[cfg(feature = "sinks-pagerduty")]
+pub mod pagerduty;
 #[cfg(feature = "sinks-papertrail")]
 pub mod papertrail;
+#[cfg(feature = "sinks-postgres")]
+pub mod postgres;
 #[cfg(feature = "sinks-prometheus")]
 pub mod prometheus;
 #[cfg(feature = "sinks-pulsar")]
@@ -61,14 +87,20 @@ pub mod pulsar;
 pub mod redis;
 #[cfg(feature = "sinks-sematext")]
 pub mod sematext;
+#[cfg(feature = "sinks-smtp")]
+pub mod smtp;
 #[cfg(feature = "sinks-socket")]
 pub mod socket;
 #[cfg(feature = "sinks-splunk_hec")]
 pub mod splunk_hec;
 #[cfg(feature = "sinks-statsd")]
 pub mod statsd;
+#[cfg(feature = "sinks-syslog")]
+pub mod syslog;
 #[cfg(feature = "sinks-vector")]
 pub mod vector;
+#[cfg(feature = "sinks-websocket_server")]
+pub mod websocket_server;
 
 pub use vector_core::sink::VectorSink;
 
@@ -0,0 +1,384 @@
+use crate::{
+    buffers::Acker,
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, EventFinalizers, EventStatus},
+    internal_events::{
+        MqttConnectionFailed, MqttEventSendFail, MqttEventSendSuccess, TemplateRenderingFailed,
+    },
+    mqtt::{MqttConnectionConfig, MqttQos},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        StreamSink,
+    },
+    template::{Template, TemplateParseError},
+};
+use async_trait::async_trait;
+use futures::{stream::BoxStream, FutureExt, StreamExt};
+use rumqttc::{AsyncClient, EventLoop, Incoming, MqttOptions, Outgoing, QoS};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("invalid topic template: {}", source))]
+    TopicTemplate { source: TemplateParseError },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MqttSinkConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    topic: String,
+    encoding: EncodingConfig<Encoding>,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default)]
+    retain: bool,
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Text,
+    Json,
+}
+
+inventory::submit! {
+    SinkDescription::new::<MqttSinkConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            port = 1883
+            topic = "vector"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SinkConfig for MqttSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let options = self.connection.options(&default_client_id())?;
+        let sink = MqttSink {
+            options: options.clone(),
+            topic: Template::try_from(self.topic.clone()).context(TopicTemplate)?,
+            encoding: self.encoding.clone(),
+            qos: self.qos.into(),
+            retain: self.retain,
+            acker: cx.acker(),
+        };
+        let healthcheck = healthcheck(options).boxed();
+        Ok((super::VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+/// Client IDs have to be unique to the broker, which disconnects the
+/// previous client of an ID when another connects.
+fn default_client_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    let hostname = crate::get_hostname().unwrap_or_else(|_| "vector".to_owned());
+    format!("vector-{}-{:x}", hostname, nanos)
+}
+
+async fn healthcheck(options: MqttOptions) -> crate::Result<()> {
+    let (client, mut eventloop) = AsyncClient::new(options, 1);
+    loop {
+        match eventloop.poll().await? {
+            rumqttc::Event::Incoming(Incoming::ConnAck(_)) => break,
+            _ => continue,
+        }
+    }
+    let _ = client.try_disconnect();
+    Ok(())
+}
+
+pub struct MqttSink {
+    options: MqttOptions,
+    topic: Template,
+    encoding: EncodingConfig<Encoding>,
+    qos: QoS,
+    retain: bool,
+    acker: Acker,
+}
+
+/// The events published and waiting for the broker. Publishes are assigned
+/// their packet IDs by the event loop in the order they were made, so the
+/// finalizers are queued until then.
+#[derive(Default)]
+struct InFlight {
+    unassigned: VecDeque<EventFinalizers>,
+    assigned: HashMap<u16, EventFinalizers>,
+}
+
+impl InFlight {
+    fn is_empty(&self) -> bool {
+        self.unassigned.is_empty() && self.assigned.is_empty()
+    }
+}
+
+#[async_trait]
+impl StreamSink for MqttSink {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let (client, eventloop) = AsyncClient::new(self.options.clone(), 100);
+        let (sender, mut notifications) = mpsc::unbounded_channel();
+        tokio::spawn(poll_eventloop(eventloop, sender));
+
+        let mut input = input.fuse();
+        let mut in_flight = InFlight::default();
+        let mut input_done = false;
+
+        while !(input_done && in_flight.is_empty()) {
+            tokio::select! {
+                event = input.next(), if !input_done => match event {
+                    None => input_done = true,
+                    Some(event) => self.publish(&client, event, &mut in_flight).await,
+                },
+                notification = notifications.recv() => match notification {
+                    Some(notification) => self.handle(notification, &mut in_flight),
+                    None => break,
+                },
+            }
+        }
+
+        let _ = client.disconnect().await;
+        Ok(())
+    }
+}
+
+impl MqttSink {
+    async fn publish(&mut self, client: &AsyncClient, mut event: Event, in_flight: &mut InFlight) {
+        let finalizers = event.metadata_mut().take_finalizers();
+        let topic = match self.topic.render_string(&event) {
+            Ok(topic) => topic,
+            Err(error) => {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some("topic"),
+                    drop_event: true,
+                });
+                finalizers.update_status(EventStatus::Failed);
+                self.acker.ack(1);
+                return;
+            }
+        };
+
+        let payload = encode_event(event, &self.encoding);
+        let byte_size = payload.len();
+        match client.publish(topic, self.qos, self.retain, payload).await {
+            Ok(()) => {
+                emit!(MqttEventSendSuccess { byte_size });
+                in_flight.unassigned.push_back(finalizers);
+            }
+            Err(error) => {
+                emit!(MqttEventSendFail {
+                    error: error.to_string()
+                });
+                finalizers.update_status(EventStatus::Errored);
+                self.acker.ack(1);
+            }
+        }
+    }
+
+    /// Events are delivered once published at QoS 0, and once acknowledged
+    /// by the broker at QoS 1.
+    fn handle(&mut self, notification: rumqttc::Event, in_flight: &mut InFlight) {
+        match notification {
+            rumqttc::Event::Outgoing(Outgoing::Publish(pkid)) => {
+                // Publishes sent again after reconnecting keep their ID.
+                if in_flight.assigned.contains_key(&pkid) {
+                    return;
+                }
+                if let Some(finalizers) = in_flight.unassigned.pop_front() {
+                    if self.qos == QoS::AtMostOnce {
+                        self.finalize(finalizers);
+                    } else {
+                        in_flight.assigned.insert(pkid, finalizers);
+                    }
+                }
+            }
+            rumqttc::Event::Incoming(Incoming::PubAck(ack)) => {
+                if let Some(finalizers) = in_flight.assigned.remove(&ack.pkid) {
+                    self.finalize(finalizers);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(&self, finalizers: EventFinalizers) {
+        finalizers.update_status(EventStatus::Delivered);
+        self.acker.ack(1);
+    }
+}
+
+/// Drives the connection, which also reconnects when polled after an error.
+async fn poll_eventloop(mut eventloop: EventLoop, sender: mpsc::UnboundedSender<rumqttc::Event>) {
+    while !sender.is_closed() {
+        match eventloop.poll().await {
+            Ok(notification) => {
+                let _ = sender.send(notification);
+            }
+            Err(error) => {
+                emit!(MqttConnectionFailed { error });
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> Vec<u8> {
+    encoding.apply_rules(&mut event);
+
+    match encoding.codec() {
+        Encoding::Json => serde_json::to_vec(event.as_log()).unwrap(),
+        Encoding::Text => event
+            .as_log()
+            .get(crate::config::log_schema().message_key())
+            .map(|v| v.as_bytes().to_vec())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSinkConfig>();
+    }
+
+    #[test]
+    fn encodes_raw_logs() {
+        let event = Event::from("foo");
+        assert_eq!(
+            b"foo".to_vec(),
+            encode_event(event, &EncodingConfig::from(Encoding::Text))
+        );
+    }
+
+    #[test]
+    fn encodes_log_events() {
+        let mut event = Event::new_empty_log();
+        let log = event.as_mut_log();
+        log.insert("x", Value::from("23"));
+        log.insert("z", Value::from(25));
+
+        let encoded = encode_event(event, &EncodingConfig::from(Encoding::Json));
+        assert_eq!(encoded, br#"{"x":"23","z":25}"#.to_vec());
+    }
+
+    #[test]
+    fn matches_acknowledgements_to_publishes() {
+        let mut sink = MqttSink {
+            options: MqttOptions::new("test", "127.0.0.1", 1883),
+            topic: Template::try_from("test").unwrap(),
+            encoding: EncodingConfig::from(Encoding::Text),
+            qos: QoS::AtLeastOnce,
+            retain: false,
+            acker: Acker::Null,
+        };
+        let mut in_flight = InFlight::default();
+        in_flight.unassigned.push_back(EventFinalizers::default());
+        in_flight.unassigned.push_back(EventFinalizers::default());
+
+        sink.handle(
+            rumqttc::Event::Outgoing(Outgoing::Publish(1)),
+            &mut in_flight,
+        );
+        sink.handle(
+            rumqttc::Event::Outgoing(Outgoing::Publish(2)),
+            &mut in_flight,
+        );
+        // Sent again after reconnecting
+        sink.handle(
+            rumqttc::Event::Outgoing(Outgoing::Publish(1)),
+            &mut in_flight,
+        );
+        assert!(in_flight.unassigned.is_empty());
+        assert_eq!(in_flight.assigned.len(), 2);
+
+        sink.handle(
+            rumqttc::Event::Incoming(Incoming::PubAck(rumqttc::PubAck::new(1))),
+            &mut in_flight,
+        );
+        sink.handle(
+            rumqttc::Event::Incoming(Incoming::PubAck(rumqttc::PubAck::new(2))),
+            &mut in_flight,
+        );
+        assert!(in_flight.is_empty());
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{random_lines_with_stream, random_string, trace_init};
+
+    #[tokio::test]
+    async fn mqtt_sink_publishes() {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config: MqttSinkConfig = toml::from_str(&format!(
+            r#"
+            host = "127.0.0.1"
+            topic = "{}"
+            encoding.codec = "text"
+            "#,
+            topic
+        ))
+        .unwrap();
+
+        let (client, mut eventloop) =
+            AsyncClient::new(MqttOptions::new(random_string(10), "127.0.0.1", 1883), 10);
+        client.subscribe(&topic, QoS::AtLeastOnce).await.unwrap();
+        let (sender, mut received) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok(notification) = eventloop.poll().await {
+                if let rumqttc::Event::Incoming(Incoming::Publish(publish)) = notification {
+                    let _ = sender.send(publish.payload);
+                }
+            }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (input, events) = random_lines_with_stream(100, 10, None);
+        sink.run(events).await.unwrap();
+
+        for line in input {
+            let payload = received.recv().await.unwrap();
+            assert_eq!(payload, line.as_bytes());
+        }
+    }
+}
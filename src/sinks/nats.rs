@@ -2,8 +2,9 @@ use crate::{
     buffers::Acker,
     config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     emit,
-    event::Event,
+    event::{Event, EventFinalizers, EventStatus},
     internal_events::{NatsEventSendFail, NatsEventSendSuccess, TemplateRenderingFailed},
+    nats,
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
         StreamSink,
@@ -11,10 +12,22 @@ use crate::{
     template::{Template, TemplateParseError},
 };
 use async_trait::async_trait;
-use futures::{stream::BoxStream, FutureExt, StreamExt, TryFutureExt};
+use futures::{
+    future, pin_mut,
+    stream::{BoxStream, FuturesOrdered},
+    FutureExt, StreamExt, TryFutureExt,
+};
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::convert::TryFrom;
+use std::{collections::HashMap, convert::TryFrom, time::Duration};
+use tokio::{sync::oneshot, time::timeout};
+
+/// The most messages published to JetStream whose acknowledgements are
+/// awaited at once.
+const MAX_PENDING_PUBLISHES: usize = 1_000;
+/// How long to wait for JetStream to acknowledge a message before the event
+/// is considered errored.
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -33,6 +46,10 @@ pub struct NatsSinkConfig {
     connection_name: String,
     subject: String,
     url: String,
+    /// Publish to JetStream, waiting for the server to acknowledge each
+    /// message before its event is considered delivered.
+    #[serde(default)]
+    jetstream: bool,
 }
 
 fn default_name() -> String {
@@ -119,6 +136,7 @@ pub struct NatsSink {
     options: NatsOptions,
     subject: Template,
     url: String,
+    jetstream: bool,
     acker: Acker,
 }
 
@@ -129,6 +147,7 @@ impl NatsSink {
             encoding: config.encoding,
             subject: Template::try_from(config.subject).context(SubjectTemplate)?,
             url: config.url,
+            jetstream: config.jetstream,
             acker,
         })
     }
@@ -152,7 +171,17 @@ impl From<&NatsSinkConfig> for NatsOptions {
 
 #[async_trait]
 impl StreamSink for NatsSink {
-    async fn run(&mut self, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
+    async fn run(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        if self.jetstream {
+            self.run_jetstream(input).await
+        } else {
+            self.run_core(input).await
+        }
+    }
+}
+
+impl NatsSink {
+    async fn run_core(&mut self, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
         let nats_options: async_nats::Options = self.options.clone().into();
 
         let nc = nats_options.connect(&self.url).await.map_err(|_| ())?;
@@ -181,7 +210,9 @@ impl StreamSink for NatsSink {
                     });
                 }
                 Err(error) => {
-                    emit!(NatsEventSendFail { error });
+                    emit!(NatsEventSendFail {
+                        error: error.into()
+                    });
                 }
             }
 
@@ -190,6 +221,126 @@ impl StreamSink for NatsSink {
 
         Ok(())
     }
+
+    /// Publish to JetStream without waiting for each acknowledgement before
+    /// publishing the next message. Replies come in on a subject of our own,
+    /// and events are acknowledged in the order they were received.
+    async fn run_jetstream(&mut self, input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let connection = nats::connect(&self.url, &self.options.connection_name)
+            .await
+            .map_err(|error| error!(message = "Failed to connect to NATS.", %error))?;
+        let inbox = connection.new_inbox();
+        let replies = connection
+            .subscribe(&format!("{}.*", inbox))
+            .await
+            .map_err(|error| error!(message = "Failed to subscribe to replies.", %error))?;
+        let replies = nats::subscription_stream(replies).fuse();
+        pin_mut!(replies);
+
+        let mut input = input.fuse();
+        // The acknowledgements awaited, by the sequence number of the reply
+        // subject of their message.
+        let mut waiting = HashMap::new();
+        let mut pending = FuturesOrdered::new();
+        let mut next_reply = 0_u64;
+
+        loop {
+            if input.is_done() && pending.is_empty() {
+                break;
+            }
+
+            tokio::select! {
+                Some(reply) = replies.next() => {
+                    let sender = reply_sequence(&reply.subject)
+                        .and_then(|sequence| waiting.remove(&sequence));
+                    if let Some(sender) = sender {
+                        let _ = sender.send(nats::parse_publish_ack(&reply.data));
+                    }
+                }
+                Some(published) = pending.next(), if !pending.is_empty() => {
+                    if let Some(Published { reply, result, finalizers, byte_size }) = published {
+                        waiting.remove(&reply);
+                        match result {
+                            Ok(()) => {
+                                finalizers.update_status(EventStatus::Delivered);
+                                emit!(NatsEventSendSuccess { byte_size });
+                            }
+                            Err(error) => {
+                                finalizers.update_status(EventStatus::Errored);
+                                emit!(NatsEventSendFail { error });
+                            }
+                        }
+                    }
+                    self.acker.ack(1);
+                }
+                Some(mut event) = input.next(), if pending.len() < MAX_PENDING_PUBLISHES => {
+                    let finalizers = event.metadata_mut().take_finalizers();
+                    let subject = match self.subject.render_string(&event) {
+                        Ok(subject) => subject,
+                        Err(error) => {
+                            emit!(TemplateRenderingFailed {
+                                error,
+                                field: Some("subject"),
+                                drop_event: true,
+                            });
+                            pending.push(future::ready(None).boxed());
+                            continue;
+                        }
+                    };
+
+                    let log = encode_event(event, &self.encoding);
+                    let byte_size = log.len();
+                    let reply = next_reply;
+                    next_reply += 1;
+
+                    let acknowledged = match connection
+                        .publish_request(&subject, &format!("{}.{}", inbox, reply), log)
+                        .await
+                    {
+                        Ok(()) => {
+                            let (sender, receiver) = oneshot::channel();
+                            waiting.insert(reply, sender);
+                            timeout(ACK_TIMEOUT, receiver)
+                                .map(|result| match result {
+                                    Ok(Ok(result)) => result.map_err(Into::into),
+                                    _ => Err(nats::JetStreamError::AckTimeout.into()),
+                                })
+                                .boxed()
+                        }
+                        Err(error) => future::ready(Err(error.into())).boxed(),
+                    };
+                    pending.push(
+                        acknowledged
+                            .map(move |result| {
+                                Some(Published {
+                                    reply,
+                                    result,
+                                    finalizers,
+                                    byte_size,
+                                })
+                            })
+                            .boxed(),
+                    );
+                }
+                else => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The outcome of publishing the message of an event to JetStream.
+struct Published {
+    reply: u64,
+    result: crate::Result<()>,
+    finalizers: EventFinalizers,
+    byte_size: usize,
+}
+
+/// The sequence number at the end of a reply subject.
+fn reply_sequence(subject: &str) -> Option<u64> {
+    subject.rsplit('.').next()?.parse().ok()
 }
 
 fn encode_event(mut event: Event, encoding: &EncodingConfig<Encoding>) -> String {
@@ -243,7 +394,10 @@ mod tests {
 #[cfg(test)]
 mod integration_tests {
     use super::*;
-    use crate::test_util::{random_lines_with_stream, random_string, trace_init};
+    use crate::{
+        event::{BatchNotifier, BatchStatus},
+        test_util::{random_lines_with_stream, random_string, trace_init},
+    };
     use std::{thread, time::Duration};
 
     #[tokio::test]
@@ -262,6 +416,7 @@ mod integration_tests {
             connection_name: "".to_owned(),
             subject: subject.clone(),
             url: "nats://127.0.0.1:4222".to_owned(),
+            jetstream: false,
         };
 
         // Establish the consumer subscription.
@@ -293,4 +448,51 @@ mod integration_tests {
             num_events
         );
     }
+
+    #[tokio::test]
+    async fn nats_jetstream_happy() {
+        trace_init();
+
+        let subject = format!("test-{}", random_string(10));
+
+        let cnf = NatsSinkConfig {
+            encoding: EncodingConfig::from(Encoding::Text),
+            connection_name: "".to_owned(),
+            subject: subject.clone(),
+            url: "nats://127.0.0.1:4222".to_owned(),
+            jetstream: true,
+        };
+
+        let nc = cnf.connect().await.unwrap();
+        let stream = serde_json::json!({
+            "name": subject,
+            "subjects": [subject],
+        });
+        nc.request(
+            &format!("$JS.API.STREAM.CREATE.{}", subject),
+            stream.to_string(),
+        )
+        .await
+        .unwrap();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let mut sink = NatsSink::new(cnf.clone(), acker).unwrap();
+        let num_events = 1_000;
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let (_, events) = random_lines_with_stream(100, num_events, Some(batch));
+
+        sink.run(Box::pin(events)).await.unwrap();
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert_eq!(
+            ack_counter.load(std::sync::atomic::Ordering::Relaxed),
+            num_events
+        );
+
+        let info = nc
+            .request(&format!("$JS.API.STREAM.INFO.{}", subject), "")
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_slice(&info.data).unwrap();
+        assert_eq!(info["state"]["messages"], num_events);
+    }
 }
@@ -155,7 +155,7 @@ impl NewRelicLogsConfig {
 
         Ok(HttpSinkConfig {
             uri: uri.into(),
-            method: Some(HttpMethod::Post),
+            method: Some(HttpMethod::Post.into()),
             auth: None,
             headers: None,
             compression: self.compression,
@@ -163,6 +163,7 @@ impl NewRelicLogsConfig {
             batch,
             request,
             tls: None,
+            dead_letter_uri: None,
         })
     }
 }
@@ -211,10 +212,10 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            format!("{}", http_config.uri),
             "https://log-api.newrelic.com/log/v1".to_string()
         );
-        assert_eq!(http_config.method, Some(HttpMethod::Post));
+        assert_eq!(http_config.method, Some(HttpMethod::Post.into()));
         assert_eq!(http_config.encoding.codec(), &Encoding::Json.into());
         assert_eq!(http_config.batch.max_bytes, Some(MAX_PAYLOAD_SIZE));
         assert_eq!(
@@ -245,10 +246,10 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            format!("{}", http_config.uri),
             "https://log-api.eu.newrelic.com/log/v1".to_string()
         );
-        assert_eq!(http_config.method, Some(HttpMethod::Post));
+        assert_eq!(http_config.method, Some(HttpMethod::Post.into()));
         assert_eq!(http_config.encoding.codec(), &Encoding::Json.into());
         assert_eq!(http_config.batch.max_bytes, Some(MAX_PAYLOAD_SIZE));
         assert_eq!(
@@ -283,10 +284,10 @@ mod tests {
         let http_config = nr_config.create_config().unwrap();
 
         assert_eq!(
-            format!("{}", http_config.uri.uri),
+            format!("{}", http_config.uri),
             "https://log-api.eu.newrelic.com/log/v1".to_string()
         );
-        assert_eq!(http_config.method, Some(HttpMethod::Post));
+        assert_eq!(http_config.method, Some(HttpMethod::Post.into()));
         assert_eq!(http_config.encoding.codec(), &Encoding::Json.into());
         assert_eq!(http_config.batch.max_bytes, Some(838860));
         assert_eq!(
@@ -0,0 +1,558 @@
+//! Mapping of Vector's events onto OTLP, the reverse of what the
+//! `opentelemetry` source does.
+
+use super::ResourceConfig;
+use crate::{
+    event::{
+        metric::{Bucket, MetricTags, TemporalityConverter},
+        otlp::{self, LogRecord},
+        trace::fields,
+        Event, LogEvent, Metric, MetricKind, MetricValue, TraceEvent, Value,
+    },
+    internal_events::OpentelemetryEventUnsupported,
+    proto::opentelemetry::proto::{
+        common::v1::{any_value, AnyValue, ArrayValue, KeyValue, KeyValueList},
+        logs::v1::{InstrumentationLibraryLogs, LogRecord as OtlpLogRecord, ResourceLogs},
+        metrics::v1::{
+            metric::Data, number_data_point, summary_data_point::ValueAtQuantile,
+            AggregationTemporality, Gauge, Histogram, HistogramDataPoint,
+            InstrumentationLibraryMetrics, Metric as OtlpMetric, NumberDataPoint, ResourceMetrics,
+            Sum, Summary, SummaryDataPoint,
+        },
+        resource::v1::Resource,
+        trace::v1::{
+            span::{Event as SpanEvent, Link, SpanKind},
+            status::StatusCode,
+            InstrumentationLibrarySpans, ResourceSpans, Span, Status,
+        },
+    },
+};
+use prost::Message;
+use std::collections::BTreeMap;
+
+/// An event encoded as OTLP, wrapped in its resource so that a batch can be
+/// regrouped by resource when it is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Record {
+    Logs(ResourceLogs),
+    Metrics(ResourceMetrics),
+    Spans(ResourceSpans),
+}
+
+impl Record {
+    pub(super) fn encoded_len(&self) -> usize {
+        match self {
+            Self::Logs(logs) => logs.encoded_len(),
+            Self::Metrics(metrics) => metrics.encoded_len(),
+            Self::Spans(spans) => spans.encoded_len(),
+        }
+    }
+}
+
+pub(super) struct Encoder {
+    resource: ResourceConfig,
+    /// OTLP gauges are always absolute, so incremental gauges are added up.
+    gauges: TemporalityConverter,
+}
+
+impl Encoder {
+    pub(super) fn new(resource: ResourceConfig) -> Self {
+        Self {
+            resource,
+            gauges: TemporalityConverter::new(MetricKind::Absolute),
+        }
+    }
+
+    pub(super) fn encode(&mut self, event: Event) -> Option<Record> {
+        match event {
+            Event::Log(log) => Some(Record::Logs(self.encode_log(log))),
+            Event::Metric(metric) => self.encode_metric(metric).map(Record::Metrics),
+            Event::Trace(trace) => Some(Record::Spans(self.encode_span(trace))),
+        }
+    }
+
+    fn encode_log(&self, mut log: LogEvent) -> ResourceLogs {
+        let resource = self.resource(log.remove(&self.resource.field));
+        let record = LogRecord::from(log);
+        ResourceLogs {
+            resource,
+            instrumentation_library_logs: vec![InstrumentationLibraryLogs {
+                instrumentation_library: None,
+                logs: vec![OtlpLogRecord {
+                    time_unix_nano: record.time_unix_nano,
+                    severity_number: record.severity_number,
+                    severity_text: record.severity_text,
+                    name: record.name,
+                    body: record.body.map(encode_any_value),
+                    attributes: encode_attributes(record.attributes),
+                    dropped_attributes_count: record.dropped_attributes_count,
+                    flags: record.flags,
+                    trace_id: record.trace_id,
+                    span_id: record.span_id,
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }
+    }
+
+    fn encode_metric(&mut self, metric: Metric) -> Option<ResourceMetrics> {
+        let metric = match metric.value() {
+            MetricValue::Gauge { .. } => self.gauges.convert(metric)?,
+            _ => metric,
+        };
+
+        let name = match metric.namespace() {
+            Some(namespace) => format!("{}.{}", namespace, metric.name()),
+            None => metric.name().to_owned(),
+        };
+        let mut tags = metric.tags().cloned().unwrap_or_default();
+        let resource_tags = self
+            .resource
+            .tags
+            .iter()
+            .filter_map(|tag| Some((tag.clone(), Value::from(tags.remove(tag)?))))
+            .collect();
+        let attributes = encode_tags(tags);
+        let time_unix_nano = metric.timestamp().map_or(0, otlp::timestamp_to_nanos);
+        let temporality = match metric.kind() {
+            MetricKind::Incremental => AggregationTemporality::Delta,
+            MetricKind::Absolute => AggregationTemporality::Cumulative,
+        } as i32;
+        let number = |value: f64| NumberDataPoint {
+            attributes: attributes.clone(),
+            time_unix_nano,
+            value: Some(number_data_point::Value::AsDouble(value)),
+            ..Default::default()
+        };
+
+        let data = match metric.value() {
+            MetricValue::Counter { value } => Data::Sum(Sum {
+                data_points: vec![number(*value)],
+                aggregation_temporality: temporality,
+                is_monotonic: true,
+            }),
+            MetricValue::Gauge { value } => Data::Gauge(Gauge {
+                data_points: vec![number(*value)],
+            }),
+            MetricValue::Set { values } => Data::Gauge(Gauge {
+                data_points: vec![number(values.len() as f64)],
+            }),
+            MetricValue::AggregatedHistogram {
+                buckets,
+                count,
+                sum,
+            } => Data::Histogram(Histogram {
+                data_points: vec![HistogramDataPoint {
+                    attributes: attributes.clone(),
+                    time_unix_nano,
+                    count: u64::from(*count),
+                    sum: *sum,
+                    explicit_bounds: buckets
+                        .iter()
+                        .map(|bucket| bucket.upper_limit)
+                        .filter(|bound| bound.is_finite())
+                        .collect(),
+                    bucket_counts: bucket_counts(buckets, *count),
+                    ..Default::default()
+                }],
+                aggregation_temporality: temporality,
+            }),
+            MetricValue::AggregatedSummary {
+                quantiles,
+                count,
+                sum,
+            } => Data::Summary(Summary {
+                data_points: vec![SummaryDataPoint {
+                    attributes: attributes.clone(),
+                    time_unix_nano,
+                    count: u64::from(*count),
+                    sum: *sum,
+                    quantile_values: quantiles
+                        .iter()
+                        .map(|quantile| ValueAtQuantile {
+                            quantile: quantile.upper_limit,
+                            value: quantile.value,
+                        })
+                        .collect(),
+                    ..Default::default()
+                }],
+            }),
+            MetricValue::Distribution { .. } => {
+                emit!(OpentelemetryEventUnsupported {
+                    kind: "distribution"
+                });
+                return None;
+            }
+            MetricValue::Sketch { .. } => {
+                emit!(OpentelemetryEventUnsupported { kind: "sketch" });
+                return None;
+            }
+        };
+
+        Some(ResourceMetrics {
+            resource: self.resource(Some(Value::Map(resource_tags))),
+            instrumentation_library_metrics: vec![InstrumentationLibraryMetrics {
+                instrumentation_library: None,
+                metrics: vec![OtlpMetric {
+                    name,
+                    data: Some(data),
+                    ..Default::default()
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        })
+    }
+
+    fn encode_span(&self, trace: TraceEvent) -> ResourceSpans {
+        let mut fields = trace.into_parts().0;
+        let resource = self.resource(fields.remove(&self.resource.field));
+
+        let span = Span {
+            trace_id: take_id(&mut fields, fields::TRACE_ID),
+            span_id: take_id(&mut fields, fields::SPAN_ID),
+            trace_state: take_string(&mut fields, TRACE_STATE),
+            parent_span_id: take_id(&mut fields, fields::PARENT_SPAN_ID),
+            name: take_string(&mut fields, fields::NAME),
+            kind: span_kind(&take_string(&mut fields, fields::KIND)) as i32,
+            start_time_unix_nano: take_time(&mut fields, fields::START_TIME),
+            end_time_unix_nano: take_time(&mut fields, fields::END_TIME),
+            attributes: encode_attributes(take_map(&mut fields, fields::ATTRIBUTES)),
+            events: take_array(&mut fields, fields::EVENTS)
+                .map(|mut event| SpanEvent {
+                    time_unix_nano: take_time(&mut event, "time"),
+                    name: take_string(&mut event, "name"),
+                    attributes: encode_attributes(take_map(&mut event, "attributes")),
+                    ..Default::default()
+                })
+                .collect(),
+            links: take_array(&mut fields, fields::LINKS)
+                .map(|mut link| Link {
+                    trace_id: take_id(&mut link, fields::TRACE_ID),
+                    span_id: take_id(&mut link, fields::SPAN_ID),
+                    trace_state: take_string(&mut link, TRACE_STATE),
+                    attributes: encode_attributes(take_map(&mut link, "attributes")),
+                    ..Default::default()
+                })
+                .collect(),
+            status: match fields.remove(fields::STATUS) {
+                Some(Value::Map(mut status)) => Some(Status {
+                    message: take_string(&mut status, "message"),
+                    code: status_code(&take_string(&mut status, "code")) as i32,
+                }),
+                _ => None,
+            },
+            ..Default::default()
+        };
+
+        ResourceSpans {
+            resource,
+            instrumentation_library_spans: vec![InstrumentationLibrarySpans {
+                instrumentation_library: None,
+                spans: vec![span],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }
+    }
+
+    /// The resource of an event with the given resource attributes, along
+    /// with the attributes configured for all resources.
+    fn resource(&self, attributes: Option<Value>) -> Option<Resource> {
+        let mut attributes = match attributes {
+            Some(Value::Map(attributes)) => attributes,
+            _ => BTreeMap::new(),
+        };
+        for (key, value) in &self.resource.attributes {
+            attributes
+                .entry(key.clone())
+                .or_insert_with(|| Value::from(value.as_str()));
+        }
+
+        if attributes.is_empty() {
+            None
+        } else {
+            Some(Resource {
+                attributes: encode_attributes(attributes),
+                dropped_attributes_count: 0,
+            })
+        }
+    }
+}
+
+const TRACE_STATE: &str = "trace_state";
+
+/// OTLP counts values above the largest bound in a bucket of their own, which
+/// Vector's histograms may leave out.
+fn bucket_counts(buckets: &[Bucket], count: u32) -> Vec<u64> {
+    let mut counts = buckets
+        .iter()
+        .map(|bucket| u64::from(bucket.count))
+        .collect::<Vec<_>>();
+    if buckets
+        .last()
+        .map_or(true, |bucket| bucket.upper_limit.is_finite())
+    {
+        let counted = counts.iter().sum::<u64>();
+        counts.push(u64::from(count).saturating_sub(counted));
+    }
+    counts
+}
+
+fn span_kind(kind: &str) -> SpanKind {
+    match kind {
+        "internal" => SpanKind::Internal,
+        "server" => SpanKind::Server,
+        "client" => SpanKind::Client,
+        "producer" => SpanKind::Producer,
+        "consumer" => SpanKind::Consumer,
+        _ => SpanKind::Unspecified,
+    }
+}
+
+fn status_code(code: &str) -> StatusCode {
+    match code {
+        "ok" => StatusCode::Ok,
+        "error" => StatusCode::Error,
+        _ => StatusCode::Unset,
+    }
+}
+
+fn take_string(fields: &mut BTreeMap<String, Value>, key: &str) -> String {
+    match fields.remove(key) {
+        Some(Value::Bytes(bytes) | Value::String(bytes)) => {
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+fn take_id(fields: &mut BTreeMap<String, Value>, key: &str) -> Vec<u8> {
+    otlp::decode_hex(&take_string(fields, key)).unwrap_or_default()
+}
+
+fn take_time(fields: &mut BTreeMap<String, Value>, key: &str) -> u64 {
+    match fields.remove(key) {
+        Some(Value::Timestamp(timestamp)) => otlp::timestamp_to_nanos(timestamp),
+        _ => 0,
+    }
+}
+
+fn take_map(fields: &mut BTreeMap<String, Value>, key: &str) -> BTreeMap<String, Value> {
+    match fields.remove(key) {
+        Some(Value::Map(map)) => map,
+        _ => BTreeMap::new(),
+    }
+}
+
+fn take_array(
+    fields: &mut BTreeMap<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = BTreeMap<String, Value>> {
+    let values = match fields.remove(key) {
+        Some(Value::Array(values)) => values,
+        _ => Vec::new(),
+    };
+    values.into_iter().filter_map(|value| match value {
+        Value::Map(map) => Some(map),
+        _ => None,
+    })
+}
+
+fn encode_tags(tags: MetricTags) -> Vec<KeyValue> {
+    tags.into_iter()
+        .map(|(key, value)| KeyValue {
+            key,
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value)),
+            }),
+        })
+        .collect()
+}
+
+fn encode_attributes(attributes: BTreeMap<String, Value>) -> Vec<KeyValue> {
+    attributes
+        .into_iter()
+        .map(|(key, value)| KeyValue {
+            key,
+            value: Some(encode_any_value(value)),
+        })
+        .collect()
+}
+
+fn encode_any_value(value: Value) -> AnyValue {
+    let value = match value {
+        Value::String(bytes) => Some(any_value::Value::StringValue(
+            String::from_utf8_lossy(&bytes).into_owned(),
+        )),
+        Value::Bytes(bytes) => Some(any_value::Value::BytesValue(bytes.to_vec())),
+        Value::Integer(integer) => Some(any_value::Value::IntValue(integer)),
+        Value::Float(float) => Some(any_value::Value::DoubleValue(float)),
+        Value::Boolean(boolean) => Some(any_value::Value::BoolValue(boolean)),
+        Value::Timestamp(timestamp) => Some(any_value::Value::StringValue(
+            timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        )),
+        Value::Map(map) => Some(any_value::Value::KvlistValue(KeyValueList {
+            values: encode_attributes(map),
+        })),
+        Value::Array(array) => Some(any_value::Value::ArrayValue(ArrayValue {
+            values: array.into_iter().map(encode_any_value).collect(),
+        })),
+        Value::Null => None,
+    };
+    AnyValue { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::log_schema;
+    use chrono::{TimeZone, Utc};
+
+    fn encoder() -> Encoder {
+        let mut resource = ResourceConfig::default();
+        resource.tags.push("host".to_owned());
+        resource
+            .attributes
+            .insert("service.name".to_owned(), "vector".to_owned());
+        Encoder::new(resource)
+    }
+
+    fn string(value: &str) -> Option<AnyValue> {
+        Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_owned())),
+        })
+    }
+
+    #[test]
+    fn encodes_logs() {
+        let mut log = LogEvent::from("hello");
+        log.insert("attributes.http\\.method", "GET");
+        log.insert("resources.host\\.name", "box");
+        log.insert(otlp::SPAN_ID, "0101010101010101");
+
+        let logs = match encoder().encode(Event::Log(log)) {
+            Some(Record::Logs(logs)) => logs,
+            record => panic!("expected logs, got {:?}", record),
+        };
+
+        let resource = logs.resource.unwrap();
+        assert_eq!(resource.attributes.len(), 2);
+        assert_eq!(resource.attributes[0].key, "host.name");
+        assert_eq!(resource.attributes[1].value, string("vector"));
+        let record = &logs.instrumentation_library_logs[0].logs[0];
+        assert_eq!(record.body, string("hello"));
+        assert_eq!(record.span_id, vec![1; 8]);
+        assert_eq!(record.attributes[0].key, "http.method");
+        assert_ne!(record.time_unix_nano, 0);
+        assert!(!record
+            .attributes
+            .iter()
+            .any(|attribute| attribute.key == log_schema().message_key()));
+    }
+
+    #[test]
+    fn encodes_metrics() {
+        let mut encoder = encoder();
+        let mut tags = MetricTags::new();
+        tags.insert("host".to_owned(), "a".to_owned());
+        tags.insert("code".to_owned(), "200".to_owned());
+        let counter = Metric::new(
+            "requests",
+            MetricKind::Incremental,
+            MetricValue::Counter { value: 2.0 },
+        )
+        .with_tags(Some(tags));
+
+        let metrics = match encoder.encode(Event::Metric(counter)) {
+            Some(Record::Metrics(metrics)) => metrics,
+            record => panic!("expected metrics, got {:?}", record),
+        };
+        let resource = metrics.resource.unwrap();
+        assert_eq!(resource.attributes[0].key, "host");
+        let metric = &metrics.instrumentation_library_metrics[0].metrics[0];
+        let sum = match &metric.data {
+            Some(Data::Sum(sum)) => sum,
+            data => panic!("expected a sum, got {:?}", data),
+        };
+        assert!(sum.is_monotonic);
+        assert_eq!(
+            sum.aggregation_temporality,
+            AggregationTemporality::Delta as i32
+        );
+        assert_eq!(sum.data_points[0].attributes.len(), 1);
+        assert_eq!(sum.data_points[0].attributes[0].key, "code");
+
+        let histogram = Metric::new(
+            "latency",
+            MetricKind::Absolute,
+            MetricValue::AggregatedHistogram {
+                buckets: vector_core::buckets![1.0 => 1, 5.0 => 2],
+                count: 4,
+                sum: 12.0,
+            },
+        );
+        let metrics = match encoder.encode(Event::Metric(histogram)) {
+            Some(Record::Metrics(metrics)) => metrics,
+            record => panic!("expected metrics, got {:?}", record),
+        };
+        match &metrics.instrumentation_library_metrics[0].metrics[0].data {
+            Some(Data::Histogram(histogram)) => {
+                assert_eq!(histogram.data_points[0].explicit_bounds, vec![1.0, 5.0]);
+                assert_eq!(histogram.data_points[0].bucket_counts, vec![1, 2, 1]);
+            }
+            data => panic!("expected a histogram, got {:?}", data),
+        }
+    }
+
+    #[test]
+    fn adds_up_incremental_gauges() {
+        let mut encoder = encoder();
+        let gauge = |value| {
+            Event::Metric(Metric::new(
+                "load",
+                MetricKind::Incremental,
+                MetricValue::Gauge { value },
+            ))
+        };
+
+        encoder.encode(gauge(2.0));
+        let metrics = match encoder.encode(gauge(-0.5)) {
+            Some(Record::Metrics(metrics)) => metrics,
+            record => panic!("expected metrics, got {:?}", record),
+        };
+        match &metrics.instrumentation_library_metrics[0].metrics[0].data {
+            Some(Data::Gauge(gauge)) => assert_eq!(
+                gauge.data_points[0].value,
+                Some(number_data_point::Value::AsDouble(1.5))
+            ),
+            data => panic!("expected a gauge, got {:?}", data),
+        }
+    }
+
+    #[test]
+    fn encodes_spans() {
+        let mut trace = TraceEvent::default();
+        trace.insert(fields::TRACE_ID, "ab".repeat(16));
+        trace.insert(fields::SPAN_ID, "cd".repeat(8));
+        trace.insert(fields::NAME, "GET /cart");
+        trace.insert(fields::KIND, "server");
+        trace.insert(fields::START_TIME, Utc.timestamp(1_628_000_000, 0));
+        trace.insert("status.code", "error");
+        trace.insert("attributes.retries", 2);
+
+        let spans = match encoder().encode(Event::Trace(trace)) {
+            Some(Record::Spans(spans)) => spans,
+            record => panic!("expected spans, got {:?}", record),
+        };
+        let span = &spans.instrumentation_library_spans[0].spans[0];
+        assert_eq!(span.trace_id, vec![0xab; 16]);
+        assert_eq!(span.name, "GET /cart");
+        assert_eq!(span.kind, SpanKind::Server as i32);
+        assert_eq!(span.start_time_unix_nano, 1_628_000_000_000_000_000);
+        assert_eq!(span.end_time_unix_nano, 0);
+        assert_eq!(span.status.as_ref().unwrap().code, StatusCode::Error as i32);
+        assert_eq!(span.attributes[0].key, "retries");
+    }
+}
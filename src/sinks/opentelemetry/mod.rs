@@ -0,0 +1,243 @@
+mod encode;
+mod service;
+
+use self::{
+    encode::{Encoder, Record},
+    service::{default_http, HyperSvc, OpentelemetryRetryLogic, OpentelemetryService},
+};
+use crate::{
+    config::{
+        DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription, SinkHealthcheckOptions,
+    },
+    event::Event,
+    sinks::util::{
+        BatchConfig, BatchSettings, BatchSink, Compression, EncodedEvent, EncodedLength,
+        ServiceBuilderExt, TowerRequestConfig, VecBuffer,
+    },
+    sinks::{Healthcheck, VectorSink},
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use futures::{stream, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tower::ServiceBuilder;
+
+/// Exports logs, metrics and traces to an OTLP/gRPC endpoint.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpentelemetryConfig {
+    address: String,
+    #[serde(default)]
+    compression: Compression,
+    #[serde(default)]
+    resource: ResourceConfig,
+    #[serde(default)]
+    batch: BatchConfig,
+    #[serde(default)]
+    request: TowerRequestConfig,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+}
+
+/// Where the attributes of the OTLP resource of each event come from.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceConfig {
+    /// The field of logs and spans holding their resource attributes, which
+    /// is where the `opentelemetry` source puts them.
+    #[serde(default = "default_resource_field")]
+    field: String,
+    /// Metric tags to send as resource attributes rather than as attributes
+    /// of the data point.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Attributes added to the resource of every event, unless the event
+    /// sets them itself.
+    #[serde(default)]
+    attributes: BTreeMap<String, String>,
+}
+
+fn default_resource_field() -> String {
+    "resources".to_owned()
+}
+
+impl Default for ResourceConfig {
+    fn default() -> Self {
+        Self {
+            field: default_resource_field(),
+            tags: Vec::new(),
+            attributes: BTreeMap::new(),
+        }
+    }
+}
+
+inventory::submit! {
+    SinkDescription::new::<OpentelemetryConfig>("opentelemetry")
+}
+
+impl GenerateConfig for OpentelemetryConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            address: "http://127.0.0.1:4317".to_owned(),
+            compression: Compression::None,
+            resource: ResourceConfig::default(),
+            batch: BatchConfig::default(),
+            request: TowerRequestConfig::default(),
+            tls: None,
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "opentelemetry")]
+impl SinkConfig for OpentelemetryConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        let tls = MaybeTlsSettings::from_config(&self.tls, false)?;
+        let uri = default_http(&self.address)?;
+
+        let healthcheck_uri = cx
+            .healthcheck
+            .uri
+            .clone()
+            .map(|uri| uri.uri)
+            .unwrap_or_else(|| uri.clone());
+        let healthcheck = healthcheck(
+            OpentelemetryService::new(HyperSvc::new(healthcheck_uri, &tls, self.compression)?),
+            cx.healthcheck.clone(),
+        );
+
+        let service = OpentelemetryService::new(HyperSvc::new(uri, &tls, self.compression)?);
+        let request = self.request.unwrap_with(&TowerRequestConfig::default());
+        let batch = BatchSettings::default()
+            .events(1000)
+            .timeout(1)
+            .parse_config(self.batch)?;
+
+        let svc = ServiceBuilder::new()
+            .settings(request, OpentelemetryRetryLogic)
+            .service(service);
+
+        let mut encoder = Encoder::new(self.resource.clone());
+        let buffer = VecBuffer::new(batch.size);
+        let sink = BatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .sink_map_err(|error| error!(message = "Fatal OpenTelemetry sink error.", %error))
+            .with_flat_map(move |mut event: Event| {
+                let finalizers = event.metadata_mut().take_finalizers();
+                let encoded = encoder
+                    .encode(event)
+                    .map(|item| EncodedEvent { item, finalizers });
+                stream::iter(encoded).map(Ok)
+            });
+
+        Ok((VectorSink::Sink(Box::new(sink)), Box::pin(healthcheck)))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "opentelemetry"
+    }
+}
+
+/// OTLP has no health check, so export an empty batch of logs, which
+/// receivers accept without effect.
+async fn healthcheck(
+    mut service: OpentelemetryService,
+    options: SinkHealthcheckOptions,
+) -> crate::Result<()> {
+    if !options.enabled {
+        return Ok(());
+    }
+
+    service.export_logs(Vec::new()).await?;
+    Ok(())
+}
+
+impl EncodedLength for Record {
+    fn encoded_length(&self) -> usize {
+        self.encoded_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::SinkContext,
+        event::{BatchNotifier, BatchStatus, LogEvent},
+        proto::opentelemetry::ExportLogsServiceRequest,
+        sinks::util::test::build_test_server_status,
+        test_util::next_addr,
+    };
+    use http::StatusCode;
+    use prost::Message;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<OpentelemetryConfig>();
+    }
+
+    #[tokio::test]
+    async fn exports_logs() {
+        let address = next_addr();
+        let config: OpentelemetryConfig = toml::from_str(&format!(
+            r#"
+            address = "http://{}/"
+            resource.attributes."service.name" = "vector"
+            "#,
+            address
+        ))
+        .unwrap();
+
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (rx, trigger, server) = build_test_server_status(address, StatusCode::OK);
+        tokio::spawn(server);
+
+        let (batch, _receiver) = BatchNotifier::new_with_receiver();
+        let events = (0..3)
+            .map(|i| Event::from(LogEvent::from(format!("line {}", i)).with_batch_notifier(&batch)))
+            .collect::<Vec<_>>();
+        drop(batch);
+        sink.run(stream::iter(events)).await.unwrap();
+        drop(trigger);
+
+        let requests = rx.collect::<Vec<_>>().await;
+        assert_eq!(requests.len(), 1);
+        let (parts, body) = &requests[0];
+        assert_eq!(
+            parts.uri.path(),
+            "/opentelemetry.proto.collector.logs.v1.LogsService/Export"
+        );
+        // Skip the uncompressed flag and length of the gRPC message.
+        let request = ExportLogsServiceRequest::decode(body.slice(5..)).unwrap();
+        assert_eq!(request.resource_logs.len(), 1);
+        let resource_logs = &request.resource_logs[0];
+        assert_eq!(
+            resource_logs.resource.as_ref().unwrap().attributes[0].key,
+            "service.name"
+        );
+        assert_eq!(resource_logs.instrumentation_library_logs[0].logs.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn acknowledges_error() {
+        let address = next_addr();
+        let config: OpentelemetryConfig =
+            toml::from_str(&format!(r#"address = "http://{}/""#, address)).unwrap();
+
+        let (sink, _) = config.build(SinkContext::new_test()).await.unwrap();
+        let (_, trigger, server) = build_test_server_status(address, StatusCode::FORBIDDEN);
+        tokio::spawn(server);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let event = Event::from(LogEvent::from("line").with_batch_notifier(&batch));
+        drop(batch);
+        sink.run(stream::once(async { event })).await.unwrap();
+        drop(trigger);
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Errored));
+    }
+}
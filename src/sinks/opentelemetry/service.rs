@@ -0,0 +1,373 @@
+use super::encode::Record;
+use crate::{
+    proto::opentelemetry::{
+        proto::{logs::v1::ResourceLogs, metrics::v1::ResourceMetrics, trace::v1::ResourceSpans},
+        ExportLogsServiceRequest, ExportMetricsServiceRequest, ExportTraceServiceRequest,
+        LogsServiceClient, MetricsServiceClient, TraceServiceClient,
+    },
+    sinks::util::{buffer::compression::GZIP_DEFAULT, retries::RetryLogic, Compression},
+    tls::{tls_connector_builder, MaybeTlsSettings},
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use flate2::write::GzEncoder;
+use futures::future::BoxFuture;
+use http::{header::HeaderValue, uri::Uri};
+use hyper::client::HttpConnector;
+use hyper_openssl::HttpsConnector;
+use snafu::Snafu;
+use std::{
+    io::Write,
+    task::{Context, Poll},
+};
+use tonic::body::BoxBody;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Request failed: {}", source))]
+    Request { source: tonic::Status },
+}
+
+/// Sends batches of records, as one export request per signal.
+#[derive(Clone)]
+pub(super) struct OpentelemetryService {
+    logs: LogsServiceClient<HyperSvc>,
+    metrics: MetricsServiceClient<HyperSvc>,
+    traces: TraceServiceClient<HyperSvc>,
+}
+
+impl OpentelemetryService {
+    pub(super) fn new(client: HyperSvc) -> Self {
+        Self {
+            logs: LogsServiceClient::new(client.clone()),
+            metrics: MetricsServiceClient::new(client.clone()),
+            traces: TraceServiceClient::new(client),
+        }
+    }
+
+    pub(super) async fn export_logs(
+        &mut self,
+        resource_logs: Vec<ResourceLogs>,
+    ) -> Result<(), Error> {
+        self.logs
+            .export(ExportLogsServiceRequest { resource_logs })
+            .await
+            .map(drop)
+            .map_err(|source| Error::Request { source })
+    }
+
+    async fn export_metrics(
+        &mut self,
+        resource_metrics: Vec<ResourceMetrics>,
+    ) -> Result<(), Error> {
+        self.metrics
+            .export(ExportMetricsServiceRequest { resource_metrics })
+            .await
+            .map(drop)
+            .map_err(|source| Error::Request { source })
+    }
+
+    async fn export_spans(&mut self, resource_spans: Vec<ResourceSpans>) -> Result<(), Error> {
+        self.traces
+            .export(ExportTraceServiceRequest { resource_spans })
+            .await
+            .map(drop)
+            .map_err(|source| Error::Request { source })
+    }
+}
+
+impl tower::Service<Vec<Record>> for OpentelemetryService {
+    type Response = ();
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // As in the `vector` sink, readiness of the clients is awaited by the
+        // export calls themselves.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, records: Vec<Record>) -> Self::Future {
+        let mut service = self.clone();
+        let (logs, metrics, spans) = group(records);
+
+        Box::pin(async move {
+            if !logs.is_empty() {
+                service.export_logs(logs).await?;
+            }
+            if !metrics.is_empty() {
+                service.export_metrics(metrics).await?;
+            }
+            if !spans.is_empty() {
+                service.export_spans(spans).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Split records by signal, merging those from the same resource. Records
+/// come from the encoder, which wraps each in a single instrumentation
+/// library, so merging only has to extend the first library.
+fn group(records: Vec<Record>) -> (Vec<ResourceLogs>, Vec<ResourceMetrics>, Vec<ResourceSpans>) {
+    let mut logs: Vec<ResourceLogs> = Vec::new();
+    let mut metrics: Vec<ResourceMetrics> = Vec::new();
+    let mut spans: Vec<ResourceSpans> = Vec::new();
+
+    for record in records {
+        match record {
+            Record::Logs(record) => {
+                match logs
+                    .iter_mut()
+                    .find(|logs| logs.resource == record.resource)
+                {
+                    Some(logs) => logs.instrumentation_library_logs[0].logs.extend(
+                        record
+                            .instrumentation_library_logs
+                            .into_iter()
+                            .flat_map(|library| library.logs),
+                    ),
+                    None => logs.push(record),
+                }
+            }
+            Record::Metrics(record) => {
+                match metrics
+                    .iter_mut()
+                    .find(|metrics| metrics.resource == record.resource)
+                {
+                    Some(metrics) => metrics.instrumentation_library_metrics[0].metrics.extend(
+                        record
+                            .instrumentation_library_metrics
+                            .into_iter()
+                            .flat_map(|library| library.metrics),
+                    ),
+                    None => metrics.push(record),
+                }
+            }
+            Record::Spans(record) => {
+                match spans
+                    .iter_mut()
+                    .find(|spans| spans.resource == record.resource)
+                {
+                    Some(spans) => spans.instrumentation_library_spans[0].spans.extend(
+                        record
+                            .instrumentation_library_spans
+                            .into_iter()
+                            .flat_map(|library| library.spans),
+                    ),
+                    None => spans.push(record),
+                }
+            }
+        }
+    }
+
+    (logs, metrics, spans)
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct OpentelemetryRetryLogic;
+
+impl RetryLogic for OpentelemetryRetryLogic {
+    type Error = Error;
+    type Response = ();
+
+    fn is_retriable_error(&self, err: &Self::Error) -> bool {
+        // The OTLP specification lists the codes a client should retry on.
+        match err {
+            Error::Request { source } => matches!(
+                source.code(),
+                tonic::Code::Cancelled
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::ResourceExhausted
+                    | tonic::Code::Aborted
+                    | tonic::Code::OutOfRange
+                    | tonic::Code::Unavailable
+                    | tonic::Code::DataLoss
+            ),
+        }
+    }
+}
+
+/// grpc doesn't like an address without a scheme, so we default to http if one isn't specified in
+/// the address.
+pub(super) fn default_http(address: &str) -> crate::Result<Uri> {
+    let uri: Uri = address.parse()?;
+    if uri.scheme().is_none() {
+        let mut parts = uri.into_parts();
+        parts.scheme = Some(
+            "http"
+                .parse()
+                .unwrap_or_else(|_| unreachable!("http should be valid")),
+        );
+        if parts.path_and_query.is_none() {
+            parts.path_and_query = Some(
+                "/".parse()
+                    .unwrap_or_else(|_| unreachable!("root should be valid")),
+            );
+        }
+        Ok(Uri::from_parts(parts)?)
+    } else {
+        Ok(uri)
+    }
+}
+
+/// Sends the requests of the generated clients to the configured address,
+/// compressing their messages on the way.
+///
+/// The version of tonic in use can't compress messages itself, so the
+/// request is buffered and its single message compressed here, which works
+/// as the export calls are all unary.
+#[derive(Clone)]
+pub(super) struct HyperSvc {
+    uri: Uri,
+    client: hyper::Client<HttpsConnector<HttpConnector>, hyper::Body>,
+    compression: Compression,
+}
+
+impl HyperSvc {
+    pub(super) fn new(
+        uri: Uri,
+        tls_settings: &MaybeTlsSettings,
+        compression: Compression,
+    ) -> crate::Result<Self> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+
+        let tls = tls_connector_builder(tls_settings)?;
+        let mut https = HttpsConnector::with_connector(http, tls)?;
+
+        let settings = tls_settings.tls().cloned();
+        https.set_callback(move |c, _uri| {
+            if let Some(settings) = &settings {
+                settings.apply_connect_configuration(c);
+            }
+
+            Ok(())
+        });
+
+        Ok(Self {
+            uri,
+            client: hyper::Client::builder().http2_only(true).build(https),
+            compression,
+        })
+    }
+}
+
+impl tower::Service<hyper::Request<BoxBody>> for HyperSvc {
+    type Response = hyper::Response<hyper::Body>;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: hyper::Request<BoxBody>) -> Self::Future {
+        let uri = Uri::builder()
+            .scheme(self.uri.scheme().unwrap().clone())
+            .authority(self.uri.authority().unwrap().clone())
+            .path_and_query(req.uri().path_and_query().unwrap().clone())
+            .build()
+            .unwrap();
+        let client = self.client.clone();
+        let compression = self.compression;
+
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+            parts.uri = uri;
+
+            let mut body = hyper::body::to_bytes(body).await?;
+            if let Compression::Gzip(level) = compression {
+                body = compress_message(&body, level.unwrap_or(GZIP_DEFAULT))?;
+                parts
+                    .headers
+                    .insert("grpc-encoding", HeaderValue::from_static("gzip"));
+            }
+
+            let response = client
+                .request(hyper::Request::from_parts(parts, hyper::Body::from(body)))
+                .await?;
+            Ok(response)
+        })
+    }
+}
+
+/// Gzip the message of a gRPC request body, which is framed as a compressed
+/// flag, the message length as a big endian `u32`, and the message.
+///
+/// See <https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md#requests>
+fn compress_message(body: &[u8], level: usize) -> crate::Result<Bytes> {
+    let message = match body {
+        [0, _, _, _, _, message @ ..] => message,
+        _ => return Err("Unexpected gRPC request framing".into()),
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::new(level as u32));
+    encoder.write_all(message)?;
+    let compressed = encoder.finish()?;
+
+    let mut framed = BytesMut::with_capacity(compressed.len() + 5);
+    framed.put_u8(1);
+    framed.put_u32(compressed.len() as u32);
+    framed.put_slice(&compressed);
+    Ok(framed.freeze())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::opentelemetry::proto::{
+        logs::v1::{InstrumentationLibraryLogs, LogRecord},
+        resource::v1::Resource,
+    };
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn logs(resource: Option<Resource>, name: &str) -> Record {
+        Record::Logs(ResourceLogs {
+            resource,
+            instrumentation_library_logs: vec![InstrumentationLibraryLogs {
+                instrumentation_library: None,
+                logs: vec![LogRecord {
+                    name: name.to_owned(),
+                    ..Default::default()
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        })
+    }
+
+    #[test]
+    fn groups_by_resource() {
+        let (logs, metrics, spans) = group(vec![
+            logs(None, "a"),
+            logs(Some(Resource::default()), "b"),
+            logs(None, "c"),
+        ]);
+
+        assert!(metrics.is_empty() && spans.is_empty());
+        assert_eq!(logs.len(), 2);
+        let names = logs[0].instrumentation_library_logs[0]
+            .logs
+            .iter()
+            .map(|log| log.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn compresses_message() {
+        let body = [&[0, 0, 0, 0, 5][..], b"hello"].concat();
+
+        let compressed = compress_message(&body, GZIP_DEFAULT).unwrap();
+
+        assert_eq!(compressed[0], 1);
+        let len = u32::from_be_bytes([compressed[1], compressed[2], compressed[3], compressed[4]]);
+        assert_eq!(len as usize, compressed.len() - 5);
+        let mut message = String::new();
+        GzDecoder::new(&compressed[5..])
+            .read_to_string(&mut message)
+            .unwrap();
+        assert_eq!(message, "hello");
+    }
+}
@@ -0,0 +1,431 @@
+//! Sends events as alerts to the Opsgenie Alert API, so that alert-shaped
+//! events can page without going through an alert manager. Events either
+//! create alerts, or acknowledge or close the alerts of their alias. Each
+//! event is sent in its own request, as the API doesn't take batches.
+
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    http::HttpClient,
+    internal_events::{OpsgenieAlertInvalid, OpsgenieAlertsProcessed, TemplateRenderingFailed},
+    sinks::{
+        util::{
+            http::{HttpSink, PartitionHttpSink},
+            severity::SeverityMapping,
+            BatchConfig, BatchSettings, BoxedRawValue, Concurrency, JsonArrayBuffer,
+            PartitionBuffer, PartitionInnerBuffer, TowerRequestConfig,
+        },
+        Healthcheck, VectorSink,
+    },
+    template::Template,
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use futures::{future, FutureExt, SinkExt};
+use http::Request;
+use indoc::indoc;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use std::convert::TryFrom;
+
+/// The lengths the API truncates fields to.
+const MAX_ALIAS_LENGTH: usize = 512;
+const MAX_MESSAGE_LENGTH: usize = 130;
+const MAX_DESCRIPTION_LENGTH: usize = 15000;
+const MAX_ENTITY_LENGTH: usize = 512;
+const MAX_SOURCE_LENGTH: usize = 100;
+/// The characters escaped in aliases in paths, which are the reserved
+/// characters of URIs.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpsgenieConfig {
+    /// Overrides the endpoint of the `region`.
+    endpoint: Option<String>,
+    #[serde(default)]
+    region: Region,
+    api_key: String,
+    /// Renders to `create`, `acknowledge` or `close`.
+    #[serde(default = "default_action")]
+    action: Template,
+    /// Identifies the alert across events, so that later events can
+    /// acknowledge or close it, and so that Opsgenie deduplicates it.
+    alias: Option<Template>,
+    #[serde(default = "default_message")]
+    message: Template,
+    description: Option<Template>,
+    source: Option<Template>,
+    entity: Option<Template>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    priority: SeverityMapping<Priority>,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    request: TowerRequestConfig<Concurrency>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Region {
+    #[derivative(Default)]
+    Us,
+    Eu,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[derivative(Default)]
+pub enum Priority {
+    P1,
+    P2,
+    #[derivative(Default)]
+    P3,
+    P4,
+    P5,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Create,
+    Acknowledge,
+    Close,
+}
+
+fn default_action() -> Template {
+    Template::try_from("create").expect("Template should be valid")
+}
+
+fn default_message() -> Template {
+    Template::try_from("{{ message }}").expect("Template should be valid")
+}
+
+inventory::submit! {
+    SinkDescription::new::<OpsgenieConfig>("opsgenie")
+}
+
+impl GenerateConfig for OpsgenieConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(indoc! {r#"
+            api_key = "${OPSGENIE_API_KEY}"
+            alias = "{{ alertname }}"
+        "#})
+        .unwrap()
+    }
+}
+
+impl OpsgenieConfig {
+    fn get_endpoint(&self) -> String {
+        let endpoint = self.endpoint.clone().unwrap_or_else(|| match self.region {
+            Region::Us => "https://api.opsgenie.com".to_owned(),
+            Region::Eu => "https://api.eu.opsgenie.com".to_owned(),
+        });
+        endpoint.trim_end_matches('/').to_owned()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "opsgenie")]
+impl SinkConfig for OpsgenieConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        // The Alert API doesn't support batching.
+        let batch = BatchSettings::default()
+            .bytes(bytesize::kib(512u64))
+            .events(1)
+            .timeout(0)
+            .parse_config(BatchConfig::default())?;
+
+        let mut request = self.request;
+        request.concurrency = request.concurrency.if_none(Concurrency::Adaptive);
+        let request = request.unwrap_with(&TowerRequestConfig::default());
+
+        let tls_settings = MaybeTlsSettings::from_config(
+            &Some(self.tls.clone().unwrap_or_else(TlsConfig::enabled)),
+            false,
+        )?;
+        let client = HttpClient::new(tls_settings, cx.proxy())?;
+
+        let sink = PartitionHttpSink::new(
+            OpsgenieSink::new(self),
+            PartitionBuffer::new(JsonArrayBuffer::new(batch.size)),
+            request,
+            batch.timeout,
+            client,
+            cx.acker(),
+        )
+        .sink_map_err(|error| error!(message = "Fatal opsgenie sink error.", %error));
+
+        // API keys of integrations can only create and update alerts, so
+        // there's nothing they could be checked against.
+        Ok((VectorSink::Sink(Box::new(sink)), future::ok(()).boxed()))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "opsgenie"
+    }
+}
+
+struct OpsgenieSink {
+    config: OpsgenieConfig,
+    endpoint: String,
+}
+
+impl OpsgenieSink {
+    fn new(config: &OpsgenieConfig) -> Self {
+        Self {
+            config: config.clone(),
+            endpoint: config.get_endpoint(),
+        }
+    }
+
+    fn render(
+        &self,
+        template: &Template,
+        event: &Event,
+        field: &str,
+        max_length: usize,
+    ) -> Option<String> {
+        template
+            .render_string(event)
+            .map(|value| value.chars().take(max_length).collect())
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    }
+
+    fn render_optional(
+        &self,
+        template: &Option<Template>,
+        event: &Event,
+        field: &str,
+        max_length: usize,
+    ) -> Option<Option<String>> {
+        match template {
+            Some(template) => self.render(template, event, field, max_length).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpSink for OpsgenieSink {
+    /// Events are partitioned by the path they're sent to.
+    type Input = PartitionInnerBuffer<serde_json::Value, String>;
+    type Output = PartitionInnerBuffer<Vec<BoxedRawValue>, String>;
+
+    fn encode_event(&self, event: Event) -> Option<Self::Input> {
+        let config = &self.config;
+        let action = self.render(&config.action, &event, "action", usize::MAX)?;
+        let action = match serde_json::from_value(json!(action)) {
+            Ok(action) => action,
+            Err(_) => {
+                emit!(OpsgenieAlertInvalid {
+                    error: "Action isn't one of `create`, `acknowledge` or `close`.",
+                });
+                return None;
+            }
+        };
+        let alias = self.render_optional(&config.alias, &event, "alias", MAX_ALIAS_LENGTH)?;
+        let message = self.render(&config.message, &event, "message", MAX_MESSAGE_LENGTH)?;
+        let source = self.render_optional(&config.source, &event, "source", MAX_SOURCE_LENGTH)?;
+
+        let mut body = Map::new();
+        if let Some(source) = source {
+            body.insert("source".into(), json!(source));
+        }
+
+        let path = match (action, alias) {
+            (Action::Create, alias) => {
+                let log = event.as_log();
+                body.insert("message".into(), json!(message));
+                if let Some(alias) = alias {
+                    body.insert("alias".into(), json!(alias));
+                }
+                if let Some(description) = self.render_optional(
+                    &config.description,
+                    &event,
+                    "description",
+                    MAX_DESCRIPTION_LENGTH,
+                )? {
+                    body.insert("description".into(), json!(description));
+                }
+                if let Some(entity) =
+                    self.render_optional(&config.entity, &event, "entity", MAX_ENTITY_LENGTH)?
+                {
+                    body.insert("entity".into(), json!(entity));
+                }
+                if !config.tags.is_empty() {
+                    body.insert("tags".into(), json!(config.tags));
+                }
+                body.insert("priority".into(), json!(config.priority.severity(log)));
+                // The details of alerts are a map of strings.
+                let details = log
+                    .all_fields()
+                    .map(|(key, value)| (key, json!(value.to_string_lossy())))
+                    .collect::<Map<_, _>>();
+                body.insert("details".into(), details.into());
+                "/v2/alerts".to_owned()
+            }
+            (action, Some(alias)) => {
+                body.insert("note".into(), json!(message));
+                format!(
+                    "/v2/alerts/{}/{}?identifierType=alias",
+                    utf8_percent_encode(&alias, PATH_SEGMENT),
+                    if action == Action::Close {
+                        "close"
+                    } else {
+                        "acknowledge"
+                    }
+                )
+            }
+            (_, None) => {
+                emit!(OpsgenieAlertInvalid {
+                    error: "Alerts can only be acknowledged or closed by their alias.",
+                });
+                return None;
+            }
+        };
+
+        Some(PartitionInnerBuffer::new(body.into(), path))
+    }
+
+    async fn build_request(&self, events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
+        let (mut events, path) = events.into_parts();
+
+        assert_eq!(events.len(), 1);
+        let body = serde_json::to_vec(&events.pop().expect("One event"))?;
+
+        emit!(OpsgenieAlertsProcessed {
+            byte_size: body.len(),
+        });
+
+        Request::post(format!("{}{}", self.endpoint, path))
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("GenieKey {}", self.config.api_key))
+            .body(body)
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::LogEvent,
+        sinks::util::test::{build_test_server_status, load_sink},
+        test_util::next_addr,
+    };
+    use futures::{stream, StreamExt};
+    use hyper::StatusCode;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<OpsgenieConfig>();
+    }
+
+    fn sink(config: &str) -> OpsgenieSink {
+        OpsgenieSink::new(&load_sink::<OpsgenieConfig>(config).unwrap().0)
+    }
+
+    #[test]
+    fn encodes_alerts() {
+        let sink = sink(indoc! {r#"
+            api_key = "key"
+            alias = "{{ service }}/{{ alertname }}"
+            entity = "{{ service }}"
+            tags = ["vector"]
+            priority.field = "level"
+            priority.mapping.fatal = "P1"
+        "#});
+
+        let mut log = LogEvent::from("x".repeat(200));
+        log.insert("service", "db");
+        log.insert("alertname", "DiskFull");
+        log.insert("level", "fatal");
+        log.insert("usage.percent", 99);
+
+        let (body, path) = sink.encode_event(log.into()).unwrap().into_parts();
+        assert_eq!(path, "/v2/alerts");
+        assert_eq!(body["message"], "x".repeat(MAX_MESSAGE_LENGTH));
+        assert_eq!(body["alias"], "db/DiskFull");
+        assert_eq!(body["entity"], "db");
+        assert_eq!(body["tags"], json!(["vector"]));
+        assert_eq!(body["priority"], "P1");
+        assert_eq!(body["details"]["usage.percent"], "99");
+    }
+
+    #[test]
+    fn encodes_closes() {
+        let sink = sink(indoc! {r#"
+            api_key = "key"
+            action = "{{ action }}"
+            alias = "{{ service }}/{{ alertname }}"
+            priority.field = "level"
+            priority.default = "P4"
+        "#});
+
+        let mut log = LogEvent::from("Disk is fine");
+        log.insert("action", "close");
+        log.insert("service", "db");
+        log.insert("alertname", "DiskFull");
+
+        let (body, path) = sink.encode_event(log.clone().into()).unwrap().into_parts();
+        assert_eq!(path, "/v2/alerts/db%2FDiskFull/close?identifierType=alias");
+        assert_eq!(body, json!({"note": "Disk is fine"}));
+
+        log.insert("action", "delete");
+        assert!(sink.encode_event(log.into()).is_none());
+    }
+
+    #[test]
+    fn uses_region_endpoints() {
+        let config = load_sink::<OpsgenieConfig>(indoc! {r#"
+            api_key = "key"
+            region = "eu"
+        "#})
+        .unwrap()
+        .0;
+        assert_eq!(config.get_endpoint(), "https://api.eu.opsgenie.com");
+    }
+
+    #[tokio::test]
+    async fn sends_api_key() {
+        let addr = next_addr();
+        let (mut config, cx) = load_sink::<OpsgenieConfig>(indoc! {r#"
+            api_key = "key"
+        "#})
+        .unwrap();
+        config.endpoint = Some(format!("http://{}", addr));
+        let (sink, _) = config.build(cx).await.unwrap();
+
+        let (rx, _trigger, server) = build_test_server_status(addr, StatusCode::ACCEPTED);
+        tokio::spawn(server);
+
+        sink.run(stream::once(async { Event::from("Disk is full") }))
+            .await
+            .unwrap();
+
+        let output = rx.take(1).collect::<Vec<_>>().await;
+        let (parts, body) = &output[0];
+        assert_eq!(parts.uri.path(), "/v2/alerts");
+        assert_eq!(parts.headers["Authorization"], "GenieKey key");
+        let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(body["message"], "Disk is full");
+        assert_eq!(body["priority"], "P3");
+    }
+}
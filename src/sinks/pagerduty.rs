@@ -0,0 +1,386 @@
+//! Sends events as alerts to the PagerDuty Events API v2, so that alert-shaped
+//! events can page without going through an alert manager. Each event is sent
+//! in its own request, as the API doesn't take batches.
+
+use crate::{
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, Value},
+    http::HttpClient,
+    internal_events::{PagerDutyEventInvalid, PagerDutyEventsProcessed, TemplateRenderingFailed},
+    sinks::{
+        util::{
+            http::{BatchedHttpSink, HttpSink},
+            severity::SeverityMapping,
+            BatchConfig, BatchSettings, BoxedRawValue, Concurrency, JsonArrayBuffer,
+            TowerRequestConfig,
+        },
+        Healthcheck, VectorSink,
+    },
+    template::Template,
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use chrono::SecondsFormat;
+use futures::{future, FutureExt, SinkExt};
+use http::Request;
+use indoc::indoc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use std::convert::TryFrom;
+
+/// The API refuses summaries longer than this.
+const MAX_SUMMARY_LENGTH: usize = 1024;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PagerDutyConfig {
+    #[serde(default = "default_endpoint")]
+    endpoint: String,
+    routing_key: String,
+    /// Renders to `trigger`, `acknowledge` or `resolve`.
+    #[serde(default = "default_action")]
+    action: Template,
+    /// Identifies the alert across events, so that later events can update,
+    /// acknowledge or resolve it.
+    dedup_key: Option<Template>,
+    #[serde(default = "default_summary")]
+    summary: Template,
+    /// Defaults to the host of events, or to the hostname of Vector.
+    source: Option<Template>,
+    component: Option<Template>,
+    group: Option<Template>,
+    class: Option<Template>,
+    #[serde(default)]
+    severity: SeverityMapping<Severity>,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    request: TowerRequestConfig<Concurrency>,
+}
+
+fn default_endpoint() -> String {
+    "https://events.pagerduty.com".to_owned()
+}
+
+fn default_action() -> Template {
+    Template::try_from("trigger").expect("Template should be valid")
+}
+
+fn default_summary() -> Template {
+    Template::try_from("{{ message }}").expect("Template should be valid")
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Severity {
+    Critical,
+    #[derivative(Default)]
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Trigger,
+    Acknowledge,
+    Resolve,
+}
+
+inventory::submit! {
+    SinkDescription::new::<PagerDutyConfig>("pagerduty")
+}
+
+impl GenerateConfig for PagerDutyConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(indoc! {r#"
+            routing_key = "${PAGERDUTY_ROUTING_KEY}"
+            dedup_key = "{{ alertname }}"
+        "#})
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "pagerduty")]
+impl SinkConfig for PagerDutyConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        // The Events API doesn't support batching.
+        let batch = BatchSettings::default()
+            .bytes(bytesize::kib(512u64))
+            .events(1)
+            .timeout(0)
+            .parse_config(BatchConfig::default())?;
+
+        let mut request = self.request;
+        request.concurrency = request.concurrency.if_none(Concurrency::Adaptive);
+        let request = request.unwrap_with(&TowerRequestConfig::default());
+
+        let tls_settings = MaybeTlsSettings::from_config(
+            &Some(self.tls.clone().unwrap_or_else(TlsConfig::enabled)),
+            false,
+        )?;
+        let client = HttpClient::new(tls_settings, cx.proxy())?;
+
+        let sink = BatchedHttpSink::new(
+            PagerDutySink::new(self),
+            JsonArrayBuffer::new(batch.size),
+            request,
+            batch.timeout,
+            client,
+            cx.acker(),
+        )
+        .sink_map_err(|error| error!(message = "Fatal pagerduty sink error.", %error));
+
+        // The Events API has no endpoint to check routing keys against.
+        Ok((VectorSink::Sink(Box::new(sink)), future::ok(()).boxed()))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "pagerduty"
+    }
+}
+
+struct PagerDutySink {
+    config: PagerDutyConfig,
+    uri: String,
+    hostname: String,
+}
+
+impl PagerDutySink {
+    fn new(config: &PagerDutyConfig) -> Self {
+        Self {
+            config: config.clone(),
+            uri: format!("{}/v2/enqueue", config.endpoint.trim_end_matches('/')),
+            hostname: crate::get_hostname().unwrap_or_default(),
+        }
+    }
+
+    fn render(&self, template: &Template, event: &Event, field: &str) -> Option<String> {
+        template
+            .render_string(event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    }
+
+    fn render_optional(
+        &self,
+        template: &Option<Template>,
+        event: &Event,
+        field: &str,
+    ) -> Option<Option<String>> {
+        match template {
+            Some(template) => self.render(template, event, field).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpSink for PagerDutySink {
+    type Input = serde_json::Value;
+    type Output = Vec<BoxedRawValue>;
+
+    fn encode_event(&self, event: Event) -> Option<Self::Input> {
+        let action = self.render(&self.config.action, &event, "action")?;
+        let action = match serde_json::from_value(json!(action)) {
+            Ok(action) => action,
+            Err(_) => {
+                emit!(PagerDutyEventInvalid {
+                    error: "Action isn't one of `trigger`, `acknowledge` or `resolve`.",
+                });
+                return None;
+            }
+        };
+        let dedup_key = self.render_optional(&self.config.dedup_key, &event, "dedup_key")?;
+        if action != Action::Trigger && dedup_key.is_none() {
+            emit!(PagerDutyEventInvalid {
+                error: "Alerts can only be acknowledged or resolved by their dedup key.",
+            });
+            return None;
+        }
+
+        let mut body = Map::new();
+        body.insert("routing_key".into(), json!(self.config.routing_key));
+        body.insert("event_action".into(), json!(action));
+        if let Some(dedup_key) = dedup_key {
+            body.insert("dedup_key".into(), json!(dedup_key));
+        }
+        if action != Action::Trigger {
+            return Some(body.into());
+        }
+
+        let summary: String = self
+            .render(&self.config.summary, &event, "summary")?
+            .chars()
+            .take(MAX_SUMMARY_LENGTH)
+            .collect();
+        let source = match self.render_optional(&self.config.source, &event, "source")? {
+            Some(source) => source,
+            None => event
+                .as_log()
+                .get(log_schema().host_key())
+                .map(Value::to_string_lossy)
+                .unwrap_or_else(|| self.hostname.clone()),
+        };
+
+        let log = event.as_log();
+        let mut payload = Map::new();
+        payload.insert("summary".into(), json!(summary));
+        payload.insert("source".into(), json!(source));
+        payload.insert("severity".into(), json!(self.config.severity.severity(log)));
+        if let Some(Value::Timestamp(timestamp)) = log.get(log_schema().timestamp_key()) {
+            payload.insert(
+                "timestamp".into(),
+                json!(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            );
+        }
+        for (key, template) in &[
+            ("component", &self.config.component),
+            ("group", &self.config.group),
+            ("class", &self.config.class),
+        ] {
+            if let Some(value) = self.render_optional(template, &event, key)? {
+                payload.insert((*key).into(), json!(value));
+            }
+        }
+        payload.insert("custom_details".into(), json!(log));
+        body.insert("payload".into(), payload.into());
+
+        Some(body.into())
+    }
+
+    async fn build_request(&self, mut events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
+        assert_eq!(events.len(), 1);
+        let body = serde_json::to_vec(&events.pop().expect("One event"))?;
+
+        emit!(PagerDutyEventsProcessed {
+            byte_size: body.len(),
+        });
+
+        Request::post(self.uri.as_str())
+            .header("Content-Type", "application/json")
+            .body(body)
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::LogEvent,
+        sinks::util::test::{build_test_server_status, load_sink},
+        test_util::next_addr,
+    };
+    use chrono::{TimeZone, Utc};
+    use futures::{stream, StreamExt};
+    use hyper::StatusCode;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<PagerDutyConfig>();
+    }
+
+    fn sink(config: &str) -> PagerDutySink {
+        PagerDutySink::new(&load_sink::<PagerDutyConfig>(config).unwrap().0)
+    }
+
+    #[test]
+    fn encodes_triggers() {
+        let sink = sink(indoc! {r#"
+            routing_key = "key"
+            dedup_key = "{{ service }}/{{ alertname }}"
+            component = "{{ service }}"
+            severity.field = "level"
+            severity.default = "warning"
+            severity.mapping.fatal = "critical"
+        "#});
+
+        let mut log = LogEvent::from("Disk is full");
+        log.insert("service", "db");
+        log.insert("alertname", "DiskFull");
+        log.insert("level", "fatal");
+        log.insert(log_schema().host_key(), "db-1");
+        log.insert(
+            log_schema().timestamp_key(),
+            Utc.ymd(2021, 8, 20).and_hms(10, 0, 0),
+        );
+
+        let body = sink.encode_event(log.into()).unwrap();
+        assert_eq!(body["routing_key"], "key");
+        assert_eq!(body["event_action"], "trigger");
+        assert_eq!(body["dedup_key"], "db/DiskFull");
+        assert_eq!(body["payload"]["summary"], "Disk is full");
+        assert_eq!(body["payload"]["source"], "db-1");
+        assert_eq!(body["payload"]["severity"], "critical");
+        assert_eq!(body["payload"]["component"], "db");
+        assert_eq!(body["payload"]["timestamp"], "2021-08-20T10:00:00.000Z");
+        assert_eq!(body["payload"]["custom_details"]["alertname"], "DiskFull");
+    }
+
+    #[test]
+    fn encodes_resolves() {
+        let sink = sink(indoc! {r#"
+            routing_key = "key"
+            action = "{{ status }}"
+            dedup_key = "{{ alertname }}"
+        "#});
+
+        let mut log = LogEvent::from("Disk is fine");
+        log.insert("status", "resolve");
+        log.insert("alertname", "DiskFull");
+        let body = sink.encode_event(log.clone().into()).unwrap();
+        assert_eq!(body["event_action"], "resolve");
+        assert_eq!(body["dedup_key"], "DiskFull");
+        assert!(body.get("payload").is_none());
+
+        log.insert("status", "firing");
+        assert!(sink.encode_event(log.clone().into()).is_none());
+
+        let sink = self::sink(indoc! {r#"
+            routing_key = "key"
+            action = "resolve"
+        "#});
+        assert!(sink.encode_event(log.into()).is_none());
+    }
+
+    #[tokio::test]
+    async fn sends_one_event_per_request() {
+        let addr = next_addr();
+        let (mut config, cx) = load_sink::<PagerDutyConfig>(indoc! {r#"
+            routing_key = "key"
+        "#})
+        .unwrap();
+        config.endpoint = format!("http://{}", addr);
+        let (sink, _) = config.build(cx).await.unwrap();
+
+        let (rx, _trigger, server) = build_test_server_status(addr, StatusCode::ACCEPTED);
+        tokio::spawn(server);
+
+        let events = vec![Event::from("first"), Event::from("second")];
+        sink.run(stream::iter(events)).await.unwrap();
+
+        let output = rx.take(2).collect::<Vec<_>>().await;
+        let mut summaries = output
+            .iter()
+            .map(|(parts, body)| {
+                assert_eq!(parts.uri.path(), "/v2/enqueue");
+                let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+                body["payload"]["summary"].as_str().unwrap().to_owned()
+            })
+            .collect::<Vec<_>>();
+        summaries.sort();
+        assert_eq!(summaries, vec!["first", "second"]);
+    }
+}
@@ -0,0 +1,394 @@
+use super::{Column, OnConflict, PostgresSinkTlsConfig, Row};
+use crate::{
+    internal_events::PostgresRowsWritten,
+    sinks::util::{retries::RetryLogic, EncodedLength},
+};
+use futures::{future::BoxFuture, pin_mut};
+use openssl::ssl::{SslConnector, SslMethod};
+use postgres_openssl::MakeTlsConnector;
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::Semaphore;
+use tokio_postgres::{
+    binary_copy::BinaryCopyInWriter,
+    error::SqlState,
+    types::{ToSql, Type},
+    Client, Config, Error as PgError, NoTls, Transaction,
+};
+
+/// The temporary table batches are copied to before being inserted into the
+/// table, when conflicts are handled. It's emptied when each transaction
+/// commits.
+const STAGING_TABLE: &str = "vector_staging";
+/// The column of the staging table numbering the rows in the order they
+/// were copied, so that the last of conflicting rows of a batch wins.
+const SEQUENCE_COLUMN: &str = "_vector_seq";
+
+/// The statements of the sink, built once from its configuration.
+#[derive(Clone, Debug)]
+pub(super) struct Statements {
+    types: Vec<Type>,
+    copy: String,
+    /// Creates the staging table, and inserts it into the table.
+    staging: Option<(String, String)>,
+    select: String,
+}
+
+impl Statements {
+    pub(super) fn new(
+        table: &str,
+        columns: &[Column],
+        on_conflict: OnConflict,
+        conflict_columns: &[String],
+    ) -> Self {
+        let table = quote_table(table);
+        let names = columns
+            .iter()
+            .map(|column| quote_identifier(&column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let types = columns
+            .iter()
+            .map(|column| column.column_type.pg_type())
+            .collect();
+        let select = format!("SELECT {} FROM {} LIMIT 0", names, table);
+
+        let key = conflict_columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let target = match key.is_empty() {
+            true => String::new(),
+            false => format!(" ({})", key),
+        };
+        let (copy_table, staging) = match on_conflict {
+            OnConflict::Error => (table, None),
+            OnConflict::Ignore | OnConflict::Update => {
+                let create = format!(
+                    "CREATE TEMPORARY TABLE IF NOT EXISTS {staging} ON COMMIT DELETE ROWS AS \
+                     SELECT {names} FROM {table} WITH NO DATA; \
+                     ALTER TABLE {staging} ADD COLUMN IF NOT EXISTS {seq} bigserial",
+                    staging = STAGING_TABLE,
+                    names = names,
+                    table = table,
+                    seq = SEQUENCE_COLUMN,
+                );
+                let insert = match on_conflict {
+                    OnConflict::Update => {
+                        let updates = columns
+                            .iter()
+                            .filter(|column| !conflict_columns.contains(&column.name))
+                            .map(|column| {
+                                let name = quote_identifier(&column.name);
+                                format!("{} = EXCLUDED.{}", name, name)
+                            })
+                            .collect::<Vec<_>>();
+                        let action = match updates.is_empty() {
+                            true => "DO NOTHING".to_owned(),
+                            false => format!("DO UPDATE SET {}", updates.join(", ")),
+                        };
+                        // A row can't be updated twice by an insert, so only
+                        // the last of the rows of the batch with the same key
+                        // is inserted.
+                        format!(
+                            "INSERT INTO {table} ({names}) SELECT {names} FROM (\
+                             SELECT DISTINCT ON ({key}) * FROM {staging} \
+                             ORDER BY {key}, {seq} DESC) AS batch \
+                             ON CONFLICT{target} {action}",
+                            table = table,
+                            names = names,
+                            key = key,
+                            staging = STAGING_TABLE,
+                            seq = SEQUENCE_COLUMN,
+                            target = target,
+                            action = action,
+                        )
+                    }
+                    _ => format!(
+                        "INSERT INTO {} ({}) SELECT {} FROM {} ON CONFLICT{} DO NOTHING",
+                        table, names, names, STAGING_TABLE, target
+                    ),
+                };
+                (STAGING_TABLE.to_owned(), Some((create, insert)))
+            }
+        };
+        let copy = format!("COPY {} ({}) FROM STDIN (FORMAT binary)", copy_table, names);
+
+        Self {
+            types,
+            copy,
+            staging,
+            select,
+        }
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes each part of a table name qualified by its schema.
+fn quote_table(table: &str) -> String {
+    table
+        .split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// A connection of the pool, which remembers whether it created its staging
+/// table, as temporary tables belong to their session.
+struct Connection {
+    client: Client,
+    staging: bool,
+}
+
+/// Connections to the database, opened when needed and reused, of which at
+/// most `size` are in use at once.
+pub(super) struct Pool {
+    config: Config,
+    tls: Option<PostgresSinkTlsConfig>,
+    idle: Mutex<Vec<Connection>>,
+    permits: Arc<Semaphore>,
+}
+
+impl Pool {
+    pub(super) fn new(
+        config: Config,
+        tls: Option<PostgresSinkTlsConfig>,
+        size: usize,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            tls,
+            idle: Mutex::new(Vec::new()),
+            permits: Arc::new(Semaphore::new(size)),
+        })
+    }
+
+    fn take(&self) -> Option<Connection> {
+        let mut idle = self.idle.lock().expect("poisoned lock");
+        while let Some(connection) = idle.pop() {
+            if !connection.client.is_closed() {
+                return Some(connection);
+            }
+        }
+        None
+    }
+
+    fn put(&self, connection: Connection) {
+        if !connection.client.is_closed() {
+            self.idle.lock().expect("poisoned lock").push(connection);
+        }
+    }
+
+    async fn connect(&self) -> crate::Result<Connection> {
+        let client = match &self.tls {
+            Some(tls_config) => {
+                let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+                builder.set_ca_file(tls_config.ca_file.clone())?;
+                let connector = MakeTlsConnector::new(builder.build());
+
+                let (client, connection) = self.config.connect(connector).await?;
+                tokio::spawn(connection);
+                client
+            }
+            None => {
+                let (client, connection) = self.config.connect(NoTls).await?;
+                tokio::spawn(connection);
+                client
+            }
+        };
+        Ok(Connection {
+            client,
+            staging: false,
+        })
+    }
+
+    async fn get(&self) -> crate::Result<Connection> {
+        match self.take() {
+            Some(connection) => Ok(connection),
+            None => self.connect().await,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct PostgresService {
+    pool: Arc<Pool>,
+    statements: Arc<Statements>,
+}
+
+impl PostgresService {
+    pub(super) fn new(pool: Arc<Pool>, statements: Statements) -> Self {
+        Self {
+            pool,
+            statements: Arc::new(statements),
+        }
+    }
+
+    async fn write(self, rows: Vec<Row>) -> crate::Result<()> {
+        let _permit = self.pool.permits.acquire().await?;
+        let mut connection = self.pool.get().await?;
+        let result = self.write_with(&mut connection, &rows).await;
+        // Connections are closed by the errors they can't recover from.
+        self.pool.put(connection);
+        let written = result?;
+
+        emit!(PostgresRowsWritten {
+            count: rows.len(),
+            byte_size: rows.iter().map(EncodedLength::encoded_length).sum(),
+            written,
+        });
+        Ok(())
+    }
+
+    /// Writes the rows, returning how many rows were written to the table.
+    async fn write_with(&self, connection: &mut Connection, rows: &[Row]) -> Result<u64, PgError> {
+        let statements = &self.statements;
+        if let Some((create, _)) = &statements.staging {
+            if !connection.staging {
+                connection.client.batch_execute(create).await?;
+                connection.staging = true;
+            }
+        }
+
+        let transaction = connection.client.transaction().await?;
+        let copied = copy(&transaction, statements, rows).await?;
+        let written = match &statements.staging {
+            Some((_, insert)) => transaction.execute(insert.as_str(), &[]).await?,
+            None => copied,
+        };
+        transaction.commit().await?;
+        Ok(written)
+    }
+}
+
+async fn copy(
+    transaction: &Transaction<'_>,
+    statements: &Statements,
+    rows: &[Row],
+) -> Result<u64, PgError> {
+    let sink = transaction.copy_in(statements.copy.as_str()).await?;
+    let writer = BinaryCopyInWriter::new(sink, &statements.types);
+    pin_mut!(writer);
+    for row in rows {
+        let values = row
+            .0
+            .iter()
+            .map(|cell| cell as &(dyn ToSql + Sync))
+            .collect::<Vec<_>>();
+        writer.as_mut().write(&values).await?;
+    }
+    writer.finish().await
+}
+
+impl tower::Service<Vec<Row>> for PostgresService {
+    type Response = ();
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, rows: Vec<Row>) -> Self::Future {
+        Box::pin(self.clone().write(rows))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct PostgresRetryLogic;
+
+impl RetryLogic for PostgresRetryLogic {
+    type Error = PgError;
+    type Response = ();
+
+    /// Retries the errors of connections, of transactions rolled back by
+    /// the server, and of servers out of resources or shutting down, rather
+    /// than the errors of the rows or of the statements.
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        match error.code() {
+            None => true,
+            Some(code) => {
+                let class = &code.code()[..2];
+                matches!(class, "08" | "40" | "53" | "57") || *code == SqlState::LOCK_NOT_AVAILABLE
+            }
+        }
+    }
+}
+
+pub(super) async fn healthcheck(pool: Arc<Pool>, statements: Statements) -> crate::Result<()> {
+    let connection = pool.connect().await?;
+    connection
+        .client
+        .execute(statements.select.as_str(), &[])
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::PostgresSinkConfig, *};
+
+    fn statements(extra: &str) -> Statements {
+        let config: PostgresSinkConfig = toml::from_str(&format!(
+            r#"
+            endpoint = "postgresql://localhost/postgres"
+            table = "public.events"
+            columns = [{{ name = "id" }}, {{ name = "message" }}]
+            {}
+            "#,
+            extra
+        ))
+        .unwrap();
+        Statements::new(
+            &config.table,
+            &config.columns().unwrap(),
+            config.on_conflict,
+            &config.conflict_columns,
+        )
+    }
+
+    #[test]
+    fn copies_to_the_table() {
+        let statements = statements("");
+        assert_eq!(
+            statements.copy,
+            r#"COPY "public"."events" ("id", "message") FROM STDIN (FORMAT binary)"#
+        );
+        assert!(statements.staging.is_none());
+    }
+
+    #[test]
+    fn inserts_from_staging_on_conflict() {
+        let statements = statements(
+            r#"
+            on_conflict = "update"
+            conflict_columns = ["id"]
+            "#,
+        );
+        assert_eq!(
+            statements.copy,
+            r#"COPY vector_staging ("id", "message") FROM STDIN (FORMAT binary)"#
+        );
+        let (_, insert) = statements.staging.unwrap();
+        assert_eq!(
+            insert,
+            "INSERT INTO \"public\".\"events\" (\"id\", \"message\") \
+             SELECT \"id\", \"message\" FROM (SELECT DISTINCT ON (\"id\") * FROM vector_staging \
+             ORDER BY \"id\", _vector_seq DESC) AS batch \
+             ON CONFLICT (\"id\") DO UPDATE SET \"message\" = EXCLUDED.\"message\""
+        );
+
+        let (_, insert) = statements(r#"on_conflict = "ignore""#).staging.unwrap();
+        assert_eq!(
+            insert,
+            "INSERT INTO \"public\".\"events\" (\"id\", \"message\") \
+             SELECT \"id\", \"message\" FROM vector_staging ON CONFLICT DO NOTHING"
+        );
+    }
+}
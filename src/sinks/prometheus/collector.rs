@@ -178,6 +178,23 @@ pub(super) trait MetricCollector {
                     self.emit_value(timestamp, name, "_sum", *sum, tags, None);
                     self.emit_value(timestamp, name, "_count", *count as f64, tags, None);
                 }
+                MetricValue::Sketch { sketch } => {
+                    // convert sketches into summaries
+                    for q in quantiles {
+                        if let Some(value) = sketch.quantile(*q) {
+                            self.emit_value(
+                                timestamp,
+                                name,
+                                "",
+                                value,
+                                tags,
+                                Some(("quantile", q.to_string())),
+                            );
+                        }
+                    }
+                    self.emit_value(timestamp, name, "_sum", sketch.sum(), tags, None);
+                    self.emit_value(timestamp, name, "_count", sketch.count() as f64, tags, None);
+                }
             }
         }
     }
@@ -268,8 +285,15 @@ impl StringCollector {
 
 type Labels = Vec<proto::Label>;
 
+#[derive(Default)]
+struct Series {
+    samples: Vec<proto::Sample>,
+    exemplars: Vec<proto::Exemplar>,
+    histograms: Vec<proto::Histogram>,
+}
+
 pub(super) struct TimeSeries {
-    buffer: IndexMap<Labels, Vec<proto::Sample>>,
+    buffer: IndexMap<Labels, Series>,
     metadata: IndexMap<String, proto::MetricMetadata>,
     timestamp: Option<i64>,
 }
@@ -306,6 +330,68 @@ impl TimeSeries {
             .timestamp
             .get_or_insert_with(|| Utc::now().timestamp_millis())
     }
+
+    /// Encodes a histogram metric as the native histogram it was converted
+    /// to, in a single series without a suffix.
+    pub(super) fn encode_native_histogram(
+        &mut self,
+        default_namespace: Option<&str>,
+        metric: &Metric,
+        mut histogram: proto::Histogram,
+    ) {
+        if metric.kind() != MetricKind::Absolute {
+            return;
+        }
+        let name = encode_namespace(metric.namespace().or(default_namespace), '_', metric.name());
+        self.emit_metadata(metric.name(), &name, metric.value());
+
+        histogram.timestamp = match metric.timestamp() {
+            Some(timestamp) => timestamp.timestamp_millis(),
+            None => self.default_timestamp(),
+        };
+        self.buffer
+            .entry(Self::make_labels(metric.tags(), &name, "", None))
+            .or_default()
+            .histograms
+            .push(histogram);
+    }
+
+    /// Attaches an exemplar to the series of the metric its value was
+    /// observed in: the series of counters and gauges, the bucket of
+    /// histograms, or the series of native histograms. Exemplars of other
+    /// metrics, and of metrics that weren't encoded, are dropped.
+    pub(super) fn encode_exemplar(
+        &mut self,
+        default_namespace: Option<&str>,
+        buckets: &[f64],
+        native_histograms: bool,
+        metric: &Metric,
+        exemplar: proto::Exemplar,
+    ) {
+        let (suffix, extra) = match metric.value() {
+            MetricValue::Counter { .. } | MetricValue::Gauge { .. } => ("", None),
+            MetricValue::Distribution {
+                statistic: StatisticKind::Histogram,
+                ..
+            } => match native_histograms {
+                true => ("", None),
+                false => {
+                    let le = buckets
+                        .iter()
+                        .find(|bucket| **bucket >= exemplar.value)
+                        .map(|bucket| bucket.to_string())
+                        .unwrap_or_else(|| "+Inf".to_string());
+                    ("_bucket", Some(("le", le)))
+                }
+            },
+            _ => return,
+        };
+        let name = encode_namespace(metric.namespace().or(default_namespace), '_', metric.name());
+        let labels = Self::make_labels(metric.tags(), &name, suffix, extra);
+        if let Some(series) = self.buffer.get_mut(&labels) {
+            series.exemplars.push(exemplar);
+        }
+    }
 }
 
 impl MetricCollector for TimeSeries {
@@ -320,7 +406,7 @@ impl MetricCollector for TimeSeries {
     }
 
     fn emit_metadata(&mut self, name: &str, fullname: &str, value: &MetricValue) {
-        if !self.metadata.contains_key(name) {
+        if !self.metadata.contains_key(fullname) {
             let r#type = prometheus_metric_type(value);
             let metadata = proto::MetricMetadata {
                 r#type: r#type as i32,
@@ -328,7 +414,7 @@ impl MetricCollector for TimeSeries {
                 help: name.into(),
                 unit: String::new(),
             };
-            self.metadata.insert(name.into(), metadata);
+            self.metadata.insert(fullname.into(), metadata);
         }
     }
 
@@ -345,6 +431,7 @@ impl MetricCollector for TimeSeries {
         self.buffer
             .entry(Self::make_labels(tags, name, suffix, extra))
             .or_default()
+            .samples
             .push(proto::Sample { value, timestamp });
     }
 
@@ -352,7 +439,12 @@ impl MetricCollector for TimeSeries {
         let timeseries = self
             .buffer
             .into_iter()
-            .map(|(labels, samples)| proto::TimeSeries { labels, samples })
+            .map(|(labels, series)| proto::TimeSeries {
+                labels,
+                samples: series.samples,
+                exemplars: series.exemplars,
+                histograms: series.histograms,
+            })
             .collect::<Vec<_>>();
         let metadata = self
             .metadata
@@ -380,7 +472,7 @@ fn prometheus_metric_type(metric_value: &MetricValue) -> proto::MetricType {
             ..
         } => MetricType::Summary,
         MetricValue::AggregatedHistogram { .. } => MetricType::Histogram,
-        MetricValue::AggregatedSummary { .. } => MetricType::Summary,
+        MetricValue::AggregatedSummary { .. } | MetricValue::Sketch { .. } => MetricType::Summary,
     }
 }
 
@@ -388,7 +480,7 @@ fn prometheus_metric_type(metric_value: &MetricValue) -> proto::MetricType {
 mod tests {
     use super::super::default_summary_quantiles;
     use super::*;
-    use crate::event::metric::{Metric, MetricKind, MetricValue, StatisticKind};
+    use crate::event::metric::{DDSketch, Metric, MetricKind, MetricValue, StatisticKind};
     use chrono::{DateTime, TimeZone};
     use indoc::indoc;
     use pretty_assertions::assert_eq;
@@ -438,6 +530,8 @@ mod tests {
                                 value: $svalue,
                                 timestamp: $timestamp,
                             }],
+                            exemplars: vec![],
+                            histograms: vec![],
                         },
                     )*
                 ],
@@ -780,6 +874,49 @@ mod tests {
         )
     }
 
+    #[test]
+    fn encodes_sketch_text() {
+        assert_eq!(
+            encode_sketch::<StringCollector>(),
+            indoc! {r#"
+                # HELP ns_requests requests
+                # TYPE ns_requests summary
+                ns_requests{code="200",quantile="0"} 1 1612325106789
+                ns_requests{code="200",quantile="1"} 3 1612325106789
+                ns_requests_sum{code="200"} 9 1612325106789
+                ns_requests_count{code="200"} 5 1612325106789
+            "#}
+        );
+    }
+
+    #[test]
+    fn encodes_sketch_request() {
+        assert_eq!(
+            encode_sketch::<TimeSeries>(),
+            write_request!(
+                "ns_requests", "requests", Summary [
+                    "" @ 1612325106789 = 1.0 ["code" => "200", "quantile" => "0"],
+                    "" @ 1612325106789 = 3.0 ["code" => "200", "quantile" => "1"],
+                    "_sum" @ 1612325106789 = 9.0 ["code" => "200"],
+                    "_count" @ 1612325106789 = 5.0 ["code" => "200"]
+                ]
+            )
+        );
+    }
+
+    fn encode_sketch<T: MetricCollector>() -> T::Output {
+        let metric = Metric::new(
+            "requests".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, vector_core::samples![1.0 => 3, 3.0 => 2]),
+            },
+        )
+        .with_tags(Some(tags()))
+        .with_timestamp(Some(timestamp()));
+        encode_one::<T>(Some("ns"), &[], &[0.0, 1.0], false, &metric)
+    }
+
     #[test]
     fn encodes_timestamp_text() {
         assert_eq!(
@@ -822,6 +959,67 @@ mod tests {
         assert!(encoded.timeseries[0].samples[0].timestamp >= now);
     }
 
+    #[test]
+    fn encodes_metadata_per_family() {
+        let mut series = TimeSeries::new();
+        for namespace in &["vector", "other"] {
+            let metric = Metric::new(
+                "hits".to_owned(),
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 1.0 },
+            )
+            .with_namespace(Some(*namespace));
+            series.encode_metric(None, &[], &[], false, &metric);
+        }
+        let request = series.finish();
+
+        let families = request
+            .metadata
+            .iter()
+            .map(|metadata| metadata.metric_family_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(families, vec!["vector_hits", "other_hits"]);
+    }
+
+    #[test]
+    fn encodes_exemplar_in_bucket() {
+        let metric = Metric::new(
+            "requests".to_owned(),
+            MetricKind::Absolute,
+            MetricValue::Distribution {
+                samples: vector_core::samples![1.0 => 3, 2.0 => 3, 3.0 => 2],
+                statistic: StatisticKind::Histogram,
+            },
+        )
+        .with_timestamp(Some(timestamp()));
+        let exemplar = proto::Exemplar {
+            labels: vec![proto::Label {
+                name: "trace_id".into(),
+                value: "abc".into(),
+            }],
+            value: 3.0,
+            timestamp: 1612325106789,
+        };
+        let buckets = [0.0, 2.5, 5.0];
+
+        let mut series = TimeSeries::new();
+        series.encode_metric(Some("vector"), &buckets, &[], false, &metric);
+        series.encode_exemplar(Some("vector"), &buckets, false, &metric, exemplar.clone());
+        let request = series.finish();
+
+        let with_exemplars = request
+            .timeseries
+            .iter()
+            .filter(|series| !series.exemplars.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(with_exemplars.len(), 1);
+        assert!(with_exemplars[0].labels.contains(&proto::Label {
+            name: "le".into(),
+            value: "5".into(),
+        }));
+        assert_eq!(with_exemplars[0].exemplars, vec![exemplar]);
+    }
+
     fn timestamp() -> DateTime<Utc> {
         Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 6, 789)
     }
@@ -1,5 +1,6 @@
 mod collector;
 pub(crate) mod exporter;
+mod native_histogram;
 pub(crate) mod remote_write;
 
 fn default_histogram_buckets() -> Vec<f64> {
@@ -0,0 +1,274 @@
+//! Conversion of histograms to Prometheus native histograms, whose buckets
+//! are exponential: for a schema `n`, bucket `i` holds the values in
+//! `(2^((i - 1) * 2^-n), 2^(i * 2^-n)]`, so they cover any range of values
+//! without configuring the buckets.
+
+use crate::event::metric::{Bucket, MetricValue, Sample, StatisticKind};
+use prometheus_parser::proto::{self, histogram};
+use std::collections::BTreeMap;
+
+pub(super) const MIN_SCHEMA: i32 = -4;
+pub(super) const MAX_SCHEMA: i32 = 8;
+
+/// The counts of the buckets of a native histogram, by index.
+#[derive(Debug, Default)]
+struct Buckets {
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero: u64,
+}
+
+impl Buckets {
+    fn add(&mut self, value: f64, count: u64, schema: i32) {
+        if value > 0.0 {
+            *self
+                .positive
+                .entry(bucket_index(value, schema))
+                .or_default() += count;
+        } else if value < 0.0 {
+            *self
+                .negative
+                .entry(bucket_index(-value, schema))
+                .or_default() += count;
+        } else {
+            self.zero += count;
+        }
+    }
+
+    fn into_histogram(self, schema: i32, count: u64, sum: f64) -> proto::Histogram {
+        let (positive_spans, positive_deltas) = encode_buckets(&self.positive);
+        let (negative_spans, negative_deltas) = encode_buckets(&self.negative);
+        proto::Histogram {
+            count: Some(histogram::Count::CountInt(count)),
+            sum,
+            schema,
+            zero_threshold: 0.0,
+            zero_count: Some(histogram::ZeroCount::ZeroCountInt(self.zero)),
+            negative_spans,
+            negative_deltas,
+            negative_counts: vec![],
+            positive_spans,
+            positive_deltas,
+            positive_counts: vec![],
+            reset_hint: histogram::ResetHint::Unknown as i32,
+            timestamp: 0,
+        }
+    }
+}
+
+/// Converts the histograms among the values to native histograms, without
+/// their timestamp. Other values aren't converted.
+pub(super) fn from_value(value: &MetricValue, schema: i32) -> Option<proto::Histogram> {
+    match value {
+        MetricValue::Distribution {
+            samples,
+            statistic: StatisticKind::Histogram,
+        } => Some(from_samples(samples, schema)),
+        MetricValue::AggregatedHistogram {
+            buckets,
+            count,
+            sum,
+        } => Some(from_buckets(buckets, *count, *sum, schema)),
+        _ => None,
+    }
+}
+
+/// Converts samples exactly, as each of them has a bucket. Samples that
+/// aren't finite are skipped.
+fn from_samples(samples: &[Sample], schema: i32) -> proto::Histogram {
+    let mut buckets = Buckets::default();
+    let mut count = 0;
+    let mut sum = 0.0;
+    for sample in samples.iter().filter(|sample| sample.value.is_finite()) {
+        buckets.add(sample.value, sample.rate as u64, schema);
+        count += sample.rate as u64;
+        sum += sample.value * sample.rate as f64;
+    }
+    buckets.into_histogram(schema, count, sum)
+}
+
+/// Converts the buckets of an aggregated histogram approximately, as their
+/// bounds don't match exponential buckets: the count of each bucket goes to
+/// the bucket of its upper bound, and the count above the last bound goes to
+/// the bucket after it.
+fn from_buckets(buckets: &[Bucket], count: u32, sum: f64, schema: i32) -> proto::Histogram {
+    let mut native = Buckets::default();
+    let mut bucketed = 0;
+    let mut last = None;
+    for bucket in buckets {
+        // The count of the `+Inf` bucket is counted with the overflow.
+        if !bucket.upper_limit.is_finite() {
+            continue;
+        }
+        native.add(bucket.upper_limit, bucket.count as u64, schema);
+        bucketed += bucket.count as u64;
+        if bucket.upper_limit > 0.0 {
+            last = Some(bucket_index(bucket.upper_limit, schema));
+        }
+    }
+
+    let overflow = (count as u64).saturating_sub(bucketed);
+    if overflow > 0 {
+        let index = last.map(|index| index + 1).unwrap_or(0);
+        *native.positive.entry(index).or_default() += overflow;
+    }
+    native.into_histogram(schema, count as u64, sum)
+}
+
+/// Encodes the counts of the non-empty buckets as spans of consecutive
+/// buckets, and the difference of each count to the previous one.
+fn encode_buckets(buckets: &BTreeMap<i32, u64>) -> (Vec<proto::BucketSpan>, Vec<i64>) {
+    let mut spans: Vec<proto::BucketSpan> = Vec::new();
+    let mut deltas = Vec::new();
+    let mut next = None;
+    let mut previous = 0;
+    for (&index, &count) in buckets.iter().filter(|(_, count)| **count > 0) {
+        match spans.last_mut() {
+            Some(span) if next == Some(index) => span.length += 1,
+            _ => spans.push(proto::BucketSpan {
+                // The first span starts at its index, the others at their
+                // gap to the previous span.
+                offset: index - next.unwrap_or(0),
+                length: 1,
+            }),
+        }
+        deltas.push(count as i64 - previous);
+        previous = count as i64;
+        next = Some(index + 1);
+    }
+    (spans, deltas)
+}
+
+/// The index of the bucket of a positive value, computed the way Prometheus
+/// does so that the values at the bounds fall in the same buckets.
+fn bucket_index(value: f64, schema: i32) -> i32 {
+    let (fraction, exponent) = frexp(value);
+    if schema > 0 {
+        // The bounds of the buckets of each power of two, between 0.5 and 1.
+        let length = 1 << schema;
+        let bound = (0..length)
+            .find(|&index| 2f64.powf(index as f64 / length as f64 - 1.0) >= fraction)
+            .unwrap_or(length);
+        bound + (exponent - 1) * length
+    } else {
+        let mut index = exponent;
+        if fraction == 0.5 {
+            index -= 1;
+        }
+        let offset = (1 << -schema) - 1;
+        (index + offset) >> -schema
+    }
+}
+
+/// Splits a positive finite value into a fraction in `[0.5, 1)` and a power
+/// of two, like C's `frexp`.
+fn frexp(value: f64) -> (f64, i32) {
+    const EXPONENT_MASK: u64 = 0x7ff << 52;
+
+    let bits = value.to_bits();
+    let exponent = ((bits & EXPONENT_MASK) >> 52) as i32;
+    if value == 0.0 {
+        (value, 0)
+    } else if exponent == 0 {
+        // Subnormal values are normalized first.
+        let (fraction, exponent) = frexp(value * 2f64.powi(54));
+        (fraction, exponent - 54)
+    } else {
+        let fraction = f64::from_bits((bits & !EXPONENT_MASK) | (1022 << 52));
+        (fraction, exponent - 1022)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_indexes() {
+        for (value, schema, index) in &[
+            (1.0, 0, 0),
+            (2.0, 0, 1),
+            (3.0, 0, 2),
+            (0.5, 0, -1),
+            (0.3, 0, -1),
+            (4.0, -1, 1),
+            (4.1, -1, 2),
+            (1.0, 3, 0),
+            (2.0, 3, 8),
+            (1.1, 3, 2),
+            (0.75, 3, -3),
+            (1e-310, 0, -1029),
+        ] {
+            assert_eq!(
+                bucket_index(*value, *schema),
+                *index,
+                "value {} in schema {}",
+                value,
+                schema
+            );
+        }
+    }
+
+    #[test]
+    fn converts_samples() {
+        let samples = vector_core::samples![
+            -2.0 => 1, 0.0 => 2, 1.0 => 3, 2.0 => 1, 8.0 => 4, f64::NAN => 1
+        ];
+        let histogram = from_samples(&samples, 0);
+
+        assert_eq!(histogram.count, Some(histogram::Count::CountInt(11)));
+        assert_eq!(histogram.sum, 35.0);
+        assert_eq!(
+            histogram.zero_count,
+            Some(histogram::ZeroCount::ZeroCountInt(2))
+        );
+        assert_eq!(
+            histogram.positive_spans,
+            vec![
+                proto::BucketSpan {
+                    offset: 0,
+                    length: 2
+                },
+                proto::BucketSpan {
+                    offset: 1,
+                    length: 1
+                },
+            ]
+        );
+        assert_eq!(histogram.positive_deltas, vec![3, -2, 3]);
+        assert_eq!(
+            histogram.negative_spans,
+            vec![proto::BucketSpan {
+                offset: 1,
+                length: 1
+            }]
+        );
+        assert_eq!(histogram.negative_deltas, vec![1]);
+    }
+
+    #[test]
+    fn converts_buckets() {
+        let histogram = from_buckets(
+            &vector_core::buckets![1.0 => 2, 4.0 => 3, f64::INFINITY => 1],
+            6,
+            12.0,
+            0,
+        );
+
+        assert_eq!(histogram.count, Some(histogram::Count::CountInt(6)));
+        assert_eq!(
+            histogram.positive_spans,
+            vec![
+                proto::BucketSpan {
+                    offset: 0,
+                    length: 1
+                },
+                proto::BucketSpan {
+                    offset: 1,
+                    length: 2
+                },
+            ]
+        );
+        assert_eq!(histogram.positive_deltas, vec![2, 1, -2]);
+    }
+}
@@ -1,7 +1,13 @@
-use super::collector::{self, MetricCollector as _};
+use super::{
+    collector::{self, MetricCollector as _},
+    native_histogram::{self, MAX_SCHEMA, MIN_SCHEMA},
+};
 use crate::{
     config::{self, SinkConfig, SinkDescription},
-    event::{Event, Metric},
+    event::{
+        metric::{MetricSeries, MetricValue},
+        Event, Metric,
+    },
     http::{Auth, HttpClient},
     internal_events::TemplateRenderingFailed,
     sinks::{
@@ -18,18 +24,31 @@ use crate::{
     tls::{TlsOptions, TlsSettings},
 };
 use bytes::{Bytes, BytesMut};
+use chrono::Utc;
 use futures::{future::BoxFuture, stream, FutureExt, SinkExt};
 use http::Uri;
+use prometheus_parser::proto;
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::task;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task,
+};
 use tower::ServiceBuilder;
 
 #[derive(Debug, Snafu)]
 enum Errors {
     #[snafu(display(r#"Prometheus remote_write sink cannot accept "set" metrics"#))]
     SetMetricInvalid,
+    #[snafu(display(
+        "Native histogram schema {} is not between {} and {}",
+        schema,
+        MIN_SCHEMA,
+        MAX_SCHEMA
+    ))]
+    InvalidSchema { schema: i32 },
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -55,8 +74,102 @@ pub(crate) struct RemoteWriteConfig {
     pub tls: Option<TlsOptions>,
 
     pub auth: Option<Auth>,
+
+    #[serde(default)]
+    pub exemplars: ExemplarsConfig,
+
+    #[serde(default)]
+    pub native_histograms: NativeHistogramsConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ExemplarsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_trace_id_tag")]
+    pub trace_id_tag: String,
+    #[serde(default = "default_span_id_tag")]
+    pub span_id_tag: String,
+}
+
+impl Default for ExemplarsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trace_id_tag: default_trace_id_tag(),
+            span_id_tag: default_span_id_tag(),
+        }
+    }
 }
 
+fn default_trace_id_tag() -> String {
+    "trace_id".to_owned()
+}
+
+fn default_span_id_tag() -> String {
+    "span_id".to_owned()
+}
+
+impl ExemplarsConfig {
+    /// Removes the tags of the trace the metric was observed in, so they
+    /// don't make a series per trace, and makes an exemplar of them and the
+    /// observed value. Only counters, gauges and distributions have one.
+    fn extract(&self, metric: &mut Metric) -> Option<proto::Exemplar> {
+        let trace_id = metric.remove_tag(&self.trace_id_tag);
+        let span_id = metric.remove_tag(&self.span_id_tag);
+
+        let value = match metric.value() {
+            MetricValue::Counter { value } | MetricValue::Gauge { value } => *value,
+            MetricValue::Distribution { samples, .. } => samples.last()?.value,
+            _ => return None,
+        };
+        let mut labels = vec![proto::Label {
+            name: "trace_id".into(),
+            value: trace_id?,
+        }];
+        if let Some(span_id) = span_id {
+            labels.push(proto::Label {
+                name: "span_id".into(),
+                value: span_id,
+            });
+        }
+        labels.sort();
+
+        let timestamp = metric.timestamp().unwrap_or_else(Utc::now);
+        Some(proto::Exemplar {
+            labels,
+            value,
+            timestamp: timestamp.timestamp_millis(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NativeHistogramsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_schema")]
+    pub schema: i32,
+}
+
+impl Default for NativeHistogramsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schema: default_schema(),
+        }
+    }
+}
+
+const fn default_schema() -> i32 {
+    3
+}
+
+/// The latest exemplar of each series, until the series is sent.
+type Exemplars = Arc<Mutex<HashMap<PartitionKey, HashMap<MetricSeries, proto::Exemplar>>>>;
+
 inventory::submit! {
     SinkDescription::new::<RemoteWriteConfig>("prometheus_remote_write")
 }
@@ -84,6 +197,18 @@ impl SinkConfig for RemoteWriteConfig {
         let tenant_id = self.tenant_id.clone();
         let auth = self.auth.clone();
 
+        let native_histograms = match self.native_histograms {
+            NativeHistogramsConfig {
+                enabled: true,
+                schema,
+            } if !(MIN_SCHEMA..=MAX_SCHEMA).contains(&schema) => {
+                return Err(Errors::InvalidSchema { schema }.into());
+            }
+            NativeHistogramsConfig { enabled, schema } => Some(schema).filter(|_| enabled),
+        };
+        let exemplar_config = Some(self.exemplars.clone()).filter(|config| config.enabled);
+        let exemplars = exemplar_config.as_ref().map(|_| Exemplars::default());
+
         let healthcheck = healthcheck(endpoint.clone(), client.clone()).boxed();
         let service = RemoteWriteService {
             endpoint,
@@ -92,6 +217,8 @@ impl SinkConfig for RemoteWriteConfig {
             buckets,
             quantiles,
             auth,
+            native_histograms,
+            exemplars: exemplars.clone(),
         };
 
         let sink = {
@@ -102,7 +229,13 @@ impl SinkConfig for RemoteWriteConfig {
 
             PartitionBatchSink::new(service, buffer, batch.timeout, cx.acker())
                 .with_flat_map(move |event: Event| {
-                    stream::iter(normalizer.apply(event).map(|event| {
+                    let mut metric = event.into_metric();
+                    let exemplar = exemplar_config
+                        .as_ref()
+                        .and_then(|config| config.extract(&mut metric));
+                    let exemplars = &exemplars;
+
+                    stream::iter(normalizer.apply(metric.into()).map(|event| {
                         let tenant_id = tenant_id.as_ref().and_then(|template| {
                             template
                                 .render_string(&event)
@@ -116,6 +249,14 @@ impl SinkConfig for RemoteWriteConfig {
                                 .ok()
                         });
                         let key = PartitionKey { tenant_id };
+                        if let (Some(exemplars), Some(exemplar)) = (exemplars, exemplar) {
+                            exemplars
+                                .lock()
+                                .expect("poisoned lock")
+                                .entry(key.clone())
+                                .or_default()
+                                .insert(event.series().clone(), exemplar);
+                        }
                         Ok(EncodedEvent::new(PartitionInnerBuffer::new(event, key)))
                     }))
                 })
@@ -169,19 +310,50 @@ struct RemoteWriteService {
     buckets: Vec<f64>,
     quantiles: Vec<f64>,
     auth: Option<Auth>,
+    native_histograms: Option<i32>,
+    exemplars: Option<Exemplars>,
 }
 
 impl RemoteWriteService {
-    fn encode_events(&self, metrics: Vec<Metric>) -> Bytes {
+    fn encode_events(&self, metrics: Vec<Metric>, key: &PartitionKey) -> Bytes {
+        let mut exemplars = self
+            .exemplars
+            .as_ref()
+            .map(|exemplars| exemplars.lock().expect("poisoned lock"));
+
         let mut time_series = collector::TimeSeries::new();
+        let default_namespace = self.default_namespace.as_deref();
         for metric in metrics {
-            time_series.encode_metric(
-                self.default_namespace.as_deref(),
-                &self.buckets,
-                &self.quantiles,
-                false,
-                &metric,
-            );
+            let histogram = self
+                .native_histograms
+                .and_then(|schema| native_histogram::from_value(metric.value(), schema));
+            let native = histogram.is_some();
+            match histogram {
+                Some(histogram) => {
+                    time_series.encode_native_histogram(default_namespace, &metric, histogram)
+                }
+                None => time_series.encode_metric(
+                    default_namespace,
+                    &self.buckets,
+                    &self.quantiles,
+                    false,
+                    &metric,
+                ),
+            }
+
+            let exemplar = exemplars
+                .as_mut()
+                .and_then(|exemplars| exemplars.get_mut(key))
+                .and_then(|exemplars| exemplars.remove(metric.series()));
+            if let Some(exemplar) = exemplar {
+                time_series.encode_exemplar(
+                    default_namespace,
+                    &self.buckets,
+                    native,
+                    &metric,
+                    exemplar,
+                );
+            }
         }
         let request = time_series.finish();
 
@@ -202,7 +374,7 @@ impl tower::Service<PartitionInnerBuffer<Vec<Metric>, PartitionKey>> for RemoteW
 
     fn call(&mut self, buffer: PartitionInnerBuffer<Vec<Metric>, PartitionKey>) -> Self::Future {
         let (events, key) = buffer.into_parts();
-        let body = self.encode_events(events);
+        let body = self.encode_events(events, &key);
         let body = snap_block(body);
 
         let mut builder = http::Request::post(self.endpoint.clone())
@@ -369,6 +541,61 @@ mod tests {
         check_output(2, "counter-1", 26.0);
     }
 
+    #[tokio::test]
+    async fn sends_exemplars() {
+        let event = Metric::new(
+            "counter-1",
+            MetricKind::Incremental,
+            MetricValue::Counter { value: 2.0 },
+        )
+        .with_tags(Some(
+            vec![
+                ("trace_id".to_owned(), "abc".to_owned()),
+                ("span_id".to_owned(), "def".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+        ))
+        .into();
+        let outputs = send_request("exemplars.enabled = true", vec![event]).await;
+
+        let (_, req) = &outputs[0];
+        assert_eq!(req.timeseries.len(), 1);
+        assert_eq!(req.timeseries[0].labels, labels!("__name__" => "counter-1"));
+        let exemplars = &req.timeseries[0].exemplars;
+        assert_eq!(exemplars.len(), 1);
+        assert_eq!(
+            exemplars[0].labels,
+            labels!("span_id" => "def", "trace_id" => "abc")
+        );
+        assert_eq!(exemplars[0].value, 2.0);
+    }
+
+    #[tokio::test]
+    async fn sends_native_histograms() {
+        let event = Metric::new(
+            "histogram-1",
+            MetricKind::Absolute,
+            MetricValue::AggregatedHistogram {
+                buckets: vector_core::buckets![1.0 => 2, 2.0 => 1],
+                count: 4,
+                sum: 8.0,
+            },
+        )
+        .into();
+        let outputs = send_request("native_histograms.enabled = true", vec![event]).await;
+
+        let (_, req) = &outputs[0];
+        assert_eq!(req.timeseries.len(), 1);
+        assert_eq!(req.timeseries[0].labels, labels!("__name__" => "histogram-1"));
+        assert!(req.timeseries[0].samples.is_empty());
+        let histograms = &req.timeseries[0].histograms;
+        assert_eq!(histograms.len(), 1);
+        assert_eq!(histograms[0].schema, 3);
+        assert_eq!(histograms[0].sum, 8.0);
+        assert_eq!(req.metadata[0].r#type, proto::MetricType::Histogram as i32);
+    }
+
     async fn send_request(
         config: &str,
         events: Vec<Event>,
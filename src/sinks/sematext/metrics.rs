@@ -229,7 +229,7 @@ fn encode_events(
         let ts = encode_timestamp(data.timestamp);
 
         // Authentication in Sematext is by inserting the token as a tag.
-        let mut tags = series.tags.unwrap_or_default();
+        let mut tags = series.tags.as_deref().cloned().unwrap_or_default();
         tags.insert("token".into(), token.into());
 
         let (metric_type, fields) = match data.value {
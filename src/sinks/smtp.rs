@@ -0,0 +1,411 @@
+//! Sends events as emails, for pipelines that alert on a low volume of
+//! events. Events rendering the same subject are batched into digests, each
+//! sent as one email whose body holds the body rendered from each event.
+
+use crate::{
+    config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::Event,
+    internal_events::{SmtpEmailSent, TemplateRenderingFailed},
+    sinks::{
+        util::{
+            retries::RetryLogic, service::Concurrency, BatchConfig, BatchSettings, EncodedEvent,
+            PartitionBatchSink, PartitionBuffer, PartitionInnerBuffer, ServiceBuilderExt,
+            TowerRequestConfig, VecBuffer,
+        },
+        Healthcheck, VectorSink,
+    },
+    template::{Template, TemplateParseError},
+    tls::TlsOptions,
+};
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream, FutureExt, SinkExt, StreamExt};
+use lazy_static::lazy_static;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::{
+        authentication::Credentials,
+        client::{Certificate, Tls, TlsParameters},
+        Error as SmtpError,
+    },
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    convert::TryFrom,
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceBuilder};
+
+lazy_static! {
+    static ref REQUEST_DEFAULTS: TowerRequestConfig = TowerRequestConfig {
+        concurrency: Concurrency::Fixed(5),
+        ..Default::default()
+    };
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpSinkConfig {
+    pub host: String,
+    /// Defaults to the port of the `security` mode.
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: Security,
+    pub auth: Option<SmtpAuth>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Rendered for each event; events rendering the same subject are sent
+    /// in the same digests.
+    pub subject: String,
+    #[serde(default = "default_body")]
+    pub body: String,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<TlsOptions>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Security {
+    /// Upgrades the connection with `STARTTLS`, failing if the server doesn't
+    /// support it.
+    #[derivative(Default)]
+    Starttls,
+    /// Connects with TLS from the start, also known as SMTPS.
+    Tls,
+    /// Sends emails in plain text.
+    None,
+}
+
+impl Security {
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Starttls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpAuth {
+    pub user: String,
+    pub password: String,
+}
+
+fn default_body() -> String {
+    "{{ message }}".to_owned()
+}
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Invalid {} address {:?}: {}", field, address, source))]
+    InvalidAddress {
+        field: &'static str,
+        address: String,
+        source: lettre::address::AddressError,
+    },
+    #[snafu(display("At least one recipient must be set in `to`"))]
+    NoRecipients,
+    #[snafu(display("Invalid {} template: {}", field, source))]
+    InvalidTemplate {
+        field: &'static str,
+        source: TemplateParseError,
+    },
+    #[snafu(display("Could not read CA file {:?}: {}", path, source))]
+    ReadCaFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Invalid TLS settings: {}", source))]
+    InvalidTls { source: SmtpError },
+    #[snafu(display("Only the CA and verification options of `tls` are supported"))]
+    UnsupportedTlsOption,
+}
+
+inventory::submit! {
+    SinkDescription::new::<SmtpSinkConfig>("smtp")
+}
+
+impl GenerateConfig for SmtpSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"host = "smtp.example.com"
+            from = "Vector <vector@example.com>"
+            to = ["oncall@example.com"]
+            subject = "[{{ level }}] {{ host }}""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "smtp")]
+impl SinkConfig for SmtpSinkConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        // One email per event by default, digests being opted into by
+        // raising `batch.max_events`.
+        let batch = BatchSettings::default()
+            .bytes(bytesize::mib(1u64))
+            .events(1)
+            .timeout(1)
+            .parse_config(self.batch)?;
+        let request = self.request.unwrap_with(&REQUEST_DEFAULTS);
+
+        let from = parse_mailbox("from", &self.from)?;
+        let to = self
+            .to
+            .iter()
+            .map(|address| parse_mailbox("to", address))
+            .collect::<Result<Vec<_>, _>>()?;
+        if to.is_empty() {
+            return Err(BuildError::NoRecipients.into());
+        }
+        let subject = Template::try_from(self.subject.as_str())
+            .context(InvalidTemplate { field: "subject" })?;
+        let body =
+            Template::try_from(self.body.as_str()).context(InvalidTemplate { field: "body" })?;
+
+        let transport = self.build_transport()?;
+        let healthcheck = healthcheck(transport.clone()).boxed();
+
+        let service = SmtpService {
+            transport,
+            from,
+            to,
+        };
+        let svc = ServiceBuilder::new()
+            .map(build_request)
+            .settings(request, SmtpRetryLogic)
+            .service(service);
+
+        let buffer = PartitionBuffer::new(VecBuffer::new(batch.size));
+        let sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
+            .with_flat_map(move |event| stream::iter(encode_event(event, &subject, &body)).map(Ok))
+            .sink_map_err(|error| error!(message = "Fatal smtp sink error.", %error));
+
+        Ok((VectorSink::Sink(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "smtp"
+    }
+}
+
+impl SmtpSinkConfig {
+    fn build_transport(&self) -> crate::Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let tls = match self.security {
+            Security::None => Tls::None,
+            Security::Starttls => Tls::Required(self.tls_parameters()?),
+            Security::Tls => Tls::Wrapper(self.tls_parameters()?),
+        };
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+            .port(self.port.unwrap_or_else(|| self.security.default_port()))
+            .tls(tls);
+        if let Some(auth) = &self.auth {
+            builder =
+                builder.credentials(Credentials::new(auth.user.clone(), auth.password.clone()));
+        }
+
+        Ok(builder.build())
+    }
+
+    fn tls_parameters(&self) -> crate::Result<TlsParameters> {
+        let mut builder = TlsParameters::builder(self.host.clone());
+        if let Some(tls) = &self.tls {
+            if tls.crt_file.is_some() || tls.key_file.is_some() {
+                return Err(BuildError::UnsupportedTlsOption.into());
+            }
+            if let Some(path) = &tls.ca_file {
+                let pem = std::fs::read(path).with_context(|| ReadCaFile { path })?;
+                builder =
+                    builder.add_root_certificate(Certificate::from_pem(&pem).context(InvalidTls)?);
+            }
+            builder = builder
+                .dangerous_accept_invalid_certs(!tls.verify_certificate.unwrap_or(true))
+                .dangerous_accept_invalid_hostnames(!tls.verify_hostname.unwrap_or(true));
+        }
+        Ok(builder.build().context(InvalidTls)?)
+    }
+}
+
+fn parse_mailbox(field: &'static str, address: &str) -> crate::Result<Mailbox> {
+    Ok(address
+        .parse()
+        .with_context(|| InvalidAddress { field, address })?)
+}
+
+async fn healthcheck(transport: AsyncSmtpTransport<Tokio1Executor>) -> crate::Result<()> {
+    if transport.test_connection().await? {
+        Ok(())
+    } else {
+        Err("SMTP server didn't answer NOOP".into())
+    }
+}
+
+fn encode_event(
+    event: Event,
+    subject: &Template,
+    body: &Template,
+) -> Option<EncodedEvent<PartitionInnerBuffer<Bytes, String>>> {
+    let render = |template: &Template, field| {
+        template
+            .render_string(&event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: true,
+                });
+            })
+            .ok()
+    };
+    let subject = render(subject, "subject")?;
+    let body = render(body, "body")?;
+
+    let mut log = event.into_log();
+    Some(EncodedEvent {
+        item: PartitionInnerBuffer::new(body.into(), subject),
+        finalizers: log.metadata_mut().take_finalizers(),
+    })
+}
+
+/// A digest of the events rendering the same subject.
+#[derive(Clone, Debug)]
+struct EmailRequest {
+    subject: String,
+    bodies: Vec<Bytes>,
+}
+
+fn build_request(partition: PartitionInnerBuffer<Vec<Bytes>, String>) -> EmailRequest {
+    let (bodies, subject) = partition.into_parts();
+    EmailRequest { subject, bodies }
+}
+
+#[derive(Clone)]
+struct SmtpService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpService {
+    fn message(&self, request: &EmailRequest) -> Result<Message, lettre::error::Error> {
+        let mut body = String::new();
+        for part in &request.bodies {
+            body.push_str(&String::from_utf8_lossy(part));
+            body.push('\n');
+        }
+
+        let mut builder = Message::builder().from(self.from.clone());
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        builder
+            .subject(request.subject.as_str())
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+    }
+}
+
+impl Service<EmailRequest> for SmtpService {
+    type Response = ();
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: EmailRequest) -> Self::Future {
+        let transport = self.transport.clone();
+        let message = self.message(&request);
+        Box::pin(async move {
+            let message = message?;
+            let byte_size = message.formatted().len();
+            transport.send(message).await?;
+            emit!(SmtpEmailSent {
+                byte_size,
+                event_count: request.bodies.len(),
+            });
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SmtpRetryLogic;
+
+impl RetryLogic for SmtpRetryLogic {
+    type Error = SmtpError;
+    type Response = ();
+
+    /// Connection failures and `4xx` replies are transient, unlike `5xx`
+    /// replies and rejected messages.
+    fn is_retriable_error(&self, error: &Self::Error) -> bool {
+        !error.is_permanent() && !error.is_client()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SmtpSinkConfig>();
+    }
+
+    #[test]
+    fn partitions_by_subject() {
+        let subject = Template::try_from("[{{ level }}] {{ host }}").unwrap();
+        let body = Template::try_from(default_body()).unwrap();
+
+        let mut log = LogEvent::from("disk full");
+        log.insert("level", "error");
+        log.insert("host", "db-1");
+        let (body_bytes, key) = encode_event(log.into(), &subject, &body)
+            .unwrap()
+            .item
+            .into_parts();
+        assert_eq!(key, "[error] db-1");
+        assert_eq!(&body_bytes[..], b"disk full");
+
+        assert!(encode_event(Event::from("no level"), &subject, &body).is_none());
+    }
+
+    #[test]
+    fn builds_digests() {
+        let service = SmtpService {
+            transport: AsyncSmtpTransport::<Tokio1Executor>::unencrypted_localhost(),
+            from: "Vector <vector@example.com>".parse().unwrap(),
+            to: vec!["oncall@example.com".parse().unwrap()],
+        };
+        let request = build_request(PartitionInnerBuffer::new(
+            vec![Bytes::from("disk full"), Bytes::from("disk still full")],
+            "[error] db-1".to_owned(),
+        ));
+        let message = String::from_utf8(service.message(&request).unwrap().formatted()).unwrap();
+
+        assert!(message.contains("Subject: [error] db-1"));
+        assert!(message.contains("disk full"));
+        assert!(message.contains("disk still full"));
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        assert!(parse_mailbox("from", "Vector <vector@example.com>").is_ok());
+        assert!(parse_mailbox("from", "vector").is_err());
+    }
+}
@@ -0,0 +1,409 @@
+//! Splunk's indexer acknowledgements, which hold the end-to-end
+//! acknowledgements of the events of each request until Splunk reports them
+//! indexed, rather than only received by the HEC endpoint.
+use super::build_uri;
+use crate::{
+    http::HttpClient,
+    internal_events::{
+        SplunkAckQueryFailed, SplunkAcksExpired, SplunkChannelRotated,
+        SplunkIndexerAcknowledgementUnavailable,
+    },
+};
+use bytes::Bytes;
+use http::{Request, StatusCode};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+use vector_core::event::{EventFinalizers, EventStatus};
+
+pub(super) const CHANNEL_HEADER: &str = "X-Splunk-Request-Channel";
+
+/// The HEC error code of requests on channels the indexers don't know.
+const INVALID_CHANNEL_CODE: u64 = 11;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct HecSinkAcknowledgementsConfig {
+    pub indexer_acknowledgements_enabled: bool,
+    pub query_interval_secs: u64,
+    /// How many times an acknowledgement is queried before its events are
+    /// considered lost
+    pub retry_limit: u8,
+    /// Requests wait past this many pending acknowledgements
+    pub max_pending_acks: u64,
+}
+
+impl Default for HecSinkAcknowledgementsConfig {
+    fn default() -> Self {
+        Self {
+            indexer_acknowledgements_enabled: false,
+            query_interval_secs: 10,
+            retry_limit: 30,
+            max_pending_acks: 1_000_000,
+        }
+    }
+}
+
+/// The body of `/services/collector/event` responses.
+#[derive(Deserialize, Debug)]
+struct HecEventResponse {
+    #[serde(rename = "ackId")]
+    ack_id: Option<u64>,
+}
+
+/// The body of `/services/collector/ack` requests.
+#[derive(Serialize, Debug)]
+struct HecAckStatusRequest<'a> {
+    acks: &'a [u64],
+}
+
+#[derive(Deserialize, Debug)]
+struct HecAckStatusResponse {
+    acks: HashMap<u64, bool>,
+}
+
+#[derive(Deserialize, Debug)]
+struct HecErrorResponse {
+    code: u64,
+}
+
+/// The channel a request was sent on, kept in the extensions of its
+/// response, with the room it took among the pending acknowledgements.
+#[derive(Debug)]
+pub(super) struct RequestChannel {
+    channel: String,
+    permit: OwnedSemaphorePermit,
+}
+
+impl RequestChannel {
+    pub(super) fn name(&self) -> &str {
+        &self.channel
+    }
+}
+
+#[derive(Debug)]
+struct PendingAck {
+    finalizers: EventFinalizers,
+    queries: u8,
+}
+
+#[derive(Debug)]
+pub(super) struct HecAckClient {
+    client: HttpClient,
+    endpoint: String,
+    token: String,
+    retry_limit: u8,
+    query_interval: Duration,
+    /// The channel new requests are sent on. Splunk forgets the pending
+    /// acknowledgements of channels when indexers restart, so the channel
+    /// is replaced when its acknowledgements are lost.
+    channel: Mutex<String>,
+    pending: Mutex<HashMap<String, HashMap<u64, PendingAck>>>,
+    permits: Arc<Semaphore>,
+}
+
+impl HecAckClient {
+    pub(super) fn new(
+        config: &HecSinkAcknowledgementsConfig,
+        client: HttpClient,
+        endpoint: &str,
+        token: &str,
+    ) -> Self {
+        let max_pending_acks = (config.max_pending_acks as usize).min(Semaphore::MAX_PERMITS);
+        Self {
+            client,
+            endpoint: endpoint.to_owned(),
+            token: token.to_owned(),
+            retry_limit: config.retry_limit.max(1),
+            query_interval: Duration::from_secs(config.query_interval_secs.max(1)),
+            channel: Mutex::new(new_channel()),
+            pending: Mutex::new(HashMap::new()),
+            permits: Arc::new(Semaphore::new(max_pending_acks.max(1))),
+        }
+    }
+
+    /// Waits for room among the pending acknowledgements, and returns the
+    /// channel to send a request on.
+    pub(super) async fn reserve(&self) -> RequestChannel {
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("Acknowledgements semaphore closed");
+        RequestChannel {
+            channel: self.channel.lock().expect("poisoned lock").clone(),
+            permit,
+        }
+    }
+
+    /// Holds the finalizers of a request until its acknowledgement is
+    /// queried. Requests without an acknowledgement ID are delivered at once.
+    pub(super) fn add(&self, channel: RequestChannel, body: &Bytes, finalizers: EventFinalizers) {
+        let ack_id = serde_json::from_slice::<HecEventResponse>(body)
+            .ok()
+            .and_then(|response| response.ack_id);
+        let ack_id = match ack_id {
+            Some(ack_id) => ack_id,
+            None => {
+                emit!(SplunkIndexerAcknowledgementUnavailable);
+                finalizers.update_status(EventStatus::Delivered);
+                return;
+            }
+        };
+
+        // The permit is given back once the acknowledgement is removed.
+        channel.permit.forget();
+        let ack = PendingAck {
+            finalizers,
+            queries: 0,
+        };
+        self.pending
+            .lock()
+            .expect("poisoned lock")
+            .entry(channel.channel)
+            .or_default()
+            .insert(ack_id, ack);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pending.lock().expect("poisoned lock").is_empty()
+    }
+
+    /// Queries the pending acknowledgements until the sink is dropped and
+    /// none are left.
+    pub(super) async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(self.query_interval).await;
+            // The sink holds the other references.
+            if Arc::strong_count(&self) == 1 && self.is_empty() {
+                break;
+            }
+
+            let channels = self
+                .pending
+                .lock()
+                .expect("poisoned lock")
+                .iter()
+                .map(|(channel, acks)| (channel.clone(), acks.keys().copied().collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            for (channel, ack_ids) in channels {
+                let result = self.query(&channel, &ack_ids).await;
+                self.update(&channel, result);
+            }
+        }
+    }
+
+    async fn query(&self, channel: &str, ack_ids: &[u64]) -> Result<HashMap<u64, bool>, AckError> {
+        let uri =
+            build_uri(&self.endpoint, "/services/collector/ack").expect("Unable to parse URI");
+        let body = serde_json::to_vec(&HecAckStatusRequest { acks: ack_ids })
+            .expect("Ack IDs are always serializable");
+        let request = Request::post(uri)
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Splunk {}", self.token))
+            .header(CHANNEL_HEADER, channel)
+            .body(Body::from(body))
+            .map_err(|error| AckError::Request(error.into()))?;
+
+        let response = self
+            .client
+            .send(request)
+            .await
+            .map_err(|error| AckError::Request(error.into()))?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|error| AckError::Request(error.into()))?;
+        match status {
+            StatusCode::OK => serde_json::from_slice::<HecAckStatusResponse>(&body)
+                .map(|response| response.acks)
+                .map_err(|error| AckError::Request(error.into())),
+            StatusCode::BAD_REQUEST
+                if serde_json::from_slice::<HecErrorResponse>(&body)
+                    .map_or(false, |response| response.code == INVALID_CHANNEL_CODE) =>
+            {
+                Err(AckError::InvalidChannel)
+            }
+            status => Err(AckError::Request(
+                format!("Unexpected status: {}", status).into(),
+            )),
+        }
+    }
+
+    /// Finalizes the acknowledgements reported indexed, and those that were
+    /// queried too many times or whose channel was lost.
+    fn update(&self, channel: &str, result: Result<HashMap<u64, bool>, AckError>) {
+        let (statuses, lost) = match result {
+            Ok(statuses) => (Some(statuses), false),
+            Err(AckError::InvalidChannel) => (None, true),
+            Err(AckError::Request(error)) => {
+                emit!(SplunkAckQueryFailed { error: &error });
+                (None, false)
+            }
+        };
+
+        let mut pending = self.pending.lock().expect("poisoned lock");
+        let acks = match pending.get_mut(channel) {
+            Some(acks) => acks,
+            None => return,
+        };
+        let count = acks.len();
+        let retry_limit = self.retry_limit;
+        let mut expired = 0;
+        acks.retain(|ack_id, ack| {
+            let indexed = statuses
+                .as_ref()
+                .and_then(|statuses| statuses.get(ack_id).copied())
+                .unwrap_or(false);
+            if indexed {
+                ack.finalizers.update_status(EventStatus::Delivered);
+                return false;
+            }
+            ack.queries += 1;
+            if lost || ack.queries >= retry_limit {
+                ack.finalizers.update_status(EventStatus::Errored);
+                expired += 1;
+                return false;
+            }
+            true
+        });
+        let removed = count - acks.len();
+        if acks.is_empty() {
+            pending.remove(channel);
+        }
+        drop(pending);
+        self.permits.add_permits(removed);
+
+        if expired > 0 {
+            emit!(SplunkAcksExpired { count: expired });
+            self.rotate(channel);
+        }
+    }
+
+    /// Sends new requests on a new channel, if the lost channel is the
+    /// current one.
+    fn rotate(&self, lost: &str) {
+        let mut channel = self.channel.lock().expect("poisoned lock");
+        if *channel == lost {
+            *channel = new_channel();
+            emit!(SplunkChannelRotated {
+                previous: lost,
+                channel: &channel,
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+enum AckError {
+    InvalidChannel,
+    Request(crate::Error),
+}
+
+fn new_channel() -> String {
+    Uuid::new_v4().to_hyphenated().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProxyConfig;
+    use tokio::sync::oneshot::error::TryRecvError;
+    use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizer};
+
+    fn ack_client(retry_limit: u8) -> HecAckClient {
+        let config = HecSinkAcknowledgementsConfig {
+            indexer_acknowledgements_enabled: true,
+            retry_limit,
+            max_pending_acks: 10,
+            ..Default::default()
+        };
+        let client = HttpClient::new(None, &ProxyConfig::default()).unwrap();
+        HecAckClient::new(&config, client, "http://localhost:8088", "token")
+    }
+
+    /// Sends a request with an acknowledgement ID on the current channel.
+    async fn send(acks: &HecAckClient, ack_id: u64) -> (String, BatchStatusReceiver) {
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let channel = acks.reserve().await;
+        let name = channel.name().to_owned();
+        let body = format!(r#"{{"text":"Success","code":0,"ackId":{}}}"#, ack_id);
+        acks.add(
+            channel,
+            &Bytes::from(body),
+            EventFinalizers::new(EventFinalizer::new(batch)),
+        );
+        (name, receiver)
+    }
+
+    fn statuses(statuses: &[(u64, bool)]) -> Result<HashMap<u64, bool>, AckError> {
+        Ok(statuses.iter().copied().collect())
+    }
+
+    #[tokio::test]
+    async fn holds_finalizers_until_indexed() {
+        let acks = ack_client(30);
+        let (channel, mut receiver) = send(&acks, 7).await;
+        assert_eq!(acks.permits.available_permits(), 9);
+
+        acks.update(&channel, statuses(&[(7, false)]));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+        acks.update(&channel, statuses(&[(7, true)]));
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert!(acks.is_empty());
+        assert_eq!(acks.permits.available_permits(), 10);
+    }
+
+    #[tokio::test]
+    async fn delivers_responses_without_ack_id() {
+        let acks = ack_client(30);
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let channel = acks.reserve().await;
+        acks.add(
+            channel,
+            &Bytes::from(r#"{"text":"Success","code":0}"#),
+            EventFinalizers::new(EventFinalizer::new(batch)),
+        );
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Delivered));
+        assert!(acks.is_empty());
+        assert_eq!(acks.permits.available_permits(), 10);
+    }
+
+    #[tokio::test]
+    async fn errors_expired_acks_and_rotates_channel() {
+        let acks = ack_client(2);
+        let (channel, mut receiver) = send(&acks, 0).await;
+
+        acks.update(&channel, statuses(&[(0, false)]));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        acks.update(&channel, Err(AckError::Request("timed out".into())));
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Errored));
+
+        assert!(acks.is_empty());
+        assert_eq!(acks.permits.available_permits(), 10);
+        assert_ne!(*acks.channel.lock().unwrap(), channel);
+    }
+
+    #[tokio::test]
+    async fn errors_acks_of_invalid_channel() {
+        let acks = ack_client(30);
+        let (channel, mut first) = send(&acks, 0).await;
+        let (_, mut second) = send(&acks, 1).await;
+
+        acks.update(&channel, Err(AckError::InvalidChannel));
+        assert_eq!(first.try_recv(), Ok(BatchStatus::Errored));
+        assert_eq!(second.try_recv(), Ok(BatchStatus::Errored));
+        assert_ne!(*acks.channel.lock().unwrap(), channel);
+
+        // Requests after the rotation are sent on the new channel.
+        let (rotated, _receiver) = send(&acks, 0).await;
+        assert_ne!(rotated, channel);
+    }
+}
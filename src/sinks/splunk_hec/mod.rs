@@ -1,23 +1,34 @@
+mod acknowledgements;
+
+pub use self::acknowledgements::HecSinkAcknowledgementsConfig;
+use self::acknowledgements::{HecAckClient, RequestChannel, CHANNEL_HEADER};
 use crate::{
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
-    event::{Event, LogEvent, Value},
+    event::{Event, EventFinalizers, EventStatus, LogEvent, Value},
     http::HttpClient,
     internal_events::{SplunkEventEncodeError, SplunkEventSent, TemplateRenderingFailed},
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
-        http::{BatchedHttpSink, HttpSink},
-        BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig,
+        http::{HttpRetryLogic, HttpSink},
+        sink::{ServiceLogic, StdServiceLogic},
+        BatchConfig, BatchSettings, Buffer, Compression, EncodedEvent, TowerRequestConfig,
     },
     template::Template,
     tls::{TlsOptions, TlsSettings},
 };
-use futures::{FutureExt, SinkExt};
-use http::{Request, StatusCode, Uri};
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream, FutureExt, SinkExt};
+use http::{HeaderValue, Request, StatusCode, Uri};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use snafu::{ResultExt, Snafu};
-use std::convert::TryFrom;
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::Service;
 
 #[derive(Debug, Snafu)]
 pub enum BuildError {
@@ -47,6 +58,8 @@ pub struct HecSinkConfig {
     #[serde(default)]
     pub request: TowerRequestConfig,
     pub tls: Option<TlsOptions>,
+    #[serde(default)]
+    pub acknowledgements: HecSinkAcknowledgementsConfig,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Derivative)]
@@ -79,6 +92,7 @@ impl GenerateConfig for HecSinkConfig {
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
             tls: None,
+            acknowledgements: HecSinkAcknowledgementsConfig::default(),
         })
         .unwrap()
     }
@@ -101,15 +115,45 @@ impl SinkConfig for HecSinkConfig {
         let tls_settings = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(tls_settings, &cx.proxy)?;
 
-        let sink = BatchedHttpSink::new(
-            self.clone(),
-            Buffer::new(batch.size, self.compression),
-            request,
-            batch.timeout,
-            client.clone(),
-            cx.acker(),
-        )
-        .sink_map_err(|error| error!(message = "Fatal splunk_hec sink error.", %error));
+        let acknowledgements = self
+            .acknowledgements
+            .indexer_acknowledgements_enabled
+            .then(|| {
+                Arc::new(HecAckClient::new(
+                    &self.acknowledgements,
+                    client.clone(),
+                    &self.endpoint,
+                    &self.token,
+                ))
+            });
+        if let Some(acknowledgements) = &acknowledgements {
+            tokio::spawn(Arc::clone(acknowledgements).run());
+        }
+
+        let config = Arc::new(self.clone());
+        let service = HecService {
+            config: Arc::clone(&config),
+            client: client.clone(),
+            acknowledgements: acknowledgements.clone(),
+        };
+        let sink = request
+            .batch_sink(
+                HttpRetryLogic,
+                service,
+                Buffer::new(batch.size, self.compression),
+                batch.timeout,
+                cx.acker(),
+                HecServiceLogic { acknowledgements },
+            )
+            .with_flat_map(move |mut event: Event| {
+                let finalizers = event.metadata_mut().take_finalizers();
+                stream::iter(
+                    config
+                        .encode_event(event)
+                        .map(|item| Ok(EncodedEvent { item, finalizers })),
+                )
+            })
+            .sink_map_err(|error| error!(message = "Fatal splunk_hec sink error.", %error));
 
         let healthcheck = healthcheck(self.clone(), client).boxed();
 
@@ -251,6 +295,83 @@ impl HttpSink for HecSinkConfig {
     }
 }
 
+/// Sends batches to the event endpoint, on a channel of the indexer
+/// acknowledgements if they're enabled.
+#[derive(Clone)]
+struct HecService {
+    config: Arc<HecSinkConfig>,
+    client: HttpClient,
+    acknowledgements: Option<Arc<HecAckClient>>,
+}
+
+impl Service<Vec<u8>> for HecService {
+    type Response = http::Response<Bytes>;
+    type Error = crate::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, body: Vec<u8>) -> Self::Future {
+        let config = Arc::clone(&self.config);
+        let client = self.client.clone();
+        let acknowledgements = self.acknowledgements.clone();
+
+        Box::pin(async move {
+            let channel = match &acknowledgements {
+                Some(acknowledgements) => Some(acknowledgements.reserve().await),
+                None => None,
+            };
+            let mut request = config.build_request(body).await?;
+            if let Some(channel) = &channel {
+                request
+                    .headers_mut()
+                    .insert(CHANNEL_HEADER, HeaderValue::from_str(channel.name())?);
+            }
+
+            let response = client.send(request.map(Body::from)).await?;
+            let (mut parts, body) = response.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            if let Some(channel) = channel {
+                parts.extensions.insert(channel);
+            }
+            Ok(http::Response::from_parts(parts, body))
+        })
+    }
+}
+
+/// Holds the finalizers of successful requests until their events are
+/// indexed, if indexer acknowledgements are enabled.
+#[derive(Clone)]
+struct HecServiceLogic {
+    acknowledgements: Option<Arc<HecAckClient>>,
+}
+
+impl ServiceLogic for HecServiceLogic {
+    type Response = http::Response<Bytes>;
+
+    fn result_status(&self, result: crate::Result<Self::Response>) -> EventStatus {
+        StdServiceLogic::default().result_status(result)
+    }
+
+    fn update_finalizers(
+        &self,
+        result: crate::Result<Self::Response>,
+        finalizers: EventFinalizers,
+    ) {
+        match (&self.acknowledgements, result) {
+            (Some(acknowledgements), Ok(mut response)) if response.status().is_success() => {
+                match response.extensions_mut().remove::<RequestChannel>() {
+                    Some(channel) => acknowledgements.add(channel, response.body(), finalizers),
+                    None => finalizers.update_status(EventStatus::Delivered),
+                }
+            }
+            (_, result) => finalizers.update_status(self.result_status(result)),
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 enum HealthcheckError {
     #[snafu(display("Invalid HEC token"))]
@@ -797,6 +918,7 @@ mod integration_tests {
             },
             request: TowerRequestConfig::default(),
             tls: None,
+            acknowledgements: Default::default(),
         }
     }
 
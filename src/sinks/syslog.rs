@@ -0,0 +1,432 @@
+use crate::{
+    config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, Value},
+    internal_events::{SyslogInvalidPriority, TemplateRenderingFailed},
+    sinks::util::{
+        encoding::{EncodingConfig, EncodingConfiguration},
+        tcp::TcpSinkConfig,
+        udp::UdpSinkConfig,
+        Encoding,
+    },
+    template::Template,
+};
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// RFC 5424 limits the length of the fields of the header.
+const MAX_HOSTNAME_LENGTH: usize = 255;
+const MAX_APP_NAME_LENGTH: usize = 48;
+const MAX_PROC_ID_LENGTH: usize = 128;
+const MAX_MSG_ID_LENGTH: usize = 32;
+/// RFC 3164 limits the tag, the name of the program, to 32 characters.
+const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Deserialize, Serialize, Debug)]
+// TODO: add back when serde-rs/serde#1358 is addressed
+// #[serde(deny_unknown_fields)]
+pub struct SyslogSinkConfig {
+    #[serde(flatten)]
+    mode: Mode,
+    #[serde(default)]
+    format: Format,
+    /// Defaults to octet counting over TCP. Ignored over UDP, where each
+    /// datagram holds a single message.
+    framing: Option<Framing>,
+    /// Renders to the name or the code of the facility.
+    #[serde(default = "default_facility")]
+    facility: Template,
+    /// Renders to the name or the code of the severity.
+    #[serde(default = "default_severity")]
+    severity: Template,
+    #[serde(default = "default_app_name")]
+    app_name: Template,
+    proc_id: Option<Template>,
+    /// Only sent with RFC 5424.
+    msg_id: Option<Template>,
+    encoding: EncodingConfig<Encoding>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum Mode {
+    Tcp(TcpSinkConfig),
+    Udp(UdpSinkConfig),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+enum Format {
+    Rfc3164,
+    #[derivative(Default)]
+    Rfc5424,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Framing {
+    /// Prefixes each message with its length, as in RFC 6587.
+    OctetCounting,
+    /// Terminates each message with a newline.
+    Newline,
+}
+
+fn default_facility() -> Template {
+    Template::try_from("user").expect("Template should be valid")
+}
+
+fn default_severity() -> Template {
+    Template::try_from("info").expect("Template should be valid")
+}
+
+fn default_app_name() -> Template {
+    Template::try_from("vector").expect("Template should be valid")
+}
+
+inventory::submit! {
+    SinkDescription::new::<SyslogSinkConfig>("syslog")
+}
+
+impl GenerateConfig for SyslogSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "127.0.0.1:514"
+            mode = "tcp"
+            encoding.codec = "text""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "syslog")]
+impl SinkConfig for SyslogSinkConfig {
+    async fn build(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::VectorSink, super::Healthcheck)> {
+        let framing = match self.mode {
+            Mode::Tcp(_) => Some(self.framing.unwrap_or(Framing::OctetCounting)),
+            Mode::Udp(_) => None,
+        };
+        let encoder = Encoder {
+            format: self.format,
+            framing,
+            facility: self.facility.clone(),
+            severity: self.severity.clone(),
+            app_name: self.app_name.clone(),
+            proc_id: self.proc_id.clone(),
+            msg_id: self.msg_id.clone(),
+            encoding: self.encoding.clone(),
+            hostname: crate::get_hostname().ok(),
+        };
+        let encode_event = move |event| encoder.encode(event);
+
+        match &self.mode {
+            Mode::Tcp(config) => config.build(cx, encode_event),
+            Mode::Udp(config) => config.build(cx, encode_event),
+        }
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "syslog"
+    }
+}
+
+struct Encoder {
+    format: Format,
+    framing: Option<Framing>,
+    facility: Template,
+    severity: Template,
+    app_name: Template,
+    proc_id: Option<Template>,
+    msg_id: Option<Template>,
+    encoding: EncodingConfig<Encoding>,
+    /// The hostname of Vector, sent for events without a host.
+    hostname: Option<String>,
+}
+
+impl Encoder {
+    fn encode(&self, mut event: Event) -> Option<Bytes> {
+        let facility = self.priority(&self.facility, &event, "facility", facility_code, 1);
+        let severity = self.priority(&self.severity, &event, "severity", severity_code, 6);
+        let app_name = self.render(&self.app_name, &event, "app_name");
+        let proc_id = self
+            .proc_id
+            .as_ref()
+            .and_then(|template| self.render(template, &event, "proc_id"));
+        let msg_id = self
+            .msg_id
+            .as_ref()
+            .and_then(|template| self.render(template, &event, "msg_id"));
+
+        let log = event.as_log();
+        let hostname = log
+            .get(log_schema().host_key())
+            .map(Value::to_string_lossy)
+            .or_else(|| self.hostname.clone());
+        let timestamp = match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => *timestamp,
+            _ => Utc::now(),
+        };
+
+        self.encoding.apply_rules(&mut event);
+        let log = event.into_log();
+        let message = match self.encoding.codec() {
+            Encoding::Json => serde_json::to_string(&log)
+                .map_err(|error| error!(message = "Unable to encode.", %error))
+                .ok()?,
+            Encoding::Text => log
+                .get(log_schema().message_key())
+                .map(Value::to_string_lossy)
+                .unwrap_or_default(),
+        };
+
+        let priority = facility * 8 + severity;
+        let line = match self.format {
+            Format::Rfc5424 => format!(
+                "<{}>1 {} {} {} {} {} - {}",
+                priority,
+                timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+                header_field(hostname.as_deref(), MAX_HOSTNAME_LENGTH),
+                header_field(app_name.as_deref(), MAX_APP_NAME_LENGTH),
+                header_field(proc_id.as_deref(), MAX_PROC_ID_LENGTH),
+                header_field(msg_id.as_deref(), MAX_MSG_ID_LENGTH),
+                message
+            ),
+            Format::Rfc3164 => format!(
+                "<{}>{} {} {}: {}",
+                priority,
+                rfc3164_timestamp(timestamp),
+                header_field(hostname.as_deref(), MAX_HOSTNAME_LENGTH),
+                rfc3164_tag(app_name.as_deref(), proc_id.as_deref()),
+                message
+            ),
+        };
+
+        Some(frame(line, self.framing))
+    }
+
+    fn render(&self, template: &Template, event: &Event, field: &str) -> Option<String> {
+        template
+            .render_string(event)
+            .map_err(|error| {
+                emit!(TemplateRenderingFailed {
+                    error,
+                    field: Some(field),
+                    drop_event: false,
+                });
+            })
+            .ok()
+    }
+
+    /// Renders the facility or the severity of the event, falling back to
+    /// `default` when it doesn't render to a known name or code.
+    fn priority(
+        &self,
+        template: &Template,
+        event: &Event,
+        field: &'static str,
+        code: fn(&str) -> Option<u8>,
+        default: u8,
+    ) -> u8 {
+        let value = match self.render(template, event, field) {
+            Some(value) => value,
+            None => return default,
+        };
+        code(value.trim()).unwrap_or_else(|| {
+            emit!(SyslogInvalidPriority {
+                field,
+                value: &value,
+            });
+            default
+        })
+    }
+}
+
+fn facility_code(value: &str) -> Option<u8> {
+    if let Ok(code) = value.parse::<u8>() {
+        return (code <= 23).then(|| code);
+    }
+    let code = match value.to_ascii_lowercase().as_str() {
+        "kern" => 0,
+        "user" => 1,
+        "mail" => 2,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "lpr" => 6,
+        "news" => 7,
+        "uucp" => 8,
+        "cron" => 9,
+        "authpriv" => 10,
+        "ftp" => 11,
+        "ntp" => 12,
+        "security" => 13,
+        "console" => 14,
+        "solaris-cron" => 15,
+        "local0" => 16,
+        "local1" => 17,
+        "local2" => 18,
+        "local3" => 19,
+        "local4" => 20,
+        "local5" => 21,
+        "local6" => 22,
+        "local7" => 23,
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn severity_code(value: &str) -> Option<u8> {
+    if let Ok(code) = value.parse::<u8>() {
+        return (code <= 7).then(|| code);
+    }
+    let code = match value.to_ascii_lowercase().as_str() {
+        "emerg" | "emergency" => 0,
+        "alert" => 1,
+        "crit" | "critical" => 2,
+        "err" | "error" => 3,
+        "warning" | "warn" => 4,
+        "notice" => 5,
+        "info" | "informational" => 6,
+        "debug" => 7,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Fields of the header are printable ASCII without spaces, or `-` when
+/// they have no value.
+fn header_field(value: Option<&str>, max_length: usize) -> String {
+    let field = value
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_length)
+        .collect::<String>();
+    if field.is_empty() {
+        "-".to_owned()
+    } else {
+        field
+    }
+}
+
+/// The day of the month is padded with a space, and there is neither the
+/// year nor the time zone, which is UTC.
+fn rfc3164_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%b %e %H:%M:%S").to_string()
+}
+
+fn rfc3164_tag(app_name: Option<&str>, proc_id: Option<&str>) -> String {
+    let tag = app_name
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+        .take(MAX_TAG_LENGTH)
+        .collect::<String>();
+    let tag = if tag.is_empty() {
+        "vector".to_owned()
+    } else {
+        tag
+    };
+    match proc_id.filter(|proc_id| !proc_id.is_empty()) {
+        Some(proc_id) => format!(
+            "{}[{}]",
+            tag,
+            header_field(Some(proc_id), MAX_PROC_ID_LENGTH)
+        ),
+        None => tag,
+    }
+}
+
+fn frame(line: String, framing: Option<Framing>) -> Bytes {
+    match framing {
+        Some(Framing::OctetCounting) => format!("{} {}", line.len(), line).into(),
+        Some(Framing::Newline) => {
+            // Messages can't span lines when they are delimited by them.
+            let mut line = line.replace('\n', " ");
+            line.push('\n');
+            line.into()
+        }
+        None => line.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SyslogSinkConfig>();
+    }
+
+    fn encoder(format: Format, framing: Option<Framing>) -> Encoder {
+        Encoder {
+            format,
+            framing,
+            facility: Template::try_from("{{ facility }}").unwrap(),
+            severity: Template::try_from("{{ level }}").unwrap(),
+            app_name: default_app_name(),
+            proc_id: Some(Template::try_from("{{ pid }}").unwrap()),
+            msg_id: None,
+            encoding: EncodingConfig::from(Encoding::Text),
+            hostname: Some("vector-host".to_owned()),
+        }
+    }
+
+    fn event() -> Event {
+        let mut event = Event::from("user logged in");
+        let log = event.as_mut_log();
+        log.insert(
+            log_schema().timestamp_key(),
+            Utc.ymd(2021, 8, 5).and_hms_micro(9, 4, 12, 5),
+        );
+        log.insert("facility", "auth");
+        log.insert("level", "warning");
+        log.insert("pid", 1234);
+        event
+    }
+
+    #[test]
+    fn encodes_rfc5424_with_octet_counting() {
+        let bytes = encoder(Format::Rfc5424, Some(Framing::OctetCounting))
+            .encode(event())
+            .unwrap();
+        let line = "<36>1 2021-08-05T09:04:12.000005Z vector-host vector 1234 - - user logged in";
+        assert_eq!(bytes, format!("{} {}", line.len(), line));
+    }
+
+    #[test]
+    fn encodes_rfc3164_with_newlines() {
+        let mut event = event();
+        event.as_mut_log().insert("host", "web-1");
+        event.as_mut_log().insert("facility", 20);
+        event.as_mut_log().insert("level", "bogus");
+
+        let bytes = encoder(Format::Rfc3164, Some(Framing::Newline))
+            .encode(event)
+            .unwrap();
+        assert_eq!(
+            bytes,
+            "<166>Aug  5 09:04:12 web-1 vector[1234]: user logged in\n"
+        );
+    }
+
+    #[test]
+    fn parses_priorities() {
+        assert_eq!(facility_code("local7"), Some(23));
+        assert_eq!(facility_code("3"), Some(3));
+        assert_eq!(facility_code("24"), None);
+        assert_eq!(severity_code("ERR"), Some(3));
+        assert_eq!(severity_code("8"), None);
+    }
+}
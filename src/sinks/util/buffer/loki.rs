@@ -24,6 +24,8 @@ pub type Labels = Vec<(String, String)>;
 pub struct LokiEvent {
     pub timestamp: i64,
     pub event: String,
+    /// Labels of the entry which aren't indexed, nor part of the stream.
+    pub structured_metadata: Labels,
 }
 
 #[derive(Clone, Debug)]
@@ -41,12 +43,21 @@ struct LokiEncodedEvent {
 
 impl From<&LokiEvent> for LokiEncodedEvent {
     // Pre-encode the record to JSON, but keep the timestamp for sorting at the end.
-    // The final output should be: `[ts, line]'
+    // The final output should be: `[ts, line]', or `[ts, line, {metadata}]'
     fn from(event: &LokiEvent) -> Self {
+        let encoded = if event.structured_metadata.is_empty() {
+            json!([format!("{}", event.timestamp), event.event])
+        } else {
+            let metadata = event
+                .structured_metadata
+                .iter()
+                .cloned()
+                .collect::<HashMap<_, _>>();
+            json!([format!("{}", event.timestamp), event.event, metadata])
+        };
         Self {
             timestamp: event.timestamp,
-            encoded: to_raw_value(&json!([format!("{}", event.timestamp), event.event]))
-                .expect("JSON encoding should never fail"),
+            encoded: to_raw_value(&encoded).expect("JSON encoding should never fail"),
         }
     }
 }
@@ -142,6 +153,7 @@ impl Batch for LokiBuffer {
             .unwrap_or(item.event.timestamp);
         if item.event.timestamp < latest_timestamp {
             match self.out_of_order_action {
+                OutOfOrderAction::Accept => {}
                 OutOfOrderAction::Drop => {
                     warn!(
                         msg = "Received out-of-order event; dropping event.",
@@ -300,6 +312,7 @@ mod tests {
                 event: LokiEvent {
                     timestamp: 123456789,
                     event: "this is an event".into(),
+                    structured_metadata: vec![],
                 },
             }),
             PushResult::Ok(false)
@@ -328,6 +341,7 @@ mod tests {
                     event: LokiEvent {
                         timestamp: 123456780 + n,
                         event: format!("event #{}", n),
+                        structured_metadata: vec![],
                     },
                 }),
                 PushResult::Ok(false)
@@ -357,6 +371,7 @@ mod tests {
                     event: LokiEvent {
                         timestamp: 123456780 + n,
                         event: format!("event #{}", n),
+                        structured_metadata: vec![],
                     },
                 }),
                 PushResult::Ok(false)
@@ -370,4 +385,64 @@ mod tests {
             r#"{"streams":[{"stream":{"asdf":"value1"},"values":[["123456781","event #1"],["123456782","event #2"],["123456783","event #3"]]}]}"#,
         );
     }
+
+    #[test]
+    fn insert_structured_metadata() {
+        let mut buffer = LokiBuffer::new(
+            BatchSettings::default().size,
+            Default::default(),
+            Default::default(),
+        );
+        assert!(matches!(
+            buffer.push(LokiRecord {
+                partition: PartitionKey { tenant_id: None },
+                labels: vec![("label1".into(), "value1".into())],
+                event: LokiEvent {
+                    timestamp: 123456789,
+                    event: "this is an event".into(),
+                    structured_metadata: vec![("trace_id".into(), "0242ac120002".into())],
+                },
+            }),
+            PushResult::Ok(false)
+        ));
+
+        test_finish(
+            buffer,
+            r#"{"streams":[{"stream":{"label1":"value1"},"values":[["123456789","this is an event",{"trace_id":"0242ac120002"}]]}]}"#,
+        );
+    }
+
+    #[test]
+    fn accept_out_of_order() {
+        let record = |timestamp| LokiRecord {
+            partition: PartitionKey { tenant_id: None },
+            labels: vec![("label1".into(), "value1".into())],
+            event: LokiEvent {
+                timestamp,
+                event: "this is an event".into(),
+                structured_metadata: vec![],
+            },
+        };
+        let mut buffer = LokiBuffer::new(
+            BatchSettings::default().size,
+            Default::default(),
+            OutOfOrderAction::Accept,
+        );
+        assert!(matches!(
+            buffer.push(record(123456789)),
+            PushResult::Ok(false)
+        ));
+        let mut next = buffer.fresh();
+        buffer.finish();
+
+        // Older than the last entry of the stream sent.
+        assert!(matches!(
+            next.push(record(123456788)),
+            PushResult::Ok(false)
+        ));
+        test_finish(
+            next,
+            r#"{"streams":[{"stream":{"label1":"value1"},"values":[["123456788","this is an event"]]}]}"#,
+        );
+    }
 }
@@ -0,0 +1,495 @@
+//! A circuit breaker, which stops sending requests to a downstream that
+//! keeps failing rather than retrying against it as fast as retries allow.
+//!
+//! The breaker opens after `failure_threshold` consecutive failed attempts,
+//! counting the attempts that took longer than `latency_threshold_secs` as
+//! failed. While it's open, requests are held back, which backs pressure up
+//! to the buffer of the sink, or shed when `shed` is set. The events of shed
+//! requests are errored, which sends them to the `dead_letter` output of the
+//! sink when that output is consumed, and drops them otherwise. After
+//! `open_duration_secs`, one request probes the downstream: the breaker
+//! closes if it succeeds, and opens again otherwise.
+
+use crate::{
+    internal_events::{CircuitBreakerClosed, CircuitBreakerOpened, CircuitBreakerRequestShed},
+    sinks::util::retries::{RetryAction, RetryLogic},
+};
+use futures::ready;
+use pin_project::pin_project;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::time::{sleep_until, Instant, Sleep};
+use tower::{Layer, Service};
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerSettings {
+    pub enabled: bool,
+    /// The number of consecutive failed attempts opening the breaker.
+    #[derivative(Default(value = "5"))]
+    pub failure_threshold: usize,
+    /// Attempts taking longer than this are counted as failed.
+    #[serde(deserialize_with = "deserialize_latency_threshold")]
+    pub latency_threshold_secs: Option<f64>,
+    /// How long the breaker stays open before probing the downstream.
+    #[derivative(Default(value = "30"))]
+    pub open_duration_secs: u64,
+    /// Sheds requests while the breaker is open, rather than holding them
+    /// back. Their events go to the `dead_letter` output of the sink.
+    pub shed: bool,
+}
+
+/// Only thresholds that convert to a duration are accepted, so that a bad
+/// threshold fails the config rather than panicking once a request completes.
+fn deserialize_latency_threshold<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let threshold = Option::<f64>::deserialize(deserializer)?;
+    match threshold {
+        Some(secs) if !(secs > 0.0 && secs < u64::MAX as f64) => Err(de::Error::custom(format!(
+            "`latency_threshold_secs` must be a positive number of seconds, got {}",
+            secs
+        ))),
+        _ => Ok(threshold),
+    }
+}
+
+/// The error of the requests rejected while the breaker is open.
+#[derive(Debug)]
+pub struct CircuitBreakerOpen;
+
+impl fmt::Display for CircuitBreakerOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Circuit breaker is open")
+    }
+}
+
+impl std::error::Error for CircuitBreakerOpen {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    Closed { failures: usize },
+    Open { until: Instant },
+    HalfOpen { probing: bool },
+}
+
+/// Whether a request can be sent.
+enum Admission {
+    Allow,
+    Probe,
+    Reject,
+    WaitUntil(Instant),
+    WaitForProbe,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: State,
+    /// The services waiting for the probe to complete.
+    waiters: Vec<Waker>,
+}
+
+/// The state of the breaker, shared by all clones of the service.
+#[derive(Debug)]
+pub struct Breaker {
+    settings: CircuitBreakerSettings,
+    inner: Mutex<Inner>,
+}
+
+impl Breaker {
+    fn new(settings: CircuitBreakerSettings) -> Self {
+        Self {
+            settings,
+            inner: Mutex::new(Inner {
+                state: State::Closed { failures: 0 },
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().expect("Circuit breaker lock poisoned")
+    }
+
+    fn admit(&self, cx: &mut Context<'_>) -> Admission {
+        let mut inner = self.lock();
+        loop {
+            let state = inner.state;
+            return match state {
+                State::Closed { .. } => Admission::Allow,
+                State::Open { until } if Instant::now() >= until => {
+                    inner.state = State::HalfOpen { probing: false };
+                    continue;
+                }
+                State::Open { .. } if self.settings.shed => Admission::Reject,
+                State::Open { until } => Admission::WaitUntil(until),
+                State::HalfOpen { probing: false } => {
+                    inner.state = State::HalfOpen { probing: true };
+                    Admission::Probe
+                }
+                State::HalfOpen { probing: true } if self.settings.shed => Admission::Reject,
+                State::HalfOpen { probing: true } => {
+                    inner.waiters.push(cx.waker().clone());
+                    Admission::WaitForProbe
+                }
+            };
+        }
+    }
+
+    fn record(&self, failed: bool, probe: bool) {
+        let mut inner = self.lock();
+        let state = inner.state;
+        match state {
+            State::Closed { failures } if failed => {
+                let failures = failures + 1;
+                if failures >= self.settings.failure_threshold {
+                    self.open(&mut inner, failures);
+                } else {
+                    inner.state = State::Closed { failures };
+                }
+            }
+            State::Closed { .. } => inner.state = State::Closed { failures: 0 },
+            State::HalfOpen { .. } if probe && failed => self.open(&mut inner, 1),
+            State::HalfOpen { .. } if probe => {
+                inner.state = State::Closed { failures: 0 };
+                emit!(CircuitBreakerClosed);
+                wake(&mut inner);
+            }
+            // Requests sent before the breaker opened complete after it.
+            _ => (),
+        }
+    }
+
+    /// Lets another request probe the downstream, when the probe was never
+    /// sent.
+    fn release_probe(&self) {
+        let mut inner = self.lock();
+        if inner.state == (State::HalfOpen { probing: true }) {
+            inner.state = State::HalfOpen { probing: false };
+            wake(&mut inner);
+        }
+    }
+
+    fn open(&self, inner: &mut Inner, failures: usize) {
+        let open_duration = Duration::from_secs(self.settings.open_duration_secs);
+        inner.state = State::Open {
+            until: Instant::now() + open_duration,
+        };
+        emit!(CircuitBreakerOpened {
+            failures,
+            open_duration,
+        });
+        wake(inner);
+    }
+}
+
+fn wake(inner: &mut Inner) {
+    for waker in inner.waiters.drain(..) {
+        waker.wake();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CircuitBreakerLayer<L> {
+    settings: CircuitBreakerSettings,
+    logic: L,
+}
+
+impl<L> CircuitBreakerLayer<L> {
+    pub fn new(settings: CircuitBreakerSettings, logic: L) -> Self {
+        Self { settings, logic }
+    }
+}
+
+impl<S, L: RetryLogic> Layer<S> for CircuitBreakerLayer<L> {
+    type Service = CircuitBreaker<S, L>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreaker {
+            inner,
+            breaker: Arc::new(Breaker::new(self.settings)),
+            logic: self.logic.clone(),
+            sleep: None,
+            probe: false,
+            reject: false,
+        }
+    }
+}
+
+/// Only sends requests to the inner service while the breaker allows it.
+pub struct CircuitBreaker<S, L> {
+    inner: S,
+    breaker: Arc<Breaker>,
+    logic: L,
+    sleep: Option<Pin<Box<Sleep>>>,
+    /// Whether the next request is the one probing the downstream.
+    probe: bool,
+    /// Whether the next request is rejected.
+    reject: bool,
+}
+
+impl<S, L, Request> Service<Request> for CircuitBreaker<S, L>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::Error>,
+    L: RetryLogic<Response = S::Response>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future, L>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Rejected requests don't reach the inner service, which then
+        // doesn't need to be ready.
+        if self.reject {
+            return Poll::Ready(Ok(()));
+        }
+        while self.breaker.settings.enabled && !self.probe {
+            match self.breaker.admit(cx) {
+                Admission::Allow => break,
+                Admission::Probe => self.probe = true,
+                Admission::Reject => {
+                    self.reject = true;
+                    return Poll::Ready(Ok(()));
+                }
+                Admission::WaitUntil(until) => {
+                    let sleep = self
+                        .sleep
+                        .get_or_insert_with(|| Box::pin(sleep_until(until)));
+                    if sleep.deadline() != until {
+                        sleep.as_mut().reset(until);
+                    }
+                    ready!(sleep.as_mut().poll(cx));
+                    self.sleep = None;
+                }
+                Admission::WaitForProbe => return Poll::Pending,
+            }
+        }
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        if mem::take(&mut self.reject) {
+            emit!(CircuitBreakerRequestShed);
+            return ResponseFuture::Rejected;
+        }
+
+        ResponseFuture::Called {
+            inner: self.inner.call(request),
+            breaker: Arc::clone(&self.breaker),
+            logic: self.logic.clone(),
+            start: Instant::now(),
+            probe: mem::take(&mut self.probe),
+        }
+    }
+}
+
+impl<S: Clone, L: Clone> Clone for CircuitBreaker<S, L> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            breaker: Arc::clone(&self.breaker),
+            logic: self.logic.clone(),
+            sleep: None,
+            probe: false,
+            reject: false,
+        }
+    }
+}
+
+impl<S, L> Drop for CircuitBreaker<S, L> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+impl<S: fmt::Debug, L> fmt::Debug for CircuitBreaker<S, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("inner", &self.inner)
+            .field("breaker", &self.breaker)
+            .field("probe", &self.probe)
+            .field("reject", &self.reject)
+            .finish()
+    }
+}
+
+/// Future for the `CircuitBreaker` service, which records whether the
+/// request failed.
+#[pin_project(project = ResponseFutureProj)]
+pub enum ResponseFuture<F, L> {
+    Called {
+        #[pin]
+        inner: F,
+        breaker: Arc<Breaker>,
+        logic: L,
+        start: Instant,
+        probe: bool,
+    },
+    Rejected,
+}
+
+impl<F, L, E> Future for ResponseFuture<F, L>
+where
+    F: Future<Output = Result<L::Response, E>>,
+    L: RetryLogic,
+    E: Into<crate::Error>,
+{
+    type Output = Result<L::Response, crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Called {
+                inner,
+                breaker,
+                logic,
+                start,
+                probe,
+            } => {
+                let output = ready!(inner.poll(cx)).map_err(Into::into);
+                let failed = match &output {
+                    Ok(response) => {
+                        matches!(logic.should_retry_response(response), RetryAction::Retry(_))
+                    }
+                    // Errors that aren't retried, such as invalid requests,
+                    // don't say anything about the state of the downstream.
+                    Err(error) => error
+                        .downcast_ref::<L::Error>()
+                        .map_or(true, |error| logic.is_retriable_error(error)),
+                };
+                let slow = breaker
+                    .settings
+                    .latency_threshold_secs
+                    .map_or(false, |threshold| {
+                        start.elapsed() > Duration::from_secs_f64(threshold)
+                    });
+                breaker.record(failed || slow, *probe);
+                Poll::Ready(output)
+            }
+            ResponseFutureProj::Rejected => Poll::Ready(Err(CircuitBreakerOpen.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_downcast_matches;
+    use snafu::Snafu;
+    use tokio::time::{advance, pause};
+    use tokio_test::{assert_pending, assert_ready_ok};
+    use tower_test::{assert_request_eq, mock};
+
+    #[derive(Clone, Copy, Debug, Snafu)]
+    enum TestError {
+        Unavailable,
+        Invalid,
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    struct TestRetryLogic;
+
+    impl RetryLogic for TestRetryLogic {
+        type Error = TestError;
+        type Response = String;
+
+        fn is_retriable_error(&self, error: &Self::Error) -> bool {
+            matches!(error, TestError::Unavailable)
+        }
+    }
+
+    fn settings(shed: bool) -> CircuitBreakerSettings {
+        CircuitBreakerSettings {
+            enabled: true,
+            failure_threshold: 2,
+            open_duration_secs: 10,
+            shed,
+            ..Default::default()
+        }
+    }
+
+    async fn fail(
+        service: &mut mock::Spawn<CircuitBreaker<mock::Mock<&'static str, String>, TestRetryLogic>>,
+        handle: &mut mock::Handle<&'static str, String>,
+        error: TestError,
+    ) {
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("request");
+        assert_request_eq!(handle, "request").send_error(error);
+        assert!(response.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn opens_on_consecutive_failures() {
+        pause();
+        let layer = CircuitBreakerLayer::new(settings(false), TestRetryLogic);
+        let (mut service, mut handle) = mock::spawn_layer(layer);
+
+        fail(&mut service, &mut handle, TestError::Unavailable).await;
+        // Invalid requests don't count.
+        fail(&mut service, &mut handle, TestError::Invalid).await;
+        fail(&mut service, &mut handle, TestError::Unavailable).await;
+        assert_ready_ok!(service.poll_ready());
+        fail(&mut service, &mut handle, TestError::Unavailable).await;
+        assert_pending!(service.poll_ready());
+
+        // The breaker half-opens, and the probe closes it.
+        advance(Duration::from_secs(10)).await;
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("probe");
+        assert_request_eq!(handle, "probe").send_response("ok".into());
+        assert_eq!(response.await.unwrap(), "ok");
+        assert_eq!(
+            service.get_ref().breaker.lock().state,
+            State::Closed { failures: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn sheds_while_open() {
+        pause();
+        let layer = CircuitBreakerLayer::new(settings(true), TestRetryLogic);
+        let (mut service, mut handle) = mock::spawn_layer(layer);
+        handle.allow(0);
+
+        // The mock isn't ready, so only rejected requests can be called.
+        service.get_mut().breaker.lock().state = State::Open {
+            until: Instant::now() + Duration::from_secs(10),
+        };
+        assert_ready_ok!(service.poll_ready());
+        let error = service.call("request").await.unwrap_err();
+        assert_downcast_matches!(error, CircuitBreakerOpen, CircuitBreakerOpen);
+    }
+
+    #[test]
+    fn rejects_invalid_latency_thresholds() {
+        let parse = |threshold: &str| {
+            toml::from_str::<CircuitBreakerSettings>(&format!(
+                "latency_threshold_secs = {}",
+                threshold
+            ))
+        };
+
+        assert_eq!(parse("2.5").unwrap().latency_threshold_secs, Some(2.5));
+        for threshold in &["0.0", "-1.0", "nan", "inf", "1e20"] {
+            let error = parse(threshold).unwrap_err();
+            assert!(error.to_string().contains("positive number of seconds"));
+        }
+        assert!(toml::from_str::<CircuitBreakerSettings>("")
+            .unwrap()
+            .latency_threshold_secs
+            .is_none());
+    }
+}
@@ -0,0 +1,120 @@
+//! CSV serialization of log events, as rows of the configured fields in their
+//! configured order. Objects and files start with a header row naming the
+//! fields, written by the sinks when they start one.
+
+use crate::event::{LogEvent, Value};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CsvOptions {
+    /// The fields of events written as the columns of each row, in order.
+    /// Fields events don't have are written as empty columns.
+    pub fields: Vec<String>,
+    #[serde(default = "default_delimiter", with = "ascii_char")]
+    pub delimiter: u8,
+    #[serde(default = "default_quote", with = "ascii_char")]
+    pub quote: u8,
+    /// Writes the names of the fields as the first row.
+    #[serde(default = "crate::serde::default_true")]
+    pub header: bool,
+}
+
+const fn default_delimiter() -> u8 {
+    b','
+}
+
+const fn default_quote() -> u8 {
+    b'"'
+}
+
+impl CsvOptions {
+    /// The header row, if one is written.
+    pub fn header(&self) -> Option<Vec<u8>> {
+        self.header.then(|| self.write_row(&self.fields))
+    }
+
+    /// Writes the fields of the event as a row, quoting the values holding
+    /// the delimiter, the quote or a newline. Objects and arrays are written
+    /// as JSON.
+    pub fn encode(&self, log: &LogEvent) -> Vec<u8> {
+        let values = self.fields.iter().map(|field| match log.get(field) {
+            None | Some(Value::Null) => Default::default(),
+            Some(value) => value.as_bytes(),
+        });
+        self.write_row(values)
+    }
+
+    fn write_row<I>(&self, values: I) -> Vec<u8>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut writer = ::csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .terminator(::csv::Terminator::Any(b'\n'))
+            .from_writer(Vec::new());
+        writer
+            .write_record(values)
+            .expect("Writing CSV to memory can't fail");
+        writer
+            .into_inner()
+            .expect("Writing CSV to memory can't fail")
+    }
+}
+
+/// A single ASCII character, which CSV delimiters and quotes must be.
+mod ascii_char {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(*value as char)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let value = char::deserialize(deserializer)?;
+        if value.is_ascii() {
+            Ok(value as u8)
+        } else {
+            Err(de::Error::custom(format!(
+                "{:?} isn't an ASCII character",
+                value
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_fields_in_order() {
+        let options: CsvOptions = toml::from_str(
+            r#"fields = ["message", "host", "missing", "tags"]
+            delimiter = ";""#,
+        )
+        .unwrap();
+
+        let mut log = LogEvent::from("hello; world");
+        log.insert("host", "a \"host\"");
+        log.insert("tags", vec!["a", "b"]);
+
+        assert_eq!(
+            options.header().unwrap(),
+            b"message;host;missing;tags\n".to_vec()
+        );
+        assert_eq!(
+            String::from_utf8(options.encode(&log)).unwrap(),
+            "\"hello; world\";\"a \"\"host\"\"\";;\"[\"\"a\"\",\"\"b\"\"]\"\n"
+        );
+
+        let options = CsvOptions {
+            header: false,
+            ..options
+        };
+        assert!(options.header().is_none());
+        assert!(toml::from_str::<CsvOptions>("fields = []\ndelimiter = \"é\"").is_err());
+    }
+}
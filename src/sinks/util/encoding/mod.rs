@@ -31,6 +31,10 @@
 
 mod config;
 pub use config::EncodingConfig;
+#[cfg(feature = "codecs-csv")]
+mod csv;
+#[cfg(feature = "codecs-csv")]
+pub use self::csv::CsvOptions;
 mod with_default;
 pub use with_default::EncodingConfigWithDefault;
 
@@ -76,8 +80,8 @@ pub trait EncodingConfiguration<E> {
                         log_event.remove_prune(removal, true);
                     }
                 }
-                Event::Metric(_) => {
-                    // Metrics don't get affected by this one!
+                Event::Metric(_) | Event::Trace(_) => {
+                    // Metrics and traces don't get affected by this one!
                 }
             }
         }
@@ -90,7 +94,8 @@ pub trait EncodingConfiguration<E> {
                         log_event.remove(field);
                     }
                 }
-                Event::Metric(_) => (), // Metrics don't get affected by this one!
+                // Metrics and traces don't get affected by this one!
+                Event::Metric(_) | Event::Trace(_) => (),
             }
         }
     }
@@ -115,7 +120,8 @@ pub trait EncodingConfiguration<E> {
                         TimestampFormat::Rfc3339 => (),
                     }
                 }
-                Event::Metric(_) => (), // Metrics don't get affected by this one!
+                // Metrics and traces don't get affected by this one!
+                Event::Metric(_) | Event::Trace(_) => (),
             }
         }
     }
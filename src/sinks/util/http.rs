@@ -6,7 +6,7 @@ use super::{
 };
 use crate::{
     buffers::Acker,
-    event::Event,
+    event::{Event, EventStatus},
     http::{HttpClient, HttpError},
 };
 use bytes::{Buf, Bytes};
@@ -32,6 +32,7 @@ pub trait HttpSink: Send + Sync + 'static {
     type Input;
     type Output;
 
+    /// Events that can't be encoded are rejected, and finalized as failed.
     fn encode_event(&self, event: Event) -> Option<Self::Input>;
     async fn build_request(&self, events: Self::Output) -> crate::Result<http::Request<Vec<u8>>>;
 }
@@ -175,8 +176,9 @@ where
 
     fn start_send(self: Pin<&mut Self>, mut event: Event) -> Result<(), Self::Error> {
         let finalizers = event.metadata_mut().take_finalizers();
-        if let Some(item) = self.sink.encode_event(event) {
-            *self.project().slot = Some(EncodedEvent { item, finalizers });
+        match self.sink.encode_event(event) {
+            Some(item) => *self.project().slot = Some(EncodedEvent { item, finalizers }),
+            None => finalizers.update_status(EventStatus::Failed),
         }
 
         Ok(())
@@ -322,8 +324,9 @@ where
 
     fn start_send(self: Pin<&mut Self>, mut event: Event) -> Result<(), Self::Error> {
         let finalizers = event.metadata_mut().take_finalizers();
-        if let Some(item) = self.sink.encode_event(event) {
-            *self.project().slot = Some(EncodedEvent { item, finalizers });
+        match self.sink.encode_event(event) {
+            Some(item) => *self.project().slot = Some(EncodedEvent { item, finalizers }),
+            None => finalizers.update_status(EventStatus::Failed),
         }
 
         Ok(())
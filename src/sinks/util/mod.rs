@@ -1,10 +1,15 @@
 pub mod adaptive_concurrency;
 pub mod batch;
 pub mod buffer;
+pub mod circuit_breaker;
 pub mod encoding;
 pub mod http;
+#[cfg(any(feature = "sinks-aws_s3", feature = "sinks-data_lake"))]
+pub mod parquet;
 pub mod retries;
 pub mod service;
+#[cfg(any(feature = "sinks-opsgenie", feature = "sinks-pagerduty"))]
+pub mod severity;
 pub mod sink;
 pub mod socket_bytes_sink;
 pub mod statistic;
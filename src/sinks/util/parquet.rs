@@ -0,0 +1,491 @@
+//! Writes batches of events as Parquet files, for the sinks storing events in
+//! that format.
+//!
+//! The files have a fixed schema of optional columns, nested in optional
+//! groups. Each column holds a field of the events of an [`EventBatch`],
+//! converted to the type of the column.
+
+use crate::event::{batch::Column, EventBatch, Value};
+use parquet::{
+    basic::{Compression, ConvertedType, Repetition, Type as PhysicalType},
+    column::writer::{ColumnWriter, ColumnWriterImpl},
+    data_type::{ByteArray, DataType},
+    errors::{ParquetError, Result},
+    file::{
+        properties::WriterProperties,
+        writer::{FileWriter, InMemoryWriteableCursor, RowGroupWriter, SerializedFileWriter},
+    },
+    schema::types::Type,
+};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, convert::TryFrom, num::NonZeroUsize, ops::Range, sync::Arc};
+
+pub const CONTENT_TYPE: &str = "application/vnd.apache.parquet";
+pub const EXTENSION: &str = "parquet";
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Eq, PartialEq, Serialize)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    None,
+    #[derivative(Default)]
+    Snappy,
+    Gzip,
+}
+
+impl From<ParquetCompression> for Compression {
+    fn from(compression: ParquetCompression) -> Self {
+        match compression {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip => Compression::GZIP,
+        }
+    }
+}
+
+/// The type of a column. Timestamps are held as integers since the Unix
+/// epoch, in the unit of the type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParquetType {
+    String,
+    Int32,
+    Int64,
+    Double,
+    Boolean,
+    TimestampMillis,
+    TimestampMicros,
+}
+
+impl ParquetType {
+    fn physical_type(self) -> (PhysicalType, ConvertedType) {
+        match self {
+            Self::String => (PhysicalType::BYTE_ARRAY, ConvertedType::UTF8),
+            Self::Int32 => (PhysicalType::INT32, ConvertedType::NONE),
+            Self::Int64 => (PhysicalType::INT64, ConvertedType::NONE),
+            Self::Double => (PhysicalType::DOUBLE, ConvertedType::NONE),
+            Self::Boolean => (PhysicalType::BOOLEAN, ConvertedType::NONE),
+            Self::TimestampMillis => (PhysicalType::INT64, ConvertedType::TIMESTAMP_MILLIS),
+            Self::TimestampMicros => (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS),
+        }
+    }
+}
+
+/// A column of the files, and the field of the events it holds.
+#[derive(Clone, Debug)]
+pub struct ParquetColumn {
+    /// The names of the groups the column is nested in, then its own name.
+    pub path: Vec<String>,
+    /// The column of the batch holding the values, then the keys of the maps
+    /// the values are nested in.
+    pub field: Vec<String>,
+    pub column_type: ParquetType,
+    /// The ID of the field, which tables such as Iceberg resolve columns by.
+    pub field_id: Option<i32>,
+}
+
+impl ParquetColumn {
+    /// A top-level column holding the column of the batch with its name.
+    pub fn new(name: impl Into<String>, column_type: ParquetType) -> Self {
+        let name = name.into();
+        Self {
+            path: vec![name.clone()],
+            field: vec![name],
+            column_type,
+            field_id: None,
+        }
+    }
+
+    /// A column nested in groups by the segments of a dotted path, holding
+    /// the field nested in maps by the same path.
+    pub fn nested(path: &str, column_type: ParquetType) -> Self {
+        let path: Vec<String> = path.split('.').map(Into::into).collect();
+        Self {
+            field: path.clone(),
+            path,
+            column_type,
+            field_id: None,
+        }
+    }
+
+    fn parquet_type(&self, name: &str) -> Result<Type> {
+        let (physical_type, converted_type) = self.column_type.physical_type();
+        let mut builder = Type::primitive_type_builder(name, physical_type)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_converted_type(converted_type);
+        if let Some(field_id) = self.field_id {
+            builder = builder.with_id(field_id);
+        }
+        builder.build()
+    }
+}
+
+/// Writes batches of events as Parquet files of the same schema.
+#[derive(Debug)]
+pub struct ParquetWriter {
+    columns: Vec<ParquetColumn>,
+    schema: Arc<Type>,
+    properties: Arc<WriterProperties>,
+    row_group_size: usize,
+}
+
+impl ParquetWriter {
+    /// Columns nested in the same group must be next to each other, as they
+    /// are when sorted by their paths.
+    pub fn new(
+        columns: Vec<ParquetColumn>,
+        compression: ParquetCompression,
+        row_group_size: NonZeroUsize,
+    ) -> Result<Self> {
+        let mut fields = group_fields(&columns, 0)?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(&mut fields)
+            .build()?;
+        let properties = WriterProperties::builder()
+            .set_compression(compression.into())
+            .build();
+
+        Ok(Self {
+            columns,
+            schema: Arc::new(schema),
+            properties: Arc::new(properties),
+            row_group_size: row_group_size.get(),
+        })
+    }
+
+    pub fn columns(&self) -> &[ParquetColumn] {
+        &self.columns
+    }
+
+    /// Writes the batch as a Parquet file, with row groups of at most
+    /// `row_group_size` rows. Values missing from the batch and values that
+    /// can't be converted to the type of their column are null.
+    pub fn write(&self, batch: &EventBatch) -> Result<Vec<u8>> {
+        let cursor = InMemoryWriteableCursor::default();
+        let mut writer = SerializedFileWriter::new(
+            cursor.clone(),
+            Arc::clone(&self.schema),
+            Arc::clone(&self.properties),
+        )?;
+
+        let mut start = 0;
+        while start < batch.len() {
+            let rows = start..batch.len().min(start + self.row_group_size);
+            let mut row_group = writer.next_row_group()?;
+            // The columns of nested schemas are in the order of their paths.
+            for column in &self.columns {
+                let mut column_writer = row_group.next_column()?.ok_or_else(|| {
+                    ParquetError::General(format!("No column of the schema for {:?}.", column.path))
+                })?;
+                let values = Values {
+                    batch,
+                    column,
+                    rows: rows.clone(),
+                };
+                match &mut column_writer {
+                    ColumnWriter::ByteArrayColumnWriter(writer) => {
+                        values.write(writer, to_byte_array)?
+                    }
+                    ColumnWriter::Int32ColumnWriter(writer) => values.write(writer, to_int32)?,
+                    ColumnWriter::Int64ColumnWriter(writer) => match column.column_type {
+                        ParquetType::TimestampMillis => values.write(writer, to_millis)?,
+                        ParquetType::TimestampMicros => values.write(writer, to_micros)?,
+                        _ => values.write(writer, to_int64)?,
+                    },
+                    ColumnWriter::DoubleColumnWriter(writer) => values.write(writer, to_double)?,
+                    ColumnWriter::BoolColumnWriter(writer) => values.write(writer, to_boolean)?,
+                    _ => {
+                        return Err(ParquetError::General(format!(
+                            "Unsupported physical type for {:?}.",
+                            column.path
+                        )))
+                    }
+                }
+                row_group.close_column(column_writer)?;
+            }
+            writer.close_row_group(row_group)?;
+            start = rows.end;
+        }
+        writer.close()?;
+
+        Ok(cursor.data())
+    }
+}
+
+/// The fields of the group at `depth` of the paths of the columns, which
+/// share their first `depth` segments.
+fn group_fields(columns: &[ParquetColumn], depth: usize) -> Result<Vec<Arc<Type>>> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    while start < columns.len() {
+        let name = &columns[start].path[depth];
+        let end = start
+            + columns[start..]
+                .iter()
+                .take_while(|column| column.path.get(depth) == Some(name))
+                .count();
+        let field = match &columns[start..end] {
+            [column] if column.path.len() == depth + 1 => column.parquet_type(name)?,
+            nested if nested.iter().all(|column| column.path.len() > depth + 1) => {
+                Type::group_type_builder(name)
+                    .with_repetition(Repetition::OPTIONAL)
+                    .with_fields(&mut group_fields(nested, depth + 1)?)
+                    .build()?
+            }
+            _ => {
+                return Err(ParquetError::General(format!(
+                    "Column {:?} is both a column and a group.",
+                    name
+                )))
+            }
+        };
+        fields.push(Arc::new(field));
+        start = end;
+    }
+    Ok(fields)
+}
+
+/// The values of a column for some rows of a batch.
+struct Values<'a> {
+    batch: &'a EventBatch,
+    column: &'a ParquetColumn,
+    rows: Range<usize>,
+}
+
+impl<'a> Values<'a> {
+    /// Writes the non-null values, with the definition level of each row.
+    /// The groups along the path are always defined, so that only the value
+    /// itself is null when it is missing.
+    fn write<T: DataType>(
+        &self,
+        writer: &mut ColumnWriterImpl<T>,
+        convert: fn(&Value) -> Option<T::T>,
+    ) -> Result<()> {
+        let max_level = self.column.path.len() as i16;
+        let source = self.batch.column(&self.column.field[0]);
+        let mut values = Vec::with_capacity(self.rows.len());
+        let mut levels = Vec::with_capacity(self.rows.len());
+        for row in self.rows.clone() {
+            let value = source.and_then(|source| lookup(source, row, &self.column.field[1..]));
+            match value.and_then(|value| convert(&value)) {
+                Some(value) => {
+                    values.push(value);
+                    levels.push(max_level);
+                }
+                None => levels.push(max_level - 1),
+            }
+        }
+        writer.write_batch(&values, Some(&levels), None)?;
+        Ok(())
+    }
+}
+
+/// The value of a column of the batch at `row`, or of the field at `path` in
+/// the maps of a column of values.
+fn lookup<'a>(column: &'a Column, row: usize, path: &[String]) -> Option<Cow<'a, Value>> {
+    match column {
+        Column::Value(values) => {
+            let value = values.get(row)?.as_ref()?;
+            path.iter()
+                .try_fold(value, |value, key| value.as_map()?.get(key))
+                .map(Cow::Borrowed)
+        }
+        column if path.is_empty() => column.get(row).map(Cow::Owned),
+        _ => None,
+    }
+}
+
+fn to_byte_array(value: &Value) -> Option<ByteArray> {
+    match value {
+        Value::Null => None,
+        value => Some(value.as_bytes().to_vec().into()),
+    }
+}
+
+fn to_int32(value: &Value) -> Option<i32> {
+    match value {
+        Value::Integer(value) => i32::try_from(*value).ok(),
+        value => value.parse_text(),
+    }
+}
+
+fn to_int64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(value) => Some(*value),
+        Value::Float(value) => Some(*value as i64),
+        value => value.parse_text(),
+    }
+}
+
+fn to_double(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(value) => Some(*value),
+        Value::Integer(value) => Some(*value as f64),
+        value => value.parse_text(),
+    }
+}
+
+fn to_boolean(value: &Value) -> Option<bool> {
+    match value {
+        Value::Boolean(value) => Some(*value),
+        value => value.parse_text(),
+    }
+}
+
+/// Integers are taken to be in milliseconds already.
+fn to_millis(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(millis) => Some(*millis),
+        value => value
+            .as_timestamp()
+            .copied()
+            .or_else(|| value.parse_timestamp())
+            .map(|timestamp| timestamp.timestamp_millis()),
+    }
+}
+
+/// Integers are taken to be in microseconds already.
+fn to_micros(value: &Value) -> Option<i64> {
+    match value {
+        Value::Integer(micros) => Some(*micros),
+        value => value
+            .as_timestamp()
+            .copied()
+            .or_else(|| value.parse_timestamp())
+            .map(|timestamp| {
+                timestamp.timestamp() * 1_000_000 + i64::from(timestamp.timestamp_subsec_micros())
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::LogEvent;
+    use chrono::{TimeZone, Utc};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+        util::cursor::SliceableCursor,
+    };
+    use std::collections::BTreeMap;
+
+    fn read(data: Vec<u8>) -> SerializedFileReader<SliceableCursor> {
+        SerializedFileReader::new(SliceableCursor::new(data)).unwrap()
+    }
+
+    fn batch() -> EventBatch {
+        let mut first = LogEvent::default();
+        first.insert("message", "first");
+        first.insert("status", "200");
+        first.insert(
+            "timestamp",
+            Utc.ymd(2021, 9, 1).and_hms_milli(12, 0, 0, 250),
+        );
+        first.insert("user.name", "root");
+        first.insert("user.uid", 0);
+
+        let mut second = LogEvent::default();
+        second.insert("message", "second");
+        second.insert("status", "unknown");
+        second.insert("timestamp", 1630497601000_i64);
+        second.insert("user.uid", 1000);
+
+        let mut third = LogEvent::default();
+        third.insert("status", 503);
+        third.insert("user", "not an object");
+
+        EventBatch::from_logs(vec![first, second, third])
+    }
+
+    #[test]
+    fn writes_nested_columns_in_row_groups() {
+        let columns = vec![
+            ParquetColumn::new("message", ParquetType::String),
+            ParquetColumn::new("status", ParquetType::Int32),
+            ParquetColumn::new("timestamp", ParquetType::TimestampMillis),
+            ParquetColumn::nested("user.name", ParquetType::String),
+            ParquetColumn::nested("user.uid", ParquetType::Int64),
+        ];
+        let writer = ParquetWriter::new(
+            columns,
+            ParquetCompression::Gzip,
+            NonZeroUsize::new(2).unwrap(),
+        )
+        .unwrap();
+
+        let reader = read(writer.write(&batch()).unwrap());
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let fields = reader.metadata().file_metadata().schema().get_fields();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[3].get_fields().len(), 2);
+
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].get_string(0).unwrap(), "first");
+        assert_eq!(rows[0].get_int(1).unwrap(), 200);
+        assert_eq!(rows[0].get_timestamp_millis(2).unwrap(), 1630497600250);
+        let user = rows[0].get_group(3).unwrap();
+        assert_eq!(user.get_string(0).unwrap(), "root");
+        assert_eq!(user.get_long(1).unwrap(), 0);
+
+        assert!(rows[1].get_int(1).is_err());
+        assert_eq!(rows[1].get_timestamp_millis(2).unwrap(), 1630497601000);
+        let user = rows[1].get_group(3).unwrap();
+        assert!(user.get_string(0).is_err());
+        assert_eq!(user.get_long(1).unwrap(), 1000);
+
+        assert!(rows[2].get_string(0).is_err());
+        assert_eq!(rows[2].get_int(1).unwrap(), 503);
+        assert!(rows[2].get_group(3).unwrap().get_long(1).is_err());
+    }
+
+    #[test]
+    fn writes_field_ids() {
+        let mut column = ParquetColumn::new("message", ParquetType::String);
+        column.field_id = Some(2);
+        let writer = ParquetWriter::new(
+            vec![column],
+            ParquetCompression::Snappy,
+            NonZeroUsize::new(100).unwrap(),
+        )
+        .unwrap();
+
+        let mut batch = EventBatch::with_len(1);
+        batch.insert_column("message", Column::String(vec![Some("first".into())]));
+        let reader = read(writer.write(&batch).unwrap());
+        let schema = reader.metadata().file_metadata().schema();
+        assert_eq!(schema.get_fields()[0].get_basic_info().id(), 2);
+    }
+
+    #[test]
+    fn rejects_columns_of_groups() {
+        let columns = vec![
+            ParquetColumn::new("user", ParquetType::String),
+            ParquetColumn::nested("user.name", ParquetType::String),
+        ];
+        let error = ParquetWriter::new(
+            columns,
+            ParquetCompression::None,
+            NonZeroUsize::new(100).unwrap(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("both a column and a group"));
+    }
+
+    #[test]
+    fn writes_maps_as_json() {
+        let writer = ParquetWriter::new(
+            vec![ParquetColumn::new("user", ParquetType::String)],
+            ParquetCompression::None,
+            NonZeroUsize::new(100).unwrap(),
+        )
+        .unwrap();
+        let mut user = BTreeMap::new();
+        user.insert("name".to_owned(), Value::from("root"));
+        let mut log = LogEvent::default();
+        log.insert("user", Value::Map(user));
+        let reader = read(writer.write(&EventBatch::from_logs(vec![log])).unwrap());
+        let rows: Vec<_> = reader.get_row_iter(None).unwrap().collect();
+        assert_eq!(rows[0].get_string(0).unwrap(), r#"{"name":"root"}"#);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Error;
+use crate::{sinks::util::circuit_breaker::CircuitBreakerOpen, Error};
 use futures::FutureExt;
 use std::{
     cmp,
@@ -135,6 +135,9 @@ where
                 } else if error.downcast_ref::<Elapsed>().is_some() {
                     warn!("Request timed out. If this happens often while the events are actually reaching their destination, try decreasing `batch.max_bytes` and/or using `compression` if applicable. Alternatively `request.timeout_secs` can be increased.");
                     Some(self.build_retry())
+                } else if error.downcast_ref::<CircuitBreakerOpen>().is_some() {
+                    // Shed requests are already reported by the circuit breaker.
+                    None
                 } else {
                     error!(
                         message = "Unexpected error type; dropping the request.",
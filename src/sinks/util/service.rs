@@ -2,6 +2,9 @@ use crate::buffers::Acker;
 use crate::sinks::util::adaptive_concurrency::{
     AdaptiveConcurrencyLimit, AdaptiveConcurrencyLimitLayer, AdaptiveConcurrencySettings,
 };
+use crate::sinks::util::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerLayer, CircuitBreakerSettings,
+};
 use crate::sinks::util::retries::{FixedRetryPolicy, RetryLogic};
 pub use crate::sinks::util::service::concurrency::{Concurrency, ConcurrencyOption};
 pub use crate::sinks::util::service::map::Map;
@@ -22,7 +25,9 @@ use tower::{
 mod concurrency;
 mod map;
 
-pub type Svc<S, L> = RateLimit<Retry<FixedRetryPolicy<L>, AdaptiveConcurrencyLimit<Timeout<S>, L>>>;
+pub type Svc<S, L> = RateLimit<
+    Retry<FixedRetryPolicy<L>, CircuitBreaker<AdaptiveConcurrencyLimit<Timeout<S>, L>, L>>,
+>;
 pub type TowerBatchedSink<S, B, RL, SL> = BatchSink<Svc<S, RL>, B, SL>;
 pub type TowerPartitionSink<S, B, RL, K, SL> = PartitionBatchSink<Svc<S, RL>, B, K, SL>;
 
@@ -78,6 +83,8 @@ pub struct TowerRequestConfig<T: ConcurrencyOption = Concurrency> {
     pub retry_initial_backoff_secs: Option<u64>, // 1
     #[serde(default)]
     pub adaptive_concurrency: AdaptiveConcurrencySettings,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerSettings,
 }
 
 pub const RATE_LIMIT_DURATION_SECONDS_DEFAULT: u64 = 1; // one second
@@ -102,6 +109,7 @@ where
             retry_max_duration_secs: Some(RETRY_MAX_DURATION_SECONDS_DEFAULT),
             retry_initial_backoff_secs: Some(RETRY_INITIAL_BACKOFF_SECONDS_DEFAULT),
             adaptive_concurrency: AdaptiveConcurrencySettings::default(),
+            circuit_breaker: CircuitBreakerSettings::default(),
         }
     }
 }
@@ -139,6 +147,7 @@ impl<T: ConcurrencyOption> TowerRequestConfig<T> {
                     .unwrap_or(RETRY_INITIAL_BACKOFF_SECONDS_DEFAULT),
             ),
             adaptive_concurrency: self.adaptive_concurrency,
+            circuit_breaker: self.circuit_breaker,
         }
     }
 
@@ -164,6 +173,7 @@ pub struct TowerRequestSettings {
     pub retry_max_duration_secs: Duration,
     pub retry_initial_backoff_secs: Duration,
     pub adaptive_concurrency: AdaptiveConcurrencySettings,
+    pub circuit_breaker: CircuitBreakerSettings,
 }
 
 impl TowerRequestSettings {
@@ -247,6 +257,10 @@ impl TowerRequestSettings {
         ServiceBuilder::new()
             .rate_limit(self.rate_limit_num, self.rate_limit_duration)
             .retry(policy)
+            .layer(CircuitBreakerLayer::new(
+                self.circuit_breaker,
+                retry_logic.clone(),
+            ))
             .layer(AdaptiveConcurrencyLimitLayer::new(
                 self.concurrency,
                 self.adaptive_concurrency,
@@ -285,6 +299,10 @@ where
                 self.settings.rate_limit_duration,
             )
             .retry(policy)
+            .layer(CircuitBreakerLayer::new(
+                self.settings.circuit_breaker,
+                self.retry_logic.clone(),
+            ))
             .timeout(self.settings.timeout)
             .service(inner);
 
@@ -0,0 +1,89 @@
+use crate::event::LogEvent;
+use indexmap::IndexMap;
+use serde::{
+    de::{
+        value::{Error, StrDeserializer},
+        DeserializeOwned, IntoDeserializer,
+    },
+    Deserialize, Serialize,
+};
+
+/// Maps the value of a field of events onto a severity, for sinks of APIs
+/// that take the severity of alerts from a fixed set.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeverityMapping<S: Default> {
+    /// The field holding the severity of events.
+    pub field: String,
+    /// The severity of events without `field`, or whose value isn't mapped.
+    pub default: S,
+    /// Severities by value of `field`. Values that are names of severities
+    /// don't need to be mapped.
+    pub mapping: IndexMap<String, S>,
+}
+
+impl<S: Default> Default for SeverityMapping<S> {
+    fn default() -> Self {
+        Self {
+            field: "severity".to_owned(),
+            default: S::default(),
+            mapping: IndexMap::new(),
+        }
+    }
+}
+
+impl<S: Clone + Default + DeserializeOwned> SeverityMapping<S> {
+    pub fn severity(&self, log: &LogEvent) -> S {
+        let value = match log.get(&self.field) {
+            Some(value) => value.to_string_lossy(),
+            None => return self.default.clone(),
+        };
+        if let Some(severity) = self.mapping.get(&value) {
+            return severity.clone();
+        }
+        // Names of severities are matched regardless of their case.
+        [value.to_lowercase(), value.to_uppercase()]
+            .iter()
+            .find_map(|name| {
+                let deserializer: StrDeserializer<'_, Error> = name.as_str().into_deserializer();
+                S::deserialize(deserializer).ok()
+            })
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Derivative)]
+    #[serde(rename_all = "snake_case")]
+    #[derivative(Default)]
+    enum Severity {
+        Critical,
+        Warning,
+        #[derivative(Default)]
+        Info,
+    }
+
+    #[test]
+    fn maps_severities() {
+        let mut mapping = SeverityMapping::<Severity> {
+            field: "level".into(),
+            ..Default::default()
+        };
+        mapping.mapping.insert("fatal".into(), Severity::Critical);
+
+        let severity = |level: Option<&str>| {
+            let mut log = LogEvent::default();
+            if let Some(level) = level {
+                log.insert("level", level);
+            }
+            mapping.severity(&log)
+        };
+        assert_eq!(severity(Some("fatal")), Severity::Critical);
+        assert_eq!(severity(Some("WARNING")), Severity::Warning);
+        assert_eq!(severity(Some("debug")), Severity::Info);
+        assert_eq!(severity(None), Severity::Info);
+    }
+}
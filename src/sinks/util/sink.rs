@@ -610,7 +610,10 @@ mod tests {
     use super::*;
     use crate::{
         buffers::Acker,
-        sinks::util::{BatchSettings, EncodedLength, VecBuffer},
+        event::{BatchNotifier, BatchStatus, EventFinalizer},
+        sinks::util::{
+            circuit_breaker::CircuitBreakerOpen, BatchSettings, EncodedLength, VecBuffer,
+        },
         test_util::trace_init,
     };
     use bytes::Bytes;
@@ -1069,6 +1072,21 @@ mod tests {
         assert_eq!(ack_counter.load(Relaxed), 10);
     }
 
+    #[tokio::test]
+    async fn service_sink_errors_shed_requests() {
+        // Errored events are the ones sent to the dead letter output of the
+        // sink, along with the failed ones.
+        let (acker, _) = Acker::new_for_testing();
+        let svc = tower::service_fn(|_: u8| future::err::<&str, _>(CircuitBreakerOpen));
+        let mut sink = ServiceSink::new(svc, acker);
+
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        sink.call(1, 1, EventFinalizers::new(EventFinalizer::new(batch)))
+            .await;
+
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Errored));
+    }
+
     #[derive(Debug, PartialEq, Eq, Ord, PartialOrd)]
     enum Partitions {
         A,
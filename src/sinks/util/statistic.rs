@@ -1,4 +1,4 @@
-use crate::event::metric::Sample;
+use crate::event::metric::{DDSketch, Sample};
 use snafu::Snafu;
 use std::cmp::Ordering;
 
@@ -80,6 +80,23 @@ impl DistributionStatistic {
             }),
         }
     }
+
+    /// The min, max, sum and count of a sketch are exact, while the median
+    /// and quantiles are within its relative accuracy.
+    pub fn from_sketch(sketch: &DDSketch, quantiles: &[f64]) -> Option<Self> {
+        Some(Self {
+            min: sketch.min()?,
+            max: sketch.max()?,
+            median: sketch.quantile(0.5)?,
+            avg: sketch.sum() / sketch.count() as f64,
+            sum: sketch.sum(),
+            count: sketch.count() as u64,
+            quantiles: quantiles
+                .iter()
+                .filter_map(|&p| sketch.quantile(p).map(|value| (p, value)))
+                .collect(),
+        })
+    }
 }
 
 /// `bins` is a cumulative histogram
@@ -88,7 +88,7 @@ enum HealthcheckError {
 }
 
 fn encode_event(event: Event) -> Bytes {
-    let data = proto::EventWrapper::from(event);
+    let data = proto::EncodedEventWrapper::from(event);
     let event_len = data.encoded_len();
     let full_len = event_len + 4;
 
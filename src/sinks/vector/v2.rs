@@ -1,6 +1,10 @@
 use crate::{
     config::{DataType, GenerateConfig, Resource, SinkContext, SinkHealthcheckOptions},
-    event::{proto::EventWrapper, Event},
+    event::{
+        proto::{downgrade, EncodedEventWrapper, SCHEMA_VERSION},
+        Event, EventStatus,
+    },
+    internal_events::VectorEventUnrepresentable,
     proto::vector as proto,
     sinks::util::{
         retries::RetryLogic, sink, BatchConfig, BatchSettings, BatchSink, EncodedEvent,
@@ -9,14 +13,24 @@ use crate::{
     sinks::{Healthcheck, VectorSink},
     tls::{tls_connector_builder, MaybeTlsSettings, TlsConfig},
 };
-use futures::{future::BoxFuture, stream, SinkExt, StreamExt, TryFutureExt};
+use futures::{
+    future::{self, BoxFuture},
+    stream, SinkExt, StreamExt, TryFutureExt,
+};
 use http::uri::Uri;
 use hyper::client::HttpConnector;
 use hyper_openssl::HttpsConnector;
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
-use std::task::{Context, Poll};
+use std::{
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
 use tonic::{body::BoxBody, IntoRequest};
 use tower::ServiceBuilder;
 
@@ -142,8 +156,18 @@ impl VectorConfig {
             client: client.clone(),
         });
 
-        let healthcheck = healthcheck(healthcheck_client, cx.healthcheck.clone());
         let client = proto::Client::new(HyperSvc { uri, client });
+        let peer_version = PeerVersion::new(client.clone());
+
+        let healthcheck = healthcheck(
+            healthcheck_client,
+            cx.healthcheck.clone(),
+            peer_version.clone(),
+        );
+        let service = VectorService {
+            client,
+            peer_version: peer_version.clone(),
+        };
         let request = self.request.unwrap_with(&TowerRequestConfig::default());
         let batch = BatchSettings::default()
             .events(1000)
@@ -152,12 +176,17 @@ impl VectorConfig {
 
         let svc = ServiceBuilder::new()
             .settings(request, VectorGrpcRetryLogic)
-            .service(client);
+            .service(service);
 
         let buffer = VecBuffer::new(batch.size);
         let sink = BatchSink::new(svc, buffer, batch.timeout, cx.acker())
             .sink_map_err(|error| error!(message = "Fatal Vector GRPC sink error.", %error))
-            .with_flat_map(move |event| stream::iter(Some(encode_event(event))).map(Ok));
+            .with_flat_map(move |event| {
+                let peer_version = peer_version.clone();
+                stream::once(async move { encode_event(event, peer_version.negotiate().await) })
+                    .filter_map(|encoded| future::ready(encoded.map(Ok)))
+                    .boxed()
+            });
 
         Ok((VectorSink::Sink(Box::new(sink)), Box::pin(healthcheck)))
     }
@@ -175,8 +204,13 @@ impl VectorConfig {
     }
 }
 
-/// Check to see if the remote service accepts new events.
-async fn healthcheck(mut client: Client, options: SinkHealthcheckOptions) -> crate::Result<()> {
+/// Check to see if the remote service accepts new events, and learn which
+/// event schema version it understands.
+async fn healthcheck(
+    mut client: Client,
+    options: SinkHealthcheckOptions,
+    peer_version: PeerVersion,
+) -> crate::Result<()> {
     if !options.enabled {
         return Ok(());
     }
@@ -184,9 +218,11 @@ async fn healthcheck(mut client: Client, options: SinkHealthcheckOptions) -> cra
     let request = client.health_check(proto::HealthCheckRequest {});
 
     if let Ok(response) = request.await {
-        let status = proto::ServingStatus::from_i32(response.into_inner().status);
+        let response = response.into_inner();
+        let status = proto::ServingStatus::from_i32(response.status);
 
         if let Some(proto::ServingStatus::Serving) = status {
+            peer_version.store(response.version);
             return Ok(());
         }
     }
@@ -194,7 +230,84 @@ async fn healthcheck(mut client: Client, options: SinkHealthcheckOptions) -> cra
     Err(Box::new(Error::Health))
 }
 
-impl tower::Service<Vec<EventWrapper>> for Client {
+/// Marks a peer version that hasn't been learnt yet.
+const UNKNOWN_VERSION: u32 = u32::MAX;
+
+/// The event schema version of the source the sink sends to. It's learnt
+/// from the health check of the source before the first event is encoded,
+/// and kept up to date from the responses to pushes. Sources that predate
+/// versioning report `0`, which is also assumed if the health check fails.
+#[derive(Clone)]
+struct PeerVersion {
+    client: Client,
+    version: Arc<AtomicU32>,
+}
+
+impl PeerVersion {
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            version: Arc::new(AtomicU32::new(UNKNOWN_VERSION)),
+        }
+    }
+
+    fn load(&self) -> u32 {
+        match self.version.load(Ordering::Relaxed) {
+            UNKNOWN_VERSION => 0,
+            version => version,
+        }
+    }
+
+    fn store(&self, version: u32) {
+        self.version.store(version, Ordering::Relaxed);
+    }
+
+    /// The version of the source, asking the source for it if it isn't known
+    /// yet.
+    async fn negotiate(&self) -> u32 {
+        let version = self.version.load(Ordering::Relaxed);
+        if version != UNKNOWN_VERSION {
+            return version;
+        }
+
+        let request = self
+            .client
+            .clone()
+            .health_check(proto::HealthCheckRequest {});
+        let version = match request.await {
+            Ok(response) => response.into_inner().version,
+            Err(error) => {
+                warn!(
+                    message = "Unable to learn the event schema version of the Vector source, assuming the oldest one.",
+                    %error,
+                );
+                0
+            }
+        };
+
+        // A response may have told the version in the meantime.
+        match self.version.compare_exchange(
+            UNKNOWN_VERSION,
+            version,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => version,
+            Err(current) => current,
+        }
+    }
+}
+
+/// The client, along with the event schema version of the source it sends
+/// to. Events are encoded for that version, and batches encoded before the
+/// version went down are downgraded as they are sent.
+#[derive(Clone)]
+struct VectorService {
+    client: Client,
+    peer_version: PeerVersion,
+}
+
+impl tower::Service<Vec<EncodedEventWrapper>> for VectorService {
     type Response = ();
     type Error = Error;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
@@ -208,14 +321,19 @@ impl tower::Service<Vec<EventWrapper>> for Client {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, events: Vec<EventWrapper>) -> Self::Future {
-        let mut client = self.clone();
+    fn call(&mut self, events: Vec<EncodedEventWrapper>) -> Self::Future {
+        let mut client = self.client.clone();
+        let peer_version = self.peer_version.clone();
 
+        let events = match downgrade_events(events, peer_version.load()) {
+            Ok(events) => events,
+            Err(error) => return Box::pin(future::ready(Err(error))),
+        };
         let request = proto::PushEventsRequest { events };
         let future = async move {
             client
                 .push_events(request.into_request())
-                .map_ok(|_| ())
+                .map_ok(|response| peer_version.store(response.into_inner().version))
                 .map_err(|source| Error::Request { source })
                 .await
         };
@@ -224,14 +342,58 @@ impl tower::Service<Vec<EventWrapper>> for Client {
     }
 }
 
-fn encode_event(mut event: Event) -> EncodedEvent<EventWrapper> {
+/// Encode the event for a source that understands schema `version`. Events
+/// that have no equivalent in that version fail, rather than being sent.
+fn encode_event(mut event: Event, version: u32) -> Option<EncodedEvent<EncodedEventWrapper>> {
+    let version = version.min(SCHEMA_VERSION);
     let finalizers = event.metadata_mut().take_finalizers();
-    let item = event.into();
 
-    EncodedEvent { item, finalizers }
+    match downgrade(event, version) {
+        Some(event) => Some(EncodedEvent {
+            item: EncodedEventWrapper {
+                version,
+                ..event.into()
+            },
+            finalizers,
+        }),
+        None => {
+            emit!(&VectorEventUnrepresentable { version });
+            finalizers.update_status(EventStatus::Failed);
+            None
+        }
+    }
+}
+
+/// Rewrite the events encoded for a newer schema than the source's
+/// `version`. If any of them has no equivalent in that version, the whole
+/// batch fails.
+fn downgrade_events(
+    events: Vec<EncodedEventWrapper>,
+    version: u32,
+) -> Result<Vec<EncodedEventWrapper>, Error> {
+    events
+        .into_iter()
+        .map(|item| {
+            if item.version <= version {
+                return Ok(item);
+            }
+
+            Event::try_from(item)
+                .ok()
+                .and_then(|event| downgrade(event, version))
+                .map(|event| EncodedEventWrapper {
+                    version,
+                    ..event.into()
+                })
+                .ok_or_else(|| {
+                    emit!(&VectorEventUnrepresentable { version });
+                    Error::Unrepresentable { version }
+                })
+        })
+        .collect()
 }
 
-impl EncodedLength for EventWrapper {
+impl EncodedLength for EncodedEventWrapper {
     fn encoded_length(&self) -> usize {
         self.encoded_len()
     }
@@ -253,6 +415,9 @@ pub enum Error {
 
     #[snafu(display("URL has no host."))]
     NoHost,
+
+    #[snafu(display("Events have no equivalent in event schema version {}", version))]
+    Unrepresentable { version: u32 },
 }
 
 #[derive(Debug, Clone)]
@@ -268,6 +433,7 @@ impl RetryLogic for VectorGrpcRetryLogic {
                 source.code(),
                 tonic::Code::Unknown | tonic::Code::Internal | tonic::Code::PermissionDenied
             ),
+            Error::Unrepresentable { .. } => false,
             _ => true,
         }
     }
@@ -285,7 +451,10 @@ mod tests {
     use futures::{channel::mpsc, StreamExt};
     use http::{request::Parts, StatusCode};
     use hyper::Method;
-    use vector_core::event::{BatchNotifier, BatchStatus};
+    use vector_core::event::{
+        metric::{DDSketch, Metric, MetricKind, MetricValue, StatisticKind},
+        BatchNotifier, BatchStatus, TraceEvent,
+    };
 
     #[test]
     fn generate_config() {
@@ -352,6 +521,54 @@ mod tests {
         assert_eq!(receiver.try_recv(), Ok(BatchStatus::Errored));
     }
 
+    #[test]
+    fn encodes_for_older_peers() {
+        let sketch = Event::Metric(Metric::new(
+            "sketch",
+            MetricKind::Incremental,
+            MetricValue::Sketch {
+                sketch: DDSketch::from_samples(0.01, vector_core::samples![1.0 => 2]),
+            },
+        ));
+        let trace = Event::Trace(TraceEvent::default());
+
+        let encoded = encode_event(sketch.clone(), SCHEMA_VERSION).unwrap();
+        assert_eq!(encoded.item.version, SCHEMA_VERSION);
+        assert_eq!(Event::try_from(encoded.item.clone()).unwrap(), sketch);
+
+        let downgraded = encode_event(sketch.clone(), 0).unwrap();
+        assert_eq!(downgraded.item.version, 0);
+        assert!(matches!(
+            Event::try_from(downgraded.item.clone())
+                .unwrap()
+                .as_metric()
+                .value(),
+            MetricValue::Distribution {
+                statistic: StatisticKind::Summary,
+                ..
+            }
+        ));
+        let redowngraded = downgrade_events(vec![encoded.item], 0).unwrap();
+        assert_eq!(redowngraded.len(), 1);
+        assert_eq!(redowngraded[0].version, 0);
+
+        let trace = encode_event(trace, SCHEMA_VERSION).unwrap();
+        assert!(matches!(
+            downgrade_events(vec![trace.item], 0),
+            Err(Error::Unrepresentable { version: 0 })
+        ));
+    }
+
+    #[test]
+    fn fails_events_older_peers_cannot_decode() {
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let trace = Event::Trace(TraceEvent::default()).with_batch_notifier(&batch);
+        drop(batch);
+
+        assert!(encode_event(trace, 0).is_none());
+        assert_eq!(receiver.try_recv(), Ok(BatchStatus::Failed));
+    }
+
     async fn get_received(
         rx: mpsc::Receiver<(Parts, Bytes)>,
         assert_parts: impl Fn(Parts),
@@ -369,7 +586,7 @@ mod tests {
 
             let mut events = Vec::with_capacity(req.events.len());
             for event in req.events {
-                let event: Event = event.into();
+                let event = Event::try_from(event).unwrap();
                 let string = event.as_log().get("message").unwrap().to_string_lossy();
                 events.push(string)
             }
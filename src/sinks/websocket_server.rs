@@ -0,0 +1,362 @@
+use crate::{
+    buffers::Acker,
+    config::{DataType, GenerateConfig, Resource, SinkConfig, SinkContext, SinkDescription},
+    event::{Event, LogEvent},
+    internal_events::{
+        ConnectionOpen, OpenGauge, WebSocketServerClientConnected,
+        WebSocketServerClientDisconnected, WebSocketServerMessageDropped,
+    },
+    sinks::{
+        util::{encode_log, encoding::EncodingConfig, Encoding, StreamSink},
+        Healthcheck, VectorSink,
+    },
+    tls::{MaybeTlsSettings, TlsConfig},
+};
+use async_trait::async_trait;
+use futures::{future, stream::BoxStream, FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use stream_cancel::{Trigger, Tripwire};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_stream::wrappers::ReceiverStream;
+use warp::{
+    http::StatusCode,
+    ws::{Message, WebSocket, Ws},
+    Filter, Rejection, Reply,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WebSocketServerSinkConfig {
+    #[serde(default = "default_address")]
+    address: SocketAddr,
+    tls: Option<TlsConfig>,
+    encoding: EncodingConfig<Encoding>,
+    /// The messages queued for each client while it catches up.
+    #[serde(default = "default_client_buffer_size")]
+    client_buffer_size: usize,
+    #[serde(default)]
+    slow_clients: SlowClients,
+    /// Further clients are refused until others disconnect.
+    max_clients: Option<usize>,
+}
+
+/// What is done with clients whose buffer is full.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+enum SlowClients {
+    /// Messages are dropped for the client until it catches up.
+    #[derivative(Default)]
+    DropMessages,
+    /// The client is disconnected, so that it can reconnect to resume.
+    Disconnect,
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, 8080))
+}
+
+const fn default_client_buffer_size() -> usize {
+    1000
+}
+
+inventory::submit! {
+    SinkDescription::new::<WebSocketServerSinkConfig>("websocket_server")
+}
+
+impl GenerateConfig for WebSocketServerSinkConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"address = "127.0.0.1:8080"
+            encoding.codec = "json""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "websocket_server")]
+impl SinkConfig for WebSocketServerSinkConfig {
+    async fn build(&self, cx: SinkContext) -> crate::Result<(VectorSink, Healthcheck)> {
+        let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
+        let sink = WebSocketServerSink {
+            address: self.address,
+            tls,
+            encoding: self.encoding.clone(),
+            clients: Arc::new(Clients::new(self)),
+            acker: cx.acker(),
+        };
+        let healthcheck = future::ok(()).boxed();
+
+        Ok((VectorSink::Stream(Box::new(sink)), healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "websocket_server"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::tcp(self.address)]
+    }
+}
+
+struct WebSocketServerSink {
+    address: SocketAddr,
+    tls: MaybeTlsSettings,
+    encoding: EncodingConfig<Encoding>,
+    clients: Arc<Clients>,
+    acker: Acker,
+}
+
+impl WebSocketServerSink {
+    /// The server shuts down once the returned trigger is dropped.
+    fn start_server(&self) -> Trigger {
+        let (trigger, tripwire) = Tripwire::new();
+        let routes = routes(Arc::clone(&self.clients));
+        let tls = self.tls.clone();
+        let address = self.address;
+
+        tokio::spawn(async move {
+            let listener = tls
+                .bind(&address)
+                .await
+                .map_err(|error| error!(message = "Server bind error.", %error))?;
+
+            warp::serve(routes)
+                .serve_incoming_with_graceful_shutdown(
+                    listener.accept_stream(),
+                    tripwire.then(crate::stream::tripwire_handler),
+                )
+                .await;
+
+            Ok::<(), ()>(())
+        });
+
+        trigger
+    }
+}
+
+#[async_trait]
+impl StreamSink for WebSocketServerSink {
+    async fn run(&mut self, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
+        let _server_shutdown_trigger = self.start_server();
+
+        while let Some(event) = input.next().await {
+            // Events are only encoded while clients are connected.
+            if !self.clients.is_empty() {
+                if let Some(bytes) = encode_log(event.clone(), &self.encoding) {
+                    let text = String::from_utf8_lossy(&bytes);
+                    let message = Message::text(text.trim_end_matches('\n'));
+                    self.clients.broadcast(event.as_log(), message);
+                }
+            }
+            self.acker.ack(1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Upgrades requests to WebSockets. Clients select the events sent to them
+/// with query parameters, each requiring a field of events to equal a value.
+fn routes(
+    clients: Arc<Clients>,
+) -> impl Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone {
+    warp::ws()
+        .and(warp::query::<HashMap<String, String>>())
+        .map(
+            move |ws: Ws, filter: HashMap<String, String>| -> Box<dyn Reply> {
+                if clients.is_full() {
+                    return Box::new(warp::reply::with_status(
+                        "Too many clients.",
+                        StatusCode::SERVICE_UNAVAILABLE,
+                    ));
+                }
+                let clients = Arc::clone(&clients);
+                Box::new(ws.on_upgrade(move |socket| clients.serve(socket, filter)))
+            },
+        )
+}
+
+struct Client {
+    filter: Vec<(String, String)>,
+    tx: mpsc::Sender<Message>,
+}
+
+impl Client {
+    fn matches(&self, log: &LogEvent) -> bool {
+        self.filter.iter().all(|(field, value)| {
+            log.get(field)
+                .map_or(false, |actual| actual.to_string_lossy() == *value)
+        })
+    }
+}
+
+struct Clients {
+    clients: Mutex<HashMap<u64, Client>>,
+    next_id: AtomicU64,
+    buffer_size: usize,
+    slow_clients: SlowClients,
+    max_clients: Option<usize>,
+    open: OpenGauge,
+}
+
+impl Clients {
+    fn new(config: &WebSocketServerSinkConfig) -> Self {
+        Self {
+            clients: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            buffer_size: config.client_buffer_size.max(1),
+            slow_clients: config.slow_clients,
+            max_clients: config.max_clients,
+            open: OpenGauge::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.clients.lock().unwrap().is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        let clients = self.clients.lock().unwrap();
+        self.max_clients.map_or(false, |max| clients.len() >= max)
+    }
+
+    /// Streams messages to the client until either end closes the connection.
+    async fn serve(self: Arc<Self>, socket: WebSocket, filter: HashMap<String, String>) {
+        let (tx, rx) = mpsc::channel(self.buffer_size);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let client = Client {
+            filter: filter.into_iter().collect(),
+            tx,
+        };
+        self.clients.lock().unwrap().insert(id, client);
+        let _open_token = self
+            .open
+            .clone()
+            .open(|count| emit!(ConnectionOpen { count }));
+        emit!(WebSocketServerClientConnected);
+
+        let (sink, mut incoming) = socket.split();
+        // Ends once the client is removed, closing the connection.
+        let send = ReceiverStream::new(rx).map(Ok).forward(sink);
+        // Reading answers the pings of the client and notices it closing the
+        // connection; anything it sends is ignored.
+        let receive = async move {
+            while let Some(Ok(message)) = incoming.next().await {
+                if message.is_close() {
+                    break;
+                }
+            }
+        };
+        tokio::select! {
+            _ = send => {},
+            _ = receive => {},
+        }
+
+        self.clients.lock().unwrap().remove(&id);
+        emit!(WebSocketServerClientDisconnected);
+    }
+
+    /// Queues the message for the clients the event matches.
+    fn broadcast(&self, log: &LogEvent, message: Message) {
+        let slow_clients = self.slow_clients;
+        self.clients.lock().unwrap().retain(|_, client| {
+            if !client.matches(log) {
+                return true;
+            }
+            match client.tx.try_send(message.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    let disconnect = slow_clients == SlowClients::Disconnect;
+                    emit!(WebSocketServerMessageDropped { disconnect });
+                    !disconnect
+                }
+                Err(TrySendError::Closed(_)) => false,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{sleep, Duration};
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WebSocketServerSinkConfig>();
+    }
+
+    fn clients(slow_clients: SlowClients) -> Arc<Clients> {
+        let config: WebSocketServerSinkConfig =
+            toml::from_str(r#"encoding.codec = "text""#).unwrap();
+        Arc::new(Clients {
+            buffer_size: 1,
+            slow_clients,
+            ..Clients::new(&config)
+        })
+    }
+
+    async fn wait_for_clients(clients: &Clients, count: usize) {
+        while clients.clients.lock().unwrap().len() != count {
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn log(message: &str, service: &str) -> LogEvent {
+        let mut log = LogEvent::from(message);
+        log.insert("service", service);
+        log
+    }
+
+    #[tokio::test]
+    async fn sends_matching_events() {
+        let clients = clients(SlowClients::DropMessages);
+        let routes = routes(Arc::clone(&clients));
+        let mut all = warp::test::ws().handshake(routes.clone()).await.unwrap();
+        let mut api = warp::test::ws()
+            .path("/?service=api")
+            .handshake(routes)
+            .await
+            .unwrap();
+        wait_for_clients(&clients, 2).await;
+
+        clients.broadcast(&log("first", "web"), Message::text("first"));
+        assert_eq!(all.recv().await.unwrap(), Message::text("first"));
+
+        clients.broadcast(&log("second", "api"), Message::text("second"));
+        assert_eq!(all.recv().await.unwrap(), Message::text("second"));
+        assert_eq!(api.recv().await.unwrap(), Message::text("second"));
+    }
+
+    #[test]
+    fn disconnects_slow_clients() {
+        let clients = clients(SlowClients::Disconnect);
+        let (tx, _rx) = mpsc::channel(1);
+        let client = Client {
+            filter: Vec::new(),
+            tx,
+        };
+        clients.clients.lock().unwrap().insert(0, client);
+
+        // The client doesn't read, so the second message doesn't fit.
+        let log = log("message", "api");
+        clients.broadcast(&log, Message::text("first"));
+        assert!(!clients.is_empty());
+        clients.broadcast(&log, Message::text("second"));
+        assert!(clients.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use rusoto_dynamodb::{AttributeValue, DynamoDb, DynamoDbClient, GetItemInput, PutItemInput};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::sync::Mutex;
+
+/// How far a shard has been read: up to a sequence number, or to its end
+/// once it has been closed by resharding.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(from = "String", into = "String")]
+pub(super) enum Checkpoint {
+    SequenceNumber(String),
+    ShardEnd,
+}
+
+const SHARD_END: &str = "SHARD_END";
+
+impl From<String> for Checkpoint {
+    fn from(value: String) -> Self {
+        if value == SHARD_END {
+            Self::ShardEnd
+        } else {
+            Self::SequenceNumber(value)
+        }
+    }
+}
+
+impl From<Checkpoint> for String {
+    fn from(checkpoint: Checkpoint) -> Self {
+        match checkpoint {
+            Checkpoint::SequenceNumber(sequence_number) => sequence_number,
+            Checkpoint::ShardEnd => SHARD_END.to_owned(),
+        }
+    }
+}
+
+/// Stores the checkpoints of the shards, in a file of the data directory or
+/// in a DynamoDB table shared by the instances reading the stream.
+pub(super) enum Checkpointer {
+    File(FileCheckpointer),
+    DynamoDb(DynamoDbCheckpointer),
+}
+
+impl Checkpointer {
+    pub(super) async fn get(&self, shard_id: &str) -> crate::Result<Option<Checkpoint>> {
+        match self {
+            Self::File(checkpointer) => Ok(checkpointer.get(shard_id).await),
+            Self::DynamoDb(checkpointer) => checkpointer.get(shard_id).await,
+        }
+    }
+
+    pub(super) async fn set(&self, shard_id: &str, checkpoint: Checkpoint) -> crate::Result<()> {
+        match self {
+            Self::File(checkpointer) => Ok(checkpointer.set(shard_id, checkpoint).await?),
+            Self::DynamoDb(checkpointer) => checkpointer.set(shard_id, checkpoint).await,
+        }
+    }
+}
+
+/// Keeps the checkpoints of all the shards in one JSON file, rewritten as
+/// a whole on each update.
+pub(super) struct FileCheckpointer {
+    path: PathBuf,
+    checkpoints: Mutex<BTreeMap<String, Checkpoint>>,
+}
+
+impl FileCheckpointer {
+    pub(super) async fn load(path: PathBuf) -> Result<Self, io::Error> {
+        let checkpoints = match tokio::fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => return Err(error),
+        };
+        Ok(Self {
+            path,
+            checkpoints: Mutex::new(checkpoints),
+        })
+    }
+
+    async fn get(&self, shard_id: &str) -> Option<Checkpoint> {
+        self.checkpoints.lock().await.get(shard_id).cloned()
+    }
+
+    async fn set(&self, shard_id: &str, checkpoint: Checkpoint) -> Result<(), io::Error> {
+        let mut checkpoints = self.checkpoints.lock().await;
+        checkpoints.insert(shard_id.to_owned(), checkpoint);
+        save(&self.path, &*checkpoints).await
+    }
+}
+
+/// Written to a temporary file first, so an interrupted write leaves the
+/// previous checkpoints in place.
+async fn save(path: &Path, checkpoints: &BTreeMap<String, Checkpoint>) -> Result<(), io::Error> {
+    let data = serde_json::to_vec(checkpoints)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let tmp_path = path.with_extension("new.json");
+    tokio::fs::write(&tmp_path, data).await?;
+    tokio::fs::rename(&tmp_path, path).await
+}
+
+/// Keeps each checkpoint in an item of the table, keyed by the stream and
+/// the shard in its `shard_key` string attribute.
+pub(super) struct DynamoDbCheckpointer {
+    client: DynamoDbClient,
+    table_name: String,
+    stream_name: String,
+}
+
+impl DynamoDbCheckpointer {
+    pub(super) const fn new(
+        client: DynamoDbClient,
+        table_name: String,
+        stream_name: String,
+    ) -> Self {
+        Self {
+            client,
+            table_name,
+            stream_name,
+        }
+    }
+
+    fn key(&self, shard_id: &str) -> HashMap<String, AttributeValue> {
+        let mut key = HashMap::new();
+        key.insert(
+            "shard_key".to_owned(),
+            string_value(format!("{}/{}", self.stream_name, shard_id)),
+        );
+        key
+    }
+
+    async fn get(&self, shard_id: &str) -> crate::Result<Option<Checkpoint>> {
+        let output = self
+            .client
+            .get_item(GetItemInput {
+                table_name: self.table_name.clone(),
+                key: self.key(shard_id),
+                consistent_read: Some(true),
+                ..Default::default()
+            })
+            .await?;
+        Ok(output
+            .item
+            .and_then(|mut item| item.remove("checkpoint"))
+            .and_then(|value| value.s)
+            .map(Checkpoint::from))
+    }
+
+    async fn set(&self, shard_id: &str, checkpoint: Checkpoint) -> crate::Result<()> {
+        let mut item = self.key(shard_id);
+        item.insert("checkpoint".to_owned(), string_value(checkpoint.into()));
+        self.client
+            .put_item(PutItemInput {
+                table_name: self.table_name.clone(),
+                item,
+                ..Default::default()
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+fn string_value(value: String) -> AttributeValue {
+    AttributeValue {
+        s: Some(value),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_checkpoints_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoints.json");
+
+        let checkpointer = FileCheckpointer::load(path.clone()).await.unwrap();
+        assert_eq!(checkpointer.get("shardId-000000000000").await, None);
+        checkpointer
+            .set(
+                "shardId-000000000000",
+                Checkpoint::SequenceNumber(
+                    "49590338271490256608559692538361571095921575989136588802".into(),
+                ),
+            )
+            .await
+            .unwrap();
+        checkpointer
+            .set("shardId-000000000001", Checkpoint::ShardEnd)
+            .await
+            .unwrap();
+
+        let checkpointer = FileCheckpointer::load(path).await.unwrap();
+        assert_eq!(
+            checkpointer.get("shardId-000000000000").await,
+            Some(Checkpoint::SequenceNumber(
+                "49590338271490256608559692538361571095921575989136588802".into()
+            ))
+        );
+        assert_eq!(
+            checkpointer.get("shardId-000000000001").await,
+            Some(Checkpoint::ShardEnd)
+        );
+    }
+}
@@ -0,0 +1,517 @@
+use crate::{
+    config::{DataType, ProxyConfig, SourceConfig, SourceContext, SourceDescription},
+    internal_events::AwsKinesisStreamsListShardsFailed,
+    rusoto::{self, AwsAuthentication, RegionOrEndpoint},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
+use rusoto_core::{Region, RusotoError};
+use rusoto_dynamodb::DynamoDbClient;
+use rusoto_kinesis::{
+    DescribeStreamConsumerError, DescribeStreamConsumerInput, DescribeStreamSummaryInput, Kinesis,
+    KinesisClient, ListShardsInput, RegisterStreamConsumerInput, Shard,
+};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    panic,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{select, time::sleep};
+use tracing::Instrument;
+
+mod checkpoint;
+mod shard;
+
+use checkpoint::{Checkpointer, DynamoDbCheckpointer, FileCheckpointer};
+use shard::{Consumer, ShardReader, ShardState};
+
+#[derive(Derivative, Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+enum Mode {
+    #[derivative(Default)]
+    Polling,
+    EnhancedFanOut,
+}
+
+/// Where shards without a checkpoint are read from.
+#[derive(Derivative, Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+enum StartingPosition {
+    #[derivative(Default)]
+    Latest,
+    TrimHorizon,
+}
+
+impl StartingPosition {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Latest => "LATEST",
+            Self::TrimHorizon => "TRIM_HORIZON",
+        }
+    }
+}
+
+#[derive(Derivative, Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[derivative(Default)]
+enum CheckpointStore {
+    #[derivative(Default)]
+    File,
+    DynamoDb,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct DynamoDbConfig {
+    table_name: String,
+    /// Defaults to the region of the stream.
+    endpoint: Option<String>,
+}
+
+/// Reads the records of all the shards of a stream, following resharding by
+/// reading the children of a closed shard once it has been read to its end.
+///
+/// Each instance reads every shard, so instances sharing a stream need
+/// consumers and checkpoint stores of their own.
+#[derive(Derivative, Clone, Debug, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct AwsKinesisStreamsConfig {
+    #[serde(flatten)]
+    region: RegionOrEndpoint,
+    #[serde(default)]
+    auth: AwsAuthentication,
+
+    stream_name: String,
+
+    #[serde(default)]
+    mode: Mode,
+    /// The consumer registered for enhanced fan-out.
+    consumer_name: Option<String>,
+    #[serde(default)]
+    starting_position: StartingPosition,
+
+    #[serde(default = "default_poll_interval_ms")]
+    #[derivative(Default(value = "default_poll_interval_ms()"))]
+    poll_interval_ms: u64,
+    #[serde(default = "default_max_records")]
+    #[derivative(Default(value = "default_max_records()"))]
+    max_records: u32,
+    #[serde(default = "default_shard_refresh_secs")]
+    #[derivative(Default(value = "default_shard_refresh_secs()"))]
+    shard_refresh_secs: u64,
+
+    #[serde(default)]
+    checkpoint: CheckpointStore,
+    dynamodb: Option<DynamoDbConfig>,
+    /// Override global data_dir
+    data_dir: Option<PathBuf>,
+}
+
+const fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+const fn default_max_records() -> u32 {
+    1000
+}
+
+const fn default_shard_refresh_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Snafu)]
+enum ConfigError {
+    #[snafu(display("`consumer_name` is required with the `enhanced_fan_out` mode"))]
+    MissingConsumerName,
+    #[snafu(display("`dynamodb.table_name` is required with the `dynamodb` checkpoint store"))]
+    MissingDynamoDbConfig,
+    #[snafu(display("`max_records` must be between 1 and 10000"))]
+    InvalidMaxRecords,
+    #[snafu(display("Consumer {} did not become active", consumer_name))]
+    ConsumerNotActive { consumer_name: String },
+}
+
+inventory::submit! {
+    SourceDescription::new::<AwsKinesisStreamsConfig>("aws_kinesis_streams")
+}
+
+impl_generate_config_from_default!(AwsKinesisStreamsConfig);
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "aws_kinesis_streams")]
+impl SourceConfig for AwsKinesisStreamsConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.max_records == 0 || self.max_records > 10_000 {
+            return Err(ConfigError::InvalidMaxRecords.into());
+        }
+
+        let region: Region = (&self.region).try_into()?;
+        let client = self.create_client(&region, &cx.proxy)?;
+
+        let checkpointer = match self.checkpoint {
+            CheckpointStore::File => {
+                let data_dir = cx
+                    .globals
+                    .resolve_and_make_data_subdir(self.data_dir.as_ref(), &cx.id)?;
+                Checkpointer::File(
+                    FileCheckpointer::load(data_dir.join(CHECKPOINTS_FILE_NAME)).await?,
+                )
+            }
+            CheckpointStore::DynamoDb => {
+                let dynamodb = self
+                    .dynamodb
+                    .as_ref()
+                    .ok_or(ConfigError::MissingDynamoDbConfig)?;
+                Checkpointer::DynamoDb(DynamoDbCheckpointer::new(
+                    self.create_dynamodb_client(dynamodb, &region, &cx.proxy)?,
+                    dynamodb.table_name.clone(),
+                    self.stream_name.clone(),
+                ))
+            }
+        };
+
+        // The consumer is registered before the source starts, so that
+        // failing to do so fails the configuration.
+        let consumer = match self.mode {
+            Mode::Polling => Consumer::Polling {
+                interval: Duration::from_millis(self.poll_interval_ms),
+                limit: i64::from(self.max_records),
+            },
+            Mode::EnhancedFanOut => {
+                let consumer_name = self
+                    .consumer_name
+                    .as_ref()
+                    .ok_or(ConfigError::MissingConsumerName)?;
+                Consumer::EnhancedFanOut {
+                    consumer_arn: self.register_consumer(&client, consumer_name).await?,
+                }
+            }
+        };
+
+        let coordinator = Coordinator {
+            client: client.clone(),
+            stream_name: self.stream_name.clone(),
+            refresh_interval: Duration::from_secs(self.shard_refresh_secs),
+            reader: ShardReader {
+                client,
+                stream_name: self.stream_name.clone(),
+                consumer,
+                starting_position: self.starting_position,
+                checkpointer: Arc::new(checkpointer),
+                acknowledgements: cx.acknowledgements,
+            },
+        };
+        Ok(Box::pin(coordinator.run(cx.out, cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "aws_kinesis_streams"
+    }
+}
+
+const CHECKPOINTS_FILE_NAME: &str = "checkpoints.json";
+
+impl AwsKinesisStreamsConfig {
+    fn create_client(&self, region: &Region, proxy: &ProxyConfig) -> crate::Result<KinesisClient> {
+        let client = rusoto::client(proxy)?;
+        let creds = self.auth.build(region, None)?;
+
+        Ok(KinesisClient::new_with(client, creds, region.clone()))
+    }
+
+    fn create_dynamodb_client(
+        &self,
+        config: &DynamoDbConfig,
+        region: &Region,
+        proxy: &ProxyConfig,
+    ) -> crate::Result<DynamoDbClient> {
+        let region = match &config.endpoint {
+            Some(endpoint) => Region::Custom {
+                name: region.name().to_owned(),
+                endpoint: endpoint.clone(),
+            },
+            None => region.clone(),
+        };
+        let client = rusoto::client(proxy)?;
+        let creds = self.auth.build(&region, None)?;
+
+        Ok(DynamoDbClient::new_with(client, creds, region))
+    }
+
+    /// Returns the ARN of the consumer, registering it first if needed, once
+    /// it is active.
+    async fn register_consumer(
+        &self,
+        client: &KinesisClient,
+        consumer_name: &str,
+    ) -> crate::Result<String> {
+        let stream_arn = client
+            .describe_stream_summary(DescribeStreamSummaryInput {
+                stream_name: self.stream_name.clone(),
+            })
+            .await?
+            .stream_description_summary
+            .stream_arn;
+
+        let describe = || {
+            client.describe_stream_consumer(DescribeStreamConsumerInput {
+                consumer_name: Some(consumer_name.to_owned()),
+                stream_arn: Some(stream_arn.clone()),
+                ..Default::default()
+            })
+        };
+        let (mut consumer_arn, mut status) = match describe().await {
+            Ok(output) => (
+                output.consumer_description.consumer_arn,
+                output.consumer_description.consumer_status,
+            ),
+            Err(RusotoError::Service(DescribeStreamConsumerError::ResourceNotFound(_))) => {
+                let consumer = client
+                    .register_stream_consumer(RegisterStreamConsumerInput {
+                        consumer_name: consumer_name.to_owned(),
+                        stream_arn: stream_arn.clone(),
+                    })
+                    .await?
+                    .consumer;
+                (consumer.consumer_arn, consumer.consumer_status)
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        // Registering takes a few seconds.
+        for _ in 0..30 {
+            if status == "ACTIVE" {
+                return Ok(consumer_arn);
+            }
+            sleep(Duration::from_secs(1)).await;
+            let description = describe().await?.consumer_description;
+            consumer_arn = description.consumer_arn;
+            status = description.consumer_status;
+        }
+        Err(ConfigError::ConsumerNotActive {
+            consumer_name: consumer_name.to_owned(),
+        }
+        .into())
+    }
+}
+
+/// Starts a reader for each shard once its parents have been read to their
+/// end, relisting the shards periodically and whenever a shard is finished.
+struct Coordinator {
+    client: KinesisClient,
+    stream_name: String,
+    refresh_interval: Duration,
+    reader: ShardReader,
+}
+
+impl Coordinator {
+    async fn run(self, out: Pipeline, shutdown: ShutdownSignal) -> Result<(), ()> {
+        let mut readers = FuturesUnordered::new();
+        let mut running = HashSet::new();
+        let mut finished = HashSet::new();
+        let mut stopping = shutdown.clone();
+
+        'refresh: loop {
+            match self.list_shards().await {
+                Ok(shards) => {
+                    for shard_id in startable_shards(&shards, &running, &finished) {
+                        let reader = self.reader.clone();
+                        let handle = tokio::spawn(
+                            reader
+                                .run(shard_id.clone(), out.clone(), shutdown.clone())
+                                .in_current_span(),
+                        );
+                        running.insert(shard_id.clone());
+                        readers.push(handle.map(move |result| (shard_id, result)));
+                    }
+                }
+                Err(error) => emit!(AwsKinesisStreamsListShardsFailed { error }),
+            }
+
+            let refresh = sleep(self.refresh_interval);
+            tokio::pin!(refresh);
+            loop {
+                select! {
+                    _ = &mut stopping => break 'refresh,
+                    _ = &mut refresh => break,
+                    Some((shard_id, result)) = readers.next() => {
+                        running.remove(&shard_id);
+                        match result {
+                            Ok(ShardState::Finished) => {
+                                // Its children may be read now.
+                                finished.insert(shard_id);
+                                break;
+                            }
+                            Ok(ShardState::Stopped) => {}
+                            Err(error) => {
+                                if error.is_panic() {
+                                    panic::resume_unwind(error.into_panic());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // The readers stop on shutdown too, once their records are sent.
+        while let Some((_, result)) = readers.next().await {
+            if let Err(error) = result {
+                if error.is_panic() {
+                    panic::resume_unwind(error.into_panic());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_shards(
+        &self,
+    ) -> Result<Vec<Shard>, RusotoError<rusoto_kinesis::ListShardsError>> {
+        let mut shards = Vec::new();
+        let mut next_token = None;
+        loop {
+            // The stream name can't be given along with a token.
+            let output = self
+                .client
+                .list_shards(ListShardsInput {
+                    stream_name: next_token.is_none().then(|| self.stream_name.clone()),
+                    next_token: next_token.take(),
+                    ..Default::default()
+                })
+                .await?;
+            shards.extend(output.shards.unwrap_or_default());
+            match output.next_token {
+                Some(token) => next_token = Some(token),
+                None => return Ok(shards),
+            }
+        }
+    }
+}
+
+/// The shards which aren't being read or finished, and whose parents are
+/// finished. Parents which are no longer listed have expired, and so are
+/// finished as well.
+fn startable_shards(
+    shards: &[Shard],
+    running: &HashSet<String>,
+    finished: &HashSet<String>,
+) -> Vec<String> {
+    let listed = shards
+        .iter()
+        .map(|shard| (shard.shard_id.as_str(), shard))
+        .collect::<HashMap<_, _>>();
+    shards
+        .iter()
+        .filter(|shard| !running.contains(&shard.shard_id) && !finished.contains(&shard.shard_id))
+        .filter(|shard| {
+            shard
+                .parent_shard_id
+                .iter()
+                .chain(shard.adjacent_parent_shard_id.iter())
+                .all(|parent| !listed.contains_key(parent.as_str()) || finished.contains(parent))
+        })
+        .map(|shard| shard.shard_id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AwsKinesisStreamsConfig>();
+    }
+
+    #[test]
+    fn parses_config() {
+        let config: AwsKinesisStreamsConfig = toml::from_str(
+            r#"
+            region = "us-east-1"
+            stream_name = "my-stream"
+            mode = "enhanced_fan_out"
+            consumer_name = "vector"
+            starting_position = "trim_horizon"
+            checkpoint = "dynamodb"
+            dynamodb.table_name = "vector-checkpoints"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.mode, Mode::EnhancedFanOut);
+        assert_eq!(config.starting_position, StartingPosition::TrimHorizon);
+        assert_eq!(config.checkpoint, CheckpointStore::DynamoDb);
+        assert_eq!(config.max_records, 1000);
+    }
+
+    fn shard(id: &str, parent: Option<&str>, adjacent_parent: Option<&str>) -> Shard {
+        Shard {
+            shard_id: id.to_owned(),
+            parent_shard_id: parent.map(str::to_owned),
+            adjacent_parent_shard_id: adjacent_parent.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    fn set(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| (*id).to_owned()).collect()
+    }
+
+    #[test]
+    fn starts_children_after_parents() {
+        // Shard 0 was split into 1 and 2, which were merged into 3.
+        let shards = vec![
+            shard("shardId-0", None, None),
+            shard("shardId-1", Some("shardId-0"), None),
+            shard("shardId-2", Some("shardId-0"), None),
+            shard("shardId-3", Some("shardId-1"), Some("shardId-2")),
+        ];
+
+        assert_eq!(
+            startable_shards(&shards, &set(&[]), &set(&[])),
+            vec!["shardId-0"]
+        );
+        assert!(startable_shards(&shards, &set(&["shardId-0"]), &set(&[])).is_empty());
+        assert_eq!(
+            startable_shards(&shards, &set(&[]), &set(&["shardId-0"])),
+            vec!["shardId-1", "shardId-2"]
+        );
+        assert!(startable_shards(
+            &shards,
+            &set(&["shardId-2"]),
+            &set(&["shardId-0", "shardId-1"])
+        )
+        .is_empty());
+        assert_eq!(
+            startable_shards(
+                &shards,
+                &set(&[]),
+                &set(&["shardId-0", "shardId-1", "shardId-2"])
+            ),
+            vec!["shardId-3"]
+        );
+    }
+
+    #[test]
+    fn expired_parents_are_finished() {
+        let shards = vec![shard("shardId-3", Some("shardId-1"), Some("shardId-2"))];
+        assert_eq!(
+            startable_shards(&shards, &set(&[]), &set(&[])),
+            vec!["shardId-3"]
+        );
+    }
+}
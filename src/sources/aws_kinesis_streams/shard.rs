@@ -0,0 +1,402 @@
+use super::{
+    checkpoint::{Checkpoint, Checkpointer},
+    StartingPosition,
+};
+use crate::{
+    config::log_schema,
+    event::{BatchNotifier, BatchStatus, Event, LogEvent},
+    internal_events::{
+        AwsKinesisStreamsCheckpointFailed, AwsKinesisStreamsEventReceived,
+        AwsKinesisStreamsReadFailed,
+    },
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{stream, SinkExt, StreamExt};
+use rusoto_core::RusotoError;
+use rusoto_kinesis::{
+    GetRecordsError, GetRecordsInput, GetShardIteratorInput, Kinesis, KinesisClient, Record,
+    SubscribeToShardEventStreamItem, SubscribeToShardInput,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{select, time::sleep};
+
+/// How the records of a shard are read.
+#[derive(Clone, Debug)]
+pub(super) enum Consumer {
+    /// With `GetRecords` requests, sharing the read throughput of the shard
+    /// with the other consumers.
+    Polling { interval: Duration, limit: i64 },
+    /// Pushed to the registered consumer over a subscription, with read
+    /// throughput of its own.
+    EnhancedFanOut { consumer_arn: String },
+}
+
+/// What became of a shard when its reader stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum ShardState {
+    /// Read to its end after resharding, so that its children can be read.
+    Finished,
+    /// Still open, and read again by the next run.
+    Stopped,
+}
+
+enum Delivery {
+    Delivered,
+    Failed,
+    Closed,
+}
+
+/// Reads one shard, resuming from its checkpoint, and checkpoints the last
+/// record of each batch once its events are delivered. The shard is read
+/// again from the checkpoint if they are not.
+#[derive(Clone)]
+pub(super) struct ShardReader {
+    pub(super) client: KinesisClient,
+    pub(super) stream_name: String,
+    pub(super) consumer: Consumer,
+    pub(super) starting_position: StartingPosition,
+    pub(super) checkpointer: Arc<Checkpointer>,
+    pub(super) acknowledgements: bool,
+}
+
+impl ShardReader {
+    pub(super) async fn run(
+        self,
+        shard_id: String,
+        mut out: Pipeline,
+        mut shutdown: ShutdownSignal,
+    ) -> ShardState {
+        loop {
+            let checkpoint = match self.checkpointer.get(&shard_id).await {
+                Ok(Some(Checkpoint::ShardEnd)) => return ShardState::Finished,
+                Ok(checkpoint) => checkpoint,
+                Err(error) => {
+                    emit!(AwsKinesisStreamsReadFailed {
+                        error,
+                        shard_id: &shard_id,
+                    });
+                    if pause(&mut shutdown, Duration::from_secs(1)).await {
+                        return ShardState::Stopped;
+                    }
+                    continue;
+                }
+            };
+
+            let result = match &self.consumer {
+                Consumer::Polling { interval, limit } => {
+                    self.poll(
+                        &shard_id,
+                        checkpoint,
+                        *interval,
+                        *limit,
+                        &mut out,
+                        &mut shutdown,
+                    )
+                    .await
+                }
+                Consumer::EnhancedFanOut { consumer_arn } => {
+                    self.subscribe(&shard_id, checkpoint, consumer_arn, &mut out, &mut shutdown)
+                        .await
+                }
+            };
+            match result {
+                Some(state) => return state,
+                // Read again from the last checkpoint, after a pause so that
+                // failing requests aren't retried in a tight loop.
+                None => {
+                    if pause(&mut shutdown, Duration::from_secs(1)).await {
+                        return ShardState::Stopped;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `None` when the shard is to be read again from its last
+    /// checkpoint.
+    async fn poll(
+        &self,
+        shard_id: &str,
+        checkpoint: Option<Checkpoint>,
+        interval: Duration,
+        limit: i64,
+        out: &mut Pipeline,
+        shutdown: &mut ShutdownSignal,
+    ) -> Option<ShardState> {
+        let (shard_iterator_type, starting_sequence_number) = match checkpoint {
+            Some(Checkpoint::SequenceNumber(sequence_number)) => {
+                ("AFTER_SEQUENCE_NUMBER", Some(sequence_number))
+            }
+            _ => (self.starting_position.as_str(), None),
+        };
+        let iterator = self
+            .client
+            .get_shard_iterator(GetShardIteratorInput {
+                shard_id: shard_id.to_owned(),
+                shard_iterator_type: shard_iterator_type.to_owned(),
+                starting_sequence_number,
+                stream_name: self.stream_name.clone(),
+                ..Default::default()
+            })
+            .await;
+        let mut iterator = match iterator {
+            Ok(output) => output.shard_iterator?,
+            Err(error) => {
+                emit!(AwsKinesisStreamsReadFailed { error, shard_id });
+                return None;
+            }
+        };
+
+        loop {
+            let output = select! {
+                _ = &mut *shutdown => return Some(ShardState::Stopped),
+                output = self.client.get_records(GetRecordsInput {
+                    limit: Some(limit),
+                    shard_iterator: iterator.clone(),
+                }) => output,
+            };
+            let output = match output {
+                Ok(output) => output,
+                // Throttled, so try again once the limits of the shard have
+                // been replenished.
+                Err(RusotoError::Service(GetRecordsError::ProvisionedThroughputExceeded(_))) => {
+                    if pause(shutdown, interval).await {
+                        return Some(ShardState::Stopped);
+                    }
+                    continue;
+                }
+                Err(error) => {
+                    emit!(AwsKinesisStreamsReadFailed { error, shard_id });
+                    return None;
+                }
+            };
+
+            match self.deliver(shard_id, output.records, out).await {
+                Delivery::Delivered => {}
+                Delivery::Failed => return None,
+                Delivery::Closed => return Some(ShardState::Stopped),
+            }
+
+            match output.next_shard_iterator {
+                Some(next) => iterator = next,
+                None => return Some(self.finish(shard_id).await),
+            }
+
+            if pause(shutdown, interval).await {
+                return Some(ShardState::Stopped);
+            }
+        }
+    }
+
+    /// Subscriptions expire after five minutes, after which the shard is
+    /// subscribed to again from its last checkpoint.
+    async fn subscribe(
+        &self,
+        shard_id: &str,
+        checkpoint: Option<Checkpoint>,
+        consumer_arn: &str,
+        out: &mut Pipeline,
+        shutdown: &mut ShutdownSignal,
+    ) -> Option<ShardState> {
+        let starting_position = match checkpoint {
+            Some(Checkpoint::SequenceNumber(sequence_number)) => rusoto_kinesis::StartingPosition {
+                sequence_number: Some(sequence_number),
+                timestamp: None,
+                type_: "AFTER_SEQUENCE_NUMBER".to_owned(),
+            },
+            _ => rusoto_kinesis::StartingPosition {
+                sequence_number: None,
+                timestamp: None,
+                type_: self.starting_position.as_str().to_owned(),
+            },
+        };
+        let subscription = self
+            .client
+            .subscribe_to_shard(SubscribeToShardInput {
+                consumer_arn: consumer_arn.to_owned(),
+                shard_id: shard_id.to_owned(),
+                starting_position,
+            })
+            .await;
+        let mut events = match subscription {
+            Ok(output) => output.event_stream,
+            Err(error) => {
+                emit!(AwsKinesisStreamsReadFailed { error, shard_id });
+                return None;
+            }
+        };
+
+        loop {
+            let event = select! {
+                _ = &mut *shutdown => return Some(ShardState::Stopped),
+                event = events.next() => event,
+            };
+            let event = match event {
+                Some(Ok(SubscribeToShardEventStreamItem::SubscribeToShardEvent(event))) => event,
+                Some(Ok(item)) => {
+                    emit!(AwsKinesisStreamsReadFailed {
+                        error: format!("{:?}", item),
+                        shard_id,
+                    });
+                    return None;
+                }
+                Some(Err(error)) => {
+                    emit!(AwsKinesisStreamsReadFailed { error, shard_id });
+                    return None;
+                }
+                // The subscription expired.
+                None => return None,
+            };
+
+            match self.deliver(shard_id, event.records, out).await {
+                Delivery::Delivered => {}
+                Delivery::Failed => return None,
+                Delivery::Closed => return Some(ShardState::Stopped),
+            }
+
+            // The child shards are only sent once the end of the shard is
+            // reached.
+            if event
+                .child_shards
+                .map_or(false, |child_shards| !child_shards.is_empty())
+            {
+                return Some(self.finish(shard_id).await);
+            }
+        }
+    }
+
+    async fn deliver(&self, shard_id: &str, records: Vec<Record>, out: &mut Pipeline) -> Delivery {
+        let last_sequence_number = match records.last() {
+            Some(record) => record.sequence_number.clone(),
+            None => return Delivery::Delivered,
+        };
+
+        let (batch, receiver) = if self.acknowledgements {
+            let (batch, receiver) = BatchNotifier::new_with_receiver();
+            (Some(batch), Some(receiver))
+        } else {
+            (None, None)
+        };
+        let events = records
+            .into_iter()
+            .map(|record| {
+                let log = self.create_log(shard_id, record);
+                match &batch {
+                    Some(batch) => Event::from(log.with_batch_notifier(batch)),
+                    None => Event::from(log),
+                }
+            })
+            .collect::<Vec<_>>();
+        drop(batch);
+
+        if let Err(error) = out.send_all(&mut stream::iter(events).map(Ok)).await {
+            error!(message = "Error sending to sink.", %error);
+            return Delivery::Closed;
+        }
+
+        let status = match receiver {
+            Some(receiver) => receiver.await,
+            None => BatchStatus::Delivered,
+        };
+        match status {
+            BatchStatus::Delivered => {
+                let checkpoint = Checkpoint::SequenceNumber(last_sequence_number);
+                if let Err(error) = self.checkpointer.set(shard_id, checkpoint).await {
+                    // The records are delivered, so reading goes on, and
+                    // they are only read again if the source restarts.
+                    emit!(AwsKinesisStreamsCheckpointFailed { error, shard_id });
+                }
+                Delivery::Delivered
+            }
+            BatchStatus::Errored | BatchStatus::Failed => Delivery::Failed,
+        }
+    }
+
+    async fn finish(&self, shard_id: &str) -> ShardState {
+        if let Err(error) = self.checkpointer.set(shard_id, Checkpoint::ShardEnd).await {
+            emit!(AwsKinesisStreamsCheckpointFailed { error, shard_id });
+        }
+        ShardState::Finished
+    }
+
+    fn create_log(&self, shard_id: &str, record: Record) -> LogEvent {
+        emit!(AwsKinesisStreamsEventReceived {
+            byte_size: record.data.len(),
+        });
+
+        let mut log = LogEvent::default();
+        log.insert(log_schema().message_key(), record.data);
+        let timestamp = record
+            .approximate_arrival_timestamp
+            .and_then(|seconds| Utc.timestamp_millis_opt((seconds * 1000.0) as i64).latest())
+            .unwrap_or_else(Utc::now);
+        log.insert(log_schema().timestamp_key(), timestamp);
+        log.insert(
+            log_schema().source_type_key(),
+            Bytes::from("aws_kinesis_streams"),
+        );
+        log.insert("stream", self.stream_name.clone());
+        log.insert("shard_id", shard_id.to_owned());
+        log.insert("partition_key", record.partition_key);
+        log.insert("sequence_number", record.sequence_number);
+        log
+    }
+}
+
+/// Returns `true` if the source is shutting down.
+async fn pause(shutdown: &mut ShutdownSignal, duration: Duration) -> bool {
+    select! {
+        _ = shutdown => true,
+        _ = sleep(duration) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::aws_kinesis_streams::checkpoint::FileCheckpointer;
+    use rusoto_core::Region;
+
+    #[tokio::test]
+    async fn creates_log_from_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let reader = ShardReader {
+            client: KinesisClient::new(Region::UsEast1),
+            stream_name: "my-stream".into(),
+            consumer: Consumer::Polling {
+                interval: Duration::from_secs(1),
+                limit: 100,
+            },
+            starting_position: StartingPosition::Latest,
+            checkpointer: Arc::new(Checkpointer::File(
+                FileCheckpointer::load(dir.path().join("checkpoints.json"))
+                    .await
+                    .unwrap(),
+            )),
+            acknowledgements: false,
+        };
+
+        let log = reader.create_log(
+            "shardId-000000000000",
+            Record {
+                approximate_arrival_timestamp: Some(1_630_000_000.5),
+                data: Bytes::from("hello"),
+                partition_key: "key".into(),
+                sequence_number: "4959".into(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(log[log_schema().message_key()], "hello".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp_millis(1_630_000_000_500).into()
+        );
+        assert_eq!(log["stream"], "my-stream".into());
+        assert_eq!(log["shard_id"], "shardId-000000000000".into());
+        assert_eq!(log["partition_key"], "key".into());
+        assert_eq!(log["sequence_number"], "4959".into());
+    }
+}
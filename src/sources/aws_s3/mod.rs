@@ -11,8 +11,9 @@ use rusoto_s3::S3Client;
 use rusoto_sqs::SqsClient;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::convert::TryInto;
+use std::{convert::TryInto, path::Path};
 
+mod poll;
 pub mod sqs;
 
 #[derive(Derivative, Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -32,6 +33,7 @@ pub enum Compression {
 enum Strategy {
     #[derivative(Default)]
     Sqs,
+    Poll,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -46,6 +48,8 @@ struct AwsS3Config {
 
     sqs: Option<sqs::Config>,
 
+    poll: Option<poll::Config>,
+
     // Deprecated name. Moved to auth.
     assume_role: Option<String>,
     #[serde(default)]
@@ -76,6 +80,19 @@ impl SourceConfig for AwsS3Config {
                     .await?
                     .run(cx.out, cx.shutdown),
             )),
+            Strategy::Poll => {
+                let poll = self
+                    .poll
+                    .as_ref()
+                    .ok_or(CreateIngestorError::PollConfigMissing)?;
+                let data_dir = cx
+                    .globals
+                    .resolve_and_make_data_subdir(poll.data_dir.as_ref(), &cx.id)?;
+                Ok(Box::pin(
+                    self.create_poll_ingestor(poll, &data_dir, multiline_config, &cx.proxy)?
+                        .run(cx.out, cx.shutdown),
+                ))
+            }
         }
     }
 
@@ -89,11 +106,38 @@ impl SourceConfig for AwsS3Config {
 }
 
 impl AwsS3Config {
+    fn create_poll_ingestor(
+        &self,
+        poll: &poll::Config,
+        data_dir: &Path,
+        multiline: Option<line_agg::Config>,
+        proxy: &ProxyConfig,
+    ) -> Result<poll::Ingestor, CreateIngestorError> {
+        let region: Region = (&self.region).try_into().context(RegionParse {})?;
+
+        let client = rusoto::client(proxy).with_context(|| Client {})?;
+        let creds = self
+            .auth
+            .build(&region, self.assume_role.clone())
+            .context(Credentials {})?;
+        let s3_client = S3Client::new_with(client, creds, region.clone());
+
+        poll::Ingestor::new(
+            region,
+            s3_client,
+            poll,
+            data_dir,
+            self.compression,
+            multiline,
+        )
+        .context(InitializePoll {})
+    }
+
     async fn create_sqs_ingestor(
         &self,
         multiline: Option<line_agg::Config>,
         proxy: &ProxyConfig,
-    ) -> Result<sqs::Ingestor, CreateSqsIngestorError> {
+    ) -> Result<sqs::Ingestor, CreateIngestorError> {
         use std::sync::Arc;
 
         let region: Region = (&self.region).try_into().context(RegionParse {})?;
@@ -129,21 +173,25 @@ impl AwsS3Config {
                 .await
                 .context(Initialize {})
             }
-            None => Err(CreateSqsIngestorError::ConfigMissing {}),
+            None => Err(CreateIngestorError::ConfigMissing {}),
         }
     }
 }
 
 #[derive(Debug, Snafu)]
-enum CreateSqsIngestorError {
+enum CreateIngestorError {
     #[snafu(display("Unable to initialize: {}", source))]
     Initialize { source: sqs::IngestorNewError },
+    #[snafu(display("Unable to initialize: {}", source))]
+    InitializePoll { source: poll::IngestorNewError },
     #[snafu(display("Unable to create AWS client: {}", source))]
     Client { source: crate::Error },
     #[snafu(display("Unable to create AWS credentials provider: {}", source))]
     Credentials { source: crate::Error },
     #[snafu(display("Configuration for `sqs` required when strategy=sqs"))]
     ConfigMissing,
+    #[snafu(display("Configuration for `poll` required when strategy=poll"))]
+    PollConfigMissing,
     #[snafu(display("Could not parse region configuration: {}", source))]
     RegionParse { source: rusoto::region::ParseError },
 }
@@ -293,7 +341,7 @@ mod test {
 #[cfg(feature = "aws-s3-integration-tests")]
 #[cfg(test)]
 mod integration_tests {
-    use super::{sqs, AwsS3Config, Compression, Strategy};
+    use super::{poll, sqs, AwsS3Config, Compression, Strategy};
     use crate::{
         config::{SourceConfig, SourceContext},
         line_agg,
@@ -306,7 +354,7 @@ mod integration_tests {
     };
     use pretty_assertions::assert_eq;
     use rusoto_core::Region;
-    use rusoto_s3::{PutObjectRequest, S3Client, S3};
+    use rusoto_s3::{CreateBucketRequest, PutObjectRequest, S3Client, S3};
     use rusoto_sqs::{Sqs, SqsClient};
 
     #[tokio::test]
@@ -414,6 +462,67 @@ mod integration_tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn s3_poll_objects() {
+        trace_init();
+
+        let s3 = s3_client();
+        let bucket = uuid::Uuid::new_v4().to_string();
+        s3.create_bucket(CreateBucketRequest {
+            bucket: bucket.clone(),
+            ..Default::default()
+        })
+        .await
+        .expect("Could not create bucket");
+
+        let logs: Vec<String> = random_lines(100).take(10).collect();
+        for (key, body) in &[
+            ("logs/a.log", logs.join("\n")),
+            ("other/b.log", "ignored".to_owned()),
+        ] {
+            s3.put_object(PutObjectRequest {
+                bucket: bucket.clone(),
+                key: key.to_string(),
+                body: Some(rusoto_core::ByteStream::from(body.clone().into_bytes())),
+                ..Default::default()
+            })
+            .await
+            .expect("Could not put object");
+        }
+
+        let data_dir = tempfile::tempdir().unwrap();
+        let config = AwsS3Config {
+            region: RegionOrEndpoint::with_endpoint("http://localhost:4566".to_owned()),
+            strategy: Strategy::Poll,
+            poll: Some(poll::Config {
+                bucket: bucket.clone(),
+                prefix: Some("logs/".to_owned()),
+                poll_secs: 1,
+                data_dir: Some(data_dir.path().to_path_buf()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let (tx, rx) = Pipeline::new_test();
+        tokio::spawn(async move {
+            config
+                .build(SourceContext::new_test(tx))
+                .await
+                .unwrap()
+                .await
+                .unwrap()
+        });
+
+        let events = collect_n(rx, logs.len()).await;
+        assert_eq!(events.len(), logs.len());
+        for (event, line) in events.iter().zip(&logs) {
+            let log = event.as_log();
+            assert_eq!(log["message"], line.as_str().into());
+            assert_eq!(log["object"], "logs/a.log".into());
+        }
+    }
+
     fn config(queue_url: &str, multiline: Option<MultilineConfig>) -> AwsS3Config {
         AwsS3Config {
             region: RegionOrEndpoint::with_endpoint("http://localhost:4566".to_owned()),
@@ -505,8 +614,8 @@ mod integration_tests {
     /// returns the bucket name
     async fn create_bucket(client: &S3Client, queue_name: &str) -> String {
         use rusoto_s3::{
-            CreateBucketRequest, NotificationConfiguration,
-            PutBucketNotificationConfigurationRequest, QueueConfiguration,
+            NotificationConfiguration, PutBucketNotificationConfigurationRequest,
+            QueueConfiguration,
         };
 
         let bucket_name = uuid::Uuid::new_v4().to_string();
@@ -0,0 +1,398 @@
+//! Finds new objects by listing the bucket, for buckets whose notifications
+//! can't be sent to an SQS queue.
+use crate::{
+    internal_events::aws_s3::source::{
+        S3ObjectProcessingFailed, S3ObjectsListFailed, S3PollProgressWriteFailed,
+    },
+    line_agg,
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use rusoto_core::{Region, RusotoError};
+use rusoto_s3::{ListObjectsV2Error, ListObjectsV2Request, S3Client, S3};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{pin, select, time::sleep};
+
+const PROGRESS_FILE_NAME: &str = "aws_s3_poll.json";
+
+#[derive(Derivative, Clone, Debug, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub(super) struct Config {
+    pub(super) bucket: String,
+
+    #[serde(default)]
+    pub(super) prefix: Option<String>,
+
+    #[serde(default)]
+    pub(super) suffix: Option<String>,
+
+    /// Glob patterns, one of which keys have to match if any are given
+    #[serde(default)]
+    pub(super) key_patterns: Vec<String>,
+
+    /// Objects last modified before this are ignored
+    #[serde(default)]
+    pub(super) modified_since: Option<DateTime<Utc>>,
+
+    #[serde(default = "default_poll_secs")]
+    #[derivative(Default(value = "default_poll_secs()"))]
+    pub(super) poll_secs: u64,
+
+    /// Override global data_dir
+    #[serde(default)]
+    pub(super) data_dir: Option<PathBuf>,
+}
+
+const fn default_poll_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Snafu)]
+pub(super) enum IngestorNewError {
+    #[snafu(display("Invalid key pattern {:?}: {}", pattern, source))]
+    InvalidKeyPattern {
+        source: glob::PatternError,
+        pattern: String,
+    },
+}
+
+/// Which listed objects are read.
+#[derive(Debug)]
+struct KeyFilter {
+    prefix: Option<String>,
+    suffix: Option<String>,
+    patterns: Vec<glob::Pattern>,
+    modified_since: Option<DateTime<Utc>>,
+}
+
+impl KeyFilter {
+    fn new(config: &Config) -> Result<Self, IngestorNewError> {
+        let patterns = config
+            .key_patterns
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern).context(InvalidKeyPattern { pattern }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            prefix: config.prefix.clone(),
+            suffix: config.suffix.clone(),
+            patterns,
+            modified_since: config.modified_since,
+        })
+    }
+
+    fn matches(&self, key: &str, last_modified: DateTime<Utc>) -> bool {
+        self.prefix
+            .as_ref()
+            .map_or(true, |prefix| key.starts_with(prefix.as_str()))
+            && self
+                .suffix
+                .as_ref()
+                .map_or(true, |suffix| key.ends_with(suffix.as_str()))
+            && (self.patterns.is_empty()
+                || self.patterns.iter().any(|pattern| pattern.matches(key)))
+            && self
+                .modified_since
+                .map_or(true, |modified_since| last_modified >= modified_since)
+    }
+}
+
+/// The objects read so far: those last modified before `last_modified`, and
+/// the `keys` last modified at it. Objects are read in the order they were
+/// last modified, so this stays small however large the bucket is.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+struct Progress {
+    last_modified: Option<DateTime<Utc>>,
+    keys: BTreeSet<String>,
+}
+
+impl Progress {
+    fn is_new(&self, key: &str, last_modified: DateTime<Utc>) -> bool {
+        match self.last_modified {
+            None => true,
+            Some(read_until) => {
+                last_modified > read_until
+                    || (last_modified == read_until && !self.keys.contains(key))
+            }
+        }
+    }
+
+    fn record(&mut self, key: &str, last_modified: DateTime<Utc>) {
+        match self.last_modified {
+            Some(read_until) if last_modified < read_until => {}
+            Some(read_until) if last_modified == read_until => {
+                self.keys.insert(key.to_owned());
+            }
+            _ => {
+                self.last_modified = Some(last_modified);
+                self.keys.clear();
+                self.keys.insert(key.to_owned());
+            }
+        }
+    }
+
+    async fn load(path: &Path) -> Result<Self, io::Error> {
+        match tokio::fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Written to a temporary file first, so an interrupted write leaves the
+    /// previous progress in place.
+    async fn save(&self, path: &Path) -> Result<(), io::Error> {
+        let data = serde_json::to_vec(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let tmp_path = path.with_extension("new.json");
+        tokio::fs::write(&tmp_path, data).await?;
+        tokio::fs::rename(&tmp_path, path).await
+    }
+}
+
+#[derive(Debug)]
+struct ListedObject {
+    key: String,
+    last_modified: DateTime<Utc>,
+}
+
+pub(super) struct Ingestor {
+    region: Region,
+    s3_client: S3Client,
+    bucket: String,
+    filter: KeyFilter,
+    poll: Duration,
+    progress_path: PathBuf,
+    compression: super::Compression,
+    multiline: Option<line_agg::Config>,
+}
+
+impl Ingestor {
+    pub(super) fn new(
+        region: Region,
+        s3_client: S3Client,
+        config: &Config,
+        data_dir: &Path,
+        compression: super::Compression,
+        multiline: Option<line_agg::Config>,
+    ) -> Result<Ingestor, IngestorNewError> {
+        Ok(Ingestor {
+            region,
+            s3_client,
+            bucket: config.bucket.clone(),
+            filter: KeyFilter::new(config)?,
+            poll: Duration::from_secs(config.poll_secs),
+            progress_path: data_dir.join(PROGRESS_FILE_NAME),
+            compression,
+            multiline,
+        })
+    }
+
+    pub(super) async fn run(self, mut out: Pipeline, shutdown: ShutdownSignal) -> Result<(), ()> {
+        let mut progress = match Progress::load(&self.progress_path).await {
+            Ok(progress) => progress,
+            Err(error) => {
+                error!(
+                    message = "Unable to load progress, reading all objects.",
+                    path = ?self.progress_path,
+                    %error,
+                );
+                Progress::default()
+            }
+        };
+
+        let shutdown = shutdown.fuse();
+        pin!(shutdown);
+
+        loop {
+            select! {
+                _ = &mut shutdown => break,
+                _ = self.run_once(&mut progress, &mut out) => {},
+            }
+            select! {
+                _ = &mut shutdown => break,
+                _ = sleep(self.poll) => {},
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_once(&self, progress: &mut Progress, out: &mut Pipeline) {
+        let mut objects = match self.list_new_objects(progress).await {
+            Ok(objects) => objects,
+            Err(error) => {
+                emit!(S3ObjectsListFailed {
+                    bucket: &self.bucket,
+                    error: &error,
+                });
+                return;
+            }
+        };
+        objects.sort_by(|a, b| (a.last_modified, &a.key).cmp(&(b.last_modified, &b.key)));
+
+        for object in objects {
+            let result = super::sqs::process_object(
+                &self.s3_client,
+                self.compression,
+                self.multiline.as_ref(),
+                out,
+                &self.bucket,
+                &object.key,
+                self.region.name(),
+            )
+            .await;
+
+            // Later objects are left for the next poll, as recording them
+            // would move past this one for good.
+            if let Err(error) = result {
+                emit!(S3ObjectProcessingFailed {
+                    bucket: &self.bucket,
+                    key: &object.key,
+                    error: &error,
+                });
+                return;
+            }
+
+            progress.record(&object.key, object.last_modified);
+            if let Err(error) = progress.save(&self.progress_path).await {
+                emit!(S3PollProgressWriteFailed { error });
+            }
+        }
+    }
+
+    async fn list_new_objects(
+        &self,
+        progress: &Progress,
+    ) -> Result<Vec<ListedObject>, RusotoError<ListObjectsV2Error>> {
+        let mut objects = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let response = self
+                .s3_client
+                .list_objects_v2(ListObjectsV2Request {
+                    bucket: self.bucket.clone(),
+                    prefix: self.filter.prefix.clone(),
+                    continuation_token,
+                    ..Default::default()
+                })
+                .await?;
+
+            for object in response.contents.unwrap_or_default() {
+                let key = match object.key {
+                    Some(key) => key,
+                    None => continue,
+                };
+                let last_modified = match object
+                    .last_modified
+                    .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                {
+                    Some(t) => t.with_timezone(&Utc),
+                    None => {
+                        warn!(message = "Skipping object without a valid modification time.", %key);
+                        continue;
+                    }
+                };
+                if self.filter.matches(&key, last_modified) && progress.is_new(&key, last_modified)
+                {
+                    objects.push(ListedObject { key, last_modified });
+                }
+            }
+
+            continuation_token = response.next_continuation_token;
+            if !response.is_truncated.unwrap_or(false) || continuation_token.is_none() {
+                return Ok(objects);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn filter(config: Config) -> KeyFilter {
+        KeyFilter::new(&Config {
+            bucket: "bucket".to_owned(),
+            ..config
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn key_filter() {
+        let now = Utc::now();
+        let filter = filter(Config {
+            prefix: Some("logs/".to_owned()),
+            suffix: Some(".gz".to_owned()),
+            key_patterns: vec!["logs/*/app-*".to_owned(), "logs/audit/*".to_owned()],
+            modified_since: Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)),
+            ..Default::default()
+        });
+
+        assert!(filter.matches("logs/web/app-1.log.gz", now));
+        assert!(filter.matches("logs/audit/1.log.gz", now));
+        assert!(!filter.matches("logs/web/db-1.log.gz", now));
+        assert!(!filter.matches("logs/web/app-1.log", now));
+        assert!(!filter.matches("other/web/app-1.log.gz", now));
+        assert!(!filter.matches(
+            "logs/web/app-1.log.gz",
+            Utc.ymd(2020, 12, 31).and_hms(0, 0, 0)
+        ));
+
+        assert!(filter(Config::default()).matches("anything", now));
+    }
+
+    #[test]
+    fn invalid_key_pattern() {
+        let config = Config {
+            key_patterns: vec!["logs/[".to_owned()],
+            ..Default::default()
+        };
+        assert!(KeyFilter::new(&config).is_err());
+    }
+
+    #[test]
+    fn progress_skips_read_objects() {
+        let earlier = Utc.ymd(2021, 1, 1).and_hms(0, 0, 0);
+        let later = Utc.ymd(2021, 1, 1).and_hms(0, 0, 1);
+        let mut progress = Progress::default();
+        assert!(progress.is_new("a", earlier));
+
+        progress.record("a", earlier);
+        assert!(!progress.is_new("a", earlier));
+        assert!(progress.is_new("b", earlier));
+        assert!(progress.is_new("a", later));
+
+        progress.record("b", later);
+        assert!(!progress.is_new("c", earlier));
+        assert!(progress.is_new("c", later));
+        assert_eq!(
+            progress.keys,
+            std::iter::once("b".to_owned()).collect::<BTreeSet<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROGRESS_FILE_NAME);
+        assert_eq!(Progress::load(&path).await.unwrap(), Progress::default());
+
+        let mut progress = Progress::default();
+        progress.record("a", Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+        progress.save(&path).await.unwrap();
+        assert_eq!(Progress::load(&path).await.unwrap(), progress);
+    }
+}
@@ -359,134 +359,16 @@ impl IngestorProcess {
             });
         }
 
-        let object = self
-            .state
-            .s3_client
-            .get_object(GetObjectRequest {
-                bucket: s3_event.s3.bucket.name.clone(),
-                key: s3_event.s3.object.key.clone(),
-                ..Default::default()
-            })
-            .await
-            .context(GetObject {
-                bucket: s3_event.s3.bucket.name.clone(),
-                key: s3_event.s3.object.key.clone(),
-            })?;
-
-        let metadata = object.metadata;
-        let timestamp = object
-            .last_modified
-            .and_then(|t| {
-                DateTime::parse_from_rfc2822(&t)
-                    .map(|ts| Utc.timestamp(ts.timestamp(), ts.timestamp_subsec_nanos()))
-                    .ok()
-            })
-            .unwrap_or_else(Utc::now);
-
-        match object.body {
-            Some(body) => {
-                let object_reader = super::s3_object_decoder(
-                    self.state.compression,
-                    &s3_event.s3.object.key,
-                    object.content_encoding.as_deref(),
-                    object.content_type.as_deref(),
-                    body,
-                )
-                .await;
-
-                // Record the read error seen to propagate up later so we avoid ack'ing the SQS
-                // message
-                //
-                // String is used as we cannot clone std::io::Error to take ownership in closure
-                //
-                // FramedRead likely stops when it gets an i/o error but I found it more clear to
-                // show that we `take_while` there hasn't been an error
-                //
-                // This can result in objects being partially processed before an error, but we
-                // prefer duplicate lines over message loss. Future work could include recording
-                // the offset of the object that has been read, but this would only be relevant in
-                // the case that the same vector instance processes the same message.
-                let mut read_error: Option<std::io::Error> = None;
-                let lines: Box<dyn Stream<Item = Bytes> + Send + Unpin> = Box::new(
-                    FramedRead::new(object_reader, BytesDelimitedCodec::new(b'\n'))
-                        .map(|res| {
-                            res.map_err(|err| {
-                                read_error = Some(err);
-                            })
-                            .ok()
-                        })
-                        .take_while(|res| ready(res.is_some()))
-                        .map(|r| r.expect("validated by take_while")),
-                );
-
-                let lines = match &self.state.multiline {
-                    Some(config) => Box::new(
-                        LineAgg::new(
-                            lines.map(|line| ((), line, ())),
-                            line_agg::Logic::new(config.clone()),
-                        )
-                        .map(|(_src, line, _context)| line),
-                    ),
-                    None => lines,
-                };
-
-                let bucket_name = Bytes::from(s3_event.s3.bucket.name.as_str().as_bytes().to_vec());
-                let object_key = Bytes::from(s3_event.s3.object.key.as_str().as_bytes().to_vec());
-                let aws_region = Bytes::from(s3_event.aws_region.as_str().as_bytes().to_vec());
-
-                let mut stream = lines.filter_map(|line| {
-                    emit!(SqsS3EventReceived {
-                        byte_size: line.len()
-                    });
-
-                    let mut event = Event::from(line);
-
-                    let log = event.as_mut_log();
-                    log.insert_flat("bucket", bucket_name.clone());
-                    log.insert_flat("object", object_key.clone());
-                    log.insert_flat("region", aws_region.clone());
-                    log.insert_flat(log_schema().timestamp_key(), timestamp);
-
-                    if let Some(metadata) = &metadata {
-                        for (key, value) in metadata {
-                            log.insert(key, value.clone());
-                        }
-                    }
-
-                    ready(Some(Ok(event)))
-                });
-
-                let send_error = match self.out.send_all(&mut stream).await {
-                    Ok(_) => None,
-                    Err(_) => Some(crate::pipeline::ClosedError),
-                };
-
-                // Up above, `lines` captures `read_error`, and eventually is captured by `stream`,
-                // so we explicitly drop it so that we can again utilize `read_error` below.
-                drop(stream);
-
-                read_error
-                    .map(|error| {
-                        Err(ProcessingError::ReadObject {
-                            source: error,
-                            bucket: s3_event.s3.bucket.name.clone(),
-                            key: s3_event.s3.object.key.clone(),
-                        })
-                    })
-                    .unwrap_or_else(|| {
-                        send_error
-                            .map(|error| {
-                                Err(ProcessingError::PipelineSend {
-                                    source: error,
-                                    bucket: s3_event.s3.bucket.name.clone(),
-                                    key: s3_event.s3.object.key.clone(),
-                                })
-                            })
-                            .unwrap_or(Ok(()))
-                    })
-            }
-            None => Ok(()),
-        }
+        process_object(
+            &self.state.s3_client,
+            self.state.compression,
+            self.state.multiline.as_ref(),
+            &mut self.out,
+            &s3_event.s3.bucket.name,
+            &s3_event.s3.object.key,
+            &s3_event.aws_region,
+        )
+        .await
     }
 
     async fn receive_messages(&mut self) -> Result<Vec<Message>, RusotoError<ReceiveMessageError>> {
@@ -517,6 +399,144 @@ impl IngestorProcess {
     }
 }
 
+/// Sends the lines of an object, with its metadata, as log events.
+pub(super) async fn process_object(
+    s3_client: &S3Client,
+    compression: super::Compression,
+    multiline: Option<&line_agg::Config>,
+    out: &mut Pipeline,
+    bucket: &str,
+    key: &str,
+    region: &str,
+) -> Result<(), ProcessingError> {
+    let object = s3_client
+        .get_object(GetObjectRequest {
+            bucket: bucket.to_owned(),
+            key: key.to_owned(),
+            ..Default::default()
+        })
+        .await
+        .context(GetObject {
+            bucket: bucket.to_owned(),
+            key: key.to_owned(),
+        })?;
+
+    let metadata = object.metadata;
+    let timestamp = object
+        .last_modified
+        .and_then(|t| {
+            DateTime::parse_from_rfc2822(&t)
+                .map(|ts| Utc.timestamp(ts.timestamp(), ts.timestamp_subsec_nanos()))
+                .ok()
+        })
+        .unwrap_or_else(Utc::now);
+
+    match object.body {
+        Some(body) => {
+            let object_reader = super::s3_object_decoder(
+                compression,
+                key,
+                object.content_encoding.as_deref(),
+                object.content_type.as_deref(),
+                body,
+            )
+            .await;
+
+            // Record the read error seen to propagate up later so we avoid ack'ing the SQS
+            // message
+            //
+            // String is used as we cannot clone std::io::Error to take ownership in closure
+            //
+            // FramedRead likely stops when it gets an i/o error but I found it more clear to
+            // show that we `take_while` there hasn't been an error
+            //
+            // This can result in objects being partially processed before an error, but we
+            // prefer duplicate lines over message loss. Future work could include recording
+            // the offset of the object that has been read, but this would only be relevant in
+            // the case that the same vector instance processes the same message.
+            let mut read_error: Option<std::io::Error> = None;
+            let lines: Box<dyn Stream<Item = Bytes> + Send + Unpin> = Box::new(
+                FramedRead::new(object_reader, BytesDelimitedCodec::new(b'\n'))
+                    .map(|res| {
+                        res.map_err(|err| {
+                            read_error = Some(err);
+                        })
+                        .ok()
+                    })
+                    .take_while(|res| ready(res.is_some()))
+                    .map(|r| r.expect("validated by take_while")),
+            );
+
+            let lines = match multiline {
+                Some(config) => Box::new(
+                    LineAgg::new(
+                        lines.map(|line| ((), line, ())),
+                        line_agg::Logic::new(config.clone()),
+                    )
+                    .map(|(_src, line, _context)| line),
+                ),
+                None => lines,
+            };
+
+            let bucket_name = Bytes::from(bucket.as_bytes().to_vec());
+            let object_key = Bytes::from(key.as_bytes().to_vec());
+            let aws_region = Bytes::from(region.as_bytes().to_vec());
+
+            let mut stream = lines.filter_map(|line| {
+                emit!(SqsS3EventReceived {
+                    byte_size: line.len()
+                });
+
+                let mut event = Event::from(line);
+
+                let log = event.as_mut_log();
+                log.insert_flat("bucket", bucket_name.clone());
+                log.insert_flat("object", object_key.clone());
+                log.insert_flat("region", aws_region.clone());
+                log.insert_flat(log_schema().timestamp_key(), timestamp);
+
+                if let Some(metadata) = &metadata {
+                    for (key, value) in metadata {
+                        log.insert(key, value.clone());
+                    }
+                }
+
+                ready(Some(Ok(event)))
+            });
+
+            let send_error = match out.send_all(&mut stream).await {
+                Ok(_) => None,
+                Err(_) => Some(crate::pipeline::ClosedError),
+            };
+
+            // Up above, `lines` captures `read_error`, and eventually is captured by `stream`,
+            // so we explicitly drop it so that we can again utilize `read_error` below.
+            drop(stream);
+
+            read_error
+                .map(|error| {
+                    Err(ProcessingError::ReadObject {
+                        source: error,
+                        bucket: bucket.to_owned(),
+                        key: key.to_owned(),
+                    })
+                })
+                .unwrap_or_else(|| {
+                    send_error
+                        .map(|error| {
+                            Err(ProcessingError::PipelineSend {
+                                source: error,
+                                bucket: bucket.to_owned(),
+                                key: key.to_owned(),
+                            })
+                        })
+                        .unwrap_or(Ok(()))
+                })
+        }
+        None => Ok(()),
+    }
+}
+
 // https://docs.aws.amazon.com/AmazonS3/latest/dev/notification-content-structure.html
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
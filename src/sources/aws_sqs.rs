@@ -0,0 +1,493 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, ProxyConfig, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{BatchNotifier, BatchStatus, Event, LogEvent},
+    internal_events::{
+        AwsSqsEventReceived, AwsSqsMessageDeleteFailed, AwsSqsMessageDeletePartialFailure,
+        AwsSqsMessageDeleteSucceeded, AwsSqsMessageReceiveFailed,
+        AwsSqsMessageVisibilityChangeFailed,
+    },
+    rusoto::{self, AwsAuthentication, RegionOrEndpoint},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{stream, FutureExt, SinkExt, StreamExt};
+use rusoto_sqs::{
+    ChangeMessageVisibilityBatchRequest, ChangeMessageVisibilityBatchRequestEntry,
+    DeleteMessageBatchRequest, DeleteMessageBatchRequestEntry, Message, ReceiveMessageRequest, Sqs,
+    SqsClient,
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp, convert::TryInto, panic, time::Duration};
+use tokio::{pin, select, time};
+use tracing::Instrument;
+
+/// Receives messages from an SQS queue, deleting them once their events are
+/// delivered.
+///
+/// Each task receives a batch of messages and handles it completely before
+/// receiving the next, so that messages of a FIFO queue, which SQS doesn't
+/// hand out while earlier messages of their group are in flight, are
+/// delivered in order within each message group.
+#[derive(Derivative, Clone, Debug, Deserialize, Serialize)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct AwsSqsConfig {
+    #[serde(flatten)]
+    region: RegionOrEndpoint,
+    #[serde(default)]
+    auth: AwsAuthentication,
+
+    queue_url: String,
+
+    // restricted to u32 for safe conversion to i64 later
+    #[serde(default = "default_poll_secs")]
+    #[derivative(Default(value = "default_poll_secs()"))]
+    poll_secs: u32,
+
+    // restricted to u32 for safe conversion to i64 later
+    #[serde(default = "default_visibility_timeout_secs")]
+    #[derivative(Default(value = "default_visibility_timeout_secs()"))]
+    visibility_timeout_secs: u32,
+
+    /// Keep extending the visibility timeout of messages while their events
+    /// are waiting to be delivered, so that slow sinks don't cause them to be
+    /// received again.
+    #[serde(default = "default_true")]
+    #[derivative(Default(value = "default_true()"))]
+    extend_visibility_timeout: bool,
+
+    #[serde(default = "default_true")]
+    #[derivative(Default(value = "default_true()"))]
+    delete_message: bool,
+
+    // number of tasks spawned for running the receive loop
+    #[serde(default = "default_client_concurrency")]
+    #[derivative(Default(value = "default_client_concurrency()"))]
+    client_concurrency: u32,
+
+    #[serde(default = "default_message_group_key")]
+    #[derivative(Default(value = "default_message_group_key()"))]
+    message_group_key: String,
+}
+
+const fn default_poll_secs() -> u32 {
+    15
+}
+
+const fn default_visibility_timeout_secs() -> u32 {
+    300
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+fn default_client_concurrency() -> u32 {
+    cmp::max(1, num_cpus::get() as u32)
+}
+
+fn default_message_group_key() -> String {
+    "message_group_id".into()
+}
+
+inventory::submit! {
+    SourceDescription::new::<AwsSqsConfig>("aws_sqs")
+}
+
+impl GenerateConfig for AwsSqsConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"queue_url = "https://sqs.us-east-2.amazonaws.com/123456789012/MyQueue"
+            region = "us-east-2""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "aws_sqs")]
+impl SourceConfig for AwsSqsConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let client = self.create_client(&cx.proxy)?;
+        let receiver = Receiver {
+            client,
+            config: self.clone(),
+            acknowledgements: cx.acknowledgements,
+        };
+
+        Ok(Box::pin(receiver.run(cx.out, cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "aws_sqs"
+    }
+}
+
+impl AwsSqsConfig {
+    fn create_client(&self, proxy: &ProxyConfig) -> crate::Result<SqsClient> {
+        let region = (&self.region).try_into()?;
+        let client = rusoto::client(proxy)?;
+
+        let creds = self.auth.build(&region, None)?;
+
+        Ok(SqsClient::new_with(client, creds, region))
+    }
+}
+
+#[derive(Clone)]
+struct Receiver {
+    client: SqsClient,
+    config: AwsSqsConfig,
+    acknowledgements: bool,
+}
+
+impl Receiver {
+    async fn run(self, out: Pipeline, shutdown: ShutdownSignal) -> Result<(), ()> {
+        let mut handles = Vec::new();
+        for _ in 0..self.config.client_concurrency {
+            let receiver = self.clone();
+            let out = out.clone();
+            let shutdown = shutdown.clone();
+            let fut = async move { receiver.run_task(out, shutdown).await };
+            handles.push(tokio::spawn(fut.in_current_span()));
+        }
+
+        // Wait for all of the tasks to finish. If any one of them panics, we resume
+        // that panic here to properly shutdown Vector.
+        for handle in handles.drain(..) {
+            if let Err(error) = handle.await {
+                if error.is_panic() {
+                    panic::resume_unwind(error.into_panic());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn run_task(self, mut out: Pipeline, shutdown: ShutdownSignal) {
+        let shutdown = shutdown.fuse();
+        pin!(shutdown);
+
+        loop {
+            let messages = select! {
+                _ = &mut shutdown => break,
+                messages = self.receive_messages() => messages,
+            };
+            let messages = messages
+                .into_iter()
+                .filter(|message| message.receipt_handle.is_some())
+                .collect::<Vec<_>>();
+            if messages.is_empty() {
+                continue;
+            }
+
+            // Once received, a batch is always seen through so that its
+            // messages are either deleted or made visible again.
+            let (batch, receiver) = if self.acknowledgements {
+                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                (Some(batch), Some(receiver))
+            } else {
+                (None, None)
+            };
+            let events = messages
+                .iter()
+                .map(|message| {
+                    let log = self.create_log(message);
+                    match &batch {
+                        Some(batch) => Event::from(log.with_batch_notifier(batch)),
+                        None => Event::from(log),
+                    }
+                })
+                .collect::<Vec<_>>();
+            drop(batch);
+
+            if let Err(error) = out.send_all(&mut stream::iter(events).map(Ok)).await {
+                error!(message = "Error sending to sink.", %error);
+                self.change_visibility(&messages, 0).await;
+                break;
+            }
+
+            let status = match receiver {
+                Some(receiver) => self.wait_for_delivery(&messages, receiver).await,
+                None => BatchStatus::Delivered,
+            };
+            match status {
+                BatchStatus::Delivered => {
+                    if self.config.delete_message {
+                        self.delete_messages(&messages).await;
+                    }
+                }
+                // Make the messages visible again right away, so that they
+                // are retried before later messages of their group.
+                BatchStatus::Errored | BatchStatus::Failed => {
+                    self.change_visibility(&messages, 0).await
+                }
+            }
+        }
+    }
+
+    fn create_log(&self, message: &Message) -> LogEvent {
+        emit!(AwsSqsEventReceived {
+            byte_size: message.body.as_ref().map_or(0, String::len),
+        });
+
+        let mut log = LogEvent::default();
+        log.insert(
+            log_schema().message_key(),
+            Bytes::from(message.body.clone().unwrap_or_default()),
+        );
+
+        let attributes = message.attributes.as_ref();
+        let timestamp = attributes
+            .and_then(|attributes| attributes.get("SentTimestamp"))
+            .and_then(|millis| millis.parse().ok())
+            .and_then(|millis| Utc.timestamp_millis_opt(millis).latest())
+            .unwrap_or_else(Utc::now);
+        log.insert(log_schema().timestamp_key(), timestamp);
+        log.insert(log_schema().source_type_key(), Bytes::from("aws_sqs"));
+
+        if let Some(group) = attributes.and_then(|attributes| attributes.get("MessageGroupId")) {
+            log.insert(&self.config.message_group_key, group.clone());
+        }
+
+        log
+    }
+
+    /// Wait for the events of the batch to be delivered, extending the
+    /// visibility timeout of its messages at half of the timeout.
+    async fn wait_for_delivery(
+        &self,
+        messages: &[Message],
+        receiver: impl std::future::Future<Output = BatchStatus>,
+    ) -> BatchStatus {
+        pin!(receiver);
+        if !self.config.extend_visibility_timeout {
+            return receiver.await;
+        }
+
+        let period = Duration::from_secs(cmp::max(
+            1,
+            u64::from(self.config.visibility_timeout_secs) / 2,
+        ));
+        let mut interval = time::interval_at(time::Instant::now() + period, period);
+        loop {
+            select! {
+                status = &mut receiver => return status,
+                _ = interval.tick() => {
+                    self.change_visibility(messages, self.config.visibility_timeout_secs)
+                        .await
+                }
+            }
+        }
+    }
+
+    async fn receive_messages(&self) -> Vec<Message> {
+        self.client
+            .receive_message(ReceiveMessageRequest {
+                queue_url: self.config.queue_url.clone(),
+                attribute_names: Some(vec!["SentTimestamp".into(), "MessageGroupId".into()]),
+                max_number_of_messages: Some(10),
+                visibility_timeout: Some(i64::from(self.config.visibility_timeout_secs)),
+                wait_time_seconds: Some(i64::from(self.config.poll_secs)),
+                ..Default::default()
+            })
+            .await
+            .map(|response| response.messages.unwrap_or_default())
+            .unwrap_or_else(|error| {
+                emit!(AwsSqsMessageReceiveFailed { error: &error });
+                Vec::new()
+            })
+    }
+
+    async fn delete_messages(&self, messages: &[Message]) {
+        let entries = messages
+            .iter()
+            .enumerate()
+            .map(|(id, message)| DeleteMessageBatchRequestEntry {
+                id: id.to_string(),
+                receipt_handle: message.receipt_handle.clone().unwrap_or_default(),
+            })
+            .collect();
+        let result = self
+            .client
+            .delete_message_batch(DeleteMessageBatchRequest {
+                queue_url: self.config.queue_url.clone(),
+                entries,
+            })
+            .await;
+
+        match result {
+            Ok(result) => {
+                // Batch deletes can have partial successes/failures.
+                if !result.successful.is_empty() {
+                    emit!(AwsSqsMessageDeleteSucceeded {
+                        count: result.successful.len()
+                    });
+                }
+                if !result.failed.is_empty() {
+                    emit!(AwsSqsMessageDeletePartialFailure {
+                        entries: &result.failed
+                    });
+                }
+            }
+            Err(error) => emit!(AwsSqsMessageDeleteFailed {
+                count: messages.len(),
+                error: &error
+            }),
+        }
+    }
+
+    async fn change_visibility(&self, messages: &[Message], timeout_secs: u32) {
+        let entries = messages
+            .iter()
+            .enumerate()
+            .map(|(id, message)| ChangeMessageVisibilityBatchRequestEntry {
+                id: id.to_string(),
+                receipt_handle: message.receipt_handle.clone().unwrap_or_default(),
+                visibility_timeout: Some(i64::from(timeout_secs)),
+            })
+            .collect();
+        let result = self
+            .client
+            .change_message_visibility_batch(ChangeMessageVisibilityBatchRequest {
+                queue_url: self.config.queue_url.clone(),
+                entries,
+            })
+            .await;
+
+        match result {
+            Ok(result) if !result.failed.is_empty() => {
+                let error = format!("{} entries failed", result.failed.len());
+                emit!(AwsSqsMessageVisibilityChangeFailed {
+                    count: result.failed.len(),
+                    error: &std::io::Error::new(std::io::ErrorKind::Other, error),
+                });
+            }
+            Ok(_) => {}
+            Err(error) => emit!(AwsSqsMessageVisibilityChangeFailed {
+                count: messages.len(),
+                error: &error
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AwsSqsConfig>();
+    }
+}
+
+#[cfg(feature = "aws-sqs-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::{event::EventStatus, test_util::random_string};
+    use rusoto_core::Region;
+    use rusoto_sqs::{CreateQueueRequest, SendMessageRequest};
+    use std::collections::HashMap;
+
+    async fn create_queue(client: &SqsClient, fifo: bool) -> String {
+        let mut attributes = HashMap::new();
+        let mut queue_name = format!("test-{}", random_string(10).to_lowercase());
+        if fifo {
+            queue_name.push_str(".fifo");
+            attributes.insert("FifoQueue".to_owned(), "true".to_owned());
+            attributes.insert("ContentBasedDeduplication".to_owned(), "true".to_owned());
+        }
+        client
+            .create_queue(CreateQueueRequest {
+                queue_name,
+                attributes: Some(attributes),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .queue_url
+            .unwrap()
+    }
+
+    async fn receive(fifo: bool, status: EventStatus) -> (Vec<Event>, SqsClient, String) {
+        let client = SqsClient::new(Region::Custom {
+            name: "localstack".into(),
+            endpoint: "http://localhost:4566".into(),
+        });
+        let queue_url = create_queue(&client, fifo).await;
+        for i in 0..3 {
+            client
+                .send_message(SendMessageRequest {
+                    queue_url: queue_url.clone(),
+                    message_body: format!("message {}", i),
+                    message_group_id: if fifo { Some("group".into()) } else { None },
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let config = AwsSqsConfig {
+            region: RegionOrEndpoint::with_endpoint("http://localhost:4566".into()),
+            queue_url: queue_url.clone(),
+            poll_secs: 1,
+            client_concurrency: 1,
+            ..Default::default()
+        };
+        let (tx, rx) = Pipeline::new_test_finalize(status);
+        let mut cx = SourceContext::new_test(tx);
+        cx.acknowledgements = true;
+        tokio::spawn(config.build(cx).await.unwrap());
+
+        let events = rx.take(3).collect::<Vec<_>>().await;
+        (events, client, queue_url)
+    }
+
+    async fn remaining(client: &SqsClient, queue_url: String) -> usize {
+        // Wait for the source to settle the received messages.
+        time::sleep(Duration::from_secs(2)).await;
+        client
+            .receive_message(ReceiveMessageRequest {
+                queue_url,
+                max_number_of_messages: Some(10),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .messages
+            .unwrap_or_default()
+            .len()
+    }
+
+    #[tokio::test]
+    async fn deletes_delivered_fifo_messages_in_order() {
+        let (events, client, queue_url) = receive(true, EventStatus::Delivered).await;
+
+        let messages = events
+            .iter()
+            .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["message 0", "message 1", "message 2"]);
+        assert_eq!(events[0].as_log()["message_group_id"], "group".into());
+        assert_eq!(remaining(&client, queue_url).await, 0);
+    }
+
+    #[tokio::test]
+    async fn keeps_failed_messages() {
+        let (_, client, queue_url) = receive(false, EventStatus::Failed).await;
+
+        assert!(remaining(&client, queue_url).await > 0);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    azure_event_hubs::ConnectionString,
+    config::{DataType, SourceConfig, SourceContext, SourceDescription},
+    sources::kafka::KafkaSourceConfig,
+};
+use serde::{Deserialize, Serialize};
+
+/// Consumes event hubs through the Kafka endpoint of their namespace.
+///
+/// The consumer group commits its offsets through the same endpoint, so
+/// Event Hubs itself stores the checkpoints and no storage account is
+/// needed. With acknowledgements enabled, an offset is only committed once
+/// the events up to it are delivered.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AzureEventHubsSourceConfig {
+    connection_string: String,
+    /// Defaults to the `EntityPath` of the connection string.
+    #[serde(default)]
+    event_hubs: Vec<String>,
+    #[serde(default = "default_consumer_group")]
+    consumer_group: String,
+}
+
+fn default_consumer_group() -> String {
+    "$Default".into()
+}
+
+inventory::submit! {
+    SourceDescription::new::<AzureEventHubsSourceConfig>("azure_event_hubs")
+}
+
+impl_generate_config_from_default!(AzureEventHubsSourceConfig);
+
+impl Default for AzureEventHubsSourceConfig {
+    fn default() -> Self {
+        Self {
+            connection_string: "Endpoint=sb://mynamespace.servicebus.windows.net/;SharedAccessKeyName=RootManageSharedAccessKey;SharedAccessKey=<key>".into(),
+            event_hubs: vec!["logs".into()],
+            consumer_group: default_consumer_group(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "azure_event_hubs")]
+impl SourceConfig for AzureEventHubsSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        self.kafka_config()?
+            .build_source(cx, "azure_event_hubs")
+            .await
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "azure_event_hubs"
+    }
+}
+
+impl AzureEventHubsSourceConfig {
+    fn kafka_config(&self) -> crate::Result<KafkaSourceConfig> {
+        let connection_string = ConnectionString::parse(&self.connection_string)?;
+        let event_hubs = if self.event_hubs.is_empty() {
+            vec![connection_string.event_hub(None)?]
+        } else {
+            self.event_hubs.clone()
+        };
+
+        Ok(KafkaSourceConfig::new(
+            connection_string.bootstrap_servers(),
+            event_hubs,
+            self.consumer_group.clone(),
+            connection_string.auth(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<AzureEventHubsSourceConfig>();
+    }
+
+    #[test]
+    fn consumes_entity_path() {
+        let config: AzureEventHubsSourceConfig = toml::from_str(
+            r#"
+            connection_string = "Endpoint=sb://vector.servicebus.windows.net/;SharedAccessKeyName=listen;SharedAccessKey=c2VjcmV0;EntityPath=logs"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.consumer_group, "$Default");
+        assert!(config.kafka_config().is_ok());
+    }
+}
@@ -1,13 +1,18 @@
-use super::util::framestream::{build_framestream_unix_source, FrameHandler};
+use super::util::{
+    framestream::{build_framestream_tcp_source, build_framestream_unix_source, FrameHandler},
+    SocketListenAddr,
+};
 use crate::{
-    config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
+    config::{log_schema, DataType, Resource, SourceConfig, SourceContext, SourceDescription},
     event::Event,
     internal_events::{DnstapEventReceived, DnstapParseDataError},
+    tcp::TcpKeepaliveConfig,
+    tls::{MaybeTlsSettings, TlsConfig},
     Result,
 };
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
 pub mod parser;
 pub use parser::parse_dnstap_data;
@@ -24,13 +29,34 @@ pub struct DnstapConfig {
     #[serde(default = "default_max_frame_length")]
     pub max_frame_length: usize,
     pub host_key: Option<String>,
-    pub socket_path: PathBuf,
+    #[serde(default)]
+    pub mode: Mode,
+    pub socket_path: Option<PathBuf>,
+    pub address: Option<SocketListenAddr>,
+    pub keepalive: Option<TcpKeepaliveConfig>,
+    pub tls: Option<TlsConfig>,
     pub raw_data_only: Option<bool>,
     pub multithreaded: Option<bool>,
     pub max_frame_handling_tasks: Option<u32>,
     pub socket_file_mode: Option<u32>,
     pub socket_receive_buffer_size: Option<usize>,
     pub socket_send_buffer_size: Option<usize>,
+    /// Only the messages of these types are turned into events, unless empty.
+    #[serde(default)]
+    pub message_types: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Unix,
+    Tcp,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Self::Unix
+    }
 }
 
 fn default_max_frame_length() -> usize {
@@ -41,7 +67,7 @@ impl DnstapConfig {
     pub fn new(socket_path: PathBuf) -> Self {
         Self {
             host_key: None,
-            socket_path,
+            socket_path: Some(socket_path),
             ..Self::default()
         }
     }
@@ -49,6 +75,21 @@ impl DnstapConfig {
     fn content_type(&self) -> String {
         "protobuf:dnstap.Dnstap".to_string() //content-type for framestream
     }
+
+    fn message_type_ids(&self) -> Result<Option<HashSet<i32>>> {
+        if self.message_types.is_empty() {
+            return Ok(None);
+        }
+        self.message_types
+            .iter()
+            .map(|message_type| {
+                parser::to_dnstap_message_type_id(message_type).ok_or_else(|| {
+                    format!("Unknown dnstap message type {:?}.", message_type).into()
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
 }
 
 impl Default for DnstapConfig {
@@ -56,13 +97,18 @@ impl Default for DnstapConfig {
         Self {
             host_key: Some("host".to_string()),
             max_frame_length: default_max_frame_length(),
-            socket_path: PathBuf::from("/run/bind/dnstap.sock"),
+            mode: Mode::Unix,
+            socket_path: Some(PathBuf::from("/run/bind/dnstap.sock")),
+            address: None,
+            keepalive: None,
+            tls: None,
             raw_data_only: None,
             multithreaded: None,
             max_frame_handling_tasks: None,
             socket_file_mode: None,
             socket_receive_buffer_size: None,
             socket_send_buffer_size: None,
+            message_types: Vec::new(),
         }
     }
 }
@@ -84,18 +130,44 @@ impl SourceConfig for DnstapConfig {
 
         let frame_handler = DnstapFrameHandler::new(
             self.max_frame_length,
-            self.socket_path.clone(),
             self.content_type(),
             self.raw_data_only.unwrap_or(false),
             self.multithreaded.unwrap_or(false),
             self.max_frame_handling_tasks.unwrap_or(1000),
-            self.socket_file_mode,
-            self.socket_receive_buffer_size,
-            self.socket_send_buffer_size,
+            self.message_type_ids()?,
             host_key,
             log_schema().timestamp_key(),
         );
-        build_framestream_unix_source(frame_handler, cx.shutdown, cx.out)
+        match self.mode {
+            Mode::Unix => {
+                let socket_path = self
+                    .socket_path
+                    .clone()
+                    .ok_or("`socket_path` is required in `unix` mode.")?;
+                build_framestream_unix_source(
+                    frame_handler,
+                    socket_path,
+                    self.socket_file_mode,
+                    self.socket_receive_buffer_size,
+                    self.socket_send_buffer_size,
+                    cx.shutdown,
+                    cx.out,
+                )
+            }
+            Mode::Tcp => {
+                let address = self.address.ok_or("`address` is required in `tcp` mode.")?;
+                let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
+                build_framestream_tcp_source(
+                    frame_handler,
+                    address,
+                    self.keepalive,
+                    tls,
+                    self.socket_receive_buffer_size,
+                    cx.shutdown,
+                    cx.out,
+                )
+            }
+        }
     }
 
     fn output_type(&self) -> DataType {
@@ -105,20 +177,24 @@ impl SourceConfig for DnstapConfig {
     fn source_type(&self) -> &'static str {
         "dnstap"
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        match (self.mode, self.address) {
+            (Mode::Tcp, Some(address)) => vec![address.into()],
+            _ => vec![],
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct DnstapFrameHandler {
     max_frame_length: usize,
-    socket_path: PathBuf,
     content_type: String,
     schema: DnstapEventSchema,
     raw_data_only: bool,
     multithreaded: bool,
     max_frame_handling_tasks: u32,
-    socket_file_mode: Option<u32>,
-    socket_receive_buffer_size: Option<usize>,
-    socket_send_buffer_size: Option<usize>,
+    message_type_ids: Option<Arc<HashSet<i32>>>,
     host_key: String,
     timestamp_key: String,
 }
@@ -126,14 +202,11 @@ pub struct DnstapFrameHandler {
 impl DnstapFrameHandler {
     pub fn new(
         max_frame_length: usize,
-        socket_path: PathBuf,
         content_type: String,
         raw_data_only: bool,
         multithreaded: bool,
         max_frame_handling_tasks: u32,
-        socket_file_mode: Option<u32>,
-        socket_receive_buffer_size: Option<usize>,
-        socket_send_buffer_size: Option<usize>,
+        message_type_ids: Option<HashSet<i32>>,
         host_key: String,
         timestamp_key: &'static str,
     ) -> Self {
@@ -144,19 +217,31 @@ impl DnstapFrameHandler {
 
         Self {
             max_frame_length,
-            socket_path,
             content_type,
             schema,
             raw_data_only,
             multithreaded,
             max_frame_handling_tasks,
-            socket_file_mode,
-            socket_receive_buffer_size,
-            socket_send_buffer_size,
+            message_type_ids: message_type_ids.map(Arc::new),
             host_key,
             timestamp_key: timestamp_key.to_string(),
         }
     }
+
+    /// Frames that fail to decode are kept, so the error is reported when
+    /// they're parsed.
+    fn is_filtered_out(&self, frame: &Bytes) -> bool {
+        match (
+            &self.message_type_ids,
+            parser::dnstap_message_type_id(frame),
+        ) {
+            (Some(message_type_ids), Ok(message_type_id)) => !message_type_id
+                .map_or(false, |message_type_id| {
+                    message_type_ids.contains(&message_type_id)
+                }),
+            _ => false,
+        }
+    }
 }
 
 impl FrameHandler for DnstapFrameHandler {
@@ -173,6 +258,10 @@ impl FrameHandler for DnstapFrameHandler {
      * Takes a data frame from the unix socket and turns it into a Vector Event.
      **/
     fn handle_event(&self, received_from: Option<Bytes>, frame: Bytes) -> Option<Event> {
+        if self.is_filtered_out(&frame) {
+            return None;
+        }
+
         let mut event = Event::new_empty_log();
 
         let log_event = event.as_mut_log();
@@ -209,10 +298,6 @@ impl FrameHandler for DnstapFrameHandler {
             }
         }
     }
-    fn socket_path(&self) -> PathBuf {
-        self.socket_path.clone()
-    }
-
     fn multithreaded(&self) -> bool {
         self.multithreaded
     }
@@ -221,18 +306,6 @@ impl FrameHandler for DnstapFrameHandler {
         self.max_frame_handling_tasks
     }
 
-    fn socket_file_mode(&self) -> Option<u32> {
-        self.socket_file_mode
-    }
-
-    fn socket_receive_buffer_size(&self) -> Option<usize> {
-        self.socket_receive_buffer_size
-    }
-
-    fn socket_send_buffer_size(&self) -> Option<usize> {
-        self.socket_send_buffer_size
-    }
-
     fn host_key(&self) -> String {
         self.host_key.clone()
     }
@@ -262,13 +335,18 @@ mod integration_tests {
             DnstapConfig {
                 max_frame_length: 102400,
                 host_key: Some("key".to_string()),
-                socket_path: socket,
+                mode: Mode::Unix,
+                socket_path: Some(socket),
+                address: None,
+                keepalive: None,
+                tls: None,
                 raw_data_only: Some(raw_data),
                 multithreaded: Some(false),
                 max_frame_handling_tasks: Some(100000),
                 socket_file_mode: Some(511),
                 socket_receive_buffer_size: Some(10485760),
                 socket_send_buffer_size: Some(10485760),
+                message_types: Vec::new(),
             }
             .build(SourceContext::new_test(sender))
             .await
@@ -344,12 +422,12 @@ mod integration_tests {
                 events
                     .iter()
                     .any(|v| v.as_log().get("messageType")
-                        == Some(&Value::Bytes("ClientQuery".into()))),
+                        == Some(&Value::String("ClientQuery".into()))),
                 "No ClientQuery event!"
             );
             assert!(
                 events.iter().any(|v| v.as_log().get("messageType")
-                    == Some(&Value::Bytes("ClientResponse".into()))),
+                    == Some(&Value::String("ClientResponse".into()))),
                 "No ClientResponse event!"
             );
         } else if query_event == "update" {
@@ -358,26 +436,24 @@ mod integration_tests {
                 events
                     .iter()
                     .any(|v| v.as_log().get("messageType")
-                        == Some(&Value::Bytes("UpdateQuery".into()))),
+                        == Some(&Value::String("UpdateQuery".into()))),
                 "No UpdateQuery event!"
             );
             assert!(
                 events.iter().any(|v| v.as_log().get("messageType")
-                    == Some(&Value::Bytes("UpdateResponse".into()))),
+                    == Some(&Value::String("UpdateResponse".into()))),
                 "No UpdateResponse event!"
             );
             assert!(
                 events
                     .iter()
                     .any(|v| v.as_log().get("messageType")
-                        == Some(&Value::Bytes("AuthQuery".into()))),
+                        == Some(&Value::String("AuthQuery".into()))),
                 "No UpdateQuery event!"
             );
             assert!(
-                events
-                    .iter()
-                    .any(|v| v.as_log().get("messageType")
-                        == Some(&Value::Bytes("AuthResponse".into()))),
+                events.iter().any(|v| v.as_log().get("messageType")
+                    == Some(&Value::String("AuthResponse".into()))),
                 "No UpdateResponse event!"
             );
         }
@@ -34,6 +34,7 @@ use super::dns_message_parser::DnsMessageParser;
 use super::schema::DnstapEventSchema;
 
 const MAX_DNSTAP_QUERY_MESSAGE_TYPE_ID: i32 = 12;
+const MAX_DNSTAP_MESSAGE_TYPE_ID: i32 = 14;
 
 #[derive(Debug, Snafu)]
 enum DnstapParserError {
@@ -80,6 +81,12 @@ pub fn parse_dnstap_data(
     DnstapParser::new(event_schema, log_event).parse_dnstap_data(frame)
 }
 
+/// The type of the message in a dnstap frame, if the frame holds a message.
+pub fn dnstap_message_type_id(frame: &Bytes) -> Result<Option<i32>> {
+    let proto_msg = Dnstap::decode(frame.clone())?;
+    Ok(proto_msg.message.map(|message| message.r#type))
+}
+
 impl<'a> DnstapParser<'a> {
     pub fn new(event_schema: &'a DnstapEventSchema, log_event: &'a mut LogEvent) -> Self {
         Self {
@@ -975,6 +982,11 @@ fn to_dnstap_message_type(type_id: i32) -> String {
     }
 }
 
+pub fn to_dnstap_message_type_id(message_type: &str) -> Option<i32> {
+    (1..=MAX_DNSTAP_MESSAGE_TYPE_ID)
+        .find(|type_id| to_dnstap_message_type(*type_id) == message_type)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{super::schema::DnstapEventSchema, *};
@@ -1021,7 +1033,7 @@ mod tests {
         assert!(log_event.all_fields().any(|(key, value)| key
             == "requestData.question[0].domainName"
             && match value {
-                Value::Bytes(domain_name) => *domain_name == Bytes::from_static(b"facebook1.com."),
+                Value::String(domain_name) => *domain_name == Bytes::from_static(b"facebook1.com."),
                 _ => false,
             }));
     }
@@ -1060,14 +1072,14 @@ mod tests {
             .all_fields()
             .any(|(key, value)| key == "messageType"
                 && match value {
-                    Value::Bytes(data_type) => *data_type == Bytes::from_static(b"UpdateResponse"),
+                    Value::String(data_type) => *data_type == Bytes::from_static(b"UpdateResponse"),
                     _ => false,
                 }));
         assert!(log_event
             .all_fields()
             .any(|(key, value)| key == "requestData.zone.zName"
                 && match value {
-                    Value::Bytes(domain_name) =>
+                    Value::String(domain_name) =>
                         *domain_name == Bytes::from_static(b"example.com."),
                     _ => false,
                 }));
@@ -1085,6 +1097,13 @@ mod tests {
         assert!(e.to_string().contains("Protobuf message"));
     }
 
+    #[test]
+    fn test_to_dnstap_message_type_id() {
+        assert_eq!(Some(5), to_dnstap_message_type_id("ClientQuery"));
+        assert_eq!(Some(14), to_dnstap_message_type_id("UpdateResponse"));
+        assert_eq!(None, to_dnstap_message_type_id("clientquery"));
+    }
+
     #[test]
     fn test_get_socket_family_name() {
         assert_eq!("INET", to_socket_family_name(1).unwrap());
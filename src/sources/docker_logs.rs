@@ -5,9 +5,10 @@ use crate::{
     event::merge_state::LogEventMergeState,
     event::{self, Event, LogEvent, PathComponent, PathIter, Value},
     internal_events::{
-        DockerLogsCommunicationError, DockerLogsContainerEventReceived,
-        DockerLogsContainerMetadataFetchFailed, DockerLogsContainerUnwatch,
-        DockerLogsContainerWatch, DockerLogsEventReceived, DockerLogsLoggingDriverUnsupported,
+        DockerLogsCheckpointWriteFailed, DockerLogsCommunicationError,
+        DockerLogsContainerEventReceived, DockerLogsContainerMetadataFetchFailed,
+        DockerLogsContainerUnwatch, DockerLogsContainerWatch, DockerLogsEventReceived,
+        DockerLogsEventStreamEnded, DockerLogsLoggingDriverUnsupported,
         DockerLogsTimestampParseFailed,
     },
     line_agg::{self, LineAgg},
@@ -22,14 +23,17 @@ use bollard::{
     Docker,
 };
 use bytes::{Buf, Bytes};
-use chrono::{DateTime, FixedOffset, Local, ParseError, Utc};
-use futures::{Stream, StreamExt};
+use chrono::{DateTime, FixedOffset, Local, ParseError, TimeZone, Utc};
+use futures::{SinkExt, Stream, StreamExt};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
     future::ready,
+    io,
+    path::PathBuf,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
     {collections::HashMap, convert::TryFrom},
 };
@@ -41,6 +45,8 @@ const CREATED_AT: &str = "container_created_at";
 const NAME: &str = "container_name";
 const STREAM: &str = "stream";
 const CONTAINER: &str = "container_id";
+const CONTAINER_EVENT: &str = "container_event";
+const EXIT_CODE: &str = "exit_code";
 // Prevent short hostname from being wrongly regconized as a container's short ID.
 const MIN_HOSTNAME_LENGTH: usize = 6;
 
@@ -65,6 +71,13 @@ pub struct DockerLogsConfig {
     auto_partial_merge: bool,
     multiline: Option<MultilineConfig>,
     retry_backoff_secs: u64,
+    /// Logs of containers without a checkpoint are read from this moment on,
+    /// instead of from when the source starts.
+    since: Option<DateTime<Utc>>,
+    persist_checkpoints: bool,
+    data_dir: Option<PathBuf>,
+    /// Whether to send an event for each start, stop, etc. of the containers.
+    container_events: bool,
 }
 
 impl Default for DockerLogsConfig {
@@ -81,6 +94,10 @@ impl Default for DockerLogsConfig {
             auto_partial_merge: true,
             multiline: None,
             retry_backoff_secs: 2,
+            since: None,
+            persist_checkpoints: false,
+            data_dir: None,
+            container_events: false,
         }
     }
 }
@@ -135,16 +152,27 @@ impl_generate_config_from_default!(DockerLogsConfig);
 #[typetag::serde(name = "docker_logs")]
 impl SourceConfig for DockerLogsConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
-        let source = DockerLogsSource::new(
+        let checkpoint_path = if self.persist_checkpoints {
+            let data_dir = cx
+                .globals
+                .resolve_and_make_data_subdir(self.data_dir.as_ref(), &cx.id)?;
+            Some(data_dir.join("checkpoints.json"))
+        } else {
+            None
+        };
+
+        let mut source = DockerLogsSource::new(
             self.clone().with_empty_partial_event_marker_field_as_none(),
+            checkpoint_path,
             cx.out,
             cx.shutdown.clone(),
         )?;
+        let checkpointer = Arc::clone(&source.esb.core.checkpointer);
 
         // Capture currently running containers, and do main future(run)
         let fut = async move {
             match source.handle_running_containers().await {
-                Ok(source) => source.run().await,
+                Ok(()) => source.run().await,
                 Err(error) => {
                     error!(
                         message = "Listing currently running containers failed.",
@@ -154,13 +182,25 @@ impl SourceConfig for DockerLogsConfig {
             }
         };
 
+        let flush_checkpointer = Arc::clone(&checkpointer);
+        let flush = async move {
+            let mut interval = tokio::time::interval(CHECKPOINT_FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                flush_checkpointer.flush().await;
+            }
+        };
+
         let shutdown = cx.shutdown;
         // Once this ShutdownSignal resolves it will drop DockerLogsSource and by extension it's ShutdownSignal.
         Ok(Box::pin(async move {
-            Ok(tokio::select! {
+            tokio::select! {
                 _ = fut => {}
+                _ = flush => {}
                 _ = shutdown => {}
-            })
+            }
+            checkpointer.flush().await;
+            Ok(())
         }))
     }
 
@@ -201,12 +241,16 @@ struct DockerLogsSourceCore {
     config: DockerLogsConfig,
     line_agg_config: Option<line_agg::Config>,
     docker: Docker,
-    /// Only logs created at, or after this moment are logged.
+    /// Only log events created at, or after this moment are logged.
     now_timestamp: DateTime<Utc>,
+    /// Only logs created at, or after this moment are logged, unless
+    /// the container has a checkpoint.
+    since: DateTime<Utc>,
+    checkpointer: Arc<Checkpointer>,
 }
 
 impl DockerLogsSourceCore {
-    fn new(config: DockerLogsConfig) -> crate::Result<Self> {
+    fn new(config: DockerLogsConfig, checkpoint_path: Option<PathBuf>) -> crate::Result<Self> {
         // ?NOTE: Constructs a new Docker instance for a docker host listening at url specified by an env var DOCKER_HOST.
         // ?      Otherwise connects to unix socket which requires sudo privileges, or docker group membership.
         let docker = docker(config.docker_host.clone(), config.tls.clone())?;
@@ -224,17 +268,22 @@ impl DockerLogsSourceCore {
             None
         };
 
+        let checkpointer = Arc::new(Checkpointer::load(checkpoint_path)?);
+
         Ok(DockerLogsSourceCore {
+            since: config.since.unwrap_or_else(|| now.into()),
             config,
             line_agg_config,
             docker,
             now_timestamp: now.into(),
+            checkpointer,
         })
     }
 
-    /// Returns event stream coming from docker.
+    /// Returns event stream coming from docker, starting at `since`.
     fn docker_logs_event_stream(
         &self,
+        since: DateTime<Utc>,
     ) -> impl Stream<Item = Result<SystemEventsResponse, DockerError>> + Send {
         let mut filters = HashMap::new();
 
@@ -244,14 +293,14 @@ impl DockerLogsSourceCore {
         // unpause | docker unpause
         // die    | docker restart, docker stop, docker kill, process exited, oom
         // pause  | docker pause
+        // destroy | docker rm
+        let mut actions = vec!["start", "unpause", "die", "pause", "destroy"];
+        if self.config.container_events {
+            actions.extend(&["create", "restart", "kill", "oom", "stop"]);
+        }
         filters.insert(
             "event".to_owned(),
-            vec![
-                "start".to_owned(),
-                "unpause".to_owned(),
-                "die".to_owned(),
-                "pause".to_owned(),
-            ],
+            actions.into_iter().map(Into::into).collect(),
         );
         filters.insert("type".to_owned(), vec!["container".to_owned()]);
 
@@ -265,7 +314,7 @@ impl DockerLogsSourceCore {
         }
 
         self.docker.events(Some(EventsOptions {
-            since: Some(self.now_timestamp),
+            since: Some(since),
             until: None,
             filters,
         }))
@@ -294,11 +343,15 @@ struct DockerLogsSource {
     /// It may contain shortened container id.
     hostname: Option<String>,
     backoff_duration: Duration,
+    /// Time of the last event received from docker, in seconds and nanoseconds,
+    /// from which the event stream is resumed once reconnected.
+    last_event_time: (DateTime<Utc>, Option<i64>),
 }
 
 impl DockerLogsSource {
     fn new(
         config: DockerLogsConfig,
+        checkpoint_path: Option<PathBuf>,
         out: Pipeline,
         shutdown: ShutdownSignal,
     ) -> crate::Result<DockerLogsSource> {
@@ -308,10 +361,11 @@ impl DockerLogsSource {
         let hostname = crate::get_hostname().ok();
 
         // Only logs created at, or after this moment are logged.
-        let core = DockerLogsSourceCore::new(config)?;
+        let core = DockerLogsSourceCore::new(config, checkpoint_path)?;
 
         // main event stream, with whom only newly started/restarted containers will be logged.
-        let events = core.docker_logs_event_stream();
+        let events = core.docker_logs_event_stream(core.now_timestamp);
+        let last_event_time = (core.now_timestamp, None);
         info!(message = "Listening to docker log events.");
 
         // Channel of communication between main future and event_stream futures
@@ -342,11 +396,14 @@ impl DockerLogsSource {
             main_recv,
             hostname,
             backoff_duration: Duration::from_secs(backoff_secs),
+            last_event_time,
         })
     }
 
     /// Future that captures currently running containers, and starts event streams for them.
-    async fn handle_running_containers(mut self) -> crate::Result<Self> {
+    /// Containers that are known but no longer running are marked as stopped, so this can
+    /// also catch up with the events missed while disconnected from docker.
+    async fn handle_running_containers(&mut self) -> crate::Result<()> {
         let mut filters = HashMap::new();
 
         // Apply include filters
@@ -358,6 +415,7 @@ impl DockerLogsSource {
             filters.insert("ancestor".to_owned(), include_images.clone());
         }
 
+        let mut running = HashSet::new();
         self.esb
             .core
             .docker
@@ -396,10 +454,62 @@ impl DockerLogsSource {
                 }
 
                 let id = ContainerId::new(id);
-                self.containers.insert(id.clone(), self.esb.start(id, None));
+                running.insert(id.clone());
+                match self.containers.get_mut(&id) {
+                    Some(state) => {
+                        if !state.is_running() {
+                            state.running();
+                            self.esb.restart(state);
+                        }
+                    }
+                    None => {
+                        let state = self.esb.start(id.clone(), None);
+                        self.containers.insert(id, state);
+                    }
+                }
             });
 
-        Ok(self)
+        for (id, state) in self.containers.iter_mut() {
+            if !running.contains(id) {
+                state.stopped();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reconnects to the docker event stream after it ended, from the last event
+    /// received, and catches up with the containers started or stopped meanwhile.
+    async fn reconnect(&mut self) {
+        tokio::time::sleep(self.backoff_duration).await;
+
+        self.events = Box::pin(
+            self.esb
+                .core
+                .docker_logs_event_stream(self.last_event_time.0),
+        );
+        if let Err(error) = self.handle_running_containers().await {
+            error!(
+                message = "Listing currently running containers failed.",
+                %error
+            );
+        }
+    }
+
+    /// False if the event was received before, as the event stream is
+    /// resumed from the second of the last event after reconnecting.
+    fn is_new_event(&mut self, event: &SystemEventsResponse) -> bool {
+        let time = event.time.map(|time| Utc.timestamp(time, 0));
+        match (event.time_nano, self.last_event_time.1) {
+            (Some(time_nano), Some(last_time_nano)) if time_nano <= last_time_nano => false,
+            _ => {
+                self.last_event_time = (
+                    time.unwrap_or(self.last_event_time.0),
+                    event.time_nano.or(self.last_event_time.1),
+                );
+                true
+            }
+        }
     }
 
     async fn run(mut self) {
@@ -444,6 +554,10 @@ impl DockerLogsSource {
                 value = self.events.next() => {
                     match value {
                         Some(Ok(mut event)) => {
+                            if !self.is_new_event(&event) {
+                                continue;
+                            }
+
                             let action = event.action.unwrap();
                             let actor = event.actor.take().unwrap();
                             let id = actor.id.unwrap();
@@ -453,6 +567,20 @@ impl DockerLogsSource {
 
                             let id = ContainerId::new(id);
 
+                            if self.esb.core.config.container_events
+                                && self.esb.core.config.container_name_or_id_included(
+                                    id.as_str(),
+                                    attributes.get("name").map(|s| s.as_str()),
+                                )
+                                && !self.exclude_self(id.as_str())
+                            {
+                                let event = self.container_event(&id, &action, &attributes, event.time);
+                                if self.esb.out.send(event).await.is_err() {
+                                    info!(message = "Shutting down docker_logs source.");
+                                    return;
+                                }
+                            }
+
                             // Update container status
                             match action.as_str() {
                                 "die" | "pause" => {
@@ -460,6 +588,12 @@ impl DockerLogsSource {
                                         state.stopped();
                                     }
                                 }
+                                "destroy" => {
+                                    self.esb.core.checkpointer.remove(&id);
+                                    if self.containers.get(&id).map_or(false, |state| state.has_ended_stream()) {
+                                        self.containers.remove(&id);
+                                    }
+                                }
                                 "start" | "unpause" => {
                                     if let Some(state) = self.containers.get_mut(&id) {
                                         state.running();
@@ -483,10 +617,10 @@ impl DockerLogsSource {
                         }
                         Some(Err(error)) => emit!(DockerLogsCommunicationError{error,container_id:None}),
                         None => {
-                            // TODO: this could be fixed, but should be tried with some timeoff and exponential backoff
-                            error!(message = "Docker log event stream has ended unexpectedly.");
-                            info!(message = "Shutting down docker_logs source.");
-                            return;
+                            emit!(DockerLogsEventStreamEnded {
+                                backoff_secs: self.backoff_duration.as_secs()
+                            });
+                            self.reconnect().await;
                         }
                     };
                 }
@@ -500,6 +634,39 @@ impl DockerLogsSource {
             .map(|hostname| id.starts_with(hostname) && hostname.len() >= MIN_HOSTNAME_LENGTH)
             .unwrap_or(false)
     }
+
+    fn container_event(
+        &self,
+        id: &ContainerId,
+        action: &str,
+        attributes: &HashMap<String, String>,
+        time: Option<i64>,
+    ) -> Event {
+        let mut log_event = LogEvent::default();
+        log_event.insert(log_schema().source_type_key(), Bytes::from("docker"));
+        log_event.insert(log_schema().message_key(), action.to_owned());
+        log_event.insert(CONTAINER_EVENT, action.to_owned());
+        log_event.insert(CONTAINER, id.0.clone());
+        if let Some(name) = attributes.get("name") {
+            log_event.insert(NAME, name.clone());
+        }
+        if let Some(image) = attributes.get("image") {
+            log_event.insert(IMAGE, image.clone());
+        }
+        if let Some(exit_code) = attributes
+            .get("exitCode")
+            .and_then(|exit_code| exit_code.parse::<i64>().ok())
+        {
+            log_event.insert(EXIT_CODE, exit_code);
+        }
+        log_event.insert(
+            log_schema().timestamp_key(),
+            time.map(|time| Utc.timestamp(time, 0))
+                .unwrap_or_else(Utc::now),
+        );
+
+        add_hostname(Event::Log(log_event), &self.esb.host_key, &self.hostname)
+    }
 }
 
 /// Used to construct and start event stream futures
@@ -532,7 +699,12 @@ impl EventStreamBuilder {
             {
                 Ok(details) => match ContainerMetadata::from_details(details) {
                     Ok(metadata) => {
-                        let info = ContainerLogInfo::new(id, metadata, this.core.now_timestamp);
+                        let mut info = ContainerLogInfo::new(id, metadata, this.core.since);
+                        // Resume after the last log read before a restart of the stream
+                        // or of the source, rather than reading its backlog again.
+                        if let Some(timestamp) = this.core.checkpointer.get(&info.id) {
+                            info.resume_after(timestamp);
+                        }
                         this.run_event_stream(info).await;
                         return;
                     }
@@ -586,12 +758,18 @@ impl EventStreamBuilder {
         let events_stream = stream
             .map(|value| {
                 match value {
-                    Ok(message) => Ok(info.new_event(
-                        message,
-                        core.config.partial_event_marker_field.clone(),
-                        core.config.auto_partial_merge,
-                        &mut partial_event_merge_state,
-                    )),
+                    Ok(message) => {
+                        let event = info.new_event(
+                            message,
+                            core.config.partial_event_marker_field.clone(),
+                            core.config.auto_partial_merge,
+                            &mut partial_event_merge_state,
+                        );
+                        if let Some((timestamp, _)) = info.last_log {
+                            core.checkpointer.set(&info.id, timestamp);
+                        }
+                        Ok(event)
+                    }
                     Err(error) => {
                         // On any error, restart connection
                         match &error {
@@ -723,6 +901,11 @@ impl ContainerState {
         self.running
     }
 
+    /// True if the event_stream of this container has ended.
+    fn has_ended_stream(&self) -> bool {
+        self.info.is_some()
+    }
+
     /// True if it needs to be restarted.
     #[must_use]
     fn return_info(&mut self, info: ContainerLogInfo) -> bool {
@@ -769,6 +952,13 @@ impl ContainerLogInfo {
         }
     }
 
+    /// Only logs after the checkpointed one are logged.
+    fn resume_after(&mut self, timestamp: DateTime<FixedOffset>) {
+        // No stream has this generation, so logs with this exact timestamp are
+        // considered already processed.
+        self.last_log = Some((timestamp, CHECKPOINT_GENERATION));
+    }
+
     /// Only logs after or equal to this point need to be fetched
     fn log_since(&self) -> i64 {
         self.last_log
@@ -963,6 +1153,93 @@ impl ContainerLogInfo {
     }
 }
 
+const CHECKPOINT_GENERATION: u64 = u64::MAX;
+
+const CHECKPOINT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Keeps the timestamp of the last log read from each container, so its logs
+/// are read from there once its stream is restarted after an error, and also
+/// once the source is restarted if persisted to a file.
+struct Checkpointer {
+    path: Option<PathBuf>,
+    state: Mutex<CheckpointerState>,
+}
+
+#[derive(Default)]
+struct CheckpointerState {
+    checkpoints: HashMap<String, DateTime<FixedOffset>>,
+    dirty: bool,
+}
+
+impl Checkpointer {
+    fn load(path: Option<PathBuf>) -> Result<Self, io::Error> {
+        let checkpoints = match &path {
+            Some(path) => match std::fs::read(path) {
+                Ok(data) => serde_json::from_slice(&data)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+                Err(error) => return Err(error),
+            },
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(CheckpointerState {
+                checkpoints,
+                dirty: false,
+            }),
+        })
+    }
+
+    fn get(&self, id: &ContainerId) -> Option<DateTime<FixedOffset>> {
+        let state = self.state.lock().expect("checkpointer mutex poisoned");
+        state.checkpoints.get(id.as_str()).cloned()
+    }
+
+    fn set(&self, id: &ContainerId, timestamp: DateTime<FixedOffset>) {
+        let mut state = self.state.lock().expect("checkpointer mutex poisoned");
+        state.checkpoints.insert(id.as_str().to_owned(), timestamp);
+        state.dirty = true;
+    }
+
+    fn remove(&self, id: &ContainerId) {
+        let mut state = self.state.lock().expect("checkpointer mutex poisoned");
+        if state.checkpoints.remove(id.as_str()).is_some() {
+            state.dirty = true;
+        }
+    }
+
+    /// Writes the checkpoints if they changed since the last write, through a
+    /// temporary file so an interrupted write leaves the previous ones in place.
+    async fn flush(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        let data = {
+            let mut state = self.state.lock().expect("checkpointer mutex poisoned");
+            if !state.dirty {
+                return;
+            }
+            state.dirty = false;
+            serde_json::to_vec(&state.checkpoints).expect("checkpoints are serializable")
+        };
+
+        let tmp_path = path.with_extension("new.json");
+        let result = match tokio::fs::write(&tmp_path, data).await {
+            Ok(()) => tokio::fs::rename(&tmp_path, path).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            emit!(DockerLogsCheckpointWriteFailed { error });
+            self.state
+                .lock()
+                .expect("checkpointer mutex poisoned")
+                .dirty = true;
+        }
+    }
+}
+
 struct ContainerMetadata {
     /// label.key -> String
     labels: HashMap<String, String>,
@@ -1031,8 +1308,13 @@ mod tests {
     #[test]
     fn exclude_self() {
         let (tx, _rx) = Pipeline::new_test();
-        let mut source =
-            DockerLogsSource::new(DockerLogsConfig::default(), tx, ShutdownSignal::noop()).unwrap();
+        let mut source = DockerLogsSource::new(
+            DockerLogsConfig::default(),
+            None,
+            tx,
+            ShutdownSignal::noop(),
+        )
+        .unwrap();
         source.hostname = Some("451062c59603".to_owned());
         assert!(
             source.exclude_self("451062c59603a1cf0c6af3e74a31c0ae63d8275aa16a5fc78ef31b923baaffc3")
@@ -1042,6 +1324,44 @@ mod tests {
         source.hostname = Some("a".to_owned());
         assert!(!source.exclude_self("a29d569bd46c"));
     }
+
+    #[tokio::test]
+    async fn checkpoints_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoints.json");
+        let id = ContainerId::new("451062c59603".to_owned());
+        let timestamp = DateTime::parse_from_rfc3339("2021-08-02T09:41:12.123456789Z").unwrap();
+
+        let checkpointer = Checkpointer::load(Some(path.clone())).unwrap();
+        assert_eq!(checkpointer.get(&id), None);
+        checkpointer.set(&id, timestamp);
+        checkpointer.flush().await;
+
+        let checkpointer = Checkpointer::load(Some(path)).unwrap();
+        assert_eq!(checkpointer.get(&id), Some(timestamp));
+    }
+
+    #[test]
+    fn skips_replayed_events() {
+        let (tx, _rx) = Pipeline::new_test();
+        let mut source = DockerLogsSource::new(
+            DockerLogsConfig::default(),
+            None,
+            tx,
+            ShutdownSignal::noop(),
+        )
+        .unwrap();
+        let event = |time_nano: i64| SystemEventsResponse {
+            time: Some(time_nano / 1_000_000_000),
+            time_nano: Some(time_nano),
+            ..Default::default()
+        };
+
+        assert!(source.is_new_event(&event(1_627_897_272_000_000_001)));
+        assert!(source.is_new_event(&event(1_627_897_272_000_000_002)));
+        assert!(!source.is_new_event(&event(1_627_897_272_000_000_002)));
+        assert_eq!(source.last_event_time.0, Utc.timestamp(1_627_897_272, 0));
+    }
 }
 
 #[cfg(all(test, feature = "docker-logs-integration-tests"))]
@@ -0,0 +1,150 @@
+//! Decoding of the DNS packets kept by the `dns` socket filter, which are
+//! read whole from their Ethernet header.
+use crate::event::{LogEvent, Value};
+use dnsmsg_parser::{dns_message::DnsRecord, dns_message_parser::DnsMessageParser};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+const ETHERNET_HEADER_LEN: usize = 14;
+const IPV6_HEADER_LEN: usize = 40;
+const UDP_HEADER_LEN: usize = 8;
+
+const ETH_P_IP: u16 = 0x0800;
+const ETH_P_IPV6: u16 = 0x86DD;
+
+pub(super) fn decode(packet: &[u8]) -> Option<LogEvent> {
+    let ether_type = u16::from_be_bytes([*packet.get(12)?, *packet.get(13)?]);
+    let ip = packet.get(ETHERNET_HEADER_LEN..)?;
+    let (source, destination, udp) = match ether_type {
+        ETH_P_IP => {
+            let header_len = usize::from(ip.first()? & 0x0F) * 4;
+            (
+                IpAddr::from(Ipv4Addr::from(<[u8; 4]>::try_from(ip.get(12..16)?).ok()?)),
+                IpAddr::from(Ipv4Addr::from(<[u8; 4]>::try_from(ip.get(16..20)?).ok()?)),
+                ip.get(header_len..)?,
+            )
+        }
+        ETH_P_IPV6 => (
+            IpAddr::from(Ipv6Addr::from(<[u8; 16]>::try_from(ip.get(8..24)?).ok()?)),
+            IpAddr::from(Ipv6Addr::from(<[u8; 16]>::try_from(ip.get(24..40)?).ok()?)),
+            ip.get(IPV6_HEADER_LEN..)?,
+        ),
+        _ => return None,
+    };
+    let source_port = u16::from_be_bytes([*udp.get(0)?, *udp.get(1)?]);
+    let destination_port = u16::from_be_bytes([*udp.get(2)?, *udp.get(3)?]);
+
+    let message = DnsMessageParser::new(udp.get(UDP_HEADER_LEN..)?.to_vec())
+        .parse_as_query_message()
+        .ok()?;
+
+    let mut log = LogEvent::default();
+    if message.header.qr == 0 {
+        log.insert("event_type", "dns_query");
+    } else {
+        log.insert("event_type", "dns_response");
+        log.insert("dns.response_code", i64::from(message.response_code));
+        if let Some(response) = message.response {
+            log.insert("dns.response", response);
+        }
+        log.insert("dns.answers", records(&message.answer_section));
+    }
+    log.insert("source_address", source.to_string());
+    log.insert("source_port", i64::from(source_port));
+    log.insert("destination_address", destination.to_string());
+    log.insert("destination_port", i64::from(destination_port));
+    log.insert("dns.id", i64::from(message.header.id));
+    log.insert(
+        "dns.questions",
+        message
+            .question_section
+            .iter()
+            .map(|question| {
+                let mut map = BTreeMap::new();
+                map.insert("name".to_owned(), Value::from(question.name.clone()));
+                if let Some(record_type) = &question.record_type {
+                    map.insert("type".to_owned(), Value::from(record_type.clone()));
+                }
+                Value::from(map)
+            })
+            .collect::<Vec<_>>(),
+    );
+    Some(log)
+}
+
+fn records(records: &[DnsRecord]) -> Vec<Value> {
+    records
+        .iter()
+        .map(|record| {
+            let mut map = BTreeMap::new();
+            map.insert("name".to_owned(), Value::from(record.name.clone()));
+            if let Some(record_type) = &record.record_type {
+                map.insert("type".to_owned(), Value::from(record_type.clone()));
+            }
+            map.insert("ttl".to_owned(), Value::from(i64::from(record.ttl)));
+            if let Some(rdata) = &record.rdata {
+                map.insert("data".to_owned(), Value::from(rdata.clone()));
+            }
+            Value::from(map)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An Ethernet frame of an IPv4 UDP query for the A record of
+    // `example.com`, from 10.0.0.1:40000 to 10.0.0.53:53.
+    fn query() -> Vec<u8> {
+        let dns = [
+            &[0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0][..],
+            &[7],
+            b"example",
+            &[3],
+            b"com",
+            &[0, 0, 1, 0, 1],
+        ]
+        .concat();
+        let udp = [
+            &40000_u16.to_be_bytes()[..],
+            &53_u16.to_be_bytes(),
+            &((UDP_HEADER_LEN + dns.len()) as u16).to_be_bytes(),
+            &[0, 0],
+            &dns,
+        ]
+        .concat();
+        let ip = [
+            &[0x45, 0, 0, 0, 0, 0, 0, 0, 64, 17, 0, 0][..],
+            &[10, 0, 0, 1],
+            &[10, 0, 0, 53],
+            &udp,
+        ]
+        .concat();
+        [&[0; 12][..], &ETH_P_IP.to_be_bytes(), &ip].concat()
+    }
+
+    #[test]
+    fn decodes_query() {
+        let log = decode(&query()).unwrap();
+
+        assert_eq!(log["event_type"], Value::from("dns_query"));
+        assert_eq!(log["source_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["destination_port"], Value::from(53));
+        assert_eq!(log["dns.id"], Value::from(0x1234));
+        assert_eq!(log["dns.questions[0].name"], Value::from("example.com."));
+        assert_eq!(log["dns.questions[0].type"], Value::from("A"));
+    }
+
+    #[test]
+    fn ignores_other_packets() {
+        let mut packet = query();
+        packet[12..14].copy_from_slice(&0x0806_u16.to_be_bytes());
+
+        assert!(decode(&packet).is_none());
+        assert!(decode(&packet[..20]).is_none());
+    }
+}
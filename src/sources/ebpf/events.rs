@@ -0,0 +1,185 @@
+//! Decoding of the events the probes output, whose layouts are those of the
+//! structs of `probes.bpf.c`.
+use crate::event::LogEvent;
+use std::{
+    convert::TryInto,
+    mem::size_of,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    ptr,
+};
+
+const EVENT_EXEC: u32 = 1;
+const EVENT_TCP: u32 = 2;
+
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 10;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct ExecEvent {
+    kind: u32,
+    pid: u32,
+    old_pid: u32,
+    uid: u32,
+    comm: [u8; 16],
+    filename: [u8; 256],
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct TcpEvent {
+    kind: u32,
+    pid: u32,
+    uid: u32,
+    family: u16,
+    inbound: u16,
+    sport: u16,
+    dport: u16,
+    saddr: [u8; 16],
+    daddr: [u8; 16],
+    comm: [u8; 16],
+}
+
+pub(super) fn decode(data: &[u8]) -> Option<LogEvent> {
+    let kind = u32::from_ne_bytes(data.get(..4)?.try_into().ok()?);
+    match kind {
+        EVENT_EXEC => read::<ExecEvent>(data).map(exec_log),
+        EVENT_TCP => read::<TcpEvent>(data).and_then(tcp_log),
+        _ => None,
+    }
+}
+
+fn read<T: Copy>(data: &[u8]) -> Option<T> {
+    // The events are plain integers and arrays, valid for any bytes.
+    (data.len() >= size_of::<T>()).then(|| unsafe { ptr::read_unaligned(data.as_ptr().cast()) })
+}
+
+fn exec_log(event: ExecEvent) -> LogEvent {
+    let mut log = LogEvent::default();
+    log.insert("event_type", "process_exec");
+    log.insert("pid", i64::from(event.pid));
+    // A thread other than the leader of its thread group exec'ing takes over
+    // the PID of the leader.
+    if event.old_pid != event.pid {
+        log.insert("old_pid", i64::from(event.old_pid));
+    }
+    log.insert("uid", i64::from(event.uid));
+    log.insert("command", c_string(&event.comm));
+    log.insert("filename", c_string(&event.filename));
+    log
+}
+
+fn tcp_log(event: TcpEvent) -> Option<LogEvent> {
+    let (source, destination) = match event.family {
+        AF_INET => (
+            IpAddr::from(Ipv4Addr::new(
+                event.saddr[0],
+                event.saddr[1],
+                event.saddr[2],
+                event.saddr[3],
+            )),
+            IpAddr::from(Ipv4Addr::new(
+                event.daddr[0],
+                event.daddr[1],
+                event.daddr[2],
+                event.daddr[3],
+            )),
+        ),
+        AF_INET6 => (
+            IpAddr::from(Ipv6Addr::from(event.saddr)),
+            IpAddr::from(Ipv6Addr::from(event.daddr)),
+        ),
+        _ => return None,
+    };
+
+    let mut log = LogEvent::default();
+    if event.inbound == 0 {
+        log.insert("event_type", "tcp_connect");
+        log.insert("pid", i64::from(event.pid));
+        log.insert("uid", i64::from(event.uid));
+        log.insert("command", c_string(&event.comm));
+    } else {
+        log.insert("event_type", "tcp_accept");
+    }
+    log.insert("source_address", source.to_string());
+    log.insert("source_port", i64::from(event.sport));
+    log.insert("destination_address", destination.to_string());
+    log.insert("destination_port", i64::from(event.dport));
+    Some(log)
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    fn bytes<T>(event: &T) -> &[u8] {
+        unsafe { std::slice::from_raw_parts((event as *const T).cast(), size_of::<T>()) }
+    }
+
+    fn padded<const N: usize>(text: &str) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        bytes
+    }
+
+    fn ipv4(octets: [u8; 4]) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..4].copy_from_slice(&octets);
+        bytes
+    }
+
+    #[test]
+    fn decodes_exec() {
+        let event = ExecEvent {
+            kind: EVENT_EXEC,
+            pid: 42,
+            old_pid: 42,
+            uid: 1000,
+            comm: padded("bash"),
+            filename: padded("/usr/bin/curl"),
+        };
+
+        let log = decode(bytes(&event)).unwrap();
+
+        assert_eq!(log["event_type"], Value::from("process_exec"));
+        assert_eq!(log["pid"], Value::from(42));
+        assert!(!log.contains("old_pid"));
+        assert_eq!(log["command"], Value::from("bash"));
+        assert_eq!(log["filename"], Value::from("/usr/bin/curl"));
+    }
+
+    #[test]
+    fn decodes_tcp_connect() {
+        let event = TcpEvent {
+            kind: EVENT_TCP,
+            pid: 42,
+            uid: 1000,
+            family: AF_INET,
+            inbound: 0,
+            sport: 51234,
+            dport: 443,
+            saddr: ipv4([10, 0, 0, 1]),
+            daddr: ipv4([93, 184, 216, 34]),
+            comm: padded("curl"),
+        };
+
+        let log = decode(bytes(&event)).unwrap();
+
+        assert_eq!(log["event_type"], Value::from("tcp_connect"));
+        assert_eq!(log["source_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["destination_address"], Value::from("93.184.216.34"));
+        assert_eq!(log["destination_port"], Value::from(443));
+    }
+
+    #[test]
+    fn ignores_truncated_events() {
+        assert!(decode(&EVENT_EXEC.to_ne_bytes()).is_none());
+        assert!(decode(&[]).is_none());
+    }
+}
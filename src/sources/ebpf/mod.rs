@@ -0,0 +1,263 @@
+mod dns;
+mod events;
+
+use crate::{
+    config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
+    event::{Event, LogEvent},
+    internal_events::{EbpfEventReceived, EbpfEventsLost, EbpfReadFailed},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use aya::{
+    maps::{
+        perf::{AsyncPerfEventArray, AsyncPerfEventArrayBuffer},
+        MapRefMut,
+    },
+    programs::{SocketFilter, TracePoint},
+    util::online_cpus,
+    Bpf,
+};
+use bytes::{Bytes, BytesMut};
+use chrono::Utc;
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::{TryFrom, TryInto},
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+use tokio::{io::unix::AsyncFd, sync::mpsc, task::JoinHandle};
+
+/// The probes compiled from `probes.bpf.c` by `build.rs`.
+static PROBES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/probes.bpf.o"));
+
+/// Large enough for any event of the probes.
+const EVENT_BUFFER_SIZE: usize = 512;
+/// Large enough for the packets of DNS over UDP.
+const PACKET_BUFFER_SIZE: usize = 65536;
+
+/// Emits process exec, TCP connection and DNS events observed in the kernel,
+/// for which Vector must run as root or with `CAP_BPF` and `CAP_PERFMON`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EbpfConfig {
+    #[serde(default = "crate::serde::default_true")]
+    process_exec: bool,
+    #[serde(default = "crate::serde::default_true")]
+    tcp_connections: bool,
+    #[serde(default = "crate::serde::default_true")]
+    dns_queries: bool,
+}
+
+inventory::submit! {
+    SourceDescription::new::<EbpfConfig>("ebpf")
+}
+
+impl_generate_config_from_default!(EbpfConfig);
+
+impl Default for EbpfConfig {
+    fn default() -> Self {
+        Self {
+            process_exec: true,
+            tcp_connections: true,
+            dns_queries: true,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "ebpf")]
+impl SourceConfig for EbpfConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let mut bpf = Bpf::load(PROBES, None)?;
+        let (tx, rx) = mpsc::channel(1024);
+        let mut tasks = Vec::new();
+
+        if self.process_exec {
+            let program: &mut TracePoint = bpf.program_mut("sched_process_exec")?.try_into()?;
+            program.load()?;
+            program.attach("sched", "sched_process_exec")?;
+        }
+        if self.tcp_connections {
+            let program: &mut TracePoint = bpf.program_mut("inet_sock_set_state")?.try_into()?;
+            program.load()?;
+            program.attach("sock", "inet_sock_set_state")?;
+        }
+        if self.process_exec || self.tcp_connections {
+            let mut perf_array = AsyncPerfEventArray::try_from(bpf.map_mut("EVENTS")?)?;
+            for cpu in online_cpus()? {
+                let buffer = perf_array.open(cpu, None)?;
+                tasks.push(tokio::spawn(read_events(buffer, tx.clone())));
+            }
+        }
+
+        if self.dns_queries {
+            let socket = AsyncFd::new(PacketSocket::new()?)?;
+            let program: &mut SocketFilter = bpf.program_mut("dns")?.try_into()?;
+            program.load()?;
+            program.attach(socket.as_raw_fd())?;
+            tasks.push(tokio::spawn(read_packets(socket, tx)));
+        }
+
+        Ok(Box::pin(ebpf_source(bpf, tasks, rx, cx.shutdown, cx.out)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "ebpf"
+    }
+}
+
+async fn ebpf_source(
+    // Detaches the probes when dropped.
+    _bpf: Bpf,
+    tasks: Vec<JoinHandle<()>>,
+    mut rx: mpsc::Receiver<LogEvent>,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    loop {
+        let mut log = tokio::select! {
+            _ = &mut shutdown => break,
+            log = rx.recv() => match log {
+                Some(log) => log,
+                None => break,
+            },
+        };
+
+        log.insert(log_schema().timestamp_key(), Utc::now());
+        log.insert(log_schema().source_type_key(), Bytes::from("ebpf"));
+
+        if let Err(error) = out.send(Event::from(log)).await {
+            error!(message = "Error sending to sink.", %error);
+            break;
+        }
+    }
+
+    for task in tasks {
+        task.abort();
+    }
+
+    Ok(())
+}
+
+async fn read_events(mut buffer: AsyncPerfEventArrayBuffer<MapRefMut>, tx: mpsc::Sender<LogEvent>) {
+    let mut buffers = (0..16)
+        .map(|_| BytesMut::with_capacity(EVENT_BUFFER_SIZE))
+        .collect::<Vec<_>>();
+
+    loop {
+        let events = match buffer.read_events(&mut buffers).await {
+            Ok(events) => events,
+            Err(error) => {
+                emit!(EbpfReadFailed {
+                    error: error.into()
+                });
+                return;
+            }
+        };
+        if events.lost > 0 {
+            emit!(EbpfEventsLost { count: events.lost });
+        }
+
+        for data in &buffers[..events.read] {
+            emit!(EbpfEventReceived {
+                byte_size: data.len()
+            });
+            if let Some(log) = events::decode(data) {
+                if tx.send(log).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn read_packets(socket: AsyncFd<PacketSocket>, tx: mpsc::Sender<LogEvent>) {
+    let mut packet = vec![0; PACKET_BUFFER_SIZE];
+
+    loop {
+        let len = match read_packet(&socket, &mut packet).await {
+            Ok(len) => len,
+            Err(error) => {
+                emit!(EbpfReadFailed {
+                    error: error.into()
+                });
+                return;
+            }
+        };
+
+        emit!(EbpfEventReceived { byte_size: len });
+        if let Some(log) = dns::decode(&packet[..len]) {
+            if tx.send(log).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn read_packet(socket: &AsyncFd<PacketSocket>, packet: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let mut guard = socket.readable().await?;
+        match guard.try_io(|socket| socket.get_ref().recv(packet)) {
+            Ok(result) => return result,
+            Err(_would_block) => continue,
+        }
+    }
+}
+
+/// A raw socket receiving the packets of every interface, which the `dns`
+/// socket filter then narrows down.
+struct PacketSocket(RawFd);
+
+impl PacketSocket {
+    fn new() -> io::Result<Self> {
+        let protocol = (libc::ETH_P_ALL as u16).to_be();
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_PACKET,
+                libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                i32::from(protocol),
+            )
+        };
+        if fd < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self(fd))
+        }
+    }
+
+    fn recv(&self, packet: &mut [u8]) -> io::Result<usize> {
+        let len = unsafe { libc::recv(self.0, packet.as_mut_ptr().cast(), packet.len(), 0) };
+        if len < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(len as usize)
+        }
+    }
+}
+
+impl AsRawFd for PacketSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl Drop for PacketSocket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<EbpfConfig>();
+    }
+}
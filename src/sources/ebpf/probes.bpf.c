@@ -0,0 +1,200 @@
+// Probes of the `ebpf` source, compiled by `build.rs` with
+// `clang -O2 -target bpf`. The events they output are decoded by
+// `src/sources/ebpf/events.rs`, which must be kept in sync with them.
+//
+// The helpers and types are declared here rather than taken from libbpf, so
+// that only clang is needed to build them.
+
+typedef unsigned char __u8;
+typedef unsigned short __u16;
+typedef unsigned int __u32;
+typedef unsigned long long __u64;
+
+#define SEC(name) __attribute__((section(name), used))
+
+#define BPF_MAP_TYPE_PERF_EVENT_ARRAY 4
+#define BPF_F_CURRENT_CPU 0xffffffffULL
+
+#define EVENT_EXEC 1
+#define EVENT_TCP 2
+
+#define AF_INET 2
+#define AF_INET6 10
+#define TCP_ESTABLISHED 1
+#define TCP_SYN_SENT 2
+#define TCP_SYN_RECV 3
+#define IPPROTO_TCP 6
+#define IPPROTO_UDP 17
+#define ETH_P_IP 0x0800
+#define ETH_P_IPV6 0x86DD
+#define DNS_PORT 53
+
+static __u64 (*bpf_get_current_pid_tgid)(void) = (void *)14;
+static __u64 (*bpf_get_current_uid_gid)(void) = (void *)15;
+static long (*bpf_get_current_comm)(void *buf, __u32 size) = (void *)16;
+static long (*bpf_perf_event_output)(void *ctx, void *map, __u64 flags, void *data,
+                                     __u64 size) = (void *)25;
+static long (*bpf_skb_load_bytes)(const void *skb, __u32 offset, void *to, __u32 len) = (void *)26;
+static long (*bpf_probe_read_kernel_str)(void *dst, __u32 size, const void *unsafe_ptr) = (void *)115;
+
+struct bpf_map_def {
+    __u32 type;
+    __u32 key_size;
+    __u32 value_size;
+    __u32 max_entries;
+    __u32 map_flags;
+};
+
+struct bpf_map_def SEC("maps/EVENTS") EVENTS = {
+    .type = BPF_MAP_TYPE_PERF_EVENT_ARRAY,
+    .key_size = sizeof(__u32),
+    .value_size = sizeof(__u32),
+    .max_entries = 1024,
+    .map_flags = 0,
+};
+
+struct exec_event {
+    __u32 kind;
+    __u32 pid;
+    __u32 old_pid;
+    __u32 uid;
+    char comm[16];
+    char filename[256];
+};
+
+struct tcp_event {
+    __u32 kind;
+    __u32 pid;
+    __u32 uid;
+    __u16 family;
+    __u16 inbound;
+    __u16 sport;
+    __u16 dport;
+    __u8 saddr[16];
+    __u8 daddr[16];
+    char comm[16];
+};
+
+// /sys/kernel/debug/tracing/events/sched/sched_process_exec/format
+struct sched_process_exec_args {
+    __u64 common;
+    __u32 filename_loc;
+    __u32 pid;
+    __u32 old_pid;
+};
+
+// /sys/kernel/debug/tracing/events/sock/inet_sock_set_state/format, as of
+// Linux 5.6 which added `protocol`.
+struct inet_sock_set_state_args {
+    __u64 common;
+    const void *skaddr;
+    int oldstate;
+    int newstate;
+    __u16 sport;
+    __u16 dport;
+    __u16 family;
+    __u16 protocol;
+    __u8 saddr[4];
+    __u8 daddr[4];
+    __u8 saddr_v6[16];
+    __u8 daddr_v6[16];
+};
+
+struct __sk_buff {
+    __u32 len;
+};
+
+SEC("tracepoint/sched_process_exec")
+int sched_process_exec(struct sched_process_exec_args *args)
+{
+    struct exec_event event = {};
+
+    event.kind = EVENT_EXEC;
+    event.pid = args->pid;
+    event.old_pid = args->old_pid;
+    event.uid = bpf_get_current_uid_gid();
+    bpf_get_current_comm(&event.comm, sizeof(event.comm));
+    bpf_probe_read_kernel_str(&event.filename, sizeof(event.filename),
+                              (const char *)args + (args->filename_loc & 0xFFFF));
+
+    bpf_perf_event_output(args, &EVENTS, BPF_F_CURRENT_CPU, &event, sizeof(event));
+    return 0;
+}
+
+SEC("tracepoint/inet_sock_set_state")
+int inet_sock_set_state(struct inet_sock_set_state_args *args)
+{
+    struct tcp_event event = {};
+    int i;
+
+    if (args->protocol != IPPROTO_TCP || args->newstate != TCP_ESTABLISHED)
+        return 0;
+    if (args->oldstate != TCP_SYN_SENT && args->oldstate != TCP_SYN_RECV)
+        return 0;
+
+    event.kind = EVENT_TCP;
+    // Inbound connections are established in softirq context, so the
+    // process is only meaningful for outbound ones.
+    event.inbound = args->oldstate == TCP_SYN_RECV;
+    if (!event.inbound) {
+        event.pid = bpf_get_current_pid_tgid() >> 32;
+        event.uid = bpf_get_current_uid_gid();
+        bpf_get_current_comm(&event.comm, sizeof(event.comm));
+    }
+    event.family = args->family;
+    event.sport = args->sport;
+    event.dport = args->dport;
+    if (args->family == AF_INET) {
+        for (i = 0; i < 4; i++) {
+            event.saddr[i] = args->saddr[i];
+            event.daddr[i] = args->daddr[i];
+        }
+    } else {
+        for (i = 0; i < 16; i++) {
+            event.saddr[i] = args->saddr_v6[i];
+            event.daddr[i] = args->daddr_v6[i];
+        }
+    }
+
+    bpf_perf_event_output(args, &EVENTS, BPF_F_CURRENT_CPU, &event, sizeof(event));
+    return 0;
+}
+
+// Keeps the UDP packets from or to port 53 on the raw socket it's attached
+// to, which reads them whole, from their Ethernet header.
+SEC("socket/dns")
+int dns(struct __sk_buff *skb)
+{
+    __u16 ether_type, sport, dport;
+    __u8 protocol, ihl;
+    __u32 udp;
+
+    if (bpf_skb_load_bytes(skb, 12, &ether_type, 2) < 0)
+        return 0;
+    ether_type = __builtin_bswap16(ether_type);
+
+    if (ether_type == ETH_P_IP) {
+        if (bpf_skb_load_bytes(skb, 14 + 9, &protocol, 1) < 0 ||
+            bpf_skb_load_bytes(skb, 14, &ihl, 1) < 0)
+            return 0;
+        udp = 14 + (ihl & 0x0F) * 4;
+    } else if (ether_type == ETH_P_IPV6) {
+        if (bpf_skb_load_bytes(skb, 14 + 6, &protocol, 1) < 0)
+            return 0;
+        udp = 14 + 40;
+    } else {
+        return 0;
+    }
+    if (protocol != IPPROTO_UDP)
+        return 0;
+
+    if (bpf_skb_load_bytes(skb, udp, &sport, 2) < 0 ||
+        bpf_skb_load_bytes(skb, udp + 2, &dport, 2) < 0)
+        return 0;
+    if (__builtin_bswap16(sport) != DNS_PORT && __builtin_bswap16(dport) != DNS_PORT)
+        return 0;
+
+    return skb->len;
+}
+
+char _license[] SEC("license") = "GPL";
@@ -1,7 +1,7 @@
 use crate::async_read::VecAsyncReadExt;
 use crate::config::{DataType, SourceContext};
 use crate::event::LogEvent;
-use crate::internal_events::{ExecCommandExecuted, ExecTimeout};
+use crate::internal_events::{ExecCommandExecuted, ExecRespawnsExhausted, ExecTimeout};
 use crate::{
     config::{log_schema, SourceConfig, SourceDescription},
     event::Event,
@@ -14,6 +14,7 @@ use chrono::Utc;
 use futures::{FutureExt, SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
+use std::cmp;
 use std::io::{Error, ErrorKind};
 use std::path::PathBuf;
 use std::process::ExitStatus;
@@ -40,6 +41,7 @@ pub struct ExecConfig {
     pub event_per_line: bool,
     #[serde(default = "default_maximum_buffer_size")]
     pub maximum_buffer_size_bytes: usize,
+    pub include_exit_event: bool,
 }
 
 // TODO: Would be nice to combine the scheduled and streaming config with the mode enum once
@@ -65,6 +67,12 @@ pub struct StreamingConfig {
     respawn_on_exit: bool,
     #[serde(default = "default_respawn_interval_secs")]
     respawn_interval_secs: u64,
+    /// Consecutive respawns double the interval up to this
+    #[serde(default)]
+    max_respawn_interval_secs: Option<u64>,
+    /// Respawning stops after this many consecutive respawns
+    #[serde(default)]
+    max_respawns: Option<u32>,
 }
 
 #[derive(Debug, PartialEq, Snafu)]
@@ -88,6 +96,7 @@ impl Default for ExecConfig {
             include_stderr: default_include_stderr(),
             event_per_line: default_events_per_line(),
             maximum_buffer_size_bytes: default_maximum_buffer_size(),
+            include_exit_event: false,
         }
     }
 }
@@ -127,6 +136,9 @@ const STDERR: &str = "stderr";
 const STREAM_KEY: &str = "stream";
 const PID_KEY: &str = "pid";
 const COMMAND_KEY: &str = "command";
+const EXIT_CODE_KEY: &str = "exit_code";
+const SIGNAL_KEY: &str = "signal";
+const EXEC_DURATION_KEY: &str = "exec_duration_seconds";
 
 inventory::submit! {
     SourceDescription::new::<ExecConfig>("exec")
@@ -156,18 +168,64 @@ impl ExecConfig {
         }
     }
 
-    fn respawn_on_exit_or_default(&self) -> bool {
+    fn respawn_or_default(&self) -> Option<Respawn> {
         match &self.streaming {
-            None => default_respawn_on_exit(),
-            Some(config) => config.respawn_on_exit,
+            None => Some(Respawn::new(
+                Duration::from_secs(default_respawn_interval_secs()),
+                None,
+                None,
+            )),
+            Some(config) if config.respawn_on_exit => Some(Respawn::new(
+                Duration::from_secs(config.respawn_interval_secs),
+                config.max_respawn_interval_secs.map(Duration::from_secs),
+                config.max_respawns,
+            )),
+            Some(_) => None,
         }
     }
+}
 
-    fn respawn_interval_secs_or_default(&self) -> u64 {
-        match &self.streaming {
-            None => default_respawn_interval_secs(),
-            Some(config) => config.respawn_interval_secs,
+/// The delays between respawns of the streaming command, which double while
+/// it keeps exiting soon after being spawned.
+#[derive(Debug)]
+struct Respawn {
+    interval: Duration,
+    max_interval: Duration,
+    max_respawns: Option<u32>,
+    delay: Duration,
+    respawns: u32,
+}
+
+impl Respawn {
+    fn new(interval: Duration, max_interval: Option<Duration>, max_respawns: Option<u32>) -> Self {
+        Self {
+            interval,
+            max_interval: max_interval.map_or(interval, |max| cmp::max(max, interval)),
+            max_respawns,
+            delay: interval,
+            respawns: 0,
+        }
+    }
+
+    /// The delay before respawning a command which ran for `ran_for`, or
+    /// `None` once out of respawns. Commands which ran for at least the
+    /// longest interval were healthy, and start over from the first one.
+    fn next_delay(&mut self, ran_for: Duration) -> Option<Duration> {
+        if ran_for >= self.max_interval {
+            self.delay = self.interval;
+            self.respawns = 0;
+        }
+        if self
+            .max_respawns
+            .map_or(false, |max_respawns| self.respawns >= max_respawns)
+        {
+            return None;
         }
+
+        let delay = self.delay;
+        self.delay = cmp::min(self.delay * 2, self.max_interval);
+        self.respawns += 1;
+        Some(delay)
     }
 }
 
@@ -189,13 +247,11 @@ impl SourceConfig for ExecConfig {
                 )))
             }
             Mode::Streaming => {
-                let respawn_on_exit = self.respawn_on_exit_or_default();
-                let respawn_interval_secs = self.respawn_interval_secs_or_default();
+                let respawn = self.respawn_or_default();
                 Ok(Box::pin(run_streaming(
                     self.clone(),
                     hostname,
-                    respawn_on_exit,
-                    respawn_interval_secs,
+                    respawn,
                     cx.shutdown,
                     cx.out,
                 )))
@@ -263,16 +319,14 @@ async fn run_scheduled(
 async fn run_streaming(
     config: ExecConfig,
     hostname: Option<String>,
-    respawn_on_exit: bool,
-    respawn_interval_secs: u64,
+    respawn: Option<Respawn>,
     shutdown: ShutdownSignal,
     out: Pipeline,
 ) -> Result<(), ()> {
-    if respawn_on_exit {
-        let duration = Duration::from_secs(respawn_interval_secs);
-
+    if let Some(mut respawn) = respawn {
         // Continue to loop while not shutdown
         loop {
+            let start = Instant::now();
             tokio::select! {
                 _ = shutdown.clone() => break, // will break early if a shutdown is started
                 output = run_command(config.clone(), hostname.clone(), shutdown.clone(), out.clone()) => {
@@ -287,13 +341,25 @@ async fn run_streaming(
             }
 
             let mut poll_shutdown = shutdown.clone();
-            if futures::poll!(&mut poll_shutdown).is_pending() {
-                warn!("Streaming process ended before shutdown.");
+            if futures::poll!(&mut poll_shutdown).is_ready() {
+                break;
             }
+            warn!("Streaming process ended before shutdown.");
+
+            let delay = match respawn.next_delay(start.elapsed()) {
+                Some(delay) => delay,
+                None => {
+                    emit!(ExecRespawnsExhausted {
+                        command: config.command_line().as_str(),
+                        respawns: respawn.respawns,
+                    });
+                    break;
+                }
+            };
 
             tokio::select! {
                 _ = &mut poll_shutdown => break, // will break early if a shutdown is started
-                _ = sleep(duration) => debug!("Restarting streaming process."),
+                _ = sleep(delay) => debug!(message = "Restarting streaming process.", delay_secs = delay.as_secs()),
             }
         }
     } else {
@@ -377,29 +443,46 @@ async fn run_command(
             });
     }
 
+    // The output is closed once the command exits, unless it closed it
+    // itself. On shutdown the command is killed rather than waited for.
+    let exited = tokio::select! {
+        exit_status = child.wait() => Some(exit_status),
+        _ = shutdown => None,
+    };
+    let exit_status = match exited {
+        Some(exit_status) => exit_status.map(Some),
+        None => child.try_wait(),
+    };
+
     let elapsed = start.elapsed();
 
-    let result = match child.try_wait() {
-        Ok(Some(exit_status)) => {
-            handle_exit_status(&config, exit_status.code(), elapsed);
-            Ok(Some(exit_status))
-        }
-        Ok(None) => {
-            handle_exit_status(&config, None, elapsed);
-            Ok(None)
-        }
+    let exit_status = match exit_status {
+        Ok(exit_status) => exit_status,
         Err(error) => {
             error!(message = "Unable to obtain exit status.", %error);
-
-            handle_exit_status(&config, None, elapsed);
-            Ok(None)
+            None
         }
     };
+    handle_exit_status(
+        &config,
+        exit_status.and_then(|exit_status| exit_status.code()),
+        elapsed,
+    );
+
+    if config.include_exit_event {
+        let event = create_exit_event(&config, &hostname, exit_status, pid, elapsed);
+        let _ = out
+            .send(event)
+            .await
+            .map_err(|_: crate::pipeline::ClosedError| {
+                error!(message = "Failed to forward events; downstream is closed.");
+            });
+    }
 
     debug!("Finished command run.");
     let _ = out.flush().await;
 
-    result
+    Ok(exit_status)
 }
 
 fn handle_exit_status(config: &ExecConfig, exit_status: Option<i32>, exec_duration: Duration) {
@@ -469,6 +552,58 @@ fn create_event(
         log_event.insert(STREAM_KEY, data_stream.clone());
     }
 
+    insert_command_fields(&mut log_event, config, hostname, pid);
+
+    Event::Log(log_event)
+}
+
+/// An event describing how the command exited.
+fn create_exit_event(
+    config: &ExecConfig,
+    hostname: &Option<String>,
+    exit_status: Option<ExitStatus>,
+    pid: Option<u32>,
+    exec_duration: Duration,
+) -> Event {
+    let mut log_event = LogEvent::default();
+
+    let code = exit_status.and_then(|exit_status| exit_status.code());
+    #[cfg(unix)]
+    let signal = {
+        use std::os::unix::process::ExitStatusExt;
+        exit_status.and_then(|exit_status| exit_status.signal())
+    };
+    #[cfg(not(unix))]
+    let signal: Option<i32> = None;
+
+    let message = match (code, signal) {
+        (Some(code), _) => format!("Command exited with code {}.", code),
+        (None, Some(signal)) => format!("Command was terminated by signal {}.", signal),
+        (None, None) => "Command exited with an unknown status.".to_owned(),
+    };
+    log_event.insert(log_schema().message_key(), message);
+    log_event.insert(log_schema().timestamp_key(), Utc::now());
+    log_event.insert(log_schema().source_type_key(), Bytes::from(EXEC));
+
+    if let Some(code) = code {
+        log_event.insert(EXIT_CODE_KEY, code as i64);
+    }
+    if let Some(signal) = signal {
+        log_event.insert(SIGNAL_KEY, signal as i64);
+    }
+    log_event.insert(EXEC_DURATION_KEY, exec_duration.as_secs_f64());
+
+    insert_command_fields(&mut log_event, config, hostname, pid);
+
+    Event::Log(log_event)
+}
+
+fn insert_command_fields(
+    log_event: &mut LogEvent,
+    config: &ExecConfig,
+    hostname: &Option<String>,
+    pid: Option<u32>,
+) {
     // Add pid (if needed)
     if let Some(pid) = pid {
         log_event.insert(PID_KEY, pid as i64);
@@ -481,8 +616,6 @@ fn create_event(
 
     // Add command
     log_event.insert(COMMAND_KEY, config.command.clone());
-
-    Event::Log(log_event)
 }
 
 fn spawn_reader_thread<R: 'static + AsyncRead + Unpin + std::marker::Send>(
@@ -601,12 +734,15 @@ mod tests {
             streaming: Some(StreamingConfig {
                 respawn_on_exit: default_respawn_on_exit(),
                 respawn_interval_secs: default_respawn_interval_secs(),
+                max_respawn_interval_secs: None,
+                max_respawns: None,
             }),
             command: vec!["./runner".to_owned(), "arg1".to_owned(), "arg2".to_owned()],
             working_directory: Some(PathBuf::from("/tmp")),
             include_stderr: default_include_stderr(),
             event_per_line: default_events_per_line(),
             maximum_buffer_size_bytes: default_maximum_buffer_size(),
+            include_exit_event: false,
         };
 
         let command = build_command(&config);
@@ -767,6 +903,64 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    async fn test_run_command_exit_event() {
+        trace_init();
+        let config = ExecConfig {
+            command: vec!["sh".to_owned(), "-c".to_owned(), "exit 3".to_owned()],
+            include_exit_event: true,
+            ..standard_scheduled_test_config()
+        };
+        let (tx, mut rx) = Pipeline::new_test();
+
+        let exit_status = tokio::time::timeout(
+            time::Duration::from_secs(5),
+            run_command(config.clone(), None, ShutdownSignal::noop(), tx),
+        )
+        .await
+        .expect("command timed out")
+        .expect("command error");
+        assert_eq!(Some(3), exit_status.unwrap().code());
+
+        let event = rx.try_next().unwrap().expect("Expected an exit event");
+        let log = event.as_log();
+        assert_eq!(log[EXIT_CODE_KEY], 3_i64.into());
+        assert_eq!(
+            log[log_schema().message_key()],
+            "Command exited with code 3.".into()
+        );
+        assert_eq!(log[COMMAND_KEY], config.command.into());
+        assert!(log.contains(EXEC_DURATION_KEY));
+        assert!(!log.contains(SIGNAL_KEY));
+    }
+
+    #[test]
+    fn test_respawn_backoff() {
+        let secs = Duration::from_secs;
+        let mut respawn = Respawn::new(secs(1), Some(secs(5)), Some(4));
+
+        assert_eq!(respawn.next_delay(secs(0)), Some(secs(1)));
+        assert_eq!(respawn.next_delay(secs(0)), Some(secs(2)));
+        assert_eq!(respawn.next_delay(secs(0)), Some(secs(4)));
+        assert_eq!(respawn.next_delay(secs(0)), Some(secs(5)));
+        assert_eq!(respawn.next_delay(secs(0)), None);
+
+        // Running for long enough starts over
+        assert_eq!(respawn.next_delay(secs(5)), Some(secs(1)));
+        assert_eq!(respawn.next_delay(secs(0)), Some(secs(2)));
+    }
+
+    #[test]
+    fn test_respawn_fixed_interval() {
+        let secs = Duration::from_secs;
+        let mut respawn = Respawn::new(secs(5), None, None);
+
+        for _ in 0..10 {
+            assert_eq!(respawn.next_delay(secs(0)), Some(secs(5)));
+        }
+    }
+
     fn standard_scheduled_test_config() -> ExecConfig {
         Default::default()
     }
@@ -778,12 +972,15 @@ mod tests {
             streaming: Some(StreamingConfig {
                 respawn_on_exit: default_respawn_on_exit(),
                 respawn_interval_secs: default_respawn_interval_secs(),
+                max_respawn_interval_secs: None,
+                max_respawns: None,
             }),
             command: vec!["yes".to_owned()],
             working_directory: None,
             include_stderr: default_include_stderr(),
             event_per_line: default_events_per_line(),
             maximum_buffer_size_bytes: default_maximum_buffer_size(),
+            include_exit_event: false,
         }
     }
 }
@@ -89,6 +89,9 @@ pub struct FileConfig {
     pub remove_after_secs: Option<u64>,
     pub line_delimiter: String,
     pub encoding: Option<EncodingConfig>,
+    pub active_window_secs: Option<u64>,
+    pub checkpoint_retention_secs: Option<u64>,
+    pub max_read_bytes_per_sec: Option<usize>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -190,6 +193,9 @@ impl Default for FileConfig {
             remove_after_secs: None,
             line_delimiter: "\n".to_string(),
             encoding: None,
+            active_window_secs: None,
+            checkpoint_retention_secs: None,
+            max_read_bytes_per_sec: None,
         }
     }
 }
@@ -295,6 +301,9 @@ pub fn file_source(
         },
         oldest_first: config.oldest_first,
         remove_after: config.remove_after_secs.map(Duration::from_secs),
+        active_window: config.active_window_secs.map(Duration::from_secs),
+        checkpoint_retention: config.checkpoint_retention_secs.map(Duration::from_secs),
+        max_read_bytes_per_sec: config.max_read_bytes_per_sec,
         emitter: FileSourceInternalEventsEmitter,
         handle: tokio::runtime::Handle::current(),
     };
@@ -1172,6 +1181,58 @@ mod tests {
         assert_eq!(after_lines, vec!["_first line", "_second line"]);
     }
 
+    #[cfg(unix)] // this test uses unix-specific function `futimes` during test time
+    #[tokio::test]
+    async fn file_active_window_skips_inactive_files() {
+        use std::os::unix::io::AsRawFd;
+        use std::time::{Duration, SystemTime};
+
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("*")],
+            active_window_secs: Some(5),
+            ..test_default_file_config(&dir)
+        };
+
+        let inactive_path = dir.path().join("inactive");
+        let mut inactive_file = File::create(&inactive_path).unwrap();
+        writeln!(&mut inactive_file, "first line").unwrap();
+        {
+            // Set the modified time before the source first looks for files
+            let inactive = SystemTime::now() - Duration::from_secs(8);
+            let inactive_time = libc::timeval {
+                tv_sec: inactive
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as _,
+                tv_usec: 0,
+            };
+            let inactive_times = [inactive_time, inactive_time];
+
+            unsafe {
+                libc::futimes(inactive_file.as_raw_fd(), inactive_times.as_ptr());
+            }
+        }
+
+        let received = run_file_source(&config, false, NoAcks, async {
+            let active_path = dir.path().join("active");
+            let mut active_file = File::create(&active_path).unwrap();
+
+            writeln!(&mut active_file, "_first line").unwrap();
+            sleep_500_millis().await;
+            writeln!(&mut active_file, "_second line").unwrap();
+            sleep_500_millis().await;
+        })
+        .await;
+
+        let files = received
+            .iter()
+            .map(|event| event.as_log()["file"].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 2);
+        assert!(!files.iter().any(|file| file.ends_with("inactive")));
+    }
+
     #[tokio::test]
     async fn file_max_line_bytes() {
         let dir = tempdir().unwrap();
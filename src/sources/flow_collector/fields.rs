@@ -0,0 +1,296 @@
+//! The information elements of IPFIX (RFC 7012), which NetFlow v9 shares, and
+//! their decoding. Fields are named after the IANA names of the elements in
+//! snake case, and those of any protocol use the same names.
+use crate::event::Value;
+use chrono::{TimeZone, Utc};
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// The length of IPFIX fields whose values carry their own length.
+pub(super) const VARIABLE_LENGTH: u16 = 65535;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Kind {
+    Unsigned,
+    Ipv4,
+    Ipv6,
+    Mac,
+    String,
+    Seconds,
+    Milliseconds,
+    Bytes,
+}
+
+const ELEMENTS: &[(u16, &str, Kind)] = &[
+    (1, "octet_delta_count", Kind::Unsigned),
+    (2, "packet_delta_count", Kind::Unsigned),
+    (3, "delta_flow_count", Kind::Unsigned),
+    (4, "protocol_identifier", Kind::Unsigned),
+    (5, "ip_class_of_service", Kind::Unsigned),
+    (6, "tcp_control_bits", Kind::Unsigned),
+    (7, "source_transport_port", Kind::Unsigned),
+    (8, "source_ipv4_address", Kind::Ipv4),
+    (9, "source_ipv4_prefix_length", Kind::Unsigned),
+    (10, "ingress_interface", Kind::Unsigned),
+    (11, "destination_transport_port", Kind::Unsigned),
+    (12, "destination_ipv4_address", Kind::Ipv4),
+    (13, "destination_ipv4_prefix_length", Kind::Unsigned),
+    (14, "egress_interface", Kind::Unsigned),
+    (15, "ip_next_hop_ipv4_address", Kind::Ipv4),
+    (16, "bgp_source_as_number", Kind::Unsigned),
+    (17, "bgp_destination_as_number", Kind::Unsigned),
+    (18, "bgp_next_hop_ipv4_address", Kind::Ipv4),
+    (19, "post_m_cast_packet_delta_count", Kind::Unsigned),
+    (20, "post_m_cast_octet_delta_count", Kind::Unsigned),
+    (21, "flow_end_sys_up_time", Kind::Unsigned),
+    (22, "flow_start_sys_up_time", Kind::Unsigned),
+    (23, "post_octet_delta_count", Kind::Unsigned),
+    (24, "post_packet_delta_count", Kind::Unsigned),
+    (25, "minimum_ip_total_length", Kind::Unsigned),
+    (26, "maximum_ip_total_length", Kind::Unsigned),
+    (27, "source_ipv6_address", Kind::Ipv6),
+    (28, "destination_ipv6_address", Kind::Ipv6),
+    (29, "source_ipv6_prefix_length", Kind::Unsigned),
+    (30, "destination_ipv6_prefix_length", Kind::Unsigned),
+    (31, "flow_label_ipv6", Kind::Unsigned),
+    (32, "icmp_type_code_ipv4", Kind::Unsigned),
+    (33, "igmp_type", Kind::Unsigned),
+    (34, "sampling_interval", Kind::Unsigned),
+    (35, "sampling_algorithm", Kind::Unsigned),
+    (36, "flow_active_timeout", Kind::Unsigned),
+    (37, "flow_idle_timeout", Kind::Unsigned),
+    (38, "engine_type", Kind::Unsigned),
+    (39, "engine_id", Kind::Unsigned),
+    (40, "exported_octet_total_count", Kind::Unsigned),
+    (41, "exported_message_total_count", Kind::Unsigned),
+    (42, "exported_flow_record_total_count", Kind::Unsigned),
+    (44, "source_ipv4_prefix", Kind::Ipv4),
+    (45, "destination_ipv4_prefix", Kind::Ipv4),
+    (46, "mpls_top_label_type", Kind::Unsigned),
+    (47, "mpls_top_label_ipv4_address", Kind::Ipv4),
+    (48, "sampler_id", Kind::Unsigned),
+    (49, "sampler_mode", Kind::Unsigned),
+    (50, "sampler_random_interval", Kind::Unsigned),
+    (52, "minimum_ttl", Kind::Unsigned),
+    (53, "maximum_ttl", Kind::Unsigned),
+    (54, "fragment_identification", Kind::Unsigned),
+    (55, "post_ip_class_of_service", Kind::Unsigned),
+    (56, "source_mac_address", Kind::Mac),
+    (57, "post_destination_mac_address", Kind::Mac),
+    (58, "vlan_id", Kind::Unsigned),
+    (59, "post_vlan_id", Kind::Unsigned),
+    (60, "ip_version", Kind::Unsigned),
+    (61, "flow_direction", Kind::Unsigned),
+    (62, "ip_next_hop_ipv6_address", Kind::Ipv6),
+    (63, "bgp_next_hop_ipv6_address", Kind::Ipv6),
+    (64, "ipv6_extension_headers", Kind::Unsigned),
+    (70, "mpls_top_label_stack_section", Kind::Bytes),
+    (80, "destination_mac_address", Kind::Mac),
+    (81, "post_source_mac_address", Kind::Mac),
+    (82, "interface_name", Kind::String),
+    (83, "interface_description", Kind::String),
+    (84, "sampler_name", Kind::String),
+    (85, "octet_total_count", Kind::Unsigned),
+    (86, "packet_total_count", Kind::Unsigned),
+    (88, "fragment_offset", Kind::Unsigned),
+    (89, "forwarding_status", Kind::Unsigned),
+    (90, "mpls_vpn_route_distinguisher", Kind::Bytes),
+    (94, "application_description", Kind::String),
+    (95, "application_id", Kind::Bytes),
+    (96, "application_name", Kind::String),
+    (128, "bgp_next_adjacent_as_number", Kind::Unsigned),
+    (129, "bgp_prev_adjacent_as_number", Kind::Unsigned),
+    (130, "exporter_ipv4_address", Kind::Ipv4),
+    (131, "exporter_ipv6_address", Kind::Ipv6),
+    (132, "dropped_octet_delta_count", Kind::Unsigned),
+    (133, "dropped_packet_delta_count", Kind::Unsigned),
+    (136, "flow_end_reason", Kind::Unsigned),
+    (137, "common_properties_id", Kind::Unsigned),
+    (138, "observation_point_id", Kind::Unsigned),
+    (139, "icmp_type_code_ipv6", Kind::Unsigned),
+    (144, "exporting_process_id", Kind::Unsigned),
+    (148, "flow_id", Kind::Unsigned),
+    (149, "observation_domain_id", Kind::Unsigned),
+    (150, "flow_start_seconds", Kind::Seconds),
+    (151, "flow_end_seconds", Kind::Seconds),
+    (152, "flow_start_milliseconds", Kind::Milliseconds),
+    (153, "flow_end_milliseconds", Kind::Milliseconds),
+    (160, "system_init_time_milliseconds", Kind::Milliseconds),
+    (161, "flow_duration_milliseconds", Kind::Unsigned),
+    (176, "icmp_type_ipv4", Kind::Unsigned),
+    (177, "icmp_code_ipv4", Kind::Unsigned),
+    (178, "icmp_type_ipv6", Kind::Unsigned),
+    (179, "icmp_code_ipv6", Kind::Unsigned),
+    (180, "udp_source_port", Kind::Unsigned),
+    (181, "udp_destination_port", Kind::Unsigned),
+    (182, "tcp_source_port", Kind::Unsigned),
+    (183, "tcp_destination_port", Kind::Unsigned),
+    (184, "tcp_sequence_number", Kind::Unsigned),
+    (185, "tcp_acknowledgement_number", Kind::Unsigned),
+    (186, "tcp_window_size", Kind::Unsigned),
+    (192, "ip_ttl", Kind::Unsigned),
+    (195, "ip_diff_serv_code_point", Kind::Unsigned),
+    (196, "ip_precedence", Kind::Unsigned),
+    (197, "fragment_flags", Kind::Unsigned),
+    (224, "ip_total_length", Kind::Unsigned),
+    (225, "post_nat_source_ipv4_address", Kind::Ipv4),
+    (226, "post_nat_destination_ipv4_address", Kind::Ipv4),
+    (227, "post_napt_source_transport_port", Kind::Unsigned),
+    (228, "post_napt_destination_transport_port", Kind::Unsigned),
+    (230, "nat_event", Kind::Unsigned),
+    (234, "ingress_vrf_id", Kind::Unsigned),
+    (235, "egress_vrf_id", Kind::Unsigned),
+    (236, "vrf_name", Kind::String),
+    (239, "biflow_direction", Kind::Unsigned),
+    (243, "dot1q_vlan_id", Kind::Unsigned),
+    (244, "dot1q_priority", Kind::Unsigned),
+    (245, "dot1q_customer_vlan_id", Kind::Unsigned),
+    (252, "ingress_physical_interface", Kind::Unsigned),
+    (253, "egress_physical_interface", Kind::Unsigned),
+    (256, "ethernet_type", Kind::Unsigned),
+    (281, "post_nat_source_ipv6_address", Kind::Ipv6),
+    (282, "post_nat_destination_ipv6_address", Kind::Ipv6),
+    (323, "observation_time_milliseconds", Kind::Milliseconds),
+];
+
+/// The scope fields of NetFlow v9 options, which have types of their own.
+const V9_SCOPES: &[&str] = &[
+    "scope_system",
+    "scope_interface",
+    "scope_line_card",
+    "scope_cache",
+    "scope_template",
+];
+
+/// A field of a template, resolved to the element it holds when the template
+/// is read.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Field {
+    pub(super) name: Cow<'static, str>,
+    pub(super) kind: Kind,
+    pub(super) len: u16,
+}
+
+impl Field {
+    pub(super) fn new(id: u16, enterprise: Option<u32>, len: u16) -> Self {
+        let (name, kind) = match enterprise {
+            Some(enterprise) => (
+                Cow::Owned(format!("enterprise_{}_{}", enterprise, id)),
+                Kind::Bytes,
+            ),
+            None => ELEMENTS
+                .iter()
+                .find(|(element, _, _)| *element == id)
+                .map_or_else(
+                    || (Cow::Owned(format!("element_{}", id)), Kind::Bytes),
+                    |(_, name, kind)| (Cow::Borrowed(*name), *kind),
+                ),
+        };
+        Self { name, kind, len }
+    }
+
+    pub(super) fn v9_scope(id: u16, len: u16) -> Self {
+        let name = V9_SCOPES.get(usize::from(id).wrapping_sub(1)).map_or_else(
+            || Cow::Owned(format!("scope_{}", id)),
+            |name| Cow::Borrowed(*name),
+        );
+        Self {
+            name,
+            kind: Kind::Unsigned,
+            len,
+        }
+    }
+
+    pub(super) fn value(&self, bytes: &[u8]) -> Value {
+        match (self.kind, bytes.len()) {
+            (Kind::Unsigned, 1..=8) => unsigned(bytes),
+            (Kind::Ipv4, 4) => Value::from(Ipv4Addr::from(array(bytes)).to_string()),
+            (Kind::Ipv6, 16) => Value::from(Ipv6Addr::from(array(bytes)).to_string()),
+            (Kind::Mac, 6) => Value::from(hex(bytes)),
+            (Kind::String, _) => {
+                let len = bytes
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(bytes.len());
+                Value::from(String::from_utf8_lossy(&bytes[..len]).into_owned())
+            }
+            (Kind::Seconds, 4) => Utc
+                .timestamp_opt(i64::from(u32::from_be_bytes(array(bytes))), 0)
+                .single()
+                .map_or(Value::Null, Value::from),
+            (Kind::Milliseconds, 8) => i64::try_from(u64::from_be_bytes(array(bytes)))
+                .ok()
+                .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+                .map_or(Value::Null, Value::from),
+            _ => Value::from(hex(bytes)),
+        }
+    }
+}
+
+/// Unsigned values past the largest integer are kept whole as strings.
+pub(super) fn unsigned(bytes: &[u8]) -> Value {
+    let value = bytes
+        .iter()
+        .fold(0_u64, |value, &byte| (value << 8) | u64::from(byte));
+    i64::try_from(value).map_or_else(|_| Value::from(value.to_string()), Value::from)
+}
+
+pub(super) fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    bytes.try_into().expect("slice of the array length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_known_elements() {
+        let field = Field::new(8, None, 4);
+        assert_eq!(field.name, "source_ipv4_address");
+        assert_eq!(field.value(&[10, 0, 0, 1]), Value::from("10.0.0.1"));
+
+        // Reduced-size encoding of a 64 bit counter.
+        let field = Field::new(1, None, 4);
+        assert_eq!(field.value(&[0, 0, 1, 0]), Value::from(256));
+
+        let field = Field::new(152, None, 8);
+        assert_eq!(
+            field.value(&1_600_000_000_123_u64.to_be_bytes()),
+            Value::from(Utc.timestamp(1_600_000_000, 123_000_000))
+        );
+
+        let field = Field::new(82, None, 8);
+        assert_eq!(field.value(b"eth0\0\0\0\0"), Value::from("eth0"));
+    }
+
+    #[test]
+    fn names_unknown_elements() {
+        let field = Field::new(999, None, 2);
+        assert_eq!(field.name, "element_999");
+        assert_eq!(field.value(&[0xab, 0xcd]), Value::from("ab:cd"));
+
+        let field = Field::new(1, Some(9), 4);
+        assert_eq!(field.name, "enterprise_9_1");
+
+        assert_eq!(Field::v9_scope(2, 4).name, "scope_interface");
+    }
+
+    #[test]
+    fn keeps_large_counters_whole() {
+        assert_eq!(
+            unsigned(&u64::MAX.to_be_bytes()),
+            Value::from(u64::MAX.to_string())
+        );
+    }
+}
@@ -0,0 +1,264 @@
+mod fields;
+mod netflow_v5;
+mod reader;
+mod sflow;
+mod templates;
+
+use self::templates::Templates;
+use crate::{
+    config::{log_schema, DataType, Resource, SourceConfig, SourceContext, SourceDescription},
+    event::{Event, LogEvent},
+    internal_events::{
+        FlowCollectorEventsReceived, FlowCollectorInvalidDatagram, FlowCollectorSocketError,
+    },
+    shutdown::ShutdownSignal,
+    udp, Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{stream, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+
+/// Large enough for any datagram.
+const MAX_DATAGRAM_SIZE: usize = 65535;
+
+#[derive(Debug, Snafu)]
+pub enum DecodeError {
+    #[snafu(display("Datagram is truncated"))]
+    Truncated,
+    #[snafu(display("Unsupported version {}", version))]
+    UnsupportedVersion { version: u16 },
+    #[snafu(display("Invalid length {}", len))]
+    InvalidLength { len: usize },
+    #[snafu(display("Invalid address type {}", address_type))]
+    InvalidAddressType { address_type: u32 },
+}
+
+impl From<reader::Truncated> for DecodeError {
+    fn from(_: reader::Truncated) -> Self {
+        Self::Truncated
+    }
+}
+
+/// Receives the flows of NetFlow v5 and v9, IPFIX and sFlow v5 exporters, all
+/// on the same address.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlowCollectorConfig {
+    #[serde(default = "default_address")]
+    address: SocketAddr,
+    /// Forget the templates of NetFlow v9 and IPFIX exporters that aren't
+    /// sent again within this many seconds.
+    #[serde(default = "default_template_timeout_secs")]
+    template_timeout_secs: u64,
+    host_key: Option<String>,
+    receive_buffer_bytes: Option<usize>,
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 2055))
+}
+
+const fn default_template_timeout_secs() -> u64 {
+    1800
+}
+
+inventory::submit! {
+    SourceDescription::new::<FlowCollectorConfig>("flow_collector")
+}
+
+impl_generate_config_from_default!(FlowCollectorConfig);
+
+impl Default for FlowCollectorConfig {
+    fn default() -> Self {
+        Self {
+            address: default_address(),
+            template_timeout_secs: default_template_timeout_secs(),
+            host_key: None,
+            receive_buffer_bytes: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "flow_collector")]
+impl SourceConfig for FlowCollectorConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let templates = Templates::new(Duration::from_secs(self.template_timeout_secs));
+        let host_key = self
+            .host_key
+            .clone()
+            .unwrap_or_else(|| log_schema().host_key().to_owned());
+
+        Ok(Box::pin(flow_collector_source(
+            self.address,
+            self.receive_buffer_bytes,
+            templates,
+            host_key,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "flow_collector"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::udp(self.address)]
+    }
+}
+
+/// Decodes a datagram of any of the protocols, going by its version.
+fn decode(
+    datagram: &[u8],
+    exporter: SocketAddr,
+    templates: &mut Templates,
+) -> Result<Vec<LogEvent>, DecodeError> {
+    // The version of sFlow takes 32 bits and those of the others 16, so no
+    // other version starts with zeros.
+    match *datagram {
+        [0, 0, 0, 5, ..] => sflow::decode(datagram),
+        [0, 5, ..] => netflow_v5::decode(datagram),
+        [0, 9, ..] => templates::decode_v9(datagram, exporter, templates),
+        [0, 10, ..] => templates::decode_ipfix(datagram, exporter, templates),
+        [high, low, ..] => Err(DecodeError::UnsupportedVersion {
+            version: u16::from_be_bytes([high, low]),
+        }),
+        _ => Err(DecodeError::Truncated),
+    }
+}
+
+async fn flow_collector_source(
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+    mut templates: Templates,
+    host_key: String,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&address)
+        .await
+        .map_err(|error| emit!(FlowCollectorSocketError::bind(error)))?;
+
+    if let Some(receive_buffer_bytes) = receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    info!(
+        message = "Listening.",
+        addr = %address,
+        r#type = "udp"
+    );
+
+    let mut datagram = vec![0; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, exporter) = tokio::select! {
+            _ = &mut shutdown => break,
+            received = socket.recv_from(&mut datagram) => match received {
+                Ok(received) => received,
+                Err(error) => {
+                    emit!(FlowCollectorSocketError::read(error));
+                    continue;
+                }
+            },
+        };
+
+        let logs = match decode(&datagram[..len], exporter, &mut templates) {
+            Ok(logs) => logs,
+            Err(error) => {
+                emit!(FlowCollectorInvalidDatagram { error, exporter });
+                continue;
+            }
+        };
+        emit!(FlowCollectorEventsReceived {
+            count: logs.len(),
+            byte_size: len
+        });
+
+        let now = Utc::now();
+        let events = logs.into_iter().map(|mut log| {
+            log.insert(host_key.as_str(), exporter.ip().to_string());
+            if !log.contains(log_schema().timestamp_key()) {
+                log.insert(log_schema().timestamp_key(), now);
+            }
+            log.insert(
+                log_schema().source_type_key(),
+                Bytes::from("flow_collector"),
+            );
+            Ok(Event::from(log))
+        });
+        if let Err(error) = out.send_all(&mut stream::iter(events)).await {
+            error!(message = "Error sending to sink.", %error);
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr};
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<FlowCollectorConfig>();
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let exporter = "192.0.2.1:2055".parse().unwrap();
+
+        assert!(matches!(
+            decode(&[0, 7, 0, 0], exporter, &mut templates),
+            Err(DecodeError::UnsupportedVersion { version: 7 })
+        ));
+        assert!(matches!(
+            decode(&[0], exporter, &mut templates),
+            Err(DecodeError::Truncated)
+        ));
+    }
+
+    #[tokio::test]
+    async fn receives_flows() {
+        let address = next_addr();
+        let (tx, rx) = Pipeline::new_test();
+        let config = FlowCollectorConfig {
+            address,
+            ..FlowCollectorConfig::default()
+        };
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // A NetFlow v5 datagram of a single record.
+        let mut datagram = vec![0, 5, 0, 1];
+        datagram.extend_from_slice(&[0; 20]);
+        datagram.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+        datagram.extend_from_slice(&[0; 40]);
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(&datagram, address).await.unwrap();
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["flow_type"], "netflow_v5".into());
+        assert_eq!(log["source_ipv4_address"], "10.0.0.1".into());
+        assert_eq!(log[log_schema().host_key()], "127.0.0.1".into());
+        assert_eq!(log[log_schema().source_type_key()], "flow_collector".into());
+    }
+}
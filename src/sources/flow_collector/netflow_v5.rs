@@ -0,0 +1,109 @@
+//! NetFlow v5, whose records have a fixed layout, read into the fields of
+//! the IPFIX elements they hold.
+use super::{reader::Reader, DecodeError};
+use crate::{config::log_schema, event::LogEvent};
+use chrono::{TimeZone, Utc};
+
+const RECORD_LEN: usize = 48;
+
+pub(super) fn decode(data: &[u8]) -> Result<Vec<LogEvent>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let _version = reader.u16()?;
+    let count = reader.u16()?;
+    let sys_up_time = reader.u32()?;
+    let unix_secs = reader.u32()?;
+    let unix_nsecs = reader.u32()?;
+    let sequence_number = reader.u32()?;
+    let engine_type = reader.u8()?;
+    let engine_id = reader.u8()?;
+    let sampling = reader.u16()?;
+
+    let mut header = LogEvent::default();
+    header.insert("flow_type", "netflow_v5");
+    header.insert("sequence_number", sequence_number);
+    header.insert("sys_up_time", sys_up_time);
+    header.insert("engine_type", engine_type);
+    header.insert("engine_id", engine_id);
+    // The top two bits are the sampling mode.
+    header.insert("sampling_interval", sampling & 0x3FFF);
+    if let Some(timestamp) = Utc.timestamp_opt(i64::from(unix_secs), unix_nsecs).single() {
+        header.insert(log_schema().timestamp_key(), timestamp);
+    }
+
+    let mut logs = Vec::with_capacity(usize::from(count).min(reader.len() / RECORD_LEN));
+    for _ in 0..count {
+        let mut record = reader.sub(RECORD_LEN)?;
+        let mut log = header.clone();
+        log.insert("record_type", "flow");
+        log.insert("source_ipv4_address", record.ipv4()?.to_string());
+        log.insert("destination_ipv4_address", record.ipv4()?.to_string());
+        log.insert("ip_next_hop_ipv4_address", record.ipv4()?.to_string());
+        log.insert("ingress_interface", record.u16()?);
+        log.insert("egress_interface", record.u16()?);
+        log.insert("packet_delta_count", record.u32()?);
+        log.insert("octet_delta_count", record.u32()?);
+        log.insert("flow_start_sys_up_time", record.u32()?);
+        log.insert("flow_end_sys_up_time", record.u32()?);
+        log.insert("source_transport_port", record.u16()?);
+        log.insert("destination_transport_port", record.u16()?);
+        record.skip(1)?;
+        log.insert("tcp_control_bits", record.u8()?);
+        log.insert("protocol_identifier", record.u8()?);
+        log.insert("ip_class_of_service", record.u8()?);
+        log.insert("bgp_source_as_number", record.u16()?);
+        log.insert("bgp_destination_as_number", record.u16()?);
+        log.insert("source_ipv4_prefix_length", record.u8()?);
+        log.insert("destination_ipv4_prefix_length", record.u8()?);
+        logs.push(log);
+    }
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    #[test]
+    fn decodes_records() {
+        let mut datagram = vec![0, 5, 0, 1];
+        datagram.extend_from_slice(&1_000_u32.to_be_bytes());
+        datagram.extend_from_slice(&1_600_000_000_u32.to_be_bytes());
+        datagram.extend_from_slice(&500_000_000_u32.to_be_bytes());
+        datagram.extend_from_slice(&7_u32.to_be_bytes());
+        datagram.extend_from_slice(&[1, 2, 0x40, 10]);
+        datagram.extend_from_slice(&[
+            10, 0, 0, 1, 10, 0, 0, 2, 10, 0, 0, 254, 0, 1, 0, 2, 0, 0, 0, 3, 0, 0, 1, 0, 0, 0, 0,
+            100, 0, 0, 0, 200, 0x1F, 0x90, 0x01, 0xBB, 0, 0x12, 6, 0, 0xFD, 0xE8, 0xFD, 0xE9, 24,
+            16, 0, 0,
+        ]);
+
+        let logs = decode(&datagram).unwrap();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log["flow_type"], Value::from("netflow_v5"));
+        assert_eq!(log["sampling_interval"], Value::from(10));
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(1_600_000_000, 500_000_000))
+        );
+        assert_eq!(log["source_ipv4_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["destination_ipv4_address"], Value::from("10.0.0.2"));
+        assert_eq!(log["ip_next_hop_ipv4_address"], Value::from("10.0.0.254"));
+        assert_eq!(log["packet_delta_count"], Value::from(3));
+        assert_eq!(log["octet_delta_count"], Value::from(256));
+        assert_eq!(log["source_transport_port"], Value::from(8080));
+        assert_eq!(log["destination_transport_port"], Value::from(443));
+        assert_eq!(log["tcp_control_bits"], Value::from(0x12));
+        assert_eq!(log["protocol_identifier"], Value::from(6));
+        assert_eq!(log["bgp_source_as_number"], Value::from(65000));
+        assert_eq!(log["source_ipv4_prefix_length"], Value::from(24));
+    }
+
+    #[test]
+    fn rejects_truncated_records() {
+        let mut datagram = vec![0, 5, 0, 2];
+        datagram.extend_from_slice(&[0; 20 + RECORD_LEN]);
+        assert!(matches!(decode(&datagram), Err(DecodeError::Truncated)));
+    }
+}
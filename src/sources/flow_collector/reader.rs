@@ -0,0 +1,74 @@
+use snafu::Snafu;
+use std::{
+    convert::TryInto,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+#[derive(Debug, PartialEq, Snafu)]
+#[snafu(display("Unexpected end of datagram"))]
+pub struct Truncated;
+
+/// Reads the big-endian fields of a datagram one after another.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(super) const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub(super) fn bytes(&mut self, len: usize) -> Result<&'a [u8], Truncated> {
+        if self.data.len() < len {
+            return Err(Truncated);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Truncated> {
+        Ok(self
+            .bytes(N)?
+            .try_into()
+            .expect("slice of the array length"))
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8, Truncated> {
+        self.array::<1>().map(|[byte]| byte)
+    }
+
+    pub(super) fn u16(&mut self) -> Result<u16, Truncated> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, Truncated> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    pub(super) fn u64(&mut self) -> Result<u64, Truncated> {
+        self.array().map(u64::from_be_bytes)
+    }
+
+    pub(super) fn ipv4(&mut self) -> Result<Ipv4Addr, Truncated> {
+        self.array::<4>().map(Ipv4Addr::from)
+    }
+
+    pub(super) fn ipv6(&mut self) -> Result<Ipv6Addr, Truncated> {
+        self.array::<16>().map(Ipv6Addr::from)
+    }
+
+    /// Reads a nested structure of the given length.
+    pub(super) fn sub(&mut self, len: usize) -> Result<Reader<'a>, Truncated> {
+        self.bytes(len).map(Reader::new)
+    }
+
+    pub(super) fn skip(&mut self, len: usize) -> Result<(), Truncated> {
+        self.bytes(len).map(|_| ())
+    }
+}
@@ -0,0 +1,378 @@
+//! sFlow v5, whose flow samples are of the headers of sampled packets, read
+//! into the same fields as the flows of NetFlow and IPFIX.
+use super::{fields, reader::Reader, DecodeError};
+use crate::event::{LogEvent, Value};
+use std::net::IpAddr;
+
+const FLOW_SAMPLE: u32 = 1;
+const COUNTERS_SAMPLE: u32 = 2;
+const EXPANDED_FLOW_SAMPLE: u32 = 3;
+const EXPANDED_COUNTERS_SAMPLE: u32 = 4;
+
+const RAW_PACKET_HEADER: u32 = 1;
+const SAMPLED_IPV4: u32 = 3;
+const SAMPLED_IPV6: u32 = 4;
+const EXTENDED_SWITCH: u32 = 1001;
+const EXTENDED_ROUTER: u32 = 1002;
+
+const GENERIC_INTERFACE_COUNTERS: u32 = 1;
+
+const HEADER_ETHERNET: u32 = 1;
+const HEADER_IPV4: u32 = 11;
+const HEADER_IPV6: u32 = 12;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+pub(super) fn decode(data: &[u8]) -> Result<Vec<LogEvent>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let _version = reader.u32()?;
+    let agent_address = address(&mut reader)?;
+    let sub_agent_id = reader.u32()?;
+    let sequence_number = reader.u32()?;
+    let sys_up_time = reader.u32()?;
+    let count = reader.u32()?;
+
+    let mut header = LogEvent::default();
+    header.insert("flow_type", "sflow_v5");
+    header.insert("agent_address", agent_address.to_string());
+    header.insert("sub_agent_id", sub_agent_id);
+    header.insert("sequence_number", sequence_number);
+    header.insert("sys_up_time", sys_up_time);
+
+    let mut logs = Vec::new();
+    for _ in 0..count {
+        let format = reader.u32()?;
+        let len = reader.u32()? as usize;
+        let mut sample = reader.sub(len)?;
+        let mut log = header.clone();
+        match format {
+            FLOW_SAMPLE | EXPANDED_FLOW_SAMPLE => {
+                log.insert("record_type", "flow");
+                log.insert("sample_sequence_number", sample.u32()?);
+                source_id(&mut sample, format == EXPANDED_FLOW_SAMPLE, &mut log)?;
+                log.insert("sampling_rate", sample.u32()?);
+                log.insert("sample_pool", sample.u32()?);
+                log.insert("drops", sample.u32()?);
+                for name in &["ingress_interface", "egress_interface"] {
+                    let interface = if format == EXPANDED_FLOW_SAMPLE {
+                        let interface_format = sample.u32()?;
+                        let interface = sample.u32()?;
+                        (interface_format == 0).then(|| interface)
+                    } else {
+                        // The top two bits are the format of the rest, of
+                        // which only zero is a single interface.
+                        let interface = sample.u32()?;
+                        (interface >> 30 == 0).then(|| interface)
+                    };
+                    if let Some(interface) = interface {
+                        log.insert(*name, interface);
+                    }
+                }
+                records(&mut sample, &mut log, flow_record)?;
+            }
+            COUNTERS_SAMPLE | EXPANDED_COUNTERS_SAMPLE => {
+                log.insert("record_type", "counters");
+                log.insert("sample_sequence_number", sample.u32()?);
+                source_id(&mut sample, format == EXPANDED_COUNTERS_SAMPLE, &mut log)?;
+                records(&mut sample, &mut log, counter_record)?;
+            }
+            // Enterprise formats are in the top bits, and none are known.
+            _ => continue,
+        }
+        logs.push(log);
+    }
+    Ok(logs)
+}
+
+fn address(reader: &mut Reader<'_>) -> Result<IpAddr, DecodeError> {
+    match reader.u32()? {
+        1 => Ok(reader.ipv4()?.into()),
+        2 => Ok(reader.ipv6()?.into()),
+        address_type => Err(DecodeError::InvalidAddressType { address_type }),
+    }
+}
+
+fn source_id(
+    sample: &mut Reader<'_>,
+    expanded: bool,
+    log: &mut LogEvent,
+) -> Result<(), DecodeError> {
+    let (source_type, index) = if expanded {
+        (sample.u32()?, sample.u32()?)
+    } else {
+        let id = sample.u32()?;
+        (id >> 24, id & 0x00FF_FFFF)
+    };
+    log.insert("source_id_type", source_type);
+    log.insert("source_id_index", index);
+    Ok(())
+}
+
+fn records(
+    sample: &mut Reader<'_>,
+    log: &mut LogEvent,
+    record: fn(u32, Reader<'_>, &mut LogEvent) -> Result<(), DecodeError>,
+) -> Result<(), DecodeError> {
+    for _ in 0..sample.u32()? {
+        let format = sample.u32()?;
+        let len = sample.u32()? as usize;
+        record(format, sample.sub(len)?, log)?;
+    }
+    Ok(())
+}
+
+fn flow_record(format: u32, mut record: Reader<'_>, log: &mut LogEvent) -> Result<(), DecodeError> {
+    match format {
+        RAW_PACKET_HEADER => {
+            let protocol = record.u32()?;
+            log.insert("frame_length", record.u32()?);
+            let _stripped = record.u32()?;
+            let len = record.u32()? as usize;
+            let mut packet = record.sub(len)?;
+            // Headers are cut at the sampled length, so keep whatever of them
+            // is there.
+            let _ = match protocol {
+                HEADER_ETHERNET => ethernet(&mut packet, log),
+                HEADER_IPV4 | HEADER_IPV6 => ip(&mut packet, log),
+                _ => Ok(()),
+            };
+        }
+        SAMPLED_IPV4 | SAMPLED_IPV6 => {
+            log.insert("ip_total_length", record.u32()?);
+            log.insert("protocol_identifier", record.u32()?);
+            if format == SAMPLED_IPV4 {
+                log.insert("source_ipv4_address", record.ipv4()?.to_string());
+                log.insert("destination_ipv4_address", record.ipv4()?.to_string());
+            } else {
+                log.insert("source_ipv6_address", record.ipv6()?.to_string());
+                log.insert("destination_ipv6_address", record.ipv6()?.to_string());
+            }
+            log.insert("source_transport_port", record.u32()?);
+            log.insert("destination_transport_port", record.u32()?);
+            log.insert("tcp_control_bits", record.u32()?);
+            log.insert("ip_class_of_service", record.u32()?);
+        }
+        EXTENDED_SWITCH => {
+            log.insert("vlan_id", record.u32()?);
+            let _source_priority = record.u32()?;
+            log.insert("post_vlan_id", record.u32()?);
+        }
+        EXTENDED_ROUTER => {
+            match address(&mut record)? {
+                IpAddr::V4(next_hop) => {
+                    log.insert("ip_next_hop_ipv4_address", next_hop.to_string())
+                }
+                IpAddr::V6(next_hop) => {
+                    log.insert("ip_next_hop_ipv6_address", next_hop.to_string())
+                }
+            };
+            log.insert("source_prefix_length", record.u32()?);
+            log.insert("destination_prefix_length", record.u32()?);
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn counter_record(
+    format: u32,
+    mut record: Reader<'_>,
+    log: &mut LogEvent,
+) -> Result<(), DecodeError> {
+    if format != GENERIC_INTERFACE_COUNTERS {
+        return Ok(());
+    }
+    log.insert("if_index", record.u32()?);
+    log.insert("if_type", record.u32()?);
+    log.insert("if_speed", counter64(record.u64()?));
+    log.insert("if_direction", record.u32()?);
+    log.insert("if_status", record.u32()?);
+    log.insert("if_in_octets", counter64(record.u64()?));
+    for name in &[
+        "if_in_ucast_pkts",
+        "if_in_multicast_pkts",
+        "if_in_broadcast_pkts",
+        "if_in_discards",
+        "if_in_errors",
+        "if_in_unknown_protos",
+    ] {
+        log.insert(*name, record.u32()?);
+    }
+    log.insert("if_out_octets", counter64(record.u64()?));
+    for name in &[
+        "if_out_ucast_pkts",
+        "if_out_multicast_pkts",
+        "if_out_broadcast_pkts",
+        "if_out_discards",
+        "if_out_errors",
+        "if_promiscuous_mode",
+    ] {
+        log.insert(*name, record.u32()?);
+    }
+    Ok(())
+}
+
+fn counter64(value: u64) -> Value {
+    fields::unsigned(&value.to_be_bytes())
+}
+
+fn ethernet(packet: &mut Reader<'_>, log: &mut LogEvent) -> Result<(), DecodeError> {
+    log.insert("destination_mac_address", fields::hex(packet.bytes(6)?));
+    log.insert("source_mac_address", fields::hex(packet.bytes(6)?));
+    let mut ethernet_type = packet.u16()?;
+    if ethernet_type == ETHERTYPE_VLAN {
+        log.insert("vlan_id", packet.u16()? & 0x0FFF);
+        ethernet_type = packet.u16()?;
+    }
+    log.insert("ethernet_type", ethernet_type);
+    match ethernet_type {
+        ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => ip(packet, log),
+        _ => Ok(()),
+    }
+}
+
+fn ip(packet: &mut Reader<'_>, log: &mut LogEvent) -> Result<(), DecodeError> {
+    let first = packet.u8()?;
+    let protocol = match first >> 4 {
+        4 => {
+            let header_len = usize::from(first & 0x0F) * 4;
+            log.insert("ip_version", 4);
+            log.insert("ip_class_of_service", packet.u8()?);
+            log.insert("ip_total_length", packet.u16()?);
+            packet.skip(4)?;
+            log.insert("ip_ttl", packet.u8()?);
+            let protocol = packet.u8()?;
+            log.insert("protocol_identifier", protocol);
+            packet.skip(2)?;
+            log.insert("source_ipv4_address", packet.ipv4()?.to_string());
+            log.insert("destination_ipv4_address", packet.ipv4()?.to_string());
+            packet.skip(header_len.saturating_sub(20))?;
+            protocol
+        }
+        6 => {
+            let traffic_class = (first << 4) | (packet.u8()? >> 4);
+            log.insert("ip_version", 6);
+            log.insert("ip_class_of_service", traffic_class);
+            // The rest of the flow label and the payload length.
+            packet.skip(4)?;
+            let protocol = packet.u8()?;
+            log.insert("protocol_identifier", protocol);
+            log.insert("ip_ttl", packet.u8()?);
+            log.insert("source_ipv6_address", packet.ipv6()?.to_string());
+            log.insert("destination_ipv6_address", packet.ipv6()?.to_string());
+            protocol
+        }
+        _ => return Ok(()),
+    };
+    match protocol {
+        PROTOCOL_TCP | PROTOCOL_UDP => {
+            log.insert("source_transport_port", packet.u16()?);
+            log.insert("destination_transport_port", packet.u16()?);
+            if protocol == PROTOCOL_TCP {
+                packet.skip(8)?;
+                log.insert("tcp_control_bits", packet.u16()? & 0x01FF);
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| word.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    fn datagram(samples: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut datagram = words(&[5, 1, 0xC0_00_02_01, 0, 7, 1_000, samples.len() as u32]);
+        for (format, sample) in samples {
+            datagram.extend(words(&[*format, sample.len() as u32]));
+            datagram.extend_from_slice(sample);
+        }
+        datagram
+    }
+
+    #[test]
+    fn decodes_flow_samples() {
+        let mut packet = vec![
+            0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 1, 0x81, 0x00, 0x00, 0x0A, 0x08, 0x00,
+        ];
+        packet.extend_from_slice(&[
+            0x45, 0, 0, 60, 0, 0, 0, 0, 64, 6, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2,
+        ]);
+        packet.extend_from_slice(&[0x1F, 0x90, 0x01, 0xBB, 0, 0, 0, 0, 0, 0, 0, 0, 0x50, 0x02]);
+        packet.resize(64, 0);
+
+        let mut sample = words(&[1, 3, 512, 2_048, 0, 4, 5, 1, RAW_PACKET_HEADER]);
+        sample.extend(words(&[
+            16 + packet.len() as u32,
+            HEADER_ETHERNET,
+            74,
+            0,
+            packet.len() as u32,
+        ]));
+        sample.extend_from_slice(&packet);
+
+        let logs = decode(&datagram(&[(FLOW_SAMPLE, sample)])).unwrap();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log["flow_type"], Value::from("sflow_v5"));
+        assert_eq!(log["agent_address"], Value::from("192.0.2.1"));
+        assert_eq!(log["record_type"], Value::from("flow"));
+        assert_eq!(log["source_id_index"], Value::from(3));
+        assert_eq!(log["sampling_rate"], Value::from(512));
+        assert_eq!(log["ingress_interface"], Value::from(4));
+        assert_eq!(log["frame_length"], Value::from(74));
+        assert_eq!(log["source_mac_address"], Value::from("00:00:00:00:00:01"));
+        assert_eq!(log["vlan_id"], Value::from(10));
+        assert_eq!(log["source_ipv4_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["destination_ipv4_address"], Value::from("10.0.0.2"));
+        assert_eq!(log["protocol_identifier"], Value::from(6));
+        assert_eq!(log["source_transport_port"], Value::from(8080));
+        assert_eq!(log["destination_transport_port"], Value::from(443));
+        assert_eq!(log["tcp_control_bits"], Value::from(2));
+    }
+
+    #[test]
+    fn decodes_counter_samples() {
+        let mut record = words(&[4, 6]);
+        record.extend_from_slice(&10_000_000_000_u64.to_be_bytes());
+        record.extend(words(&[1, 3]));
+        record.extend_from_slice(&1_234_u64.to_be_bytes());
+        record.extend(words(&[1, 2, 3, 4, 5, 6]));
+        record.extend_from_slice(&u64::MAX.to_be_bytes());
+        record.extend(words(&[7, 8, 9, 10, 11, 0]));
+
+        let mut sample = words(&[1, 3, 1, GENERIC_INTERFACE_COUNTERS, record.len() as u32]);
+        sample.extend(record);
+
+        let logs = decode(&datagram(&[(COUNTERS_SAMPLE, sample)])).unwrap();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log["record_type"], Value::from("counters"));
+        assert_eq!(log["if_index"], Value::from(4));
+        assert_eq!(log["if_speed"], Value::from(10_000_000_000_i64));
+        assert_eq!(log["if_in_octets"], Value::from(1_234));
+        assert_eq!(log["if_out_octets"], Value::from(u64::MAX.to_string()));
+        assert_eq!(log["if_out_errors"], Value::from(11));
+    }
+
+    #[test]
+    fn rejects_invalid_agent_addresses() {
+        assert!(matches!(
+            decode(&words(&[5, 3])),
+            Err(DecodeError::InvalidAddressType { address_type: 3 })
+        ));
+    }
+}
@@ -0,0 +1,463 @@
+//! NetFlow v9 (RFC 3954) and IPFIX (RFC 7011), whose records can only be read
+//! with the templates exporters send apart from them.
+use super::{
+    fields::{Field, VARIABLE_LENGTH},
+    reader::Reader,
+    DecodeError,
+};
+use crate::{config::log_schema, event::LogEvent, internal_events::FlowCollectorTemplateMissing};
+use chrono::{TimeZone, Utc};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+const V9_TEMPLATE_SET: u16 = 0;
+const V9_OPTIONS_TEMPLATE_SET: u16 = 1;
+const IPFIX_TEMPLATE_SET: u16 = 2;
+const IPFIX_OPTIONS_TEMPLATE_SET: u16 = 3;
+/// Sets below this are templates or reserved, and the rest data.
+const MIN_DATA_SET: u16 = 256;
+
+const SET_HEADER_LEN: u16 = 4;
+const ENTERPRISE_BIT: u16 = 0x8000;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Protocol {
+    NetflowV9,
+    Ipfix,
+}
+
+/// Templates are scoped to the exporter and its observation domain, which
+/// NetFlow v9 calls the source ID.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct TemplateKey {
+    exporter: SocketAddr,
+    protocol: Protocol,
+    domain: u32,
+    id: u16,
+}
+
+#[derive(Debug)]
+struct Template {
+    /// The first fields of options templates are scope fields.
+    options: bool,
+    fields: Vec<Field>,
+    received: Instant,
+}
+
+/// The templates of every exporter, which are forgotten if they aren't sent
+/// again in time.
+#[derive(Debug)]
+pub(super) struct Templates {
+    templates: HashMap<TemplateKey, Template>,
+    timeout: Duration,
+}
+
+impl Templates {
+    pub(super) fn new(timeout: Duration) -> Self {
+        Self {
+            templates: HashMap::new(),
+            timeout,
+        }
+    }
+
+    fn insert(&mut self, key: TemplateKey, options: bool, fields: Vec<Field>) {
+        let template = Template {
+            options,
+            fields,
+            received: Instant::now(),
+        };
+        self.templates.insert(key, template);
+    }
+
+    fn get(&mut self, key: &TemplateKey) -> Option<&Template> {
+        let timeout = self.timeout;
+        if self
+            .templates
+            .get(key)
+            .map_or(false, |template| template.received.elapsed() > timeout)
+        {
+            self.templates.remove(key);
+        }
+        self.templates.get(key)
+    }
+
+    /// Withdraws a template, or all of the templates of the domain.
+    fn withdraw(&mut self, key: TemplateKey, all: bool) {
+        if all {
+            self.templates.retain(|other, _| {
+                (other.exporter, other.protocol, other.domain)
+                    != (key.exporter, key.protocol, key.domain)
+            });
+        } else {
+            self.templates.remove(&key);
+        }
+    }
+}
+
+pub(super) fn decode_v9(
+    data: &[u8],
+    exporter: SocketAddr,
+    templates: &mut Templates,
+) -> Result<Vec<LogEvent>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let _version = reader.u16()?;
+    let _count = reader.u16()?;
+    let sys_up_time = reader.u32()?;
+    let unix_secs = reader.u32()?;
+    let sequence_number = reader.u32()?;
+    let source_id = reader.u32()?;
+
+    let mut header = LogEvent::default();
+    header.insert("flow_type", "netflow_v9");
+    header.insert("sequence_number", sequence_number);
+    header.insert("source_id", source_id);
+    header.insert("sys_up_time", sys_up_time);
+    if let Some(timestamp) = Utc.timestamp_opt(i64::from(unix_secs), 0).single() {
+        header.insert(log_schema().timestamp_key(), timestamp);
+    }
+
+    let key = |id| TemplateKey {
+        exporter,
+        protocol: Protocol::NetflowV9,
+        domain: source_id,
+        id,
+    };
+    let mut logs = Vec::new();
+    while let Some((set_id, mut set)) = next_set(&mut reader)? {
+        match set_id {
+            V9_TEMPLATE_SET => {
+                while set.len() >= 4 {
+                    let id = set.u16()?;
+                    let count = set.u16()?;
+                    let mut fields = Vec::new();
+                    for _ in 0..count {
+                        fields.push(Field::new(set.u16()?, None, set.u16()?));
+                    }
+                    templates.insert(key(id), false, fields);
+                }
+            }
+            V9_OPTIONS_TEMPLATE_SET => {
+                // Options templates are padded to a multiple of four bytes.
+                while set.len() >= 6 {
+                    let id = set.u16()?;
+                    let scope_len = set.u16()?;
+                    let options_len = set.u16()?;
+                    let mut fields = Vec::new();
+                    for _ in 0..scope_len / 4 {
+                        fields.push(Field::v9_scope(set.u16()?, set.u16()?));
+                    }
+                    for _ in 0..options_len / 4 {
+                        fields.push(Field::new(set.u16()?, None, set.u16()?));
+                    }
+                    templates.insert(key(id), true, fields);
+                }
+            }
+            id if id >= MIN_DATA_SET => match templates.get(&key(id)) {
+                Some(template) => records(&header, template, set, false, &mut logs)?,
+                None => emit!(FlowCollectorTemplateMissing {
+                    exporter,
+                    template_id: id
+                }),
+            },
+            _ => (),
+        }
+    }
+    Ok(logs)
+}
+
+pub(super) fn decode_ipfix(
+    data: &[u8],
+    exporter: SocketAddr,
+    templates: &mut Templates,
+) -> Result<Vec<LogEvent>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let _version = reader.u16()?;
+    let len = usize::from(reader.u16()?);
+    let export_time = reader.u32()?;
+    let sequence_number = reader.u32()?;
+    let domain = reader.u32()?;
+    // The message may be followed by other data in the datagram.
+    let mut reader = Reader::new(
+        data.get(16..len)
+            .ok_or(DecodeError::InvalidLength { len })?,
+    );
+
+    let mut header = LogEvent::default();
+    header.insert("flow_type", "ipfix");
+    header.insert("sequence_number", sequence_number);
+    header.insert("observation_domain_id", domain);
+    if let Some(timestamp) = Utc.timestamp_opt(i64::from(export_time), 0).single() {
+        header.insert(log_schema().timestamp_key(), timestamp);
+    }
+
+    let key = |id| TemplateKey {
+        exporter,
+        protocol: Protocol::Ipfix,
+        domain,
+        id,
+    };
+    let mut logs = Vec::new();
+    while let Some((set_id, mut set)) = next_set(&mut reader)? {
+        match set_id {
+            IPFIX_TEMPLATE_SET | IPFIX_OPTIONS_TEMPLATE_SET => {
+                let options = set_id == IPFIX_OPTIONS_TEMPLATE_SET;
+                while set.len() >= 4 {
+                    let id = set.u16()?;
+                    let count = set.u16()?;
+                    if count == 0 {
+                        // Withdrawing the ID of the set withdraws every
+                        // template of the domain.
+                        templates.withdraw(key(id), id == set_id);
+                        continue;
+                    }
+                    if options {
+                        let _scope_count = set.u16()?;
+                    }
+                    let mut fields = Vec::new();
+                    for _ in 0..count {
+                        let id = set.u16()?;
+                        let len = set.u16()?;
+                        let enterprise = if id & ENTERPRISE_BIT == 0 {
+                            None
+                        } else {
+                            Some(set.u32()?)
+                        };
+                        fields.push(Field::new(id & !ENTERPRISE_BIT, enterprise, len));
+                    }
+                    templates.insert(key(id), options, fields);
+                }
+            }
+            id if id >= MIN_DATA_SET => match templates.get(&key(id)) {
+                Some(template) => records(&header, template, set, true, &mut logs)?,
+                None => emit!(FlowCollectorTemplateMissing {
+                    exporter,
+                    template_id: id
+                }),
+            },
+            _ => (),
+        }
+    }
+    Ok(logs)
+}
+
+fn next_set<'a>(reader: &mut Reader<'a>) -> Result<Option<(u16, Reader<'a>)>, DecodeError> {
+    if reader.len() < usize::from(SET_HEADER_LEN) {
+        return Ok(None);
+    }
+    let id = reader.u16()?;
+    let len = reader.u16()?;
+    if len < SET_HEADER_LEN {
+        return Err(DecodeError::InvalidLength {
+            len: usize::from(len),
+        });
+    }
+    let set = reader.sub(usize::from(len - SET_HEADER_LEN))?;
+    Ok(Some((id, set)))
+}
+
+/// Reads the records of a data set, until what is left is too short for a
+/// record and so is padding.
+fn records(
+    header: &LogEvent,
+    template: &Template,
+    mut set: Reader<'_>,
+    variable_lengths: bool,
+    logs: &mut Vec<LogEvent>,
+) -> Result<(), DecodeError> {
+    let min_len = template
+        .fields
+        .iter()
+        .map(|field| match field.len {
+            VARIABLE_LENGTH if variable_lengths => 1,
+            len => usize::from(len),
+        })
+        .sum::<usize>()
+        .max(1);
+
+    while set.len() >= min_len {
+        let mut log = header.clone();
+        log.insert(
+            "record_type",
+            if template.options { "options" } else { "flow" },
+        );
+        for field in &template.fields {
+            let len = field_len(&mut set, field.len, variable_lengths)?;
+            let value = set.bytes(len)?;
+            log.insert(field.name.as_ref(), field.value(value));
+        }
+        logs.push(log);
+    }
+    Ok(())
+}
+
+/// IPFIX values of variable length are prefixed with theirs, in one byte or
+/// three for the longer ones.
+fn field_len(set: &mut Reader<'_>, len: u16, variable_lengths: bool) -> Result<usize, DecodeError> {
+    if len != VARIABLE_LENGTH || !variable_lengths {
+        return Ok(usize::from(len));
+    }
+    match set.u8()? {
+        255 => Ok(usize::from(set.u16()?)),
+        len => Ok(usize::from(len)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    fn exporter() -> SocketAddr {
+        "192.0.2.1:2055".parse().unwrap()
+    }
+
+    fn set(id: u16, body: &[u8]) -> Vec<u8> {
+        let mut set = id.to_be_bytes().to_vec();
+        set.extend_from_slice(&(body.len() as u16 + SET_HEADER_LEN).to_be_bytes());
+        set.extend_from_slice(body);
+        set
+    }
+
+    fn v9(sets: &[Vec<u8>]) -> Vec<u8> {
+        let mut message = vec![0, 9, 0, sets.len() as u8];
+        message.extend_from_slice(&1_000_u32.to_be_bytes());
+        message.extend_from_slice(&1_600_000_000_u32.to_be_bytes());
+        message.extend_from_slice(&7_u32.to_be_bytes());
+        message.extend_from_slice(&42_u32.to_be_bytes());
+        message.extend(sets.concat());
+        message
+    }
+
+    fn ipfix(sets: &[Vec<u8>]) -> Vec<u8> {
+        let sets = sets.concat();
+        let mut message = vec![0, 10];
+        message.extend_from_slice(&(sets.len() as u16 + 16).to_be_bytes());
+        message.extend_from_slice(&1_600_000_000_u32.to_be_bytes());
+        message.extend_from_slice(&7_u32.to_be_bytes());
+        message.extend_from_slice(&42_u32.to_be_bytes());
+        message.extend(sets);
+        message
+    }
+
+    #[test]
+    fn decodes_v9_with_template() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let template = set(V9_TEMPLATE_SET, &[1, 0, 0, 2, 0, 8, 0, 4, 0, 1, 0, 4]);
+        let data = set(256, &[10, 0, 0, 1, 0, 0, 1, 0, 0, 0]);
+
+        let logs = decode_v9(&v9(&[template, data]), exporter(), &mut templates).unwrap();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log["flow_type"], Value::from("netflow_v9"));
+        assert_eq!(log["record_type"], Value::from("flow"));
+        assert_eq!(log["source_id"], Value::from(42));
+        assert_eq!(log["source_ipv4_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["octet_delta_count"], Value::from(256));
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Value::from(Utc.timestamp(1_600_000_000, 0))
+        );
+    }
+
+    #[test]
+    fn decodes_v9_options() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let template = set(
+            V9_OPTIONS_TEMPLATE_SET,
+            &[1, 1, 0, 4, 0, 4, 0, 1, 0, 4, 0, 34, 0, 4, 0, 0],
+        );
+        let data = set(257, &[0, 0, 0, 9, 0, 0, 0, 100]);
+
+        let logs = decode_v9(&v9(&[template, data]), exporter(), &mut templates).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["record_type"], Value::from("options"));
+        assert_eq!(logs[0]["scope_system"], Value::from(9));
+        assert_eq!(logs[0]["sampling_interval"], Value::from(100));
+    }
+
+    #[test]
+    fn decodes_ipfix_with_variable_lengths() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let template = set(
+            IPFIX_TEMPLATE_SET,
+            &[
+                1, 1, 0, 3, 0, 8, 0, 4, 0, 82, 255, 255, 0x80, 1, 0, 2, 0, 0, 0, 9,
+            ],
+        );
+        let message = ipfix(&[template]);
+        assert!(decode_ipfix(&message, exporter(), &mut templates)
+            .unwrap()
+            .is_empty());
+
+        // Templates are kept between messages.
+        let data = set(257, &[10, 0, 0, 1, 4, b'e', b't', b'h', b'0', 0xab, 0xcd]);
+        let logs = decode_ipfix(&ipfix(&[data]), exporter(), &mut templates).unwrap();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log["flow_type"], Value::from("ipfix"));
+        assert_eq!(log["observation_domain_id"], Value::from(42));
+        assert_eq!(log["source_ipv4_address"], Value::from("10.0.0.1"));
+        assert_eq!(log["interface_name"], Value::from("eth0"));
+        assert_eq!(log["enterprise_9_1"], Value::from("ab:cd"));
+    }
+
+    #[test]
+    fn scopes_templates_to_exporters() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let template = set(IPFIX_TEMPLATE_SET, &[1, 0, 0, 1, 0, 8, 0, 4]);
+        let data = set(256, &[10, 0, 0, 1]);
+        decode_ipfix(&ipfix(&[template]), exporter(), &mut templates).unwrap();
+
+        let other = "192.0.2.2:2055".parse().unwrap();
+        let message = ipfix(std::slice::from_ref(&data));
+        assert!(decode_ipfix(&message, other, &mut templates)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            decode_ipfix(&message, exporter(), &mut templates)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Withdrawing the template set ID withdraws all of them.
+        let withdrawal = set(IPFIX_TEMPLATE_SET, &[0, 2, 0, 0]);
+        decode_ipfix(&ipfix(&[withdrawal, data]), exporter(), &mut templates).unwrap();
+        assert!(decode_ipfix(&message, exporter(), &mut templates)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn expires_templates() {
+        let mut templates = Templates::new(Duration::from_secs(0));
+        let template = set(IPFIX_TEMPLATE_SET, &[1, 0, 0, 1, 0, 8, 0, 4]);
+        decode_ipfix(&ipfix(&[template]), exporter(), &mut templates).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+
+        let data = set(256, &[10, 0, 0, 1]);
+        assert!(decode_ipfix(&ipfix(&[data]), exporter(), &mut templates)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        let mut templates = Templates::new(Duration::from_secs(60));
+        let mut message = ipfix(&[set(256, &[0; 4])]);
+        message[3] = 40;
+        assert!(matches!(
+            decode_ipfix(&message, exporter(), &mut templates),
+            Err(DecodeError::InvalidLength { len: 40 })
+        ));
+
+        let message = v9(&[vec![1, 0, 0, 2]]);
+        assert!(matches!(
+            decode_v9(&message, exporter(), &mut templates),
+            Err(DecodeError::InvalidLength { len: 2 })
+        ));
+    }
+}
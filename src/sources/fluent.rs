@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     io::{self, Read},
+    sync::Arc,
 };
 use tokio_util::codec::Decoder;
 
@@ -30,6 +31,25 @@ pub struct FluentConfig {
     tls: Option<TlsConfig>,
     keepalive: Option<TcpKeepaliveConfig>,
     receive_buffer_bytes: Option<usize>,
+    security: Option<FluentSecurityConfig>,
+}
+
+/// The shared key, and optionally the users, which clients in secure mode
+/// authenticate with in the handshake.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FluentSecurityConfig {
+    shared_key: String,
+    self_hostname: Option<String>,
+    #[serde(default)]
+    users: Vec<FluentUserConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FluentUserConfig {
+    username: String,
+    password: String,
 }
 
 inventory::submit! {
@@ -43,6 +63,7 @@ impl GenerateConfig for FluentConfig {
             keepalive: None,
             tls: None,
             receive_buffer_bytes: None,
+            security: None,
         })
         .unwrap()
     }
@@ -52,7 +73,18 @@ impl GenerateConfig for FluentConfig {
 #[typetag::serde(name = "fluent")]
 impl SourceConfig for FluentConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
-        let source = FluentSource {};
+        let security = match &self.security {
+            Some(security) => Some(Arc::new(Security {
+                shared_key: security.shared_key.clone(),
+                self_hostname: match &security.self_hostname {
+                    Some(self_hostname) => self_hostname.clone(),
+                    None => crate::get_hostname()?,
+                },
+                users: security.users.clone(),
+            })),
+            None => None,
+        };
+        let source = FluentSource { security };
         let shutdown_secs = 30;
         let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
         source.run(
@@ -61,6 +93,7 @@ impl SourceConfig for FluentConfig {
             shutdown_secs,
             tls,
             self.receive_buffer_bytes,
+            None,
             cx.shutdown,
             cx.out,
         )
@@ -80,17 +113,33 @@ impl SourceConfig for FluentConfig {
 }
 
 #[derive(Debug, Clone)]
-struct FluentSource;
+struct FluentSource {
+    security: Option<Arc<Security>>,
+}
+
+#[derive(Debug)]
+struct Security {
+    shared_key: String,
+    self_hostname: String,
+    users: Vec<FluentUserConfig>,
+}
 
 impl TcpSource for FluentSource {
     type Error = DecodeError;
     type Decoder = FluentDecoder;
 
     fn decoder(&self) -> Self::Decoder {
-        FluentDecoder::new()
+        match &self.security {
+            Some(security) => FluentDecoder::secure(Arc::clone(security)),
+            None => FluentDecoder::new(),
+        }
     }
 
-    fn build_event(&self, frame: FluentFrame, host: Bytes) -> Option<Event> {
+    fn build_event(&self, item: FluentItem, host: Bytes) -> Option<Event> {
+        let frame = match item {
+            FluentItem::Event(frame, _) => frame,
+            FluentItem::Reply(_) => return None,
+        };
         let mut log = LogEvent::from(frame);
 
         if !log.contains(log_schema().host_key()) {
@@ -99,6 +148,22 @@ impl TcpSource for FluentSource {
 
         Some(Event::from(log))
     }
+
+    fn build_ack(&self, item: &FluentItem) -> Bytes {
+        match item {
+            FluentItem::Event(_, Some(chunk)) => encode_ack(chunk),
+            FluentItem::Event(_, None) => Bytes::new(),
+            FluentItem::Reply(reply) => reply.clone(),
+        }
+    }
+
+    fn build_greeting(&self, decoder: &FluentDecoder) -> Bytes {
+        decoder
+            .handshake
+            .as_ref()
+            .map(Handshake::helo)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug)]
@@ -107,6 +172,7 @@ pub enum DecodeError {
     Decode(decode::Error),
     UnknownCompression(String),
     UnexpectedValue(rmpv::Value),
+    Unauthenticated,
 }
 
 impl std::fmt::Display for DecodeError {
@@ -120,6 +186,7 @@ impl std::fmt::Display for DecodeError {
             DecodeError::UnexpectedValue(value) => {
                 write!(f, "unexpected msgpack value, ignoring: {}", value)
             }
+            DecodeError::Unauthenticated => write!(f, "connection is not authenticated"),
         }
     }
 }
@@ -131,6 +198,7 @@ impl TcpIsErrorFatal for DecodeError {
             DecodeError::Decode(_) => false,
             DecodeError::UnknownCompression(_) => false,
             DecodeError::UnexpectedValue(_) => false,
+            DecodeError::Unauthenticated => true,
         }
     }
 }
@@ -150,37 +218,77 @@ impl From<decode::Error> for DecodeError {
 #[derive(Debug)]
 struct FluentDecoder {
     // unread frames from previous fluent message
-    unread_frames: VecDeque<FluentFrame>,
+    unread_frames: VecDeque<FluentItem>,
+    // the secure forward handshake, if required
+    handshake: Option<Handshake>,
 }
 
 impl FluentDecoder {
     fn new() -> Self {
         FluentDecoder {
             unread_frames: VecDeque::new(),
+            handshake: None,
+        }
+    }
+
+    fn secure(security: Arc<Security>) -> Self {
+        FluentDecoder {
+            unread_frames: VecDeque::new(),
+            handshake: Some(Handshake::new(security)),
         }
     }
 
     fn handle_message(&mut self, message: FluentMessage) -> Result<(), DecodeError> {
+        if let Some(handshake) = &mut self.handshake {
+            if !handshake.is_authenticated() {
+                let pong = handshake.handle_ping(message)?;
+                self.unread_frames.push_back(FluentItem::Reply(pong));
+                return Ok(());
+            }
+        }
+
+        let chunk = self.handle_events(message)?;
+
+        // The chunk is acknowledged once the last event of the message is
+        // sent, or right away if there is none.
+        if let Some(chunk) = chunk {
+            match self.unread_frames.back_mut() {
+                Some(FluentItem::Event(_, ack)) => *ack = Some(chunk),
+                _ => self
+                    .unread_frames
+                    .push_back(FluentItem::Reply(encode_ack(&chunk))),
+            }
+        }
+        Ok(())
+    }
+
+    /// Queues the events of the message, returning the chunk to acknowledge
+    /// if the client asked for it.
+    fn handle_events(&mut self, message: FluentMessage) -> Result<Option<String>, DecodeError> {
         match message {
-            FluentMessage::Message(tag, timestamp, record)
-            | FluentMessage::MessageWithOptions(tag, timestamp, record, ..) => {
-                self.unread_frames.push_back(FluentFrame {
+            FluentMessage::Message(tag, timestamp, record) => {
+                self.push_frame(FluentFrame {
                     tag,
                     timestamp,
                     record,
                 });
-                Ok(())
+                Ok(None)
             }
-            FluentMessage::Forward(tag, entries)
-            | FluentMessage::ForwardWithOptions(tag, entries, ..) => {
-                self.unread_frames.extend(entries.into_iter().map(
-                    |FluentEntry(timestamp, record)| FluentFrame {
-                        tag: tag.clone(),
-                        timestamp,
-                        record,
-                    },
-                ));
-                Ok(())
+            FluentMessage::MessageWithOptions(tag, timestamp, record, options) => {
+                self.push_frame(FluentFrame {
+                    tag,
+                    timestamp,
+                    record,
+                });
+                Ok(options.chunk)
+            }
+            FluentMessage::Forward(tag, entries) => {
+                self.push_entries(&tag, entries);
+                Ok(None)
+            }
+            FluentMessage::ForwardWithOptions(tag, entries, options) => {
+                self.push_entries(&tag, entries);
+                Ok(options.chunk)
             }
             FluentMessage::PackedForward(tag, bin) => {
                 let mut buf = BytesMut::from(&bin[..]);
@@ -188,13 +296,13 @@ impl FluentDecoder {
                 let mut decoder = FluentEntryStreamDecoder;
 
                 while let Some(FluentEntry(timestamp, record)) = decoder.decode(&mut buf)? {
-                    self.unread_frames.push_back(FluentFrame {
+                    self.push_frame(FluentFrame {
                         tag: tag.clone(),
                         timestamp,
                         record,
                     });
                 }
-                Ok(())
+                Ok(None)
             }
             FluentMessage::PackedForwardWithOptions(tag, bin, options) => {
                 let buf = match options.compressed.as_deref() {
@@ -214,22 +322,206 @@ impl FluentDecoder {
                 let mut decoder = FluentEntryStreamDecoder;
 
                 while let Some(FluentEntry(timestamp, record)) = decoder.decode(&mut buf)? {
-                    self.unread_frames.push_back(FluentFrame {
+                    self.push_frame(FluentFrame {
                         tag: tag.clone(),
                         timestamp,
                         record,
                     });
                 }
-                Ok(())
+                Ok(options.chunk)
             }
-            FluentMessage::Heartbeat(rmpv::Value::Nil) => Ok(()),
+            FluentMessage::Heartbeat(rmpv::Value::Nil) => Ok(None),
             FluentMessage::Heartbeat(value) => Err(DecodeError::UnexpectedValue(value)),
         }
     }
+
+    fn push_frame(&mut self, frame: FluentFrame) {
+        self.unread_frames.push_back(FluentItem::Event(frame, None));
+    }
+
+    fn push_entries(&mut self, tag: &str, entries: Vec<FluentEntry>) {
+        for FluentEntry(timestamp, record) in entries {
+            self.push_frame(FluentFrame {
+                tag: tag.to_owned(),
+                timestamp,
+                record,
+            });
+        }
+    }
+}
+
+/// The handshake of the forward protocol's secure mode, in which the server
+/// sends a `HELO` with a nonce, and the client authenticates with a `PING`
+/// hashing it with the shared key, which the server answers with a `PONG`.
+///
+/// https://github.com/fluent/fluentd/wiki/Forward-Protocol-Specification-v1#handshake-messages
+#[derive(Debug)]
+struct Handshake {
+    security: Arc<Security>,
+    nonce: [u8; 16],
+    // salt for the users' passwords, empty without users
+    auth_salt: Vec<u8>,
+    state: HandshakeState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HandshakeState {
+    AwaitingPing,
+    Authenticated,
+    Failed,
+}
+
+impl Handshake {
+    fn new(security: Arc<Security>) -> Self {
+        let auth_salt = if security.users.is_empty() {
+            Vec::new()
+        } else {
+            rand::random::<[u8; 16]>().to_vec()
+        };
+        Self {
+            security,
+            nonce: rand::random(),
+            auth_salt,
+            state: HandshakeState::AwaitingPing,
+        }
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.state == HandshakeState::Authenticated
+    }
+
+    fn helo(&self) -> Bytes {
+        encode(rmpv::Value::Array(vec![
+            "HELO".into(),
+            rmpv::Value::Map(vec![
+                ("nonce".into(), self.nonce.to_vec().into()),
+                ("auth".into(), self.auth_salt.clone().into()),
+                ("keepalive".into(), true.into()),
+            ]),
+        ]))
+    }
+
+    /// Checks the client's `PING`, returning the `PONG` to answer it with.
+    /// Anything else, or anything after a failed `PING`, ends the connection.
+    fn handle_ping(&mut self, message: FluentMessage) -> Result<Bytes, DecodeError> {
+        let ping = match (self.state, message) {
+            (HandshakeState::AwaitingPing, FluentMessage::Heartbeat(value)) => Ping::parse(&value),
+            _ => None,
+        }
+        .ok_or(DecodeError::Unauthenticated)?;
+
+        let security = &self.security;
+        let shared_key_digest = digest(&[
+            ping.shared_key_salt,
+            ping.hostname,
+            &self.nonce,
+            security.shared_key.as_bytes(),
+        ]);
+        let user_authenticated = security.users.is_empty()
+            || security.users.iter().any(|user| {
+                user.username.as_bytes() == ping.username
+                    && digest(&[
+                        &self.auth_salt,
+                        user.username.as_bytes(),
+                        user.password.as_bytes(),
+                    ])
+                    .as_bytes()
+                        == ping.password_digest
+            });
+
+        let pong = if shared_key_digest.as_bytes() != ping.shared_key_digest {
+            self.state = HandshakeState::Failed;
+            pong(false, "shared_key mismatch", &security.self_hostname, "")
+        } else if !user_authenticated {
+            self.state = HandshakeState::Failed;
+            pong(
+                false,
+                "username/password mismatch",
+                &security.self_hostname,
+                "",
+            )
+        } else {
+            self.state = HandshakeState::Authenticated;
+            let digest = digest(&[
+                ping.shared_key_salt,
+                security.self_hostname.as_bytes(),
+                &self.nonce,
+                security.shared_key.as_bytes(),
+            ]);
+            pong(true, "", &security.self_hostname, &digest)
+        };
+        Ok(pong)
+    }
+}
+
+/// The client's `PING`, whose fields may be either strings or binaries.
+struct Ping<'a> {
+    hostname: &'a [u8],
+    shared_key_salt: &'a [u8],
+    shared_key_digest: &'a [u8],
+    username: &'a [u8],
+    password_digest: &'a [u8],
+}
+
+impl<'a> Ping<'a> {
+    fn parse(value: &'a rmpv::Value) -> Option<Self> {
+        let bytes = |value: &'a rmpv::Value| match value {
+            rmpv::Value::String(value) => Some(value.as_bytes()),
+            rmpv::Value::Binary(value) => Some(value.as_slice()),
+            _ => None,
+        };
+        match value.as_array()?.as_slice() {
+            [kind, hostname, shared_key_salt, shared_key_digest, username, password_digest]
+                if kind.as_str() == Some("PING") =>
+            {
+                Some(Self {
+                    hostname: bytes(hostname)?,
+                    shared_key_salt: bytes(shared_key_salt)?,
+                    shared_key_digest: bytes(shared_key_digest)?,
+                    username: bytes(username)?,
+                    password_digest: bytes(password_digest)?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn pong(authenticated: bool, reason: &str, self_hostname: &str, digest: &str) -> Bytes {
+    encode(rmpv::Value::Array(vec![
+        "PONG".into(),
+        authenticated.into(),
+        reason.into(),
+        self_hostname.into(),
+        digest.into(),
+    ]))
+}
+
+/// The hex encoded SHA-512 digest of the parts.
+fn digest(parts: &[&[u8]]) -> String {
+    let mut hasher = openssl::sha::Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher
+        .finish()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn encode_ack(chunk: &str) -> Bytes {
+    encode(rmpv::Value::Map(vec![("ack".into(), chunk.into())]))
+}
+
+fn encode(value: rmpv::Value) -> Bytes {
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &value).expect("Writing to a Vec can't fail");
+    buf.into()
 }
 
 impl Decoder for FluentDecoder {
-    type Item = FluentFrame;
+    type Item = FluentItem;
     type Error = DecodeError;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
@@ -320,6 +612,14 @@ impl Decoder for FluentEntryStreamDecoder {
     }
 }
 
+/// Decoded from a connection, an event along with the chunk to acknowledge
+/// once it is sent, or a reply to send back to the client.
+#[derive(Debug)]
+enum FluentItem {
+    Event(FluentFrame, Option<String>),
+    Reply(Bytes),
+}
+
 /// Normalized fluent message.
 #[derive(Debug, PartialEq)]
 struct FluentFrame {
@@ -348,10 +648,16 @@ impl From<FluentFrame> for LogEvent {
 
 #[cfg(test)]
 mod tests {
-    use crate::sources::fluent::{DecodeError, FluentConfig, FluentDecoder};
+    use crate::sources::fluent::{
+        digest, encode, encode_ack, DecodeError, FluentConfig, FluentDecoder, FluentItem,
+        FluentSource, FluentUserConfig, Security,
+    };
+    use crate::sources::util::TcpSource;
+    use bytes::Bytes;
     use bytes::BytesMut;
     use chrono::DateTime;
     use shared::{assert_event_data_eq, btreemap};
+    use std::sync::Arc;
     use tokio_util::codec::Decoder;
     use vector_core::event::{LogEvent, Value};
 
@@ -582,14 +888,185 @@ mod tests {
         assert_event_data_eq!(got[2], expected[2]);
     }
 
+    #[test]
+    fn acks_chunk_after_last_event() {
+        //[
+        //  "tag.name",
+        //  [[1441588984, {"message": "foo"}], [1441588985, {"message": "bar"}]],
+        //  {"chunk": "p8n9gmxTQVC8/nh2wlKKeQ=="}
+        //]
+        let message = encode(rmpv::Value::Array(vec![
+            "tag.name".into(),
+            rmpv::Value::Array(vec![entry(1441588984, "foo"), entry(1441588985, "bar")]),
+            rmpv::Value::Map(vec![("chunk".into(), "p8n9gmxTQVC8/nh2wlKKeQ==".into())]),
+        ]));
+
+        let acks = decode_items(FluentDecoder::new(), &message)
+            .unwrap()
+            .iter()
+            .map(|item| FluentSource { security: None }.build_ack(item))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            acks,
+            vec![Bytes::new(), encode_ack("p8n9gmxTQVC8/nh2wlKKeQ==")]
+        );
+
+        let ack = rmpv::decode::read_value(&mut &acks[1][..]).unwrap();
+        assert_eq!(
+            ack,
+            rmpv::Value::Map(vec![("ack".into(), "p8n9gmxTQVC8/nh2wlKKeQ==".into())])
+        );
+    }
+
+    #[test]
+    fn secure_forward_handshake() {
+        let decoder = FluentDecoder::secure(security(vec![]));
+        let nonce = decoder.handshake.as_ref().unwrap().nonce;
+        let greeting = FluentSource { security: None }.build_greeting(&decoder);
+        let helo = rmpv::decode::read_value(&mut &greeting[..]).unwrap();
+        assert_eq!(helo[0].as_str(), Some("HELO"));
+
+        let mut message = ping("secret", &nonce, "", "").to_vec();
+        message.extend_from_slice(&encode(rmpv::Value::Array(vec![
+            "tag.name".into(),
+            rmpv::Value::Array(vec![entry(1441588984, "foo")]),
+        ])));
+        let items = decode_items(decoder, &message).unwrap();
+
+        let pong = match &items[0] {
+            FluentItem::Reply(pong) => rmpv::decode::read_value(&mut &pong[..]).unwrap(),
+            item => panic!("expected a PONG, got {:?}", item),
+        };
+        assert_eq!(
+            pong,
+            rmpv::Value::Array(vec![
+                "PONG".into(),
+                true.into(),
+                "".into(),
+                "vector".into(),
+                digest(&[b"salt", b"vector", &nonce, b"secret"]).into(),
+            ])
+        );
+        assert!(matches!(items[1], FluentItem::Event(..)));
+    }
+
+    #[test]
+    fn secure_forward_rejects_wrong_shared_key() {
+        let mut decoder = FluentDecoder::secure(security(vec![]));
+        let nonce = decoder.handshake.as_ref().unwrap().nonce;
+
+        let mut buf = BytesMut::from(&ping("wrong", &nonce, "", "")[..]);
+        match decoder.decode(&mut buf).unwrap() {
+            Some(FluentItem::Reply(pong)) => {
+                let pong = rmpv::decode::read_value(&mut &pong[..]).unwrap();
+                assert_eq!(pong[1], rmpv::Value::from(false));
+                assert_eq!(pong[2].as_str(), Some("shared_key mismatch"));
+            }
+            item => panic!("expected a PONG, got {:?}", item),
+        }
+
+        let mut buf = BytesMut::from(&ping("secret", &nonce, "", "")[..]);
+        assert!(matches!(
+            decoder.decode(&mut buf),
+            Err(DecodeError::Unauthenticated)
+        ));
+    }
+
+    #[test]
+    fn secure_forward_authenticates_users() {
+        let users = vec![FluentUserConfig {
+            username: "alice".to_owned(),
+            password: "hunter2".to_owned(),
+        }];
+        let mut decoder = FluentDecoder::secure(security(users));
+        let handshake = decoder.handshake.as_ref().unwrap();
+        let nonce = handshake.nonce;
+        let password_digest = digest(&[&handshake.auth_salt, b"alice", b"hunter2"]);
+
+        let mut buf = BytesMut::from(&ping("secret", &nonce, "alice", &password_digest)[..]);
+        match decoder.decode(&mut buf).unwrap() {
+            Some(FluentItem::Reply(pong)) => {
+                let pong = rmpv::decode::read_value(&mut &pong[..]).unwrap();
+                assert_eq!(pong[1], rmpv::Value::from(true));
+            }
+            item => panic!("expected a PONG, got {:?}", item),
+        }
+
+        let mut decoder = FluentDecoder::secure(security(vec![FluentUserConfig {
+            username: "alice".to_owned(),
+            password: "hunter2".to_owned(),
+        }]));
+        let nonce = decoder.handshake.as_ref().unwrap().nonce;
+        let mut buf = BytesMut::from(&ping("secret", &nonce, "alice", &password_digest)[..]);
+        match decoder.decode(&mut buf).unwrap() {
+            Some(FluentItem::Reply(pong)) => {
+                let pong = rmpv::decode::read_value(&mut &pong[..]).unwrap();
+                assert_eq!(pong[2].as_str(), Some("username/password mismatch"));
+            }
+            item => panic!("expected a PONG, got {:?}", item),
+        }
+    }
+
+    #[test]
+    fn secure_forward_requires_ping() {
+        let message = encode(rmpv::Value::Array(vec![
+            "tag.name".into(),
+            rmpv::Value::Array(vec![entry(1441588984, "foo")]),
+        ]));
+        assert!(matches!(
+            decode_items(FluentDecoder::secure(security(vec![])), &message),
+            Err(DecodeError::Unauthenticated)
+        ));
+    }
+
+    fn security(users: Vec<FluentUserConfig>) -> Arc<Security> {
+        Arc::new(Security {
+            shared_key: "secret".to_owned(),
+            self_hostname: "vector".to_owned(),
+            users,
+        })
+    }
+
+    fn entry(timestamp: u32, message: &str) -> rmpv::Value {
+        rmpv::Value::Array(vec![
+            timestamp.into(),
+            rmpv::Value::Map(vec![("message".into(), message.into())]),
+        ])
+    }
+
+    fn ping(shared_key: &str, nonce: &[u8], username: &str, password_digest: &str) -> Bytes {
+        encode(rmpv::Value::Array(vec![
+            "PING".into(),
+            "client".into(),
+            "salt".into(),
+            digest(&[b"salt", b"client", nonce, shared_key.as_bytes()]).into(),
+            username.into(),
+            password_digest.into(),
+        ]))
+    }
+
+    fn decode_items(
+        mut decoder: FluentDecoder,
+        message: &[u8],
+    ) -> Result<Vec<FluentItem>, DecodeError> {
+        let mut buf = BytesMut::from(message);
+        let mut items = vec![];
+        while let Some(item) = decoder.decode(&mut buf)? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+
     fn decode_all(message: Vec<u8>) -> Result<Vec<LogEvent>, DecodeError> {
         let mut buf = BytesMut::from(&message[..]);
 
         let mut decoder = FluentDecoder::new();
 
         let mut frames = vec![];
-        while let Some(frame) = decoder.decode(&mut buf)? {
-            frames.push(LogEvent::from(frame))
+        while let Some(item) = decoder.decode(&mut buf)? {
+            if let FluentItem::Event(frame, _) = item {
+                frames.push(LogEvent::from(frame))
+            }
         }
         Ok(frames)
     }
@@ -835,6 +1312,7 @@ mod integration_tests {
                 tls: None,
                 keepalive: None,
                 receive_buffer_bytes: None,
+                security: None,
             }
             .build(SourceContext::new_test(sender))
             .await
@@ -1,3 +1,4 @@
+use bytes::Bytes;
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
@@ -11,7 +12,8 @@ use vector_core::event::Value;
 /// The spec refers to 4 ways, but really CompressedPackedForward is encoded the
 /// same as PackedForward, it just has an additional decompression step.
 ///
-/// Not yet handled are the handshake messages.
+/// The handshake messages of secure mode match none of these, and are left to
+/// the decoder as a `Heartbeat` value.
 ///
 /// https://github.com/fluent/fluentd/wiki/Forward-Protocol-Specification-v1#event-modes
 #[derive(Debug, Deserialize)]
@@ -42,8 +44,8 @@ pub(super) enum FluentMessage {
 #[derive(Default, Debug, Deserialize)]
 #[serde(default)]
 pub(super) struct FluentMessageOptions {
-    size: Option<u64>,     // client provided hint for the number of entries
-    chunk: Option<String>, // unused right now, would be used for acks
+    size: Option<u64>,                // client provided hint for the number of entries
+    pub(super) chunk: Option<String>, // acknowledged once the events are sent
     pub(super) compressed: Option<String>, // this one is required if present
 }
 
@@ -141,10 +143,10 @@ impl From<FluentValue> for Value {
                 .map(Value::Integer)
                 // unwrap large numbers to string similar to how
                 // `From<serde_json::Value> for Value` handles it
-                .unwrap_or_else(|| Value::Bytes(i.to_string().into())),
+                .unwrap_or_else(|| Value::from(i.to_string())),
             rmpv::Value::F32(f) => Value::Float(f.into()),
             rmpv::Value::F64(f) => Value::Float(f),
-            rmpv::Value::String(s) => Value::Bytes(s.into_bytes().into()),
+            rmpv::Value::String(s) => Value::from(Bytes::from(s.into_bytes())),
             rmpv::Value::Binary(bytes) => Value::Bytes(bytes.into()),
             rmpv::Value::Array(values) => Value::Array(
                 values
@@ -231,7 +233,7 @@ mod test {
         fn from_u64(input: u64) -> () {
             if input > i64::max_value() as u64 {
                 assert_eq!(Value::from(FluentValue(rmpv::Value::Integer(rmpv::Integer::from(input)))),
-                           Value::Bytes(input.to_string().into()))
+                           Value::String(input.to_string().into()))
             } else {
                 assert_eq!(Value::from(FluentValue(rmpv::Value::Integer(rmpv::Integer::from(input)))),
                            Value::Integer(input as i64))
@@ -274,7 +276,7 @@ mod test {
     quickcheck! {
       fn from_string(input: String) -> () {
           assert_eq!(Value::from(FluentValue(rmpv::Value::String(rmpv::Utf8String::from(input.clone())))),
-                     Value::Bytes(input.into_bytes().into()))
+                     Value::String(input.into_bytes().into()))
       }
     }
 
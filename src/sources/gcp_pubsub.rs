@@ -0,0 +1,328 @@
+use crate::{
+    config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
+    event::{BatchNotifier, BatchStatus, Event, LogEvent, Value},
+    gcp::{GcpAuthConfig, GcpGrpcService},
+    internal_events::{GcpPubsubEventReceived, GcpPubsubStreamingPullFailed},
+    proto::google::pubsub::v1::{
+        subscriber_client::SubscriberClient, ReceivedMessage, StreamingPullRequest,
+    },
+    shutdown::ShutdownSignal,
+    tls::{MaybeTlsSettings, TlsConfig},
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{stream, stream::FuturesUnordered, FutureExt, SinkExt, StreamExt};
+use goauth::scopes::Scope;
+use http::uri::Uri;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+const DEFAULT_ENDPOINT: &str = "https://pubsub.googleapis.com";
+
+/// How long to wait before opening a new stream after one failed.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Pulls messages from a Pub/Sub subscription over a streaming pull, which
+/// the server keeps fed within the flow control limits.
+#[derive(Deserialize, Serialize, Debug, Clone, Derivative)]
+#[derivative(Default)]
+#[serde(deny_unknown_fields)]
+pub struct PubsubConfig {
+    project: String,
+    subscription: String,
+    endpoint: Option<String>,
+    #[serde(default)]
+    skip_authentication: bool,
+    #[serde(flatten)]
+    auth: GcpAuthConfig,
+    tls: Option<TlsConfig>,
+
+    /// How long the server waits for a message to be acknowledged before
+    /// redelivering it, between 10 and 600 seconds.
+    #[serde(default = "default_ack_deadline_secs")]
+    #[derivative(Default(value = "default_ack_deadline_secs()"))]
+    ack_deadline_secs: i32,
+
+    /// The most messages that may be received and not yet acknowledged.
+    #[serde(default = "default_max_outstanding_messages")]
+    #[derivative(Default(value = "default_max_outstanding_messages()"))]
+    max_outstanding_messages: i64,
+
+    /// The most bytes of messages that may be received and not yet
+    /// acknowledged.
+    #[serde(default = "default_max_outstanding_bytes")]
+    #[derivative(Default(value = "default_max_outstanding_bytes()"))]
+    max_outstanding_bytes: i64,
+}
+
+const fn default_ack_deadline_secs() -> i32 {
+    600
+}
+
+const fn default_max_outstanding_messages() -> i64 {
+    1000
+}
+
+const fn default_max_outstanding_bytes() -> i64 {
+    100 * 1024 * 1024
+}
+
+inventory::submit! {
+    SourceDescription::new::<PubsubConfig>("gcp_pubsub")
+}
+
+impl_generate_config_from_default!(PubsubConfig);
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "gcp_pubsub")]
+impl SourceConfig for PubsubConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        // We only need to load the credentials if we are not targeting an emulator.
+        let creds = if self.skip_authentication {
+            None
+        } else {
+            self.auth.make_credentials(Scope::PubSub).await?
+        };
+        if let Some(creds) = &creds {
+            creds.spawn_regenerate_token();
+        }
+
+        let tls = MaybeTlsSettings::from_config(&self.tls, false)?;
+        let uri: Uri = self
+            .endpoint
+            .as_deref()
+            .unwrap_or(DEFAULT_ENDPOINT)
+            .parse()?;
+        let client = SubscriberClient::new(GcpGrpcService::new(
+            uri,
+            &tls,
+            creds,
+            self.auth.api_key.clone(),
+        )?);
+
+        let source = PubsubSource {
+            client,
+            subscription: format!(
+                "projects/{}/subscriptions/{}",
+                self.project, self.subscription
+            ),
+            ack_deadline_secs: self.ack_deadline_secs,
+            max_outstanding_messages: self.max_outstanding_messages,
+            max_outstanding_bytes: self.max_outstanding_bytes,
+            acknowledgements: cx.acknowledgements,
+        };
+
+        Ok(Box::pin(source.run(cx.out, cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "gcp_pubsub"
+    }
+}
+
+struct PubsubSource {
+    client: SubscriberClient<GcpGrpcService>,
+    subscription: String,
+    ack_deadline_secs: i32,
+    max_outstanding_messages: i64,
+    max_outstanding_bytes: i64,
+    acknowledgements: bool,
+}
+
+enum State {
+    Shutdown,
+    RetryDelay,
+}
+
+impl PubsubSource {
+    async fn run(mut self, mut out: Pipeline, mut shutdown: ShutdownSignal) -> Result<(), ()> {
+        loop {
+            match self.run_stream(&mut out, &mut shutdown).await {
+                State::Shutdown => break,
+                State::RetryDelay => {
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = tokio::time::sleep(RETRY_DELAY) => {},
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull messages over a single stream until it fails or the source shuts
+    /// down.
+    async fn run_stream(&mut self, out: &mut Pipeline, shutdown: &mut ShutdownSignal) -> State {
+        let (requests, receiver) = mpsc::channel(16);
+        let initial = StreamingPullRequest {
+            subscription: self.subscription.clone(),
+            stream_ack_deadline_seconds: self.ack_deadline_secs,
+            max_outstanding_messages: self.max_outstanding_messages,
+            max_outstanding_bytes: self.max_outstanding_bytes,
+            ..Default::default()
+        };
+        let request_stream = stream::once(async { initial }).chain(ReceiverStream::new(receiver));
+
+        let mut responses = match self.client.streaming_pull(request_stream).await {
+            Ok(response) => response.into_inner(),
+            Err(error) => {
+                emit!(GcpPubsubStreamingPullFailed {
+                    error: error.into()
+                });
+                return State::RetryDelay;
+            }
+        };
+
+        // Messages whose events are still being delivered.
+        let mut pending = FuturesUnordered::new();
+        let state = loop {
+            tokio::select! {
+                _ = &mut *shutdown => break State::Shutdown,
+                Some((status, ack_ids)) = pending.next(), if !pending.is_empty() => {
+                    acknowledge(&requests, status, ack_ids).await
+                }
+                response = responses.message() => match response {
+                    Ok(Some(response)) => {
+                        let (events, ack_ids) = create_events(response.received_messages);
+                        if self.acknowledgements {
+                            let (batch, receiver) = BatchNotifier::new_with_receiver();
+                            let events = events
+                                .into_iter()
+                                .map(|event| event.with_batch_notifier(&batch))
+                                .collect::<Vec<_>>();
+                            drop(batch);
+                            match out.send_all(&mut stream::iter(events).map(Ok)).await {
+                                Err(error) => {
+                                    error!(message = "Error sending to sink.", %error);
+                                    break State::Shutdown;
+                                }
+                                Ok(_) => pending.push(receiver.map(move |status| (status, ack_ids))),
+                            }
+                        } else {
+                            match out.send_all(&mut stream::iter(events).map(Ok)).await {
+                                Err(error) => {
+                                    error!(message = "Error sending to sink.", %error);
+                                    break State::Shutdown;
+                                }
+                                Ok(_) => acknowledge(&requests, BatchStatus::Delivered, ack_ids).await,
+                            }
+                        }
+                    }
+                    Ok(None) => break State::RetryDelay,
+                    Err(error) => {
+                        emit!(GcpPubsubStreamingPullFailed {
+                            error: error.into()
+                        });
+                        break State::RetryDelay;
+                    }
+                },
+            }
+        };
+
+        // Settle the messages already sent on while the stream is still
+        // open, so they are only redelivered if their events were not.
+        while let Some((status, ack_ids)) = pending.next().await {
+            acknowledge(&requests, status, ack_ids).await;
+        }
+
+        state
+    }
+}
+
+fn create_events(messages: Vec<ReceivedMessage>) -> (Vec<Event>, Vec<String>) {
+    messages
+        .into_iter()
+        .filter_map(|received| {
+            let message = received.message?;
+            emit!(GcpPubsubEventReceived {
+                byte_size: message.data.len()
+            });
+
+            let mut log = LogEvent::default();
+            log.insert(log_schema().message_key(), Bytes::from(message.data));
+
+            let timestamp = message
+                .publish_time
+                .and_then(|time| Utc.timestamp_opt(time.seconds, time.nanos as u32).latest())
+                .unwrap_or_else(Utc::now);
+            log.insert(log_schema().timestamp_key(), timestamp);
+            log.insert(log_schema().source_type_key(), Bytes::from("gcp_pubsub"));
+
+            log.insert("message_id", message.message_id);
+            if !message.ordering_key.is_empty() {
+                log.insert("ordering_key", message.ordering_key);
+            }
+            let attributes = message
+                .attributes
+                .into_iter()
+                .map(|(key, value)| (key, Value::from(value)))
+                .collect::<BTreeMap<_, _>>();
+            log.insert("attributes", attributes);
+
+            Some((Event::from(log), received.ack_id))
+        })
+        .unzip()
+}
+
+/// Acknowledge the messages, or have them redelivered right away by setting
+/// their ack deadline to zero if their events weren't delivered. Pub/Sub
+/// redelivers messages with an ordering key in order, so this keeps their
+/// order too.
+async fn acknowledge(
+    requests: &mpsc::Sender<StreamingPullRequest>,
+    status: BatchStatus,
+    ack_ids: Vec<String>,
+) {
+    let request = match status {
+        BatchStatus::Delivered => StreamingPullRequest {
+            ack_ids,
+            ..Default::default()
+        },
+        BatchStatus::Errored | BatchStatus::Failed => StreamingPullRequest {
+            modify_deadline_seconds: vec![0; ack_ids.len()],
+            modify_deadline_ack_ids: ack_ids,
+            ..Default::default()
+        },
+    };
+    // The stream only closes when the source stops pulling, after which the
+    // messages are redelivered once their deadline passes.
+    let _ = requests.send(request).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::google::pubsub::v1::PubsubMessage;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<PubsubConfig>();
+    }
+
+    #[test]
+    fn creates_events() {
+        let (events, ack_ids) = create_events(vec![ReceivedMessage {
+            ack_id: "ack".into(),
+            message: Some(PubsubMessage {
+                data: b"hello".to_vec(),
+                message_id: "1".into(),
+                ordering_key: "key".into(),
+                ..Default::default()
+            }),
+            delivery_attempt: 0,
+        }]);
+
+        assert_eq!(ack_ids, vec!["ack"]);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "hello".into());
+        assert_eq!(log["ordering_key"], "key".into());
+        assert_eq!(log[log_schema().source_type_key()], "gcp_pubsub".into());
+    }
+}
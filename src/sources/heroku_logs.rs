@@ -4,7 +4,9 @@ use crate::{
         SourceDescription,
     },
     event::Event,
-    internal_events::{HerokuLogplexRequestReadError, HerokuLogplexRequestReceived},
+    internal_events::{
+        HerokuLogplexDrainRejected, HerokuLogplexRequestReadError, HerokuLogplexRequestReceived,
+    },
     sources::util::{add_query_parameters, ErrorMessage, HttpSource, HttpSourceAuthConfig},
     tls::TlsConfig,
 };
@@ -16,6 +18,7 @@ use std::{
     io::{BufRead, BufReader},
     net::SocketAddr,
     str::FromStr,
+    sync::Arc,
 };
 
 use warp::http::{HeaderMap, StatusCode};
@@ -27,6 +30,19 @@ pub struct LogplexConfig {
     query_parameters: Vec<String>,
     tls: Option<TlsConfig>,
     auth: Option<HttpSourceAuthConfig>,
+    /// Requests from other drains are rejected, unless there are none.
+    #[serde(default)]
+    drains: Vec<LogplexDrainConfig>,
+}
+
+/// A drain allowed to send logs, identified by the `Logplex-Drain-Token`
+/// header of its requests.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LogplexDrainConfig {
+    token: String,
+    /// The app the drain is attached to, added to its events.
+    app: Option<String>,
 }
 
 inventory::submit! {
@@ -44,6 +60,7 @@ impl GenerateConfig for LogplexConfig {
             query_parameters: Vec::new(),
             tls: None,
             auth: None,
+            drains: Vec::new(),
         })
         .unwrap()
     }
@@ -52,6 +69,8 @@ impl GenerateConfig for LogplexConfig {
 #[derive(Clone, Default)]
 struct LogplexSource {
     query_parameters: Vec<String>,
+    // the apps of the allowed drains, by token
+    drains: Arc<HashMap<String, Option<String>>>,
 }
 
 impl HttpSource for LogplexSource {
@@ -62,7 +81,7 @@ impl HttpSource for LogplexSource {
         query_parameters: HashMap<String, String>,
         _full_path: &str,
     ) -> Result<Vec<Event>, ErrorMessage> {
-        decode_message(body, header_map)
+        decode_message(body, header_map, &self.drains)
             .map(|events| add_query_parameters(events, &self.query_parameters, query_parameters))
     }
 }
@@ -73,6 +92,12 @@ impl SourceConfig for LogplexConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
         let source = LogplexSource {
             query_parameters: self.query_parameters.clone(),
+            drains: Arc::new(
+                self.drains
+                    .iter()
+                    .map(|drain| (drain.token.clone(), drain.app.clone()))
+                    .collect(),
+            ),
         };
         source.run(self.address, "events", true, &self.tls, &self.auth, cx)
     }
@@ -114,7 +139,11 @@ impl SourceConfig for LogplexCompatConfig {
     }
 }
 
-fn decode_message(body: Bytes, header_map: HeaderMap) -> Result<Vec<Event>, ErrorMessage> {
+fn decode_message(
+    body: Bytes,
+    header_map: HeaderMap,
+    drains: &HashMap<String, Option<String>>,
+) -> Result<Vec<Event>, ErrorMessage> {
     // Deal with headers
     let msg_count = match usize::from_str(get_header(&header_map, "Logplex-Msg-Count")?) {
         Ok(v) => v,
@@ -129,8 +158,27 @@ fn decode_message(body: Bytes, header_map: HeaderMap) -> Result<Vec<Event>, Erro
         drain_token
     });
 
+    let app = match drains.get(drain_token) {
+        Some(app) => app.as_deref(),
+        None if drains.is_empty() => None,
+        None => {
+            emit!(HerokuLogplexDrainRejected { drain_token });
+            return Err(ErrorMessage::new(
+                StatusCode::FORBIDDEN,
+                "Unknown drain token".to_owned(),
+            ));
+        }
+    };
+
     // Deal with body
-    let events = body_to_events(body);
+    let mut events = body_to_events(body);
+    for event in &mut events {
+        let log = event.as_mut_log();
+        log.insert("drain_token", drain_token.to_owned());
+        if let Some(app) = app {
+            log.insert("app", app.to_owned());
+        }
+    }
 
     if events.len() != msg_count {
         let error_msg = format!(
@@ -219,7 +267,7 @@ fn line_to_event(line: String) -> Event {
 
 #[cfg(test)]
 mod tests {
-    use super::{HttpSourceAuthConfig, LogplexConfig};
+    use super::{HttpSourceAuthConfig, LogplexConfig, LogplexDrainConfig};
     use crate::{
         config::{log_schema, SourceConfig, SourceContext},
         test_util::{next_addr, random_string, spawn_collect_n, trace_init, wait_for_tcp},
@@ -239,6 +287,7 @@ mod tests {
     async fn source(
         auth: Option<HttpSourceAuthConfig>,
         query_parameters: Vec<String>,
+        drains: Vec<LogplexDrainConfig>,
         status: EventStatus,
         acknowledgements: bool,
     ) -> (impl Stream<Item = Event>, SocketAddr) {
@@ -252,6 +301,7 @@ mod tests {
                 query_parameters,
                 tls: None,
                 auth,
+                drains,
             }
             .build(context)
             .await
@@ -303,6 +353,7 @@ mod tests {
         let (rx, addr) = source(
             Some(auth.clone()),
             vec!["appname".to_string(), "absent".to_string()],
+            vec![],
             EventStatus::Delivered,
             true,
         )
@@ -338,6 +389,43 @@ mod tests {
         assert_eq!(log[log_schema().source_type_key()], "heroku_logs".into());
         assert_eq!(log["appname"], "lumberjack-store".into());
         assert_eq!(log["absent"], Value::Null);
+        assert_eq!(log["drain_token"], "drain-bar".into());
+        assert_eq!(log["app"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn logplex_tags_allowed_drains() {
+        trace_init();
+
+        let drains = vec![LogplexDrainConfig {
+            token: "drain-bar".to_owned(),
+            app: Some("lumberjack-store".to_owned()),
+        }];
+        let (rx, addr) = source(None, vec![], drains, EventStatus::Delivered, true).await;
+
+        let mut events = spawn_collect_n(
+            async move { assert_eq!(200, send(addr, SAMPLE_BODY, None, "").await) },
+            rx,
+            SAMPLE_BODY.lines().count(),
+        )
+        .await;
+
+        let event = events.remove(0);
+        assert_eq!(event.as_log()["drain_token"], "drain-bar".into());
+        assert_eq!(event.as_log()["app"], "lumberjack-store".into());
+    }
+
+    #[tokio::test]
+    async fn logplex_rejects_unknown_drains() {
+        trace_init();
+
+        let drains = vec![LogplexDrainConfig {
+            token: "drain-baz".to_owned(),
+            app: None,
+        }];
+        let (_rx, addr) = source(None, vec![], drains, EventStatus::Delivered, true).await;
+
+        assert_eq!(403, send(addr, SAMPLE_BODY, None, "").await);
     }
 
     #[tokio::test]
@@ -346,7 +434,14 @@ mod tests {
 
         let auth = make_auth();
 
-        let (rx, addr) = source(Some(auth.clone()), vec![], EventStatus::Failed, true).await;
+        let (rx, addr) = source(
+            Some(auth.clone()),
+            vec![],
+            vec![],
+            EventStatus::Failed,
+            true,
+        )
+        .await;
 
         let events = spawn_collect_n(
             async move {
@@ -369,7 +464,14 @@ mod tests {
 
         let auth = make_auth();
 
-        let (rx, addr) = source(Some(auth.clone()), vec![], EventStatus::Failed, false).await;
+        let (rx, addr) = source(
+            Some(auth.clone()),
+            vec![],
+            vec![],
+            EventStatus::Failed,
+            false,
+        )
+        .await;
 
         let events = spawn_collect_n(
             async move {
@@ -390,7 +492,14 @@ mod tests {
     async fn logplex_auth_failure() {
         trace_init();
 
-        let (_rx, addr) = source(Some(make_auth()), vec![], EventStatus::Delivered, true).await;
+        let (_rx, addr) = source(
+            Some(make_auth()),
+            vec![],
+            vec![],
+            EventStatus::Delivered,
+            true,
+        )
+        .await;
 
         assert_eq!(
             401,
@@ -31,6 +31,7 @@ mod disk;
 mod filesystem;
 mod memory;
 mod network;
+mod process;
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -42,6 +43,7 @@ enum Collector {
     Host,
     Memory,
     Network,
+    Process,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -75,6 +77,8 @@ pub struct HostMetricsConfig {
     filesystem: filesystem::FilesystemConfig,
     #[serde(default)]
     network: network::NetworkConfig,
+    #[serde(default)]
+    process: process::ProcessConfig,
 }
 
 const fn default_scrape_interval() -> u64 {
@@ -125,7 +129,9 @@ impl HostMetricsConfig {
 
     fn has_collector(&self, collector: Collector) -> bool {
         match &self.collectors {
-            None => true,
+            // There may be many processes, so their metrics are only
+            // collected when asked for.
+            None => collector != Collector::Process,
             Some(collectors) => collectors.iter().any(|&c| c == collector),
         }
     }
@@ -155,6 +161,9 @@ impl HostMetricsConfig {
         if self.has_collector(Collector::Network) {
             metrics.extend(add_collector("network", self.network_metrics().await));
         }
+        if self.has_collector(Collector::Process) {
+            metrics.extend(add_collector("process", self.process_metrics().await));
+        }
         if let Ok(hostname) = &hostname {
             for metric in &mut metrics {
                 metric.insert_tag("host".into(), hostname.into());
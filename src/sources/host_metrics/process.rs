@@ -0,0 +1,353 @@
+use super::{FilterList, HostMetricsConfig};
+use crate::event::metric::Metric;
+#[cfg(target_os = "linux")]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use shared::btreemap;
+#[cfg(target_os = "linux")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The kernel reports CPU times in clock ticks of `USER_HZ`, which is 100 on
+/// all the architectures supported.
+#[cfg(any(target_os = "linux", test))]
+const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(super) struct ProcessConfig {
+    #[serde(default)]
+    names: FilterList,
+    #[serde(default)]
+    cgroups: FilterList,
+}
+
+impl HostMetricsConfig {
+    pub async fn process_metrics(&self) -> Vec<Metric> {
+        #[cfg(target_os = "linux")]
+        let result = {
+            let config = self.clone();
+            // Reading procfs blocks, and there may be many processes.
+            match tokio::task::spawn_blocking(move || config.read_process_metrics()).await {
+                Ok(metrics) => metrics,
+                Err(error) => {
+                    error!(message = "Failed to load process info.", %error, internal_log_rate_secs = 60);
+                    vec![]
+                }
+            }
+        };
+        #[cfg(not(target_os = "linux"))]
+        let result = vec![];
+
+        result
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_process_metrics(&self) -> Vec<Metric> {
+        let root = procfs_root();
+        let entries = match fs::read_dir(&root) {
+            Ok(entries) => entries,
+            Err(error) => {
+                error!(message = "Failed to list processes.", %error, internal_log_rate_secs = 60);
+                return vec![];
+            }
+        };
+
+        let timestamp = Utc::now();
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+            // Processes may exit while being read, and are then skipped.
+            .filter_map(|pid| {
+                let dir = root.join(pid.to_string());
+                let name = fs::read_to_string(dir.join("comm")).ok()?;
+                let name = name.trim_end().to_owned();
+                let cgroup = fs::read_to_string(dir.join("cgroup"))
+                    .ok()
+                    .and_then(|cgroup| parse_cgroup(&cgroup));
+                (self.process.names.contains_str(Some(&name))
+                    && self.process.cgroups.contains_str(cgroup.as_deref()))
+                .then(|| (pid, name, dir))
+            })
+            .flat_map(|(pid, name, dir)| self.single_process_metrics(pid, &name, &dir, timestamp))
+            .collect()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn single_process_metrics(
+        &self,
+        pid: u32,
+        name: &str,
+        dir: &Path,
+        timestamp: DateTime<Utc>,
+    ) -> Vec<Metric> {
+        let pid = pid.to_string();
+        let mut metrics = Vec::new();
+
+        let times = fs::read_to_string(dir.join("stat"))
+            .ok()
+            .and_then(|stat| parse_cpu_times(&stat));
+        if let Some((user, system)) = times {
+            metrics.push(self.counter(
+                "process_cpu_seconds_total",
+                timestamp,
+                user,
+                btreemap! { "pid" => &pid, "name" => name, "mode" => "user" },
+            ));
+            metrics.push(self.counter(
+                "process_cpu_seconds_total",
+                timestamp,
+                system,
+                btreemap! { "pid" => &pid, "name" => name, "mode" => "system" },
+            ));
+        }
+
+        // Kernel threads have no memory of their own.
+        if let Ok(status) = fs::read_to_string(dir.join("status")) {
+            let status = parse_status(&status);
+            let gauges = [
+                ("process_resident_memory_bytes", status.resident_bytes),
+                ("process_virtual_memory_bytes", status.virtual_bytes),
+                ("process_threads", status.threads),
+            ];
+            for (metric, value) in gauges.iter() {
+                if let Some(value) = value {
+                    metrics.push(self.gauge(
+                        metric,
+                        timestamp,
+                        *value,
+                        btreemap! { "pid" => &pid, "name" => name },
+                    ));
+                }
+            }
+        }
+
+        // The open files and I/O of processes of other users are only
+        // readable with extra privileges.
+        if let Ok(fds) = fs::read_dir(dir.join("fd")) {
+            metrics.push(self.gauge(
+                "process_open_fds",
+                timestamp,
+                fds.count() as f64,
+                btreemap! { "pid" => &pid, "name" => name },
+            ));
+        }
+        let io = fs::read_to_string(dir.join("io"))
+            .ok()
+            .and_then(|io| parse_io(&io));
+        if let Some((read, written)) = io {
+            metrics.push(self.counter(
+                "process_read_bytes_total",
+                timestamp,
+                read,
+                btreemap! { "pid" => &pid, "name" => name },
+            ));
+            metrics.push(self.counter(
+                "process_written_bytes_total",
+                timestamp,
+                written,
+                btreemap! { "pid" => &pid, "name" => name },
+            ));
+        }
+
+        metrics
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn procfs_root() -> PathBuf {
+    std::env::var_os("PROCFS_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/proc"))
+}
+
+/// Returns the user and system CPU seconds from `/proc/<pid>/stat`. The
+/// process name may contain spaces and parentheses, so the fields are
+/// counted from the last parenthesis.
+#[cfg(any(target_os = "linux", test))]
+fn parse_cpu_times(stat: &str) -> Option<(f64, f64)> {
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+    // `utime` and `stime` are the 14th and 15th fields, after the state.
+    let user = fields.nth(11)?.parse::<f64>().ok()?;
+    let system = fields.next()?.parse::<f64>().ok()?;
+    Some((user / CLOCK_TICKS_PER_SEC, system / CLOCK_TICKS_PER_SEC))
+}
+
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Default, PartialEq)]
+struct Status {
+    resident_bytes: Option<f64>,
+    virtual_bytes: Option<f64>,
+    threads: Option<f64>,
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_status(status: &str) -> Status {
+    let mut result = Status::default();
+    for line in status.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let kilobytes = || {
+            value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .map(|value| value * 1024.0)
+        };
+        match key {
+            "VmRSS" => result.resident_bytes = kilobytes(),
+            "VmSize" => result.virtual_bytes = kilobytes(),
+            "Threads" => result.threads = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Returns the bytes read from and written to storage, from
+/// `/proc/<pid>/io`.
+#[cfg(any(target_os = "linux", test))]
+fn parse_io(io: &str) -> Option<(f64, f64)> {
+    let mut read = None;
+    let mut written = None;
+    for line in io.lines() {
+        match line.split_once(':') {
+            Some(("read_bytes", value)) => read = value.trim().parse().ok(),
+            Some(("write_bytes", value)) => written = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some((read?, written?))
+}
+
+/// Returns the path of the process's cgroup from `/proc/<pid>/cgroup`, which
+/// is the unified hierarchy's with cgroup v2, or else the first hierarchy's.
+#[cfg(any(target_os = "linux", test))]
+fn parse_cgroup(cgroup: &str) -> Option<String> {
+    let paths = cgroup
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            Some((parts.next()?, parts.nth(1)?))
+        })
+        .collect::<Vec<_>>();
+    paths
+        .iter()
+        .find(|(hierarchy, _)| *hierarchy == "0")
+        .or_else(|| paths.first())
+        .map(|(_, path)| (*path).to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cpu_times() {
+        let stat = "1234 (my (weird) name) S 1 1234 1234 0 -1 4194560 1000 0 0 0 250 125 0 0 20 0 1 0 100 1000000 200 18446744073709551615";
+        assert_eq!(parse_cpu_times(stat), Some((2.5, 1.25)));
+        assert_eq!(parse_cpu_times("1234 (truncated"), None);
+    }
+
+    #[test]
+    fn parses_status() {
+        let status = "Name:\tvector\nState:\tS (sleeping)\nVmSize:\t  100 kB\nVmRSS:\t    10 kB\nThreads:\t8\n";
+        assert_eq!(
+            parse_status(status),
+            Status {
+                resident_bytes: Some(10240.0),
+                virtual_bytes: Some(102400.0),
+                threads: Some(8.0),
+            }
+        );
+        // Kernel threads
+        assert_eq!(
+            parse_status("Name:\tkthreadd\nThreads:\t1\n"),
+            Status {
+                threads: Some(1.0),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_io() {
+        let io = "rchar: 100\nwchar: 200\nsyscr: 1\nsyscw: 2\nread_bytes: 4096\nwrite_bytes: 8192\ncancelled_write_bytes: 0\n";
+        assert_eq!(parse_io(io), Some((4096.0, 8192.0)));
+    }
+
+    #[test]
+    fn parses_cgroup() {
+        assert_eq!(
+            parse_cgroup("0::/system.slice/vector.service\n"),
+            Some("/system.slice/vector.service".to_owned())
+        );
+        assert_eq!(
+            parse_cgroup("12:pids:/docker/abc\n1:name=systemd:/docker/abc\n0::/docker/abc\n"),
+            Some("/docker/abc".to_owned())
+        );
+        assert_eq!(
+            parse_cgroup("12:pids:/docker/abc\n1:name=systemd:/docker/abc\n"),
+            Some("/docker/abc".to_owned())
+        );
+        assert_eq!(parse_cgroup(""), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn generates_process_metrics() {
+        use super::super::{tests::count_tag, PatternWrapper};
+
+        let name = fs::read_to_string("/proc/self/comm").unwrap();
+        let metrics = HostMetricsConfig {
+            process: ProcessConfig {
+                names: FilterList {
+                    includes: Some(vec![PatternWrapper::new(name.trim_end()).unwrap()]),
+                    excludes: None,
+                },
+                cgroups: FilterList::default(),
+            },
+            ..Default::default()
+        }
+        .process_metrics()
+        .await;
+
+        let pid = std::process::id().to_string();
+        assert!(metrics
+            .iter()
+            .any(|metric| metric.name() == "process_resident_memory_bytes"
+                && metric.tags().unwrap()["pid"] == pid));
+        assert!(metrics
+            .iter()
+            .any(|metric| metric.name() == "process_open_fds"));
+        assert!(!metrics
+            .iter()
+            .any(|metric| !metric.name().starts_with("process_")));
+        assert_eq!(count_tag(&metrics, "name"), metrics.len());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn process_metrics_filters_on_cgroup() {
+        use super::super::PatternWrapper;
+
+        let metrics = HostMetricsConfig {
+            process: ProcessConfig {
+                names: FilterList::default(),
+                cgroups: FilterList {
+                    includes: Some(vec![PatternWrapper::new("/nonexistent/*").unwrap()]),
+                    excludes: None,
+                },
+            },
+            ..Default::default()
+        }
+        .process_metrics()
+        .await;
+        assert!(metrics.is_empty());
+    }
+}
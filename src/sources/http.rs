@@ -4,22 +4,32 @@ use crate::{
         SourceDescription,
     },
     event::{Event, Value},
+    internal_events::TemplateRenderingFailed,
+    protobuf::{MessageDecoder, ProtobufOptions},
     sources::util::{
         add_query_parameters, decode_body, Encoding, ErrorMessage, HttpSource, HttpSourceAuthConfig,
     },
+    template::Template,
     tls::TlsConfig,
 };
 use bytes::Bytes;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, net::SocketAddr};
+use std::{collections::HashMap, convert::TryFrom, net::SocketAddr, sync::Arc};
 
-use warp::http::{HeaderMap, HeaderValue};
+use warp::{
+    http::{header::HeaderName, HeaderMap, HeaderValue, StatusCode},
+    reply::Response,
+    Reply,
+};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SimpleHttpConfig {
     address: SocketAddr,
     #[serde(default)]
     encoding: Encoding,
+    /// The message type of the `protobuf` encoding.
+    protobuf: Option<ProtobufOptions>,
     #[serde(default)]
     headers: Vec<String>,
     #[serde(default)]
@@ -32,6 +42,45 @@ pub struct SimpleHttpConfig {
     path: String,
     #[serde(default = "default_path_key")]
     path_key: String,
+    /// Routes requests by their path instead of `path`, each to events
+    /// with their name under `route_key`.
+    #[serde(default)]
+    routes: Vec<RouteConfig>,
+    #[serde(default = "default_route_key")]
+    route_key: String,
+    #[serde(default)]
+    response: ResponseConfig,
+}
+
+/// A path whose `:name` segments match any segment, which is then added to
+/// events under `name`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    name: String,
+    path: String,
+}
+
+/// The response to requests whose events are delivered, whose body is
+/// rendered from the first event of the request.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseConfig {
+    #[serde(default = "default_response_status")]
+    status: u16,
+    body: Option<Template>,
+    #[serde(default)]
+    headers: IndexMap<String, String>,
+}
+
+impl Default for ResponseConfig {
+    fn default() -> Self {
+        Self {
+            status: default_response_status(),
+            body: None,
+            headers: IndexMap::new(),
+        }
+    }
 }
 
 inventory::submit! {
@@ -43,6 +92,7 @@ impl GenerateConfig for SimpleHttpConfig {
         toml::Value::try_from(Self {
             address: "0.0.0.0:8080".parse().unwrap(),
             encoding: Default::default(),
+            protobuf: None,
             headers: Vec::new(),
             query_parameters: Vec::new(),
             tls: None,
@@ -50,6 +100,9 @@ impl GenerateConfig for SimpleHttpConfig {
             path_key: "path".to_string(),
             path: "/".to_string(),
             strict_path: true,
+            routes: Vec::new(),
+            route_key: default_route_key(),
+            response: ResponseConfig::default(),
         })
         .unwrap()
     }
@@ -63,12 +116,116 @@ fn default_path_key() -> String {
     "path".to_string()
 }
 
+fn default_route_key() -> String {
+    "route".to_string()
+}
+
+const fn default_response_status() -> u16 {
+    200
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Parameter(String),
+}
+
+#[derive(Clone, Debug)]
+struct Route {
+    name: Option<String>,
+    segments: Vec<Segment>,
+}
+
+impl Route {
+    fn new(name: Option<String>, path: &str) -> Self {
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => Segment::Parameter(name.to_owned()),
+                None => Segment::Literal(segment.to_owned()),
+            })
+            .collect();
+        Self { name, segments }
+    }
+
+    /// The segments before the first parameter, which the server can match
+    /// requests on by itself.
+    fn prefix(&self) -> String {
+        self.segments
+            .iter()
+            .take_while(|segment| matches!(segment, Segment::Literal(_)))
+            .filter_map(|segment| match segment {
+                Segment::Literal(literal) => Some(literal.as_str()),
+                Segment::Parameter(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn has_parameters(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Parameter(_)))
+    }
+
+    /// The parameters of the path, if it matches. Non-strict routes also
+    /// match paths under theirs.
+    fn matches<'a>(&'a self, path: &'a str, strict: bool) -> Option<Vec<(&'a str, &'a str)>> {
+        let mut parts = path.split('/').filter(|part| !part.is_empty());
+        let mut parameters = Vec::new();
+        for segment in &self.segments {
+            let part = parts.next()?;
+            match segment {
+                Segment::Literal(literal) if literal != part => return None,
+                Segment::Literal(_) => (),
+                Segment::Parameter(name) => parameters.push((name.as_str(), part)),
+            }
+        }
+        if strict && parts.next().is_some() {
+            return None;
+        }
+        Some(parameters)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ResponseSettings {
+    status: StatusCode,
+    body: Option<Template>,
+    headers: HeaderMap,
+}
+
+impl TryFrom<&ResponseConfig> for ResponseSettings {
+    type Error = crate::Error;
+
+    fn try_from(config: &ResponseConfig) -> crate::Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        Ok(Self {
+            status: StatusCode::from_u16(config.status)?,
+            body: config.body.clone(),
+            headers,
+        })
+    }
+}
+
 #[derive(Clone)]
 struct SimpleHttpSource {
     encoding: Encoding,
+    protobuf: Option<Arc<MessageDecoder>>,
     headers: Vec<String>,
     query_parameters: Vec<String>,
     path_key: String,
+    routes: Vec<Route>,
+    strict_path: bool,
+    route_key: String,
+    response: ResponseSettings,
 }
 
 impl HttpSource for SimpleHttpSource {
@@ -79,10 +236,32 @@ impl HttpSource for SimpleHttpSource {
         query_parameters: HashMap<String, String>,
         request_path: &str,
     ) -> Result<Vec<Event>, ErrorMessage> {
-        decode_body(body, self.encoding)
+        let (route, parameters) = self
+            .routes
+            .iter()
+            .find_map(|route| {
+                route
+                    .matches(request_path, self.strict_path)
+                    .map(|parameters| (route, parameters))
+            })
+            .ok_or_else(|| ErrorMessage::new(StatusCode::NOT_FOUND, "Not found".to_string()))?;
+
+        decode_body(body, self.encoding, self.protobuf.as_deref())
             .map(|events| add_headers(events, &self.headers, header_map))
             .map(|events| add_query_parameters(events, &self.query_parameters, query_parameters))
             .map(|events| add_path(events, self.path_key.as_str(), request_path))
+            .map(|mut events| {
+                for event in &mut events {
+                    let log = event.as_mut_log();
+                    for (name, value) in &parameters {
+                        log.insert(*name, value.to_string());
+                    }
+                    if let Some(name) = &route.name {
+                        log.insert(self.route_key.as_str(), name.clone());
+                    }
+                }
+                events
+            })
             .map(|mut events| {
                 // Add source type
                 let key = log_schema().source_type_key();
@@ -92,22 +271,73 @@ impl HttpSource for SimpleHttpSource {
                 events
             })
     }
+
+    fn build_response(&self, events: &[Event]) -> Response {
+        let body = match (&self.response.body, events.first()) {
+            (Some(template), Some(event)) => render_response_body(template, event),
+            (Some(template), None) => render_response_body(template, &Event::new_empty_log()),
+            (None, _) => String::new(),
+        };
+
+        let mut response = warp::reply::with_status(body, self.response.status).into_response();
+        response.headers_mut().extend(self.response.headers.clone());
+        response
+    }
+}
+
+fn render_response_body(template: &Template, event: &Event) -> String {
+    template.render_string(event).unwrap_or_else(|error| {
+        emit!(TemplateRenderingFailed {
+            error,
+            field: Some("response.body"),
+            drop_event: false,
+        });
+        String::new()
+    })
 }
 
 #[async_trait::async_trait]
 #[typetag::serde(name = "http")]
 impl SourceConfig for SimpleHttpConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let routes = if self.routes.is_empty() {
+            vec![Route::new(None, &self.path)]
+        } else {
+            self.routes
+                .iter()
+                .map(|route| Route::new(Some(route.name.clone()), &route.path))
+                .collect()
+        };
+        // The server only matches what is common to every route, and the
+        // rest is matched with the events.
+        let (path, strict_path) = match routes.as_slice() {
+            [route] => (route.prefix(), self.strict_path && !route.has_parameters()),
+            _ => (String::new(), false),
+        };
+
+        let protobuf = match (self.encoding, &self.protobuf) {
+            (Encoding::Protobuf, Some(options)) => Some(Arc::new(options.decoder()?)),
+            (Encoding::Protobuf, None) => {
+                return Err("`encoding = \"protobuf\"` requires `protobuf`".into())
+            }
+            _ => None,
+        };
+
         let source = SimpleHttpSource {
             encoding: self.encoding,
+            protobuf,
             headers: self.headers.clone(),
             query_parameters: self.query_parameters.clone(),
             path_key: self.path_key.clone(),
+            routes,
+            strict_path: self.strict_path,
+            route_key: self.route_key.clone(),
+            response: ResponseSettings::try_from(&self.response)?,
         };
         source.run(
             self.address,
-            self.path.as_str(),
-            self.strict_path,
+            path.as_str(),
+            strict_path,
             &self.tls,
             &self.auth,
             cx,
@@ -158,7 +388,7 @@ fn add_headers(
 
 #[cfg(test)]
 mod tests {
-    use super::{Encoding, SimpleHttpConfig};
+    use super::{default_route_key, Encoding, ResponseConfig, SimpleHttpConfig};
     use crate::{
         config::{log_schema, SourceConfig, SourceContext},
         event::{Event, EventStatus, Value},
@@ -201,6 +431,7 @@ mod tests {
             SimpleHttpConfig {
                 address,
                 encoding,
+                protobuf: None,
                 headers,
                 query_parameters,
                 tls: None,
@@ -208,6 +439,9 @@ mod tests {
                 strict_path,
                 path_key,
                 path,
+                routes: Vec::new(),
+                route_key: default_route_key(),
+                response: ResponseConfig::default(),
             }
             .build(context)
             .await
@@ -219,6 +453,23 @@ mod tests {
         (recv, address)
     }
 
+    async fn source_with_config(config: &str) -> (impl Stream<Item = Event>, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test_finalize(EventStatus::Delivered);
+        let address = next_addr();
+        let config: SimpleHttpConfig =
+            toml::from_str(&format!("address = \"{}\"\n{}", address, config)).unwrap();
+        tokio::spawn(async move {
+            config
+                .build(SourceContext::new_test(sender))
+                .await
+                .unwrap()
+                .await
+                .unwrap();
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
     async fn send(address: SocketAddr, body: &str) -> u16 {
         reqwest::Client::new()
             .post(&format!("http://{}/", address))
@@ -695,6 +946,36 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn http_zstd() {
+        trace_init();
+
+        let body = zstd::stream::encode_all("test body".as_bytes(), 0).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "zstd".parse().unwrap());
+
+        let (rx, addr) = source(
+            Encoding::default(),
+            vec![],
+            vec![],
+            "http_path",
+            "/",
+            true,
+            EventStatus::Delivered,
+            true,
+        )
+        .await;
+
+        let mut events = spawn_ok_collect_n(send_bytes(addr, body, headers), rx, 1).await;
+
+        let event = events.remove(0);
+        assert_eq!(
+            event.as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
     #[tokio::test]
     async fn http_path() {
         trace_init();
@@ -850,4 +1131,130 @@ mod tests {
 
         assert_eq!(events.len(), 1);
     }
+
+    #[tokio::test]
+    async fn http_path_parameters() {
+        trace_init();
+        let (rx, addr) = source_with_config(
+            r#"
+            encoding = "ndjson"
+            path = "/tenants/:tenant/logs"
+            "#,
+        )
+        .await;
+
+        let mut events = spawn_collect_n(
+            async move {
+                assert_eq!(
+                    200,
+                    send_with_path(addr, r#"{"key":"value"}"#, "/tenants/acme/logs").await
+                );
+                assert_eq!(
+                    404,
+                    send_with_path(addr, r#"{"key":"value"}"#, "/tenants/acme/metrics").await
+                );
+            },
+            rx,
+            1,
+        )
+        .await;
+
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["tenant"], "acme".into());
+        assert_eq!(log["path"], "/tenants/acme/logs".into());
+        assert!(log.get("route").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_routes() {
+        trace_init();
+        let (rx, addr) = source_with_config(
+            r#"
+            encoding = "ndjson"
+
+            [[routes]]
+            name = "hec"
+            path = "/services/collector/:channel"
+
+            [[routes]]
+            name = "events"
+            path = "/events"
+            "#,
+        )
+        .await;
+
+        let mut events = spawn_collect_n(
+            async move {
+                assert_eq!(
+                    200,
+                    send_with_path(addr, r#"{"key":"value1"}"#, "/services/collector/abc").await
+                );
+                assert_eq!(
+                    200,
+                    send_with_path(addr, r#"{"key":"value2"}"#, "/events").await
+                );
+                assert_eq!(
+                    404,
+                    send_with_path(addr, r#"{"key":"value3"}"#, "/services/collector").await
+                );
+                assert_eq!(
+                    404,
+                    send_with_path(addr, r#"{"key":"value4"}"#, "/events/more").await
+                );
+            },
+            rx,
+            2,
+        )
+        .await;
+
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value1".into());
+            assert_eq!(log["route"], "hec".into());
+            assert_eq!(log["channel"], "abc".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value2".into());
+            assert_eq!(log["route"], "events".into());
+            assert!(log.get("channel").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_custom_response() {
+        trace_init();
+        let (rx, addr) = source_with_config(
+            r#"
+            encoding = "ndjson"
+            response.status = 202
+            response.body = '{"text":"Success","id":"{{ id }}"}'
+            response.headers.content-type = "application/json"
+            "#,
+        )
+        .await;
+
+        spawn_collect_n(
+            async move {
+                let response = reqwest::Client::new()
+                    .post(&format!("http://{}/", addr))
+                    .body(r#"{"id":"x1"}"#)
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(202, response.status().as_u16());
+                assert_eq!(response.headers()["content-type"], "application/json");
+                assert_eq!(
+                    response.text().await.unwrap(),
+                    r#"{"text":"Success","id":"x1"}"#
+                );
+            },
+            rx,
+            1,
+        )
+        .await;
+    }
 }
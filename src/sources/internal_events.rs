@@ -0,0 +1,159 @@
+use crate::{
+    config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
+    event::Event,
+    shutdown::ShutdownSignal,
+    trace, Pipeline,
+};
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InternalEventsConfig {
+    host_key: Option<String>,
+    pid_key: Option<String>,
+}
+
+inventory::submit! {
+    SourceDescription::new::<InternalEventsConfig>("internal_events")
+}
+
+impl_generate_config_from_default!(InternalEventsConfig);
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "internal_events")]
+impl SourceConfig for InternalEventsConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let host_key = self
+            .host_key
+            .as_deref()
+            .unwrap_or_else(|| log_schema().host_key())
+            .to_owned();
+        let pid_key = self.pid_key.as_deref().unwrap_or("pid").to_owned();
+
+        Ok(Box::pin(run(host_key, pid_key, cx.out, cx.shutdown)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "internal_events"
+    }
+}
+
+async fn run(
+    host_key: String,
+    pid_key: String,
+    out: Pipeline,
+    mut shutdown: ShutdownSignal,
+) -> Result<(), ()> {
+    let mut out = out.sink_map_err(|error| error!(message = "Error sending event.", %error));
+    let mut rx = trace::subscribe_component_events();
+
+    let hostname = crate::get_hostname();
+    let pid = std::process::id();
+
+    // Note: This loop, or anything called within it, MUST NOT generate
+    // any warnings or errors within a component that don't break the loop,
+    // as that could cause an infinite loop since it receives all of them.
+    loop {
+        tokio::select! {
+            receive = rx.recv() => {
+                match receive {
+                    Ok(mut log) => {
+                        if let Ok(hostname) = &hostname {
+                            log.insert(host_key.clone(), hostname.to_owned());
+                        }
+                        log.insert(pid_key.clone(), pid);
+                        out.send(Event::from(log)).await?
+                    }
+                    Err(RecvError::Lagged(_)) => (),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{internal_events::InternalEvent, test_util::collect_ready};
+    use tokio::time::{sleep, Duration};
+
+    #[derive(Debug)]
+    struct TestRequestFailed;
+
+    impl InternalEvent for TestRequestFailed {
+        fn emit_logs(&self) {
+            error!(message = "Request failed.", error = "timed out");
+        }
+    }
+
+    #[test]
+    fn generates_config() {
+        crate::test_util::test_generate_config::<InternalEventsConfig>();
+    }
+
+    #[tokio::test]
+    async fn receives_component_errors() {
+        trace::init(false, false, "debug", false);
+
+        let (tx, rx) = Pipeline::new_test();
+        let source = InternalEventsConfig::default()
+            .build(SourceContext::new_test(tx))
+            .await
+            .unwrap();
+        tokio::spawn(source);
+        sleep(Duration::from_millis(1)).await;
+
+        error!(message = "Outside of any component.");
+        error_span!(
+            "sink",
+            component_id = "internal_events_test",
+            component_kind = "sink",
+            component_type = "http",
+        )
+        .in_scope(|| {
+            info_span!("request").in_scope(|| {
+                info!(message = "Not an error.");
+                emit!(TestRequestFailed);
+            });
+            warn!(message = "Retrying.");
+        });
+
+        sleep(Duration::from_millis(1)).await;
+        // Components of other tests may be running meanwhile.
+        let events = collect_ready(rx)
+            .await
+            .into_iter()
+            .filter(|event| {
+                event.as_log().get("component_id") == Some(&"internal_events_test".into())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 2);
+
+        let log = events[0].as_log();
+        assert_eq!(log["message"], "Request failed.".into());
+        assert_eq!(log["error"], "timed out".into());
+        assert_eq!(log["error_code"], "TestRequestFailed".into());
+        assert_eq!(log["component_id"], "internal_events_test".into());
+        assert_eq!(log["component_kind"], "sink".into());
+        assert_eq!(log["component_type"], "http".into());
+        assert_eq!(log["metadata.level"], "ERROR".into());
+        assert_eq!(log["pid"], (std::process::id() as i64).into());
+
+        let log = events[1].as_log();
+        assert_eq!(log["message"], "Retrying.".into());
+        assert!(log.get("error_code").is_none());
+        assert_eq!(log["component_id"], "internal_events_test".into());
+        assert_eq!(log["metadata.level"], "WARN".into());
+    }
+}
@@ -17,9 +17,9 @@ use nix::{
 use serde::{Deserialize, Serialize};
 use serde_json::{Error as JsonError, Value as JsonValue};
 use snafu::{ResultExt, Snafu};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::SeekFrom,
     iter::FromIterator,
     process::Stdio,
@@ -62,6 +62,13 @@ enum BuildError {
         unit
     ))]
     DuplicatedUnit { unit: String },
+    #[snafu(display("Cannot use both `since` and `since_cursor`"))]
+    BothSinceAndSinceCursor,
+    #[snafu(display(
+        "The field {:?} of `include_matches` is not a journal field name",
+        field
+    ))]
+    InvalidMatchField { field: String },
 }
 
 #[derive(Deserialize, Serialize, Debug, Default)]
@@ -75,6 +82,17 @@ pub struct JournaldConfig {
     pub batch_size: Option<usize>,
     pub journalctl_path: Option<PathBuf>,
     pub journal_directory: Option<PathBuf>,
+    /// The journal namespace to read, as set by `LogNamespace=` in the units
+    /// logging to it.
+    pub journal_namespace: Option<String>,
+    /// Start right after this journal cursor when there is no checkpoint.
+    pub since_cursor: Option<String>,
+    /// Start from this time when there is no checkpoint, in any format the
+    /// `--since` option of journalctl accepts.
+    pub since: Option<String>,
+    /// Only read entries with one of the given values of each field, as
+    /// matched by the journal itself rather than in Vector.
+    pub include_matches: BTreeMap<String, Vec<String>>,
     /// Deprecated
     #[serde(default)]
     remap_priority: bool,
@@ -120,25 +138,38 @@ impl SourceConfig for JournaldConfig {
             return Err(BuildError::DuplicatedUnit { unit }.into());
         }
 
+        if self.since.is_some() && self.since_cursor.is_some() {
+            return Err(BuildError::BothSinceAndSinceCursor.into());
+        }
+        if let Some(field) = self
+            .include_matches
+            .keys()
+            .find(|field| !is_journal_field(field))
+        {
+            let field = field.clone();
+            return Err(BuildError::InvalidMatchField { field }.into());
+        }
+
         let mut checkpoint_path = data_dir;
         checkpoint_path.push(CHECKPOINT_FILENAME);
 
-        let journalctl_path = self
-            .journalctl_path
-            .clone()
-            .unwrap_or_else(|| JOURNALCTL.clone());
-
         let batch_size = self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
-        let current_boot_only = self.current_boot_only.unwrap_or(true);
-        let journal_dir = self.journal_directory.clone();
+
+        let journalctl = Journalctl {
+            path: self
+                .journalctl_path
+                .clone()
+                .unwrap_or_else(|| JOURNALCTL.clone()),
+            journal_dir: self.journal_directory.clone(),
+            namespace: self.journal_namespace.clone(),
+            current_boot_only: self.current_boot_only.unwrap_or(true),
+            since: self.since.clone(),
+            since_cursor: self.since_cursor.clone(),
+            include_matches: self.include_matches.clone(),
+        };
 
         let start: StartJournalctlFn = Box::new(move |cursor| {
-            let mut command = create_command(
-                &journalctl_path,
-                journal_dir.as_ref(),
-                current_boot_only,
-                cursor,
-            );
+            let mut command = journalctl.command(cursor);
             start_journalctl(&mut command)
         });
 
@@ -364,35 +395,69 @@ fn start_journalctl(
     Ok((stream, stop))
 }
 
-fn create_command(
-    path: &Path,
-    journal_dir: Option<&PathBuf>,
+/// How to run journalctl, apart from the checkpoint to resume from.
+struct Journalctl {
+    path: PathBuf,
+    journal_dir: Option<PathBuf>,
+    namespace: Option<String>,
     current_boot_only: bool,
-    cursor: &Option<String>,
-) -> Command {
-    let mut command = Command::new(path);
-    command.stdout(Stdio::piped());
-    command.arg("--follow");
-    command.arg("--all");
-    command.arg("--show-cursor");
-    command.arg("--output=json");
+    since: Option<String>,
+    since_cursor: Option<String>,
+    include_matches: BTreeMap<String, Vec<String>>,
+}
 
-    if let Some(dir) = journal_dir {
-        command.arg(format!("--directory={}", dir.display()));
-    }
+impl Journalctl {
+    fn command(&self, checkpoint: &Option<String>) -> Command {
+        let mut command = Command::new(&self.path);
+        command.stdout(Stdio::piped());
+        command.arg("--follow");
+        command.arg("--all");
+        command.arg("--show-cursor");
+        command.arg("--output=json");
+
+        if let Some(dir) = &self.journal_dir {
+            command.arg(format!("--directory={}", dir.display()));
+        }
 
-    if current_boot_only {
-        command.arg("--boot");
-    }
+        if let Some(namespace) = &self.namespace {
+            command.arg(format!("--namespace={}", namespace));
+        }
 
-    if let Some(cursor) = cursor {
-        command.arg(format!("--after-cursor={}", cursor));
-    } else {
-        // journalctl --follow only outputs a few lines without a starting point
-        command.arg("--since=2000-01-01");
+        if self.current_boot_only {
+            command.arg("--boot");
+        }
+
+        // The checkpoint always wins, so that configuring a starting point
+        // doesn't read the same entries again on every restart.
+        if let Some(cursor) = checkpoint.as_ref().or_else(|| self.since_cursor.as_ref()) {
+            command.arg(format!("--after-cursor={}", cursor));
+        } else if let Some(since) = &self.since {
+            command.arg(format!("--since={}", since));
+        } else {
+            // journalctl --follow only outputs a few lines without a starting point
+            command.arg("--since=2000-01-01");
+        }
+
+        // journalctl ORs the matches of a field together, and ANDs those of
+        // different fields.
+        for (field, values) in &self.include_matches {
+            for value in values {
+                command.arg(format!("{}={}", field, value));
+            }
+        }
+
+        command
     }
+}
 
-    command
+/// Whether the name is valid for a journal field, so a match on it can't be
+/// taken by journalctl as an option.
+fn is_journal_field(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
 }
 
 fn create_event(record: Record) -> Event {
@@ -405,7 +470,7 @@ fn create_event(record: Record) -> Event {
         log.insert(log_schema().host_key(), host);
     }
     // Translate the timestamp, and so leave both old and new names.
-    if let Some(Value::Bytes(timestamp)) = log
+    if let Some(Value::Bytes(timestamp) | Value::String(timestamp)) = log
         .get(&*SOURCE_TIMESTAMP)
         .or_else(|| log.get(RECEIVED_TIMESTAMP))
     {
@@ -706,24 +771,24 @@ mod tests {
         assert_eq!(received.len(), 7);
         assert_eq!(
             message(&received[0]),
-            Value::Bytes("System Initialization".into())
+            Value::String("System Initialization".into())
         );
         assert_eq!(
             received[0].as_log()[log_schema().source_type_key()],
             "journald".into()
         );
         assert_eq!(timestamp(&received[0]), value_ts(1578529839, 140001000));
-        assert_eq!(priority(&received[0]), Value::Bytes("INFO".into()));
-        assert_eq!(message(&received[1]), Value::Bytes("unit message".into()));
+        assert_eq!(priority(&received[0]), Value::String("INFO".into()));
+        assert_eq!(message(&received[1]), Value::String("unit message".into()));
         assert_eq!(timestamp(&received[1]), value_ts(1578529839, 140002000));
-        assert_eq!(priority(&received[1]), Value::Bytes("DEBUG".into()));
+        assert_eq!(priority(&received[1]), Value::String("DEBUG".into()));
     }
 
     #[tokio::test]
     async fn includes_units() {
         let received = run_journal(&["unit.service"], &[], None).await;
         assert_eq!(received.len(), 1);
-        assert_eq!(message(&received[0]), Value::Bytes("unit message".into()));
+        assert_eq!(message(&received[0]), Value::String("unit message".into()));
         assert_eq!(timestamp(&received[0]), value_ts(1578529839, 140002000));
     }
 
@@ -733,15 +798,15 @@ mod tests {
         assert_eq!(received.len(), 5);
         assert_eq!(
             message(&received[0]),
-            Value::Bytes("System Initialization".into())
+            Value::String("System Initialization".into())
         );
         assert_eq!(
             message(&received[1]),
-            Value::Bytes("Missing timestamp".into())
+            Value::String("Missing timestamp".into())
         );
         assert_eq!(
             message(&received[2]),
-            Value::Bytes("Different timestamps".into())
+            Value::String("Different timestamps".into())
         );
     }
 
@@ -749,7 +814,7 @@ mod tests {
     async fn handles_checkpoint() {
         let received = run_journal(&[], &[], Some("1")).await;
         assert_eq!(received.len(), 6);
-        assert_eq!(message(&received[0]), Value::Bytes("unit message".into()));
+        assert_eq!(message(&received[0]), Value::String("unit message".into()));
         assert_eq!(timestamp(&received[0]), value_ts(1578529839, 140002000));
     }
 
@@ -757,7 +822,7 @@ mod tests {
     async fn parses_array_messages() {
         let received = run_journal(&["badunit.service"], &[], None).await;
         assert_eq!(received.len(), 1);
-        assert_eq!(message(&received[0]), Value::Bytes("¿Hello?".into()));
+        assert_eq!(message(&received[0]), Value::String("¿Hello?".into()));
     }
 
     #[tokio::test]
@@ -766,7 +831,7 @@ mod tests {
         assert_eq!(received.len(), 1);
         assert_eq!(
             received[0].as_log()["SYSLOG_RAW"],
-            Value::Bytes("¿World?".into())
+            Value::String("¿World?".into())
         );
     }
 
@@ -776,7 +841,7 @@ mod tests {
         assert_eq!(received.len(), 1);
         assert_eq!(
             received[0].as_log()["SYSLOG_FACILITY"],
-            Value::Bytes(r#"["DHCP4","DHCP6"]"#.into())
+            Value::String(r#"["DHCP4","DHCP6"]"#.into())
         );
     }
 
@@ -810,31 +875,85 @@ mod tests {
         assert!(filter_unit(Some(&two), &includes, &excludes));
     }
 
+    fn journalctl() -> Journalctl {
+        Journalctl {
+            path: PathBuf::from("jornalctl"),
+            journal_dir: None,
+            namespace: None,
+            current_boot_only: false,
+            since: None,
+            since_cursor: None,
+            include_matches: BTreeMap::new(),
+        }
+    }
+
     #[test]
     fn command_options() {
-        let path = PathBuf::from("jornalctl");
+        let mut journalctl = journalctl();
 
-        let journal_dir = None;
-        let current_boot_only = false;
-        let cursor = None;
-
-        let command = create_command(&path, journal_dir, current_boot_only, &cursor);
+        let command = journalctl.command(&None);
         let cmd_line = format!("{:?}", command);
         assert!(!cmd_line.contains("--directory="));
+        assert!(!cmd_line.contains("--namespace="));
         assert!(!cmd_line.contains("--boot"));
         assert!(cmd_line.contains("--since=2000-01-01"));
 
-        let journal_dir = Some(PathBuf::from("/tmp/journal-dir"));
-        let current_boot_only = true;
+        journalctl.journal_dir = Some(PathBuf::from("/tmp/journal-dir"));
+        journalctl.namespace = Some(String::from("audit"));
+        journalctl.current_boot_only = true;
         let cursor = Some(String::from("2021-01-01"));
 
-        let command = create_command(&path, journal_dir.as_ref(), current_boot_only, &cursor);
+        let command = journalctl.command(&cursor);
         let cmd_line = format!("{:?}", command);
         assert!(cmd_line.contains("--directory=/tmp/journal-dir"));
+        assert!(cmd_line.contains("--namespace=audit"));
         assert!(cmd_line.contains("--boot"));
         assert!(cmd_line.contains("--after-cursor="));
     }
 
+    #[test]
+    fn command_starting_point() {
+        let mut journalctl = journalctl();
+        journalctl.since = Some(String::from("-1h"));
+
+        let cmd_line = format!("{:?}", journalctl.command(&None));
+        assert!(cmd_line.contains("--since=-1h"));
+
+        let cmd_line = format!("{:?}", journalctl.command(&Some(String::from("saved"))));
+        assert!(cmd_line.contains("--after-cursor=saved"));
+        assert!(!cmd_line.contains("--since="));
+
+        journalctl.since = None;
+        journalctl.since_cursor = Some(String::from("configured"));
+
+        let cmd_line = format!("{:?}", journalctl.command(&None));
+        assert!(cmd_line.contains("--after-cursor=configured"));
+        assert!(!cmd_line.contains("--since="));
+    }
+
+    #[test]
+    fn command_matches() {
+        let mut journalctl = journalctl();
+        journalctl
+            .include_matches
+            .insert("PRIORITY".into(), vec!["0".into(), "1".into()]);
+        journalctl
+            .include_matches
+            .insert("_TRANSPORT".into(), vec!["kernel".into()]);
+
+        let cmd_line = format!("{:?}", journalctl.command(&None));
+        assert!(cmd_line.contains(r#""PRIORITY=0" "PRIORITY=1" "_TRANSPORT=kernel""#));
+    }
+
+    #[test]
+    fn validates_journal_fields() {
+        assert!(is_journal_field("_SYSTEMD_UNIT"));
+        assert!(is_journal_field("PRIORITY"));
+        assert!(!is_journal_field("priority"));
+        assert!(!is_journal_field("--output"));
+        assert!(!is_journal_field(""));
+    }
+
     fn message(event: &Event) -> Value {
         event.as_log()[log_schema().message_key()].clone()
     }
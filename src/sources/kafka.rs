@@ -1,8 +1,13 @@
 use super::util::finalizer::OrderedFinalizer;
 use crate::{
+    avro::{AvroDecoder, AvroError, AvroOptions},
     config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
-    internal_events::{KafkaEventFailed, KafkaEventReceived, KafkaOffsetUpdateFailed},
+    internal_events::{
+        AvroDecodingFailed, KafkaEventFailed, KafkaEventReceived, KafkaOffsetUpdateFailed,
+        ProtobufDecodingFailed,
+    },
     kafka::{KafkaAuthConfig, KafkaStatisticsContext},
+    protobuf::{MessageDecoder, ProtobufOptions},
     shutdown::ShutdownSignal,
     Pipeline,
 };
@@ -27,6 +32,8 @@ enum BuildError {
     KafkaCreateError { source: rdkafka::error::KafkaError },
     #[snafu(display("Could not subscribe to Kafka topics: {}", source))]
     KafkaSubscribeError { source: rdkafka::error::KafkaError },
+    #[snafu(display("Only one of `avro` or `protobuf` can be set"))]
+    ConflictingDecoders,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -55,6 +62,12 @@ pub struct KafkaSourceConfig {
     offset_key: String,
     #[serde(default = "default_headers_key")]
     headers_key: String,
+    /// Decodes message values as Avro, with their fields at the root of the
+    /// events rather than in the message.
+    avro: Option<AvroOptions>,
+    /// Decodes message values as bare protobuf messages, with their fields at
+    /// the root of the events.
+    protobuf: Option<ProtobufOptions>,
     librdkafka_options: Option<HashMap<String, String>>,
     #[serde(flatten)]
     auth: KafkaAuthConfig,
@@ -106,24 +119,72 @@ inventory::submit! {
 
 impl_generate_config_from_default!(KafkaSourceConfig);
 
-#[async_trait::async_trait]
-#[typetag::serde(name = "kafka")]
-impl SourceConfig for KafkaSourceConfig {
-    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+impl KafkaSourceConfig {
+    /// A consumer of the given topics with the defaults of every other option,
+    /// for the sources built on top of this one.
+    #[cfg(feature = "sources-azure_event_hubs")]
+    pub(super) fn new(
+        bootstrap_servers: String,
+        topics: Vec<String>,
+        group_id: String,
+        auth: KafkaAuthConfig,
+    ) -> Self {
+        Self {
+            bootstrap_servers,
+            topics,
+            group_id,
+            auto_offset_reset: default_auto_offset_reset(),
+            session_timeout_ms: default_session_timeout_ms(),
+            socket_timeout_ms: default_socket_timeout_ms(),
+            fetch_wait_max_ms: default_fetch_wait_max_ms(),
+            commit_interval_ms: default_commit_interval_ms(),
+            key_field: default_key_field(),
+            topic_key: default_topic_key(),
+            partition_key: default_partition_key(),
+            offset_key: default_offset_key(),
+            headers_key: default_headers_key(),
+            avro: None,
+            protobuf: None,
+            librdkafka_options: None,
+            auth,
+        }
+    }
+
+    pub(super) async fn build_source(
+        &self,
+        cx: SourceContext,
+        source_type: &'static str,
+    ) -> crate::Result<super::Source> {
         let consumer = create_consumer(self)?;
+        let decoder = match (&self.avro, &self.protobuf) {
+            (Some(_), Some(_)) => return Err(BuildError::ConflictingDecoders.into()),
+            (Some(avro), None) => Some(Decoder::Avro(avro.decoder(&cx.proxy).await?)),
+            (None, Some(protobuf)) => Some(Decoder::Protobuf(protobuf.decoder()?)),
+            (None, None) => None,
+        };
 
         Ok(Box::pin(kafka_source(
             consumer,
+            source_type,
             self.key_field.clone(),
             self.topic_key.clone(),
             self.partition_key.clone(),
             self.offset_key.clone(),
             self.headers_key.clone(),
+            decoder,
             cx.shutdown,
             cx.out,
             cx.acknowledgements,
         )))
     }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "kafka")]
+impl SourceConfig for KafkaSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        self.build_source(cx, "kafka").await
+    }
 
     fn output_type(&self) -> DataType {
         DataType::Log
@@ -134,13 +195,21 @@ impl SourceConfig for KafkaSourceConfig {
     }
 }
 
+/// Decodes message values to the fields of events.
+enum Decoder {
+    Avro(AvroDecoder),
+    Protobuf(MessageDecoder),
+}
+
 async fn kafka_source(
     consumer: StreamConsumer<KafkaStatisticsContext>,
+    source_type: &'static str,
     key_field: String,
     topic_key: String,
     partition_key: String,
     offset_key: String,
     headers_key: String,
+    decoder: Option<Decoder>,
     shutdown: ShutdownSignal,
     mut out: Pipeline,
     acknowledgements: bool,
@@ -167,10 +236,38 @@ async fn kafka_source(
                 };
                 let mut log = LogEvent::default();
 
-                log.insert(
-                    log_schema().message_key(),
-                    Value::from(Bytes::from(payload.to_owned())),
-                );
+                match &decoder {
+                    Some(Decoder::Avro(avro)) => match avro.decode_message(payload) {
+                        Ok(Value::Map(fields)) => {
+                            for (key, value) in fields {
+                                log.insert_flat(key, value);
+                            }
+                        }
+                        Ok(_) => {
+                            emit!(AvroDecodingFailed {
+                                error: &AvroError::NotARecord
+                            });
+                            continue;
+                        }
+                        Err(error) => {
+                            emit!(AvroDecodingFailed { error: &error });
+                            continue;
+                        }
+                    },
+                    Some(Decoder::Protobuf(protobuf)) => match protobuf.decode(payload) {
+                        Ok(decoded) => log = decoded,
+                        Err(error) => {
+                            emit!(ProtobufDecodingFailed { error: &error });
+                            continue;
+                        }
+                    },
+                    None => {
+                        log.insert(
+                            log_schema().message_key(),
+                            Value::from(Bytes::from(payload.to_owned())),
+                        );
+                    }
+                }
 
                 // Extract timestamp from kafka message
                 let timestamp = msg
@@ -181,7 +278,7 @@ async fn kafka_source(
                 log.insert(log_schema().timestamp_key(), timestamp);
 
                 // Add source type
-                log.insert(log_schema().source_type_key(), Bytes::from("kafka"));
+                log.insert(log_schema().source_type_key(), Bytes::from(source_type));
 
                 let msg_key = msg
                     .key()
@@ -197,13 +294,11 @@ async fn kafka_source(
 
                 let mut headers_map = BTreeMap::new();
                 if let Some(headers) = msg.headers() {
-                    // Using index-based for loop because rdkafka's `Headers` trait
-                    // does not provide Iterator-based API
-                    for i in 0..headers.count() {
-                        if let Some(header) = headers.get(i) {
+                    for header in headers.iter() {
+                        if let Some(value) = header.value {
                             headers_map.insert(
-                                header.0.to_string(),
-                                Bytes::from(header.1.to_owned()).into(),
+                                header.key.to_string(),
+                                Bytes::from(value.to_owned()).into(),
                             );
                         }
                     }
@@ -222,7 +317,7 @@ async fn kafka_source(
                     None => match out.send(log.into()).await {
                         Err(error) => error!(message = "Error sending to sink.", %error),
                         Ok(_) => {
-                            if let Err(error) = consumer.store_offset(&msg) {
+                            if let Err(error) = consumer.store_offset_from_message(&msg) {
                                 emit!(KafkaOffsetUpdateFailed { error });
                             }
                         }
@@ -295,7 +390,7 @@ fn create_consumer(
     }
 
     let consumer = client_config
-        .create_with_context::<_, StreamConsumer<_>>(KafkaStatisticsContext)
+        .create_with_context::<_, StreamConsumer<_>>(KafkaStatisticsContext::new(&config.auth)?)
         .context(KafkaCreateError)?;
     let topics: Vec<&str> = config.topics.iter().map(|s| s.as_str()).collect();
     consumer.subscribe(&topics).context(KafkaSubscribeError)?;
@@ -363,7 +458,7 @@ mod integration_test {
     use rdkafka::{
         config::{ClientConfig, FromClientConfig},
         consumer::BaseConsumer,
-        message::OwnedHeaders,
+        message::{Header, OwnedHeaders},
         producer::{FutureProducer, FutureRecord},
         util::Timeout,
     };
@@ -399,7 +494,10 @@ mod integration_test {
                 .payload(&text)
                 .key(key)
                 .timestamp(timestamp)
-                .headers(OwnedHeaders::new().add(header_key, header_value));
+                .headers(OwnedHeaders::new().insert(Header {
+                    key: header_key,
+                    value: Some(header_value),
+                }));
 
             if let Err(error) = producer.send(record, Timeout::Never).await {
                 panic!("Cannot send event to Kafka: {:?}", error);
@@ -439,11 +537,13 @@ mod integration_test {
         let (tx, rx) = Pipeline::new_test_finalize(EventStatus::Delivered);
         tokio::spawn(kafka_source(
             create_consumer(&config).unwrap(),
+            "kafka",
             config.key_field,
             config.topic_key,
             config.partition_key,
             config.offset_key,
             config.headers_key,
+            None,
             shutdown,
             tx,
             acknowledgements,
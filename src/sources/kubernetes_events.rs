@@ -0,0 +1,482 @@
+//! This mod implements `kubernetes_events` source.
+//! The scope of this source is to watch the Events API of the cluster, and
+//! emit the events the cluster components report on the activity of its
+//! objects, such as scheduling, image pulls, probe failures or evictions.
+//! Unlike `kubernetes_logs`, it's meant to run as a single instance per
+//! cluster.
+
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, ProxyConfig, SourceConfig, SourceContext,
+        SourceDescription,
+    },
+    event::{Event, LogEvent, Value},
+    internal_events::{
+        kubernetes::reflector as reflector_events, KubernetesEventsCheckpointFailed,
+        KubernetesEventsReceived,
+    },
+    kubernetes as k8s,
+    kubernetes::{
+        resource_version,
+        watcher::{self, Watcher},
+    },
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::{SinkExt, StreamExt};
+use k8s_openapi::{
+    api::core::v1::Event as KubeEvent, apimachinery::pkg::apis::meta::v1::WatchEvent, WatchOptional,
+};
+use serde::{Deserialize, Serialize};
+use std::{io::SeekFrom, path::PathBuf, time::Duration};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    time::sleep,
+};
+
+const COMPONENT_ID: &str = "kubernetes_events";
+
+const CHECKPOINT_FILENAME: &str = "resource_version.txt";
+
+/// The key of the kind of watch notification an event was emitted for, either
+/// `ADDED` or `MODIFIED`.
+const VERB_KEY: &str = "verb";
+
+/// Configuration for the `kubernetes_events` source.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct Config {
+    /// Specifies the field selector to filter the `Event`s with, such as
+    /// `involvedObject.kind=Pod` or `type=Warning`.
+    field_selector: String,
+
+    /// Specifies the label selector to filter the `Event`s with.
+    label_selector: String,
+
+    /// `Event`s last seen longer ago than this are skipped. Without a resource
+    /// version to resume from, the watch starts with all the `Event`s the
+    /// cluster still keeps, for an hour by default.
+    max_event_age_secs: u64,
+
+    /// Override global data_dir
+    data_dir: Option<PathBuf>,
+
+    /// Optional path to a kubeconfig file readable by Vector. If not set,
+    /// Vector will try to connect to Kubernetes using in-cluster configuration.
+    kube_config_file: Option<PathBuf>,
+}
+
+inventory::submit! {
+    SourceDescription::new::<Config>(COMPONENT_ID)
+}
+
+impl GenerateConfig for Config {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(&Self::default()).unwrap()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            field_selector: "".to_string(),
+            label_selector: "".to_string(),
+            max_event_age_secs: 600,
+            data_dir: None,
+            kube_config_file: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "kubernetes_events")]
+impl SourceConfig for Config {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let client = client(self, &cx.proxy)?;
+        let watcher =
+            k8s::api_watcher::ApiWatcher::new(client, KubeEvent::watch_event_for_all_namespaces);
+        let watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(watcher);
+
+        let data_dir = cx
+            .globals
+            .resolve_and_make_data_subdir(self.data_dir.as_ref(), &cx.id)?;
+        let checkpointer = Checkpointer::new(data_dir.join(CHECKPOINT_FILENAME)).await?;
+
+        let source = EventsSource {
+            field_selector: non_empty(&self.field_selector),
+            label_selector: non_empty(&self.label_selector),
+            max_event_age: ChronoDuration::seconds(self.max_event_age_secs as i64),
+            checkpointer,
+        };
+        Ok(Box::pin(source.run(watcher, cx.shutdown, cx.out)))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        COMPONENT_ID
+    }
+}
+
+fn client(config: &Config, proxy: &ProxyConfig) -> crate::Result<k8s::client::Client> {
+    let k8s_config = match &config.kube_config_file {
+        Some(kc) => k8s::client::config::Config::kubeconfig(kc)?,
+        None => k8s::client::config::Config::in_cluster()?,
+    };
+    Ok(k8s::client::Client::new(k8s_config, proxy)?)
+}
+
+fn non_empty(selector: &str) -> Option<String> {
+    (!selector.is_empty()).then(|| selector.to_owned())
+}
+
+struct EventsSource {
+    field_selector: Option<String>,
+    label_selector: Option<String>,
+    max_event_age: ChronoDuration,
+    checkpointer: Checkpointer,
+}
+
+impl EventsSource {
+    async fn run<W>(
+        mut self,
+        mut watcher: W,
+        mut shutdown: ShutdownSignal,
+        mut out: Pipeline,
+    ) -> Result<(), ()>
+    where
+        W: Watcher<Object = KubeEvent> + Send,
+    {
+        let mut resource_version = resource_version::State::new();
+        match self.checkpointer.get().await {
+            Ok(Some(checkpoint)) => {
+                resource_version.update(resource_version::Candidate::from_persisted(checkpoint));
+            }
+            Ok(None) => {}
+            Err(error) => emit!(KubernetesEventsCheckpointFailed { error }),
+        }
+
+        'outer: loop {
+            let watch_optional = WatchOptional {
+                field_selector: self.field_selector.as_deref(),
+                label_selector: self.label_selector.as_deref(),
+                pretty: None,
+                resource_version: resource_version.get(),
+                timeout_seconds: Some(290), // https://github.com/kubernetes/kubernetes/issues/6513
+                allow_watch_bookmarks: Some(true),
+            };
+            let stream = tokio::select! {
+                _ = &mut shutdown => break,
+                result = watcher.watch(watch_optional) => result,
+            };
+            let mut stream = match stream {
+                Ok(stream) => Box::pin(stream),
+                Err(watcher::invocation::Error::Desync { source }) => {
+                    emit!(reflector_events::InvocationDesyncReceived { error: source });
+                    resource_version.reset();
+                    continue;
+                }
+                Err(watcher::invocation::Error::Recoverable { source }) => {
+                    emit!(reflector_events::InvocationHttpErrorReceived { error: source });
+                    if pause(&mut shutdown).await {
+                        break;
+                    }
+                    continue;
+                }
+                Err(watcher::invocation::Error::Other { source }) => {
+                    error!(message = "Watcher error.", error = ?source);
+                    if pause(&mut shutdown).await {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            loop {
+                let item = tokio::select! {
+                    _ = &mut shutdown => break 'outer,
+                    item = stream.next() => match item {
+                        Some(item) => item,
+                        // The watch timed out, resume it.
+                        None => continue 'outer,
+                    },
+                };
+                let watch_event = match item {
+                    Ok(watch_event) => watch_event,
+                    Err(watcher::stream::Error::Desync { source }) => {
+                        emit!(reflector_events::StreamDesyncReceived { error: source });
+                        resource_version.reset();
+                        continue 'outer;
+                    }
+                    Err(watcher::stream::Error::Recoverable { source }) => {
+                        emit!(reflector_events::InvocationHttpErrorReceived { error: source });
+                        continue 'outer;
+                    }
+                };
+
+                let candidate = match resource_version::Candidate::from_watch_event(&watch_event) {
+                    Some(candidate) => candidate,
+                    None => continue,
+                };
+                let event = match watch_event {
+                    WatchEvent::Added(object) => self.to_event(object, "ADDED"),
+                    WatchEvent::Modified(object) => self.to_event(object, "MODIFIED"),
+                    // `Event`s are deleted as they expire, and bookmarks only
+                    // move the resource version on.
+                    _ => None,
+                };
+                if let Some(event) = event {
+                    if let Err(error) = out.send(event).await {
+                        error!(message = "Error sending event.", %error);
+                        return Err(());
+                    }
+                }
+
+                // Record the bookmark only once the event is sent, so it's
+                // emitted again if Vector stops before.
+                resource_version.update(candidate);
+                if let Some(checkpoint) = resource_version.get() {
+                    if let Err(error) = self.checkpointer.set(checkpoint).await {
+                        emit!(KubernetesEventsCheckpointFailed { error });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_event(&self, object: KubeEvent, verb: &str) -> Option<Event> {
+        let timestamp = last_seen(&object);
+        if timestamp.map_or(false, |timestamp| {
+            Utc::now() - timestamp > self.max_event_age
+        }) {
+            return None;
+        }
+
+        let json = serde_json::to_value(&object).ok()?;
+        let byte_size = json.to_string().len();
+        let mut log = match Value::from(json) {
+            Value::Map(fields) => LogEvent::from(fields),
+            _ => return None,
+        };
+        emit!(KubernetesEventsReceived { byte_size });
+
+        log.insert(VERB_KEY, verb);
+        log.insert(
+            log_schema().timestamp_key(),
+            timestamp.unwrap_or_else(Utc::now),
+        );
+        log.insert(log_schema().source_type_key(), Bytes::from(COMPONENT_ID));
+        Some(log.into())
+    }
+}
+
+/// Waits before retrying a failed watch request, returns whether the source is
+/// shutting down.
+async fn pause(shutdown: &mut ShutdownSignal) -> bool {
+    tokio::select! {
+        _ = shutdown => true,
+        _ = sleep(Duration::from_secs(1)) => false,
+    }
+}
+
+/// When the event last occurred, out of the fields the different reporters
+/// fill in.
+fn last_seen(event: &KubeEvent) -> Option<DateTime<Utc>> {
+    event
+        .series
+        .as_ref()
+        .and_then(|series| series.last_observed_time.as_ref())
+        .map(|time| time.0)
+        .or_else(|| event.last_timestamp.as_ref().map(|time| time.0))
+        .or_else(|| event.event_time.as_ref().map(|time| time.0))
+        .or_else(|| event.first_timestamp.as_ref().map(|time| time.0))
+        .or_else(|| {
+            event
+                .metadata
+                .creation_timestamp
+                .as_ref()
+                .map(|time| time.0)
+        })
+}
+
+/// Keeps the resource version to resume watching from across restarts.
+struct Checkpointer {
+    file: File,
+}
+
+impl Checkpointer {
+    async fn new(filename: PathBuf) -> Result<Self, std::io::Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&filename)
+            .await?;
+        Ok(Self { file })
+    }
+
+    async fn set(&mut self, resource_version: &str) -> Result<(), std::io::Error> {
+        let line = format!("{}\n", resource_version);
+        self.file.seek(SeekFrom::Start(0)).await?;
+        self.file.write_all(line.as_bytes()).await?;
+        self.file.set_len(line.len() as u64).await
+    }
+
+    async fn get(&mut self) -> Result<Option<String>, std::io::Error> {
+        let mut text = String::new();
+        self.file.seek(SeekFrom::Start(0)).await?;
+        self.file.read_to_string(&mut text).await?;
+        Ok(text
+            .lines()
+            .next()
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kubernetes::mock_watcher::{
+            MockWatcher, ScenarioActionInvocation, ScenarioActionStream, ScenarioEvent,
+        },
+        test_util::collect_ready,
+    };
+    use futures::channel::mpsc;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time};
+    use tempfile::tempdir;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<Config>();
+    }
+
+    fn kube_event(name: &str, resource_version: &str, last_timestamp: DateTime<Utc>) -> KubeEvent {
+        KubeEvent {
+            metadata: ObjectMeta {
+                name: Some(name.to_owned()),
+                namespace: Some("default".to_owned()),
+                resource_version: Some(resource_version.to_owned()),
+                ..ObjectMeta::default()
+            },
+            reason: Some("Scheduled".to_owned()),
+            message: Some("Successfully assigned default/pod to node".to_owned()),
+            last_timestamp: Some(Time(last_timestamp)),
+            ..KubeEvent::default()
+        }
+    }
+
+    async fn source(dir: &std::path::Path) -> EventsSource {
+        EventsSource {
+            field_selector: None,
+            label_selector: None,
+            max_event_age: ChronoDuration::seconds(600),
+            checkpointer: Checkpointer::new(dir.join(CHECKPOINT_FILENAME))
+                .await
+                .unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpointer_round_trips() {
+        let dir = tempdir().unwrap();
+        let mut checkpointer = Checkpointer::new(dir.path().join(CHECKPOINT_FILENAME))
+            .await
+            .unwrap();
+        assert_eq!(checkpointer.get().await.unwrap(), None);
+
+        checkpointer.set("12345").await.unwrap();
+        checkpointer.set("678").await.unwrap();
+        assert_eq!(checkpointer.get().await.unwrap(), Some("678".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn skips_old_events() {
+        let dir = tempdir().unwrap();
+        let source = source(dir.path()).await;
+
+        let now = Utc::now();
+        let event = source
+            .to_event(kube_event("recent", "1", now), "ADDED")
+            .unwrap();
+        let log = event.as_log();
+        assert_eq!(log["reason"], "Scheduled".into());
+        assert_eq!(log["metadata.name"], "recent".into());
+        assert_eq!(log[VERB_KEY], "ADDED".into());
+        assert_eq!(log[log_schema().timestamp_key()], now.into());
+        assert_eq!(log[log_schema().source_type_key()], COMPONENT_ID.into());
+
+        let old = now - ChronoDuration::hours(1);
+        assert!(source
+            .to_event(kube_event("old", "2", old), "ADDED")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn resumes_from_checkpoint() {
+        let dir = tempdir().unwrap();
+        let mut source = source(dir.path()).await;
+        source.checkpointer.set("41").await.unwrap();
+
+        let (watcher_events_tx, mut watcher_events_rx) = mpsc::channel(0);
+        let (mut watcher_invocations_tx, watcher_invocations_rx) = mpsc::channel(0);
+        let watcher = MockWatcher::<KubeEvent>::new(watcher_events_tx, watcher_invocations_rx);
+        let (out, rx) = Pipeline::new_test();
+        let (trigger, shutdown, _tripwire) = ShutdownSignal::new_wired();
+        let run = tokio::spawn(source.run(watcher, shutdown, out));
+
+        // The watch resumes from the persisted resource version.
+        match watcher_events_rx.next().await.unwrap() {
+            ScenarioEvent::Invocation(watch_optional) => {
+                assert_eq!(watch_optional.resource_version.as_deref(), Some("41"))
+            }
+            _ => panic!("Unexpected event from watcher mock"),
+        }
+        let (mut watch_stream_tx, watch_stream_rx) = mpsc::channel(0);
+        watcher_invocations_tx
+            .send(ScenarioActionInvocation::Ok(watch_stream_rx))
+            .await
+            .unwrap();
+
+        for watch_event in vec![
+            WatchEvent::Added(kube_event("first", "42", Utc::now())),
+            WatchEvent::Bookmark {
+                resource_version: "43".to_owned(),
+            },
+        ] {
+            assert_eq!(
+                watcher_events_rx.next().await.unwrap(),
+                ScenarioEvent::Stream
+            );
+            watch_stream_tx
+                .send(ScenarioActionStream::Ok(watch_event))
+                .await
+                .unwrap();
+        }
+
+        // Once the source waits for more, everything before was processed.
+        assert_eq!(
+            watcher_events_rx.next().await.unwrap(),
+            ScenarioEvent::Stream
+        );
+        drop(trigger);
+        run.await.unwrap().unwrap();
+
+        let events = collect_ready(rx).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].as_log()["metadata.name"], "first".into());
+
+        let mut checkpointer = Checkpointer::new(dir.path().join(CHECKPOINT_FILENAME))
+            .await
+            .unwrap();
+        assert_eq!(checkpointer.get().await.unwrap(), Some("43".to_owned()));
+    }
+}
@@ -12,7 +12,8 @@ use std::path::PathBuf;
 /// A paths provider implementation that uses the state obtained from the
 /// the k8s API.
 pub struct K8sPathsProvider {
-    pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+    /// The state of each scope the `Pod`s are watched in.
+    pods_state_readers: Vec<ReadHandle<String, k8s::state::evmap::Value<Pod>>>,
     namespace_state_reader: ReadHandle<String, k8s::state::evmap::Value<Namespace>>,
     exclude_paths: Vec<glob::Pattern>,
 }
@@ -20,12 +21,12 @@ pub struct K8sPathsProvider {
 impl K8sPathsProvider {
     /// Create a new [`K8sPathsProvider`].
     pub fn new(
-        pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+        pods_state_readers: Vec<ReadHandle<String, k8s::state::evmap::Value<Pod>>>,
         namespace_state_reader: ReadHandle<String, k8s::state::evmap::Value<Namespace>>,
         exclude_paths: Vec<glob::Pattern>,
     ) -> Self {
         Self {
-            pods_state_reader,
+            pods_state_readers,
             namespace_state_reader,
             exclude_paths,
         }
@@ -36,7 +37,19 @@ impl PathsProvider for K8sPathsProvider {
     type IntoIter = Vec<PathBuf>;
 
     fn paths(&self) -> Vec<PathBuf> {
-        let read_ref = match self.pods_state_reader.read() {
+        self.pods_state_readers
+            .iter()
+            .flat_map(|reader| self.paths_of(reader))
+            .collect()
+    }
+}
+
+impl K8sPathsProvider {
+    fn paths_of(
+        &self,
+        pods_state_reader: &ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+    ) -> Vec<PathBuf> {
+        let read_ref = match pods_state_reader.read() {
             Some(v) => v,
             None => {
                 // The state is not initialized or gone, fallback to using an
@@ -26,11 +26,16 @@ use file_source::{
     Checkpointer, FileServer, FileServerShutdown, FingerprintStrategy, Fingerprinter, Line,
     ReadFrom,
 };
-use k8s_openapi::api::core::v1::{Namespace, Pod};
+use k8s_openapi::{
+    api::core::v1::{Namespace, Pod},
+    http::Request,
+    RequestError, WatchOptional,
+};
 use serde::{Deserialize, Serialize};
 use shared::TimeZone;
 use std::convert::TryInto;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 mod k8s_paths_provider;
@@ -39,6 +44,7 @@ mod namespace_metadata_annotator;
 mod parser;
 mod partial_events_merger;
 mod path_helpers;
+mod pod_lookup;
 mod pod_metadata_annotator;
 mod transform_utils;
 mod util;
@@ -47,6 +53,8 @@ use futures::{future::FutureExt, sink::Sink, stream::StreamExt};
 use k8s_paths_provider::K8sPathsProvider;
 use lifecycle::Lifecycle;
 use namespace_metadata_annotator::NamespaceMetadataAnnotator;
+use path_helpers::parse_log_file_path;
+use pod_lookup::PodLookup;
 use pod_metadata_annotator::PodMetadataAnnotator;
 
 /// The key we use for `file` field.
@@ -72,6 +80,11 @@ pub struct Config {
     /// addition to the built-in `Node` filter.
     extra_field_selector: String,
 
+    /// Watch only the `Pod`s of these namespaces, with a watch request per
+    /// namespace. Reduces the load on the API server in large clusters,
+    /// compared to filtering the `Pod`s of all the namespaces.
+    namespaces: Vec<String>,
+
     /// Automatically merge partial events.
     auto_partial_merge: bool,
 
@@ -144,6 +157,7 @@ impl Default for Config {
             extra_label_selector: "".to_string(),
             self_node_name: default_self_node_name_env_template(),
             extra_field_selector: "".to_string(),
+            namespaces: Vec::new(),
             auto_partial_merge: true,
             data_dir: None,
             pod_annotation_fields: pod_metadata_annotator::FieldsSpec::default(),
@@ -192,6 +206,7 @@ struct Source {
     namespace_fields_spec: namespace_metadata_annotator::FieldsSpec,
     field_selector: String,
     label_selector: String,
+    namespaces: Vec<String>,
     exclude_paths: Vec<glob::Pattern>,
     max_read_bytes: usize,
     max_line_bytes: usize,
@@ -235,6 +250,7 @@ impl Source {
             namespace_fields_spec: config.namespace_annotation_fields.clone(),
             field_selector,
             label_selector,
+            namespaces: config.namespaces.clone(),
             exclude_paths,
             max_read_bytes: config.max_read_bytes,
             max_line_bytes: config.max_line_bytes,
@@ -258,6 +274,7 @@ impl Source {
             namespace_fields_spec,
             field_selector,
             label_selector,
+            namespaces,
             exclude_paths,
             max_read_bytes,
             max_line_bytes,
@@ -267,27 +284,43 @@ impl Source {
             timezone,
         } = self;
 
-        let watcher =
-            k8s::api_watcher::ApiWatcher::new(client.clone(), Pod::watch_pod_for_all_namespaces);
-        let watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(watcher);
-        let (state_reader, state_writer) = evmap::new();
-        let state_writer = k8s::state::evmap::Writer::new(
-            state_writer,
-            Some(Duration::from_millis(10)),
-            HashKey::Uid,
-        );
-        let state_writer = k8s::state::instrumenting::Writer::new(state_writer);
-        let state_writer =
-            k8s::state::delayed_delete::Writer::new(state_writer, Duration::from_secs(60));
-
-        let mut reflector = k8s::reflector::Reflector::new(
-            watcher,
-            state_writer,
-            Some(field_selector),
-            Some(label_selector),
-            Duration::from_secs(1),
-        );
-        let reflector_process = reflector.run();
+        // A reflector per namespace to watch, or a single one for all of them.
+        let scopes = if namespaces.is_empty() {
+            vec![None]
+        } else {
+            namespaces.into_iter().map(Some).collect()
+        };
+        let mut state_readers = Vec::with_capacity(scopes.len());
+        let mut reflectors = Vec::with_capacity(scopes.len());
+        for scope in scopes {
+            let watcher =
+                k8s::api_watcher::ApiWatcher::new(client.clone(), PodsWatchRequestBuilder(scope));
+            let watcher = k8s::instrumenting_watcher::InstrumentingWatcher::new(watcher);
+            let (state_reader, state_writer) = evmap::new();
+            let state_writer = k8s::state::evmap::Writer::new(
+                state_writer,
+                Some(Duration::from_millis(10)),
+                HashKey::Uid,
+            );
+            let state_writer = k8s::state::instrumenting::Writer::new(state_writer);
+            let state_writer =
+                k8s::state::delayed_delete::Writer::new(state_writer, Duration::from_secs(60));
+
+            state_readers.push(state_reader);
+            reflectors.push(k8s::reflector::Reflector::new(
+                watcher,
+                state_writer,
+                Some(field_selector.clone()),
+                Some(label_selector.clone()),
+                Duration::from_secs(1),
+            ));
+        }
+        let reflector_process = futures::future::select_all(
+            reflectors
+                .iter_mut()
+                .map(|reflector| Box::pin(reflector.run())),
+        )
+        .map(|(result, _, _)| result);
 
         // -----------------------------------------------------------------
 
@@ -313,9 +346,13 @@ impl Source {
         );
         let ns_reflector_process = ns_reflector.run();
 
-        let paths_provider =
-            K8sPathsProvider::new(state_reader.clone(), ns_state_reader.clone(), exclude_paths);
-        let annotator = PodMetadataAnnotator::new(state_reader, pod_fields_spec);
+        let paths_provider = K8sPathsProvider::new(
+            state_readers.clone(),
+            ns_state_reader.clone(),
+            exclude_paths,
+        );
+        let pod_lookup = Arc::new(PodLookup::new(client, pod_fields_spec.clone()));
+        let annotator = PodMetadataAnnotator::new(state_readers, pod_fields_spec);
         let ns_annotator = NamespaceMetadataAnnotator::new(ns_state_reader, namespace_fields_spec);
 
         // TODO: maybe more of the parameters have to be configurable.
@@ -372,6 +409,9 @@ impl Source {
             oldest_first: false,
             // We do not remove the log files, `kubelet` is responsible for it.
             remove_after: None,
+            active_window: None,
+            checkpoint_retention: None,
+            max_read_bytes_per_sec: None,
             // The standard emitter.
             emitter: FileSourceInternalEventsEmitter,
             // A handle to the current tokio runtime
@@ -401,22 +441,30 @@ impl Source {
                 pod_name: file_info.as_ref().map(|info| info.pod_name),
             });
 
-            if file_info.is_none() {
-                emit!(KubernetesLogsEventAnnotationFailed { event: &event });
-            } else {
-                let namespace = file_info.as_ref().map(|info| info.pod_namespace);
+            // The namespace is known from the path, even if the pod isn't
+            // in the state.
+            if let Some(file_info) = parse_log_file_path(&line.filename) {
+                let ns_info = ns_annotator.annotate(&mut event, file_info.pod_namespace);
 
-                if let Some(name) = namespace {
-                    let ns_info = ns_annotator.annotate(&mut event, name);
-
-                    if ns_info.is_none() {
-                        emit!(KubernetesLogsEventNamespaceAnnotationFailed { event: &event });
-                    }
+                if ns_info.is_none() {
+                    emit!(KubernetesLogsEventNamespaceAnnotationFailed { event: &event });
                 }
             }
 
             checkpoints.update(line.file_id, line.offset);
-            event
+            let missing_pod = file_info.is_none();
+            (event, missing_pod.then(|| line.filename))
+        });
+        let events = events.then(move |(mut event, missing_pod_file)| {
+            let pod_lookup = Arc::clone(&pod_lookup);
+            async move {
+                if let Some(file) = missing_pod_file {
+                    if !pod_lookup.annotate(&mut event, &file).await {
+                        emit!(KubernetesLogsEventAnnotationFailed { event: &event });
+                    }
+                }
+                event
+            }
         });
         let events = events.flat_map(move |event| {
             let mut buf = Vec::with_capacity(1);
@@ -490,6 +538,21 @@ impl Source {
     }
 }
 
+/// Builds the requests to watch the `Pod`s of a namespace, or of all of them.
+struct PodsWatchRequestBuilder(Option<String>);
+
+impl k8s::WatchRequestBuilder for PodsWatchRequestBuilder {
+    type Object = Pod;
+
+    fn build(&self, watch_optional: WatchOptional<'_>) -> Result<Request<Vec<u8>>, RequestError> {
+        let (request, _) = match &self.0 {
+            Some(namespace) => Pod::watch_namespaced_pod(namespace, watch_optional)?,
+            None => Pod::watch_pod_for_all_namespaces(watch_optional)?,
+        };
+        Ok(request)
+    }
+}
+
 fn create_event(line: Bytes, file: &str, ingestion_timestamp_field: Option<&str>) -> Event {
     let mut event = LogEvent::from(line);
 
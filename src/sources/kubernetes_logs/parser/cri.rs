@@ -71,7 +71,7 @@ fn normalize_event(log: &mut LogEvent) -> Result<(), NormalizationError> {
         .remove(MULTILINE_TAG)
         .context(MultilineTagFieldMissing)?;
     let multiline_tag = match multiline_tag {
-        Value::Bytes(val) => val,
+        Value::Bytes(val) | Value::String(val) => val,
         _ => return Err(NormalizationError::MultilineTagValueUnexpectedType),
     };
 
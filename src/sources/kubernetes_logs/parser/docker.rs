@@ -43,7 +43,7 @@ fn parse_json(log: &mut LogEvent) -> Result<(), ParsingError> {
         .ok_or(ParsingError::NoMessageField)?;
 
     let bytes = match message {
-        Value::Bytes(bytes) => bytes,
+        Value::Bytes(bytes) | Value::String(bytes) => bytes,
         _ => return Err(ParsingError::MessageFieldNotInBytes),
     };
 
@@ -68,7 +68,7 @@ fn normalize_event(log: &mut LogEvent) -> Result<(), NormalizationError> {
     // Parse and rename timestamp.
     let time = log.remove(&*TIME).context(TimeFieldMissing)?;
     let time = match time {
-        Value::Bytes(val) => val,
+        Value::Bytes(val) | Value::String(val) => val,
         _ => return Err(NormalizationError::TimeValueUnexpectedType),
     };
     let time = DateTime::parse_from_rfc3339(String::from_utf8_lossy(time.as_ref()).as_ref())
@@ -78,7 +78,7 @@ fn normalize_event(log: &mut LogEvent) -> Result<(), NormalizationError> {
     // Parse message, remove trailing newline and detect if it's partial.
     let message = log.remove(&*LOG).context(LogFieldMissing)?;
     let mut message = match message {
-        Value::Bytes(val) => val,
+        Value::Bytes(val) | Value::String(val) => val,
         _ => return Err(NormalizationError::LogValueUnexpectedType),
     };
     // Here we apply out heuristics to detect if message is partial.
@@ -44,7 +44,7 @@ impl FunctionTransform for Picker {
                 };
 
                 let bytes = match message {
-                    Value::Bytes(bytes) => bytes,
+                    Value::Bytes(bytes) | Value::String(bytes) => bytes,
                     _ => {
                         emit!(KubernetesLogsFormatPickerEdgeCase {
                             what: "got an event with non-bytes message field"
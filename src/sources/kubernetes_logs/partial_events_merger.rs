@@ -5,6 +5,11 @@ use super::FILE_KEY;
 use crate::event;
 use crate::transforms::merge::{Merge, MergeConfig};
 
+/// The key of the output stream of the container, both parsers keep it.
+/// The runtime splits the lines of `stdout` and `stderr` independently, so
+/// their partial lines interleave within the file.
+const STREAM_KEY: &str = "stream";
+
 /// Partial event merger.
 pub type PartialEventsMerger = Optional<Merge>;
 
@@ -14,7 +19,7 @@ pub fn build(enabled: bool) -> PartialEventsMerger {
             MergeConfig {
                 partial_event_marker_field: event::PARTIAL.to_string(),
                 fields: vec![crate::config::log_schema().message_key().to_string()],
-                stream_discriminant_fields: vec![(&*FILE_KEY).to_string()],
+                stream_discriminant_fields: vec![(&*FILE_KEY).to_string(), STREAM_KEY.to_string()],
             }
             .into(),
         )
@@ -22,3 +27,41 @@ pub fn build(enabled: bool) -> PartialEventsMerger {
         None
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::log_schema,
+        event::{Event, LogEvent},
+        transforms::TaskTransform,
+    };
+    use futures::{stream, StreamExt};
+
+    fn line(message: &str, stream: &str, partial: bool) -> Event {
+        let mut log = LogEvent::from(message);
+        log.insert(FILE_KEY, "/var/log/pods/ns_pod_uid/container/0.log");
+        log.insert(STREAM_KEY, stream);
+        if partial {
+            log.insert(event::PARTIAL, true);
+        }
+        log.into()
+    }
+
+    #[tokio::test]
+    async fn merges_partial_lines_of_each_stream() {
+        let lines = vec![
+            line("out ", "stdout", true),
+            line("err ", "stderr", true),
+            line("first", "stdout", false),
+            line("second", "stderr", false),
+        ];
+
+        let merged = Box::new(build(true))
+            .transform(Box::pin(stream::iter(lines)))
+            .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(merged, vec!["out first", "err second"]);
+    }
+}
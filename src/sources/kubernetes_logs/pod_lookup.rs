@@ -0,0 +1,118 @@
+//! Looks up the pods missing from the watched state.
+
+#![deny(missing_docs)]
+
+use super::{
+    path_helpers::parse_log_file_path,
+    pod_metadata_annotator::{annotate_from_pod, FieldsSpec},
+};
+use crate::{event::Event, internal_events::KubernetesLogsPodLookupFailed, kubernetes as k8s};
+use http::StatusCode;
+use k8s_openapi::api::core::v1::Pod;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// How long the result of a lookup is reused, found or not.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Lookups taking longer than this are given up on, as they hold back the
+/// events behind.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Expired lookups are only dropped once there are this many cached.
+const MAX_CACHED: usize = 1024;
+
+/// Annotates events with the metadata of `Pod`s requested from the API on
+/// demand, for the lines of `Pod`s no longer or not yet in the watched state,
+/// such as the last ones of deleted `Pod`s.
+pub struct PodLookup {
+    client: k8s::client::Client,
+    fields_spec: FieldsSpec,
+    /// The `Pod`s by UID, or `None` for those not found.
+    cache: Mutex<HashMap<String, (Instant, Option<Arc<Pod>>)>>,
+}
+
+impl PodLookup {
+    /// Create a new [`PodLookup`].
+    pub fn new(client: k8s::client::Client, fields_spec: FieldsSpec) -> Self {
+        Self {
+            client,
+            fields_spec,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Annotates an event the same as the
+    /// [`PodMetadataAnnotator`](super::pod_metadata_annotator::PodMetadataAnnotator),
+    /// returns whether the `Pod` of the file was found.
+    pub async fn annotate(&self, event: &mut Event, file: &str) -> bool {
+        let file_info = match parse_log_file_path(file) {
+            Some(file_info) => file_info,
+            None => return false,
+        };
+
+        let cached = self
+            .cache
+            .lock()
+            .expect("Pod lookup cache poisoned")
+            .get(file_info.pod_uid)
+            .filter(|(looked_up, _)| looked_up.elapsed() < CACHE_TTL)
+            .map(|(_, pod)| pod.clone());
+        let pod = match cached {
+            Some(pod) => pod,
+            None => {
+                let pod = match self
+                    .fetch(
+                        file_info.pod_namespace,
+                        file_info.pod_name,
+                        file_info.pod_uid,
+                    )
+                    .await
+                {
+                    Ok(pod) => pod.map(Arc::new),
+                    Err(error) => {
+                        emit!(KubernetesLogsPodLookupFailed {
+                            pod_name: file_info.pod_name,
+                            error
+                        });
+                        None
+                    }
+                };
+                let mut cache = self.cache.lock().expect("Pod lookup cache poisoned");
+                if cache.len() >= MAX_CACHED {
+                    cache.retain(|_, (looked_up, _)| looked_up.elapsed() < CACHE_TTL);
+                }
+                cache.insert(file_info.pod_uid.to_owned(), (Instant::now(), pod.clone()));
+                pod
+            }
+        };
+
+        match pod {
+            Some(pod) => {
+                annotate_from_pod(event.as_mut_log(), &self.fields_spec, &file_info, &pod);
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn fetch(&self, namespace: &str, name: &str, uid: &str) -> crate::Result<Option<Pod>> {
+        let (request, _) = Pod::read_namespaced_pod(name, namespace, Default::default())?;
+        let mut client = self.client.clone();
+        let response = tokio::time::timeout(LOOKUP_TIMEOUT, client.send(request)).await??;
+        match response.status() {
+            StatusCode::OK => {}
+            StatusCode::NOT_FOUND => return Ok(None),
+            status => return Err(format!("unexpected status {}", status).into()),
+        }
+
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let pod: Pod = serde_json::from_slice(&body)?;
+        // A `Pod` recreated under the same name isn't the one the file
+        // belongs to.
+        Ok((pod.metadata.uid.as_deref() == Some(uid)).then(|| pod))
+    }
+}
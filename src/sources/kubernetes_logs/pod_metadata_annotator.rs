@@ -27,6 +27,7 @@ pub struct FieldsSpec {
     pub container_name: String,
     pub container_id: String,
     pub container_image: String,
+    pub container_image_id: String,
 }
 
 impl Default for FieldsSpec {
@@ -42,24 +43,26 @@ impl Default for FieldsSpec {
             container_name: "kubernetes.container_name".to_owned(),
             container_id: "kubernetes.container_id".to_owned(),
             container_image: "kubernetes.container_image".to_owned(),
+            container_image_id: "kubernetes.container_image_id".to_owned(),
         }
     }
 }
 
 /// Annotate the event with pod metadata.
 pub struct PodMetadataAnnotator {
-    pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+    /// The state of each scope the `Pod`s are watched in.
+    pods_state_readers: Vec<ReadHandle<String, k8s::state::evmap::Value<Pod>>>,
     fields_spec: FieldsSpec,
 }
 
 impl PodMetadataAnnotator {
     /// Create a new [`PodMetadataAnnotator`].
     pub fn new(
-        pods_state_reader: ReadHandle<String, k8s::state::evmap::Value<Pod>>,
+        pods_state_readers: Vec<ReadHandle<String, k8s::state::evmap::Value<Pod>>>,
         fields_spec: FieldsSpec,
     ) -> Self {
         Self {
-            pods_state_reader,
+            pods_state_readers,
             fields_spec,
         }
     }
@@ -70,40 +73,51 @@ impl PodMetadataAnnotator {
     /// The event has to be obtained from kubernetes log file, and have a
     /// [`FILE_KEY`] field set with a file that the line came from.
     pub fn annotate<'a>(&self, event: &mut Event, file: &'a str) -> Option<LogFileInfo<'a>> {
-        let log = event.as_mut_log();
         let file_info = parse_log_file_path(file)?;
-        let guard = self.pods_state_reader.get(file_info.pod_uid)?;
+        let guard = self
+            .pods_state_readers
+            .iter()
+            .find_map(|reader| reader.get(file_info.pod_uid))?;
         let entry = guard.get_one()?;
         let pod: &Pod = entry.as_ref();
 
-        annotate_from_file_info(log, &self.fields_spec, &file_info);
-        annotate_from_metadata(log, &self.fields_spec, &pod.metadata);
+        annotate_from_pod(event.as_mut_log(), &self.fields_spec, &file_info, pod);
+        Some(file_info)
+    }
+}
 
-        let container;
-        if let Some(ref pod_spec) = pod.spec {
-            annotate_from_pod_spec(log, &self.fields_spec, pod_spec);
+/// Annotates the log with the information of the `Pod` the file belongs to.
+pub fn annotate_from_pod(
+    log: &mut LogEvent,
+    fields_spec: &FieldsSpec,
+    file_info: &LogFileInfo<'_>,
+    pod: &Pod,
+) {
+    annotate_from_file_info(log, fields_spec, file_info);
+    annotate_from_metadata(log, fields_spec, &pod.metadata);
 
-            container = pod_spec
-                .containers
-                .iter()
-                .find(|c| c.name == file_info.container_name);
-            if let Some(container) = container {
-                annotate_from_container(log, &self.fields_spec, container);
-            }
+    if let Some(ref pod_spec) = pod.spec {
+        annotate_from_pod_spec(log, fields_spec, pod_spec);
+
+        let container = pod_spec
+            .containers
+            .iter()
+            .find(|c| c.name == file_info.container_name);
+        if let Some(container) = container {
+            annotate_from_container(log, fields_spec, container);
         }
+    }
 
-        if let Some(ref pod_status) = pod.status {
-            annotate_from_pod_status(log, &self.fields_spec, pod_status);
-            if let Some(ref container_statuses) = pod_status.container_statuses {
-                let container_status = container_statuses
-                    .iter()
-                    .find(|c| c.name == file_info.container_name);
-                if let Some(container_status) = container_status {
-                    annotate_from_container_status(log, &self.fields_spec, container_status)
-                }
+    if let Some(ref pod_status) = pod.status {
+        annotate_from_pod_status(log, fields_spec, pod_status);
+        if let Some(ref container_statuses) = pod_status.container_statuses {
+            let container_status = container_statuses
+                .iter()
+                .find(|c| c.name == file_info.container_name);
+            if let Some(container_status) = container_status {
+                annotate_from_container_status(log, fields_spec, container_status)
             }
         }
-        Some(file_info)
     }
 }
 
@@ -178,6 +192,16 @@ fn annotate_from_container_status(
             log.insert(key, val.to_owned());
         }
     }
+
+    // The digest of the image the container actually runs, unlike the
+    // possibly mutable tag of `container_image`. It's empty until the image
+    // is pulled.
+    if !container_status.image_id.is_empty() {
+        log.insert(
+            &fields_spec.container_image_id,
+            container_status.image_id.clone(),
+        );
+    }
 }
 
 fn annotate_from_container(log: &mut LogEvent, fields_spec: &FieldsSpec, container: &Container) {
@@ -492,6 +516,23 @@ mod tests {
                     log
                 },
             ),
+            (
+                FieldsSpec {
+                    container_image_id: "image_digest".to_owned(),
+                    ..FieldsSpec::default()
+                },
+                ContainerStatus {
+                    container_id: Some("container_id_foo".to_owned()),
+                    image_id: "docker.io/library/busybox@sha256:0123abcd".to_owned(),
+                    ..ContainerStatus::default()
+                },
+                {
+                    let mut log = LogEvent::default();
+                    log.insert("kubernetes.container_id", "container_id_foo");
+                    log.insert("image_digest", "docker.io/library/busybox@sha256:0123abcd");
+                    log
+                },
+            ),
         ];
         for (fields_spec, container_status, expected) in cases.into_iter() {
             let mut log = LogEvent::default();
@@ -59,6 +59,7 @@ impl SourceConfig for LogstashConfig {
             shutdown_secs,
             tls,
             self.receive_buffer_bytes,
+            None,
             cx.shutdown,
             cx.out,
         )
@@ -6,22 +6,34 @@ pub mod apache_metrics;
 pub mod aws_ecs_metrics;
 #[cfg(feature = "sources-aws_kinesis_firehose")]
 pub mod aws_kinesis_firehose;
+#[cfg(feature = "sources-aws_kinesis_streams")]
+pub mod aws_kinesis_streams;
 #[cfg(feature = "sources-aws_s3")]
 pub mod aws_s3;
+#[cfg(feature = "sources-aws_sqs")]
+pub mod aws_sqs;
+#[cfg(feature = "sources-azure_event_hubs")]
+pub mod azure_event_hubs;
 #[cfg(feature = "sources-datadog")]
 pub mod datadog;
 #[cfg(all(unix, feature = "sources-dnstap"))]
 pub mod dnstap;
 #[cfg(feature = "sources-docker_logs")]
 pub mod docker_logs;
+#[cfg(all(target_os = "linux", feature = "sources-ebpf"))]
+pub mod ebpf;
 #[cfg(feature = "sources-eventstoredb_metrics")]
 pub mod eventstoredb_metrics;
 #[cfg(feature = "sources-exec")]
 pub mod exec;
 #[cfg(feature = "sources-file")]
 pub mod file;
+#[cfg(feature = "sources-flow_collector")]
+pub mod flow_collector;
 #[cfg(feature = "sources-fluent")]
 pub mod fluent;
+#[cfg(feature = "sources-gcp_pubsub")]
+pub mod gcp_pubsub;
 #[cfg(feature = "sources-generator")]
 pub mod generator;
 #[cfg(feature = "sources-heroku_logs")]
@@ -30,6 +42,8 @@ pub mod heroku_logs;
 pub mod host_metrics;
 #[cfg(feature = "sources-http")]
 pub mod http;
+#[cfg(feature = "sources-internal_events")]
+pub mod internal_events;
 #[cfg(feature = "sources-internal_logs")]
 pub mod internal_logs;
 #[cfg(feature = "sources-internal_metrics")]
@@ -38,20 +52,34 @@ pub mod internal_metrics;
 pub mod journald;
 #[cfg(all(feature = "sources-kafka", feature = "rdkafka"))]
 pub mod kafka;
+#[cfg(feature = "sources-kubernetes_events")]
+pub mod kubernetes_events;
 #[cfg(feature = "sources-kubernetes_logs")]
 pub mod kubernetes_logs;
 #[cfg(all(feature = "sources-logstash"))]
 pub mod logstash;
 #[cfg(feature = "sources-mongodb_metrics")]
 pub mod mongodb_metrics;
+#[cfg(feature = "sources-mqtt")]
+pub mod mqtt;
 #[cfg(all(feature = "sources-nats"))]
 pub mod nats;
 #[cfg(feature = "sources-nginx_metrics")]
 pub mod nginx_metrics;
+#[cfg(feature = "sources-opentelemetry")]
+pub mod opentelemetry;
+#[cfg(feature = "sources-postgres_cdc")]
+pub mod postgres_cdc;
 #[cfg(feature = "sources-postgresql_metrics")]
 pub mod postgresql_metrics;
 #[cfg(feature = "sources-prometheus")]
 pub mod prometheus;
+#[cfg(feature = "sources-pulsar")]
+pub mod pulsar;
+#[cfg(feature = "sources-redis")]
+pub mod redis;
+#[cfg(feature = "sources-snmp_trap")]
+pub mod snmp_trap;
 #[cfg(feature = "sources-socket")]
 pub mod socket;
 #[cfg(feature = "sources-splunk_hec")]
@@ -64,6 +92,8 @@ pub mod stdin;
 pub mod syslog;
 #[cfg(feature = "sources-vector")]
 pub mod vector;
+#[cfg(all(windows, feature = "sources-windows_eventlog"))]
+pub mod windows_eventlog;
 
 mod util;
 
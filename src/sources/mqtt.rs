@@ -0,0 +1,233 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+    },
+    event::{Event, Value},
+    internal_events::{MqttConnectionFailed, MqttEventReceived},
+    mqtt::{MqttConnectionConfig, MqttQos},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::SinkExt;
+use rumqttc::{AsyncClient, EventLoop, Incoming, Publish, SubscribeFilter};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MqttSourceConfig {
+    #[serde(flatten)]
+    connection: MqttConnectionConfig,
+    /// Topic filters, which may contain the `+` and `#` wildcards
+    topics: Vec<String>,
+    #[serde(default)]
+    qos: MqttQos,
+    #[serde(default = "default_topic_key")]
+    topic_key: String,
+}
+
+fn default_topic_key() -> String {
+    "topic".to_owned()
+}
+
+inventory::submit! {
+    SourceDescription::new::<MqttSourceConfig>("mqtt")
+}
+
+impl GenerateConfig for MqttSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            host = "127.0.0.1"
+            port = 1883
+            topics = ["vector/#"]"#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "mqtt")]
+impl SourceConfig for MqttSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.topics.is_empty() {
+            return Err("`topics` cannot be empty.".into());
+        }
+        let options = self.connection.options(&cx.id)?;
+        let (client, eventloop) = AsyncClient::new(options, 100);
+
+        let filters = self
+            .topics
+            .iter()
+            .map(|topic| SubscribeFilter::new(topic.clone(), self.qos.into()))
+            .collect::<Vec<_>>();
+
+        Ok(Box::pin(mqtt_source(
+            client,
+            eventloop,
+            filters,
+            self.topic_key.clone(),
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "mqtt"
+    }
+}
+
+async fn mqtt_source(
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    filters: Vec<SubscribeFilter>,
+    topic_key: String,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    loop {
+        let notification = tokio::select! {
+            _ = &mut shutdown => break,
+            notification = eventloop.poll() => notification,
+        };
+
+        match notification {
+            // Subscriptions are made again on each connection, as a clean
+            // session has none.
+            Ok(rumqttc::Event::Incoming(Incoming::ConnAck(_))) => {
+                if let Err(error) = client.subscribe_many(filters.clone()).await {
+                    error!(message = "Failed to subscribe.", %error);
+                }
+            }
+            Ok(rumqttc::Event::Incoming(Incoming::Publish(publish))) => {
+                emit!(MqttEventReceived {
+                    byte_size: publish.payload.len(),
+                });
+                // Messages are acknowledged by the client once received.
+                let event = create_event(publish, &topic_key);
+                if let Err(error) = out.send(event).await {
+                    error!(message = "Error sending to sink.", %error);
+                }
+            }
+            Ok(_) => {}
+            // The next poll connects again.
+            Err(error) => {
+                emit!(MqttConnectionFailed { error });
+                tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => {},
+                }
+            }
+        }
+    }
+
+    let _ = client.try_disconnect();
+    Ok(())
+}
+
+fn create_event(publish: Publish, topic_key: &str) -> Event {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+
+    log.insert(log_schema().message_key(), Value::from(publish.payload));
+    log.insert_flat(topic_key, publish.topic);
+    log.insert(log_schema().timestamp_key(), Utc::now());
+    log.insert(log_schema().source_type_key(), Bytes::from("mqtt"));
+
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::QoS;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<MqttSourceConfig>();
+    }
+
+    #[test]
+    fn parses_connection_options() {
+        let config: MqttSourceConfig = toml::from_str(
+            r#"
+            host = "broker"
+            topics = ["sensors/+/temperature"]
+            qos = 0
+            clean_session = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.connection.port, 1883);
+        assert!(!config.connection.clean_session);
+        assert_eq!(config.qos, MqttQos::AtMostOnce);
+    }
+
+    #[test]
+    fn creates_event_from_publish() {
+        let publish = Publish::new("sensors/1/temperature", QoS::AtLeastOnce, "21.5");
+        let event = create_event(publish, "topic");
+        let log = event.as_log();
+        assert_eq!(log[log_schema().message_key()], "21.5".into());
+        assert_eq!(log["topic"], "sensors/1/temperature".into());
+        assert_eq!(log[log_schema().source_type_key()], "mqtt".into());
+    }
+}
+
+#[cfg(feature = "mqtt-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{collect_n, random_string, trace_init};
+    use rumqttc::{MqttOptions, QoS};
+
+    #[tokio::test]
+    async fn mqtt_source_receives_messages() {
+        trace_init();
+
+        let topic = format!("test-{}", random_string(10));
+        let config: MqttSourceConfig = toml::from_str(&format!(
+            r#"
+            host = "127.0.0.1"
+            topics = ["{}/#"]
+            "#,
+            topic
+        ))
+        .unwrap();
+        let (tx, rx) = Pipeline::new_test();
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let (client, mut eventloop) =
+            AsyncClient::new(MqttOptions::new(random_string(10), "127.0.0.1", 1883), 10);
+        tokio::spawn(async move { while eventloop.poll().await.is_ok() {} });
+        for i in 0..3 {
+            client
+                .publish(
+                    format!("{}/{}", topic, i),
+                    QoS::AtLeastOnce,
+                    false,
+                    format!("message {}", i),
+                )
+                .await
+                .unwrap();
+        }
+
+        let events = collect_n(rx, 3).await;
+        for (i, event) in events.iter().enumerate() {
+            let log = event.as_log();
+            assert_eq!(
+                log[log_schema().message_key()],
+                format!("message {}", i).into()
+            );
+            assert_eq!(log["topic"], format!("{}/{}", topic, i).into());
+        }
+    }
+}
@@ -2,16 +2,23 @@ use crate::{
     config::{
         log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
     },
-    event::{Event, Value},
-    internal_events::NatsEventReceived,
+    event::{BatchNotifier, BatchStatus, Event, Value},
+    internal_events::{NatsAcknowledgementFailed, NatsEventReceived},
+    nats::{self, subscription_stream},
     shutdown::ShutdownSignal,
     Pipeline,
 };
 use bytes::Bytes;
 use chrono::Utc;
-use futures::{pin_mut, stream, SinkExt, Stream, StreamExt};
+use futures::{
+    future::{self, BoxFuture},
+    pin_mut,
+    stream::FuturesUnordered,
+    FutureExt, SinkExt, StreamExt,
+};
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
+use tokio::sync::mpsc;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -29,6 +36,16 @@ pub struct NatsSourceConfig {
     connection_name: String,
     subject: String,
     queue: Option<String>,
+    jetstream: Option<NatsJetStreamConfig>,
+}
+
+/// Consume the subject through a durable JetStream consumer, whose messages
+/// are acknowledged once their events are delivered, redelivered if their
+/// events errored and terminated if they were rejected.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct NatsJetStreamConfig {
+    durable_name: String,
 }
 
 inventory::submit! {
@@ -51,6 +68,17 @@ impl GenerateConfig for NatsSourceConfig {
 #[typetag::serde(name = "nats")]
 impl SourceConfig for NatsSourceConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if let Some(jetstream) = &self.jetstream {
+            let (connection, subscription) = create_jetstream_subscription(self, jetstream).await?;
+            return Ok(Box::pin(jetstream_source(
+                connection,
+                subscription,
+                cx.shutdown,
+                cx.out,
+                cx.acknowledgements,
+            )));
+        }
+
         let (connection, subscription) = create_subscription(self).await?;
 
         Ok(Box::pin(nats_source(
@@ -95,14 +123,6 @@ impl From<NatsSourceConfig> for async_nats::Options {
     }
 }
 
-fn get_subscription_stream(
-    subscription: async_nats::Subscription,
-) -> impl Stream<Item = async_nats::Message> {
-    stream::unfold(subscription, |subscription| async move {
-        subscription.next().await.map(|msg| (msg, subscription))
-    })
-}
-
 async fn nats_source(
     // Take ownership of the connection so it doesn't get dropped.
     _connection: async_nats::Connection,
@@ -110,33 +130,158 @@ async fn nats_source(
     shutdown: ShutdownSignal,
     mut out: Pipeline,
 ) -> Result<(), ()> {
-    let stream = get_subscription_stream(subscription).take_until(shutdown);
+    let stream = subscription_stream(subscription).take_until(shutdown);
     pin_mut!(stream);
     while let Some(msg) = stream.next().await {
         emit!(NatsEventReceived {
             byte_size: msg.data.len(),
         });
 
-        let mut event = Event::new_empty_log();
-        let log = event.as_mut_log();
+        let event = create_event(Bytes::from(msg.data));
 
-        log.insert(
-            log_schema().message_key(),
-            Value::from(Bytes::from(msg.data)),
-        );
+        if let Err(error) = out.send(event).await {
+            error!(message = "Error sending to sink.", %error)
+        }
+    }
+    Ok(())
+}
 
-        log.insert(log_schema().timestamp_key(), Utc::now());
+fn create_event(data: Bytes) -> Event {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
 
-        // Add source type
-        log.insert(log_schema().source_type_key(), Bytes::from("nats"));
+    log.insert(log_schema().message_key(), Value::from(data));
 
-        if let Err(error) = out.send(event).await {
-            error!(message = "Error sending to sink.", %error)
+    log.insert(log_schema().timestamp_key(), Utc::now());
+
+    // Add source type
+    log.insert(log_schema().source_type_key(), Bytes::from("nats"));
+
+    event
+}
+
+async fn jetstream_source(
+    // Take ownership of the connection so it doesn't get dropped.
+    _connection: async_nats::Connection,
+    subscription: async_nats::Subscription,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+    acknowledgements: bool,
+) -> Result<(), ()> {
+    let messages = subscription_stream(subscription).fuse();
+    pin_mut!(messages);
+    // Acknowledgements run on their own task, so that they aren't held up
+    // while sending events blocks.
+    let (pending, receiver) = mpsc::unbounded_channel();
+    let acks = tokio::spawn(acknowledge_messages(receiver));
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            msg = messages.next() => match msg {
+                None => break,
+                Some(msg) => {
+                    emit!(NatsEventReceived {
+                        byte_size: msg.data.len(),
+                    });
+
+                    let event = create_event(Bytes::from(msg.data.clone()));
+                    let (event, status) = if acknowledgements {
+                        let (batch, receiver) = BatchNotifier::new_with_receiver();
+                        (event.with_batch_notifier(&batch), receiver.boxed())
+                    } else {
+                        (event, future::ready(BatchStatus::Delivered).boxed())
+                    };
+                    match out.send(event).await {
+                        Err(error) => error!(message = "Error sending to sink.", %error),
+                        // The task only stops early if it panicked.
+                        Ok(_) => {
+                            let _ = pending.send((status, msg));
+                        }
+                    }
+                }
+            },
         }
     }
+
+    drop(pending);
+    if let Err(error) = acks.await {
+        error!(message = "Acknowledgements failed.", %error);
+    }
+
     Ok(())
 }
 
+/// Acknowledge the messages once the status of their events is known, until
+/// the source stops sending messages and they are all acknowledged.
+async fn acknowledge_messages(
+    mut messages: mpsc::UnboundedReceiver<(BoxFuture<'static, BatchStatus>, async_nats::Message)>,
+) {
+    // Messages whose events are still being delivered.
+    let mut pending = FuturesUnordered::new();
+    // Acknowledgements being published.
+    let mut acks = FuturesUnordered::new();
+
+    loop {
+        tokio::select! {
+            msg = messages.recv() => match msg {
+                None => break,
+                Some((status, msg)) => pending.push(status.map(move |status| (status, msg))),
+            },
+            Some((status, msg)) = pending.next(), if !pending.is_empty() => {
+                acks.push(acknowledge(msg, status));
+            }
+            Some(()) = acks.next(), if !acks.is_empty() => (),
+        }
+    }
+
+    while let Some((status, msg)) = pending.next().await {
+        acks.push(acknowledge(msg, status));
+    }
+    while acks.next().await.is_some() {}
+}
+
+/// Acknowledge a message, have it redelivered if its events errored, or
+/// terminate it if they failed, since redelivering it would fail again.
+/// Acknowledgements are published as replies to the message, without waiting
+/// for the server to confirm them.
+async fn acknowledge(msg: async_nats::Message, status: BatchStatus) {
+    if let Err(error) = msg.respond(ack_reply(status)).await {
+        emit!(NatsAcknowledgementFailed { error });
+    }
+}
+
+fn ack_reply(status: BatchStatus) -> &'static [u8] {
+    match status {
+        BatchStatus::Delivered => b"+ACK",
+        BatchStatus::Errored => b"-NAK",
+        BatchStatus::Failed => b"+TERM",
+    }
+}
+
+async fn create_jetstream_subscription(
+    config: &NatsSourceConfig,
+    jetstream: &NatsJetStreamConfig,
+) -> crate::Result<(async_nats::Connection, async_nats::Subscription)> {
+    let nc = config.connect().await?;
+
+    let deliver_subject = nats::push_consumer(
+        &nc,
+        &config.subject,
+        &jetstream.durable_name,
+        config.queue.as_deref(),
+    )
+    .await?;
+    let subscription = match &config.queue {
+        None => nc.subscribe(&deliver_subject).await,
+        Some(queue) => nc.queue_subscribe(&deliver_subject, queue).await,
+    };
+
+    let subscription = subscription?;
+
+    Ok((nc, subscription))
+}
+
 async fn create_subscription(
     config: &NatsSourceConfig,
 ) -> crate::Result<(async_nats::Connection, async_nats::Subscription)> {
@@ -160,6 +305,28 @@ mod tests {
     fn generate_config() {
         crate::test_util::test_generate_config::<NatsSourceConfig>();
     }
+
+    #[test]
+    fn parses_jetstream() {
+        let config: NatsSourceConfig = toml::from_str(
+            r#"
+            connection_name = "vector"
+            subject = "from.vector"
+            url = "nats://127.0.0.1:4222"
+            jetstream.durable_name = "vector"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.jetstream.unwrap().durable_name, "vector");
+    }
+
+    #[test]
+    fn terminates_failed_messages() {
+        assert_eq!(ack_reply(BatchStatus::Delivered), b"+ACK");
+        assert_eq!(ack_reply(BatchStatus::Errored), b"-NAK");
+        assert_eq!(ack_reply(BatchStatus::Failed), b"+TERM");
+    }
 }
 
 #[cfg(feature = "nats-integration-tests")]
@@ -177,6 +344,7 @@ mod integration_tests {
             subject: subject.clone(),
             url: "nats://127.0.0.1:4222".to_owned(),
             queue: None,
+            jetstream: None,
         };
 
         let (nc, sub) = create_subscription(&conf).await.unwrap();
@@ -191,4 +359,52 @@ mod integration_tests {
         println!("Received event  {:?}", events[0].as_log());
         assert_eq!(events[0].as_log()[log_schema().message_key()], msg.into());
     }
+
+    #[tokio::test]
+    async fn nats_jetstream_acknowledged() {
+        let subject = format!("test-{}", random_string(10));
+
+        let conf = NatsSourceConfig {
+            connection_name: "".to_owned(),
+            subject: subject.clone(),
+            url: "nats://127.0.0.1:4222".to_owned(),
+            queue: None,
+            jetstream: Some(NatsJetStreamConfig {
+                durable_name: subject.clone(),
+            }),
+        };
+
+        let nc = conf.connect().await.unwrap();
+        let stream = serde_json::json!({
+            "name": subject,
+            "subjects": [subject],
+        });
+        nc.request(
+            &format!("$JS.API.STREAM.CREATE.{}", subject),
+            stream.to_string(),
+        )
+        .await
+        .unwrap();
+        let ack = nc.request(&subject, "my message").await.unwrap();
+        nats::parse_publish_ack(&ack.data).unwrap();
+
+        let (connection, sub) =
+            create_jetstream_subscription(&conf, conf.jetstream.as_ref().unwrap())
+                .await
+                .unwrap();
+        let (tx, rx) = Pipeline::new_test_finalize(crate::event::EventStatus::Delivered);
+        tokio::spawn(jetstream_source(
+            connection,
+            sub,
+            ShutdownSignal::noop(),
+            tx,
+            true,
+        ));
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "my message".into()
+        );
+    }
 }
@@ -0,0 +1,536 @@
+//! Mapping of OTLP requests onto Vector's events.
+//!
+//! Log records are laid out as described in `vector_core::event::otlp`, and
+//! spans with the field names of `vector_core::event::trace::fields`. The
+//! attributes of the resource the telemetry came from are kept under
+//! [`RESOURCES`] on logs and spans, and become tags on metrics.
+
+use crate::{
+    config::log_schema,
+    event::{
+        metric::{Bucket, MetricTags, Quantile},
+        otlp::{self, LogRecord},
+        trace::fields,
+        Event, LogEvent, Metric, MetricKind, MetricValue, TraceEvent, Value,
+    },
+    proto::opentelemetry::proto::{
+        common::v1::{any_value::Value as AnyValueKind, AnyValue, KeyValue},
+        logs::v1::ResourceLogs,
+        metrics::v1::{
+            metric::Data, number_data_point::Value as NumberValue, AggregationTemporality,
+            Metric as OtlpMetric, NumberDataPoint, ResourceMetrics,
+        },
+        resource::v1::Resource,
+        trace::v1::{span::SpanKind, status::StatusCode, ResourceSpans, Span},
+    },
+};
+use bytes::Bytes;
+use chrono::Utc;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+pub const RESOURCES: &str = "resources";
+
+const TRACE_STATE: &str = "trace_state";
+
+pub fn logs(resource_logs: Vec<ResourceLogs>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for resource_logs in resource_logs {
+        let resources = resource_attributes(resource_logs.resource);
+        for library_logs in resource_logs.instrumentation_library_logs {
+            events.extend(library_logs.logs.into_iter().map(|record| {
+                let mut log = LogEvent::from(LogRecord {
+                    time_unix_nano: record.time_unix_nano,
+                    severity_number: record.severity_number,
+                    severity_text: record.severity_text,
+                    name: record.name,
+                    body: record.body.map(decode_any_value),
+                    attributes: decode_attributes(record.attributes),
+                    dropped_attributes_count: record.dropped_attributes_count,
+                    flags: record.flags,
+                    trace_id: record.trace_id,
+                    span_id: record.span_id,
+                });
+                if !log.contains(log_schema().timestamp_key()) {
+                    log.insert(log_schema().timestamp_key(), Utc::now());
+                }
+                if !resources.is_empty() {
+                    log.insert_flat(RESOURCES, resources.clone());
+                }
+                log.insert_flat(log_schema().source_type_key(), Bytes::from("opentelemetry"));
+                Event::from(log)
+            }));
+        }
+    }
+    events
+}
+
+pub fn metrics(resource_metrics: Vec<ResourceMetrics>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for resource_metrics in resource_metrics {
+        let tags = resource_attributes(resource_metrics.resource)
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string_lossy()))
+            .collect::<MetricTags>();
+        for library_metrics in resource_metrics.instrumentation_library_metrics {
+            for metric in library_metrics.metrics {
+                events.extend(decode_metric(metric, &tags).into_iter().map(Event::Metric));
+            }
+        }
+    }
+    events
+}
+
+pub fn spans(resource_spans: Vec<ResourceSpans>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for resource_spans in resource_spans {
+        let resources = resource_attributes(resource_spans.resource);
+        for library_spans in resource_spans.instrumentation_library_spans {
+            events.extend(
+                library_spans
+                    .spans
+                    .into_iter()
+                    .map(|span| Event::Trace(decode_span(span, &resources))),
+            );
+        }
+    }
+    events
+}
+
+/// One metric per data point, tagged with the attributes of the point on top
+/// of those of the resource.
+fn decode_metric(metric: OtlpMetric, resource_tags: &MetricTags) -> Vec<Metric> {
+    let name = metric.name;
+    let make = |kind: MetricKind, value: MetricValue, attributes: Vec<KeyValue>, time: u64| {
+        let mut tags = resource_tags.clone();
+        tags.extend(
+            decode_attributes(attributes)
+                .into_iter()
+                .map(|(key, value)| (key, value.to_string_lossy())),
+        );
+        Metric::new(name.clone(), kind, value)
+            .with_tags(if tags.is_empty() { None } else { Some(tags) })
+            .with_timestamp(otlp::timestamp_from_nanos(time))
+    };
+
+    match metric.data {
+        Some(Data::Gauge(gauge)) => gauge
+            .data_points
+            .into_iter()
+            .filter_map(|point| {
+                let value = MetricValue::Gauge {
+                    value: number(&point)?,
+                };
+                Some(make(
+                    MetricKind::Absolute,
+                    value,
+                    point.attributes,
+                    point.time_unix_nano,
+                ))
+            })
+            .collect(),
+        Some(Data::Sum(sum)) => {
+            let kind = metric_kind(sum.aggregation_temporality);
+            sum.data_points
+                .into_iter()
+                .filter_map(|point| {
+                    let value = number(&point)?;
+                    // Sums that can go down are reported as gauges.
+                    let value = if sum.is_monotonic {
+                        MetricValue::Counter { value }
+                    } else {
+                        MetricValue::Gauge { value }
+                    };
+                    Some(make(kind, value, point.attributes, point.time_unix_nano))
+                })
+                .collect()
+        }
+        Some(Data::Histogram(histogram)) => {
+            let kind = metric_kind(histogram.aggregation_temporality);
+            histogram
+                .data_points
+                .into_iter()
+                .map(|point| {
+                    let value = MetricValue::AggregatedHistogram {
+                        buckets: buckets(&point.explicit_bounds, &point.bucket_counts),
+                        count: saturate(point.count),
+                        sum: point.sum,
+                    };
+                    make(kind, value, point.attributes, point.time_unix_nano)
+                })
+                .collect()
+        }
+        Some(Data::Summary(summary)) => summary
+            .data_points
+            .into_iter()
+            .map(|point| {
+                let value = MetricValue::AggregatedSummary {
+                    quantiles: point
+                        .quantile_values
+                        .iter()
+                        .map(|quantile| Quantile {
+                            upper_limit: quantile.quantile,
+                            value: quantile.value,
+                        })
+                        .collect(),
+                    count: saturate(point.count),
+                    sum: point.sum,
+                };
+                // Summaries are always cumulative.
+                make(
+                    MetricKind::Absolute,
+                    value,
+                    point.attributes,
+                    point.time_unix_nano,
+                )
+            })
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn metric_kind(temporality: i32) -> MetricKind {
+    if temporality == AggregationTemporality::Delta as i32 {
+        MetricKind::Incremental
+    } else {
+        MetricKind::Absolute
+    }
+}
+
+fn number(point: &NumberDataPoint) -> Option<f64> {
+    match point.value? {
+        NumberValue::AsDouble(value) => Some(value),
+        NumberValue::AsInt(value) => Some(value as f64),
+    }
+}
+
+/// OTLP has one more bucket count than bounds, the last counting everything
+/// above the largest bound.
+fn buckets(bounds: &[f64], counts: &[u64]) -> Vec<Bucket> {
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| Bucket {
+            upper_limit: bounds.get(i).copied().unwrap_or(f64::INFINITY),
+            count: saturate(count),
+        })
+        .collect()
+}
+
+fn saturate(count: u64) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+fn decode_span(span: Span, resources: &BTreeMap<String, Value>) -> TraceEvent {
+    let mut trace = TraceEvent::default();
+    trace.insert(fields::TRACE_ID, otlp::encode_hex(&span.trace_id));
+    trace.insert(fields::SPAN_ID, otlp::encode_hex(&span.span_id));
+    if !span.parent_span_id.is_empty() {
+        trace.insert(
+            fields::PARENT_SPAN_ID,
+            otlp::encode_hex(&span.parent_span_id),
+        );
+    }
+    if !span.trace_state.is_empty() {
+        trace.insert(TRACE_STATE, span.trace_state);
+    }
+    trace.insert(fields::NAME, span.name);
+    trace.insert(fields::KIND, span_kind(span.kind));
+    if let Some(start_time) = otlp::timestamp_from_nanos(span.start_time_unix_nano) {
+        trace.insert(fields::START_TIME, start_time);
+    }
+    if let Some(end_time) = otlp::timestamp_from_nanos(span.end_time_unix_nano) {
+        trace.insert(fields::END_TIME, end_time);
+    }
+    trace.insert(fields::ATTRIBUTES, decode_attributes(span.attributes));
+
+    let events = span
+        .events
+        .into_iter()
+        .map(|event| {
+            let mut fields = BTreeMap::new();
+            fields.insert("name".to_owned(), Value::from(event.name));
+            if let Some(time) = otlp::timestamp_from_nanos(event.time_unix_nano) {
+                fields.insert("time".to_owned(), Value::from(time));
+            }
+            fields.insert(
+                "attributes".to_owned(),
+                Value::from(decode_attributes(event.attributes)),
+            );
+            Value::from(fields)
+        })
+        .collect::<Vec<_>>();
+    if !events.is_empty() {
+        trace.insert(fields::EVENTS, events);
+    }
+
+    let links = span
+        .links
+        .into_iter()
+        .map(|link| {
+            let mut fields = BTreeMap::new();
+            fields.insert(
+                fields::TRACE_ID.to_owned(),
+                Value::from(otlp::encode_hex(&link.trace_id)),
+            );
+            fields.insert(
+                fields::SPAN_ID.to_owned(),
+                Value::from(otlp::encode_hex(&link.span_id)),
+            );
+            if !link.trace_state.is_empty() {
+                fields.insert(TRACE_STATE.to_owned(), Value::from(link.trace_state));
+            }
+            fields.insert(
+                "attributes".to_owned(),
+                Value::from(decode_attributes(link.attributes)),
+            );
+            Value::from(fields)
+        })
+        .collect::<Vec<_>>();
+    if !links.is_empty() {
+        trace.insert(fields::LINKS, links);
+    }
+
+    if let Some(status) = span.status {
+        let mut fields = BTreeMap::new();
+        fields.insert("code".to_owned(), Value::from(status_code(status.code)));
+        if !status.message.is_empty() {
+            fields.insert("message".to_owned(), Value::from(status.message));
+        }
+        trace.insert(fields::STATUS, fields);
+    }
+
+    if !resources.is_empty() {
+        trace.insert(RESOURCES, resources.clone());
+    }
+    trace
+}
+
+fn span_kind(kind: i32) -> &'static str {
+    match SpanKind::from_i32(kind) {
+        Some(SpanKind::Internal) => "internal",
+        Some(SpanKind::Server) => "server",
+        Some(SpanKind::Client) => "client",
+        Some(SpanKind::Producer) => "producer",
+        Some(SpanKind::Consumer) => "consumer",
+        Some(SpanKind::Unspecified) | None => "unspecified",
+    }
+}
+
+fn status_code(code: i32) -> &'static str {
+    match StatusCode::from_i32(code) {
+        Some(StatusCode::Ok) => "ok",
+        Some(StatusCode::Error) => "error",
+        Some(StatusCode::Unset) | None => "unset",
+    }
+}
+
+fn resource_attributes(resource: Option<Resource>) -> BTreeMap<String, Value> {
+    resource.map_or_else(BTreeMap::new, |resource| {
+        decode_attributes(resource.attributes)
+    })
+}
+
+fn decode_attributes(attributes: Vec<KeyValue>) -> BTreeMap<String, Value> {
+    attributes
+        .into_iter()
+        .map(|KeyValue { key, value }| (key, value.map_or(Value::Null, decode_any_value)))
+        .collect()
+}
+
+fn decode_any_value(value: AnyValue) -> Value {
+    match value.value {
+        Some(AnyValueKind::StringValue(string)) => Value::from(string),
+        Some(AnyValueKind::BoolValue(boolean)) => Value::Boolean(boolean),
+        Some(AnyValueKind::IntValue(integer)) => Value::Integer(integer),
+        Some(AnyValueKind::DoubleValue(float)) => Value::Float(float),
+        Some(AnyValueKind::ArrayValue(array)) => {
+            Value::Array(array.values.into_iter().map(decode_any_value).collect())
+        }
+        Some(AnyValueKind::KvlistValue(list)) => Value::Map(decode_attributes(list.values)),
+        Some(AnyValueKind::BytesValue(bytes)) => Value::Bytes(bytes.into()),
+        None => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::opentelemetry::proto::{
+        common::v1::InstrumentationLibrary,
+        logs::v1::{InstrumentationLibraryLogs, LogRecord as OtlpLogRecord},
+        metrics::v1::{
+            number_data_point, Histogram, HistogramDataPoint, InstrumentationLibraryMetrics, Sum,
+        },
+        trace::v1::{InstrumentationLibrarySpans, Status},
+    };
+    use chrono::TimeZone;
+
+    fn string(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_owned(),
+            value: Some(AnyValue {
+                value: Some(AnyValueKind::StringValue(value.to_owned())),
+            }),
+        }
+    }
+
+    fn resource() -> Option<Resource> {
+        Some(Resource {
+            attributes: vec![string("service.name", "checkout")],
+            dropped_attributes_count: 0,
+        })
+    }
+
+    #[test]
+    fn decodes_logs() {
+        let events = logs(vec![ResourceLogs {
+            resource: resource(),
+            instrumentation_library_logs: vec![InstrumentationLibraryLogs {
+                instrumentation_library: Some(InstrumentationLibrary::default()),
+                logs: vec![OtlpLogRecord {
+                    time_unix_nano: 1_628_000_000_000_000_000,
+                    severity_number: 9,
+                    body: Some(AnyValue {
+                        value: Some(AnyValueKind::StringValue("hello".to_owned())),
+                    }),
+                    attributes: vec![string("http.method", "GET")],
+                    span_id: vec![1; 8],
+                    ..Default::default()
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }]);
+
+        assert_eq!(events.len(), 1);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "hello".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.timestamp(1_628_000_000, 0).into()
+        );
+        assert_eq!(log["attributes.http\\.method"], "GET".into());
+        assert_eq!(log["resources.service\\.name"], "checkout".into());
+        assert_eq!(log[otlp::SPAN_ID], "0101010101010101".into());
+        assert_eq!(log[log_schema().source_type_key()], "opentelemetry".into());
+    }
+
+    #[test]
+    fn decodes_metrics() {
+        let point = NumberDataPoint {
+            attributes: vec![string("host", "a")],
+            time_unix_nano: 1_628_000_000_000_000_000,
+            value: Some(number_data_point::Value::AsInt(3)),
+            ..Default::default()
+        };
+        let events = metrics(vec![ResourceMetrics {
+            resource: resource(),
+            instrumentation_library_metrics: vec![InstrumentationLibraryMetrics {
+                instrumentation_library: None,
+                metrics: vec![
+                    OtlpMetric {
+                        name: "requests".to_owned(),
+                        data: Some(Data::Sum(Sum {
+                            data_points: vec![point],
+                            aggregation_temporality: AggregationTemporality::Delta as i32,
+                            is_monotonic: true,
+                        })),
+                        ..Default::default()
+                    },
+                    OtlpMetric {
+                        name: "latency".to_owned(),
+                        data: Some(Data::Histogram(Histogram {
+                            data_points: vec![HistogramDataPoint {
+                                count: 3,
+                                sum: 7.5,
+                                bucket_counts: vec![1, 2, 0],
+                                explicit_bounds: vec![1.0, 5.0],
+                                ..Default::default()
+                            }],
+                            aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                        })),
+                        ..Default::default()
+                    },
+                ],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }]);
+
+        assert_eq!(events.len(), 2);
+        let counter = events[0].as_metric();
+        assert_eq!(counter.name(), "requests");
+        assert_eq!(counter.kind(), MetricKind::Incremental);
+        assert_eq!(counter.value(), &MetricValue::Counter { value: 3.0 });
+        let tags = counter.tags().unwrap();
+        assert_eq!(tags["host"], "a");
+        assert_eq!(tags["service.name"], "checkout");
+
+        let histogram = events[1].as_metric();
+        assert_eq!(histogram.kind(), MetricKind::Absolute);
+        assert_eq!(
+            histogram.value(),
+            &MetricValue::AggregatedHistogram {
+                buckets: vec![
+                    Bucket {
+                        upper_limit: 1.0,
+                        count: 1
+                    },
+                    Bucket {
+                        upper_limit: 5.0,
+                        count: 2
+                    },
+                    Bucket {
+                        upper_limit: f64::INFINITY,
+                        count: 0
+                    },
+                ],
+                count: 3,
+                sum: 7.5,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_spans() {
+        let events = spans(vec![ResourceSpans {
+            resource: resource(),
+            instrumentation_library_spans: vec![InstrumentationLibrarySpans {
+                instrumentation_library: None,
+                spans: vec![Span {
+                    trace_id: vec![0xab; 16],
+                    span_id: vec![0xcd; 8],
+                    name: "GET /cart".to_owned(),
+                    kind: SpanKind::Server as i32,
+                    start_time_unix_nano: 1_628_000_000_000_000_000,
+                    end_time_unix_nano: 1_628_000_001_000_000_000,
+                    status: Some(Status {
+                        message: "boom".to_owned(),
+                        code: StatusCode::Error as i32,
+                    }),
+                    ..Default::default()
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }]);
+
+        assert_eq!(events.len(), 1);
+        let trace = match &events[0] {
+            Event::Trace(trace) => trace,
+            event => panic!("expected a span, got {:?}", event),
+        };
+        assert_eq!(trace.trace_id(), Some(&Value::from("ab".repeat(16))));
+        assert_eq!(trace.get(fields::KIND), Some(&Value::from("server")));
+        assert_eq!(
+            trace.get(fields::END_TIME),
+            Some(&Value::from(Utc.timestamp(1_628_000_001, 0)))
+        );
+        assert_eq!(trace.get("status.code"), Some(&Value::from("error")));
+        assert_eq!(
+            trace.get("resources.service\\.name"),
+            Some(&Value::from("checkout"))
+        );
+        assert!(!trace.contains(fields::PARENT_SPAN_ID));
+    }
+}
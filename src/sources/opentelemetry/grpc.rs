@@ -0,0 +1,127 @@
+use super::{convert, GrpcConfig};
+use crate::{
+    config::SourceContext,
+    event::Event,
+    internal_events::OpentelemetryEventsReceived,
+    proto::opentelemetry::{
+        ExportLogsServiceRequest, ExportLogsServiceResponse, ExportMetricsServiceRequest,
+        ExportMetricsServiceResponse, ExportTraceServiceRequest, ExportTraceServiceResponse,
+        LogsService, LogsServiceServer, MetricsService, MetricsServiceServer, TraceService,
+        TraceServiceServer,
+    },
+    shutdown::ShutdownSignalToken,
+    tls::MaybeTlsSettings,
+    Pipeline,
+};
+use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
+use std::sync::Arc;
+use tonic::{transport::Server, Request, Response, Status};
+use vector_core::{
+    event::{BatchNotifier, BatchStatus, BatchStatusReceiver},
+    ByteSizeOf,
+};
+
+#[derive(Debug, Clone)]
+struct Service {
+    pipeline: Pipeline,
+    acknowledgements: bool,
+}
+
+impl Service {
+    async fn send(&self, mut events: Vec<Event>) -> Result<(), Status> {
+        emit!(OpentelemetryEventsReceived {
+            count: events.len(),
+            byte_size: events.size_of(),
+        });
+
+        let receiver = self.acknowledgements.then(|| {
+            let (batch, receiver) = BatchNotifier::new_with_receiver();
+            for event in &mut events {
+                event.add_batch_notifier(Arc::clone(&batch));
+            }
+
+            receiver
+        });
+
+        self.pipeline
+            .clone()
+            .send_all(&mut futures::stream::iter(events).map(Ok))
+            .map_err(|err| Status::unavailable(err.to_string()))
+            .and_then(|_| handle_batch_status(receiver))
+            .await
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for Service {
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        self.send(convert::logs(request.into_inner().resource_logs))
+            .await?;
+        Ok(Response::new(ExportLogsServiceResponse {}))
+    }
+}
+
+#[tonic::async_trait]
+impl MetricsService for Service {
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        self.send(convert::metrics(request.into_inner().resource_metrics))
+            .await?;
+        Ok(Response::new(ExportMetricsServiceResponse {}))
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for Service {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        self.send(convert::spans(request.into_inner().resource_spans))
+            .await?;
+        Ok(Response::new(ExportTraceServiceResponse {}))
+    }
+}
+
+async fn handle_batch_status(receiver: Option<BatchStatusReceiver>) -> Result<(), Status> {
+    let status = match receiver {
+        Some(receiver) => receiver.await,
+        None => BatchStatus::Delivered,
+    };
+
+    match status {
+        BatchStatus::Errored => Err(Status::internal("Delivery error")),
+        BatchStatus::Failed => Err(Status::data_loss("Delivery failed")),
+        BatchStatus::Delivered => Ok(()),
+    }
+}
+
+pub(super) async fn run(config: GrpcConfig, cx: SourceContext) -> crate::Result<()> {
+    let _span = crate::trace::current_span();
+
+    let tls_settings = MaybeTlsSettings::from_config(&config.tls, true)?;
+    let service = Service {
+        pipeline: cx.out,
+        acknowledgements: cx.acknowledgements,
+    };
+    let (tx, rx) = tokio::sync::oneshot::channel::<ShutdownSignalToken>();
+
+    let listener = tls_settings.bind(&config.address).await?;
+    let stream = listener.accept_stream();
+
+    Server::builder()
+        .add_service(LogsServiceServer::new(service.clone()))
+        .add_service(MetricsServiceServer::new(service.clone()))
+        .add_service(TraceServiceServer::new(service))
+        .serve_with_incoming_shutdown(stream, cx.shutdown.map(|token| tx.send(token).unwrap()))
+        .await?;
+
+    drop(rx.await);
+
+    Ok(())
+}
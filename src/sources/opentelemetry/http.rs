@@ -0,0 +1,70 @@
+use super::convert;
+use crate::{
+    event::Event,
+    proto::opentelemetry::{
+        ExportLogsServiceRequest, ExportMetricsServiceRequest, ExportTraceServiceRequest,
+    },
+    sources::util::{ErrorMessage, HttpSource},
+};
+use bytes::Bytes;
+use prost::Message;
+use std::collections::HashMap;
+use warp::http::{header::CONTENT_TYPE, HeaderMap, StatusCode};
+
+pub(super) const PATH: &str = "/v1";
+
+const PROTOBUF: &str = "application/x-protobuf";
+
+/// Accepts binary protobuf OTLP requests, posted to the `/v1/logs`,
+/// `/v1/metrics` and `/v1/traces` endpoints of the OTLP/HTTP specification.
+#[derive(Clone)]
+pub(super) struct OpentelemetryHttpSource;
+
+impl HttpSource for OpentelemetryHttpSource {
+    fn build_events(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        _query_parameters: HashMap<String, String>,
+        request_path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let content_type = header_map
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or(PROTOBUF);
+        if !content_type.starts_with(PROTOBUF) {
+            return Err(ErrorMessage::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("Unsupported content type {}", content_type),
+            ));
+        }
+
+        match request_path.trim_end_matches('/') {
+            "/v1/logs" => {
+                let request = decode::<ExportLogsServiceRequest>(body)?;
+                Ok(convert::logs(request.resource_logs))
+            }
+            "/v1/metrics" => {
+                let request = decode::<ExportMetricsServiceRequest>(body)?;
+                Ok(convert::metrics(request.resource_metrics))
+            }
+            "/v1/traces" => {
+                let request = decode::<ExportTraceServiceRequest>(body)?;
+                Ok(convert::spans(request.resource_spans))
+            }
+            _ => Err(ErrorMessage::new(
+                StatusCode::NOT_FOUND,
+                "Not found".to_owned(),
+            )),
+        }
+    }
+}
+
+fn decode<T: Message + Default>(body: Bytes) -> Result<T, ErrorMessage> {
+    T::decode(body).map_err(|error| {
+        ErrorMessage::new(
+            StatusCode::BAD_REQUEST,
+            format!("Error decoding request: {}", error),
+        )
+    })
+}
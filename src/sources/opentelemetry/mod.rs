@@ -0,0 +1,271 @@
+mod convert;
+mod grpc;
+mod http;
+
+use self::http::OpentelemetryHttpSource;
+use crate::{
+    config::{DataType, GenerateConfig, Resource, SourceConfig, SourceContext, SourceDescription},
+    sources::{
+        util::{HttpSource, HttpSourceAuthConfig},
+        Source,
+    },
+    tls::TlsConfig,
+};
+use futures::{future::try_join_all, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::net::SocketAddr;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("At least one of `grpc` and `http` must be configured"))]
+    NoServers,
+}
+
+/// Receives logs, metrics and traces over the OpenTelemetry protocol, on a
+/// gRPC server, an HTTP server, or both.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpentelemetryConfig {
+    #[serde(default)]
+    grpc: Option<GrpcConfig>,
+    #[serde(default)]
+    http: Option<HttpConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct GrpcConfig {
+    address: SocketAddr,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct HttpConfig {
+    address: SocketAddr,
+    #[serde(default)]
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    auth: Option<HttpSourceAuthConfig>,
+}
+
+inventory::submit! {
+    SourceDescription::new::<OpentelemetryConfig>("opentelemetry")
+}
+
+impl GenerateConfig for OpentelemetryConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            grpc: Some(GrpcConfig {
+                address: "0.0.0.0:4317".parse().unwrap(),
+                tls: None,
+            }),
+            http: Some(HttpConfig {
+                address: "0.0.0.0:4318".parse().unwrap(),
+                tls: None,
+                auth: None,
+            }),
+        })
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "opentelemetry")]
+impl SourceConfig for OpentelemetryConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<Source> {
+        let mut servers: Vec<Source> = Vec::new();
+        if let Some(config) = &self.http {
+            // Both servers share the pipeline and shutdown signal of the source.
+            let cx = SourceContext {
+                id: cx.id.clone(),
+                globals: cx.globals.clone(),
+                shutdown: cx.shutdown.clone(),
+                out: cx.out.clone(),
+                acknowledgements: cx.acknowledgements,
+                proxy: cx.proxy.clone(),
+            };
+            servers.push(OpentelemetryHttpSource.run(
+                config.address,
+                http::PATH,
+                false,
+                &config.tls,
+                &config.auth,
+                cx,
+            )?);
+        }
+        if let Some(config) = &self.grpc {
+            let server = grpc::run(config.clone(), cx).map_err(|error| {
+                error!(message = "Source future failed.", %error);
+            });
+            servers.push(Box::pin(server));
+        }
+        if servers.is_empty() {
+            return Err(BuildError::NoServers.into());
+        }
+
+        Ok(Box::pin(try_join_all(servers).map_ok(|_| ())))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn source_type(&self) -> &'static str {
+        "opentelemetry"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        self.grpc
+            .iter()
+            .map(|config| config.address)
+            .chain(self.http.iter().map(|config| config.address))
+            .map(Resource::tcp)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::log_schema,
+        event::{Event, EventStatus},
+        proto::opentelemetry::{
+            proto::{
+                common::v1::{any_value, AnyValue},
+                logs::v1::{InstrumentationLibraryLogs, LogRecord, ResourceLogs},
+            },
+            ExportLogsServiceRequest,
+        },
+        test_util::{next_addr, spawn_collect_n, trace_init, wait_for_tcp},
+        Pipeline,
+    };
+    use futures::Stream;
+    use prost::Message;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<OpentelemetryConfig>();
+    }
+
+    async fn source(status: EventStatus) -> (impl Stream<Item = Event>, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test_finalize(status);
+        let address = next_addr();
+        let mut context = SourceContext::new_test(sender);
+        context.acknowledgements = true;
+        tokio::spawn(async move {
+            OpentelemetryConfig {
+                grpc: None,
+                http: Some(HttpConfig {
+                    address,
+                    tls: None,
+                    auth: None,
+                }),
+            }
+            .build(context)
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
+    fn request(message: &str) -> Vec<u8> {
+        ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                resource: None,
+                instrumentation_library_logs: vec![InstrumentationLibraryLogs {
+                    instrumentation_library: None,
+                    logs: vec![LogRecord {
+                        body: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue(message.to_owned())),
+                        }),
+                        ..Default::default()
+                    }],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        }
+        .encode_to_vec()
+    }
+
+    async fn send(address: SocketAddr, path: &str, content_type: &str, body: Vec<u8>) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}{}", address, path))
+            .header("content-type", content_type)
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    #[tokio::test]
+    async fn http_logs() {
+        trace_init();
+        let (rx, address) = source(EventStatus::Delivered).await;
+
+        let events = spawn_collect_n(
+            async move {
+                assert_eq!(
+                    200,
+                    send(
+                        address,
+                        "/v1/logs",
+                        "application/x-protobuf",
+                        request("hello")
+                    )
+                    .await
+                );
+            },
+            rx,
+            1,
+        )
+        .await;
+
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "hello".into());
+        assert_eq!(log[log_schema().source_type_key()], "opentelemetry".into());
+    }
+
+    #[tokio::test]
+    async fn http_rejects_json() {
+        trace_init();
+        let (_rx, address) = source(EventStatus::Delivered).await;
+
+        assert_eq!(
+            415,
+            send(address, "/v1/logs", "application/json", b"{}".to_vec()).await
+        );
+        assert_eq!(
+            404,
+            send(
+                address,
+                "/v1/profiles",
+                "application/x-protobuf",
+                request("hello")
+            )
+            .await
+        );
+    }
+
+    #[test]
+    fn requires_a_server() {
+        let config: OpentelemetryConfig = toml::from_str("").unwrap();
+        let (sender, _recv) = Pipeline::new_test();
+        let error = futures::executor::block_on(config.build(SourceContext::new_test(sender)))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "At least one of `grpc` and `http` must be configured"
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! Decodes the binary messages of the built-in `pgoutput` plugin.
+//!
+//! See https://www.postgresql.org/docs/current/protocol-logicalrep-message-formats.html
+use super::{Change, DecodeError, Decoded, Operation, Transaction};
+use crate::event::Value;
+use bytes::{Buf, Bytes};
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Seconds from the Unix epoch to the PostgreSQL epoch, 2000-01-01.
+const POSTGRES_EPOCH_SECS: i64 = 946_684_800;
+
+const BOOL_OID: u32 = 16;
+const INT8_OID: u32 = 20;
+const INT2_OID: u32 = 21;
+const INT4_OID: u32 = 23;
+const FLOAT4_OID: u32 = 700;
+const FLOAT8_OID: u32 = 701;
+
+#[derive(Debug)]
+struct Column {
+    name: String,
+    type_oid: u32,
+}
+
+#[derive(Debug)]
+struct Relation {
+    schema: String,
+    table: String,
+    columns: Vec<Column>,
+}
+
+/// Relations are described once before their first change, so they are kept
+/// for as long as the connection they were decoded from.
+#[derive(Debug, Default)]
+pub(super) struct Decoder {
+    relations: HashMap<u32, Relation>,
+}
+
+impl Decoder {
+    pub(super) fn decode(&mut self, data: &[u8]) -> Result<Option<Decoded>, DecodeError> {
+        let mut buf = Bytes::copy_from_slice(data);
+        let decoded = match get_u8(&mut buf)? {
+            b'B' => {
+                let final_lsn = get_u64(&mut buf)?;
+                let timestamp = get_timestamp(&mut buf)?;
+                let xid = get_u32(&mut buf)?;
+                Some(Decoded::Begin(Transaction {
+                    xid: Some(xid.to_string()),
+                    lsn: Some(format_lsn(final_lsn)),
+                    commit_timestamp: Some(timestamp),
+                }))
+            }
+            b'C' => Some(Decoded::Commit),
+            b'R' => {
+                let id = get_u32(&mut buf)?;
+                let schema = get_cstring(&mut buf)?;
+                let table = get_cstring(&mut buf)?;
+                let _replica_identity = get_u8(&mut buf)?;
+                let count = get_u16(&mut buf)?;
+                let columns = (0..count)
+                    .map(|_| {
+                        let _flags = get_u8(&mut buf)?;
+                        let name = get_cstring(&mut buf)?;
+                        let type_oid = get_u32(&mut buf)?;
+                        let _type_modifier = get_u32(&mut buf)?;
+                        Ok(Column { name, type_oid })
+                    })
+                    .collect::<Result<_, DecodeError>>()?;
+                self.relations.insert(
+                    id,
+                    Relation {
+                        schema,
+                        table,
+                        columns,
+                    },
+                );
+                None
+            }
+            b'I' => {
+                let relation = self.relation(get_u32(&mut buf)?)?;
+                expect_tag(&mut buf, b'N')?;
+                let columns = get_tuple(&mut buf, relation)?;
+                Some(relation.change(Operation::Insert, Some(columns), None))
+            }
+            b'U' => {
+                let relation = self.relation(get_u32(&mut buf)?)?;
+                let mut old = None;
+                let tag = get_u8(&mut buf)?;
+                if tag == b'K' || tag == b'O' {
+                    old = Some(get_tuple(&mut buf, relation)?);
+                    expect_tag(&mut buf, b'N')?;
+                } else if tag != b'N' {
+                    return Err(DecodeError::UnexpectedTag { tag });
+                }
+                let columns = get_tuple(&mut buf, relation)?;
+                Some(relation.change(Operation::Update, Some(columns), old))
+            }
+            b'D' => {
+                let relation = self.relation(get_u32(&mut buf)?)?;
+                let tag = get_u8(&mut buf)?;
+                if tag != b'K' && tag != b'O' {
+                    return Err(DecodeError::UnexpectedTag { tag });
+                }
+                let old = get_tuple(&mut buf, relation)?;
+                Some(relation.change(Operation::Delete, None, Some(old)))
+            }
+            b'T' => {
+                let count = get_u32(&mut buf)?;
+                let _options = get_u8(&mut buf)?;
+                let changes = (0..count)
+                    .map(|_| {
+                        let relation = self.relation(get_u32(&mut buf)?)?;
+                        Ok(Change {
+                            operation: Operation::Truncate,
+                            schema: relation.schema.clone(),
+                            table: relation.table.clone(),
+                            columns: None,
+                            old: None,
+                        })
+                    })
+                    .collect::<Result<_, DecodeError>>()?;
+                Some(Decoded::Changes(changes))
+            }
+            // Origins, types and messages
+            _ => None,
+        };
+        Ok(decoded)
+    }
+
+    fn relation(&self, id: u32) -> Result<&Relation, DecodeError> {
+        self.relations
+            .get(&id)
+            .ok_or(DecodeError::UnknownRelation { id })
+    }
+}
+
+impl Relation {
+    fn change(
+        &self,
+        operation: Operation,
+        columns: Option<BTreeMap<String, Value>>,
+        old: Option<BTreeMap<String, Value>>,
+    ) -> Decoded {
+        Decoded::Changes(vec![Change {
+            operation,
+            schema: self.schema.clone(),
+            table: self.table.clone(),
+            columns,
+            old,
+        }])
+    }
+}
+
+fn get_tuple(buf: &mut Bytes, relation: &Relation) -> Result<BTreeMap<String, Value>, DecodeError> {
+    let count = get_u16(buf)? as usize;
+    if count > relation.columns.len() {
+        return Err(DecodeError::ColumnCountMismatch {
+            expected: relation.columns.len(),
+            count,
+        });
+    }
+
+    let mut tuple = BTreeMap::new();
+    for column in &relation.columns[..count] {
+        match get_u8(buf)? {
+            b'n' => {
+                tuple.insert(column.name.clone(), Value::Null);
+            }
+            // Unchanged values stored out of line aren't sent, so are left out.
+            b'u' => {}
+            b't' => {
+                let len = get_u32(buf)? as usize;
+                if buf.remaining() < len {
+                    return Err(DecodeError::Truncated);
+                }
+                let text = buf.split_to(len);
+                tuple.insert(column.name.clone(), parse_text(column.type_oid, text));
+            }
+            tag => return Err(DecodeError::UnexpectedTag { tag }),
+        }
+    }
+    Ok(tuple)
+}
+
+/// Values are sent in their text representation, which is parsed for the
+/// types with an equivalent, and otherwise kept as a string. Only text that
+/// isn't valid UTF-8 is kept as binary.
+fn parse_text(type_oid: u32, text: Bytes) -> Value {
+    let parsed = std::str::from_utf8(&text)
+        .ok()
+        .and_then(|s| match type_oid {
+            BOOL_OID => Some(Value::Boolean(s == "t")),
+            INT2_OID | INT4_OID | INT8_OID => s.parse::<i64>().ok().map(Value::from),
+            FLOAT4_OID | FLOAT8_OID => s.parse::<f64>().ok().map(Value::from),
+            _ => None,
+        });
+    parsed.unwrap_or_else(|| Value::from(text))
+}
+
+/// Formats an LSN the way PostgreSQL does, as two hexadecimal halves.
+pub(super) fn format_lsn(lsn: u64) -> String {
+    format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF)
+}
+
+fn expect_tag(buf: &mut Bytes, expected: u8) -> Result<(), DecodeError> {
+    match get_u8(buf)? {
+        tag if tag == expected => Ok(()),
+        tag => Err(DecodeError::UnexpectedTag { tag }),
+    }
+}
+
+fn get_u8(buf: &mut Bytes) -> Result<u8, DecodeError> {
+    if buf.remaining() < 1 {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(buf.get_u8())
+}
+
+fn get_u16(buf: &mut Bytes) -> Result<u16, DecodeError> {
+    if buf.remaining() < 2 {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(buf.get_u16())
+}
+
+fn get_u32(buf: &mut Bytes) -> Result<u32, DecodeError> {
+    if buf.remaining() < 4 {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(buf.get_u32())
+}
+
+fn get_u64(buf: &mut Bytes) -> Result<u64, DecodeError> {
+    if buf.remaining() < 8 {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(buf.get_u64())
+}
+
+/// Microseconds since the PostgreSQL epoch.
+fn get_timestamp(buf: &mut Bytes) -> Result<DateTime<Utc>, DecodeError> {
+    let micros = get_u64(buf)? as i64;
+    let secs = micros.div_euclid(1_000_000) + POSTGRES_EPOCH_SECS;
+    let nanos = micros.rem_euclid(1_000_000) as u32 * 1000;
+    Ok(Utc.timestamp(secs, nanos))
+}
+
+fn get_cstring(buf: &mut Bytes) -> Result<String, DecodeError> {
+    let end = buf
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(DecodeError::Truncated)?;
+    let s = String::from_utf8_lossy(&buf[..end]).into_owned();
+    buf.advance(end + 1);
+    Ok(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BufMut;
+
+    fn relation_message() -> Vec<u8> {
+        let mut buf = vec![b'R'];
+        buf.put_u32(16384);
+        buf.put_slice(b"public\0users\0");
+        buf.put_u8(b'd');
+        buf.put_u16(3);
+        for (name, oid) in &[("id", INT4_OID), ("name", 25), ("active", BOOL_OID)] {
+            buf.put_u8(1);
+            buf.put_slice(name.as_bytes());
+            buf.put_u8(0);
+            buf.put_u32(*oid);
+            buf.put_u32(u32::MAX);
+        }
+        buf
+    }
+
+    fn put_text(buf: &mut Vec<u8>, text: &str) {
+        buf.put_u8(b't');
+        buf.put_u32(text.len() as u32);
+        buf.put_slice(text.as_bytes());
+    }
+
+    #[test]
+    fn decodes_begin() {
+        let mut buf = vec![b'B'];
+        buf.put_u64(0x1_6B37_4D48);
+        buf.put_u64(1_000_000);
+        buf.put_u32(490);
+
+        let decoded = Decoder::default().decode(&buf).unwrap();
+        assert_eq!(
+            decoded,
+            Some(Decoded::Begin(Transaction {
+                xid: Some("490".to_owned()),
+                lsn: Some("1/6B374D48".to_owned()),
+                commit_timestamp: Some(Utc.ymd(2000, 1, 1).and_hms(0, 0, 1)),
+            }))
+        );
+    }
+
+    #[test]
+    fn decodes_insert_and_update() {
+        let mut decoder = Decoder::default();
+        assert_eq!(decoder.decode(&relation_message()).unwrap(), None);
+
+        let mut insert = vec![b'I'];
+        insert.put_u32(16384);
+        insert.put_u8(b'N');
+        insert.put_u16(3);
+        put_text(&mut insert, "1");
+        put_text(&mut insert, "alice");
+        insert.put_u8(b'n');
+
+        let mut columns = BTreeMap::new();
+        columns.insert("id".to_owned(), Value::from(1));
+        columns.insert("name".to_owned(), Value::from("alice"));
+        columns.insert("active".to_owned(), Value::Null);
+        assert_eq!(
+            decoder.decode(&insert).unwrap(),
+            Some(Decoded::Changes(vec![Change {
+                operation: Operation::Insert,
+                schema: "public".to_owned(),
+                table: "users".to_owned(),
+                columns: Some(columns),
+                old: None,
+            }]))
+        );
+
+        let mut update = vec![b'U'];
+        update.put_u32(16384);
+        update.put_u8(b'K');
+        update.put_u16(1);
+        put_text(&mut update, "1");
+        update.put_u8(b'N');
+        update.put_u16(3);
+        put_text(&mut update, "2");
+        update.put_u8(b'u');
+        put_text(&mut update, "t");
+
+        let mut columns = BTreeMap::new();
+        columns.insert("id".to_owned(), Value::from(2));
+        columns.insert("active".to_owned(), Value::Boolean(true));
+        let mut old = BTreeMap::new();
+        old.insert("id".to_owned(), Value::from(1));
+        assert_eq!(
+            decoder.decode(&update).unwrap(),
+            Some(Decoded::Changes(vec![Change {
+                operation: Operation::Update,
+                schema: "public".to_owned(),
+                table: "users".to_owned(),
+                columns: Some(columns),
+                old: Some(old),
+            }]))
+        );
+    }
+
+    #[test]
+    fn parses_text_as_strings() {
+        assert_eq!(
+            parse_text(25, Bytes::from_static(b"alice")),
+            Value::String(Bytes::from_static(b"alice"))
+        );
+        assert_eq!(
+            parse_text(25, Bytes::from_static(b"\xff\xfe")),
+            Value::Bytes(Bytes::from_static(b"\xff\xfe"))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_relation() {
+        let mut insert = vec![b'I'];
+        insert.put_u32(1);
+        insert.put_u8(b'N');
+        insert.put_u16(0);
+        assert!(matches!(
+            Decoder::default().decode(&insert),
+            Err(DecodeError::UnknownRelation { id: 1 })
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        let mut decoder = Decoder::default();
+        let message = relation_message();
+        assert!(matches!(
+            decoder.decode(&message[..10]),
+            Err(DecodeError::Truncated)
+        ));
+    }
+}
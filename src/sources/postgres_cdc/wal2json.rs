@@ -0,0 +1,146 @@
+//! Decodes the output of the `wal2json` plugin in its version 2 format, which
+//! has one JSON object per change and per transaction boundary.
+//!
+//! See https://github.com/eulerto/wal2json
+use super::{Change, DecodeError, Decoded, Operation, Transaction};
+use crate::event::Value;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The options asking for the format and transaction metadata decoded here.
+pub(super) const OPTIONS: &[(&str, &str)] = &[
+    ("format-version", "2"),
+    ("include-xids", "1"),
+    ("include-timestamp", "1"),
+    ("include-lsn", "1"),
+];
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    action: String,
+    xid: Option<u64>,
+    timestamp: Option<String>,
+    lsn: Option<String>,
+    schema: Option<String>,
+    table: Option<String>,
+    #[serde(default)]
+    columns: Vec<Column>,
+    #[serde(default)]
+    identity: Vec<Column>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Column {
+    name: String,
+    value: serde_json::Value,
+}
+
+pub(super) fn decode(data: &str) -> Result<Option<Decoded>, DecodeError> {
+    let message: Message =
+        serde_json::from_str(data).map_err(|source| DecodeError::InvalidJson { source })?;
+
+    let operation = match message.action.as_str() {
+        "B" => {
+            return Ok(Some(Decoded::Begin(Transaction {
+                xid: message.xid.map(|xid| xid.to_string()),
+                lsn: message.lsn,
+                commit_timestamp: message.timestamp.as_deref().and_then(parse_timestamp),
+            })))
+        }
+        "C" => return Ok(Some(Decoded::Commit)),
+        "I" => Operation::Insert,
+        "U" => Operation::Update,
+        "D" => Operation::Delete,
+        "T" => Operation::Truncate,
+        // Logical decoding messages
+        _ => return Ok(None),
+    };
+
+    let (schema, table) = match (message.schema, message.table) {
+        (Some(schema), Some(table)) => (schema, table),
+        _ => return Err(DecodeError::MissingTable),
+    };
+    let columns = (!message.columns.is_empty()).then(|| to_map(message.columns));
+    let old = (!message.identity.is_empty()).then(|| to_map(message.identity));
+
+    Ok(Some(Decoded::Changes(vec![Change {
+        operation,
+        schema,
+        table,
+        columns,
+        old,
+    }])))
+}
+
+fn to_map(columns: Vec<Column>) -> BTreeMap<String, Value> {
+    columns
+        .into_iter()
+        .map(|column| (column.name, Value::from(column.value)))
+        .collect()
+}
+
+/// Timestamps are formatted like `2021-01-01 00:00:00.123456+00`.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f%#z")
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn decodes_begin() {
+        let decoded = decode(
+            r#"{"action":"B","xid":490,"timestamp":"2021-01-01 10:00:00.5+02","lsn":"0/16B2D40"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            Some(Decoded::Begin(Transaction {
+                xid: Some("490".to_owned()),
+                lsn: Some("0/16B2D40".to_owned()),
+                commit_timestamp: Some(Utc.ymd(2021, 1, 1).and_hms_milli(8, 0, 0, 500)),
+            }))
+        );
+    }
+
+    #[test]
+    fn decodes_delete() {
+        let decoded = decode(
+            r#"{"action":"D","schema":"public","table":"users","identity":[{"name":"id","type":"integer","value":1}]}"#,
+        )
+        .unwrap();
+
+        let mut old = BTreeMap::new();
+        old.insert("id".to_owned(), Value::from(1));
+        assert_eq!(
+            decoded,
+            Some(Decoded::Changes(vec![Change {
+                operation: Operation::Delete,
+                schema: "public".to_owned(),
+                table: "users".to_owned(),
+                columns: None,
+                old: Some(old),
+            }]))
+        );
+    }
+
+    #[test]
+    fn ignores_messages() {
+        let decoded =
+            decode(r#"{"action":"M","transactional":false,"prefix":"p","content":"c"}"#).unwrap();
+        assert_eq!(decoded, None);
+    }
+
+    #[test]
+    fn rejects_change_without_table() {
+        assert!(matches!(
+            decode(r#"{"action":"I","columns":[]}"#),
+            Err(DecodeError::MissingTable)
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+use super::relabel::Labels;
+use crate::{
+    config::ProxyConfig,
+    http::{Auth, HttpClient},
+    tls::{TlsOptions, TlsSettings},
+};
+use http::{Request, StatusCode, Uri};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Discovers targets from an HTTP endpoint, in the format of Prometheus'
+/// HTTP service discovery.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpSdConfig {
+    url: String,
+    #[serde(default = "default_refresh_interval_secs")]
+    refresh_interval_secs: u64,
+    tls: Option<TlsOptions>,
+    auth: Option<Auth>,
+}
+
+const fn default_refresh_interval_secs() -> u64 {
+    60
+}
+
+/// A group of targets sharing the same labels.
+#[derive(Debug, Deserialize)]
+struct TargetGroup {
+    targets: Vec<String>,
+    #[serde(default)]
+    labels: Labels,
+}
+
+pub struct HttpDiscoverer {
+    url: Uri,
+    refresh_interval_secs: u64,
+    auth: Option<Auth>,
+    client: HttpClient,
+}
+
+impl HttpDiscoverer {
+    pub fn new(config: HttpSdConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        let url = config.url.parse::<Uri>()?;
+        let tls = TlsSettings::from_options(&config.tls)?;
+        let client = HttpClient::new(tls, proxy)?;
+        Ok(Self {
+            url,
+            refresh_interval_secs: config.refresh_interval_secs,
+            auth: config.auth,
+            client,
+        })
+    }
+
+    pub const fn refresh_interval_secs(&self) -> u64 {
+        self.refresh_interval_secs
+    }
+
+    pub async fn discover(&mut self) -> crate::Result<Vec<Labels>> {
+        let mut request = Request::get(&self.url).body(Body::empty())?;
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.send(request)).await??;
+        if response.status() != StatusCode::OK {
+            return Err(format!("unexpected status {}", response.status()).into());
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        parse_target_groups(&body)
+    }
+}
+
+fn parse_target_groups(body: &[u8]) -> crate::Result<Vec<Labels>> {
+    let groups: Vec<TargetGroup> = serde_json::from_slice(body)?;
+    Ok(groups
+        .into_iter()
+        .flat_map(|group| {
+            let labels = group.labels;
+            group.targets.into_iter().map(move |target| {
+                let mut labels = labels.clone();
+                labels.insert("__address__".to_owned(), target);
+                labels
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_target_groups() {
+        let targets = parse_target_groups(
+            br#"[
+                {
+                    "targets": ["10.0.0.1:9100", "10.0.0.2:9100"],
+                    "labels": {"__meta_datacenter": "eu-1", "job": "node"}
+                },
+                {"targets": ["10.0.0.3:9100"]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[1]["__address__"], "10.0.0.2:9100");
+        assert_eq!(targets[1]["job"], "node");
+        assert_eq!(targets[2]["__address__"], "10.0.0.3:9100");
+        assert!(!targets[2].contains_key("job"));
+    }
+}
@@ -0,0 +1,395 @@
+use super::relabel::Labels;
+use crate::{config::ProxyConfig, kubernetes as k8s};
+use http::{Request, StatusCode};
+use k8s_openapi::{
+    api::core::v1::{Endpoints, Pod},
+    List, ListOptional, RequestError,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Discovers targets from the `Pod`s or `Endpoints` of the cluster, with
+/// the meta labels Prometheus' Kubernetes service discovery gives them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KubernetesSdConfig {
+    role: KubernetesRole,
+    /// The namespaces to discover targets in, all of them if empty.
+    #[serde(default)]
+    namespaces: Vec<String>,
+    label_selector: Option<String>,
+    field_selector: Option<String>,
+    /// Optional path to a kubeconfig file readable by Vector. If not set,
+    /// Vector will try to connect to Kubernetes using in-cluster configuration.
+    kube_config_file: Option<PathBuf>,
+    #[serde(default = "default_refresh_interval_secs")]
+    refresh_interval_secs: u64,
+}
+
+const fn default_refresh_interval_secs() -> u64 {
+    30
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum KubernetesRole {
+    Pod,
+    Endpoints,
+}
+
+pub struct KubernetesDiscoverer {
+    config: KubernetesSdConfig,
+    client: k8s::client::Client,
+}
+
+impl KubernetesDiscoverer {
+    pub fn new(config: KubernetesSdConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        let k8s_config = match &config.kube_config_file {
+            Some(kc) => k8s::client::config::Config::kubeconfig(kc)?,
+            None => k8s::client::config::Config::in_cluster()?,
+        };
+        let client = k8s::client::Client::new(k8s_config, proxy)?;
+        Ok(Self { config, client })
+    }
+
+    pub const fn refresh_interval_secs(&self) -> u64 {
+        self.config.refresh_interval_secs
+    }
+
+    /// Lists the resources anew on each refresh.
+    pub async fn discover(&mut self) -> crate::Result<Vec<Labels>> {
+        let namespaces = if self.config.namespaces.is_empty() {
+            vec![None]
+        } else {
+            self.config.namespaces.iter().cloned().map(Some).collect()
+        };
+
+        let mut targets = Vec::new();
+        for namespace in namespaces {
+            let optional = ListOptional {
+                label_selector: self.config.label_selector.as_deref(),
+                field_selector: self.config.field_selector.as_deref(),
+                ..Default::default()
+            };
+            let namespace = namespace.as_deref();
+            match self.config.role {
+                KubernetesRole::Pod => {
+                    let request = match namespace {
+                        Some(namespace) => Pod::list_namespaced_pod(namespace, optional),
+                        None => Pod::list_pod_for_all_namespaces(optional),
+                    };
+                    let pods: List<Pod> = self.list(request).await?;
+                    targets.extend(pods.items.iter().flat_map(pod_targets));
+                }
+                KubernetesRole::Endpoints => {
+                    let request = match namespace {
+                        Some(namespace) => {
+                            Endpoints::list_namespaced_endpoints(namespace, optional)
+                        }
+                        None => Endpoints::list_endpoints_for_all_namespaces(optional),
+                    };
+                    let endpoints: List<Endpoints> = self.list(request).await?;
+                    targets.extend(endpoints.items.iter().flat_map(endpoints_targets));
+                }
+            }
+        }
+        Ok(targets)
+    }
+
+    async fn list<T: DeserializeOwned, R>(
+        &mut self,
+        request: Result<(Request<Vec<u8>>, R), RequestError>,
+    ) -> crate::Result<T> {
+        let (request, _) = request?;
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.send(request)).await??;
+        if response.status() != StatusCode::OK {
+            return Err(format!("unexpected status {}", response.status()).into());
+        }
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// A target for each port of each container, or a single one with the IP
+/// alone for `Pod`s without any ports, to be completed with relabeling.
+fn pod_targets(pod: &Pod) -> Vec<Labels> {
+    let ip = match pod
+        .status
+        .as_ref()
+        .and_then(|status| status.pod_ip.as_ref())
+    {
+        Some(ip) => ip,
+        // Not yet scheduled
+        None => return vec![],
+    };
+
+    let mut common = BTreeMap::new();
+    add_meta(&mut common, "namespace", pod.metadata.namespace.as_ref());
+    add_meta(&mut common, "pod_name", pod.metadata.name.as_ref());
+    add_meta(&mut common, "pod_ip", Some(ip));
+    add_meta(&mut common, "pod_uid", pod.metadata.uid.as_ref());
+    if let Some(spec) = &pod.spec {
+        add_meta(&mut common, "pod_node_name", spec.node_name.as_ref());
+    }
+    if let Some(status) = &pod.status {
+        add_meta(&mut common, "pod_phase", status.phase.as_ref());
+        let ready = status
+            .conditions
+            .iter()
+            .flatten()
+            .any(|condition| condition.type_ == "Ready" && condition.status == "True");
+        add_meta(&mut common, "pod_ready", Some(&ready.to_string()));
+    }
+    add_object_meta(
+        &mut common,
+        "pod",
+        &pod.metadata.labels,
+        &pod.metadata.annotations,
+    );
+
+    let containers = pod.spec.iter().flat_map(|spec| spec.containers.iter());
+    let mut targets = Vec::new();
+    for container in containers {
+        for port in container.ports.iter().flatten() {
+            let mut labels = common.clone();
+            labels.insert(
+                "__address__".to_owned(),
+                format!("{}:{}", ip, port.container_port),
+            );
+            add_meta(&mut labels, "pod_container_name", Some(&container.name));
+            add_meta(&mut labels, "pod_container_port_name", port.name.as_ref());
+            add_meta(
+                &mut labels,
+                "pod_container_port_number",
+                Some(&port.container_port.to_string()),
+            );
+            add_meta(
+                &mut labels,
+                "pod_container_port_protocol",
+                port.protocol.as_ref(),
+            );
+            targets.push(labels);
+        }
+    }
+    if targets.is_empty() {
+        common.insert("__address__".to_owned(), ip.clone());
+        targets.push(common);
+    }
+    targets
+}
+
+/// A target for each port of each address, ready or not.
+fn endpoints_targets(endpoints: &Endpoints) -> Vec<Labels> {
+    let mut common = BTreeMap::new();
+    add_meta(
+        &mut common,
+        "namespace",
+        endpoints.metadata.namespace.as_ref(),
+    );
+    add_meta(
+        &mut common,
+        "endpoints_name",
+        endpoints.metadata.name.as_ref(),
+    );
+    add_object_meta(
+        &mut common,
+        "endpoints",
+        &endpoints.metadata.labels,
+        &endpoints.metadata.annotations,
+    );
+
+    let mut targets = Vec::new();
+    for subset in endpoints.subsets.iter().flatten() {
+        let addresses = subset
+            .addresses
+            .iter()
+            .flatten()
+            .map(|address| (address, true))
+            .chain(
+                subset
+                    .not_ready_addresses
+                    .iter()
+                    .flatten()
+                    .map(|address| (address, false)),
+            );
+        for (address, ready) in addresses {
+            for port in subset.ports.iter().flatten() {
+                let mut labels = common.clone();
+                labels.insert(
+                    "__address__".to_owned(),
+                    format!("{}:{}", address.ip, port.port),
+                );
+                add_meta(&mut labels, "endpoint_ready", Some(&ready.to_string()));
+                add_meta(&mut labels, "endpoint_port_name", port.name.as_ref());
+                add_meta(
+                    &mut labels,
+                    "endpoint_port_protocol",
+                    port.protocol.as_ref(),
+                );
+                add_meta(&mut labels, "endpoint_hostname", address.hostname.as_ref());
+                add_meta(
+                    &mut labels,
+                    "endpoint_node_name",
+                    address.node_name.as_ref(),
+                );
+                if let Some(target) = &address.target_ref {
+                    add_meta(
+                        &mut labels,
+                        "endpoint_address_target_kind",
+                        target.kind.as_ref(),
+                    );
+                    add_meta(
+                        &mut labels,
+                        "endpoint_address_target_name",
+                        target.name.as_ref(),
+                    );
+                }
+                targets.push(labels);
+            }
+        }
+    }
+    targets
+}
+
+fn add_meta(labels: &mut Labels, name: &str, value: Option<&String>) {
+    if let Some(value) = value {
+        labels.insert(format!("__meta_kubernetes_{}", name), value.clone());
+    }
+}
+
+fn add_object_meta(
+    labels: &mut Labels,
+    kind: &str,
+    object_labels: &Option<BTreeMap<String, String>>,
+    annotations: &Option<BTreeMap<String, String>>,
+) {
+    for (name, value) in object_labels.iter().flatten() {
+        labels.insert(
+            format!("__meta_kubernetes_{}_label_{}", kind, sanitize(name)),
+            value.clone(),
+        );
+    }
+    for (name, value) in annotations.iter().flatten() {
+        labels.insert(
+            format!("__meta_kubernetes_{}_annotation_{}", kind, sanitize(name)),
+            value.clone(),
+        );
+    }
+}
+
+/// Label and annotation names may contain characters label names can't,
+/// such as in `prometheus.io/scrape`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::{
+        api::core::v1::{
+            Container, ContainerPort, EndpointAddress, EndpointPort, EndpointSubset, PodSpec,
+            PodStatus,
+        },
+        apimachinery::pkg::apis::meta::v1::ObjectMeta,
+    };
+    use shared::btreemap;
+
+    fn metadata() -> ObjectMeta {
+        ObjectMeta {
+            name: Some("web-1".to_owned()),
+            namespace: Some("default".to_owned()),
+            labels: Some(btreemap! { "app.kubernetes.io/name" => "web" }),
+            annotations: Some(btreemap! { "prometheus.io/scrape" => "true" }),
+            ..ObjectMeta::default()
+        }
+    }
+
+    #[test]
+    fn discovers_pod_ports() {
+        let pod = Pod {
+            metadata: metadata(),
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "server".to_owned(),
+                    ports: Some(vec![
+                        ContainerPort {
+                            container_port: 8080,
+                            name: Some("http".to_owned()),
+                            ..ContainerPort::default()
+                        },
+                        ContainerPort {
+                            container_port: 9102,
+                            name: Some("metrics".to_owned()),
+                            ..ContainerPort::default()
+                        },
+                    ]),
+                    ..Container::default()
+                }],
+                ..PodSpec::default()
+            }),
+            status: Some(PodStatus {
+                pod_ip: Some("10.0.0.1".to_owned()),
+                ..PodStatus::default()
+            }),
+        };
+
+        let targets = pod_targets(&pod);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1]["__address__"], "10.0.0.1:9102");
+        assert_eq!(
+            targets[1]["__meta_kubernetes_pod_container_port_name"],
+            "metrics"
+        );
+        assert_eq!(targets[1]["__meta_kubernetes_pod_name"], "web-1");
+        assert_eq!(
+            targets[1]["__meta_kubernetes_pod_label_app_kubernetes_io_name"],
+            "web"
+        );
+        assert_eq!(
+            targets[1]["__meta_kubernetes_pod_annotation_prometheus_io_scrape"],
+            "true"
+        );
+    }
+
+    #[test]
+    fn skips_pods_without_ip() {
+        let pod = Pod {
+            metadata: metadata(),
+            ..Pod::default()
+        };
+        assert!(pod_targets(&pod).is_empty());
+    }
+
+    #[test]
+    fn discovers_endpoints() {
+        let address = |ip: &str| EndpointAddress {
+            ip: ip.to_owned(),
+            ..EndpointAddress::default()
+        };
+        let endpoints = Endpoints {
+            metadata: metadata(),
+            subsets: Some(vec![EndpointSubset {
+                addresses: Some(vec![address("10.0.0.1")]),
+                not_ready_addresses: Some(vec![address("10.0.0.2")]),
+                ports: Some(vec![EndpointPort {
+                    port: 9102,
+                    name: Some("metrics".to_owned()),
+                    ..EndpointPort::default()
+                }]),
+            }]),
+        };
+
+        let targets = endpoints_targets(&endpoints);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0]["__address__"], "10.0.0.1:9102");
+        assert_eq!(targets[0]["__meta_kubernetes_endpoint_ready"], "true");
+        assert_eq!(targets[1]["__address__"], "10.0.0.2:9102");
+        assert_eq!(targets[1]["__meta_kubernetes_endpoint_ready"], "false");
+        assert_eq!(targets[1]["__meta_kubernetes_endpoints_name"], "web-1");
+    }
+}
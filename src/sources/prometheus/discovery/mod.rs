@@ -0,0 +1,207 @@
+//! Service discovery of the targets to scrape, which keeps them up to date
+//! with the cluster or the HTTP endpoint they are discovered from.
+
+use crate::{
+    config::ProxyConfig,
+    internal_events::{PrometheusDiscoveryFailed, PrometheusTargetsDiscovered},
+    shutdown::ShutdownSignal,
+};
+use futures::{stream, StreamExt};
+use std::time::Duration;
+use tokio::sync::watch;
+
+mod http_sd;
+mod kubernetes_sd;
+mod relabel;
+
+pub use http_sd::HttpSdConfig;
+pub use kubernetes_sd::KubernetesSdConfig;
+use relabel::{relabel, Labels};
+pub use relabel::{RelabelConfig, RelabelError};
+
+/// A target to scrape, with the labels added to its metrics as tags.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Target {
+    pub url: http::Uri,
+    pub labels: Labels,
+}
+
+impl Target {
+    pub fn new(url: http::Uri) -> Self {
+        Self {
+            url,
+            labels: Labels::new(),
+        }
+    }
+
+    /// Builds the target from its labels once relabeled. The URL comes from
+    /// the `__scheme__`, `__address__`, `__metrics_path__` and `__param_*`
+    /// labels, which are then removed with the other `__` ones.
+    fn from_labels(mut labels: Labels) -> Option<Self> {
+        let address = labels.get("__address__")?.clone();
+        let scheme = labels
+            .get("__scheme__")
+            .map(String::as_str)
+            .unwrap_or("http");
+        let path = labels
+            .get("__metrics_path__")
+            .map(String::as_str)
+            .unwrap_or("/metrics");
+        let params = labels
+            .iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix("__param_")?, value)))
+            .collect::<Vec<_>>();
+        let mut uri = format!("{}://{}{}", scheme, address, path);
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(
+                &url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(params)
+                    .finish(),
+            );
+        }
+        let url = match uri.parse() {
+            Ok(url) => url,
+            Err(error) => {
+                warn!(message = "Skipping target with invalid URL.", url = %uri, %error, internal_log_rate_secs = 60);
+                return None;
+            }
+        };
+
+        labels.entry("instance".to_owned()).or_insert(address);
+        labels.retain(|name, _| !name.starts_with("__"));
+        Some(Self { url, labels })
+    }
+}
+
+pub enum Discoverer {
+    Kubernetes(kubernetes_sd::KubernetesDiscoverer),
+    Http(http_sd::HttpDiscoverer),
+}
+
+impl Discoverer {
+    pub fn kubernetes(config: &KubernetesSdConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        kubernetes_sd::KubernetesDiscoverer::new(config.clone(), proxy).map(Self::Kubernetes)
+    }
+
+    pub fn http(config: &HttpSdConfig, proxy: &ProxyConfig) -> crate::Result<Self> {
+        http_sd::HttpDiscoverer::new(config.clone(), proxy).map(Self::Http)
+    }
+
+    const fn mechanism(&self) -> &'static str {
+        match self {
+            Self::Kubernetes(_) => "kubernetes",
+            Self::Http(_) => "http",
+        }
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(match self {
+            Self::Kubernetes(discoverer) => discoverer.refresh_interval_secs(),
+            Self::Http(discoverer) => discoverer.refresh_interval_secs(),
+        })
+    }
+
+    async fn discover(&mut self) -> crate::Result<Vec<Labels>> {
+        match self {
+            Self::Kubernetes(discoverer) => discoverer.discover().await,
+            Self::Http(discoverer) => discoverer.discover().await,
+        }
+    }
+}
+
+/// Refreshes the targets of each discoverer on its own interval, and
+/// publishes the static ones along with all those discovered. The targets
+/// of a discoverer which fails to refresh are kept as they were.
+pub async fn run(
+    static_targets: Vec<Target>,
+    discoverers: Vec<Discoverer>,
+    relabel_configs: Vec<RelabelConfig>,
+    targets: watch::Sender<Vec<Target>>,
+    shutdown: ShutdownSignal,
+) {
+    let mut discovered = vec![Vec::new(); discoverers.len()];
+
+    let refreshes = discoverers
+        .into_iter()
+        .enumerate()
+        .map(|(index, discoverer)| {
+            let interval = tokio::time::interval(discoverer.refresh_interval());
+            stream::unfold(
+                (discoverer, interval),
+                move |(mut discoverer, mut interval)| async move {
+                    interval.tick().await;
+                    let result = discoverer.discover().await;
+                    let mechanism = discoverer.mechanism();
+                    Some(((index, mechanism, result), (discoverer, interval)))
+                },
+            )
+            .boxed()
+        });
+    let mut refreshes = stream::select_all(refreshes).take_until(shutdown);
+
+    while let Some((index, mechanism, result)) = refreshes.next().await {
+        match result {
+            Ok(labels) => {
+                discovered[index] = labels
+                    .into_iter()
+                    .filter_map(|labels| relabel(&relabel_configs, labels))
+                    .filter_map(Target::from_labels)
+                    .collect::<Vec<_>>();
+                emit!(PrometheusTargetsDiscovered {
+                    mechanism,
+                    count: discovered[index].len(),
+                });
+            }
+            Err(error) => emit!(PrometheusDiscoveryFailed { error, mechanism }),
+        }
+
+        let all = static_targets
+            .iter()
+            .chain(discovered.iter().flatten())
+            .cloned()
+            .collect();
+        if targets.send(all).is_err() {
+            // The scraping stopped.
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::btreemap;
+
+    #[test]
+    fn builds_target_from_labels() {
+        let target = Target::from_labels(btreemap! {
+            "__address__" => "10.0.0.1:9102",
+            "__scheme__" => "https",
+            "__metrics_path__" => "/stats/prometheus",
+            "__param_format" => "text",
+            "__meta_kubernetes_pod_name" => "web-1",
+            "pod" => "web-1",
+        })
+        .unwrap();
+
+        assert_eq!(
+            target.url,
+            "https://10.0.0.1:9102/stats/prometheus?format=text"
+        );
+        assert_eq!(
+            target.labels,
+            btreemap! {
+                "instance" => "10.0.0.1:9102",
+                "pod" => "web-1",
+            }
+        );
+    }
+
+    #[test]
+    fn defaults_target_url() {
+        let target = Target::from_labels(btreemap! { "__address__" => "10.0.0.1:9102" }).unwrap();
+        assert_eq!(target.url, "http://10.0.0.1:9102/metrics");
+        assert!(Target::from_labels(Labels::new()).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+pub type Labels = BTreeMap<String, String>;
+
+#[derive(Debug, Snafu)]
+pub enum RelabelError {
+    #[snafu(display("Relabeling with `{:?}` requires a `target_label`", action))]
+    MissingTargetLabel { action: RelabelAction },
+}
+
+/// A rule rewriting the labels of discovered targets, or filtering the
+/// targets on them, as in Prometheus' `relabel_configs`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RelabelConfig {
+    #[serde(default)]
+    source_labels: Vec<String>,
+    #[serde(default = "default_separator")]
+    separator: String,
+    #[serde(default)]
+    regex: RelabelRegex,
+    target_label: Option<String>,
+    #[serde(default = "default_replacement")]
+    replacement: String,
+    #[serde(default)]
+    action: RelabelAction,
+}
+
+fn default_separator() -> String {
+    ";".to_owned()
+}
+
+fn default_replacement() -> String {
+    "$1".to_owned()
+}
+
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, Serialize, Eq, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RelabelAction {
+    #[derivative(Default)]
+    Replace,
+    Keep,
+    Drop,
+    LabelMap,
+    LabelDrop,
+    LabelKeep,
+}
+
+/// A regular expression which has to match whole values.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RelabelRegex {
+    source: String,
+    regex: Regex,
+}
+
+impl Default for RelabelRegex {
+    fn default() -> Self {
+        Self::try_from("(.*)".to_owned()).expect("The default regex is valid")
+    }
+}
+
+impl TryFrom<String> for RelabelRegex {
+    type Error = regex::Error;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        let regex = Regex::new(&format!("^(?:{})$", source))?;
+        Ok(Self { source, regex })
+    }
+}
+
+impl From<RelabelRegex> for String {
+    fn from(regex: RelabelRegex) -> Self {
+        regex.source
+    }
+}
+
+impl RelabelConfig {
+    pub fn validate(&self) -> Result<(), RelabelError> {
+        match (self.action, &self.target_label) {
+            (RelabelAction::Replace, None) => Err(RelabelError::MissingTargetLabel {
+                action: self.action,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies the rule to the labels, returning `false` if the target is
+    /// to be dropped.
+    fn apply(&self, labels: &mut Labels) -> bool {
+        match self.action {
+            RelabelAction::Replace => {
+                let value = self.source_value(labels);
+                if let Some(captures) = self.regex.regex.captures(&value) {
+                    let mut replaced = String::new();
+                    captures.expand(&self.replacement, &mut replaced);
+                    let target_label = self
+                        .target_label
+                        .as_ref()
+                        .expect("Relabel configs are validated");
+                    if replaced.is_empty() {
+                        labels.remove(target_label);
+                    } else {
+                        labels.insert(target_label.clone(), replaced);
+                    }
+                }
+                true
+            }
+            RelabelAction::Keep => self.regex.regex.is_match(&self.source_value(labels)),
+            RelabelAction::Drop => !self.regex.regex.is_match(&self.source_value(labels)),
+            RelabelAction::LabelMap => {
+                let mapped = labels
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        let captures = self.regex.regex.captures(name)?;
+                        let mut mapped = String::new();
+                        captures.expand(&self.replacement, &mut mapped);
+                        Some((mapped, value.clone()))
+                    })
+                    .collect::<Vec<_>>();
+                labels.extend(mapped);
+                true
+            }
+            RelabelAction::LabelDrop => {
+                labels.retain(|name, _| !self.regex.regex.is_match(name));
+                true
+            }
+            RelabelAction::LabelKeep => {
+                labels.retain(|name, _| self.regex.regex.is_match(name));
+                true
+            }
+        }
+    }
+
+    /// Missing labels count as empty values.
+    fn source_value(&self, labels: &Labels) -> String {
+        self.source_labels
+            .iter()
+            .map(|name| labels.get(name).map(String::as_str).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+    }
+}
+
+/// Applies the rules in order, returning `None` if the target is dropped.
+pub fn relabel(configs: &[RelabelConfig], mut labels: Labels) -> Option<Labels> {
+    configs
+        .iter()
+        .all(|config| config.apply(&mut labels))
+        .then(|| labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::btreemap;
+
+    fn parse(config: &str) -> Vec<RelabelConfig> {
+        #[derive(Deserialize)]
+        struct Configs {
+            relabel_configs: Vec<RelabelConfig>,
+        }
+        toml::from_str::<Configs>(config).unwrap().relabel_configs
+    }
+
+    fn pod_labels() -> Labels {
+        btreemap! {
+            "__address__" => "10.0.0.1:8080",
+            "__meta_kubernetes_namespace" => "default",
+            "__meta_kubernetes_pod_name" => "web-1",
+            "__meta_kubernetes_pod_annotation_prometheus_io_scrape" => "true",
+            "__meta_kubernetes_pod_annotation_prometheus_io_port" => "9102",
+            "__meta_kubernetes_pod_label_app" => "web",
+        }
+    }
+
+    #[test]
+    fn keeps_and_drops() {
+        let configs = parse(
+            r#"
+            [[relabel_configs]]
+            source_labels = ["__meta_kubernetes_pod_annotation_prometheus_io_scrape"]
+            action = "keep"
+            regex = "true"
+            "#,
+        );
+        assert!(relabel(&configs, pod_labels()).is_some());
+
+        let configs = parse(
+            r#"
+            [[relabel_configs]]
+            source_labels = ["__meta_kubernetes_namespace"]
+            action = "drop"
+            regex = "kube-.*"
+            "#,
+        );
+        assert!(relabel(&configs, pod_labels()).is_some());
+        let mut labels = pod_labels();
+        labels.insert(
+            "__meta_kubernetes_namespace".to_owned(),
+            "kube-system".to_owned(),
+        );
+        assert!(relabel(&configs, labels).is_none());
+    }
+
+    #[test]
+    fn replaces() {
+        let configs = parse(
+            r#"
+            [[relabel_configs]]
+            source_labels = ["__address__", "__meta_kubernetes_pod_annotation_prometheus_io_port"]
+            regex = "([^:]+)(?::\\d+)?;(\\d+)"
+            replacement = "$1:$2"
+            target_label = "__address__"
+
+            [[relabel_configs]]
+            source_labels = ["__meta_kubernetes_pod_name"]
+            target_label = "pod"
+            "#,
+        );
+        let labels = relabel(&configs, pod_labels()).unwrap();
+        assert_eq!(labels["__address__"], "10.0.0.1:9102");
+        assert_eq!(labels["pod"], "web-1");
+    }
+
+    #[test]
+    fn maps_and_drops_labels() {
+        let configs = parse(
+            r#"
+            [[relabel_configs]]
+            action = "labelmap"
+            regex = "__meta_kubernetes_pod_label_(.+)"
+
+            [[relabel_configs]]
+            action = "labeldrop"
+            regex = "__meta_kubernetes_pod_annotation_.*"
+            "#,
+        );
+        let labels = relabel(&configs, pod_labels()).unwrap();
+        assert_eq!(labels["app"], "web");
+        assert!(!labels
+            .keys()
+            .any(|name| name.starts_with("__meta_kubernetes_pod_annotation_")));
+    }
+
+    #[test]
+    fn replace_requires_target_label() {
+        let configs = parse(
+            r#"
+            [[relabel_configs]]
+            source_labels = ["__meta_kubernetes_pod_name"]
+            "#,
+        );
+        assert!(configs[0].validate().is_err());
+    }
+}
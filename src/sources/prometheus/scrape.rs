@@ -1,4 +1,9 @@
-use super::parser;
+use super::{
+    discovery::{
+        self, Discoverer, HttpSdConfig, KubernetesSdConfig, RelabelConfig, RelabelError, Target,
+    },
+    parser,
+};
 use crate::{
     config::{self, GenerateConfig, ProxyConfig, SourceConfig, SourceContext, SourceDescription},
     http::Auth,
@@ -12,7 +17,7 @@ use crate::{
     tls::{TlsOptions, TlsSettings},
     Pipeline,
 };
-use futures::{stream, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use futures::{future, stream, FutureExt, SinkExt, StreamExt, TryFutureExt};
 use hyper::{Body, Request};
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
@@ -20,23 +25,35 @@ use std::{
     future::ready,
     time::{Duration, Instant},
 };
+use tokio::sync::watch;
 use tokio_stream::wrappers::IntervalStream;
 
 #[derive(Debug, Snafu)]
 enum ConfigError {
     #[snafu(display("Cannot set both `endpoints` and `hosts`"))]
     BothEndpointsAndHosts,
+    #[snafu(display("One of `endpoints`, `kubernetes_sd` or `http_sd` is required"))]
+    NoTargets,
+    #[snafu(display("Invalid relabel config: {}", source))]
+    InvalidRelabelConfig { source: RelabelError },
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 struct PrometheusScrapeConfig {
     // Deprecated name
-    #[serde(alias = "hosts")]
+    #[serde(alias = "hosts", default)]
     endpoints: Vec<String>,
     #[serde(default = "default_scrape_interval_secs")]
     scrape_interval_secs: u64,
     tls: Option<TlsOptions>,
     auth: Option<Auth>,
+    #[serde(default)]
+    kubernetes_sd: Vec<KubernetesSdConfig>,
+    #[serde(default)]
+    http_sd: Vec<HttpSdConfig>,
+    /// Applied to the discovered targets only
+    #[serde(default)]
+    relabel_configs: Vec<RelabelConfig>,
 }
 
 pub fn default_scrape_interval_secs() -> u64 {
@@ -58,6 +75,9 @@ impl GenerateConfig for PrometheusScrapeConfig {
             scrape_interval_secs: default_scrape_interval_secs(),
             tls: None,
             auth: None,
+            kubernetes_sd: Vec::new(),
+            http_sd: Vec::new(),
+            relabel_configs: Vec::new(),
         })
         .unwrap()
     }
@@ -67,20 +87,51 @@ impl GenerateConfig for PrometheusScrapeConfig {
 #[typetag::serde(name = "prometheus_scrape")]
 impl SourceConfig for PrometheusScrapeConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<sources::Source> {
-        let urls = self
+        if self.endpoints.is_empty() && self.kubernetes_sd.is_empty() && self.http_sd.is_empty() {
+            return Err(ConfigError::NoTargets.into());
+        }
+        for config in &self.relabel_configs {
+            config.validate().context(InvalidRelabelConfig)?;
+        }
+
+        let static_targets = self
             .endpoints
             .iter()
             .map(|s| s.parse::<http::Uri>().context(sources::UriParseError))
-            .collect::<Result<Vec<http::Uri>, sources::BuildError>>()?;
+            .map(|url| url.map(Target::new))
+            .collect::<Result<Vec<_>, sources::BuildError>>()?;
+        let discoverers = self
+            .kubernetes_sd
+            .iter()
+            .map(|config| Discoverer::kubernetes(config, &cx.proxy))
+            .chain(
+                self.http_sd
+                    .iter()
+                    .map(|config| Discoverer::http(config, &cx.proxy)),
+            )
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let (sender, targets) = watch::channel(static_targets.clone());
+        let discovery = discovery::run(
+            static_targets,
+            discoverers,
+            self.relabel_configs.clone(),
+            sender,
+            cx.shutdown.clone(),
+        );
+
         let tls = TlsSettings::from_options(&self.tls)?;
-        Ok(prometheus(
-            urls,
+        let scrape = prometheus(
+            targets,
             tls,
             self.auth.clone(),
             cx.proxy.clone(),
             self.scrape_interval_secs,
             cx.shutdown,
             cx.out,
+        );
+        Ok(Box::pin(
+            future::join(discovery, scrape).map(|(_, result)| result),
         ))
     }
 
@@ -99,12 +150,18 @@ impl SourceConfig for PrometheusScrapeConfig {
 struct PrometheusCompatConfig {
     // Clone of PrometheusScrapeConfig to work around serde bug
     // https://github.com/serde-rs/serde/issues/1504
-    #[serde(alias = "hosts")]
+    #[serde(alias = "hosts", default)]
     endpoints: Vec<String>,
     #[serde(default = "default_scrape_interval_secs")]
     scrape_interval_secs: u64,
     tls: Option<TlsOptions>,
     auth: Option<Auth>,
+    #[serde(default)]
+    kubernetes_sd: Vec<KubernetesSdConfig>,
+    #[serde(default)]
+    http_sd: Vec<HttpSdConfig>,
+    #[serde(default)]
+    relabel_configs: Vec<RelabelConfig>,
 }
 
 #[async_trait::async_trait]
@@ -118,6 +175,9 @@ impl SourceConfig for PrometheusCompatConfig {
             scrape_interval_secs: self.scrape_interval_secs,
             tls: self.tls.clone(),
             auth: self.auth.clone(),
+            kubernetes_sd: self.kubernetes_sd.clone(),
+            http_sd: self.http_sd.clone(),
+            relabel_configs: self.relabel_configs.clone(),
         };
         config.build(cx).await
     }
@@ -132,7 +192,7 @@ impl SourceConfig for PrometheusCompatConfig {
 }
 
 fn prometheus(
-    urls: Vec<http::Uri>,
+    targets: watch::Receiver<Vec<Target>>,
     tls: TlsSettings,
     auth: Option<Auth>,
     proxy: ProxyConfig,
@@ -144,9 +204,9 @@ fn prometheus(
 
     Box::pin(IntervalStream::new(tokio::time::interval(Duration::from_secs(interval)))
         .take_until(shutdown)
-        .map(move |_| stream::iter(urls.clone()))
+        .map(move |_| stream::iter(targets.borrow().clone()))
         .flatten()
-        .map(move |url| {
+        .map(move |Target { url, labels }| {
             let client = HttpClient::new(tls.clone(), &proxy).expect("Building HTTP client failed");
 
             let mut request = Request::get(&url)
@@ -184,7 +244,20 @@ fn prometheus(
                                         count: metrics.len(),
                                         uri: url.clone()
                                     });
-                                    Some(stream::iter(metrics).map(Ok))
+                                    let metrics = metrics
+                                        .into_iter()
+                                        .map(|mut event| {
+                                            // The metrics' own tags take precedence.
+                                            let metric = event.as_mut_metric();
+                                            for (name, value) in &labels {
+                                                metric
+                                                    .tag_entry(name.clone())
+                                                    .or_insert_with(|| value.clone());
+                                            }
+                                            Ok(event)
+                                        })
+                                        .collect::<Vec<_>>();
+                                    Some(stream::iter(metrics))
                                 }
                                 Err(error) => {
                                     if url.path() == "/" {
@@ -308,6 +381,9 @@ mod test {
                 scrape_interval_secs: 1,
                 tls: None,
                 auth: None,
+                kubernetes_sd: Vec::new(),
+                http_sd: Vec::new(),
+                relabel_configs: Vec::new(),
             },
         );
         config.add_sink(
@@ -389,6 +465,9 @@ mod integration_tests {
             scrape_interval_secs: 1,
             auth: None,
             tls: None,
+            kubernetes_sd: Vec::new(),
+            http_sd: Vec::new(),
+            relabel_configs: Vec::new(),
         };
 
         let (tx, rx) = Pipeline::new_test();
@@ -0,0 +1,291 @@
+use crate::{
+    config::{log_schema, DataType, SourceConfig, SourceContext, SourceDescription},
+    event::{BatchNotifier, BatchStatus, Event, LogEvent, Value},
+    internal_events::{PulsarAcknowledgementFailed, PulsarEventFailed, PulsarEventReceived},
+    pulsar::AuthConfig,
+    shutdown::ShutdownSignal,
+    tls::TlsOptions,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{stream::FuturesUnordered, FutureExt, SinkExt, StreamExt};
+use pulsar::{consumer::Message, Consumer, SubType, TokioExecutor};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Could not load TLS certificate chain: {}", source))]
+    PulsarTlsError { source: std::io::Error },
+    #[snafu(display("Could not create Pulsar consumer: {}", source))]
+    PulsarCreateError { source: pulsar::Error },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PulsarSourceConfig {
+    endpoint: String,
+    topics: Vec<String>,
+    #[serde(default = "default_subscription_name")]
+    subscription_name: String,
+    #[serde(default)]
+    subscription_type: SubscriptionType,
+    consumer_name: Option<String>,
+    auth: Option<AuthConfig>,
+    tls: Option<TlsOptions>,
+    #[serde(default = "default_key_field")]
+    key_field: String,
+    #[serde(default = "default_topic_key")]
+    topic_key: String,
+    #[serde(default = "default_properties_key")]
+    properties_key: String,
+}
+
+/// How messages of the topics are shared between the consumers of the
+/// subscription.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionType {
+    /// Only one consumer may be attached to the subscription.
+    Exclusive,
+    /// Messages are distributed round robin across the consumers.
+    Shared,
+    /// All messages go to one consumer, with the others taking over if it
+    /// disconnects.
+    Failover,
+}
+
+impl Default for SubscriptionType {
+    fn default() -> Self {
+        Self::Exclusive
+    }
+}
+
+impl From<SubscriptionType> for SubType {
+    fn from(subscription_type: SubscriptionType) -> Self {
+        match subscription_type {
+            SubscriptionType::Exclusive => SubType::Exclusive,
+            SubscriptionType::Shared => SubType::Shared,
+            SubscriptionType::Failover => SubType::Failover,
+        }
+    }
+}
+
+fn default_subscription_name() -> String {
+    "vector".into()
+}
+
+fn default_key_field() -> String {
+    "message_key".into()
+}
+
+fn default_topic_key() -> String {
+    "topic".into()
+}
+
+fn default_properties_key() -> String {
+    "properties".into()
+}
+
+inventory::submit! {
+    SourceDescription::new::<PulsarSourceConfig>("pulsar")
+}
+
+impl_generate_config_from_default!(PulsarSourceConfig);
+
+impl Default for PulsarSourceConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "pulsar://127.0.0.1:6650".into(),
+            topics: vec!["topic-1234".into()],
+            subscription_name: default_subscription_name(),
+            subscription_type: SubscriptionType::default(),
+            consumer_name: None,
+            auth: None,
+            tls: None,
+            key_field: default_key_field(),
+            topic_key: default_topic_key(),
+            properties_key: default_properties_key(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "pulsar")]
+impl SourceConfig for PulsarSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let consumer = self.create_consumer().await?;
+
+        Ok(Box::pin(pulsar_source(
+            consumer,
+            self.key_field.clone(),
+            self.topic_key.clone(),
+            self.properties_key.clone(),
+            cx.shutdown,
+            cx.out,
+            cx.acknowledgements,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "pulsar"
+    }
+}
+
+impl PulsarSourceConfig {
+    async fn create_consumer(&self) -> crate::Result<Consumer<Vec<u8>, TokioExecutor>> {
+        let builder = crate::pulsar::builder(&self.endpoint, &self.auth);
+        let builder = crate::pulsar::with_tls(builder, &self.tls).context(PulsarTlsError)?;
+        let pulsar = builder.build().await.context(PulsarCreateError)?;
+
+        let mut consumer = pulsar
+            .consumer()
+            .with_topics(&self.topics)
+            .with_subscription_type(self.subscription_type.into())
+            .with_subscription(&self.subscription_name);
+        if let Some(consumer_name) = &self.consumer_name {
+            consumer = consumer.with_consumer_name(consumer_name);
+        }
+
+        Ok(consumer.build().await.context(PulsarCreateError)?)
+    }
+}
+
+async fn pulsar_source(
+    mut consumer: Consumer<Vec<u8>, TokioExecutor>,
+    key_field: String,
+    topic_key: String,
+    properties_key: String,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+    acknowledgements: bool,
+) -> Result<(), ()> {
+    // Messages waiting for their events to be delivered, which are
+    // acknowledged or negatively acknowledged, and so redelivered, depending
+    // on the status of their batch.
+    let mut pending = FuturesUnordered::new();
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            Some((status, msg)) = pending.next(), if !pending.is_empty() => {
+                finalize(&mut consumer, status, &msg).await
+            }
+            message = consumer.next() => match message {
+                None => break,
+                Some(Err(error)) => emit!(PulsarEventFailed { error }),
+                Some(Ok(msg)) => {
+                    emit!(PulsarEventReceived {
+                        byte_size: msg.payload.data.len()
+                    });
+
+                    let log = create_log(&msg, &key_field, &topic_key, &properties_key);
+                    if acknowledgements {
+                        let (batch, receiver) = BatchNotifier::new_with_receiver();
+                        let log = log.with_batch_notifier(&batch);
+                        match out.send(Event::from(log)).await {
+                            Err(error) => error!(message = "Error sending to sink.", %error),
+                            Ok(_) => pending.push(receiver.map(move |status| (status, msg))),
+                        }
+                    } else {
+                        match out.send(Event::from(log)).await {
+                            Err(error) => error!(message = "Error sending to sink.", %error),
+                            Ok(_) => finalize(&mut consumer, BatchStatus::Delivered, &msg).await,
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    // Settle the messages already sent on before stopping, so they are only
+    // redelivered if their events were not.
+    while let Some((status, msg)) = pending.next().await {
+        finalize(&mut consumer, status, &msg).await;
+    }
+
+    Ok(())
+}
+
+fn create_log(
+    msg: &Message<Vec<u8>>,
+    key_field: &str,
+    topic_key: &str,
+    properties_key: &str,
+) -> LogEvent {
+    let mut log = LogEvent::default();
+
+    log.insert(
+        log_schema().message_key(),
+        Value::from(Bytes::from(msg.payload.data.clone())),
+    );
+
+    // Pulsar publish times are in milliseconds since the epoch.
+    let timestamp = Utc
+        .timestamp_millis_opt(msg.payload.metadata.publish_time as i64)
+        .latest()
+        .unwrap_or_else(Utc::now);
+    log.insert(log_schema().timestamp_key(), timestamp);
+
+    log.insert(log_schema().source_type_key(), Bytes::from("pulsar"));
+
+    log.insert(key_field, msg.key().map(Value::from).unwrap_or(Value::Null));
+
+    log.insert(topic_key, Value::from(msg.topic.clone()));
+
+    let properties = msg
+        .payload
+        .metadata
+        .properties
+        .iter()
+        .map(|property| (property.key.clone(), Value::from(property.value.clone())))
+        .collect::<BTreeMap<_, _>>();
+    log.insert(properties_key, Value::from(properties));
+
+    log
+}
+
+async fn finalize(
+    consumer: &mut Consumer<Vec<u8>, TokioExecutor>,
+    status: BatchStatus,
+    msg: &Message<Vec<u8>>,
+) {
+    let result = match status {
+        BatchStatus::Delivered => consumer.ack(msg).await,
+        BatchStatus::Errored | BatchStatus::Failed => consumer.nack(msg).await,
+    };
+    if let Err(error) = result {
+        emit!(PulsarAcknowledgementFailed { error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<PulsarSourceConfig>();
+    }
+
+    #[test]
+    fn parses_subscription_type() {
+        let config: PulsarSourceConfig = toml::from_str(
+            r#"
+            endpoint = "pulsar://127.0.0.1:6650"
+            topics = ["logs"]
+            subscription_type = "failover"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.subscription_type, SubscriptionType::Failover);
+        assert_eq!(config.subscription_name, "vector");
+    }
+}
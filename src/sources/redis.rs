@@ -0,0 +1,470 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, SourceConfig, SourceContext, SourceDescription,
+    },
+    event::{BatchNotifier, BatchStatus, Event, Value},
+    internal_events::{RedisAcknowledgementFailed, RedisEventReceived, RedisReceiveEventFailed},
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{stream, stream::FuturesUnordered, FutureExt, SinkExt, StreamExt};
+use redis::{
+    aio::ConnectionManager,
+    streams::{StreamId, StreamPendingCountReply, StreamReadOptions, StreamReadReply},
+    AsyncCommands, RedisResult,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Copy, Clone, Debug, Derivative, Deserialize, Serialize, PartialEq)]
+#[derivative(Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DataTypeConfig {
+    #[derivative(Default)]
+    Stream,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedisSourceConfig {
+    url: String,
+    key: String,
+    #[serde(default)]
+    data_type: DataTypeConfig,
+    stream: StreamOption,
+    /// The field the ID of the entry is added to
+    #[serde(default = "default_id_key")]
+    id_key: String,
+}
+
+/// Read the stream as a member of a consumer group. Entries are acknowledged
+/// once their events are delivered, and are otherwise left pending, to be read
+/// again when Vector restarts or claimed by another consumer of the group.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamOption {
+    group: String,
+    /// Defaults to the hostname
+    consumer: Option<String>,
+    /// Where a group created by Vector starts reading
+    #[serde(default = "default_start_id")]
+    start_id: String,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    #[serde(default = "default_block_ms")]
+    block_ms: usize,
+    /// Entries pending for other consumers for this long are claimed
+    claim_min_idle_ms: Option<u64>,
+}
+
+fn default_id_key() -> String {
+    "id".to_owned()
+}
+
+fn default_start_id() -> String {
+    "$".to_owned()
+}
+
+const fn default_batch_size() -> usize {
+    100
+}
+
+const fn default_block_ms() -> usize {
+    1000
+}
+
+inventory::submit! {
+    SourceDescription::new::<RedisSourceConfig>("redis")
+}
+
+impl GenerateConfig for RedisSourceConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"
+            url = "redis://127.0.0.1:6379/0"
+            key = "vector"
+            data_type = "stream"
+            stream.group = "vector"
+            "#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "redis")]
+impl SourceConfig for RedisSourceConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.key.is_empty() {
+            return Err("`key` cannot be empty.".into());
+        }
+        let client = redis::Client::open(self.url.as_str())?;
+        let mut conn = client.get_tokio_connection_manager().await?;
+
+        let consumer = match &self.stream.consumer {
+            Some(consumer) => consumer.clone(),
+            None => crate::get_hostname()?,
+        };
+        create_group(&mut conn, &self.key, &self.stream).await?;
+
+        let reader = StreamReader {
+            conn,
+            key: self.key.clone(),
+            group: self.stream.group.clone(),
+            consumer,
+            batch_size: self.stream.batch_size,
+            block_ms: self.stream.block_ms,
+            claim_min_idle: self.stream.claim_min_idle_ms.map(Duration::from_millis),
+            id_key: self.id_key.clone(),
+        };
+        Ok(Box::pin(reader.run(
+            cx.shutdown,
+            cx.out,
+            cx.acknowledgements,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "redis"
+    }
+}
+
+/// Creates the group, and the stream if needed, unless it already exists.
+async fn create_group(
+    conn: &mut ConnectionManager,
+    key: &str,
+    stream: &StreamOption,
+) -> RedisResult<()> {
+    let result: RedisResult<()> = conn
+        .xgroup_create_mkstream(key, &stream.group, &stream.start_id)
+        .await;
+    match result {
+        Err(error) if error.code() == Some("BUSYGROUP") => Ok(()),
+        result => result,
+    }
+}
+
+struct StreamReader {
+    conn: ConnectionManager,
+    key: String,
+    group: String,
+    consumer: String,
+    batch_size: usize,
+    block_ms: usize,
+    claim_min_idle: Option<Duration>,
+    id_key: String,
+}
+
+impl StreamReader {
+    async fn run(
+        mut self,
+        mut shutdown: ShutdownSignal,
+        mut out: Pipeline,
+        acknowledgements: bool,
+    ) -> Result<(), ()> {
+        // Entries delivered to this consumer before it last stopped are read
+        // first, paging through them from the start.
+        let mut pending_from = Some("0".to_owned());
+        let mut next_claim = Instant::now();
+        // Entries whose events are still being delivered.
+        let mut pending = FuturesUnordered::new();
+
+        loop {
+            if let Some(min_idle) = self.claim_min_idle {
+                if next_claim <= Instant::now() {
+                    next_claim = Instant::now() + min_idle;
+                    match self.claim(min_idle).await {
+                        Ok(entries) => {
+                            if let Some(ack) = self.send(entries, &mut out, acknowledgements).await
+                            {
+                                pending.push(ack);
+                            }
+                        }
+                        Err(error) => emit!(RedisReceiveEventFailed { error }),
+                    }
+                }
+            }
+
+            let id = pending_from.as_deref().unwrap_or(">").to_owned();
+            let entries = tokio::select! {
+                _ = &mut shutdown => break,
+                Some((status, ids)) = pending.next(), if !pending.is_empty() => {
+                    self.acknowledge(status, ids).await;
+                    continue;
+                }
+                entries = self.read(&id) => entries,
+            };
+
+            match entries {
+                Ok(entries) => {
+                    if pending_from.is_some() {
+                        pending_from = entries.last().map(|entry| entry.id.clone());
+                    }
+                    if let Some(ack) = self.send(entries, &mut out, acknowledgements).await {
+                        pending.push(ack);
+                    }
+                }
+                Err(error) => {
+                    emit!(RedisReceiveEventFailed { error });
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => {},
+                    }
+                }
+            }
+        }
+
+        while let Some((status, ids)) = pending.next().await {
+            self.acknowledge(status, ids).await;
+        }
+
+        Ok(())
+    }
+
+    async fn read(&mut self, id: &str) -> RedisResult<Vec<StreamId>> {
+        let options = StreamReadOptions::default()
+            .group(&self.group, &self.consumer)
+            .count(self.batch_size)
+            .block(self.block_ms);
+        let reply: StreamReadReply = self
+            .conn
+            .xread_options(&[&self.key], &[id], &options)
+            .await?;
+        Ok(reply
+            .keys
+            .into_iter()
+            .flat_map(|stream| stream.ids)
+            .collect())
+    }
+
+    /// Takes over the entries left pending by other consumers for too long,
+    /// such as those which stopped for good.
+    async fn claim(&mut self, min_idle: Duration) -> RedisResult<Vec<StreamId>> {
+        let reply: StreamPendingCountReply = self
+            .conn
+            .xpending_count(&self.key, &self.group, "-", "+", self.batch_size)
+            .await?;
+        let ids = reply
+            .ids
+            .into_iter()
+            .filter(|entry| {
+                entry.consumer != self.consumer
+                    && entry.last_delivered_ms as u128 >= min_idle.as_millis()
+            })
+            .map(|entry| entry.id)
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let reply: redis::streams::StreamClaimReply = self
+            .conn
+            .xclaim(
+                &self.key,
+                &self.group,
+                &self.consumer,
+                min_idle.as_millis() as usize,
+                &ids,
+            )
+            .await?;
+        Ok(reply.ids)
+    }
+
+    /// Sends the events of the entries, returning what to await before
+    /// acknowledging them when that depends on their delivery.
+    async fn send(
+        &mut self,
+        entries: Vec<StreamId>,
+        out: &mut Pipeline,
+        acknowledgements: bool,
+    ) -> Option<impl std::future::Future<Output = (BatchStatus, Vec<String>)>> {
+        if entries.is_empty() {
+            return None;
+        }
+
+        let ids = entries
+            .iter()
+            .map(|entry| entry.id.clone())
+            .collect::<Vec<_>>();
+        let (batch, receiver) = if acknowledgements {
+            let (batch, receiver) = BatchNotifier::new_with_receiver();
+            (Some(batch), Some(receiver))
+        } else {
+            (None, None)
+        };
+
+        let id_key = &self.id_key;
+        let mut events = stream::iter(entries).map(|entry| {
+            let event = create_event(entry, id_key);
+            Ok(match &batch {
+                Some(batch) => event.with_batch_notifier(batch),
+                None => event,
+            })
+        });
+        let result = out.send_all(&mut events).await;
+        drop(batch);
+
+        match (result, receiver) {
+            (Err(error), _) => {
+                error!(message = "Error sending to sink.", %error);
+                None
+            }
+            (Ok(()), Some(receiver)) => {
+                Some(receiver.map(move |status| (status, ids)).left_future())
+            }
+            (Ok(()), None) => {
+                Some(futures::future::ready((BatchStatus::Delivered, ids)).right_future())
+            }
+        }
+    }
+
+    /// Entries whose events weren't delivered are left pending.
+    async fn acknowledge(&mut self, status: BatchStatus, ids: Vec<String>) {
+        if status != BatchStatus::Delivered {
+            return;
+        }
+        let result: RedisResult<usize> = self.conn.xack(&self.key, &self.group, &ids).await;
+        if let Err(error) = result {
+            emit!(RedisAcknowledgementFailed { error });
+        }
+    }
+}
+
+fn create_event(entry: StreamId, id_key: &str) -> Event {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+
+    let mut byte_size = 0;
+    for (field, value) in entry.map {
+        let value = match value {
+            redis::Value::Data(data) => {
+                byte_size += data.len();
+                Value::from(Bytes::from(data))
+            }
+            redis::Value::Int(int) => Value::from(int),
+            redis::Value::Status(status) => Value::from(status),
+            _ => continue,
+        };
+        log.insert_flat(field, value);
+    }
+    emit!(RedisEventReceived { byte_size });
+
+    log.insert_flat(id_key, entry.id);
+    log.insert(log_schema().timestamp_key(), Utc::now());
+    log.insert(log_schema().source_type_key(), Bytes::from("redis"));
+
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<RedisSourceConfig>();
+    }
+
+    #[test]
+    fn parses_stream_options() {
+        let config: RedisSourceConfig = toml::from_str(
+            r#"
+            url = "redis://127.0.0.1:6379/0"
+            key = "vector"
+            stream.group = "vector"
+            stream.consumer = "vector-0"
+            stream.claim_min_idle_ms = 60000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.data_type, DataTypeConfig::Stream);
+        assert_eq!(config.stream.consumer.as_deref(), Some("vector-0"));
+        assert_eq!(config.stream.start_id, "$");
+        assert_eq!(config.stream.batch_size, 100);
+        assert_eq!(config.stream.claim_min_idle_ms, Some(60000));
+        assert_eq!(config.id_key, "id");
+    }
+
+    #[test]
+    fn creates_event_from_entry() {
+        let mut map = HashMap::new();
+        map.insert("message".to_owned(), redis::Value::Data(b"hello".to_vec()));
+        map.insert("count".to_owned(), redis::Value::Int(3));
+        let entry = StreamId {
+            id: "1-0".to_owned(),
+            map,
+        };
+
+        let event = create_event(entry, "id");
+        let log = event.as_log();
+        assert_eq!(log["message"], "hello".into());
+        assert_eq!(log["count"], 3.into());
+        assert_eq!(log["id"], "1-0".into());
+        assert_eq!(log[log_schema().source_type_key()], "redis".into());
+    }
+}
+
+#[cfg(feature = "redis-integration-tests")]
+#[cfg(test)]
+mod integration_tests {
+    use super::*;
+    use crate::test_util::{collect_n, random_string};
+
+    const REDIS_SERVER: &str = "redis://127.0.0.1:6379/0";
+
+    fn config(key: &str) -> RedisSourceConfig {
+        toml::from_str(&format!(
+            r#"
+            url = "{}"
+            key = "{}"
+            stream.group = "vector"
+            stream.consumer = "vector-0"
+            stream.start_id = "0"
+            "#,
+            REDIS_SERVER, key
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn redis_source_stream_acknowledges() {
+        let key = format!("test-stream-{}", random_string(10));
+        let client = redis::Client::open(REDIS_SERVER).unwrap();
+        let mut conn = client.get_tokio_connection_manager().await.unwrap();
+        for i in 0..10 {
+            let _: String = conn
+                .xadd(&key, "*", &[("message", format!("line {}", i))])
+                .await
+                .unwrap();
+        }
+
+        let (tx, rx) = Pipeline::new_test();
+        let source = config(&key)
+            .build(SourceContext::new_test(tx))
+            .await
+            .unwrap();
+        tokio::spawn(source);
+
+        let events = collect_n(rx, 10).await;
+        for (i, event) in events.iter().enumerate() {
+            assert_eq!(event.as_log()["message"], format!("line {}", i).into());
+        }
+
+        // Nothing is left pending once acknowledged
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let reply: StreamPendingCountReply = conn
+            .xpending_count(&key, "vector", "-", "+", 100)
+            .await
+            .unwrap();
+        assert!(reply.ids.is_empty());
+    }
+}
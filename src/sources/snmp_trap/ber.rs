@@ -0,0 +1,234 @@
+//! The subset of the Basic Encoding Rules that SNMP messages use: definite
+//! lengths and single byte tags only.
+use snafu::Snafu;
+
+pub(super) const INTEGER: u8 = 0x02;
+pub(super) const OCTET_STRING: u8 = 0x04;
+pub(super) const NULL: u8 = 0x05;
+pub(super) const OBJECT_IDENTIFIER: u8 = 0x06;
+pub(super) const SEQUENCE: u8 = 0x30;
+
+pub(super) const IP_ADDRESS: u8 = 0x40;
+pub(super) const COUNTER32: u8 = 0x41;
+pub(super) const GAUGE32: u8 = 0x42;
+pub(super) const TIMETICKS: u8 = 0x43;
+pub(super) const OPAQUE: u8 = 0x44;
+pub(super) const COUNTER64: u8 = 0x46;
+
+pub(super) const NO_SUCH_OBJECT: u8 = 0x80;
+pub(super) const NO_SUCH_INSTANCE: u8 = 0x81;
+pub(super) const END_OF_MIB_VIEW: u8 = 0x82;
+
+#[derive(Debug, PartialEq, Snafu)]
+pub enum BerError {
+    #[snafu(display("Unexpected end of data"))]
+    Truncated,
+    #[snafu(display("Expected tag {:#04x}, found {:#04x}", expected, found))]
+    UnexpectedTag { expected: u8, found: u8 },
+    #[snafu(display("Unsupported length encoding"))]
+    UnsupportedLength,
+    #[snafu(display("Integer does not fit in 64 bits"))]
+    IntegerOverflow,
+    #[snafu(display("Invalid object identifier"))]
+    InvalidOid,
+}
+
+/// Reads the elements of a constructed value one after another.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(super) const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Reads the next element, whatever its tag.
+    pub(super) fn any(&mut self) -> Result<(u8, &'a [u8]), BerError> {
+        let (&tag, rest) = self.data.split_first().ok_or(BerError::Truncated)?;
+        let (&first, mut rest) = rest.split_first().ok_or(BerError::Truncated)?;
+        let len = if first & 0x80 == 0 {
+            usize::from(first)
+        } else {
+            let count = usize::from(first & 0x7F);
+            // Zero is the indefinite length, which SNMP doesn't allow.
+            if count == 0 || count > 4 || rest.len() < count {
+                return Err(BerError::UnsupportedLength);
+            }
+            let (bytes, tail) = rest.split_at(count);
+            rest = tail;
+            bytes
+                .iter()
+                .fold(0, |len, &byte| (len << 8) | usize::from(byte))
+        };
+        if rest.len() < len {
+            return Err(BerError::Truncated);
+        }
+        let (value, tail) = rest.split_at(len);
+        self.data = tail;
+        Ok((tag, value))
+    }
+
+    pub(super) fn expect(&mut self, expected: u8) -> Result<&'a [u8], BerError> {
+        match self.any()? {
+            (tag, value) if tag == expected => Ok(value),
+            (found, _) => Err(BerError::UnexpectedTag { expected, found }),
+        }
+    }
+
+    pub(super) fn sequence(&mut self) -> Result<Reader<'a>, BerError> {
+        self.expect(SEQUENCE).map(Reader::new)
+    }
+
+    pub(super) fn integer(&mut self) -> Result<i64, BerError> {
+        self.expect(INTEGER).and_then(integer)
+    }
+
+    pub(super) fn octet_string(&mut self) -> Result<&'a [u8], BerError> {
+        self.expect(OCTET_STRING)
+    }
+
+    pub(super) fn oid(&mut self) -> Result<Vec<u32>, BerError> {
+        self.expect(OBJECT_IDENTIFIER).and_then(oid)
+    }
+}
+
+pub(super) fn integer(value: &[u8]) -> Result<i64, BerError> {
+    if value.len() > 8 {
+        return Err(BerError::IntegerOverflow);
+    }
+    let sign = match value.first() {
+        Some(byte) if byte & 0x80 != 0 => -1,
+        _ => 0,
+    };
+    Ok(value
+        .iter()
+        .fold(sign, |integer, &byte| (integer << 8) | i64::from(byte)))
+}
+
+/// Unsigned application types are encoded as integers, so the largest values
+/// take a leading zero byte.
+pub(super) fn unsigned(value: &[u8]) -> Result<u64, BerError> {
+    let value = match value {
+        [0, rest @ ..] => rest,
+        value => value,
+    };
+    if value.len() > 8 {
+        return Err(BerError::IntegerOverflow);
+    }
+    Ok(value
+        .iter()
+        .fold(0, |integer, &byte| (integer << 8) | u64::from(byte)))
+}
+
+pub(super) fn oid(value: &[u8]) -> Result<Vec<u32>, BerError> {
+    let mut subids = Vec::with_capacity(value.len() + 1);
+    let mut subid: u32 = 0;
+    for &byte in value {
+        if subid > u32::MAX >> 7 {
+            return Err(BerError::InvalidOid);
+        }
+        subid = (subid << 7) | u32::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            if subids.is_empty() {
+                // The first two arcs share the first subidentifier.
+                let first = (subid / 40).min(2);
+                subids.push(first);
+                subids.push(subid - first * 40);
+            } else {
+                subids.push(subid);
+            }
+            subid = 0;
+        }
+    }
+    if subids.is_empty() || value.last().map_or(false, |byte| byte & 0x80 != 0) {
+        return Err(BerError::InvalidOid);
+    }
+    Ok(subids)
+}
+
+/// Encodes one element, for the responses to informs.
+pub(super) fn encode(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(value.len() + 6);
+    encoded.push(tag);
+    if value.len() < 0x80 {
+        encoded.push(value.len() as u8);
+    } else {
+        let len = (value.len() as u32).to_be_bytes();
+        let skip = len.iter().take_while(|&&byte| byte == 0).count();
+        encoded.push(0x80 | (len.len() - skip) as u8);
+        encoded.extend_from_slice(&len[skip..]);
+    }
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+pub(super) fn encode_integer(integer: i64) -> Vec<u8> {
+    let bytes = integer.to_be_bytes();
+    // Drop the leading bytes that only repeat the sign of the next one.
+    let skip = (0..bytes.len() - 1)
+        .take_while(|&i| {
+            (bytes[i] == 0 && bytes[i + 1] & 0x80 == 0)
+                || (bytes[i] == 0xFF && bytes[i + 1] & 0x80 != 0)
+        })
+        .count();
+    encode(INTEGER, &bytes[skip..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_long_lengths() {
+        let value = vec![7; 300];
+        let encoded = encode(OCTET_STRING, &value);
+        assert_eq!(&encoded[..4], &[OCTET_STRING, 0x82, 0x01, 0x2C]);
+
+        let mut reader = Reader::new(&encoded);
+        assert_eq!(reader.octet_string().unwrap(), &value[..]);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let mut reader = Reader::new(&[OCTET_STRING, 5, 1, 2]);
+        assert_eq!(reader.any(), Err(BerError::Truncated));
+    }
+
+    #[test]
+    fn integers_round_trip() {
+        for &integer in &[0, 1, 127, 128, 256, -1, -128, -129, i64::MAX, i64::MIN] {
+            let encoded = encode_integer(integer);
+            assert_eq!(Reader::new(&encoded).integer(), Ok(integer), "{}", integer);
+        }
+        assert_eq!(encode_integer(128), vec![INTEGER, 2, 0, 128]);
+    }
+
+    #[test]
+    fn reads_unsigned() {
+        assert_eq!(
+            unsigned(&[0, 0xFF, 0xFF, 0xFF, 0xFF]),
+            Ok(u64::from(u32::MAX))
+        );
+        assert_eq!(unsigned(&[0x01, 0x00]), Ok(256));
+    }
+
+    #[test]
+    fn reads_oids() {
+        assert_eq!(
+            oid(&[0x2B, 6, 1, 6, 3, 1, 1, 4, 1, 0]),
+            Ok(vec![1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0])
+        );
+        assert_eq!(
+            oid(&[0x2B, 6, 1, 4, 1, 0x82, 0x37, 1]),
+            Ok(vec![1, 3, 6, 1, 4, 1, 311, 1])
+        );
+        assert_eq!(oid(&[0x2B, 0x86]), Err(BerError::InvalidOid));
+    }
+}
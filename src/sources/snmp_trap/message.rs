@@ -0,0 +1,704 @@
+//! Decoding of the traps and informs of SNMP v1, v2c and v3 messages into
+//! log events.
+use super::{
+    ber::{self, BerError, Reader},
+    mib::{format_oid, Mibs},
+    usm::{SecurityParameters, Users, UsmError},
+};
+use crate::event::{LogEvent, Value};
+use snafu::{ResultExt, Snafu};
+use std::{collections::BTreeMap, convert::TryFrom, net::Ipv4Addr};
+
+const VERSION_1: i64 = 0;
+const VERSION_2C: i64 = 1;
+const VERSION_3: i64 = 3;
+
+const USM_SECURITY_MODEL: i64 = 3;
+
+const RESPONSE_PDU: u8 = 0xA2;
+const TRAP_V1_PDU: u8 = 0xA4;
+const INFORM_PDU: u8 = 0xA6;
+const TRAP_V2_PDU: u8 = 0xA7;
+
+const SYS_UP_TIME: &[u32] = &[1, 3, 6, 1, 2, 1, 1, 3, 0];
+const SNMP_TRAP_OID: &[u32] = &[1, 3, 6, 1, 6, 3, 1, 1, 4, 1, 0];
+/// The parent of the notifications the generic traps of SNMPv1 map to.
+const SNMP_TRAPS: &[u32] = &[1, 3, 6, 1, 6, 3, 1, 1, 5];
+const ENTERPRISE_SPECIFIC: i64 = 6;
+
+#[derive(Debug, Snafu)]
+pub enum DecodeError {
+    #[snafu(display("Malformed message: {}", source))]
+    Malformed { source: BerError },
+    #[snafu(display("Unsupported SNMP version {}", version))]
+    UnsupportedVersion { version: i64 },
+    #[snafu(display("Unknown community {:?}", community))]
+    UnknownCommunity { community: String },
+    #[snafu(display("Unsupported security model {}", model))]
+    UnsupportedSecurityModel { model: i64 },
+    #[snafu(display("{}", source))]
+    Security { source: UsmError },
+    #[snafu(display("Unsupported PDU type {:#04x}", tag))]
+    UnsupportedPdu { tag: u8 },
+    #[snafu(display("Missing the snmpTrapOID.0 variable"))]
+    MissingTrapOid,
+}
+
+impl From<BerError> for DecodeError {
+    fn from(source: BerError) -> Self {
+        Self::Malformed { source }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Decoded {
+    pub(super) log: LogEvent,
+    /// The response acknowledging an inform, to send back to its sender.
+    pub(super) response: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub(super) struct Decoder {
+    /// The communities v1 and v2c messages must have, if any.
+    pub(super) communities: Vec<String>,
+    pub(super) users: Users,
+    pub(super) mibs: Mibs,
+}
+
+impl Decoder {
+    pub(super) fn decode(&self, packet: &[u8]) -> Result<Decoded, DecodeError> {
+        let mut message = Reader::new(packet).sequence()?;
+        let mut log = LogEvent::default();
+        let response = match message.integer()? {
+            version @ VERSION_1 | version @ VERSION_2C => {
+                let community = message.octet_string()?;
+                let community = String::from_utf8_lossy(community).into_owned();
+                if !self.communities.is_empty() && !self.communities.contains(&community) {
+                    return Err(DecodeError::UnknownCommunity { community });
+                }
+
+                let (tag, pdu) = message.any()?;
+                log.insert("version", if version == VERSION_1 { "1" } else { "2c" });
+                let inform = match (version, tag) {
+                    (VERSION_1, TRAP_V1_PDU) => {
+                        self.trap_v1(pdu, &mut log)?;
+                        None
+                    }
+                    (VERSION_2C, TRAP_V2_PDU) | (VERSION_2C, INFORM_PDU) => {
+                        self.trap_v2(tag, pdu, &mut log)?
+                    }
+                    (_, tag) => return Err(DecodeError::UnsupportedPdu { tag }),
+                };
+                let response = inform.map(|(request_id, variables)| {
+                    response(version, community.as_bytes(), request_id, variables)
+                });
+                log.insert("community", community);
+                response
+            }
+            VERSION_3 => {
+                self.message_v3(packet, message, &mut log)?;
+                None
+            }
+            version => return Err(DecodeError::UnsupportedVersion { version }),
+        };
+        Ok(Decoded { log, response })
+    }
+
+    fn message_v3(
+        &self,
+        packet: &[u8],
+        mut message: Reader<'_>,
+        log: &mut LogEvent,
+    ) -> Result<(), DecodeError> {
+        let mut global = message.sequence()?;
+        let _id = global.integer()?;
+        let _max_size = global.integer()?;
+        let flags = global.octet_string()?.first().copied().unwrap_or(0);
+        match global.integer()? {
+            USM_SECURITY_MODEL => (),
+            model => return Err(DecodeError::UnsupportedSecurityModel { model }),
+        }
+
+        let mut usm = Reader::new(message.octet_string()?).sequence()?;
+        let parameters = SecurityParameters {
+            engine_id: usm.octet_string()?,
+            engine_boots: unsigned_integer(&mut usm)?,
+            engine_time: unsigned_integer(&mut usm)?,
+            user_name: usm.octet_string()?,
+            auth: usm.octet_string()?,
+            privacy: usm.octet_string()?,
+        };
+
+        let encrypted = flags & 0x02 != 0;
+        let data = message.expect(if encrypted {
+            ber::OCTET_STRING
+        } else {
+            ber::SEQUENCE
+        })?;
+        let decrypted = self
+            .users
+            .process(packet, flags, &parameters, data)
+            .context(Security)?;
+        let mut scoped = match &decrypted {
+            // Encryption pads the scoped PDU, so only read its first element.
+            Some(decrypted) => Reader::new(decrypted).sequence()?,
+            None => Reader::new(data),
+        };
+        let _context_engine_id = scoped.octet_string()?;
+        let context_name = scoped.octet_string()?;
+
+        let (tag, pdu) = scoped.any()?;
+        log.insert("version", "3");
+        match tag {
+            // Acknowledging informs needs an engine of our own, so they are
+            // only read.
+            TRAP_V2_PDU | INFORM_PDU => self.trap_v2(tag, pdu, log)?,
+            tag => return Err(DecodeError::UnsupportedPdu { tag }),
+        };
+        log.insert(
+            "user",
+            String::from_utf8_lossy(parameters.user_name).into_owned(),
+        );
+        log.insert("engine_id", hex(parameters.engine_id));
+        if !context_name.is_empty() {
+            log.insert(
+                "context_name",
+                String::from_utf8_lossy(context_name).into_owned(),
+            );
+        }
+        Ok(())
+    }
+
+    fn trap_v1(&self, pdu: &[u8], log: &mut LogEvent) -> Result<(), DecodeError> {
+        let mut pdu = Reader::new(pdu);
+        let enterprise = pdu.oid()?;
+        let agent_address = match *pdu.expect(ber::IP_ADDRESS)? {
+            [a, b, c, d] => Ipv4Addr::new(a, b, c, d),
+            _ => return Err(BerError::Truncated.into()),
+        };
+        let generic_trap = pdu.integer()?;
+        let specific_trap = pdu.integer()?;
+        let uptime = ber::unsigned(pdu.expect(ber::TIMETICKS)?)?;
+        let variables = self.variables(pdu.sequence()?)?;
+
+        // The notification of SNMPv2 the trap maps to, as in RFC 3584.
+        let trap_oid = match u32::try_from(generic_trap) {
+            Ok(generic_trap) if i64::from(generic_trap) < ENTERPRISE_SPECIFIC => {
+                [SNMP_TRAPS, &[generic_trap + 1]].concat()
+            }
+            _ => [
+                &enterprise[..],
+                &[0, u32::try_from(specific_trap).unwrap_or(0)],
+            ]
+            .concat(),
+        };
+
+        log.insert("pdu_type", "trap");
+        self.insert_trap(log, &trap_oid);
+        log.insert("uptime", uptime as i64);
+        log.insert("enterprise", self.mibs.name(&enterprise));
+        log.insert("agent_address", agent_address.to_string());
+        log.insert("generic_trap", generic_trap);
+        log.insert("specific_trap", specific_trap);
+        log.insert("variables", variables);
+        Ok(())
+    }
+
+    /// Reads the trap or inform, and returns the request ID and variable
+    /// bindings of an inform to acknowledge it.
+    fn trap_v2<'a>(
+        &self,
+        tag: u8,
+        pdu: &'a [u8],
+        log: &mut LogEvent,
+    ) -> Result<Option<(i64, &'a [u8])>, DecodeError> {
+        let mut pdu = Reader::new(pdu);
+        let request_id = pdu.integer()?;
+        let _error_status = pdu.integer()?;
+        let _error_index = pdu.integer()?;
+        let bindings = pdu.expect(ber::SEQUENCE)?;
+
+        // The first two variables are the uptime and the notification, which
+        // are fields of their own.
+        let mut uptime = None;
+        let mut trap_oid = None;
+        let mut rest = Reader::new(bindings);
+        while !rest.is_empty() && trap_oid.is_none() {
+            let mut peek = rest;
+            let mut binding = peek.sequence()?;
+            match &binding.oid()?[..] {
+                SYS_UP_TIME => uptime = Some(ber::unsigned(binding.expect(ber::TIMETICKS)?)?),
+                SNMP_TRAP_OID => trap_oid = Some(binding.oid()?),
+                _ => break,
+            }
+            rest = peek;
+        }
+        let trap_oid = trap_oid.ok_or(DecodeError::MissingTrapOid)?;
+        let variables = self.variables(rest)?;
+
+        log.insert(
+            "pdu_type",
+            if tag == INFORM_PDU { "inform" } else { "trap" },
+        );
+        self.insert_trap(log, &trap_oid);
+        if let Some(uptime) = uptime {
+            log.insert("uptime", uptime as i64);
+        }
+        log.insert("variables", variables);
+        Ok((tag == INFORM_PDU).then(|| (request_id, bindings)))
+    }
+
+    fn insert_trap(&self, log: &mut LogEvent, oid: &[u32]) {
+        log.insert("trap", self.mibs.name(oid));
+        log.insert("trap_oid", format_oid(oid));
+    }
+
+    fn variables(&self, mut bindings: Reader<'_>) -> Result<Vec<Value>, DecodeError> {
+        let mut variables = Vec::new();
+        while !bindings.is_empty() {
+            let mut binding = bindings.sequence()?;
+            let oid = binding.oid()?;
+            let (tag, value) = binding.any()?;
+            let (r#type, value) = self.value(tag, value)?;
+
+            let mut variable = BTreeMap::new();
+            variable.insert("oid".to_owned(), Value::from(format_oid(&oid)));
+            variable.insert("name".to_owned(), Value::from(self.mibs.name(&oid)));
+            variable.insert("type".to_owned(), Value::from(r#type));
+            variable.insert("value".to_owned(), value);
+            variables.push(Value::from(variable));
+        }
+        Ok(variables)
+    }
+
+    fn value(&self, tag: u8, value: &[u8]) -> Result<(&'static str, Value), DecodeError> {
+        Ok(match tag {
+            ber::INTEGER => ("integer", Value::from(ber::integer(value)?)),
+            ber::OCTET_STRING => ("octet_string", Value::from(octet_string(value))),
+            ber::NULL => ("null", Value::Null),
+            ber::OBJECT_IDENTIFIER => (
+                "object_identifier",
+                Value::from(self.mibs.name(&ber::oid(value)?)),
+            ),
+            ber::IP_ADDRESS => match *value {
+                [a, b, c, d] => (
+                    "ip_address",
+                    Value::from(Ipv4Addr::new(a, b, c, d).to_string()),
+                ),
+                _ => ("ip_address", Value::from(hex(value))),
+            },
+            ber::COUNTER32 => ("counter32", unsigned_value(value)?),
+            ber::GAUGE32 => ("gauge32", unsigned_value(value)?),
+            ber::TIMETICKS => ("timeticks", unsigned_value(value)?),
+            ber::OPAQUE => ("opaque", Value::from(hex(value))),
+            ber::COUNTER64 => ("counter64", unsigned_value(value)?),
+            ber::NO_SUCH_OBJECT => ("no_such_object", Value::Null),
+            ber::NO_SUCH_INSTANCE => ("no_such_instance", Value::Null),
+            ber::END_OF_MIB_VIEW => ("end_of_mib_view", Value::Null),
+            _ => ("unknown", Value::from(hex(value))),
+        })
+    }
+}
+
+fn unsigned_integer(reader: &mut Reader<'_>) -> Result<u32, DecodeError> {
+    u32::try_from(reader.integer()?).map_err(|_| BerError::IntegerOverflow.into())
+}
+
+/// Counter64 values past the largest integer are kept whole as strings.
+fn unsigned_value(value: &[u8]) -> Result<Value, DecodeError> {
+    let value = ber::unsigned(value)?;
+    Ok(i64::try_from(value).map_or_else(|_| Value::from(value.to_string()), Value::from))
+}
+
+/// Octet strings are text more often than not, but hold binary values such as
+/// MAC addresses too.
+fn octet_string(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => text.to_owned(),
+        _ => hex(value),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn response(version: i64, community: &[u8], request_id: i64, variables: &[u8]) -> Vec<u8> {
+    let pdu = [
+        ber::encode_integer(request_id),
+        ber::encode_integer(0),
+        ber::encode_integer(0),
+        ber::encode(ber::SEQUENCE, variables),
+    ]
+    .concat();
+    let message = [
+        ber::encode_integer(version),
+        ber::encode(ber::OCTET_STRING, community),
+        ber::encode(RESPONSE_PDU, &pdu),
+    ]
+    .concat();
+    ber::encode(ber::SEQUENCE, &message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{
+        hash::MessageDigest,
+        pkey::PKey,
+        sign::Signer,
+        symm::{encrypt, Cipher},
+    };
+
+    fn decoder() -> Decoder {
+        Decoder {
+            communities: Vec::new(),
+            users: Users::default(),
+            mibs: Mibs::load(&[]).unwrap(),
+        }
+    }
+
+    fn encode_oid(oid: &[u32]) -> Vec<u8> {
+        let mut value = vec![(oid[0] * 40 + oid[1]) as u8];
+        for &subid in &oid[2..] {
+            let mut bytes = vec![(subid & 0x7F) as u8];
+            let mut rest = subid >> 7;
+            while rest > 0 {
+                bytes.push(0x80 | (rest & 0x7F) as u8);
+                rest >>= 7;
+            }
+            value.extend(bytes.iter().rev());
+        }
+        ber::encode(ber::OBJECT_IDENTIFIER, &value)
+    }
+
+    fn binding(oid: &[u32], value: Vec<u8>) -> Vec<u8> {
+        ber::encode(ber::SEQUENCE, &[encode_oid(oid), value].concat())
+    }
+
+    /// The variable bindings of a linkDown notification of the third
+    /// interface.
+    fn link_down_bindings() -> Vec<u8> {
+        [
+            binding(SYS_UP_TIME, ber::encode(ber::TIMETICKS, &[0x01, 0x00])),
+            binding(SNMP_TRAP_OID, encode_oid(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 3])),
+            binding(&[1, 3, 6, 1, 2, 1, 2, 2, 1, 1, 3], ber::encode_integer(3)),
+            binding(
+                &[1, 3, 6, 1, 2, 1, 2, 2, 1, 2, 3],
+                ber::encode(ber::OCTET_STRING, b"eth0"),
+            ),
+            binding(
+                &[1, 3, 6, 1, 2, 1, 2, 2, 1, 6, 3],
+                ber::encode(ber::OCTET_STRING, &[0x00, 0x1b, 0x21, 0x0a, 0x0b, 0x0c]),
+            ),
+        ]
+        .concat()
+    }
+
+    fn pdu(tag: u8, request_id: i64, bindings: &[u8]) -> Vec<u8> {
+        ber::encode(
+            tag,
+            &[
+                ber::encode_integer(request_id),
+                ber::encode_integer(0),
+                ber::encode_integer(0),
+                ber::encode(ber::SEQUENCE, bindings),
+            ]
+            .concat(),
+        )
+    }
+
+    fn community_message(version: i64, community: &[u8], pdu: Vec<u8>) -> Vec<u8> {
+        ber::encode(
+            ber::SEQUENCE,
+            &[
+                ber::encode_integer(version),
+                ber::encode(ber::OCTET_STRING, community),
+                pdu,
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn decodes_v2c_traps() {
+        let packet = community_message(
+            VERSION_2C,
+            b"public",
+            pdu(TRAP_V2_PDU, 7, &link_down_bindings()),
+        );
+
+        let Decoded { log, response } = decoder().decode(&packet).unwrap();
+
+        assert!(response.is_none());
+        assert_eq!(log["version"], Value::from("2c"));
+        assert_eq!(log["community"], Value::from("public"));
+        assert_eq!(log["pdu_type"], Value::from("trap"));
+        assert_eq!(log["trap"], Value::from("IF-MIB::linkDown"));
+        assert_eq!(log["trap_oid"], Value::from("1.3.6.1.6.3.1.1.5.3"));
+        assert_eq!(log["uptime"], Value::from(256));
+        assert_eq!(
+            log["variables[0].oid"],
+            Value::from("1.3.6.1.2.1.2.2.1.1.3")
+        );
+        assert_eq!(
+            log["variables[0].name"],
+            Value::from("SNMPv2-SMI::mib-2.2.2.1.1.3")
+        );
+        assert_eq!(log["variables[0].type"], Value::from("integer"));
+        assert_eq!(log["variables[0].value"], Value::from(3));
+        assert_eq!(log["variables[1].value"], Value::from("eth0"));
+        assert_eq!(log["variables[2].value"], Value::from("00:1b:21:0a:0b:0c"));
+    }
+
+    #[test]
+    fn acknowledges_v2c_informs() {
+        let bindings = link_down_bindings();
+        let packet = community_message(VERSION_2C, b"public", pdu(INFORM_PDU, 7, &bindings));
+
+        let Decoded { log, response } = decoder().decode(&packet).unwrap();
+
+        assert_eq!(log["pdu_type"], Value::from("inform"));
+        assert_eq!(
+            response.unwrap(),
+            community_message(VERSION_2C, b"public", pdu(RESPONSE_PDU, 7, &bindings))
+        );
+    }
+
+    #[test]
+    fn decodes_v1_traps() {
+        let pdu = ber::encode(
+            TRAP_V1_PDU,
+            &[
+                encode_oid(&[1, 3, 6, 1, 4, 1, 99999]),
+                ber::encode(ber::IP_ADDRESS, &[192, 0, 2, 1]),
+                ber::encode_integer(6),
+                ber::encode_integer(17),
+                ber::encode(ber::TIMETICKS, &[0x00, 0xFF, 0xFF, 0xFF, 0xFF]),
+                ber::encode(
+                    ber::SEQUENCE,
+                    &binding(
+                        &[1, 3, 6, 1, 4, 1, 99999, 1, 0],
+                        ber::encode(
+                            ber::COUNTER64,
+                            &[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+                        ),
+                    ),
+                ),
+            ]
+            .concat(),
+        );
+        let packet = community_message(VERSION_1, b"public", pdu);
+
+        let Decoded { log, .. } = decoder().decode(&packet).unwrap();
+
+        assert_eq!(log["version"], Value::from("1"));
+        assert_eq!(log["trap_oid"], Value::from("1.3.6.1.4.1.99999.0.17"));
+        assert_eq!(
+            log["trap"],
+            Value::from("SNMPv2-SMI::enterprises.99999.0.17")
+        );
+        assert_eq!(
+            log["enterprise"],
+            Value::from("SNMPv2-SMI::enterprises.99999")
+        );
+        assert_eq!(log["agent_address"], Value::from("192.0.2.1"));
+        assert_eq!(log["uptime"], Value::from(u32::MAX));
+        assert_eq!(log["variables[0].type"], Value::from("counter64"));
+        assert_eq!(log["variables[0].value"], Value::from(u64::MAX.to_string()));
+    }
+
+    #[test]
+    fn maps_v1_generic_traps() {
+        let pdu = ber::encode(
+            TRAP_V1_PDU,
+            &[
+                encode_oid(&[1, 3, 6, 1, 4, 1, 99999]),
+                ber::encode(ber::IP_ADDRESS, &[192, 0, 2, 1]),
+                ber::encode_integer(0),
+                ber::encode_integer(0),
+                ber::encode(ber::TIMETICKS, &[0]),
+                ber::encode(ber::SEQUENCE, &[]),
+            ]
+            .concat(),
+        );
+        let packet = community_message(VERSION_1, b"public", pdu);
+
+        let Decoded { log, .. } = decoder().decode(&packet).unwrap();
+
+        assert_eq!(log["trap"], Value::from("SNMPv2-MIB::coldStart"));
+    }
+
+    #[test]
+    fn rejects_unknown_communities() {
+        let mut decoder = decoder();
+        decoder.communities = vec!["private".to_owned()];
+        let packet = community_message(
+            VERSION_2C,
+            b"public",
+            pdu(TRAP_V2_PDU, 7, &link_down_bindings()),
+        );
+
+        assert!(matches!(
+            decoder.decode(&packet),
+            Err(DecodeError::UnknownCommunity { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_traps_without_notification() {
+        let packet = community_message(
+            VERSION_2C,
+            b"public",
+            pdu(
+                TRAP_V2_PDU,
+                7,
+                &binding(SYS_UP_TIME, ber::encode(ber::TIMETICKS, &[1])),
+            ),
+        );
+
+        assert!(matches!(
+            decoder().decode(&packet),
+            Err(DecodeError::MissingTrapOid)
+        ));
+    }
+
+    const ENGINE_ID: &[u8] = &[0x80, 0x00, 0x1f, 0x88, 0x04, 0x74, 0x65, 0x73, 0x74];
+    const SENTINEL: [u8; 12] = [0xA5; 12];
+
+    fn key(digest: MessageDigest, passphrase: &str) -> Vec<u8> {
+        let expanded = passphrase
+            .bytes()
+            .cycle()
+            .take(1_048_576)
+            .collect::<Vec<_>>();
+        let key = openssl::hash::hash(digest, &expanded).unwrap();
+        openssl::hash::hash(digest, &[&key[..], ENGINE_ID, &key].concat())
+            .unwrap()
+            .to_vec()
+    }
+
+    /// An SNMPv3 trap authenticated with HMAC-SHA-96 and encrypted with
+    /// AES-128, as a sender would encode it.
+    fn v3_message(user: &[u8], auth_passphrase: &str, privacy_passphrase: &str) -> Vec<u8> {
+        let (boots, time, salt) = (5_u32, 1234_u32, [1, 2, 3, 4, 5, 6, 7, 8]);
+        let scoped = ber::encode(
+            ber::SEQUENCE,
+            &[
+                ber::encode(ber::OCTET_STRING, ENGINE_ID),
+                ber::encode(ber::OCTET_STRING, b"edge"),
+                pdu(TRAP_V2_PDU, 7, &link_down_bindings()),
+            ]
+            .concat(),
+        );
+        let iv = [&boots.to_be_bytes()[..], &time.to_be_bytes(), &salt].concat();
+        let privacy_key = key(MessageDigest::sha1(), privacy_passphrase);
+        let encrypted = encrypt(
+            Cipher::aes_128_cfb128(),
+            &privacy_key[..16],
+            Some(&iv),
+            &scoped,
+        )
+        .unwrap();
+
+        let usm = ber::encode(
+            ber::SEQUENCE,
+            &[
+                ber::encode(ber::OCTET_STRING, ENGINE_ID),
+                ber::encode_integer(boots.into()),
+                ber::encode_integer(time.into()),
+                ber::encode(ber::OCTET_STRING, user),
+                ber::encode(ber::OCTET_STRING, &SENTINEL),
+                ber::encode(ber::OCTET_STRING, &salt),
+            ]
+            .concat(),
+        );
+        let global = ber::encode(
+            ber::SEQUENCE,
+            &[
+                ber::encode_integer(1),
+                ber::encode_integer(65507),
+                ber::encode(ber::OCTET_STRING, &[0x03]),
+                ber::encode_integer(USM_SECURITY_MODEL),
+            ]
+            .concat(),
+        );
+        let mut packet = ber::encode(
+            ber::SEQUENCE,
+            &[
+                ber::encode_integer(VERSION_3),
+                global,
+                ber::encode(ber::OCTET_STRING, &usm),
+                ber::encode(ber::OCTET_STRING, &encrypted),
+            ]
+            .concat(),
+        );
+
+        let offset = packet
+            .windows(SENTINEL.len())
+            .position(|window| window == SENTINEL)
+            .unwrap();
+        packet[offset..offset + SENTINEL.len()].copy_from_slice(&[0; 12]);
+        let auth_key = PKey::hmac(&key(MessageDigest::sha1(), auth_passphrase)).unwrap();
+        let mut signer = Signer::new(MessageDigest::sha1(), &auth_key).unwrap();
+        signer.update(&packet).unwrap();
+        let mac = signer.sign_to_vec().unwrap();
+        packet[offset..offset + SENTINEL.len()].copy_from_slice(&mac[..12]);
+        packet
+    }
+
+    fn v3_decoder() -> Decoder {
+        let user = toml::from_str(
+            r#"
+            name = "operator"
+            auth = { protocol = "sha", passphrase = "authpassphrase" }
+            privacy = { protocol = "aes", passphrase = "privpassphrase" }
+            "#,
+        )
+        .unwrap();
+        Decoder {
+            users: Users::new(&[user]).unwrap(),
+            ..decoder()
+        }
+    }
+
+    #[test]
+    fn decodes_v3_traps() {
+        let packet = v3_message(b"operator", "authpassphrase", "privpassphrase");
+
+        let Decoded { log, response } = v3_decoder().decode(&packet).unwrap();
+
+        assert!(response.is_none());
+        assert_eq!(log["version"], Value::from("3"));
+        assert_eq!(log["user"], Value::from("operator"));
+        assert_eq!(log["engine_id"], Value::from("80:00:1f:88:04:74:65:73:74"));
+        assert_eq!(log["context_name"], Value::from("edge"));
+        assert_eq!(log["trap"], Value::from("IF-MIB::linkDown"));
+        assert_eq!(log["variables[1].value"], Value::from("eth0"));
+    }
+
+    #[test]
+    fn rejects_v3_traps_failing_authentication() {
+        let decoder = v3_decoder();
+
+        let packet = v3_message(b"operator", "wrongpassphrase", "privpassphrase");
+        assert!(matches!(
+            decoder.decode(&packet),
+            Err(DecodeError::Security {
+                source: UsmError::AuthenticationFailed { .. }
+            })
+        ));
+
+        let packet = v3_message(b"intruder", "authpassphrase", "privpassphrase");
+        assert!(matches!(
+            decoder.decode(&packet),
+            Err(DecodeError::Security {
+                source: UsmError::UnknownUser { .. }
+            })
+        ));
+    }
+}
@@ -0,0 +1,452 @@
+//! Resolution of object identifiers to the names MIB modules give them.
+//!
+//! Only the assignments of object identifiers are read from the modules, so
+//! this is far from a full SMI parser, but it needs nothing else to name the
+//! notifications and objects of traps.
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The roots of every registration tree, and the standard traps, which are
+/// named even without any MIB configured.
+const BUILTIN: &str = r#"
+SNMPv2-SMI DEFINITIONS ::= BEGIN
+org OBJECT IDENTIFIER ::= { iso 3 }
+dod OBJECT IDENTIFIER ::= { org 6 }
+internet OBJECT IDENTIFIER ::= { dod 1 }
+directory OBJECT IDENTIFIER ::= { internet 1 }
+mgmt OBJECT IDENTIFIER ::= { internet 2 }
+mib-2 OBJECT IDENTIFIER ::= { mgmt 1 }
+transmission OBJECT IDENTIFIER ::= { mib-2 10 }
+experimental OBJECT IDENTIFIER ::= { internet 3 }
+private OBJECT IDENTIFIER ::= { internet 4 }
+enterprises OBJECT IDENTIFIER ::= { private 1 }
+security OBJECT IDENTIFIER ::= { internet 5 }
+snmpV2 OBJECT IDENTIFIER ::= { internet 6 }
+snmpDomains OBJECT IDENTIFIER ::= { snmpV2 1 }
+snmpProxys OBJECT IDENTIFIER ::= { snmpV2 2 }
+snmpModules OBJECT IDENTIFIER ::= { snmpV2 3 }
+END
+
+SNMPv2-MIB DEFINITIONS ::= BEGIN
+system OBJECT IDENTIFIER ::= { mib-2 1 }
+sysDescr OBJECT-TYPE ::= { system 1 }
+sysObjectID OBJECT-TYPE ::= { system 2 }
+sysUpTime OBJECT-TYPE ::= { system 3 }
+sysContact OBJECT-TYPE ::= { system 4 }
+sysName OBJECT-TYPE ::= { system 5 }
+sysLocation OBJECT-TYPE ::= { system 6 }
+snmpMIB MODULE-IDENTITY ::= { snmpModules 1 }
+snmpMIBObjects OBJECT IDENTIFIER ::= { snmpMIB 1 }
+snmpTrap OBJECT IDENTIFIER ::= { snmpMIBObjects 4 }
+snmpTrapOID OBJECT-TYPE ::= { snmpTrap 1 }
+snmpTrapEnterprise OBJECT-TYPE ::= { snmpTrap 3 }
+snmpTraps OBJECT IDENTIFIER ::= { snmpMIBObjects 5 }
+coldStart NOTIFICATION-TYPE ::= { snmpTraps 1 }
+warmStart NOTIFICATION-TYPE ::= { snmpTraps 2 }
+authenticationFailure NOTIFICATION-TYPE ::= { snmpTraps 5 }
+END
+
+IF-MIB DEFINITIONS ::= BEGIN
+linkDown NOTIFICATION-TYPE ::= { snmpTraps 3 }
+linkUp NOTIFICATION-TYPE ::= { snmpTraps 4 }
+END
+"#;
+
+/// The macros whose values are object identifiers.
+const OID_MACROS: &[&str] = &[
+    "AGENT-CAPABILITIES",
+    "MODULE-COMPLIANCE",
+    "MODULE-IDENTITY",
+    "NOTIFICATION-GROUP",
+    "NOTIFICATION-TYPE",
+    "OBJECT-GROUP",
+    "OBJECT-IDENTITY",
+    "OBJECT-TYPE",
+];
+
+#[derive(Debug, Snafu)]
+pub enum MibError {
+    #[snafu(display("Could not read MIB {:?}: {}", path, source))]
+    Read { path: PathBuf, source: io::Error },
+}
+
+/// An assignment `name ::= { parent sub-identifiers.. }`, whose parent may be
+/// defined in any module.
+#[derive(Debug)]
+struct Assignment {
+    module: String,
+    name: String,
+    parent: String,
+    subids: Vec<u32>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Mibs {
+    names: HashMap<Vec<u32>, String>,
+}
+
+impl Mibs {
+    /// Loads the MIB files at the paths, or in them for directories.
+    pub(super) fn load(paths: &[PathBuf]) -> Result<Self, MibError> {
+        let mut assignments = Vec::new();
+        parse(BUILTIN, &mut assignments);
+        for path in paths {
+            for file in files(path)? {
+                let bytes = fs::read(&file).context(Read { path: &file })?;
+                parse(&String::from_utf8_lossy(&bytes), &mut assignments);
+            }
+        }
+        Ok(Self::from_assignments(assignments))
+    }
+
+    fn from_assignments(mut assignments: Vec<Assignment>) -> Self {
+        let mut oids = HashMap::new();
+        oids.insert("ccitt".to_owned(), vec![0]);
+        oids.insert("iso".to_owned(), vec![1]);
+        oids.insert("joint-iso-ccitt".to_owned(), vec![2]);
+
+        let mut names = HashMap::new();
+        names.insert(vec![0], "SNMPv2-SMI::ccitt".to_owned());
+        names.insert(vec![1], "SNMPv2-SMI::iso".to_owned());
+        names.insert(vec![2], "SNMPv2-SMI::joint-iso-ccitt".to_owned());
+
+        // Modules are in no particular order, so resolve whatever assignments
+        // have a known parent until no more do.
+        loop {
+            let before = assignments.len();
+            assignments.retain(|assignment| {
+                let parent = match oids.get(&assignment.parent) {
+                    Some(parent) => parent,
+                    None => return true,
+                };
+                let oid = [&parent[..], &assignment.subids].concat();
+                names
+                    .entry(oid.clone())
+                    .or_insert_with(|| format!("{}::{}", assignment.module, assignment.name));
+                oids.entry(assignment.name.clone()).or_insert(oid);
+                false
+            });
+            if assignments.is_empty() || assignments.len() == before {
+                break;
+            }
+        }
+        for assignment in assignments {
+            debug!(
+                message = "MIB object has an unknown parent.",
+                module = %assignment.module,
+                name = %assignment.name,
+                parent = %assignment.parent
+            );
+        }
+
+        Self { names }
+    }
+
+    /// Names the object identifier after its closest named ancestor, followed
+    /// by the rest of its sub-identifiers, such as `IF-MIB::ifDescr.3`.
+    pub(super) fn name(&self, oid: &[u32]) -> String {
+        for len in (1..=oid.len()).rev() {
+            if let Some(name) = self.names.get(&oid[..len]) {
+                let mut name = name.clone();
+                for subid in &oid[len..] {
+                    name.push('.');
+                    name.push_str(&subid.to_string());
+                }
+                return name;
+            }
+        }
+        format_oid(oid)
+    }
+}
+
+pub(super) fn format_oid(oid: &[u32]) -> String {
+    oid.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn files(path: &Path) -> Result<Vec<PathBuf>, MibError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_owned()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path).context(Read { path })? {
+        let entry = entry.context(Read { path })?;
+        if entry.path().is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn parse(text: &str, assignments: &mut Vec<Assignment>) {
+    let tokens = tokenize(text);
+    let mut module = String::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i..] {
+            [name, "DEFINITIONS", ..] => {
+                module = (*name).to_owned();
+                i += 2;
+            }
+            // The definitions of the macros themselves are grammars, not
+            // assignments.
+            ["MACRO", ..] => {
+                i += tokens[i..]
+                    .iter()
+                    .position(|&token| token == "END")
+                    .unwrap_or(tokens.len() - i);
+            }
+            [name, "OBJECT", "IDENTIFIER", "::=", "{", ..] if is_value_name(name) => {
+                i += 4;
+                i += parse_value(&module, name, &tokens[i..], assignments);
+            }
+            [name, "TRAP-TYPE", ..] if is_value_name(name) => {
+                i += 2;
+                i += parse_trap_type(&module, name, &tokens[i..], assignments);
+            }
+            [name, r#macro, ..] if is_value_name(name) && OID_MACROS.contains(r#macro) => {
+                // Skip the clauses of the macro, which hold no assignment.
+                let end = tokens[i..]
+                    .windows(2)
+                    .position(|pair| pair == ["::=", "{"])
+                    .map_or(tokens.len(), |end| i + end + 1);
+                i = end + parse_value(&module, name, &tokens[end..], assignments);
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Reads the braces of an object identifier value, as either `{ parent 1 }`
+/// or `{ iso org(3) dod(6) }`, returning the number of tokens read.
+fn parse_value(
+    module: &str,
+    name: &str,
+    tokens: &[&str],
+    assignments: &mut Vec<Assignment>,
+) -> usize {
+    let end = match tokens.iter().position(|&token| token == "}") {
+        Some(end) => end,
+        None => return tokens.len(),
+    };
+    let components = &tokens[1.min(end)..end];
+    let mut parent = match components.first() {
+        Some(parent) => (*parent).to_owned(),
+        None => return end + 1,
+    };
+    let mut subids = Vec::new();
+    let mut j = 1;
+    while j < components.len() {
+        match &components[j..] {
+            // A named component also defines that name.
+            [name, "(", number, ")", ..] => {
+                if let Ok(number) = number.parse() {
+                    subids.push(number);
+                    assignments.push(Assignment {
+                        module: module.to_owned(),
+                        name: (*name).to_owned(),
+                        parent: parent.clone(),
+                        subids: std::mem::take(&mut subids),
+                    });
+                    parent = (*name).to_owned();
+                }
+                j += 4;
+            }
+            [number, ..] => {
+                if let Ok(number) = number.parse() {
+                    subids.push(number);
+                }
+                j += 1;
+            }
+            [] => break,
+        }
+    }
+    assignments.push(Assignment {
+        module: module.to_owned(),
+        name: name.to_owned(),
+        parent,
+        subids,
+    });
+    end + 1
+}
+
+/// SMIv1 traps are numbered within their enterprise, which maps to the
+/// notification `enterprise.0.number`.
+fn parse_trap_type(
+    module: &str,
+    name: &str,
+    tokens: &[&str],
+    assignments: &mut Vec<Assignment>,
+) -> usize {
+    let mut enterprise = None;
+    for (j, window) in tokens.windows(2).enumerate() {
+        match window {
+            ["ENTERPRISE", value] => enterprise = Some(*value),
+            ["::=", number] => {
+                if let (Some(enterprise), Ok(number)) = (enterprise, number.parse()) {
+                    assignments.push(Assignment {
+                        module: module.to_owned(),
+                        name: name.to_owned(),
+                        parent: enterprise.to_owned(),
+                        subids: vec![0, number],
+                    });
+                }
+                return j + 2;
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Values are named in lower camel case, unlike types and keywords.
+fn is_value_name(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+/// Splits the module into words and punctuation, leaving out comments and
+/// quoted strings.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        let len = if rest.starts_with("--") {
+            // Comments run to the end of the line, or the next `--`.
+            let comment = &rest[2..];
+            let end = comment.find('\n').unwrap_or(comment.len());
+            let len = comment[..end].find("--").map_or(end, |end| end + 2);
+            rest = &comment[len..];
+            continue;
+        } else if c == '"' {
+            let len = rest[1..].find('"').map_or(rest.len(), |end| end + 2);
+            rest = &rest[len..];
+            continue;
+        } else if rest.starts_with("::=") {
+            3
+        } else if "{}(),;|".contains(c) {
+            1
+        } else {
+            rest.find(|c: char| c.is_whitespace() || "{}(),;|\"".contains(c))
+                .unwrap_or(rest.len())
+        };
+        let (token, tail) = rest.split_at(len);
+        tokens.push(token);
+        rest = tail;
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mibs(text: &str) -> Mibs {
+        let mut assignments = Vec::new();
+        parse(BUILTIN, &mut assignments);
+        parse(text, &mut assignments);
+        Mibs::from_assignments(assignments)
+    }
+
+    #[test]
+    fn names_builtin_traps() {
+        let mibs = Mibs::load(&[]).unwrap();
+
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 6, 3, 1, 1, 5, 3]),
+            "IF-MIB::linkDown"
+        );
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 2, 1, 1, 3, 0]),
+            "SNMPv2-MIB::sysUpTime.0"
+        );
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 4, 1, 9999, 1]),
+            "SNMPv2-SMI::enterprises.9999.1"
+        );
+        assert_eq!(mibs.name(&[3, 1]), "3.1");
+    }
+
+    #[test]
+    fn names_objects_of_modules() {
+        // The child is defined before its parent, and comments and strings
+        // hold text that looks like assignments.
+        let mibs = mibs(
+            r#"
+            ACME-MIB DEFINITIONS ::= BEGIN
+            IMPORTS
+                MODULE-IDENTITY, OBJECT-TYPE, NOTIFICATION-TYPE, enterprises
+                    FROM SNMPv2-SMI;
+
+            acmeTemperature OBJECT-TYPE
+                SYNTAX      Integer32 (-100..200)
+                MAX-ACCESS  read-only
+                STATUS      current
+                DESCRIPTION "Not { acme 9 } ::= { acme 9 }"
+                ::= { acmeObjects 1 }
+
+            acmeMIB MODULE-IDENTITY
+                LAST-UPDATED "202101010000Z"
+                ORGANIZATION "Acme"
+                DESCRIPTION  "The Acme MIB."
+                ::= { enterprises 99999 }
+
+            -- bogus OBJECT IDENTIFIER ::= { acmeMIB 7 }
+            acmeObjects OBJECT IDENTIFIER ::= { acmeMIB 1 }
+            acmeNotifications OBJECT IDENTIFIER ::= { acmeMIB 2 }
+
+            acmeOverheat NOTIFICATION-TYPE
+                OBJECTS { acmeTemperature }
+                STATUS  current
+                ::= { acmeNotifications 1 }
+            END
+            "#,
+        );
+
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 4, 1, 99999, 2, 1]),
+            "ACME-MIB::acmeOverheat"
+        );
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 4, 1, 99999, 1, 1, 0]),
+            "ACME-MIB::acmeTemperature.0"
+        );
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 4, 1, 99999, 7]),
+            "ACME-MIB::acmeMIB.7"
+        );
+    }
+
+    #[test]
+    fn names_smiv1_traps_and_named_components() {
+        let mibs = mibs(
+            r#"
+            OLD-MIB DEFINITIONS ::= BEGIN
+            oldCorp OBJECT IDENTIFIER ::= { iso org(3) dod(6) internet(1) private(4) enterprises(1) 88888 }
+            oldFanFailure TRAP-TYPE
+                ENTERPRISE  oldCorp
+                VARIABLES   { oldFanIndex }
+                DESCRIPTION "A fan failed."
+                ::= 3
+            END
+            "#,
+        );
+
+        assert_eq!(
+            mibs.name(&[1, 3, 6, 1, 4, 1, 88888, 0, 3]),
+            "OLD-MIB::oldFanFailure"
+        );
+        assert_eq!(mibs.name(&[1, 3, 6, 1, 4, 1, 88888]), "OLD-MIB::oldCorp");
+    }
+}
@@ -0,0 +1,225 @@
+mod ber;
+mod message;
+mod mib;
+mod usm;
+
+pub use self::message::DecodeError;
+use self::{
+    message::{Decoded, Decoder},
+    mib::Mibs,
+    usm::{UserConfig, Users},
+};
+use crate::{
+    config::{log_schema, DataType, Resource, SourceConfig, SourceContext, SourceDescription},
+    event::Event,
+    internal_events::{
+        SnmpTrapEventReceived, SnmpTrapInvalidMessage, SnmpTrapResponseFailed, SnmpTrapSocketError,
+    },
+    shutdown::ShutdownSignal,
+    udp, Pipeline,
+};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::PathBuf,
+};
+use tokio::net::UdpSocket;
+
+/// Large enough for any datagram.
+const MAX_MESSAGE_SIZE: usize = 65535;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnmpTrapConfig {
+    #[serde(default = "default_address")]
+    address: SocketAddr,
+    /// The communities v1 and v2c traps must have, or any if empty.
+    #[serde(default)]
+    communities: Vec<String>,
+    /// The users v3 traps may come from.
+    #[serde(default)]
+    users: Vec<UserConfig>,
+    /// MIB files, or directories of them, to name the object identifiers of
+    /// traps with.
+    #[serde(default)]
+    mibs: Vec<PathBuf>,
+    host_key: Option<String>,
+    receive_buffer_bytes: Option<usize>,
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 162))
+}
+
+inventory::submit! {
+    SourceDescription::new::<SnmpTrapConfig>("snmp_trap")
+}
+
+impl_generate_config_from_default!(SnmpTrapConfig);
+
+impl Default for SnmpTrapConfig {
+    fn default() -> Self {
+        Self {
+            address: default_address(),
+            communities: Vec::new(),
+            users: Vec::new(),
+            mibs: Vec::new(),
+            host_key: None,
+            receive_buffer_bytes: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "snmp_trap")]
+impl SourceConfig for SnmpTrapConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        let decoder = Decoder {
+            communities: self.communities.clone(),
+            users: Users::new(&self.users)?,
+            mibs: Mibs::load(&self.mibs)?,
+        };
+        let host_key = self
+            .host_key
+            .clone()
+            .unwrap_or_else(|| log_schema().host_key().to_owned());
+
+        Ok(Box::pin(snmp_trap_source(
+            self.address,
+            self.receive_buffer_bytes,
+            decoder,
+            host_key,
+            cx.shutdown,
+            cx.out,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "snmp_trap"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource::udp(self.address)]
+    }
+}
+
+async fn snmp_trap_source(
+    address: SocketAddr,
+    receive_buffer_bytes: Option<usize>,
+    decoder: Decoder,
+    host_key: String,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+) -> Result<(), ()> {
+    let socket = UdpSocket::bind(&address)
+        .await
+        .map_err(|error| emit!(SnmpTrapSocketError::bind(error)))?;
+
+    if let Some(receive_buffer_bytes) = receive_buffer_bytes {
+        if let Err(error) = udp::set_receive_buffer_size(&socket, receive_buffer_bytes) {
+            warn!(message = "Failed configuring receive buffer size on UDP socket.", %error);
+        }
+    }
+
+    info!(
+        message = "Listening.",
+        addr = %address,
+        r#type = "udp"
+    );
+
+    let mut packet = vec![0; MAX_MESSAGE_SIZE];
+    loop {
+        let (len, peer) = tokio::select! {
+            _ = &mut shutdown => break,
+            received = socket.recv_from(&mut packet) => match received {
+                Ok(received) => received,
+                Err(error) => {
+                    emit!(SnmpTrapSocketError::read(error));
+                    continue;
+                }
+            },
+        };
+
+        emit!(SnmpTrapEventReceived { byte_size: len });
+        let Decoded { mut log, response } = match decoder.decode(&packet[..len]) {
+            Ok(decoded) => decoded,
+            Err(error) => {
+                emit!(SnmpTrapInvalidMessage { error, peer });
+                continue;
+            }
+        };
+
+        if let Some(response) = response {
+            if let Err(error) = socket.send_to(&response, peer).await {
+                emit!(SnmpTrapResponseFailed { error, peer });
+            }
+        }
+
+        log.insert(host_key.as_str(), peer.ip().to_string());
+        log.insert(log_schema().timestamp_key(), Utc::now());
+        log.insert(log_schema().source_type_key(), Bytes::from("snmp_trap"));
+
+        if let Err(error) = out.send(Event::from(log)).await {
+            error!(message = "Error sending to sink.", %error);
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{collect_n, next_addr};
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SnmpTrapConfig>();
+    }
+
+    #[tokio::test]
+    async fn receives_and_acknowledges_informs() {
+        let address = next_addr();
+        let (tx, rx) = Pipeline::new_test();
+        let config = SnmpTrapConfig {
+            address,
+            communities: vec!["public".to_owned()],
+            ..SnmpTrapConfig::default()
+        };
+        let source = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(source);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        // An inform with only the uptime and notification variables.
+        let inform = [
+            0x30, 0x3a, 0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', 0xa6,
+            0x2d, 0x02, 0x01, 0x2a, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x22, 0x30, 0x0d,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00, 0x43, 0x01, 0x05, 0x30,
+            0x11, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x06, 0x03, 0x01, 0x01, 0x04, 0x01, 0x00, 0x06,
+            0x03, 0x2b, 0x06, 0x01,
+        ];
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(&inform, address).await.unwrap();
+
+        let mut response = [0; 128];
+        let len = socket.recv(&mut response).await.unwrap();
+        assert_eq!(response[13], 0xa2);
+        assert_eq!(&response[14..len], &inform[14..]);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["pdu_type"], "inform".into());
+        assert_eq!(log["trap_oid"], "1.3.6.1".into());
+        assert_eq!(log["uptime"], 5.into());
+        assert_eq!(log[log_schema().host_key()], "127.0.0.1".into());
+        assert_eq!(log[log_schema().source_type_key()], "snmp_trap".into());
+    }
+}
@@ -0,0 +1,388 @@
+//! The User-based Security Model of SNMPv3 (RFC 3414), with the AES privacy
+//! protocol of RFC 3826 and the SHA-2 authentication protocols of RFC 7860.
+//!
+//! The sender of a trap is its authoritative engine, so keys are localized to
+//! the engine ID each trap carries.
+use openssl::{
+    error::ErrorStack,
+    hash::{Hasher, MessageDigest},
+    memcmp,
+    pkey::PKey,
+    sign::Signer,
+    symm::{Cipher, Crypter, Mode},
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::collections::HashMap;
+
+/// Passphrases are stretched to this many bytes before hashing them.
+const PASSPHRASE_EXPANSION: usize = 1_048_576;
+const MIN_PASSPHRASE_LEN: usize = 8;
+const PRIVACY_PARAMETERS_LEN: usize = 8;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    name: String,
+    auth: Option<AuthConfig>,
+    privacy: Option<PrivacyConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct AuthConfig {
+    protocol: AuthProtocol,
+    passphrase: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct PrivacyConfig {
+    protocol: PrivacyProtocol,
+    passphrase: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum AuthProtocol {
+    Md5,
+    Sha,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl AuthProtocol {
+    fn digest(self) -> MessageDigest {
+        match self {
+            Self::Md5 => MessageDigest::md5(),
+            Self::Sha => MessageDigest::sha1(),
+            Self::Sha224 => MessageDigest::sha224(),
+            Self::Sha256 => MessageDigest::sha256(),
+            Self::Sha384 => MessageDigest::sha384(),
+            Self::Sha512 => MessageDigest::sha512(),
+        }
+    }
+
+    /// The length of the truncated HMAC in the authentication parameters.
+    const fn mac_len(self) -> usize {
+        match self {
+            Self::Md5 | Self::Sha => 12,
+            Self::Sha224 => 16,
+            Self::Sha256 => 24,
+            Self::Sha384 => 32,
+            Self::Sha512 => 48,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PrivacyProtocol {
+    Des,
+    Aes,
+}
+
+#[derive(Debug, Snafu)]
+pub enum UserError {
+    #[snafu(display("User {:?} is configured more than once", name))]
+    DuplicateUser { name: String },
+    #[snafu(display("User {:?} has privacy without authentication", name))]
+    PrivacyWithoutAuth { name: String },
+    #[snafu(display(
+        "The passphrases of user {:?} must be at least {} characters long",
+        name,
+        MIN_PASSPHRASE_LEN
+    ))]
+    ShortPassphrase { name: String },
+    #[snafu(display("Could not derive the keys of user {:?}: {}", name, source))]
+    DeriveKeys { name: String, source: ErrorStack },
+}
+
+#[derive(Debug, Snafu)]
+pub enum UsmError {
+    #[snafu(display("Unknown user {:?}", name))]
+    UnknownUser { name: String },
+    #[snafu(display("Security level does not match that of user {:?}", name))]
+    SecurityLevel { name: String },
+    #[snafu(display("Authentication failed for user {:?}", name))]
+    AuthenticationFailed { name: String },
+    #[snafu(display("Invalid privacy parameters"))]
+    InvalidPrivacyParameters,
+    #[snafu(display("Cryptographic error: {}", source))]
+    Crypto { source: ErrorStack },
+}
+
+/// The security parameters of a message, whose authentication parameters
+/// must be within the message itself.
+#[derive(Debug)]
+pub(super) struct SecurityParameters<'a> {
+    pub(super) engine_id: &'a [u8],
+    pub(super) engine_boots: u32,
+    pub(super) engine_time: u32,
+    pub(super) user_name: &'a [u8],
+    pub(super) auth: &'a [u8],
+    pub(super) privacy: &'a [u8],
+}
+
+#[derive(Debug)]
+struct User {
+    /// The protocol and the key of the passphrase, before localization.
+    auth: Option<(AuthProtocol, Vec<u8>)>,
+    privacy: Option<(PrivacyProtocol, Vec<u8>)>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Users {
+    users: HashMap<Vec<u8>, User>,
+}
+
+impl Users {
+    pub(super) fn new(configs: &[UserConfig]) -> Result<Self, UserError> {
+        let mut users = HashMap::new();
+        for config in configs {
+            let name = &config.name;
+            let auth = config
+                .auth
+                .as_ref()
+                .map(|auth| {
+                    check_passphrase(name, &auth.passphrase)?;
+                    let key = passphrase_key(auth.protocol.digest(), &auth.passphrase)
+                        .context(DeriveKeys { name })?;
+                    Ok((auth.protocol, key))
+                })
+                .transpose()?;
+            let privacy = match (&config.privacy, &auth) {
+                (None, _) => None,
+                (Some(_), None) => {
+                    return Err(UserError::PrivacyWithoutAuth { name: name.clone() })
+                }
+                (Some(privacy), Some((auth, _))) => {
+                    check_passphrase(name, &privacy.passphrase)?;
+                    let key = passphrase_key(auth.digest(), &privacy.passphrase)
+                        .context(DeriveKeys { name })?;
+                    Some((privacy.protocol, key))
+                }
+            };
+
+            let user = User { auth, privacy };
+            if users.insert(name.as_bytes().to_vec(), user).is_some() {
+                return Err(UserError::DuplicateUser { name: name.clone() });
+            }
+        }
+        Ok(Self { users })
+    }
+
+    /// Checks the security level and the authentication of the message, and
+    /// returns the decrypted scoped PDU of an encrypted message.
+    pub(super) fn process(
+        &self,
+        packet: &[u8],
+        flags: u8,
+        parameters: &SecurityParameters<'_>,
+        data: &[u8],
+    ) -> Result<Option<Vec<u8>>, UsmError> {
+        let name = || String::from_utf8_lossy(parameters.user_name).into_owned();
+        let user = self
+            .users
+            .get(parameters.user_name)
+            .ok_or_else(|| UsmError::UnknownUser { name: name() })?;
+
+        let authenticated = flags & 0x01 != 0;
+        let encrypted = flags & 0x02 != 0;
+        if authenticated != user.auth.is_some() || encrypted != user.privacy.is_some() {
+            return Err(UsmError::SecurityLevel { name: name() });
+        }
+
+        let (auth, auth_key) = match &user.auth {
+            Some(auth) => auth,
+            None => return Ok(None),
+        };
+        let digest = auth.digest();
+        let auth_key = localize_key(digest, auth_key, parameters.engine_id).context(Crypto)?;
+        if !authentic(packet, *auth, &auth_key, parameters.auth).context(Crypto)? {
+            return Err(UsmError::AuthenticationFailed { name: name() });
+        }
+
+        match &user.privacy {
+            Some((privacy, privacy_key)) => {
+                let privacy_key =
+                    localize_key(digest, privacy_key, parameters.engine_id).context(Crypto)?;
+                decrypt(*privacy, &privacy_key, parameters, data).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn check_passphrase(name: &str, passphrase: &str) -> Result<(), UserError> {
+    if passphrase.len() < MIN_PASSPHRASE_LEN {
+        Err(UserError::ShortPassphrase {
+            name: name.to_owned(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// The key of a passphrase, from the passphrase repeated to a megabyte.
+fn passphrase_key(digest: MessageDigest, passphrase: &str) -> Result<Vec<u8>, ErrorStack> {
+    let passphrase = passphrase.as_bytes();
+    let mut hasher = Hasher::new(digest)?;
+    let mut chunk = [0; 64];
+    let mut index = 0;
+    for _ in 0..PASSPHRASE_EXPANSION / chunk.len() {
+        for byte in chunk.iter_mut() {
+            *byte = passphrase[index % passphrase.len()];
+            index += 1;
+        }
+        hasher.update(&chunk)?;
+    }
+    Ok(hasher.finish()?.to_vec())
+}
+
+/// Localizes the key to an engine, so that a key leaked by one engine is no
+/// use with another.
+fn localize_key(
+    digest: MessageDigest,
+    key: &[u8],
+    engine_id: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut hasher = Hasher::new(digest)?;
+    hasher.update(key)?;
+    hasher.update(engine_id)?;
+    hasher.update(key)?;
+    Ok(hasher.finish()?.to_vec())
+}
+
+/// The HMAC of the message covers the whole message, with its authentication
+/// parameters zeroed.
+fn authentic(
+    packet: &[u8],
+    protocol: AuthProtocol,
+    key: &[u8],
+    parameters: &[u8],
+) -> Result<bool, ErrorStack> {
+    let mac_len = protocol.mac_len();
+    let offset = (parameters.as_ptr() as usize).wrapping_sub(packet.as_ptr() as usize);
+    if parameters.len() != mac_len || offset + mac_len > packet.len() {
+        return Ok(false);
+    }
+
+    let mut zeroed = packet.to_vec();
+    zeroed[offset..offset + mac_len]
+        .iter_mut()
+        .for_each(|byte| *byte = 0);
+
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(protocol.digest(), &key)?;
+    signer.update(&zeroed)?;
+    let mac = signer.sign_to_vec()?;
+    Ok(memcmp::eq(&mac[..mac_len], parameters))
+}
+
+fn decrypt(
+    protocol: PrivacyProtocol,
+    key: &[u8],
+    parameters: &SecurityParameters<'_>,
+    data: &[u8],
+) -> Result<Vec<u8>, UsmError> {
+    let salt = parameters.privacy;
+    if salt.len() != PRIVACY_PARAMETERS_LEN {
+        return Err(UsmError::InvalidPrivacyParameters);
+    }
+    let (cipher, key, iv) = match protocol {
+        PrivacyProtocol::Des => {
+            if data.len() % 8 != 0 {
+                return Err(UsmError::InvalidPrivacyParameters);
+            }
+            let iv = key[8..16]
+                .iter()
+                .zip(salt)
+                .map(|(pre_iv, salt)| pre_iv ^ salt)
+                .collect::<Vec<_>>();
+            (Cipher::des_cbc(), &key[..8], iv)
+        }
+        PrivacyProtocol::Aes => {
+            let iv = [
+                &parameters.engine_boots.to_be_bytes()[..],
+                &parameters.engine_time.to_be_bytes(),
+                salt,
+            ]
+            .concat();
+            (Cipher::aes_128_cfb128(), &key[..16], iv)
+        }
+    };
+
+    let mut crypter = Crypter::new(cipher, Mode::Decrypt, key, Some(&iv)).context(Crypto)?;
+    crypter.pad(false);
+    let mut decrypted = vec![0; data.len() + cipher.block_size()];
+    let mut len = crypter.update(data, &mut decrypted).context(Crypto)?;
+    len += crypter.finalize(&mut decrypted[len..]).context(Crypto)?;
+    decrypted.truncate(len);
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // The engine ID and the keys of the examples of RFC 3414 A.3.
+    const ENGINE_ID: &str = "000000000000000000000002";
+
+    #[test]
+    fn localizes_md5_keys() {
+        let digest = MessageDigest::md5();
+        let key = passphrase_key(digest, "maplesyrup").unwrap();
+        assert_eq!(key, hex("9faf3283884e92834ebc9847d8edd963"));
+
+        let key = localize_key(digest, &key, &hex(ENGINE_ID)).unwrap();
+        assert_eq!(key, hex("526f5eed9fcce26f8964c2930787d82b"));
+    }
+
+    #[test]
+    fn localizes_sha_keys() {
+        let digest = MessageDigest::sha1();
+        let key = passphrase_key(digest, "maplesyrup").unwrap();
+        assert_eq!(key, hex("9fb5cc0381497b3793528939ff788d5d79145211"));
+
+        let key = localize_key(digest, &key, &hex(ENGINE_ID)).unwrap();
+        assert_eq!(key, hex("6695febc9288e36282235fc7151f128497b38f3f"));
+    }
+
+    #[test]
+    fn rejects_invalid_users() {
+        let user = |toml: &str| toml::from_str::<UserConfig>(toml).unwrap();
+
+        assert!(matches!(
+            Users::new(&[user(
+                r#"
+                name = "a"
+                privacy = { protocol = "aes", passphrase = "12345678" }
+                "#
+            )]),
+            Err(UserError::PrivacyWithoutAuth { .. })
+        ));
+        assert!(matches!(
+            Users::new(&[user(
+                r#"
+                name = "a"
+                auth = { protocol = "sha", passphrase = "short" }
+                "#
+            )]),
+            Err(UserError::ShortPassphrase { .. })
+        ));
+        assert!(matches!(
+            Users::new(&[user(r#"name = "a""#), user(r#"name = "a""#)]),
+            Err(UserError::DuplicateUser { .. })
+        ));
+    }
+}
@@ -12,7 +12,7 @@ use crate::{
     tls::MaybeTlsSettings,
 };
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 // TODO: add back when https://github.com/serde-rs/serde/issues/1358 is addressed
@@ -80,8 +80,13 @@ impl SourceConfig for SocketConfig {
     async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
         match self.mode.clone() {
             Mode::Tcp(config) => {
+                let protobuf = match config.protobuf() {
+                    Some(protobuf) => Some(Arc::new(protobuf.decoder()?)),
+                    None => None,
+                };
                 let tcp = tcp::RawTcpSource {
                     config: config.clone(),
+                    protobuf,
                 };
                 let tls = MaybeTlsSettings::from_config(config.tls(), true)?;
                 tcp.run(
@@ -90,6 +95,7 @@ impl SourceConfig for SocketConfig {
                     config.shutdown_timeout_secs(),
                     tls,
                     config.receive_buffer_bytes(),
+                    None,
                     cx.shutdown,
                     cx.out,
                 )
@@ -99,11 +105,16 @@ impl SourceConfig for SocketConfig {
                     .host_key()
                     .clone()
                     .unwrap_or_else(|| log_schema().host_key().to_string());
+                let protobuf = match config.protobuf() {
+                    Some(protobuf) => Some(protobuf.decoder()?),
+                    None => None,
+                };
                 Ok(udp::udp(
                     config.address(),
                     config.max_length(),
                     host_key,
                     config.receive_buffer_bytes(),
+                    protobuf,
                     cx.shutdown,
                     cx.out,
                 ))
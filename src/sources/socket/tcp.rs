@@ -1,14 +1,17 @@
 use crate::{
     event::Event,
-    internal_events::{SocketEventReceived, SocketMode},
+    internal_events::{ProtobufDecodingFailed, SocketEventReceived, SocketMode},
+    protobuf::{MessageDecoder, ProtobufOptions},
     sources::util::{SocketListenAddr, TcpSource},
     tcp::TcpKeepaliveConfig,
     tls::TlsConfig,
 };
-use bytes::Bytes;
+use bytes::{Buf, Bytes, BytesMut};
 use codec::BytesDelimitedCodec;
 use getset::{CopyGetters, Getters, Setters};
 use serde::{Deserialize, Serialize};
+use std::{io, sync::Arc};
+use tokio_util::codec::Decoder;
 
 #[derive(Deserialize, Serialize, Debug, Clone, Getters, CopyGetters, Setters)]
 pub struct TcpConfig {
@@ -28,6 +31,10 @@ pub struct TcpConfig {
     tls: Option<TlsConfig>,
     #[get_copy = "pub"]
     receive_buffer_bytes: Option<usize>,
+    /// Decodes the stream as protobuf messages, each preceded by its length,
+    /// rather than as lines.
+    #[get = "pub"]
+    protobuf: Option<ProtobufOptions>,
 }
 
 fn default_max_length() -> usize {
@@ -56,6 +63,7 @@ impl TcpConfig {
             host_key,
             tls,
             receive_buffer_bytes,
+            protobuf: None,
         }
     }
 
@@ -68,6 +76,7 @@ impl TcpConfig {
             host_key: None,
             tls: None,
             receive_buffer_bytes: None,
+            protobuf: None,
         }
     }
 }
@@ -75,19 +84,43 @@ impl TcpConfig {
 #[derive(Debug, Clone)]
 pub struct RawTcpSource {
     pub config: TcpConfig,
+    pub protobuf: Option<Arc<MessageDecoder>>,
 }
 
 impl TcpSource for RawTcpSource {
     type Error = std::io::Error;
-    type Decoder = BytesDelimitedCodec;
+    type Decoder = FrameDecoder;
 
     fn decoder(&self) -> Self::Decoder {
-        BytesDelimitedCodec::new_with_max_length(b'\n', self.config.max_length)
+        match self.protobuf {
+            Some(_) => {
+                FrameDecoder::LengthDelimited(LengthDelimitedCodec::new(self.config.max_length))
+            }
+            None => FrameDecoder::Lines(BytesDelimitedCodec::new_with_max_length(
+                b'\n',
+                self.config.max_length,
+            )),
+        }
     }
 
     fn build_event(&self, frame: Bytes, host: Bytes) -> Option<Event> {
         let byte_size = frame.len();
-        let mut event = Event::from(frame);
+        let mut event = match &self.protobuf {
+            Some(protobuf) => match protobuf.decode(&frame) {
+                Ok(mut log) => {
+                    log.try_insert(
+                        crate::config::log_schema().timestamp_key(),
+                        chrono::Utc::now(),
+                    );
+                    Event::from(log)
+                }
+                Err(error) => {
+                    emit!(ProtobufDecodingFailed { error: &error });
+                    return None;
+                }
+            },
+            None => Event::from(frame),
+        };
 
         event.as_mut_log().insert(
             crate::config::log_schema().source_type_key(),
@@ -108,8 +141,118 @@ impl TcpSource for RawTcpSource {
     }
 }
 
+/// Splits the stream into lines, or into protobuf messages.
+#[derive(Debug)]
+pub enum FrameDecoder {
+    Lines(BytesDelimitedCodec),
+    LengthDelimited(LengthDelimitedCodec),
+}
+
+impl Decoder for FrameDecoder {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        match self {
+            Self::Lines(codec) => codec.decode(buf),
+            Self::LengthDelimited(codec) => codec.decode(buf),
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        match self {
+            Self::Lines(codec) => codec.decode_eof(buf),
+            Self::LengthDelimited(codec) => codec.decode_eof(buf),
+        }
+    }
+}
+
+/// Frames preceded by their length as a varint, as protobuf's
+/// `writeDelimitedTo` writes messages. Frames longer than `max_length` are
+/// discarded.
+#[derive(Debug)]
+pub struct LengthDelimitedCodec {
+    max_length: usize,
+    /// The number of bytes of the frame being discarded still to skip.
+    discarding: usize,
+}
+
+/// The longest varint encoding of a 64 bit integer.
+const MAX_VARINT_LENGTH: usize = 10;
+
+impl LengthDelimitedCodec {
+    const fn new(max_length: usize) -> Self {
+        Self {
+            max_length,
+            discarding: 0,
+        }
+    }
+}
+
+impl Decoder for LengthDelimitedCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        loop {
+            if self.discarding > 0 {
+                let skipped = self.discarding.min(buf.len());
+                buf.advance(skipped);
+                self.discarding -= skipped;
+                if self.discarding > 0 {
+                    return Ok(None);
+                }
+            }
+
+            let (length, prefix) = match decode_length(buf)? {
+                Some(decoded) => decoded,
+                None => return Ok(None),
+            };
+            if length > self.max_length as u64 {
+                warn!(
+                    message = "Discarding frame larger than max_length.",
+                    length,
+                    max_length = self.max_length,
+                    internal_log_rate_secs = 30
+                );
+                buf.advance(prefix);
+                self.discarding = length as usize;
+                continue;
+            }
+
+            let length = length as usize;
+            if buf.len() < prefix + length {
+                buf.reserve(prefix + length - buf.len());
+                return Ok(None);
+            }
+            buf.advance(prefix);
+            return Ok(Some(buf.split_to(length).freeze()));
+        }
+    }
+}
+
+/// The length of the next frame, and the size of its prefix, if the whole
+/// prefix has been received.
+fn decode_length(buf: &[u8]) -> Result<Option<(u64, usize)>, io::Error> {
+    let mut length = 0u64;
+    for (index, byte) in buf.iter().take(MAX_VARINT_LENGTH).enumerate() {
+        length |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Ok(Some((length, index + 1)));
+        }
+    }
+    if buf.len() >= MAX_VARINT_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid length of protobuf message",
+        ));
+    }
+    Ok(None)
+}
+
 #[cfg(test)]
 mod test {
+    use super::*;
 
     #[test]
     fn tcp_it_defaults_max_length() {
@@ -131,4 +274,20 @@ mod test {
         assert_eq!(with.max_length, 19);
         assert_eq!(without.max_length, super::default_max_length());
     }
+
+    #[test]
+    fn splits_length_delimited_frames() {
+        let mut codec = LengthDelimitedCodec::new(3);
+        let mut buf = BytesMut::from(&[2, b'h', b'i', 5, 1, 2, 3, 4, 5, 1][..]);
+
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(Bytes::from("hi")));
+        // The frame longer than `max_length` is skipped.
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b"!");
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(Bytes::from("!")));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&[0xff; MAX_VARINT_LENGTH][..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
 }
@@ -1,7 +1,11 @@
 use crate::udp;
 use crate::{
+    config::log_schema,
     event::Event,
-    internal_events::{SocketEventReceived, SocketMode, SocketReceiveError},
+    internal_events::{
+        ProtobufDecodingFailed, SocketEventReceived, SocketMode, SocketReceiveError,
+    },
+    protobuf::{MessageDecoder, ProtobufOptions},
     shutdown::ShutdownSignal,
     sources::Source,
     Pipeline,
@@ -15,7 +19,8 @@ use std::net::SocketAddr;
 use tokio::net::UdpSocket;
 use tokio_util::codec::Decoder;
 
-/// UDP processes messages per packet, where messages are separated by newline.
+/// UDP processes messages per packet, where messages are separated by newline,
+/// or where each packet is a protobuf message.
 #[derive(Deserialize, Serialize, Debug, Clone, Getters, CopyGetters)]
 #[serde(deny_unknown_fields)]
 pub struct UdpConfig {
@@ -28,6 +33,9 @@ pub struct UdpConfig {
     host_key: Option<String>,
     #[get_copy = "pub"]
     receive_buffer_bytes: Option<usize>,
+    /// Decodes each packet as a protobuf message, rather than as lines.
+    #[get = "pub"]
+    protobuf: Option<ProtobufOptions>,
 }
 
 fn default_max_length() -> usize {
@@ -41,6 +49,7 @@ impl UdpConfig {
             max_length: default_max_length(),
             host_key: None,
             receive_buffer_bytes: None,
+            protobuf: None,
         }
     }
 }
@@ -50,6 +59,7 @@ pub fn udp(
     max_length: usize,
     host_key: String,
     receive_buffer_bytes: Option<usize>,
+    protobuf: Option<MessageDecoder>,
     mut shutdown: ShutdownSignal,
     out: Pipeline,
 ) -> Source {
@@ -89,14 +99,29 @@ pub fn udp(
                     let mut payload = buf.split_to(byte_size);
 
                     // UDP processes messages per payload, where messages are separated by newline
-                    // and stretch to end of payload.
-                    let mut decoder = BytesDelimitedCodec::new(b'\n');
-                    while let Ok(Some(line)) = decoder.decode_eof(&mut payload) {
-                        let mut event = Event::from(line);
-
+                    // and stretch to end of payload, or are the whole payload.
+                    let events = match &protobuf {
+                        Some(protobuf) => match protobuf.decode(&payload) {
+                            Ok(mut log) => {
+                                log.try_insert(log_schema().timestamp_key(), chrono::Utc::now());
+                                vec![Event::from(log)]
+                            }
+                            Err(error) => {
+                                emit!(ProtobufDecodingFailed { error: &error });
+                                Vec::new()
+                            }
+                        },
+                        None => {
+                            let mut decoder = BytesDelimitedCodec::new(b'\n');
+                            std::iter::from_fn(|| decoder.decode_eof(&mut payload).ok().flatten())
+                                .map(Event::from)
+                                .collect()
+                        }
+                    };
+                    for mut event in events {
                         event
                             .as_mut_log()
-                            .insert(crate::config::log_schema().source_type_key(), Bytes::from("socket"));
+                            .insert(log_schema().source_type_key(), Bytes::from("socket"));
                         event
                             .as_mut_log()
                             .insert(host_key.clone(), address.to_string());
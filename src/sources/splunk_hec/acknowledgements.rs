@@ -0,0 +1,228 @@
+//! Splunk's indexer acknowledgements, answered with the status of the events
+//! of each request in Vector's end-to-end acknowledgements.
+use super::ApiError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot::error::TryRecvError;
+use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct HecAcknowledgementsConfig {
+    pub enabled: bool,
+    /// Requests past this many pending acknowledgements of their channel are
+    /// refused until some are queried
+    pub max_pending_acks_per_channel: usize,
+    pub max_number_of_ack_channels: usize,
+    /// Channels not used for this long are forgotten, with their pending
+    /// acknowledgements, to make room for others
+    pub max_idle_secs: u64,
+}
+
+impl Default for HecAcknowledgementsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pending_acks_per_channel: 1_000_000,
+            max_number_of_ack_channels: 1_000_000,
+            max_idle_secs: 300,
+        }
+    }
+}
+
+/// The body of `/services/collector/ack` requests.
+#[derive(Deserialize, Debug)]
+pub(super) struct HecAckStatusRequest {
+    pub acks: Vec<u64>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(super) struct HecAckStatusResponse {
+    pub acks: BTreeMap<u64, bool>,
+}
+
+#[derive(Debug)]
+struct Channel {
+    next_ack_id: u64,
+    pending: HashMap<u64, BatchStatusReceiver>,
+    last_used: Instant,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            next_ack_id: 0,
+            pending: HashMap::new(),
+            last_used: Instant::now(),
+        }
+    }
+
+    fn delivered(&mut self, ack_id: u64) -> bool {
+        let status = match self.pending.get_mut(&ack_id) {
+            Some(receiver) => receiver.try_recv(),
+            None => return false,
+        };
+        match status {
+            Ok(status) => {
+                self.pending.remove(&ack_id);
+                status == BatchStatus::Delivered
+            }
+            Err(TryRecvError::Closed) => {
+                self.pending.remove(&ack_id);
+                false
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct IndexerAcknowledgements {
+    max_pending_acks_per_channel: usize,
+    max_number_of_ack_channels: usize,
+    max_idle: Duration,
+    channels: Mutex<HashMap<String, Channel>>,
+}
+
+impl IndexerAcknowledgements {
+    pub(super) fn new(config: &HecAcknowledgementsConfig) -> Self {
+        Self {
+            max_pending_acks_per_channel: config.max_pending_acks_per_channel,
+            max_number_of_ack_channels: config.max_number_of_ack_channels,
+            max_idle: Duration::from_secs(config.max_idle_secs),
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gives the events of a request an acknowledgement ID of the channel,
+    /// whose status follows that of the returned batch.
+    pub(super) fn register(&self, channel: &str) -> Result<(u64, Arc<BatchNotifier>), ApiError> {
+        let mut channels = self
+            .channels
+            .lock()
+            .expect("Acknowledgements lock poisoned");
+        if !channels.contains_key(channel) && channels.len() >= self.max_number_of_ack_channels {
+            let max_idle = self.max_idle;
+            channels.retain(|_, channel| channel.last_used.elapsed() < max_idle);
+            if channels.len() >= self.max_number_of_ack_channels {
+                return Err(ApiError::ServiceUnavailable);
+            }
+        }
+
+        let channel = channels
+            .entry(channel.to_owned())
+            .or_insert_with(Channel::new);
+        channel.last_used = Instant::now();
+        if channel.pending.len() >= self.max_pending_acks_per_channel {
+            return Err(ApiError::ServiceUnavailable);
+        }
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let ack_id = channel.next_ack_id;
+        channel.next_ack_id += 1;
+        channel.pending.insert(ack_id, receiver);
+        Ok((ack_id, batch))
+    }
+
+    /// Whether the events of each acknowledgement ID were delivered. IDs are
+    /// forgotten once they are reported delivered, or can no longer be.
+    pub(super) fn query(&self, channel: &str, ack_ids: &[u64]) -> HecAckStatusResponse {
+        let mut channels = self
+            .channels
+            .lock()
+            .expect("Acknowledgements lock poisoned");
+        let mut channel = channels.get_mut(channel);
+        if let Some(channel) = &mut channel {
+            channel.last_used = Instant::now();
+        }
+
+        let acks = ack_ids
+            .iter()
+            .map(|&ack_id| {
+                let delivered = channel
+                    .as_mut()
+                    .map_or(false, |channel| channel.delivered(ack_id));
+                (ack_id, delivered)
+            })
+            .collect();
+        HecAckStatusResponse { acks }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vector_core::event::{Event, EventStatus};
+
+    fn acknowledgements(max_pending_acks_per_channel: usize) -> IndexerAcknowledgements {
+        IndexerAcknowledgements::new(&HecAcknowledgementsConfig {
+            enabled: true,
+            max_pending_acks_per_channel,
+            max_number_of_ack_channels: 1,
+            max_idle_secs: 0,
+        })
+    }
+
+    #[test]
+    fn reports_delivered_batches_once() {
+        let acks = acknowledgements(10);
+        let (first, batch) = acks.register("channel").unwrap();
+        let mut event = Event::new_empty_log();
+        event.add_batch_notifier(batch);
+        let (second, _batch) = acks.register("channel").unwrap();
+        assert_eq!((first, second), (0, 1));
+
+        assert_eq!(
+            acks.query("channel", &[0, 1]).acks,
+            vec![(0, false), (1, false)].into_iter().collect()
+        );
+        drop(event);
+        assert_eq!(
+            acks.query("channel", &[0, 1, 2]).acks,
+            vec![(0, true), (1, false), (2, false)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            acks.query("channel", &[0]).acks,
+            vec![(0, false)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn reports_failed_batches_undelivered() {
+        let acks = acknowledgements(10);
+        let (ack_id, batch) = acks.register("channel").unwrap();
+        let mut event = Event::new_empty_log();
+        event.add_batch_notifier(batch);
+        event.metadata().update_status(EventStatus::Failed);
+        drop(event);
+
+        assert_eq!(
+            acks.query("channel", &[ack_id]).acks,
+            vec![(ack_id, false)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn limits_pending_acks_and_channels() {
+        let acks = acknowledgements(1);
+        let (_, _batch) = acks.register("channel").unwrap();
+        assert!(matches!(
+            acks.register("channel"),
+            Err(ApiError::ServiceUnavailable)
+        ));
+
+        // The idle channel makes room for another, forgetting its acks.
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(acks.register("other").is_ok());
+        assert_eq!(
+            acks.query("channel", &[0]).acks,
+            vec![(0, false)].into_iter().collect()
+        );
+    }
+}
@@ -1,6 +1,11 @@
+mod acknowledgements;
+
+use self::acknowledgements::{
+    HecAckStatusRequest, HecAcknowledgementsConfig, IndexerAcknowledgements,
+};
 use crate::{
     config::{log_schema, DataType, Resource, SourceConfig, SourceContext, SourceDescription},
-    event::{Event, LogEvent, Value},
+    event::{BatchNotifier, Event, LogEvent, Value},
     internal_events::{
         SplunkHecEventReceived, SplunkHecRequestBodyInvalid, SplunkHecRequestError,
         SplunkHecRequestReceived,
@@ -21,6 +26,7 @@ use std::{
     future,
     io::Read,
     net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
 };
 
 use warp::{filters::BoxedFilter, path, reject::Rejection, reply::Response, Filter, Reply};
@@ -31,6 +37,9 @@ pub const INDEX: &str = "splunk_index";
 pub const SOURCE: &str = "splunk_source";
 pub const SOURCETYPE: &str = "splunk_sourcetype";
 
+// Event metadata secret holding the token a request authenticated with
+pub const HEC_TOKEN: &str = "splunk_hec_token";
+
 /// Accepts HTTP requests.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields, default)]
@@ -42,7 +51,13 @@ pub struct SplunkConfig {
     token: Option<String>,
     /// A list of tokens to accept. Omit this to accept any token
     valid_tokens: Option<Vec<String>>,
+    /// Keep the token each request authenticated with as a secret of its
+    /// events, for sinks that forward it
+    store_hec_token: bool,
     tls: Option<TlsConfig>,
+    /// Answer the indexer acknowledgement queries of forwarders, with
+    /// whether the events of their requests were delivered
+    acknowledgements: HecAcknowledgementsConfig,
 }
 
 inventory::submit! {
@@ -67,7 +82,9 @@ impl Default for SplunkConfig {
             address: default_socket_address(),
             token: None,
             valid_tokens: None,
+            store_hec_token: false,
             tls: None,
+            acknowledgements: HecAcknowledgementsConfig::default(),
         }
     }
 }
@@ -85,6 +102,7 @@ impl SourceConfig for SplunkConfig {
         let event_service = source.event_service(cx.out.clone());
         let raw_service = source.raw_service(cx.out);
         let health_service = source.health_service();
+        let ack_service = source.ack_service();
         let options = SplunkSource::options();
 
         let services = path!("services" / "collector" / ..)
@@ -103,6 +121,8 @@ impl SourceConfig for SplunkConfig {
                     .unify()
                     .or(health_service)
                     .unify()
+                    .or(ack_service)
+                    .unify()
                     .or(options)
                     .unify(),
             )
@@ -141,6 +161,8 @@ impl SourceConfig for SplunkConfig {
 /// Shared data for responding to requests.
 struct SplunkSource {
     valid_credentials: Vec<String>,
+    store_hec_token: bool,
+    idx_ack: Option<Arc<IndexerAcknowledgements>>,
 }
 
 impl SplunkSource {
@@ -154,22 +176,22 @@ impl SplunkSource {
             valid_credentials: valid_tokens
                 .map(|token| format!("Splunk {}", token))
                 .collect(),
+            store_hec_token: config.store_hec_token,
+            idx_ack: config
+                .acknowledgements
+                .enabled
+                .then(|| Arc::new(IndexerAcknowledgements::new(&config.acknowledgements))),
         }
     }
 
     fn event_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
-        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
-            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
-        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
-
-        let splunk_channel = splunk_channel_header
-            .and(splunk_channel_query_param)
-            .map(|header: Option<String>, query_param| header.or(query_param));
+        let idx_ack = self.idx_ack.clone();
 
         warp::post()
             .and(path!("event").or(path!("event" / "1.0")))
             .and(self.authorization())
-            .and(splunk_channel)
+            .and(self.token())
+            .and(Self::channel())
             .and(warp::addr::remote())
             .and(warp::header::optional::<String>("X-Forwarded-For"))
             .and(self.gzip())
@@ -177,6 +199,7 @@ impl SplunkSource {
             .and_then(
                 move |_,
                       _,
+                      token: Option<Arc<str>>,
                       channel: Option<String>,
                       remote: Option<SocketAddr>,
                       xff: Option<String>,
@@ -185,14 +208,26 @@ impl SplunkSource {
                     let mut out = out
                         .clone()
                         .sink_map_err(|_| Rejection::from(ApiError::ServerShutdown));
+                    let idx_ack = idx_ack.clone();
                     async move {
+                        let (ack_id, batch) = match (&idx_ack, &channel) {
+                            (Some(idx_ack), Some(channel)) => {
+                                let (ack_id, batch) = idx_ack.register(channel)?;
+                                (Some(ack_id), Some(batch))
+                            }
+                            (Some(_), None) => return Err(ApiError::MissingChannel.into()),
+                            (None, _) => (None, None),
+                        };
+
                         let reader: Box<dyn Read + Send> = if gzip {
                             Box::new(MultiGzDecoder::new(body.reader()))
                         } else {
                             Box::new(body.reader())
                         };
 
-                        let events = stream::iter(EventIterator::new(reader, channel, remote, xff));
+                        let events =
+                            stream::iter(EventIterator::new(reader, channel, remote, xff, token))
+                                .map(move |event| event.map(|event| with_batch(event, &batch)));
 
                         // `fn send_all` can be used once https://github.com/rust-lang/futures-rs/issues/2402
                         // is resolved.
@@ -200,7 +235,7 @@ impl SplunkSource {
 
                         out.flush().await?;
 
-                        res
+                        res.map(|_| ack_id)
                     }
                 },
             )
@@ -209,22 +244,13 @@ impl SplunkSource {
     }
 
     fn raw_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
-        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
-            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
-        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
-
-        let splunk_channel = splunk_channel_header
-            .and(splunk_channel_query_param)
-            .and_then(|header: Option<String>, query_param| async move {
-                header
-                    .or(query_param)
-                    .ok_or_else(|| Rejection::from(ApiError::MissingChannel))
-            });
+        let idx_ack = self.idx_ack.clone();
 
         warp::post()
             .and(path!("raw" / "1.0").or(path!("raw")))
             .and(self.authorization())
-            .and(splunk_channel)
+            .and(self.token())
+            .and(Self::required_channel())
             .and(warp::addr::remote())
             .and(warp::header::optional::<String>("X-Forwarded-For"))
             .and(self.gzip())
@@ -232,19 +258,30 @@ impl SplunkSource {
             .and_then(
                 move |_,
                       _,
+                      token: Option<Arc<str>>,
                       channel: String,
                       remote: Option<SocketAddr>,
                       xff: Option<String>,
                       gzip: bool,
                       body: Bytes| {
                     let out = out.clone();
+                    let idx_ack = idx_ack.clone();
                     async move {
-                        let event = future::ready(raw_event(body, gzip, channel, remote, xff));
-                        futures::stream::once(event)
+                        let (ack_id, batch) = match &idx_ack {
+                            Some(idx_ack) => {
+                                let (ack_id, batch) = idx_ack.register(&channel)?;
+                                (Some(ack_id), Some(batch))
+                            }
+                            None => (None, None),
+                        };
+
+                        let event = raw_event(body, gzip, channel, remote, xff, token)
+                            .map(|event| with_batch(event, &batch));
+                        futures::stream::once(future::ready(event))
                             .forward(
                                 out.sink_map_err(|_| Rejection::from(ApiError::ServerShutdown)),
                             )
-                            .map_ok(|_| ())
+                            .map_ok(|_| ack_id)
                             .await
                     }
                 },
@@ -276,13 +313,36 @@ impl SplunkSource {
             .boxed()
     }
 
+    fn ack_service(&self) -> BoxedFilter<(Response,)> {
+        let idx_ack = self.idx_ack.clone();
+
+        warp::post()
+            .and(path!("ack"))
+            .and(self.authorization())
+            .and(Self::required_channel())
+            .and(warp::body::bytes())
+            .and_then(move |_, channel: String, body: Bytes| {
+                let idx_ack = idx_ack.clone();
+                async move {
+                    let idx_ack =
+                        idx_ack.ok_or_else(|| Rejection::from(ApiError::AckIsDisabled))?;
+                    let request = serde_json::from_slice::<HecAckStatusRequest>(&body)
+                        .map_err(|_| Rejection::from(ApiError::BadRequest))?;
+                    let response = idx_ack.query(&channel, &request.acks);
+                    Ok::<_, Rejection>(response_json(StatusCode::OK, response))
+                }
+            })
+            .boxed()
+    }
+
     fn options() -> BoxedFilter<(Response,)> {
         let post = warp::options()
             .and(
                 path!("event")
                     .or(path!("event" / "1.0"))
                     .or(path!("raw" / "1.0"))
-                    .or(path!("raw")),
+                    .or(path!("raw"))
+                    .or(path!("ack")),
             )
             .map(|_| warp::reply::with_header(warp::reply(), "Allow", "POST").into_response());
 
@@ -293,6 +353,26 @@ impl SplunkSource {
         post.or(get).unify().boxed()
     }
 
+    /// The data channel of the request, from its header or query
+    fn channel() -> BoxedFilter<(Option<String>,)> {
+        let splunk_channel_query_param = warp::query::<HashMap<String, String>>()
+            .map(|qs: HashMap<String, String>| qs.get("channel").map(|v| v.to_owned()));
+        let splunk_channel_header = warp::header::optional::<String>("x-splunk-request-channel");
+
+        splunk_channel_header
+            .and(splunk_channel_query_param)
+            .map(|header: Option<String>, query_param| header.or(query_param))
+            .boxed()
+    }
+
+    fn required_channel() -> BoxedFilter<(String,)> {
+        Self::channel()
+            .and_then(|channel: Option<String>| async move {
+                channel.ok_or_else(|| Rejection::from(ApiError::MissingChannel))
+            })
+            .boxed()
+    }
+
     /// Authorize request
     fn authorization(&self) -> BoxedFilter<((),)> {
         let valid_credentials = self.valid_credentials.clone();
@@ -311,6 +391,18 @@ impl SplunkSource {
             .boxed()
     }
 
+    /// The token the request authenticated with, if it is to be stored
+    fn token(&self) -> BoxedFilter<(Option<Arc<str>>,)> {
+        let store_hec_token = self.store_hec_token;
+        warp::header::optional::<String>("Authorization")
+            .map(move |authorization: Option<String>| {
+                authorization
+                    .filter(|_| store_hec_token)
+                    .and_then(|authorization| authorization.strip_prefix("Splunk ").map(Arc::from))
+            })
+            .boxed()
+    }
+
     /// Is body encoded with gzip
     fn gzip(&self) -> BoxedFilter<(bool,)> {
         warp::header::optional::<String>("Content-Encoding")
@@ -337,6 +429,8 @@ struct EventIterator<R: Read> {
     time: Time,
     /// Remaining extracted default values
     extractors: [DefaultExtractor; 4],
+    /// Token to store as a secret of each event
+    token: Option<Arc<str>>,
 }
 
 impl<R: Read> EventIterator<R> {
@@ -345,6 +439,7 @@ impl<R: Read> EventIterator<R> {
         channel: Option<String>,
         remote: Option<SocketAddr>,
         remote_addr: Option<String>,
+        token: Option<Arc<str>>,
     ) -> Self {
         EventIterator {
             data,
@@ -367,6 +462,7 @@ impl<R: Read> EventIterator<R> {
                 DefaultExtractor::new("source", SOURCE),
                 DefaultExtractor::new("sourcetype", SOURCETYPE),
             ],
+            token,
         }
     }
 
@@ -389,6 +485,12 @@ impl<R: Read> EventIterator<R> {
         let mut event = Event::new_empty_log();
         let log = event.as_mut_log();
 
+        if let Some(token) = &self.token {
+            log.metadata_mut()
+                .secrets_mut()
+                .insert(HEC_TOKEN, Arc::clone(token));
+        }
+
         // Add source type
         log.insert(log_schema().source_type_key(), Bytes::from("splunk_hec"));
 
@@ -600,6 +702,7 @@ fn raw_event(
     channel: String,
     remote: Option<SocketAddr>,
     xff: Option<String>,
+    token: Option<Arc<str>>,
 ) -> Result<Event, Rejection> {
     // Process gzip
     let message: Value = if gzip {
@@ -626,6 +729,10 @@ fn raw_event(
     // Add channel
     log.insert(CHANNEL, channel);
 
+    if let Some(token) = token {
+        log.metadata_mut().secrets_mut().insert(HEC_TOKEN, token);
+    }
+
     // host-field priority for raw endpoint:
     // - x-forwarded-for is set to `host` field first, if present. If not present:
     // - set remote addr to host field
@@ -660,6 +767,8 @@ pub(crate) enum ApiError {
     EmptyEventField { event: usize },
     MissingEventField { event: usize },
     BadRequest,
+    ServiceUnavailable,
+    AckIsDisabled,
 }
 
 impl warp::reject::Reject for ApiError {}
@@ -689,11 +798,30 @@ mod splunk_response {
             json_to_bytes(json!({"text":"unsupported content encoding"}));
         pub static ref NO_CHANNEL: Bytes =
             json_to_bytes(json!({"text":"Data channel is missing","code":10}));
+        pub static ref SERVER_IS_BUSY: Bytes =
+            json_to_bytes(json!({"text":"Server is busy","code":9}));
+        pub static ref ACK_IS_DISABLED: Bytes =
+            json_to_bytes(json!({"text":"ACK is disabled","code":14}));
     }
 }
 
-fn finish_ok(_: ()) -> Response {
-    response_json(StatusCode::OK, splunk_response::SUCCESS.as_ref())
+/// Attaches the batch whose status is reported for the acknowledgement ID of
+/// the request.
+fn with_batch(event: Event, batch: &Option<Arc<BatchNotifier>>) -> Event {
+    match batch {
+        Some(batch) => event.with_batch_notifier(batch),
+        None => event,
+    }
+}
+
+fn finish_ok(maybe_ack_id: Option<u64>) -> Response {
+    match maybe_ack_id {
+        Some(ack_id) => response_json(
+            StatusCode::OK,
+            json!({"text":"Success","code":0,"ackId":ack_id}),
+        ),
+        None => response_json(StatusCode::OK, splunk_response::SUCCESS.as_ref()),
+    }
 }
 
 async fn finish_err(rejection: Rejection) -> Result<(Response,), Rejection> {
@@ -731,6 +859,14 @@ async fn finish_err(rejection: Rejection) -> Result<(Response,), Rejection> {
                 event_error("Event field is required", 12, event)
             }
             ApiError::BadRequest => empty_response(StatusCode::BAD_REQUEST),
+            ApiError::ServiceUnavailable => response_json(
+                StatusCode::SERVICE_UNAVAILABLE,
+                splunk_response::SERVER_IS_BUSY.as_ref(),
+            ),
+            ApiError::AckIsDisabled => response_json(
+                StatusCode::BAD_REQUEST,
+                splunk_response::ACK_IS_DISABLED.as_ref(),
+            ),
         },))
     } else {
         Err(rejection)
@@ -772,7 +908,7 @@ fn event_error(text: &str, code: u16, event: usize) -> Response {
 #[cfg(feature = "sinks-splunk_hec")]
 #[cfg(test)]
 mod tests {
-    use super::{parse_timestamp, SplunkConfig};
+    use super::{acknowledgements::HecAcknowledgementsConfig, parse_timestamp, SplunkConfig};
     use crate::{
         config::{log_schema, SinkConfig, SinkContext, SourceConfig, SourceContext},
         event::Event,
@@ -786,6 +922,7 @@ mod tests {
     };
     use chrono::{TimeZone, Utc};
     use futures::{channel::mpsc, stream, StreamExt};
+    use serde_json::json;
     use std::{future::ready, net::SocketAddr};
 
     #[test]
@@ -798,12 +935,13 @@ mod tests {
     const VALID_TOKENS: &[&str; 2] = &[TOKEN, "secondary-token"];
 
     async fn source() -> (mpsc::Receiver<Event>, SocketAddr) {
-        source_with(Some(TOKEN.to_owned()), None).await
+        source_with(Some(TOKEN.to_owned()), None, false).await
     }
 
     async fn source_with(
         token: Option<String>,
         valid_tokens: Option<&[&str]>,
+        store_hec_token: bool,
     ) -> (mpsc::Receiver<Event>, SocketAddr) {
         let (sender, recv) = Pipeline::new_test();
         let address = next_addr();
@@ -814,7 +952,31 @@ mod tests {
                 address,
                 token,
                 valid_tokens,
+                store_hec_token,
                 tls: None,
+                acknowledgements: HecAcknowledgementsConfig::default(),
+            }
+            .build(SourceContext::new_test(sender))
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
+    async fn source_with_acknowledgements() -> (mpsc::Receiver<Event>, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test();
+        let address = next_addr();
+        tokio::spawn(async move {
+            SplunkConfig {
+                token: Some(TOKEN.to_owned()),
+                acknowledgements: HecAcknowledgementsConfig {
+                    enabled: true,
+                    ..HecAcknowledgementsConfig::default()
+                },
+                ..SplunkConfig::on(address)
             }
             .build(SourceContext::new_test(sender))
             .await
@@ -844,6 +1006,7 @@ mod tests {
             batch: BatchConfig::default(),
             request: TowerRequestConfig::default(),
             tls: None,
+            acknowledgements: Default::default(),
         }
         .build(SinkContext::new_test())
         .await
@@ -1218,7 +1381,7 @@ mod tests {
         trace_init();
 
         let message = r#"{"event":"first", "color": "blue"}"#;
-        let (_source, address) = source_with(None, Some(VALID_TOKENS)).await;
+        let (_source, address) = source_with(None, Some(VALID_TOKENS), false).await;
         let options = SendWithOpts {
             channel: None,
             forwarded_for: None,
@@ -1237,12 +1400,50 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn store_hec_token() {
+        trace_init();
+
+        let (source, address) = source_with(Some(TOKEN.to_owned()), None, true).await;
+
+        assert_eq!(
+            200,
+            post(address, "services/collector/event", r#"{"event":"first"}"#).await
+        );
+        assert_eq!(200, post(address, "services/collector/raw", "second").await);
+
+        let events = collect_n(source, 2).await;
+        for event in events {
+            let secrets = event.as_log().metadata().secrets();
+            assert_eq!(
+                secrets.get(super::HEC_TOKEN).map(|token| &**token),
+                Some(TOKEN)
+            );
+            assert!(!format!("{:?}", event).contains(&format!("\"{}\"", TOKEN)));
+        }
+    }
+
+    #[tokio::test]
+    async fn hec_token_not_stored_by_default() {
+        trace_init();
+
+        let (source, address) = source().await;
+
+        assert_eq!(
+            200,
+            post(address, "services/collector/event", r#"{"event":"first"}"#).await
+        );
+
+        let event = collect_n(source, 1).await.remove(0);
+        assert!(event.as_log().metadata().secrets().is_empty());
+    }
+
     #[tokio::test]
     async fn no_authorization() {
         trace_init();
 
         let message = "no_authorization";
-        let (source, address) = source_with(None, None).await;
+        let (source, address) = source_with(None, None, false).await;
         let (sink, health) = sink(address, Encoding::Text, Compression::gzip_default()).await;
         assert!(health.await.is_ok());
 
@@ -1251,6 +1452,80 @@ mod tests {
         assert_eq!(event.as_log()[log_schema().message_key()], message.into());
     }
 
+    async fn post_json(address: SocketAddr, api: &str, message: &str) -> (u16, serde_json::Value) {
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/{}", address, api))
+            .header("Authorization", format!("Splunk {}", TOKEN))
+            .header("x-splunk-request-channel", "channel")
+            .body(message.to_owned())
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn acknowledgements() {
+        trace_init();
+
+        let (source, address) = source_with_acknowledgements().await;
+
+        let (status, body) =
+            post_json(address, "services/collector/event", r#"{"event":"first"}"#).await;
+        assert_eq!(200, status);
+        assert_eq!(body, json!({"text":"Success","code":0,"ackId":0}));
+        let (status, body) = post_json(address, "services/collector/raw", "second").await;
+        assert_eq!(200, status);
+        assert_eq!(body["ackId"], json!(1));
+
+        let events = collect_n(source, 2).await;
+        let (_, body) = post_json(address, "services/collector/ack", r#"{"acks":[0,1,2]}"#).await;
+        assert_eq!(body, json!({"acks":{"0":false,"1":false,"2":false}}));
+
+        drop(events);
+        let (status, body) =
+            post_json(address, "services/collector/ack", r#"{"acks":[0,1,2]}"#).await;
+        assert_eq!(200, status);
+        assert_eq!(body, json!({"acks":{"0":true,"1":true,"2":false}}));
+    }
+
+    #[tokio::test]
+    async fn acknowledgements_require_channel() {
+        trace_init();
+
+        let (_source, address) = source_with_acknowledgements().await;
+
+        let opts = SendWithOpts::default();
+        assert_eq!(
+            400,
+            send_with(address, "services/collector/event", "{}", TOKEN, &opts).await
+        );
+        assert_eq!(
+            400,
+            send_with(
+                address,
+                "services/collector/ack",
+                r#"{"acks":[0]}"#,
+                TOKEN,
+                &opts
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn acknowledgements_disabled() {
+        trace_init();
+
+        let (_source, address) = source().await;
+
+        assert_eq!(
+            400,
+            post(address, "services/collector/ack", r#"{"acks":[0]}"#).await
+        );
+    }
+
     #[tokio::test]
     async fn partial() {
         trace_init();
@@ -21,9 +21,9 @@ pub mod parser;
 #[cfg(unix)]
 mod unix;
 
-use parser::parse;
+use parser::parse_line;
 #[cfg(unix)]
-use unix::{statsd_unix, UnixConfig};
+use unix::{statsd_unix, statsd_unix_datagram, UnixConfig, UnixDatagramConfig};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "mode", rename_all = "snake_case")]
@@ -32,6 +32,8 @@ enum StatsdConfig {
     Udp(UdpConfig),
     #[cfg(unix)]
     Unix(UnixConfig),
+    #[cfg(unix)]
+    UnixDatagram(UnixDatagramConfig),
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -106,17 +108,23 @@ impl SourceConfig for StatsdConfig {
                     config.shutdown_timeout_secs,
                     tls,
                     config.receive_buffer_bytes,
+                    None,
                     cx.shutdown,
                     cx.out,
                 )
             }
             #[cfg(unix)]
             StatsdConfig::Unix(config) => Ok(statsd_unix(config.clone(), cx.shutdown, cx.out)),
+            #[cfg(unix)]
+            StatsdConfig::UnixDatagram(config) => {
+                Ok(statsd_unix_datagram(config.clone(), cx.shutdown, cx.out))
+            }
         }
     }
 
+    // DogStatsD events and service checks are logs
     fn output_type(&self) -> config::DataType {
-        config::DataType::Metric
+        config::DataType::Any
     }
 
     fn source_type(&self) -> &'static str {
@@ -128,18 +136,18 @@ impl SourceConfig for StatsdConfig {
             Self::Tcp(tcp) => vec![tcp.address.into()],
             Self::Udp(udp) => vec![Resource::udp(udp.address)],
             #[cfg(unix)]
-            Self::Unix(_) => vec![],
+            Self::Unix(_) | Self::UnixDatagram(_) => vec![],
         }
     }
 }
 
 pub(self) fn parse_event(line: &str) -> Option<Event> {
-    match parse(line) {
-        Ok(metric) => {
+    match parse_line(line) {
+        Ok(event) => {
             emit!(StatsdEventReceived {
                 byte_size: line.len()
             });
-            Some(Event::Metric(metric))
+            Some(event)
         }
         Err(error) => {
             emit!(StatsdInvalidRecord { error, text: line });
@@ -294,6 +302,30 @@ mod test {
         test_statsd(config, sender).await;
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_statsd_unix_datagram() {
+        let in_path = tempfile::tempdir()
+            .unwrap()
+            .into_path()
+            .join("unix_datagram_test");
+        let config = StatsdConfig::UnixDatagram(UnixDatagramConfig {
+            path: in_path.clone(),
+            max_length: 8192,
+        });
+        let (sender, mut receiver) = mpsc::channel(200);
+        tokio::spawn(async move {
+            let socket = tokio::net::UnixDatagram::unbound().unwrap();
+            while let Some(bytes) = receiver.next().await {
+                // The source may not be listening yet
+                while socket.send_to(bytes, &in_path).await.is_err() {
+                    sleep(Duration::from_millis(10)).await;
+                }
+            }
+        });
+        test_statsd(config, sender).await;
+    }
+
     async fn test_statsd(
         statsd_config: StatsdConfig,
         // could use unbounded channel,
@@ -1,4 +1,11 @@
-use crate::event::metric::{Metric, MetricKind, MetricValue, StatisticKind};
+use crate::{
+    config::log_schema,
+    event::{
+        metric::{Metric, MetricKind, MetricValue, Sample, StatisticKind},
+        Event, LogEvent, Value,
+    },
+};
+use chrono::{TimeZone, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{
@@ -31,31 +38,33 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
 
     let name = sanitize_key(key);
     let metric_type = parts[1];
-
-    // sampling part is optional and comes after metric type part
-    let sampling = parts.get(2).filter(|s| s.starts_with('@'));
-    let sample_rate = if let Some(s) = sampling {
-        1.0 / sanitize_sampling(parse_sampling(s)?)
-    } else {
-        1.0
-    };
-
-    // tags are optional and could be found either after sampling of after metric type part
-    let tags = if sampling.is_none() {
-        parts.get(2)
-    } else {
-        parts.get(3)
-    };
-    let tags = tags.filter(|s| s.starts_with('#'));
-    let tags = if let Some(t) = tags {
-        Some(parse_tags(t)?)
-    } else {
-        None
-    };
+    // DogStatsD packs several values of the same metric in one line
+    let values = parts[0].split(':').collect::<Vec<_>>();
+
+    // the other parts are optional, and told apart by their prefix
+    let mut sample_rate = 1.0;
+    let mut tags: Option<BTreeMap<String, String>> = None;
+    let mut timestamp = None;
+    for part in &parts[2..] {
+        if part.starts_with('@') {
+            sample_rate = 1.0 / sanitize_sampling(parse_sampling(part)?);
+        } else if part.starts_with('#') {
+            tags.get_or_insert_with(BTreeMap::new)
+                .extend(parse_tags(part)?);
+        } else if let Some(container_id) = part.strip_prefix("c:") {
+            tags.get_or_insert_with(BTreeMap::new)
+                .insert("container_id".to_owned(), container_id.to_owned());
+        } else if let Some(seconds) = part.strip_prefix('T') {
+            timestamp = Some(Utc.timestamp(seconds.parse()?, 0));
+        }
+    }
 
     let metric = match metric_type {
         "c" => {
-            let val: f64 = parts[0].parse()?;
+            let val = values
+                .iter()
+                .map(|value| value.parse::<f64>())
+                .sum::<Result<f64, _>>()?;
             Metric::new(
                 name,
                 MetricKind::Incremental,
@@ -66,27 +75,40 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
             .with_tags(tags)
         }
         unit @ "h" | unit @ "ms" | unit @ "d" => {
-            let val: f64 = parts[0].parse()?;
+            let samples = values
+                .iter()
+                .map(|value| {
+                    Ok(Sample {
+                        value: convert_to_base_units(unit, value.parse()?),
+                        rate: sample_rate as u32,
+                    })
+                })
+                .collect::<Result<_, ParseError>>()?;
             Metric::new(
-                name, MetricKind::Incremental, MetricValue::Distribution {
-                    samples: vector_core::samples![convert_to_base_units(unit, val) => sample_rate as u32],
+                name,
+                MetricKind::Incremental,
+                MetricValue::Distribution {
+                    samples,
                     statistic: convert_to_statistic(unit),
                 },
-            ).with_tags(tags)
+            )
+            .with_tags(tags)
         }
         "g" => {
-            let value = if parts[0]
+            // only the last of packed values is kept, as it replaces the others
+            let last = values[values.len() - 1];
+            let value = if last
                 .chars()
                 .next()
                 .map(|c| c.is_ascii_digit())
                 .ok_or(ParseError::Malformed("empty first body component"))?
             {
-                parts[0].parse()?
+                last.parse()?
             } else {
-                parts[0][1..].parse()?
+                last[1..].parse()?
             };
 
-            match parse_direction(parts[0])? {
+            match parse_direction(last)? {
                 None => Metric::new(name, MetricKind::Absolute, MetricValue::Gauge { value })
                     .with_tags(tags),
                 Some(sign) => Metric::new(
@@ -103,13 +125,123 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
             name,
             MetricKind::Incremental,
             MetricValue::Set {
-                values: vec![parts[0].into()].into_iter().collect(),
+                values: values.iter().map(|value| (*value).to_owned()).collect(),
             },
         )
         .with_tags(tags),
         other => return Err(ParseError::UnknownMetricType(other.into())),
     };
-    Ok(metric)
+    Ok(metric.with_timestamp(timestamp))
+}
+
+/// Parses a line, which DogStatsD extends with events and service checks,
+/// turned into log events.
+pub fn parse_line(line: &str) -> Result<Event, ParseError> {
+    if let Some(rest) = line.strip_prefix("_e{") {
+        parse_dogstatsd_event(rest).map(Event::Log)
+    } else if let Some(rest) = line.strip_prefix("_sc|") {
+        parse_service_check(rest).map(Event::Log)
+    } else {
+        parse(line).map(Event::Metric)
+    }
+}
+
+/// `_e{<TITLE_LENGTH>,<TEXT_LENGTH>}:<TITLE>|<TEXT>|d:<TIMESTAMP>|h:<HOSTNAME>|p:<PRIORITY>|t:<ALERT_TYPE>|#<TAGS>`,
+/// with the fields named as the Datadog events API has them.
+fn parse_dogstatsd_event(input: &str) -> Result<LogEvent, ParseError> {
+    let (lengths, rest) = input
+        .split_once("}:")
+        .ok_or(ParseError::Malformed("expected '}:' after event lengths"))?;
+    let (title_len, text_len) = lengths.split_once(',').ok_or(ParseError::Malformed(
+        "expected ',' separated event lengths",
+    ))?;
+    let (title_len, text_len) = (title_len.parse::<usize>()?, text_len.parse::<usize>()?);
+
+    let title = rest.get(..title_len).ok_or(ParseError::Malformed(
+        "event title is shorter than its length",
+    ))?;
+    let rest = rest
+        .get(title_len..)
+        .and_then(|rest| rest.strip_prefix('|'))
+        .ok_or(ParseError::Malformed("expected '|' after event title"))?;
+    let text = rest.get(..text_len).ok_or(ParseError::Malformed(
+        "event text is shorter than its length",
+    ))?;
+    let rest = &rest[text_len..];
+
+    let mut log = LogEvent::default();
+    log.insert("title", title);
+    log.insert("text", text.replace("\\n", "\n"));
+    let mut timestamp = None;
+    for part in rest.split('|').filter(|part| !part.is_empty()) {
+        if let Some(seconds) = part.strip_prefix("d:") {
+            timestamp = Some(Utc.timestamp(seconds.parse()?, 0));
+        } else if let Some(host) = part.strip_prefix("h:") {
+            log.insert("host", host);
+        } else if let Some(key) = part.strip_prefix("k:") {
+            log.insert("aggregation_key", key);
+        } else if let Some(priority) = part.strip_prefix("p:") {
+            log.insert("priority", priority);
+        } else if let Some(source) = part.strip_prefix("s:") {
+            log.insert("source_type_name", source);
+        } else if let Some(alert_type) = part.strip_prefix("t:") {
+            log.insert("alert_type", alert_type);
+        } else if let Some(tags) = part.strip_prefix('#') {
+            log.insert("tags", split_tags(tags));
+        }
+    }
+    log.insert(
+        log_schema().timestamp_key(),
+        timestamp.unwrap_or_else(Utc::now),
+    );
+    Ok(log)
+}
+
+/// `_sc|<NAME>|<STATUS>|d:<TIMESTAMP>|h:<HOSTNAME>|#<TAGS>|m:<MESSAGE>`
+fn parse_service_check(input: &str) -> Result<LogEvent, ParseError> {
+    // the message comes last, and may contain pipes
+    let (input, message) = match input.split_once("|m:") {
+        Some((input, message)) => (input, Some(message)),
+        None => (input, None),
+    };
+    let mut parts = input.split('|');
+    let name = parts
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or(ParseError::Malformed("expected service check name"))?;
+    let status = parts
+        .next()
+        .ok_or(ParseError::Malformed("expected service check status"))?
+        .parse::<u8>()?;
+
+    let mut log = LogEvent::default();
+    log.insert("check", name);
+    log.insert("status", status);
+    let mut timestamp = None;
+    for part in parts {
+        if let Some(seconds) = part.strip_prefix("d:") {
+            timestamp = Some(Utc.timestamp(seconds.parse()?, 0));
+        } else if let Some(host) = part.strip_prefix("h:") {
+            log.insert("host", host);
+        } else if let Some(tags) = part.strip_prefix('#') {
+            log.insert("tags", split_tags(tags));
+        }
+    }
+    if let Some(message) = message {
+        log.insert(log_schema().message_key(), message);
+    }
+    log.insert(
+        log_schema().timestamp_key(),
+        timestamp.unwrap_or_else(Utc::now),
+    );
+    Ok(log)
+}
+
+fn split_tags(tags: &str) -> Vec<Value> {
+    tags.split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(Value::from)
+        .collect()
 }
 
 fn parse_sampling(input: &str) -> Result<f64, ParseError> {
@@ -224,8 +356,15 @@ impl From<ParseFloatError> for ParseError {
 
 #[cfg(test)]
 mod test {
-    use super::{parse, sanitize_key, sanitize_sampling};
-    use crate::event::metric::{Metric, MetricKind, MetricValue, StatisticKind};
+    use super::{parse, parse_line, sanitize_key, sanitize_sampling};
+    use crate::{
+        config::log_schema,
+        event::{
+            metric::{Metric, MetricKind, MetricValue, StatisticKind},
+            Value,
+        },
+    };
+    use chrono::{TimeZone, Utc};
     use shared::assert_event_data_eq;
 
     #[test]
@@ -393,6 +532,86 @@ mod test {
         );
     }
 
+    #[test]
+    fn packed_values() {
+        assert_event_data_eq!(
+            parse("glork:320:160|ms|@0.5"),
+            Ok(Metric::new(
+                "glork",
+                MetricKind::Incremental,
+                MetricValue::Distribution {
+                    samples: vector_core::samples![0.320 => 2, 0.160 => 2],
+                    statistic: StatisticKind::Histogram
+                },
+            )),
+        );
+        assert_event_data_eq!(
+            parse("foo:1:2|c"),
+            Ok(Metric::new(
+                "foo",
+                MetricKind::Incremental,
+                MetricValue::Counter { value: 3.0 },
+            )),
+        );
+    }
+
+    #[test]
+    fn container_id_and_timestamp() {
+        assert_event_data_eq!(
+            parse("foo:1|c|#env:prod|c:abc123|T1609459200"),
+            Ok(Metric::new(
+                "foo",
+                MetricKind::Incremental,
+                MetricValue::Counter { value: 1.0 },
+            )
+            .with_tags(Some(
+                vec![
+                    ("env".to_owned(), "prod".to_owned()),
+                    ("container_id".to_owned(), "abc123".to_owned()),
+                ]
+                .into_iter()
+                .collect(),
+            ))
+            .with_timestamp(Some(Utc.ymd(2021, 1, 1).and_hms(0, 0, 0)))),
+        );
+    }
+
+    #[test]
+    fn dogstatsd_event() {
+        let event = parse_line(
+            "_e{5,11}:Title|Line\\nline2|d:1609459200|h:web-1|p:low|t:warning|#env:prod,role",
+        )
+        .unwrap();
+        let log = event.as_log();
+        assert_eq!(log["title"], "Title".into());
+        assert_eq!(log["text"], "Line\nline2".into());
+        assert_eq!(log["host"], "web-1".into());
+        assert_eq!(log["priority"], "low".into());
+        assert_eq!(log["alert_type"], "warning".into());
+        assert_eq!(
+            log["tags"],
+            Value::from(vec![Value::from("env:prod"), Value::from("role")])
+        );
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 1, 1).and_hms(0, 0, 0).into()
+        );
+
+        assert!(parse_line("_e{10,4}:Title|text").is_err());
+    }
+
+    #[test]
+    fn dogstatsd_service_check() {
+        let event = parse_line("_sc|db.up|2|h:db-1|#env:prod|m:Down | since 10:00").unwrap();
+        let log = event.as_log();
+        assert_eq!(log["check"], "db.up".into());
+        assert_eq!(log["status"], 2.into());
+        assert_eq!(log["host"], "db-1".into());
+        assert_eq!(log[log_schema().message_key()], "Down | since 10:00".into());
+
+        assert!(parse_line("_sc|db.up|critical").is_err());
+    }
+
     #[test]
     fn sanitizing_keys() {
         assert_eq!("foo-bar-baz", sanitize_key("foo/bar/baz"));
@@ -1,6 +1,9 @@
 use crate::{
-    event::Event, shutdown::ShutdownSignal, sources::util::build_unix_stream_source,
-    sources::Source, Pipeline,
+    event::Event,
+    shutdown::ShutdownSignal,
+    sources::util::{build_unix_datagram_source, build_unix_stream_source},
+    sources::Source,
+    Pipeline,
 };
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
@@ -12,6 +15,20 @@ pub struct UnixConfig {
     pub path: PathBuf,
 }
 
+/// The socket DogStatsD clients send to, where each datagram may hold
+/// several lines.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UnixDatagramConfig {
+    pub path: PathBuf,
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+}
+
+const fn default_max_length() -> usize {
+    // DogStatsD's default buffer size for Unix sockets
+    8192
+}
+
 fn build_event(_: &str, _: Option<Bytes>, line: &str) -> Option<Event> {
     super::parse_event(line)
 }
@@ -26,3 +43,19 @@ pub fn statsd_unix(config: UnixConfig, shutdown: ShutdownSignal, out: Pipeline)
         build_event,
     )
 }
+
+pub fn statsd_unix_datagram(
+    config: UnixDatagramConfig,
+    shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> Source {
+    build_unix_datagram_source(
+        config.path,
+        config.max_length,
+        String::new(),
+        LinesCodec::new(),
+        shutdown,
+        out,
+        build_event,
+    )
+}
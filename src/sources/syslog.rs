@@ -1,4 +1,4 @@
-use super::util::{SocketListenAddr, TcpSource};
+use super::util::{ConnectionRateLimit, SocketListenAddr, TcpSource};
 #[cfg(unix)]
 use crate::sources::util::build_unix_stream_source;
 use crate::udp;
@@ -49,6 +49,7 @@ pub enum Mode {
         keepalive: Option<TcpKeepaliveConfig>,
         tls: Option<TlsConfig>,
         receive_buffer_bytes: Option<usize>,
+        connection_rate_limit: Option<ConnectionRateLimit>,
     },
     Udp {
         address: SocketAddr,
@@ -84,6 +85,7 @@ impl GenerateConfig for SyslogConfig {
                 keepalive: None,
                 tls: None,
                 receive_buffer_bytes: None,
+                connection_rate_limit: None,
             },
             host_key: None,
             max_length: default_max_length(),
@@ -107,6 +109,7 @@ impl SourceConfig for SyslogConfig {
                 keepalive,
                 tls,
                 receive_buffer_bytes,
+                connection_rate_limit,
             } => {
                 let source = SyslogTcpSource {
                     max_length: self.max_length,
@@ -120,6 +123,7 @@ impl SourceConfig for SyslogConfig {
                     shutdown_secs,
                     tls,
                     receive_buffer_bytes,
+                    connection_rate_limit,
                     cx.shutdown,
                     cx.out,
                 )
@@ -196,6 +200,8 @@ enum State {
 struct SyslogDecoder {
     other: LinesCodec,
     octet_decoding: Option<State>,
+    /// Whether a frame using octet counting has been decoded.
+    octet_counted: bool,
 }
 
 impl SyslogDecoder {
@@ -203,6 +209,7 @@ impl SyslogDecoder {
         Self {
             other: LinesCodec::new_with_max_length(max_length),
             octet_decoding: None,
+            octet_counted: false,
         }
     }
 
@@ -241,7 +248,7 @@ impl SyslogDecoder {
                 // We have a certain number of chars to discard.
                 // There aren't enough in this frame so we need to discard
                 // The entire frame and adjust the amount to discard accordingly.
-                self.octet_decoding = Some(State::Discarding(src.len() - chars));
+                self.octet_decoding = Some(State::Discarding(chars - src.len()));
                 src.advance(src.len());
                 Ok(None)
             }
@@ -315,6 +322,7 @@ impl SyslogDecoder {
                     // We have managed to read the entire message as valid UTF8!
                     src.advance(to);
                     self.octet_decoding = None;
+                    self.octet_counted = true;
                     Ok(Some(s))
                 } else {
                     // We have an acceptable number of bytes in this message, but all the data
@@ -355,7 +363,17 @@ impl SyslogDecoder {
         &mut self,
         src: &mut BytesMut,
     ) -> Option<Result<Option<String>, LinesCodecError>> {
-        if let Some(&first_byte) = src.get(0) {
+        if self.octet_decoding.is_none() && self.octet_counted {
+            // Some senders end octet counted frames with a newline anyway,
+            // which isn't part of the next frame.
+            let newlines = src
+                .iter()
+                .take_while(|&&b| b == b'\n' || b == b'\r')
+                .count();
+            src.advance(newlines);
+        }
+
+        if let (None, Some(&first_byte)) = (self.octet_decoding, src.get(0)) {
             if (49..=57).contains(&first_byte) {
                 // First character is non zero number so we can assume that
                 // octet count framing is used.
@@ -548,10 +566,18 @@ fn insert_fields_from_syslog(event: &mut Event, parsed: Message<&str>) {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{config::log_schema, event::Event};
+    use crate::{
+        config::log_schema,
+        event::Event,
+        test_util::{next_addr, wait_for_tcp},
+        tls::{self, TlsOptions},
+    };
     use bytes::BufMut;
     use chrono::prelude::*;
+    use openssl::ssl::{SslConnector, SslFiletype, SslMethod};
     use shared::assert_event_data_eq;
+    use std::{pin::Pin, time::Duration};
+    use tokio::{io::AsyncWriteExt, net::TcpStream, time::timeout};
 
     #[test]
     fn generate_config() {
@@ -592,6 +618,34 @@ mod test {
         assert_eq!(receive_buffer_bytes, Some(256));
     }
 
+    #[test]
+    fn config_tcp_with_connection_rate_limit() {
+        let config: SyslogConfig = toml::from_str(
+            r#"
+            mode = "tcp"
+            address = "127.0.0.1:1235"
+            connection_rate_limit.num = 100
+          "#,
+        )
+        .unwrap();
+
+        let connection_rate_limit = match config.mode {
+            Mode::Tcp {
+                connection_rate_limit,
+                ..
+            } => connection_rate_limit,
+            _ => panic!("expected Mode::Tcp"),
+        };
+
+        assert_eq!(
+            connection_rate_limit,
+            Some(ConnectionRateLimit {
+                num: 100,
+                duration_secs: 1
+            })
+        );
+    }
+
     #[test]
     fn config_tcp_keepalive_empty() {
         let config: SyslogConfig = toml::from_str(
@@ -1024,4 +1078,105 @@ mod test {
         assert!(result.is_err());
         assert_eq!(b"32 something valid"[..], buffer);
     }
+
+    #[test]
+    fn octet_decode_discards_exceeded_frame_length_across_frames() {
+        let mut decoder = SyslogDecoder::new(16);
+        let mut buffer = BytesMut::with_capacity(32);
+
+        buffer.put(&b"26 abc"[..]);
+        assert_eq!(Ok(None), decoder.decode(&mut buffer).map_err(|_| false));
+        assert_eq!(Ok(None), decoder.decode(&mut buffer).map_err(|_| false));
+        assert_eq!(decoder.octet_decoding, Some(State::Discarding(23)));
+
+        buffer.put(&b"defghijklmnopqrstuvwxyz5 valid"[..]);
+        assert!(decoder.decode(&mut buffer).is_err());
+        assert_eq!(
+            Ok(Some("valid".to_string())),
+            decoder.decode(&mut buffer).map_err(|_| false)
+        );
+    }
+
+    #[test]
+    fn octet_decode_skips_newlines_between_frames() {
+        let mut decoder = SyslogDecoder::new(16);
+        let mut buffer = BytesMut::with_capacity(32);
+
+        buffer.put(&b"5 first\n6 second\r\n"[..]);
+        assert_eq!(
+            Ok(Some("first".to_string())),
+            decoder.decode(&mut buffer).map_err(|_| false)
+        );
+        assert_eq!(
+            Ok(Some("second".to_string())),
+            decoder.decode(&mut buffer).map_err(|_| false)
+        );
+        assert_eq!(Ok(None), decoder.decode(&mut buffer).map_err(|_| false));
+        assert!(buffer.is_empty());
+    }
+
+    async fn send_octet_counted_tls(addr: SocketAddr, client_certificate: bool, message: &str) {
+        let stream = TcpStream::connect(&addr).await.unwrap();
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_ca_file(tls::TEST_PEM_CA_PATH).unwrap();
+        if client_certificate {
+            connector
+                .set_certificate_file(tls::TEST_PEM_CRT_PATH, SslFiletype::PEM)
+                .unwrap();
+            connector
+                .set_private_key_file(tls::TEST_PEM_KEY_PATH, SslFiletype::PEM)
+                .unwrap();
+        }
+        let ssl = connector
+            .build()
+            .configure()
+            .unwrap()
+            .into_ssl("localhost")
+            .unwrap();
+
+        // Under TLS 1.3 a rejected client certificate may only show up after
+        // the handshake, so errors past this point are expected too.
+        let mut stream = tokio_openssl::SslStream::new(ssl, stream).unwrap();
+        if Pin::new(&mut stream).connect().await.is_ok() {
+            let frame = format!("{} {}", message.len(), message);
+            let _ = stream.write_all(frame.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn tcp_with_mutual_tls() {
+        let (tx, mut rx) = Pipeline::new_test();
+        let addr = next_addr();
+
+        let config = SyslogConfig::from_mode(Mode::Tcp {
+            address: addr.into(),
+            keepalive: None,
+            tls: Some(TlsConfig {
+                enabled: Some(true),
+                options: TlsOptions {
+                    verify_certificate: Some(true),
+                    ..TlsOptions::test_options()
+                },
+            }),
+            receive_buffer_bytes: None,
+            connection_rate_limit: None,
+        });
+        let server = config.build(SourceContext::new_test(tx)).await.unwrap();
+        tokio::spawn(server);
+        wait_for_tcp(addr).await;
+
+        send_octet_counted_tls(addr, false, "<13>1 - host app - - - anonymous").await;
+        send_octet_counted_tls(addr, true, "<13>1 - host app - - - authenticated").await;
+
+        let event = rx.next().await.unwrap();
+        assert_eq!(
+            event.as_log()[log_schema().message_key()],
+            "authenticated".into()
+        );
+        assert!(timeout(Duration::from_millis(500), rx.next())
+            .await
+            .is_err());
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{
+    avro::AvroDecoder,
     config::log_schema,
-    event::{Event, LogEvent},
+    event::{Event, LogEvent, Value},
+    protobuf::MessageDecoder,
     sources::util::http::ErrorMessage,
 };
 use bytes::{Bytes, BytesMut};
@@ -20,6 +22,10 @@ pub enum Encoding {
     Ndjson,
     Json,
     Binary,
+    /// Avro object container files, holding the schema of their records.
+    Avro,
+    /// A single protobuf message, of the type of the `protobuf` option.
+    Protobuf,
 }
 
 fn body_to_lines(buf: Bytes) -> impl Iterator<Item = Result<Bytes, ErrorMessage>> {
@@ -44,7 +50,13 @@ fn body_to_lines(buf: Bytes) -> impl Iterator<Item = Result<Bytes, ErrorMessage>
     })
 }
 
-pub fn decode_body(body: Bytes, enc: Encoding) -> Result<Vec<Event>, ErrorMessage> {
+/// Decodes the body as events. The `protobuf` decoder is required with the
+/// `Protobuf` encoding.
+pub fn decode_body(
+    body: Bytes,
+    enc: Encoding,
+    protobuf: Option<&MessageDecoder>,
+) -> Result<Vec<Event>, ErrorMessage> {
     match enc {
         Encoding::Text => body_to_lines(body)
             .map(|r| Ok(LogEvent::from(r?).into()))
@@ -62,6 +74,41 @@ pub fn decode_body(body: Bytes, enc: Encoding) -> Result<Vec<Event>, ErrorMessag
             json_parse_array_of_object(parsed_json)
         }
         Encoding::Binary => Ok(vec![LogEvent::from(body).into()]),
+        Encoding::Avro => AvroDecoder::default()
+            .decode_container(&body)
+            .map_err(|error| {
+                ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad Avro: {}", error))
+            })?
+            .into_iter()
+            .map(avro_parse_record)
+            .collect::<Result<_, _>>(),
+        Encoding::Protobuf => {
+            let mut log = protobuf
+                .expect("protobuf encoding without a decoder")
+                .decode(&body)
+                .map_err(|error| {
+                    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad protobuf: {}", error))
+                })?;
+            log.try_insert(log_schema().timestamp_key(), Utc::now());
+            Ok(vec![log.into()])
+        }
+    }
+}
+
+fn avro_parse_record(value: Value) -> Result<Event, ErrorMessage> {
+    match value {
+        Value::Map(map) => {
+            let mut log = LogEvent::default();
+            log.insert(log_schema().timestamp_key(), Utc::now()); // Add timestamp
+            for (k, v) in map {
+                log.insert_flat(k, v);
+            }
+            Ok(log.into())
+        }
+        _ => Err(ErrorMessage::new(
+            StatusCode::BAD_REQUEST,
+            "Bad Avro: expected records".to_owned(),
+        )),
     }
 }
 
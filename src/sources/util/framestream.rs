@@ -1,10 +1,14 @@
+use super::tcp::make_listener;
 use crate::{
     event::Event,
     internal_events::{
-        SocketEventReceived, SocketMode, UnixSocketError, UnixSocketFileDeleteFailed,
+        SocketEventReceived, SocketMode, TcpSocketConnectionError, TcpSocketError, UnixSocketError,
+        UnixSocketFileDeleteFailed,
     },
     shutdown::ShutdownSignal,
-    sources::Source,
+    sources::{util::SocketListenAddr, Source},
+    tcp::TcpKeepaliveConfig,
+    tls::MaybeTlsSettings,
     Pipeline,
 };
 use bytes::{Buf, Bytes, BytesMut};
@@ -14,6 +18,7 @@ use futures::{
     sink::{Sink, SinkExt},
     stream::{self, StreamExt, TryStreamExt},
 };
+use listenfd::ListenFd;
 #[cfg(unix)]
 use std::os::unix::{fs::PermissionsExt, io::AsRawFd};
 use std::{
@@ -28,10 +33,15 @@ use std::{
     thread,
     time::Duration,
 };
-use tokio::{self, net::UnixListener, task::JoinHandle};
+use tokio::{
+    self,
+    io::{AsyncRead, AsyncWrite},
+    net::UnixListener,
+    task::JoinHandle,
+};
 use tokio_stream::wrappers::UnixListenerStream;
 use tokio_util::codec::{length_delimited, Framed};
-use tracing::field;
+use tracing::{field, Span};
 use tracing_futures::Instrument;
 
 const FSTRM_CONTROL_FRAME_LENGTH_MAX: usize = 512;
@@ -43,6 +53,7 @@ struct FrameStreamReader {
     response_sink: Mutex<FrameStreamSink>,
     expected_content_type: String,
     state: FrameStreamState,
+    mode: SocketMode,
 }
 
 struct FrameStreamState {
@@ -135,11 +146,16 @@ fn advance_u32(b: &mut Bytes) -> Result<u32, ()> {
 }
 
 impl FrameStreamReader {
-    pub fn new(response_sink: FrameStreamSink, expected_content_type: String) -> Self {
+    pub fn new(
+        response_sink: FrameStreamSink,
+        expected_content_type: String,
+        mode: SocketMode,
+    ) -> Self {
         FrameStreamReader {
             response_sink: Mutex::new(response_sink),
             expected_content_type,
             state: FrameStreamState::new(),
+            mode,
         }
     }
 
@@ -157,7 +173,7 @@ impl FrameStreamReader {
             if self.state.control_state == ControlState::ReadingData {
                 emit!(SocketEventReceived {
                     byte_size: frame.len(),
-                    mode: SocketMode::Unix,
+                    mode: self.mode,
                 });
                 Some(frame) //return data frame
             } else {
@@ -346,12 +362,8 @@ pub trait FrameHandler {
     fn content_type(&self) -> String;
     fn max_frame_length(&self) -> usize;
     fn handle_event(&self, received_from: Option<Bytes>, frame: Bytes) -> Option<Event>;
-    fn socket_path(&self) -> PathBuf;
     fn multithreaded(&self) -> bool;
     fn max_frame_handling_tasks(&self) -> u32;
-    fn socket_file_mode(&self) -> Option<u32>;
-    fn socket_receive_buffer_size(&self) -> Option<usize>;
-    fn socket_send_buffer_size(&self) -> Option<usize>;
     fn host_key(&self) -> String;
     fn timestamp_key(&self) -> String;
 }
@@ -363,11 +375,13 @@ pub trait FrameHandler {
  **/
 pub fn build_framestream_unix_source(
     frame_handler: impl FrameHandler + Send + Sync + Clone + 'static,
+    path: PathBuf,
+    socket_file_mode: Option<u32>,
+    socket_receive_buffer_size: Option<usize>,
+    socket_send_buffer_size: Option<usize>,
     shutdown: ShutdownSignal,
     out: Pipeline,
 ) -> crate::Result<Source> {
-    let path = frame_handler.socket_path();
-
     let out = out.sink_map_err(|e| error!("Error sending event: {:?}.", e));
 
     //check if the path already exists (and try to delete it)
@@ -387,7 +401,7 @@ pub fn build_framestream_unix_source(
     let listener = UnixListener::bind(&path)?;
 
     // system's 'net.core.rmem_max' might have to be changed if socket receive buffer is not updated properly
-    if let Some(socket_receive_buffer_size) = frame_handler.socket_receive_buffer_size() {
+    if let Some(socket_receive_buffer_size) = socket_receive_buffer_size {
         let _ = nix::sys::socket::setsockopt(
             listener.as_raw_fd(),
             nix::sys::socket::sockopt::RcvBuf,
@@ -402,7 +416,7 @@ pub fn build_framestream_unix_source(
     }
 
     // system's 'net.core.wmem_max' might have to be changed if socket send buffer is not updated properly
-    if let Some(socket_send_buffer_size) = frame_handler.socket_send_buffer_size() {
+    if let Some(socket_send_buffer_size) = socket_send_buffer_size {
         let _ = nix::sys::socket::setsockopt(
             listener.as_raw_fd(),
             nix::sys::socket::sockopt::SndBuf,
@@ -417,7 +431,7 @@ pub fn build_framestream_unix_source(
     }
 
     // the permissions to unix socket are restricted from 0o700 to 0o777, which are 448 and 511 in decimal
-    if let Some(socket_permission) = frame_handler.socket_file_mode() {
+    if let Some(socket_permission) = socket_file_mode {
         if !(448..=511).contains(&socket_permission) {
             return Err(format!(
                 "Invalid Socket permission {:#o}. Must between 0o700 and 0o777.",
@@ -454,10 +468,7 @@ pub fn build_framestream_unix_source(
                 Ok(s) => s,
             };
             let peer_addr = socket.peer_addr().ok();
-            let content_type = frame_handler.content_type();
             let listen_path = path.clone();
-            let mut event_sink = out.clone();
-            let active_task_nums_ = Arc::clone(&active_parsing_task_nums);
 
             let span = info_span!("connection");
             let path = if let Some(addr) = peer_addr {
@@ -473,70 +484,22 @@ pub fn build_framestream_unix_source(
             let received_from: Option<Bytes> =
                 path.map(|p| p.to_string_lossy().into_owned().into());
 
-            let (sock_sink, sock_stream) = Framed::new(
+            handle_stream(
+                frame_handler.clone(),
+                shutdown.clone(),
                 socket,
-                length_delimited::Builder::new()
-                    .max_frame_length(frame_handler.max_frame_length())
-                    .new_codec(),
-            )
-            .split();
-            let mut fs_reader = FrameStreamReader::new(Box::new(sock_sink), content_type);
-            let frame_handler_copy = frame_handler.clone();
-            let frames = sock_stream
-                .take_until(shutdown.clone())
-                .map_err(move |error| {
+                SocketMode::Unix,
+                received_from,
+                out.clone(),
+                Arc::clone(&active_parsing_task_nums),
+                move |error| {
                     emit!(UnixSocketError {
                         error,
                         path: &listen_path,
-                    });
-                })
-                .filter_map(move |frame| {
-                    future::ready(match frame {
-                        Ok(f) => fs_reader.handle_frame(Bytes::from(f)),
-                        Err(_) => None,
                     })
-                });
-            if !frame_handler.multithreaded() {
-                let mut events = frames.filter_map(move |f| {
-                    future::ready(
-                        frame_handler_copy
-                            .handle_event(received_from.clone(), f)
-                            .map(Ok),
-                    )
-                });
-
-                let handler = async move {
-                    let _ = event_sink.send_all(&mut events).await;
-                    info!("Finished sending.");
-                };
-                tokio::spawn(handler.instrument(span));
-            } else {
-                let handler = async move {
-                    frames
-                        .for_each(move |f| {
-                            future::ready({
-                                let max_frame_handling_tasks =
-                                    frame_handler_copy.max_frame_handling_tasks();
-                                let f_handler = frame_handler_copy.clone();
-                                let received_from_copy = received_from.clone();
-                                let event_sink_copy = event_sink.clone();
-                                let active_task_nums_copy = Arc::clone(&active_task_nums_);
-
-                                spawn_event_handling_tasks(
-                                    f,
-                                    f_handler,
-                                    event_sink_copy,
-                                    received_from_copy,
-                                    active_task_nums_copy,
-                                    max_frame_handling_tasks,
-                                );
-                            })
-                        })
-                        .await;
-                    info!("Finished sending.");
-                };
-                tokio::spawn(handler.instrument(span));
-            }
+                },
+                span,
+            );
         }
 
         // Cleanup
@@ -553,6 +516,183 @@ pub fn build_framestream_unix_source(
     Ok(Box::pin(fut))
 }
 
+/**
+ * Same as build_framestream_unix_source, but accepts framestream
+ * connections on a TCP listener.
+ **/
+pub fn build_framestream_tcp_source(
+    frame_handler: impl FrameHandler + Send + Sync + Clone + 'static,
+    address: SocketListenAddr,
+    keepalive: Option<TcpKeepaliveConfig>,
+    tls: MaybeTlsSettings,
+    receive_buffer_bytes: Option<usize>,
+    shutdown: ShutdownSignal,
+    out: Pipeline,
+) -> crate::Result<Source> {
+    let out = out.sink_map_err(|e| error!("Error sending event: {:?}.", e));
+    let listenfd = ListenFd::from_env();
+
+    let fut = async move {
+        let listener = match make_listener(address, listenfd, &tls).await {
+            None => return Err(()),
+            Some(listener) => listener,
+        };
+        let active_parsing_task_nums = Arc::new(AtomicU32::new(0));
+
+        info!(
+            message = "Listening...",
+            addr = %listener
+                .local_addr()
+                .map(SocketListenAddr::SocketAddr)
+                .unwrap_or(address),
+            r#type = "tcp"
+        );
+
+        let mut stream = listener.accept_stream().take_until(shutdown.clone());
+        while let Some(socket) = stream.next().await {
+            let mut socket = match socket {
+                Err(e) => {
+                    error!("Failed to accept socket; error = {:?}.", e);
+                    continue;
+                }
+                Ok(s) => s,
+            };
+            let peer_addr = socket.peer_addr().ip().to_string();
+            let span = info_span!("connection", %peer_addr);
+            let received_from = Some(Bytes::from(peer_addr));
+
+            let frame_handler = frame_handler.clone();
+            let mut shutdown = shutdown.clone();
+            let out = out.clone();
+            let active_task_nums = Arc::clone(&active_parsing_task_nums);
+            tokio::spawn(
+                async move {
+                    tokio::select! {
+                        result = socket.handshake() => {
+                            if let Err(error) = result {
+                                emit!(TcpSocketConnectionError { error });
+                                return;
+                            }
+                        },
+                        _ = &mut shutdown => return,
+                    };
+
+                    if let Some(keepalive) = keepalive {
+                        if let Err(error) = socket.set_keepalive(keepalive) {
+                            warn!(message = "Failed configuring TCP keepalive.", %error);
+                        }
+                    }
+                    if let Some(receive_buffer_bytes) = receive_buffer_bytes {
+                        if let Err(error) = socket.set_receive_buffer_bytes(receive_buffer_bytes) {
+                            warn!(
+                                message = "Failed configuring receive buffer size on TCP socket.",
+                                %error
+                            );
+                        }
+                    }
+
+                    handle_stream(
+                        frame_handler,
+                        shutdown,
+                        socket,
+                        SocketMode::Tcp,
+                        received_from,
+                        out,
+                        active_task_nums,
+                        |error| emit!(TcpSocketError { error }),
+                        Span::current(),
+                    );
+                }
+                .instrument(span),
+            );
+        }
+
+        Ok(())
+    };
+
+    Ok(Box::pin(fut))
+}
+
+/**
+ * Reads the frames of a framestream connection, answering its control
+ * frames and turning its data frames into events.
+ **/
+fn handle_stream<T, S>(
+    frame_handler: impl FrameHandler + Send + Sync + Clone + 'static,
+    shutdown: ShutdownSignal,
+    socket: T,
+    mode: SocketMode,
+    received_from: Option<Bytes>,
+    mut event_sink: S,
+    active_parsing_task_nums: Arc<AtomicU32>,
+    on_error: impl Fn(std::io::Error) + Send + 'static,
+    span: Span,
+) where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    S: Sink<Event, Error = ()> + Clone + Send + Unpin + 'static,
+{
+    let (sock_sink, sock_stream) = Framed::new(
+        socket,
+        length_delimited::Builder::new()
+            .max_frame_length(frame_handler.max_frame_length())
+            .new_codec(),
+    )
+    .split();
+    let mut fs_reader =
+        FrameStreamReader::new(Box::new(sock_sink), frame_handler.content_type(), mode);
+    let frame_handler_copy = frame_handler.clone();
+    let frames = sock_stream
+        .take_until(shutdown)
+        .map_err(on_error)
+        .filter_map(move |frame| {
+            future::ready(match frame {
+                Ok(f) => fs_reader.handle_frame(Bytes::from(f)),
+                Err(_) => None,
+            })
+        });
+    if !frame_handler.multithreaded() {
+        let mut events = frames.filter_map(move |f| {
+            future::ready(
+                frame_handler_copy
+                    .handle_event(received_from.clone(), f)
+                    .map(Ok),
+            )
+        });
+
+        let handler = async move {
+            let _ = event_sink.send_all(&mut events).await;
+            info!("Finished sending.");
+        };
+        tokio::spawn(handler.instrument(span));
+    } else {
+        let handler = async move {
+            frames
+                .for_each(move |f| {
+                    future::ready({
+                        let max_frame_handling_tasks =
+                            frame_handler_copy.max_frame_handling_tasks();
+                        let f_handler = frame_handler_copy.clone();
+                        let received_from_copy = received_from.clone();
+                        let event_sink_copy = event_sink.clone();
+                        let active_task_nums_copy = Arc::clone(&active_parsing_task_nums);
+
+                        spawn_event_handling_tasks(
+                            f,
+                            f_handler,
+                            event_sink_copy,
+                            received_from_copy,
+                            active_task_nums_copy,
+                            max_frame_handling_tasks,
+                        );
+                    })
+                })
+                .await;
+            info!("Finished sending.");
+        };
+        tokio::spawn(handler.instrument(span));
+    }
+}
+
 fn spawn_event_handling_tasks<S>(
     event_data: Bytes,
     event_handler: impl FrameHandler + Send + Sync + 'static,
@@ -589,12 +729,13 @@ fn wait_for_task_quota(active_task_nums: &Arc<AtomicU32>, max_tasks: u32) {
 #[cfg(test)]
 mod test {
     use super::{
-        build_framestream_unix_source, spawn_event_handling_tasks, ControlField, ControlHeader,
-        FrameHandler,
+        build_framestream_tcp_source, build_framestream_unix_source, spawn_event_handling_tasks,
+        ControlField, ControlHeader, FrameHandler,
     };
     use crate::{
         config::log_schema,
-        test_util::{collect_n, collect_n_stream},
+        test_util::{collect_n, collect_n_stream, next_addr, wait_for_tcp},
+        tls::MaybeTlsSettings,
     };
     use crate::{event::Event, shutdown::SourceShutdownCoordinator, Pipeline};
     use bytes::{buf::Buf, Bytes, BytesMut};
@@ -614,7 +755,7 @@ mod test {
     };
     use tokio::{
         self,
-        net::UnixStream,
+        net::{TcpStream, UnixStream},
         task::JoinHandle,
         time::{Duration, Instant},
     };
@@ -624,12 +765,8 @@ mod test {
     struct MockFrameHandler<F: Send + Sync + Clone + FnOnce() + 'static> {
         content_type: String,
         max_frame_length: usize,
-        socket_path: PathBuf,
         multithreaded: bool,
         max_frame_handling_tasks: u32,
-        socket_file_mode: Option<u32>,
-        socket_receive_buffer_size: Option<usize>,
-        socket_send_buffer_size: Option<usize>,
         extra_task_handling_routine: F,
         host_key: String,
         timestamp_key: String,
@@ -640,12 +777,8 @@ mod test {
             Self {
                 content_type,
                 max_frame_length: bytesize::kib(100u64) as usize,
-                socket_path: tempfile::tempdir().unwrap().into_path().join("unix_test"),
                 multithreaded,
                 max_frame_handling_tasks: 0,
-                socket_file_mode: None,
-                socket_receive_buffer_size: None,
-                socket_send_buffer_size: None,
                 extra_task_handling_routine: extra_routine,
                 host_key: "test_framestream".to_string(),
                 timestamp_key: "my_timestamp".to_string(),
@@ -675,9 +808,6 @@ mod test {
             Some(event)
         }
 
-        fn socket_path(&self) -> PathBuf {
-            self.socket_path.clone()
-        }
         fn multithreaded(&self) -> bool {
             self.multithreaded
         }
@@ -685,18 +815,6 @@ mod test {
             self.max_frame_handling_tasks
         }
 
-        fn socket_file_mode(&self) -> Option<u32> {
-            self.socket_file_mode
-        }
-
-        fn socket_receive_buffer_size(&self) -> Option<usize> {
-            self.socket_receive_buffer_size
-        }
-
-        fn socket_send_buffer_size(&self) -> Option<usize> {
-            self.socket_send_buffer_size
-        }
-
         fn host_key(&self) -> String {
             self.host_key.clone()
         }
@@ -715,11 +833,19 @@ mod test {
         JoinHandle<Result<(), ()>>,
         SourceShutdownCoordinator,
     ) {
-        let socket_path = frame_handler.socket_path();
+        let socket_path = tempfile::tempdir().unwrap().into_path().join("unix_test");
         let mut shutdown = SourceShutdownCoordinator::default();
         let (shutdown_signal, _) = shutdown.register_source(source_name);
-        let server = build_framestream_unix_source(frame_handler, shutdown_signal, pipeline)
-            .expect("Failed to build framestream unix source.");
+        let server = build_framestream_unix_source(
+            frame_handler,
+            socket_path.clone(),
+            None,
+            None,
+            None,
+            shutdown_signal,
+            pipeline,
+        )
+        .expect("Failed to build framestream unix source.");
 
         let join_handle = tokio::spawn(server);
 
@@ -1065,6 +1191,61 @@ mod test {
         let _ = source_handle.await.unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tcp_framestream() {
+        let source_name = "test_source";
+        let (tx, rx) = Pipeline::new_test();
+        let addr = next_addr();
+        let mut shutdown = SourceShutdownCoordinator::default();
+        let (shutdown_signal, _) = shutdown.register_source(source_name);
+        let server = build_framestream_tcp_source(
+            create_frame_handler(false),
+            addr.into(),
+            None,
+            MaybeTlsSettings::Raw(()),
+            None,
+            shutdown_signal,
+            tx,
+        )
+        .expect("Failed to build framestream tcp source.");
+        let source_handle = tokio::spawn(server);
+        wait_for_tcp(addr).await;
+
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let (mut sock_sink, mut sock_stream) =
+            Framed::new(socket, length_delimited::Builder::new().new_codec()).split();
+
+        //1 - send READY frame (with content_type)
+        let content_type = Bytes::from(&b"test_content"[..]);
+        let ready_msg =
+            create_control_frame_with_content(ControlHeader::Ready, vec![content_type.clone()]);
+        send_control_frame(&mut sock_sink, ready_msg).await;
+
+        //2 - wait for ACCEPT frame
+        let mut frame_vec = collect_n_stream(&mut sock_stream, 2).await;
+        assert_eq!(frame_vec[0].as_ref().unwrap().len(), 0);
+        assert_accept_frame(frame_vec[1].as_mut().unwrap(), content_type);
+
+        //3 - send START frame and data
+        send_control_frame(&mut sock_sink, create_control_frame(ControlHeader::Start)).await;
+        send_data_frames(&mut sock_sink, vec![Ok(Bytes::from("hello"))]).await;
+        let events = collect_n(rx, 1).await;
+
+        //4 - send STOP frame
+        send_control_frame(&mut sock_sink, create_control_frame(ControlHeader::Stop)).await;
+
+        assert_eq!(
+            events[0].as_log()[&log_schema().message_key()],
+            "hello".into(),
+        );
+        assert_eq!(events[0].as_log()["test_framestream"], "127.0.0.1".into(),);
+
+        std::mem::drop(sock_stream);
+
+        signal_shutdown(source_name, &mut shutdown).await;
+        let _ = source_handle.await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_spawn_event_handling_tasks() {
         let (tx, rx) = Pipeline::new_test();
@@ -19,7 +19,8 @@ use warp::{
     filters::{path::FullPath, path::Tail, BoxedFilter},
     http::{HeaderMap, StatusCode},
     reject::Rejection,
-    Filter,
+    reply::Response,
+    Filter, Reply,
 };
 
 #[cfg(any(feature = "sources-http", feature = "sources-heroku_logs"))]
@@ -150,6 +151,9 @@ pub fn decode(header: &Option<String>, mut body: Bytes) -> Result<Bytes, ErrorMe
                     .decompress_vec(&body)
                     .map_err(|error| handle_decode_error(encoding, error))?
                     .into(),
+                "zstd" => zstd::stream::decode_all(body.reader())
+                    .map_err(|error| handle_decode_error(encoding, error))?
+                    .into(),
                 encoding => {
                     return Err(ErrorMessage::new(
                         StatusCode::UNSUPPORTED_MEDIA_TYPE,
@@ -184,6 +188,11 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         path: &str,
     ) -> Result<Vec<Event>, ErrorMessage>;
 
+    /// The response to a request once its events are delivered.
+    fn build_response(&self, _events: &[Event]) -> Response {
+        warp::reply().into_response()
+    }
+
     fn run(
         self,
         address: SocketAddr,
@@ -240,7 +249,10 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                             .and_then(|body| {
                                 let body_len = body.len();
                                 self.build_events(body, headers, query_parameters, path.as_str())
-                                    .map(|events| (events, body_len))
+                                    .map(|events| {
+                                        let response = self.build_response(&events);
+                                        (events, body_len, response)
+                                    })
                             });
 
                         handle_request(events, acknowledgements, out.clone())
@@ -278,12 +290,12 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
 }
 
 async fn handle_request(
-    events: Result<(Vec<Event>, usize), ErrorMessage>,
+    events: Result<(Vec<Event>, usize, Response), ErrorMessage>,
     acknowledgements: bool,
     mut out: Pipeline,
 ) -> Result<impl warp::Reply, Rejection> {
     match events {
-        Ok((mut events, body_size)) => {
+        Ok((mut events, body_size, response)) => {
             emit!(HttpEventsReceived {
                 events_count: events.len(),
                 byte_size: body_size,
@@ -305,7 +317,7 @@ async fn handle_request(
                     error!(message = "Tried to send the following event.", %error);
                     warp::reject::custom(RejectShuttingDown)
                 })
-                .and_then(|_| handle_batch_status(receiver))
+                .and_then(|_| handle_batch_status(receiver, response))
                 .await
         }
         Err(error) => {
@@ -320,11 +332,12 @@ async fn handle_request(
 
 async fn handle_batch_status(
     receiver: Option<BatchStatusReceiver>,
+    response: Response,
 ) -> Result<impl warp::Reply, Rejection> {
     match receiver {
-        None => Ok(warp::reply()),
+        None => Ok(response),
         Some(receiver) => match receiver.await {
-            BatchStatus::Delivered => Ok(warp::reply()),
+            BatchStatus::Delivered => Ok(response),
             BatchStatus::Errored => Err(warp::reject::custom(ErrorMessage::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Error delivering contents to sink".into(),
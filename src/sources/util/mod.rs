@@ -10,7 +10,7 @@ mod http;
 pub mod multiline_config;
 #[cfg(all(feature = "sources-utils-tls", feature = "listenfd"))]
 mod tcp;
-#[cfg(all(unix, feature = "sources-socket"))]
+#[cfg(all(unix, any(feature = "sources-socket", feature = "sources-statsd")))]
 mod unix_datagram;
 #[cfg(all(unix, feature = "sources-utils-unix"))]
 mod unix_stream;
@@ -26,8 +26,8 @@ pub(crate) use self::http::{ErrorMessage, HttpSource, HttpSourceAuthConfig};
 pub use encoding_config::EncodingConfig;
 pub use multiline_config::MultilineConfig;
 #[cfg(all(feature = "sources-utils-tls", feature = "listenfd"))]
-pub use tcp::{IsErrorFatal as TcpIsErrorFatal, SocketListenAddr, TcpSource};
-#[cfg(all(unix, feature = "sources-socket",))]
+pub use tcp::{ConnectionRateLimit, IsErrorFatal as TcpIsErrorFatal, SocketListenAddr, TcpSource};
+#[cfg(all(unix, any(feature = "sources-socket", feature = "sources-statsd")))]
 pub use unix_datagram::build_unix_datagram_source;
 #[cfg(all(unix, feature = "sources-utils-unix",))]
 pub use unix_stream::build_unix_stream_source;
@@ -16,12 +16,12 @@ use std::{fmt, io, mem::drop, net::SocketAddr, time::Duration};
 use tokio::{
     io::AsyncWriteExt,
     net::{TcpListener, TcpStream},
-    time::sleep,
+    time::{sleep, sleep_until, Instant},
 };
 use tokio_util::codec::{Decoder, FramedRead, LinesCodecError};
 use tracing_futures::Instrument;
 
-async fn make_listener(
+pub(crate) async fn make_listener(
     addr: SocketListenAddr,
     mut listenfd: ListenFd,
     tls: &MaybeTlsSettings,
@@ -86,6 +86,12 @@ where
         Bytes::new()
     }
 
+    /// Returns the bytes written to each connection before anything is read
+    /// from it, as for protocols where the server speaks first.
+    fn build_greeting(&self, _decoder: &Self::Decoder) -> Bytes {
+        Bytes::new()
+    }
+
     fn run(
         self,
         addr: SocketListenAddr,
@@ -93,6 +99,7 @@ where
         shutdown_timeout_secs: u64,
         tls: MaybeTlsSettings,
         receive_buffer_bytes: Option<usize>,
+        connection_rate_limit: Option<ConnectionRateLimit>,
         shutdown_signal: ShutdownSignal,
         out: Pipeline,
     ) -> crate::Result<crate::sources::Source> {
@@ -171,6 +178,7 @@ where
                                 socket,
                                 keepalive,
                                 receive_buffer_bytes,
+                                connection_rate_limit,
                                 source,
                                 tripwire,
                                 host,
@@ -194,6 +202,7 @@ async fn handle_stream<T>(
     mut socket: MaybeTlsIncomingStream<TcpStream>,
     keepalive: Option<TcpKeepaliveConfig>,
     receive_buffer_bytes: Option<usize>,
+    connection_rate_limit: Option<ConnectionRateLimit>,
     source: T,
     mut tripwire: BoxFuture<'static, ()>,
     host: Bytes,
@@ -226,7 +235,17 @@ async fn handle_stream<T>(
         }
     }
 
-    let mut reader = FramedRead::new(socket, source.decoder());
+    let decoder = source.decoder();
+    let greeting = source.build_greeting(&decoder);
+    if !greeting.is_empty() {
+        if let Err(error) = socket.write_all(&greeting).await {
+            emit!(TcpSendAckError { error });
+            return;
+        }
+    }
+
+    let mut reader = FramedRead::new(socket, decoder);
+    let mut rate_limiter = connection_rate_limit.map(ConnectionRateLimiter::new);
 
     loop {
         tokio::select! {
@@ -250,11 +269,15 @@ async fn handle_stream<T>(
             res = reader.next() => {
                 match res {
                     Some(Ok(frame)) => {
+                        if let Some(rate_limiter) = &mut rate_limiter {
+                            rate_limiter.acquire().await;
+                        }
+
                         let host = host.clone();
                         let ack = source.build_ack(&frame);
 
-                        if let Some(event) = source.build_event(frame, host) {
-                            match out.send(event).await {
+                        match source.build_event(frame, host) {
+                            Some(event) => match out.send(event).await {
                                 Ok(_) => {
                                     let stream = reader.get_mut();
                                     if let Err(error) = stream.write_all(&ack).await {
@@ -266,7 +289,17 @@ async fn handle_stream<T>(
                                     warn!("Failed to send event.");
                                     break;
                                 }
+                            },
+                            // Frames without an event may still be answered,
+                            // as in handshakes.
+                            None if !ack.is_empty() => {
+                                let stream = reader.get_mut();
+                                if let Err(error) = stream.write_all(&ack).await {
+                                    emit!(TcpSendAckError{ error });
+                                    break;
+                                }
                             }
+                            None => {}
                         }
                     }
                     Some(Err(error)) => {
@@ -286,6 +319,55 @@ async fn handle_stream<T>(
     }
 }
 
+/// Limits the frames read from each connection to `num` in every
+/// `duration_secs`. Reads are held back once the limit is reached, which
+/// pushes back on the sender rather than dropping its events.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionRateLimit {
+    pub num: u64,
+    #[serde(default = "default_rate_limit_duration_secs")]
+    pub duration_secs: u64,
+}
+
+const fn default_rate_limit_duration_secs() -> u64 {
+    1
+}
+
+#[derive(Debug)]
+struct ConnectionRateLimiter {
+    limit: ConnectionRateLimit,
+    window_start: Instant,
+    count: u64,
+}
+
+impl ConnectionRateLimiter {
+    fn new(limit: ConnectionRateLimit) -> Self {
+        Self {
+            limit,
+            window_start: Instant::now(),
+            count: 0,
+        }
+    }
+
+    async fn acquire(&mut self) {
+        let window_end = self.window_start + Duration::from_secs(self.limit.duration_secs);
+        if Instant::now() >= window_end {
+            self.window_start = Instant::now();
+            self.count = 0;
+        } else if self.count >= self.limit.num {
+            debug!(
+                message = "Connection rate limit reached, delaying reads.",
+                internal_log_rate_secs = 10
+            );
+            sleep_until(window_end).await;
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum SocketListenAddr {
@@ -360,4 +442,23 @@ mod test {
         let test: Config = toml::from_str(r#"addr="systemd#3""#).unwrap();
         assert_eq!(test.addr, SocketListenAddr::SystemdFd(2));
     }
+
+    #[tokio::test]
+    async fn connection_rate_limiter_delays_past_limit() {
+        tokio::time::pause();
+        let start = Instant::now();
+        let mut limiter = ConnectionRateLimiter::new(ConnectionRateLimit {
+            num: 2,
+            duration_secs: 5,
+        });
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
 }
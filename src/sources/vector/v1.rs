@@ -1,7 +1,10 @@
 use crate::{
     config::{DataType, GenerateConfig, Resource, SourceContext},
-    event::{proto, Event},
-    internal_events::{VectorEventReceived, VectorProtoDecodeError},
+    event::{
+        proto::{self, SCHEMA_VERSION},
+        Event,
+    },
+    internal_events::{VectorEventReceived, VectorEventUnsupported, VectorProtoDecodeError},
     sources::{
         util::{SocketListenAddr, TcpSource},
         Source,
@@ -13,6 +16,7 @@ use bytes::{Bytes, BytesMut};
 use getset::Setters;
 use prost::Message;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use tokio_util::codec::LengthDelimitedCodec;
 
 #[derive(Deserialize, Serialize, Debug, Clone, Setters)]
@@ -62,6 +66,7 @@ impl VectorConfig {
             self.shutdown_timeout_secs,
             tls,
             self.receive_buffer_bytes,
+            None,
             cx.shutdown,
             cx.out,
         )
@@ -93,11 +98,26 @@ impl TcpSource for VectorSource {
 
     fn build_event(&self, frame: BytesMut, _host: Bytes) -> Option<Event> {
         let byte_size = frame.len();
-        match proto::EventWrapper::decode(frame).map(Event::from) {
+        let wrapper = match proto::EncodedEventWrapper::decode(frame) {
+            Ok(wrapper) => wrapper,
+            Err(error) => {
+                emit!(VectorProtoDecodeError {
+                    error: error.into()
+                });
+                return None;
+            }
+        };
+
+        let version = wrapper.version;
+        match Event::try_from(wrapper) {
             Ok(event) => {
                 emit!(VectorEventReceived { byte_size });
                 Some(event)
             }
+            Err(_) if version > SCHEMA_VERSION => {
+                emit!(VectorEventUnsupported { version });
+                None
+            }
             Err(error) => {
                 emit!(VectorProtoDecodeError { error });
                 None
@@ -138,6 +158,7 @@ mod test {
         bytes::BytesMut,
         futures::SinkExt,
         prost::Message,
+        std::convert::TryFrom,
         tokio_util::codec::{FramedWrite, LengthDelimitedCodec},
     };
 
@@ -298,6 +319,6 @@ mod test {
         shutdown_down.await;
 
         let output = collect_ready(rx).await;
-        assert_event_data_eq!([Event::from(event)][..], output.as_slice());
+        assert_event_data_eq!([Event::try_from(event).unwrap()][..], output.as_slice());
     }
 }
@@ -1,23 +1,23 @@
 use crate::{
     config::SourceContext,
     config::{DataType, GenerateConfig, Resource},
+    internal_events::{VectorEventUnsupported, VectorProtoDecodeError},
     proto::vector as proto,
     shutdown::ShutdownSignalToken,
     sources::Source,
-    tls::{MaybeTlsIncomingStream, MaybeTlsSettings, TlsConfig},
+    tls::{MaybeTlsSettings, TlsConfig},
     Pipeline,
 };
 
 use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpStream;
-use tonic::{
-    transport::{server::Connected, Certificate, Server},
-    Request, Response, Status,
+use tonic::{transport::Server, Request, Response, Status};
+use vector_core::event::{
+    proto::SCHEMA_VERSION, BatchNotifier, BatchStatus, BatchStatusReceiver, Event,
 };
-use vector_core::event::{BatchNotifier, BatchStatus, BatchStatusReceiver, Event};
 
 #[derive(Debug, Clone)]
 pub struct Service {
@@ -35,7 +35,20 @@ impl proto::Service for Service {
             .into_inner()
             .events
             .into_iter()
-            .map(Event::from)
+            .filter_map(|wrapper| {
+                let version = wrapper.version;
+                match Event::try_from(wrapper) {
+                    Ok(event) => Some(event),
+                    Err(_) if version > SCHEMA_VERSION => {
+                        emit!(VectorEventUnsupported { version });
+                        None
+                    }
+                    Err(error) => {
+                        emit!(VectorProtoDecodeError { error });
+                        None
+                    }
+                }
+            })
             .collect();
 
         let receiver = self.acknowledgements.then(|| {
@@ -54,7 +67,9 @@ impl proto::Service for Service {
             .and_then(|_| handle_batch_status(receiver))
             .await?;
 
-        Ok(Response::new(proto::PushEventsResponse {}))
+        Ok(Response::new(proto::PushEventsResponse {
+            version: SCHEMA_VERSION,
+        }))
     }
 
     // TODO: figure out a way to determine if the current Vector instance is "healthy".
@@ -64,6 +79,7 @@ impl proto::Service for Service {
     ) -> Result<Response<proto::HealthCheckResponse>, Status> {
         let message = proto::HealthCheckResponse {
             status: proto::ServingStatus::Serving.into(),
+            version: SCHEMA_VERSION,
         };
 
         Ok(Response::new(message))
@@ -157,28 +173,3 @@ async fn run(
 
     Ok(())
 }
-
-#[derive(Clone)]
-pub struct MaybeTlsConnectInfo {
-    pub remote_addr: SocketAddr,
-    pub peer_certs: Option<Vec<Certificate>>,
-}
-
-impl Connected for MaybeTlsIncomingStream<TcpStream> {
-    type ConnectInfo = MaybeTlsConnectInfo;
-
-    fn connect_info(&self) -> Self::ConnectInfo {
-        MaybeTlsConnectInfo {
-            remote_addr: self.peer_addr(),
-            peer_certs: self
-                .ssl_stream()
-                .and_then(|s| s.ssl().peer_cert_chain())
-                .map(|s| {
-                    s.into_iter()
-                        .filter_map(|c| c.to_pem().ok())
-                        .map(Certificate::from_pem)
-                        .collect()
-                }),
-        }
-    }
-}
@@ -0,0 +1,252 @@
+mod subscription;
+
+use self::subscription::{Batch, Subscription};
+use crate::{
+    config::{DataType, SourceConfig, SourceContext, SourceDescription},
+    event::{BatchNotifier, BatchStatus, Event},
+    internal_events::WindowsEventLogBookmarkFailed,
+    shutdown::ShutdownSignal,
+    Pipeline,
+};
+use futures::{stream, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::{fs, sync::mpsc};
+
+const BOOKMARK_FILENAME: &str = "bookmark.xml";
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("At least one channel must be configured"))]
+    NoChannels,
+    #[snafu(display("Could not read bookmark {:?}: {}", path, source))]
+    ReadBookmark { path: PathBuf, source: io::Error },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowsEventLogConfig {
+    channels: Vec<String>,
+    /// An XPath filter of the events to read from every channel.
+    #[serde(default = "default_query")]
+    query: String,
+    /// Read the events already in the channels when there is no bookmark,
+    /// rather than only those logged from then on.
+    #[serde(default)]
+    read_existing_events: bool,
+    /// Format the message of each event with the resources of its provider,
+    /// as the Event Viewer shows it.
+    #[serde(default = "crate::serde::default_true")]
+    render_message: bool,
+    #[serde(default = "default_batch_size")]
+    batch_size: u32,
+    data_dir: Option<PathBuf>,
+}
+
+fn default_query() -> String {
+    "*".into()
+}
+
+const fn default_batch_size() -> u32 {
+    100
+}
+
+inventory::submit! {
+    SourceDescription::new::<WindowsEventLogConfig>("windows_eventlog")
+}
+
+impl_generate_config_from_default!(WindowsEventLogConfig);
+
+impl Default for WindowsEventLogConfig {
+    fn default() -> Self {
+        Self {
+            channels: vec!["Application".into(), "System".into()],
+            query: default_query(),
+            read_existing_events: false,
+            render_message: true,
+            batch_size: default_batch_size(),
+            data_dir: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "windows_eventlog")]
+impl SourceConfig for WindowsEventLogConfig {
+    async fn build(&self, cx: SourceContext) -> crate::Result<super::Source> {
+        if self.channels.is_empty() {
+            return Err(BuildError::NoChannels.into());
+        }
+
+        let data_dir = cx
+            .globals
+            .resolve_and_make_data_subdir(self.data_dir.as_ref(), &cx.id)?;
+        let bookmark_path = data_dir.join(BOOKMARK_FILENAME);
+        let bookmark = match fs::read_to_string(&bookmark_path).await {
+            Ok(bookmark) => Some(bookmark),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(source) => {
+                return Err(BuildError::ReadBookmark {
+                    path: bookmark_path,
+                    source,
+                }
+                .into())
+            }
+        };
+
+        let subscription = Subscription {
+            query: structured_query(&self.channels, &self.query),
+            bookmark,
+            read_existing_events: self.read_existing_events,
+            render_message: self.render_message,
+            batch_size: self.batch_size.max(1),
+        };
+
+        Ok(Box::pin(windows_eventlog_source(
+            subscription,
+            bookmark_path,
+            cx.shutdown,
+            cx.out,
+            cx.acknowledgements,
+        )))
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn source_type(&self) -> &'static str {
+        "windows_eventlog"
+    }
+}
+
+/// Select the events matching the query from every channel, in a single
+/// subscription so that one bookmark covers them all.
+fn structured_query(channels: &[String], query: &str) -> String {
+    let selects = channels
+        .iter()
+        .map(|channel| {
+            format!(
+                r#"<Select Path="{}">{}</Select>"#,
+                escape_xml(channel),
+                escape_xml(query)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<QueryList><Query Id="0">{}</Query></QueryList>"#,
+        selects
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+async fn windows_eventlog_source(
+    subscription: Subscription,
+    bookmark_path: PathBuf,
+    mut shutdown: ShutdownSignal,
+    mut out: Pipeline,
+    acknowledgements: bool,
+) -> Result<(), ()> {
+    // The Event Log API blocks, so events are read on their own thread,
+    // which stops once it sees the source has.
+    let (tx, mut rx) = mpsc::channel(1);
+    let stopped = Arc::new(AtomicBool::new(false));
+    let reader = tokio::task::spawn_blocking({
+        let stopped = Arc::clone(&stopped);
+        move || subscription.read(tx, &stopped)
+    });
+
+    loop {
+        let Batch { events, bookmark } = tokio::select! {
+            _ = &mut shutdown => break,
+            batch = rx.recv() => match batch {
+                Some(batch) => batch,
+                None => break,
+            },
+        };
+
+        let (batch, receiver) = if acknowledgements {
+            let (batch, receiver) = BatchNotifier::new_with_receiver();
+            (Some(batch), Some(receiver))
+        } else {
+            (None, None)
+        };
+        let events = events
+            .into_iter()
+            .map(|log| match &batch {
+                Some(batch) => Event::from(log.with_batch_notifier(batch)),
+                None => Event::from(log),
+            })
+            .collect::<Vec<_>>();
+        drop(batch);
+
+        if let Err(error) = out.send_all(&mut stream::iter(events).map(Ok)).await {
+            error!(message = "Error sending to sink.", %error);
+            break;
+        }
+
+        let status = match receiver {
+            Some(receiver) => receiver.await,
+            None => BatchStatus::Delivered,
+        };
+        // Leave the bookmark before undelivered events, so they are read
+        // again if the source restarts before a later batch is delivered.
+        if status == BatchStatus::Delivered {
+            if let Err(error) = save_bookmark(&bookmark_path, &bookmark).await {
+                emit!(WindowsEventLogBookmarkFailed { error });
+            }
+        }
+    }
+
+    stopped.store(true, Ordering::Relaxed);
+    drop(rx);
+    if let Err(error) = reader.await {
+        error!(message = "Event Log reader panicked.", %error);
+    }
+
+    Ok(())
+}
+
+/// Replace the bookmark whole, so a crash never leaves a partial one.
+async fn save_bookmark(path: &Path, bookmark: &str) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, bookmark).await?;
+    fs::rename(&temporary, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<WindowsEventLogConfig>();
+    }
+
+    #[test]
+    fn selects_from_every_channel() {
+        let query = structured_query(
+            &["Application".into(), "System".into()],
+            "*[System[Level<=2 and (EventID=1 or EventID=2)]]",
+        );
+
+        assert_eq!(
+            query,
+            r#"<QueryList><Query Id="0"><Select Path="Application">*[System[Level&lt;=2 and (EventID=1 or EventID=2)]]</Select><Select Path="System">*[System[Level&lt;=2 and (EventID=1 or EventID=2)]]</Select></Query></QueryList>"#
+        );
+    }
+}
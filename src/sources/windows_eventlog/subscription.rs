@@ -0,0 +1,470 @@
+use crate::{
+    config::log_schema,
+    event::LogEvent,
+    internal_events::{
+        WindowsEventLogEventReceived, WindowsEventLogReadFailed, WindowsEventLogRenderFailed,
+    },
+};
+use bytes::Bytes;
+use chrono::{DateTime, TimeZone, Utc};
+use std::{
+    collections::HashMap,
+    io, ptr, slice,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tokio::sync::mpsc;
+use winapi::{
+    shared::{
+        minwindef::{DWORD, FALSE, TRUE},
+        winerror::{ERROR_INSUFFICIENT_BUFFER, ERROR_NO_MORE_ITEMS, WAIT_TIMEOUT},
+    },
+    um::{
+        handleapi::CloseHandle,
+        synchapi::{CreateEventW, ResetEvent, WaitForSingleObject},
+        winbase::WAIT_OBJECT_0,
+        winevt::{
+            EvtClose, EvtCreateBookmark, EvtCreateRenderContext, EvtFormatMessage,
+            EvtFormatMessageEvent, EvtNext, EvtOpenPublisherMetadata, EvtRender, EvtRenderBookmark,
+            EvtRenderContextSystem, EvtRenderEventValues, EvtRenderEventXml, EvtSubscribe,
+            EvtSubscribeStartAfterBookmark, EvtSubscribeStartAtOldestRecord,
+            EvtSubscribeToFutureEvents, EvtSystemChannel, EvtSystemComputer, EvtSystemEventID,
+            EvtSystemEventRecordId, EvtSystemLevel, EvtSystemProviderName, EvtSystemTimeCreated,
+            EvtUpdateBookmark, EvtVarTypeByte, EvtVarTypeFileTime, EvtVarTypeString,
+            EvtVarTypeUInt16, EvtVarTypeUInt64, EVT_HANDLE, EVT_VARIANT,
+        },
+        winnt::HANDLE,
+    },
+};
+
+/// How long to wait for new events before checking whether the source
+/// has stopped.
+const POLL_TIMEOUT_MS: DWORD = 500;
+
+/// The number of 100ns intervals from 1601-01-01, the epoch of `FILETIME`,
+/// to the Unix epoch.
+const UNIX_EPOCH_INTERVALS: u64 = 116_444_736_000_000_000;
+
+pub(super) struct Subscription {
+    pub query: String,
+    pub bookmark: Option<String>,
+    pub read_existing_events: bool,
+    pub render_message: bool,
+    pub batch_size: u32,
+}
+
+/// The events read in one go, with the bookmark of the last of them.
+pub(super) struct Batch {
+    pub events: Vec<LogEvent>,
+    pub bookmark: String,
+}
+
+impl Subscription {
+    pub(super) fn read(self, tx: mpsc::Sender<Batch>, stopped: &AtomicBool) {
+        if let Err(error) = self.try_read(&tx, stopped) {
+            emit!(WindowsEventLogReadFailed { error });
+        }
+    }
+
+    fn try_read(&self, tx: &mpsc::Sender<Batch>, stopped: &AtomicBool) -> io::Result<()> {
+        let signal = Signal::new()?;
+        let bookmark_xml = self.bookmark.as_deref().map(to_wide);
+        let bookmark = EvtHandle::new(unsafe {
+            EvtCreateBookmark(
+                bookmark_xml
+                    .as_ref()
+                    .map_or(ptr::null(), |bookmark| bookmark.as_ptr()),
+            )
+        })?;
+        let flags = if bookmark_xml.is_some() {
+            EvtSubscribeStartAfterBookmark
+        } else if self.read_existing_events {
+            EvtSubscribeStartAtOldestRecord
+        } else {
+            EvtSubscribeToFutureEvents
+        };
+        let query = to_wide(&self.query);
+        let subscription = EvtHandle::new(unsafe {
+            EvtSubscribe(
+                ptr::null_mut(),
+                signal.0,
+                ptr::null(),
+                query.as_ptr(),
+                if bookmark_xml.is_some() {
+                    bookmark.0
+                } else {
+                    ptr::null_mut()
+                },
+                ptr::null_mut(),
+                None,
+                flags,
+            )
+        })?;
+        let render_context = EvtHandle::new(unsafe {
+            EvtCreateRenderContext(0, ptr::null_mut(), EvtRenderContextSystem)
+        })?;
+        let mut publishers = Publishers::default();
+        let mut handles = vec![ptr::null_mut(); self.batch_size as usize];
+
+        while !stopped.load(Ordering::Relaxed) {
+            match unsafe { WaitForSingleObject(signal.0, POLL_TIMEOUT_MS) } {
+                WAIT_OBJECT_0 => {}
+                WAIT_TIMEOUT => continue,
+                _ => return Err(io::Error::last_os_error()),
+            }
+            // Reset before draining, so events logged meanwhile signal again.
+            unsafe { ResetEvent(signal.0) };
+
+            loop {
+                let mut returned = 0;
+                if unsafe {
+                    EvtNext(
+                        subscription.0,
+                        self.batch_size,
+                        handles.as_mut_ptr(),
+                        0,
+                        0,
+                        &mut returned,
+                    )
+                } == FALSE
+                {
+                    let error = io::Error::last_os_error();
+                    if error.raw_os_error() == Some(ERROR_NO_MORE_ITEMS as i32) {
+                        break;
+                    }
+                    return Err(error);
+                }
+
+                let records = handles[..returned as usize]
+                    .iter()
+                    .map(|&handle| EvtHandle(handle))
+                    .collect::<Vec<_>>();
+                let events = records
+                    .iter()
+                    .filter_map(|record| {
+                        self.create_log(&render_context, record, &mut publishers)
+                            .map_err(|error| emit!(WindowsEventLogRenderFailed { error }))
+                            .ok()
+                    })
+                    .collect();
+                if let Some(last) = records.last() {
+                    if unsafe { EvtUpdateBookmark(bookmark.0, last.0) } == FALSE {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                let xml = render_xml(&bookmark, EvtRenderBookmark)?;
+
+                if tx
+                    .blocking_send(Batch {
+                        events,
+                        bookmark: xml,
+                    })
+                    .is_err()
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_log(
+        &self,
+        render_context: &EvtHandle,
+        record: &EvtHandle,
+        publishers: &mut Publishers,
+    ) -> io::Result<LogEvent> {
+        let system = System::render(render_context, record)?;
+        let xml = render_xml(record, EvtRenderEventXml)?;
+
+        emit!(WindowsEventLogEventReceived {
+            byte_size: xml.len()
+        });
+
+        let mut log = LogEvent::default();
+
+        // Events whose provider isn't installed can't be formatted, and are
+        // left without a message, their content still being in `xml`.
+        if self.render_message {
+            if let Some(message) = system
+                .provider_name
+                .as_deref()
+                .and_then(|provider_name| publishers.get(provider_name))
+                .and_then(|publisher| format_message(publisher, record).ok())
+            {
+                log.insert(log_schema().message_key(), message);
+            }
+        }
+
+        let timestamp = system
+            .time_created
+            .and_then(filetime_to_datetime)
+            .unwrap_or_else(Utc::now);
+        log.insert(log_schema().timestamp_key(), timestamp);
+
+        log.insert(
+            log_schema().source_type_key(),
+            Bytes::from("windows_eventlog"),
+        );
+
+        if let Some(channel) = system.channel {
+            log.insert("channel", channel);
+        }
+        if let Some(provider_name) = system.provider_name {
+            log.insert("provider_name", provider_name);
+        }
+        if let Some(event_id) = system.event_id {
+            log.insert("event_id", i64::from(event_id));
+        }
+        if let Some(level) = system.level {
+            log.insert("level", i64::from(level));
+        }
+        if let Some(record_id) = system.record_id {
+            log.insert("record_id", record_id as i64);
+        }
+        if let Some(computer) = system.computer {
+            log.insert("computer", computer);
+        }
+        log.insert("xml", xml);
+
+        Ok(log)
+    }
+}
+
+/// The system properties of an event.
+#[derive(Default)]
+struct System {
+    provider_name: Option<String>,
+    event_id: Option<u16>,
+    level: Option<u8>,
+    time_created: Option<u64>,
+    record_id: Option<u64>,
+    channel: Option<String>,
+    computer: Option<String>,
+}
+
+impl System {
+    fn render(render_context: &EvtHandle, record: &EvtHandle) -> io::Result<Self> {
+        let mut used = 0;
+        let mut count = 0;
+        let mut buffer: Vec<u64> = Vec::new();
+        // The values point into the buffer, which is sized to hold them as
+        // `EVT_VARIANT`s and so is made of `u64`s to be aligned for them.
+        loop {
+            let size = (buffer.len() * 8) as DWORD;
+            if unsafe {
+                EvtRender(
+                    render_context.0,
+                    record.0,
+                    EvtRenderEventValues,
+                    size,
+                    buffer.as_mut_ptr().cast(),
+                    &mut used,
+                    &mut count,
+                )
+            } != FALSE
+            {
+                break;
+            }
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) || size >= used {
+                return Err(error);
+            }
+            buffer.resize((used as usize + 7) / 8, 0);
+        }
+
+        let values =
+            unsafe { slice::from_raw_parts(buffer.as_ptr().cast::<EVT_VARIANT>(), count as usize) };
+        let value = |id: u32| values.get(id as usize);
+        unsafe {
+            Ok(Self {
+                provider_name: value(EvtSystemProviderName).and_then(|value| string(value)),
+                event_id: value(EvtSystemEventID)
+                    .filter(|value| value.Type == EvtVarTypeUInt16)
+                    .map(|value| *value.u.UInt16Val()),
+                level: value(EvtSystemLevel)
+                    .filter(|value| value.Type == EvtVarTypeByte)
+                    .map(|value| *value.u.ByteVal()),
+                time_created: value(EvtSystemTimeCreated)
+                    .filter(|value| value.Type == EvtVarTypeFileTime)
+                    .map(|value| *value.u.FileTimeVal()),
+                record_id: value(EvtSystemEventRecordId)
+                    .filter(|value| value.Type == EvtVarTypeUInt64)
+                    .map(|value| *value.u.UInt64Val()),
+                channel: value(EvtSystemChannel).and_then(|value| string(value)),
+                computer: value(EvtSystemComputer).and_then(|value| string(value)),
+            })
+        }
+    }
+}
+
+unsafe fn string(value: &EVT_VARIANT) -> Option<String> {
+    let pointer = *value.u.StringVal();
+    if value.Type != EvtVarTypeString || pointer.is_null() {
+        return None;
+    }
+    let len = (0..).take_while(|&i| *pointer.add(i) != 0).count();
+    Some(String::from_utf16_lossy(slice::from_raw_parts(
+        pointer, len,
+    )))
+}
+
+/// The metadata of the providers seen so far, which holds the resources
+/// their messages are formatted with.
+#[derive(Default)]
+struct Publishers(HashMap<String, Option<EvtHandle>>);
+
+impl Publishers {
+    fn get(&mut self, provider_name: &str) -> Option<&EvtHandle> {
+        self.0
+            .entry(provider_name.to_owned())
+            .or_insert_with(|| {
+                let provider_name = to_wide(provider_name);
+                EvtHandle::new(unsafe {
+                    EvtOpenPublisherMetadata(
+                        ptr::null_mut(),
+                        provider_name.as_ptr(),
+                        ptr::null(),
+                        0,
+                        0,
+                    )
+                })
+                .ok()
+            })
+            .as_ref()
+    }
+}
+
+fn format_message(publisher: &EvtHandle, record: &EvtHandle) -> io::Result<String> {
+    let mut buffer: Vec<u16> = Vec::new();
+    let mut used = 0;
+    loop {
+        let size = buffer.len() as DWORD;
+        if unsafe {
+            EvtFormatMessage(
+                publisher.0,
+                record.0,
+                0,
+                0,
+                ptr::null_mut(),
+                EvtFormatMessageEvent,
+                size,
+                buffer.as_mut_ptr(),
+                &mut used,
+            )
+        } != FALSE
+        {
+            return Ok(from_wide(&buffer[..used as usize]));
+        }
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) || size >= used {
+            return Err(error);
+        }
+        buffer.resize(used as usize, 0);
+    }
+}
+
+/// Render an event or a bookmark as XML.
+fn render_xml(fragment: &EvtHandle, flags: DWORD) -> io::Result<String> {
+    let mut buffer: Vec<u16> = Vec::new();
+    let mut used = 0;
+    let mut count = 0;
+    loop {
+        // Sizes are in bytes, of UTF-16 text.
+        let size = (buffer.len() * 2) as DWORD;
+        if unsafe {
+            EvtRender(
+                ptr::null_mut(),
+                fragment.0,
+                flags,
+                size,
+                buffer.as_mut_ptr().cast(),
+                &mut used,
+                &mut count,
+            )
+        } != FALSE
+        {
+            return Ok(from_wide(&buffer[..used as usize / 2]));
+        }
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) || size >= used {
+            return Err(error);
+        }
+        buffer.resize((used as usize + 1) / 2, 0);
+    }
+}
+
+fn filetime_to_datetime(filetime: u64) -> Option<DateTime<Utc>> {
+    let intervals = filetime.checked_sub(UNIX_EPOCH_INTERVALS)?;
+    Utc.timestamp_opt(
+        (intervals / 10_000_000) as i64,
+        (intervals % 10_000_000 * 100) as u32,
+    )
+    .single()
+}
+
+fn to_wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(Some(0)).collect()
+}
+
+/// Decode a UTF-16 string, up to its terminating nul if it has one.
+fn from_wide(text: &[u16]) -> String {
+    let len = text.iter().position(|&c| c == 0).unwrap_or(text.len());
+    String::from_utf16_lossy(&text[..len])
+}
+
+struct EvtHandle(EVT_HANDLE);
+
+impl EvtHandle {
+    fn new(handle: EVT_HANDLE) -> io::Result<Self> {
+        if handle.is_null() {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self(handle))
+        }
+    }
+}
+
+impl Drop for EvtHandle {
+    fn drop(&mut self) {
+        unsafe { EvtClose(self.0) };
+    }
+}
+
+/// A manual reset event, signalled by the subscription when it has events.
+struct Signal(HANDLE);
+
+impl Signal {
+    fn new() -> io::Result<Self> {
+        let handle = unsafe { CreateEventW(ptr::null_mut(), TRUE, TRUE, ptr::null()) };
+        if handle.is_null() {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self(handle))
+        }
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_filetime() {
+        let timestamp = filetime_to_datetime(UNIX_EPOCH_INTERVALS + 15_000_000).unwrap();
+
+        assert_eq!(timestamp, Utc.timestamp(1, 500_000_000));
+        assert_eq!(filetime_to_datetime(0), None);
+    }
+
+    #[test]
+    fn decodes_nul_terminated_text() {
+        assert_eq!(from_wide(&to_wide("System")), "System");
+    }
+}
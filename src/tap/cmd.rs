@@ -55,7 +55,11 @@ pub async fn cmd(opts: &super::Opts) -> exitcode::ExitCode {
     while let Some(Some(res)) = stream.next().await {
         if let Some(d) = res.data {
             for log_event in d.output_events.iter().filter_map(|ev| ev.as_log()) {
-                println!("{}", log_event.string);
+                if opts.lineage {
+                    println!("{}: {}", log_event.lineage.join(" -> "), log_event.string);
+                } else {
+                    println!("{}", log_event.string);
+                }
             }
         }
     }
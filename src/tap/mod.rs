@@ -25,6 +25,11 @@ pub struct Opts {
     #[structopt(default_value = "json", possible_values = &["json", "yaml"], short = "f", long)]
     format: TapEncodingFormat,
 
+    /// Print the components each log event passed through before the event. Requires
+    /// the `lineage` global option to be enabled
+    #[structopt(long)]
+    lineage: bool,
+
     /// Components to observe (comma-separated; accepts glob patterns)
     #[structopt(default_value = "*", use_delimiter(true))]
     components: Vec<String>,
@@ -1,6 +1,6 @@
 use crate::{
     config::log_schema,
-    event::{EventRef, Metric, Value},
+    event::{trace, EventRef, Metric, Value},
 };
 use bytes::Bytes;
 use chrono::{
@@ -160,6 +160,7 @@ fn render_fields<'a>(src: &str, event: EventRef<'a>) -> Result<String, TemplateR
             match event {
                 EventRef::Log(log) => log.get(&key).map(|val| val.to_string_lossy()),
                 EventRef::Metric(metric) => render_metric_field(key, metric),
+                EventRef::Trace(trace) => trace.get(&key).map(|val| val.to_string_lossy()),
             }
             .unwrap_or_else(|| {
                 missing_keys.push(key.to_owned());
@@ -192,6 +193,10 @@ fn render_timestamp(src: &str, event: EventRef<'_>) -> String {
             .and_then(Value::as_timestamp)
             .copied(),
         EventRef::Metric(metric) => metric.timestamp(),
+        EventRef::Trace(trace) => trace
+            .get(trace::fields::START_TIME)
+            .and_then(Value::as_timestamp)
+            .copied(),
     };
     if let Some(ts) = timestamp {
         ts.format(src).to_string()
@@ -0,0 +1,302 @@
+use crate::test_util::{next_addr, wait_for_tcp_duration};
+use bollard::{
+    container::{
+        Config as ContainerConfig, CreateContainerOptions, LogsOptions, RemoveContainerOptions,
+    },
+    image::CreateImageOptions,
+    models::{HostConfig, PortBinding},
+    network::CreateNetworkOptions,
+    Docker,
+};
+use futures::StreamExt;
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use uuid::Uuid;
+
+/// How long to wait for a container to report it is ready to serve requests.
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How to tell a started container is ready to serve requests.
+#[derive(Clone, Debug)]
+pub enum Readiness {
+    /// The given container port accepts connections.
+    Port(u16),
+
+    /// The container wrote a line containing the given text to its output.
+    LogLine(&'static str),
+}
+
+/// Describes a container to start within an [`Environment`].
+#[derive(Clone, Debug)]
+pub struct ContainerSpec {
+    /// The name other containers of the environment reach this one under.
+    pub name: &'static str,
+    pub image: &'static str,
+    pub tag: &'static str,
+    pub env: Vec<String>,
+    pub cmd: Option<Vec<String>>,
+
+    /// The container ports to publish on the host, each is bound to a free
+    /// host port which the spec may need to know ahead of time (e.g. for
+    /// Kafka's advertised listeners), see [`Environment::reserve_port`].
+    pub ports: Vec<(u16, u16)>,
+    pub ready: Readiness,
+}
+
+impl ContainerSpec {
+    pub fn new(
+        name: &'static str,
+        image: &'static str,
+        tag: &'static str,
+        ready: Readiness,
+    ) -> Self {
+        Self {
+            name,
+            image,
+            tag,
+            env: Vec::new(),
+            cmd: None,
+            ports: Vec::new(),
+            ready,
+        }
+    }
+
+    pub fn env(mut self, key: &str, value: impl std::fmt::Display) -> Self {
+        self.env.push(format!("{}={}", key, value));
+        self
+    }
+
+    pub fn cmd(mut self, cmd: &[&str]) -> Self {
+        self.cmd = Some(cmd.iter().map(|arg| (*arg).to_owned()).collect());
+        self
+    }
+
+    pub fn port(mut self, container: u16, host: u16) -> Self {
+        self.ports.push((container, host));
+        self
+    }
+}
+
+/// A started container.
+#[derive(Debug)]
+pub struct Container {
+    id: String,
+    ports: HashMap<u16, u16>,
+}
+
+impl Container {
+    /// The host address the given container port is published on.
+    pub fn addr(&self, port: u16) -> SocketAddr {
+        let host_port = self
+            .ports
+            .get(&port)
+            .unwrap_or_else(|| panic!("container port {} isn't published", port));
+
+        SocketAddr::from(([127, 0, 0, 1], *host_port))
+    }
+}
+
+/// A set of containers sharing a network, torn down together when the
+/// environment is dropped, even if the test using it panics.
+///
+/// Every environment gets its own network and container names, so tests
+/// running concurrently don't interfere with each other.
+pub struct Environment {
+    docker: Docker,
+    id: String,
+    network: String,
+    containers: Vec<Container>,
+}
+
+impl Environment {
+    pub async fn new() -> Self {
+        let docker = crate::docker::docker(None, None).expect("unable to connect to docker");
+        let id = Uuid::new_v4().to_simple().to_string();
+        let network = format!("vector_e2e_{}", id);
+
+        docker
+            .create_network(CreateNetworkOptions {
+                name: network.clone(),
+                ..Default::default()
+            })
+            .await
+            .expect("unable to create network");
+
+        Self {
+            docker,
+            id,
+            network,
+            containers: Vec::new(),
+        }
+    }
+
+    /// Picks a free host port, to publish a container port on.
+    pub fn reserve_port(&self) -> u16 {
+        next_addr().port()
+    }
+
+    /// The hostname a container of this environment is reachable under from
+    /// the other containers.
+    pub fn hostname(&self, name: &str) -> String {
+        format!("{}_{}", name, self.id)
+    }
+
+    /// Starts the container and waits for it to be ready.
+    pub async fn start(&mut self, spec: ContainerSpec) -> &Container {
+        self.pull(spec.image, spec.tag).await;
+
+        let name = self.hostname(spec.name);
+        let exposed_ports = spec
+            .ports
+            .iter()
+            .map(|(container, _)| (format!("{}/tcp", container), HashMap::new()))
+            .collect();
+        let port_bindings = spec
+            .ports
+            .iter()
+            .map(|(container, host)| {
+                let binding = PortBinding {
+                    host_ip: Some("127.0.0.1".to_owned()),
+                    host_port: Some(host.to_string()),
+                };
+                (format!("{}/tcp", container), Some(vec![binding]))
+            })
+            .collect();
+
+        let config = ContainerConfig {
+            image: Some(format!("{}:{}", spec.image, spec.tag)),
+            env: Some(spec.env.clone()),
+            cmd: spec.cmd.clone(),
+            exposed_ports: Some(exposed_ports),
+            host_config: Some(HostConfig {
+                network_mode: Some(self.network.clone()),
+                port_bindings: Some(port_bindings),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let container = self
+            .docker
+            .create_container(Some(CreateContainerOptions { name: name.clone() }), config)
+            .await
+            .unwrap_or_else(|error| panic!("unable to create {}: {}", name, error));
+        // Keep track of the container before starting it, so that it's
+        // removed even if it fails to start.
+        self.containers.push(Container {
+            id: container.id,
+            ports: spec.ports.iter().copied().collect(),
+        });
+        let container = self.containers.last().expect("just pushed");
+
+        self.docker
+            .start_container::<String>(&container.id, None)
+            .await
+            .unwrap_or_else(|error| panic!("unable to start {}: {}", name, error));
+
+        match spec.ready {
+            Readiness::Port(port) => {
+                wait_for_tcp_duration(container.addr(port), READY_TIMEOUT).await
+            }
+            Readiness::LogLine(line) => self.wait_for_log_line(&container.id, line).await,
+        }
+
+        container
+    }
+
+    async fn pull(&self, image: &str, tag: &str) {
+        let options = CreateImageOptions {
+            from_image: image,
+            tag,
+            ..Default::default()
+        };
+
+        let mut progress = self.docker.create_image(Some(options), None, None);
+        while let Some(info) = progress.next().await {
+            let info = info.unwrap_or_else(|error| panic!("unable to pull {}: {}", image, error));
+            if let Some(error) = info.error {
+                panic!("unable to pull {}: {}", image, error);
+            }
+        }
+    }
+
+    async fn wait_for_log_line(&self, id: &str, line: &str) {
+        let options = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+
+        let mut logs = self.docker.logs(id, Some(options));
+        let found = tokio::time::timeout(READY_TIMEOUT, async {
+            while let Some(output) = logs.next().await {
+                match output {
+                    Ok(output) if output.to_string().contains(line) => return true,
+                    Ok(_) => {}
+                    Err(_) => return false,
+                }
+            }
+
+            false
+        })
+        .await;
+
+        assert!(
+            matches!(found, Ok(true)),
+            "container {} didn't log {:?} before exiting or timing out",
+            id,
+            line
+        );
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        let containers = self
+            .containers
+            .drain(..)
+            .map(|container| container.id)
+            .collect();
+        let network = self.network.clone();
+
+        // The runtime of the test can't be blocked on, and may be unwinding
+        // from a panic, so the environment is removed from a runtime of its
+        // own.
+        let teardown = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("unable to start a runtime")
+                .block_on(teardown(containers, network))
+        });
+        if teardown.join().is_err() {
+            error!(message = "Unable to tear down environment.", network = %self.network);
+        }
+    }
+}
+
+/// Removes the containers, then the network they share.
+async fn teardown(containers: Vec<String>, network: String) {
+    let docker = match crate::docker::docker(None, None) {
+        Ok(docker) => docker,
+        Err(error) => {
+            error!(message = "Unable to connect to docker.", %error);
+            return;
+        }
+    };
+
+    for id in containers {
+        let options = RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        };
+
+        if let Err(error) = docker.remove_container(&id, Some(options)).await {
+            error!(message = "Unable to remove container.", %id, %error);
+        }
+    }
+
+    if let Err(error) = docker.remove_network(&network).await {
+        error!(message = "Unable to remove network.", %network, %error);
+    }
+}
@@ -0,0 +1,93 @@
+//! End-to-end test harness.
+//!
+//! Starts the services components talk to in throwaway containers, runs
+//! topologies loaded from real configuration against them, and asserts
+//! events make it through and get acknowledged. Only built with the
+//! `e2e-tests` feature, and needs a Docker daemon to talk to; run with
+//! `make test-e2e`.
+
+mod container;
+mod services;
+mod tests;
+
+pub use container::{Container, ContainerSpec, Environment, Readiness};
+pub use services::{elasticsearch, kafka, minio, redis, MINIO_ACCESS_KEY, MINIO_SECRET_KEY};
+
+use crate::{
+    config::{self, Format},
+    test_util::start_topology,
+    topology::RunningTopology,
+};
+use futures::Future;
+use std::time::Duration;
+use tokio::{sync::mpsc::UnboundedReceiver, time::Instant};
+
+/// How long to wait for the sent lines to come out of a topology.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A running topology, loaded from a real configuration.
+pub struct Topology {
+    topology: RunningTopology,
+    _crash: UnboundedReceiver<()>,
+}
+
+impl Topology {
+    /// Loads the TOML `config` and starts its topology. The healthchecks of
+    /// all of its sinks have to pass.
+    pub async fn start(config: &str) -> Self {
+        let config = config::load_from_str(config, Some(Format::Toml))
+            .unwrap_or_else(|errors| panic!("invalid config: {:?}", errors));
+        let (topology, crash) = start_topology(config, true).await;
+
+        Self {
+            topology,
+            _crash: crash,
+        }
+    }
+
+    /// Shuts the topology down, once its sources stopped and its sinks
+    /// flushed the events in flight.
+    pub async fn stop(self) {
+        self.topology.stop().await;
+    }
+}
+
+/// Fetches what made it through a topology until all of the `count` lines
+/// sent into it were received or the timeout passes, and returns the lines
+/// received last.
+pub async fn receive<F, Fut>(count: usize, mut fetch: F) -> Vec<String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Vec<String>>,
+{
+    let deadline = Instant::now() + RECEIVE_TIMEOUT;
+    loop {
+        let received = fetch().await;
+        if received.len() >= count || Instant::now() >= deadline {
+            return received;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Asserts every sent line was received exactly once, regardless of order.
+pub fn assert_delivered(sent: &[String], received: &[String]) {
+    let mut sent = sent.to_vec();
+    let mut received = received.to_vec();
+    sent.sort();
+    received.sort();
+
+    let missing = sent
+        .iter()
+        .filter(|line| received.binary_search(line).is_err())
+        .count();
+    assert_eq!(missing, 0, "{} sent lines weren't received", missing);
+    assert_eq!(
+        sent.len(),
+        received.len(),
+        "received {} lines for {} sent",
+        received.len(),
+        sent.len()
+    );
+    assert_eq!(sent, received);
+}
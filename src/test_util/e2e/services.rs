@@ -0,0 +1,109 @@
+//! The services sinks and sources talk to, started with the settings the
+//! end-to-end tests expect.
+//!
+//! Published ports accept connections as soon as the container starts, so
+//! readiness is judged from what the services log instead.
+
+use super::{ContainerSpec, Environment, Readiness};
+
+pub const MINIO_ACCESS_KEY: &str = "vector-e2e";
+pub const MINIO_SECRET_KEY: &str = "vector-e2e-secret";
+
+/// Starts a single broker Kafka cluster, returning its bootstrap servers.
+pub async fn kafka(env: &mut Environment) -> String {
+    env.start(ContainerSpec::new(
+        "zookeeper",
+        "wurstmeister/zookeeper",
+        "latest",
+        Readiness::LogLine("binding to port"),
+    ))
+    .await;
+
+    // The broker advertises the host port it's published on, so clients
+    // outside of the environment's network are able to reach it.
+    let port = env.reserve_port();
+    let zookeeper = format!("{}:2181", env.hostname("zookeeper"));
+    let kafka = env
+        .start(
+            ContainerSpec::new(
+                "kafka",
+                "wurstmeister/kafka",
+                "2.13-2.6.0",
+                Readiness::LogLine("started (kafka.server.KafkaServer)"),
+            )
+            .env("KAFKA_BROKER_ID", 1)
+            .env("KAFKA_ZOOKEEPER_CONNECT", zookeeper)
+            .env("KAFKA_LISTENERS", "PLAINTEXT://:9091")
+            .env(
+                "KAFKA_ADVERTISED_LISTENERS",
+                format!("PLAINTEXT://localhost:{}", port),
+            )
+            .env("KAFKA_AUTO_CREATE_TOPICS_ENABLE", true)
+            .port(9091, port),
+        )
+        .await;
+
+    kafka.addr(9091).to_string()
+}
+
+/// Starts a single node Elasticsearch cluster, returning its endpoint.
+pub async fn elasticsearch(env: &mut Environment) -> String {
+    let port = env.reserve_port();
+    let elasticsearch = env
+        .start(
+            ContainerSpec::new(
+                "elasticsearch",
+                "docker.elastic.co/elasticsearch/elasticsearch",
+                "7.13.1",
+                Readiness::LogLine(r#""message": "started""#),
+            )
+            .env("discovery.type", "single-node")
+            .env("ES_JAVA_OPTS", "-Xms400m -Xmx400m")
+            .port(9200, port),
+        )
+        .await;
+
+    format!("http://{}", elasticsearch.addr(9200))
+}
+
+/// Starts a Redis server, returning its URL.
+pub async fn redis(env: &mut Environment) -> String {
+    let port = env.reserve_port();
+    let redis = env
+        .start(
+            ContainerSpec::new(
+                "redis",
+                "redis",
+                "6-alpine",
+                Readiness::LogLine("Ready to accept connections"),
+            )
+            .port(6379, port),
+        )
+        .await;
+
+    format!("redis://{}/0", redis.addr(6379))
+}
+
+/// Starts a MinIO server standing in for S3, returning its endpoint.
+///
+/// Requests have to be signed with [`MINIO_ACCESS_KEY`] and
+/// [`MINIO_SECRET_KEY`].
+pub async fn minio(env: &mut Environment) -> String {
+    let port = env.reserve_port();
+    let minio = env
+        .start(
+            ContainerSpec::new(
+                "minio",
+                "minio/minio",
+                "RELEASE.2021-08-05T22-01-19Z",
+                Readiness::LogLine("API:"),
+            )
+            .env("MINIO_ROOT_USER", MINIO_ACCESS_KEY)
+            .env("MINIO_ROOT_PASSWORD", MINIO_SECRET_KEY)
+            .cmd(&["server", "/data"])
+            .port(9000, port),
+        )
+        .await;
+
+    format!("http://{}", minio.addr(9000))
+}
@@ -0,0 +1,361 @@
+use super::*;
+use crate::test_util::{
+    next_addr, random_lines, random_string, send_lines, trace_init, wait_for_tcp,
+};
+use futures::TryStreamExt;
+use rdkafka::{
+    consumer::{BaseConsumer, Consumer},
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    Message, Offset, TopicPartitionList,
+};
+use redis::AsyncCommands;
+use rusoto_core::{HttpClient, Region};
+use rusoto_credential::StaticProvider;
+use rusoto_s3::{CreateBucketRequest, GetObjectRequest, ListObjectsV2Request, S3Client, S3};
+use serde_json::{json, Value};
+use std::{net::SocketAddr, time::Duration};
+use tokio::time::Instant;
+
+const EVENT_COUNT: usize = 1000;
+
+/// A TCP socket source reading lines, and its address.
+fn socket_source() -> (SocketAddr, String) {
+    let addr = next_addr();
+    let config = format!(
+        r#"
+        [sources.in]
+        type = "socket"
+        mode = "tcp"
+        address = "{}"
+        "#,
+        addr
+    );
+    (addr, config)
+}
+
+/// Sends `count` random lines to the socket source at `addr`.
+async fn send_to_socket(addr: SocketAddr, count: usize) -> Vec<String> {
+    wait_for_tcp(addr).await;
+    let lines = random_lines(100).take(count).collect::<Vec<_>>();
+    send_lines(addr, lines.clone()).await.unwrap();
+    lines
+}
+
+fn kafka_client_config(servers: &str, group_id: Option<&str>) -> rdkafka::ClientConfig {
+    let mut client_config = rdkafka::ClientConfig::new();
+    client_config.set("bootstrap.servers", servers);
+    if let Some(group_id) = group_id {
+        client_config.set("group.id", group_id);
+    }
+    client_config
+}
+
+/// Reads everything from the first partition of `topic`.
+fn consume_topic(servers: &str, topic: &str, count: usize) -> Vec<String> {
+    let consumer: BaseConsumer = kafka_client_config(servers, Some(&random_string(10)))
+        .create()
+        .unwrap();
+    let mut partitions = TopicPartitionList::new();
+    partitions
+        .add_partition(topic, 0)
+        .set_offset(Offset::Beginning)
+        .unwrap();
+    consumer.assign(&partitions).unwrap();
+
+    let mut received = Vec::new();
+    let mut failures = 0;
+    while received.len() < count && failures < 100 {
+        match consumer.poll(Duration::from_secs(3)) {
+            Some(Ok(message)) => {
+                let payload: &str = message.payload_view().unwrap().unwrap();
+                received.push(payload.to_owned());
+            }
+            _ => failures += 1,
+        }
+    }
+    received
+}
+
+/// The offset the consumer group committed on the first partition of
+/// `topic`.
+fn committed_offset(servers: &str, group_id: &str, topic: &str) -> Offset {
+    let consumer: BaseConsumer = kafka_client_config(servers, Some(group_id))
+        .create()
+        .unwrap();
+    let mut partitions = TopicPartitionList::new();
+    partitions.add_partition(topic, 0);
+    consumer
+        .committed_offsets(partitions, Duration::from_secs(10))
+        .unwrap()
+        .find_partition(topic, 0)
+        .unwrap()
+        .offset()
+}
+
+/// Lines go from one topic to another through the Kafka source and sink.
+/// The source acknowledges the lines once the sink delivered them, by
+/// committing the offsets of its consumer group.
+#[tokio::test]
+async fn kafka_source_to_kafka_sink() {
+    trace_init();
+
+    let mut env = Environment::new().await;
+    let servers = kafka(&mut env).await;
+    let input = format!("e2e-in-{}", random_string(10));
+    let output = format!("e2e-out-{}", random_string(10));
+    let group_id = format!("e2e-{}", random_string(10));
+
+    let producer: FutureProducer = kafka_client_config(&servers, None).create().unwrap();
+    let lines = random_lines(100).take(EVENT_COUNT).collect::<Vec<_>>();
+    for line in &lines {
+        producer
+            .send(
+                FutureRecord::<(), _>::to(&input).payload(line),
+                Timeout::Never,
+            )
+            .await
+            .expect("Cannot send line to Kafka");
+    }
+
+    let topology = Topology::start(&format!(
+        r#"
+        [sources.in]
+        type = "kafka"
+        bootstrap_servers = "{servers}"
+        topics = ["{input}"]
+        group_id = "{group_id}"
+        auto_offset_reset = "beginning"
+        commit_interval_ms = 100
+        acknowledgements = true
+
+        [sinks.out]
+        type = "kafka"
+        inputs = ["in"]
+        bootstrap_servers = "{servers}"
+        topic = "{output}"
+        encoding.codec = "text"
+        "#,
+        servers = servers,
+        input = input,
+        output = output,
+        group_id = group_id,
+    ))
+    .await;
+
+    // Both consumers block, so they run off of the runtime the topology is on.
+    let received = {
+        let (servers, output, count) = (servers.clone(), output.clone(), lines.len());
+        tokio::task::spawn_blocking(move || consume_topic(&servers, &output, count))
+            .await
+            .unwrap()
+    };
+    assert_delivered(&lines, &received);
+
+    let expected = Offset::from_raw(EVENT_COUNT as i64);
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let committed = loop {
+        let (servers, group_id, input) = (servers.clone(), group_id.clone(), input.clone());
+        let committed =
+            tokio::task::spawn_blocking(move || committed_offset(&servers, &group_id, &input))
+                .await
+                .unwrap();
+        if committed == expected || Instant::now() >= deadline {
+            break committed;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+    topology.stop().await;
+
+    assert_eq!(committed, expected);
+}
+
+/// Lines go through a transform on their way to Elasticsearch.
+#[tokio::test]
+async fn socket_source_to_elasticsearch_sink() {
+    trace_init();
+
+    let mut env = Environment::new().await;
+    let endpoint = elasticsearch(&mut env).await;
+    let index = format!("e2e-{}", random_string(10).to_lowercase());
+
+    let (addr, source) = socket_source();
+    let topology = Topology::start(&format!(
+        r#"
+        {}
+
+        [transforms.tag]
+        type = "remap"
+        inputs = ["in"]
+        source = '.pipeline = "e2e"'
+
+        [sinks.out]
+        type = "elasticsearch"
+        inputs = ["tag"]
+        endpoint = "{}"
+        index = "{}"
+        "#,
+        source, endpoint, index
+    ))
+    .await;
+    let lines = send_to_socket(addr, EVENT_COUNT).await;
+
+    let client = &reqwest::Client::new();
+    let (endpoint, index) = (&endpoint, &index);
+    let search = move || async move {
+        // The index only exists once the sink sent something.
+        let _ = client
+            .post(&format!("{}/{}/_refresh", endpoint, index))
+            .send()
+            .await;
+
+        let response = client
+            .get(&format!("{}/{}/_search", endpoint, index))
+            .json(&json!({
+                "query": { "match": { "pipeline": "e2e" } },
+                "size": EVENT_COUNT,
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json::<Value>()
+            .await
+            .unwrap();
+
+        response["hits"]["hits"]
+            .as_array()
+            .map(|hits| {
+                hits.iter()
+                    .map(|hit| hit["_source"]["message"].as_str().unwrap().to_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let received = receive(lines.len(), search).await;
+    topology.stop().await;
+
+    assert_delivered(&lines, &received);
+}
+
+#[tokio::test]
+async fn socket_source_to_redis_sink() {
+    trace_init();
+
+    let mut env = Environment::new().await;
+    let url = redis(&mut env).await;
+    let key = format!("e2e-{}", random_string(10));
+
+    let (addr, source) = socket_source();
+    let topology = Topology::start(&format!(
+        r#"
+        {}
+
+        [sinks.out]
+        type = "redis"
+        inputs = ["in"]
+        url = "{}"
+        key = "{}"
+        data_type = "list"
+        list.method = "rpush"
+        encoding.codec = "text"
+        "#,
+        source, url, key
+    ))
+    .await;
+    let lines = send_to_socket(addr, EVENT_COUNT).await;
+
+    let client = &redis::Client::open(url.as_str()).unwrap();
+    let key = &key;
+    let received = receive(lines.len(), move || async move {
+        let mut conn = client.get_async_connection().await.unwrap();
+        conn.lrange(key, 0, -1).await.unwrap()
+    })
+    .await;
+    topology.stop().await;
+
+    assert_delivered(&lines, &received);
+}
+
+#[tokio::test]
+async fn socket_source_to_aws_s3_sink() {
+    trace_init();
+
+    let mut env = Environment::new().await;
+    let endpoint = minio(&mut env).await;
+    let bucket = format!("e2e-{}", random_string(10).to_lowercase());
+
+    let client = S3Client::new_with(
+        HttpClient::new().unwrap(),
+        StaticProvider::new_minimal(MINIO_ACCESS_KEY.into(), MINIO_SECRET_KEY.into()),
+        Region::Custom {
+            name: "minio".to_owned(),
+            endpoint: endpoint.clone(),
+        },
+    );
+    client
+        .create_bucket(CreateBucketRequest {
+            bucket: bucket.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let (addr, source) = socket_source();
+    let topology = Topology::start(&format!(
+        r#"
+        {}
+
+        [sinks.out]
+        type = "aws_s3"
+        inputs = ["in"]
+        bucket = "{}"
+        endpoint = "{}"
+        compression = "none"
+        encoding.codec = "text"
+        batch.timeout_secs = 1
+        auth.access_key_id = "{}"
+        auth.secret_access_key = "{}"
+        "#,
+        source, bucket, endpoint, MINIO_ACCESS_KEY, MINIO_SECRET_KEY
+    ))
+    .await;
+    let lines = send_to_socket(addr, EVENT_COUNT).await;
+
+    let (client, bucket) = (&client, &bucket);
+    let objects = move || async move {
+        let objects = client
+            .list_objects_v2(ListObjectsV2Request {
+                bucket: bucket.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .contents
+            .unwrap_or_default();
+
+        let mut received = Vec::new();
+        for object in objects {
+            let body = client
+                .get_object(GetObjectRequest {
+                    bucket: bucket.clone(),
+                    key: object.key.unwrap(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .body
+                .unwrap()
+                .map_ok(|bytes| bytes.to_vec())
+                .try_concat()
+                .await
+                .unwrap();
+
+            received.extend(String::from_utf8(body).unwrap().lines().map(Into::into));
+        }
+        received
+    };
+    let received = receive(lines.len(), objects).await;
+    topology.stop().await;
+
+    assert_delivered(&lines, &received);
+}
@@ -139,7 +139,7 @@ impl<S> MaybeTlsIncomingStream<S> {
         }
     }
 
-    #[cfg(feature = "sources-vector")]
+    #[cfg(any(feature = "sources-vector", feature = "sources-opentelemetry"))]
     pub(crate) fn ssl_stream(&self) -> Option<&SslStream<S>> {
         use super::MaybeTls;
 
@@ -311,3 +311,31 @@ impl AsyncWrite for MaybeTlsIncomingStream<TcpStream> {
         }
     }
 }
+
+/// What a gRPC service learns about the connection a request came in on.
+#[cfg(any(feature = "sources-vector", feature = "sources-opentelemetry"))]
+#[derive(Clone)]
+pub struct MaybeTlsConnectInfo {
+    pub remote_addr: SocketAddr,
+    pub peer_certs: Option<Vec<tonic::transport::Certificate>>,
+}
+
+#[cfg(any(feature = "sources-vector", feature = "sources-opentelemetry"))]
+impl tonic::transport::server::Connected for MaybeTlsIncomingStream<TcpStream> {
+    type ConnectInfo = MaybeTlsConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        MaybeTlsConnectInfo {
+            remote_addr: self.peer_addr(),
+            peer_certs: self
+                .ssl_stream()
+                .and_then(|s| s.ssl().peer_cert_chain())
+                .map(|s| {
+                    s.into_iter()
+                        .filter_map(|c| c.to_pem().ok())
+                        .map(tonic::transport::Certificate::from_pem)
+                        .collect()
+                }),
+        }
+    }
+}
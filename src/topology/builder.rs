@@ -1,12 +1,13 @@
 use super::{
+    channel::{self, InputCloner},
+    dead_letter::DeadLetter,
     fanout::{self, Fanout},
     task::{Task, TaskOutput},
     BuiltBuffer, ConfigDiff,
 };
 use crate::{
-    buffers,
-    config::{DataType, ProxyConfig, SinkContext, SourceContext},
-    event::Event,
+    config::{dead_letter_output, DataType, ProxyConfig, SinkContext, SourceContext},
+    event::{self, Event},
     internal_events::{EventIn, EventOut},
     shutdown::SourceShutdownCoordinator,
     transforms::Transform,
@@ -23,7 +24,7 @@ use stream_cancel::{StreamExt as StreamCancelExt, Trigger, Tripwire};
 use tokio::time::{timeout, Duration};
 
 pub struct Pieces {
-    pub inputs: HashMap<String, (buffers::BufferInputCloner<Event>, Vec<String>)>,
+    pub inputs: HashMap<String, (InputCloner, Vec<String>)>,
     pub outputs: HashMap<String, fanout::ControlChannel>,
     pub tasks: HashMap<String, Task>,
     pub source_tasks: HashMap<String, Task>,
@@ -46,6 +47,8 @@ pub async fn build_pieces(
     let mut shutdown_coordinator = SourceShutdownCoordinator::default();
     let mut detach_triggers = HashMap::new();
 
+    event::set_size_accounting(config.global.size_accounting);
+
     let mut errors = vec![];
 
     // Build sources
@@ -77,8 +80,17 @@ pub async fn build_pieces(
             Ok(server) => server,
         };
 
-        let (output, control) = Fanout::new();
-        let pump = rx.map(Ok).forward(output).map_ok(|_| TaskOutput::Source);
+        let (mut output, control) = Fanout::new();
+        if config.global.lineage {
+            output.record_lineage(id);
+        }
+        let pump = rx
+            .map(|mut event: Event| {
+                event.metadata_mut().record_ingest();
+                Ok(event)
+            })
+            .forward(output)
+            .map_ok(|_| TaskOutput::Source);
         let pump = Task::new(id, typetag, pump);
 
         // The force_shutdown_tripwire is a Future that when it resolves means that this source
@@ -109,6 +121,11 @@ pub async fn build_pieces(
         .filter(|(id, _)| diff.transforms.contains_new(id))
     {
         let trans_inputs = &transform.inputs;
+        let (input_tx, input_rx) = channel::edges(
+            id,
+            transform.input_capacity,
+            transform.input_capacities.clone(),
+        );
 
         let typetag = transform.inner.transform_type();
 
@@ -121,15 +138,12 @@ pub async fn build_pieces(
             Ok(transform) => transform,
         };
 
-        let (input_tx, input_rx, _) =
-            vector_core::buffers::build(vector_core::buffers::Variant::Memory {
-                max_events: 100,
-                when_full: vector_core::buffers::WhenFull::Block,
-            })
-            .unwrap();
         let input_rx = crate::utilization::wrap(Pin::new(input_rx));
 
-        let (output, control) = Fanout::new();
+        let (mut output, control) = Fanout::new();
+        if config.global.lineage {
+            output.record_lineage(id);
+        }
 
         let transform = match transform {
             Transform::Function(mut t) => input_rx
@@ -189,7 +203,10 @@ pub async fn build_pieces(
                     errors.push(format!("Sink \"{}\": {}", id, error));
                     continue;
                 }
-                Ok((tx, rx, acker)) => (tx, Arc::new(Mutex::new(Some(rx.into()))), acker),
+                Ok((tx, rx, acker)) => {
+                    let (tx, rx) = channel::instrument(id, tx, rx, sink.buffer.max_events());
+                    (tx, Arc::new(Mutex::new(Some(rx.into()))), acker)
+                }
             }
         };
 
@@ -208,6 +225,17 @@ pub async fn build_pieces(
             Ok(built) => built,
         };
 
+        let dead_letter = if config.dead_letter_consumed(id) {
+            let (mut output, control) = Fanout::new();
+            if config.global.lineage {
+                output.record_lineage(id);
+            }
+            outputs.insert(dead_letter_output(id), control);
+            Some(DeadLetter::new(id, typetag, output))
+        } else {
+            None
+        };
+
         let (trigger, tripwire) = Tripwire::new();
 
         let sink = async move {
@@ -225,14 +253,22 @@ pub async fn build_pieces(
 
             let mut rx = Box::pin(crate::utilization::wrap(rx));
 
-            sink.run(
-                rx.by_ref()
-                    .filter(|event| ready(filter_event_type(event, input_type)))
-                    .inspect(|_| emit!(EventIn))
-                    .take_until_if(tripwire),
-            )
-            .await
-            .map(|_| {
+            let (dead_letter, forward_dead_letter) = match dead_letter {
+                Some((dead_letter, forward)) => (Some(dead_letter), forward),
+                None => (None, future::ready(()).boxed()),
+            };
+            let events = rx
+                .by_ref()
+                .filter(|event| ready(filter_event_type(event, input_type)))
+                .inspect(|_| emit!(EventIn))
+                .take_until_if(tripwire)
+                .map(move |event| match &dead_letter {
+                    Some(dead_letter) => dead_letter.track(event),
+                    None => event,
+                });
+
+            let (result, _) = future::join(sink.run(events), forward_dead_letter).await;
+            result.map(|_| {
                 debug!("Finished.");
                 TaskOutput::Sink(rx, acker)
             })
@@ -309,5 +345,6 @@ fn filter_event_type(event: &Event, data_type: DataType) -> bool {
         DataType::Any => true,
         DataType::Log => matches!(event, Event::Log(_)),
         DataType::Metric => matches!(event, Event::Metric(_)),
+        DataType::Trace => matches!(event, Event::Trace(_)),
     }
 }
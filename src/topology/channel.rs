@@ -0,0 +1,452 @@
+//! Instrumented edges between topology components.
+//!
+//! Every transform reads from a channel of its own for each of the upstream
+//! components connected to it, with the capacity configured for that edge.
+//! Sinks read from their buffer, which is shared by all of their upstream
+//! components. The wrappers in this module keep track of how many events are
+//! queued in each of those and of how long each upstream component is blocked
+//! sending into them, so that backpressure can be traced back to the specific
+//! hop causing it.
+
+use super::fanout::RouterSink;
+use crate::{
+    buffers::{self, BufferInputCloner, EventStream, WhenFull},
+    event::Event,
+    internal_events::{channel_labels, ChannelEventsQueued, ChannelSendBlocked},
+};
+use futures::{
+    channel::mpsc,
+    stream::{Fuse, SelectAll},
+    Sink, Stream, StreamExt,
+};
+use indexmap::IndexMap;
+use metrics::Label;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+/// The number of events sent into a buffer but not yet read out of it.
+#[derive(Clone, Debug)]
+struct Occupancy {
+    queued: Arc<AtomicUsize>,
+    capacity: Option<usize>,
+}
+
+impl Occupancy {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            queued: Arc::new(AtomicUsize::new(0)),
+            capacity,
+        }
+    }
+
+    fn sent(&self) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    fn received(&self) -> usize {
+        // Buffers reused across a reload, or disk buffers reopened at startup,
+        // may hold events that were never counted, so saturate at zero.
+        let previous = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                Some(queued.saturating_sub(1))
+            })
+            .unwrap_or_default();
+        previous.saturating_sub(1)
+    }
+}
+
+/// Hands out instrumented senders into the input of component `id`.
+pub struct InputCloner {
+    id: String,
+    inner: Input,
+}
+
+enum Input {
+    /// A buffer shared by all of the inputs.
+    Buffer {
+        inner: BufferInputCloner<Event>,
+        occupancy: Option<Occupancy>,
+    },
+    /// A channel for each of the inputs, whose receivers are merged into
+    /// the input stream of the component.
+    Edges {
+        capacity: usize,
+        capacities: IndexMap<String, usize>,
+        streams: mpsc::UnboundedSender<EventStream>,
+    },
+}
+
+impl InputCloner {
+    /// A sender for the edge from component `input`.
+    pub fn get(&self, input: &str) -> RouterSink {
+        let (inner, occupancy) = match &self.inner {
+            Input::Buffer { inner, occupancy } => (inner.get(), occupancy.clone()),
+            Input::Edges {
+                capacity,
+                capacities,
+                streams,
+            } => {
+                let capacity = capacities.get(input).copied().unwrap_or(*capacity);
+                let (tx, rx, _) = buffers::build(buffers::Variant::Memory {
+                    max_events: capacity,
+                    when_full: WhenFull::Block,
+                })
+                .expect("memory buffers are infallible");
+                let occupancy = Occupancy::new(Some(capacity));
+                // The component may have stopped already, in which case
+                // nothing reads from the edge either.
+                let _ = streams.unbounded_send(Box::new(InstrumentedStream::new(
+                    channel_labels(&self.id, Some(input)),
+                    rx,
+                    occupancy.clone(),
+                )));
+                (tx.get(), Some(occupancy))
+            }
+        };
+
+        Box::new(InstrumentedSink {
+            downstream_component_id: self.id.clone(),
+            inner,
+            occupancy,
+            blocked_since: None,
+        })
+    }
+}
+
+/// Instruments both ends of the input buffer of component `id`.
+///
+/// Occupancy is only tracked for buffers which block when full, since those
+/// that drop events would never see the dropped ones come out the other end.
+pub fn instrument(
+    id: &str,
+    tx: BufferInputCloner<Event>,
+    rx: EventStream,
+    capacity: Option<usize>,
+) -> (InputCloner, EventStream) {
+    let occupancy = match when_full(&tx) {
+        WhenFull::Block => Some(Occupancy::new(capacity)),
+        WhenFull::DropNewest => None,
+    };
+
+    let rx: EventStream = match &occupancy {
+        Some(occupancy) => Box::new(InstrumentedStream::new(
+            channel_labels(id, None),
+            rx,
+            occupancy.clone(),
+        )),
+        None => rx,
+    };
+
+    let tx = InputCloner {
+        id: id.to_owned(),
+        inner: Input::Buffer {
+            inner: tx,
+            occupancy,
+        },
+    };
+
+    (tx, rx)
+}
+
+/// The input of component `id`, with an instrumented channel for each edge
+/// into it. Edges hold `capacity` events, or the capacity configured for
+/// their input in `capacities`.
+///
+/// The stream ends once the cloner and all of the senders it handed out are
+/// dropped, as for a buffer.
+pub fn edges(
+    id: &str,
+    capacity: usize,
+    capacities: IndexMap<String, usize>,
+) -> (InputCloner, EventStream) {
+    let (streams, rx) = mpsc::unbounded();
+
+    let tx = InputCloner {
+        id: id.to_owned(),
+        inner: Input::Edges {
+            capacity,
+            capacities,
+            streams,
+        },
+    };
+    let rx = Box::new(MergedEdges {
+        streams: rx.fuse(),
+        edges: SelectAll::new(),
+    });
+
+    (tx, rx)
+}
+
+fn when_full(tx: &BufferInputCloner<Event>) -> WhenFull {
+    match tx {
+        BufferInputCloner::Memory(_, when_full) => *when_full,
+        #[cfg(feature = "disk-buffer")]
+        BufferInputCloner::Disk(_, when_full) => *when_full,
+    }
+}
+
+struct InstrumentedSink {
+    downstream_component_id: String,
+    inner: Box<dyn Sink<Event, Error = ()> + Send + Unpin>,
+    occupancy: Option<Occupancy>,
+    blocked_since: Option<Instant>,
+}
+
+impl Sink<Event> for InstrumentedSink {
+    type Error = ();
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut this.inner).poll_ready(cx) {
+            Poll::Pending => {
+                this.blocked_since.get_or_insert_with(Instant::now);
+                Poll::Pending
+            }
+            ready => {
+                if let Some(since) = this.blocked_since.take() {
+                    emit!(ChannelSendBlocked {
+                        downstream_component_id: &this.downstream_component_id,
+                        duration: since.elapsed(),
+                    });
+                }
+                ready
+            }
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Event) -> Result<(), ()> {
+        let this = self.get_mut();
+
+        Pin::new(&mut this.inner).start_send(item)?;
+        if let Some(occupancy) = &this.occupancy {
+            occupancy.sent();
+        }
+
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+/// Reads from a channel, reporting how many events are left in it every
+/// `REPORT_INTERVAL` events, and whenever the reader catches up.
+struct InstrumentedStream {
+    labels: Vec<Label>,
+    inner: EventStream,
+    occupancy: Occupancy,
+    unreported: usize,
+}
+
+/// The number of events read from a channel between reports of how many are
+/// queued in it, so that busy channels don't report on every event.
+const REPORT_INTERVAL: usize = 1024;
+
+impl InstrumentedStream {
+    fn new(labels: Vec<Label>, inner: EventStream, occupancy: Occupancy) -> Self {
+        Self {
+            labels,
+            inner,
+            occupancy,
+            unreported: 0,
+        }
+    }
+
+    fn report(&mut self, count: usize) {
+        self.unreported = 0;
+        emit!(ChannelEventsQueued {
+            labels: &self.labels,
+            count,
+            capacity: self.occupancy.capacity,
+        });
+    }
+}
+
+impl Stream for InstrumentedStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let poll = Pin::new(&mut this.inner).poll_next(cx);
+        match poll {
+            Poll::Ready(Some(_)) => {
+                let count = this.occupancy.received();
+                this.unreported += 1;
+                if this.unreported >= REPORT_INTERVAL {
+                    this.report(count);
+                }
+            }
+            _ if this.unreported > 0 => this.report(this.occupancy.queued()),
+            _ => {}
+        }
+
+        poll
+    }
+}
+
+struct MergedEdges {
+    streams: Fuse<mpsc::UnboundedReceiver<EventStream>>,
+    edges: SelectAll<EventStream>,
+}
+
+impl Stream for MergedEdges {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while let Poll::Ready(Some(edge)) = Pin::new(&mut this.streams).poll_next(cx) {
+            this.edges.push(edge);
+        }
+
+        match Pin::new(&mut this.edges).poll_next(cx) {
+            // No edge is left, but more may still be added.
+            Poll::Ready(None) if !this.streams.is_done() => Poll::Pending,
+            poll => poll,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{buffers, test_util::collect_ready};
+    use futures::SinkExt;
+
+    fn memory(max_events: usize, when_full: WhenFull) -> (InputCloner, EventStream) {
+        let (tx, rx, _) = buffers::build(buffers::Variant::Memory {
+            max_events,
+            when_full,
+        })
+        .unwrap();
+
+        instrument("out", tx, rx, Some(max_events))
+    }
+
+    fn occupancy(tx: &InputCloner) -> Option<Occupancy> {
+        match &tx.inner {
+            Input::Buffer { occupancy, .. } => occupancy.clone(),
+            Input::Edges { .. } => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_queued_events() {
+        let (tx, mut rx) = memory(10, WhenFull::Block);
+        let occupancy = occupancy(&tx).unwrap();
+
+        let mut a = tx.get("a");
+        let mut b = tx.get("b");
+        a.send(Event::from("a")).await.unwrap();
+        b.send(Event::from("b")).await.unwrap();
+        a.send(Event::from("c")).await.unwrap();
+        assert_eq!(occupancy.queued.load(Ordering::Relaxed), 3);
+
+        rx.next().await.unwrap();
+        assert_eq!(occupancy.queued.load(Ordering::Relaxed), 2);
+
+        drop((a, b, tx));
+        assert_eq!(collect_ready(rx).await.len(), 2);
+        assert_eq!(occupancy.queued.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn skips_occupancy_when_dropping() {
+        let (tx, _rx) = memory(1, WhenFull::DropNewest);
+        assert!(occupancy(&tx).is_none());
+    }
+
+    #[tokio::test]
+    async fn merges_edges_of_their_own_capacity() {
+        let mut capacities = IndexMap::new();
+        capacities.insert("b".to_owned(), 2);
+        let (tx, rx) = edges("out", 1, capacities);
+
+        // Each sender may send one event past the capacity of its channel.
+        let mut a = tx.get("a");
+        let mut b = tx.get("b");
+        for message in &["a1", "a2"] {
+            a.send(Event::from(*message)).await.unwrap();
+        }
+        for message in &["b1", "b2", "b3"] {
+            b.send(Event::from(*message)).await.unwrap();
+        }
+        assert!(futures::poll!(a.send(Event::from("a3"))).is_pending());
+        assert!(futures::poll!(b.send(Event::from("b4"))).is_pending());
+
+        drop((a, b, tx));
+        let mut messages = collect_ready(rx)
+            .await
+            .into_iter()
+            .map(|event| event.as_log()["message"].to_string_lossy())
+            .collect::<Vec<_>>();
+        messages.sort();
+        assert_eq!(messages, vec!["a1", "a2", "b1", "b2", "b3"]);
+    }
+
+    #[tokio::test]
+    async fn edges_end_with_their_senders() {
+        let (tx, mut rx) = edges("out", 1, IndexMap::new());
+        let mut a = tx.get("a");
+        a.send(Event::from("a")).await.unwrap();
+        drop(a);
+
+        assert!(rx.next().await.is_some());
+        // The cloner may still hand out senders.
+        assert!(futures::poll!(rx.next()).is_pending());
+        drop(tx);
+        assert!(rx.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_when_caught_up() {
+        let (tx, rx, _) = buffers::build(buffers::Variant::Memory {
+            max_events: 10,
+            when_full: WhenFull::Block,
+        })
+        .unwrap();
+        let occupancy = Occupancy::new(Some(10));
+        let mut rx = InstrumentedStream::new(channel_labels("out", None), rx, occupancy.clone());
+
+        let mut tx = tx.get();
+        for _ in 0..2 {
+            tx.send(Event::from("a")).await.unwrap();
+            occupancy.sent();
+        }
+
+        rx.next().await.unwrap();
+        rx.next().await.unwrap();
+        assert_eq!(rx.unreported, 2);
+        assert!(futures::poll!(rx.next()).is_pending());
+        assert_eq!(rx.unreported, 0);
+    }
+
+    #[test]
+    fn occupancy_saturates() {
+        let occupancy = Occupancy::new(None);
+
+        assert_eq!(occupancy.received(), 0);
+        occupancy.sent();
+        assert_eq!(occupancy.received(), 0);
+        assert_eq!(occupancy.queued.load(Ordering::Relaxed), 0);
+    }
+}
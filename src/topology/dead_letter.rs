@@ -0,0 +1,165 @@
+//! Sends the events a sink rejects permanently to its `dead_letter` output,
+//! so that they can be routed elsewhere for replay instead of being lost.
+//!
+//! Rejections are observed through the finalization of events, which sinks
+//! already report: an event whose status ends up `Failed` is rejected, and
+//! one that ends up `Errored` was given up on after its retries. Both are
+//! sent, with the status they ended up with. Copies of events are kept until
+//! their status is known, so events are only tracked while the output is
+//! consumed, and only up to a limit: beyond it, events aren't copied at all
+//! and are counted as dropped.
+
+use super::fanout::Fanout;
+use crate::{
+    event::{BatchNotifier, BatchStatus, BatchStatusReceiver, Event},
+    internal_events::{DeadLetterEventsDropped, DeadLetterEventsSent},
+};
+use chrono::Utc;
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt, StreamExt,
+};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The number of events waited on at once. As many again are queued until
+/// earlier events are finalized, and events beyond those aren't tracked.
+const MAX_PENDING: usize = 1_000;
+
+pub struct DeadLetter {
+    tx: mpsc::Sender<(Event, BatchStatusReceiver)>,
+}
+
+impl DeadLetter {
+    /// Returns the tracker of the events of the sink, along with the future
+    /// sending rejected events to `output`. That future completes once the
+    /// tracker is dropped and all tracked events are finalized.
+    pub fn new(id: &str, typetag: &'static str, output: Fanout) -> (Self, BoxFuture<'static, ()>) {
+        let (tx, rx) = mpsc::channel(MAX_PENDING);
+        let id = id.to_owned();
+
+        let forward = ReceiverStream::new(rx)
+            .map(|(event, status)| status.map(move |status| (event, status)))
+            .buffer_unordered(MAX_PENDING)
+            .filter(|(_, status)| future::ready(*status != BatchStatus::Delivered))
+            .map(move |(event, status)| {
+                emit!(DeadLetterEventsSent { count: 1 });
+                Ok(annotate(event, &id, typetag, status))
+            })
+            .forward(output)
+            .map(|_| ())
+            .boxed();
+
+        (Self { tx }, forward)
+    }
+
+    /// Keeps a copy of the event, until the sink finalizes it. The event isn't
+    /// copied if too many events are already tracked.
+    pub fn track(&self, mut event: Event) -> Event {
+        let permit = match self.tx.try_reserve() {
+            Ok(permit) => permit,
+            Err(TrySendError::Full(())) => {
+                emit!(DeadLetterEventsDropped { count: 1 });
+                return event;
+            }
+            // The receiving end only closes along with the sink.
+            Err(TrySendError::Closed(())) => return event,
+        };
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let mut copy = event.clone();
+        // The copy doesn't hold up the acknowledgement of the event.
+        drop(copy.metadata_mut().take_finalizers());
+        event.add_batch_notifier(batch);
+        permit.send((copy, receiver));
+        event
+    }
+}
+
+/// Attaches the sink that rejected the event and how it was rejected.
+fn annotate(mut event: Event, id: &str, typetag: &str, status: BatchStatus) -> Event {
+    let status = match status {
+        BatchStatus::Delivered => "delivered",
+        BatchStatus::Errored => "errored",
+        BatchStatus::Failed => "failed",
+    };
+    match &mut event {
+        Event::Log(log) => {
+            log.insert("dead_letter.component_id", id.to_owned());
+            log.insert("dead_letter.component_type", typetag.to_owned());
+            log.insert("dead_letter.status", status);
+            log.insert("dead_letter.rejected_at", Utc::now());
+        }
+        Event::Metric(metric) => {
+            metric.insert_tag("dead_letter_component_id".into(), id.to_owned());
+            metric.insert_tag("dead_letter_component_type".into(), typetag.to_owned());
+            metric.insert_tag("dead_letter_status".into(), status.to_owned());
+        }
+        Event::Trace(trace) => {
+            trace.insert("dead_letter.component_id", id.to_owned());
+            trace.insert("dead_letter.component_type", typetag.to_owned());
+            trace.insert("dead_letter.status", status);
+            trace.insert("dead_letter.rejected_at", Utc::now());
+        }
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventStatus, LogEvent};
+    use futures::{channel::mpsc as futures_mpsc, SinkExt};
+
+    #[tokio::test]
+    async fn sends_rejected_events() {
+        let (mut output, control) = Fanout::new();
+        let (tx, rx) = futures_mpsc::unbounded();
+        output.add("consumer".into(), Box::new(tx.sink_map_err(|_| ())));
+        drop(control);
+
+        let (dead_letter, forward) = DeadLetter::new("out", "http", output);
+        let forward = tokio::spawn(forward);
+
+        let mut rejected = dead_letter.track(LogEvent::from("rejected").into());
+        let mut delivered = dead_letter.track(LogEvent::from("delivered").into());
+        rejected.metadata_mut().update_status(EventStatus::Failed);
+        delivered
+            .metadata_mut()
+            .update_status(EventStatus::Delivered);
+        drop((rejected, delivered));
+
+        let mut errored = dead_letter.track(LogEvent::from("errored").into());
+        errored.metadata_mut().update_status(EventStatus::Errored);
+        drop((errored, dead_letter));
+        forward.await.unwrap();
+
+        let mut events = rx.collect::<Vec<_>>().await;
+        events.sort_by_key(|event| event.as_log()["message"].to_string_lossy());
+        assert_eq!(events.len(), 2);
+
+        let log = events[0].as_log();
+        assert_eq!(log["message"], "errored".into());
+        assert_eq!(log["dead_letter.status"], "errored".into());
+
+        let log = events[1].as_log();
+        assert_eq!(log["message"], "rejected".into());
+        assert_eq!(log["dead_letter.component_id"], "out".into());
+        assert_eq!(log["dead_letter.component_type"], "http".into());
+        assert_eq!(log["dead_letter.status"], "failed".into());
+    }
+
+    #[tokio::test]
+    async fn doesnt_track_beyond_limit() {
+        let (output, _control) = Fanout::new();
+        // Nothing is forwarded, so the queue of the tracker fills up.
+        let (dead_letter, _forward) = DeadLetter::new("out", "http", output);
+
+        for _ in 0..MAX_PENDING {
+            let event = dead_letter.track(LogEvent::from("tracked").into());
+            assert!(event.metadata().awaits_delivery());
+        }
+        let event = dead_letter.track(LogEvent::from("untracked").into());
+        assert!(!event.metadata().awaits_delivery());
+    }
+}
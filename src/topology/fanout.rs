@@ -3,6 +3,7 @@ use futures::{channel::mpsc, future, stream::Fuse, Sink, Stream, StreamExt};
 use std::{
     fmt,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -32,6 +33,7 @@ pub struct Fanout {
     sinks: Vec<(String, Option<Pin<RouterSink>>)>,
     i: usize,
     control_channel: Fuse<mpsc::UnboundedReceiver<ControlMessage>>,
+    lineage: Option<Arc<str>>,
 }
 
 impl Fanout {
@@ -42,11 +44,17 @@ impl Fanout {
             sinks: vec![],
             i: 0,
             control_channel: control_rx.fuse(),
+            lineage: None,
         };
 
         (fanout, control_tx)
     }
 
+    /// Record `id` in the lineage of every event sent through the fanout.
+    pub fn record_lineage(&mut self, id: &str) {
+        self.lineage = Some(Arc::from(id));
+    }
+
     pub fn add(&mut self, id: String, sink: RouterSink) {
         assert!(
             !self.sinks.iter().any(|(n, _)| n == &id),
@@ -156,7 +164,11 @@ impl Sink<Event> for Fanout {
         Poll::Ready(Ok(()))
     }
 
-    fn start_send(mut self: Pin<&mut Self>, item: Event) -> Result<(), ()> {
+    fn start_send(mut self: Pin<&mut Self>, mut item: Event) -> Result<(), ()> {
+        if let Some(id) = &self.lineage {
+            item.metadata_mut().record_lineage(Arc::clone(id));
+        }
+
         let mut i = 1;
         while let Some((_, sink)) = self.sinks.get_mut(i) {
             if let Some(sink) = sink.as_mut() {
@@ -422,6 +434,35 @@ mod tests {
         fanout.send(recs[1].clone()).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn fanout_records_lineage() {
+        let (tx_a, rx_a) = mpsc::unbounded();
+        let tx_a = Box::new(tx_a.sink_map_err(|_| unreachable!()));
+        let (tx_b, rx_b) = mpsc::unbounded();
+        let tx_b = Box::new(tx_b.sink_map_err(|_| unreachable!()));
+
+        let (mut fanout, _fanout_control) = Fanout::new();
+        fanout.record_lineage("transform");
+
+        fanout.add("a".to_string(), tx_a);
+        fanout.add("b".to_string(), tx_b);
+
+        let mut event = make_events(1).remove(0);
+        event.metadata_mut().record_lineage("source".into());
+        fanout.send(event).await.unwrap();
+
+        for rx in [rx_a, rx_b] {
+            let events = collect_ready(rx).await;
+            let lineage = events[0]
+                .metadata()
+                .lineage()
+                .iter()
+                .map(|id| &**id)
+                .collect::<Vec<_>>();
+            assert_eq!(lineage, vec!["source", "transform"]);
+        }
+    }
+
     #[tokio::test]
     async fn fanout_replace() {
         let (tx_a1, rx_a1) = mpsc::unbounded();
@@ -7,6 +7,8 @@
 //! each type of component.
 
 pub mod builder;
+mod channel;
+mod dead_letter;
 pub mod fanout;
 mod running;
 mod task;
@@ -17,7 +19,6 @@ mod test;
 use crate::{
     buffers::{self, EventStream},
     config::{Config, ConfigDiff},
-    event::Event,
     topology::{
         builder::Pieces,
         task::{Task, TaskOutput},
@@ -36,7 +37,7 @@ use tokio::sync::{mpsc, watch};
 type TaskHandle = tokio::task::JoinHandle<Result<TaskOutput, ()>>;
 
 type BuiltBuffer = (
-    buffers::BufferInputCloner<Event>,
+    channel::InputCloner,
     Arc<Mutex<Option<Pin<EventStream>>>>,
     buffers::Acker,
 );
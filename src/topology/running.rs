@@ -1,13 +1,11 @@
 use crate::topology::builder;
 use crate::topology::fanout::{ControlChannel, ControlMessage};
 use crate::topology::{
-    build_or_log_errors, handle_errors, retain, take_healthchecks, BuiltBuffer, Outputs,
-    TaskHandle, WatchRx, WatchTx,
+    build_or_log_errors, channel::InputCloner, handle_errors, retain, take_healthchecks,
+    BuiltBuffer, Outputs, TaskHandle, WatchRx, WatchTx,
 };
 use crate::{
-    buffers,
-    config::{Config, ConfigDiff, HealthcheckOptions, Resource},
-    event::Event,
+    config::{dead_letter_output, Config, ConfigDiff, HealthcheckOptions, Resource},
     shutdown::SourceShutdownCoordinator,
     topology::{builder::Pieces, task::TaskOutput},
     trigger::DisabledTrigger,
@@ -25,7 +23,7 @@ use tracing::Instrument;
 
 #[allow(dead_code)]
 pub struct RunningTopology {
-    inputs: HashMap<String, buffers::BufferInputCloner<Event>>,
+    inputs: HashMap<String, InputCloner>,
     outputs: HashMap<String, ControlChannel>,
     source_tasks: HashMap<String, TaskHandle>,
     tasks: HashMap<String, TaskHandle>,
@@ -387,6 +385,10 @@ impl RunningTopology {
         // Second pass for final cleanup
 
         // Cleanup removed
+        for id in diff.sinks.removed_and_changed() {
+            self.remove_outputs(&dead_letter_output(id));
+        }
+
         for id in &diff.sinks.to_remove {
             let previous = self.tasks.remove(id).unwrap();
             if wait_for_sinks.contains(id) {
@@ -434,6 +436,14 @@ impl RunningTopology {
             self.setup_outputs(id, new_pieces).await;
         }
 
+        // Dead letter outputs of sinks can be inputs of transforms too.
+        for id in diff.sinks.changed_and_added() {
+            let output = dead_letter_output(id);
+            if new_pieces.outputs.contains_key(&output) {
+                self.setup_outputs(&output, new_pieces).await;
+            }
+        }
+
         for id in &diff.transforms.to_change {
             self.replace_inputs(id, new_pieces).await;
         }
@@ -587,7 +597,7 @@ impl RunningTopology {
                 // be present.
                 if let Some(input) = self.inputs.get(sink_id) {
                     let _ = output
-                        .send(ControlMessage::Add(sink_id.clone(), input.get()))
+                        .send(ControlMessage::Add(sink_id.clone(), input.get(id)))
                         .await;
                 }
             }
@@ -598,7 +608,7 @@ impl RunningTopology {
                 // not be present.
                 if let Some(input) = self.inputs.get(transform_id) {
                     let _ = output
-                        .send(ControlMessage::Add(transform_id.clone(), input.get()))
+                        .send(ControlMessage::Add(transform_id.clone(), input.get(id)))
                         .await;
                 }
             }
@@ -616,7 +626,7 @@ impl RunningTopology {
                 .outputs
                 .get_mut(&input)
                 .unwrap()
-                .send(ControlMessage::Add(id.to_string(), tx.get()))
+                .send(ControlMessage::Add(id.to_string(), tx.get(&input)))
                 .await;
         }
 
@@ -657,7 +667,7 @@ impl RunningTopology {
                 .outputs
                 .get_mut(input)
                 .unwrap()
-                .send(ControlMessage::Add(id.to_string(), tx.get()))
+                .send(ControlMessage::Add(id.to_string(), tx.get(&input)))
                 .await;
         }
 
@@ -667,7 +677,7 @@ impl RunningTopology {
                 .outputs
                 .get_mut(input)
                 .unwrap()
-                .send(ControlMessage::Replace(id.to_string(), Some(tx.get())))
+                .send(ControlMessage::Replace(id.to_string(), Some(tx.get(input))))
                 .await;
         }
 
@@ -1,18 +1,23 @@
 use crate::event::LogEvent;
 use metrics_tracing_context::MetricsLayer;
 use once_cell::sync::OnceCell;
-use std::sync::{Mutex, MutexGuard};
+use std::{
+    cell::Cell,
+    fmt,
+    sync::{Mutex, MutexGuard},
+};
 use tokio::sync::broadcast::{self, Receiver, Sender};
 use tracing::{
     dispatcher::{set_global_default, Dispatch},
+    field::{Field, Visit},
     span::Span,
     subscriber::Interest,
-    Id, Metadata, Subscriber,
+    Id, Level, Metadata, Subscriber,
 };
 use tracing_core::span;
 use tracing_limit::RateLimitedLayer;
 use tracing_log::LogTracer;
-use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{layer::SubscriberExt, registry::LookupSpan};
 
 /// BUFFER contains all of the internal log events generated by Vector
 /// before the topology has been initialized. It will be cleared (set to
@@ -24,6 +29,15 @@ static BUFFER: OnceCell<Mutex<Option<Vec<LogEvent>>>> = OnceCell::new();
 /// initialized.
 static SENDER: OnceCell<Sender<LogEvent>> = OnceCell::new();
 
+/// COMPONENT_EVENT_SENDER receives a copy of the warnings and errors
+/// logged within a component, along with the context of the component.
+static COMPONENT_EVENT_SENDER: OnceCell<Sender<LogEvent>> = OnceCell::new();
+
+thread_local! {
+    /// Type name of the internal event whose logs are being emitted.
+    static INTERNAL_EVENT: Cell<Option<&'static str>> = Cell::new(None);
+}
+
 pub use tracing_futures::Instrument;
 pub use tracing_tower::{InstrumentableService, InstrumentedService};
 
@@ -159,11 +173,116 @@ pub fn subscribe() -> TraceSubscription {
     TraceSubscription { buffer, receiver }
 }
 
+/// Subscribes to the warnings and errors logged within components, which
+/// have the `component_id`, `component_kind` and `component_type` of the
+/// component, and an `error_code` naming the internal event that logged them.
+pub fn subscribe_component_events() -> Receiver<LogEvent> {
+    COMPONENT_EVENT_SENDER
+        .get_or_init(|| broadcast::channel(99).0)
+        .subscribe()
+}
+
+/// Runs `emit_logs` with the name of the internal event `E`, so the events
+/// it logs can be told apart.
+pub fn with_internal_event<E>(emit_logs: impl FnOnce()) {
+    INTERNAL_EVENT.with(|name| {
+        let previous = name.replace(Some(std::any::type_name::<E>()));
+        emit_logs();
+        name.set(previous);
+    });
+}
+
+/// `vector::internal_events::http::HttpBadRequest<'_>` -> `HttpBadRequest`
+fn internal_event_name(type_name: &str) -> &str {
+    let type_name = type_name.split('<').next().unwrap_or(type_name);
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+/// Kept in the extensions of the spans of the components.
+#[derive(Clone, Debug, Default)]
+struct ComponentContext {
+    id: String,
+    kind: String,
+    component_type: String,
+}
+
+impl ComponentContext {
+    fn from_attributes(attributes: &span::Attributes<'_>) -> Option<Self> {
+        attributes.metadata().fields().field("component_id")?;
+        let mut context = Self::default();
+        attributes.record(&mut context);
+        Some(context)
+    }
+}
+
+impl Visit for ComponentContext {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "component_id" => self.id = value.to_owned(),
+            "component_kind" => self.kind = value.to_owned(),
+            "component_type" => self.component_type = value.to_owned(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_str(field, &format!("{:?}", value));
+    }
+}
+
 struct BroadcastSubscriber<S> {
     subscriber: S,
 }
 
-impl<S: Subscriber + 'static> Subscriber for BroadcastSubscriber<S> {
+impl<S> BroadcastSubscriber<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    /// Context of the innermost component span the event is within.
+    fn component_context(&self, event: &tracing::Event<'_>) -> Option<ComponentContext> {
+        let id = if event.is_root() {
+            return None;
+        } else if let Some(id) = event.parent() {
+            id.clone()
+        } else {
+            self.subscriber.current_span().id()?.clone()
+        };
+
+        let mut span = self.subscriber.span(&id)?;
+        loop {
+            let context = span.extensions().get::<ComponentContext>().cloned();
+            if context.is_some() {
+                return context;
+            }
+            span = span.parent()?;
+        }
+    }
+
+    fn send_component_event(&self, event: &tracing::Event<'_>) {
+        let sender = match COMPONENT_EVENT_SENDER.get() {
+            Some(sender) if sender.receiver_count() > 0 => sender,
+            _ => return,
+        };
+        if *event.metadata().level() > Level::WARN {
+            return;
+        }
+        if let Some(context) = self.component_context(event) {
+            let mut log = LogEvent::from(event);
+            log.insert("component_id", context.id);
+            log.insert("component_kind", context.kind);
+            log.insert("component_type", context.component_type);
+            if let Some(type_name) = INTERNAL_EVENT.with(Cell::get) {
+                log.insert("error_code", internal_event_name(type_name).to_owned());
+            }
+            let _ = sender.send(log); // Ignore errors
+        }
+    }
+}
+
+impl<S> Subscriber for BroadcastSubscriber<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + 'static,
+{
     #[inline]
     fn enabled(&self, metadata: &Metadata<'_>) -> bool {
         self.subscriber.enabled(metadata)
@@ -171,7 +290,13 @@ impl<S: Subscriber + 'static> Subscriber for BroadcastSubscriber<S> {
 
     #[inline]
     fn new_span(&self, span: &tracing::span::Attributes<'_>) -> Id {
-        self.subscriber.new_span(span)
+        let id = self.subscriber.new_span(span);
+        if let Some(context) = ComponentContext::from_attributes(span) {
+            if let Some(span) = self.subscriber.span(&id) {
+                span.extensions_mut().insert(context);
+            }
+        }
+        id
     }
 
     #[inline]
@@ -192,6 +317,7 @@ impl<S: Subscriber + 'static> Subscriber for BroadcastSubscriber<S> {
         if let Some(sender) = SENDER.get() {
             let _ = sender.send(event.into()); // Ignore errors
         }
+        self.send_component_event(event);
         self.subscriber.event(event)
     }
 
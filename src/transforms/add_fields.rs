@@ -85,9 +85,9 @@ impl AddFields {
         let mut with_templates = IndexMap::with_capacity(fields.len());
         for (k, v) in fields.drain(..) {
             let maybe_template = match v {
-                Value::Bytes(s) => match Template::try_from(String::from_utf8(s.to_vec())?) {
+                Value::String(s) => match Template::try_from(String::from_utf8(s.to_vec())?) {
                     Ok(t) => TemplateOrValue::from(t),
-                    Err(_) => TemplateOrValue::from(Value::Bytes(s)),
+                    Err(_) => TemplateOrValue::from(Value::String(s)),
                 },
                 v => TemplateOrValue::from(v),
             };
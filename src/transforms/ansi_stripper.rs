@@ -59,7 +59,7 @@ impl FunctionTransform for AnsiStripper {
 
         match log.get_mut(&self.field) {
             None => emit!(AnsiStripperFieldMissing { field: &self.field }),
-            Some(Value::Bytes(ref mut bytes)) => {
+            Some(Value::Bytes(ref mut bytes) | Value::String(ref mut bytes)) => {
                 match strip_ansi_escapes::strip(&bytes) {
                     Ok(b) => *bytes = b.into(),
                     Err(error) => emit!(AnsiStripperFailed {
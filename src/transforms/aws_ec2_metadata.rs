@@ -209,6 +209,13 @@ impl Ec2MetadataTransform {
                         }
                     });
                 }
+                Event::Trace(ref mut trace) => {
+                    read_ref.into_iter().for_each(|(k, v)| {
+                        if let Some(value) = v.get_one() {
+                            trace.insert(k.clone(), value.clone());
+                        }
+                    });
+                }
             }
         }
 
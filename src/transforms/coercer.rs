@@ -150,7 +150,7 @@ mod tests {
     #[tokio::test]
     async fn leaves_unnamed_fields_as_is() {
         let log = parse_it("").await;
-        assert_eq!(log["other"], Value::Bytes("no".into()));
+        assert_eq!(log["other"], Value::String("no".into()));
     }
 
     #[tokio::test]
@@ -2,7 +2,7 @@ use crate::{
     config::{
         log_schema, DataType, GenerateConfig, GlobalOptions, TransformConfig, TransformDescription,
     },
-    event::{Event, Value},
+    event::{DropReason, Event, Value},
     internal_events::DedupeEventDiscarded,
     transforms::{TaskTransform, Transform},
 };
@@ -138,6 +138,7 @@ fn type_id_for_value(val: &Value) -> TypeId {
         Value::Map(_) => 5,
         Value::Array(_) => 6,
         Value::Null => 7,
+        Value::String(_) => 8,
     }
 }
 
@@ -151,9 +152,12 @@ impl Dedupe {
         }
     }
 
-    fn transform_one(&mut self, event: Event) -> Option<Event> {
+    fn transform_one(&mut self, mut event: Event) -> Option<Event> {
         let cache_entry = build_cache_entry(&event, &self.fields);
         if self.cache.put(cache_entry, true).is_some() {
+            event
+                .metadata_mut()
+                .set_drop_reason(Some(DropReason::Deduplicated));
             emit!(DedupeEventDiscarded { event });
             None
         } else {
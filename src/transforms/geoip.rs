@@ -83,10 +83,12 @@ impl TransformConfig for GeoipConfig {
 }
 
 // MaxMind GeoIP database files have a type field we can use to recognize specific
-// products. If we encounter one of these two types, we look for ASN/ISP information;
-// otherwise we expect to be working with a City database.
+// products. If we encounter one of the first two types, we look for ASN/ISP information,
+// if we encounter the third one we look for the connection type; otherwise we expect to
+// be working with a City database.
 const ASN_DATABASE_TYPE: &str = "GeoLite2-ASN";
 const ISP_DATABASE_TYPE: &str = "GeoIP2-ISP";
+const CONNECTION_TYPE_DATABASE_TYPE: &str = "GeoIP2-Connection-Type";
 
 impl Geoip {
     pub fn new(database: String, source: String, target: String) -> crate::Result<Self> {
@@ -102,6 +104,10 @@ impl Geoip {
         self.dbreader.metadata.database_type == ASN_DATABASE_TYPE
             || self.dbreader.metadata.database_type == ISP_DATABASE_TYPE
     }
+
+    fn has_connection_type_db(&self) -> bool {
+        self.dbreader.metadata.database_type == CONNECTION_TYPE_DATABASE_TYPE
+    }
 }
 
 #[derive(Default, Serialize)]
@@ -112,6 +118,11 @@ struct Isp<'a> {
     organization: &'a str,
 }
 
+#[derive(Default, Serialize)]
+struct ConnectionType<'a> {
+    connection_type: &'a str,
+}
+
 #[derive(Default, Serialize)]
 struct City<'a> {
     city_name: &'a str,
@@ -126,6 +137,7 @@ struct City<'a> {
 impl FunctionTransform for Geoip {
     fn transform(&mut self, output: &mut Vec<Event>, mut event: Event) {
         let mut isp: Isp = Default::default();
+        let mut connection_type: ConnectionType = Default::default();
         let mut city: City = Default::default();
         let target_field = self.target.clone();
         let ipaddress = event
@@ -149,6 +161,15 @@ impl FunctionTransform for Geoip {
                             isp.organization = organization;
                         }
                     }
+                } else if self.has_connection_type_db() {
+                    if let Ok(data) = self
+                        .dbreader
+                        .lookup::<maxminddb::geoip2::ConnectionType>(ip)
+                    {
+                        if let Some(kind) = data.connection_type {
+                            connection_type.connection_type = kind;
+                        }
+                    }
                 } else if let Ok(data) = self.dbreader.lookup::<maxminddb::geoip2::City>(ip) {
                     if let Some(city_names) = data.city.and_then(|c| c.names) {
                         if let Some(city_name) = city_names.get("en") {
@@ -191,6 +212,8 @@ impl FunctionTransform for Geoip {
 
         let json_value = if self.has_isp_db() {
             serde_json::to_value(isp)
+        } else if self.has_connection_type_db() {
+            serde_json::to_value(connection_type)
         } else {
             serde_json::to_value(city)
         };
@@ -352,6 +375,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn geoip_connection_type_lookup_success() {
+        let new_event = parse_one(
+            r#"{"remote_addr": "1.0.1.200", "request_path": "foo/bar"}"#,
+            "tests/data/GeoIP2-Connection-Type-Test.mmdb",
+        );
+
+        let geodata = new_event
+            .as_log()
+            .get("geo.connection_type")
+            .unwrap()
+            .to_string_lossy();
+        assert_eq!(geodata, "Cellular");
+    }
+
+    #[test]
+    fn geoip_connection_type_lookup_no_results() {
+        let new_event = parse_one(
+            r#"{"remote_addr": "10.1.12.1", "request_path": "foo/bar"}"#,
+            "tests/data/GeoIP2-Connection-Type-Test.mmdb",
+        );
+
+        let geodata = new_event
+            .as_log()
+            .get("geo.connection_type")
+            .unwrap()
+            .to_string_lossy();
+        assert_eq!(geodata, "");
+    }
+
     fn parse_one(text: &str, database: &str) -> Event {
         let mut parser = JsonParser::from(JsonParserConfig::default());
         let event = Event::from(text);
@@ -231,8 +231,8 @@ mod tests {
     #[tokio::test]
     async fn it_separates_whitespace() {
         let log = parse_log("foo=bar beep=bop", None, None, true, &[], None, None, None).await;
-        assert_eq!(log["foo"], Value::Bytes("bar".into()));
-        assert_eq!(log["beep"], Value::Bytes("bop".into()));
+        assert_eq!(log["foo"], Value::String("bar".into()));
+        assert_eq!(log["beep"], Value::String("bop".into()));
     }
 
     #[tokio::test]
@@ -248,8 +248,8 @@ mod tests {
             None,
         )
         .await;
-        assert_eq!(log["foo"], Value::Bytes("bar".into()));
-        assert_eq!(log["beep"], Value::Bytes("bop".into()));
+        assert_eq!(log["foo"], Value::String("bar".into()));
+        assert_eq!(log["beep"], Value::String("bop".into()));
     }
 
     #[tokio::test]
@@ -265,8 +265,8 @@ mod tests {
             None,
         )
         .await;
-        assert_eq!(log["foo"], Value::Bytes("bar".into()));
-        assert_eq!(log["beep"], Value::Bytes("bop".into()));
+        assert_eq!(log["foo"], Value::String("bar".into()));
+        assert_eq!(log["beep"], Value::String("bop".into()));
         assert_eq!(log["score"], Value::Integer(10));
     }
 
@@ -284,8 +284,8 @@ mod tests {
         )
         .await;
 
-        assert_eq!(log["foo"], Value::Bytes("bar".into()));
-        assert_eq!(log["beep"], Value::Bytes("bop".into()));
+        assert_eq!(log["foo"], Value::String("bar".into()));
+        assert_eq!(log["beep"], Value::String("bop".into()));
         assert_eq!(log["score"], Value::Integer(10));
     }
 
@@ -302,8 +302,8 @@ mod tests {
             None,
         )
         .await;
-        assert_eq!(log["foo"], Value::Bytes("=bar".into()));
-        assert_eq!(log["beep"], Value::Bytes("bop=bap".into()));
+        assert_eq!(log["foo"], Value::String("=bar".into()));
+        assert_eq!(log["beep"], Value::String("bop=bap".into()));
         assert_eq!(log["score"], Value::Integer(10));
     }
 
@@ -321,7 +321,7 @@ mod tests {
         )
         .await;
         assert!(log.contains("score"));
-        assert_eq!(log["score"], Value::Bytes("".into()))
+        assert_eq!(log["score"], Value::String("".into()))
     }
 
     #[tokio::test]
@@ -355,7 +355,7 @@ mod tests {
             None,
         )
         .await;
-        assert_eq!(log["bop"], Value::Bytes("[beep]".into()))
+        assert_eq!(log["bop"], Value::String("[beep]".into()))
     }
 
     #[tokio::test]
@@ -390,7 +390,7 @@ mod tests {
         )
         .await;
         assert_eq!(log["foo"], Value::Integer(0));
-        assert_eq!(log["bop"], Value::Bytes("beep".into()));
+        assert_eq!(log["bop"], Value::String("beep".into()));
         assert_eq!(log["score"], Value::Integer(78));
     }
 }
@@ -180,7 +180,7 @@ mod tests {
         assert_eq!(log["number"], Value::Float(42.3));
         assert_eq!(log["flag"], Value::Boolean(true));
         assert_eq!(log["code"], Value::Integer(1234));
-        assert_eq!(log["rest"], Value::Bytes("word".into()));
+        assert_eq!(log["rest"], Value::String("word".into()));
     }
 
     #[tokio::test]
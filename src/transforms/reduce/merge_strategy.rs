@@ -58,7 +58,7 @@ impl ConcatMerger {
 
 impl ReduceValueMerger for ConcatMerger {
     fn add(&mut self, v: Value) -> Result<(), String> {
-        if let Value::Bytes(b) = v {
+        if let Value::Bytes(b) | Value::String(b) = v {
             self.v.extend(&[self.join_by as u8]);
             self.v.extend_from_slice(&b);
             Ok(())
@@ -71,7 +71,7 @@ impl ReduceValueMerger for ConcatMerger {
     }
 
     fn insert_into(self: Box<Self>, k: String, v: &mut LogEvent) -> Result<(), String> {
-        v.insert(k, Value::Bytes(self.v.into()));
+        v.insert(k, Value::from(self.v.freeze()));
         Ok(())
     }
 }
@@ -371,6 +371,7 @@ impl From<Value> for Box<dyn ReduceValueMerger> {
             Value::Null => Box::new(DiscardMerger::new(v)),
             Value::Boolean(_) => Box::new(DiscardMerger::new(v)),
             Value::Bytes(_) => Box::new(DiscardMerger::new(v)),
+            Value::String(_) => Box::new(DiscardMerger::new(v)),
             Value::Array(_) => Box::new(DiscardMerger::new(v)),
         }
     }
@@ -403,7 +404,7 @@ pub fn get_value_merger(v: Value, m: &MergeStrategy) -> Result<Box<dyn ReduceVal
             )),
         },
         MergeStrategy::Concat => match v {
-            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, ' '))),
+            Value::Bytes(b) | Value::String(b) => Ok(Box::new(ConcatMerger::new(b, ' '))),
             Value::Array(a) => Ok(Box::new(ConcatArrayMerger::new(a))),
             _ => Err(format!(
                 "expected string or array value, found: '{}'",
@@ -411,7 +412,7 @@ pub fn get_value_merger(v: Value, m: &MergeStrategy) -> Result<Box<dyn ReduceVal
             )),
         },
         MergeStrategy::ConcatNewline => match v {
-            Value::Bytes(b) => Ok(Box::new(ConcatMerger::new(b, '\n'))),
+            Value::Bytes(b) | Value::String(b) => Ok(Box::new(ConcatMerger::new(b, '\n'))),
             _ => Err(format!(
                 "expected string value, found: '{}'",
                 v.to_string_lossy()
@@ -1,6 +1,6 @@
 use crate::{
     config::{DataType, GlobalOptions, TransformConfig, TransformDescription},
-    event::{Event, VrlTarget},
+    event::{DropReason, Event, VrlTarget},
     internal_events::{RemapMappingAbort, RemapMappingError},
     transforms::{FunctionTransform, Transform},
     Result,
@@ -128,7 +128,7 @@ impl FunctionTransform for Remap {
                 });
 
                 if !self.drop_on_abort {
-                    output.push(original_event.expect("event will be set"))
+                    output.push(failed_event(original_event, DropReason::MappingAborted))
                 }
             }
             Err(Terminate::Error(error)) => {
@@ -138,13 +138,21 @@ impl FunctionTransform for Remap {
                 });
 
                 if !self.drop_on_error {
-                    output.push(original_event.expect("event will be set"))
+                    output.push(failed_event(original_event, DropReason::MappingFailed))
                 }
             }
         }
     }
 }
 
+/// The original event, marked with why its mapping didn't go through, so
+/// downstream components can tell it apart from successfully mapped events.
+fn failed_event(original_event: Option<Event>, reason: DropReason) -> Event {
+    let mut event = original_event.expect("event will be set");
+    event.metadata_mut().set_drop_reason(Some(reason));
+    event
+}
+
 #[derive(Debug, Snafu)]
 pub enum BuildError {
     #[snafu(display("must provide exactly one of `source` or `file` configuration"))]
@@ -303,6 +311,10 @@ mod tests {
         assert_eq!(event.as_log().get("bar"), Some(&Value::from("is a string")));
         assert!(event.as_log().get("foo").is_none());
         assert!(event.as_log().get("baz").is_none());
+        assert_eq!(
+            event.metadata().drop_reason(),
+            Some(DropReason::MappingFailed)
+        );
     }
 
     #[test]
@@ -382,6 +394,10 @@ mod tests {
         assert_eq!(event.as_log().get("bar"), Some(&Value::from("is a string")));
         assert!(event.as_log().get("foo").is_none());
         assert!(event.as_log().get("baz").is_none());
+        assert_eq!(
+            event.metadata().drop_reason(),
+            Some(DropReason::MappingAborted)
+        );
     }
 
     #[test]
@@ -216,6 +216,45 @@ async fn nonexistant_input() {
     );
 }
 
+#[cfg(all(
+    feature = "sources-socket",
+    feature = "transforms-sample",
+    feature = "sinks-socket"
+))]
+#[tokio::test]
+async fn capacity_of_nonexistent_edge() {
+    let err = load(
+        r#"
+        [sources.in]
+        type = "socket"
+        mode = "tcp"
+        address = "127.0.0.1:1235"
+
+        [transforms.sample]
+        type = "sample"
+        inputs = ["in"]
+        input_capacity = 500
+        input_capacities.asdf = 1000
+        rate = 10
+
+        [sinks.out]
+        type = "socket"
+        mode = "tcp"
+        inputs = ["sample"]
+        encoding = "text"
+        address = "127.0.0.1:9999"
+        "#,
+        Some(Format::Toml),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        vec!["Transform \"sample\" has a capacity for \"asdf\", which isn't one of its inputs."]
+    );
+}
+
 #[cfg(all(
     feature = "sources-socket",
     feature = "transforms-sample",
@@ -223,6 +223,7 @@ impl FunctionTransform for MockTransform {
                     }
                     MetricValue::AggregatedHistogram { .. } => None,
                     MetricValue::AggregatedSummary { .. } => None,
+                    MetricValue::Sketch { .. } => None,
                     MetricValue::Set { .. } => {
                         let mut values = BTreeSet::new();
                         values.insert(self.suffix.clone());
@@ -237,6 +238,7 @@ impl FunctionTransform for MockTransform {
                     }));
                 }
             }
+            Event::Trace(_) => {}
         };
         output.push(event);
     }
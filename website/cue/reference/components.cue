@@ -410,6 +410,7 @@ components: {
 	#Input: {
 		logs:    bool
 		metrics: #MetricInput | null
+		traces?: bool
 	}
 
 	#LogOutput: [Name=string]: {
@@ -930,6 +931,40 @@ components: {
 				}
 			}
 
+			if Kind == "transform" {
+				input_capacity: {
+					common:      false
+					description: """
+						The number of events buffered on each edge into this transform. Every input has a
+						channel of its own, and blocks once that channel is full. See the
+						`channel_send_blocked_seconds` and `channel_queued_events` internal metrics to find
+						which edge is applying backpressure.
+						"""
+					required:    false
+					type: uint: {
+						default: 100
+						unit:    "events"
+					}
+				}
+
+				input_capacities: {
+					common:      false
+					description: "The number of events buffered on the edges from some of the inputs, overriding `input_capacity` for those inputs."
+					required:    false
+					type: object: {
+						examples: [{"my-source-id": 1000}]
+						options: {
+							"*": {
+								description: "The number of events buffered on the edge from the input."
+								required:    true
+								type: uint: unit: "events"
+							}
+						}
+					}
+				}
+			}
+
+
 			"type": {
 				description: "The component type. This is a required field for all components and tells Vector which component to use."
 				required:    true
@@ -0,0 +1,51 @@
+package metadata
+
+components: _csv: configuration: csv: {
+	common:      false
+	description: "Options for the `csv` codec, which writes the fields of events as [CSV](\(urls.rfc_4180)) rows. Required with the `csv` codec."
+	required:    false
+	warnings: []
+	type: object: {
+		examples: []
+		options: {
+			delimiter: {
+				common:      false
+				description: "The ASCII character separating the columns."
+				required:    false
+				warnings: []
+				type: string: {
+					default: ","
+					examples: [";", "\t"]
+					syntax: "literal"
+				}
+			}
+			fields: {
+				description: "The fields of events written as the columns of each row, in order. Fields missing from an event are written as empty columns, and objects and arrays as JSON."
+				required:    true
+				warnings: []
+				type: array: items: type: string: {
+					examples: ["timestamp", "host", "message"]
+					syntax: "literal"
+				}
+			}
+			header: {
+				common:      false
+				description: "Whether to write the names of the fields as the first row."
+				required:    false
+				warnings: []
+				type: bool: default: true
+			}
+			quote: {
+				common:      false
+				description: "The ASCII character quoting the values holding the delimiter, the quote, or a line break."
+				required:    false
+				warnings: []
+				type: string: {
+					default: "\""
+					examples: ["'"]
+					syntax: "literal"
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,144 @@
+package metadata
+
+components: _mqtt: {
+	features: {
+		collect: from: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT 3.1.1"
+						url:   urls.mqtt_v311
+					}
+					direction: "outgoing"
+					port:      1883
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+
+		send: to: {
+			service: services.mqtt
+			interface: {
+				socket: {
+					api: {
+						title: "MQTT 3.1.1"
+						url:   urls.mqtt_v311
+					}
+					direction: "outgoing"
+					protocols: ["tcp"]
+					ssl: "optional"
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		host: {
+			description: "The host name or IP address of the broker."
+			required:    true
+			type: string: {
+				examples: ["127.0.0.1", "broker.example.com"]
+				syntax: "literal"
+			}
+		}
+		port: {
+			common:      true
+			description: "The port of the broker."
+			required:    false
+			type: uint: {
+				default: 1883
+				unit:    null
+			}
+		}
+		client_id: {
+			common:      false
+			description: "The client ID to connect with, which has to be unique to the broker. Defaults to the ID of the source, and to one generated from the host name for the sink."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		user: {
+			common:      false
+			description: "The user name to authenticate with, along with `password`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		password: {
+			common:      false
+			description: "The password to authenticate with, along with `user`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["${MQTT_PASSWORD}"]
+				syntax: "literal"
+			}
+		}
+		keep_alive_secs: {
+			common:      false
+			description: "The interval at which the broker is pinged while no other packets are sent."
+			required:    false
+			type: uint: {
+				default: 60
+				unit:    "seconds"
+			}
+		}
+		clean_session: {
+			common:      false
+			description: "Start with a new session when connecting. Otherwise the session of the previous connection with the same client ID is resumed, along with its subscriptions and the messages in flight."
+			required:    false
+			type: bool: default: true
+		}
+		qos: {
+			common:      true
+			description: "The quality of service, either `0` (at most once) or `1` (at least once). Exactly once delivery isn't supported."
+			required:    false
+			type: uint: {
+				default: 1
+				examples: [0, 1]
+				unit: null
+			}
+		}
+	}
+
+	how_it_works: {
+		protocol_version: {
+			title: "Protocol version"
+			body: """
+				Brokers are connected to with version 3.1.1 of the protocol, which brokers supporting
+				version 5 accept too.
+				"""
+		}
+		tls: {
+			title: "TLS"
+			body: """
+				The broker is verified against `tls.ca_file` only, which is required when TLS is enabled,
+				so `tls.verify_certificate` and `tls.verify_hostname` don't apply.
+				"""
+		}
+	}
+}
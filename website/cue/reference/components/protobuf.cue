@@ -0,0 +1,48 @@
+package metadata
+
+components: _protobuf: configuration: protobuf: {
+	common:      false
+	description: "Options for the `protobuf` codec, which handles messages of any type described by a [file descriptor set](\(urls.protobuf_descriptor_set)). Required with the `protobuf` codec."
+	required:    false
+	warnings: []
+	type: object: {
+		examples: []
+		options: {
+			descriptor_set_file: {
+				description: "The file descriptor set describing the message type, along with the files it imports, as written by `protoc --include_imports --descriptor_set_out`."
+				required:    true
+				warnings: []
+				type: string: {
+					examples: ["/etc/vector/events.desc"]
+					syntax: "literal"
+				}
+			}
+			fields: {
+				common:      false
+				description: "Fields of the message, mapped to the fields of events. Other fields of the message are the fields of events with the same name, nested messages are objects, and repeated fields are arrays."
+				required:    false
+				warnings: []
+				type: object: {
+					examples: [{"service_name": "kubernetes.labels.app", "body": "message"}]
+					options: {
+						"*": {
+							description: "The field of events the field of the message is read from or written to."
+							required:    true
+							warnings: []
+							type: string: syntax: "literal"
+						}
+					}
+				}
+			}
+			message_type: {
+				description: "The full name of the message type, including its package."
+				required:    true
+				warnings: []
+				type: string: {
+					examples: ["logs.v1.Record"]
+					syntax: "literal"
+				}
+			}
+		}
+	}
+}
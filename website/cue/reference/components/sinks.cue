@@ -275,6 +275,55 @@ components: sinks: [Name=string]: {
 									}
 								}
 							}
+							circuit_breaker: {
+								common:      false
+								description: "Configures a circuit breaker stopping requests to a downstream that keeps failing."
+								required:    false
+								type: object: {
+									examples: []
+									options: {
+										enabled: {
+											common:      false
+											description: "Whether the circuit breaker is enabled. While open, the breaker holds requests back instead of sending them to a downstream that is failing, preventing retry storms."
+											required:    false
+											type: bool: default: false
+										}
+										failure_threshold: {
+											common:      false
+											description: "The number of consecutive failed requests that open the breaker. Requests that are retried, that fail with a retriable error, or that breach `latency_threshold_secs` count as failed."
+											required:    false
+											type: uint: {
+												default: 5
+												unit:    "requests"
+											}
+										}
+										latency_threshold_secs: {
+											common:      false
+											description: "Requests taking longer than this are counted as failed, so that the breaker also opens on a downstream that slows down. Must be a positive number of seconds."
+											required:    false
+											type: float: {
+												default: null
+												unit:    "seconds"
+											}
+										}
+										open_duration_secs: {
+											common:      false
+											description: "How long the breaker stays open. A single probe request is then sent, whose result closes the breaker or opens it again."
+											required:    false
+											type: uint: {
+												default: 30
+												unit:    "seconds"
+											}
+										}
+										shed: {
+											common:      false
+											description: "Sheds requests while the breaker is open instead of holding them back. The events of shed requests are sent to the [dead letter output](#dead-letter-output) of the sink, with the `errored` status, when that output is consumed. Otherwise they are dropped, and their acknowledgements fail."
+											required:    false
+											type: bool: default: false
+										}
+									}
+								}
+							}
 							concurrency: {
 								common: true
 								if features.send.request.adaptive_concurrency {
@@ -453,6 +502,35 @@ components: sinks: [Name=string]: {
 			}
 		}
 
+		dead_letter: {
+			title: "Dead letter output"
+			body: """
+				Events this component rejects permanently, such as events it can't encode or that the
+				downstream service refuses with a non-retriable response, are sent to its
+				`<component_id>.dead_letter` output. Other components can take that output as input,
+				for example to write the rejected events to object storage for replay:
+
+				```toml title="vector.toml"
+				[sinks.my-sink]
+				  inputs = ["my-source"]
+
+				[sinks.my-sink-failures]
+				  type = "aws_s3"
+				  inputs = ["my-sink.dead_letter"]
+				```
+
+				Events that fail after exhausting their retries are sent too, as are the events of
+				requests shed while the `request.circuit_breaker` is open. Rejected log and trace
+				events get a `dead_letter` field with the `component_id` and `component_type` of the
+				sink, the `status` the event ended up with, `failed` or `errored`, and the
+				`rejected_at` timestamp, while rejected metrics get `dead_letter_component_id`,
+				`dead_letter_component_type` and `dead_letter_status` tags. Events are only tracked
+				while the output is consumed, and at most 2,000 of them at once per sink: beyond
+				that, events aren't tracked and are counted by the `dead_letter_events_dropped_total`
+				metric. Dead letter outputs aren't matched by wildcards in `inputs`.
+				"""
+		}
+
 		if features.healthcheck.enabled {
 			healthchecks: {
 				title: "Health checks"
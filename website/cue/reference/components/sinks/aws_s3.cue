@@ -33,7 +33,7 @@ components: sinks: aws_s3: components._aws & {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["ndjson", "text"]
+					enum: ["avro", "csv", "ndjson", "parquet", "text"]
 				}
 			}
 			proxy: enabled: true
@@ -100,6 +100,107 @@ components: sinks: aws_s3: components._aws & {
 				syntax: "literal"
 			}
 		}
+		avro: {
+			common:      false
+			description: "Options for the `avro` codec. The `compression` option doesn't apply to Avro objects, whose blocks are compressed with `avro.compression` instead. One of `schema`, `schema_file` or `schema_registry` is required."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      false
+						description: "The codec of the blocks of the objects."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "snappy"
+							enum: {
+								none:    "No compression."
+								deflate: "Deflate compression, smaller and slower than Snappy."
+								snappy:  "[Snappy](\(urls.snappy)) compression, fast to write and to read."
+							}
+							syntax: "literal"
+						}
+					}
+					schema: {
+						common:      true
+						description: "The Avro schema of the events, as JSON. It must be a record, whose fields are read from the top-level fields of the events."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: [#"{"type": "record", "name": "Log", "fields": [{"name": "message", "type": "string"}]}"#]
+							syntax: "literal"
+						}
+					}
+					schema_file: {
+						common:      true
+						description: "A file holding the Avro schema of the events, rather than `schema`."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["/etc/vector/log.avsc"]
+							syntax: "literal"
+						}
+					}
+					schema_registry: {
+						common:      false
+						description: "Looks up the Avro schema of the events in a [Confluent Schema Registry](\(urls.confluent_schema_registry)) when Vector starts, rather than `schema`."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: []
+							options: {
+								auth: configuration._http_auth & {_args: {
+									password_example: "${SCHEMA_REGISTRY_PASSWORD}"
+									username_example: "${SCHEMA_REGISTRY_USERNAME}"
+								}}
+								subject: {
+									description: "The subject of the schema."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["logs-value"]
+										syntax: "literal"
+									}
+								}
+								tls: {
+									common:      false
+									description: "TLS options of the connections to the registry, as the `tls` options of the `http` sink."
+									required:    false
+									warnings: []
+									type: object: {
+										examples: []
+										options: {}
+									}
+								}
+								url: {
+									description: "The URL of the registry."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["http://localhost:8081"]
+										syntax: "literal"
+									}
+								}
+								version: {
+									common:      false
+									description: "The version of the subject the events are encoded with. Defaults to the latest version."
+									required:    false
+									warnings: []
+									type: uint: {
+										default: null
+										unit:    null
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 		bucket: {
 			description: "The S3 bucket name. Do not include a leading `s3://` or a trailing `/`."
 			required:    true
@@ -132,6 +233,7 @@ components: sinks: aws_s3: components._aws & {
 				syntax:  "literal"
 			}
 		}
+		csv: components._csv.configuration.csv
 		filename_append_uuid: {
 			category:    "File Naming"
 			common:      false
@@ -222,6 +324,68 @@ components: sinks: aws_s3: components._aws & {
 				syntax: "template"
 			}
 		}
+		parquet: {
+			common:      false
+			description: "Options for the `parquet` codec. The `compression` option doesn't apply to Parquet objects, which are compressed by column instead."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					compression: {
+						common:      true
+						description: "The compression codec of the columns."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "snappy"
+							enum: {
+								none:   "No compression."
+								snappy: "[Snappy](\(urls.snappy)) compression, fast to write and to query."
+								gzip:   "Gzip compression, smaller and slower than Snappy."
+							}
+							syntax: "literal"
+						}
+					}
+					row_group_size: {
+						common:      false
+						description: "The maximum number of events per row group. Query engines split their work by row group, and read fewer of them when their column statistics exclude the query."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 100000
+							unit:    "events"
+						}
+					}
+					schema: {
+						common:      true
+						description: "The columns of the objects, by name, with their type. Nested fields are referenced by their path, such as `kubernetes.pod_name`. Values that can't be converted to the type of their column are null. If not set, the schema is inferred from the events of each batch."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: [{status: "long", message: "string", timestamp: "timestamp"}]
+							options: {
+								"*": {
+									description: "The type of the column."
+									required:    true
+									warnings: []
+									type: string: {
+										enum: {
+											string:    "A UTF-8 string. Values of other types are encoded as JSON."
+											long:      "A 64-bit integer."
+											double:    "A 64-bit float."
+											boolean:   "A boolean."
+											timestamp: "A timestamp with microsecond precision, from RFC 3339 strings."
+										}
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 		server_side_encryption: {
 			category:    "Encryption"
 			common:      false
@@ -287,6 +451,33 @@ components: sinks: aws_s3: components._aws & {
 	}
 
 	how_it_works: {
+		avro: {
+			title: "Avro objects"
+			body:  """
+				With the `avro` codec, each batch is written as an [Avro](\(urls.apache_avro)) object
+				container file, holding the schema of its records in its header, so that readers such
+				as Athena, Spark, and Hive don't need it configured separately. The objects are named
+				with the `avro` extension, and their content type is `application/avro`.
+
+				Events are converted to records of the schema by their top-level fields. Fields missing
+				from an event are set to their default, or to null where the schema allows it.
+				Timestamps convert to the `timestamp-millis`, `timestamp-micros`, and `date` logical
+				types, and `decimal` and `duration` types aren't supported. Events that don't fit the
+				schema are dropped, and counted by the `encode_errors_total` metric.
+				"""
+		}
+
+		csv: {
+			title: "CSV objects"
+			body:  """
+				With the `csv` codec, each batch is written as an object of [CSV](\(urls.rfc_4180))
+				rows holding the `csv.fields` of its events, in order. Each object starts with a
+				header row naming the fields, unless `csv.header` is `false`, so that every object can
+				be read on its own. The objects are named with the `csv` extension, or `csv.gz` when
+				compressed, and their content type is `text/csv`.
+				"""
+		}
+
 		cross_account: {
 			title: "Cross account object writing"
 			body:  """
@@ -368,6 +559,26 @@ components: sinks: aws_s3: components._aws & {
 				"""
 		}
 
+		parquet: {
+			title: "Parquet objects"
+			body:  """
+				With the `parquet` codec, each batch is written as a [Parquet](\(urls.apache_parquet))
+				object, which query engines like [Athena](\(urls.aws_athena)) scan far less of than
+				JSON objects, since they only read the columns, and the row groups, that a query needs.
+				The objects are named with the `parquet` extension, and their content type is
+				`application/vnd.apache.parquet`.
+
+				Columns are set by `parquet.schema`. Without it, the schema of each object is inferred
+				from the top-level fields of its events: booleans, integers, and floats become
+				`boolean`, `long`, and `double` columns, RFC 3339 strings such as the event timestamp
+				become `timestamp` columns, and other values become `string` columns, with objects and
+				arrays encoded as JSON. Fields holding values of different types become `double`
+				columns if they hold numbers, and `string` columns otherwise. Since inferred schemas
+				can differ between objects, configure the schema for tables queried with a fixed
+				schema.
+				"""
+		}
+
 		server_side_encryption: {
 			title: "Server-Side Encryption (SSE)"
 			body:  """
@@ -416,6 +627,7 @@ components: sinks: aws_s3: components._aws & {
 	]
 
 	telemetry: metrics: {
+		encode_errors_total:     components.sources.internal_metrics.output.metrics.encode_errors_total
 		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
 		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
 	}
@@ -0,0 +1,353 @@
+package metadata
+
+components: sinks: aws_security_lake: components._aws & {
+	title: "Amazon Security Lake"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: ["AWS"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       true
+				max_bytes:    500000000
+				timeout_secs: 300
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			proxy: enabled:       true
+			request: {
+				enabled:        true
+				concurrency:    10
+				rate_limit_num: 10
+				headers:        false
+			}
+			tls: enabled: false
+			to: {
+				service: services.aws_security_lake
+
+				interface: {
+					socket: {
+						api: {
+							title: "AWS S3 API"
+							url:   urls.aws_s3_endpoints
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: [
+			"""
+				The custom source must be [registered in Security Lake](\(urls.aws_security_lake_custom_sources))
+				with the OCSF class of the events, and the credentials of the sink must be those of the role
+				Security Lake created for the source, or allowed to write to its location.
+				""",
+		]
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		account_id: {
+			description: "The ID of the AWS account the events are from, in the partitions of the objects."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["123456789012", "{{ cloud.account.uid }}"]
+				syntax: "template"
+			}
+		}
+		bucket: {
+			description: "The bucket of the data lake, as shown in the settings of Security Lake for the region."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["aws-security-data-lake-us-east-1-abcdefghijklmnop"]
+				syntax: "literal"
+			}
+		}
+		compression: {
+			common:      false
+			description: "The compression of the columns of the Parquet objects."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "snappy"
+				enum: {
+					none:   "No compression."
+					snappy: "[Snappy](\(urls.snappy)) compression, fast to write and to query."
+					gzip:   "Gzip compression, smaller and slower than Snappy."
+				}
+				syntax: "literal"
+			}
+		}
+		ocsf: {
+			description: "How events are normalized to the [OCSF](\(urls.ocsf_schema)) class of the source."
+			required:    true
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					activity_id: {
+						common:      true
+						description: "Renders to the ID of the activity of the class, such as `1` for logons of the authentication class. Values that aren't integers are recorded as `99`, the ID of other activities. Activities are unknown, `0`, if not set."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["1", "{{ activity_id }}"]
+							syntax: "template"
+						}
+					}
+					attributes: {
+						common:      true
+						description: "The attributes of the class set from the events."
+						required:    false
+						warnings: []
+						type: array: {
+							default: []
+							items: type: object: {
+								examples: []
+								options: {
+									name: {
+										description: "The path of the attribute, with nested objects separated by dots. The attributes set by the sink can't be configured."
+										required:    true
+										warnings: []
+										type: string: {
+											examples: ["user.name", "src_endpoint.ip"]
+											syntax: "literal"
+										}
+									}
+									type: {
+										common:      true
+										description: "The OCSF data type of the attribute. Values are converted to it, and left out if they can't be."
+										required:    false
+										warnings: []
+										type: string: {
+											default: "string"
+											enum: {
+												string:    "A UTF-8 string. Values of other types are converted to strings, timestamps as RFC 3339."
+												integer:   "A 32-bit integer, from integers and strings."
+												long:      "A 64-bit integer, from integers and strings."
+												float:     "A 64-bit float, from floats, integers and strings."
+												boolean:   "A boolean, from booleans and the strings `true` and `false`."
+												timestamp: "Milliseconds since the Unix epoch, from timestamps, integers and RFC 3339 strings."
+											}
+											syntax: "literal"
+										}
+									}
+									field: {
+										common:      false
+										description: "The field of the event the value is read from. Defaults to the name of the attribute."
+										required:    false
+										warnings: []
+										type: string: {
+											default: null
+											examples: ["user"]
+											syntax: "literal"
+										}
+									}
+									template: {
+										common:      false
+										description: "Renders the value from the event instead of reading it from a field."
+										required:    false
+										warnings: []
+										type: string: {
+											default: null
+											examples: ["{{ client.ip }}"]
+											syntax: "template"
+										}
+									}
+								}
+							}
+						}
+					}
+					class_uid: {
+						description: "The UID of the class of the events, such as `3002` for authentication. The category of the events is derived from it."
+						required:    true
+						warnings: []
+						type: uint: {
+							examples: [1001, 3002, 4001]
+							unit: null
+						}
+					}
+					product: {
+						description: "The product that reported the events, recorded in their `metadata.product`."
+						required:    true
+						warnings: []
+						type: object: {
+							examples: []
+							options: {
+								name: {
+									description: "The name of the product."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["sshd"]
+										syntax: "literal"
+									}
+								}
+								vendor_name: {
+									description: "The name of the vendor of the product."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["OpenBSD"]
+										syntax: "literal"
+									}
+								}
+								version: {
+									common:      false
+									description: "The version of the product."
+									required:    false
+									warnings: []
+									type: string: {
+										default: null
+										examples: ["9.3"]
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+					raw_data: {
+						common:      false
+						description: "Sets the `raw_data` attribute to the whole event, as JSON."
+						required:    false
+						warnings: []
+						type: bool: default: false
+					}
+					severity: {
+						common:      true
+						description: "Renders to the name or the ID of the severity of the event, such as `high` or `4`. Other values are recorded as `99`, the ID of other severities. Severities are unknown, `0`, if not set."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["informational", "{{ severity }}"]
+							syntax: "template"
+						}
+					}
+					version: {
+						common:      false
+						description: "The version of OCSF the events conform to, recorded in their `metadata.version`."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "1.1.0"
+							syntax:  "literal"
+						}
+					}
+				}
+			}
+		}
+		source_name: {
+			description: "The name the custom source was registered with."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		source_version: {
+			common:      false
+			description: "The version of the custom source, which the locations of custom sources of Security Lake 2 include after their name."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["1.0"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		normalization: {
+			title: "Normalization"
+			body:  """
+				Each event becomes an event of the configured OCSF class. The sink sets the attributes
+				required of every class: `class_uid`, `category_uid`, `activity_id`, `type_uid`,
+				`severity_id`, `time` from the timestamp of the event, `message` from its message, and
+				`metadata.version` and `metadata.product`. Other attributes are set from the event by
+				`ocsf.attributes`, nested in objects by the dots of their names.
+
+				The Parquet objects have a column for every attribute, whether events set it or not, so
+				that all objects of the source share their schema.
+				"""
+		}
+
+		objects: {
+			title: "Objects"
+			body:  """
+				Each batch is written as one [Parquet](\(urls.apache_parquet)) object per partition, in the
+				location Security Lake expects of custom sources:
+				`ext/{source_name}/region={region}/accountId={account_id}/eventDay={YYYYMMDD}/`, with the day
+				of the timestamp of the events. Objects are named with the time they were written and a
+				random UUID, with the `parquet` extension, and are written with the
+				`bucket-owner-full-control` ACL.
+
+				Security Lake recommends objects of 256 MB to 1 GB, written at most every five minutes for
+				each partition, which the default batch settings aim for.
+				"""
+		}
+	}
+
+	permissions: iam: [
+		{
+			platform:      "aws"
+			_service:      "s3"
+			_docs_tag:     "AmazonS3"
+			_url_fragment: "API"
+
+			policies: [
+				{
+					_action: "HeadBucket"
+					required_for: ["healthcheck"]
+				},
+				{
+					_action: "PutObject"
+				},
+				{
+					_action: "PutObjectAcl"
+				},
+			]
+		},
+	]
+
+	telemetry: metrics: {
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
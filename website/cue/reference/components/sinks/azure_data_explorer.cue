@@ -0,0 +1,252 @@
+package metadata
+
+components: sinks: azure_data_explorer: {
+	title: "Azure Data Explorer"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: ["Azure"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    10485760
+				timeout_secs: 30
+			}
+			compression: {
+				enabled: true
+				default: "gzip"
+				algorithms: ["none", "gzip"]
+				levels: ["none", "fast", "default", "best", 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+			}
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			proxy: enabled: true
+			request: {
+				enabled: true
+				headers: false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.azure_data_explorer
+
+				interface: {
+					socket: {
+						api: {
+							title: "Azure Data Explorer ingestion"
+							url:   urls.azure_data_explorer_ingestion
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		auth: {
+			description: "How the sink authenticates with [Azure Active Directory](\(urls.azure_active_directory))."
+			required:    true
+			warnings: []
+			type: object: options: {
+				authority_host: {
+					common:        false
+					description:   "The Azure Active Directory authority the tokens are requested from."
+					relevant_when: "strategy = \"client_secret\""
+					required:      false
+					warnings: []
+					type: string: {
+						default: "https://login.microsoftonline.com"
+						examples: ["https://login.microsoftonline.us"]
+						syntax: "literal"
+					}
+				}
+				client_id: {
+					description: "The client ID of the application, or of the user-assigned managed identity. The system-assigned managed identity is used if it isn't set with `managed_identity`."
+					required:    false
+					warnings: []
+					type: string: {
+						default: null
+						examples: ["${AZURE_CLIENT_ID}", "00000000-0000-0000-0000-000000000000"]
+						syntax: "literal"
+					}
+				}
+				client_secret: {
+					description:   "The client secret of the application."
+					relevant_when: "strategy = \"client_secret\""
+					required:      false
+					warnings: []
+					type: string: {
+						default: null
+						examples: ["${AZURE_CLIENT_SECRET}"]
+						syntax: "literal"
+					}
+				}
+				strategy: {
+					description: "The authentication strategy to use."
+					required:    true
+					warnings: []
+					type: string: {
+						enum: {
+							client_secret:    "The client credentials of an application registered in Azure Active Directory."
+							managed_identity: "The [managed identity](\(urls.azure_managed_identities)) of the Azure resource Vector runs on."
+						}
+						syntax: "literal"
+					}
+				}
+				tenant_id: {
+					description:   "The ID of the Azure Active Directory tenant of the application."
+					relevant_when: "strategy = \"client_secret\""
+					required:      false
+					warnings: []
+					type: string: {
+						default: null
+						examples: ["${AZURE_TENANT_ID}", "00000000-0000-0000-0000-000000000000"]
+						syntax: "literal"
+					}
+				}
+			}
+		}
+		database: {
+			description: "The database of the tables the events are ingested into."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["logs"]
+				syntax: "literal"
+			}
+		}
+		endpoint: {
+			description: "The URI of the cluster."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["https://mycluster.westeurope.kusto.windows.net"]
+				syntax: "literal"
+			}
+		}
+		ingest_endpoint: {
+			common:        false
+			description:   "The URI of the data management service of the cluster, which queued ingestion goes through. Defaults to `endpoint` with `ingest-` prefixed to its host."
+			relevant_when: "ingestion = \"queued\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["https://ingest-mycluster.westeurope.kusto.windows.net"]
+				syntax: "literal"
+			}
+		}
+		ingestion: {
+			common:      true
+			description: "How the batches are ingested."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "queued"
+				enum: {
+					queued:    "Batches are uploaded as blobs to the data management service, which ingests them within minutes, aggregating them with the other blobs of the table. Suited to large volumes."
+					streaming: "Batches are sent to the cluster, which ingests them before answering. Requires streaming ingestion to be enabled on the cluster and the table, and batches of at most 4MiB, which is lowered to 1MiB and 1 second by default."
+				}
+				syntax: "literal"
+			}
+		}
+		mapping: {
+			common:      false
+			description: "The name of a JSON [ingestion mapping](\(urls.azure_data_explorer_mappings)) of the table, which maps the fields of the events to its columns. The fields are mapped to the columns of the same name if it isn't set."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["events_mapping", "{{ application }}_mapping"]
+				syntax: "template"
+			}
+		}
+		table: {
+			description: "The table the events are ingested into."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["events", "{{ application }}_events"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		queued_ingestion: {
+			title: "Queued ingestion"
+			body:  """
+				With queued ingestion, the sink fetches the ingestion resources of the data management
+				service, which are storage containers and queues with their SAS tokens, and fetches them again
+				every hour. Each batch is uploaded to a blob of one of the containers, and a message pointing
+				at the blob is posted to one of the queues, spreading the batches over them. The events are
+				acknowledged once the message is posted, before the blob is ingested, so ingestion failures,
+				such as events not matching the mapping, are only reported by the cluster.
+				"""
+		}
+
+		partitioning: {
+			title: "Partitioning"
+			body:  """
+				`table` and `mapping` are templates, and the events are batched per table and mapping they
+				render to, so that a sink can ingest the events into several tables. Events for which they
+				fail to render are dropped.
+				"""
+		}
+
+		permissions: {
+			title: "Permissions"
+			body:  """
+				The identity of the sink needs the `Ingestor` role on the database. Tokens are requested for
+				the endpoint the batches are sent to, which is `ingest_endpoint` with queued ingestion and
+				`endpoint` with streaming ingestion, and refreshed five minutes before they expire.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		processed_bytes_total:  components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total: components.sources.internal_metrics.output.metrics.processed_events_total
+	}
+}
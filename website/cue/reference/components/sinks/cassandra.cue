@@ -0,0 +1,294 @@
+package metadata
+
+components: sinks: cassandra: {
+	title: "Cassandra"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    40960
+				max_events:   100
+				timeout_secs: 1
+			}
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			request: {
+				enabled: true
+				headers: false
+			}
+			tls: enabled: false
+			to: {
+				service: services.cassandra
+
+				interface: {
+					socket: {
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		auth: {
+			common:      false
+			description: "Password authentication with the cluster."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					username: {
+						description: "The username to authenticate with."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["vector"]
+							syntax: "literal"
+						}
+					}
+					password: {
+						description: "The password to authenticate with."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["${CASSANDRA_PASSWORD}"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+		columns: {
+			common:      true
+			description: "The columns of the table written, with the fields of the events they are read from. The insert statement is prepared from them."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: []
+					options: {
+						name: {
+							description: "The name of the column."
+							required:    true
+							warnings: []
+							type: string: {
+								examples: ["message", "host"]
+								syntax: "literal"
+							}
+						}
+						type: {
+							common:      true
+							description: "The CQL type of the column. Values are converted to it, and are null if they can't be."
+							required:    false
+							warnings: []
+							type: string: {
+								default: "text"
+								enum: {
+									text:      "Text. Values of other types are converted to text, timestamps as RFC 3339 and objects and arrays as JSON."
+									int:       "A 32-bit integer, from integers in its range and strings."
+									bigint:    "A 64-bit integer, from integers, truncated floats and strings."
+									double:    "A double precision float, from floats, integers and strings."
+									boolean:   "A boolean, from booleans and the strings `true` and `false`."
+									timestamp: "A timestamp, from timestamps and RFC 3339 strings, with millisecond precision."
+								}
+								syntax: "literal"
+							}
+						}
+						field: {
+							common:      false
+							description: "The field of the event the value is read from. Defaults to the name of the column."
+							required:    false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["http.status"]
+								syntax: "literal"
+							}
+						}
+					}
+				}
+			}
+		}
+		consistency: {
+			common:      true
+			description: "The [consistency level](\(urls.cassandra_consistency)) batches are written with."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "local_quorum"
+				enum: {
+					any:          "Written once stored by any node, hints included."
+					one:          "Written once stored by one replica."
+					two:          "Written once stored by two replicas."
+					three:        "Written once stored by three replicas."
+					quorum:       "Written once stored by a quorum of the replicas of all datacenters."
+					all:          "Written once stored by all the replicas."
+					local_quorum: "Written once stored by a quorum of the replicas of the local datacenter."
+					each_quorum:  "Written once stored by a quorum of the replicas of each datacenter."
+					local_one:    "Written once stored by one replica of the local datacenter."
+				}
+				syntax: "literal"
+			}
+		}
+		endpoints: {
+			description: "The nodes first connected to, as `host:port`. The other nodes of the cluster are discovered from them."
+			required:    true
+			warnings: []
+			type: array: items: type: string: {
+				examples: ["cassandra-1:9042", "cassandra-2:9042"]
+				syntax: "literal"
+			}
+		}
+		event_column: {
+			common:      true
+			description: "A `text` column the whole event is written to as JSON, after the other columns."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["event"]
+				syntax: "literal"
+			}
+		}
+		keyspace: {
+			description: "The keyspace of the table."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		local_datacenter: {
+			common:      false
+			description: "The datacenter whose nodes are preferred, the nodes of other datacenters only being used when none of them are available."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["us-east-1"]
+				syntax: "literal"
+			}
+		}
+		partition_key: {
+			common:      true
+			description: "The columns of the partition key of the table, which must be configured `columns`. Batches only hold rows of the same partition when it's set."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["host", "day"]
+					syntax: "literal"
+				}
+			}
+		}
+		table: {
+			description: "The table the events are written to."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["events"]
+				syntax: "literal"
+			}
+		}
+		tls: {
+			common:      false
+			description: "TLS options to connect to the cluster."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					ca_file: {
+						description: "Path to CA certificate file."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["certs/ca.pem"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+		ttl_secs: {
+			common:      false
+			description: "The time to live of the rows written, after which they are deleted. Defaults to the TTL of the table."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [86400]
+				unit: "seconds"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		prepared_statements: {
+			title: "Prepared statements"
+			body:  """
+				The sink prepares an `INSERT` statement for the configured columns when it connects, and writes
+				each batch as an unlogged [batch](\(urls.cassandra_batch)) of that statement, with the
+				configured `consistency`. Batches failing because nodes are unavailable, overloaded or timing
+				out are retried, while batches failing because of their rows are not.
+				"""
+		}
+
+		partitioning: {
+			title: "Partitioning"
+			body:  """
+				Unlogged batches spanning several partitions are coordinated by one node for all of their
+				replicas, which loads the cluster. With `partition_key` set, each batch only holds rows of the
+				same partition, and is sent to one of its replicas, preferring those of `local_datacenter`.
+				Keep the partitions of the table coarse enough that batches fill up, such as by host and day.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		processed_bytes_total:  components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total: components.sources.internal_metrics.output.metrics.processed_events_total
+	}
+}
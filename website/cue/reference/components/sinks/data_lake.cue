@@ -0,0 +1,434 @@
+package metadata
+
+components: sinks: data_lake: {
+	title: "Data Lake Table"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: ["AWS"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       true
+				max_bytes:    100000000
+				max_events:   100000
+				timeout_secs: 300
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			request: enabled:     false
+			tls: enabled:         false
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: [
+			"""
+				S3 has no conditional writes, so Delta Lake tables stored in S3 must have a single writer: commits
+				of concurrent writers can overwrite each other.
+				""",
+		]
+		notices: []
+	}
+
+	configuration: {
+		auth: {
+			common:      false
+			description: "Options for the AWS authentication strategy, used for tables stored in S3 and for the Glue catalog."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: components._aws.configuration.auth.type.object.options
+			}
+		}
+		columns: {
+			description: "The columns of the table written, in order."
+			required:    true
+			warnings: []
+			type: array: items: type: object: {
+				examples: []
+				options: {
+					name: {
+						description: "The name of the column."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["message", "date"]
+							syntax: "literal"
+						}
+					}
+					type: {
+						common:      true
+						description: "The type of the column. Values are converted to it, and are null if they can't be."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "string"
+							enum: {
+								string:    "A UTF-8 string. Values of other types are converted to strings, timestamps as RFC 3339."
+								long:      "A 64-bit integer, from integers, truncated floats and strings."
+								double:    "A 64-bit float, from floats, integers and strings."
+								boolean:   "A boolean, from booleans and the strings `true` and `false`."
+								timestamp: "A timestamp with microsecond precision, from timestamps and RFC 3339 strings."
+							}
+							syntax: "literal"
+						}
+					}
+					field: {
+						common:      false
+						description: "The field of the event the value is read from. Defaults to the name of the column."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["kubernetes.pod_name"]
+							syntax: "literal"
+						}
+					}
+					template: {
+						common:      false
+						description: "Renders the value from the event instead of reading it from a field, which is how partition columns like dates are derived from events."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["%F", "{{ service }}"]
+							syntax: "template"
+						}
+					}
+				}
+			}
+		}
+		endpoint: {
+			common:      false
+			description: "Custom endpoint for S3-compatible storage. Providing a value for this option will make `region` moot."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["http://127.0.0.0:9000"]
+				syntax: "literal"
+			}
+		}
+		region: {
+			common:      true
+			description: "The [AWS region](\(urls.aws_regions)) of the S3 bucket of the table, and of the Glue catalog. Required if either is used, unless `endpoint` is provided."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["us-east-1"]
+				syntax: "literal"
+			}
+		}
+		table: {
+			description: "The table the events are written to."
+			required:    true
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					format: {
+						description: "The format of the table."
+						required:    true
+						warnings: []
+						type: string: {
+							enum: {
+								delta_lake: "A [Delta Lake](\(urls.delta_lake)) table, committed to by writing its transaction log."
+								iceberg:    "An [Apache Iceberg](\(urls.apache_iceberg)) table of format version 2, committed to through its catalog."
+							}
+							syntax: "literal"
+						}
+					}
+					location: {
+						description:   "The location of the Delta Lake table, an `s3://` URI or an absolute path. The table is created with the columns as its schema if it has no transaction log."
+						relevant_when: "format = \"delta_lake\""
+						required:      true
+						warnings: []
+						type: string: {
+							examples: ["s3://my-bucket/tables/logs", "/var/lib/tables/logs"]
+							syntax: "literal"
+						}
+					}
+					partition_by: {
+						common:        true
+						description:   "The columns the Delta Lake table is partitioned by, of type `string`, `long` or `boolean`. Their values are in the paths of the data files rather than in the files."
+						relevant_when: "format = \"delta_lake\""
+						required:      false
+						warnings: []
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["date"]
+								syntax: "literal"
+							}
+						}
+					}
+					namespace: {
+						description:   "The namespace of the Iceberg table, with levels separated by dots."
+						relevant_when: "format = \"iceberg\""
+						required:      true
+						warnings: []
+						type: string: {
+							examples: ["logs", "lake.logs"]
+							syntax: "literal"
+						}
+					}
+					name: {
+						description:   "The name of the Iceberg table."
+						relevant_when: "format = \"iceberg\""
+						required:      true
+						warnings: []
+						type: string: {
+							examples: ["events"]
+							syntax: "literal"
+						}
+					}
+					catalog: {
+						description:   "The catalog of the Iceberg table."
+						relevant_when: "format = \"iceberg\""
+						required:      true
+						warnings: []
+						type: object: {
+							examples: []
+							options: {
+								type: {
+									description: "The type of the catalog."
+									required:    true
+									warnings: []
+									type: string: {
+										enum: {
+											rest: "A catalog implementing the [Iceberg REST catalog API](\(urls.iceberg_rest_catalog))."
+											glue: "The AWS Glue Data Catalog, through its [Iceberg REST endpoint](\(urls.aws_glue_iceberg_rest)) in the configured `region`, signed with the AWS credentials."
+										}
+										syntax: "literal"
+									}
+								}
+								uri: {
+									description:   "The base URI of the REST catalog."
+									relevant_when: "type = \"rest\""
+									required:      true
+									warnings: []
+									type: string: {
+										examples: ["http://localhost:8181"]
+										syntax: "literal"
+									}
+								}
+								prefix: {
+									common:        false
+									description:   "The prefix of the paths of the REST catalog, as returned in the `prefix` override of its configuration."
+									relevant_when: "type = \"rest\""
+									required:      false
+									warnings: []
+									type: string: {
+										default: null
+										examples: ["warehouse"]
+										syntax: "literal"
+									}
+								}
+								auth: {
+									common:        false
+									description:   "The authentication of the requests to the REST catalog."
+									relevant_when: "type = \"rest\""
+									required:      false
+									warnings: []
+									type: object: {
+										examples: []
+										options: {
+											strategy: {
+												description: "The authentication strategy."
+												required:    true
+												warnings: []
+												type: string: {
+													enum: {
+														basic:  "Basic authentication with `user` and `password`."
+														bearer: "A bearer `token`."
+													}
+													syntax: "literal"
+												}
+											}
+											user: {
+												description:   "The user name."
+												relevant_when: "strategy = \"basic\""
+												required:      true
+												warnings: []
+												type: string: {
+													examples: ["vector"]
+													syntax: "literal"
+												}
+											}
+											password: {
+												description:   "The password."
+												relevant_when: "strategy = \"basic\""
+												required:      true
+												warnings: []
+												type: string: {
+													examples: ["${CATALOG_PASSWORD}"]
+													syntax: "literal"
+												}
+											}
+											token: {
+												description:   "The token."
+												relevant_when: "strategy = \"bearer\""
+												required:      true
+												warnings: []
+												type: string: {
+													examples: ["${CATALOG_TOKEN}"]
+													syntax: "literal"
+												}
+											}
+										}
+									}
+								}
+								catalog_id: {
+									description:   "The ID of the Glue Data Catalog, the ID of the AWS account it belongs to."
+									relevant_when: "type = \"glue\""
+									required:      true
+									warnings: []
+									type: string: {
+										examples: ["123456789012"]
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+		writer_id: {
+			common:      false
+			description: "The ID the commits of the sink are tagged with, to recognize them when a commit is retried. Sinks writing to the same table must have different IDs."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "vector"
+				examples: ["vector-aggregator-0"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		commits: {
+			title: "Commits"
+			body:  """
+				Each batch is written as one Parquet file per partition, compressed with Snappy, and the files
+				are committed to the table in one transaction, so that they become visible to readers at once.
+				The events of the batch are acknowledged once it has been committed. The files, and the commit,
+				are retried until they succeed, with a backoff of up to a minute.
+
+				Delta Lake batches are committed as the next version of the [transaction log](\(urls.delta_lake_protocol)).
+				Iceberg batches are appended as a snapshot of the table, with a manifest of the files, committed
+				through the catalog if the snapshot they are based on is still current, and based on the new
+				current snapshot otherwise.
+				"""
+		}
+
+		exactly_once: {
+			title: "Exactly-once commits"
+			body:  """
+				The commits are tagged with the `writer_id` and the sequence number of the batch: with a `txn`
+				action in Delta Lake tables, and in the summary of the snapshot in Iceberg tables. When the
+				outcome of a commit is unknown, as when its response is lost, the table is checked for the tag
+				before the commit is attempted again, so that each batch is committed once. Files written by
+				attempts that failed are left unreferenced, for the maintenance of the table to remove.
+
+				The sequence numbers follow the clock, so that they keep increasing across restarts.
+				"""
+		}
+
+		partitioning: {
+			title: "Partitioning"
+			body:  """
+				The values of partition columns are usually rendered with the `template` of the column, as a
+				date from the timestamp of the events with `%F`. The data files of each partition are written
+				in a directory named after its values.
+
+				The partitioning of Delta Lake tables is set by `partition_by` when the sink creates them. The
+				partitioning of Iceberg tables is their default partition spec, whose fields must be identity
+				partitions of configured columns.
+				"""
+		}
+
+		schemas: {
+			title: "Schemas"
+			body:  """
+				The columns of Iceberg tables are resolved by name to the fields of the current schema of the
+				table, whose IDs are written in the data files. The fields must have the types of the columns,
+				with `timestamp` columns matching `timestamp` and `timestamptz` fields, and be optional.
+
+				Delta Lake tables are created with the columns as their schema, and the schema of existing
+				tables is not checked.
+				"""
+		}
+	}
+
+	permissions: iam: [
+		{
+			platform:      "aws"
+			_service:      "s3"
+			_docs_tag:     "AmazonS3"
+			_url_fragment: "API"
+
+			policies: [
+				{
+					_action: "GetObject"
+				},
+				{
+					_action: "ListBucket"
+					required_for: ["healthcheck", "operation"]
+				},
+				{
+					_action: "PutObject"
+				},
+			]
+		},
+		{
+			platform:      "aws"
+			_service:      "glue"
+			_url_fragment: "webapi"
+
+			policies: [
+				{
+					_action:       "GetTable"
+					required_when: "[`table.catalog.type`](#table.catalog.type) is set to `glue`"
+				},
+				{
+					_action:       "UpdateTable"
+					required_when: "[`table.catalog.type`](#table.catalog.type) is set to `glue`"
+				},
+			]
+		},
+	]
+
+	telemetry: metrics: {
+		processed_bytes_total:  components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total: components.sources.internal_metrics.output.metrics.processed_events_total
+		request_errors_total:   components.sources.internal_metrics.output.metrics.request_errors_total
+	}
+}
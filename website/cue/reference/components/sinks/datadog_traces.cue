@@ -0,0 +1,128 @@
+package metadata
+
+components: sinks: datadog_traces: {
+	title: "Datadog Traces"
+
+	classes: sinks._datadog.classes & {
+		development: "beta"
+	}
+
+	features: {
+		buffer: enabled:      false
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    3145728
+				max_events:   1000
+				timeout_secs: 2
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			proxy: enabled:       true
+			request: {
+				enabled:                    true
+				concurrency:                5
+				rate_limit_duration_secs:   1
+				rate_limit_num:             5
+				retry_initial_backoff_secs: 1
+				retry_max_duration_secs:    10
+				timeout_secs:               60
+				headers:                    false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        true
+			}
+			to: {
+				service: services.datadog_traces
+
+				interface: {
+					socket: {
+						api: {
+							title: "Datadog trace intake"
+							url:   urls.datadog_traces
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: sinks._datadog.support
+
+	configuration: {
+		default_api_key: sinks._datadog.configuration.api_key & {
+			description: "The Datadog [API key](https://docs.datadoghq.com/api/?lang=bash#authentication) of the spans that don't carry one in their metadata."
+		}
+		endpoint: sinks._datadog.configuration.endpoint
+		site:     sinks._datadog.configuration.site
+		env: {
+			common:      false
+			description: "The environment of the spans whose resource doesn't set `deployment.environment`."
+			required:    false
+			type: string: {
+				default: "none"
+				examples: ["prod", "staging"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    false
+		metrics: null
+		traces:  true
+	}
+
+	how_it_works: {
+		span_mapping: {
+			title: "Span mapping"
+			body: """
+				Spans are expected in the layout of the `opentelemetry` source and are mapped
+				following the OpenTelemetry semantic conventions, as the Datadog exporter of
+				the OpenTelemetry Collector does:
+
+				* The service is the `service.name` resource attribute, or `unknown_service`.
+				* The operation name is the `operation.name` attribute, or
+				  `opentelemetry.<kind>`.
+				* The resource is the `resource.name` attribute, the HTTP method and route,
+				  or the span name.
+				* Other attributes of the span and of its resource become tags, in `meta` for
+				  text and in `metrics` for numbers.
+
+				Datadog trace and span IDs are 64 bits, so only the lower 64 bits of the
+				OpenTelemetry IDs are kept. Spans without a trace ID, span ID or start time
+				are dropped.
+				"""
+		}
+
+		apm_stats: {
+			title: "APM stats"
+			body: """
+				The trace intake doesn't compute the [trace metrics](\(urls.datadog_trace_metrics))
+				of the spans it receives, so this sink computes them as the Datadog Agent
+				does, from the top-level and measured spans of each batch, and sends them
+				along with the spans.
+
+				A span is top-level when its parent is of another service. Only the spans of
+				a batch are known when computing its stats, so spans whose parent was sent in
+				an earlier batch are counted as top-level. Stats that fail to be sent are
+				dropped rather than retried, as retrying would send the spans again.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -0,0 +1,188 @@
+package metadata
+
+components: sinks: doris: {
+	title: "Apache Doris / StarRocks"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    10485760
+				timeout_secs: 1
+			}
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			proxy: enabled: true
+			request: {
+				enabled: true
+				headers: true
+			}
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.doris
+
+				interface: {
+					socket: {
+						api: {
+							title: "Stream Load API"
+							url:   urls.doris_stream_load
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: [
+			"""
+				`two_phase_commit` is only supported by Apache Doris, StarRocks has no two-phase commit for
+				Stream Load.
+				""",
+		]
+		notices: []
+	}
+
+	configuration: {
+		auth: configuration._http_auth & {_args: {
+			password_example: "${DORIS_PASSWORD}"
+			username_example: "${DORIS_USERNAME}"
+		}}
+		database: {
+			description: "The database of the table the events are loaded into."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["logs", "logs_{{ environment }}"]
+				syntax: "template"
+			}
+		}
+		endpoint: {
+			description: "The HTTP endpoint of a frontend (FE) of the cluster, which redirects the loads to a backend (BE)."
+			required:    true
+			type: string: {
+				examples: ["http://localhost:8030"]
+				syntax: "literal"
+			}
+		}
+		label_prefix: {
+			common:      false
+			description: "The prefix of the labels of the loads, which may only contain letters, digits, `-`, `_` and `:`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "vector"
+				examples: ["vector_aggregator"]
+				syntax: "literal"
+			}
+		}
+		table: {
+			description: "The table the events are loaded into."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["events", "{{ application }}_events"]
+				syntax: "template"
+			}
+		}
+		two_phase_commit: {
+			common:      false
+			description: "Loads the batches in two phases, committing the transaction of each load once the load succeeded. Requires `enable_stream_load_2pc` on Apache Doris 1.x."
+			required:    false
+			type: bool: default: false
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		stream_load: {
+			title: "Stream Load"
+			body:  """
+				Each batch is loaded into its table with a [Stream Load](\(urls.doris_stream_load)) of the
+				events as a JSON array, with `format: json` and `strip_outer_array: true`. The fields of the
+				events are mapped to the columns of the table by name. The headers of `request.headers` are
+				added to the loads, to set properties like `columns`, `jsonpaths` or `max_filter_ratio`.
+
+				The frontend redirects the loads to a backend, which the sink follows with the credentials of
+				the load. Loads failing because rows were filtered out by the table are not retried, and the
+				`ErrorURL` of the load, with the rejected rows, is logged.
+
+				Timestamps are encoded in RFC 3339 by default, which `DATETIME` columns don't accept: set
+				`encoding.timestamp_format` to `unix`, or convert them with `columns`.
+				"""
+		}
+
+		deduplication: {
+			title: "Deduplication"
+			body:  """
+				Each batch is loaded with a unique label, made of `label_prefix`, the database, the table and
+				a UUID, and kept when the load is retried. A batch whose label was already loaded, as when the
+				response of its load is lost, is not loaded again.
+				"""
+		}
+
+		partitioning: {
+			title: "Partitioning"
+			body:  """
+				`database` and `table` are templates, and the events are batched per table they render to, so
+				that a sink can load the events into several tables. Events for which they fail to render are
+				dropped.
+				"""
+		}
+
+		two_phase_commit: {
+			title: "Two-phase commit"
+			body:  """
+				With `two_phase_commit`, the transaction of each load is precommitted by the load, and
+				committed by the sink once the load succeeded, so the rows only become visible once the
+				events are acknowledged. A retried load whose transaction was precommitted is committed by its
+				label instead of being loaded again.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		doris_filtered_rows_total: components.sources.internal_metrics.output.metrics.doris_filtered_rows_total
+		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:    components.sources.internal_metrics.output.metrics.processed_events_total
+	}
+}
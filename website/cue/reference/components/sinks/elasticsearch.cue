@@ -84,6 +84,22 @@ components: sinks: elasticsearch: {
 	}
 
 	configuration: {
+		api_version: {
+			common:      false
+			description: "The version of the bulk API of the cluster, which decides whether the mapping type of the documents, `doc_type`, is sent."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "auto"
+				enum: {
+					auto: "Determine the version from the cluster on startup, assuming 7.x if it can't be determined."
+					v6:   "Elasticsearch 6.x."
+					v7:   "Elasticsearch 7.x, or OpenSearch 1.x."
+					v8:   "Elasticsearch 8.x, or OpenSearch 2.x and later, which removed mapping types."
+				}
+				syntax: "literal"
+			}
+		}
 		auth: {
 			common:      false
 			description: "Options for the authentication strategy."
@@ -209,6 +225,63 @@ components: sinks: elasticsearch: {
 						warnings: []
 						type: bool: default: true
 					}
+					template: {
+						common:      false
+						description: "An index template created on startup, unless it already exists, so the data streams written to are created with it."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: []
+							options: {
+								name: {
+									description: "The name of the index template."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["vector-logs"]
+										syntax: "literal"
+									}
+								}
+								index_patterns: {
+									description: "The patterns of the data streams the template applies to."
+									required:    true
+									warnings: []
+									type: array: items: type: string: {
+										examples: ["logs-vector-*"]
+										syntax: "literal"
+									}
+								}
+								priority: {
+									common:      false
+									description: "The priority of the template, above the one of the built-in `logs-*-*` and `metrics-*-*` templates by default."
+									required:    false
+									warnings: []
+									type: uint: {
+										default: 200
+										unit:    null
+									}
+								}
+								ilm_policy: {
+									common:      false
+									description: "The [ILM policy](\(urls.elasticsearch_ilm)) applied to the backing indices of the data streams. The policy must exist."
+									required:    false
+									warnings: []
+									type: string: {
+										default: null
+										examples: ["logs-30d"]
+										syntax: "literal"
+									}
+								}
+								overwrite: {
+									common:      false
+									description: "Whether to overwrite the template if it already exists."
+									required:    false
+									warnings: []
+									type: bool: default: false
+								}
+							}
+						}
+					}
 					type: {
 						common:      false
 						description: "The data stream type used to construct the data stream at index time."
@@ -289,7 +362,7 @@ components: sinks: elasticsearch: {
 		}
 		mode: {
 			common:      true
-			description: "The type of index mechanism. If `data_stream` mode is enabled, the `bulk_action` is set to `create`."
+			description: "The type of index mechanism. If `data_stream` mode is enabled, the `bulk_action` is set to `create`, and configuring another one is an error."
 			required:    false
 			warnings: []
 			type: string: {
@@ -298,6 +371,31 @@ components: sinks: elasticsearch: {
 				syntax: "literal"
 			}
 		}
+		routing_key: {
+			common:      false
+			description: "The name of the event key whose value routes the document to a shard, as its [`_routing` field](\(urls.elasticsearch_routing_field)). The key is kept in the document."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["tenant_id"]
+				syntax: "literal"
+			}
+		}
+		opensearch_service_type: {
+			common:      false
+			description: "The kind of service the endpoint belongs to."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "managed"
+				enum: {
+					managed:    "Elasticsearch or OpenSearch clusters, including Amazon OpenSearch Service domains."
+					serverless: "[Amazon OpenSearch Serverless](\(urls.aws_opensearch_serverless)) collections. Requires the `aws` authentication strategy, requests are signed for the `aoss` service, the healthcheck is skipped, and `api_version` is `v8`."
+				}
+				syntax: "literal"
+			}
+		}
 		pipeline: {
 			common:      true
 			description: "Name of the pipeline to apply."
@@ -345,6 +443,26 @@ components: sinks: elasticsearch: {
 				To use [Data streams](\(urls.elasticsearch_data_streams)), set the `mode` to
 				`data_stream`. Use the combination of `data_stream.type`, `data_stream.dataset` and
 				`data_stream.namespace` instead of `index`.
+
+				Data streams are only created if an index template with data streams enabled matches
+				their name. Elasticsearch has built-in templates for `logs-*-*` and `metrics-*-*`, and
+				`data_stream.template` creates one on startup, which can apply an
+				[ILM policy](\(urls.elasticsearch_ilm)) to the backing indices. The template isn't replaced
+				if it already exists, unless `data_stream.template.overwrite` is enabled. If it can't be
+				created, the error is logged and the sink starts anyway.
+				"""
+		}
+
+		opensearch: {
+			title: "OpenSearch"
+			body:  """
+				OpenSearch clusters accept the same bulk requests as Elasticsearch. With `api_version`
+				left to `auto`, the version of the cluster is read from its root endpoint on startup, and
+				the mapping type of the documents isn't sent to OpenSearch 2.x and later, which removed them.
+
+				For Amazon OpenSearch Serverless collections, set `opensearch_service_type` to
+				`serverless` and `auth.strategy` to `aws`, so the requests are signed with AWS Signature
+				Version 4 for the `aoss` service.
 				"""
 		}
 
@@ -362,7 +480,8 @@ components: sinks: elasticsearch: {
 	}
 
 	telemetry: metrics: {
-		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		events_discarded_total:          components.sources.internal_metrics.output.metrics.events_discarded_total
+		processing_errors_total:         components.sources.internal_metrics.output.metrics.processing_errors_total
+		template_bootstrap_errors_total: components.sources.internal_metrics.output.metrics.template_bootstrap_errors_total
 	}
 }
@@ -28,7 +28,7 @@ components: sinks: file: {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["ndjson", "text"]
+					enum: ["csv", "ndjson", "text"]
 				}
 			}
 			request: enabled: false
@@ -53,6 +53,7 @@ components: sinks: file: {
 	}
 
 	configuration: {
+		csv: components._csv.configuration.csv
 		idle_timeout_secs: {
 			common:      false
 			description: "The amount of time a file can be idle  and stay open. After not receiving any events for this timeout, the file will be flushed and closed.\n"
@@ -80,6 +81,16 @@ components: sinks: file: {
 	}
 
 	how_it_works: {
+		csv: {
+			title: "CSV files"
+			body:  """
+				With the `csv` codec, events are written as [CSV](\(urls.rfc_4180)) rows holding
+				their `csv.fields`, in order. New, empty files start with a header row naming the
+				fields, unless `csv.header` is `false`. Files that already hold rows, such as those
+				written before Vector restarted, are appended to without writing the header again.
+				"""
+		}
+
 		dir_and_file_creation: {
 			title: "File & Directory Creation"
 			body: """
@@ -0,0 +1,276 @@
+package metadata
+
+components: sinks: gcp_bigquery: {
+	title: "GCP BigQuery"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: ["GCP"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    9437184
+				max_events:   1000
+				timeout_secs: 1
+			}
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			request: {
+				enabled: true
+				headers: false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.gcp_bigquery
+
+				interface: {
+					socket: {
+						api: {
+							title: "BigQuery Storage Write API"
+							url:   urls.gcp_bigquery_storage_write_api
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		api_key: {
+			common:      false
+			description: "A [Google Cloud API key](\(urls.gcp_authentication_api_key)) used to authenticate access to the tables. Either this or `credentials_path` must be set."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["${GCP_API_KEY}", "ef8d5de700e7989468166c40fc8a0ccd"]
+				syntax: "literal"
+			}
+		}
+		credentials_path: {
+			common:      true
+			description: "The filename for a Google Cloud service account credentials JSON file used to authenticate access to the tables. If this is unset, Vector checks the `GOOGLE_APPLICATION_CREDENTIALS` environment variable for a filename.\n\nIf no filename is named, Vector will attempt to fetch an instance service account for the compute instance the program is running on. If Vector is not running on a GCE instance, you must define a credentials file as above."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/path/to/credentials.json"]
+				syntax: "literal"
+			}
+		}
+		dataset: {
+			description: "The dataset of the tables."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["logs"]
+				syntax: "literal"
+			}
+		}
+		dead_letter_table: {
+			common:      true
+			description: "The table of the dataset the rows not matching the schema are written to, with the event as JSON in a `payload` column, the reason it was rejected in an `error` column and when in a `timestamp` column. The rows are dropped if it's not set."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["events_dead_letters"]
+				syntax: "literal"
+			}
+		}
+		endpoint: {
+			common:      false
+			description: "The endpoint of the Storage Write API."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "https://bigquerystorage.googleapis.com"
+				examples: ["https://bigquerystorage.googleapis.com"]
+				syntax: "literal"
+			}
+		}
+		project: {
+			description: "The project of the dataset."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["vector-123456"]
+				syntax: "literal"
+			}
+		}
+		schema: {
+			description: "The fields of the rows, which are matched to the columns of the table by name. Columns left out of the schema are null."
+			required:    true
+			warnings: []
+			type: array: items: type: object: {
+				examples: []
+				options: {
+					name: {
+						description: "The name of the column."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["message", "status"]
+							syntax: "literal"
+						}
+					}
+					type: {
+						common:      true
+						description: "The type of the column. Values are converted to it, and the rows of events whose values can't be are rejected."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "string"
+							enum: {
+								string:    "A `STRING` column. Values of other types are converted to strings, timestamps as RFC 3339 and objects and arrays as JSON."
+								bytes:     "A `BYTES` column, from strings."
+								int64:     "An `INT64` column, from integers, floats without a fraction and strings."
+								float64:   "A `FLOAT64` column, from floats, integers and strings."
+								bool:      "A `BOOL` column, from booleans and the strings `true` and `false`."
+								timestamp: "A `TIMESTAMP` column, from timestamps and RFC 3339 strings."
+								json:      "A `JSON` column, from any value."
+							}
+							syntax: "literal"
+						}
+					}
+					mode: {
+						common:      false
+						description: "The mode of the column."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "nullable"
+							enum: {
+								nullable: "Missing and null values are left out of the row."
+								required: "The rows of events missing the value are rejected."
+								repeated: "The value must be an array, whose elements are converted to the type of the column."
+							}
+							syntax: "literal"
+						}
+					}
+					field: {
+						common:      false
+						description: "The field of the event the value is read from. Defaults to the name of the column."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["http.status"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+		table: {
+			description: "The table the events are written to."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["events"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		storage_write_api: {
+			title: "Storage Write API"
+			body:  """
+				Rows are written with the [Storage Write API](\(urls.gcp_bigquery_storage_write_api)), serialized
+				as Protocol Buffers messages whose fields are the fields of `schema`, in their order. The
+				message descriptor is built from `schema` and sent along with the rows, so the table isn't read
+				to find its schema.
+				"""
+		}
+
+		exactly_once: {
+			title: "Exactly-once appends"
+			body:  """
+				The batches are appended one at a time to a committed write stream the sink creates, each at the
+				[offset](\(urls.gcp_bigquery_write_api_exactly_once)) following the previous batch. A retried
+				batch is appended at the same offset, so BigQuery rejects its rows with `ALREADY_EXISTS` if an
+				earlier attempt wrote them, rather than writing them twice. When the retries of a batch are
+				exhausted without knowing whether it was written, the next batch goes to a new stream.
+				"""
+		}
+
+		dead_letters: {
+			title: "Dead letters"
+			body:  """
+				Events whose values can't be converted to the types of the schema, or missing required
+				values, are rejected, as are the rows BigQuery reports as not matching the table. BigQuery
+				rejects a whole append for such rows, so they are taken out and the other rows are appended
+				again. The rejected rows are written to `dead_letter_table` if it's set, through its default
+				stream, which writes them at least once, and are dropped otherwise.
+				"""
+		}
+	}
+
+	permissions: iam: [
+		{
+			platform: "gcp"
+			_service: "bigquery"
+
+			policies: [
+				{
+					_action: "tables.get"
+					required_for: ["healthcheck"]
+				},
+				{
+					_action: "tables.updateData"
+					required_for: ["operation"]
+				},
+			]
+		},
+	]
+
+	telemetry: metrics: {
+		bigquery_rows_rejected_total: components.sources.internal_metrics.output.metrics.bigquery_rows_rejected_total
+		processed_bytes_total:        components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:       components.sources.internal_metrics.output.metrics.processed_events_total
+	}
+}
@@ -178,6 +178,76 @@ components: sinks: gcp_cloud_storage: {
 				syntax: "literal"
 			}
 		}
+		partitioning: {
+			category:    "File Naming"
+			common:      false
+			description: "Lays the objects out in Hive style partitions of windows of the timestamp of their events, under `key_prefix`, and marks the partitions complete once their windows are closed and all their objects are written."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					close_delay_secs: {
+						common:      true
+						description: "How long after the end of a window its late events are waited for, before its partition is complete."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 300
+							unit:    "seconds"
+						}
+					}
+					fields: {
+						common:      true
+						description: "The fields of the events added to the path of their partition as `field=value/`, before the time of their window. Events missing a field are in the `__HIVE_DEFAULT_PARTITION__` partition."
+						required:    false
+						warnings: []
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["service", "region"]
+								syntax: "literal"
+							}
+						}
+					}
+					manifest: {
+						common:      false
+						description: "Whether a `_manifest.json` object listing the objects of the partition, with their size, is written when the partition is complete."
+						required:    false
+						warnings: []
+						type: bool: default: false
+					}
+					success_marker: {
+						common:      true
+						description: "Whether an empty `_SUCCESS` object is written when the partition is complete."
+						required:    false
+						warnings: []
+						type: bool: default: true
+					}
+					time_format: {
+						common:      true
+						description: "The path of the partition of a window, formatted from its start with [`strftime` specifiers](\(urls.chrono_time_formats))."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "dt=%Y-%m-%d/hour=%H/"
+							examples: ["dt=%Y-%m-%d/", "year=%Y/month=%m/day=%d/hour=%H/"]
+							syntax: "literal"
+						}
+					}
+					window_secs: {
+						common:      true
+						description: "The length of the windows events are grouped into by their timestamp."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 3600
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
 		storage_class: {
 			category:    "Storage"
 			common:      false
@@ -252,6 +322,26 @@ components: sinks: gcp_cloud_storage: {
 				"""
 		}
 
+		partitioning: {
+			title: "Partitioning"
+			body:  """
+				With `partitioning` set, events are grouped into windows of `window_secs` by their timestamp,
+				and their objects are written under the path of their partition, such as
+				`<key_prefix>service=api/dt=2021-09-01/hour=12/`. The default `key_prefix` is empty then.
+
+				Once a window is closed, `close_delay_secs` after its end, and all the objects of its
+				partition are written, the sink writes the `_manifest.json` object of the partition when
+				`manifest` is set, then its `_SUCCESS` object, so that batch jobs waiting for it only read
+				complete partitions. Partitions with an object that failed to be written are never marked
+				complete.
+
+				Events arriving after their partition is marked complete are still written to it, and the
+				markers of the partition are written again. Partitions are remembered for a day after their
+				window closes, and the state of the partitions is kept in memory, so the manifests don't list
+				the objects written before Vector restarted.
+				"""
+		}
+
 		storage_class: {
 			title: "Storage Class"
 			body:  """
@@ -292,7 +382,10 @@ components: sinks: gcp_cloud_storage: {
 	]
 
 	telemetry: metrics: {
-		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
-		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+		events_discarded_total:     components.sources.internal_metrics.output.metrics.events_discarded_total
+		late_events_total:          components.sources.internal_metrics.output.metrics.late_events_total
+		partitions_completed_total: components.sources.internal_metrics.output.metrics.partitions_completed_total
+		partitions_failed_total:    components.sources.internal_metrics.output.metrics.partitions_failed_total
+		processing_errors_total:    components.sources.internal_metrics.output.metrics.processing_errors_total
 	}
 }
@@ -0,0 +1,194 @@
+package metadata
+
+components: sinks: grpc: {
+	title: "gRPC"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       false
+				max_bytes:    null
+				max_events:   100
+				timeout_secs: 1
+			}
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			request: {
+				enabled: true
+				headers: false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.grpc
+
+				interface: {
+					socket: {
+						api: {
+							title: "gRPC"
+							url:   urls.grpc
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		address: {
+			description: "The address of the gRPC server. It's sent plaintext HTTP/2 requests when it has no scheme or the `http` scheme."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["http://127.0.0.1:50051", "https://events.internal:443"]
+				syntax: "literal"
+			}
+		}
+		batch_field: {
+			common:      false
+			description: "A repeated message field of the request of unary methods. Batches are then sent in a single request, each event being a message of the field. Without it, each event is sent in a request of its own."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["events"]
+				syntax: "literal"
+			}
+		}
+		descriptor_set_file: {
+			description: "The [file descriptor set](\(urls.protobuf_descriptor_set)) describing the service, along with the files it imports, as written by `protoc --include_imports --descriptor_set_out`."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["/etc/vector/events.desc"]
+				syntax: "literal"
+			}
+		}
+		fields: {
+			common:      false
+			description: "Fields of the message, mapped to the fields of events setting them. Other fields are set from the fields of events with the same name."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [{"service_name": "kubernetes.labels.app", "body": "message"}]
+				options: {
+					"*": {
+						description: "The field of events setting the field of the message."
+						required:    true
+						warnings: []
+						type: string: syntax: "literal"
+					}
+				}
+			}
+		}
+		metadata: {
+			common:      false
+			description: "The [metadata](\(urls.grpc_metadata)) sent along with every call, such as credentials."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [{"authorization": "Bearer ${GRPC_TOKEN}"}]
+				options: {
+					"*": {
+						description: "The value of the metadata key."
+						required:    true
+						warnings: []
+						type: string: syntax: "literal"
+					}
+				}
+			}
+		}
+		method: {
+			description: "The method called, written as `package.Service/Method`. Unary and client streaming methods are supported."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["events.v1.EventService/Push"]
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		messages: {
+			title: "Messages"
+			body: """
+				Events are serialized as the request message of the method, read from the descriptor
+				set when Vector starts. Fields of the message are set from the fields of events with
+				the same name, unless `fields` maps them to other fields. Nested messages are set
+				from objects, repeated fields from arrays, maps from objects and enums from the names
+				or the numbers of their values. `google.protobuf.Timestamp` fields are set from
+				timestamps. Fields of events that aren't in the message are left out.
+
+				Events whose fields can't be converted to the types of the message are rejected.
+				"""
+		}
+		calls: {
+			title: "Calls"
+			body: """
+				Client streaming methods are sent a batch of events as a single stream of messages.
+				Unary methods are sent a single request per batch when `batch_field` is set, and a
+				request per event otherwise. Responses are ignored, but for their status: calls
+				failing with the `UNAVAILABLE`, `RESOURCE_EXHAUSTED`, `DEADLINE_EXCEEDED`, `ABORTED`,
+				`INTERNAL` or `UNKNOWN` status are retried.
+				"""
+		}
+		healthcheck: {
+			title: "Health checks"
+			body: """
+				Health checks call the [standard health checking service](\(urls.grpc_health_checking))
+				of the server. Servers that don't implement it are considered healthy.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
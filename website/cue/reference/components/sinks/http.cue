@@ -96,7 +96,43 @@ components: sinks: http: {
 			required: true
 			warnings: []
 			type: string: {
-				examples: ["https://10.22.212.22:9000/endpoint"]
+				examples: ["https://10.22.212.22:9000/endpoint", "https://{{ tenant }}.example.com/endpoint"]
+				syntax: "template"
+			}
+		}
+		method: {
+			common:      false
+			description: "The HTTP method of the requests, which can be templated."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "post"
+				enum: {
+					delete:  "DELETE"
+					get:     "GET"
+					head:    "HEAD"
+					options: "OPTIONS"
+					patch:   "PATCH"
+					post:    "POST"
+					put:     "PUT"
+					trace:   "TRACE"
+				}
+				examples: ["{{ method }}"]
+				syntax: "template"
+			}
+		}
+		dead_letter_uri: {
+			common: false
+			description: """
+				The URI to `POST` events to when the templates of their request fail to render,
+				or render an invalid URI, method or header. Such events are dropped when this
+				isn't set.
+				"""
+			required: false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["https://10.22.212.22:9000/dead-letters"]
 				syntax: "literal"
 			}
 		}
@@ -121,10 +157,28 @@ components: sinks: http: {
 		metrics: null
 	}
 
+	how_it_works: {
+		templated_requests: {
+			title: "Templated requests"
+			body: """
+				The `uri`, the `method` and the values of `request.headers` are rendered for each
+				event when they hold `{{ field }}` placeholders, so that one sink can send events
+				to an endpoint per tenant. Events are batched by the request they render, and
+				each rendered request gets its own `request` settings, including concurrency and
+				rate limits, so that an endpoint that is slow doesn't hold back the others.
+
+				Templated options are also rendered as `strftime` specifiers, so a `%` in them
+				must be escaped as `%%`. Credentials in a rendered URI are used as basic
+				authentication, and can't be used along with `auth`.
+				"""
+		}
+	}
+
 	telemetry: metrics: {
 		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
 		http_bad_requests_total: components.sources.internal_metrics.output.metrics.http_bad_requests_total
 		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
 	}
 }
@@ -0,0 +1,230 @@
+package metadata
+
+components: sinks: influxdb_tcp: {
+	title: "InfluxDB Line Protocol over TCP"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: enabled: false
+			}
+			send_buffer_bytes: enabled: true
+			keepalive: enabled:         true
+			request: enabled:           false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.questdb
+
+				interface: {
+					socket: {
+						api: {
+							title: "InfluxDB line protocol"
+							url:   urls.questdb_ilp
+						}
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		address: {
+			description: "The address to connect to, as `host:port`. QuestDB listens on port 9009."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["localhost:9009"]
+				syntax: "literal"
+			}
+		}
+		column_types: {
+			common:      true
+			description: "The types the fields of log events are written as, by field. Values are converted to them, and the fields are skipped if they can't be. Fields without a type are written with the type of their value."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [{"status": "long", "level": "symbol", "started_at": "timestamp"}]
+				options: {
+					"*": {
+						description: "The type of the field."
+						required:    true
+						warnings: []
+						type: string: {
+							enum: {
+								symbol:    "A tag, a `SYMBOL` column in QuestDB."
+								string:    "A string. Values of other types are converted to strings, timestamps as RFC 3339."
+								long:      "A 64-bit integer, from integers, truncated floats and strings."
+								double:    "A double precision float, from floats, integers and strings."
+								boolean:   "A boolean, from booleans and the strings `true` and `false`."
+								timestamp: "A timestamp with microsecond precision, from timestamps and RFC 3339 strings. Only QuestDB accepts it."
+							}
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+		default_namespace: {
+			common:      false
+			description: "The namespace of the metrics that don't have one, prefixed to their name to form their measurement."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["service"]
+				syntax: "literal"
+			}
+		}
+		measurement: {
+			common:      true
+			description: "The measurement, the table in QuestDB, log events are written to. Metrics are written to the measurement named after them."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "vector"
+				examples: ["logs", "logs_{{ application }}"]
+				syntax: "template"
+			}
+		}
+		out_of_order: {
+			common:      false
+			description: "What is done with events arriving late, behind the latest event of their measurement."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					late_events: {
+						common:      true
+						description: "What is done with the events later than `max_lateness_secs`."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "send"
+							enum: {
+								send:             "The events are written as they are, for the server to sort."
+								drop:             "The events are dropped."
+								server_timestamp: "The events are written without their timestamp, for the server to timestamp them when it receives them."
+							}
+							syntax: "literal"
+						}
+					}
+					max_lateness_secs: {
+						description: "How far an event can be behind the latest event of its measurement before it's late."
+						required:    true
+						warnings: []
+						type: uint: {
+							examples: [60]
+							unit: "seconds"
+						}
+					}
+				}
+			}
+		}
+		quantiles: {
+			common:      false
+			description: "The quantiles written for distributions."
+			required:    false
+			warnings: []
+			type: array: {
+				default: [0.5, 0.75, 0.9, 0.95, 0.99]
+				items: type: float: examples: [0.5, 0.99]
+			}
+		}
+		tags: {
+			common:      true
+			description: "The fields of log events written as tags, symbols in QuestDB."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["host", "level"]
+					syntax: "field_path"
+				}
+			}
+		}
+	}
+
+	input: {
+		logs: true
+		metrics: {
+			counter:      true
+			distribution: true
+			gauge:        true
+			histogram:    true
+			set:          true
+			summary:      true
+		}
+	}
+
+	how_it_works: {
+		line_protocol: {
+			title: "Line protocol over TCP"
+			body:  """
+				Events are written in the [InfluxDB line protocol](\(urls.influxdb_line_protocol)) over a
+				long lived TCP connection, as QuestDB and the socket listeners of InfluxDB and Telegraf accept it.
+				This is faster than the HTTP API of the `influxdb_logs` and `influxdb_metrics` sinks, but the
+				server doesn't respond to the lines it writes, so the lines it rejects are lost. Integers are
+				written with the `i` suffix, as QuestDB has no unsigned integers.
+				"""
+		}
+
+		out_of_order: {
+			title: "Out of order events"
+			body:  """
+				QuestDB sorts the rows of a table by their timestamp, and rows arriving far behind the latest
+				rows of their table are costly to write. The sink tracks the latest timestamp of each
+				measurement, and with `out_of_order` set, handles the events later than `max_lateness_secs`
+				according to `late_events`: writing them anyway, dropping them, or leaving their timestamp to
+				the server.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_established_total: components.sources.internal_metrics.output.metrics.connection_established_total
+		connection_errors_total:      components.sources.internal_metrics.output.metrics.connection_errors_total
+		connection_shutdown_total:    components.sources.internal_metrics.output.metrics.connection_shutdown_total
+		events_discarded_total:       components.sources.internal_metrics.output.metrics.events_discarded_total
+		late_events_total:            components.sources.internal_metrics.output.metrics.late_events_total
+	}
+}
@@ -34,7 +34,7 @@ components: sinks: kafka: {
 				codec: {
 					enabled: true
 					default: null
-					enum: ["json", "text"]
+					enum: ["json", "protobuf", "text"]
 				}
 			}
 			request: enabled: false
@@ -53,6 +53,24 @@ components: sinks: kafka: {
 
 	configuration: {
 		bootstrap_servers: components._kafka.configuration.bootstrap_servers
+		headers_key: {
+			common:      false
+			description: "A map of log events whose fields are sent as the [headers](#headers) of their message. Null fields are sent as headers without a value."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["headers"]
+				syntax: "literal"
+			}
+		}
+		idempotence: {
+			common:      false
+			description: "Enables the idempotent producer, which retries without duplicating or reordering messages. Implied by `transaction`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
 		key_field: {
 			common:      true
 			description: "The log field name or tags key to use for the topic key. If the field does not exist in the log or in tags, a blank value will be used. If unspecified, the key is not sent. Kafka uses a hash of the key to choose the partition or uses round-robin if the record has no key."
@@ -76,6 +94,7 @@ components: sinks: kafka: {
 				unit: null
 			}
 		}
+		protobuf: components._protobuf.configuration.protobuf
 		sasl: {
 			common:      false
 			description: "Options for SASL/SCRAM authentication support."
@@ -127,6 +146,139 @@ components: sinks: kafka: {
 				}
 			}
 		}
+		schema_registry: {
+			common:      false
+			description: "Serializes the values of messages with a schema of a [Confluent Schema Registry][urls.confluent_schema_registry], rather than with `encoding.codec`. See [Schema Registry](#schema-registry)."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					auth: configuration._http_auth & {_args: {
+						password_example: "${SCHEMA_REGISTRY_PASSWORD}"
+						username_example: "${SCHEMA_REGISTRY_USERNAME}"
+					}}
+					auto_register: {
+						common:      true
+						description: "Registers `schema_file` under the subject, rather than looking up a version already registered. Registering a schema already registered returns its ID."
+						required:    false
+						warnings: []
+						type: bool: default: false
+					}
+					descriptor_set_file: {
+						common:      false
+						description: "The file descriptor set of `protobuf` schemas, along with its imports, as written by `protoc --include_imports --descriptor_set_out`. Required with `protobuf` schemas."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["/etc/vector/logs.desc"]
+							syntax: "literal"
+						}
+					}
+					fields: {
+						common:      false
+						description: "Fields of `protobuf` messages, mapped to the fields of events setting them. Fields not mapped are set by the event fields of the same name."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: [{"severity": "level"}]
+							options: {}
+						}
+					}
+					message_type: {
+						common:      false
+						description: "The message type of `protobuf` schemas, as `package.Message`. Required with `protobuf` schemas."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["logs.v1.Record"]
+							syntax: "literal"
+						}
+					}
+					schema_file: {
+						common:      true
+						description: "The schema registered with `auto_register`. The record names of the `record_name` and `topic_record_name` strategies are read from it: the full name of Avro records, or the `title` of JSON schemas."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["/etc/vector/log.avsc"]
+							syntax: "literal"
+						}
+					}
+					schema_type: {
+						description: "The type of the schema."
+						required:    true
+						warnings: []
+						type: string: {
+							enum: {
+								avro:     "[Apache Avro][urls.apache_avro] schemas."
+								json:     "JSON schemas. Values are written as JSON objects, without being validated."
+								protobuf: "Protocol Buffers schemas, whose messages are described by `descriptor_set_file`."
+							}
+							syntax: "literal"
+						}
+					}
+					subject: {
+						common:      false
+						description: "The subject of the schema, rather than the subject named by `subject_name_strategy`."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["logs-value"]
+							syntax: "literal"
+						}
+					}
+					subject_name_strategy: {
+						common:      false
+						description: "How the subject of the schema is named, as the strategies of the serializers of the registry do. The topic strategies require a `topic` without templates."
+						required:    false
+						warnings: []
+						type: string: {
+							default: "topic_name"
+							enum: {
+								topic_name:        "The subject is `{topic}-value`."
+								record_name:       "The subject is the record name of the schema."
+								topic_record_name: "The subject is `{topic}-{record name}`."
+							}
+							syntax: "literal"
+						}
+					}
+					tls: {
+						common:      false
+						description: "TLS options of the connections to the registry, as the `tls` options of the `http` sink."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: []
+							options: {}
+						}
+					}
+					url: {
+						description: "The URL of the registry."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["http://localhost:8081"]
+							syntax: "literal"
+						}
+					}
+					version: {
+						common:      false
+						description: "The version of the subject looked up when not registering the schema. Defaults to the latest version."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: null
+							unit:    null
+						}
+					}
+				}
+			}
+		}
 		socket_timeout_ms: components._kafka.configuration.socket_timeout_ms
 		topic: {
 			description: "The Kafka topic name to write events to."
@@ -137,6 +289,56 @@ components: sinks: kafka: {
 				syntax: "literal"
 			}
 		}
+		transaction: {
+			common:      false
+			description: "Produces the events in [transactions](#transactions), which are committed before the events are acknowledged."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					id: {
+						description: "The `transactional.id` of the producer. It must be unique to this sink, and stay the same across restarts, so that the transactions left open by an earlier run are aborted."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["vector-kafka-sink-0"]
+							syntax: "literal"
+						}
+					}
+					commit_interval_ms: {
+						common:      true
+						description: "The maximum time events are produced in a transaction before it's committed."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 1000
+							unit:    "milliseconds"
+						}
+					}
+					max_events: {
+						common:      false
+						description: "The maximum number of events produced in a transaction."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 10000
+							unit:    "events"
+						}
+					}
+					timeout_ms: {
+						common:      false
+						description: "The time after which brokers abort transactions that aren't committed. `message_timeout_ms` is lowered to it if it's longer."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 60000
+							unit:    "milliseconds"
+						}
+					}
+				}
+			}
+		}
 	}
 
 	input: {
@@ -151,7 +353,59 @@ components: sinks: kafka: {
 		}
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: components._kafka.how_it_works & {
+		headers: {
+			title: "Headers"
+			body:  """
+				With `headers_key`, the fields of the map at that key of log events are sent as the
+				headers of their message, their values as bytes. The `kafka` source reads headers
+				into the same kind of map, so headers are kept by Kafka-to-Kafka pipelines. Add the
+				key to `encoding.except_fields` to leave it out of the message itself.
+				"""
+		}
+		schema_registry: {
+			title: "Schema Registry"
+			body:  """
+				With `schema_registry`, the schema of the subject is registered or looked up when
+				the sink starts, and message values are written in the
+				[wire format][urls.confluent_schema_registry_wire_format] of the serializers of the
+				registry: a zero byte, the ID of the schema as four bytes, then the value. Protobuf
+				values are preceded by the indexes of their message type in the schema. Consumers
+				using the deserializers of the registry, such as Kafka Connect and ksqlDB, can read
+				them.
+
+				Avro values are resolved against the schema, so that fields the events don't have
+				are set to their defaults. Events that don't fit the schema are dropped. Keys are
+				written as they are, without a schema.
+				"""
+		}
+		protobuf: {
+			title: "Protobuf"
+			body:  """
+				With `encoding.codec` set to `protobuf`, the values of messages are bare protobuf
+				messages of `protobuf.message_type`, without the framing of the Schema Registry.
+				Only log events are written, and events that don't fit the message type are
+				dropped. Use `schema_registry` with a protobuf schema instead for consumers
+				reading with the deserializers of the registry.
+				"""
+		}
+		transactions: {
+			title: "Transactions"
+			body:  """
+				With the `transaction` option, the events are produced in transactions, and only
+				acknowledged once their transaction is committed. Consumers reading with
+				`isolation.level` set to `read_committed`, the default of librdkafka consumers like the `kafka` source,
+				only see the messages of committed transactions. When a transaction fails, it's
+				aborted and its events are produced again in a new transaction, so they're seen
+				once. Events whose messages are rejected, for example because they are too large,
+				are dropped from the retried transaction.
+
+				In Kafka-to-Kafka pipelines where the `kafka` source has `acknowledgements` enabled,
+				the source commits the offsets of the events once their transaction is committed. Events are only produced again if Vector stops between the commit of
+				a transaction and the commit of the offsets of its events.
+				"""
+		}
+	}
 
 	telemetry: metrics: {
 		events_discarded_total:              components.sources.internal_metrics.output.metrics.events_discarded_total
@@ -166,5 +420,7 @@ components: sinks: kafka: {
 		kafka_produced_messages_bytes_total: components.sources.internal_metrics.output.metrics.kafka_produced_messages_bytes_total
 		kafka_consumed_messages_total:       components.sources.internal_metrics.output.metrics.kafka_consumed_messages_total
 		kafka_consumed_messages_bytes_total: components.sources.internal_metrics.output.metrics.kafka_consumed_messages_bytes_total
+		kafka_transactions_aborted_total:    components.sources.internal_metrics.output.metrics.kafka_transactions_aborted_total
+		kafka_transactions_committed_total:  components.sources.internal_metrics.output.metrics.kafka_transactions_committed_total
 	}
 }
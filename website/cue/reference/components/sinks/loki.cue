@@ -84,6 +84,20 @@ components: sinks: loki: {
 				syntax: "literal"
 			}
 		}
+		compression: {
+			common:      false
+			description: "The format of the payloads."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "none"
+				enum: {
+					none:   "JSON payloads."
+					snappy: "Snappy-compressed protobuf payloads, as sent by Promtail, which are smaller and cheaper for Loki to decode."
+				}
+				syntax: "literal"
+			}
+		}
 		auth: configuration._http_auth & {_args: {
 			password_example: "${LOKI_PASSWORD}"
 			username_example: "${LOKI_USERNAME}"
@@ -136,6 +150,7 @@ components: sinks: loki: {
 				enum: {
 					"drop":              "Drop the event, with a warning."
 					"rewrite_timestamp": "Rewrite timestamp of the event to the latest timestamp that was pushed."
+					"accept":            "Send the event as is. Loki 2.4 and later accept out-of-order events within their `max_chunk_age` window, if `unordered_writes` is enabled."
 				}
 			}
 		}
@@ -153,6 +168,36 @@ components: sinks: loki: {
 			warnings: []
 			type: bool: default: true
 		}
+		structured_metadata: {
+			common:      false
+			description: """
+				A set of labels that are attached to each event as [structured metadata](\(urls.loki_structured_metadata)),
+				which isn't indexed, so unlike `labels` it can hold high cardinality values such as trace IDs. Both keys
+				and values are templatable. Requires Loki 2.9 or later.
+				"""
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [
+					{
+						"trace_id": "{{ trace_id }}"
+						"pod":      "{{ kubernetes.pod_name }}"
+					},
+				]
+				options: {
+					"*": {
+						common:      false
+						description: "Any structured metadata label, templateable"
+						required:    false
+						type: string: {
+							default: null
+							examples: ["{{ trace_id }}"]
+							syntax: "template"
+						}
+					}
+				}
+			}
+		}
 		tenant_id: {
 			common:      false
 			description: """
@@ -180,13 +225,14 @@ components: sinks: loki: {
 		decentralized_deployments: {
 			title: "Decentralized Deployments"
 			body: """
-				Loki currently does not support out-of-order inserts. If
+				Loki before 2.4 does not support out-of-order inserts. If
 				Vector is deployed in a decentralized setup then there is
 				the possibility that logs might get rejected due to data
 				races between Vector instances. To avoid this we suggest
 				either assigning each Vector instance with a unique label
 				or deploying a centralized Vector which will ensure no logs
-				will get sent out-of-order.
+				will get sent out-of-order. With Loki 2.4 and later, set
+				`out_of_order_action` to `accept` instead.
 				"""
 		}
 
@@ -0,0 +1,79 @@
+package metadata
+
+components: sinks: mqtt: {
+	title: "MQTT"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			to: components._mqtt.features.send.to
+		}
+	}
+
+	support: components._mqtt.support
+
+	configuration: components._mqtt.configuration & {
+		topic: {
+			description: "The topic to publish messages to."
+			required:    true
+			type: string: {
+				examples: ["vector", "devices/{{ host }}/logs"]
+				syntax: "template"
+			}
+		}
+		retain: {
+			common:      false
+			description: "Have the broker retain the last message of each topic, for the clients subscribing later."
+			required:    false
+			type: bool: default: false
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		delivery: {
+			title: "Delivery"
+			body: """
+				At QoS 0 events are delivered once published, and at QoS 1 once the broker acknowledges
+				them.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		send_errors_total:       components.sources.internal_metrics.output.metrics.send_errors_total
+	}
+}
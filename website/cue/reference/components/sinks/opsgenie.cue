@@ -0,0 +1,276 @@
+package metadata
+
+components: sinks: opsgenie: {
+	title: "Opsgenie"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: ["Opsgenie"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: false
+		send: {
+			batch: {
+				enabled:      false
+				common:       false
+				timeout_secs: 0
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			proxy: enabled:       true
+			request: {
+				enabled:              true
+				adaptive_concurrency: true
+				headers:              false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        true
+			}
+			to: {
+				service: services.opsgenie
+
+				interface: {
+					socket: {
+						api: {
+							title: "Opsgenie Alert API"
+							url:   urls.opsgenie_alert_api
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: [
+			"""
+				Every event is sent as an alert. Filter events upstream so that only alert-shaped
+				events reach this sink.
+				""",
+		]
+		notices: []
+	}
+
+	configuration: {
+		endpoint: {
+			common:      false
+			description: "The endpoint of the Alert API. Overrides the endpoint of the `region`."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["https://api.opsgenie.com"]
+				syntax: "literal"
+			}
+		}
+		region: {
+			common:      true
+			description: "The region of the Opsgenie account."
+			required:    false
+			type: string: {
+				default: "us"
+				enum: {
+					us: "Sends alerts to `api.opsgenie.com`."
+					eu: "Sends alerts to `api.eu.opsgenie.com`."
+				}
+				syntax: "literal"
+			}
+		}
+		api_key: {
+			description: "The API key of the API integration alerts are sent to."
+			required:    true
+			type: string: {
+				examples: ["${OPSGENIE_API_KEY}"]
+				syntax: "literal"
+			}
+		}
+		action: {
+			common:      true
+			description: "Whether events create, acknowledge or close alerts. Renders to `create`, `acknowledge` or `close`; events rendering anything else are dropped."
+			required:    false
+			type: string: {
+				default: "create"
+				examples: ["{{ status }}"]
+				syntax: "template"
+			}
+		}
+		alias: {
+			common:      true
+			description: "The alias identifying the alert across events: Opsgenie deduplicates the alerts of the same alias. Required to acknowledge or close alerts. Truncated to 512 characters."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ labels.alertname }}/{{ labels.instance }}"]
+				syntax: "template"
+			}
+		}
+		message: {
+			common:      true
+			description: "The message of the alert, truncated to 130 characters. Also the note of acknowledgements and closes."
+			required:    false
+			type: string: {
+				default: "{{ message }}"
+				examples: ["{{ host }}: {{ message }}"]
+				syntax: "template"
+			}
+		}
+		description: {
+			common:      false
+			description: "The description of the alert, truncated to 15000 characters."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ message }}"]
+				syntax: "template"
+			}
+		}
+		source: {
+			common:      false
+			description: "The source of the alert, truncated to 100 characters."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ host }}"]
+				syntax: "template"
+			}
+		}
+		entity: {
+			common:      false
+			description: "The entity the alert is about, truncated to 512 characters."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ service }}"]
+				syntax: "template"
+			}
+		}
+		tags: {
+			common:      false
+			description: "The tags of the alerts."
+			required:    false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["vector", "production"]
+					syntax: "literal"
+				}
+			}
+		}
+		priority: {
+			common:      true
+			description: "How the priority of alerts is taken from events."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					field: {
+						common:      true
+						description: "The field holding the severity of events. Its values that are names of priority levels, regardless of their case, are used as they are."
+						required:    false
+						type: string: {
+							default: "severity"
+							examples: ["level", "labels.severity"]
+							syntax: "literal"
+						}
+					}
+					default: {
+						common:      true
+						description: "The priority of events without `field`, or whose value isn't a priority or mapped to one."
+						required:    false
+						type: string: {
+							default: "P3"
+							enum: {
+								P1: "Critical."
+								P2: "High."
+								P3: "Moderate."
+								P4: "Low."
+								P5: "Informational."
+							}
+							syntax: "literal"
+						}
+					}
+					mapping: {
+						common:      false
+						description: "The priority of values of `field` that aren't names of priority levels."
+						required:    false
+						type: object: {
+							examples: [{critical: "P1", warning: "P4"}]
+							options: {
+								"*": {
+									description: "The priority of the value."
+									required:    true
+									type: string: {
+										enum: {
+											P1: "Critical."
+											P2: "High."
+											P3: "Moderate."
+											P4: "Low."
+											P5: "Informational."
+										}
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		alerts: {
+			title: "Alerts"
+			body: """
+				Each event is sent to the Alert API in its own request. Events creating alerts
+				carry their fields in the `details` of the alert, with nested fields flattened into
+				their path and values converted to strings. Events acknowledging or closing alerts
+				address them by their `alias`, and carry their `message` as the note, so events
+				acknowledging or closing alerts without one are dropped.
+				"""
+		}
+
+		deduplication: {
+			title: "Deduplication"
+			body: """
+				Opsgenie counts the alerts created with the `alias` of an open alert rather than
+				opening new ones, so rendering `alias` from the fields identifying an alert, such
+				as its name and the instance it's about, keeps repeated events from paging again.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
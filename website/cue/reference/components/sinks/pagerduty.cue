@@ -0,0 +1,259 @@
+package metadata
+
+components: sinks: pagerduty: {
+	title: "PagerDuty"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: ["PagerDuty"]
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: false
+		send: {
+			batch: {
+				enabled:      false
+				common:       false
+				timeout_secs: 0
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			proxy: enabled:       true
+			request: {
+				enabled:              true
+				adaptive_concurrency: true
+				headers:              false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        true
+			}
+			to: {
+				service: services.pagerduty
+
+				interface: {
+					socket: {
+						api: {
+							title: "PagerDuty Events API v2"
+							url:   urls.pagerduty_events_api
+						}
+						direction: "outgoing"
+						protocols: ["http"]
+						ssl: "required"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: [
+			"""
+				Every event is sent as an alert. Filter events upstream so that only alert-shaped
+				events reach this sink.
+				""",
+		]
+		notices: []
+	}
+
+	configuration: {
+		endpoint: {
+			common:      false
+			description: "The endpoint of the Events API."
+			required:    false
+			type: string: {
+				default: "https://events.pagerduty.com"
+				examples: ["https://events.eu.pagerduty.com"]
+				syntax: "literal"
+			}
+		}
+		routing_key: {
+			description: "The integration key of the service, or of the global event orchestration, alerts are sent to."
+			required:    true
+			type: string: {
+				examples: ["${PAGERDUTY_ROUTING_KEY}"]
+				syntax: "literal"
+			}
+		}
+		action: {
+			common:      true
+			description: "Whether events trigger, acknowledge or resolve alerts. Renders to `trigger`, `acknowledge` or `resolve`; events rendering anything else are dropped."
+			required:    false
+			type: string: {
+				default: "trigger"
+				examples: ["{{ status }}"]
+				syntax: "template"
+			}
+		}
+		dedup_key: {
+			common:      true
+			description: "The key identifying the alert across events: events with the same key update the same alert rather than opening new ones. Required to acknowledge or resolve alerts."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ labels.alertname }}/{{ labels.instance }}"]
+				syntax: "template"
+			}
+		}
+		summary: {
+			common:      true
+			description: "The summary of the alert, truncated to 1024 characters."
+			required:    false
+			type: string: {
+				default: "{{ message }}"
+				examples: ["{{ host }}: {{ message }}"]
+				syntax: "template"
+			}
+		}
+		source: {
+			common:      true
+			description: "The system the alert is about. Defaults to the host of events, or to the hostname of Vector."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ kubernetes.pod_name }}"]
+				syntax: "template"
+			}
+		}
+		component: {
+			common:      false
+			description: "The component of the source the alert is about."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ service }}"]
+				syntax: "template"
+			}
+		}
+		group: {
+			common:      false
+			description: "The logical grouping of the components of the source."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ cluster }}"]
+				syntax: "template"
+			}
+		}
+		class: {
+			common:      false
+			description: "The class or type of the alert."
+			required:    false
+			type: string: {
+				default: null
+				examples: ["{{ alertname }}"]
+				syntax: "template"
+			}
+		}
+		severity: {
+			common:      true
+			description: "How the severity of alerts is taken from events."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					field: {
+						common:      true
+						description: "The field holding the severity of events. Its values that are names of severity levels, regardless of their case, are used as they are."
+						required:    false
+						type: string: {
+							default: "severity"
+							examples: ["level", "labels.severity"]
+							syntax: "literal"
+						}
+					}
+					default: {
+						common:      true
+						description: "The severity of events without `field`, or whose value isn't a severity or mapped to one."
+						required:    false
+						type: string: {
+							default: "error"
+							enum: {
+								critical: "The alert is critical."
+								error:    "The alert is an error."
+								warning:  "The alert is a warning."
+								info:     "The alert is informational."
+							}
+							syntax: "literal"
+						}
+					}
+					mapping: {
+						common:      false
+						description: "The severity of values of `field` that aren't names of severity levels."
+						required:    false
+						type: object: {
+							examples: [{fatal: "critical", warn: "warning"}]
+							options: {
+								"*": {
+									description: "The severity of the value."
+									required:    true
+									type: string: {
+										enum: {
+											critical: "The alert is critical."
+											error:    "The alert is an error."
+											warning:  "The alert is a warning."
+											info:     "The alert is informational."
+										}
+										syntax: "literal"
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		alerts: {
+			title: "Alerts"
+			body: """
+				Each event is sent as a PagerDuty event, in its own request. Events triggering alerts
+				carry their fields in `custom_details`, and their timestamp as the time of the alert.
+				Events acknowledging or resolving alerts only carry their `dedup_key`, so events
+				acknowledging or resolving alerts without one are dropped.
+				"""
+		}
+
+		deduplication: {
+			title: "Deduplication"
+			body: """
+				PagerDuty groups the events of the same `dedup_key` into the same alert, so
+				rendering `dedup_key` from the fields identifying an alert, such as its name and
+				the instance it's about, keeps repeated events from paging again. Events triggering
+				alerts without a `dedup_key` always open new alerts.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
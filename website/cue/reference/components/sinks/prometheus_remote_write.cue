@@ -125,6 +125,65 @@ components: sinks: prometheus_remote_write: {
 				items: type: float: examples: [0.005, 0.01]
 			}
 		}
+		exemplars: {
+			common:      false
+			description: "Sends [exemplars](\(urls.prometheus_exemplars)) linking the metrics to the traces they were observed in."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					enabled: {
+						common:      true
+						description: "Makes exemplars of the trace tags of the metrics, instead of sending them as labels."
+						required:    false
+						type: bool: default: false
+					}
+					span_id_tag: {
+						common:      false
+						description: "The tag holding the ID of the span the metric was observed in."
+						required:    false
+						type: string: {
+							default: "span_id"
+							syntax:  "literal"
+						}
+					}
+					trace_id_tag: {
+						common:      false
+						description: "The tag holding the ID of the trace the metric was observed in. Metrics without it have no exemplar."
+						required:    false
+						type: string: {
+							default: "trace_id"
+							syntax:  "literal"
+						}
+					}
+				}
+			}
+		}
+		native_histograms: {
+			common:      false
+			description: "Sends histograms as Prometheus [native histograms](\(urls.prometheus_native_histograms)) instead of `_bucket`, `_sum` and `_count` series."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					enabled: {
+						common:      true
+						description: "Converts distributions and aggregated histograms to native histograms."
+						required:    false
+						type: bool: default: false
+					}
+					schema: {
+						common:      false
+						description: "The resolution of the native histograms, from -4 to 8. Each power of two is divided into `2^schema` buckets."
+						required:    false
+						type: int: {
+							default: 3
+							examples: [0, 3, 8]
+						}
+					}
+				}
+			}
+		}
 		quantiles: {
 			common:      false
 			description: "Quantiles to use for aggregating [distribution](\(urls.vector_metric)/#distribution) metrics into a summary."
@@ -148,6 +207,36 @@ components: sinks: prometheus_remote_write: {
 		}
 	}
 
+	how_it_works: {
+		metadata: {
+			title: "Metadata"
+			body:  """
+				The type of each metric family is sent as metadata with its series, so that backends like
+				Mimir or Thanos know the type of the series without inferring it from their names.
+				"""
+		}
+		exemplars: {
+			title: "Exemplars"
+			body:  """
+				When `exemplars.enabled` is set, the `trace_id` and `span_id` tags of the metrics are removed
+				before the metrics are aggregated, so they don't make a series per trace. The latest value
+				observed with them is sent as the exemplar of the series: of the series of counters and
+				gauges, of the bucket of the value for distributions, or of the series of native histograms.
+				Backends only store exemplars when their exemplar storage is enabled.
+				"""
+		}
+		native_histograms: {
+			title: "Native histograms"
+			body:  """
+				When `native_histograms.enabled` is set, distributions are converted to native histograms
+				with exponential buckets, which keep each sample in its bucket. Aggregated histograms are
+				converted approximately, as their buckets don't match exponential buckets: the count of each
+				bucket is sent in the native bucket of its upper bound, and the count above the last bound in
+				the native bucket after it. Backends only accept native histograms when they are enabled.
+				"""
+		}
+	}
+
 	input: {
 		logs: false
 		metrics: {
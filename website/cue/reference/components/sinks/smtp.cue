@@ -0,0 +1,204 @@
+package metadata
+
+components: sinks: smtp: {
+	title: "SMTP"
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "batch"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			batch: {
+				enabled:      true
+				common:       true
+				max_bytes:    1048576
+				max_events:   1
+				timeout_secs: 1
+			}
+			compression: enabled: false
+			encoding: enabled:    false
+			request: {
+				enabled:                    true
+				concurrency:                5
+				rate_limit_duration_secs:   1
+				rate_limit_num:             9223372036854775807
+				retry_initial_backoff_secs: 1
+				retry_max_duration_secs:    3600
+				timeout_secs:               60
+				headers:                    false
+			}
+			tls: {
+				enabled:                true
+				can_enable:             false
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        true
+			}
+			to: {
+				service: services.smtp
+
+				interface: {
+					socket: {
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: [
+			"""
+				Every email is sent to all the recipients of `to`, which makes this sink a poor fit
+				for anything but low volumes of events. Sample or throttle events upstream when
+				they may burst.
+				""",
+		]
+		notices: []
+	}
+
+	configuration: {
+		host: {
+			description: "The host of the SMTP server."
+			required:    true
+			type: string: {
+				examples: ["smtp.example.com"]
+				syntax: "literal"
+			}
+		}
+		port: {
+			common:      true
+			description: "The port of the SMTP server. Defaults to 587 with `starttls`, 465 with `tls` and 25 with `none`."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [587, 465, 25]
+				unit: null
+			}
+		}
+		security: {
+			common:      true
+			description: "How the connection to the SMTP server is secured."
+			required:    false
+			type: string: {
+				default: "starttls"
+				enum: {
+					starttls: "Upgrades the connection with [`STARTTLS`](\(urls.smtp_starttls)), failing if the server doesn't support it."
+					tls:      "Connects with TLS from the start, also known as SMTPS."
+					none:     "Sends emails in plain text."
+				}
+				syntax: "literal"
+			}
+		}
+		auth: {
+			common:      true
+			description: "The credentials to log in to the SMTP server with."
+			required:    false
+			type: object: options: {
+				user: {
+					description: "The user to log in as."
+					required:    true
+					type: string: {
+						examples: ["vector@example.com"]
+						syntax: "literal"
+					}
+				}
+				password: {
+					description: "The password of the user."
+					required:    true
+					type: string: {
+						examples: ["${SMTP_PASSWORD}"]
+						syntax: "literal"
+					}
+				}
+			}
+		}
+		from: {
+			description: "The sender of the emails."
+			required:    true
+			type: string: {
+				examples: ["Vector <vector@example.com>"]
+				syntax: "literal"
+			}
+		}
+		to: {
+			description: "The recipients of the emails."
+			required:    true
+			type: array: items: type: string: {
+				examples: ["oncall@example.com", "On-call <oncall@example.com>"]
+				syntax: "literal"
+			}
+		}
+		subject: {
+			description: "The subject of the emails, rendered for each event. Events rendering the same subject are batched into the same digests."
+			required:    true
+			type: string: {
+				examples: ["[{{ level }}] {{ host }}"]
+				syntax: "template"
+			}
+		}
+		body: {
+			common:      true
+			description: "The body rendered for each event. The body of an email is the bodies of its events, one after the other."
+			required:    false
+			type: string: {
+				default: "{{ message }}"
+				examples: ["{{ timestamp }} {{ host }}: {{ message }}"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		digests: {
+			title: "Digests"
+			body: """
+				Each event is sent in its own email by default. Raising `batch.max_events` sends
+				digests instead: the events rendering the same `subject` within `batch.timeout_secs`
+				are sent in one email, up to `batch.max_events` of them, with their bodies one
+				after the other.
+				"""
+		}
+
+		retries: {
+			title: "Retries"
+			body: """
+				Emails are retried when the connection fails or the server answers with a
+				transient (`4xx`) reply. Permanent (`5xx`) replies, such as a rejected recipient,
+				aren't retried.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -83,6 +83,49 @@ components: sinks: splunk_hec: {
 	}
 
 	configuration: {
+		acknowledgements: {
+			common:      false
+			description: "Holds the acknowledgements of the events until Splunk reports them indexed, with [indexer acknowledgements](\(urls.splunk_hec_indexer_acknowledgements))."
+			required:    false
+			type: object: {
+				examples: []
+				options: {
+					indexer_acknowledgements_enabled: {
+						common:      true
+						description: "Queries the indexer acknowledgements of the requests. Indexer acknowledgement must be enabled on the HEC token."
+						required:    false
+						type: bool: default: false
+					}
+					max_pending_acks: {
+						common:      false
+						description: "The maximum number of requests waiting for their acknowledgements. Requests wait for room past it."
+						required:    false
+						type: uint: {
+							default: 1000000
+							unit:    null
+						}
+					}
+					query_interval_secs: {
+						common:      false
+						description: "How often the pending acknowledgements are queried."
+						required:    false
+						type: uint: {
+							default: 10
+							unit:    "seconds"
+						}
+					}
+					retry_limit: {
+						common:      false
+						description: "How many times an acknowledgement is queried before its events are considered lost and errored."
+						required:    false
+						type: uint: {
+							default: 30
+							unit:    null
+						}
+					}
+				}
+			}
+		}
 		endpoint: {
 			description: "The base URL of the Splunk instance."
 			required:    true
@@ -162,17 +205,35 @@ components: sinks: splunk_hec: {
 		}
 	}
 
+	how_it_works: {
+		indexer_acknowledgements: {
+			title: "Indexer acknowledgements"
+			body:  """
+				By default, events are acknowledged once the HEC endpoint accepts them, before they are
+				indexed, so events received by an indexer that restarts before indexing them are lost. With
+				`acknowledgements.indexer_acknowledgements_enabled`, requests are sent on a channel, and their
+				events are acknowledged once querying `/services/collector/ack` reports them indexed.
+
+				Events whose acknowledgement isn't reported indexed after `retry_limit` queries, or whose
+				channel the indexers no longer know, are marked errored, so that sources with end-to-end
+				acknowledgements deliver them again. New requests are then sent on a new channel, as the
+				pending acknowledgements of the previous one were likely lost with a restart.
+				"""
+		}
+	}
+
 	input: {
 		logs:    true
 		metrics: null
 	}
 
 	telemetry: metrics: {
-		encode_errors_total:       components.sources.internal_metrics.output.metrics.encode_errors_total
-		http_request_errors_total: components.sources.internal_metrics.output.metrics.http_request_errors_total
-		processing_errors_total:   components.sources.internal_metrics.output.metrics.processing_errors_total
-		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
-		processed_events_total:    components.sources.internal_metrics.output.metrics.processed_events_total
-		requests_received_total:   components.sources.internal_metrics.output.metrics.requests_received_total
+		encode_errors_total:               components.sources.internal_metrics.output.metrics.encode_errors_total
+		http_request_errors_total:         components.sources.internal_metrics.output.metrics.http_request_errors_total
+		processing_errors_total:           components.sources.internal_metrics.output.metrics.processing_errors_total
+		processed_bytes_total:             components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:            components.sources.internal_metrics.output.metrics.processed_events_total
+		requests_received_total:           components.sources.internal_metrics.output.metrics.requests_received_total
+		splunk_pending_acks_expired_total: components.sources.internal_metrics.output.metrics.splunk_pending_acks_expired_total
 	}
 }
@@ -0,0 +1,206 @@
+package metadata
+
+components: sinks: syslog: {
+	title: "Syslog"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      true
+		healthcheck: enabled: true
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			send_buffer_bytes: enabled: true
+			keepalive: {
+				enabled:       true
+				relevant_when: "mode = `tcp`"
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    true
+				enabled_default:        false
+			}
+			to: {
+				service: services.syslog
+
+				interface: {
+					socket: {
+						api: {
+							title: "Syslog"
+							url:   urls.syslog
+						}
+						direction: "outgoing"
+						protocols: ["tcp", "udp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		address: {
+			description: "The address of the syslog server. The address _must_ include a port."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["127.0.0.1:514", "siem.example.com:6514"]
+				syntax: "literal"
+			}
+		}
+		mode: {
+			description: "The transport to send messages over."
+			required:    true
+			warnings: []
+			type: string: {
+				enum: {
+					tcp: "TCP, optionally with TLS."
+					udp: "UDP, one message per datagram."
+				}
+				syntax: "literal"
+			}
+		}
+		format: {
+			common:      true
+			description: "The format of the messages."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "rfc5424"
+				enum: {
+					rfc3164: "The [BSD syslog format](\(urls.syslog_3164)), with the timestamp in UTC."
+					rfc5424: "The [syslog protocol](\(urls.syslog_5424)), with timestamps in RFC 3339."
+				}
+				syntax: "literal"
+			}
+		}
+		framing: {
+			common:        false
+			description:   "How messages are delimited in the TCP stream, as described in [RFC 6587](\(urls.syslog_6587)). Defaults to `octet_counting`."
+			relevant_when: "mode = `tcp`"
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				enum: {
+					octet_counting: "Each message is prefixed with its length in bytes and a space."
+					newline:        "Each message is terminated with a newline. Newlines within messages are replaced with spaces."
+				}
+				syntax: "literal"
+			}
+		}
+		facility: {
+			common:      true
+			description: "The [facility](\(urls.syslog_facility)) of messages. Renders to the name of the facility, such as `local0`, or to its code. Messages rendering anything else are sent with the `user` facility."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "user"
+				examples: ["local0", "{{ facility }}"]
+				syntax: "template"
+			}
+		}
+		severity: {
+			common:      true
+			description: "The [severity](\(urls.syslog_levels)) of messages. Renders to the name of the severity, such as `err` or `warning`, or to its code. Messages rendering anything else are sent with the `info` severity."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "info"
+				examples: ["{{ level }}"]
+				syntax: "template"
+			}
+		}
+		app_name: {
+			common:      false
+			description: "The name of the application sending messages, which is the tag of RFC 3164 messages."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "vector"
+				examples: ["{{ service }}"]
+				syntax: "template"
+			}
+		}
+		proc_id: {
+			common:      false
+			description: "The ID of the process sending messages."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["{{ pid }}"]
+				syntax: "template"
+			}
+		}
+		msg_id: {
+			common:      false
+			description: "The type of messages. Only sent in the RFC 5424 format."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["{{ event_type }}"]
+				syntax: "template"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		header: {
+			title: "Message header"
+			body: """
+				The hostname of messages is the host of events, or the hostname of Vector for
+				events without one, and their timestamp is the timestamp of events. Fields of the
+				header are stripped of spaces and non-ASCII characters, and truncated to the
+				lengths RFC 5424 allows. Messages carry no structured data.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		processing_errors_total: components.sources.internal_metrics.output.metrics.processing_errors_total
+	}
+}
@@ -0,0 +1,153 @@
+package metadata
+
+components: sinks: websocket_server: {
+	title: "WebSocket Server"
+
+	classes: {
+		commonly_used: false
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		service_providers: []
+		stateful: false
+	}
+
+	features: {
+		buffer: enabled:      false
+		healthcheck: enabled: false
+		send: {
+			compression: enabled: false
+			encoding: {
+				enabled: true
+				codec: {
+					enabled: true
+					default: null
+					enum: ["json", "text"]
+				}
+			}
+			request: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			to: {
+				service: services.websocket_client
+
+				interface: {
+					socket: {
+						api: {
+							title: "WebSocket"
+							url:   urls.websocket
+						}
+						direction: "incoming"
+						port:      8080
+						protocols: ["http"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		address: {
+			common:      true
+			description: "The address to accept WebSocket connections on."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "127.0.0.1:8080"
+				examples: ["0.0.0.0:8080"]
+				syntax: "literal"
+			}
+		}
+		client_buffer_size: {
+			common:      false
+			description: "The number of messages queued for each client while it catches up."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 1000
+				unit:    "events"
+			}
+		}
+		max_clients: {
+			common:      false
+			description: "The number of clients connected at once. Further clients are refused with a `503 Service Unavailable` response until others disconnect."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [100]
+				unit: null
+			}
+		}
+		slow_clients: {
+			common:      false
+			description: "What is done with clients whose buffer is full."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "drop_messages"
+				enum: {
+					drop_messages: "Messages are dropped for the client until it catches up."
+					disconnect:    "The client is disconnected, and can reconnect to resume."
+				}
+				syntax: "literal"
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		filtering: {
+			title: "Filtering events"
+			body: """
+				Clients receive every event by default. They can receive a subset of events instead
+				with the query parameters of the WebSocket URL: each parameter requires a field of
+				events to equal a value. For example, clients connecting to
+				`ws://127.0.0.1:8080/?service=api&level=error` only receive the events whose
+				`service` field is `api` and whose `level` field is `error`.
+				"""
+		}
+		slow_clients: {
+			title: "Slow clients"
+			body: """
+				Events are sent to each client through a buffer of `client_buffer_size` messages, so
+				that slow clients don't hold up either Vector or other clients. Events are
+				acknowledged once they are queued for clients, and dropped while no clients are
+				connected.
+				"""
+		}
+	}
+
+	telemetry: metrics: {
+		client_messages_dropped_total: components.sources.internal_metrics.output.metrics.client_messages_dropped_total
+		connection_established_total:  components.sources.internal_metrics.output.metrics.connection_established_total
+		connection_shutdown_total:     components.sources.internal_metrics.output.metrics.connection_shutdown_total
+		open_connections:              components.sources.internal_metrics.output.metrics.open_connections
+	}
+}
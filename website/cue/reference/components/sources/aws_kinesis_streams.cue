@@ -0,0 +1,314 @@
+package metadata
+
+components: sources: aws_kinesis_streams: components._aws & {
+	title: "AWS Kinesis Data Streams"
+
+	features: {
+		multiline: enabled: false
+		collect: {
+			tls: enabled:        false
+			checkpoint: enabled: true
+			proxy: enabled:      true
+			from: service:       services.aws_kinesis_data_streams
+		}
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		stream_name: {
+			description: "The name of the stream to read."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["my-stream"]
+				syntax: "literal"
+			}
+		}
+		mode: {
+			common:      true
+			description: "How the records of the shards are read."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "polling"
+				enum: {
+					polling:          "Poll each shard with `GetRecords` requests, sharing the read throughput of the shards with the other consumers of the stream."
+					enhanced_fan_out: "Subscribe to each shard as an [enhanced fan-out consumer](\(urls.aws_kinesis_enhanced_fan_out)), with read throughput of its own. The consumer is registered if it doesn't exist."
+				}
+				syntax: "literal"
+			}
+		}
+		consumer_name: {
+			common:      false
+			description: "The name of the consumer to subscribe as. Required if mode=`enhanced_fan_out`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		starting_position: {
+			common:      false
+			description: "Where shards without a checkpoint are read from."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "latest"
+				enum: {
+					latest:       "Only read the records added after the source starts."
+					trim_horizon: "Read the oldest records still retained by the stream."
+				}
+				syntax: "literal"
+			}
+		}
+		poll_interval_ms: {
+			common:      false
+			description: "How long to wait between `GetRecords` requests to each shard. Used if mode=`polling`."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 1000
+				unit:    "milliseconds"
+			}
+		}
+		max_records: {
+			common:      false
+			description: "The maximum number of records of each `GetRecords` request, up to 10000. Used if mode=`polling`."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 1000
+				unit:    null
+			}
+		}
+		shard_refresh_secs: {
+			common:      false
+			description: "How long to wait between listings of the shards of the stream, which discover the shards added by resharding."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 60
+				unit:    "seconds"
+			}
+		}
+		checkpoint: {
+			common:      true
+			description: "Where the checkpoints of the shards are stored."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "file"
+				enum: {
+					file:     "In a file of the `data_dir`."
+					dynamodb: "In a DynamoDB table, configured with `dynamodb`."
+				}
+				syntax: "literal"
+			}
+		}
+		dynamodb: {
+			common:      false
+			description: "DynamoDB checkpoint store options. Required if checkpoint=`dynamodb`."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					table_name: {
+						description: "The name of the table to store the checkpoints in. The table must have a string partition key named `shard_key`."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["vector-checkpoints"]
+							syntax: "literal"
+						}
+					}
+					endpoint: {
+						common:      false
+						description: "Custom endpoint for use with AWS-compatible services. Defaults to the region of the stream."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["http://127.0.0.0:8000"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "A record of the stream."
+		fields: {
+			message: {
+				description: "The data of the record."
+				required:    true
+				type: string: {
+					examples: ["53.126.150.246 - - [01/Oct/2020:11:25:58 -0400] \"GET /disintermediate HTTP/2.0\" 401 20308"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The approximate time the record arrived in the stream. Defaults the current timestamp if this information is missing."
+			}
+			stream: {
+				description: "The stream the record was read from."
+				required:    true
+				type: string: {
+					examples: ["my-stream"]
+					syntax: "literal"
+				}
+			}
+			shard_id: {
+				description: "The shard the record was read from."
+				required:    true
+				type: string: {
+					examples: ["shardId-000000000000"]
+					syntax: "literal"
+				}
+			}
+			partition_key: {
+				description: "The partition key of the record."
+				required:    true
+				type: string: {
+					examples: ["e5a5d2b4-1b3c-4f0e-9d3a-7c1f2e8b9a60"]
+					syntax: "literal"
+				}
+			}
+			sequence_number: {
+				description: "The sequence number of the record within its shard."
+				required:    true
+				type: string: {
+					examples: ["49590338271490256608559692538361571095921575989136588802"]
+					syntax: "literal"
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		checkpoint_stores: {
+			title: "Checkpoint stores"
+			body:  """
+				With the `file` checkpoint store, the checkpoints are kept in the data directory, whereas the
+				`dynamodb` store keeps them in a DynamoDB table, so they outlive the instance reading the stream.
+
+				The sequence number of the last record of each batch read from a shard is checkpointed once the
+				batch has been sent on, or once its events have been delivered with acknowledgements enabled.
+				The shards are read from their checkpoints after a restart, and from the checkpoint of a shard
+				again if the delivery of its events fails.
+
+				Each instance of the source reads every shard of the stream, so instances reading the same stream
+				need a checkpoint store, and with enhanced fan-out a consumer, of their own.
+				"""
+		}
+
+		resharding: {
+			title: "Resharding"
+			body:  """
+				The shards of the stream are listed every `shard_refresh_secs`, and whenever a shard has been read
+				to its end. The shards created by splitting or merging shards are only read once their parents
+				have been read to their end, so that the records of each partition key are read in order.
+				"""
+		}
+
+		aggregation: {
+			title: "Aggregated records"
+			body:  """
+				The records are read as they are stored, so the records of the Kinesis Producer Library aggregated
+				into one are not split.
+				"""
+		}
+	}
+
+	permissions: iam: [
+		{
+			platform: "aws"
+			_service: "kinesis"
+
+			policies: [
+				{
+					_action: "ListShards"
+				},
+				{
+					_action:       "GetShardIterator"
+					required_when: "[`mode`](#mode) is set to `polling`"
+				},
+				{
+					_action:       "GetRecords"
+					required_when: "[`mode`](#mode) is set to `polling`"
+				},
+				{
+					_action:       "DescribeStreamSummary"
+					required_when: "[`mode`](#mode) is set to `enhanced_fan_out`"
+				},
+				{
+					_action:       "DescribeStreamConsumer"
+					required_when: "[`mode`](#mode) is set to `enhanced_fan_out`"
+				},
+				{
+					_action:       "RegisterStreamConsumer"
+					required_when: "[`mode`](#mode) is set to `enhanced_fan_out` and the consumer doesn't exist"
+				},
+				{
+					_action:       "SubscribeToShard"
+					required_when: "[`mode`](#mode) is set to `enhanced_fan_out`"
+				},
+			]
+		},
+		{
+			platform:  "aws"
+			_service:  "dynamodb"
+			_docs_tag: "amazondynamodb"
+
+			policies: [
+				{
+					_action:       "GetItem"
+					required_when: "[`checkpoint`](#checkpoint) is set to `dynamodb`"
+				},
+				{
+					_action:       "PutItem"
+					required_when: "[`checkpoint`](#checkpoint) is set to `dynamodb`"
+				},
+			]
+		},
+	]
+
+	telemetry: metrics: {
+		checkpoint_write_errors_total: components.sources.internal_metrics.output.metrics.checkpoint_write_errors_total
+		events_in_total:               components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:         components.sources.internal_metrics.output.metrics.processed_bytes_total
+		request_errors_total:          components.sources.internal_metrics.output.metrics.request_errors_total
+	}
+}
@@ -56,6 +56,7 @@ components: sources: aws_s3: components._aws & {
 				default: "sqs"
 				enum: {
 					sqs: "Consume S3 objects by polling for bucket notifications sent to an [AWS SQS queue](\(urls.aws_sqs))."
+					poll: "Consume S3 objects by periodically listing the bucket, for buckets whose notifications can't be sent to SQS."
 				}
 				syntax: "literal"
 			}
@@ -122,6 +123,89 @@ components: sources: aws_s3: components._aws & {
 				}
 			}
 		}
+		poll: {
+			common:      false
+			description: "Poll strategy options. Required if strategy=`poll`. Objects are read in the order they were last modified, and the progress is kept in the `data_dir`, so objects are not read again after a restart."
+			required:    false
+			warnings: ["Each poll lists every object under the `prefix`, so keep it as narrow as possible for large buckets."]
+			type: object: {
+				examples: []
+				options: {
+					bucket: {
+						description: "The name of the bucket to list objects of."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["my-bucket"]
+							syntax: "literal"
+						}
+					}
+					prefix: {
+						common:      true
+						description: "Only read objects whose key starts with this prefix."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["logs/"]
+							syntax: "literal"
+						}
+					}
+					suffix: {
+						common:      false
+						description: "Only read objects whose key ends with this suffix."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: [".log.gz"]
+							syntax: "literal"
+						}
+					}
+					key_patterns: {
+						common:      false
+						description: "Only read objects whose key matches one of these glob patterns."
+						required:    false
+						warnings: []
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["logs/*/app-*.log"]
+								syntax: "literal"
+							}
+						}
+					}
+					modified_since: {
+						common:      false
+						description: "Only read objects last modified at or after this time."
+						required:    false
+						warnings: []
+						type: timestamp: {}
+					}
+					poll_secs: {
+						common:      true
+						description: "How long to wait between listings of the bucket."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 60
+							unit:    "seconds"
+						}
+					}
+					data_dir: {
+						common:      false
+						description: "The directory used to persist the polling progress. By default, the global `data_dir` option is used."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["/var/lib/vector"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
 	}
 
 	output: logs: object: {
@@ -256,6 +340,10 @@ components: sources: aws_s3: components._aws & {
 				{
 					_action: "GetObject"
 				},
+				{
+					_action:       "ListBucket"
+					required_when: "[`strategy`](#strategy) is set to `poll`"
+				},
 			]
 		},
 		{
@@ -24,12 +24,18 @@ components: sources: dnstap: {
 					}
 					direction: "incoming"
 					port:      0
-					protocols: ["unix"]
+					protocols: ["unix", "tcp"]
 					socket: "/run/bind/dnstap.sock"
-					ssl:    "disabled"
+					ssl:    "optional"
 				}
 			}
-			tls: enabled: false
+			keepalive: enabled: true
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: true
+				enabled_default:        false
+			}
 		}
 	}
 
@@ -51,6 +57,16 @@ components: sources: dnstap: {
 	}
 
 	configuration: {
+		address: {
+			description:   "The address to listen for connections on, or `systemd#N` to use the Nth socket passed by systemd socket activation. If an address is used it _must_ include a port."
+			relevant_when: "mode = `tcp`"
+			required:      true
+			warnings: []
+			type: string: {
+				examples: ["0.0.0.0:9000", "systemd", "systemd#3"]
+				syntax: "literal"
+			}
+		}
 		max_frame_length: {
 			common:      false
 			description: "Max dnstap frame length that the dnstap source can handle."
@@ -60,13 +76,44 @@ components: sources: dnstap: {
 				unit:    "bytes"
 			}
 		}
+		message_types: {
+			common: false
+			description: """
+				The types of the dnstap messages to turn into events. The frames
+				holding other messages are discarded as they're received, before
+				being parsed. If empty, the messages of all types are kept.
+				"""
+			required: false
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["ClientQuery", "ClientResponse"]
+					syntax: "literal"
+				}
+			}
+		}
+		mode: {
+			common:      true
+			description: "The type of socket the DNS server sends dnstap data to."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "unix"
+				enum: {
+					unix: "Unix domain stream socket."
+					tcp:  "TCP socket."
+				}
+				syntax: "literal"
+			}
+		}
 		socket_path: {
 			description: """
 				Absolute path of server socket file to which the DNS server is
 				configured to send dnstap data. The socket file will be created
 				by dnstap source component automatically upon startup.
 				"""
-			required: true
+			relevant_when: "mode = `unix`"
+			required:      true
 			type: string: {
 				examples: ["/run/bind/dnstap.sock"]
 				syntax: "file_system_path"
@@ -81,7 +128,8 @@ components: sources: dnstap: {
 				supported by TOML, but it'd be more intuitive to use an octal number.
 				Also note that the value specified must be between `0o700` and `0o777`.
 				"""
-			required: false
+			relevant_when: "mode = `unix`"
+			required:      false
 			type: uint: {
 				default: null
 				unit:    null
@@ -91,7 +139,8 @@ components: sources: dnstap: {
 		socket_receive_buffer_size: {
 			common: false
 			description: """
-				Set receive buffer size of server Unix socket if specified.
+				Set receive buffer size of server Unix socket, or of the
+				accepted TCP connections, if specified.
 				No change to the default size if omitted.
 				"""
 			required: false
@@ -113,7 +162,8 @@ components: sources: dnstap: {
 				Set send buffer size of server Unix socket if specified.
 				No change to the default size if omitted.
 				"""
-			required: false
+			relevant_when: "mode = `unix`"
+			required:      false
 			type: uint: {
 				default: null
 				unit:    "bytes"
@@ -589,6 +639,9 @@ components: sources: dnstap: {
 								A pseudo section containing EDNS options of DNS query request
 								message. See [RFC 6891](\(urls.rfc_6891)) for detailed
 								information about its content.
+								The values of the NSID, Client Subnet, Cookie, Keepalive, Padding
+								and Extended DNS Error options are decoded, the values of
+								other options are encoded in Base64.
 								"""
 							required:    false
 							type: object: {
@@ -601,7 +654,7 @@ components: sources: dnstap: {
 											{
 												"optCode":  10
 												"optName":  "Cookie"
-												"optValue": "hbbDFmHUM9w="
+												"optValue": "85B6C31661D433DC"
 											},
 										]
 										"udpPayloadSize": 4096
@@ -887,6 +940,9 @@ components: sources: dnstap: {
 								A pseudo section containing EDNS options of DNS query response
 								message. See [RFC 6891](\(urls.rfc_6891)) for detailed
 								information about its content.
+								The values of the NSID, Client Subnet, Cookie, Keepalive, Padding
+								and Extended DNS Error options are decoded, the values of
+								other options are encoded in Base64.
 								"""
 							required:    false
 							type: object: {
@@ -1237,6 +1293,43 @@ components: sources: dnstap: {
 			]
 		}
 
+		server_tcp: {
+			title: "Server TCP Socket"
+			body: """
+				DNS servers that can't reach a local Unix Domain Socket, such as servers
+				running on other machines, can send dnstap data over TCP instead. With
+				`mode` set to `tcp`, the `dnstap` source listens for framestream connections
+				on the configured `address`, optionally with TLS:
+
+				```toml
+				[sources.my_dnstap_source]
+				type = "dnstap"
+				mode = "tcp"
+				address = "0.0.0.0:9000"
+				# Other configs
+				```
+
+				The IP address of the DNS server is added to the events in the `host_key` field.
+				"""
+		}
+
+		message_filtering: {
+			title: "Filtering Message Types"
+			body: """
+				Busy DNS servers log many more messages than are often needed, for instance
+				both the queries they receive from clients and the ones they send to other
+				servers. The [`message_types`](#message_types) option keeps only the messages
+				of the given types, discarding the others before they're parsed:
+
+				```toml
+				[sources.my_dnstap_source]
+				type = "dnstap"
+				message_types = ["ClientQuery", "ClientResponse"]
+				# Other configs
+				```
+				"""
+		}
+
 		manipulate_uds_buffer_size: {
 			title: "Manipulate UDS Buffer Size"
 			body: """
@@ -1266,9 +1359,10 @@ components: sources: dnstap: {
 	}
 
 	telemetry: metrics: {
-		events_in_total:        components.sources.internal_metrics.output.metrics.events_in_total
-		processed_bytes_total:  components.sources.internal_metrics.output.metrics.processed_bytes_total
-		processed_events_total: components.sources.internal_metrics.output.metrics.processed_events_total
-		parse_errors_total:     components.sources.internal_metrics.output.metrics.parse_errors_total
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_in_total:         components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		parse_errors_total:      components.sources.internal_metrics.output.metrics.parse_errors_total
 	}
 }
@@ -50,7 +50,7 @@ components: sources: docker_logs: {
 
 	features: {
 		collect: {
-			checkpoint: enabled: false
+			checkpoint: enabled: true
 			from: {
 				service: services.docker
 
@@ -261,6 +261,30 @@ components: sources: docker_logs: {
 				default: 1
 			}
 		}
+		since: {
+			common:      false
+			description: "Where the logs of containers without a checkpoint are read from. Defaults to when the source starts."
+			required:    false
+			warnings: []
+			type: timestamp: {
+				default: null
+				examples: ["2021-08-02T09:41:12Z"]
+			}
+		}
+		persist_checkpoints: {
+			common:      false
+			description: "Whether to store the checkpoints of the containers in the `data_dir`, so their logs are read from where they were left after a restart of Vector."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		container_events: {
+			common:      false
+			description: "Whether to send an event whenever a container is created, started, stopped, killed, destroyed, etc."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
 		host_key: {
 			category:    "Context"
 			common:      false
@@ -347,6 +371,64 @@ components: sources: docker_logs: {
 		}
 	}
 
+	output: logs: container_event: {
+		description: "A Docker container lifecycle event, sent if `container_events` is enabled."
+		fields: {
+			container_event: {
+				description: "The action of the container."
+				required:    true
+				type: string: {
+					examples: ["create", "start", "restart", "die", "kill", "oom", "stop", "destroy", "pause", "unpause"]
+					syntax: "literal"
+				}
+			}
+			container_id: {
+				description: "The Docker container ID."
+				required:    true
+				type: string: {
+					examples: ["9b6247364a03", "715ebfcee040"]
+					syntax: "literal"
+				}
+			}
+			container_name: {
+				description: "The Docker container name."
+				required:    true
+				type: string: {
+					examples: ["evil_ptolemy", "nostalgic_stallman"]
+					syntax: "literal"
+				}
+			}
+			exit_code: {
+				description: "The exit code of the container, on `die` events."
+				required:    false
+				common:      true
+				type: int: {
+					examples: [0, 137]
+				}
+			}
+			host: fields._local_host
+			image: {
+				description: "The image name of the container."
+				required:    true
+				type: string: {
+					examples: ["ubuntu:latest", "busybox"]
+					syntax: "literal"
+				}
+			}
+			message: {
+				description: "The action of the container, as `container_event`."
+				required:    true
+				type: string: {
+					examples: ["die"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The time of the event."
+			}
+		}
+	}
+
 	examples: [
 		{
 			_container_name: "flog"
@@ -390,15 +472,42 @@ components: sources: docker_logs: {
 				`partial_event_marker_field` option.
 				"""
 		}
+
+		restarts: {
+			title: "Restarts"
+			body: """
+				The time of the last log read from each container is kept as its checkpoint, and its logs are
+				read again from its checkpoint after an error, skipping the logs already read. With
+				`persist_checkpoints` enabled, the checkpoints are also stored in the `data_dir`, so the logs
+				are read from where they were left after a restart of Vector. The checkpoint of a container is
+				removed once it is destroyed.
+
+				If the Docker daemon restarts, the source reconnects after `retry_backoff_secs`, resumes the
+				container events from the last one received, and catches up with the containers started or
+				stopped meanwhile.
+				"""
+		}
+
+		routing: {
+			title: "Routing"
+			body: """
+				The events of all the containers, and their lifecycle events if `container_events` is
+				enabled, are sent together. The [`route` transform](\(urls.vector_route_transform)) can
+				split them by label, e.g. with `exists(.label."com.example.team")`, and send the lifecycle events,
+				those with a `container_event` field, elsewhere.
+				"""
+		}
 	}
 
 	telemetry: metrics: {
 		events_in_total:                       components.sources.internal_metrics.output.metrics.events_in_total
+		checkpoint_write_errors_total:         components.sources.internal_metrics.output.metrics.checkpoint_write_errors_total
 		communication_errors_total:            components.sources.internal_metrics.output.metrics.communication_errors_total
 		container_metadata_fetch_errors_total: components.sources.internal_metrics.output.metrics.container_metadata_fetch_errors_total
 		container_processed_events_total:      components.sources.internal_metrics.output.metrics.container_processed_events_total
 		containers_unwatched_total:            components.sources.internal_metrics.output.metrics.containers_unwatched_total
 		containers_watched_total:              components.sources.internal_metrics.output.metrics.containers_watched_total
+		connection_errors_total:               components.sources.internal_metrics.output.metrics.connection_errors_total
 		logging_driver_errors_total:           components.sources.internal_metrics.output.metrics.logging_driver_errors_total
 		processed_bytes_total:                 components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:                components.sources.internal_metrics.output.metrics.processed_events_total
@@ -87,6 +87,12 @@ components: sources: exec: {
 			required:    false
 			type: bool: default: true
 		}
+		include_exit_event: {
+			common:      false
+			description: "Generate an event describing how the command exited, with its exit code or terminating signal and how long it ran, each time it exits."
+			required:    false
+			type: bool: default: false
+		}
 		maximum_buffer_size_bytes: {
 			common:      false
 			description: "The maximum buffer size allowed before a log event will be generated."
@@ -143,6 +149,28 @@ components: sources: exec: {
 							unit:    "seconds"
 						}
 					}
+					max_respawn_interval_secs: {
+						common:        false
+						description:   "Double the interval between consecutive restarts up to this many seconds. A command which runs for at least this long counts as healthy, so the next restart uses `respawn_interval_secs` again. By default the interval stays fixed."
+						relevant_when: "mode = `streaming`"
+						required:      false
+						warnings: []
+						type: uint: {
+							default: null
+							unit:    "seconds"
+						}
+					}
+					max_respawns: {
+						common:        false
+						description:   "Stop restarting the command after this many consecutive restarts, without it running for `max_respawn_interval_secs` in between. By default it is restarted forever."
+						relevant_when: "mode = `streaming`"
+						required:      false
+						warnings: []
+						type: uint: {
+							default: null
+							unit:    null
+						}
+					}
 				}
 			}
 		}
@@ -185,6 +213,49 @@ components: sources: exec: {
 		}
 	}
 
+	output: logs: exit: {
+		description: "An event generated when the command exits, if `include_exit_event` is enabled."
+		fields: {
+			host:      fields._local_host
+			timestamp: fields._current_timestamp
+			message: {
+				description: "A description of how the command exited."
+				required:    true
+				type: string: {
+					examples: ["Command exited with code 1."]
+					syntax: "literal"
+				}
+			}
+			exit_code: {
+				description: "The exit code of the command, absent if it was terminated by a signal."
+				required:    false
+				common:      true
+				type: uint: {
+					default: null
+					examples: [0, 1]
+					unit: null
+				}
+			}
+			signal: {
+				description: "The signal which terminated the command, on Unix."
+				required:    false
+				common:      false
+				type: uint: {
+					default: null
+					examples: [9, 15]
+					unit: null
+				}
+			}
+			exec_duration_seconds: {
+				description: "How long the command ran."
+				required:    true
+				type: float: examples: [1.5]
+			}
+			pid:     components.sources.exec.output.logs.line.fields.pid
+			command: components.sources.exec.output.logs.line.fields.command
+		}
+	}
+
 	examples: [
 		{
 			_line:      "64 bytes from 127.0.0.1: icmp_seq=0 ttl=64 time=0.060 ms"
@@ -205,6 +205,37 @@ components: sources: file: {
 				unit: "bytes"
 			}
 		}
+		max_read_bytes_per_sec: {
+			category:    "Reading"
+			common:      false
+			description: "An approximate limit on the rate at which data is read from each file, allowing bursts of up to a second's worth."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [1048576]
+				unit: "bytes"
+			}
+		}
+		active_window_secs: {
+			common:      false
+			description: "Only watch files modified within this many seconds. Other files are not opened, fingerprinted, or read until they are modified again, and watched files idle for this long are closed, keeping their checkpoints. This speeds up startup when many rotated files match `include`."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [86400]
+				unit: "seconds"
+			}
+		}
+		checkpoint_retention_secs: {
+			common:      false
+			description: "Drop the checkpoints of files which are no longer watched after this many seconds without updates, so the checkpoint file does not grow with every file ever read. Files whose checkpoint was dropped are read from the beginning if they are modified again."
+			required:    false
+			type: uint: {
+				default: null
+				examples: [604800]
+				unit: "seconds"
+			}
+		}
 		oldest_first: {
 			category:    "Reading"
 			common:      false
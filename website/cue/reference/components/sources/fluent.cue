@@ -69,6 +69,57 @@ components: sources: fluent: {
 				syntax: "literal"
 			}
 		}
+		security: {
+			common:      false
+			description: "Requires clients to authenticate with the secure forward handshake before sending events. Combine with `tls` to encrypt the connection as well."
+			required:    false
+			type: object: options: {
+				shared_key: {
+					description: "The key shared with the clients, matching their `shared_key`."
+					required:    true
+					type: string: {
+						examples: ["${FLUENT_SHARED_KEY}"]
+						syntax: "literal"
+					}
+				}
+				self_hostname: {
+					common:      false
+					description: "The hostname sent to the clients in the handshake. Defaults to the hostname of the machine."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["aggregator.example.com"]
+						syntax: "literal"
+					}
+				}
+				users: {
+					common:      false
+					description: "The users clients have to authenticate as, in addition to the shared key. No user authentication is required if empty."
+					required:    false
+					type: array: {
+						default: []
+						items: type: object: options: {
+							username: {
+								description: "The name of the user."
+								required:    true
+								type: string: {
+									examples: ["fluentd"]
+									syntax: "literal"
+								}
+							}
+							password: {
+								description: "The password of the user."
+								required:    true
+								type: string: {
+									examples: ["${FLUENT_PASSWORD}"]
+									syntax: "literal"
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 	}
 
 	output: logs: line: {
@@ -181,27 +232,25 @@ components: sources: fluent: {
 		}
 
 		secure_mode: {
-			title: "Secure forward mode support"
+			title: "Secure forward mode"
 			body:  """
-				The `fluent` source currently supports using TLS, but does not support the authentication part of the
-				Fluent protocol including:
-
-				- Shared key
-				- Username and password
+				With `security` set, the source greets each connection with a `HELO` and requires the client to
+				authenticate with a `PING` before it accepts any events, as in the [secure forward mode](\(urls.fluent))
+				of the forward output plugins of Fluentd and Fluent Bit. The client hashes the nonce of the `HELO`
+				with the shared key, and with its password if `users` are configured, and the connection is closed
+				if these don't match.
 
-				And so these options of the secure forward output plugins for Fluent and Fluent Bit cannot be used.
-
-				If you would find this useful, [please let us know](\(urls.vector_repo)/issues/7532).
+				The handshake authenticates the client but doesn't encrypt the connection, which requires `tls` too.
 				"""
 		}
 
 		acking: {
 			title: "Acknowledgement support"
 			body:  """
-				The `fluent` source currently does not support the acknowledgement parts of the Fluent protocol and so
-				the `require_ack_response` option forward output plugins for Fluent and Fluent Bit cannot be used.
-
-				If you would find this useful, [please let us know](\(urls.vector_repo)/issues/7533).
+				When a client sends events with the `chunk` option, as the forward output plugins do with
+				`require_ack_response` enabled, the source replies with an `ack` for the chunk once all of its events
+				have been sent on to the next components. Clients which don't receive the `ack` in time resend the
+				chunk.
 				"""
 		}
 	}
@@ -71,12 +71,56 @@ components: sources: heroku_logs: {
 		acknowledgements: configuration._acknowledgements
 		address:          sources.http.configuration.address
 		auth:             sources.http.configuration.auth
+		drains: {
+			common:      false
+			description: "The drains allowed to send logs, identified by the `Logplex-Drain-Token` header of their requests. Requests from other drains are rejected with a `403`. If empty, requests from all drains are accepted."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: []
+					options: {
+						token: {
+							description: "The token of the drain, as shown by `heroku drains --json`."
+							required:    true
+							warnings: []
+							type: string: {
+								examples: ["d.8bfa9b69-9f5c-4ab1-ba30-c77b6e7a0b0f"]
+								syntax: "literal"
+							}
+						}
+						app: {
+							common:      true
+							description: "The name of the app the drain is attached to, added to its events as the `app` field."
+							required:    false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["lumberjack-store"]
+								syntax: "literal"
+							}
+						}
+					}
+				}
+			}
+		}
 		query_parameters: sources.http.configuration.query_parameters
 	}
 
 	output: logs: line: {
 		description: "An individual event from a batch of events received through an HTTP POST request."
 		fields: {
+			app: {
+				description: "The app of the drain the event was received from, if configured in `drains`."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["lumberjack-store"]
+					syntax: "literal"
+				}
+			}
 			app_name: {
 				description: "The app name field extracted from log message."
 				required:    true
@@ -85,6 +129,14 @@ components: sources: heroku_logs: {
 					syntax: "literal"
 				}
 			}
+			drain_token: {
+				description: "The token of the drain the event was received from."
+				required:    true
+				type: string: {
+					examples: ["d.8bfa9b69-9f5c-4ab1-ba30-c77b6e7a0b0f"]
+					syntax: "literal"
+				}
+			}
 			host: fields._local_host
 			message: {
 				description: "The message field, containing the plain text message."
@@ -106,10 +158,23 @@ components: sources: heroku_logs: {
 		}
 	}
 
+	how_it_works: {
+		multiple_apps: {
+			title: "Receiving logs from multiple apps"
+			body:  """
+				One source can receive the logs of the drains of many apps. Listing the drains in `drains` keeps
+				other drains from sending logs to it, and tags the events of each drain with the name of its
+				app, so that a [`route` transform](\(urls.vector_route_transform)) can send the events of each
+				app on to different components, for instance with a `.app == "lumberjack-store"` condition.
+				"""
+		}
+	}
+
 	telemetry: metrics: {
 		events_in_total:           components.sources.internal_metrics.output.metrics.events_in_total
 		processed_bytes_total:     components.sources.internal_metrics.output.metrics.processed_bytes_total
 		request_read_errors_total: components.sources.internal_metrics.output.metrics.request_read_errors_total
 		requests_received_total:   components.sources.internal_metrics.output.metrics.requests_received_total
+		requests_rejected_total:   components.sources.internal_metrics.output.metrics.requests_rejected_total
 	}
 }
@@ -79,7 +79,7 @@ components: sources: host_metrics: {
 
 	configuration: {
 		collectors: {
-			description: "The list of host metric collector services to use. Defaults to all collectors."
+			description: "The list of host metric collector services to use. Defaults to all collectors, except `process`."
 			common:      true
 			required:    false
 			type: array: {
@@ -93,6 +93,7 @@ components: sources: host_metrics: {
 						host:       "Metrics related to host"
 						memory:     "Metrics related to memory utilization."
 						network:    "Metrics related to network utilization."
+						process:    "Metrics of each process matching the `process` options (Linux only)."
 					}
 					syntax: "literal"
 				}
@@ -332,6 +333,93 @@ components: sources: host_metrics: {
 				}
 			}
 		}
+		process: {
+			common:      false
+			description: #"Options for the "process" metrics collector."#
+			required:    false
+			type: object: options: {
+				names: {
+					common:      false
+					required:    false
+					description: "Lists of process name patterns to include or exclude."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which to gather metrics, matched against
+								the name the kernel reports, which is truncated to 15 characters.
+								Defaults to including all processes.
+								The patterns are matched using [globbing](#globbing).
+								"""
+							type: array: {
+								default: ["*"]
+								items: type: string: {
+									examples: ["nginx", "postgres*"]
+									syntax: "literal"
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of process name patterns for which not to gather metrics.
+								Defaults to excluding no processes.
+								The patterns are matched using [globbing](#globbing).
+								"""
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["kworker*"]
+									syntax: "literal"
+								}
+							}
+						}
+					}
+				}
+				cgroups: {
+					common:      false
+					required:    false
+					description: "Lists of cgroup path patterns to include or exclude."
+					type: object: options: {
+						includes: {
+							required: false
+							common:   false
+							description: """
+								The list of cgroup path patterns for which to gather process metrics. The path
+								is the one in the unified hierarchy with cgroup v2, or else the first listed.
+								Defaults to including all cgroups.
+								The patterns are matched using [globbing](#globbing).
+								"""
+							type: array: {
+								default: ["*"]
+								items: type: string: {
+									examples: ["/system.slice/*", "/docker/*"]
+									syntax: "literal"
+								}
+							}
+						}
+						excludes: {
+							required: false
+							common:   false
+							description: """
+								The list of cgroup path patterns for which not to gather process metrics.
+								Defaults to excluding no cgroups.
+								The patterns are matched using [globbing](#globbing).
+								"""
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["/user.slice/*"]
+									syntax: "literal"
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 	}
 
 	output: metrics: {
@@ -417,6 +505,26 @@ components: sources: host_metrics: {
 		network_transmit_packets_drop_total: _host & _network_nomac & {description: "The number of packets dropped during transmits on this interface."}
 		network_transmit_packets_total:      _host & _network_nomac & {description: "The number of packets transmitted on this interface."}
 
+		// Host processes
+		process_cpu_seconds_total: _host & {
+			description: "The number of CPU seconds used by the process in different modes."
+			type:        "counter"
+			tags:        _process_tags & {
+				mode: {
+					description: "Which mode the process was running in."
+					required:    true
+					examples: ["user", "system"]
+				}
+			}
+			relevant_when: "OS is Linux"
+		}
+		process_open_fds:              _host & _process_gauge & {description: "The number of files the process has open, if readable by Vector."}
+		process_read_bytes_total:      _host & _process_counter & {description: "The number of bytes the process read from storage, if readable by Vector."}
+		process_resident_memory_bytes: _host & _process_gauge & {description: "The number of bytes of main memory used by the process."}
+		process_threads:               _host & _process_gauge & {description: "The number of threads of the process."}
+		process_virtual_memory_bytes:  _host & _process_gauge & {description: "The number of bytes of virtual memory of the process."}
+		process_written_bytes_total:   _host & _process_counter & {description: "The number of bytes the process wrote to storage, if readable by Vector."}
+
 		// Helpers
 		_host: {
 			default_namespace: "host"
@@ -486,6 +594,29 @@ components: sources: host_metrics: {
 			}
 		}
 		_network_nomac: _network_gauge & {relevant_when: "OS is not macOS"}
+		_process_tags: _host_metrics_tags & {
+			collector: examples: ["process"]
+			pid: {
+				description: "The ID of the process."
+				required:    true
+				examples: ["1234"]
+			}
+			name: {
+				description: "The name of the process."
+				required:    true
+				examples: ["nginx"]
+			}
+		}
+		_process_counter: {
+			type:          "counter"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
+		_process_gauge: {
+			type:          "gauge"
+			tags:          _process_tags
+			relevant_when: "OS is Linux"
+		}
 	}
 
 	telemetry: metrics: {
@@ -71,15 +71,17 @@ components: sources: http: {
 		}
 		encoding: {
 			common:      true
-			description: "The expected encoding of received data. Note that for `json` and `ndjson` encodings, the fields of the JSON objects are output as separate fields."
+			description: "The expected encoding of received data. Note that for `json` and `ndjson` encodings, the fields of the JSON objects are output as separate fields, as are the fields of the records of `avro` bodies and of `protobuf` messages."
 			required:    false
 			type: string: {
 				default: "text"
 				enum: {
-					text:   "Newline-delimited text, with each line forming a message."
-					ndjson: "Newline-delimited JSON objects, where each line must contain a JSON object."
-					json:   "Array of JSON objects, which must be a JSON array containing JSON objects."
-					binary: "Binary or text, whole http request body is considered as one message."
+					text:     "Newline-delimited text, with each line forming a message."
+					ndjson:   "Newline-delimited JSON objects, where each line must contain a JSON object."
+					json:     "Array of JSON objects, which must be a JSON array containing JSON objects."
+					binary:   "Binary or text, whole http request body is considered as one message."
+					avro:     "An [Avro](\(urls.apache_avro)) object container file, holding the schema of its records, with each record forming an event. The blocks of the file can be uncompressed, or compressed with deflate or Snappy."
+					protobuf: "A single [protobuf](\(urls.protobuf)) message of the type of the `protobuf` option, forming an event."
 				}
 				syntax: "literal"
 			}
@@ -109,6 +111,7 @@ components: sources: http: {
 				}
 			}
 		}
+		protobuf: components._protobuf.configuration.protobuf
 		path: {
 			common:      false
 			description: "The URL path on which log event POST requests shall be sent."
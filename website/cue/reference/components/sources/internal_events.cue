@@ -0,0 +1,213 @@
+package metadata
+
+components: sources: internal_events: {
+	title:       "Internal Events"
+	description: "The internal events source exposes the warnings and errors of the components of the running Vector instance, with the component they come from and an error code."
+
+	classes: {
+		commonly_used: false
+		delivery:      "at_least_once"
+		deployment_roles: ["aggregator", "daemon", "sidecar"]
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			from: service: {
+				name:     "Vector instance"
+				thing:    "a \(name)"
+				url:      urls.vector_docs
+				versions: ">= 0.16.0"
+			}
+		}
+		multiline: enabled: false
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+		notices: []
+		requirements: []
+		warnings: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		host_key: {
+			category:    "Context"
+			common:      false
+			description: """
+				The key name added to each event representing the current host. This can also be globally set via the
+				[global `host_key` option](\(urls.vector_configuration)/global-options#log_schema.host_key).
+
+				Set to "" to suppress this key.
+				"""
+			required:    false
+			warnings: []
+			type: string: {
+				default: "host"
+				syntax:  "literal"
+			}
+		}
+		pid_key: {
+			category: "Context"
+			common:   false
+			description: """
+				The key name added to each event representing the current process ID.
+
+				Set to "" to suppress this key.
+				"""
+			required: false
+			warnings: []
+			type: string: {
+				default: "pid"
+				syntax:  "literal"
+			}
+		}
+	}
+
+	output: logs: event: {
+		description: "A warning or error logged within a component."
+		fields: {
+			message: {
+				description: "The textual message of the warning or error."
+				required:    true
+				type: string: {
+					examples: ["HTTP error."]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp & {
+				description: "The exact time the warning or error was logged."
+			}
+			host: fields._local_host
+			pid: {
+				description: "The process ID of the Vector instance."
+				required:    true
+				type: uint: {
+					examples: [4232]
+					unit: null
+				}
+			}
+			component_id: {
+				description: "The ID of the component, as in the configuration."
+				required:    true
+				type: string: {
+					examples: ["my_sink"]
+					syntax: "literal"
+				}
+			}
+			component_kind: {
+				description: "The kind of the component."
+				required:    true
+				type: string: {
+					enum: {
+						source:    "The component is a source."
+						transform: "The component is a transform."
+						sink:      "The component is a sink."
+					}
+					syntax: "literal"
+				}
+			}
+			component_type: {
+				description: "The type of the component."
+				required:    true
+				type: string: {
+					examples: ["http", "elasticsearch"]
+					syntax: "literal"
+				}
+			}
+			error_code: {
+				description: "The name of the internal event the warning or error is part of, which identifies its class. Missing for the few warnings and errors logged outside of internal events."
+				required:    false
+				common:      true
+				type: string: {
+					default: null
+					examples: ["HttpClientError", "ElasticSearchResponseError"]
+					syntax: "literal"
+				}
+			}
+			"*": {
+				description: "Each field from the original message, such as `error`, is copied into the event."
+				required:    true
+				type: "*": {}
+			}
+			metadata: {
+				description: "Metadata from the log event."
+				required:    true
+				type: object: {
+					examples: []
+					options: {
+						level: {
+							description: "The level of the event."
+							required:    true
+							type: string: {
+								enum: {
+									WARN:  "Designates hazardous situations."
+									ERROR: "Designates very serious errors."
+								}
+								syntax: "literal"
+							}
+						}
+						module_path: {
+							description: "The path to the internal module where the event occurred"
+							required:    true
+							type: string: {
+								examples: ["vector::internal_events::http"]
+								syntax: "literal"
+							}
+						}
+						target: {
+							description: "Describes the part of the system where the event occurred."
+							required:    true
+							type: string: {
+								examples: ["vector"]
+								syntax: "literal"
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	how_it_works: {
+		alerting: {
+			title: "Alerting on failure classes"
+			body: """
+				Unlike the `internal_logs` source, only the warnings and errors logged within a component are
+				received, and each event tells which component it comes from in `component_id`,
+				`component_kind` and `component_type`. The `error_code` names the kind of failure, so a
+				[`route` transform](\(urls.vector_route_transform)) on, for example,
+				`.error_code == "HttpClientError" && .component_id == "my_sink"` can alert on a specific class
+				of failures of a specific component.
+
+				Avoid sending these events to a sink whose own errors would produce more of them, as that
+				feeds back into the source.
+				"""
+		}
+
+		limited_logs: {
+			title: "Events are limited by startup options"
+			body: """
+				The `LOG` environment variable and the `--quiet` command-line option also apply to the events
+				received by the `internal_events` source, so with `--quiet` warnings are not received. Rate
+				limiting of repeated log messages does not apply.
+				"""
+		}
+	}
+}
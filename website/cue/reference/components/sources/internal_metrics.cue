@@ -149,6 +149,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _internal_metrics_tags
 		}
+		client_messages_dropped_total: {
+			description:       "The total number of messages dropped for clients that didn't keep up with the events sent to them."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		config_load_errors_total: {
 			description:       "The total number of errors loading the Vector configuration."
 			type:              "counter"
@@ -254,6 +260,62 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _internal_metrics_tags
 		}
+		bigquery_rows_rejected_total: {
+			description:       "The total number of rows the GCP BigQuery sink rejected because they didn't match the schema of the table."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				dead_lettered: {
+					description: "Whether the rows were written to the dead-letter table, rather than dropped."
+					required:    true
+					enum: {
+						"true":  "The rows were written to the dead-letter table."
+						"false": "The rows were dropped."
+					}
+				}
+			}
+		}
+		channel_capacity_events: {
+			description:       "The number of events an edge into a transform, or the input buffer of a sink, can hold."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				downstream_component_id: {
+					description: "The ID of the transform or sink reading the events."
+					required:    true
+				}
+				upstream_component_id: {
+					description: "The ID of the upstream component sending on the edge. Not set for the input buffers of sinks, which are shared by all of their inputs."
+					required:    false
+				}
+			}
+		}
+		channel_queued_events: {
+			description:       "The number of events waiting on an edge into a transform, or in the input buffer of a sink. Reported every 1024 events read, and whenever the reader catches up."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				downstream_component_id: {
+					description: "The ID of the transform or sink reading the events."
+					required:    true
+				}
+				upstream_component_id: {
+					description: "The ID of the upstream component sending on the edge. Not set for the input buffers of sinks, which are shared by all of their inputs."
+					required:    false
+				}
+			}
+		}
+		channel_send_blocked_seconds: {
+			description:       "The time a component spent blocked sending events to a downstream component whose input buffer was full."
+			type:              "histogram"
+			default_namespace: "vector"
+			tags:              _component_tags & {
+				downstream_component_id: {
+					description: "The ID of the downstream component."
+					required:    true
+				}
+			}
+		}
 		checkpoint_write_errors_total: {
 			description:       "The total number of errors writing checkpoints."
 			type:              "counter"
@@ -274,6 +336,24 @@ components: sources: internal_metrics: {
 				file: _file
 			}
 		}
+		circuit_breaker_open: {
+			description:       "Whether the circuit breaker of the requests of this component is open."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags
+		}
+		circuit_breaker_opened_total: {
+			description:       "The total number of times the circuit breaker of the requests of this component opened."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags
+		}
+		circuit_breaker_requests_shed_total: {
+			description:       "The total number of requests shed while the circuit breaker was open."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags
+		}
 		collect_completed_total: {
 			description:       "The total number of metrics collections completed for this component."
 			type:              "counter"
@@ -348,12 +428,46 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		dead_letter_events_dropped_total: {
+			description:       "The total number of events this sink didn't send to its dead letter output if rejected, as too many events were pending."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags
+		}
+		dead_letter_events_total: {
+			description:       "The total number of events rejected by this sink and sent to its dead letter output."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags
+		}
 		decode_errors_total: {
 			description:       "The total number of decode errors seen when decoding data in a source component."
 			type:              "counter"
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		discovered_targets: {
+			description:       "The number of targets discovered by the last refresh of a service discovery mechanism."
+			type:              "gauge"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				mechanism: _sd_mechanism
+			}
+		}
+		discovery_errors_total: {
+			description:       "The total number of failures to refresh the targets of a service discovery mechanism."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				mechanism: _sd_mechanism
+			}
+		}
+		doris_filtered_rows_total: {
+			description:       "The total number of rows the Doris sink loaded that were filtered out by the table."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		k8s_format_picker_edge_cases_total: {
 			description:       "The total number of edge cases encountered while picking format of the Kubernetes log message."
 			type:              "counter"
@@ -561,6 +675,18 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		kafka_transactions_aborted_total: {
+			description:       "The total number of transactions the Kafka sink aborted to retry them."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		kafka_transactions_committed_total: {
+			description:       "The total number of transactions the Kafka sink committed."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		file_delete_errors_total: {
 			description:       "The total number of failures to delete a file."
 			type:              "counter"
@@ -683,6 +809,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		late_events_total: {
+			description:       "The total number of events arriving late: later than the tolerance of the `influxdb_tcp` sink, or after the partition of the `gcp_cloud_storage` sink they belong to was forgotten."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		logging_driver_errors_total: {
 			description: """
 				The total number of logging driver errors encountered caused by not using either
@@ -762,6 +894,24 @@ components: sources: internal_metrics: {
 				error_type: _error_type
 			}
 		}
+		partitions_completed_total: {
+			description:       "The total number of partitions marked complete by the `gcp_cloud_storage` sink."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		partitions_failed_total: {
+			description:       "The total number of partitions of the `gcp_cloud_storage` sink never marked complete, as one of their objects failed to be written."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
+		postgres_conflicting_rows_skipped_total: {
+			description:       "The total number of rows the PostgreSQL sink skipped because they conflicted with rows of the table."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		protobuf_decode_errors_total: {
 			description:       "The total number of [Protocol Buffers](\(urls.protobuf)) errors thrown during communication between Vector instances."
 			type:              "counter"
@@ -804,12 +954,24 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		requests_rejected_total: {
+			description:       "The total number of requests rejected by this component."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		send_errors_total: {
 			description:       "The total number of errors sending messages."
 			type:              "counter"
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		splunk_pending_acks_expired_total: {
+			description:       "The total number of requests the Splunk HEC sink errored because Splunk didn't report their events indexed."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		sqs_message_delete_failed_total: {
 			description:       "The total number of failures to delete SQS messages."
 			type:              "counter"
@@ -888,6 +1050,12 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _component_tags
 		}
+		template_bootstrap_errors_total: {
+			description:       "The total number of errors creating index templates on startup."
+			type:              "counter"
+			default_namespace: "vector"
+			tags:              _component_tags
+		}
 		timestamp_parse_errors_total: {
 			description:       "The total number of errors encountered parsing [RFC 3339](\(urls.rfc_3339)) timestamps."
 			type:              "counter"
@@ -1043,6 +1211,11 @@ components: sources: internal_metrics: {
 			description: "The file that produced the error"
 			required:    false
 		}
+		_sd_mechanism: {
+			description: "The service discovery mechanism."
+			required:    true
+			examples: ["kubernetes", "http"]
+		}
 		_host: {
 			description: "The hostname of the originating system."
 			required:    true
@@ -79,6 +79,21 @@ components: sources: journald: {
 				}
 			}
 		}
+		include_matches: {
+			common:      false
+			description: "Only read the entries with one of the listed values of each field. The matches are evaluated by the journal itself, so entries of other fields are never read. Values of the same field are ORed, and different fields are ANDed."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [
+					{
+						"_SYSTEMD_UNIT": ["sshd.service", "ntpd.service"]
+						"PRIORITY": ["0", "1", "2", "3"]
+					},
+				]
+				options: {}
+			}
+		}
 		include_units: {
 			common:      true
 			description: "The list of unit names to monitor. If empty or not present, all units are accepted. Unit names lacking a `\".\"` will have `\".service\"` appended to make them a valid service unit name."
@@ -114,6 +129,39 @@ components: sources: journald: {
 				syntax: "literal"
 			}
 		}
+		journal_namespace: {
+			common:      false
+			description: "The journal namespace to read, as set by `LogNamespace=` in the units logging to it. If not set, the default namespace is read."
+			required:    false
+			warnings: ["Requires systemd 245 or later."]
+			type: string: {
+				default: null
+				examples: ["audit"]
+				syntax: "literal"
+			}
+		}
+		since: {
+			common:      false
+			description: "Where to start reading when there is no checkpoint yet, as a time in any format accepted by the `--since` option of `journalctl`. If neither this nor `since_cursor` is set, all the available entries are read."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["2021-06-01 00:00:00", "-1h", "today"]
+				syntax: "literal"
+			}
+		}
+		since_cursor: {
+			common:      false
+			description: "Start reading right after this journal cursor when there is no checkpoint yet. Can't be used together with `since`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["s=4e9c5d1c6b4c4b8f9b7d2e1f0a3c5b7d;i=1a2b;b=0f1e2d3c4b5a69788796a5b4c3d2e1f0;m=3c4d5e;t=5c0d1e2f3a4b5;x=1f2e3d4c5b6a7988"]
+				syntax: "literal"
+			}
+		}
 	}
 
 	output: logs: {
@@ -50,6 +50,92 @@ components: sources: kafka: {
 				syntax: "literal"
 			}
 		}
+		avro: {
+			common:      false
+			description: "Decodes the values of messages as [Avro](\(urls.apache_avro)) records, whose fields are set at the root of the events rather than in the message. One of `schema`, `schema_file` or `schema_registry` is required. See [Avro](#avro)."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					schema: {
+						common:      true
+						description: "The Avro schema of the values, as JSON."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: [#"{"type": "record", "name": "Log", "fields": [{"name": "message", "type": "string"}]}"#]
+							syntax: "literal"
+						}
+					}
+					schema_file: {
+						common:      true
+						description: "A file holding the Avro schema of the values, rather than `schema`."
+						required:    false
+						warnings: []
+						type: string: {
+							default: null
+							examples: ["/etc/vector/log.avsc"]
+							syntax: "literal"
+						}
+					}
+					schema_registry: {
+						common:      false
+						description: "Looks up the Avro schemas of the values in a [Confluent Schema Registry](\(urls.confluent_schema_registry)) when Vector starts, rather than `schema`."
+						required:    false
+						warnings: []
+						type: object: {
+							examples: []
+							options: {
+								auth: configuration._http_auth & {_args: {
+									password_example: "${SCHEMA_REGISTRY_PASSWORD}"
+									username_example: "${SCHEMA_REGISTRY_USERNAME}"
+								}}
+								subject: {
+									description: "The subject of the schema."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["logs-value"]
+										syntax: "literal"
+									}
+								}
+								tls: {
+									common:      false
+									description: "TLS options of the connections to the registry, as the `tls` options of the `http` sink."
+									required:    false
+									warnings: []
+									type: object: {
+										examples: []
+										options: {}
+									}
+								}
+								url: {
+									description: "The URL of the registry."
+									required:    true
+									warnings: []
+									type: string: {
+										examples: ["http://localhost:8081"]
+										syntax: "literal"
+									}
+								}
+								version: {
+									common:      false
+									description: "The version of the subject records are resolved to, so that they have its fields whichever version they were written with. Defaults to each record's own schema."
+									required:    false
+									warnings: []
+									type: uint: {
+										default: null
+										unit:    null
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		}
 		bootstrap_servers: components._kafka.configuration.bootstrap_servers
 		commit_interval_ms: {
 			common:      false
@@ -138,6 +224,7 @@ components: sources: kafka: {
 			}
 		}
 		librdkafka_options: components._kafka.configuration.librdkafka_options
+		protobuf: components._protobuf.configuration.protobuf
 		sasl: {
 			common:      false
 			description: "Options for SASL/SCRAM authentication support."
@@ -256,6 +343,7 @@ components: sources: kafka: {
 	telemetry: metrics: {
 		events_in_total:                      components.sources.internal_metrics.output.metrics.events_in_total
 		consumer_offset_updates_failed_total: components.sources.internal_metrics.output.metrics.consumer_offset_updates_failed_total
+		decode_errors_total:                  components.sources.internal_metrics.output.metrics.decode_errors_total
 		events_failed_total:                  components.sources.internal_metrics.output.metrics.events_failed_total
 		processed_bytes_total:                components.sources.internal_metrics.output.metrics.processed_bytes_total
 		processed_events_total:               components.sources.internal_metrics.output.metrics.processed_events_total
@@ -271,5 +359,35 @@ components: sources: kafka: {
 		kafka_consumed_messages_bytes_total:  components.sources.internal_metrics.output.metrics.kafka_consumed_messages_bytes_total
 	}
 
-	how_it_works: components._kafka.how_it_works
+	how_it_works: components._kafka.how_it_works & {
+		avro: {
+			title: "Avro"
+			body:  """
+				With the `avro` option, the values of messages are decoded as Avro records, and the
+				fields of each record are set at the root of its event, along with the `topic`,
+				`partition`, and `offset` fields, rather than in the `message` field.
+
+				With `schema` or `schema_file`, values are bare records of the schema. With
+				`schema_registry`, the schemas of every version of the subject are looked up when
+				the source starts, and values are read in the
+				[wire format](\(urls.confluent_schema_registry_wire_format)) of the serializers of
+				the registry, with the schema their ID refers to. Versions registered after the
+				source starts aren't known until it restarts.
+
+				Messages that can't be decoded are skipped, and counted by the
+				`decode_errors_total` metric.
+				"""
+		}
+		protobuf: {
+			title: "Protobuf"
+			body:  """
+				With the `protobuf` option, the values of messages are decoded as bare protobuf
+				messages of `protobuf.message_type`, and the fields of each message are set at the
+				root of its event, like those of Avro records. Nested messages are objects,
+				repeated fields are arrays, enums are the names of their values, and
+				`google.protobuf.Timestamp` fields are timestamps. Fields the message doesn't set
+				are left out. Only one of `avro` or `protobuf` can be set.
+				"""
+		}
+	}
 }
@@ -0,0 +1,94 @@
+package metadata
+
+components: sources: mqtt: {
+	title: "MQTT"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			tls: {
+				enabled:                true
+				can_enable:             true
+				can_verify_certificate: false
+				can_verify_hostname:    false
+				enabled_default:        false
+			}
+			from: components._mqtt.features.collect.from
+		}
+		multiline: enabled: false
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "best_effort"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: components._mqtt.support
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: components._mqtt.configuration & {
+		topics: {
+			description: "The topic filters to subscribe to, which may contain the `+` and `#` wildcards."
+			required:    true
+			type: array: items: type: string: {
+				examples: ["sensors/+/temperature", "vector/#"]
+				syntax: "literal"
+			}
+		}
+		topic_key: {
+			common:      false
+			description: "The log field name to use for the topic the message was published to."
+			required:    false
+			type: string: {
+				default: "topic"
+				syntax:  "literal"
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual MQTT message."
+		fields: {
+			message: {
+				description: "The payload of the message."
+				required:    true
+				type: string: {
+					examples: ["{\"temperature\": 21.5}"]
+					syntax: "literal"
+				}
+			}
+			topic: {
+				description: "The topic the message was published to."
+				required:    true
+				type: string: {
+					examples: ["sensors/1/temperature"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp
+		}
+	}
+
+	telemetry: metrics: {
+		connection_errors_total: components.sources.internal_metrics.output.metrics.connection_errors_total
+		events_in_total:         components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: components._mqtt.how_it_works & {
+		acknowledgements: {
+			title: "Acknowledgements"
+			body: """
+				Messages received at QoS 1 are acknowledged to the broker once received, before they are
+				delivered to the sinks.
+				"""
+		}
+	}
+}
@@ -63,16 +63,205 @@ components: sources: prometheus_scrape: {
 
 	configuration: {
 		endpoints: {
-			description: "Endpoints to scrape metrics from."
-			required:    true
+			description: "Endpoints to scrape metrics from. Required unless targets are discovered with `kubernetes_sd` or `http_sd`."
+			required:    false
+			common:      true
 			warnings: ["You must explicitly add the path to your endpoints. Vector will _not_ automatically add `/metics`."]
 			type: array: {
+				default: []
 				items: type: string: {
 					examples: ["http://localhost:9090/metrics"]
 					syntax: "literal"
 				}
 			}
 		}
+		kubernetes_sd: {
+			common:      false
+			description: "Discovers the targets to scrape from the `Pod`s or `Endpoints` of a Kubernetes cluster, listed again on every refresh. Each target is given the same `__meta_kubernetes_*` labels as with Prometheus' Kubernetes service discovery."
+			required:    false
+			type: array: {
+				default: []
+				items: type: object: options: {
+					role: {
+						description: "The kind of resource to discover targets from."
+						required:    true
+						type: string: {
+							enum: {
+								pod:       "A target for each declared port of each container of each `Pod`, or for the `Pod`'s IP alone if it has none."
+								endpoints: "A target for each port of each address of each `Endpoints`."
+							}
+							syntax: "literal"
+						}
+					}
+					namespaces: {
+						common:      false
+						description: "The namespaces to discover targets in. Defaults to all namespaces."
+						required:    false
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["default", "monitoring"]
+								syntax: "literal"
+							}
+						}
+					}
+					label_selector: {
+						common:      false
+						description: "The label selector to filter the resources with."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["app=web"]
+							syntax: "literal"
+						}
+					}
+					field_selector: {
+						common:      false
+						description: "The field selector to filter the resources with."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["status.phase=Running"]
+							syntax: "literal"
+						}
+					}
+					kube_config_file: {
+						common:      false
+						description: "Optional path to a kubeconfig file readable by Vector. If not set, Vector will try to connect to Kubernetes using in-cluster configuration."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["/path/to/.kube/config"]
+							syntax: "literal"
+						}
+					}
+					refresh_interval_secs: {
+						common:      false
+						description: "The interval between refreshes of the targets."
+						required:    false
+						type: uint: {
+							default: 30
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
+		http_sd: {
+			common:      false
+			description: "Discovers the targets to scrape from an HTTP endpoint, in the format of [Prometheus' HTTP service discovery](\(urls.prometheus_http_sd))."
+			required:    false
+			type: array: {
+				default: []
+				items: type: object: options: {
+					url: {
+						description: "The URL returning the target groups."
+						required:    true
+						type: string: {
+							examples: ["http://discovery.example.com/targets"]
+							syntax: "literal"
+						}
+					}
+					refresh_interval_secs: {
+						common:      false
+						description: "The interval between refreshes of the targets."
+						required:    false
+						type: uint: {
+							default: 60
+							unit:    "seconds"
+						}
+					}
+					tls: configuration._tls_connect & {_args: {
+						can_enable:             false
+						can_verify_certificate: true
+						can_verify_hostname:    true
+						enabled_default:        false
+					}}
+					auth: configuration._http_auth & {_args: {
+						password_example: "${DISCOVERY_PASSWORD}"
+						username_example: "${DISCOVERY_USERNAME}"
+					}}
+				}
+			}
+		}
+		relabel_configs: {
+			common:      false
+			description: "Rules applied in order to the labels of the discovered targets, as in Prometheus' `relabel_configs`, to rewrite them or to filter the targets on them."
+			required:    false
+			type: array: {
+				default: []
+				items: type: object: options: {
+					action: {
+						common:      true
+						description: "What to do with the labels."
+						required:    false
+						type: string: {
+							default: "replace"
+							enum: {
+								replace:   "Sets `target_label` to `replacement`, expanded with the groups of `regex` if it matches the source labels."
+								keep:      "Drops the target unless `regex` matches the source labels."
+								drop:      "Drops the target if `regex` matches the source labels."
+								labelmap:  "Copies the labels whose names match `regex` to labels named after `replacement`."
+								labeldrop: "Removes the labels whose names match `regex`."
+								labelkeep: "Removes the labels whose names don't match `regex`."
+							}
+							syntax: "literal"
+						}
+					}
+					source_labels: {
+						common:      true
+						description: "The labels whose values are joined with `separator` and matched against `regex`. Missing labels are empty."
+						required:    false
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["__meta_kubernetes_pod_annotation_prometheus_io_scrape"]
+								syntax: "literal"
+							}
+						}
+					}
+					separator: {
+						common:      false
+						description: "The separator of the joined values of the source labels."
+						required:    false
+						type: string: {
+							default: ";"
+							syntax:  "literal"
+						}
+					}
+					regex: {
+						common:      true
+						description: "The regular expression, which has to match the whole value."
+						required:    false
+						type: string: {
+							default: "(.*)"
+							examples: ["true", "kube-.*"]
+							syntax: "regex"
+						}
+					}
+					target_label: {
+						common:      true
+						description: "The label set by the `replace` action, which requires it."
+						required:    false
+						type: string: {
+							default: null
+							examples: ["__address__", "pod"]
+							syntax: "literal"
+						}
+					}
+					replacement: {
+						common:      false
+						description: "The value set by the `replace` action or the name given by the `labelmap` one, where `$1` and `${name}` are replaced by the groups of `regex`."
+						required:    false
+						type: string: {
+							default: "$1"
+							examples: ["$1:$2"]
+							syntax: "literal"
+						}
+					}
+				}
+			}
+		}
 		scrape_interval_secs: {
 			common:      true
 			description: "The interval between scrapes, in seconds."
@@ -89,6 +278,25 @@ components: sources: prometheus_scrape: {
 		}}
 	}
 
+	how_it_works: {
+		service_discovery: {
+			title: "Service discovery"
+			body:  """
+				Besides the static `endpoints`, the targets to scrape can be discovered with
+				`kubernetes_sd` and `http_sd`, which refresh them on an interval so that they follow
+				the cluster's changes. The targets of a mechanism that fails to refresh are kept
+				until it next succeeds.
+
+				The labels of each discovered target are first rewritten by the `relabel_configs`,
+				which may also drop it. The URL to scrape is then built from the `__scheme__`
+				(`http` by default), `__address__`, `__metrics_path__` (`/metrics` by default) and
+				`__param_<name>` labels. The labels not starting with `__` are added to the scraped
+				metrics as tags, unless a metric has a tag of the same name, along with an
+				`instance` tag set to the address by default.
+				"""
+		}
+	}
+
 	output: metrics: {
 		counter:   output._passthrough_counter
 		gauge:     output._passthrough_gauge
@@ -97,6 +305,8 @@ components: sources: prometheus_scrape: {
 	}
 
 	telemetry: metrics: {
+		discovered_targets:        components.sources.internal_metrics.output.metrics.discovered_targets
+		discovery_errors_total:    components.sources.internal_metrics.output.metrics.discovery_errors_total
 		events_in_total:           components.sources.internal_metrics.output.metrics.events_in_total
 		http_error_response_total: components.sources.internal_metrics.output.metrics.http_error_response_total
 		http_request_errors_total: components.sources.internal_metrics.output.metrics.http_request_errors_total
@@ -0,0 +1,189 @@
+package metadata
+
+components: sources: redis: {
+	title: "Redis"
+
+	features: {
+		collect: {
+			checkpoint: enabled: false
+			from: {
+				service: services.redis
+				interface: {
+					socket: {
+						direction: "outgoing"
+						protocols: ["tcp"]
+						ssl: "optional"
+					}
+				}
+			}
+		}
+		multiline: enabled: false
+	}
+
+	classes: {
+		commonly_used: false
+		deployment_roles: ["aggregator"]
+		delivery:      "at_least_once"
+		development:   "beta"
+		egress_method: "stream"
+		stateful:      false
+	}
+
+	support: {
+		targets: {
+			"aarch64-unknown-linux-gnu":      true
+			"aarch64-unknown-linux-musl":     true
+			"armv7-unknown-linux-gnueabihf":  true
+			"armv7-unknown-linux-musleabihf": true
+			"x86_64-apple-darwin":            true
+			"x86_64-pc-windows-msv":          true
+			"x86_64-unknown-linux-gnu":       true
+			"x86_64-unknown-linux-musl":      true
+		}
+
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	installation: {
+		platform_name: null
+	}
+
+	configuration: {
+		acknowledgements: configuration._acknowledgements
+		url: {
+			description: "The Redis URL to connect to. The url _must_ take the form of `protocol://server:port/db` where the protocol can either be `redis` or `rediss` for connections secured via TLS."
+			required:    true
+			type: string: {
+				examples: ["redis://127.0.0.1:6379/0"]
+				syntax: "literal"
+			}
+		}
+		key: {
+			description: "The Redis key to read messages from."
+			required:    true
+			type: string: {
+				examples: ["vector"]
+				syntax: "literal"
+			}
+		}
+		data_type: {
+			common:      false
+			description: "The Redis data type (`stream`) to use."
+			required:    false
+			type: string: {
+				default: "stream"
+				enum: {
+					stream: "Use the Redis `stream` data type, read through a consumer group."
+				}
+				syntax: "literal"
+			}
+		}
+		id_key: {
+			common:      false
+			description: "The log field name to use for the ID of the stream entry."
+			required:    false
+			type: string: {
+				default: "id"
+				syntax:  "literal"
+			}
+		}
+		stream: {
+			description: "Options for reading streams."
+			required:    true
+			type: object: options: {
+				group: {
+					description: "The consumer group to read the stream as a member of. It is created, along with the stream, if it doesn't exist."
+					required:    true
+					type: string: {
+						examples: ["vector"]
+						syntax: "literal"
+					}
+				}
+				consumer: {
+					common:      false
+					description: "The name of this consumer within the group. Defaults to the hostname."
+					required:    false
+					type: string: {
+						default: null
+						examples: ["vector-0"]
+						syntax: "literal"
+					}
+				}
+				start_id: {
+					common:      false
+					description: "The ID a group created by Vector starts reading from. `$` reads only new entries, `0` reads the whole stream."
+					required:    false
+					type: string: {
+						default: "$"
+						examples: ["$", "0"]
+						syntax: "literal"
+					}
+				}
+				batch_size: {
+					common:      false
+					description: "The maximum number of entries read at once."
+					required:    false
+					type: uint: {
+						default: 100
+						unit:    null
+					}
+				}
+				block_ms: {
+					common:      false
+					description: "How long to wait for new entries before reading again."
+					required:    false
+					type: uint: {
+						default: 1000
+						unit:    "milliseconds"
+					}
+				}
+				claim_min_idle_ms: {
+					common:      false
+					description: "If set, entries left pending by other consumers of the group for at least this long are claimed and read by this consumer."
+					required:    false
+					type: uint: {
+						default: null
+						examples: [60000]
+						unit: "milliseconds"
+					}
+				}
+			}
+		}
+	}
+
+	output: logs: record: {
+		description: "An individual Redis stream entry. Each field of the entry becomes a field of the event."
+		fields: {
+			id: {
+				description: "The ID of the stream entry."
+				required:    true
+				type: string: {
+					examples: ["1638316800000-0"]
+					syntax: "literal"
+				}
+			}
+			timestamp: fields._current_timestamp
+		}
+	}
+
+	telemetry: metrics: {
+		connection_send_ack_errors_total: components.sources.internal_metrics.output.metrics.connection_send_ack_errors_total
+		events_in_total:                  components.sources.internal_metrics.output.metrics.events_in_total
+		processed_bytes_total:            components.sources.internal_metrics.output.metrics.processed_bytes_total
+	}
+
+	how_it_works: {
+		consumer_groups: {
+			title: "Consumer groups"
+			body: """
+				Entries are read as a member of a consumer group and are acknowledged with `XACK` once
+				written, or once delivered to the sinks when acknowledgements are enabled. Entries which
+				aren't acknowledged stay pending: on start, Vector first reads the entries still pending for
+				its consumer, and with `claim_min_idle_ms` set it claims those left pending by other
+				consumers for too long, such as consumers which stopped for good.
+				"""
+		}
+	}
+}
@@ -119,6 +119,9 @@ components: sources: socket: {
 				syntax: "literal"
 			}
 		}
+		protobuf: components._protobuf.configuration.protobuf & {
+			relevant_when: "mode = `tcp` or `udp`"
+		}
 		shutdown_timeout_secs: {
 			common:        false
 			description:   "The timeout before a connection is forcefully closed during shutdown."
@@ -159,8 +162,21 @@ components: sources: socket: {
 		},
 	]
 
+	how_it_works: protobuf: {
+		title: "Protobuf"
+		body:  """
+			With the `protobuf` option, messages are decoded as protobuf messages of
+			`protobuf.message_type` rather than as lines, with the fields of each message set at
+			the root of its event. Over TCP, each message is preceded by its length as a varint,
+			as protobuf's `writeDelimitedTo` writes them, and messages longer than `max_length`
+			are discarded. Over UDP, each datagram is one message. Messages that can't be
+			decoded are skipped, and counted by the `decode_errors_total` metric.
+			"""
+	}
+
 	telemetry: metrics: {
 		events_in_total:                  components.sources.internal_metrics.output.metrics.events_in_total
+		decode_errors_total:              components.sources.internal_metrics.output.metrics.decode_errors_total
 		connection_errors_total:          components.sources.internal_metrics.output.metrics.connection_errors_total
 		connection_failed_total:          components.sources.internal_metrics.output.metrics.connection_failed_total
 		connection_established_total:     components.sources.internal_metrics.output.metrics.connection_established_total
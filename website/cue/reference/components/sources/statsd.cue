@@ -76,16 +76,17 @@ components: sources: statsd: {
 			warnings: []
 			type: string: {
 				enum: {
-					tcp:  "TCP Socket."
-					udp:  "UDP Socket."
-					unix: "Unix Domain Socket."
+					tcp:           "TCP Socket."
+					udp:           "UDP Socket."
+					unix:          "Unix Domain Socket."
+					unix_datagram: "Unix Domain Datagram Socket, as used by DogStatsD clients."
 				}
 				syntax: "literal"
 			}
 		}
 		path: {
 			description:   "The unix socket path. *This should be an absolute path*."
-			relevant_when: "mode = `unix`"
+			relevant_when: "mode = `unix` or `unix_datagram`"
 			required:      true
 			warnings: []
 			type: string: {
@@ -93,6 +94,17 @@ components: sources: statsd: {
 				syntax: "literal"
 			}
 		}
+		max_length: {
+			common:        false
+			description:   "The maximum size of a datagram, which may contain several lines. Larger datagrams are truncated."
+			relevant_when: "mode = `unix_datagram`"
+			required:      false
+			warnings: []
+			type: uint: {
+				default: 8192
+				unit:    "bytes"
+			}
+		}
 		shutdown_timeout_secs: {
 			common:        false
 			description:   "The timeout before a connection is forcefully closed during shutdown."
@@ -114,6 +126,143 @@ components: sources: statsd: {
 		set:          output._passthrough_set
 	}
 
+	output: logs: {
+		event: {
+			description: "A DogStatsD event, as sent with the `_e{<title length>,<text length>}:<title>|<text>` format."
+			fields: {
+				title: {
+					description: "The title of the event."
+					required:    true
+					type: string: {
+						examples: ["Deployment finished"]
+						syntax: "literal"
+					}
+				}
+				text: {
+					description: "The text of the event, where escaped newlines are unescaped."
+					required:    true
+					type: string: {
+						examples: ["Version 1.2.3 is now live."]
+						syntax: "literal"
+					}
+				}
+				host: {
+					description: "The host the event is about, from the `h:` field."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["my-host"]
+						syntax: "literal"
+					}
+				}
+				aggregation_key: {
+					description: "The key grouping related events, from the `k:` field."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["deploys"]
+						syntax: "literal"
+					}
+				}
+				priority: {
+					description: "The priority of the event, from the `p:` field."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["normal", "low"]
+						syntax: "literal"
+					}
+				}
+				source_type_name: {
+					description: "The type of source of the event, from the `s:` field."
+					required:    false
+					common:      false
+					type: string: {
+						default: null
+						examples: ["jenkins"]
+						syntax: "literal"
+					}
+				}
+				alert_type: {
+					description: "The alert type of the event, from the `t:` field."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["info", "error", "warning", "success"]
+						syntax: "literal"
+					}
+				}
+				tags:      _tags
+				timestamp: _timestamp
+			}
+		}
+		service_check: {
+			description: "A DogStatsD service check, as sent with the `_sc|<name>|<status>` format."
+			fields: {
+				check: {
+					description: "The name of the check."
+					required:    true
+					type: string: {
+						examples: ["app.is_up"]
+						syntax: "literal"
+					}
+				}
+				status: {
+					description: "The status of the check, `0` for OK, `1` for warning, `2` for critical and `3` for unknown."
+					required:    true
+					type: uint: {
+						examples: [0, 2]
+						unit: null
+					}
+				}
+				host: {
+					description: "The host the check is about, from the `h:` field."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["my-host"]
+						syntax: "literal"
+					}
+				}
+				message: {
+					description: "The message of the check, from the `m:` field."
+					required:    false
+					common:      true
+					type: string: {
+						default: null
+						examples: ["Connection refused"]
+						syntax: "literal"
+					}
+				}
+				tags:      _tags
+				timestamp: _timestamp
+			}
+		}
+
+		_tags: {
+			description: "The tags of the event, as sent."
+			required:    false
+			common:      true
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["env:prod", "team:web"]
+					syntax: "literal"
+				}
+			}
+		}
+		_timestamp: {
+			description: "The time of the event from the `d:` field, or the time it was received."
+			required:    true
+			type: timestamp: {}
+		}
+	}
+
 	how_it_works: {
 		timestamps: {
 			title: "Timestamps"
@@ -124,6 +273,24 @@ components: sources: statsd: {
 				`null` timestamps are substituted with the current time by downstream sinks or
 				third-party services during sending/ingestion. See the
 				[metric data model](\(urls.vector_metric)) page for more info.
+
+				The exception is DogStatsD's `|T<unix seconds>` field, which sets the timestamp
+				of the metric.
+				"""
+		}
+		dogstatsd: {
+			title: "DogStatsD extensions"
+			body:  """
+				Besides `#` tags, the source accepts the extensions of the DogStatsD protocol, so
+				that it can stand in for a local Datadog agent:
+
+				* Several values packed into one metric, as in `latency:12:15:19|ms`.
+				* A `|c:<container ID>` field, added as the `container_id` tag.
+				* A `|T<unix seconds>` field, setting the timestamp of the metric.
+				* Events and service checks, which are output as logs.
+
+				DogStatsD clients usually send to a Unix datagram socket, which the
+				`unix_datagram` mode listens on. Each datagram may hold several lines.
 				"""
 		}
 	}
@@ -5,7 +5,7 @@ components: transforms: geoip: {
 
 	description: """
 		Enrich events with geolocation data from the MaxMind GeoIP2-City,
-		GeoLite2-City, GeoIP2-ISP and GeoLite2-ASN databases.
+		GeoLite2-City, GeoIP2-ISP, GeoLite2-ASN and GeoIP2-Connection-Type databases.
 		"""
 
 	classes: {
@@ -50,7 +50,7 @@ components: transforms: geoip: {
 				"""
 			required:    true
 			type: string: {
-				examples: ["/path/to/GeoLite2-City.mmdb", "/path/to/GeoLite2-ISP.mmdb"]
+				examples: ["/path/to/GeoLite2-City.mmdb", "/path/to/GeoLite2-ISP.mmdb", "/path/to/GeoIP2-Connection-Type.mmdb"]
 				syntax: "literal"
 			}
 		}
@@ -97,6 +97,8 @@ components: transforms: geoip: {
 				* [GeoIP2-ISP.mmdb](\(urls.maxmind_geoip2_isp)) (paid) — Determine the Internet
 					Service Provider (ISP), organization name, and autonomous system organization
 					and number associated with an IP address.
+				* [GeoIP2-Connection-Type.mmdb](\(urls.maxmind_geoip2_connection_type)) (paid) —
+					Determine the connection type associated with an IP address.
 
 				The database files should be in the [MaxMind DB file
 				format](\(urls.maxmind_db_file_format)).
@@ -115,7 +117,7 @@ components: transforms: geoip: {
 			geoip: {
 				description: """
 					The root field containing all geolocation data as subfields. Depending on the
-					database used, either the city or the ISP field is populated.
+					database used, either the city, the ISP or the connection type fields are populated.
 					"""
 				required: true
 				type: object: {
@@ -167,6 +169,20 @@ components: transforms: geoip: {
 							}
 							groups: ["City"]
 						}
+						connection_type: {
+							description: """
+								The connection type associated with the IP address. Available with the
+								[GeoIP2-Connection-Type](\(urls.maxmind_geoip2_connection_type)) database.
+								"""
+							required:    false
+							common:      false
+							type: string: {
+								default: null
+								examples: ["Cable/DSL", "Cellular", "Corporate", "Dialup", "Satellite"]
+								syntax: "literal"
+							}
+							groups: ["Connection Type"]
+						}
 						continent_code: {
 							description: """
 								The continent code associated with the IP address.
@@ -130,6 +130,25 @@ configuration: {
 			}
 		}
 
+		size_accounting: {
+			common:      false
+			description: """
+				How the in-memory size of log events is accounted for as they are buffered and
+				batched. Events received from another Vector instance whose fields haven't been read
+				count the size of their encoded fields either way.
+				"""
+			required:    false
+			warnings: []
+			type: string: {
+				default: "cached"
+				enum: {
+					cached: "The size is measured once and kept up to date as fields change, only measuring the changed fields again."
+					exact:  "Every value is measured each time the size is needed."
+				}
+				syntax: "literal"
+			}
+		}
+
 		timezone: {
 			common:      false
 			description: """
@@ -0,0 +1,25 @@
+package metadata
+
+remap: errors: "109": {
+	title:       "Closure arity mismatch"
+	description: """
+		A [function call expression](\(urls.vrl_expressions)#function-call) supplies a closure that declares a
+		different number of variables than the function binds.
+		"""
+	resolution: """
+		Declare exactly as many closure variables as the function documents.
+		"""
+
+	examples: [
+		{
+			"title": title
+			source: #"""
+				filter([1, 2]) -> |value| { value != 1 }
+				"""#
+			diff: #"""
+				-filter([1, 2]) -> |value| { value != 1 }
+				+filter([1, 2]) -> |_index, value| { value != 1 }
+				"""#
+		},
+	]
+}
@@ -0,0 +1,25 @@
+package metadata
+
+remap: errors: "111": {
+	title:       "Unexpected closure"
+	description: """
+		A [function call expression](\(urls.vrl_expressions)#function-call) supplies a closure to a function that
+		doesn't accept one.
+		"""
+	resolution: """
+		Remove the closure from the function call.
+		"""
+
+	examples: [
+		{
+			"title": title
+			source: #"""
+				upcase("foo") -> |value| { value }
+				"""#
+			diff: #"""
+				-upcase("foo") -> |value| { value }
+				+upcase("foo")
+				"""#
+		},
+	]
+}
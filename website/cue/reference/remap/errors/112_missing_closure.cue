@@ -0,0 +1,25 @@
+package metadata
+
+remap: errors: "112": {
+	title:       "Missing closure"
+	description: """
+		A [function call expression](\(urls.vrl_expressions)#function-call) invokes a function that requires a
+		closure, without supplying one.
+		"""
+	resolution: """
+		Add a closure to the function call, adhering to the function's documented signature.
+		"""
+
+	examples: [
+		{
+			"title": title
+			source: #"""
+				map_keys({ "a": 1 })
+				"""#
+			diff: #"""
+				-map_keys({ "a": 1 })
+				+map_keys({ "a": 1 }) -> |key| { upcase(key) }
+				"""#
+		},
+	]
+}
@@ -0,0 +1,26 @@
+package metadata
+
+remap: errors: "113": {
+	title:       "Invalid closure return type"
+	description: """
+		A [function call expression](\(urls.vrl_expressions)#function-call) supplies a closure that can resolve to a
+		type the function doesn't accept.
+		"""
+	resolution: """
+		Ensure the last expression of the closure block resolves to the type documented by the function, for example
+		by using a type function such as `string!` or `to_bool`.
+		"""
+
+	examples: [
+		{
+			"title": title
+			source: #"""
+				filter({ "a": true }) -> |_key, value| { value }
+				"""#
+			diff: #"""
+				-filter({ "a": true }) -> |_key, value| { value }
+				+filter({ "a": true }) -> |_key, value| { to_bool(value) ?? false }
+				"""#
+		},
+	]
+}
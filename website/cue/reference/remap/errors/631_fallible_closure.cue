@@ -0,0 +1,31 @@
+package metadata
+
+remap: errors: "631": {
+	title: "Fallible closure"
+	description: """
+		You've supplied a closure whose block can fail to a function.
+		"""
+
+	rationale: """
+		In VRL, closures run by functions need to be infallible. Otherwise, the function would fail part way through
+		iterating over its value, and its outcome would be indeterminate.
+		"""
+
+	resolution: """
+		Make the closure block infallible, potentially by aborting on error using `!`, coalescing the error using `??`,
+		or via some other method.
+		"""
+
+	examples: [
+		{
+			"title": "\(title)"
+			source: #"""
+				map_values({ "a": "1" }) -> |value| { to_int(value) }
+				"""#
+			diff: #"""
+				- 	map_values({ "a": "1" }) -> |value| { to_int(value) }
+				+# 	map_values({ "a": "1" }) -> |value| { to_int(value) ?? 0 }
+				"""#
+		},
+	]
+}
@@ -15,7 +15,7 @@ remap: expressions: function_call: {
 
 	grammar: {
 		source: """
-			function ~ abort? ~ "(" ~ arguments? ~ ")"
+			function ~ abort? ~ "(" ~ arguments? ~ ")" ~ closure?
 			"""
 		definitions: {
 			function: {
@@ -95,6 +95,22 @@ remap: expressions: function_call: {
 					}
 				}
 			}
+			closure: {
+				description: """
+					The `closure` is an optional block of expressions, preceded by `->` and a pipe-delimited list of
+					variable names, that some functions run for each item they iterate over:
+
+					```vrl
+					map_values(.) -> |value| { upcase!(value) }
+					```
+
+					Functions that accept a closure document which values are bound to its variables, and require
+					one to be supplied. Variables bound by the closure are only in scope within the closure block,
+					any variable of the same name outside of it is left untouched.
+
+					The closure block must be infallible, and must resolve to the type documented by the function.
+					"""
+			}
 		}
 	}
 
@@ -120,5 +136,12 @@ remap: expressions: function_call: {
 				"""#
 			return: ["hello", "world!"]
 		},
+		{
+			title: "Function invocation with a closure"
+			source: #"""
+				filter([1, 2, 3]) -> |_index, value| { value != 2 }
+				"""#
+			return: [1, 3]
+		},
 	]
 }
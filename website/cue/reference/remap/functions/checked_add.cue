@@ -0,0 +1,52 @@
+package metadata
+
+remap: functions: checked_add: {
+	category: "Number"
+	description: """
+		Adds `other` to `value`, raising an error if the result overflows rather than silently wrapping
+		around.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The number to add to."
+			required:    true
+			type: ["integer", "float"]
+		},
+		{
+			name:        "other"
+			description: "The number to add."
+			required:    true
+			type: ["integer", "float"]
+		},
+	]
+	internal_failure_reasons: [
+		"The sum of two integers is out of the range of a 64-bit signed integer.",
+		"The sum of two numbers, at least one of which is a float, is infinite.",
+	]
+	return: {
+		types: ["integer", "float"]
+		rules: [
+			"If both `value` and `other` are integers, an integer is returned.",
+			"Otherwise, a float is returned.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Add integers"
+			source: #"""
+				checked_add!(1, 2)
+				"""#
+			return: 3
+		},
+		{
+			title: "Add integers that overflow"
+			source: #"""
+				checked_add(9223372036854775807, 1) ?? -1
+				"""#
+			return: -1
+		},
+	]
+}
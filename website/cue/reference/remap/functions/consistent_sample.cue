@@ -0,0 +1,54 @@
+package metadata
+
+remap: functions: consistent_sample: {
+	category:    "Random"
+	description: """
+		Deterministically decides whether an event belongs to a sample of the given `rate`, based on the hash
+		of `key`.
+
+		The same `key` and `seed` always yield the same decision, across restarts and across Vector instances,
+		which makes this function suitable for sampling all events sharing a trace or request ID in a `filter`
+		condition.
+		"""
+
+	arguments: [
+		{
+			name:        "key"
+			description: "The value to hash, typically a trace or request ID. Non-string values are hashed by their string representation."
+			required:    true
+			type: ["any"]
+		},
+		{
+			name:        "rate"
+			description: "The fraction of keys to sample, between `0` and `1`. Must be a literal."
+			required:    true
+			type: ["float", "integer"]
+		},
+		{
+			name:        "seed"
+			description: "The seed mixed into the hash. Instances that must agree on decisions must share the same seed. Must be a literal."
+			required:    false
+			default:     0
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["boolean"]
+
+	examples: [
+		{
+			title: "Sample by trace ID"
+			source: #"""
+				consistent_sample("4bf92f3577b34da6", 0.5)
+				"""#
+			return: false
+		},
+		{
+			title: "Sample by trace ID with a seed"
+			source: #"""
+				consistent_sample("4bf92f3577b34da6", 0.5, seed: 1)
+				"""#
+			return: true
+		},
+	]
+}
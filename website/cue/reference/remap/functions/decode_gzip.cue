@@ -0,0 +1,35 @@
+package metadata
+
+remap: functions: decode_gzip: {
+	category:    "Codec"
+	description: """
+		Decodes the `value` (a [Gzip](\(urls.gzip)) string) into its original string. Concatenated Gzip members
+		decode to the concatenation of their contents.
+
+		This allows decoding compressed payloads inline, such as the `data` of
+		[CloudWatch Logs subscriptions](\(urls.aws_cloudwatch_logs_subscriptions)) once Base64 decoded.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The [Gzip](\(urls.gzip)) data to decode."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid encoded Gzip string.",
+	]
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Decode Gzip data"
+			source: """
+				decode_gzip!(decode_base64!("H4sIAAAAAAACAyvISU0sTlVISU3OT0lVyE0FAJsZ870QAAAA"))
+				"""
+			return: "please decode me"
+		},
+	]
+}
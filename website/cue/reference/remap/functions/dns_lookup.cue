@@ -0,0 +1,47 @@
+package metadata
+
+remap: functions: dns_lookup: {
+	category: "IP"
+	description: """
+		Resolves the `value` hostname into the list of IP addresses it points to, using the system resolver.
+		"""
+	notices: [
+		"""
+			Successful lookups are cached for 60 seconds and shared with `reverse_dns`, so repeated lookups of the same
+			host don't hit the resolver for every event. Failed lookups aren't cached.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The hostname to resolve."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "timeout"
+			description: "The number of seconds to wait for the resolver before giving up."
+			required:    false
+			default:     1
+			type: ["integer", "float"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` couldn't be resolved.",
+		"The resolution didn't complete within `timeout`.",
+		"`timeout` isn't a positive number of at most 60 seconds.",
+		"Too many resolutions are already waiting for the resolver.",
+	]
+	return: types: ["array"]
+
+	examples: [
+		{
+			title: "Resolve a hostname"
+			source: #"""
+				dns_lookup!("localhost")
+				"""#
+			return: ["127.0.0.1"]
+		},
+	]
+}
@@ -0,0 +1,44 @@
+package metadata
+
+remap: functions: filter: {
+	category: "Enumerate"
+	description: """
+		Filters the elements of the `value` object or array by running the closure for each of them, and only keeping
+		those for which the closure resolves to `true`.
+
+		The closure binds two variables. For objects, these hold the key and value of each element, for arrays, they
+		hold the index and value of each element.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object or array to filter."
+			required:    true
+			type: ["array", "object"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array", "object"]
+		rules: [
+			"The return type matches the `value` type.",
+		]
+	}
+	examples: [
+		{
+			title: "Filter object"
+			source: #"""
+				filter({ "a": 1, "b": null }) -> |_key, value| { value != null }
+				"""#
+			return: {"a": 1}
+		},
+		{
+			title: "Filter array"
+			source: #"""
+				filter([10, 20, 30]) -> |index, _value| { index != 1 }
+				"""#
+			return: [10, 30]
+		},
+	]
+}
@@ -13,6 +13,19 @@ remap: functions: flatten: {
 			required:    true
 			type: ["array", "object"]
 		},
+		{
+			name:        "separator"
+			description: "The string used to join the keys of nested objects."
+			required:    false
+			default:     "."
+			type: ["string"]
+		},
+		{
+			name:        "max_depth"
+			description: "The maximum number of nesting levels to flatten. Values nested deeper are kept as they are. When omitted, every level is flattened."
+			required:    false
+			type: ["integer"]
+		},
 	]
 	internal_failure_reasons: []
 	return: {
@@ -49,5 +62,25 @@ remap: functions: flatten: {
 				"parent2.child3": 3
 			}
 		},
+		{
+			title: "Flatten object with custom separator"
+			source: #"""
+				flatten({ "parent": { "child": 1 } }, separator: "_")
+				"""#
+			return: {
+				"parent_child": 1
+			}
+		},
+		{
+			title: "Flatten object up to a maximum depth"
+			source: #"""
+				flatten({ "parent": { "child": { "grandchild": 1 } } }, max_depth: 1)
+				"""#
+			return: {
+				"parent.child": {
+					"grandchild": 1
+				}
+			}
+		},
 	]
 }
@@ -0,0 +1,46 @@
+package metadata
+
+remap: functions: get_asn: {
+	category: "IP"
+	description: """
+		Looks up the autonomous system of the IP address `value` in a MaxMind
+		[GeoLite2 ASN](\(urls.maxmind_geolite2_asn)) or [GeoIP2 ISP](\(urls.maxmind_geoip2_isp)) database.
+
+		The ISP database additionally provides the `isp` and `organization` fields, which are `null` when
+		using the ASN database. Returns `null` if the IP address isn't found in the database.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The IP address (v4 or v6) to look up."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "database"
+			description: "The path to the MaxMind ASN or ISP database. The database is loaded when the program is compiled, so the path has to be a literal."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid IP address",
+	]
+	return: types: ["object", "null"]
+
+	examples: [
+		{
+			title: "Look up the autonomous system of an IP address"
+			source: #"""
+				get_asn!("1.128.0.0", database: "/path/to/GeoLite2-ASN.mmdb")
+				"""#
+			return: {
+				autonomous_system_number:       1221
+				autonomous_system_organization: "Telstra Pty Ltd"
+				isp:                            null
+				organization:                   null
+			}
+		},
+	]
+}
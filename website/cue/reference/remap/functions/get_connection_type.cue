@@ -0,0 +1,41 @@
+package metadata
+
+remap: functions: get_connection_type: {
+	category: "IP"
+	description: """
+		Looks up the connection type of the IP address `value` in a MaxMind
+		[GeoIP2 Connection Type](\(urls.maxmind_geoip2_connection_type)) database, one of `Dialup`,
+		`Cable/DSL`, `Corporate`, `Cellular` and `Satellite`.
+
+		Returns `null` if the IP address isn't found in the database.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The IP address (v4 or v6) to look up."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "database"
+			description: "The path to the MaxMind connection type database. The database is loaded when the program is compiled, so the path has to be a literal."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid IP address",
+	]
+	return: types: ["string", "null"]
+
+	examples: [
+		{
+			title: "Look up the connection type of an IP address"
+			source: #"""
+				get_connection_type!("1.0.1.200", database: "/path/to/GeoIP2-Connection-Type.mmdb")
+				"""#
+			return: "Cellular"
+		},
+	]
+}
@@ -0,0 +1,65 @@
+package metadata
+
+remap: functions: hmac: {
+	category:    "Hash"
+	description: """
+		Calculates a [HMAC](\(urls.hmac)) of the `value` using the given `key`.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The string to calculate the HMAC for."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "key"
+			description: "The secret key used to sign the `value`. Keys of any length are accepted."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "algorithm"
+			description: "The hash algorithm used by the HMAC."
+			enum: {
+				"SHA-256": "SHA-256 algorithm"
+				"SHA-512": "SHA-512 algorithm"
+				"BLAKE3":  "BLAKE3 algorithm"
+			}
+			required: false
+			default:  "SHA-256"
+			type: ["string"]
+		},
+		{
+			name:        "encoding"
+			description: "The encoding of the returned code."
+			enum: {
+				hex:    "Lowercase hexadecimal encoding"
+				base64: "Standard base64 encoding, with padding"
+			}
+			required: false
+			default:  "hex"
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Calculate HMAC"
+			source: #"""
+				hmac("The quick brown fox jumps over the lazy dog", "key")
+				"""#
+			return: "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+		},
+		{
+			title: "Calculate base64 encoded HMAC-SHA-512"
+			source: #"""
+				hmac("foo", "secret", algorithm: "SHA-512", encoding: "base64")
+				"""#
+			return: "gt9xA96Ngt5F4BxF/mQrXRPGwrR97K/rwAlDHGZcb6Xz0a9Ol46hvekUJmIgc+vqxho0Ye/UZ+CXHHiLyOvbvg=="
+		},
+	]
+}
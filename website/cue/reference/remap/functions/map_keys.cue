@@ -0,0 +1,46 @@
+package metadata
+
+remap: functions: map_keys: {
+	category: "Enumerate"
+	description: """
+		Maps the keys within the `value` object by running the closure for each of them, and replacing the key with
+		the result of the closure.
+
+		The closure binds a single variable, holding the key being mapped, and must resolve to a string. If two keys
+		map to the same string, the value of the last one is kept.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object to map the keys of."
+			required:    true
+			type: ["object"]
+		},
+		{
+			name:        "recursive"
+			description: "Whether to recursively map the keys of nested objects, including objects nested in arrays."
+			required:    false
+			default:     false
+			type: ["boolean"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["object"]
+	examples: [
+		{
+			title: "Upcase keys"
+			source: #"""
+				map_keys({ "a": 1, "b": { "c": 2 } }) -> |key| { upcase(key) }
+				"""#
+			return: {"A": 1, "B": {"c": 2}}
+		},
+		{
+			title: "Recursively prefix keys"
+			source: #"""
+				map_keys({ "a": 1, "b": [{ "c": 2 }] }, recursive: true) -> |key| { "_" + key }
+				"""#
+			return: {"_a": 1, "_b": [{"_c": 2}]}
+		},
+	]
+}
@@ -0,0 +1,53 @@
+package metadata
+
+remap: functions: map_values: {
+	category: "Enumerate"
+	description: """
+		Maps the values within the `value` object or array by running the closure for each of them, and replacing the
+		value with the result of the closure.
+
+		The closure binds a single variable, holding the value being mapped.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object or array to map the values of."
+			required:    true
+			type: ["array", "object"]
+		},
+		{
+			name:        "recursive"
+			description: """
+				Whether to recursively map the values of nested objects and arrays. When enabled, nested objects and
+				arrays are walked into, rather than passed to the closure.
+				"""
+			required: false
+			default:  false
+			type: ["boolean"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array", "object"]
+		rules: [
+			"The return type matches the `value` type.",
+		]
+	}
+	examples: [
+		{
+			title: "Multiply values"
+			source: #"""
+				map_values({ "a": 1, "b": 2 }) -> |value| { int!(value) * 10 }
+				"""#
+			return: {"a": 10, "b": 20}
+		},
+		{
+			title: "Recursively upcase values"
+			source: #"""
+				map_values({ "a": "foo", "b": ["bar"] }, recursive: true) -> |value| { upcase(to_string(value) ?? "") }
+				"""#
+			return: {"a": "FOO", "b": ["BAR"]}
+		},
+	]
+}
@@ -0,0 +1,37 @@
+package metadata
+
+remap: functions: max: {
+	category: "Number"
+	description: """
+		Returns the largest number in the `value` array.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+	]
+	return: {
+		types: ["integer", "float", "null"]
+		rules: [
+			"Returns the number as is, an integer or a float.",
+			"Returns `null` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Find the largest number"
+			source: #"""
+				max!([3, 1.5, 2])
+				"""#
+			return: 3
+		},
+	]
+}
@@ -0,0 +1,36 @@
+package metadata
+
+remap: functions: mean: {
+	category: "Number"
+	description: """
+		Calculates the arithmetic mean of the numbers in the `value` array.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+	]
+	return: {
+		types: ["float", "null"]
+		rules: [
+			"Returns `null` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Calculate the mean"
+			source: #"""
+				mean!([1, 2, 3, 4])
+				"""#
+			return: 2.5
+		},
+	]
+}
@@ -0,0 +1,44 @@
+package metadata
+
+remap: functions: median: {
+	category: "Number"
+	description: """
+		Calculates the median of the numbers in the `value` array, the mean of the two middle numbers if it has
+		an even length.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+	]
+	return: {
+		types: ["float", "null"]
+		rules: [
+			"Returns `null` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Calculate the median"
+			source: #"""
+				median!([3, 1, 2])
+				"""#
+			return: 2.0
+		},
+		{
+			title: "Calculate the median of an even number of numbers"
+			source: #"""
+				median!([4, 1, 3, 2])
+				"""#
+			return: 2.5
+		},
+	]
+}
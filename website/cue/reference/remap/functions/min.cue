@@ -0,0 +1,37 @@
+package metadata
+
+remap: functions: min: {
+	category: "Number"
+	description: """
+		Returns the smallest number in the `value` array.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+	]
+	return: {
+		types: ["integer", "float", "null"]
+		rules: [
+			"Returns the number as is, an integer or a float.",
+			"Returns `null` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Find the smallest number"
+			source: #"""
+				min!([3, 1.5, 2])
+				"""#
+			return: 1.5
+		},
+	]
+}
@@ -4,7 +4,8 @@ remap: functions: parse_apache_log: {
 	category:    "Parse"
 	description: """
 		Parses Apache access and error log lines. Lines can be in [`common`](\(urls.apache_common)),
-		[`combined`](\(urls.apache_combined)), or default [`error`](\(urls.apache_error)) format.
+		[`combined`](\(urls.apache_combined)), default [`error`](\(urls.apache_error)) format, or in a `custom`
+		format given by the `log_format`.
 		"""
 	notices: [
 		"""
@@ -24,6 +25,9 @@ remap: functions: parse_apache_log: {
 			description: """
 				The [date/time format](https://docs.rs/chrono/latest/chrono/format/strftime/index.html) to use for
 				encoding the timestamp. The time is parsed in local time if the timestamp doesn't specify a timezone.
+				Error logs are parsed with `%a %b %d %H:%M:%S%.f %Y`, the default of Apache 2.4, if their timestamp
+				doesn't match the default format. Custom formats default to the format given by their `%{format}t`
+				directive, if any.
 				"""
 			required: false
 			default:  "%d/%b/%Y:%T %z"
@@ -37,15 +41,33 @@ remap: functions: parse_apache_log: {
 				"common":   "Common format"
 				"combined": "Apache combined format"
 				"error":    "Default Apache error format"
+				"custom":   "The format given by the `log_format`"
 			}
 			type: ["string"]
 		},
+		{
+			name: "log_format"
+			description: """
+				The Apache [`LogFormat`](\(urls.apache_formats)) of the lines, required by and only allowed with the
+				`custom` format. It must be a static expression.
+
+				Every directive produces a field typed after it: for example `%>s` produces the `status` integer,
+				`%D` the `duration_us` integer, and `%t` the `timestamp`. `%r` produces the `message`, split into
+				its `method`, `path` and `protocol` like the predefined formats. Headers, environment variables,
+				notes and cookies produce a string field named after them in lowercase, with dashes replaced by
+				underscores, except for the `Referer` and `User-agent` headers which produce the `referrer` and
+				`agent` of the combined format.
+				"""
+			required: false
+			type: ["string"]
+		},
 	]
 
 	internal_failure_reasons: [
 		"`value` doesn't match the specified format",
 		"`timestamp_format` isn't a valid format string",
 		"The timestamp in `value` fails to parse using the provided `timestamp_format`",
+		"A field of `value` doesn't match the type of the directive of the `log_format` producing it",
 	]
 	return: types: ["object"]
 
@@ -110,5 +132,44 @@ remap: functions: parse_apache_log: {
 				timestamp: "2021-03-01T12:00:19Z"
 			}
 		},
+		{
+			title: "Parse via Apache log format (error, Apache 2.4)"
+			source: #"""
+				parse_apache_log!(
+					s'[Wed Oct 11 14:32:52.123456 2000] [core:error] [pid 35708:tid 4328636416] [client 72.15.99.187] AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico',
+					"error"
+				)
+				"""#
+			return: {
+				client:    "72.15.99.187"
+				message:   "AH00128: File does not exist: /usr/local/apache2/htdocs/favicon.ico"
+				module:    "core"
+				pid:       35708
+				severity:  "error"
+				thread:    "4328636416"
+				timestamp: "2000-10-11T14:32:52.123456Z"
+			}
+		},
+		{
+			title: "Parse via custom Apache log format"
+			source: #"""
+				parse_apache_log!(
+					s'127.0.0.1 [10/Oct/2000:13:55:36 -0700] "GET /apache_pb.gif HTTP/1.0" 200 2326 1500',
+					"custom",
+					log_format: s'%a %t "%r" %>s %b %D'
+				)
+				"""#
+			return: {
+				client:      "127.0.0.1"
+				timestamp:   "2000-10-10T20:55:36Z"
+				message:     "GET /apache_pb.gif HTTP/1.0"
+				method:      "GET"
+				path:        "/apache_pb.gif"
+				protocol:    "HTTP/1.0"
+				status:      200
+				size:        2326
+				duration_us: 1500
+			}
+		},
 	]
 }
@@ -3,11 +3,13 @@ package metadata
 remap: functions: parse_csv: {
 	category: "Parse"
 	description: #"""
-		Parses a single CSV formatted row. Only the first row is parsed in case of multiline input value.
+		Parses a single CSV formatted row. Only the first row is parsed in case of multiline input value,
+		or the row following the header row when `headers` is `true`.
 		"""#
 	notices: [
 		"""
-			All values are returned as strings. We recommend manually coercing values to desired types as you see fit.
+			All values are returned as strings unless `coerce_numbers` is `true`. We recommend manually coercing values
+			to desired types as you see fit.
 			""",
 	]
 
@@ -25,12 +27,43 @@ remap: functions: parse_csv: {
 			default:     ","
 			type: ["string"]
 		},
+		{
+			name:        "quote"
+			description: "The character used to quote fields. Must be a single-byte utf8 character, or empty to disable quoting."
+			required:    false
+			default:     "\""
+			type: ["string"]
+		},
+		{
+			name:        "headers"
+			description: """
+				The names of the fields, or `true` to read them from the first row. When set, the fields are
+				returned as an object keyed by these names instead of as an array.
+				"""
+			required:    false
+			type: ["array", "boolean"]
+		},
+		{
+			name:        "coerce_numbers"
+			description: "Whether to return fields holding an integer or a float as such, instead of as strings."
+			required:    false
+			default:     false
+			type: ["boolean"]
+		},
 	]
 	internal_failure_reasons: [
 		"delimiter must be a single-byte utf8 character",
+		"quote must be a single-byte utf8 character or empty",
+		"`headers` contains a value that isn't a string",
+		"the row doesn't have as many fields as there are headers",
 		"`value` isn't a valid CSV string",
 	]
-	return: types: ["array"]
+	return: {
+		types: ["array", "object"]
+		rules: [
+			"An object is returned when `headers` is set, an array otherwise.",
+		]
+	}
 
 	examples: [
 		{
@@ -47,5 +80,25 @@ remap: functions: parse_csv: {
 				"""#
 			return: ["foo", "bar"]
 		},
+		{
+			title: "Parse a single CSV formatted row with supplied headers"
+			source: #"""
+				parse_csv!("foo,1", headers: ["name", "count"], coerce_numbers: true)
+				"""#
+			return: {
+				"name":  "foo"
+				"count": 1
+			}
+		},
+		{
+			title: "Parse a single CSV formatted row with a header row"
+			source: #"""
+				parse_csv!("name,count\nfoo,1", headers: true)
+				"""#
+			return: {
+				"name":  "foo"
+				"count": "1"
+			}
+		},
 	]
 }
@@ -7,6 +7,7 @@ remap: functions: parse_key_value: {
 
 		* Keys and values can be wrapped with `"`.
 		* `"` characters can be escaped using `\\`.
+		* Values can optionally be wrapped with `[` and `]`, see `accept_bracketed_values`.
 		"""
 	notices: [
 		"""
@@ -53,6 +54,25 @@ remap: functions: parse_key_value: {
 			type: ["boolean"]
 			default: true
 		},
+		{
+			name:        "accept_bracketed_values"
+			description: "Whether values wrapped in `[` and `]` should be parsed as a single value, with the brackets removed. Brackets can be nested, and brackets inside `\"` quotes are ignored."
+			required:    false
+			type: ["boolean"]
+			default: false
+		},
+		{
+			name:        "duplicate_keys"
+			description: "Defines how a key that appears more than once is handled."
+			required:    false
+			enum: {
+				first: "Keep the first value."
+				last:  "Keep the last value."
+				array: "Collect all values into an array, in the order they appear."
+			}
+			default: "last"
+			type: ["string"]
+		},
 	]
 	internal_failure_reasons: [
 		"`value` isn't a properly formatted key/value string",
@@ -113,5 +133,19 @@ remap: functions: parse_key_value: {
 				beta:    true
 			}
 		},
+		{
+			title: "Parse log with bracketed values and duplicate keys"
+			source: #"""
+				parse_key_value!(
+					"time=[10/Oct/2000:13:55:36 -0700] srcip=10.0.0.1 srcip=10.0.0.2",
+					accept_bracketed_values: true,
+					duplicate_keys: "array"
+				)
+				"""#
+			return: {
+				time:  "10/Oct/2000:13:55:36 -0700"
+				srcip: ["10.0.0.1", "10.0.0.2"]
+			}
+		},
 	]
 }
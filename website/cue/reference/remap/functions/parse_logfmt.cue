@@ -6,9 +6,11 @@ remap: functions: parse_logfmt: {
 		Parses the `value` in [logfmt](\(urls.logfmt)).
 
 		* Keys and values can be wrapped using the `\"` character.
-		* `\"` characters can be escaped by the `\\` character.
+		* `\"` characters can be escaped by the `\\` character. Escape sequences in wrapped values are
+		  unescaped, so `\\"`, `\\\\`, `\\n`, `\\r`, and `\\t` become the characters they stand for.
 		* As per this [logfmt specification](\(urls.logfmt_specs)), the `parse_logfmt` function
 		  accepts standalone keys and assigns them a Boolean value of `true`.
+		* Unwrapped `true` and `false` values are parsed as Booleans.
 		"""
 	notices:     functions.encode_key_value.notices
 
@@ -19,6 +21,18 @@ remap: functions: parse_logfmt: {
 			required:    true
 			type: ["string"]
 		},
+		{
+			name:        "duplicate_keys"
+			description: "Defines how a key that appears more than once is handled."
+			required:    false
+			enum: {
+				first: "Keep the first value."
+				last:  "Keep the last value."
+				array: "Collect all values into an array, in the order they appear."
+			}
+			default: "last"
+			type: ["string"]
+		},
 	]
 	internal_failure_reasons: [
 		"`value` isn't a properly formatted key/value string",
@@ -42,5 +56,19 @@ remap: functions: parse_logfmt: {
 				module:           "kafka.consumer.ConsumerFetcherManager"
 			}
 		},
+		{
+			title: "Parse logfmt log with escaped quotes, Booleans, and duplicate keys"
+			source: #"""
+				parse_logfmt!(
+					s'msg="say \"hi\"" debug=true tag=a tag=b',
+					duplicate_keys: "array"
+				)
+				"""#
+			return: {
+				msg:   #"say "hi""#
+				debug: true
+				tag: ["a", "b"]
+			}
+		},
 	]
 }
@@ -3,7 +3,7 @@ package metadata
 remap: functions: parse_nginx_log: {
 	category:    "Parse"
 	description: """
-        Parses Nginx access and error log lines. Lines can be in [`combined`](\(urls.nginx_combined)), or [`error`](\(urls.nginx_error)) format.
+        Parses Nginx access and error log lines. Lines can be in [`combined`](\(urls.nginx_combined)), [`error`](\(urls.nginx_error)) format, or in a `custom` format given by the `log_format`.
         """
 	notices: [
 		"""
@@ -24,7 +24,8 @@ remap: functions: parse_nginx_log: {
 
 				The [date/time format](\(urls.chrono_time_formats)) to use for encoding the timestamp. The time is parsed
 				in local time if the timestamp doesn't specify a timezone. The default format is `%d/%b/%Y:%T %z` for
-				combined logs and `%Y/%m/%d %H:%M:%S` for error logs.
+				combined logs and `%Y/%m/%d %H:%M:%S` for error logs. Custom formats default to the format of their
+				`$time_local` or `$time_iso8601` variable.
 				"""
 			required:    false
 			default:     "%d/%b/%Y:%T %z"
@@ -37,15 +38,31 @@ remap: functions: parse_nginx_log: {
 			enum: {
 				"combined": "Nginx combined format"
 				"error":    "Default Nginx error format"
+				"custom":   "The format given by the `log_format`"
 			}
 			type: ["string"]
 		},
+		{
+			name:        "log_format"
+			description: """
+				The Nginx [`log_format`](\(urls.nginx_log_format)) of the lines, required by and only allowed with the
+				`custom` format. It must be a static expression.
+
+				The variables of the combined format produce the same fields as it, for example `$remote_addr`
+				produces the `client` and `$body_bytes_sent` the `size`. All other variables produce a field named
+				after them, typed after the variable: for example `$request_time` produces a float and
+				`$connection` an integer.
+				"""
+			required:    false
+			type: ["string"]
+		},
 	]
 
 	internal_failure_reasons: [
 		"`value` doesn't match the specified format",
 		"`timestamp_format` isn't a valid format string",
 		"The timestamp in `value` fails to parse using the provided `timestamp_format`",
+		"A field of `value` doesn't match the type of the variable of the `log_format` producing it",
 	]
 	return: types: ["object"]
 
@@ -94,5 +111,27 @@ remap: functions: parse_nginx_log: {
 				host:      "localhost:8081"
 			}
 		},
+		{
+			title: "Parse via custom Nginx log format"
+			source: #"""
+				parse_nginx_log!(
+				    s'172.17.0.1 [31/Mar/2021:12:04:07 +0000] "GET / HTTP/1.1" 200 612 0.005 "10.0.0.1"',
+				    "custom",
+				    log_format: s'$remote_addr [$time_local] "$request" $status $body_bytes_sent $request_time "$http_x_forwarded_for"'
+				)
+				"""#
+			return: {
+				client:               "172.17.0.1"
+				timestamp:            "2021-03-31T12:04:07Z"
+				request:              "GET / HTTP/1.1"
+				method:               "GET"
+				path:                 "/"
+				protocol:             "HTTP/1.1"
+				status:               200
+				size:                 612
+				request_time:         0.005
+				http_x_forwarded_for: "10.0.0.1"
+			}
+		},
 	]
 }
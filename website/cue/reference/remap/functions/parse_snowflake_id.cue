@@ -0,0 +1,55 @@
+package metadata
+
+remap: functions: parse_snowflake_id: {
+	category:    "Parse"
+	description: """
+		Parses the `value` as a [Snowflake ID](\(urls.snowflake_id)), as generated by Twitter, Discord and
+		others, into its creation timestamp, machine ID and sequence number.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The Snowflake ID, as a string or an integer."
+			required:    true
+			type: ["string", "integer"]
+		},
+		{
+			name:        "epoch"
+			description: "The Unix timestamp, in milliseconds, the timestamps of the IDs are relative to. Defaults to Twitter's epoch; Discord uses `1420070400000`."
+			required:    false
+			default:     1288834974657
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a non-negative integer",
+		"the timestamp of `value` is out of range",
+	]
+	return: types: ["object"]
+
+	examples: [
+		{
+			title: "Parse Twitter Snowflake ID"
+			source: #"""
+				parse_snowflake_id!("1382350606417817604")
+				"""#
+			return: {
+				timestamp:  "2021-04-14T15:10:42.059Z"
+				machine_id: 327
+				sequence:   4
+			}
+		},
+		{
+			title: "Parse Discord Snowflake ID"
+			source: #"""
+				parse_snowflake_id!("175928847299117063", epoch: 1420070400000)
+				"""#
+			return: {
+				timestamp:  "2016-04-30T11:18:25.796Z"
+				machine_id: 32
+				sequence:   7
+			}
+		},
+	]
+}
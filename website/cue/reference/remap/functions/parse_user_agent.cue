@@ -10,6 +10,11 @@ remap: functions: parse_user_agent: {
 		"All values are returned as strings or as null. We recommend manually coercing values to desired types as you see fit.",
 		"Different modes return different schema.",
 		"Field which were not parsed out are set as `null`.",
+		"""
+			Whenever the [uap project](\(urls.uap)) parser is used, the device `category` is refined using the
+			device it detects. This adds the `tablet` category and detects crawlers that [Woothee](\(urls.woothee))
+			doesn't recognize.
+			""",
 	]
 
 	arguments: [
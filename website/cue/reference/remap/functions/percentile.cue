@@ -0,0 +1,44 @@
+package metadata
+
+remap: functions: percentile: {
+	category: "Number"
+	description: """
+		Calculates the given `percentile` of the numbers in the `value` array, interpolating linearly between
+		the two closest numbers.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+		{
+			name:        "percentile"
+			description: "The percentile to calculate, from `0` to `100`."
+			required:    true
+			type: ["integer", "float"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+		"`percentile` isn't between `0` and `100`",
+	]
+	return: {
+		types: ["float", "null"]
+		rules: [
+			"Returns `null` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Calculate the 95th percentile"
+			source: #"""
+				percentile!([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11], 95)
+				"""#
+			return: 10.5
+		},
+	]
+}
@@ -0,0 +1,69 @@
+package metadata
+
+remap: functions: query: {
+	category: "Enumerate"
+	description: #"""
+		Queries the `value` with a [jq](\(urls.jq))-like `expression`, returning an array of every value
+		matched. Unlike paths, queries can match any number of values, which is useful for extracting values
+		from deeply nested or dynamically shaped data.
+
+		The expression is made up of the following segments:
+
+		* `.foo` or `."foo bar"` or `["foo bar"]` matches the field of an object.
+		* `[n]` matches the `n`th element of an array, counting from the end if `n` is negative.
+		* `[start:end]` matches an array of the elements from `start` up to, but not including, `end`.
+		  Either may be left out or negative.
+		* `[]` matches every element of an array or every value of an object.
+		* `..` matches the value itself and every value nested in it, so that `..foo` matches the `foo`
+		  field at any depth.
+
+		A query of `.` matches the value itself.
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The value to query."
+			required:    true
+			type: ["any"]
+		},
+		{
+			name:        "expression"
+			description: "The query expression. Must be a string literal."
+			required:    true
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["array"]
+		rules: [
+			"Values that don't have the field or element being queried, or aren't an object or array, are skipped rather than causing an error.",
+			"Values are returned in the order they're encountered, with object fields in alphabetical order.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Query every element of an array"
+			source: #"""
+				query({ "users": [{ "name": "foo" }, { "id": 1 }, { "name": "bar" }] }, ".users[].name")
+				"""#
+			return: ["foo", "bar"]
+		},
+		{
+			title: "Query fields at any depth"
+			source: #"""
+				query({ "a": { "id": 1, "b": [{ "id": 2 }] } }, "..id")
+				"""#
+			return: [1, 2]
+		},
+		{
+			title: "Query a slice of an array"
+			source: #"""
+				query({ "items": [1, 2, 3, 4] }, ".items[-2:]")
+				"""#
+			return: [[3, 4]]
+		},
+	]
+}
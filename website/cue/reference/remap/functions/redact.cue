@@ -6,8 +6,11 @@ remap: functions: redact: {
 		Redact sensitive data in `value` such as:
 
 		- [US social security card numbers](\(urls.us_social_security_number))
+		- credit card numbers
+		- email addresses
+		- IP addresses
+		- API keys and access tokens
 		- and other forms of personally identifiable information via custom patterns
-		- (more to come!)
 
 		This can help achieve compliance by ensuring sensitive data never leaves your network.
 		"""
@@ -27,7 +30,7 @@ remap: functions: redact: {
 				For arrays and objects it will recurse into any nested arrays or objects. Any non-string elements will
 				be skipped.
 
-				Any redacted text will be replaced with `[REDACTED]`.
+				How redacted text is replaced is controlled by the `redactor`.
 				"""#
 			required: true
 			type: ["string", "object", "array"]
@@ -48,15 +51,37 @@ remap: functions: redact: {
 				- `pattern`: Redact text matching any regular expressions specified in the, required, `patterns`
 					key. This is the expanded form of just passing a regular expression as a filter.
 				- `us_social_security_number`: Redact US social security card numbers.
+				- `credit_card_number`: Redact credit card numbers of 13 to 19 digits, optionally grouped by spaces or
+					dashes. Only numbers passing the [Luhn check](\(urls.luhn_algorithm)) are redacted.
+				- `email_address`: Redact email addresses.
+				- `ip_address`: Redact IPv4 and IPv6 addresses.
+				- `api_key`: Redact well-known API keys and access tokens: AWS access key IDs, GitHub tokens, Slack
+					tokens, Stripe secret and restricted keys, and Google API keys.
 
 				See examples for more details.
 
 				This parameter must be a static expression. You cannot use variables or other dynamic expressions
 				with it. This allows us to validate the argument at compile-time to avoid runtime errors.
 				"""#
-			required: false
+			required: true
 			type: ["array"]
 		},
+		{
+			name: "redactor"
+			description: #"""
+				How to replace redacted text:
+
+				- `full`: Replace it with `[REDACTED]`.
+				- `mask`: Replace every character of it with `*`, keeping its length.
+				- `hash`: Replace it with its hex encoded SHA-256 digest, so redacted values can still be correlated.
+				- `remove`: Remove it.
+
+				This parameter must be a static expression.
+				"""#
+			required: false
+			default:  "full"
+			type: ["string"]
+		},
 	]
 	internal_failure_reasons: []
 	return: types: ["string", "object", "array"]
@@ -79,5 +104,26 @@ remap: functions: redact: {
 				ssn:  "[REDACTED]"
 			}
 		},
+		{
+			title: "Replace credit card numbers"
+			source: #"""
+				redact("card 4111 1111 1111 1111 expires 12/24", filters: ["credit_card_number"])
+				"""#
+			return: "card [REDACTED] expires 12/24"
+		},
+		{
+			title: "Mask email addresses"
+			source: #"""
+				redact("contact jane@example.com", filters: ["email_address"], redactor: "mask")
+				"""#
+			return: "contact ****************"
+		},
+		{
+			title: "Hash IP addresses"
+			source: #"""
+				redact("from 10.0.0.1", filters: ["ip_address"], redactor: "hash")
+				"""#
+			return: "from f5047344122f0dee9974ba6761e61c6b8649e1f3968d13a635ebbf7be53a3a0d"
+		},
 	]
 }
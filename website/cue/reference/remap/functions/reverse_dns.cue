@@ -0,0 +1,48 @@
+package metadata
+
+remap: functions: reverse_dns: {
+	category: "IP"
+	description: """
+		Resolves the `value` IP address into the hostname it's registered under, using the system resolver.
+		"""
+	notices: [
+		"""
+			Successful lookups are cached for 60 seconds and shared with `dns_lookup`, so repeated lookups of the same
+			address don't hit the resolver for every event. Failed lookups aren't cached.
+			""",
+	]
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The IP address (v4 or v6) to resolve."
+			required:    true
+			type: ["string"]
+		},
+		{
+			name:        "timeout"
+			description: "The number of seconds to wait for the resolver before giving up."
+			required:    false
+			default:     1
+			type: ["integer", "float"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` isn't a valid IP address.",
+		"`value` couldn't be resolved.",
+		"The resolution didn't complete within `timeout`.",
+		"`timeout` isn't a positive number of at most 60 seconds.",
+		"Too many resolutions are already waiting for the resolver.",
+	]
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Resolve an IP address"
+			source: #"""
+				reverse_dns!("127.0.0.1")
+				"""#
+			return: "localhost"
+		},
+	]
+}
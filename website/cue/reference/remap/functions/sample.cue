@@ -0,0 +1,61 @@
+package metadata
+
+remap: functions: sample: {
+	category:    "Random"
+	description: """
+		Decides whether an event belongs to a sample of the given `rate`.
+
+		Without a `key`, the decision is random. With a `key`, such as a trace ID, the key is
+		hashed with the `seed` using [Seahash](https://docs.rs/seahash), so that the same decision
+		is made for every event sharing the key, across restarts and across instances using the
+		same seed. This is the same decision as [`consistent_sample`](#consistent_sample) makes.
+		"""
+
+	arguments: [
+		{
+			name:        "rate"
+			description: "The fraction of events to sample, between `0` and `1`. Must be a literal."
+			required:    true
+			type: ["float", "integer"]
+		},
+		{
+			name:        "key"
+			description: "The value to hash, such as a trace ID. Values that aren't strings are hashed by their string representation."
+			required:    false
+			type: ["any"]
+		},
+		{
+			name:        "seed"
+			description: "The seed to hash the `key` with. Must be a literal, and requires a `key`."
+			required:    false
+			default:     0
+			type: ["integer"]
+		},
+	]
+	internal_failure_reasons: []
+	return: types: ["boolean"]
+
+	examples: [
+		{
+			title: "Sample everything"
+			source: #"""
+				sample(1)
+				"""#
+			return: true
+		},
+		{
+			title: "Sample nothing"
+			source: #"""
+				sample(0)
+				"""#
+			return: false
+		},
+		{
+			title: "Sample by trace ID"
+			source: #"""
+				sample(0.5, "4bf92f3577b34da6", seed: 1)
+				"""#
+			return: true
+		},
+	]
+}
@@ -0,0 +1,49 @@
+package metadata
+
+remap: functions: saturating_mul: {
+	category: "Number"
+	description: """
+		Multiplies `value` by `other`, clamping the result to the largest or smallest representable number
+		if it overflows rather than silently wrapping around.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The number to multiply."
+			required:    true
+			type: ["integer", "float"]
+		},
+		{
+			name:        "other"
+			description: "The number to multiply by."
+			required:    true
+			type: ["integer", "float"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["integer", "float"]
+		rules: [
+			"If both `value` and `other` are integers, an integer is returned, clamped to the range of a 64-bit signed integer.",
+			"Otherwise, a float is returned, clamped to the range of a 64-bit float.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Multiply integers"
+			source: #"""
+				saturating_mul(3, 4)
+				"""#
+			return: 12
+		},
+		{
+			title: "Multiply integers that overflow"
+			source: #"""
+				saturating_mul(9223372036854775807, 2)
+				"""#
+			return: 9223372036854775807
+		},
+	]
+}
@@ -3,7 +3,9 @@ package metadata
 remap: functions: strip_ansi_escape_codes: {
 	category:    "String"
 	description: """
-		Strips [ANSI escape codes](\(urls.ansi_escape_codes)) from the `value`.
+		Strips [ANSI escape codes](\(urls.ansi_escape_codes)) from the `value`, including
+		operating system commands such as hyperlinks and window titles, and sequences started
+		by C1 control codes such as `\u{9b}`. Tabs, carriage returns and newlines are kept.
 		"""
 
 	arguments: [
@@ -25,5 +27,12 @@ remap: functions: strip_ansi_escape_codes: {
 				"""#
 			return: "foo bar"
 		},
+		{
+			title: "Strip hyperlinks"
+			source: #"""
+				strip_ansi_escape_codes("\e]8;;https://vector.dev\e\\foo\e]8;;\e\\\tbar")
+				"""#
+			return: "foo\tbar"
+		},
 	]
 }
@@ -0,0 +1,45 @@
+package metadata
+
+remap: functions: sum: {
+	category: "Number"
+	description: """
+		Sums the numbers in the `value` array.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The array of numbers."
+			required:    true
+			type: ["array"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` contains an element that isn't an integer or a float",
+		"the sum of the integers in `value` overflows an integer",
+	]
+	return: {
+		types: ["integer", "float"]
+		rules: [
+			"Returns an integer if all of the numbers are integers, and a float otherwise.",
+			"Returns `0` if `value` is empty.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Sum numbers"
+			source: #"""
+				sum!([1, 2, 3])
+				"""#
+			return: 6
+		},
+		{
+			title: "Sum integers and floats"
+			source: #"""
+				sum!([1, 2.5])
+				"""#
+			return: 3.5
+		},
+	]
+}
@@ -0,0 +1,49 @@
+package metadata
+
+remap: functions: to_bigint: {
+	category: "Coerce"
+	description: """
+		Converts the `value` into an arbitrary-precision integer, represented as a string of its decimal
+		digits. Unlike [`to_int`](#to_int), integers out of the range of a 64-bit signed integer, such as
+		unsigned 64-bit counters, keep their precision.
+		"""
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The value to convert to an arbitrary-precision integer."
+			required:    true
+			type: ["integer", "float", "string"]
+		},
+	]
+	internal_failure_reasons: [
+		"`value` is a string but the text is not an integer.",
+		"`value` is an infinite float.",
+	]
+	return: {
+		types: ["string"]
+		rules: [
+			"If `value` is an integer, its decimal representation is returned.",
+			"If `value` is a float, it is truncated to its integer portion.",
+			"If `value` is a string, it must be the string representation of an integer, optionally signed and with leading zeros, or else an error is raised.",
+			"The returned string has no leading zeros and is only signed if negative.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Convert a string"
+			source: #"""
+				to_bigint!("+0018446744073709551616")
+				"""#
+			return: "18446744073709551616"
+		},
+		{
+			title: "Convert an integer"
+			source: #"""
+				to_bigint(-5)
+				"""#
+			return: "-5"
+		},
+	]
+}
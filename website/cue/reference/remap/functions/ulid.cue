@@ -0,0 +1,23 @@
+package metadata
+
+remap: functions: ulid: {
+	category:    "Random"
+	description: """
+		Generates a random [ULID](\(urls.ulid)) string. ULIDs start with the current Unix timestamp in
+		milliseconds, so they sort by creation time, and are encoded in 26 characters of Crockford's base32.
+		"""
+
+	arguments: []
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Create a ULID"
+			source: #"""
+				ulid()
+				"""#
+			return: "01FZ8Q0K4W3X5VJ6N2C7B9D1EA"
+		},
+	]
+}
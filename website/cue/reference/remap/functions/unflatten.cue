@@ -0,0 +1,66 @@
+package metadata
+
+remap: functions: unflatten: {
+	category: "Enumerate"
+	description: #"""
+		Unflattens the `value` into a nested representation, splitting each key on the `separator`. This is
+		the inverse of [`flatten`](#flatten).
+		"""#
+
+	arguments: [
+		{
+			name:        "value"
+			description: "The object to unflatten."
+			required:    true
+			type: ["object"]
+		},
+		{
+			name:        "separator"
+			description: "The string separating the keys of nested objects."
+			required:    false
+			default:     "."
+			type: ["string"]
+		},
+	]
+	internal_failure_reasons: []
+	return: {
+		types: ["object"]
+		rules: [
+			"Keys of nested objects are unflattened as well.",
+			"When a key is both a value and the parent of other keys, such as `a` and `a.b`, the nested keys take precedence.",
+		]
+	}
+
+	examples: [
+		{
+			title: "Unflatten object"
+			source: #"""
+				unflatten({
+					"parent1.child1": 1,
+					"parent1.child2": 2,
+					"parent2.child3": 3
+				})
+				"""#
+			return: {
+				"parent1": {
+					"child1": 1
+					"child2": 2
+				}
+				"parent2": {
+					"child3": 3
+				}
+			}
+		},
+		{
+			title: "Unflatten object with custom separator"
+			source: #"""
+				unflatten({ "parent_child": 1 }, separator: "_")
+				"""#
+			return: {
+				"parent": {
+					"child": 1
+				}
+			}
+		},
+	]
+}
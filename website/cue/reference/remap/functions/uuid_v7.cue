@@ -0,0 +1,23 @@
+package metadata
+
+remap: functions: uuid_v7: {
+	category:    "Random"
+	description: """
+		Generates a random [UUIDv7](\(urls.uuidv7)) string. Unlike UUIDv4, UUIDv7 strings start with the current
+		Unix timestamp in milliseconds, so they sort by creation time.
+		"""
+
+	arguments: []
+	internal_failure_reasons: []
+	return: types: ["string"]
+
+	examples: [
+		{
+			title: "Create a UUIDv7"
+			source: #"""
+				uuid_v7()
+				"""#
+			return: "017f22e2-79b0-7cc3-98c4-dc0c0c07398f"
+		},
+	]
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: aws_security_lake: {
+	name:     "Amazon Security Lake"
+	thing:    "an \(name) custom source"
+	url:      urls.aws_security_lake
+	versions: null
+
+	description: "[Amazon Security Lake](\(urls.aws_security_lake)) centralizes security data from AWS, from third parties and from custom sources in a data lake stored in S3, normalized to the [Open Cybersecurity Schema Framework (OCSF)](\(urls.ocsf)) so that it can be queried and analyzed as a whole."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: azure_data_explorer: {
+	name:     "Azure Data Explorer"
+	thing:    "an \(name) cluster"
+	url:      urls.azure_data_explorer
+	versions: null
+
+	description: "[Azure Data Explorer](\(urls.azure_data_explorer)) is a fast, fully managed data analytics service for real-time analysis on large volumes of data streaming from applications, websites, IoT devices, and more, queried with the Kusto Query Language (KQL)."
+}
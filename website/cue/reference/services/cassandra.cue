@@ -0,0 +1,10 @@
+package metadata
+
+services: cassandra: {
+	name:     "Apache Cassandra"
+	thing:    "an \(name) or ScyllaDB cluster"
+	url:      urls.cassandra
+	versions: ">= 3.0"
+
+	description: "[Apache Cassandra](\(urls.cassandra)) is an open source wide-column store, designed to handle large amounts of data across many servers with no single point of failure. [ScyllaDB](\(urls.scylladb)) is a compatible reimplementation of it."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: datadog_traces: {
+	name:     "Datadog traces"
+	thing:    "a \(name) account"
+	url:      urls.datadog_traces
+	versions: null
+
+	description: services._datadog.description
+}
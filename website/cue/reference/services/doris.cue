@@ -0,0 +1,10 @@
+package metadata
+
+services: doris: {
+	name:     "Apache Doris"
+	thing:    "an \(name) or StarRocks database"
+	url:      urls.doris
+	versions: null
+
+	description: "[Apache Doris](\(urls.doris)) and its fork [StarRocks](\(urls.starrocks)) are MPP analytical databases, which load data over HTTP with Stream Load and serve real-time queries on it."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: grpc: {
+	name:     "gRPC"
+	thing:    "a \(name) server"
+	url:      urls.grpc
+	versions: null
+
+	description: "[gRPC](\(urls.grpc)) is a high performance RPC framework, whose services and messages are described with [Protocol Buffers](\(urls.protobuf))."
+}
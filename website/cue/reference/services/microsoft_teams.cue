@@ -0,0 +1,10 @@
+package metadata
+
+services: microsoft_teams: {
+	name:     "Microsoft Teams"
+	thing:    "a \(name) channel"
+	url:      urls.microsoft_teams
+	versions: null
+
+	description: "[Microsoft Teams](\(urls.microsoft_teams)) is a messaging platform by Microsoft where teams discuss in channels."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: opsgenie: {
+	name:     "Opsgenie"
+	thing:    "an \(name) account"
+	url:      urls.opsgenie
+	versions: null
+
+	description: "[Opsgenie](\(urls.opsgenie)) is an alerting and incident management service by Atlassian that notifies on-call teams of alerts."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: pagerduty: {
+	name:     "PagerDuty"
+	thing:    "a \(name) service"
+	url:      urls.pagerduty
+	versions: null
+
+	description: "[PagerDuty](\(urls.pagerduty)) is an incident response platform that pages on-call responders when alerts are triggered."
+}
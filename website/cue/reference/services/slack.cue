@@ -0,0 +1,10 @@
+package metadata
+
+services: slack: {
+	name:     "Slack"
+	thing:    "a \(name) workspace"
+	url:      urls.slack
+	versions: null
+
+	description: "[Slack](\(urls.slack)) is a messaging platform where teams discuss in channels, commonly used for operational notifications."
+}
@@ -0,0 +1,10 @@
+package metadata
+
+services: smtp: {
+	name:     "SMTP"
+	thing:    "an \(name) server"
+	url:      urls.smtp
+	versions: null
+
+	description: "[SMTP](\(urls.smtp)) is the protocol mail servers use to send and relay emails."
+}
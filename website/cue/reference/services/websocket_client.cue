@@ -0,0 +1,8 @@
+package metadata
+
+services: websocket_client: {
+	name:     "WebSocket client"
+	thing:    "a \(name)"
+	url:      urls.websocket
+	versions: null
+}
@@ -7,12 +7,16 @@ urls: {
 	amazon_linux:                                             "https://aws.amazon.com/amazon-linux-ami/"
 	ansi_escape_codes:                                        "\(wikipedia)/wiki/ANSI_escape_code"
 	apache:                                                   "https://httpd.apache.org"
+	apache_avro:                                              "https://avro.apache.org"
 	apache_common:                                            "\(apache)/docs/current/logs.html#common"
 	apache_combined:                                          "\(apache)/docs/current/logs.html#combined"
 	apache_error:                                             "\(apache)/docs/current/logs.html#errorlog"
 	apache_extended_status:                                   "\(apache)/docs/current/mod/core.html#extendedstatus"
+	apache_formats:                                           "\(apache)/docs/current/mod/mod_log_config.html#formats"
+	apache_iceberg:                                           "https://iceberg.apache.org"
 	apache_install:                                           "\(apache)/docs/current/install.html"
 	apache_mod_status:                                        "http://httpd.apache.org/docs/current/mod/mod_status.html"
+	apache_parquet:                                           "https://parquet.apache.org"
 	apt:                                                      "\(wikipedia)/wiki/APT_(software)"
 	arm:                                                      "\(wikipedia)/wiki/ARM_architecture"
 	aws_access_keys:                                          "\(aws_docs)/IAM/latest/UserGuide/id_credentials_access-keys.html"
@@ -47,9 +51,11 @@ urls: {
 	aws_elb:                                                  "https://aws.amazon.com/elasticloadbalancing/"
 	aws_elb_access_format:                                    "\(aws_docs)/elasticloadbalancing/latest/application/load-balancer-access-logs.html#access-log-entry-examples"
 	aws_elb_https:                                            "\(aws_docs)/elasticloadbalancing/latest/classic/elb-create-https-ssl-load-balancer.html"
+	aws_glue_iceberg_rest:                                    "\(aws_docs)/glue/latest/dg/connect-glu-iceberg-rest.html"
 	aws_iam:                                                  "\(aws_docs)/IAM/latest/UserGuide/introduction.html"
 	aws_iam_role:                                             "\(aws_docs)/IAM/latest/UserGuide/id_roles.html"
 	aws_imds_v1_security_problems:                            "https://aws.amazon.com/blogs/security/defense-in-depth-open-firewalls-reverse-proxies-ssrf-vulnerabilities-ec2-instance-metadata-service/"
+	aws_kinesis_enhanced_fan_out:                             "\(aws_docs)/streams/latest/dev/enhanced-consumers.html"
 	aws_kinesis_firehose:                                     "https://aws.amazon.com/kinesis/data-firehose/"
 	aws_kinesis_firehose_http_protocol:                       "\(aws_docs)/firehose/latest/dev/create-destination.html#create-destination-http"
 	aws_firehose_http_request_spec:                           "\(aws_docs)/firehose/latest/dev/httpdeliveryrequestresponse.html"
@@ -61,6 +67,7 @@ urls: {
 	aws_kinesis_streams_api:                                  "\(aws_docs)/kinesis/latest/APIReference/API_PutRecords.html"
 	aws_kinesis_streams_service_limits:                       "\(aws_docs)/streams/latest/dev/service-sizes-and-limits.html"
 	aws_kinesis_split_shards:                                 "\(aws_docs)/streams/latest/dev/kinesis-using-sdk-java-resharding-split.html"
+	aws_opensearch_serverless:                                "\(aws_docs)/opensearch-service/latest/developerguide/serverless.html"
 	aws_regions:                                              "\(aws_docs)/AmazonRDS/latest/UserGuide/Concepts.RegionsAndAvailabilityZones.html"
 	aws_s3:                                                   "https://aws.amazon.com/s3/"
 	aws_s3_acl:                                               "\(aws_docs)/AmazonS3/latest/dev/acl-overview.html"
@@ -76,13 +83,20 @@ urls: {
 	aws_s3_sse:                                               "\(aws_docs)/AmazonS3/latest/dev/UsingServerSideEncryption.html"
 	aws_s3_storage_classes:                                   "https://aws.amazon.com/s3/storage-classes/"
 	aws_s3_tags:                                              "\(aws_docs)/AmazonS3/latest/user-guide/add-object-tags.html"
+	aws_security_lake:                                        "https://aws.amazon.com/security-lake/"
+	aws_security_lake_custom_sources:                         "\(aws_docs)/security-lake/latest/userguide/custom-sources.html"
 	aws_sqs:                                                  "https://aws.amazon.com/sqs/"
 	aws_sqs_api:                                              "\(aws_docs)/AWSSimpleQueueService/latest/APIReference/Welcome.html"
 	aws_sqs_create:                                           "\(aws_docs)/AWSSimpleQueueService/latest/SQSDeveloperGuide/sqs-configure-create-queue.html"
 	aws_sqs_message_deduplication_id:                         "\(aws_docs)/AWSSimpleQueueService/latest/SQSDeveloperGuide/using-messagededuplicationid-property.html"
 	aws_vpc_flow_logs:                                        "\(aws_docs)/vpc/latest/userguide/flow-logs.html"
+	azure_active_directory:                                   "https://docs.microsoft.com/en-us/azure/active-directory/"
 	azure_blob:                                               "https://azure.microsoft.com/en-us/services/storage/blobs/"
 	azure_blob_endpoints:                                     "https://docs.microsoft.com/en-us/rest/api/storageservices/blob-service-rest-api"
+	azure_data_explorer:                                      "https://azure.microsoft.com/en-us/services/data-explorer/"
+	azure_data_explorer_ingestion:                            "https://docs.microsoft.com/en-us/azure/data-explorer/ingest-data-overview"
+	azure_data_explorer_mappings:                             "https://docs.microsoft.com/en-us/azure/data-explorer/kusto/management/mappings"
+	azure_managed_identities:                                 "https://docs.microsoft.com/en-us/azure/active-directory/managed-identities-azure-resources/overview"
 	azure_monitor:                                            "https://azure.microsoft.com/en-us/services/monitor/"
 	azure_monitor_logs_endpoints:                             "https://docs.microsoft.com/en-us/rest/api/monitor/"
 	base64:                                                   "\(wikipedia)/wiki/Base64"
@@ -94,6 +108,9 @@ urls: {
 	bind_dnstap:                                              "https://kb.isc.org/docs/aa-01342"
 	b_tree_map:                                               "https://doc.rust-lang.org/std/collections/struct.BTreeMap.html"
 	cargo_audit:                                              "\(github)/RustSec/cargo-audit"
+	cassandra:                                                "https://cassandra.apache.org/"
+	cassandra_batch:                                          "https://cassandra.apache.org/doc/latest/cassandra/cql/dml.html#batch_statement"
+	cassandra_consistency:                                    "https://cassandra.apache.org/doc/latest/cassandra/architecture/dynamo.html#tunable-consistency"
 	centos:                                                   "https://www.centos.org/"
 	chrono_time_formats:                                      "https://docs.rs/chrono/latest/chrono/format/strftime/index.html#specifiers"
 	cgroups_limit_resources:                                  "https://the.binbashtheory.com/control-resources-cgroups/"
@@ -103,6 +120,8 @@ urls: {
 	cloudsmith_apt:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-deb"
 	cloudsmith_yum:                                           "https://cloudsmith.io/~timber/repos/vector/setup/#formats-rpm"
 	console:                                                  "\(wikipedia)/wiki/System_console"
+	confluent_schema_registry:                                "https://docs.confluent.io/platform/current/schema-registry/index.html"
+	confluent_schema_registry_wire_format:                    "https://docs.confluent.io/platform/current/schema-registry/fundamentals/serdes-develop/index.html#wire-format"
 	conventional_commits:                                     "https://www.conventionalcommits.org"
 	contributing:                                             "\(vector_repo)/blob/master/CONTRIBUTING.md#setup"
 	crc:                                                      "\(wikipedia)/wiki/Cyclic_redundancy_check"
@@ -121,11 +140,15 @@ urls: {
 	datadog_metrics:                                          "\(datadog_docs)/metrics/"
 	datadog_events:                                           "\(datadog_docs)/events/"
 	datadog_metrics_endpoints:                                "\(datadog_docs)/api/v1/metrics/"
+	datadog_traces:                                           "\(datadog_docs)/tracing/"
+	datadog_trace_metrics:                                    "\(datadog_docs)/tracing/guide/metrics_namespace/"
 	datadog_search_syntax:                                    "\(datadog_docs)/logs/explorer/search_syntax/"
 	date:                                                     "https://man7.org/linux/man-pages/man1/date.1.html"
 	debian:                                                   "https://www.debian.org/"
 	debian_system_groups:                                     "https://wiki.debian.org/SystemGroups"
 	default_configuration:                                    "\(vector_repo)/blob/master/config/vector.toml"
+	delta_lake:                                               "https://delta.io"
+	delta_lake_protocol:                                      "https://github.com/delta-io/delta/blob/master/PROTOCOL.md"
 	dnstap:                                                   "http://dnstap.info/"
 	docker:                                                   "https://www.docker.com/"
 	docker_alpine:                                            "\(docker_hub)/_/alpine"
@@ -152,6 +175,8 @@ urls: {
 	docker_setup:                                             "\(docker_docs)/get-docker/"
 	dockerfile:                                               "\(vector_repo)/blob/master/Dockerfile"
 	dogstatsd:                                                "\(datadog_docs)/developers/dogstatsd/?tab=hostagent"
+	doris:                                                    "https://doris.apache.org"
+	doris_stream_load:                                        "https://doris.apache.org/docs/data-operate/import/import-way/stream-load-manual"
 	dot_format:                                               "https://graphviz.org/doc/info/lang.html"
 	dpkg:                                                     "https://wiki.debian.org/dpkg"
 	dry_code:                                                 "\(wikipedia)/wiki/Don%27t_repeat_yourself"
@@ -162,7 +187,9 @@ urls: {
 	elasticsearch_data_streams:                               "https://www.elastic.co/guide/en/elasticsearch/reference/current/data-streams.html"
 	elasticsearch_id_field:                                   "https://www.elastic.co/guide/en/elasticsearch/reference/current/mapping-id-field.html"
 	elasticsearch_id_performance:                             "https://www.elastic.co/guide/en/elasticsearch/reference/master/tune-for-indexing-speed.html#_use_auto_generated_ids"
+	elasticsearch_ilm:                                        "https://www.elastic.co/guide/en/elasticsearch/reference/current/index-lifecycle-management.html"
 	elasticsearch_ignore_malformed:                           "https://www.elastic.co/guide/en/elasticsearch/reference/current/ignore-malformed.html"
+	elasticsearch_routing_field:                              "https://www.elastic.co/guide/en/elasticsearch/reference/current/mapping-routing-field.html"
 	encoding_charset_labels:                                  "https://encoding.spec.whatwg.org/#concept-encoding-get"
 	encoding_standard:                                        "https://encoding.spec.whatwg.org/"
 	endler_dev:                                               "https://endler.dev/"
@@ -186,6 +213,9 @@ urls: {
 	gcp_authentication_api_key:                               "\(gcp)/docs/authentication/api-keys"
 	gcp_authentication_server_to_server:                      "\(gcp)/docs/authentication/production"
 	gcp_authentication_service_account:                       "\(gcp)/docs/authentication/production#obtaining_and_providing_service_account_credentials_manually"
+	gcp_bigquery:                                             "\(gcp)/bigquery"
+	gcp_bigquery_storage_write_api:                           "\(gcp)/bigquery/docs/write-api"
+	gcp_bigquery_write_api_exactly_once:                      "\(gcp)/bigquery/docs/write-api#exactly-once_semantics"
 	gcp_cloud_storage:                                        "\(gcp)/storage"
 	gcp_folders:                                              "\(gcp)/resource-manager/docs/creating-managing-folders"
 	gcp_pubsub:                                               "\(gcp)/pubsub/"
@@ -214,12 +244,16 @@ urls: {
 	grok:                                                     "https://grokdebug.herokuapp.com/"
 	grok_debugger:                                            "https://grokdebug.herokuapp.com/"
 	grok_patterns:                                            "\(github)/daschl/grok/tree/master/patterns"
+	grpc:                                                     "https://grpc.io/"
+	grpc_health_checking:                                     "\(github)/grpc/grpc/blob/master/doc/health-checking.md"
+	grpc_metadata:                                            "https://grpc.io/docs/what-is-grpc/core-concepts/#metadata"
 	gzip:                                                     "https://www.gzip.org/"
 	haproxy:                                                  "https://www.haproxy.org/"
 	helm:                                                     "https://helm.sh/"
 	heroku:                                                   "https://www.heroku.com"
 	heroku_http_log_drain:                                    "https://devcenter.heroku.com/articles/log-drains#https-drains"
 	heroku_start:                                             "https://devcenter.heroku.com/start"
+	hmac:                                                     "\(wikipedia)/wiki/HMAC"
 	homebrew:                                                 "https://brew.sh/"
 	homebrew_services:                                        "\(github)/Homebrew/homebrew-services"
 	honeycomb:                                                "https://honeycomb.io"
@@ -234,6 +268,7 @@ urls: {
 	iam_instance_profile:                                     "\(aws_docs)/IAM/latest/UserGuide/id_roles_use_switch-role-ec2_instance-profiles.html"
 	iana_time_zone_format:                                    "\(wikipedia)/wiki/Tz_database#Names_of_time_zones"
 	iana_time_zones:                                          "\(wikipedia)/wiki/List_of_tz_database_time_zones"
+	iceberg_rest_catalog:                                     "https://github.com/apache/iceberg/blob/main/open-api/rest-catalog-open-api.yaml"
 	ieee_754:                                                 "\(wikipedia)/wiki/IEEE_754"
 	ietf_rfc_6750:                                            "https://tools.ietf.org/html/rfc6750"
 	initd:                                                    "https://bash.cyberciti.biz/guide//etc/init.d"
@@ -248,6 +283,7 @@ urls: {
 	iso_8601:                                                 "\(wikipedia)/wiki/ISO_8601"
 	iso3166_2:                                                "\(wikipedia)/wiki/ISO_3166-2"
 	issue_1694:                                               "\(vector_repo)/issues/1694"
+	jq:                                                       "https://stedolan.github.io/jq/manual/"
 	journalctl:                                               "https://www.freedesktop.org/software/systemd/man/journalctl.html"
 	journald:                                                 "https://www.freedesktop.org/software/systemd/man/systemd-journald.service.html"
 	json:                                                     "\(wikipedia)/wiki/JSON"
@@ -284,6 +320,7 @@ urls: {
 	logstash_protocol:                                        "https://github.com/elastic/logstash-forwarder/blob/master/PROTOCOL.md"
 	loki:                                                     "https://grafana.com/oss/loki/"
 	loki_multi_tenancy:                                       "\(github)/grafana/loki/blob/master/docs/operations/multi-tenancy.md"
+	loki_structured_metadata:                                 "https://grafana.com/docs/loki/latest/get-started/labels/structured-metadata/"
 	log_event_source:                                         "\(vector_repo)/blob/master/src/event/"
 	logplex:                                                  "https://devcenter.heroku.com/articles/logplex"
 	logplex_protocol:                                         "\(github)/heroku/logplex/blob/master/doc/README.http_drains.md"
@@ -306,6 +343,7 @@ urls: {
 	lua_sequence:                                             "\(lua)/pil/11.1.html"
 	lua_string:                                               "\(lua)/pil/2.4.html"
 	lua_tonumber:                                             "\(lua)/manual/5.4/manual.html#pdf-tonumber"
+	luhn_algorithm:                                           "\(wikipedia)/wiki/Luhn_algorithm"
 	lz4:                                                      "https://lz4.github.io/lz4/"
 	macos:                                                    "\(wikipedia)/wiki/MacOS"
 	magic_bytes:                                              "\(wikipedia)/wiki/List_of_file_signatures"
@@ -314,16 +352,21 @@ urls: {
 	maxmind_db_file_format:                                   "https://maxmind.github.io/MaxMind-DB/"
 	maxmind_geoip2:                                           "https://dev.maxmind.com/geoip/geoip2/downloadable"
 	maxmind_geoip2_city:                                      "https://www.maxmind.com/en/geoip2-city"
+	maxmind_geoip2_connection_type:                           "https://www.maxmind.com/en/geoip2-connection-type-database"
 	maxmind_geoip2_isp:                                       "https://www.maxmind.com/en/geoip2-isp-database"
 	maxmind_geolite2_asn:                                     "https://dev.maxmind.com/geoip/geoip2/geolite2/#Download_Access"
 	maxmind_geolite2_city:                                    "https://dev.maxmind.com/geoip/geoip2/geolite2/#Download_Access"
 	memory_safety:                                            "\(wikipedia)/wiki/Memory_safety"
 	memory_safety_bugs:                                       "https://thenewstack.io/microsoft-rust-is-the-industrys-best-chance-at-safe-systems-programming/"
 	metric_event_source:                                      "\(vector_repo)/blob/master/src/event/metric.rs"
+	microsoft_teams:                                          "https://www.microsoft.com/microsoft-teams"
+	microsoft_teams_incoming_webhooks:                        "https://docs.microsoft.com/en-us/microsoftteams/platform/webhooks-and-connectors/how-to/add-incoming-webhook"
 	mlua:                                                     "\(github)/khvzak/mlua"
 	mongodb:                                                  "https://www.mongodb.com"
 	mongodb_command_server_status:                            "https://docs.mongodb.com/manual/reference/command/serverStatus/"
 	mongodb_connection_string_uri_format:                     "https://docs.mongodb.com/manual/reference/connection-string/"
+	mqtt:                                                     "https://mqtt.org/"
+	mqtt_v311:                                                "https://docs.oasis-open.org/mqtt/mqtt/v3.1.1/mqtt-v3.1.1.html"
 	musl_builder_docker_image:                                "\(vector_repo)/blob/master/scripts/ci-docker-images/builder-x86_64-unknown-linux-musl/Dockerfile"
 	nats:                                                     "https://nats.io/"
 	nats_rs:                                                  "\(github)/nats-io/nats.rs"
@@ -339,12 +382,19 @@ urls: {
 	nginx:                                                    "https://www.nginx.com/"
 	nginx_combined:                                           "https://nginx.org/en/docs/http/ngx_http_log_module.html"
 	nginx_error:                                              "https://github.com/nginx/nginx/blob/branches/stable-1.18/src/core/ngx_log.c#L102"
+	nginx_log_format:                                         "https://nginx.org/en/docs/http/ngx_http_log_module.html#log_format"
 	nginx_stub_status_module:                                 "http://nginx.org/en/docs/http/ngx_http_stub_status_module.html"
 	nix:                                                      "https://nixos.org/nix/"
 	nixos:                                                    "https://nixos.org/"
 	nixpkgs_9682:                                             "\(github)/NixOS/nixpkgs/issues/9682"
+	ocsf:                                                     "https://ocsf.io"
+	ocsf_schema:                                              "https://schema.ocsf.io"
 	openssl:                                                  "https://www.openssl.org/"
+	opsgenie:                                                 "https://www.atlassian.com/software/opsgenie"
+	opsgenie_alert_api:                                       "https://docs.opsgenie.com/docs/alert-api"
 	order_of_ops:                                             "\(wikipedia)/wiki/Order_of_operations"
+	pagerduty:                                                "https://www.pagerduty.com/"
+	pagerduty_events_api:                                     "https://developer.pagerduty.com/docs/events-api-v2/overview/"
 	papertrail:                                               "https://www.papertrail.com/"
 	papertrail_syslog:                                        "https://help.papertrailapp.com/kb/how-it-works/http-api/#submitting-log-messages"
 	perl_windows:                                             "https://www.perl.org/get.html#win32"
@@ -359,25 +409,33 @@ urls: {
 	percent_encoding_www_form_urlencoded:                     "https://url.spec.whatwg.org/#application-x-www-form-urlencoded-percent-encode-set"
 	posix_acls:                                               "https://www.usenix.org/legacy/publications/library/proceedings/usenix03/tech/freenix03/full_papers/gruenbacher/gruenbacher_html/main.html"
 	postgresql:                                               "https://www.postgresql.org/"
+	postgresql_copy:                                          "https://www.postgresql.org/docs/current/sql-copy.html"
 	postgresql_csvlog:                                        "https://www.postgresql.org/docs/current/runtime-config-logging.html#RUNTIME-CONFIG-LOGGING-CSVLOG"
 	postgresql_matching:                                      "https://www.postgresql.org/docs/current/functions-matching.html#FUNCTIONS-POSIX-REGEXP"
+	postgresql_on_conflict:                                   "https://www.postgresql.org/docs/current/sql-insert.html#SQL-ON-CONFLICT"
 	prometheus:                                               "https://prometheus.io/"
 	prometheus_client:                                        "https://prometheus.io/docs/instrumenting/clientlibs/"
 	prometheus_remote_integrations:                           "https://prometheus.io/docs/operating/integrations/#remote-endpoints-and-storage"
 	prometheus_counter:                                       "https://prometheus.io/docs/concepts/metric_types/#counter"
+	prometheus_exemplars:                                     "https://prometheus.io/docs/prometheus/latest/feature_flags/#exemplars-storage"
 	prometheus_gauge:                                         "https://prometheus.io/docs/concepts/metric_types/#gauge"
 	prometheus_high_cardinality:                              "https://prometheus.io/docs/practices/naming/#labels"
 	prometheus_histogram:                                     "https://prometheus.io/docs/concepts/metric_types/#histogram"
 	prometheus_histograms_guide:                              "https://prometheus.io/docs/practices/histograms/"
+	prometheus_http_sd:                                       "https://prometheus.io/docs/prometheus/latest/http_sd/"
 	prometheus_summary:                                       "https://prometheus.io/docs/concepts/metric_types/#summary"
 	prometheus_text_based_exposition_format:                  "\(github)/prometheus/docs/blob/master/content/docs/instrumenting/exposition_formats.md#text-based-format"
 	prometheus_metric_naming:                                 "https://prometheus.io/docs/practices/naming/#metric-names"
+	prometheus_native_histograms:                             "https://prometheus.io/docs/specs/native_histograms/"
 	prometheus_remote_integrations:                           "https://prometheus.io/docs/operating/integrations/#remote-endpoints-and-storage"
 	prometheus_remote_write:                                  "https://prometheus.io/docs/prometheus/latest/configuration/configuration/#remote_write"
 	prometheus_remote_write_protocol:                         "https://docs.google.com/document/d/1LPhVRSFkGNSuU1fBd81ulhsCPR4hkSZyyBj1SZ8fWOM/edit#heading=h.n0d0vphea3fe"
 	protobuf:                                                 "https://developers.google.com/protocol-buffers"
+	protobuf_descriptor_set:                                  "https://developers.google.com/protocol-buffers/docs/techniques#self-description"
 	pulsar:                                                   "https://pulsar.apache.org/"
 	pulsar_protocol:                                          "https://pulsar.apache.org/docs/en/develop-binary-protocol/"
+	questdb:                                                  "https://questdb.io/"
+	questdb_ilp:                                              "https://questdb.io/docs/reference/api/ilp/overview/"
 	raspbian:                                                 "https://www.raspbian.org/"
 	rdkafka:                                                  "\(github)/edenhill/librdkafka"
 	regex:                                                    "\(wikipedia)/wiki/Regular_expression"
@@ -411,6 +469,7 @@ urls: {
 	rustup:                                                   "https://rustup.rs"
 	redis:                                                    "https://redis.io"
 	redis_rs:                                                 "https://github.com/mitsuhiko/redis-rs"
+	scylladb:                                                 "https://www.scylladb.com/"
 	sematext:                                                 "https://sematext.com"
 	sematext_create_logs_app:                                 "https://apps.sematext.com/ui/integrations"
 	sematext_es:                                              "https://sematext.com/docs/logs/index-events-via-elasticsearch-api/"
@@ -422,17 +481,26 @@ urls: {
 	sha2:                                                     "\(wikipedia)/wiki/SHA-2"
 	sha3:                                                     "\(wikipedia)/wiki/SHA-3"
 	signal:                                                   "\(wikipedia)/wiki/Signal_(IPC)"
+	slack:                                                    "https://slack.com/"
+	slack_incoming_webhooks:                                  "https://api.slack.com/messaging/webhooks"
+	slack_post_message:                                       "https://api.slack.com/methods/chat.postMessage"
+	smtp:                                                     "\(wikipedia)/wiki/Simple_Mail_Transfer_Protocol"
+	smtp_starttls:                                            "https://datatracker.ietf.org/doc/html/rfc3207"
 	snake_case:                                               "\(wikipedia)/wiki/Snake_case"
 	snappy:                                                   "https://google.github.io/snappy/"
+	snowflake_id:                                             "\(wikipedia)/wiki/Snowflake_ID"
 	socket:                                                   "\(wikipedia)/wiki/Network_socket"
 	splunk:                                                   "https://www.splunk.com"
 	splunk_hec:                                               "https://dev.splunk.com/enterprise/docs/dataapps/httpeventcollector/"
 	splunk_hec_event_endpoint:                                "https://docs.splunk.com/Documentation/Splunk/8.0.0/RESTREF/RESTinput#services.2Fcollector.2Fevent"
 	splunk_hec_indexed_fields:                                "https://docs.splunk.com/Documentation/Splunk/8.0.0/Data/IFXandHEC"
+	splunk_hec_indexer_acknowledgements:                      "https://docs.splunk.com/Documentation/Splunk/latest/Data/AboutHECIDXAck"
 	splunk_hec_protocol:                                      "https://docs.splunk.com/Documentation/Splunk/8.0.0/Data/HECRESTendpoints"
 	splunk_hec_raw_endpoint:                                  "https://docs.splunk.com/Documentation/Splunk/8.0.0/RESTREF/RESTinput#services.2Fcollector.2Fraw"
 	splunk_hec_setup:                                         "https://docs.splunk.com/Documentation/Splunk/latest/Data/UsetheHTTPEventCollector"
 	standard_streams:                                         "\(wikipedia)/wiki/Standard_streams"
+	starrocks:                                                "https://www.starrocks.io"
+	starrocks_stream_load:                                    "https://docs.starrocks.io/docs/loading/StreamLoad/"
 	statsd:                                                   "\(github)/statsd/statsd"
 	statsd_multi:                                             "\(github)/statsd/statsd/blob/master/docs/metric_types.md#multi-metric-packets"
 	statsd_set:                                               "\(github)/statsd/statsd/blob/master/docs/metric_types.md#sets"
@@ -466,11 +534,13 @@ urls: {
 	ubuntu:                                                   "https://ubuntu.com/"
 	udp:                                                      "\(wikipedia)/wiki/User_Datagram_Protocol"
 	uds:                                                      "\(wikipedia)/wiki/Unix_domain_socket"
+	ulid:                                                     "https://github.com/ulid/spec"
 	unicode_replacement_character:                            "\(wikipedia)/wiki/Specials_(Unicode_block)#Replacement_character"
 	unicode_whitespace:                                       "\(wikipedia)/wiki/Unicode_character_property#Whitespace"
 	unix_timestamp:                                           "\(wikipedia)/wiki/Unix_time"
 	utf8:                                                     "\(wikipedia)/wiki/UTF-8"
 	uuidv4:                                                   "\(wikipedia)/wiki/Universally_unique_identifier#Version_4_(random)"
+	uuidv7:                                                   "https://datatracker.ietf.org/doc/html/draft-peabody-dispatch-new-uuid-format#section-5.2"
 	url:                                                      "\(wikipedia)/wiki/URL"
 	us_social_security_number:                                "https://www.ssa.gov/history/ssn/geocard.html"
 	user_agent:                                               "https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/User-Agent"
@@ -588,6 +658,7 @@ urls: {
 	vote_feature:                                             "\(vector_repo)/issues?q=is%3Aissue+is%3Aopen+sort%3Areactions-%2B1-desc+label%3A%22Type%3A+New+Feature%22"
 	wasm:                                                     "https://webassembly.org/"
 	wasm_languages:                                           "\(github)/appcypher/awesome-wasm-langs"
+	websocket:                                                "https://datatracker.ietf.org/doc/html/rfc6455"
 	wikipedia:                                                "https://en.wikipedia.org"
 	windows:                                                  "https://www.microsoft.com/en-us/windows"
 	windows_installer:                                        "\(wikipedia)/wiki/Windows_Installer"